@@ -0,0 +1,48 @@
+//! Compare a bench report against a baseline
+//!
+//! Prints every compared metric and exits with status 1 when a metric
+//! regressed past its threshold (or a baseline benchmark went missing).
+use bench_utils::{BenchReport, Thresholds, compare};
+use clap::Parser;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Compare a bench report against a baseline
+#[derive(Parser)]
+#[command(name = "bench-compare")]
+struct Args {
+    /// Baseline report (JSON)
+    baseline: PathBuf,
+    /// Current report (JSON)
+    current: PathBuf,
+    /// Regression thresholds (TOML); without it metrics are only reported
+    #[arg(short, long)]
+    thresholds: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let result = (|| {
+        let baseline = BenchReport::load(&args.baseline)?;
+        let current = BenchReport::load(&args.current)?;
+        let thresholds = match &args.thresholds {
+            Some(path) => Thresholds::load(path)?,
+            None => Thresholds::default(),
+        };
+        Ok::<_, bench_utils::BenchError>(compare(&baseline, &current, &thresholds))
+    })();
+    match result {
+        Ok(comparison) => {
+            println!("{}", comparison);
+            if comparison.passed() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(e) => {
+            eprintln!("bench-compare: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
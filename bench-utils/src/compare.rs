@@ -0,0 +1,354 @@
+//! Bench comparison module
+//!
+//! Compares a current report against a baseline. Each metric may regress by
+//! at most its threshold, a percentage read from a TOML file:
+//!
+//! ```toml
+//! # Fail when a baseline benchmark is missing from the current report
+//! fail_on_missing = true
+//!
+//! # Applies to every benchmark
+//! [default]
+//! throughput = 10.0 # fail if throughput drops by more than 10%
+//! p99 = 10.0        # fail if p99 latency grows by more than 10%
+//! errors = 0.0      # fail on any new error
+//!
+//! # Overrides for one benchmark
+//! [benchmarks."accept_path/check_generation"]
+//! p99 = 25.0
+//! ```
+//!
+//! Metrics without a threshold are reported but never fail.
+use crate::error::BenchError;
+use crate::report::{BenchMetrics, BenchReport};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Slack, in percentage points, so a regression of exactly the threshold is
+/// not failed by floating point rounding
+const THRESHOLD_EPSILON_PCT: f64 = 1e-9;
+
+/// A compared metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Operations per second (higher is better)
+    Throughput,
+    /// Median latency (lower is better)
+    P50,
+    /// 95th percentile latency (lower is better)
+    P95,
+    /// 99th percentile latency (lower is better)
+    P99,
+    /// Failed operations (lower is better)
+    Errors,
+}
+
+impl Metric {
+    /// Every metric, in report order
+    pub const ALL: [Metric; 5] = [
+        Metric::Throughput,
+        Metric::P50,
+        Metric::P95,
+        Metric::P99,
+        Metric::Errors,
+    ];
+
+    /// Metric name, as used in threshold files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Throughput => "throughput",
+            Self::P50 => "p50",
+            Self::P95 => "p95",
+            Self::P99 => "p99",
+            Self::Errors => "errors",
+        }
+    }
+
+    /// Value of the metric in `metrics`, if recorded
+    fn value(&self, metrics: &BenchMetrics) -> Option<f64> {
+        match self {
+            Self::Throughput => metrics.throughput_per_sec,
+            Self::P50 => metrics.p50_ms,
+            Self::P95 => metrics.p95_ms,
+            Self::P99 => metrics.p99_ms,
+            Self::Errors => Some(metrics.errors as f64),
+        }
+    }
+
+    /// Whether a higher value is an improvement
+    fn higher_is_better(&self) -> bool {
+        matches!(self, Self::Throughput)
+    }
+}
+
+impl std::fmt::Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Allowed regression of each metric, in percent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricThresholds {
+    /// Allowed throughput drop
+    pub throughput: Option<f64>,
+    /// Allowed median latency growth
+    pub p50: Option<f64>,
+    /// Allowed 95th percentile latency growth
+    pub p95: Option<f64>,
+    /// Allowed 99th percentile latency growth
+    pub p99: Option<f64>,
+    /// Allowed error count growth
+    pub errors: Option<f64>,
+}
+
+impl MetricThresholds {
+    /// Threshold of `metric`, if set
+    fn get(&self, metric: Metric) -> Option<f64> {
+        match metric {
+            Metric::Throughput => self.throughput,
+            Metric::P50 => self.p50,
+            Metric::P95 => self.p95,
+            Metric::P99 => self.p99,
+            Metric::Errors => self.errors,
+        }
+    }
+}
+
+/// Regression thresholds for a comparison
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    /// Fail when a baseline benchmark is missing from the current report
+    pub fail_on_missing: bool,
+    /// Thresholds of every benchmark
+    pub default: MetricThresholds,
+    /// Per-benchmark overrides, by benchmark id
+    pub benchmarks: BTreeMap<String, MetricThresholds>,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            fail_on_missing: true,
+            default: MetricThresholds::default(),
+            benchmarks: BTreeMap::new(),
+        }
+    }
+}
+
+impl Thresholds {
+    /// Parse thresholds from TOML
+    pub fn parse(content: &str) -> Result<Self, BenchError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Read thresholds from a TOML file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BenchError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|source| BenchError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&content)
+    }
+
+    /// Threshold of `metric` for `benchmark`: its override, else the default
+    pub fn threshold(&self, benchmark: &str, metric: Metric) -> Option<f64> {
+        self.benchmarks
+            .get(benchmark)
+            .and_then(|overrides| overrides.get(metric))
+            .or_else(|| self.default.get(metric))
+    }
+}
+
+/// Outcome of one metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricStatus {
+    /// Within the threshold
+    Pass,
+    /// Regressed by more than the threshold
+    Fail,
+    /// No threshold set, so not checked
+    Unchecked,
+    /// Not recorded in one of the reports
+    Missing,
+}
+
+/// Comparison of one metric of one benchmark
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricComparison {
+    /// Benchmark id
+    pub benchmark: String,
+    /// Compared metric
+    pub metric: Metric,
+    /// Baseline value
+    pub baseline: Option<f64>,
+    /// Current value
+    pub current: Option<f64>,
+    /// Change from the baseline in percent (infinite when the baseline is 0
+    /// and the current value is not)
+    pub delta_pct: Option<f64>,
+    /// Allowed regression in percent
+    pub threshold_pct: Option<f64>,
+    /// Outcome
+    pub status: MetricStatus,
+}
+
+/// Result of comparing two reports
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// Every metric recorded for a benchmark in both reports
+    pub metrics: Vec<MetricComparison>,
+    /// Baseline benchmarks missing from the current report
+    pub missing: Vec<String>,
+    /// Current benchmarks without a baseline
+    pub added: Vec<String>,
+    /// Whether missing benchmarks fail the comparison
+    pub fail_on_missing: bool,
+}
+
+impl Comparison {
+    /// Whether no metric regressed past its threshold (and, with
+    /// `fail_on_missing`, no benchmark went missing)
+    pub fn passed(&self) -> bool {
+        (!self.fail_on_missing || self.missing.is_empty())
+            && self
+                .metrics
+                .iter()
+                .all(|metric| metric.status != MetricStatus::Fail)
+    }
+
+    /// Metrics that regressed past their threshold
+    pub fn failures(&self) -> impl Iterator<Item = &MetricComparison> {
+        self.metrics
+            .iter()
+            .filter(|metric| metric.status == MetricStatus::Fail)
+    }
+}
+
+impl std::fmt::Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.4}", v));
+        for m in &self.metrics {
+            let delta = m
+                .delta_pct
+                .map_or("-".to_string(), |d| format!("{:+.2}%", d));
+            let threshold = m
+                .threshold_pct
+                .map_or("-".to_string(), |t| format!("{:.2}%", t));
+            writeln!(
+                f,
+                "{:<9} {} {}: {} -> {} ({}, threshold {})",
+                format!("{:?}", m.status).to_uppercase(),
+                m.benchmark,
+                m.metric,
+                value(m.baseline),
+                value(m.current),
+                delta,
+                threshold
+            )?;
+        }
+        for id in &self.missing {
+            writeln!(f, "MISSING   {}", id)?;
+        }
+        for id in &self.added {
+            writeln!(f, "ADDED     {}", id)?;
+        }
+        write!(f, "{}", if self.passed() { "PASSED" } else { "FAILED" })
+    }
+}
+
+/// Change from `baseline` to `current` in percent
+///
+/// A zero baseline gives 0 when the current value is also 0 and an infinite
+/// change otherwise.
+pub fn delta_pct(baseline: f64, current: f64) -> f64 {
+    if baseline == 0.0 {
+        if current == 0.0 {
+            0.0
+        } else {
+            f64::INFINITY.copysign(current)
+        }
+    } else {
+        (current - baseline) / baseline.abs() * 100.0
+    }
+}
+
+/// Compare `current` against `baseline`
+///
+/// A metric fails when it moved in the worse direction by strictly more than
+/// its threshold; a regression of exactly the threshold passes.
+pub fn compare(
+    baseline: &BenchReport,
+    current: &BenchReport,
+    thresholds: &Thresholds,
+) -> Comparison {
+    let mut metrics = Vec::new();
+    let mut missing = Vec::new();
+    for (id, base) in &baseline.benchmarks {
+        let Some(cur) = current.benchmarks.get(id) else {
+            missing.push(id.clone());
+            continue;
+        };
+        for metric in Metric::ALL {
+            // Metrics neither report records are left out
+            if metric.value(base).is_some() || metric.value(cur).is_some() {
+                metrics.push(compare_metric(id, metric, base, cur, thresholds));
+            }
+        }
+    }
+    let added = current
+        .benchmarks
+        .keys()
+        .filter(|id| !baseline.benchmarks.contains_key(*id))
+        .cloned()
+        .collect();
+    Comparison {
+        metrics,
+        missing,
+        added,
+        fail_on_missing: thresholds.fail_on_missing,
+    }
+}
+
+/// Compare one metric of a benchmark present in both reports
+fn compare_metric(
+    benchmark: &str,
+    metric: Metric,
+    baseline: &BenchMetrics,
+    current: &BenchMetrics,
+    thresholds: &Thresholds,
+) -> MetricComparison {
+    let baseline = metric.value(baseline);
+    let current = metric.value(current);
+    let threshold_pct = thresholds.threshold(benchmark, metric);
+    let delta = baseline.zip(current).map(|(b, c)| delta_pct(b, c));
+    let status = match (delta, threshold_pct) {
+        (None, _) => MetricStatus::Missing,
+        (Some(_), None) => MetricStatus::Unchecked,
+        (Some(delta), Some(threshold)) => {
+            let regression = if metric.higher_is_better() {
+                -delta
+            } else {
+                delta
+            };
+            if regression > threshold + THRESHOLD_EPSILON_PCT {
+                MetricStatus::Fail
+            } else {
+                MetricStatus::Pass
+            }
+        }
+    };
+    MetricComparison {
+        benchmark: benchmark.to_string(),
+        metric,
+        baseline,
+        current,
+        delta_pct: delta,
+        threshold_pct,
+        status,
+    }
+}
@@ -0,0 +1,22 @@
+//! Bench utils error module
+//!
+use std::path::PathBuf;
+
+/// Bench utils error enum
+#[derive(Debug, thiserror::Error)]
+pub enum BenchError {
+    /// A report or threshold file could not be read or written
+    #[error("{path}: {source}")]
+    Io {
+        /// File being accessed
+        path: PathBuf,
+        /// Underlying error
+        source: std::io::Error,
+    },
+    /// A report is not valid JSON
+    #[error("invalid bench report: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A threshold file is not valid TOML
+    #[error("invalid thresholds: {0}")]
+    Toml(#[from] toml::de::Error),
+}
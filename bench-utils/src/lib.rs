@@ -0,0 +1,17 @@
+//! Bench Utils Library
+//!
+//! Structured benchmark results and regression checks: benches record
+//! their throughput, latency percentiles and error counts in a
+//! [`BenchReport`] written as one JSON file, and [`compare`] checks a
+//! current report against a baseline using per-metric thresholds.
+
+pub mod compare;
+pub mod error;
+pub mod report;
+
+pub use crate::compare::{
+    Comparison, Metric, MetricComparison, MetricStatus, MetricThresholds, Thresholds,
+    compare, delta_pct,
+};
+pub use crate::error::BenchError;
+pub use crate::report::{BenchMetrics, BenchReport, REPORT_PATH_ENV_KEY, ReportWriter};
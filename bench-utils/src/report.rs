@@ -0,0 +1,171 @@
+//! Bench report module
+//!
+//! Results are keyed by benchmark id (e.g. `accept_path/check_generation`)
+//! and written as a single JSON file:
+//!
+//! ```json
+//! {
+//!   "benchmarks": {
+//!     "accept_path/check_generation": {
+//!       "throughput_per_sec": 1250000.0,
+//!       "p50_ms": 0.0008,
+//!       "p95_ms": 0.0011,
+//!       "p99_ms": 0.0014,
+//!       "errors": 0
+//!     }
+//!   }
+//! }
+//! ```
+use crate::error::BenchError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Environment variable naming the file a [`ReportWriter`] writes to
+pub const REPORT_PATH_ENV_KEY: &str = "LEMONADE_BENCH_REPORT";
+
+/// Results of one benchmark
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchMetrics {
+    /// Operations per second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throughput_per_sec: Option<f64>,
+    /// Median latency in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p50_ms: Option<f64>,
+    /// 95th percentile latency in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p95_ms: Option<f64>,
+    /// 99th percentile latency in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p99_ms: Option<f64>,
+    /// Failed operations
+    #[serde(default)]
+    pub errors: u64,
+}
+
+impl BenchMetrics {
+    /// Metrics of `latencies` measured over `elapsed`, with `errors` failures
+    ///
+    /// Throughput counts every latency sample; percentiles are left unset
+    /// when there are none.
+    pub fn from_latencies(
+        latencies: &[Duration],
+        errors: u64,
+        elapsed: Duration,
+    ) -> Self {
+        let mut sorted: Vec<f64> = latencies
+            .iter()
+            .map(|latency| latency.as_secs_f64() * 1000.0)
+            .collect();
+        sorted.sort_by(f64::total_cmp);
+        let throughput_per_sec =
+            (!elapsed.is_zero()).then(|| latencies.len() as f64 / elapsed.as_secs_f64());
+        Self {
+            throughput_per_sec,
+            p50_ms: percentile(&sorted, 0.50),
+            p95_ms: percentile(&sorted, 0.95),
+            p99_ms: percentile(&sorted, 0.99),
+            errors,
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[f64], quantile: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((sorted.len() as f64) * quantile).ceil() as usize;
+    Some(sorted[rank.saturating_sub(1).min(sorted.len() - 1)])
+}
+
+/// Benchmark results, by benchmark id
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// Results of each benchmark
+    pub benchmarks: BTreeMap<String, BenchMetrics>,
+}
+
+impl BenchReport {
+    /// Record the results of `id`, replacing earlier ones
+    pub fn record(&mut self, id: impl Into<String>, metrics: BenchMetrics) {
+        self.benchmarks.insert(id.into(), metrics);
+    }
+
+    /// Read a report from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BenchError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|source| BenchError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Write the report as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BenchError> {
+        let path = path.as_ref();
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content).map_err(|source| BenchError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Collects results from a bench process and writes them once, when dropped
+///
+/// Create it at the top of the bench and keep it alive until the process
+/// exits; benches feed it through a shared reference.
+#[derive(Debug)]
+pub struct ReportWriter {
+    /// File the report is written to (`None` collects without writing)
+    path: Option<PathBuf>,
+    /// Results collected so far
+    report: Mutex<BenchReport>,
+}
+
+impl ReportWriter {
+    /// Writer for `path`
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            report: Mutex::new(BenchReport::default()),
+        }
+    }
+
+    /// Writer for the file named by [`REPORT_PATH_ENV_KEY`], if set
+    pub fn from_env() -> Self {
+        Self::new(std::env::var_os(REPORT_PATH_ENV_KEY).map(PathBuf::from))
+    }
+
+    /// Record the results of `id`
+    pub fn record(&self, id: impl Into<String>, metrics: BenchMetrics) {
+        self.report
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .record(id, metrics);
+    }
+
+    /// Results collected so far
+    pub fn report(&self) -> BenchReport {
+        self.report
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl Drop for ReportWriter {
+    fn drop(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(e) = self.report().save(path) {
+            eprintln!("Failed to write bench report: {}", e);
+        }
+    }
+}
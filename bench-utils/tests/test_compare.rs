@@ -0,0 +1,253 @@
+//! Tests for the bench comparison
+//!
+use bench_utils::{
+    BenchMetrics, BenchReport, Metric, MetricStatus, Thresholds, compare, delta_pct,
+};
+use rstest::rstest;
+
+fn metrics(throughput: f64, p99_ms: f64, errors: u64) -> BenchMetrics {
+    BenchMetrics {
+        throughput_per_sec: Some(throughput),
+        p50_ms: None,
+        p95_ms: None,
+        p99_ms: Some(p99_ms),
+        errors,
+    }
+}
+
+fn report(benchmarks: &[(&str, BenchMetrics)]) -> BenchReport {
+    let mut report = BenchReport::default();
+    for (id, metrics) in benchmarks {
+        report.record(*id, metrics.clone());
+    }
+    report
+}
+
+fn thresholds() -> Thresholds {
+    Thresholds::parse(
+        r#"
+[default]
+throughput = 10.0
+p99 = 10.0
+errors = 0.0
+"#,
+    )
+    .expect("Thresholds should parse")
+}
+
+fn status(report: &bench_utils::Comparison, id: &str, metric: Metric) -> MetricStatus {
+    report
+        .metrics
+        .iter()
+        .find(|m| m.benchmark == id && m.metric == metric)
+        .map(|m| m.status)
+        .expect("Metric should be compared")
+}
+
+#[rstest]
+#[case(100.0, 110.0, 10.0)]
+#[case(100.0, 90.0, -10.0)]
+#[case(200.0, 200.0, 0.0)]
+#[case(0.0, 0.0, 0.0)]
+fn delta_pct_is_relative_to_baseline(
+    #[case] baseline: f64,
+    #[case] current: f64,
+    #[case] expected: f64,
+) {
+    assert!((delta_pct(baseline, current) - expected).abs() < 1e-9);
+}
+
+#[test]
+fn delta_pct_from_zero_baseline_is_infinite() {
+    assert_eq!(delta_pct(0.0, 3.0), f64::INFINITY);
+    assert_eq!(delta_pct(0.0, -3.0), f64::NEG_INFINITY);
+}
+
+#[test]
+fn compare_within_thresholds_passes() {
+    let baseline = report(&[("bench", metrics(1000.0, 2.0, 0))]);
+    let current = report(&[("bench", metrics(950.0, 2.1, 0))]);
+
+    let comparison = compare(&baseline, &current, &thresholds());
+
+    assert!(comparison.passed());
+    assert_eq!(
+        status(&comparison, "bench", Metric::Throughput),
+        MetricStatus::Pass
+    );
+    assert_eq!(
+        status(&comparison, "bench", Metric::P99),
+        MetricStatus::Pass
+    );
+    let p99 = comparison
+        .metrics
+        .iter()
+        .find(|m| m.metric == Metric::P99)
+        .unwrap();
+    assert!((p99.delta_pct.unwrap() - 5.0).abs() < 1e-9);
+}
+
+#[test]
+fn compare_latency_regression_past_threshold_fails() {
+    let baseline = report(&[("bench", metrics(1000.0, 2.0, 0))]);
+    let current = report(&[("bench", metrics(1000.0, 2.3, 0))]);
+
+    let comparison = compare(&baseline, &current, &thresholds());
+
+    assert!(!comparison.passed());
+    assert_eq!(
+        status(&comparison, "bench", Metric::P99),
+        MetricStatus::Fail
+    );
+    assert_eq!(comparison.failures().count(), 1);
+}
+
+#[test]
+fn compare_throughput_drop_past_threshold_fails_but_gain_passes() {
+    let baseline = report(&[
+        ("a", metrics(1000.0, 2.0, 0)),
+        ("b", metrics(1000.0, 2.0, 0)),
+    ]);
+    let current = report(&[
+        ("a", metrics(850.0, 2.0, 0)),
+        ("b", metrics(2000.0, 2.0, 0)),
+    ]);
+
+    let comparison = compare(&baseline, &current, &thresholds());
+
+    assert_eq!(
+        status(&comparison, "a", Metric::Throughput),
+        MetricStatus::Fail
+    );
+    assert_eq!(
+        status(&comparison, "b", Metric::Throughput),
+        MetricStatus::Pass
+    );
+}
+
+#[test]
+fn compare_regression_of_exactly_the_threshold_passes() {
+    let baseline = report(&[("bench", metrics(1000.0, 4.0, 0))]);
+    let current = report(&[("bench", metrics(900.0, 4.4, 0))]);
+
+    let comparison = compare(&baseline, &current, &thresholds());
+
+    assert_eq!(
+        status(&comparison, "bench", Metric::Throughput),
+        MetricStatus::Pass
+    );
+    assert_eq!(
+        status(&comparison, "bench", Metric::P99),
+        MetricStatus::Pass
+    );
+    assert!(comparison.passed());
+}
+
+#[test]
+fn compare_new_errors_fail_a_zero_threshold() {
+    let baseline = report(&[("bench", metrics(1000.0, 2.0, 0))]);
+    let current = report(&[("bench", metrics(1000.0, 2.0, 1))]);
+
+    let comparison = compare(&baseline, &current, &thresholds());
+
+    assert_eq!(
+        status(&comparison, "bench", Metric::Errors),
+        MetricStatus::Fail
+    );
+}
+
+#[test]
+fn compare_metric_without_threshold_is_unchecked() {
+    let baseline = report(&[("bench", metrics(1000.0, 2.0, 0))]);
+    let current = report(&[("bench", metrics(1000.0, 20.0, 0))]);
+
+    let comparison = compare(&baseline, &current, &Thresholds::default());
+
+    assert_eq!(
+        status(&comparison, "bench", Metric::P99),
+        MetricStatus::Unchecked
+    );
+    assert!(comparison.passed());
+}
+
+#[test]
+fn compare_metric_missing_from_a_report_is_missing() {
+    let baseline = report(&[("bench", metrics(1000.0, 2.0, 0))]);
+    let mut without_p99 = metrics(1000.0, 2.0, 0);
+    without_p99.p99_ms = None;
+    let current = report(&[("bench", without_p99)]);
+
+    let comparison = compare(&baseline, &current, &thresholds());
+
+    assert_eq!(
+        status(&comparison, "bench", Metric::P99),
+        MetricStatus::Missing
+    );
+    assert!(comparison.passed());
+}
+
+#[test]
+fn compare_per_benchmark_override_wins() {
+    let thresholds = Thresholds::parse(
+        r#"
+[default]
+p99 = 10.0
+
+[benchmarks."slow/bench"]
+p99 = 50.0
+"#,
+    )
+    .unwrap();
+    let baseline = report(&[
+        ("slow/bench", metrics(1000.0, 2.0, 0)),
+        ("fast", metrics(1000.0, 2.0, 0)),
+    ]);
+    let current = report(&[
+        ("slow/bench", metrics(1000.0, 2.8, 0)),
+        ("fast", metrics(1000.0, 2.8, 0)),
+    ]);
+
+    let comparison = compare(&baseline, &current, &thresholds);
+
+    assert_eq!(
+        status(&comparison, "slow/bench", Metric::P99),
+        MetricStatus::Pass
+    );
+    assert_eq!(status(&comparison, "fast", Metric::P99), MetricStatus::Fail);
+    assert_eq!(thresholds.threshold("slow/bench", Metric::Throughput), None);
+}
+
+#[test]
+fn compare_missing_benchmark_fails_by_default() {
+    let baseline = report(&[
+        ("kept", metrics(1000.0, 2.0, 0)),
+        ("gone", metrics(1000.0, 2.0, 0)),
+    ]);
+    let current = report(&[
+        ("kept", metrics(1000.0, 2.0, 0)),
+        ("new", metrics(1000.0, 2.0, 0)),
+    ]);
+
+    let comparison = compare(&baseline, &current, &thresholds());
+
+    assert_eq!(comparison.missing, vec!["gone".to_string()]);
+    assert_eq!(comparison.added, vec!["new".to_string()]);
+    assert!(!comparison.passed());
+    assert!(comparison.to_string().contains("MISSING   gone"));
+}
+
+#[test]
+fn compare_missing_benchmark_allowed_passes() {
+    let thresholds = Thresholds::parse("fail_on_missing = false\n").unwrap();
+    let baseline = report(&[("gone", metrics(1000.0, 2.0, 0))]);
+
+    let comparison = compare(&baseline, &BenchReport::default(), &thresholds);
+
+    assert_eq!(comparison.missing, vec!["gone".to_string()]);
+    assert!(comparison.passed());
+}
+
+#[test]
+fn thresholds_reject_unknown_metrics() {
+    assert!(Thresholds::parse("[default]\np42 = 1.0\n").is_err());
+}
@@ -0,0 +1,62 @@
+//! Tests for the bench report
+//!
+use bench_utils::{BenchMetrics, BenchReport, ReportWriter};
+use std::time::Duration;
+use tempfile::TempDir;
+
+#[test]
+fn bench_metrics_from_latencies_computes_percentiles_and_throughput() {
+    let latencies: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+
+    let metrics = BenchMetrics::from_latencies(&latencies, 3, Duration::from_secs(2));
+
+    assert_eq!(metrics.throughput_per_sec, Some(50.0));
+    assert_eq!(metrics.p50_ms, Some(50.0));
+    assert_eq!(metrics.p95_ms, Some(95.0));
+    assert_eq!(metrics.p99_ms, Some(99.0));
+    assert_eq!(metrics.errors, 3);
+}
+
+#[test]
+fn bench_metrics_from_no_latencies_leaves_percentiles_unset() {
+    let metrics = BenchMetrics::from_latencies(&[], 0, Duration::ZERO);
+
+    assert_eq!(metrics, BenchMetrics::default());
+}
+
+#[test]
+fn bench_report_round_trips_through_json() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("report.json");
+    let mut report = BenchReport::default();
+    report.record(
+        "accept_path/check_generation",
+        BenchMetrics {
+            throughput_per_sec: Some(1_000_000.0),
+            p99_ms: Some(0.002),
+            ..BenchMetrics::default()
+        },
+    );
+
+    report.save(&path).expect("Report should be written");
+
+    assert_eq!(
+        BenchReport::load(&path).expect("Report should load"),
+        report
+    );
+}
+
+#[test]
+fn report_writer_writes_once_dropped() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("report.json");
+    let writer = ReportWriter::new(Some(path.clone()));
+    writer.record("a", BenchMetrics::default());
+    writer.record("b", BenchMetrics::default());
+    assert!(!path.exists());
+
+    drop(writer);
+
+    let report = BenchReport::load(&path).expect("Report should be written on drop");
+    assert_eq!(report.benchmarks.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+}
@@ -0,0 +1,69 @@
+//! Accept path micro-benchmark
+//!
+//! Measures the per-accept work of the proxy: checking whether the cached
+//! strategy and route table are stale, then picking a backend, for every
+//! built-in strategy.
+use criterion::{Criterion, criterion_group, criterion_main};
+use lemonade_load_balancer::prelude::*;
+use std::hint::black_box;
+
+/// Backends in the benchmarked route table
+const BACKENDS: u8 = 8;
+
+/// Build a context over `BACKENDS` backends for a strategy
+fn context(strategy: Strategy) -> Arc<Context> {
+    let mut config = ConfigBuilder::from_env().expect("Failed to build config");
+    config.strategy = strategy;
+    config.backends = (0..BACKENDS)
+        .map(|id| {
+            BackendConfig::from(BackendMeta::new(
+                id,
+                Some(format!("backend-{}", id)),
+                SocketAddr::from(([127, 0, 0, 1], 4000 + id as u16)),
+                Some(1u8),
+            ))
+        })
+        .collect();
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+/// Benchmark the accept path
+fn bench_accept_path(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("Failed to build runtime");
+
+    let ctx = context(Strategy::RoundRobin);
+    c.bench_function("accept_path/reload_strategy_and_route_table", |b| {
+        b.iter(|| black_box((ctx.strategy(), ctx.routing_table())))
+    });
+    c.bench_function("accept_path/check_generation", |b| {
+        b.iter(|| black_box(ctx.generation()))
+    });
+
+    for strategy in [
+        Strategy::RoundRobin,
+        Strategy::WeightedRoundRobin,
+        Strategy::Random,
+        Strategy::LeastConnections,
+        Strategy::FastestResponseTime,
+        Strategy::Adaptive,
+    ] {
+        let ctx = context(strategy.clone());
+        let service = ctx.strategy().as_ref().clone();
+        c.bench_function(&format!("accept_path/pick_backend/{:?}", strategy), |b| {
+            b.iter(|| {
+                rt.block_on(async {
+                    let backend = service
+                        .pick_backend(ctx.clone())
+                        .await
+                        .expect("Backend should be picked");
+                    black_box(backend.can_accept_new_connections())
+                })
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_accept_path);
+criterion_main!(benches);
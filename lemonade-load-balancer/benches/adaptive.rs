@@ -0,0 +1,78 @@
+//! Adaptive strategy micro-benchmark
+//!
+//! Measures the per-pick cost of adaptive scoring over growing route tables,
+//! both with every score cached and with a cold cache so the pick scores
+//! every backend.
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use lemonade_load_balancer::prelude::*;
+use std::hint::black_box;
+
+/// Build a context over `count` backends with uneven load and latencies
+fn context(count: usize) -> Arc<Context> {
+    let mut config = ConfigBuilder::from_env().expect("Failed to build config");
+    config.strategy = Strategy::Adaptive;
+    config.backends = (0..count)
+        .map(|id| {
+            let id = id as u8;
+            BackendConfig::from(BackendMeta::new(
+                id,
+                Some(format!("backend-{}", id)),
+                SocketAddr::from(([127, 0, 0, 1], 4000 + id as u16)),
+                Some(id % 4 + 1),
+            ))
+        })
+        .collect();
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    for backend in ctx.routing_table().all_backends() {
+        for _ in 0..(backend.id() as usize * 37 % 23) + 1 {
+            backend.increment_connection();
+        }
+        backend.record_request(backend.id() as u64 % 50 + 5, false);
+    }
+    ctx
+}
+
+/// Benchmark cached and cold picks over 8, 64 and 256 backends
+fn bench_adaptive(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("Failed to build runtime");
+
+    let mut group = c.benchmark_group("adaptive");
+    for count in [8, 64, 256] {
+        let ctx = context(count);
+        let strategy = AdaptiveStrategy::new();
+        group.bench_with_input(BenchmarkId::new("cached", count), &ctx, |b, ctx| {
+            b.iter(|| {
+                rt.block_on(async {
+                    black_box(
+                        strategy
+                            .pick_backend(ctx.clone())
+                            .await
+                            .expect("Backend should be picked"),
+                    )
+                })
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("cold", count), &ctx, |b, ctx| {
+            b.iter_batched(
+                AdaptiveStrategy::new,
+                |strategy| {
+                    rt.block_on(async {
+                        black_box(
+                            strategy
+                                .pick_backend(ctx.clone())
+                                .await
+                                .expect("Backend should be picked"),
+                        )
+                    })
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_adaptive);
+criterion_main!(benches);
@@ -0,0 +1,62 @@
+//! Least connections micro-benchmark
+//!
+//! Compares the per-pick cost of scanning every backend's connection count
+//! with taking the lowest bucket of the connection index, over the largest
+//! route table a `u8` backend id allows.
+use criterion::{Criterion, criterion_group, criterion_main};
+use lemonade_load_balancer::prelude::*;
+use std::hint::black_box;
+
+/// Build a context over 256 backends with uneven connection counts
+fn context() -> Arc<Context> {
+    let mut config = ConfigBuilder::from_env().expect("Failed to build config");
+    config.strategy = Strategy::LeastConnections;
+    config.backends = (0..=u8::MAX)
+        .map(|id| {
+            BackendConfig::from(BackendMeta::new(
+                id,
+                Some(format!("backend-{}", id)),
+                SocketAddr::from(([127, 0, 0, 1], 4000 + id as u16)),
+                Some(1u8),
+            ))
+        })
+        .collect();
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    for backend in ctx.routing_table().all_backends() {
+        for _ in 0..(backend.id() as usize * 37 % 23) + 1 {
+            backend.increment_connection();
+        }
+    }
+    ctx
+}
+
+/// Benchmark scan and indexed picks; each pick opens and closes a connection
+/// on the picked backend, as the proxy does
+fn bench_least_connections(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("Failed to build runtime");
+
+    for (name, strategy) in [
+        ("scan", LeastConnectionsStrategy::new(usize::MAX)),
+        ("index", LeastConnectionsStrategy::new(0)),
+    ] {
+        let ctx = context();
+        c.bench_function(&format!("least_connections/256_backends/{}", name), |b| {
+            b.iter(|| {
+                rt.block_on(async {
+                    let backend = strategy
+                        .pick_backend(ctx.clone())
+                        .await
+                        .expect("Backend should be picked");
+                    backend.increment_connection();
+                    backend.decrement_connection();
+                    black_box(backend)
+                })
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_least_connections);
+criterion_main!(benches);
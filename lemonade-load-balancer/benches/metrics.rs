@@ -0,0 +1,36 @@
+//! Metrics aggregation micro-benchmark
+//!
+//! Compares the per-event cost of recording a connection latency into a
+//! bucket histogram and into a sampling reservoir, and the cost of the
+//! reservoir's percentile computation on each metrics flush.
+use criterion::{Criterion, criterion_group, criterion_main};
+use lemonade_load_balancer::prelude::*;
+use std::hint::black_box;
+
+/// Latencies recorded in turn, spread over several histogram buckets
+const LATENCIES_MICROS: [u64; 8] = [180, 950, 2_300, 4_100, 12_000, 48_000, 90_000, 750];
+
+/// Benchmark recording in each aggregation mode, then flushing a full reservoir
+fn bench_metrics(c: &mut Criterion) {
+    for aggregation in [LatencyAggregation::Histogram, LatencyAggregation::Reservoir] {
+        let recorder = LatencyRecorder::new(aggregation);
+        let mut next = 0;
+        c.bench_function(&format!("metrics/record/{:?}", aggregation), |b| {
+            b.iter(|| {
+                next = (next + 1) % LATENCIES_MICROS.len();
+                recorder.record(black_box(LATENCIES_MICROS[next]));
+            })
+        });
+    }
+
+    let reservoir = LatencyReservoir::default();
+    for i in 0..RESERVOIR_CAPACITY * 4 {
+        reservoir.record(LATENCIES_MICROS[i % LATENCIES_MICROS.len()]);
+    }
+    c.bench_function("metrics/flush/Reservoir", |b| {
+        b.iter(|| black_box(reservoir.flush()))
+    });
+}
+
+criterion_group!(benches, bench_metrics);
+criterion_main!(benches);
@@ -0,0 +1,116 @@
+//! Proxy runtime isolation benchmark
+//!
+//! Measures round trips through the proxy while background tasks block the
+//! shared runtime's workers, once with the proxy on the shared runtime and
+//! once on a dedicated runtime (`runtime.proxy_worker_threads`). Criterion's
+//! sample distribution shows the tail latency added by the blocking work.
+use criterion::{Criterion, criterion_group, criterion_main};
+use lemonade_load_balancer::App;
+use lemonade_load_balancer::prelude::*;
+use std::hint::black_box;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Pause of each simulated background flush, blocking a shared worker
+const BACKGROUND_PAUSE: Duration = Duration::from_millis(2);
+
+/// Spawn an echo backend and return its address
+async fn echo_backend() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind echo backend");
+    let addr = listener.local_addr().expect("echo address");
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let (mut reader, mut writer) = stream.split();
+                let _ = tokio::io::copy(&mut reader, &mut writer).await;
+            });
+        }
+    });
+    addr
+}
+
+/// Start the load balancer over one echo backend and return its address
+async fn start(proxy_worker_threads: Option<usize>) -> (Arc<Context>, SocketAddr) {
+    let backend = echo_backend().await;
+    let listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = ConfigBuilder::from_env().expect("Failed to build config");
+    config.proxy.listen_address = listen_address;
+    config.runtime.proxy_worker_threads = proxy_worker_threads;
+    config.backends = vec![BackendConfig::from(BackendMeta::new(
+        0u8,
+        Some("echo"),
+        backend,
+        Some(1u8),
+    ))];
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let app = App::new(
+        Arc::new(StaticConfigService::new()),
+        Arc::new(
+            BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.health)))
+                .expect("Failed to create health service"),
+        ),
+        Arc::new(
+            AggregatingMetricsService::new(Arc::new(ArcSwap::from_pointee(
+                config.metrics,
+            )))
+            .expect("Failed to create metrics service"),
+        ),
+        Arc::new(
+            TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+                .expect("Failed to create proxy service"),
+        ),
+    )
+    .await;
+    tokio::spawn({
+        let ctx = ctx.clone();
+        async move { app.run(ctx).await }
+    });
+    while !ctx.readiness().is_accepting() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    (ctx, listen_address)
+}
+
+/// Open a connection through the proxy and echo one message
+async fn round_trip(addr: SocketAddr) {
+    let mut stream = TcpStream::connect(addr).await.expect("connect");
+    stream.write_all(b"ping").await.expect("write");
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await.expect("read");
+    black_box(reply);
+}
+
+/// Benchmark proxy round trips with and without runtime isolation
+fn bench_proxy_runtime(c: &mut Criterion) {
+    for (name, threads) in [("shared", None), ("dedicated", Some(2))] {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .expect("Failed to build runtime");
+        let (ctx, addr) = rt.block_on(start(threads));
+
+        // Background work that blocks the shared workers
+        for _ in 0..2 {
+            rt.spawn(async {
+                loop {
+                    std::thread::sleep(BACKGROUND_PAUSE);
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+            });
+        }
+
+        c.bench_function(&format!("proxy_runtime/round_trip/{}", name), |b| {
+            b.iter(|| rt.block_on(round_trip(addr)))
+        });
+        let _ = ctx.channels().shutdown_tx().send(());
+        rt.shutdown_background();
+    }
+}
+
+criterion_group!(benches, bench_proxy_runtime);
+criterion_main!(benches);
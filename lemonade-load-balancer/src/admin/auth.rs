@@ -0,0 +1,74 @@
+//! Admin authentication module
+//!
+use crate::prelude::*;
+
+/// Admin request authorizer
+///
+/// Checks the bearer token (when configured) before anything else, so an
+/// unauthenticated caller learns nothing about the API, then rejects
+/// mutating requests when the API is read-only.
+#[derive(Debug, Clone, Copy)]
+pub struct AdminAuth<'a> {
+    /// Expected bearer token
+    token: Option<&'a str>,
+    /// Reject mutating requests
+    read_only: bool,
+}
+
+impl<'a> AdminAuth<'a> {
+    /// Create an authorizer from the admin config
+    pub fn new(config: &'a AdminConfig) -> Self {
+        Self {
+            token: config.token.as_deref(),
+            read_only: config.read_only,
+        }
+    }
+
+    /// Authorize a request
+    ///
+    /// # Arguments
+    /// * `authorization` - Value of the `Authorization` header, if any
+    /// * `mutating` - Whether the request changes load balancer state
+    pub fn authorize(
+        &self,
+        authorization: Option<&str>,
+        mutating: bool,
+    ) -> Result<(), AdminError> {
+        if let Some(expected) = self.token {
+            let provided = authorization
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .unwrap_or_default();
+            if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+                return Err(AdminError::Unauthorized);
+            }
+        }
+        if mutating && self.read_only {
+            return Err(AdminError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Audit actor for requests authorized by this token
+    ///
+    /// The token is identified by a short fingerprint, never by its value.
+    pub fn actor(&self) -> AuditActor {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+
+        AuditActor::Admin(self.token.map(|token| {
+            let mut hasher = DefaultHasher::new();
+            token.hash(&mut hasher);
+            format!("{:08x}", hasher.finish() >> 32)
+        }))
+    }
+}
+
+/// Compare two byte strings in time independent of where they differ
+///
+/// Only the length of `expected` influences the running time.
+pub fn constant_time_eq(provided: &[u8], expected: &[u8]) -> bool {
+    let mut diff = (provided.len() != expected.len()) as u8;
+    for (index, byte) in expected.iter().enumerate() {
+        diff |= byte ^ provided.get(index).copied().unwrap_or(!byte);
+    }
+    diff == 0
+}
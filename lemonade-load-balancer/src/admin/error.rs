@@ -0,0 +1,32 @@
+//! Admin Error module
+//!
+use crate::prelude::*;
+
+/// Admin error enum
+#[derive(Debug, thiserror::Error)]
+pub enum AdminError {
+    /// Listener or connection IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Missing or invalid credentials
+    #[error("unauthorized")]
+    Unauthorized,
+    /// Mutating request while the admin API is read-only
+    #[error("admin API is read-only")]
+    ReadOnly,
+    /// Request body that cannot be read
+    #[error("bad request: {0}")]
+    BadRequest(String),
+    /// Unknown route or resource
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// Request that cannot be served in the current state
+    #[error("conflict: {0}")]
+    Conflict(String),
+    /// Config reload failed
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+    /// Applying a change to the context failed
+    #[error("context error: {0}")]
+    Context(#[from] ContextError),
+}
@@ -0,0 +1,7 @@
+//! Admin module
+//!
+
+pub mod auth;
+pub mod error;
+pub mod models;
+pub mod server;
@@ -0,0 +1,69 @@
+//! Admin models module
+//!
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr};
+
+/// Default admin listen port
+const ADMIN_PORT_DEFAULT: u16 = 9090;
+
+/// Admin API config struct
+///
+/// The admin API binds to loopback by default. Binding it to any other
+/// address requires a bearer token.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    /// Serve the admin API
+    pub enabled: bool,
+    /// Admin listen address (read at startup)
+    pub listen_address: SocketAddr,
+    /// Static bearer token required on every request (optional)
+    pub token: Option<String>,
+    /// Reject mutating endpoints (drain, reload, shutdown)
+    pub read_only: bool,
+}
+
+impl AdminConfig {
+    /// Validate the admin config
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.token.as_deref().is_some_and(str::is_empty) {
+            return Err(ConfigError::Admin(
+                "admin token must not be empty".to_string(),
+            ));
+        }
+        if self.enabled && !self.listen_address.ip().is_loopback() && self.token.is_none()
+        {
+            return Err(ConfigError::Admin(format!(
+                "admin API on non-loopback address {} requires a token",
+                self.listen_address
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_address: SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                ADMIN_PORT_DEFAULT,
+            ),
+            token: None,
+            read_only: false,
+        }
+    }
+}
+
+impl std::fmt::Debug for AdminConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminConfig")
+            .field("enabled", &self.enabled)
+            .field("listen_address", &self.listen_address)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("read_only", &self.read_only)
+            .finish()
+    }
+}
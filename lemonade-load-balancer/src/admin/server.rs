@@ -0,0 +1,650 @@
+//! Admin server module
+//!
+//! Minimal HTTP/1.1 admin API:
+//! - `GET /status` - load balancer, backend and listener generation state
+//! - `GET /strategy/explain` - score breakdown of every healthy backend, for
+//!   strategies that score backends (adaptive); empty for the others
+//! - `GET /groups` - status of every backend group
+//! - `GET /groups/{name}/status`, `GET /groups/{name}/strategy/explain`,
+//!   `POST /groups/{name}/backends/{id}/drain`,
+//!   `PATCH /groups/{name}/backends/{id}/weight` - the group-scoped forms of
+//!   the routes below
+//! - `POST /backends/{id}/drain` - stop routing new connections to a backend;
+//!   `?policy=finish|immediate|deadline:<millis>` overrides the configured
+//!   drain policy for its open connections
+//! - `PATCH /backends/{id}/weight?weight=<n>` - change a backend's weight in
+//!   place, without draining it; `weight=none` clears it
+//! - `POST /config/reload` - reload and apply the config file
+//! - `POST /config/dry-run?format=json|toml|yaml` - check the posted config
+//!   document as a reload would and report what it would change, without
+//!   applying anything (allowed on a read-only admin API)
+//! - `POST /shutdown` - trigger a graceful shutdown
+//!
+//! Mutations, including rejected attempts, are recorded in the audit log.
+use crate::prelude::*;
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+use hyper::server::conn::http1::Builder;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tokio::net::{TcpListener, TcpStream};
+
+/// Largest config document accepted by `POST /config/dry-run`
+const MAX_CONFIG_BODY_BYTES: usize = 1024 * 1024;
+
+/// Admin API server
+///
+/// Credentials and the read-only flag are read from the live config on every
+/// request, so a config reload can rotate the token.
+#[derive(Debug, Clone, Default)]
+pub struct AdminServer {
+    /// Config file reloaded by `POST /config/reload`
+    config_file: Option<PathBuf>,
+    /// Backend groups served alongside the admin API's own context
+    groups: Option<Arc<Groups>>,
+}
+
+impl AdminServer {
+    /// Create a new admin server
+    ///
+    /// # Arguments
+    /// * `config_file` - Config file to reload on request (None disables reload)
+    pub fn new(config_file: Option<PathBuf>) -> Self {
+        Self {
+            config_file,
+            groups: None,
+        }
+    }
+
+    /// Expose every backend group and reload them together
+    pub fn with_groups(mut self, groups: Arc<Groups>) -> Self {
+        self.groups = Some(groups);
+        self
+    }
+
+    /// Bind the configured admin address and serve until shutdown
+    pub async fn run(&self, ctx: Arc<Context>) -> Result<(), AdminError> {
+        let listen_address = ctx.config().admin.listen_address;
+        let listener = TcpListener::bind(listen_address).await?;
+        tracing::info!("Admin API listening on {}", listen_address);
+        self.serve(listener, ctx).await
+    }
+
+    /// Serve the admin API on an already bound listener until shutdown
+    ///
+    /// After the shutdown signal the API stays up while connections drain
+    /// (at most `drain_timeout_millis`), so `/status` can report progress.
+    pub async fn serve(
+        &self,
+        listener: TcpListener,
+        ctx: Arc<Context>,
+    ) -> Result<(), AdminError> {
+        let mut shutdown_rx = ctx.channels().shutdown_rx();
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("Admin API received shutdown signal");
+                    break;
+                }
+
+                accept_result = listener.accept() => {
+                    self.spawn_connection(accept_result, &ctx);
+                }
+            }
+        }
+
+        let drain_ms = ctx.config().runtime.drain_timeout_millis;
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(drain_ms);
+        while !ctx.drain_progress().is_drained() {
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline) => break,
+                _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                    // Check the drain again
+                }
+                accept_result = listener.accept() => {
+                    self.spawn_connection(accept_result, &ctx);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Serve an accepted admin connection on its own task
+    fn spawn_connection(
+        &self,
+        accept_result: std::io::Result<(TcpStream, SocketAddr)>,
+        ctx: &Arc<Context>,
+    ) {
+        let (stream, peer) = match accept_result {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                tracing::warn!("Admin API accept error: {}", e);
+                return;
+            }
+        };
+
+        let server = self.clone();
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |req| {
+                let server = server.clone();
+                let ctx = ctx.clone();
+                async move { Ok::<_, Infallible>(server.handle(req, ctx).await) }
+            });
+            if let Err(e) = Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!("Admin API connection from {} failed: {}", peer, e);
+            }
+        });
+    }
+
+    /// Authorize and route a single request
+    async fn handle(
+        &self,
+        req: Request<Incoming>,
+        ctx: Arc<Context>,
+    ) -> Response<Full<Bytes>> {
+        let config = ctx.config();
+        let authorization = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+        let mutating = !matches!(*req.method(), Method::GET | Method::HEAD)
+            && !is_dry_run(req.uri().path());
+        let auth = AdminAuth::new(&config.admin);
+        let result = match auth.authorize(authorization, mutating) {
+            Ok(()) => {
+                let (parts, body) = req.into_parts();
+                let uri = &parts.uri;
+                self.route(
+                    &parts.method,
+                    uri.path(),
+                    uri.query(),
+                    body,
+                    &ctx,
+                    &auth.actor(),
+                )
+                .await
+            }
+            Err(e) => {
+                if let Some(action) = mutation_action(req.uri().path()) {
+                    // Unauthenticated callers are not attributed to the token
+                    let actor = match e {
+                        AdminError::Unauthorized => AuditActor::Admin(None),
+                        _ => auth.actor(),
+                    };
+                    ctx.audit()
+                        .record(AuditRecord::new(&actor, action).rejected(&e));
+                }
+                Err(e)
+            }
+        };
+
+        match result {
+            Ok(body) => json_response(StatusCode::OK, body),
+            Err(e) => error_response(e),
+        }
+    }
+
+    /// Dispatch an authorized request
+    async fn route(
+        &self,
+        method: &Method,
+        path: &str,
+        query: Option<&str>,
+        body: Incoming,
+        ctx: &Context,
+        actor: &AuditActor,
+    ) -> Result<serde_json::Value, AdminError> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            (&Method::GET, ["status"]) => Ok(status(ctx)),
+            (&Method::GET, ["strategy", "explain"]) => Ok(explain(ctx)),
+            (&Method::GET, ["groups"]) => {
+                let groups: serde_json::Map<String, serde_json::Value> =
+                    match &self.groups {
+                        Some(groups) => groups
+                            .iter()
+                            .map(|(name, group)| (name.to_string(), status(group)))
+                            .collect(),
+                        None => [(ctx.group().to_string(), status(ctx))]
+                            .into_iter()
+                            .collect(),
+                    };
+                Ok(serde_json::Value::Object(groups))
+            }
+            (_, ["groups", name, rest @ ..]) => {
+                let group = self
+                    .groups
+                    .as_ref()
+                    .and_then(|groups| groups.get(name))
+                    .ok_or_else(|| AdminError::NotFound(format!("group {}", name)))?;
+                match (method, rest) {
+                    (&Method::GET, ["status"]) => Ok(status(&group)),
+                    (&Method::GET, ["strategy", "explain"]) => Ok(explain(&group)),
+                    (&Method::POST, ["backends", id, "drain"]) => {
+                        drain_backend(&group, id, query, actor)
+                    }
+                    (&Method::PATCH, ["backends", id, "weight"]) => {
+                        set_backend_weight(&group, id, query, actor)
+                    }
+                    (&Method::PATCH, ["backends", id, "admin-state"]) => {
+                        set_backend_admin_state(&group, id, query, actor)
+                    }
+                    _ => Err(AdminError::NotFound(path.to_string())),
+                }
+            }
+            (&Method::POST, ["backends", id, "drain"]) => {
+                drain_backend(ctx, id, query, actor)
+            }
+            (&Method::PATCH, ["backends", id, "weight"]) => {
+                set_backend_weight(ctx, id, query, actor)
+            }
+            (&Method::PATCH, ["backends", id, "admin-state"]) => {
+                set_backend_admin_state(ctx, id, query, actor)
+            }
+            (&Method::POST, ["config", "reload"]) => {
+                let Some(path) = self.config_file.as_ref() else {
+                    return Err(AdminError::Conflict(
+                        "no config file to reload".to_string(),
+                    ));
+                };
+                let config = match ConfigBuilder::from_file(Some(path)) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        ctx.audit().record(
+                            AuditRecord::new(actor, AuditAction::ConfigReload)
+                                .with_target(path.display().to_string())
+                                .rejected(&e),
+                        );
+                        return Err(e.into());
+                    }
+                };
+                match &self.groups {
+                    Some(groups) => groups.migrate_as(config, actor.clone()).await?,
+                    None => ctx.migrate_as(config, actor.clone()).await?,
+                }
+                tracing::info!("Admin API: config reloaded from {}", path.display());
+                Ok(serde_json::json!({ "reloaded": true }))
+            }
+            (&Method::POST, ["config", "dry-run"]) => {
+                self.dry_run(ctx, query, body).await
+            }
+            (&Method::POST, ["shutdown"]) => {
+                tracing::info!("Admin API: shutdown requested");
+                let _ = ctx.channels().shutdown_tx().send(());
+                if let Some(groups) = &self.groups {
+                    groups.shutdown();
+                }
+                ctx.audit()
+                    .record(AuditRecord::new(actor, AuditAction::Shutdown));
+                Ok(serde_json::json!({ "shutdown": true }))
+            }
+            _ => Err(AdminError::NotFound(path.to_string())),
+        }
+    }
+
+    /// Check a posted config document against the live config
+    ///
+    /// The document goes through the parsing and validation of a reload and
+    /// is diffed against every running group; nothing is applied. The
+    /// `format` query parameter selects json (default), toml or yaml.
+    async fn dry_run(
+        &self,
+        ctx: &Context,
+        query: Option<&str>,
+        body: Incoming,
+    ) -> Result<serde_json::Value, AdminError> {
+        let format = query_param(query, "format")
+            .map(str::parse::<ConfigFormat>)
+            .transpose()?
+            .unwrap_or_default();
+        let body = Limited::new(body, MAX_CONFIG_BODY_BYTES)
+            .collect()
+            .await
+            .map_err(|e| AdminError::BadRequest(format!("cannot read config: {}", e)))?
+            .to_bytes();
+        let content = std::str::from_utf8(&body)
+            .map_err(|e| AdminError::BadRequest(format!("config is not UTF-8: {}", e)))?;
+
+        let report = match ConfigBuilder::parse_content(content, format) {
+            Ok(candidate) => {
+                let live: Vec<(String, Arc<Config>)> = match &self.groups {
+                    Some(groups) => groups
+                        .iter()
+                        .map(|(name, group)| (name.to_string(), group.config()))
+                        .collect(),
+                    None => vec![(ctx.group().to_string(), ctx.config())],
+                };
+                DryRunReport::check(&live, &candidate)
+            }
+            Err(e) => DryRunReport::rejected(&e),
+        };
+        tracing::info!(
+            valid = report.valid,
+            errors = report.errors.len(),
+            "Admin API: config dry run"
+        );
+        serde_json::to_value(report)
+            .map_err(|e| AdminError::Config(ConfigError::Parse(e.to_string())))
+    }
+}
+
+/// Mark a backend of `ctx` draining
+///
+/// The `policy` query parameter overrides the configured drain policy.
+fn drain_backend(
+    ctx: &Context,
+    id: &str,
+    query: Option<&str>,
+    actor: &AuditActor,
+) -> Result<serde_json::Value, AdminError> {
+    let record = AuditRecord::new(actor, AuditAction::BackendDrain)
+        .with_target(format!("backend:{}", id));
+    let policy = match query_param(query, "policy") {
+        Some(policy) => match policy.parse::<DrainPolicy>() {
+            Ok(policy) => policy,
+            Err(e) => {
+                let e = AdminError::Config(ConfigError::Parse(e.to_string()));
+                ctx.audit().record(record.rejected(&e));
+                return Err(e);
+            }
+        },
+        None => ctx.config().runtime.drain_policy,
+    };
+    let Some(backend) = id
+        .parse::<BackendId>()
+        .ok()
+        .and_then(|id| ctx.routing_table().get(id))
+    else {
+        let e = AdminError::NotFound(format!("backend {}", id));
+        ctx.audit().record(record.rejected(&e));
+        return Err(e);
+    };
+    let was_draining = backend.is_draining();
+    ctx.drain_backend(&backend, policy);
+    ctx.audit().record(
+        record
+            .with_change(if was_draining { "draining" } else { "active" }, "draining")
+            .with_detail(format!("policy {}", policy)),
+    );
+    tracing::info!(
+        "Admin API: backend {} of group {} marked draining (policy {})",
+        backend.id(),
+        ctx.group(),
+        policy
+    );
+    Ok(serde_json::json!({ "drained": backend.id(), "policy": policy }))
+}
+
+/// Change the weight of a backend of `ctx` in place
+///
+/// The `weight` query parameter is required; `none` clears the weight.
+fn set_backend_weight(
+    ctx: &Context,
+    id: &str,
+    query: Option<&str>,
+    actor: &AuditActor,
+) -> Result<serde_json::Value, AdminError> {
+    let record = AuditRecord::new(actor, AuditAction::BackendWeight)
+        .with_target(format!("backend:{}", id));
+    let weight = match query_param(query, "weight") {
+        Some("none") => Ok(None),
+        Some(weight) => weight
+            .parse::<u8>()
+            .map(Some)
+            .map_err(|e| ConfigError::Parse(format!("Invalid weight {}: {}", weight, e))),
+        None => Err(ConfigError::Parse("missing weight parameter".to_string())),
+    };
+    let weight = match weight {
+        Ok(weight) => weight,
+        Err(e) => {
+            let e = AdminError::Config(e);
+            ctx.audit().record(record.rejected(&e));
+            return Err(e);
+        }
+    };
+    let Some((backend_id, previous)) = id.parse::<BackendId>().ok().and_then(|id| {
+        ctx.set_backend_weight(id, weight)
+            .map(|previous| (id, previous))
+    }) else {
+        let e = AdminError::NotFound(format!("backend {}", id));
+        ctx.audit().record(record.rejected(&e));
+        return Err(e);
+    };
+    let describe =
+        |weight: Option<u8>| weight.map_or("none".to_string(), |w| w.to_string());
+    ctx.audit()
+        .record(record.with_change(describe(previous), describe(weight)));
+    tracing::info!(
+        "Admin API: backend {} of group {} weight changed from {} to {}",
+        backend_id,
+        ctx.group(),
+        describe(previous),
+        describe(weight)
+    );
+    Ok(serde_json::json!({ "backend": backend_id, "weight": weight }))
+}
+
+/// Change the admin state of a backend of `ctx` in place
+///
+/// The `state` query parameter (`enabled`, `disabled` or `draining`) is
+/// required; the applied change is audited by the context.
+fn set_backend_admin_state(
+    ctx: &Context,
+    id: &str,
+    query: Option<&str>,
+    actor: &AuditActor,
+) -> Result<serde_json::Value, AdminError> {
+    let record = AuditRecord::new(actor, AuditAction::BackendAdminState)
+        .with_target(format!("backend:{}", id));
+    let state = match query_param(query, "state") {
+        Some(state) => state
+            .parse::<AdminState>()
+            .map_err(|e| ConfigError::Parse(e.to_string())),
+        None => Err(ConfigError::Parse("missing state parameter".to_string())),
+    };
+    let state = match state {
+        Ok(state) => state,
+        Err(e) => {
+            let e = AdminError::Config(e);
+            ctx.audit().record(record.rejected(&e));
+            return Err(e);
+        }
+    };
+    let Some((backend_id, previous)) = id.parse::<BackendId>().ok().and_then(|id| {
+        ctx.set_admin_state_as(id, state, actor.clone())
+            .map(|previous| (id, previous))
+    }) else {
+        let e = AdminError::NotFound(format!("backend {}", id));
+        ctx.audit().record(record.rejected(&e));
+        return Err(e);
+    };
+    tracing::info!(
+        "Admin API: backend {} of group {} admin state changed from {} to {}",
+        backend_id,
+        ctx.group(),
+        previous,
+        state
+    );
+    Ok(serde_json::json!({ "backend": backend_id, "admin_state": state }))
+}
+
+/// Value of a `key=value` query parameter
+fn query_param<'a>(query: Option<&'a str>, key: &str) -> Option<&'a str> {
+    query?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find_map(|(name, value)| (name == key).then_some(value))
+}
+
+/// Whether `path` is the config dry run, which changes nothing
+fn is_dry_run(path: &str) -> bool {
+    path.trim_matches('/') == "config/dry-run"
+}
+
+/// Audited action for a mutating admin route
+fn mutation_action(path: &str) -> Option<AuditAction> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["backends", _, "drain"] | ["groups", _, "backends", _, "drain"] => {
+            Some(AuditAction::BackendDrain)
+        }
+        ["backends", _, "weight"] | ["groups", _, "backends", _, "weight"] => {
+            Some(AuditAction::BackendWeight)
+        }
+        ["backends", _, "admin-state"] | ["groups", _, "backends", _, "admin-state"] => {
+            Some(AuditAction::BackendAdminState)
+        }
+        ["config", "reload"] => Some(AuditAction::ConfigReload),
+        ["shutdown"] => Some(AuditAction::Shutdown),
+        _ => None,
+    }
+}
+
+/// Score breakdown of the backends the strategy would pick from
+fn explain(ctx: &Context) -> serde_json::Value {
+    let strategy = ctx.strategy();
+    serde_json::json!({
+        "strategy": strategy.strategy(),
+        "backends": strategy.explain_pick(ctx),
+    })
+}
+
+/// Snapshot of the load balancer state
+fn status(ctx: &Context) -> serde_json::Value {
+    let config = ctx.config();
+    let mut backends = ctx.routing_table().all_backends();
+    backends.sort_by_key(|backend| backend.id());
+    let backends: Vec<serde_json::Value> = backends
+        .iter()
+        .map(|backend| {
+            let (hedges_started, hedges_won) = backend.hedge_totals();
+            let (deadlines_propagated, deadlines_exceeded) = backend.deadline_totals();
+            serde_json::json!({
+                "id": backend.id(),
+                "name": backend.name(),
+                "address": backend.address().as_str(),
+                "weight": backend.weight(),
+                "priority": backend.priority(),
+                "zone": backend.zone(),
+                "alive": backend.is_alive(),
+                "draining": backend.is_draining(),
+                "admin_state": backend.admin_state(),
+                "circuit": backend.circuit().state(),
+                "active_connections": backend.active_connections(),
+                "pending_connects": backend.pending_connects(),
+                "forced_closes": backend.forced_closes(),
+                "backpressure_events": backend.backpressure_events(),
+                "selections": backend.selections(),
+                "selection_exclusions": backend
+                    .selection_exclusions()
+                    .into_iter()
+                    .map(|(reason, count)| (reason.as_str(), count))
+                    .collect::<BTreeMap<_, _>>(),
+                "hedges": {
+                    "started": hedges_started,
+                    "won": hedges_won,
+                },
+                "deadlines": {
+                    "propagated": deadlines_propagated,
+                    "exceeded": deadlines_exceeded,
+                },
+            })
+        })
+        .collect();
+    let generations = ctx.listener_generations();
+    let listener_generations: Vec<serde_json::Value> = generations
+        .snapshot()
+        .into_iter()
+        .map(|(generation, active)| {
+            serde_json::json!({
+                "generation": generation,
+                "active_connections": active,
+            })
+        })
+        .collect();
+    let (retries_spent, retries_suppressed) = ctx.retry_budget().totals();
+    let pick_durations: serde_json::Map<String, serde_json::Value> = ctx
+        .pick_timings()
+        .snapshot()
+        .into_iter()
+        .map(|(strategy, histogram)| {
+            let summary = serde_json::json!({
+                "count": histogram.count,
+                "mean_ms": histogram.mean_ms(),
+                "p99_ms": histogram.percentile_ms(0.99),
+            });
+            (strategy, summary)
+        })
+        .collect();
+
+    serde_json::json!({
+        "group": ctx.group(),
+        "build": crate::build_info(),
+        "listen_address": config.proxy.listen_address.to_string(),
+        "strategy": config.strategy,
+        "backends": backends,
+        "listener_generation": generations.current(),
+        "listener_generations": listener_generations,
+        "drain": ctx.drain_state().as_deref(),
+        "retry_budget": {
+            "tokens": ctx.retry_budget().tokens(),
+            "spent": retries_spent,
+            "suppressed": retries_suppressed,
+        },
+        "strategy_pick_duration": pick_durations,
+        "stale_entries_evicted": ctx.stale_entries_evicted(),
+        "invariant_violations": ctx.invariant_violations(),
+        "strategy_fallbacks": ctx.strategy_fallbacks(),
+        "events_expired": {
+            "metrics": ctx.channels().metrics_events_expired(),
+            "health": ctx.channels().health_events_expired(),
+        },
+        "backend_failures": ctx.channels().backend_failure_stats(),
+    })
+}
+
+/// Build a JSON response
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+/// Map an admin error to a JSON error response
+fn error_response(error: AdminError) -> Response<Full<Bytes>> {
+    let status = match &error {
+        AdminError::Unauthorized => StatusCode::UNAUTHORIZED,
+        AdminError::ReadOnly => StatusCode::FORBIDDEN,
+        AdminError::BadRequest(_) => StatusCode::BAD_REQUEST,
+        AdminError::NotFound(_) => StatusCode::NOT_FOUND,
+        AdminError::Conflict(_) => StatusCode::CONFLICT,
+        AdminError::Config(_) | AdminError::Context(_) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        AdminError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let mut response =
+        json_response(status, serde_json::json!({ "error": error.to_string() }));
+    if status == StatusCode::UNAUTHORIZED {
+        response.headers_mut().insert(
+            WWW_AUTHENTICATE,
+            hyper::header::HeaderValue::from_static("Bearer"),
+        );
+    }
+    response
+}
@@ -0,0 +1,99 @@
+//! Public API module
+//!
+//! The supported surface for embedding the load balancer and extending it
+//! with custom strategies and services. Items are re-exported by name so
+//! the set is reviewed on every change (see `tests/api`); the internal
+//! prelude is not part of it and may change between releases.
+
+// Entry points
+pub use crate::{App, build_info, run, run_with_overrides};
+
+// Errors
+pub use crate::{
+    admin::error::AdminError,
+    config::error::ConfigError,
+    error::Error,
+    health::error::HealthError,
+    metrics::error::MetricsError,
+    proxy::error::ProxyError,
+    strategy::error::StrategyError,
+    types::{
+        AdminStateError, BackendAddressError, BackendConnectError, ContextError,
+        DrainPolicyError, RouteTableError,
+    },
+};
+
+// Configuration and builders
+pub use crate::config::{
+    builder::ConfigBuilder,
+    compat::{CONFIG_VERSION, ConfigFormat, Migration, migrate_file, upgrade},
+    diff::{ConfigDiff, DryRunReport},
+    models::{
+        Config, ConfigSource, GroupConfig, LogRateLimitConfig, RuntimeConfig,
+        ServiceOverrides, ServicesConfig,
+    },
+};
+pub use crate::{
+    admin::models::AdminConfig,
+    audit::models::AuditConfig,
+    health::models::{
+        HealthCheck, HealthConfig, HealthEndpointConfig, InitialHealthState,
+        PreflightConfig,
+    },
+    metrics::models::{AutoWeightConfig, LatencyAggregation, MetricsConfig},
+    proxy::models::ProxyConfig,
+    state::models::{HealthStateFileConfig, StateFileConfig},
+};
+
+// Strategies and the strategy registry
+pub use crate::strategy::{
+    builder::StrategyBuilder,
+    is_registered, lookup,
+    models::{BackendScoreBreakdown, Strategy},
+    port::StrategyService,
+    register,
+    registry::StrategyFactory,
+    trace::{ExclusionReason, trace_exclusions},
+    unregister,
+};
+
+// Runtime state shared with services and strategies
+pub use crate::types::{
+    AdminState, Backend, BackendAddress, BackendConfig, BackendId, BackendMeta,
+    BackendMetrics, BackendStream, CircuitBreaker, CircuitState, Clock, Context,
+    DrainPolicy, Groups, HistogramSnapshot, LabelSelector, Labels, LatencyPercentiles,
+    LatencySummary, LogRateLimiter, MetricsSnapshot, RequestHeadLimits, RequestMeta,
+    RouteTable, SystemClock, UnixAddress,
+};
+
+// Service ports and the bundled adapters
+pub use crate::{
+    admin::server::AdminServer,
+    config::{
+        impls::{NotifyConfigService, StaticConfigService},
+        port::ConfigService,
+    },
+    health::{
+        adapters::{BackendHealthService, NoopHealthService},
+        endpoint::HealthEndpointServer,
+        history::HealthProbeRecord,
+        models::HealthFailureReason,
+        port::HealthService,
+    },
+    metrics::{
+        adapters::{
+            AggregatingMetricsService, ExternalMetricsService, NoopMetricsService,
+            WORKER_STATS_PATH, WorkerStats,
+        },
+        port::MetricsService,
+    },
+    proxy::{
+        adapters::{DEADLINE_HEADER, HOP_HEADER, TokioProxyService},
+        port::ProxyService,
+    },
+    state::{health::HealthStateWriter, writer::StateFileWriter},
+};
+
+// Dependencies appearing in the signatures above
+pub use arc_swap::ArcSwap;
+pub use async_trait::async_trait;
@@ -14,6 +14,14 @@ pub struct App {
     metrics_service: Arc<dyn MetricsService>,
     /// Proxy service
     proxy_service: Arc<dyn ProxyService>,
+    /// Admin API server (optional)
+    admin_server: Option<AdminServer>,
+    /// Liveness and readiness endpoint (optional)
+    health_endpoint: Option<HealthEndpointServer>,
+    /// State file writer (optional)
+    state_file: Option<StateFileWriter>,
+    /// Health state file writer (optional)
+    health_state_file: Option<HealthStateWriter>,
 }
 
 impl App {
@@ -29,16 +37,68 @@ impl App {
             health_service,
             metrics_service,
             proxy_service,
+            admin_server: None,
+            health_endpoint: None,
+            state_file: None,
+            health_state_file: None,
         }
     }
 
+    /// Serve the admin API alongside the proxy
+    pub fn with_admin_server(mut self, admin_server: AdminServer) -> Self {
+        self.admin_server = Some(admin_server);
+        self
+    }
+
+    /// Serve `/healthz` and `/readyz` when `health_endpoint.listen_address`
+    /// is configured
+    pub fn with_health_endpoint(mut self, health_endpoint: HealthEndpointServer) -> Self {
+        self.health_endpoint = Some(health_endpoint);
+        self
+    }
+
+    /// Write the state file when `state_file` is configured
+    pub fn with_state_file(mut self, state_file: StateFileWriter) -> Self {
+        self.state_file = Some(state_file);
+        self
+    }
+
+    /// Persist backend health when `health_state_file` is configured
+    pub fn with_health_state_file(
+        mut self,
+        health_state_file: HealthStateWriter,
+    ) -> Self {
+        self.health_state_file = Some(health_state_file);
+        self
+    }
+
     /// Run the app
     #[tracing::instrument(skip(self, ctx), fields(service.name = "lemonade-load-balancer"))]
     pub async fn run(&self, ctx: Arc<Context>) -> Result<()> {
-        tracing::info!("Starting load balancer");
+        let build = crate::build_info();
+        tracing::info!(
+            version = build.version,
+            git_sha = build.git_sha,
+            build_timestamp = build.build_timestamp,
+            rng_seed = ctx.rng().seed(),
+            "Starting load balancer"
+        );
 
-        // Spawn background service tasks
-        let config_handle = tokio::spawn({
+        // Pre-flight backend reachability check (before any service starts)
+        let startup_config = ctx.config();
+        if startup_config.preflight.verify_backends_on_start {
+            BackendHealthService::verify_backends(
+                &ctx.routing_table(),
+                startup_config.health.timeout,
+                &startup_config.preflight,
+                startup_config.proxy.backend_bind_address,
+            )
+            .await?;
+        }
+
+        // Spawn background service tasks (stopped together on shutdown)
+        let mut background = tokio::task::JoinSet::new();
+        background.spawn({
             let ctx = ctx.clone();
             let svc = self.config_service.clone();
             async move {
@@ -46,7 +106,7 @@ impl App {
             }
         });
 
-        let health_handle = tokio::spawn({
+        background.spawn({
             let ctx = ctx.clone();
             let svc = self.health_service.clone();
             async move {
@@ -54,7 +114,7 @@ impl App {
             }
         });
 
-        let metrics_handle = tokio::spawn({
+        background.spawn({
             let ctx = ctx.clone();
             let svc = self.metrics_service.clone();
             async move {
@@ -62,33 +122,141 @@ impl App {
             }
         });
 
+        // Auto-weight controller (idles unless `auto_weight` is enabled)
+        background.spawn(WeightController::run(ctx.clone()));
+
+        // Consistency audit (returns at once unless `audit_interval_millis` is set)
+        background.spawn(ConsistencyChecker::run(ctx.clone()));
+
+        // Health endpoint (optional)
+        if let Some(server) = self.health_endpoint.clone() {
+            let ctx = ctx.clone();
+            background.spawn(async move {
+                if let Err(e) = server.run(ctx).await {
+                    tracing::error!("Health endpoint failed: {}", e);
+                }
+            });
+        }
+
+        // State file writer (optional, idles unless `state_file` is configured)
+        if let Some(writer) = self.state_file.clone() {
+            let ctx = ctx.clone();
+            background.spawn(async move { writer.run(ctx).await });
+        }
+
+        // Health state writer (optional, idles unless `health_state_file` is
+        // configured)
+        if let Some(writer) = self.health_state_file.clone() {
+            let ctx = ctx.clone();
+            background.spawn(async move { writer.run(ctx).await });
+        }
+
+        // Admin API (optional, kept up while connections drain)
+        let mut admin_handle = self.admin_server.clone().map(|server| {
+            let ctx = ctx.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.run(ctx).await {
+                    tracing::error!("Admin API failed: {}", e);
+                }
+            })
+        });
+
         // Spawn Ctrl-C handler
         let shutdown_tx = ctx.channels().shutdown_tx();
+        let signal_ctx = ctx.clone();
         tokio::spawn(async move {
             let _ = tokio::signal::ctrl_c().await;
             tracing::info!("Shutdown signal received");
             let _ = shutdown_tx.send(());
+            signal_ctx
+                .audit()
+                .record(AuditRecord::new(&AuditActor::Signal, AuditAction::Shutdown));
         });
 
         // PROXY RUNS ON MAIN THREAD (HOT PATH)
-        // This is critical for performance - no extra task overhead
-        tracing::info!("Starting proxy service on main thread");
-        let proxy_result = self.proxy_service.accept_connections(ctx.clone()).await;
+        // This is critical for performance - no extra task overhead. With
+        // `proxy_worker_threads` it gets a runtime of its own instead, so
+        // background work never adds jitter to the data path.
+        let proxy_runtime = match startup_config.runtime.proxy_worker_threads {
+            Some(threads) => Some(build_proxy_runtime(threads)?),
+            None => None,
+        };
+        let proxy_result = match &proxy_runtime {
+            Some(runtime) => {
+                tracing::info!(
+                    worker_threads = startup_config.runtime.proxy_worker_threads,
+                    "Starting proxy service on dedicated runtime"
+                );
+                let svc = self.proxy_service.clone();
+                let proxy_ctx = ctx.clone();
+                runtime
+                    .spawn(async move { svc.accept_connections(proxy_ctx).await })
+                    .await
+                    .unwrap_or_else(|e| Err(ProxyError::Unexpected(e.to_string())))
+            }
+            None => {
+                tracing::info!("Starting proxy service on main thread");
+                self.proxy_service.accept_connections(ctx.clone()).await
+            }
+        };
+        ctx.readiness().set_accepting(false);
 
-        // If proxy exits (shutdown or error), wait for background services
-        tracing::info!("Proxy service stopped, waiting for background services");
+        // If proxy exits (shutdown or error), stop background services. With
+        // no connection left there is nothing to drain: the admin API stops
+        // with the rest and the drain wait is skipped.
         let cfg = ctx.config();
         let timeout_ms = cfg.runtime.background_timeout_millis;
+        let idle = ctx.active_connections() == 0;
+        if idle {
+            tracing::info!("Proxy service stopped with no active connections");
+            if let Some(admin_handle) = admin_handle.take() {
+                background.spawn(async move {
+                    let _ = admin_handle.await;
+                });
+            }
+        } else {
+            tracing::info!("Proxy service stopped, waiting for background services");
+        }
+        // Services still running at the timeout are aborted with the set
         let _ = tokio::time::timeout(Duration::from_millis(timeout_ms), async {
-            let _ = tokio::join!(config_handle, health_handle, metrics_handle);
+            while background.join_next().await.is_some() {}
         })
         .await;
 
-        // Drain remaining connections
-        let drain_ms = cfg.runtime.drain_timeout_millis;
-        ctx.wait_for_drain(Duration::from_millis(drain_ms)).await?;
+        // Drain remaining connections (the admin API keeps reporting progress)
+        let drain_result = if idle {
+            Ok(())
+        } else {
+            let drain_ms = cfg.runtime.drain_timeout_millis;
+            ctx.wait_for_drain(Duration::from_millis(drain_ms)).await
+        };
+        if let Some(admin_handle) = admin_handle {
+            let _ = tokio::time::timeout(Duration::from_millis(timeout_ms), admin_handle)
+                .await;
+        }
+        if let Some(state_file) = &self.state_file {
+            state_file.write_final(&ctx).await;
+        }
+        if let Some(health_state_file) = &self.health_state_file {
+            health_state_file.write_final(&ctx).await;
+        }
+        // Connections were drained on the proxy runtime; stop its workers
+        if let Some(runtime) = proxy_runtime {
+            runtime.shutdown_background();
+        }
+        drain_result?;
 
         tracing::info!("Shutdown complete");
         proxy_result.map_err(crate::error::Error::Proxy)
     }
 }
+
+/// Build the dedicated runtime hosting the accept loop and proxied connections
+fn build_proxy_runtime(worker_threads: usize) -> Result<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(worker_threads)
+        .thread_name("lemonade-proxy")
+        .enable_all()
+        .build()
+        .map_err(|e| crate::error::Error::Proxy(ProxyError::Io(e)))
+}
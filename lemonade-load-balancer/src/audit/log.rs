@@ -0,0 +1,137 @@
+//! Audit log module
+//!
+//! Audit sink: every record is emitted on the `audit` tracing target and,
+//! when configured, appended to a size-rotated JSONL file.
+use crate::prelude::*;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Audit log sink
+#[derive(Debug)]
+pub struct AuditLog {
+    /// File sink (None when no audit file is configured)
+    file: Mutex<Option<AuditFile>>,
+}
+
+/// Open audit file with its rotation settings
+#[derive(Debug)]
+struct AuditFile {
+    /// Config the file was opened with
+    config: AuditConfig,
+    /// Audit file path
+    path: PathBuf,
+    /// Open file handle (append mode)
+    file: File,
+    /// Current file size in bytes
+    size: u64,
+}
+
+impl AuditLog {
+    /// Create an audit log from its config
+    ///
+    /// An audit file that cannot be opened is reported and skipped; records
+    /// still reach the tracing target.
+    pub fn new(config: &AuditConfig) -> Self {
+        Self {
+            file: Mutex::new(AuditFile::open(config)),
+        }
+    }
+
+    /// Apply a new audit config (reopens the file if it changed)
+    pub fn reconfigure(&self, config: &AuditConfig) {
+        let mut file = self.file.lock().unwrap();
+        if file.as_ref().map(|f| &f.config) != Some(config) {
+            *file = AuditFile::open(config);
+        }
+    }
+
+    /// Record an audited action
+    pub fn record(&self, record: AuditRecord) {
+        tracing::info!(
+            target: "audit",
+            actor = %record.actor,
+            action = ?record.action,
+            target_id = record.target.as_deref(),
+            old = record.old.as_deref(),
+            new = record.new.as_deref(),
+            detail = record.detail.as_deref(),
+            outcome = ?record.outcome,
+            reason = record.reason.as_deref(),
+            "audit"
+        );
+
+        let mut file = self.file.lock().unwrap();
+        if let Some(audit_file) = file.as_mut()
+            && let Err(e) = audit_file.append(&record)
+        {
+            tracing::error!("Failed to write audit record: {}", e);
+        }
+    }
+}
+
+impl AuditFile {
+    /// Open the configured audit file, if any
+    fn open(config: &AuditConfig) -> Option<Self> {
+        let path = config.file.clone()?;
+        match Self::open_file(&path) {
+            Ok((file, size)) => Some(Self {
+                config: config.clone(),
+                path,
+                file,
+                size,
+            }),
+            Err(e) => {
+                tracing::error!("Failed to open audit file {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Open a file for appending and return its current size
+    fn open_file(path: &Path) -> std::io::Result<(File, u64)> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok((file, size))
+    }
+
+    /// Append a record, rotating first if it would exceed the size limit
+    fn append(&mut self, record: &AuditRecord) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        if self.size > 0 && self.size + line.len() as u64 > self.config.max_file_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift `file.N` to `file.N+1` (dropping the oldest) and start a new file
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let max_files = self.config.max_files;
+        if max_files > 0 {
+            for index in (1..max_files).rev() {
+                let from = Self::rotated_path(&self.path, index);
+                if from.exists() {
+                    std::fs::rename(&from, Self::rotated_path(&self.path, index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, Self::rotated_path(&self.path, 1))?;
+        } else {
+            std::fs::remove_file(&self.path)?;
+        }
+        let (file, size) = Self::open_file(&self.path)?;
+        self.file = file;
+        self.size = size;
+        Ok(())
+    }
+
+    /// Path of the `index`-th rotated file
+    fn rotated_path(path: &Path, index: usize) -> PathBuf {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(format!(".{}", index));
+        PathBuf::from(rotated)
+    }
+}
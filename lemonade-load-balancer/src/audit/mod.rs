@@ -0,0 +1,5 @@
+//! Audit module
+//!
+
+pub mod log;
+pub mod models;
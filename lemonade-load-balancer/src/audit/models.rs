@@ -0,0 +1,163 @@
+//! Audit models module
+//!
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Default size of an audit file before it is rotated (10 MiB)
+const AUDIT_MAX_FILE_BYTES_DEFAULT: u64 = 10 * 1024 * 1024;
+
+/// Default number of rotated audit files kept
+const AUDIT_MAX_FILES_DEFAULT: usize = 5;
+
+/// Audit log config struct
+///
+/// Audit records always go to the `audit` tracing target; `file` additionally
+/// appends them as JSON lines to a size-rotated file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Append-only JSONL audit file (optional)
+    pub file: Option<PathBuf>,
+    /// Size in bytes at which the audit file is rotated
+    pub max_file_bytes: u64,
+    /// Number of rotated files kept (`audit.jsonl.1` is the most recent)
+    pub max_files: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            max_file_bytes: AUDIT_MAX_FILE_BYTES_DEFAULT,
+            max_files: AUDIT_MAX_FILES_DEFAULT,
+        }
+    }
+}
+
+/// Who performed an audited action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditActor {
+    /// Admin API caller, identified by its token id when a token is configured
+    Admin(Option<String>),
+    /// Config file watcher
+    FileWatch,
+    /// Process signal (e.g. Ctrl-C)
+    Signal,
+    /// Programmatic call through the library API
+    Api,
+}
+
+impl std::fmt::Display for AuditActor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Admin(Some(token_id)) => write!(f, "admin:{}", token_id),
+            Self::Admin(None) => write!(f, "admin"),
+            Self::FileWatch => write!(f, "file-watch"),
+            Self::Signal => write!(f, "signal"),
+            Self::Api => write!(f, "api"),
+        }
+    }
+}
+
+/// Audited action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// Config reload (full migration)
+    ConfigReload,
+    /// Strategy-only config change
+    StrategyChange,
+    /// Backend marked draining
+    BackendDrain,
+    /// Backend weight changed through the admin API
+    BackendWeight,
+    /// Backend admin state (operator override) changed
+    BackendAdminState,
+    /// Graceful shutdown requested
+    Shutdown,
+}
+
+/// Outcome of an audited action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The action was applied
+    Applied,
+    /// The action was attempted and rejected
+    Rejected,
+}
+
+/// Audit record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Who performed the action
+    pub actor: String,
+    /// What was done
+    pub action: AuditAction,
+    /// What it was done to (backend, config file, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Value before the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    /// Value after the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+    /// Free-form description of the change (e.g. a config diff summary)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Whether the action was applied
+    pub outcome: AuditOutcome,
+    /// Why the action was rejected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl AuditRecord {
+    /// Create a record for an applied action
+    pub fn new(actor: &AuditActor, action: AuditAction) -> Self {
+        let timestamp_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Self {
+            timestamp_ms,
+            actor: actor.to_string(),
+            action,
+            target: None,
+            old: None,
+            new: None,
+            detail: None,
+            outcome: AuditOutcome::Applied,
+            reason: None,
+        }
+    }
+
+    /// Set the target of the action
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Set the old and new values
+    pub fn with_change(mut self, old: impl Into<String>, new: impl Into<String>) -> Self {
+        self.old = Some(old.into());
+        self.new = Some(new.into());
+        self
+    }
+
+    /// Set a free-form description of the change
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Mark the action as rejected
+    pub fn rejected(mut self, reason: impl std::fmt::Display) -> Self {
+        self.outcome = AuditOutcome::Rejected;
+        self.reason = Some(reason.to_string());
+        self
+    }
+}
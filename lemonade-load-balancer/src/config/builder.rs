@@ -77,6 +77,89 @@ impl ConfigBuilder {
                     ))
                 })?;
 
+        let drain_progress_interval_millis =
+            std::env::var(constants::LB_DRAIN_PROGRESS_INTERVAL_MS_ENV_KEY)
+                .unwrap_or_else(|_| {
+                    constants::LB_DRAIN_PROGRESS_INTERVAL_MS_DEFAULT.to_string()
+                })
+                .parse::<u64>()
+                .map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        constants::LB_DRAIN_PROGRESS_INTERVAL_MS_ENV_KEY,
+                        e
+                    ))
+                })?;
+
+        let drain_policy = std::env::var(constants::LB_DRAIN_POLICY_ENV_KEY)
+            .unwrap_or_else(|_| constants::LB_DRAIN_POLICY_DEFAULT.to_string())
+            .parse::<DrainPolicy>()
+            .map_err(|e| {
+                ConfigError::Parse(format!(
+                    "Invalid {}: {}",
+                    constants::LB_DRAIN_POLICY_ENV_KEY,
+                    e
+                ))
+            })?;
+
+        let proxy_worker_threads =
+            std::env::var(constants::LB_PROXY_WORKER_THREADS_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<usize>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            constants::LB_PROXY_WORKER_THREADS_ENV_KEY,
+                            e
+                        ))
+                    })
+                })
+                .transpose()?;
+
+        let audit_interval_millis =
+            std::env::var(constants::LB_AUDIT_INTERVAL_MS_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<u64>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            constants::LB_AUDIT_INTERVAL_MS_ENV_KEY,
+                            e
+                        ))
+                    })
+                })
+                .transpose()?;
+
+        let log_rate_limit_defaults = LogRateLimitConfig::default();
+        let log_rate_limit_window_millis =
+            std::env::var(constants::LB_LOG_RATE_LIMIT_WINDOW_MS_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<u64>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            constants::LB_LOG_RATE_LIMIT_WINDOW_MS_ENV_KEY,
+                            e
+                        ))
+                    })
+                })
+                .transpose()?
+                .unwrap_or(log_rate_limit_defaults.window_millis);
+        let log_rate_limit_burst =
+            std::env::var(constants::LB_LOG_RATE_LIMIT_BURST_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<u32>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            constants::LB_LOG_RATE_LIMIT_BURST_ENV_KEY,
+                            e
+                        ))
+                    })
+                })
+                .transpose()?
+                .unwrap_or(log_rate_limit_defaults.burst);
+
         // Proxy config
         let listen_address = std::env::var(LB_LISTEN_ADDRESS_ENV_KEY)
             .unwrap_or_else(|_| LB_LISTEN_ADDRESS_DEFAULT.to_string())
@@ -100,6 +183,398 @@ impl ConfigBuilder {
             })
             .transpose()?;
 
+        let coalesce_defaults = CoalesceConfig::default();
+        let coalesce_enabled = std::env::var(LB_COALESCE_ENABLED_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_COALESCE_ENABLED_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(coalesce_defaults.enabled);
+
+        let coalesce_micros = std::env::var(LB_COALESCE_MICROS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u64>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_COALESCE_MICROS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(coalesce_defaults.coalesce_micros);
+
+        let coalesce_max_bytes = std::env::var(LB_COALESCE_MAX_BYTES_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<usize>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_COALESCE_MAX_BYTES_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(coalesce_defaults.max_bytes);
+
+        let max_buffered_bytes = std::env::var(LB_MAX_BUFFERED_BYTES_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<usize>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_MAX_BUFFERED_BYTES_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(LB_MAX_BUFFERED_BYTES_DEFAULT);
+
+        let backend_bind_address = std::env::var(LB_BACKEND_BIND_ADDRESS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<IpAddr>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_BACKEND_BIND_ADDRESS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let backend_dscp = std::env::var(LB_BACKEND_DSCP_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u8>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_BACKEND_DSCP_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let fd_headroom = std::env::var(LB_FD_HEADROOM_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u64>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_FD_HEADROOM_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(LB_FD_HEADROOM_DEFAULT);
+
+        let max_backend_peer_connections =
+            std::env::var(LB_MAX_BACKEND_PEER_CONNECTIONS_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<usize>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            LB_MAX_BACKEND_PEER_CONNECTIONS_ENV_KEY, e
+                        ))
+                    })
+                })
+                .transpose()?;
+
+        let max_hops = std::env::var(LB_MAX_HOPS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u32>().map_err(|e| {
+                    ConfigError::Parse(format!("Invalid {}: {}", LB_MAX_HOPS_ENV_KEY, e))
+                })
+            })
+            .transpose()?;
+
+        let transparent = std::env::var(LB_TRANSPARENT_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_TRANSPARENT_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(false);
+
+        let report_backend_aborts = std::env::var(LB_REPORT_BACKEND_ABORTS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_REPORT_BACKEND_ABORTS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(true);
+
+        let listen_unix = std::env::var(LB_LISTEN_UNIX_ENV_KEY)
+            .ok()
+            .map(|v| {
+                UnixAddress::parse(&v).map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_LISTEN_UNIX_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let sticky_sessions = std::env::var(LB_STICKY_SESSIONS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_STICKY_SESSIONS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(false);
+
+        let sticky_ttl_millis = std::env::var(LB_STICKY_TTL_MS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u64>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_STICKY_TTL_MS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(LB_STICKY_TTL_MS_DEFAULT);
+
+        let max_pending_connects = std::env::var(LB_MAX_PENDING_CONNECTS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<usize>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_MAX_PENDING_CONNECTS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(LB_MAX_PENDING_CONNECTS_DEFAULT);
+
+        let local_zone = std::env::var(LB_LOCAL_ZONE_ENV_KEY)
+            .ok()
+            .filter(|v| !v.is_empty());
+
+        let zone_spillover_min_healthy =
+            std::env::var(LB_ZONE_SPILLOVER_MIN_HEALTHY_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<usize>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            LB_ZONE_SPILLOVER_MIN_HEALTHY_ENV_KEY, e
+                        ))
+                    })
+                })
+                .transpose()?
+                .unwrap_or(LB_ZONE_SPILLOVER_MIN_HEALTHY_DEFAULT);
+
+        let propagate_deadlines = std::env::var(LB_PROPAGATE_DEADLINES_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_PROPAGATE_DEADLINES_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(false);
+
+        let request_timeout_millis = std::env::var(LB_REQUEST_TIMEOUT_MS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u64>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_REQUEST_TIMEOUT_MS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(LB_REQUEST_TIMEOUT_MS_DEFAULT);
+
+        let hedging_defaults = HedgingConfig::default();
+        let hedging_enabled = std::env::var(LB_HEDGING_ENABLED_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_HEDGING_ENABLED_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(hedging_defaults.enabled);
+
+        let hedging_delay_millis = std::env::var(LB_HEDGING_DELAY_MS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u64>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_HEDGING_DELAY_MS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(hedging_defaults.delay_millis);
+
+        let hedging_adaptive_delay = std::env::var(LB_HEDGING_ADAPTIVE_DELAY_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_HEDGING_ADAPTIVE_DELAY_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(hedging_defaults.adaptive_delay);
+
+        let hedging_max_rate = std::env::var(LB_HEDGING_MAX_RATE_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<f64>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_HEDGING_MAX_RATE_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(hedging_defaults.max_hedge_rate);
+
+        let circuit_breaker_defaults = CircuitBreakerConfig::default();
+        let circuit_breaker_enabled = std::env::var(LB_CIRCUIT_BREAKER_ENABLED_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_CIRCUIT_BREAKER_ENABLED_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(circuit_breaker_defaults.enabled);
+
+        let circuit_breaker_failure_threshold =
+            std::env::var(LB_CIRCUIT_BREAKER_FAILURE_THRESHOLD_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<u32>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            LB_CIRCUIT_BREAKER_FAILURE_THRESHOLD_ENV_KEY, e
+                        ))
+                    })
+                })
+                .transpose()?
+                .unwrap_or(circuit_breaker_defaults.failure_threshold);
+
+        let circuit_breaker_open_duration_millis =
+            std::env::var(LB_CIRCUIT_BREAKER_OPEN_DURATION_MS_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<u64>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            LB_CIRCUIT_BREAKER_OPEN_DURATION_MS_ENV_KEY, e
+                        ))
+                    })
+                })
+                .transpose()?
+                .unwrap_or(circuit_breaker_defaults.open_duration_millis);
+
+        let slow_connect_warn_ms = std::env::var(LB_SLOW_CONNECT_WARN_MS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u64>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_SLOW_CONNECT_WARN_MS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let slow_connection_warn_secs =
+            std::env::var(LB_SLOW_CONNECTION_WARN_SECS_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<u64>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            LB_SLOW_CONNECTION_WARN_SECS_ENV_KEY, e
+                        ))
+                    })
+                })
+                .transpose()?;
+
+        let strategy_pick_warn_micros =
+            std::env::var(LB_STRATEGY_PICK_WARN_MICROS_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<u64>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            LB_STRATEGY_PICK_WARN_MICROS_ENV_KEY, e
+                        ))
+                    })
+                })
+                .transpose()?;
+
+        let initial_read_timeout_millis =
+            std::env::var(LB_INITIAL_READ_TIMEOUT_MS_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<u64>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            LB_INITIAL_READ_TIMEOUT_MS_ENV_KEY, e
+                        ))
+                    })
+                })
+                .transpose()?;
+
+        let slow_log_max_per_minute = std::env::var(LB_SLOW_LOG_MAX_PER_MINUTE_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u32>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_SLOW_LOG_MAX_PER_MINUTE_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(LB_SLOW_LOG_MAX_PER_MINUTE_DEFAULT);
+
         // Strategy
         let strategy_str = std::env::var(LB_STRATEGY_ENV_KEY)
             .unwrap_or_else(|_| LB_STRATEGY_DEFAULT.to_string());
@@ -108,6 +583,29 @@ impl ConfigBuilder {
             .map_err(|e: StrategyError| {
                 ConfigError::Parse(format!("Invalid {}: {}", LB_STRATEGY_ENV_KEY, e))
             })?;
+        let strategy_params = std::env::var(LB_STRATEGY_PARAMS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                serde_json::from_str::<serde_json::Value>(&v).map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_STRATEGY_PARAMS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let fallback = std::env::var(LB_STRATEGY_FALLBACK_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<Strategy>().map_err(|e: StrategyError| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_STRATEGY_FALLBACK_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?;
 
         // Health config
         let health_interval_ms = std::env::var(LB_HEALTH_INTERVAL_MS_ENV_KEY)
@@ -130,6 +628,172 @@ impl ConfigBuilder {
                 ))
             })?;
 
+        let health_max_event_age_ms = std::env::var(LB_HEALTH_MAX_EVENT_AGE_MS_ENV_KEY)
+            .unwrap_or_else(|_| LB_HEALTH_MAX_EVENT_AGE_MS_DEFAULT.to_string())
+            .parse::<u64>()
+            .map_err(|e| {
+                ConfigError::Parse(format!(
+                    "Invalid {}: {}",
+                    LB_HEALTH_MAX_EVENT_AGE_MS_ENV_KEY, e
+                ))
+            })?;
+
+        let health_evict_on_unhealthy =
+            std::env::var(LB_HEALTH_EVICT_ON_UNHEALTHY_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<bool>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            LB_HEALTH_EVICT_ON_UNHEALTHY_ENV_KEY, e
+                        ))
+                    })
+                })
+                .transpose()?
+                .unwrap_or(false);
+
+        let health_reuse_connections = std::env::var(LB_HEALTH_REUSE_CONNECTIONS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_HEALTH_REUSE_CONNECTIONS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(true);
+
+        let health_slow_start_ms = std::env::var(LB_HEALTH_SLOW_START_MS_ENV_KEY)
+            .unwrap_or_else(|_| LB_HEALTH_SLOW_START_MS_DEFAULT.to_string())
+            .parse::<u64>()
+            .map_err(|e| {
+                ConfigError::Parse(format!(
+                    "Invalid {}: {}",
+                    LB_HEALTH_SLOW_START_MS_ENV_KEY, e
+                ))
+            })?;
+
+        let health_outlier_error_percent =
+            std::env::var(LB_HEALTH_OUTLIER_ERROR_PERCENT_ENV_KEY)
+                .unwrap_or_else(|_| LB_HEALTH_OUTLIER_ERROR_PERCENT_DEFAULT.to_string())
+                .parse::<u8>()
+                .map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_HEALTH_OUTLIER_ERROR_PERCENT_ENV_KEY, e
+                    ))
+                })?;
+
+        let health_outlier_min_connections =
+            std::env::var(LB_HEALTH_OUTLIER_MIN_CONNECTIONS_ENV_KEY)
+                .unwrap_or_else(|_| LB_HEALTH_OUTLIER_MIN_CONNECTIONS_DEFAULT.to_string())
+                .parse::<u32>()
+                .map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_HEALTH_OUTLIER_MIN_CONNECTIONS_ENV_KEY, e
+                    ))
+                })?;
+
+        let health_outlier_window_ms = std::env::var(LB_HEALTH_OUTLIER_WINDOW_MS_ENV_KEY)
+            .unwrap_or_else(|_| LB_HEALTH_OUTLIER_WINDOW_MS_DEFAULT.to_string())
+            .parse::<u64>()
+            .map_err(|e| {
+                ConfigError::Parse(format!(
+                    "Invalid {}: {}",
+                    LB_HEALTH_OUTLIER_WINDOW_MS_ENV_KEY, e
+                ))
+            })?;
+
+        let health_base_ejection_ms = std::env::var(LB_HEALTH_BASE_EJECTION_MS_ENV_KEY)
+            .unwrap_or_else(|_| LB_HEALTH_BASE_EJECTION_MS_DEFAULT.to_string())
+            .parse::<u64>()
+            .map_err(|e| {
+                ConfigError::Parse(format!(
+                    "Invalid {}: {}",
+                    LB_HEALTH_BASE_EJECTION_MS_ENV_KEY, e
+                ))
+            })?;
+
+        let health_max_ejection_percent =
+            std::env::var(LB_HEALTH_MAX_EJECTION_PERCENT_ENV_KEY)
+                .unwrap_or_else(|_| LB_HEALTH_MAX_EJECTION_PERCENT_DEFAULT.to_string())
+                .parse::<u8>()
+                .map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_HEALTH_MAX_EJECTION_PERCENT_ENV_KEY, e
+                    ))
+                })?;
+
+        let health_healthy_threshold = std::env::var(LB_HEALTH_HEALTHY_THRESHOLD_ENV_KEY)
+            .unwrap_or_else(|_| LB_HEALTH_HEALTHY_THRESHOLD_DEFAULT.to_string())
+            .parse::<u32>()
+            .map_err(|e| {
+                ConfigError::Parse(format!(
+                    "Invalid {}: {}",
+                    LB_HEALTH_HEALTHY_THRESHOLD_ENV_KEY, e
+                ))
+            })?;
+
+        let health_unhealthy_threshold =
+            std::env::var(LB_HEALTH_UNHEALTHY_THRESHOLD_ENV_KEY)
+                .unwrap_or_else(|_| LB_HEALTH_UNHEALTHY_THRESHOLD_DEFAULT.to_string())
+                .parse::<u32>()
+                .map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_HEALTH_UNHEALTHY_THRESHOLD_ENV_KEY, e
+                    ))
+                })?;
+
+        let health_jitter_percent = std::env::var(LB_HEALTH_JITTER_PERCENT_ENV_KEY)
+            .unwrap_or_else(|_| LB_HEALTH_JITTER_PERCENT_DEFAULT.to_string())
+            .parse::<u8>()
+            .map_err(|e| {
+                ConfigError::Parse(format!(
+                    "Invalid {}: {}",
+                    LB_HEALTH_JITTER_PERCENT_ENV_KEY, e
+                ))
+            })?;
+
+        let health_initial_state = std::env::var(LB_HEALTH_INITIAL_STATE_ENV_KEY)
+            .unwrap_or_else(|_| LB_HEALTH_INITIAL_STATE_DEFAULT.to_string())
+            .parse::<InitialHealthState>()
+            .map_err(|e| {
+                ConfigError::Parse(format!(
+                    "Invalid {}: {}",
+                    LB_HEALTH_INITIAL_STATE_ENV_KEY, e
+                ))
+            })?;
+
+        // An HTTP check path switches probes from TCP connects to HTTP GETs
+        let health_check = match std::env::var(LB_HEALTH_CHECK_PATH_ENV_KEY)
+            .ok()
+            .filter(|v| !v.is_empty())
+        {
+            Some(path) => HealthCheck::Http {
+                path,
+                expected_status: std::env::var(LB_HEALTH_CHECK_EXPECTED_STATUS_ENV_KEY)
+                    .unwrap_or_else(|_| {
+                        LB_HEALTH_CHECK_EXPECTED_STATUS_DEFAULT.to_string()
+                    })
+                    .parse::<u16>()
+                    .map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            LB_HEALTH_CHECK_EXPECTED_STATUS_ENV_KEY, e
+                        ))
+                    })?,
+                host_header: std::env::var(LB_HEALTH_CHECK_HOST_ENV_KEY)
+                    .ok()
+                    .filter(|v| !v.is_empty()),
+            },
+            None => HealthCheck::Tcp,
+        };
+
         // Metrics config
         let metrics_interval_ms = std::env::var(LB_METRICS_INTERVAL_MS_ENV_KEY)
             .unwrap_or_else(|_| LB_METRICS_INTERVAL_MS_DEFAULT.to_string())
@@ -151,10 +815,203 @@ impl ConfigBuilder {
                 ))
             })?;
 
+        let metrics_max_event_age_ms = std::env::var(LB_METRICS_MAX_EVENT_AGE_MS_ENV_KEY)
+            .unwrap_or_else(|_| LB_METRICS_MAX_EVENT_AGE_MS_DEFAULT.to_string())
+            .parse::<u64>()
+            .map_err(|e| {
+                ConfigError::Parse(format!(
+                    "Invalid {}: {}",
+                    LB_METRICS_MAX_EVENT_AGE_MS_ENV_KEY, e
+                ))
+            })?;
+
+        let metrics_aggregation = std::env::var(LB_METRICS_AGGREGATION_ENV_KEY)
+            .unwrap_or_else(|_| LB_METRICS_AGGREGATION_DEFAULT.to_string())
+            .parse::<LatencyAggregation>()
+            .map_err(|e| {
+                ConfigError::Parse(format!(
+                    "Invalid {}: {}",
+                    LB_METRICS_AGGREGATION_ENV_KEY, e
+                ))
+            })?;
+
+        let metrics_selection_trace = std::env::var(LB_METRICS_SELECTION_TRACE_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_METRICS_SELECTION_TRACE_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(false);
+
+        let auto_weight = std::env::var(LB_AUTO_WEIGHT_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_AUTO_WEIGHT_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(false);
+
+        let rng_seed = std::env::var(LB_RNG_SEED_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u64>().map_err(|e| {
+                    ConfigError::Parse(format!("Invalid {}: {}", LB_RNG_SEED_ENV_KEY, e))
+                })
+            })
+            .transpose()?;
+
+        // Pre-flight check
+        let preflight_defaults = PreflightConfig::default();
+        let verify_backends_on_start = std::env::var(LB_VERIFY_BACKENDS_ON_START_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_VERIFY_BACKENDS_ON_START_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(preflight_defaults.verify_backends_on_start);
+
+        let preflight_strict = std::env::var(LB_PREFLIGHT_STRICT_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_PREFLIGHT_STRICT_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(preflight_defaults.strict);
+
+        let preflight_min_reachable = std::env::var(LB_PREFLIGHT_MIN_REACHABLE_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<usize>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_PREFLIGHT_MIN_REACHABLE_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(preflight_defaults.min_reachable);
+
+        // Health endpoint
+        let health_endpoint_listen_address =
+            std::env::var(LB_HEALTH_ENDPOINT_LISTEN_ADDRESS_ENV_KEY)
+                .ok()
+                .map(|v| {
+                    v.parse::<SocketAddr>().map_err(|e| {
+                        ConfigError::Parse(format!(
+                            "Invalid {}: {}",
+                            LB_HEALTH_ENDPOINT_LISTEN_ADDRESS_ENV_KEY, e
+                        ))
+                    })
+                })
+                .transpose()?;
+
+        let min_healthy_backends = std::env::var(LB_MIN_HEALTHY_BACKENDS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<usize>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_MIN_HEALTHY_BACKENDS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(HealthEndpointConfig::default().min_healthy_backends);
+
+        // Admin config
+        let admin_defaults = AdminConfig::default();
+        let admin_enabled = std::env::var(LB_ADMIN_ENABLED_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_ADMIN_ENABLED_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(admin_defaults.enabled);
+
+        let admin_listen_address = std::env::var(LB_ADMIN_LISTEN_ADDRESS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<std::net::SocketAddr>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_ADMIN_LISTEN_ADDRESS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(admin_defaults.listen_address);
+
+        let admin_read_only = std::env::var(LB_ADMIN_READ_ONLY_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_ADMIN_READ_ONLY_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(admin_defaults.read_only);
+
+        // Background services
+        let services_defaults = ServicesConfig::default();
+        let services_health = std::env::var(LB_SERVICES_HEALTH_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_SERVICES_HEALTH_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(services_defaults.health);
+
+        let services_metrics = std::env::var(LB_SERVICES_METRICS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        LB_SERVICES_METRICS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(services_defaults.metrics);
+
         let otlp_endpoint = std::env::var(LB_OTLP_ENDPOINT_ENV_KEY).ok();
         let otlp_protocol = std::env::var(LB_OTLP_PROTOCOL_ENV_KEY).ok();
 
-        Ok(Config {
+        let mut config = Config {
+            version: CONFIG_VERSION,
             source: ConfigSource::Environment,
             runtime: RuntimeConfig {
                 metrics_cap,
@@ -163,27 +1020,134 @@ impl ConfigBuilder {
                 background_timeout_millis,
                 accept_timeout_millis,
                 config_watch_interval_millis,
+                drain_progress_interval_millis,
+                drain_policy,
+                proxy_worker_threads,
+                audit_interval_millis,
+                log_rate_limit: LogRateLimitConfig {
+                    window_millis: log_rate_limit_window_millis,
+                    burst: log_rate_limit_burst,
+                },
             },
             proxy: ProxyConfig {
                 listen_address,
                 max_connections,
+                coalesce: CoalesceConfig {
+                    enabled: coalesce_enabled,
+                    coalesce_micros,
+                    max_bytes: coalesce_max_bytes,
+                },
+                hedging: HedgingConfig {
+                    enabled: hedging_enabled,
+                    delay_millis: hedging_delay_millis,
+                    adaptive_delay: hedging_adaptive_delay,
+                    max_hedge_rate: hedging_max_rate,
+                },
+                retry_budget: RetryBudgetConfig::default(),
+                circuit_breaker: CircuitBreakerConfig {
+                    enabled: circuit_breaker_enabled,
+                    failure_threshold: circuit_breaker_failure_threshold,
+                    open_duration_millis: circuit_breaker_open_duration_millis,
+                },
+                route_by_sni: SniRoutingConfig::default(),
+                route_by_alpn: AlpnRoutingConfig::default(),
+                route_by_http: HttpRoutingConfig::default(),
+                slow_connect_warn_ms,
+                slow_connection_warn_secs,
+                strategy_pick_warn_micros,
+                initial_read_timeout_millis,
+                slow_log_max_per_minute,
+                max_buffered_bytes,
+                backend_bind_address,
+                backend_dscp,
+                fd_headroom,
+                max_backend_peer_connections,
+                max_hops,
+                transparent,
+                report_backend_aborts,
+                sticky_sessions,
+                sticky_ttl_millis,
+                listen_unix,
+                max_pending_connects,
+                local_zone,
+                zone_spillover_min_healthy,
+                propagate_deadlines,
+                request_timeout_millis,
             },
             strategy,
+            strategy_params,
+            fallback,
             backends: Vec::new(),
+            groups: Default::default(),
+            allow_duplicate_addresses: false,
+            auto_weight,
+            auto_weight_tuning: AutoWeightConfig::default(),
+            admin: AdminConfig {
+                enabled: admin_enabled,
+                listen_address: admin_listen_address,
+                token: admin_token_from_env(),
+                read_only: admin_read_only,
+            },
+            audit: AuditConfig::default(),
             health: HealthConfig {
                 interval: Duration::from_millis(health_interval_ms),
                 timeout: Duration::from_millis(health_timeout_ms),
+                max_event_age_millis: health_max_event_age_ms,
+                evict_on_unhealthy: health_evict_on_unhealthy,
+                slow_start_millis: health_slow_start_ms,
+                outlier_error_percent: health_outlier_error_percent,
+                outlier_min_connections: health_outlier_min_connections,
+                outlier_window_millis: health_outlier_window_ms,
+                base_ejection_millis: health_base_ejection_ms,
+                max_ejection_percent: health_max_ejection_percent,
+                check: health_check,
+                healthy_threshold: health_healthy_threshold,
+                unhealthy_threshold: health_unhealthy_threshold,
+                jitter_percent: health_jitter_percent,
+                initial_state: health_initial_state,
+                reuse_connections: health_reuse_connections,
+            },
+            preflight: PreflightConfig {
+                verify_backends_on_start,
+                strict: preflight_strict,
+                min_reachable: preflight_min_reachable,
+            },
+            health_endpoint: HealthEndpointConfig {
+                listen_address: health_endpoint_listen_address,
+                min_healthy_backends,
             },
             metrics: MetricsConfig {
                 interval: Duration::from_millis(metrics_interval_ms),
                 timeout: Duration::from_millis(metrics_timeout_ms),
+                max_event_age_millis: metrics_max_event_age_ms,
+                aggregation: metrics_aggregation,
+                selection_trace: metrics_selection_trace,
+            },
+            services: ServicesConfig {
+                health: services_health,
+                metrics: services_metrics,
+                ..services_defaults
             },
             otlp_protocol,
+            state_file: None,
+            health_state_file: None,
             otlp_endpoint,
-        })
+            rng_seed,
+            secrets: Default::default(),
+        };
+        if config.admin.token.is_some() {
+            config.secrets.insert("admin.token".to_string());
+        }
+        config.validate()?;
+        Ok(config)
     }
 
-    /// Load configuration from a file (supports JSON and TOML)
+    /// Load configuration from a file (supports JSON, TOML and YAML)
+    ///
+    /// Files written for older schema versions are upgraded on load (see
+    /// [`crate::config::compat`]). String fields may reference secrets as
+    /// `${env:VAR}` or `${file:/path}`; they are resolved after parsing and
+    /// before validation.
     pub fn from_file(path: Option<impl Into<PathBuf>>) -> Result<Config, ConfigError> {
         if let Some(path) = path {
             let path = path.into();
@@ -191,37 +1155,52 @@ impl ConfigBuilder {
                 return Err(ConfigError::FileNotFound(path));
             }
 
+            let format = ConfigFormat::from_path(&path)?;
             let content = std::fs::read_to_string(&path)?;
-            let extension =
-                path.extension()
-                    .and_then(|ext| ext.to_str())
-                    .ok_or_else(|| {
-                        ConfigError::UnsupportedFormat(path.to_string_lossy().to_string())
-                    })?;
-
-            match extension.to_lowercase().as_str() {
-                "json" => {
-                    let mut config: Config = serde_json::from_str(&content)?;
-                    config.source = ConfigSource::File;
-                    Ok(config)
-                }
-                "toml" => {
-                    let mut config: Config = toml::from_str(&content)?;
-                    config.source = ConfigSource::File;
-                    Ok(config)
-                }
-                "yaml" | "yml" => {
-                    let config: Config = serde_yaml::from_str(&content)?;
-                    Ok(config)
-                }
-                _ => Err(ConfigError::UnsupportedFormat(
-                    path.to_string_lossy().to_string(),
-                )),
-            }
+            let config = Self::parse_content(&content, format)?;
+            config.validate()?;
+            Ok(config)
         } else {
             Self::from_env()
         }
     }
+
+    /// Load configuration from the content of a config file
+    ///
+    /// Behaves like [`ConfigBuilder::from_file`] for a file of the given
+    /// format, e.g. for a config document posted to the admin API.
+    pub fn from_content(
+        content: &str,
+        format: ConfigFormat,
+    ) -> Result<Config, ConfigError> {
+        let config = Self::parse_content(content, format)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parse, upgrade and resolve the secrets of config file content, without
+    /// validating it
+    ///
+    /// Lets callers report validation errors along with what the config would
+    /// change (see [`DryRunReport`]).
+    pub fn parse_content(
+        content: &str,
+        format: ConfigFormat,
+    ) -> Result<Config, ConfigError> {
+        let mut config = config_from_value(format.parse(content)?)?;
+        config.source = ConfigSource::File;
+        config.resolve_secrets()?;
+        if let Some(token) = admin_token_from_env() {
+            config.admin.token = Some(token);
+            config.secrets.insert("admin.token".to_string());
+        }
+        Ok(config)
+    }
+}
+
+/// Admin token from the environment (takes precedence over the config file)
+fn admin_token_from_env() -> Option<String> {
+    std::env::var(LB_ADMIN_TOKEN_ENV_KEY).ok()
 }
 
 mod constants {
@@ -244,17 +1223,86 @@ mod constants {
     pub const LB_CONFIG_WATCH_INTERVAL_MS_ENV_KEY: &str =
         "LEMONADE_LB_CONFIG_WATCH_INTERVAL_MS";
     pub const LB_CONFIG_WATCH_INTERVAL_MS_DEFAULT: u64 = 1000;
+    pub const LB_DRAIN_PROGRESS_INTERVAL_MS_ENV_KEY: &str =
+        "LEMONADE_LB_DRAIN_PROGRESS_INTERVAL_MS";
+    pub const LB_DRAIN_PROGRESS_INTERVAL_MS_DEFAULT: u64 = 1000;
+    pub const LB_DRAIN_POLICY_ENV_KEY: &str = "LEMONADE_LB_DRAIN_POLICY";
+    pub const LB_DRAIN_POLICY_DEFAULT: &str = "finish";
+    pub const LB_PROXY_WORKER_THREADS_ENV_KEY: &str = "LEMONADE_LB_PROXY_WORKER_THREADS";
+    pub const LB_AUDIT_INTERVAL_MS_ENV_KEY: &str = "LEMONADE_LB_AUDIT_INTERVAL_MS";
+    pub const LB_LOG_RATE_LIMIT_WINDOW_MS_ENV_KEY: &str =
+        "LEMONADE_LB_LOG_RATE_LIMIT_WINDOW_MS";
+    pub const LB_LOG_RATE_LIMIT_BURST_ENV_KEY: &str = "LEMONADE_LB_LOG_RATE_LIMIT_BURST";
 
     // Proxy config
     pub const LB_LISTEN_ADDRESS_ENV_KEY: &str = "LEMONADE_LB_LISTEN_ADDRESS";
     pub const LB_MAX_CONNECTIONS_ENV_KEY: &str = "LEMONADE_LB_MAX_CONNECTIONS";
 
+    pub const LB_COALESCE_ENABLED_ENV_KEY: &str = "LEMONADE_LB_COALESCE_ENABLED";
+    pub const LB_COALESCE_MICROS_ENV_KEY: &str = "LEMONADE_LB_COALESCE_MICROS";
+    pub const LB_COALESCE_MAX_BYTES_ENV_KEY: &str = "LEMONADE_LB_COALESCE_MAX_BYTES";
+
+    pub const LB_MAX_BUFFERED_BYTES_ENV_KEY: &str = "LEMONADE_LB_MAX_BUFFERED_BYTES";
+    pub const LB_MAX_BUFFERED_BYTES_DEFAULT: usize = 256 * 1024; // 256 KiB
+
+    pub const LB_BACKEND_BIND_ADDRESS_ENV_KEY: &str = "LEMONADE_LB_BACKEND_BIND_ADDRESS";
+    pub const LB_BACKEND_DSCP_ENV_KEY: &str = "LEMONADE_LB_BACKEND_DSCP";
+
+    pub const LB_FD_HEADROOM_ENV_KEY: &str = "LEMONADE_LB_FD_HEADROOM";
+    pub const LB_FD_HEADROOM_DEFAULT: u64 = 64;
+
+    pub const LB_MAX_BACKEND_PEER_CONNECTIONS_ENV_KEY: &str =
+        "LEMONADE_LB_MAX_BACKEND_PEER_CONNECTIONS";
+    pub const LB_MAX_HOPS_ENV_KEY: &str = "LEMONADE_LB_MAX_HOPS";
+
+    pub const LB_TRANSPARENT_ENV_KEY: &str = "LEMONADE_LB_TRANSPARENT";
+    pub const LB_REPORT_BACKEND_ABORTS_ENV_KEY: &str =
+        "LEMONADE_LB_REPORT_BACKEND_ABORTS";
+    pub const LB_LISTEN_UNIX_ENV_KEY: &str = "LEMONADE_LB_LISTEN_UNIX";
+    pub const LB_STICKY_SESSIONS_ENV_KEY: &str = "LEMONADE_LB_STICKY_SESSIONS";
+    pub const LB_STICKY_TTL_MS_ENV_KEY: &str = "LEMONADE_LB_STICKY_TTL_MS";
+    pub const LB_STICKY_TTL_MS_DEFAULT: u64 = 300_000;
+    pub const LB_MAX_PENDING_CONNECTS_ENV_KEY: &str = "LEMONADE_LB_MAX_PENDING_CONNECTS";
+    pub const LB_MAX_PENDING_CONNECTS_DEFAULT: usize = 32;
+    pub const LB_LOCAL_ZONE_ENV_KEY: &str = "LEMONADE_LB_LOCAL_ZONE";
+    pub const LB_ZONE_SPILLOVER_MIN_HEALTHY_ENV_KEY: &str =
+        "LEMONADE_LB_ZONE_SPILLOVER_MIN_HEALTHY";
+    pub const LB_ZONE_SPILLOVER_MIN_HEALTHY_DEFAULT: usize = 1;
+    pub const LB_PROPAGATE_DEADLINES_ENV_KEY: &str = "LEMONADE_LB_PROPAGATE_DEADLINES";
+    pub const LB_REQUEST_TIMEOUT_MS_ENV_KEY: &str = "LEMONADE_LB_REQUEST_TIMEOUT_MS";
+    pub const LB_REQUEST_TIMEOUT_MS_DEFAULT: u64 = 30_000;
+
+    pub const LB_HEDGING_ENABLED_ENV_KEY: &str = "LEMONADE_LB_HEDGING_ENABLED";
+    pub const LB_HEDGING_DELAY_MS_ENV_KEY: &str = "LEMONADE_LB_HEDGING_DELAY_MS";
+    pub const LB_HEDGING_ADAPTIVE_DELAY_ENV_KEY: &str =
+        "LEMONADE_LB_HEDGING_ADAPTIVE_DELAY";
+    pub const LB_HEDGING_MAX_RATE_ENV_KEY: &str = "LEMONADE_LB_HEDGING_MAX_RATE";
+    pub const LB_CIRCUIT_BREAKER_ENABLED_ENV_KEY: &str =
+        "LEMONADE_LB_CIRCUIT_BREAKER_ENABLED";
+    pub const LB_CIRCUIT_BREAKER_FAILURE_THRESHOLD_ENV_KEY: &str =
+        "LEMONADE_LB_CIRCUIT_BREAKER_FAILURE_THRESHOLD";
+    pub const LB_CIRCUIT_BREAKER_OPEN_DURATION_MS_ENV_KEY: &str =
+        "LEMONADE_LB_CIRCUIT_BREAKER_OPEN_DURATION_MS";
+
+    pub const LB_SLOW_CONNECT_WARN_MS_ENV_KEY: &str = "LEMONADE_LB_SLOW_CONNECT_WARN_MS";
+    pub const LB_SLOW_CONNECTION_WARN_SECS_ENV_KEY: &str =
+        "LEMONADE_LB_SLOW_CONNECTION_WARN_SECS";
+    pub const LB_STRATEGY_PICK_WARN_MICROS_ENV_KEY: &str =
+        "LEMONADE_LB_STRATEGY_PICK_WARN_MICROS";
+    pub const LB_INITIAL_READ_TIMEOUT_MS_ENV_KEY: &str =
+        "LEMONADE_LB_INITIAL_READ_TIMEOUT_MS";
+    pub const LB_SLOW_LOG_MAX_PER_MINUTE_ENV_KEY: &str =
+        "LEMONADE_LB_SLOW_LOG_MAX_PER_MINUTE";
+    pub const LB_SLOW_LOG_MAX_PER_MINUTE_DEFAULT: u32 = 10;
+
     pub const LB_LISTEN_ADDRESS_DEFAULT: &str = "127.0.0.1:3000";
     // max_connections is optional, no default
 
     // Strategy
     pub const LB_STRATEGY_ENV_KEY: &str = "LEMONADE_LB_STRATEGY";
     pub const LB_STRATEGY_DEFAULT: &str = "round_robin";
+    pub const LB_STRATEGY_PARAMS_ENV_KEY: &str = "LEMONADE_LB_STRATEGY_PARAMS";
+    pub const LB_STRATEGY_FALLBACK_ENV_KEY: &str = "LEMONADE_LB_STRATEGY_FALLBACK";
 
     // Health config
     pub const LB_HEALTH_INTERVAL_MS_ENV_KEY: &str = "LEMONADE_LB_HEALTH_INTERVAL_MS";
@@ -262,6 +1310,46 @@ mod constants {
 
     pub const LB_HEALTH_INTERVAL_MS_DEFAULT: u64 = 30000; // 10 seconds
     pub const LB_HEALTH_TIMEOUT_MS_DEFAULT: u64 = 30000; // 30 seconds
+    pub const LB_HEALTH_MAX_EVENT_AGE_MS_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_MAX_EVENT_AGE_MS";
+    pub const LB_HEALTH_MAX_EVENT_AGE_MS_DEFAULT: u64 = 10000; // 10 seconds
+    pub const LB_HEALTH_EVICT_ON_UNHEALTHY_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_EVICT_ON_UNHEALTHY";
+    pub const LB_HEALTH_SLOW_START_MS_ENV_KEY: &str = "LEMONADE_LB_HEALTH_SLOW_START_MS";
+    pub const LB_HEALTH_SLOW_START_MS_DEFAULT: u64 = 0; // disabled
+    pub const LB_HEALTH_OUTLIER_ERROR_PERCENT_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_OUTLIER_ERROR_PERCENT";
+    pub const LB_HEALTH_OUTLIER_ERROR_PERCENT_DEFAULT: u8 = 0; // disabled
+    pub const LB_HEALTH_OUTLIER_MIN_CONNECTIONS_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_OUTLIER_MIN_CONNECTIONS";
+    pub const LB_HEALTH_OUTLIER_MIN_CONNECTIONS_DEFAULT: u32 = 10;
+    pub const LB_HEALTH_OUTLIER_WINDOW_MS_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_OUTLIER_WINDOW_MS";
+    pub const LB_HEALTH_OUTLIER_WINDOW_MS_DEFAULT: u64 = 10000; // 10 seconds
+    pub const LB_HEALTH_BASE_EJECTION_MS_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_BASE_EJECTION_MS";
+    pub const LB_HEALTH_BASE_EJECTION_MS_DEFAULT: u64 = 30000; // 30 seconds
+    pub const LB_HEALTH_MAX_EJECTION_PERCENT_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_MAX_EJECTION_PERCENT";
+    pub const LB_HEALTH_MAX_EJECTION_PERCENT_DEFAULT: u8 = 10;
+    pub const LB_HEALTH_CHECK_PATH_ENV_KEY: &str = "LEMONADE_LB_HEALTH_CHECK_PATH"; // HTTP checks when set
+    pub const LB_HEALTH_CHECK_EXPECTED_STATUS_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_CHECK_EXPECTED_STATUS";
+    pub const LB_HEALTH_CHECK_EXPECTED_STATUS_DEFAULT: u16 = 200;
+    pub const LB_HEALTH_CHECK_HOST_ENV_KEY: &str = "LEMONADE_LB_HEALTH_CHECK_HOST";
+    pub const LB_HEALTH_HEALTHY_THRESHOLD_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_HEALTHY_THRESHOLD";
+    pub const LB_HEALTH_HEALTHY_THRESHOLD_DEFAULT: u32 = 2;
+    pub const LB_HEALTH_UNHEALTHY_THRESHOLD_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_UNHEALTHY_THRESHOLD";
+    pub const LB_HEALTH_UNHEALTHY_THRESHOLD_DEFAULT: u32 = 3;
+    pub const LB_HEALTH_JITTER_PERCENT_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_JITTER_PERCENT";
+    pub const LB_HEALTH_JITTER_PERCENT_DEFAULT: u8 = 10;
+    pub const LB_HEALTH_INITIAL_STATE_ENV_KEY: &str = "LEMONADE_LB_HEALTH_INITIAL_STATE";
+    pub const LB_HEALTH_INITIAL_STATE_DEFAULT: &str = "healthy";
+    pub const LB_HEALTH_REUSE_CONNECTIONS_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_REUSE_CONNECTIONS";
 
     // Metrics config
     pub const LB_METRICS_INTERVAL_MS_ENV_KEY: &str = "LEMONADE_LB_METRICS_INTERVAL_MS";
@@ -269,7 +1357,40 @@ mod constants {
 
     pub const LB_METRICS_INTERVAL_MS_DEFAULT: u64 = 10000; // 10 seconds
     pub const LB_METRICS_TIMEOUT_MS_DEFAULT: u64 = 10000; // 10 seconds
+    pub const LB_METRICS_MAX_EVENT_AGE_MS_ENV_KEY: &str =
+        "LEMONADE_LB_METRICS_MAX_EVENT_AGE_MS";
+    pub const LB_METRICS_MAX_EVENT_AGE_MS_DEFAULT: u64 = 30000; // 30 seconds
+    pub const LB_METRICS_AGGREGATION_ENV_KEY: &str = "LEMONADE_LB_METRICS_AGGREGATION";
+    pub const LB_METRICS_AGGREGATION_DEFAULT: &str = "histogram";
+    pub const LB_METRICS_SELECTION_TRACE_ENV_KEY: &str =
+        "LEMONADE_LB_METRICS_SELECTION_TRACE";
+
+    // Auto-weight
+    pub const LB_AUTO_WEIGHT_ENV_KEY: &str = "LEMONADE_LB_AUTO_WEIGHT";
+
+    // Randomness
+    pub const LB_RNG_SEED_ENV_KEY: &str = "LEMONADE_LB_RNG_SEED";
+
+    // Pre-flight check
+    pub const LB_VERIFY_BACKENDS_ON_START_ENV_KEY: &str =
+        "LEMONADE_LB_VERIFY_BACKENDS_ON_START";
+    pub const LB_PREFLIGHT_STRICT_ENV_KEY: &str = "LEMONADE_LB_PREFLIGHT_STRICT";
+    pub const LB_PREFLIGHT_MIN_REACHABLE_ENV_KEY: &str =
+        "LEMONADE_LB_PREFLIGHT_MIN_REACHABLE";
+
+    pub const LB_HEALTH_ENDPOINT_LISTEN_ADDRESS_ENV_KEY: &str =
+        "LEMONADE_LB_HEALTH_ENDPOINT_LISTEN_ADDRESS";
+    pub const LB_MIN_HEALTHY_BACKENDS_ENV_KEY: &str = "LEMONADE_LB_MIN_HEALTHY_BACKENDS";
+
+    // Admin config
+    pub const LB_ADMIN_ENABLED_ENV_KEY: &str = "LEMONADE_LB_ADMIN_ENABLED";
+    pub const LB_ADMIN_LISTEN_ADDRESS_ENV_KEY: &str = "LEMONADE_LB_ADMIN_LISTEN_ADDRESS";
+    pub const LB_ADMIN_TOKEN_ENV_KEY: &str = "LEMONADE_LB_ADMIN_TOKEN";
+    pub const LB_ADMIN_READ_ONLY_ENV_KEY: &str = "LEMONADE_LB_ADMIN_READ_ONLY";
 
+    // Background services
+    pub const LB_SERVICES_HEALTH_ENV_KEY: &str = "LEMONADE_LB_SERVICES_HEALTH";
+    pub const LB_SERVICES_METRICS_ENV_KEY: &str = "LEMONADE_LB_SERVICES_METRICS";
     pub const LB_OTLP_ENDPOINT_ENV_KEY: &str = "LEMONADE_OTLP_ENDPOINT";
 
     pub const LB_OTLP_PROTOCOL_ENV_KEY: &str = "LEMONADE_OTLP_PROTOCOL";
@@ -0,0 +1,276 @@
+//! Config compatibility module
+//!
+//! Loads config files written for older schema versions. A file is parsed
+//! into a generic value, upgraded one version at a time to
+//! [`CONFIG_VERSION`] and only then deserialized. Unknown fields are
+//! rejected in files declaring the current version, so typos in new files
+//! are caught while files written for older versions keep loading.
+use crate::prelude::*;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Newest config file schema version
+///
+/// Version 2 renamed the millisecond `interval` and `timeout` fields of
+/// `health`, `metrics` and `auto_weight_tuning` to `interval_millis` and
+/// `timeout_millis`.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Upgrade of a parsed config file by one version
+type Upgrade = fn(&mut Map<String, Value>);
+
+/// Upgrade steps, the one at index `n` taking a file from version `n + 1`
+const UPGRADES: [Upgrade; CONFIG_VERSION as usize - 1] = [upgrade_v1_to_v2];
+
+/// Config file format, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfigFormat {
+    /// `.json`
+    #[default]
+    Json,
+    /// `.toml`
+    Toml,
+    /// `.yaml` or `.yml`
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Format of the config file at `path`
+    pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("json") => Ok(Self::Json),
+            Some("toml") => Ok(Self::Toml),
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            _ => Err(ConfigError::UnsupportedFormat(
+                path.to_string_lossy().to_string(),
+            )),
+        }
+    }
+
+    /// Name of the format, as accepted by [`str::parse`]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+        }
+    }
+
+    /// Parse file content into a generic value
+    pub fn parse(&self, content: &str) -> Result<Value, ConfigError> {
+        Ok(match self {
+            Self::Json => serde_json::from_str(content)?,
+            Self::Toml => toml::from_str(content)?,
+            Self::Yaml => serde_yaml::from_str(content)?,
+        })
+    }
+
+    /// Render a generic value as file content
+    pub fn render(&self, value: &Value) -> Result<String, ConfigError> {
+        Ok(match self {
+            Self::Json => serde_json::to_string_pretty(value)? + "\n",
+            Self::Toml => toml::to_string_pretty(value)
+                .map_err(|e| ConfigError::Parse(format!("cannot write TOML: {}", e)))?,
+            Self::Yaml => serde_yaml::to_string(value)?,
+        })
+    }
+}
+
+impl std::str::FromStr for ConfigFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "toml" => Ok(Self::Toml),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => Err(ConfigError::UnsupportedFormat(s.to_string())),
+        }
+    }
+}
+
+/// Outcome of [`migrate_file`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    /// Version the file declared
+    pub from_version: u32,
+    /// Version the file declares now
+    pub to_version: u32,
+}
+
+impl Migration {
+    /// Check whether the file was rewritten
+    pub fn is_upgrade(&self) -> bool {
+        self.from_version != self.to_version
+    }
+}
+
+/// Upgrade a parsed config file to [`CONFIG_VERSION`] in place
+///
+/// Returns the version the file declared. Files without a `version` are
+/// version 1; versions newer than this build supports are rejected.
+pub fn upgrade(value: &mut Value) -> Result<u32, ConfigError> {
+    let Some(config) = value.as_object_mut() else {
+        return Err(ConfigError::Parse("config must be a map".to_string()));
+    };
+    let version = match config.get("version") {
+        None => 1,
+        Some(version) => {
+            let found = version.as_u64().ok_or_else(|| {
+                ConfigError::Parse(format!("invalid config version {}", version))
+            })?;
+            match u32::try_from(found) {
+                Ok(version) if (1..=CONFIG_VERSION).contains(&version) => version,
+                _ => {
+                    return Err(ConfigError::UnsupportedVersion {
+                        found,
+                        supported: CONFIG_VERSION,
+                    });
+                }
+            }
+        }
+    };
+    for upgrade in &UPGRADES[version as usize - 1..] {
+        upgrade(config);
+    }
+    config.insert("version".to_string(), Value::from(CONFIG_VERSION));
+    Ok(version)
+}
+
+/// Deserialize a parsed config file of any supported version
+///
+/// Does not resolve secrets or validate the result.
+pub fn config_from_value(mut value: Value) -> Result<Config, ConfigError> {
+    let version = upgrade(&mut value)?;
+    let config: Config = serde_json::from_value(value.clone())?;
+    if version == CONFIG_VERSION {
+        check_unknown_fields(&value, &config)?;
+    }
+    Ok(config)
+}
+
+/// Rewrite the config file at `path` in the current schema
+///
+/// The upgraded file must deserialize and validate before it replaces the
+/// original (through a temporary file and a rename). Secret references are
+/// kept as written; comments are not preserved. Files already at
+/// [`CONFIG_VERSION`] are left untouched.
+pub fn migrate_file(path: &Path) -> Result<Migration, ConfigError> {
+    if !path.exists() {
+        return Err(ConfigError::FileNotFound(path.to_path_buf()));
+    }
+    let format = ConfigFormat::from_path(path)?;
+    let mut value = format.parse(&std::fs::read_to_string(path)?)?;
+    let from_version = upgrade(&mut value)?;
+    let migration = Migration {
+        from_version,
+        to_version: CONFIG_VERSION,
+    };
+    config_from_value(value.clone())?.validate()?;
+    if !migration.is_upgrade() {
+        return Ok(migration);
+    }
+
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(".migrate");
+    let temp_path = path.with_file_name(temp_name);
+    std::fs::write(&temp_path, format.render(&value)?)?;
+    if let Err(e) = std::fs::rename(&temp_path, path) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e.into());
+    }
+    Ok(migration)
+}
+
+/// Reject fields of `input` that deserializing into `config` ignored
+///
+/// A field is unknown when it does not survive a round trip through
+/// [`Config`]. Empty values (`null`, `{}`, `[]`) are exempt since optional
+/// fields skip serializing them.
+fn check_unknown_fields(input: &Value, config: &Config) -> Result<(), ConfigError> {
+    let known = serde_json::to_value(config)?;
+    let mut unknown = Vec::new();
+    collect_unknown(input, &known, "", &mut unknown);
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigError::UnknownFields(unknown))
+    }
+}
+
+/// Paths of the fields in `input` that are missing from `known`
+fn collect_unknown(input: &Value, known: &Value, path: &str, unknown: &mut Vec<String>) {
+    match (input, known) {
+        (Value::Object(input), Value::Object(known)) => {
+            for (key, value) in input {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match known.get(key) {
+                    Some(known) => collect_unknown(value, known, &field, unknown),
+                    None if is_empty(value) => {}
+                    None => unknown.push(field),
+                }
+            }
+        }
+        (Value::Array(input), Value::Array(known)) => {
+            for (index, (value, known)) in input.iter().zip(known).enumerate() {
+                collect_unknown(value, known, &format!("{}[{}]", path, index), unknown);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Check whether a value is `null` or an empty map or list
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Object(map) => map.is_empty(),
+        Value::Array(list) => list.is_empty(),
+        _ => false,
+    }
+}
+
+/// Version 1 to 2: millisecond durations gain a `_millis` suffix
+fn upgrade_v1_to_v2(config: &mut Map<String, Value>) {
+    let mut sections: Vec<&mut Map<String, Value>> = Vec::new();
+    for (key, section) in config.iter_mut() {
+        match (key.as_str(), section) {
+            ("health" | "metrics" | "auto_weight_tuning", Value::Object(section)) => {
+                sections.push(section);
+            }
+            ("groups", Value::Object(groups)) => {
+                sections.extend(
+                    groups
+                        .values_mut()
+                        .filter_map(|group| group.get_mut("health"))
+                        .filter_map(Value::as_object_mut),
+                );
+            }
+            _ => {}
+        }
+    }
+    for section in sections {
+        for field in ["interval", "timeout"] {
+            rename_field(section, field, &format!("{}_millis", field));
+        }
+    }
+}
+
+/// Move `from` to `to`, unless `to` is already set
+fn rename_field(section: &mut Map<String, Value>, from: &str, to: &str) {
+    if section.contains_key(to) {
+        return;
+    }
+    if let Some(value) = section.remove(from) {
+        section.insert(to.to_string(), value);
+    }
+}
@@ -0,0 +1,309 @@
+//! Config diff module
+//!
+//! Computes the difference between two configurations so migrations can pick
+//! the cheapest path that applies the change (e.g. strategy-only swaps).
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Structured difference between an old and a new configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigDiff {
+    /// New strategy, if the strategy, its parameters or its fallback changed
+    pub strategy: Option<Strategy>,
+    /// New listen address, if the listen address changed
+    pub listen_address: Option<SocketAddr>,
+    /// Backends present only in the new config
+    pub added_backends: Vec<BackendId>,
+    /// Backends present only in the old config
+    pub removed_backends: Vec<BackendId>,
+    /// Backends present in both configs with different parameters
+    /// (other than address-only changes)
+    pub changed_backends: Vec<BackendId>,
+    /// Backends that kept their id and only changed address
+    pub address_changed_backends: Vec<BackendId>,
+    /// Backends that kept their id and only changed weight
+    pub weight_changed_backends: Vec<BackendId>,
+    /// Duplicate address policy changed
+    pub duplicate_addresses_changed: bool,
+    /// Runtime config changed
+    pub runtime_changed: bool,
+    /// Proxy config changed (other than the listen address)
+    pub proxy_changed: bool,
+    /// Health config changed
+    pub health_changed: bool,
+    /// Metrics config changed
+    pub metrics_changed: bool,
+    /// OTLP endpoint or protocol changed
+    pub otlp_changed: bool,
+    /// Auto-weight toggle or tuning changed
+    pub auto_weight_changed: bool,
+    /// Admin API config changed
+    pub admin_changed: bool,
+    /// Audit log config changed
+    pub audit_changed: bool,
+    /// Other top-level sections that changed, by name (`groups`,
+    /// `preflight`, `health_endpoint`, `services`, `state_file`,
+    /// `health_state_file`, `rng_seed`). A reload swaps them into the running
+    /// config; most are only read at startup
+    pub other_changed: Vec<String>,
+}
+
+impl ConfigDiff {
+    /// Compute the diff between two configurations
+    pub fn between(old: &Config, new: &Config) -> Self {
+        let old_backends: HashMap<BackendId, &BackendConfig> =
+            old.backends.iter().map(|b| (b.id, b)).collect();
+        let new_backends: HashMap<BackendId, &BackendConfig> =
+            new.backends.iter().map(|b| (b.id, b)).collect();
+
+        let mut added_backends: Vec<BackendId> = new_backends
+            .keys()
+            .filter(|id| !old_backends.contains_key(id))
+            .copied()
+            .collect();
+        let mut removed_backends: Vec<BackendId> = old_backends
+            .keys()
+            .filter(|id| !new_backends.contains_key(id))
+            .copied()
+            .collect();
+        let mut changed_backends = Vec::new();
+        let mut address_changed_backends = Vec::new();
+        let mut weight_changed_backends = Vec::new();
+        for (id, new_backend) in &new_backends {
+            let Some(old_backend) = old_backends.get(id) else {
+                continue;
+            };
+            if old_backend == new_backend {
+                continue;
+            }
+            if old_backend.address != new_backend.address
+                && old_backend.name == new_backend.name
+                && old_backend.weight == new_backend.weight
+                && old_backend.labels == new_backend.labels
+                && old_backend.bind_address == new_backend.bind_address
+                && old_backend.dscp == new_backend.dscp
+                && old_backend.priority == new_backend.priority
+                && old_backend.zone == new_backend.zone
+            {
+                address_changed_backends.push(*id);
+            } else if old_backend.weight != new_backend.weight
+                && old_backend.address == new_backend.address
+                && old_backend.name == new_backend.name
+                && old_backend.labels == new_backend.labels
+                && old_backend.bind_address == new_backend.bind_address
+                && old_backend.dscp == new_backend.dscp
+                && old_backend.priority == new_backend.priority
+                && old_backend.zone == new_backend.zone
+            {
+                weight_changed_backends.push(*id);
+            } else {
+                changed_backends.push(*id);
+            }
+        }
+        added_backends.sort_unstable();
+        removed_backends.sort_unstable();
+        changed_backends.sort_unstable();
+        address_changed_backends.sort_unstable();
+        weight_changed_backends.sort_unstable();
+
+        // Compare proxy config with the listen address factored out
+        let mut old_proxy = old.proxy.clone();
+        old_proxy.listen_address = new.proxy.listen_address;
+
+        Self {
+            strategy: (old.strategy != new.strategy
+                || old.strategy_params != new.strategy_params
+                || old.fallback != new.fallback)
+                .then(|| new.strategy.clone()),
+            listen_address: (old.proxy.listen_address != new.proxy.listen_address)
+                .then_some(new.proxy.listen_address),
+            added_backends,
+            removed_backends,
+            changed_backends,
+            address_changed_backends,
+            weight_changed_backends,
+            duplicate_addresses_changed: old.allow_duplicate_addresses
+                != new.allow_duplicate_addresses,
+            runtime_changed: old.runtime != new.runtime,
+            proxy_changed: old_proxy != new.proxy,
+            health_changed: old.health != new.health,
+            metrics_changed: old.metrics != new.metrics,
+            otlp_changed: old.otlp_endpoint != new.otlp_endpoint
+                || old.otlp_protocol != new.otlp_protocol,
+            auto_weight_changed: old.auto_weight != new.auto_weight
+                || old.auto_weight_tuning != new.auto_weight_tuning,
+            admin_changed: old.admin != new.admin,
+            audit_changed: old.audit != new.audit,
+            other_changed: [
+                ("groups", old.groups != new.groups),
+                ("preflight", old.preflight != new.preflight),
+                (
+                    "health_endpoint",
+                    old.health_endpoint != new.health_endpoint,
+                ),
+                ("services", old.services != new.services),
+                ("state_file", old.state_file != new.state_file),
+                (
+                    "health_state_file",
+                    old.health_state_file != new.health_state_file,
+                ),
+                ("rng_seed", old.rng_seed != new.rng_seed),
+            ]
+            .into_iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        }
+    }
+
+    /// Check if the backend set changed
+    pub fn backends_changed(&self) -> bool {
+        !self.added_backends.is_empty()
+            || !self.removed_backends.is_empty()
+            || !self.changed_backends.is_empty()
+            || !self.address_changed_backends.is_empty()
+            || !self.weight_changed_backends.is_empty()
+    }
+
+    /// Check if anything other than the strategy changed
+    fn non_strategy_changed(&self) -> bool {
+        self.listen_address.is_some()
+            || self.backends_changed()
+            || self.duplicate_addresses_changed
+            || self.runtime_changed
+            || self.proxy_changed
+            || self.health_changed
+            || self.metrics_changed
+            || self.otlp_changed
+            || self.auto_weight_changed
+            || self.admin_changed
+            || self.audit_changed
+            || !self.other_changed.is_empty()
+    }
+
+    /// Short human-readable description of the changes (used in audit records)
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(strategy) = &self.strategy {
+            parts.push(format!("strategy={}", strategy.as_ref()));
+        }
+        if let Some(listen_address) = self.listen_address {
+            parts.push(format!("listen_address={}", listen_address));
+        }
+        for (label, ids) in [
+            ("added_backends", &self.added_backends),
+            ("removed_backends", &self.removed_backends),
+            ("changed_backends", &self.changed_backends),
+            ("address_changed_backends", &self.address_changed_backends),
+            ("weight_changed_backends", &self.weight_changed_backends),
+        ] {
+            if !ids.is_empty() {
+                parts.push(format!("{}={:?}", label, ids));
+            }
+        }
+        for (label, changed) in [
+            ("duplicate_addresses", self.duplicate_addresses_changed),
+            ("runtime", self.runtime_changed),
+            ("proxy", self.proxy_changed),
+            ("health", self.health_changed),
+            ("metrics", self.metrics_changed),
+            ("otlp", self.otlp_changed),
+            ("auto_weight", self.auto_weight_changed),
+            ("admin", self.admin_changed),
+            ("audit", self.audit_changed),
+        ] {
+            if changed {
+                parts.push(label.to_string());
+            }
+        }
+        parts.extend(self.other_changed.iter().cloned());
+
+        if parts.is_empty() {
+            "no changes".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    /// Check if the configs are identical
+    pub fn is_empty(&self) -> bool {
+        self.strategy.is_none() && !self.non_strategy_changed()
+    }
+
+    /// Check if the strategy is the only thing that changed
+    pub fn is_strategy_only(&self) -> bool {
+        self.strategy.is_some() && !self.non_strategy_changed()
+    }
+
+    /// Check if backend weights are the only thing that changed
+    pub fn is_weight_only(&self) -> bool {
+        !self.weight_changed_backends.is_empty()
+            && Self {
+                weight_changed_backends: Vec::new(),
+                ..self.clone()
+            }
+            .is_empty()
+    }
+}
+
+/// Outcome of checking a candidate config against the live one
+///
+/// Computed without applying anything: the candidate goes through the same
+/// parsing and validation as a reload, and is diffed per backend group.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DryRunReport {
+    /// Whether a reload would accept the candidate
+    pub valid: bool,
+    /// Parse and validation errors
+    pub errors: Vec<String>,
+    /// Changes per backend group (empty when the candidate cannot be parsed)
+    pub groups: BTreeMap<String, ConfigDiff>,
+}
+
+impl DryRunReport {
+    /// Check a parsed `candidate` against the `live` group configs
+    ///
+    /// `live` lists the group name and config of every running group, in
+    /// listener start order. Like a reload, the candidate must be valid and
+    /// keep the same groups.
+    pub fn check(live: &[(String, Arc<Config>)], candidate: &Config) -> Self {
+        let mut errors = Vec::new();
+        if let Err(e) = candidate.validate() {
+            errors.push(e.to_string());
+        }
+        let live_names: Vec<&str> = live.iter().map(|(name, _)| name.as_str()).collect();
+        let names = candidate.group_names();
+        if names != live_names {
+            errors.push(
+                ConfigError::Groups(format!(
+                    "groups changed from {:?} to {:?}; restart to apply",
+                    live_names, names
+                ))
+                .to_string(),
+            );
+        }
+        let groups = candidate
+            .group_configs()
+            .into_iter()
+            .filter_map(|(name, group_config)| {
+                let (_, live_config) = live.iter().find(|(live, _)| *live == name)?;
+                Some((name, ConfigDiff::between(live_config, &group_config)))
+            })
+            .collect();
+        Self {
+            valid: errors.is_empty(),
+            errors,
+            groups,
+        }
+    }
+
+    /// Report for a candidate that could not be parsed
+    pub fn rejected(error: &ConfigError) -> Self {
+        Self {
+            valid: false,
+            errors: vec![error.to_string()],
+            groups: BTreeMap::new(),
+        }
+    }
+}
@@ -29,4 +29,49 @@ pub enum ConfigError {
     /// Parse error
     #[error("Parse error: {0}")]
     Parse(String),
+    /// Invalid strategy settings
+    #[error("Invalid strategy config: {0}")]
+    Strategy(String),
+    /// Invalid runtime settings
+    #[error("Invalid runtime config: {0}")]
+    Runtime(String),
+    /// Invalid proxy settings
+    #[error("Invalid proxy config: {0}")]
+    Proxy(String),
+    /// Invalid health check settings
+    #[error("Invalid health config: {0}")]
+    Health(String),
+    /// Invalid backend groups
+    #[error("Invalid backend groups: {0}")]
+    Groups(String),
+    /// Invalid admin API settings
+    #[error("Invalid admin config: {0}")]
+    Admin(String),
+    /// Invalid state file settings
+    #[error("Invalid state file config: {0}")]
+    StateFile(String),
+    /// Config file schema version this binary cannot load
+    #[error(
+        "Unsupported config version {found} (this build reads versions 1 to {supported})"
+    )]
+    UnsupportedVersion {
+        /// Version declared by the file
+        found: u64,
+        /// Newest version this build supports
+        supported: u32,
+    },
+    /// Fields not part of the current config schema
+    #[error("Unknown config fields: {}", .0.join(", "))]
+    UnknownFields(Vec<String>),
+    /// Secret reference could not be resolved
+    #[error("Cannot resolve secret for {field}: {reason}")]
+    SecretResolution {
+        /// Config field holding the reference
+        field: String,
+        /// Why resolution failed
+        reason: String,
+    },
+    /// Invalid backend list
+    #[error("Invalid backends: {0}")]
+    Backends(#[from] crate::types::RouteTableError),
 }
@@ -13,6 +13,8 @@ use std::path::PathBuf;
 pub struct NotifyConfigService {
     /// Config file path
     config_path: Option<PathBuf>,
+    /// Backend groups migrated on reload (only the watched context otherwise)
+    groups: Option<Arc<Groups>>,
 }
 
 impl NotifyConfigService {
@@ -34,7 +36,16 @@ impl NotifyConfigService {
             return Err(ConfigError::FileNotFound(path.clone()));
         }
 
-        Ok(Self { config_path })
+        Ok(Self {
+            config_path,
+            groups: None,
+        })
+    }
+
+    /// Migrate every backend group, not just the watched context, on reload
+    pub fn with_groups(mut self, groups: Arc<Groups>) -> Self {
+        self.groups = Some(groups);
+        self
     }
 }
 
@@ -118,8 +129,12 @@ impl ConfigService for NotifyConfigService {
                                         new_config.strategy
                                     );
 
-                                    // Call ctx.migrate() to handle all updates atomically
-                                    if let Err(e) = ctx.migrate(new_config).await {
+                                    // Call migrate_as() to handle all updates atomically
+                                    let migrated = match &self.groups {
+                                        Some(groups) => groups.migrate_as(new_config, AuditActor::FileWatch).await,
+                                        None => ctx.migrate_as(new_config, AuditActor::FileWatch).await,
+                                    };
+                                    if let Err(e) = migrated {
                                         tracing::error!("Failed to migrate config: {}", e);
                                     } else {
                                         tracing::debug!("Config migrated successfully");
@@ -130,6 +145,11 @@ impl ConfigService for NotifyConfigService {
                                         "Failed to reload config file, keeping previous configuration: {}",
                                         e
                                     );
+                                    ctx.audit().record(
+                                        AuditRecord::new(&AuditActor::FileWatch, AuditAction::ConfigReload)
+                                            .with_target(config_path.display().to_string())
+                                            .rejected(&e),
+                                    );
                                 }
                             }
                         }
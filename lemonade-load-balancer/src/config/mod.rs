@@ -2,8 +2,11 @@
 //!
 
 pub mod builder;
+pub mod compat;
+pub mod diff;
 pub mod error;
 pub mod impls;
 pub mod models;
 pub mod port;
+pub mod secrets;
 pub mod serde_helpers;
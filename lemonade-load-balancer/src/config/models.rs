@@ -2,6 +2,13 @@
 //!
 use crate::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Name of the group formed by the top-level `backends` list
+pub const DEFAULT_GROUP: &str = "default";
+
+/// Largest DSCP value (six bits)
+const MAX_DSCP: u8 = 63;
 
 /// Config source enum
 ///
@@ -17,8 +24,13 @@ pub enum ConfigSource {
 }
 
 /// Config struct
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Debug output redacts fields resolved from secret references.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Config {
+    /// Config file schema version (files without one are version 1)
+    #[serde(default = "default_version")]
+    pub version: u32,
     /// Source of configuration (set automatically, not part of serialized config)
     #[serde(skip)]
     pub source: ConfigSource,
@@ -28,18 +40,461 @@ pub struct Config {
     pub proxy: ProxyConfig,
     /// Strategy
     pub strategy: Strategy,
+    /// Parameters for a custom strategy's factory
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub strategy_params: serde_json::Value,
+    /// Strategy picking when the primary one fails (see [`FallbackStrategy`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Strategy>,
     /// Backend List
     pub backends: Vec<BackendConfig>,
+    /// Named backend groups, each served by its own listener
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, GroupConfig>,
+    /// Allow several backends to share an address (e.g. to stack weights)
+    #[serde(default)]
+    pub allow_duplicate_addresses: bool,
+    /// Adjust effective backend weights from observed latency
+    #[serde(default)]
+    pub auto_weight: bool,
+    /// Auto-weight controller tuning
+    #[serde(default)]
+    pub auto_weight_tuning: AutoWeightConfig,
+    /// Admin API config
+    #[serde(default)]
+    pub admin: AdminConfig,
+    /// Audit log config
+    #[serde(default)]
+    pub audit: AuditConfig,
     /// Health config
     pub health: HealthConfig,
+    /// Startup backend reachability check
+    #[serde(default)]
+    pub preflight: PreflightConfig,
+    /// Built-in `/healthz` and `/readyz` listener
+    #[serde(default)]
+    pub health_endpoint: HealthEndpointConfig,
     /// Metrics config
     pub metrics: MetricsConfig,
+    /// Background services to run (all of them by default)
+    #[serde(default)]
+    pub services: ServicesConfig,
+    /// Periodic state snapshot file (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_file: Option<StateFileConfig>,
+    /// Backend health persisted across restarts (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_state_file: Option<HealthStateFileConfig>,
     /// OTLP exporter endpoint (optional)
     #[serde(default)]
     pub otlp_endpoint: Option<String>,
     /// OTLP exporter protocol (optional)
     #[serde(default)]
     pub otlp_protocol: Option<String>,
+    /// Seed for randomized components (random from entropy when unset)
+    ///
+    /// Read at startup; the seed in use is logged so a run can be reproduced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rng_seed: Option<u64>,
+    /// Fields resolved from `${env:..}` / `${file:..}` references (set by the
+    /// builder, not part of the serialized config)
+    #[serde(skip)]
+    pub secrets: std::collections::BTreeSet<String>,
+}
+
+/// Default for [`Config::version`]
+fn default_version() -> u32 {
+    1
+}
+
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let config = self.redacted();
+        f.debug_struct("Config")
+            .field("version", &config.version)
+            .field("source", &config.source)
+            .field("runtime", &config.runtime)
+            .field("proxy", &config.proxy)
+            .field("strategy", &config.strategy)
+            .field("strategy_params", &config.strategy_params)
+            .field("fallback", &config.fallback)
+            .field("backends", &config.backends)
+            .field("groups", &config.groups)
+            .field(
+                "allow_duplicate_addresses",
+                &config.allow_duplicate_addresses,
+            )
+            .field("auto_weight", &config.auto_weight)
+            .field("auto_weight_tuning", &config.auto_weight_tuning)
+            .field("admin", &config.admin)
+            .field("audit", &config.audit)
+            .field("health", &config.health)
+            .field("preflight", &config.preflight)
+            .field("health_endpoint", &config.health_endpoint)
+            .field("metrics", &config.metrics)
+            .field("services", &config.services)
+            .field("state_file", &config.state_file)
+            .field("health_state_file", &config.health_state_file)
+            .field("otlp_endpoint", &config.otlp_endpoint)
+            .field("otlp_protocol", &config.otlp_protocol)
+            .field("secrets", &config.secrets)
+            .finish()
+    }
+}
+
+impl Config {
+    /// Validate the configuration
+    ///
+    /// Backends must have unique ids and, unless `allow_duplicate_addresses`
+    /// is set, unique addresses. The admin API may only listen on a
+    /// non-loopback address when a token is configured, the hedge rate must
+    /// be a fraction, custom strategies must have a registered factory and
+    /// built-in ones valid `strategy_params`, and a fallback strategy must
+    /// differ from the primary one and be buildable. A dedicated proxy runtime
+    /// needs at least one worker thread, an enabled consistency audit a
+    /// non-zero interval and backends room for at least one connect in
+    /// flight.
+    /// These rules apply to every backend group; groups must also have valid
+    /// names and distinct listen addresses, and HTTP routing rules must
+    /// select backends in each of them.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.runtime.proxy_worker_threads == Some(0) {
+            return Err(ConfigError::Runtime(
+                "proxy_worker_threads must be at least 1".to_string(),
+            ));
+        }
+        if self.runtime.audit_interval_millis == Some(0) {
+            return Err(ConfigError::Runtime(
+                "audit_interval_millis must be at least 1".to_string(),
+            ));
+        }
+        if self.runtime.log_rate_limit.is_enabled()
+            && self.runtime.log_rate_limit.burst == 0
+        {
+            return Err(ConfigError::Runtime(
+                "log_rate_limit.burst must be at least 1".to_string(),
+            ));
+        }
+        self.admin.validate()?;
+        self.health.validate()?;
+        self.proxy.hedging.validate()?;
+        self.proxy.retry_budget.validate()?;
+        self.proxy.circuit_breaker.validate()?;
+        self.proxy.route_by_sni.validate()?;
+        self.proxy
+            .route_by_alpn
+            .validate(&self.proxy.route_by_sni)?;
+        if let Some(state_file) = &self.state_file {
+            state_file.validate()?;
+        }
+        if let Some(health_state_file) = &self.health_state_file {
+            health_state_file.validate()?;
+        }
+        if self.has_default_group() && self.groups.contains_key(DEFAULT_GROUP) {
+            return Err(ConfigError::Groups(format!(
+                "group {} conflicts with the top-level backends",
+                DEFAULT_GROUP
+            )));
+        }
+        let own_addresses = self.listen_addresses();
+        let mut listen_addresses = HashSet::new();
+        for (name, group) in self.group_configs() {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+            {
+                return Err(ConfigError::Groups(format!(
+                    "invalid group name {:?}",
+                    name
+                )));
+            }
+            if !listen_addresses.insert(group.proxy.listen_address) {
+                return Err(ConfigError::Groups(format!(
+                    "group {} shares listen address {} with another group",
+                    name, group.proxy.listen_address
+                )));
+            }
+            group.validate_backends(&own_addresses)?;
+            group.proxy.route_by_http.validate(&group.backends)?;
+        }
+        Ok(())
+    }
+
+    /// Addresses the load balancer listens on: every group's proxy listener,
+    /// plus the admin API and health endpoint when enabled
+    pub fn listen_addresses(&self) -> Vec<SocketAddr> {
+        let mut addresses: Vec<SocketAddr> = self
+            .group_configs()
+            .into_iter()
+            .map(|(_, group)| group.proxy.listen_address)
+            .collect();
+        if self.admin.enabled {
+            addresses.push(self.admin.listen_address);
+        }
+        addresses.extend(self.health_endpoint.listen_address);
+        addresses
+    }
+
+    /// Check the strategy and backend list of a single group
+    ///
+    /// Backends pointing at one of `own_addresses` would proxy back into the
+    /// load balancer and are rejected.
+    fn validate_backends(&self, own_addresses: &[SocketAddr]) -> Result<(), ConfigError> {
+        match &self.strategy {
+            Strategy::Custom(name) if !crate::strategy::is_registered(name) => {
+                return Err(ConfigError::Strategy(format!(
+                    "no factory registered for custom strategy {}",
+                    name
+                )));
+            }
+            Strategy::Custom(_) => {}
+            // Built-in strategies check their params when built
+            strategy => {
+                StrategyBuilder::new()
+                    .with_strategy(strategy.clone())
+                    .with_params(self.strategy_params.clone())
+                    .build()
+                    .map_err(|e| ConfigError::Strategy(e.to_string()))?;
+            }
+        }
+        match &self.fallback {
+            Some(fallback) if *fallback == self.strategy => {
+                return Err(ConfigError::Strategy(format!(
+                    "fallback strategy {} is the primary strategy",
+                    fallback.as_ref()
+                )));
+            }
+            Some(fallback) => {
+                StrategyBuilder::new()
+                    .with_strategy(fallback.clone())
+                    .build()
+                    .map_err(|e| {
+                        ConfigError::Strategy(format!("fallback strategy: {}", e))
+                    })?;
+            }
+            None => {}
+        }
+        RouteTable::try_new(self.backends.clone(), self.allow_duplicate_addresses)?;
+        if let Some(dscp) = self.proxy.backend_dscp
+            && dscp > MAX_DSCP
+        {
+            return Err(ConfigError::Proxy(format!(
+                "backend_dscp {} is out of range (0-{})",
+                dscp, MAX_DSCP
+            )));
+        }
+        if self.proxy.max_pending_connects == 0 {
+            return Err(ConfigError::Proxy(
+                "max_pending_connects must be at least 1".to_string(),
+            ));
+        }
+        if self.proxy.zone_spillover_min_healthy == 0 {
+            return Err(ConfigError::Proxy(
+                "zone_spillover_min_healthy must be at least 1".to_string(),
+            ));
+        }
+        if self.proxy.max_hops == Some(0) {
+            return Err(ConfigError::Proxy(
+                "max_hops must be at least 1".to_string(),
+            ));
+        }
+        if self.proxy.request_timeout_millis == 0 {
+            return Err(ConfigError::Proxy(
+                "request_timeout_millis must be at least 1".to_string(),
+            ));
+        }
+        if self.proxy.local_zone.as_deref() == Some("") {
+            return Err(ConfigError::Proxy(
+                "local_zone must not be empty".to_string(),
+            ));
+        }
+        for backend in &self.backends {
+            if backend.zone.as_deref() == Some("") {
+                return Err(ConfigError::Proxy(format!(
+                    "backend {} zone must not be empty",
+                    backend.id
+                )));
+            }
+            if let Some(dscp) = backend.dscp
+                && dscp > MAX_DSCP
+            {
+                return Err(ConfigError::Proxy(format!(
+                    "backend {} dscp {} is out of range (0-{})",
+                    backend.id, dscp, MAX_DSCP
+                )));
+            }
+            // Hostnames are resolved at connect time, so only IP literals can
+            // be checked here
+            if let Ok(address) = backend.address.as_str().parse::<SocketAddr>()
+                && let Some(own) = own_addresses
+                    .iter()
+                    .find(|own| is_own_address(address, **own))
+            {
+                return Err(ConfigError::Proxy(format!(
+                    "backend {} at {} points at listen address {} (proxy loop)",
+                    backend.id, address, own
+                )));
+            }
+            let Some(source) = backend.bind_address.or(self.proxy.backend_bind_address)
+            else {
+                continue;
+            };
+            // Hostnames are resolved at connect time, so only IP literals can
+            // be checked here
+            if let Ok(address) = backend.address.as_str().parse::<SocketAddr>()
+                && address.is_ipv4() != source.is_ipv4()
+            {
+                return Err(ConfigError::Proxy(format!(
+                    "backend {} at {} cannot be reached from bind address {}",
+                    backend.id, address, source
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the top-level `backends` list forms the [`DEFAULT_GROUP`]
+    ///
+    /// It does when no `groups` are defined (so flat configs keep working)
+    /// or when it is not empty.
+    fn has_default_group(&self) -> bool {
+        self.groups.is_empty() || !self.backends.is_empty()
+    }
+
+    /// Names of the backend groups, in listener start order
+    pub fn group_names(&self) -> Vec<String> {
+        let default = self.has_default_group().then(|| DEFAULT_GROUP.to_string());
+        default
+            .into_iter()
+            .chain(self.groups.keys().cloned())
+            .collect()
+    }
+
+    /// Flat config serving a single backend group
+    ///
+    /// The group's listen address, strategy, backends and health settings
+    /// replace the top-level ones (a group never listens on `listen_unix`);
+    /// everything else is shared. Returns `None` for an unknown group.
+    pub fn group_config(&self, name: &str) -> Option<Config> {
+        if name == DEFAULT_GROUP && self.has_default_group() {
+            let mut config = self.clone();
+            config.groups.clear();
+            config.secrets.retain(|field| !field.starts_with("groups."));
+            return Some(config);
+        }
+        let group = self.groups.get(name)?;
+        let prefix = format!("groups.{}.", name);
+        let mut config = self.clone();
+        config.groups.clear();
+        config.proxy.listen_address = group.listen_address;
+        config.proxy.listen_unix = None;
+        config.strategy = group.strategy.clone();
+        config.strategy_params = group.strategy_params.clone();
+        config.fallback = group.fallback.clone();
+        config.backends = group.backends.clone();
+        if let Some(health) = &group.health {
+            config.health = health.clone();
+        }
+        config.secrets = self
+            .secrets
+            .iter()
+            .filter_map(|field| field.strip_prefix(&prefix).map(str::to_string))
+            .collect();
+        Some(config)
+    }
+
+    /// Flat configs of every backend group, in listener start order
+    pub fn group_configs(&self) -> Vec<(String, Config)> {
+        self.group_names()
+            .into_iter()
+            .filter_map(|name| self.group_config(&name).map(|config| (name, config)))
+            .collect()
+    }
+}
+
+/// Whether connecting to `backend` reaches the listener bound to `listen`
+///
+/// A wildcard listener is reached through any loopback address on its port;
+/// other local interface addresses are not known here.
+fn is_own_address(backend: SocketAddr, listen: SocketAddr) -> bool {
+    let local = |ip: IpAddr| ip.is_loopback() || ip.is_unspecified();
+    backend.port() == listen.port()
+        && (backend.ip() == listen.ip()
+            || (listen.ip().is_unspecified() && local(backend.ip()))
+            || (backend.ip().is_unspecified() && local(listen.ip())))
+}
+
+/// Background services config
+///
+/// Disabling a service runs a no-op in its place, e.g. to benchmark the bare
+/// proxy path: without health checks every backend stays healthy and the
+/// proxy stops reporting connection failures, without metrics aggregation
+/// the proxy stops sending metrics events and strategies that rank backends
+/// by latency fall back to least connections. Read at startup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServicesConfig {
+    /// Run health checks and process proxy failure events
+    pub health: bool,
+    /// Aggregate metrics events into backend metrics
+    pub metrics: bool,
+    /// Watch the config file for changes
+    pub config_watch: bool,
+}
+
+impl Default for ServicesConfig {
+    fn default() -> Self {
+        Self {
+            health: true,
+            metrics: true,
+            config_watch: true,
+        }
+    }
+}
+
+/// Services disabled on the command line
+///
+/// Applied on top of the loaded config's [`ServicesConfig`]: a service runs
+/// only if neither disables it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ServiceOverrides {
+    /// Disable health checking (`--no-health-checks`)
+    pub no_health_checks: bool,
+    /// Disable metrics aggregation (`--no-metrics`)
+    pub no_metrics: bool,
+}
+
+impl ServiceOverrides {
+    /// Disable the overridden services in `services`
+    pub fn apply(&self, services: &mut ServicesConfig) {
+        services.health &= !self.no_health_checks;
+        services.metrics &= !self.no_metrics;
+    }
+}
+
+/// Named backend group config
+///
+/// A group is served by its own listener and has its own strategy, backends
+/// and (optionally) health check settings. Backend ids only need to be unique
+/// within a group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupConfig {
+    /// Listen address serving this group
+    pub listen_address: SocketAddr,
+    /// Strategy
+    pub strategy: Strategy,
+    /// Parameters for a custom strategy's factory
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub strategy_params: serde_json::Value,
+    /// Strategy picking when the primary one fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Strategy>,
+    /// Backend list
+    pub backends: Vec<BackendConfig>,
+    /// Health config (the top-level one when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<HealthConfig>,
 }
 
 /// Events emitted when configuration changes occur
@@ -50,7 +505,7 @@ pub struct Config {
 /// # Examples
 ///
 /// ```no_run
-/// use lemonade_load_balancer::config::models::ConfigEvent;
+/// use lemonade_load_balancer::prelude::{ConfigEvent, Strategy};
 /// use std::net::SocketAddr;
 ///
 /// // When config migrates successfully
@@ -59,6 +514,9 @@ pub struct Config {
 /// // When listen address changes
 /// let new_addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
 /// let event = ConfigEvent::ListenAddressChanged(new_addr);
+///
+/// // When only the strategy changes
+/// let event = ConfigEvent::StrategyChanged(Strategy::LeastConnections);
 /// ```
 #[derive(Debug, Clone)]
 pub enum ConfigEvent {
@@ -76,10 +534,29 @@ pub enum ConfigEvent {
     /// 2. Bind to the new address
     /// 3. Allow active connections to drain gracefully
     ListenAddressChanged(SocketAddr),
+
+    /// Load balancing strategy changed
+    ///
+    /// Emitted when the strategy is the only change in a new configuration.
+    /// The strategy is swapped in place: the routing table, backend state and
+    /// in-flight connections are left untouched.
+    StrategyChanged(Strategy),
+
+    /// Backend kept its id but moved to a new address
+    ///
+    /// The backend's address is updated in place: health and metrics state is
+    /// preserved, new connections go to the new address and the health service
+    /// should re-probe the backend immediately.
+    BackendAddressChanged {
+        /// Backend ID
+        backend_id: BackendId,
+        /// New backend address
+        address: BackendAddress,
+    },
 }
 
 /// Runtime config struct
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RuntimeConfig {
     /// Metrics capacity
     pub metrics_cap: usize,
@@ -93,4 +570,57 @@ pub struct RuntimeConfig {
     pub accept_timeout_millis: u64,
     /// Config file watch interval in milliseconds
     pub config_watch_interval_millis: u64,
+    /// Interval between drain progress reports during shutdown, in milliseconds
+    #[serde(default = "default_drain_progress_interval_millis")]
+    pub drain_progress_interval_millis: u64,
+    /// What happens to connections open to backends drained by a migration
+    #[serde(default)]
+    pub drain_policy: DrainPolicy,
+    /// Worker threads of a dedicated runtime for the accept loop and proxied
+    /// connections; unset shares the caller's runtime with the background
+    /// services. Takes effect on restart.
+    #[serde(default)]
+    pub proxy_worker_threads: Option<usize>,
+    /// Interval between consistency audits of the route table and the
+    /// strategy state, in milliseconds; unset disables the audit
+    #[serde(default)]
+    pub audit_interval_millis: Option<u64>,
+    /// Cap on repetitive warnings (no backend available, accept errors,
+    /// proxy failures)
+    #[serde(default)]
+    pub log_rate_limit: LogRateLimitConfig,
+}
+
+/// Default drain progress report interval
+fn default_drain_progress_interval_millis() -> u64 {
+    1000
+}
+
+/// Log rate limit config struct
+///
+/// Each rate-limited callsite logs at most `burst` messages per window and
+/// reports how many it suppressed when the next window opens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogRateLimitConfig {
+    /// Length of a window in milliseconds (0 disables rate limiting)
+    pub window_millis: u64,
+    /// Messages logged per callsite per window
+    pub burst: u32,
+}
+
+impl LogRateLimitConfig {
+    /// Whether messages are rate limited at all
+    pub fn is_enabled(&self) -> bool {
+        self.window_millis > 0
+    }
+}
+
+impl Default for LogRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            window_millis: 10_000,
+            burst: 10,
+        }
+    }
 }
@@ -0,0 +1,140 @@
+//! Config secrets module
+//!
+//! Resolves `${env:VAR}` and `${file:/path}` references in string config
+//! fields so credentials don't have to live inline in a committed config.
+//! `$${` escapes a literal `${`. Resolved fields are remembered on the config
+//! and redacted from Debug output and [`Config::redacted`].
+use crate::prelude::*;
+
+/// Placeholder shown instead of a resolved secret
+pub const REDACTED: &str = "<redacted>";
+
+impl Config {
+    /// Resolve secret references in every string field
+    ///
+    /// Fields that contained a reference are recorded in `secrets`.
+    pub fn resolve_secrets(&mut self) -> Result<(), ConfigError> {
+        let mut resolved = Vec::new();
+        for (field, value) in self.string_fields_mut() {
+            if let Some(secret) = resolve_references(&field, value)? {
+                *value = secret;
+                resolved.push(field);
+            }
+        }
+        self.secrets.extend(resolved);
+        Ok(())
+    }
+
+    /// Copy of the config with every resolved secret replaced by a placeholder
+    pub fn redacted(&self) -> Config {
+        let mut config = self.clone();
+        let secrets = std::mem::take(&mut config.secrets);
+        for (field, value) in config.string_fields_mut() {
+            if secrets.contains(&field) {
+                *value = REDACTED.to_string();
+            }
+        }
+        config.secrets = secrets;
+        config
+    }
+
+    /// String fields that may hold secret references, keyed by field path
+    fn string_fields_mut(&mut self) -> Vec<(String, &mut String)> {
+        let mut fields = Vec::new();
+        for (index, backend) in self.backends.iter_mut().enumerate() {
+            if let Some(name) = backend.name.as_mut() {
+                fields.push((format!("backends[{}].name", index), name));
+            }
+        }
+        for (group_name, group) in self.groups.iter_mut() {
+            for (index, backend) in group.backends.iter_mut().enumerate() {
+                if let Some(name) = backend.name.as_mut() {
+                    fields.push((
+                        format!("groups.{}.backends[{}].name", group_name, index),
+                        name,
+                    ));
+                }
+            }
+        }
+        if let Some(token) = self.admin.token.as_mut() {
+            fields.push(("admin.token".to_string(), token));
+        }
+        if let Some(endpoint) = self.otlp_endpoint.as_mut() {
+            fields.push(("otlp_endpoint".to_string(), endpoint));
+        }
+        if let Some(protocol) = self.otlp_protocol.as_mut() {
+            fields.push(("otlp_protocol".to_string(), protocol));
+        }
+        fields
+    }
+}
+
+/// Resolve the references in a single value
+///
+/// Returns `None` when the value contains no reference (escapes alone are
+/// unescaped in place).
+fn resolve_references(
+    field: &str,
+    value: &mut String,
+) -> Result<Option<String>, ConfigError> {
+    if !value.contains("${") {
+        return Ok(None);
+    }
+
+    let error = |reason: String| ConfigError::SecretResolution {
+        field: field.to_string(),
+        reason,
+    };
+
+    let mut output = String::with_capacity(value.len());
+    let mut has_reference = false;
+    let mut rest = value.as_str();
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // `$${` is an escaped literal `${`
+        if let Some(after) = rest.strip_prefix("$${") {
+            output.push_str("${");
+            rest = after;
+            continue;
+        }
+        let Some(after) = rest.strip_prefix("${") else {
+            output.push('$');
+            rest = &rest[1..];
+            continue;
+        };
+
+        let end = after
+            .find('}')
+            .ok_or_else(|| error("unterminated reference".to_string()))?;
+        let reference = &after[..end];
+        rest = &after[end + 1..];
+        has_reference = true;
+
+        match reference.split_once(':') {
+            Some(("env", name)) => {
+                let secret = std::env::var(name).map_err(|_| {
+                    error(format!("environment variable {} is not set", name))
+                })?;
+                output.push_str(&secret);
+            }
+            Some(("file", path)) => {
+                let secret = std::fs::read_to_string(path)
+                    .map_err(|e| error(format!("cannot read {}: {}", path, e)))?;
+                output.push_str(secret.trim_end_matches(['\r', '\n']));
+            }
+            _ => {
+                return Err(error(format!("unsupported reference ${{{}}}", reference)));
+            }
+        }
+    }
+    output.push_str(rest);
+
+    if has_reference {
+        Ok(Some(output))
+    } else {
+        *value = output;
+        Ok(None)
+    }
+}
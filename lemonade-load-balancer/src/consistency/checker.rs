@@ -0,0 +1,176 @@
+//! Consistency checker module
+//!
+//! Low-frequency audit (enabled by `runtime.audit_interval_millis`) that
+//! cross-checks the route table against the state other structures keep per
+//! backend. Violations are logged and counted, never panicked on.
+use crate::prelude::*;
+
+/// Smallest surplus of backend connections over accepted ones tolerated
+const CONNECTION_TOLERANCE_MIN: usize = 8;
+
+/// Surplus tolerated per accepted connection, as a divisor (one in ten)
+const CONNECTION_TOLERANCE_DIVISOR: usize = 10;
+
+/// Consistency audit of a context
+///
+/// Each round checks that:
+/// - every backend the strategy keeps state for, clients stick to or with a
+///   probe history is in the route table
+/// - the routed backends do not count more open connections than the
+///   listeners accepted, beyond a tolerance for hedged connects and counters
+///   read mid-update
+/// - no backend is draining and accepting new connections at once
+/// - the route table's healthy backends match a fresh computation
+pub struct ConsistencyChecker;
+
+impl ConsistencyChecker {
+    /// Check every invariant once, returning the violations found
+    pub fn check(ctx: &Context) -> Vec<InvariantViolation> {
+        let routing = ctx.routing_table();
+        let mut violations = Vec::new();
+        Self::check_tracked(
+            "strategy",
+            ctx.strategy().tracked_backends(),
+            &routing,
+            &mut violations,
+        );
+        Self::check_tracked(
+            "affinity",
+            ctx.affinity().tracked_backends(),
+            &routing,
+            &mut violations,
+        );
+        Self::check_tracked(
+            "health_history",
+            ctx.health_history_backends(),
+            &routing,
+            &mut violations,
+        );
+        Self::check_connections(ctx, &routing, &mut violations);
+        Self::check_draining(&routing, &mut violations);
+        Self::check_healthy(&routing, &mut violations);
+        violations
+    }
+
+    /// Check every invariant once and record the violations on the context
+    ///
+    /// Returns the number of violations found.
+    pub fn audit(ctx: &Context) -> usize {
+        let violations = Self::check(ctx);
+        for violation in &violations {
+            ctx.record_invariant_violation(violation);
+        }
+        violations.len()
+    }
+
+    /// Audit the context every `runtime.audit_interval_millis` until shutdown
+    ///
+    /// Returns immediately when the audit is disabled. The interval is read
+    /// at startup.
+    pub async fn run(ctx: Arc<Context>) {
+        let Some(interval_ms) = ctx.config().runtime.audit_interval_millis else {
+            return;
+        };
+        let mut shutdown_rx = ctx.channels().shutdown_rx();
+        let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+        // The first tick completes immediately; start auditing one interval in
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("Consistency audit received shutdown signal");
+                    break;
+                }
+
+                _ = interval.tick() => {
+                    Self::audit(&ctx);
+                }
+            }
+        }
+    }
+
+    /// Surplus of backend connections over `accepted` ones tolerated
+    pub fn connection_tolerance(accepted: usize) -> usize {
+        CONNECTION_TOLERANCE_MIN.max(accepted / CONNECTION_TOLERANCE_DIVISOR)
+    }
+
+    /// Report ids `structure` tracks that are not in the route table
+    fn check_tracked(
+        structure: &'static str,
+        tracked: Vec<BackendId>,
+        routing: &RouteTable,
+        violations: &mut Vec<InvariantViolation>,
+    ) {
+        violations.extend(tracked.into_iter().filter(|id| !routing.contains(*id)).map(
+            |backend_id| InvariantViolation::UnknownBackend {
+                structure,
+                backend_id,
+            },
+        ));
+    }
+
+    /// Compare the routed backends' connections with the accepted ones
+    fn check_connections(
+        ctx: &Context,
+        routing: &RouteTable,
+        violations: &mut Vec<InvariantViolation>,
+    ) {
+        let backends: usize = routing
+            .all_backends()
+            .iter()
+            .map(|backend| backend.active_connections())
+            .sum();
+        let accepted = ctx.listener_generations().total();
+        let tolerance = Self::connection_tolerance(accepted);
+        if backends > accepted + tolerance {
+            violations.push(InvariantViolation::ConnectionCountMismatch {
+                backends,
+                accepted,
+                tolerance,
+            });
+        }
+    }
+
+    /// Report draining backends that still accept new connections
+    fn check_draining(routing: &RouteTable, violations: &mut Vec<InvariantViolation>) {
+        violations.extend(
+            routing
+                .all_backends()
+                .iter()
+                .filter(|backend| {
+                    backend.is_draining() && backend.can_accept_new_connections()
+                })
+                .map(|backend| InvariantViolation::DrainingAccepting {
+                    backend_id: backend.id(),
+                }),
+        );
+    }
+
+    /// Compare the route table's healthy backends with a fresh computation
+    ///
+    /// A mismatch is confirmed by a second comparison, so a health change
+    /// landing between the two reads is not reported.
+    fn check_healthy(routing: &RouteTable, violations: &mut Vec<InvariantViolation>) {
+        let compare = || {
+            let snapshot: Vec<BackendId> = routing
+                .healthy_backends()
+                .iter()
+                .map(|backend| backend.id())
+                .collect();
+            let fresh: Vec<BackendId> = routing
+                .all_backends()
+                .iter()
+                .filter(|backend| backend.can_accept_new_connections())
+                .map(|backend| backend.id())
+                .collect();
+            (snapshot != fresh).then_some((snapshot, fresh))
+        };
+        if compare().is_some()
+            && let Some((snapshot, fresh)) = compare()
+        {
+            violations
+                .push(InvariantViolation::HealthySnapshotMismatch { snapshot, fresh });
+        }
+    }
+}
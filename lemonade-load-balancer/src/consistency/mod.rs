@@ -0,0 +1,7 @@
+//! Consistency module
+//!
+//! Periodic audit of invariants spanning the route table, the strategy and
+//! the connection counters.
+
+pub mod checker;
+pub mod models;
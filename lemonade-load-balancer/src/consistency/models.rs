@@ -0,0 +1,55 @@
+//! Consistency models module
+//!
+use crate::prelude::*;
+
+/// Cross-structure invariant found broken by the consistency audit
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvariantViolation {
+    /// A structure holds state for a backend missing from the route table
+    #[error("{structure} holds state for backend {backend_id}, which is not routed")]
+    UnknownBackend {
+        /// Structure holding the entry
+        structure: &'static str,
+        /// Backend the entry belongs to
+        backend_id: BackendId,
+    },
+    /// Backends count more open connections than the listeners accepted
+    #[error(
+        "backends count {backends} open connections but only {accepted} were \
+         accepted (tolerance {tolerance})"
+    )]
+    ConnectionCountMismatch {
+        /// Sum of the routed backends' active connections
+        backends: usize,
+        /// Connections open across listener generations
+        accepted: usize,
+        /// Surplus allowed for hedged connects and counts read mid-update
+        tolerance: usize,
+    },
+    /// A draining backend still accepts new connections
+    #[error("backend {backend_id} is draining but still accepts new connections")]
+    DrainingAccepting {
+        /// Backend ID
+        backend_id: BackendId,
+    },
+    /// The route table's healthy backends differ from a fresh computation
+    #[error("healthy backends {snapshot:?} differ from a fresh computation {fresh:?}")]
+    HealthySnapshotMismatch {
+        /// Ids from [`RouteTable::healthy_backends`]
+        snapshot: Vec<BackendId>,
+        /// Ids of the backends that can accept new connections
+        fresh: Vec<BackendId>,
+    },
+}
+
+impl InvariantViolation {
+    /// Name of the violated invariant, used as the metric label
+    pub fn invariant(&self) -> &'static str {
+        match self {
+            Self::UnknownBackend { .. } => "unknown_backend",
+            Self::ConnectionCountMismatch { .. } => "connection_count",
+            Self::DrainingAccepting { .. } => "draining_accepting",
+            Self::HealthySnapshotMismatch { .. } => "healthy_snapshot",
+        }
+    }
+}
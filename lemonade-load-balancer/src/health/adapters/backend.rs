@@ -1,23 +1,46 @@
 //! Backend implementation of HealthService
 //!
-//! Performs periodic health checks on backends using TCP connections
-//! and listens for immediate failure alerts from proxy
+//! Performs periodic health checks on backends using TCP connections (or
+//! HTTP requests, see [`HealthCheck`]) and listens for immediate failure
+//! alerts from proxy. Backends sharing an address (e.g. blue/green overlaps)
+//! are probed once per sweep and the result is applied to each of them, with
+//! probes of different addresses spread over the interval (see
+//! [`ProbeSchedule`]). Backends the proxy reports a burst of failures for are
+//! ejected until their ejection ends, whatever their probes say (see
+//! [`OutlierDetector`]). HTTP checks keep their connection open for the next
+//! probe of the same address (see [`ProbeConnectionPool`]).
 
 use crate::health::error::HealthError;
 use crate::health::models::{
-    BackendFailureEvent, HealthEvent, HealthFailureReason, HealthStatus,
+    BackendFailureEvent, BackendFailureIgnored, HealthEvent, HealthFailureReason,
+    HealthStatus, ProbeFailure, TransitionCause,
 };
+use crate::health::outlier::{OutlierDetector, OutlierVerdict};
 use crate::health::port::HealthService;
 use crate::prelude::*;
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::Instrument;
+
+/// Longest HTTP status line an HTTP check reads, in bytes
+const MAX_STATUS_LINE_BYTES: usize = 1024;
+
+/// Longest HTTP response head a pooled HTTP check reads, in bytes
+const MAX_RESPONSE_HEAD_BYTES: usize = 8 * 1024;
+
+/// Longest HTTP response body a pooled HTTP check reads to keep its
+/// connection, in bytes; a connection with a longer body is closed
+const MAX_POOLED_BODY_BYTES: usize = 64 * 1024;
 
 /// Backend health service implementation
 pub struct BackendHealthService {
     /// Health configuration (reference to global config's health slice)
     config: Arc<ArcSwap<HealthConfig>>,
+    /// Keep-alive connections of HTTP checks
+    pool: Arc<ProbeConnectionPool>,
 }
 
 impl BackendHealthService {
@@ -29,21 +52,497 @@ impl BackendHealthService {
     /// # Returns
     /// * `Ok(Self)` if service was created successfully
     pub fn new(config: Arc<ArcSwap<HealthConfig>>) -> Result<Self, HealthError> {
-        Ok(Self { config })
+        Ok(Self {
+            config,
+            pool: Arc::default(),
+        })
+    }
+
+    /// Number of HTTP check connections kept open for the next probe
+    pub fn pooled_connections(&self) -> usize {
+        self.pool.idle_connections()
+    }
+
+    /// Probe `address` as `config` says, from `source` when set
+    ///
+    /// HTTP checks reuse the connection of the last probe of the address
+    /// when `reuse_connections` is set (see [`Self::pooled_probe`]).
+    pub async fn check_endpoint(
+        &self,
+        address: &Arc<BackendAddress>,
+        source: Option<IpAddr>,
+        config: &HealthConfig,
+    ) -> Result<ProbeTimings, ProbeFailure> {
+        Self::pooled_probe(&self.pool, address, source, config).await
+    }
+
+    /// Open (and drop) a single TCP connection to `address` within `timeout`,
+    /// from `source` when set
+    ///
+    /// Returns the connect time, or why the backend could not be reached.
+    pub async fn connect_probe(
+        address: &BackendAddress,
+        source: Option<IpAddr>,
+        timeout: Duration,
+    ) -> Result<Duration, ProbeFailure> {
+        // Hostnames are resolved lazily
+        let check_start = std::time::Instant::now();
+        match tokio::time::timeout(timeout, address.connect_from(source)).await {
+            Ok(Ok(_)) => Ok(check_start.elapsed()),
+            Ok(Err(e)) => Err(Self::connect_failure(e)),
+            Err(_) => Err(ProbeFailure::new(
+                HealthFailureReason::Timeout,
+                format!("no connection within {:?}", timeout),
+            )),
+        }
+    }
+
+    /// Send an HTTP GET for `path` to `address` and check that the response
+    /// status is `expected_status`, all within `timeout`
+    ///
+    /// Only the status line is read. `host_header` defaults to the backend
+    /// address. Returns the time to the status line, or why the check
+    /// failed: a different status or a response that is not HTTP is an
+    /// invalid response.
+    pub async fn http_probe(
+        address: &BackendAddress,
+        source: Option<IpAddr>,
+        timeout: Duration,
+        path: &str,
+        expected_status: u16,
+        host_header: Option<&str>,
+    ) -> Result<Duration, ProbeFailure> {
+        let check_start = std::time::Instant::now();
+        let exchange = async {
+            let mut stream = address
+                .connect_from(source)
+                .await
+                .map_err(Self::connect_failure)?;
+            let request = http_request(address, path, host_header, false);
+            stream.write_all(request.as_bytes()).await.map_err(|e| {
+                ProbeFailure::new(HealthFailureReason::Transport, e.to_string())
+            })?;
+            let status = read_status(&mut stream).await?;
+            if status != expected_status {
+                return Err(ProbeFailure::new(
+                    HealthFailureReason::InvalidResponse,
+                    format!("status {}, expected {}", status, expected_status),
+                ));
+            }
+            Ok(())
+        };
+        match tokio::time::timeout(timeout, exchange).await {
+            Ok(Ok(())) => Ok(check_start.elapsed()),
+            Ok(Err(failure)) => Err(failure),
+            Err(_) => Err(ProbeFailure::new(
+                HealthFailureReason::Timeout,
+                format!("no HTTP status within {:?}", timeout),
+            )),
+        }
+    }
+
+    /// Probe `address` as `config` says, over the pooled connection to it
+    /// when the check is HTTP and `reuse_connections` is set
+    ///
+    /// A pooled probe times the request and response only; when no pooled
+    /// connection is left, or the backend closed it, the connect time of a
+    /// new one is returned apart as the handshake. The connection goes back
+    /// to `pool` unless the backend asked to close it or the response body
+    /// cannot be read to its end.
+    async fn pooled_probe(
+        pool: &ProbeConnectionPool,
+        address: &Arc<BackendAddress>,
+        source: Option<IpAddr>,
+        config: &HealthConfig,
+    ) -> Result<ProbeTimings, ProbeFailure> {
+        let timeout = config.timeout;
+        let HealthCheck::Http {
+            path,
+            expected_status,
+            host_header,
+        } = &config.check
+        else {
+            return Self::connect_probe(address, source, timeout)
+                .await
+                .map(ProbeTimings::new);
+        };
+        if !config.reuse_connections {
+            return Self::http_probe(
+                address,
+                source,
+                timeout,
+                path,
+                *expected_status,
+                host_header.as_deref(),
+            )
+            .await
+            .map(ProbeTimings::new);
+        }
+
+        let endpoint = (address.clone(), source);
+        let request = http_request(address, path, host_header.as_deref(), true);
+        let exchange = async {
+            if let Some(mut stream) = pool.checkout(&endpoint) {
+                let sent_at = std::time::Instant::now();
+                // A failure here is most likely the backend having closed
+                // the idle connection, so it is retried on a new one
+                if let Ok(response) = keep_alive_exchange(&mut stream, &request).await {
+                    let rtt = sent_at.elapsed();
+                    if response.reusable {
+                        pool.checkin(endpoint, stream);
+                    }
+                    return Ok((response.status, ProbeTimings::new(rtt)));
+                }
+            }
+            let connect_start = std::time::Instant::now();
+            let mut stream = address
+                .connect_from(source)
+                .await
+                .map_err(Self::connect_failure)?;
+            let handshake = connect_start.elapsed();
+            let sent_at = std::time::Instant::now();
+            let response = keep_alive_exchange(&mut stream, &request).await?;
+            let rtt = sent_at.elapsed();
+            if response.reusable {
+                pool.checkin(endpoint, stream);
+            }
+            Ok((
+                response.status,
+                ProbeTimings {
+                    rtt,
+                    handshake: Some(handshake),
+                },
+            ))
+        };
+        let (status, timings) = match tokio::time::timeout(timeout, exchange).await {
+            Ok(result) => result?,
+            Err(_) => {
+                return Err(ProbeFailure::new(
+                    HealthFailureReason::Timeout,
+                    format!("no HTTP response within {:?}", timeout),
+                ));
+            }
+        };
+        if status != *expected_status {
+            return Err(ProbeFailure::new(
+                HealthFailureReason::InvalidResponse,
+                format!("status {}, expected {}", status, expected_status),
+            ));
+        }
+        Ok(timings)
+    }
+
+    /// Probe `address` the way `check` says
+    pub async fn probe(
+        address: &BackendAddress,
+        source: Option<IpAddr>,
+        timeout: Duration,
+        check: &HealthCheck,
+    ) -> Result<Duration, ProbeFailure> {
+        match check {
+            HealthCheck::Tcp => Self::connect_probe(address, source, timeout).await,
+            HealthCheck::Http {
+                path,
+                expected_status,
+                host_header,
+            } => {
+                Self::http_probe(
+                    address,
+                    source,
+                    timeout,
+                    path,
+                    *expected_status,
+                    host_header.as_deref(),
+                )
+                .await
+            }
+        }
     }
 
-    /// Get current time in milliseconds
-    fn now_ms() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64
+    /// Why a probe could not connect
+    fn connect_failure(error: BackendConnectError) -> ProbeFailure {
+        match error {
+            e @ BackendConnectError::Bind { .. } => {
+                ProbeFailure::new(HealthFailureReason::SourceBind, e.to_string())
+            }
+            e => ProbeFailure::new(HealthFailureReason::ConnectionRefused, e.to_string()),
+        }
+    }
+
+    /// Check that the backends are reachable before the proxy starts
+    ///
+    /// Connects to every backend once, concurrently, logs a per-backend and
+    /// an overall summary and returns the number of reachable backends. In
+    /// strict mode fewer than `min_reachable` reachable backends is an error.
+    /// Backends without a `bind_address` connect from `default_source`.
+    pub async fn verify_backends(
+        routing: &RouteTable,
+        timeout: Duration,
+        preflight: &PreflightConfig,
+        default_source: Option<IpAddr>,
+    ) -> Result<usize, HealthError> {
+        let mut probes = tokio::task::JoinSet::new();
+        for backend in routing.all_backends() {
+            probes.spawn(async move {
+                let source = backend.source_address(default_source);
+                let result =
+                    Self::connect_probe(&backend.address(), source, timeout).await;
+                (backend, result)
+            });
+        }
+
+        let total = routing.len();
+        let mut reachable = 0;
+        while let Some(Ok((backend, result))) = probes.join_next().await {
+            match result {
+                Ok(rtt) => {
+                    reachable += 1;
+                    tracing::info!(
+                        "Pre-flight: backend {} ({}) reachable in {:?}",
+                        backend.id(),
+                        backend.address(),
+                        rtt
+                    );
+                }
+                Err(failure) => tracing::warn!(
+                    "Pre-flight: backend {} ({}) unreachable: {}",
+                    backend.id(),
+                    backend.address(),
+                    failure
+                ),
+            }
+        }
+        tracing::info!("Pre-flight: {} of {} backends reachable", reachable, total);
+
+        if preflight.strict && reachable < preflight.min_reachable {
+            return Err(HealthError::Preflight {
+                reachable,
+                total,
+                required: preflight.min_reachable,
+            });
+        }
+        Ok(reachable)
+    }
+
+    /// Apply the result of a probe: update the backend's health state and
+    /// emit health events
+    ///
+    /// A backend turns unhealthy after `unhealthy_threshold` failed probes in
+    /// a row and healthy after `healthy_threshold` passing ones. A passing
+    /// probe does not bring an ejected backend back before its ejection ends.
+    async fn apply_probe(
+        backend: &Arc<Backend>,
+        result: Result<ProbeTimings, ProbeFailure>,
+        config: &HealthConfig,
+        streaks: &mut HealthStreaks,
+        outliers: &OutlierDetector,
+        health_tx: &MpscSender<HealthEvent>,
+        ctx: &Context,
+    ) {
+        let backend_id = backend.id();
+        let was_alive = backend.is_alive();
+        let clock = ctx.clock();
+        let handshake = result.as_ref().ok().and_then(|timings| timings.handshake);
+        let result = result.map(|timings| timings.rtt);
+        ctx.record_probe(
+            backend_id,
+            HealthProbeRecord::new(clock.now_millis(), &result).with_handshake(handshake),
+        );
+
+        match result {
+            Ok(rtt) => {
+                let rtt_micros = rtt.as_micros() as u64;
+                tracing::debug!(
+                    "Backend {} is healthy (RTT: {}μs)",
+                    backend_id,
+                    rtt_micros
+                );
+                let _ = health_tx
+                    .send(HealthEvent::BackendHealthy {
+                        backend_id,
+                        rtt_micros,
+                    })
+                    .await;
+                // Only down because `initial_state` started it so: its first
+                // passing probe brings it in, like the initial check. A
+                // backend restored down from the health state file still
+                // needs `healthy_threshold` passes
+                let first = backend.started_unhealthy();
+                let passed = streaks.record(backend, true);
+                let now_ms = clock.now_millis();
+                if outliers.is_ejected(backend_id, now_ms) {
+                    tracing::debug!(
+                        "Backend {} passed its probe but stays ejected",
+                        backend_id
+                    );
+                    return;
+                }
+                if !was_alive && !first && passed < config.healthy_threshold {
+                    tracing::debug!(
+                        "Backend {} passed {} of {} probes needed to recover",
+                        backend_id,
+                        passed,
+                        config.healthy_threshold
+                    );
+                    return;
+                }
+                backend.set_health(true, now_ms);
+                if !was_alive {
+                    backend.start_slow_start(now_ms, config.slow_start_millis);
+                    Self::report_transition(
+                        backend,
+                        TransitionCause::recovery(rtt_micros),
+                        health_tx,
+                    )
+                    .await;
+                }
+            }
+            Err(failure) => {
+                if ctx.should_log("health.check_failed") {
+                    let history = ctx.health_history(backend_id);
+                    tracing::warn!(
+                        "Backend {} health check failed: {} ({} of its last {} probes failed)",
+                        backend_id,
+                        failure,
+                        history.iter().filter(|record| !record.passed()).count(),
+                        history.len()
+                    );
+                }
+                let _ = health_tx
+                    .send(HealthEvent::BackendUnhealthy {
+                        backend_id,
+                        reason: failure.reason,
+                    })
+                    .await;
+                let failed = streaks.record(backend, false);
+                if was_alive && failed < config.unhealthy_threshold {
+                    tracing::debug!(
+                        "Backend {} failed {} of {} probes that mark it down",
+                        backend_id,
+                        failed,
+                        config.unhealthy_threshold
+                    );
+                    return;
+                }
+                backend.set_health(false, clock.now_millis());
+                if was_alive {
+                    Self::report_transition(
+                        backend,
+                        TransitionCause::failure(failure.reason, failed, failure.detail),
+                        health_tx,
+                    )
+                    .await;
+                    Self::evict_if_enabled(backend, config);
+                }
+            }
+        }
+    }
+
+    /// Log a health transition of `backend` and send it on the health channel
+    ///
+    /// Going down is logged at warn level with the reason; the log event is
+    /// exported as an OTLP span event once observability is initialized.
+    async fn report_transition(
+        backend: &Backend,
+        cause: TransitionCause,
+        health_tx: &MpscSender<HealthEvent>,
+    ) {
+        let backend_id = backend.id();
+        let (from, to) = match cause.reason {
+            Some(_) => (HealthStatus::Healthy, HealthStatus::Unhealthy),
+            None => (HealthStatus::Unhealthy, HealthStatus::Healthy),
+        };
+        match cause.reason {
+            Some(reason) => tracing::warn!(
+                backend.id = backend_id,
+                backend.name = backend.name().unwrap_or_default(),
+                backend.addr = %backend.address(),
+                health.reason = reason.as_str(),
+                health.consecutive_failures = cause.consecutive_failures,
+                health.error = cause.detail.as_deref().unwrap_or_default(),
+                "Backend {} health transition: healthy -> unhealthy ({})",
+                backend_id,
+                reason
+            ),
+            None => tracing::info!(
+                backend.id = backend_id,
+                backend.name = backend.name().unwrap_or_default(),
+                backend.addr = %backend.address(),
+                health.rtt_micros = cause.rtt_micros,
+                "Backend {} health transition: unhealthy -> healthy",
+                backend_id
+            ),
+        }
+        let _ = health_tx
+            .send(HealthEvent::HealthTransition {
+                backend_id,
+                from,
+                to,
+                cause,
+            })
+            .await;
+    }
+
+    /// Count a proxy failure of `backend` towards its outlier window, ejecting
+    /// it on a high error rate
+    fn eject_if_outlier(
+        backend: &Arc<Backend>,
+        outliers: &mut OutlierDetector,
+        config: &HealthConfig,
+        ctx: &Context,
+    ) {
+        let routing = ctx.routing_table();
+        let now_ms = ctx.clock().now_millis();
+        match outliers.record_failure(backend, now_ms, config, &routing) {
+            OutlierVerdict::Tolerated => {}
+            OutlierVerdict::Ejected(interval) => tracing::warn!(
+                backend.id = backend.id(),
+                backend.addr = %backend.address(),
+                health.ejection_millis = interval.as_millis() as u64,
+                "Backend {} ejected for {:?} after failing {}% or more of its connections within the window",
+                backend.id(),
+                interval,
+                config.outlier_error_percent
+            ),
+            OutlierVerdict::Skipped(reason) => tracing::warn!(
+                "Backend {} reached the outlier error rate but was not ejected: {:?}",
+                backend.id(),
+                reason
+            ),
+        }
+    }
+
+    /// Close the pooled probe connection of a backend marked down, so its
+    /// next probe starts from a new connection
+    fn close_pooled(backend: &Backend, pool: &ProbeConnectionPool) {
+        if pool.close(&backend.address()) > 0 {
+            tracing::debug!(
+                "Closed the pooled probe connection of backend {}",
+                backend.id()
+            );
+        }
+    }
+
+    /// Cut the connections open to a backend that just turned unhealthy, when
+    /// `evict_on_unhealthy` is set
+    fn evict_if_enabled(backend: &Backend, config: &HealthConfig) {
+        if !config.evict_on_unhealthy {
+            return;
+        }
+        let evicted = backend.evict_connections();
+        if evicted > 0 {
+            tracing::warn!(
+                "Backend {} turned unhealthy, evicting {} connections",
+                backend.id(),
+                evicted
+            );
+        }
     }
 }
 
 #[async_trait]
 impl HealthService for BackendHealthService {
-    #[tracing::instrument(skip(self, ctx), fields(service.name = "lemonade-load-balancer", service.type = "health"))]
+    #[tracing::instrument(skip(self, ctx), fields(service.name = "lemonade-load-balancer", service.type = "health", lb.group = %ctx.group()))]
     async fn check_health(&self, ctx: Arc<Context>) {
         tracing::info!("Starting health service");
 
@@ -53,68 +552,107 @@ impl HealthService for BackendHealthService {
             .backend_failure_rx()
             .expect("Backend failure receiver already taken");
         let mut shutdown_rx = ctx.channels().shutdown_rx();
+        let mut config_rx = ctx.channels().config_tx().subscribe();
         let health_tx = ctx.channels().health_tx();
 
         // Get initial config
         let initial_config = self.config.load();
-        // Use interval_at to fire immediately, then at regular intervals
-        let mut interval = tokio::time::interval_at(
-            tokio::time::Instant::now(),
-            initial_config.interval,
-        );
+        // Periodic checks run on the context clock
+        let clock = ctx.clock();
 
         let backend_count = ctx.routing_table().len();
         tracing::info!("Health service will monitor {} backends", backend_count);
-        
+
         // Perform immediate health check on startup
         tracing::info!("Performing initial health check on all backends");
-        let routing = ctx.routing_table();
+        let mut targets = ProbeTargets::default();
+        // Probe results in a row, per backend
+        let mut streaks = HealthStreaks::default();
         let health_tx_clone = health_tx.clone();
-        let timeout = initial_config.timeout;
-        for backend in routing.all_backends() {
-            let backend_id = backend.id();
-            let address = backend.address().clone();
-            
-            let check_start = std::time::Instant::now();
-            let is_healthy = match tokio::time::timeout(
-                timeout,
-                tokio::net::TcpStream::connect(address.as_str()),
-            )
-            .await
-            {
-                Ok(Ok(_)) => {
-                    let rtt_micros = check_start.elapsed().as_micros() as u64;
-                    tracing::info!("Backend {} initial health check: healthy (RTT: {}μs)", backend_id, rtt_micros);
-                    let _ = health_tx_clone.send(HealthEvent::BackendHealthy {
-                        backend_id,
-                        rtt_micros,
-                    }).await;
-                    true
-                }
-                Ok(Err(_)) => {
-                    tracing::warn!("Backend {} initial health check: connection refused", backend_id);
-                    let _ = health_tx_clone.send(HealthEvent::BackendUnhealthy {
-                        backend_id,
-                        reason: HealthFailureReason::ConnectionRefused,
-                    }).await;
-                    false
-                }
-                Err(_) => {
-                    tracing::warn!("Backend {} initial health check: timeout", backend_id);
-                    let _ = health_tx_clone.send(HealthEvent::BackendUnhealthy {
-                        backend_id,
-                        reason: HealthFailureReason::Timeout,
-                    }).await;
-                    false
-                }
+        let default_source = ctx.config().proxy.backend_bind_address;
+        for group in targets.groups(&ctx) {
+            let Some(first) = group.first() else {
+                continue;
             };
-            
-            let now_ms = Self::now_ms();
-            backend.set_health(is_healthy, now_ms);
+            let source = first.source_address(default_source);
+            let result = self
+                .check_endpoint(&first.address(), source, &initial_config)
+                .await;
+            let handshake = result.as_ref().ok().and_then(|timings| timings.handshake);
+            let result = result.map(|timings| timings.rtt);
+
+            let record = HealthProbeRecord::new(clock.now_millis(), &result)
+                .with_handshake(handshake);
+            for backend in group {
+                let backend_id = backend.id();
+                ctx.record_probe(backend_id, record.clone());
+                let is_healthy = match &result {
+                    Ok(rtt) => {
+                        let rtt_micros = rtt.as_micros() as u64;
+                        tracing::info!(
+                            "Backend {} initial health check: healthy (RTT: {}μs)",
+                            backend_id,
+                            rtt_micros
+                        );
+                        let _ = health_tx_clone
+                            .send(HealthEvent::BackendHealthy {
+                                backend_id,
+                                rtt_micros,
+                            })
+                            .await;
+                        true
+                    }
+                    Err(failure) => {
+                        tracing::warn!(
+                            "Backend {} initial health check: {}",
+                            backend_id,
+                            failure
+                        );
+                        let _ = health_tx_clone
+                            .send(HealthEvent::BackendUnhealthy {
+                                backend_id,
+                                reason: failure.reason,
+                            })
+                            .await;
+                        false
+                    }
+                };
+
+                // The initial check is decisive, and starts the streaks
+                streaks.record(backend, is_healthy);
+                backend.set_health(is_healthy, clock.now_millis());
+                if !is_healthy {
+                    Self::close_pooled(backend, &self.pool);
+                }
+            }
         }
         tracing::info!("Initial health check completed");
+        ctx.readiness().mark_health_checked();
+
+        // Probes in flight, applied as they complete
+        let mut probes = ProbeSet::new(self.pool.clone());
+        // Backends ejected on bursts of proxy failures
+        let mut outliers = OutlierDetector::default();
+        // Periodic probes, staggered from the initial check on
+        let mut schedule = ProbeSchedule::default();
+        schedule.sync(
+            &targets.leaders(&ctx),
+            clock.now_millis(),
+            initial_config.interval,
+        );
 
         loop {
+            if targets.is_stale(&ctx) {
+                schedule.sync(
+                    &targets.leaders(&ctx),
+                    clock.now_millis(),
+                    self.config.load().interval,
+                );
+            }
+            let next_check = schedule.next_due();
+            let readmission_wait = outliers.next_readmission().map(|at_ms| {
+                Duration::from_millis(at_ms.saturating_sub(clock.now_millis()))
+            });
             tokio::select! {
                 _ = shutdown_rx.recv() => {
                     tracing::info!("Health service received shutdown signal");
@@ -123,149 +661,624 @@ impl HealthService for BackendHealthService {
 
                 // IMMEDIATE: Proxy detected backend failure
                 Some(failure) = backend_failure_rx.recv() => {
+                    let backend_id = failure.backend_id();
+                    ctx.channels().record_backend_failure_received();
+
+                    // A failure that waited too long in the queue may predate
+                    // a successful probe, so it must not mark the backend down
+                    if clock.is_expired(failure.at_micros(), self.config.load().max_event_age_millis) {
+                        ctx.channels().record_health_event_expired();
+                        tracing::debug!(
+                            "Discarded expired failure event for backend {}: {:?}",
+                            backend_id,
+                            failure
+                        );
+                        continue;
+                    }
+
                     let routing = ctx.routing_table();
-                    let backend_id = match &failure {
-                        BackendFailureEvent::ConnectionRefused { backend_id } => *backend_id,
-                        BackendFailureEvent::Timeout { backend_id } => *backend_id,
-                        BackendFailureEvent::BackendClosed { backend_id } => *backend_id,
-                        BackendFailureEvent::ConsecutiveErrors { backend_id, .. } => *backend_id,
+
+                    let Some(backend) = routing.get(backend_id) else {
+                        ctx.channels()
+                            .record_backend_failure_ignored(BackendFailureIgnored::UnknownBackend);
+                        tracing::debug!(
+                            "Ignored failure event for unknown backend {}: {:?}",
+                            backend_id,
+                            failure
+                        );
+                        continue;
                     };
 
-                    if let Some(backend) = routing.get(backend_id) {
-                        let was_alive = backend.is_alive();
-                        let now_ms = Self::now_ms();
+                    let was_alive = backend.is_alive();
+                    let now_ms = clock.now_millis();
 
+                    if ctx.should_log("health.proxy_failure") {
                         tracing::warn!(
                             "Backend {} marked unhealthy due to proxy failure: {:?}",
                             backend_id,
                             failure
                         );
+                    }
 
-                        backend.set_health(false, now_ms);
+                    // Marked down at once; recovering takes a full streak of
+                    // passing probes
+                    backend.set_health(false, now_ms);
+                    streaks.record(&backend, false);
 
-                        // Send health event for observability
-                        let _ = health_tx.send(HealthEvent::BackendUnhealthy {
-                            backend_id,
-                            reason: match &failure {
-                                BackendFailureEvent::ConnectionRefused { .. } => HealthFailureReason::ConnectionRefused,
-                                BackendFailureEvent::Timeout { .. } => HealthFailureReason::Timeout,
-                                _ => HealthFailureReason::Transport,
-                            },
-                        }).await;
-
-                        // Send transition event if state changed
-                        if was_alive {
-                            tracing::info!("Backend {} health transition: healthy -> unhealthy", backend_id);
-                            let _ = health_tx.send(HealthEvent::HealthTransition {
-                                backend_id,
-                                from: HealthStatus::Healthy,
-                                to: HealthStatus::Unhealthy,
-                            }).await;
-                        }
+                    // Send health event for observability
+                    let reason = match &failure {
+                        BackendFailureEvent::ConnectionRefused { .. } => HealthFailureReason::ConnectionRefused,
+                        BackendFailureEvent::Timeout { .. } => HealthFailureReason::Timeout,
+                        BackendFailureEvent::SourceBindFailed { .. } => HealthFailureReason::SourceBind,
+                        _ => HealthFailureReason::Transport,
+                    };
+                    let _ = health_tx.send(HealthEvent::BackendUnhealthy {
+                        backend_id,
+                        reason,
+                    }).await;
+
+                    // Send transition event if state changed
+                    if was_alive {
+                        ctx.channels().record_backend_failure_acted_upon();
+                        let cause = TransitionCause::failure(
+                            reason,
+                            backend.consecutive_failures(),
+                            format!("reported by proxy: {:?}", failure),
+                        );
+                        Self::report_transition(&backend, cause, &health_tx).await;
+                        Self::evict_if_enabled(&backend, &self.config.load());
+                        Self::close_pooled(&backend, &self.pool);
+                    } else {
+                        ctx.channels().record_backend_failure_ignored(
+                            BackendFailureIgnored::AlreadyUnhealthy,
+                        );
                     }
+                    Self::eject_if_outlier(&backend, &mut outliers, &self.config.load(), &ctx);
                 }
 
-                // PERIODIC: Proactive health checks
-                _ = interval.tick() => {
-                    let routing = ctx.routing_table();
+                // An ejection ended: re-probe the backend, which comes back
+                // once its probe passes
+                _ = clock.sleep(readmission_wait.unwrap_or_default()), if readmission_wait.is_some() => {
+                    let default_source = ctx.config().proxy.backend_bind_address;
                     let config = self.config.load();
-                    let health_tx = health_tx.clone();
-
-                    tracing::debug!("Starting health check cycle for {} backends", routing.len());
-
-                    for backend in routing.all_backends() {
-                        let backend_id = backend.id();
-                        let address = backend.address();
+                    for backend in outliers.take_readmitted(clock.now_millis()) {
+                        tracing::info!(
+                            "Backend {} ejection ended, re-probing",
+                            backend.id()
+                        );
+                        probes.cancel(backend.id());
+                        probes.launch(vec![backend], default_source, &config);
+                    }
+                }
 
-                        // Skip if backend has high load (respect backend capacity)
-                        // Use a reasonable threshold (e.g., 100 connections) to avoid overloading
-                        if !backend.has_capacity_for_health_check(100) {
+                // A probe finished; apply it to each backend probed unless
+                // that backend moved or left
+                Some(outcome) = probes.join_next() => {
+                    let routing = ctx.routing_table();
+                    let config = self.config.load();
+                    for backend in &outcome.backends {
+                        if !outcome.is_current(backend, &routing) {
                             tracing::debug!(
-                                "Skipping health check for busy backend {} ({} active connections)",
-                                backend_id,
-                                backend.active_connections()
+                                "Discarded stale probe of backend {} at {}",
+                                backend.id(),
+                                outcome.address
                             );
                             continue;
                         }
-
-                        let check_span = tracing::debug_span!(
-                            "health.check",
-                            service.name = "lemonade-load-balancer",
-                            backend.id = %backend_id,
-                            backend.addr = %address
-                        );
-                        let _check_guard = check_span.enter();
-
-                        // Perform TCP health check (ToSocketAddrs will resolve hostname lazily)
-                        let check_start = std::time::Instant::now();
-                        let is_healthy = match tokio::time::timeout(
-                            config.timeout,
-                            tokio::net::TcpStream::connect(address.as_str()),
+                        let was_alive = backend.is_alive();
+                        Self::apply_probe(
+                            backend,
+                            outcome.result.clone(),
+                            &config,
+                            &mut streaks,
+                            &outliers,
+                            &health_tx,
+                            &ctx,
                         )
-                        .await
-                        {
-                            Ok(Ok(_)) => {
-                                let rtt_micros = check_start.elapsed().as_micros() as u64;
-                                tracing::debug!("Backend {} is healthy (RTT: {}μs)", backend_id, rtt_micros);
-                                let _ = health_tx.send(HealthEvent::BackendHealthy {
-                                    backend_id,
-                                    rtt_micros,
-                                }).await;
-                                true
-                            }
-                            Ok(Err(_)) => {
-                                tracing::warn!("Backend {} health check failed: connection refused", backend_id);
-                                let _ = health_tx.send(HealthEvent::BackendUnhealthy {
-                                    backend_id,
-                                    reason: HealthFailureReason::ConnectionRefused,
-                                }).await;
-                                false
-                            }
-                            Err(_) => {
-                                tracing::warn!("Backend {} health check failed: timeout", backend_id);
-                                let _ = health_tx.send(HealthEvent::BackendUnhealthy {
+                        .await;
+                        if was_alive && !backend.is_alive() {
+                            Self::close_pooled(backend, &self.pool);
+                        }
+                    }
+                }
+
+                // IMMEDIATE: Backend moved to a new address, re-probe it
+                Ok(event) = config_rx.recv() => {
+                    match event {
+                        ConfigEvent::BackendAddressChanged { backend_id, address } => {
+                            if let Some(backend) = ctx.routing_table().get(backend_id) {
+                                tracing::info!(
+                                    "Backend {} moved to {}, re-probing",
                                     backend_id,
-                                    reason: HealthFailureReason::Timeout,
-                                }).await;
-                                false
+                                    address
+                                );
+                                let _ = health_tx
+                                    .send(HealthEvent::BackendConfigUpdated { backend_id })
+                                    .await;
+                                // A probe to the old address is now meaningless
+                                probes.cancel(backend_id);
+                                probes.launch(
+                                    vec![backend],
+                                    ctx.config().proxy.backend_bind_address,
+                                    &self.config.load(),
+                                );
                             }
-                        };
+                        }
+                        // Removed or replaced backends must not report into
+                        // their id any more
+                        ConfigEvent::Migrated => {
+                            let routing = ctx.routing_table();
+                            probes.cancel_stale(&routing);
+                            streaks.retain_current(&routing);
+                            outliers.retain_current(&routing);
+                            let routed: HashSet<_> = routing
+                                .all_backends()
+                                .iter()
+                                .map(|backend| backend.address())
+                                .collect();
+                            self.pool.retain_routed(|address| routed.contains(address));
+                        }
+                        _ => {}
+                    }
+                }
 
-                        // Update backend health state
-                        let was_alive = backend.is_alive();
-                        let now_ms = Self::now_ms();
-                        backend.set_health(is_healthy, now_ms);
-
-                        // Send transition event if state changed
-                        if was_alive != is_healthy {
-                            let from_status = if was_alive { "healthy" } else { "unhealthy" };
-                            let to_status = if is_healthy { "healthy" } else { "unhealthy" };
-                            tracing::info!(
-                                "Backend {} health transition: {} -> {}",
-                                backend_id,
-                                from_status,
-                                to_status
-                            );
+                // PERIODIC: Proactive health checks, one per address, each
+                // on its own staggered and jittered schedule
+                _ = clock.sleep_until(next_check.unwrap_or_default()), if next_check.is_some() => {
+                    let config = self.config.load();
+                    let default_source = ctx.config().proxy.backend_bind_address;
+                    let scheduled = schedule.take_due(
+                        clock.now_millis(),
+                        config.interval,
+                        config.jitter_percent,
+                    );
 
-                            let _ = health_tx.send(HealthEvent::HealthTransition {
-                                backend_id,
-                                from: if was_alive {
-                                    HealthStatus::Healthy
-                                } else {
-                                    HealthStatus::Unhealthy
-                                },
-                                to: if is_healthy {
-                                    HealthStatus::Healthy
-                                } else {
-                                    HealthStatus::Unhealthy
-                                },
-                            }).await;
+                    tracing::debug!(
+                        "Starting health checks of {} addresses",
+                        scheduled.len()
+                    );
+
+                    for group in targets.groups(&ctx) {
+                        let leader = group.first().map(|backend| backend.id());
+                        if !leader.is_some_and(|id| scheduled.contains(&id)) {
+                            continue;
+                        }
+                        let due: Vec<_> = group
+                            .iter()
+                            .filter(|backend| {
+                                let backend_id = backend.id();
+
+                                // Skip if backend has high load (respect backend capacity)
+                                // Use a reasonable threshold (e.g., 100 connections) to avoid overloading
+                                if !backend.has_capacity_for_health_check(100) {
+                                    tracing::debug!(
+                                        "Skipping health check for busy backend {} ({} active connections)",
+                                        backend_id,
+                                        backend.active_connections()
+                                    );
+                                    return false;
+                                }
+
+                                // A probe still in flight from the last cycle covers it
+                                !probes.is_probing(backend_id)
+                            })
+                            .cloned()
+                            .collect();
+                        if !due.is_empty() {
+                            probes.launch(due, default_source, &config);
                         }
                     }
-                    tracing::debug!("Health check cycle completed");
+                    tracing::debug!("Health checks launched");
                 }
             }
         }
         tracing::info!("Health service stopped");
     }
 }
+
+/// Read the status line of an HTTP response and return its status code
+///
+/// At most [`MAX_STATUS_LINE_BYTES`] are read; a longer line, a connection
+/// closed before the line ends or a line that is not an HTTP status line is
+/// an invalid response.
+async fn read_status(stream: &mut BackendStream) -> Result<u16, ProbeFailure> {
+    let invalid =
+        |detail: &str| ProbeFailure::new(HealthFailureReason::InvalidResponse, detail);
+    let mut head = Vec::with_capacity(128);
+    let mut chunk = [0u8; 128];
+    loop {
+        if let Some(end) = head.iter().position(|byte| *byte == b'\n') {
+            return parse_status_line(&head[..end])
+                .ok_or_else(|| invalid("malformed HTTP status line"));
+        }
+        if head.len() >= MAX_STATUS_LINE_BYTES {
+            return Err(invalid("HTTP status line too long"));
+        }
+        let read = stream.read(&mut chunk).await.map_err(|e| {
+            ProbeFailure::new(HealthFailureReason::Transport, e.to_string())
+        })?;
+        if read == 0 {
+            return Err(invalid("connection closed before the HTTP status line"));
+        }
+        head.extend_from_slice(&chunk[..read]);
+    }
+}
+
+/// Request of an HTTP check for `path`, with `host_header` or the backend
+/// address as `Host`, asking to keep the connection open when `keep_alive`
+fn http_request(
+    address: &BackendAddress,
+    path: &str,
+    host_header: Option<&str>,
+    keep_alive: bool,
+) -> String {
+    let host = host_header.unwrap_or_else(|| match address.unix() {
+        Some(_) => "localhost",
+        None => address.as_str(),
+    });
+    let connection = if keep_alive { "keep-alive" } else { "close" };
+    format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: lemonade-load-balancer\r\nConnection: {}\r\n\r\n",
+        path, host, connection
+    )
+}
+
+/// Status of an HTTP response read off a keep-alive connection, and whether
+/// the connection can carry the next request
+struct KeepAliveResponse {
+    /// Status code
+    status: u16,
+    /// The response was read to its end and the backend keeps the
+    /// connection open
+    reusable: bool,
+}
+
+/// Send `request` on `stream` and read the response head, then the body
+/// when its length is known and at most [`MAX_POOLED_BODY_BYTES`]
+///
+/// A head longer than [`MAX_RESPONSE_HEAD_BYTES`], a connection closed
+/// before the head ends or a head that is not HTTP is an invalid response.
+/// Once the head is read the response is only left unread, and the
+/// connection not reusable, when it asks to close it, is not HTTP/1.1, has
+/// no known length or is cut short.
+async fn keep_alive_exchange(
+    stream: &mut BackendStream,
+    request: &str,
+) -> Result<KeepAliveResponse, ProbeFailure> {
+    let invalid =
+        |detail: &str| ProbeFailure::new(HealthFailureReason::InvalidResponse, detail);
+    let transport = |e: std::io::Error| {
+        ProbeFailure::new(HealthFailureReason::Transport, e.to_string())
+    };
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(transport)?;
+
+    let mut received = Vec::with_capacity(512);
+    let mut chunk = [0u8; 512];
+    let head_len = loop {
+        if let Some(end) = received.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if received.len() >= MAX_RESPONSE_HEAD_BYTES {
+            return Err(invalid("HTTP response head too long"));
+        }
+        let read = stream.read(&mut chunk).await.map_err(transport)?;
+        if read == 0 {
+            return Err(invalid("connection closed before the HTTP response head"));
+        }
+        received.extend_from_slice(&chunk[..read]);
+    };
+    let head = std::str::from_utf8(&received[..head_len])
+        .map_err(|_| invalid("malformed HTTP response head"))?;
+    let mut lines = head.split("\r\n");
+    let status_line = lines.next().unwrap_or_default();
+    let status = parse_status_line(status_line.as_bytes())
+        .ok_or_else(|| invalid("malformed HTTP status line"))?;
+
+    let mut keep_alive = status_line.starts_with("HTTP/1.1 ");
+    let mut content_length = None;
+    for (name, value) in lines.filter_map(|line| line.split_once(':')) {
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("connection") && value.eq_ignore_ascii_case("close")
+        {
+            keep_alive = false;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            // Chunked bodies are not read: the connection is not reused
+            keep_alive = false;
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse::<usize>().ok();
+        }
+    }
+    let body_len = match (status, content_length) {
+        (100..=199 | 204 | 304, _) => 0,
+        (_, Some(len)) => len,
+        (_, None) => {
+            return Ok(KeepAliveResponse {
+                status,
+                reusable: false,
+            });
+        }
+    };
+    if !keep_alive || body_len > MAX_POOLED_BODY_BYTES {
+        return Ok(KeepAliveResponse {
+            status,
+            reusable: false,
+        });
+    }
+
+    let mut body_read = received.len() - head_len;
+    while body_read < body_len {
+        match stream.read(&mut chunk).await {
+            Ok(read) if read > 0 => body_read += read,
+            _ => {
+                return Ok(KeepAliveResponse {
+                    status,
+                    reusable: false,
+                });
+            }
+        }
+    }
+    Ok(KeepAliveResponse {
+        status,
+        // Bytes past the body mean the backend is out of step with us
+        reusable: body_read == body_len,
+    })
+}
+
+/// Probe results in a row of each backend
+///
+/// An opposite result starts a backend's streak over, and so does a new
+/// backend under a tracked id.
+#[derive(Default)]
+struct HealthStreaks {
+    /// Streak of each backend probed since it joined the route table
+    streaks: HashMap<BackendId, HealthStreak>,
+}
+
+/// Same probe results in a row of one backend
+struct HealthStreak {
+    /// Backend probed, as it was in the route table
+    backend: Arc<Backend>,
+    /// Whether the probes passed
+    passed: bool,
+    /// Results in a row
+    count: u32,
+}
+
+impl HealthStreaks {
+    /// Record a probe result of `backend` and return the length of its
+    /// streak, this result included
+    fn record(&mut self, backend: &Arc<Backend>, passed: bool) -> u32 {
+        let streak = self
+            .streaks
+            .entry(backend.id())
+            .or_insert_with(|| HealthStreak {
+                backend: backend.clone(),
+                passed,
+                count: 0,
+            });
+        if streak.passed != passed || !Arc::ptr_eq(&streak.backend, backend) {
+            *streak = HealthStreak {
+                backend: backend.clone(),
+                passed,
+                count: 0,
+            };
+        }
+        streak.count = streak.count.saturating_add(1);
+        streak.count
+    }
+
+    /// Forget the backends no longer in `routing` (removed or replaced)
+    fn retain_current(&mut self, routing: &RouteTable) {
+        self.streaks.retain(|id, streak| {
+            routing
+                .get(*id)
+                .is_some_and(|current| Arc::ptr_eq(&current, &streak.backend))
+        });
+    }
+}
+
+/// Backends grouped by the endpoint a probe connects to
+///
+/// Rebuilt when the route table generation changes; an address change only
+/// happens in a migration, which moves the generation too.
+#[derive(Default)]
+struct ProbeTargets {
+    /// Route table generation the groups were built at
+    generation: Option<u64>,
+    /// Backends sharing an address and source address, in route table order
+    groups: Vec<Vec<Arc<Backend>>>,
+}
+
+impl ProbeTargets {
+    /// Check whether the groups predate the current route table
+    fn is_stale(&self, ctx: &Context) -> bool {
+        self.generation != Some(ctx.generation())
+    }
+
+    /// First backend of each group for the current route table, which the
+    /// group's probes are scheduled by
+    fn leaders(&mut self, ctx: &Context) -> Vec<BackendId> {
+        self.groups(ctx)
+            .iter()
+            .filter_map(|group| group.first().map(|backend| backend.id()))
+            .collect()
+    }
+
+    /// Groups for the current route table
+    fn groups(&mut self, ctx: &Context) -> &[Vec<Arc<Backend>>] {
+        // Read before the route table, so a swap in between rebuilds again
+        let generation = ctx.generation();
+        if self.generation != Some(generation) {
+            let default_source = ctx.config().proxy.backend_bind_address;
+            let mut index = HashMap::new();
+            self.groups.clear();
+            for backend in ctx.routing_table().all_backends() {
+                let endpoint =
+                    (backend.address(), backend.source_address(default_source));
+                let slot = *index.entry(endpoint).or_insert_with(|| {
+                    self.groups.push(Vec::new());
+                    self.groups.len() - 1
+                });
+                self.groups[slot].push(backend);
+            }
+            self.generation = Some(generation);
+        }
+        &self.groups
+    }
+}
+
+/// Result of a probe, tagged with the backends and address it probed
+struct ProbeOutcome {
+    /// Backends sharing the address, as they were in the route table at launch
+    backends: Vec<Arc<Backend>>,
+    /// Address probed
+    address: Arc<BackendAddress>,
+    /// Probe timings, or why the address could not be reached
+    result: Result<ProbeTimings, ProbeFailure>,
+}
+
+impl ProbeOutcome {
+    /// Check that `backend` is still routed at the probed address
+    ///
+    /// A migration may move the backend or replace it while the probe is in
+    /// flight; the result then describes an endpoint nobody routes to.
+    fn is_current(&self, backend: &Arc<Backend>, routing: &RouteTable) -> bool {
+        routing
+            .get(backend.id())
+            .is_some_and(|current| Arc::ptr_eq(&current, backend))
+            && backend.address() == self.address
+    }
+}
+
+/// Probes in flight, at most one per backend
+///
+/// Backends probed together share one task and abort handle.
+struct ProbeSet {
+    /// Running probes
+    tasks: tokio::task::JoinSet<ProbeOutcome>,
+    /// Backend and abort handle of each running probe
+    in_flight: HashMap<BackendId, (Arc<Backend>, tokio::task::AbortHandle)>,
+    /// Keep-alive connections the probes of HTTP checks reuse
+    pool: Arc<ProbeConnectionPool>,
+}
+
+impl ProbeSet {
+    /// Create an empty probe set reusing the connections of `pool`
+    fn new(pool: Arc<ProbeConnectionPool>) -> Self {
+        Self {
+            tasks: tokio::task::JoinSet::new(),
+            in_flight: HashMap::new(),
+            pool,
+        }
+    }
+
+    /// Probe `backends`, which share an address, once at that address (from
+    /// their source address, or `default_source`) as `config` says
+    fn launch(
+        &mut self,
+        backends: Vec<Arc<Backend>>,
+        default_source: Option<IpAddr>,
+        config: &HealthConfig,
+    ) {
+        let Some(first) = backends.first() else {
+            return;
+        };
+        let address = first.address();
+        let source = first.source_address(default_source);
+        let check_span = tracing::debug_span!(
+            "health.check",
+            service.name = "lemonade-load-balancer",
+            backend.id = %first.id(),
+            backend.addr = %address,
+            backend.count = backends.len()
+        );
+        let probed = backends.clone();
+        let config = config.clone();
+        let pool = self.pool.clone();
+        let handle = self.tasks.spawn(
+            async move {
+                let result =
+                    BackendHealthService::pooled_probe(&pool, &address, source, &config)
+                        .await;
+                ProbeOutcome {
+                    backends: probed,
+                    address,
+                    result,
+                }
+            }
+            .instrument(check_span),
+        );
+        for backend in backends {
+            self.in_flight
+                .insert(backend.id(), (backend, handle.clone()));
+        }
+    }
+
+    /// Check whether a probe of `backend_id` is running
+    fn is_probing(&self, backend_id: BackendId) -> bool {
+        self.in_flight.contains_key(&backend_id)
+    }
+
+    /// Stop waiting on the running probe of `backend_id`, if any
+    ///
+    /// The probe is aborted unless it also covers other backends.
+    fn cancel(&mut self, backend_id: BackendId) {
+        if let Some((_, handle)) = self.in_flight.remove(&backend_id) {
+            self.abort_if_unshared(&handle);
+        }
+    }
+
+    /// Stop waiting on the probes of backends no longer in `routing`
+    fn cancel_stale(&mut self, routing: &RouteTable) {
+        let mut stale = Vec::new();
+        self.in_flight.retain(|id, (backend, handle)| {
+            let current = routing
+                .get(*id)
+                .is_some_and(|current| Arc::ptr_eq(&current, backend));
+            if !current {
+                stale.push(handle.clone());
+            }
+            current
+        });
+        for handle in stale {
+            self.abort_if_unshared(&handle);
+        }
+    }
+
+    /// Abort a probe no remaining backend waits on
+    fn abort_if_unshared(&self, handle: &tokio::task::AbortHandle) {
+        if !self
+            .in_flight
+            .values()
+            .any(|(_, other)| other.id() == handle.id())
+        {
+            handle.abort();
+        }
+    }
+
+    /// Wait for the next probe to finish, skipping aborted ones
+    ///
+    /// Returns `None` when no probe is running.
+    async fn join_next(&mut self) -> Option<ProbeOutcome> {
+        while let Some(joined) = self.tasks.join_next_with_id().await {
+            let Ok((task_id, outcome)) = joined else {
+                continue;
+            };
+            for backend in &outcome.backends {
+                let id = backend.id();
+                if self
+                    .in_flight
+                    .get(&id)
+                    .is_some_and(|(_, handle)| handle.id() == task_id)
+                {
+                    self.in_flight.remove(&id);
+                }
+            }
+            return Some(outcome);
+        }
+        None
+    }
+}
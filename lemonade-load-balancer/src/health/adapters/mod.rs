@@ -2,5 +2,7 @@
 //!
 
 mod backend;
+mod noop;
 
 pub use backend::BackendHealthService;
+pub use noop::NoopHealthService;
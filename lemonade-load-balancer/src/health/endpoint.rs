@@ -0,0 +1,181 @@
+//! Health endpoint module
+//!
+//! Minimal HTTP listener for orchestrator probes, separate from the admin
+//! API so it can be exposed without exposing the admin routes:
+//! - `GET /healthz`: 200 while the proxy accept loop is running
+//! - `GET /readyz`: 200 once the initial health check has finished and at
+//!   least `min_healthy_backends` backends are healthy, else 503 with a
+//!   JSON reason
+
+use crate::prelude::*;
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::header::CONTENT_TYPE;
+use hyper::server::conn::http1::Builder;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use tokio::net::TcpListener;
+
+/// Liveness and readiness HTTP endpoint
+#[derive(Debug, Clone, Default)]
+pub struct HealthEndpointServer {
+    /// Backend groups that must all be ready (the served context otherwise)
+    groups: Option<Arc<Groups>>,
+}
+
+impl HealthEndpointServer {
+    /// Create a health endpoint for a single context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require every backend group to be live and ready
+    pub fn with_groups(mut self, groups: Arc<Groups>) -> Self {
+        self.groups = Some(groups);
+        self
+    }
+
+    /// Bind the configured address and serve until shutdown
+    ///
+    /// Does nothing when no `health_endpoint.listen_address` is configured.
+    pub async fn run(&self, ctx: Arc<Context>) -> std::io::Result<()> {
+        let Some(listen_address) = ctx.config().health_endpoint.listen_address else {
+            return Ok(());
+        };
+        let listener = TcpListener::bind(listen_address).await?;
+        tracing::info!("Health endpoint listening on {}", listen_address);
+        self.serve(listener, ctx).await
+    }
+
+    /// Serve probes on an already bound listener until shutdown
+    pub async fn serve(
+        &self,
+        listener: TcpListener,
+        ctx: Arc<Context>,
+    ) -> std::io::Result<()> {
+        let mut shutdown_rx = ctx.channels().shutdown_rx();
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("Health endpoint received shutdown signal");
+                    break;
+                }
+
+                accept_result = listener.accept() => {
+                    let (stream, peer) = match accept_result {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            tracing::warn!("Health endpoint accept error: {}", e);
+                            continue;
+                        }
+                    };
+
+                    let server = self.clone();
+                    let ctx = ctx.clone();
+                    tokio::spawn(async move {
+                        let service = service_fn(move |req| {
+                            let response = server.handle(&req, &ctx);
+                            async move { Ok::<_, Infallible>(response) }
+                        });
+                        if let Err(e) = Builder::new()
+                            .serve_connection(TokioIo::new(stream), service)
+                            .await
+                        {
+                            tracing::debug!("Health endpoint connection from {} failed: {}", peer, e);
+                        }
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer a single probe
+    fn handle(
+        &self,
+        req: &Request<Incoming>,
+        ctx: &Arc<Context>,
+    ) -> Response<Full<Bytes>> {
+        if !matches!(*req.method(), Method::GET | Method::HEAD) {
+            return json_response(
+                StatusCode::METHOD_NOT_ALLOWED,
+                serde_json::json!({ "error": "method not allowed" }),
+            );
+        }
+        let result = match req.uri().path() {
+            "/healthz" => self.contexts(ctx).iter().try_for_each(|ctx| liveness(ctx)),
+            "/readyz" => self.contexts(ctx).iter().try_for_each(|ctx| readiness(ctx)),
+            path => {
+                return json_response(
+                    StatusCode::NOT_FOUND,
+                    serde_json::json!({ "error": format!("not found: {}", path) }),
+                );
+            }
+        };
+        match result {
+            Ok(()) => {
+                json_response(StatusCode::OK, serde_json::json!({ "status": "ok" }))
+            }
+            Err(reason) => json_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                serde_json::json!({ "status": "unavailable", "reason": reason }),
+            ),
+        }
+    }
+
+    /// Contexts whose state the probes report
+    fn contexts(&self, ctx: &Arc<Context>) -> Vec<Arc<Context>> {
+        match &self.groups {
+            Some(groups) => groups.iter().map(|(_, ctx)| ctx.clone()).collect(),
+            None => vec![ctx.clone()],
+        }
+    }
+}
+
+/// Check that the group's proxy is accepting connections
+fn liveness(ctx: &Context) -> Result<(), String> {
+    if ctx.readiness().is_accepting() {
+        Ok(())
+    } else {
+        Err(format!(
+            "group {}: proxy is not accepting connections",
+            ctx.group()
+        ))
+    }
+}
+
+/// Check that the group can serve traffic
+fn readiness(ctx: &Context) -> Result<(), String> {
+    liveness(ctx)?;
+    if !ctx.readiness().is_health_checked() {
+        return Err(format!(
+            "group {}: initial health check has not finished",
+            ctx.group()
+        ));
+    }
+    let required = ctx.config().health_endpoint.min_healthy_backends;
+    let healthy = ctx.routing_table().healthy_backends().len();
+    if healthy < required {
+        return Err(format!(
+            "group {}: {} healthy backends, {} required",
+            ctx.group(),
+            healthy,
+            required
+        ));
+    }
+    Ok(())
+}
+
+/// Build a JSON response
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
@@ -3,4 +3,17 @@
 
 /// Health error enum
 #[derive(Debug, thiserror::Error)]
-pub enum HealthError {}
+pub enum HealthError {
+    /// Too few backends answered the startup reachability check
+    #[error(
+        "only {reachable} of {total} backends reachable at startup, {required} required"
+    )]
+    Preflight {
+        /// Backends that accepted a connection
+        reachable: usize,
+        /// Backends checked
+        total: usize,
+        /// Backends required by `preflight.min_reachable`
+        required: usize,
+    },
+}
@@ -0,0 +1,111 @@
+//! Health history module
+//!
+//! Bounded record of the latest probe results of each backend, so the admin
+//! API and logs can tell how a backend has been doing lately ("failed 8 of
+//! its last 10 probes") rather than only where its health stands.
+use crate::prelude::*;
+use std::collections::VecDeque;
+
+/// Result of one health probe of a backend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthProbeRecord {
+    /// When the probe completed, in milliseconds
+    pub at_ms: u64,
+    /// Round trip time of a passing probe
+    pub rtt_micros: Option<u64>,
+    /// Connect time of a passing HTTP check that opened a new connection
+    /// rather than reusing a pooled one
+    pub handshake_rtt_micros: Option<u64>,
+    /// Why a failing probe failed
+    pub failure: Option<HealthFailureReason>,
+}
+
+impl HealthProbeRecord {
+    /// Record of a probe that completed at `at_ms` with `result`
+    pub fn new(at_ms: u64, result: &Result<Duration, ProbeFailure>) -> Self {
+        match result {
+            Ok(rtt) => Self {
+                at_ms,
+                rtt_micros: Some(rtt.as_micros() as u64),
+                handshake_rtt_micros: None,
+                failure: None,
+            },
+            Err(failure) => Self {
+                at_ms,
+                rtt_micros: None,
+                handshake_rtt_micros: None,
+                failure: Some(failure.reason),
+            },
+        }
+    }
+
+    /// Set the connect time of a probe that opened a new connection
+    pub fn with_handshake(mut self, handshake: Option<Duration>) -> Self {
+        self.handshake_rtt_micros = handshake.map(|rtt| rtt.as_micros() as u64);
+        self
+    }
+
+    /// Check whether the probe passed
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Latest probe results of each backend, oldest first
+///
+/// Each backend keeps at most `capacity` records, the oldest evicted first.
+/// Entries are sharded, so recording one backend's probe only locks its own
+/// ring.
+#[derive(Debug)]
+pub struct HealthHistory {
+    /// Records kept per backend
+    capacity: usize,
+    /// Records of each backend probed at least once
+    backends: DashMap<BackendId, VecDeque<HealthProbeRecord>>,
+}
+
+impl HealthHistory {
+    /// Create a history keeping `capacity` records per backend
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            backends: DashMap::new(),
+        }
+    }
+
+    /// Append `record` to the history of `backend_id`, evicting its oldest
+    /// record once full
+    pub fn record(&self, backend_id: BackendId, record: HealthProbeRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut records = self.backends.entry(backend_id).or_default();
+        if records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Get the records of `backend_id`, oldest first
+    pub fn get(&self, backend_id: BackendId) -> Vec<HealthProbeRecord> {
+        self.backends
+            .get(&backend_id)
+            .map(|records| records.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the ids of the backends with at least one record
+    pub fn tracked_backends(&self) -> Vec<BackendId> {
+        let mut ids: Vec<BackendId> =
+            self.backends.iter().map(|entry| *entry.key()).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Drop the histories of any of `ids`, returning how many went
+    pub fn remove_backends(&self, ids: &[BackendId]) -> usize {
+        ids.iter()
+            .filter(|id| self.backends.remove(id).is_some())
+            .count()
+    }
+}
@@ -2,6 +2,11 @@
 //!
 
 pub mod adapters;
+pub mod endpoint;
 pub mod error;
+pub mod history;
 pub mod models;
+pub mod outlier;
+pub mod pool;
 pub mod port;
+pub mod schedule;
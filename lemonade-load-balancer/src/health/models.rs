@@ -3,15 +3,316 @@
 use crate::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// Most [`HealthConfig::jitter_percent`] allowed
+pub const MAX_JITTER_PERCENT: u8 = 50;
+
 /// Health config struct
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct HealthConfig {
     /// Health check interval
-    #[serde(with = "crate::config::serde_helpers")]
+    #[serde(rename = "interval_millis", with = "crate::config::serde_helpers")]
     pub interval: Duration,
     /// Health check timeout
-    #[serde(with = "crate::config::serde_helpers")]
+    #[serde(rename = "timeout_millis", with = "crate::config::serde_helpers")]
     pub timeout: Duration,
+    /// Proxy failure events older than this when processed are discarded as
+    /// expired instead of marking the backend down, in milliseconds (0 keeps
+    /// every event)
+    #[serde(default = "default_max_event_age_millis")]
+    pub max_event_age_millis: u64,
+    /// Cut the connections open to a backend when it turns unhealthy, so
+    /// clients reconnect to a healthy one
+    #[serde(default)]
+    pub evict_on_unhealthy: bool,
+    /// Window over which a backend that recovers ramps from a small share of
+    /// its weight up to its full weight, in milliseconds (0 disables)
+    #[serde(default)]
+    pub slow_start_millis: u64,
+    /// Share of a backend's connections within `outlier_window_millis`, as a
+    /// percent, that must have failed to eject it from rotation even while
+    /// its probes pass (0 disables outlier ejection)
+    #[serde(default)]
+    pub outlier_error_percent: u8,
+    /// Fewest connections, failed or completed, within
+    /// `outlier_window_millis` before a backend's error rate can eject it
+    #[serde(default = "default_outlier_min_connections")]
+    pub outlier_min_connections: u32,
+    /// Sliding window proxy failures and completed connections are counted
+    /// over, in milliseconds
+    #[serde(default = "default_outlier_window_millis")]
+    pub outlier_window_millis: u64,
+    /// Length of a first ejection, in milliseconds; it doubles with each
+    /// ejection that follows before the backend stays in rotation for as long
+    /// as it was last out
+    #[serde(default = "default_base_ejection_millis")]
+    pub base_ejection_millis: u64,
+    /// Most backends ejected at once, as a percent of all backends (at least
+    /// one may always be ejected, never the last healthy one)
+    #[serde(default = "default_max_ejection_percent")]
+    pub max_ejection_percent: u8,
+    /// How a probe checks a backend
+    #[serde(default)]
+    pub check: HealthCheck,
+    /// Passing probes in a row that bring an unhealthy backend back
+    #[serde(default = "default_healthy_threshold")]
+    pub healthy_threshold: u32,
+    /// Failed probes in a row that mark a healthy backend unhealthy
+    #[serde(default = "default_unhealthy_threshold")]
+    pub unhealthy_threshold: u32,
+    /// Most a backend's probe interval is stretched or shortened, as a
+    /// percent of `interval`, so probes of many backends do not line up
+    #[serde(default = "default_jitter_percent")]
+    pub jitter_percent: u8,
+    /// Health a backend starts with, until its first probe
+    #[serde(default)]
+    pub initial_state: InitialHealthState,
+    /// Keep the connection of an HTTP check open for the next probe of the
+    /// same address, so probes measure the request rather than a handshake
+    #[serde(default = "default_reuse_connections")]
+    pub reuse_connections: bool,
+}
+
+/// Health a backend starts with
+///
+/// Read when a backend is added, at startup or by a migration. Only applies
+/// while health checking runs (`services.health`); otherwise nothing would
+/// bring a backend started unhealthy into rotation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InitialHealthState {
+    /// Routed to before its first probe
+    #[default]
+    Healthy,
+    /// Kept out of rotation until a probe passes
+    Unhealthy,
+}
+
+impl InitialHealthState {
+    /// Check whether a backend starting in this state is healthy
+    pub fn is_healthy(self) -> bool {
+        self == Self::Healthy
+    }
+}
+
+impl std::str::FromStr for InitialHealthState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "healthy" => Ok(Self::Healthy),
+            "unhealthy" => Ok(Self::Unhealthy),
+            _ => Err(format!(
+                "unknown initial health state {} (expected healthy or unhealthy)",
+                s
+            )),
+        }
+    }
+}
+
+/// How a health probe checks a backend
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HealthCheck {
+    /// The backend accepts a TCP connection
+    #[default]
+    Tcp,
+    /// The backend answers an HTTP GET with the expected status
+    ///
+    /// A different status or a response that is not HTTP counts as a failed
+    /// probe, as does no status line within the health timeout.
+    Http {
+        /// Path requested
+        #[serde(default = "default_http_check_path")]
+        path: String,
+        /// Status code of a healthy backend
+        #[serde(default = "default_http_check_expected_status")]
+        expected_status: u16,
+        /// `Host` header sent (the backend address when unset)
+        #[serde(default)]
+        host_header: Option<String>,
+    },
+}
+
+impl HealthConfig {
+    /// Validate the thresholds, jitter, outlier ejection and HTTP check
+    /// settings
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_ejection_percent > 100 {
+            return Err(ConfigError::Health(format!(
+                "max_ejection_percent must be at most 100, got {}",
+                self.max_ejection_percent
+            )));
+        }
+        if self.outlier_error_percent > 100 {
+            return Err(ConfigError::Health(format!(
+                "outlier_error_percent must be at most 100, got {}",
+                self.outlier_error_percent
+            )));
+        }
+        if self.outlier_ejection_enabled() {
+            if self.outlier_window_millis == 0 || self.base_ejection_millis == 0 {
+                return Err(ConfigError::Health(
+                    "outlier windows and ejections must last at least 1ms".to_string(),
+                ));
+            }
+            if self.outlier_min_connections == 0 {
+                return Err(ConfigError::Health(
+                    "outlier_min_connections must be at least 1".to_string(),
+                ));
+            }
+        }
+        if self.healthy_threshold == 0 || self.unhealthy_threshold == 0 {
+            return Err(ConfigError::Health(
+                "healthy_threshold and unhealthy_threshold must be at least 1"
+                    .to_string(),
+            ));
+        }
+        if self.jitter_percent > MAX_JITTER_PERCENT {
+            return Err(ConfigError::Health(format!(
+                "jitter_percent must be at most {}, got {}",
+                MAX_JITTER_PERCENT, self.jitter_percent
+            )));
+        }
+        if let HealthCheck::Http {
+            path,
+            expected_status,
+            host_header,
+        } = &self.check
+        {
+            if !path.starts_with('/') || path.contains(char::is_whitespace) {
+                return Err(ConfigError::Health(format!(
+                    "check path must start with / and have no whitespace, got {:?}",
+                    path
+                )));
+            }
+            if !(100..=599).contains(expected_status) {
+                return Err(ConfigError::Health(format!(
+                    "check expected_status must be an HTTP status code, got {}",
+                    expected_status
+                )));
+            }
+            if host_header
+                .as_deref()
+                .is_some_and(|host| host.is_empty() || host.contains(char::is_control))
+            {
+                return Err(ConfigError::Health(
+                    "check host_header must be a non-empty header value".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether backends are ejected on high proxy error rates
+    pub fn outlier_ejection_enabled(&self) -> bool {
+        self.outlier_error_percent > 0
+    }
+}
+
+/// Default for [`HealthConfig::max_event_age_millis`]
+fn default_max_event_age_millis() -> u64 {
+    10_000
+}
+
+/// Default for [`HealthConfig::outlier_min_connections`]
+fn default_outlier_min_connections() -> u32 {
+    10
+}
+
+/// Default for [`HealthConfig::outlier_window_millis`]
+fn default_outlier_window_millis() -> u64 {
+    10_000
+}
+
+/// Default for [`HealthConfig::base_ejection_millis`]
+fn default_base_ejection_millis() -> u64 {
+    30_000
+}
+
+/// Default for [`HealthConfig::max_ejection_percent`]
+fn default_max_ejection_percent() -> u8 {
+    10
+}
+
+/// Default for [`HealthConfig::healthy_threshold`]
+fn default_healthy_threshold() -> u32 {
+    2
+}
+
+/// Default for [`HealthConfig::unhealthy_threshold`]
+fn default_unhealthy_threshold() -> u32 {
+    3
+}
+
+/// Default for [`HealthConfig::jitter_percent`]
+fn default_jitter_percent() -> u8 {
+    10
+}
+
+/// Default for reusing HTTP check connections
+fn default_reuse_connections() -> bool {
+    true
+}
+
+/// Default path of an HTTP check
+fn default_http_check_path() -> String {
+    "/health".to_string()
+}
+
+/// Default expected status of an HTTP check
+fn default_http_check_expected_status() -> u16 {
+    200
+}
+
+/// Startup backend reachability check config
+///
+/// When enabled, every backend gets a single TCP connect (bounded by the
+/// health timeout) before the proxy starts accepting, and a reachable /
+/// unreachable summary is logged. In strict mode the load balancer refuses
+/// to start if fewer than `min_reachable` backends answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreflightConfig {
+    /// Check backend reachability before accepting connections
+    pub verify_backends_on_start: bool,
+    /// Refuse to start when too few backends are reachable
+    pub strict: bool,
+    /// Reachable backends required in strict mode
+    pub min_reachable: usize,
+}
+
+impl Default for PreflightConfig {
+    fn default() -> Self {
+        Self {
+            verify_backends_on_start: false,
+            strict: false,
+            min_reachable: 1,
+        }
+    }
+}
+
+/// Built-in health endpoint config
+///
+/// When `listen_address` is set, a small HTTP listener separate from the
+/// admin API answers `GET /healthz` (the proxy is accepting connections) and
+/// `GET /readyz` (the initial health check has finished and at least
+/// `min_healthy_backends` backends are healthy) for orchestrator probes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthEndpointConfig {
+    /// Health endpoint listen address (off when unset, read at startup)
+    pub listen_address: Option<SocketAddr>,
+    /// Healthy backends required for readiness
+    pub min_healthy_backends: usize,
+}
+
+impl Default for HealthEndpointConfig {
+    fn default() -> Self {
+        Self {
+            listen_address: None,
+            min_healthy_backends: 1,
+        }
+    }
 }
 
 /// Health event struct
@@ -44,6 +345,8 @@ pub enum HealthEvent {
         from: HealthStatus,
         /// To health status
         to: HealthStatus,
+        /// What triggered the change
+        cause: TransitionCause,
     },
     /// Config reload changed backend parameters
     BackendConfigUpdated {
@@ -52,8 +355,102 @@ pub enum HealthEvent {
     },
 }
 
+/// Longest error text kept in a [`TransitionCause`], in bytes
+pub const MAX_TRANSITION_DETAIL_LEN: usize = 256;
+
+/// What triggered a health transition
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransitionCause {
+    /// Why the backend went down (`None` when it recovered)
+    pub reason: Option<HealthFailureReason>,
+    /// Unhealthy reports in a row, including the triggering one
+    pub consecutive_failures: u32,
+    /// Round trip time of the probe that brought the backend back
+    pub rtt_micros: Option<u64>,
+    /// Error text, truncated to [`MAX_TRANSITION_DETAIL_LEN`] bytes
+    pub detail: Option<String>,
+}
+
+impl TransitionCause {
+    /// Cause of a backend going down
+    pub fn failure(
+        reason: HealthFailureReason,
+        consecutive_failures: u32,
+        detail: impl Into<String>,
+    ) -> Self {
+        let mut detail = detail.into();
+        if detail.len() > MAX_TRANSITION_DETAIL_LEN {
+            let mut end = MAX_TRANSITION_DETAIL_LEN;
+            while !detail.is_char_boundary(end) {
+                end -= 1;
+            }
+            detail.truncate(end);
+        }
+        Self {
+            reason: Some(reason),
+            consecutive_failures,
+            rtt_micros: None,
+            detail: Some(detail),
+        }
+    }
+
+    /// Cause of a backend coming back after a probe answered in `rtt_micros`
+    pub fn recovery(rtt_micros: u64) -> Self {
+        Self {
+            rtt_micros: Some(rtt_micros),
+            ..Self::default()
+        }
+    }
+}
+
+/// Timings of a passing health probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbeTimings {
+    /// Round trip time of the check: the connect of a TCP check, or the
+    /// request and response of an HTTP check
+    pub rtt: Duration,
+    /// Connect time of an HTTP check that could not reuse a pooled
+    /// connection, measured apart from `rtt`
+    pub handshake: Option<Duration>,
+}
+
+impl ProbeTimings {
+    /// Timings of a probe that took `rtt`, handshake included
+    pub fn new(rtt: Duration) -> Self {
+        Self {
+            rtt,
+            handshake: None,
+        }
+    }
+}
+
+/// Why a health probe failed, with the error text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeFailure {
+    /// Failure category
+    pub reason: HealthFailureReason,
+    /// Error text (e.g. the connect error)
+    pub detail: String,
+}
+
+impl ProbeFailure {
+    /// Create a probe failure
+    pub fn new(reason: HealthFailureReason, detail: impl Into<String>) -> Self {
+        Self {
+            reason,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ProbeFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.reason, self.detail)
+    }
+}
+
 /// Health failure reason enum
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HealthFailureReason {
     /// Timeout
     Timeout,
@@ -65,6 +462,28 @@ pub enum HealthFailureReason {
     DnsError,
     /// Transport error
     Transport,
+    /// Source address could not be bound
+    SourceBind,
+}
+
+impl HealthFailureReason {
+    /// Reason code used in logs and telemetry
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Timeout => "timeout",
+            Self::ConnectionRefused => "connection_refused",
+            Self::InvalidResponse => "invalid_response",
+            Self::DnsError => "dns_error",
+            Self::Transport => "transport",
+            Self::SourceBind => "source_bind",
+        }
+    }
+}
+
+impl std::fmt::Display for HealthFailureReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Health status enum
@@ -83,21 +502,35 @@ pub enum HealthStatus {
 /// uses these events for immediate failure detection without waiting for
 /// the next periodic health check.
 ///
+/// The health service is the sole consumer of the channel: it takes the
+/// receiver from the [`ChannelBundle`] once and, for every event, either
+/// marks the backend unhealthy or ignores the event (too old, backend
+/// unknown or already unhealthy). Each outcome is counted in
+/// [`BackendFailureStats`]. With health checks disabled the proxy sends
+/// nothing, so the channel has no consumer.
+///
 /// # Examples
 ///
 /// ```no_run
-/// use lemonade_load_balancer::health::models::BackendFailureEvent;
+/// use lemonade_load_balancer::prelude::BackendFailureEvent;
 ///
 /// // Report connection refused
-/// let event = BackendFailureEvent::ConnectionRefused { backend_id: 0 };
+/// let event = BackendFailureEvent::ConnectionRefused {
+///     backend_id: 0,
+///     at_micros: 1_700_000_000_000_000,
+/// };
 ///
 /// // Report timeout
-/// let event = BackendFailureEvent::Timeout { backend_id: 1 };
+/// let event = BackendFailureEvent::Timeout {
+///     backend_id: 1,
+///     at_micros: 1_700_000_000_000_000,
+/// };
 ///
 /// // Report consecutive errors
 /// let event = BackendFailureEvent::ConsecutiveErrors {
 ///     backend_id: 2,
 ///     count: 5,
+///     at_micros: 1_700_000_000_000_000,
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -110,6 +543,8 @@ pub enum BackendFailureEvent {
     ConnectionRefused {
         /// Backend identifier
         backend_id: BackendId,
+        /// Timestamp
+        at_micros: u64,
     },
 
     /// Proxy detected timeout
@@ -119,6 +554,8 @@ pub enum BackendFailureEvent {
     Timeout {
         /// Backend identifier
         backend_id: BackendId,
+        /// Timestamp
+        at_micros: u64,
     },
 
     /// Proxy detected backend closed connection
@@ -129,6 +566,20 @@ pub enum BackendFailureEvent {
     BackendClosed {
         /// Backend identifier
         backend_id: BackendId,
+        /// Timestamp
+        at_micros: u64,
+    },
+
+    /// Proxy could not bind the backend socket to its source address
+    ///
+    /// The configured `bind_address` is not available on this host (or does
+    /// not match the backend's address family), so no connection to the
+    /// backend can be made from it.
+    SourceBindFailed {
+        /// Backend identifier
+        backend_id: BackendId,
+        /// Timestamp
+        at_micros: u64,
     },
 
     /// Multiple consecutive errors detected
@@ -141,5 +592,57 @@ pub enum BackendFailureEvent {
         backend_id: BackendId,
         /// Number of consecutive errors
         count: u32,
+        /// Timestamp
+        at_micros: u64,
     },
 }
+
+/// Why the health service ignored a backend failure event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendFailureIgnored {
+    /// The backend was already unhealthy, so no transition happened
+    AlreadyUnhealthy,
+    /// No routed backend has the event's id (removed by a migration)
+    UnknownBackend,
+}
+
+/// Outcomes of the backend failure events consumed by the health service
+///
+/// Every received event is counted in exactly one of the other fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackendFailureStats {
+    /// Events taken off the channel
+    pub received: u64,
+    /// Events that marked a healthy backend unhealthy
+    pub acted_upon: u64,
+    /// Events for a backend that was already unhealthy
+    pub ignored_already_unhealthy: u64,
+    /// Events for a backend no longer routed
+    pub ignored_unknown_backend: u64,
+    /// Events discarded for exceeding `health.max_event_age_millis`
+    pub expired: u64,
+}
+
+impl BackendFailureEvent {
+    /// Backend the failure was reported for
+    pub fn backend_id(&self) -> BackendId {
+        match self {
+            Self::ConnectionRefused { backend_id, .. }
+            | Self::Timeout { backend_id, .. }
+            | Self::BackendClosed { backend_id, .. }
+            | Self::SourceBindFailed { backend_id, .. }
+            | Self::ConsecutiveErrors { backend_id, .. } => *backend_id,
+        }
+    }
+
+    /// When the failure happened, in microseconds since the Unix epoch
+    pub fn at_micros(&self) -> u64 {
+        match self {
+            Self::ConnectionRefused { at_micros, .. }
+            | Self::Timeout { at_micros, .. }
+            | Self::BackendClosed { at_micros, .. }
+            | Self::SourceBindFailed { at_micros, .. }
+            | Self::ConsecutiveErrors { at_micros, .. } => *at_micros,
+        }
+    }
+}
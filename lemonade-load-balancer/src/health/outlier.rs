@@ -0,0 +1,233 @@
+//! Outlier detection module
+//!
+//! Ejects backends that the proxy reports failing too many of their
+//! connections, even while their health probes still pass. A backend with at
+//! least `outlier_min_connections` connections within `outlier_window_millis`,
+//! `outlier_error_percent` of them failed, is kept out of rotation for an
+//! ejection interval that doubles with each repeated ejection.
+//!
+//! Failures arrive one by one from the proxy; completed connections are read
+//! off the backend's running count at each failure, so a window's completed
+//! connections are those since the last failure before it (or its first
+//! failure, if none came before).
+use crate::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+/// Most times an ejection interval doubles (32 times the base interval)
+pub const MAX_EJECTION_DOUBLINGS: u32 = 5;
+
+/// Proxy failures and ejections of one backend
+#[derive(Debug)]
+struct OutlierState {
+    /// Backend tracked, as it was in the route table
+    backend: Arc<Backend>,
+    /// Times of the failures within the window, in milliseconds
+    failures: VecDeque<u64>,
+    /// Time of each failure within the window and of the last one before it,
+    /// in milliseconds, with the backend's completed connections then
+    completed: VecDeque<(u64, u64)>,
+    /// End of the current ejection, if the backend is ejected
+    ejected_until_ms: Option<u64>,
+    /// Ejections in a row, each before the backend stayed back in rotation
+    /// as long as it was last out
+    ejections: u32,
+    /// Length of the last ejection, in milliseconds
+    last_ejection_ms: u64,
+    /// When the last ejection ended, in milliseconds
+    readmitted_at_ms: u64,
+}
+
+/// Why a high error rate did not eject a backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EjectionSkipped {
+    /// `max_ejection_percent` of the backends are already ejected
+    MaxEjectionPercent,
+    /// No other backend is healthy
+    LastHealthy,
+}
+
+/// Outcome of recording a proxy failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutlierVerdict {
+    /// Below the error rate or connection volume, or already ejected
+    Tolerated,
+    /// Ejected for the given interval
+    Ejected(Duration),
+    /// Over the threshold but kept in rotation
+    Skipped(EjectionSkipped),
+}
+
+/// Per-backend error rate windows and ejections
+///
+/// Owned by the health service loop, which records proxy failures, keeps
+/// probes from re-admitting ejected backends and re-probes them once their
+/// ejection ends.
+#[derive(Debug, Default)]
+pub struct OutlierDetector {
+    /// State of each backend that failed at least once
+    backends: HashMap<BackendId, OutlierState>,
+}
+
+impl OutlierDetector {
+    /// Record a proxy failure of `backend` at `now_ms` and eject it if its
+    /// error rate within the window reaches `outlier_error_percent` over at
+    /// least `outlier_min_connections` connections
+    ///
+    /// The backend is not ejected while `max_ejection_percent` of the backends
+    /// in `routing` are (at least one may always be), nor when no other
+    /// backend is healthy.
+    pub fn record_failure(
+        &mut self,
+        backend: &Arc<Backend>,
+        now_ms: u64,
+        config: &HealthConfig,
+        routing: &RouteTable,
+    ) -> OutlierVerdict {
+        if !config.outlier_ejection_enabled() {
+            return OutlierVerdict::Tolerated;
+        }
+        let max_ejected =
+            (routing.len() * config.max_ejection_percent as usize / 100).max(1);
+        let ejected = self.ejected_count(now_ms);
+        let state = self.state(backend);
+        match state.ejected_until_ms {
+            Some(until) if until > now_ms => return OutlierVerdict::Tolerated,
+            // Over, though not handed back yet
+            Some(until) => state.readmit(until),
+            None => {}
+        }
+
+        state.failures.push_back(now_ms);
+        state
+            .completed
+            .push_back((now_ms, backend.connections_completed()));
+        if let Some(window_start) = now_ms.checked_sub(config.outlier_window_millis) {
+            while state.failures.front().is_some_and(|at| *at <= window_start) {
+                state.failures.pop_front();
+            }
+            while state
+                .completed
+                .get(1)
+                .is_some_and(|(at, _)| *at <= window_start)
+            {
+                state.completed.pop_front();
+            }
+        }
+        let failures = state.failures.len() as u64;
+        let completed = match (state.completed.front(), state.completed.back()) {
+            (Some((_, first)), Some((_, last))) => last.saturating_sub(*first),
+            _ => 0,
+        };
+        let connections = failures + completed;
+        if connections < u64::from(config.outlier_min_connections)
+            || failures * 100 < connections * u64::from(config.outlier_error_percent)
+        {
+            return OutlierVerdict::Tolerated;
+        }
+
+        if ejected >= max_ejected {
+            return OutlierVerdict::Skipped(EjectionSkipped::MaxEjectionPercent);
+        }
+        let others_healthy = routing
+            .healthy_backends()
+            .iter()
+            .any(|other| other.id() != backend.id());
+        if !others_healthy {
+            return OutlierVerdict::Skipped(EjectionSkipped::LastHealthy);
+        }
+
+        // Back in rotation for as long as it was last out: start over
+        if now_ms.saturating_sub(state.readmitted_at_ms) >= state.last_ejection_ms {
+            state.ejections = 0;
+        }
+        let doublings = state.ejections.min(MAX_EJECTION_DOUBLINGS);
+        let interval_ms = config.base_ejection_millis.saturating_mul(1 << doublings);
+        state.ejections += 1;
+        state.last_ejection_ms = interval_ms;
+        state.ejected_until_ms = Some(now_ms.saturating_add(interval_ms));
+        state.failures.clear();
+        state.completed.clear();
+        OutlierVerdict::Ejected(Duration::from_millis(interval_ms))
+    }
+
+    /// Check whether `backend_id` is ejected at `now_ms`
+    pub fn is_ejected(&self, backend_id: BackendId, now_ms: u64) -> bool {
+        self.backends
+            .get(&backend_id)
+            .and_then(|state| state.ejected_until_ms)
+            .is_some_and(|until| until > now_ms)
+    }
+
+    /// Get the number of backends ejected at `now_ms`
+    pub fn ejected_count(&self, now_ms: u64) -> usize {
+        self.backends
+            .keys()
+            .filter(|id| self.is_ejected(**id, now_ms))
+            .count()
+    }
+
+    /// Get the earliest end of a running ejection, in milliseconds
+    pub fn next_readmission(&self) -> Option<u64> {
+        self.backends
+            .values()
+            .filter_map(|state| state.ejected_until_ms)
+            .min()
+    }
+
+    /// End the ejections over at `now_ms` and return their backends
+    pub fn take_readmitted(&mut self, now_ms: u64) -> Vec<Arc<Backend>> {
+        let mut readmitted = Vec::new();
+        for state in self.backends.values_mut() {
+            if let Some(until) = state.ejected_until_ms
+                && until <= now_ms
+            {
+                state.readmit(until);
+                readmitted.push(state.backend.clone());
+            }
+        }
+        readmitted.sort_by_key(|backend| backend.id());
+        readmitted
+    }
+
+    /// Forget the backends no longer in `routing` (removed or replaced)
+    pub fn retain_current(&mut self, routing: &RouteTable) {
+        self.backends.retain(|id, state| {
+            routing
+                .get(*id)
+                .is_some_and(|current| Arc::ptr_eq(&current, &state.backend))
+        });
+    }
+
+    /// State of `backend`, started over if it replaced a tracked backend
+    fn state(&mut self, backend: &Arc<Backend>) -> &mut OutlierState {
+        let state = self
+            .backends
+            .entry(backend.id())
+            .or_insert_with(|| OutlierState::new(backend.clone()));
+        if !Arc::ptr_eq(&state.backend, backend) {
+            *state = OutlierState::new(backend.clone());
+        }
+        state
+    }
+}
+
+impl OutlierState {
+    /// State of a backend never ejected
+    fn new(backend: Arc<Backend>) -> Self {
+        Self {
+            backend,
+            failures: VecDeque::new(),
+            completed: VecDeque::new(),
+            ejected_until_ms: None,
+            ejections: 0,
+            last_ejection_ms: 0,
+            readmitted_at_ms: 0,
+        }
+    }
+
+    /// End the ejection, as of `at_ms`
+    fn readmit(&mut self, at_ms: u64) {
+        self.ejected_until_ms = None;
+        self.readmitted_at_ms = at_ms;
+    }
+}
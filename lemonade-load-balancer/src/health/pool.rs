@@ -0,0 +1,50 @@
+//! Probe connection pool module
+//!
+//! Keep-alive connections HTTP checks reuse between probes, so a probe
+//! measures the request rather than a TCP handshake each time. At most one
+//! idle connection is kept per probed endpoint (backend address and source
+//! address), as probes of an endpoint never overlap.
+use crate::prelude::*;
+
+/// Endpoint a probe connects to: backend address and source address
+pub type ProbeEndpoint = (Arc<BackendAddress>, Option<IpAddr>);
+
+/// Idle keep-alive connections of HTTP checks
+#[derive(Debug, Default)]
+pub struct ProbeConnectionPool {
+    /// Idle connection of each endpoint
+    idle: DashMap<ProbeEndpoint, BackendStream>,
+}
+
+impl ProbeConnectionPool {
+    /// Take the idle connection to `endpoint`, if any
+    pub fn checkout(&self, endpoint: &ProbeEndpoint) -> Option<BackendStream> {
+        self.idle.remove(endpoint).map(|(_, stream)| stream)
+    }
+
+    /// Keep `stream` for the next probe of `endpoint`
+    pub fn checkin(&self, endpoint: ProbeEndpoint, stream: BackendStream) {
+        self.idle.insert(endpoint, stream);
+    }
+
+    /// Close the idle connections to `address`, returning how many were open
+    pub fn close(&self, address: &BackendAddress) -> usize {
+        let before = self.idle.len();
+        self.idle
+            .retain(|(pooled, _), _| pooled.as_ref() != address);
+        before - self.idle.len()
+    }
+
+    /// Close the idle connections to addresses `routed` rejects, returning
+    /// how many were open
+    pub fn retain_routed(&self, routed: impl Fn(&BackendAddress) -> bool) -> usize {
+        let before = self.idle.len();
+        self.idle.retain(|(address, _), _| routed(address));
+        before - self.idle.len()
+    }
+
+    /// Number of idle connections
+    pub fn idle_connections(&self) -> usize {
+        self.idle.len()
+    }
+}
@@ -0,0 +1,113 @@
+//! Probe schedule module
+//!
+//! Spreads periodic probes over the health check interval instead of firing
+//! them all at once. Each backend's first probe is staggered by an offset
+//! derived from its id, and every later interval is stretched or shortened by
+//! up to `jitter_percent`. Both are deterministic per backend id.
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// Offset of a backend's probes into each health check interval
+///
+/// Backend ids are bit-reversed, so any number of consecutive ids spreads
+/// evenly over the interval: ids 0 and 1 probe half an interval apart, ids 0
+/// to 3 a quarter apart, and so on.
+pub fn stagger_offset(backend_id: BackendId, interval: Duration) -> Duration {
+    let interval_ms = interval.as_millis() as u64;
+    Duration::from_millis(interval_ms * backend_id.reverse_bits() as u64 / 256)
+}
+
+/// Interval before probe `round` of a backend, off `interval` by at most
+/// `jitter_percent` percent either way
+pub fn jittered_interval(
+    backend_id: BackendId,
+    round: u64,
+    interval: Duration,
+    jitter_percent: u8,
+) -> Duration {
+    let interval_ms = interval.as_millis() as u64;
+    let spread_ms = interval_ms * jitter_percent as u64 / 100;
+    if spread_ms == 0 {
+        return interval;
+    }
+    // Uniform over [interval - spread, interval + spread]
+    let roll = mix((backend_id as u64) << 56 ^ round) % (2 * spread_ms + 1);
+    Duration::from_millis(interval_ms - spread_ms + roll)
+}
+
+/// SplitMix64 finalizer
+fn mix(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+/// Next periodic probe of one backend
+#[derive(Debug, Clone, Copy)]
+struct ScheduledProbe {
+    /// When the probe is due, in milliseconds
+    due_ms: u64,
+    /// Probes scheduled so far
+    round: u64,
+}
+
+/// When each backend is probed next
+///
+/// Owned by the health service loop, keyed by the first backend of each
+/// group of backends probed together.
+#[derive(Debug, Default)]
+pub struct ProbeSchedule {
+    /// Next probe of each scheduled backend
+    probes: HashMap<BackendId, ScheduledProbe>,
+}
+
+impl ProbeSchedule {
+    /// Schedule the first probe of the backends in `backend_ids` not
+    /// scheduled yet, and forget the backends missing from it
+    ///
+    /// A first probe is due at the next time the backend's stagger offset
+    /// comes around after `now_ms` (a full interval for offset zero).
+    pub fn sync(&mut self, backend_ids: &[BackendId], now_ms: u64, interval: Duration) {
+        self.probes.retain(|id, _| backend_ids.contains(id));
+        for &id in backend_ids {
+            self.probes.entry(id).or_insert_with(|| {
+                let offset = match stagger_offset(id, interval) {
+                    Duration::ZERO => interval,
+                    offset => offset,
+                };
+                ScheduledProbe {
+                    due_ms: now_ms.saturating_add(offset.as_millis() as u64),
+                    round: 0,
+                }
+            });
+        }
+    }
+
+    /// Get the earliest probe due, in milliseconds
+    pub fn next_due(&self) -> Option<u64> {
+        self.probes.values().map(|probe| probe.due_ms).min()
+    }
+
+    /// Take the backends due at `now_ms`, in id order, and schedule their
+    /// next probe one jittered interval later
+    pub fn take_due(
+        &mut self,
+        now_ms: u64,
+        interval: Duration,
+        jitter_percent: u8,
+    ) -> Vec<BackendId> {
+        let mut due = Vec::new();
+        for (&id, probe) in self.probes.iter_mut() {
+            if probe.due_ms > now_ms {
+                continue;
+            }
+            probe.round += 1;
+            let next = jittered_interval(id, probe.round, interval, jitter_percent);
+            probe.due_ms = now_ms.saturating_add(next.as_millis() as u64);
+            due.push(id);
+        }
+        due.sort_unstable();
+        due
+    }
+}
@@ -1,21 +1,39 @@
 //! Lemonade Load Balancer Library
 //!
 
+pub(crate) mod admin;
 pub(crate) mod app;
+pub(crate) mod audit;
 pub(crate) mod config;
+pub(crate) mod consistency;
 pub(crate) mod health;
 pub(crate) mod metrics;
 pub(crate) mod proxy;
-pub(crate) mod strategy;
+pub(crate) mod state;
 pub(crate) mod types;
 
+pub mod api;
 pub mod error;
+#[cfg(feature = "test-util")]
 pub mod prelude;
+#[cfg(not(feature = "test-util"))]
+pub(crate) mod prelude;
+pub mod strategy;
 pub use app::App;
 
+use lemonade_observability::BuildInfo;
 use prelude::*;
 use std::{path::PathBuf, sync::Arc};
 
+/// Build information of the load balancer, with its enabled cargo features
+pub fn build_info() -> BuildInfo {
+    let mut features = Vec::new();
+    if cfg!(feature = "test-util") {
+        features.push("test-util");
+    }
+    BuildInfo::new(env!("CARGO_PKG_VERSION")).with_features(features)
+}
+
 /// Run the load balancer with the given configuration
 ///
 /// # Arguments
@@ -24,10 +42,27 @@ use std::{path::PathBuf, sync::Arc};
 /// # Returns
 /// * `Ok(())` if the load balancer ran successfully
 /// * `Err(Box<dyn std::error::Error>)` if there was an error
-#[tracing::instrument(skip_all, fields(service.name = "lemonade-load-balancer", config.file = ?config_file))]
 pub async fn run(config_file: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    run_with_overrides(config_file, ServiceOverrides::default()).await
+}
+
+/// Run the load balancer with some background services disabled
+///
+/// # Arguments
+/// * `config_file` - Optional path to config file for hot-reloading
+/// * `overrides` - Services to disable on top of the config's `services`
+///
+/// # Returns
+/// * `Ok(())` if the load balancer ran successfully
+/// * `Err(Box<dyn std::error::Error>)` if there was an error
+#[tracing::instrument(skip_all, fields(service.name = "lemonade-load-balancer", config.file = ?config_file))]
+pub async fn run_with_overrides(
+    config_file: Option<PathBuf>,
+    overrides: ServiceOverrides,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Load config
-    let config = ConfigBuilder::from_file(config_file.as_deref())?;
+    let mut config = ConfigBuilder::from_file(config_file.as_deref())?;
+    overrides.apply(&mut config.services);
 
     // Initialize tracing with load balancer service name and package version
     // OTLP config comes from environment variables (OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_EXPORTER_OTLP_PROTOCOL)
@@ -48,37 +83,104 @@ pub async fn run(config_file: Option<PathBuf>) -> Result<(), Box<dyn std::error:
         config.otlp_protocol.as_deref(),
     )?;
 
-    // Create context from config (context-first initialization)
-    let ctx = Arc::new(Context::new(config.clone())?);
-
-    // Create services (they don't need initial config, they get it from context)
-    let config_service: Arc<dyn ConfigService> = if config.source == ConfigSource::File {
-        Arc::new(NotifyConfigService::new(config_file)?)
-    } else {
-        Arc::new(StaticConfigService::new())
-    };
-
-    let health_config = Arc::new(ArcSwap::from_pointee(config.health.clone()));
-    let health_service: Arc<dyn HealthService> =
-        Arc::new(BackendHealthService::new(health_config)?);
-
-    let metrics_config = Arc::new(ArcSwap::from_pointee(config.metrics.clone()));
-    let metrics_service: Arc<dyn MetricsService> =
-        Arc::new(AggregatingMetricsService::new(metrics_config)?);
-
-    let proxy_config = Arc::new(ArcSwap::from_pointee(config.proxy.clone()));
-    let proxy_service: Arc<dyn ProxyService> =
-        Arc::new(TokioProxyService::new(proxy_config)?);
-
-    // Create and run app
-    let app = App::new(
-        config_service,
-        health_service,
-        metrics_service,
-        proxy_service,
-    )
+    // Create one context per backend group (context-first initialization)
+    let groups = Arc::new(Groups::new(&config)?);
+    groups.forward_shutdown();
+
+    // The first group's app watches the config file and hosts the admin API;
+    // every group gets its own proxy, health and metrics services
+    let mut apps = Vec::new();
+    for (index, (_, ctx)) in groups.iter().enumerate() {
+        let primary = index == 0;
+        let group_config = ctx.config();
+
+        // Create services (they don't need initial config, they get it from context)
+        let config_service: Arc<dyn ConfigService> = if primary
+            && config.source == ConfigSource::File
+            && config.services.config_watch
+        {
+            Arc::new(
+                NotifyConfigService::new(config_file.clone())?
+                    .with_groups(groups.clone()),
+            )
+        } else {
+            Arc::new(StaticConfigService::new())
+        };
+
+        let health_config = Arc::new(ArcSwap::from_pointee(group_config.health.clone()));
+        let health_service: Arc<dyn HealthService> = if config.services.health {
+            Arc::new(BackendHealthService::new(health_config)?)
+        } else {
+            Arc::new(NoopHealthService::new())
+        };
+
+        let metrics_config =
+            Arc::new(ArcSwap::from_pointee(group_config.metrics.clone()));
+        let metrics_service: Arc<dyn MetricsService> = if config.services.metrics {
+            Arc::new(AggregatingMetricsService::new(metrics_config)?)
+        } else {
+            Arc::new(NoopMetricsService::new())
+        };
+
+        let proxy_config = Arc::new(ArcSwap::from_pointee(group_config.proxy.clone()));
+        let proxy_service: Arc<dyn ProxyService> =
+            Arc::new(TokioProxyService::new(proxy_config)?);
+
+        // Create app
+        let mut app = App::new(
+            config_service,
+            health_service,
+            metrics_service,
+            proxy_service,
+        )
+        .await;
+        if primary && config.admin.enabled {
+            app = app.with_admin_server(
+                AdminServer::new(config_file.clone()).with_groups(groups.clone()),
+            );
+        }
+        if primary {
+            app = app
+                .with_state_file(StateFileWriter::new().with_groups(groups.clone()))
+                .with_health_state_file(
+                    HealthStateWriter::new().with_groups(groups.clone()),
+                );
+        }
+        if primary && config.health_endpoint.listen_address.is_some() {
+            app = app.with_health_endpoint(
+                HealthEndpointServer::new().with_groups(groups.clone()),
+            );
+        }
+        apps.push((app, ctx.clone()));
+    }
+
+    // Run the other groups in the background and the first on this task
+    let mut apps = apps.into_iter();
+    let (primary_app, primary_ctx) = apps.next().ok_or("no backend group to serve")?;
+    // Exporters are process-wide, so only the first group reloads them
+    tokio::spawn(OtlpReloader::run(primary_ctx.clone()));
+    let mut others = tokio::task::JoinSet::new();
+    for (app, ctx) in apps {
+        others.spawn(async move { app.run(ctx).await });
+    }
+    let result: Result<(), Box<dyn std::error::Error>> = async {
+        let primary_result = primary_app.run(primary_ctx).await;
+        while let Some(result) = others.join_next().await {
+            result??;
+        }
+        primary_result?;
+        Ok(())
+    }
+    .await;
+
+    // Export what is still buffered before the process exits
+    let flushed = tokio::task::spawn_blocking(|| {
+        lemonade_observability::flush_exporters().map_err(|e| e.to_string())
+    })
     .await;
-    app.run(ctx).await?;
+    if let Ok(Err(e)) = flushed {
+        tracing::warn!("Failed to flush telemetry: {}", e);
+    }
 
-    Ok(())
+    result
 }
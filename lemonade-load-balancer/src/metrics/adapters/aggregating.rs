@@ -9,7 +9,6 @@ use crate::prelude::*;
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Aggregating metrics service implementation
 pub struct AggregatingMetricsService {
@@ -28,19 +27,53 @@ impl AggregatingMetricsService {
     pub fn new(config: Arc<ArcSwap<MetricsConfig>>) -> Result<Self, MetricsError> {
         Ok(Self { config })
     }
+}
+
+/// Export the selection trace counts of `backend` added since the last flush
+fn export_selection_exclusions(backend: &Backend) {
+    let exclusions = backend.take_unexported_exclusions();
+    if exclusions.is_empty() {
+        return;
+    }
+    let timings =
+        lemonade_observability::get_connection_metrics("lemonade-load-balancer");
+    for (reason, count) in exclusions {
+        timings.record_selection_exclusions(backend.id(), reason.as_str(), count);
+    }
+}
 
-    /// Get current time in milliseconds
-    fn now_ms() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64
+/// Export the picks that chose `backend` since the last flush
+fn export_selections(backend: &Backend) {
+    let selections = backend.take_unexported_selections();
+    if selections > 0 {
+        lemonade_observability::get_connection_metrics("lemonade-load-balancer")
+            .record_backend_selections(backend.id(), selections);
+    }
+}
+
+/// Export the connects in flight to `backend`
+fn export_pending_connects(backend: &Backend) {
+    lemonade_observability::get_connection_metrics("lemonade-load-balancer")
+        .record_pending_connects(backend.id(), backend.pending_connects() as u64);
+}
+
+/// Flush the metrics of every backend in `routing` at `now_ms`: update their
+/// timestamps, sampled latency percentiles and byte rates, and export their
+/// counters
+fn flush_all(routing: &RouteTable, now_ms: u64) {
+    for backend in routing.all_backends() {
+        backend.update_metrics_timestamp(now_ms);
+        backend.flush_latency_percentiles();
+        backend.flush_byte_rates(now_ms);
+        export_selection_exclusions(&backend);
+        export_selections(&backend);
+        export_pending_connects(&backend);
     }
 }
 
 #[async_trait]
 impl MetricsService for AggregatingMetricsService {
-    #[tracing::instrument(skip(self, ctx), fields(service.name = "lemonade-load-balancer", service.type = "metrics"))]
+    #[tracing::instrument(skip(self, ctx), fields(service.name = "lemonade-load-balancer", service.type = "metrics", lb.group = %ctx.group()))]
     async fn collect_metrics(&self, ctx: Arc<Context>) {
         tracing::info!("Starting metrics service");
 
@@ -53,7 +86,10 @@ impl MetricsService for AggregatingMetricsService {
 
         // Get initial config
         let initial_config = self.config.load();
-        let mut interval = tokio::time::interval(initial_config.interval);
+        let clock = ctx.clock();
+        let mut next_flush = clock.sleep(initial_config.interval);
+        // Expired events since the last flush, reported in one line
+        let mut expired_since_flush = 0u64;
 
         loop {
             tokio::select! {
@@ -63,6 +99,16 @@ impl MetricsService for AggregatingMetricsService {
                 }
 
                 event = metrics_rx.recv() => {
+                    // Drop events that waited too long in the queue (e.g. a
+                    // backlog built up while this task was stalled) so they
+                    // do not skew current-window stats
+                    if let Some(at_micros) = event.as_ref().and_then(MetricsEvent::at_micros)
+                        && clock.is_expired(at_micros, self.config.load().max_event_age_millis)
+                    {
+                        ctx.channels().record_metrics_event_expired();
+                        expired_since_flush += 1;
+                        continue;
+                    }
                     match event {
                         Some(MetricsEvent::ConnectionOpened { .. }) => {
                             // Connection opened - no action needed, connection count tracked in backend
@@ -70,24 +116,48 @@ impl MetricsService for AggregatingMetricsService {
                         Some(MetricsEvent::ConnectionClosed {
                             backend_id,
                             duration_micros,
+                            connect_micros,
+                            ttfb_micros,
+                            bytes_in,
+                            bytes_out,
+                            close_reason,
                             ..
                         }) => {
+                            // Forced closes are counted even once the drained
+                            // backend has left the route table
+                            if close_reason.is_forced() {
+                                let timings = lemonade_observability::get_connection_metrics("lemonade-load-balancer");
+                                timings.record_forced_close(backend_id, close_reason.as_str());
+                            } else if close_reason.is_abort() {
+                                let timings = lemonade_observability::get_connection_metrics("lemonade-load-balancer");
+                                timings.record_aborted(backend_id, close_reason.as_str());
+                            }
+
                             // Record connection metrics
                             let routing = ctx.routing_table();
                             if let Some(backend) = routing.get(backend_id) {
-                                // Record as a request (connection duration as latency)
+                                // Record as a request (connection duration as latency);
+                                // only a backend abort counts as the backend's error
                                 let latency_ms = duration_micros / 1000;
-                                backend.record_request(latency_ms, false);
+                                backend.record_request(
+                                    latency_ms,
+                                    close_reason == CloseReason::BackendAborted,
+                                );
+                                backend.record_connection_timings(connect_micros, ttfb_micros, duration_micros);
+                                backend.record_bytes(bytes_in, bytes_out);
 
                                 // Export to OpenTelemetry (each connection = one request from client perspective)
                                 let metrics = lemonade_observability::get_http_metrics("lemonade-load-balancer");
                                 metrics.record_request("PROXY", "/", 200, duration_micros);
+                                let timings = lemonade_observability::get_connection_metrics("lemonade-load-balancer");
+                                timings.record(backend_id, connect_micros, ttfb_micros, duration_micros);
                             }
                         }
                         Some(MetricsEvent::RequestCompleted {
                             backend_id,
                             latency_micros,
                             status_code,
+                            ..
                         }) => {
                             // Record successful request
                             let routing = ctx.routing_table();
@@ -116,23 +186,45 @@ impl MetricsService for AggregatingMetricsService {
                                 metrics.record_request("PROXY", "/", 500, latency_micros);
                             }
                         }
-                        Some(MetricsEvent::FlushSnapshot) | None => {
-                            // Update metrics timestamps for all backends
+                        Some(MetricsEvent::HedgeResolved {
+                            primary_backend_id,
+                            hedge_backend_id,
+                            hedge_won,
+                            ..
+                        }) => {
+                            // Hedge counters live on the hedge target
                             let routing = ctx.routing_table();
-                            let now_ms = Self::now_ms();
-                            for backend in routing.all_backends() {
-                                backend.update_metrics_timestamp(now_ms);
+                            if let Some(backend) = routing.get(hedge_backend_id) {
+                                backend.record_hedge(hedge_won);
                             }
+                            tracing::debug!(
+                                primary_backend_id,
+                                hedge_backend_id,
+                                hedge_won,
+                                "Hedged connect resolved"
+                            );
+                        }
+                        Some(MetricsEvent::RetrySuppressed { backend_id, kind, .. }) => {
+                            let timings = lemonade_observability::get_connection_metrics("lemonade-load-balancer");
+                            timings.record_retry_suppressed(backend_id, kind.as_str());
+                        }
+                        Some(MetricsEvent::FlushSnapshot) | None => {
+                            flush_all(&ctx.routing_table(), clock.now_millis());
                         }
                     }
                 }
 
-                _ = interval.tick() => {
-                    // Periodically update metrics timestamps
-                    let routing = ctx.routing_table();
-                    let now_ms = Self::now_ms();
-                    for backend in routing.all_backends() {
-                        backend.update_metrics_timestamp(now_ms);
+                _ = &mut next_flush => {
+                    // Periodically update metrics timestamps, sampled
+                    // latency percentiles and byte rates
+                    next_flush = clock.sleep(self.config.load().interval);
+                    flush_all(&ctx.routing_table(), clock.now_millis());
+                    if expired_since_flush > 0 && ctx.should_log("metrics.expired_events") {
+                        tracing::warn!(
+                            "Discarded {} expired metrics events ({} total)",
+                            std::mem::take(&mut expired_since_flush),
+                            ctx.channels().metrics_events_expired()
+                        );
                     }
                     tracing::debug!("Metrics timestamps updated");
                 }
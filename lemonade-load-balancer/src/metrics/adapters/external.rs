@@ -1,18 +1,113 @@
 //! External implementation of MetricsService
 //!
-//! Fetches metrics from external sources (Prometheus/OpenTelemetry OTLP)
+//! Polls each backend's `/stats` endpoint for the resource usage workers
+//! report about themselves (CPU and resident memory) and stores it on the
+//! backend, where it shows up in [`BackendMetrics`] for strategies.
 
 use crate::metrics::error::MetricsError;
 use crate::metrics::port::MetricsService;
 use crate::prelude::*;
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
+use serde::Deserialize;
 use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Path of the worker stats endpoint
+pub const WORKER_STATS_PATH: &str = "/stats";
+
+/// Largest stats response read from a worker
+const MAX_STATS_RESPONSE_BYTES: u64 = 64 * 1024;
+
+/// Resource usage reported by a worker on `/stats`
+///
+/// Other fields of the response are ignored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct WorkerStats {
+    /// CPU usage, in percent of one core
+    #[serde(default)]
+    pub cpu_percent: Option<f64>,
+    /// Resident memory, in bytes
+    #[serde(default)]
+    pub rss_bytes: Option<u64>,
+}
+
+impl WorkerStats {
+    /// Parse the JSON body of a `/stats` response
+    pub fn parse(body: &[u8]) -> Result<Self, MetricsError> {
+        serde_json::from_slice(body)
+            .map_err(|e| MetricsError::WorkerStats(format!("invalid stats body: {}", e)))
+    }
+
+    /// Parse a raw HTTP/1.x `/stats` response (status line, headers and body)
+    pub fn parse_response(response: &[u8]) -> Result<Self, MetricsError> {
+        let header_end = response
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .ok_or_else(|| MetricsError::WorkerStats("truncated response".to_string()))?;
+        let head = String::from_utf8_lossy(&response[..header_end]);
+        let status = head
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or_default();
+        if status != "200" {
+            return Err(MetricsError::WorkerStats(format!(
+                "unexpected status {}",
+                status
+            )));
+        }
+        Self::parse(&response[header_end + 4..])
+    }
+
+    /// Fetch the stats of `backend`, giving up after `timeout`
+    pub async fn fetch(
+        backend: &Backend,
+        default_source: Option<IpAddr>,
+        timeout: Duration,
+    ) -> Result<Self, MetricsError> {
+        let address = backend.address();
+        let source = backend.source_address(default_source);
+        let exchange = async {
+            let mut stream = address
+                .connect_from(source)
+                .await
+                .map_err(|e| MetricsError::WorkerStats(e.to_string()))?;
+            // HTTP/1.0 so the body is never chunked; a Unix socket has no
+            // host to name
+            let host = match address.unix() {
+                Some(_) => "localhost",
+                None => address.as_str(),
+            };
+            let request = format!(
+                "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+                WORKER_STATS_PATH, host
+            );
+            stream
+                .write_all(request.as_bytes())
+                .await
+                .map_err(|e| MetricsError::WorkerStats(e.to_string()))?;
+            let mut response = Vec::new();
+            stream
+                .take(MAX_STATS_RESPONSE_BYTES)
+                .read_to_end(&mut response)
+                .await
+                .map_err(|e| MetricsError::WorkerStats(e.to_string()))?;
+            Self::parse_response(&response)
+        };
+        tokio::time::timeout(timeout, exchange)
+            .await
+            .map_err(|_| MetricsError::WorkerStats("timed out".to_string()))?
+    }
+}
 
 /// External metrics service implementation
+///
+/// Every metrics interval, fetches `/stats` from all backends concurrently
+/// (each within the metrics timeout). A backend that fails to answer has its
+/// previous reading cleared so stale usage does not bias strategies.
 pub struct ExternalMetricsService {
     /// Metrics configuration (reference to global config's metrics slice)
-    #[allow(dead_code)]
     config: Arc<ArcSwap<MetricsConfig>>,
 }
 
@@ -27,14 +122,69 @@ impl ExternalMetricsService {
     pub fn new(config: Arc<ArcSwap<MetricsConfig>>) -> Result<Self, MetricsError> {
         Ok(Self { config })
     }
+
+    /// Fetch the stats of every backend once and record them
+    ///
+    /// Returns the number of backends that reported.
+    pub async fn poll_workers(&self, ctx: &Context) -> usize {
+        let timeout = self.config.load().timeout;
+        let default_source = ctx.config().proxy.backend_bind_address;
+        let mut fetches = tokio::task::JoinSet::new();
+        for backend in ctx.routing_table().all_backends() {
+            fetches.spawn(async move {
+                let stats = WorkerStats::fetch(&backend, default_source, timeout).await;
+                (backend, stats)
+            });
+        }
+
+        let mut reported = 0;
+        while let Some(joined) = fetches.join_next().await {
+            let Ok((backend, stats)) = joined else {
+                continue;
+            };
+            match stats {
+                Ok(stats) => {
+                    backend.set_resource_usage(stats.cpu_percent, stats.rss_bytes);
+                    reported += 1;
+                }
+                Err(e) => {
+                    tracing::debug!(
+                        "Failed to fetch stats from backend {}: {}",
+                        backend.id(),
+                        e
+                    );
+                    backend.set_resource_usage(None, None);
+                }
+            }
+        }
+        reported
+    }
 }
 
 #[async_trait]
 impl MetricsService for ExternalMetricsService {
     async fn collect_metrics(&self, ctx: Arc<Context>) {
-        // TODO: Implement periodic fetching from external sources (Prometheus/OTLP)
-        // For now, just wait for shutdown
         let mut shutdown_rx = ctx.channels().shutdown_rx();
-        let _ = shutdown_rx.recv().await;
+        let mut period = self.config.load().interval;
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("External metrics service received shutdown signal");
+                    break;
+                }
+
+                _ = interval.tick() => {
+                    self.poll_workers(&ctx).await;
+                    let configured = self.config.load().interval;
+                    if configured != period {
+                        period = configured;
+                        interval = tokio::time::interval(period);
+                        interval.reset();
+                    }
+                }
+            }
+        }
     }
 }
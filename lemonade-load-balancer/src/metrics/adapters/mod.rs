@@ -3,6 +3,8 @@
 
 mod aggregating;
 mod external;
+mod noop;
 
 pub use aggregating::AggregatingMetricsService;
-pub use external::ExternalMetricsService;
+pub use external::{ExternalMetricsService, WORKER_STATS_PATH, WorkerStats};
+pub use noop::NoopMetricsService;
@@ -0,0 +1,26 @@
+//! No-op implementation of MetricsService
+//!
+//! Stands in for metrics aggregation when `services.metrics` is disabled.
+
+use crate::prelude::*;
+
+/// Metrics service that never aggregates metrics events
+///
+/// The proxy stops sending events once metrics are disabled on the
+/// context, so there is nothing to drain.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopMetricsService;
+
+impl NoopMetricsService {
+    /// Create a new NoopMetricsService
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl MetricsService for NoopMetricsService {
+    async fn collect_metrics(&self, _ctx: Arc<Context>) {
+        tracing::info!("Metrics aggregation disabled");
+    }
+}
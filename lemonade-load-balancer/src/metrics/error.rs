@@ -6,4 +6,7 @@ pub enum MetricsError {
     /// Internal error
     #[error("Internal error: {0}")]
     Internal(String),
+    /// Fetching or parsing a worker's `/stats` response failed
+    #[error("Worker stats error: {0}")]
+    WorkerStats(String),
 }
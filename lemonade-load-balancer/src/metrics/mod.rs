@@ -4,4 +4,6 @@
 pub mod adapters;
 pub mod error;
 pub mod models;
+pub mod otlp_reloader;
 pub mod port;
+pub mod weight_controller;
@@ -4,14 +4,96 @@ use crate::prelude::*;
 use serde::{Deserialize, Serialize};
 
 /// Metrics config struct
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MetricsConfig {
     /// Metrics collection interval
-    #[serde(with = "crate::config::serde_helpers")]
+    #[serde(rename = "interval_millis", with = "crate::config::serde_helpers")]
     pub interval: Duration,
     /// Metrics collection timeout
-    #[serde(with = "crate::config::serde_helpers")]
+    #[serde(rename = "timeout_millis", with = "crate::config::serde_helpers")]
     pub timeout: Duration,
+    /// Events older than this when processed are discarded as expired
+    /// instead of being folded into current stats, in milliseconds (0 keeps
+    /// every event)
+    #[serde(default = "default_max_event_age_millis")]
+    pub max_event_age_millis: u64,
+    /// How per-backend connection latencies are aggregated into percentiles
+    #[serde(default)]
+    pub aggregation: LatencyAggregation,
+    /// Count why strategies skip each backend at selection time (see
+    /// [`trace_exclusions`](crate::strategy::trace::trace_exclusions))
+    #[serde(default)]
+    pub selection_trace: bool,
+}
+
+/// Default for [`MetricsConfig::max_event_age_millis`]
+fn default_max_event_age_millis() -> u64 {
+    30_000
+}
+
+/// Latency aggregation mode
+///
+/// Read when a backend is added: backends already routed keep their mode
+/// across reloads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LatencyAggregation {
+    /// Fixed-bucket histograms; percentiles are bucket upper bounds
+    #[default]
+    Histogram,
+    /// Fixed-size reservoir of sampled latencies; percentiles are computed
+    /// from the samples on each metrics flush
+    Reservoir,
+}
+
+impl std::str::FromStr for LatencyAggregation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "histogram" => Ok(Self::Histogram),
+            "reservoir" => Ok(Self::Reservoir),
+            _ => Err(format!(
+                "unknown latency aggregation {} (expected histogram or reservoir)",
+                s
+            )),
+        }
+    }
+}
+
+/// Auto-weight controller config struct
+///
+/// Tunes how effective weights follow each backend's latency relative to the
+/// pool median when `auto_weight` is enabled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoWeightConfig {
+    /// Adjustment interval
+    #[serde(rename = "interval_millis", with = "crate::config::serde_helpers")]
+    pub interval: Duration,
+    /// Lowest multiplier applied to a configured weight
+    pub min_multiplier: f64,
+    /// Highest multiplier applied to a configured weight
+    pub max_multiplier: f64,
+    /// Largest multiplier change per adjustment
+    pub max_step: f64,
+    /// Fraction of the gap to the target multiplier closed per adjustment
+    pub damping: f64,
+    /// Relative latency band around the median treated as "at median"
+    pub tolerance: f64,
+}
+
+impl Default for AutoWeightConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            min_multiplier: 0.2,
+            max_multiplier: 1.0,
+            max_step: 0.1,
+            damping: 0.5,
+            tolerance: 0.1,
+        }
+    }
 }
 
 /// Metrics event enum
@@ -30,10 +112,20 @@ pub enum MetricsEvent {
         backend_id: u8,
         /// Duration in microseconds
         duration_micros: u64,
+        /// Backend connect time in microseconds
+        connect_micros: u64,
+        /// Time to the first backend byte in microseconds (None if the backend sent nothing)
+        ttfb_micros: Option<u64>,
         /// Bytes in
         bytes_in: u64,
         /// Bytes out
         bytes_out: u64,
+        /// Why the connection was closed
+        close_reason: CloseReason,
+        /// Client of the connection
+        peer: PeerInfo,
+        /// Timestamp
+        at_micros: u64,
     },
     /// A proxied request finished
     RequestCompleted {
@@ -43,6 +135,8 @@ pub enum MetricsEvent {
         latency_micros: u64,
         /// Status code
         status_code: u16,
+        /// Timestamp
+        at_micros: u64,
     },
     /// A proxied request failed
     RequestFailed {
@@ -52,11 +146,66 @@ pub enum MetricsEvent {
         latency_micros: u64,
         /// Error class
         error_class: MetricsErrorClass,
+        /// Timestamp
+        at_micros: u64,
+    },
+    /// A hedged connect finished with one of the two attempts connected
+    HedgeResolved {
+        /// Backend ID picked by the strategy
+        primary_backend_id: u8,
+        /// Backend ID raced as the hedge
+        hedge_backend_id: u8,
+        /// Whether the hedge attempt won the race
+        hedge_won: bool,
+        /// Timestamp
+        at_micros: u64,
+    },
+    /// An extra backend attempt was skipped because the retry budget was spent
+    RetrySuppressed {
+        /// Backend ID of the original attempt
+        backend_id: u8,
+        /// Kind of attempt skipped
+        kind: RetryKind,
+        /// Timestamp
+        at_micros: u64,
     },
     /// Periodic snapshot trigger (internal tick)
     FlushSnapshot,
 }
 
+impl MetricsEvent {
+    /// When the event happened, in microseconds since the Unix epoch
+    ///
+    /// `None` for internal ticks, which never expire.
+    pub fn at_micros(&self) -> Option<u64> {
+        match self {
+            Self::ConnectionOpened { at_micros, .. }
+            | Self::ConnectionClosed { at_micros, .. }
+            | Self::RequestCompleted { at_micros, .. }
+            | Self::RequestFailed { at_micros, .. }
+            | Self::HedgeResolved { at_micros, .. }
+            | Self::RetrySuppressed { at_micros, .. } => Some(*at_micros),
+            Self::FlushSnapshot => None,
+        }
+    }
+}
+
+/// Kind of extra backend attempt drawn from the retry budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryKind {
+    /// Connect raced against a slow primary
+    Hedge,
+}
+
+impl RetryKind {
+    /// Kind name, as used in metric attributes
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hedge => "hedge",
+        }
+    }
+}
+
 /// Metrics error class enum
 #[derive(Debug, Clone, Copy)]
 pub enum MetricsErrorClass {
@@ -0,0 +1,71 @@
+//! OTLP reloader module
+//!
+//! Points the process's span and metric exporters at a new OTLP endpoint
+//! when a config reload changes `otlp_endpoint` or `otlp_protocol`.
+use crate::prelude::*;
+
+/// OTLP exporter reloader
+pub struct OtlpReloader;
+
+impl OtlpReloader {
+    /// Reconfigure the exporters after each migration that changes the OTLP
+    /// settings, until shutdown
+    ///
+    /// Exporters are process-wide: run this for one group only.
+    pub async fn run(ctx: Arc<Context>) {
+        let mut shutdown_rx = ctx.channels().shutdown_rx();
+        let mut config_rx = ctx.channels().config_rx();
+        let mut current = Self::settings(&ctx.config());
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("OTLP reloader received shutdown signal");
+                    break;
+                }
+
+                event = config_rx.recv() => {
+                    match event {
+                        Ok(ConfigEvent::Migrated)
+                        | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Ok(_) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                    let settings = Self::settings(&ctx.config());
+                    if settings == current {
+                        continue;
+                    }
+                    current = settings.clone();
+                    Self::reconfigure(settings).await;
+                }
+            }
+        }
+    }
+
+    /// OTLP endpoint and protocol of a config
+    fn settings(config: &Config) -> (Option<String>, Option<String>) {
+        (config.otlp_endpoint.clone(), config.otlp_protocol.clone())
+    }
+
+    /// Swap the exporters (flushing the previous ones blocks)
+    async fn reconfigure((endpoint, protocol): (Option<String>, Option<String>)) {
+        tracing::info!(
+            otlp.endpoint = ?endpoint,
+            otlp.protocol = ?protocol,
+            "Reconfiguring OTLP exporters"
+        );
+        let result = tokio::task::spawn_blocking(move || {
+            lemonade_observability::reconfigure_exporters(
+                endpoint.as_deref(),
+                protocol.as_deref(),
+            )
+            .map_err(|e| e.to_string())
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::error!("Failed to reconfigure OTLP exporters: {}", e),
+            Err(e) => tracing::error!("OTLP reconfiguration task failed: {}", e),
+        }
+    }
+}
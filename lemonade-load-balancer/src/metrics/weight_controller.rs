@@ -0,0 +1,231 @@
+//! Weight controller module
+//!
+//! Feedback controller that scales each backend's effective weight from its
+//! recent latency relative to the pool median (auto-weight mode).
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// Multiplier changes smaller than this are not applied
+const MIN_MULTIPLIER_CHANGE: f64 = 0.001;
+
+/// Auto-weight controller
+///
+/// Each adjustment round compares every backend's p95 response latency over
+/// the last window against the pool median and moves its weight multiplier a
+/// damped, bounded step towards `median / latency` (clamped to the configured
+/// range). Backends back within the tolerance band of the median converge
+/// back to their configured weight.
+pub struct WeightController {
+    /// Controller tuning
+    config: AutoWeightConfig,
+    /// Totals seen at the previous round, per backend
+    last_totals: HashMap<BackendId, WindowStart>,
+}
+
+/// Totals of a backend at the start of an adjustment window
+struct WindowStart {
+    /// Total requests
+    requests: u64,
+    /// Total request latency in milliseconds
+    latency_ms: u64,
+    /// Connection timing histograms
+    timings: ConnectionTimings,
+}
+
+impl WeightController {
+    /// Create a new weight controller
+    pub fn new(config: AutoWeightConfig) -> Self {
+        Self {
+            config,
+            last_totals: HashMap::new(),
+        }
+    }
+
+    /// Replace the controller tuning
+    pub fn set_config(&mut self, config: AutoWeightConfig) {
+        self.config = config;
+    }
+
+    /// Run one adjustment round over the route table
+    ///
+    /// Returns the backends whose multiplier changed, with the new value.
+    pub fn adjust(&mut self, routing: &RouteTable) -> Vec<(BackendId, f64)> {
+        self.retain_backends(routing);
+        let backends = routing.all_backends();
+
+        // p95 latency over the last window (backends without traffic are skipped)
+        let mut samples: Vec<(Arc<Backend>, f64)> = Vec::new();
+        for backend in backends {
+            let (requests, latency_ms) = backend.request_totals();
+            let metrics = backend.metrics_snapshot();
+            let start = WindowStart {
+                requests,
+                latency_ms,
+                timings: metrics.timings.clone(),
+            };
+            let previous = self.last_totals.insert(backend.id(), start);
+            if let Some(previous) = previous
+                && requests > previous.requests
+                && latency_ms >= previous.latency_ms
+            {
+                let average = (latency_ms - previous.latency_ms) as f64
+                    / (requests - previous.requests) as f64;
+                samples.push((backend, Self::window_p95(&metrics, &previous, average)));
+            }
+        }
+
+        // Nothing to compare against
+        if samples.len() < 2 {
+            return Vec::new();
+        }
+
+        let median = Self::median(samples.iter().map(|(_, latency)| *latency).collect());
+        let mut changes = Vec::new();
+        for (backend, latency) in samples {
+            let current = backend.weight_multiplier();
+            let target = self.target_multiplier(latency, median);
+            let next = self.step(current, target);
+            if (next - current).abs() < MIN_MULTIPLIER_CHANGE {
+                continue;
+            }
+
+            backend.set_weight_multiplier(next);
+            tracing::info!(
+                "Auto-weight: backend {} multiplier {:.3} -> {:.3} (p95 {:.1}ms, median {:.1}ms)",
+                backend.id(),
+                current,
+                next,
+                latency,
+                median
+            );
+            changes.push((backend.id(), next));
+        }
+        changes
+    }
+
+    /// Forget the totals of backends no longer in `routing`
+    ///
+    /// Returns the number of entries evicted.
+    pub fn retain_backends(&mut self, routing: &RouteTable) -> usize {
+        let before = self.last_totals.len();
+        self.last_totals.retain(|id, _| routing.contains(*id));
+        before - self.last_totals.len()
+    }
+
+    /// Reset every backend to its configured weight
+    pub fn reset(&mut self, routing: &RouteTable) {
+        self.last_totals.clear();
+        for backend in routing.all_backends() {
+            if (backend.weight_multiplier() - 1.0).abs() >= MIN_MULTIPLIER_CHANGE {
+                tracing::info!(
+                    "Auto-weight: backend {} reset to configured weight",
+                    backend.id()
+                );
+                backend.set_weight_multiplier(1.0);
+            }
+        }
+    }
+
+    /// Run the controller until shutdown
+    ///
+    /// The controller idles (and resets multipliers) while `auto_weight` is
+    /// disabled, so the feature can be toggled by a config reload.
+    pub async fn run(ctx: Arc<Context>) {
+        let mut shutdown_rx = ctx.channels().shutdown_rx();
+        let initial_config = ctx.config();
+        let mut controller = Self::new(initial_config.auto_weight_tuning.clone());
+        let mut interval = tokio::time::interval(controller.config.interval);
+        let mut active = false;
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("Weight controller received shutdown signal");
+                    break;
+                }
+
+                _ = interval.tick() => {
+                    let config = ctx.config();
+                    let routing = ctx.routing_table();
+                    if !config.auto_weight {
+                        if active {
+                            controller.reset(&routing);
+                            active = false;
+                        }
+                        continue;
+                    }
+
+                    active = true;
+                    if config.auto_weight_tuning != controller.config {
+                        if config.auto_weight_tuning.interval != controller.config.interval {
+                            interval = tokio::time::interval(config.auto_weight_tuning.interval);
+                        }
+                        controller.set_config(config.auto_weight_tuning.clone());
+                    }
+                    let evicted = controller.retain_backends(&routing);
+                    ctx.record_stale_entries_evicted("weight_controller", evicted);
+                    controller.adjust(&routing);
+                }
+            }
+        }
+    }
+
+    /// p95 response latency of a backend since `start`
+    ///
+    /// Diffs the connection timing histograms against the window start, so
+    /// a backend that recovered is not held back by its earlier samples.
+    /// Request totals carry no distribution, so backends without timings
+    /// fall back to their window average.
+    fn window_p95(metrics: &BackendMetrics, start: &WindowStart, average: f64) -> f64 {
+        let window = BackendMetrics {
+            avg_latency_ms: average,
+            p95_latency_ms: average,
+            timings: ConnectionTimings {
+                connect: metrics.timings.connect.since(&start.timings.connect),
+                ttfb: metrics.timings.ttfb.since(&start.timings.ttfb),
+                total: metrics.timings.total.since(&start.timings.total),
+            },
+            ..BackendMetrics::default()
+        };
+        window.response_p95_latency_ms()
+    }
+
+    /// Target multiplier for a backend latency given the pool median
+    fn target_multiplier(&self, latency: f64, median: f64) -> f64 {
+        if median <= 0.0 || (latency - median).abs() <= median * self.config.tolerance {
+            return 1.0;
+        }
+        if latency <= 0.0 {
+            return self.config.max_multiplier;
+        }
+        (median / latency).clamp(self.config.min_multiplier, self.config.max_multiplier)
+    }
+
+    /// Damped, bounded step from the current multiplier towards the target
+    fn step(&self, current: f64, target: f64) -> f64 {
+        let delta = ((target - current) * self.config.damping)
+            .clamp(-self.config.max_step, self.config.max_step);
+        let next = current + delta;
+        // Snap to the target once close enough to avoid endless tiny steps
+        let next = if (target - next).abs() < MIN_MULTIPLIER_CHANGE {
+            target
+        } else {
+            next
+        };
+        next.clamp(
+            self.config.min_multiplier,
+            self.config.max_multiplier.max(1.0),
+        )
+    }
+
+    /// Median of a non-empty list of latencies
+    fn median(mut values: Vec<f64>) -> f64 {
+        values.sort_by(|a, b| a.total_cmp(b));
+        let mid = values.len() / 2;
+        if values.len().is_multiple_of(2) {
+            (values[mid - 1] + values[mid]) / 2.0
+        } else {
+            values[mid]
+        }
+    }
+}
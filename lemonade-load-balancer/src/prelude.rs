@@ -1,18 +1,41 @@
 //! Prelude module
 //!
+//! Glob re-exports of the crate internals for use inside the crate. It is
+//! public only with the `test-util` feature, for the integration tests and
+//! benches; embedders use [`crate::api`].
+#![cfg_attr(not(feature = "test-util"), allow(unused_imports))]
 
 // Re-export internal types for convenience
 pub use crate::{
+    // Admin module
+    admin::{auth::*, error::*, models::*, server::*},
+    // Audit module
+    audit::{log::*, models::*},
     // Config module
-    config::{builder::*, error::*, impls::*, models::*, port::*},
+    config::{
+        builder::*, compat::*, diff::*, error::*, impls::*, models::*, port::*,
+        secrets::*,
+    },
+    // Consistency audit module
+    consistency::{checker::*, models::*},
     // Health module
-    health::{adapters::*, error::*, models::*, port::*},
+    health::{
+        adapters::*, endpoint::*, error::*, history::*, models::*, outlier::*, pool::*,
+        port::*, schedule::*,
+    },
     // Metrics module
-    metrics::{adapters::*, error::*, models::*, port::*},
+    metrics::{
+        adapters::*, error::*, models::*, otlp_reloader::*, port::*, weight_controller::*,
+    },
     // Proxy module
     proxy::{adapters::*, error::*, models::*, port::*},
+    // State file module
+    state::{health::*, models::*, writer::*},
     // Strategy module
-    strategy::{adapters::*, builder::*, constants::*, error::*, models::*, port::*},
+    strategy::{
+        adapters::*, builder::*, constants::*, error::*, models::*, port::*,
+        registry::StrategyFactory, trace::*,
+    },
     // Common types module
     types::*,
 };
@@ -22,8 +45,8 @@ pub use crate::{
 // Async traits
 pub use async_trait::async_trait;
 
-// SocketAddr for network addresses
-pub use std::net::SocketAddr;
+// SocketAddr and IpAddr for network addresses
+pub use std::net::{IpAddr, SocketAddr};
 // Arc for shared references
 pub use std::sync::Arc;
 // Atomic types
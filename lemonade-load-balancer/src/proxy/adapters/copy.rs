@@ -0,0 +1,244 @@
+//! Copy module
+//!
+//! One direction of the bidirectional proxy copy, with optional write
+//! coalescing. Bytes read but not yet written never exceed the configured
+//! buffered-bytes cap: once it is reached the copy stops reading from the
+//! fast side until the slow side accepts the pending data.
+//!
+//! Read and write errors are kept on the outcome so the proxy can tell a
+//! client that went away from a backend that died mid-transfer.
+
+use crate::proxy::models::CoalesceConfig;
+use crate::types::CloseReason;
+use bytes::BytesMut;
+use std::io;
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Read buffer size for a single copy direction
+const READ_BUFFER_SIZE: usize = 8192;
+
+/// Result of copying one direction of a connection
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CopyOutcome {
+    /// Bytes successfully written to the peer
+    pub bytes: u64,
+    /// When the first byte was read (None if the source sent nothing)
+    pub first_read_at: Option<Instant>,
+    /// Most bytes held at once (read but not yet written)
+    pub max_buffered: usize,
+    /// Times reading paused because the writer was not ready for pending data
+    pub backpressure_events: u64,
+    /// Error that stopped reading from the source, if any
+    pub read_error: Option<io::ErrorKind>,
+    /// Error that stopped writing to the peer, if any
+    pub write_error: Option<io::ErrorKind>,
+}
+
+/// Check whether `kind` means the other end reset or dropped the connection
+fn is_abort(kind: io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        io::ErrorKind::ConnectionReset
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionAborted
+    )
+}
+
+/// Classify how a connection ended from the outcomes of its two copies
+///
+/// `sent` copies from the client to the backend and `received` from the
+/// backend to the client. A reset on the backend socket wins over one on
+/// the client socket, since it is the one worth alerting on; any other end
+/// is [`CloseReason::Normal`].
+pub fn classify_close(sent: &CopyOutcome, received: &CopyOutcome) -> CloseReason {
+    let aborted = |error: Option<io::ErrorKind>| error.is_some_and(is_abort);
+    if aborted(sent.write_error) || aborted(received.read_error) {
+        CloseReason::BackendAborted
+    } else if aborted(sent.read_error) || aborted(received.write_error) {
+        CloseReason::ClientAborted
+    } else {
+        CloseReason::Normal
+    }
+}
+
+/// Copy from `reader` to `writer` until EOF or an error on either side
+///
+/// Pending data is always flushed before the writer is shut down, so a
+/// half-close is forwarded only after everything read has been written. At
+/// most `max_buffered_bytes` (at least one) are held between a read and the
+/// matching write.
+pub async fn copy_stream<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    coalesce: &CoalesceConfig,
+    max_buffered_bytes: usize,
+) -> CopyOutcome
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let max_buffered_bytes = max_buffered_bytes.max(1);
+    let outcome = if coalesce.enabled {
+        copy_coalesced(reader, writer, coalesce, max_buffered_bytes).await
+    } else {
+        copy_direct(reader, writer, max_buffered_bytes).await
+    };
+    let _ = writer.shutdown().await;
+    outcome
+}
+
+/// Write every read straight through
+async fn copy_direct<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    max_buffered_bytes: usize,
+) -> CopyOutcome
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut outcome = CopyOutcome::default();
+    let mut buf = vec![0u8; READ_BUFFER_SIZE.min(max_buffered_bytes)];
+    loop {
+        match reader.read(&mut buf).await {
+            Ok(0) => break, // EOF
+            Ok(n) => {
+                outcome.first_read_at.get_or_insert_with(Instant::now);
+                outcome.max_buffered = outcome.max_buffered.max(n);
+                if write_all(writer, &buf[..n], &mut outcome).await.is_err() {
+                    break;
+                }
+                outcome.bytes += n as u64;
+            }
+            Err(e) => {
+                outcome.read_error = Some(e.kind());
+                break;
+            }
+        }
+    }
+    outcome
+}
+
+/// Accumulate reads and flush on the size threshold or the coalesce timeout
+///
+/// The size threshold is capped by `max_buffered_bytes`, and reads never
+/// take more than the room left under the cap.
+async fn copy_coalesced<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    coalesce: &CoalesceConfig,
+    max_buffered_bytes: usize,
+) -> CopyOutcome
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let max_bytes = coalesce.max_bytes.clamp(1, max_buffered_bytes);
+    let delay = std::time::Duration::from_micros(coalesce.coalesce_micros);
+    let mut outcome = CopyOutcome::default();
+    let mut pending = BytesMut::with_capacity(max_bytes);
+    let mut buf = [0u8; READ_BUFFER_SIZE];
+    // Deadline for the oldest pending byte
+    let mut flush_at: Option<tokio::time::Instant> = None;
+
+    loop {
+        // Pending stays below `max_bytes` between flushes, so there is room
+        let room = READ_BUFFER_SIZE.min(max_buffered_bytes - pending.len());
+        let read = match flush_at {
+            None => reader.read(&mut buf[..room]).await,
+            Some(deadline) => {
+                tokio::select! {
+                    read = reader.read(&mut buf[..room]) => read,
+                    _ = tokio::time::sleep_until(deadline) => {
+                        if flush(writer, &mut pending, &mut outcome).await.is_err() {
+                            return outcome;
+                        }
+                        flush_at = None;
+                        continue;
+                    }
+                }
+            }
+        };
+
+        match read {
+            Ok(0) => break, // EOF
+            Err(e) => {
+                outcome.read_error = Some(e.kind());
+                break;
+            }
+            Ok(n) => {
+                outcome.first_read_at.get_or_insert_with(Instant::now);
+                pending.extend_from_slice(&buf[..n]);
+                outcome.max_buffered = outcome.max_buffered.max(pending.len());
+                if pending.len() >= max_bytes {
+                    if flush(writer, &mut pending, &mut outcome).await.is_err() {
+                        return outcome;
+                    }
+                    flush_at = None;
+                } else if flush_at.is_none() {
+                    flush_at = Some(tokio::time::Instant::now() + delay);
+                }
+            }
+        }
+    }
+
+    // Flush what is left before the caller shuts the writer down
+    let _ = flush(writer, &mut pending, &mut outcome).await;
+    outcome
+}
+
+/// Write all pending bytes, counting them once written
+async fn flush<W>(
+    writer: &mut W,
+    pending: &mut BytesMut,
+    outcome: &mut CopyOutcome,
+) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    if pending.is_empty() {
+        return Ok(());
+    }
+    write_all(writer, pending, outcome).await?;
+    outcome.bytes += pending.len() as u64;
+    pending.clear();
+    Ok(())
+}
+
+/// Write all of `data`, counting a backpressure event if the writer is not
+/// ready for it right away and recording the error that stops it
+async fn write_all<W>(
+    writer: &mut W,
+    mut data: &[u8],
+    outcome: &mut CopyOutcome,
+) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut waited = false;
+    while !data.is_empty() {
+        let written = tokio::select! {
+            biased;
+            written = writer.write(data) => match written {
+                Ok(written) => written,
+                Err(e) => {
+                    outcome.write_error = Some(e.kind());
+                    return Err(e);
+                }
+            },
+            // Dropping a pending write is cancel safe: nothing was written
+            _ = std::future::ready(()), if !waited => {
+                outcome.backpressure_events += 1;
+                waited = true;
+                continue;
+            }
+        };
+        if written == 0 {
+            outcome.write_error = Some(io::ErrorKind::WriteZero);
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        data = &data[written..];
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Deadline module
+//!
+//! Deadline propagation to backends: the first request head of a cleartext
+//! HTTP/1 connection is forwarded with a [`DEADLINE_HEADER`] field saying
+//! how many milliseconds of its time budget are left, and the status line of
+//! the backend's first response is watched to tell whether the backend gave
+//! up. Later requests on the connection are not framed, so they are passed
+//! through unchanged.
+
+use super::http_head::{parse_status_line, replace_header_field};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// Request header field carrying the deadline, in milliseconds
+pub const DEADLINE_HEADER: &str = "X-Lemonade-Deadline-Ms";
+
+/// Response status of a backend that gave up on a request's deadline
+pub const DEADLINE_EXCEEDED_STATUS: u16 = 504;
+
+/// Longest response status line watched for, in bytes
+const MAX_STATUS_LINE_BYTES: usize = 1024;
+
+/// Rewrite the request head starting `prelude` to carry a deadline of
+/// `remaining_ms`, leaving the bytes after the head untouched
+///
+/// A deadline the client sent is replaced by the shorter of it and
+/// `remaining_ms`, so a client can tighten its deadline but not extend it.
+/// Returns `None` when `prelude` does not start with a complete head.
+pub fn with_deadline_header(prelude: &[u8], remaining_ms: u64) -> Option<Vec<u8>> {
+    replace_header_field(prelude, DEADLINE_HEADER, |sent| {
+        sent.iter()
+            .filter_map(|value| value.parse::<u64>().ok())
+            .fold(remaining_ms, u64::min)
+            .to_string()
+    })
+}
+
+/// Reader passing a backend's response through while watching for the
+/// status code of its first status line
+///
+/// At most [`MAX_STATUS_LINE_BYTES`] are kept aside; a response that does not
+/// start with an HTTP/1 status line within them has no status.
+#[derive(Debug)]
+pub struct ResponseStatusTap<R> {
+    /// Backend side of the connection
+    inner: R,
+    /// Start of the response, until its status line ends
+    head: Vec<u8>,
+    /// Status code, once the status line was read
+    status: Option<u16>,
+    /// Whether the status line was read or given up on
+    settled: bool,
+}
+
+impl<R> ResponseStatusTap<R> {
+    /// Watch the response read from `inner`
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            head: Vec::new(),
+            status: None,
+            settled: false,
+        }
+    }
+
+    /// Status code of the response, if its status line was read
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    /// Keep aside the start of `read`, settling once the status line ends,
+    /// the cap is reached or the response ended (`read` empty)
+    fn observe(&mut self, read: &[u8]) {
+        let take = read.len().min(MAX_STATUS_LINE_BYTES - self.head.len());
+        self.head.extend_from_slice(&read[..take]);
+        if let Some(end) = self.head.iter().position(|&b| b == b'\n') {
+            self.status = parse_status_line(&self.head[..end]);
+            self.settled = true;
+        } else if read.is_empty() || self.head.len() >= MAX_STATUS_LINE_BYTES {
+            self.settled = true;
+        }
+        if self.settled {
+            self.head = Vec::new();
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ResponseStatusTap<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if !self.settled && matches!(poll, Poll::Ready(Ok(()))) {
+            self.observe(&buf.filled()[before..]);
+        }
+        poll
+    }
+}
@@ -0,0 +1,99 @@
+//! File descriptor budget module
+//!
+//! Every proxied connection holds two sockets (client and backend), so the
+//! process soft limit on open files caps how many connections can be served.
+//! The budget keeps `proxy.fd_headroom` descriptors back for health probes,
+//! the admin API and metrics export and derives the connection ceiling from
+//! the rest.
+
+use std::time::Duration;
+
+/// Sockets held by one proxied connection
+const FDS_PER_CONNECTION: u64 = 2;
+
+/// How long accepting pauses after running out of file descriptors, unless a
+/// connection closes first
+pub const FD_EXHAUSTED_PAUSE: Duration = Duration::from_secs(1);
+
+/// Connection ceiling derived from the process file descriptor limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FdBudget {
+    /// Soft limit on open files (`None` when it cannot be read)
+    soft_limit: Option<u64>,
+}
+
+impl FdBudget {
+    /// Budget for a known soft limit
+    pub fn new(soft_limit: Option<u64>) -> Self {
+        Self { soft_limit }
+    }
+
+    /// Budget for this process's current soft limit
+    pub fn detect() -> Self {
+        Self::new(soft_fd_limit())
+    }
+
+    /// Soft limit on open files
+    pub fn soft_limit(&self) -> Option<u64> {
+        self.soft_limit
+    }
+
+    /// Most connections the limit supports after keeping `headroom`
+    /// descriptors back (`None` when the limit is unknown)
+    pub fn ceiling(&self, headroom: u64) -> Option<u64> {
+        self.soft_limit
+            .map(|limit| limit.saturating_sub(headroom) / FDS_PER_CONNECTION)
+    }
+
+    /// Connection cap to enforce: the configured `max_connections`, lowered
+    /// to the ceiling when unset or higher
+    pub fn max_connections(&self, configured: Option<u64>, headroom: u64) -> Option<u64> {
+        match (configured, self.ceiling(headroom)) {
+            (Some(configured), Some(ceiling)) => Some(configured.min(ceiling)),
+            (configured, ceiling) => configured.or(ceiling),
+        }
+    }
+
+    /// Warning for a configured `max_connections` the limit cannot support
+    pub fn warning(&self, configured: Option<u64>, headroom: u64) -> Option<String> {
+        let configured = configured?;
+        let ceiling = self.ceiling(headroom)?;
+        (configured > ceiling).then(|| {
+            format!(
+                "max_connections {} exceeds the {} connections the open file limit \
+                 of {} supports ({} descriptors kept back, {} per connection); \
+                 capping at {}",
+                configured,
+                ceiling,
+                self.soft_limit.unwrap_or_default(),
+                headroom,
+                FDS_PER_CONNECTION,
+                ceiling
+            )
+        })
+    }
+}
+
+/// Check whether an accept error means the process or system ran out of
+/// file descriptors (`EMFILE`/`ENFILE`)
+pub fn is_fd_exhausted(error: &std::io::Error) -> bool {
+    // EMFILE and ENFILE on Linux and the BSDs
+    matches!(error.raw_os_error(), Some(23 | 24))
+}
+
+/// Read the soft limit on open files from `/proc/self/limits`
+///
+/// Returns `None` where procfs is unavailable or the limit is unlimited.
+fn soft_fd_limit() -> Option<u64> {
+    let limits = std::fs::read_to_string("/proc/self/limits").ok()?;
+    parse_soft_fd_limit(&limits)
+}
+
+/// Parse the soft limit out of a `/proc/<pid>/limits` table
+pub fn parse_soft_fd_limit(limits: &str) -> Option<u64> {
+    limits
+        .lines()
+        .find_map(|line| line.strip_prefix("Max open files"))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|soft| soft.parse().ok())
+}
@@ -0,0 +1,83 @@
+//! Hedge module
+//!
+//! Helpers for hedged backend connects: the hedge rate budget, the hedge
+//! delay and the choice of the second backend
+
+use crate::prelude::*;
+use crate::proxy::models::HedgingConfig;
+
+/// Caps the fraction of connections that may hedge
+#[derive(Debug, Default)]
+pub struct HedgeBudget {
+    /// Connections seen with hedging enabled
+    connections: AtomicU64,
+    /// Connections that hedged
+    hedges: AtomicU64,
+}
+
+impl HedgeBudget {
+    /// Count a connection eligible for hedging
+    pub fn record_connection(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Take a hedge if that keeps hedges within `max_rate` of the connections
+    pub fn try_hedge(&self, max_rate: f64) -> bool {
+        let connections = self.connections.load(Ordering::Relaxed);
+        self.hedges
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |hedges| {
+                ((hedges + 1) as f64 <= max_rate * connections as f64)
+                    .then_some(hedges + 1)
+            })
+            .is_ok()
+    }
+
+    /// Give back a hedge taken with [`HedgeBudget::try_hedge`] but not started
+    pub fn cancel_hedge(&self) {
+        let _ = self
+            .hedges
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |hedges| {
+                hedges.checked_sub(1)
+            });
+    }
+
+    /// Totals as (connections, hedges)
+    pub fn totals(&self) -> (u64, u64) {
+        (
+            self.connections.load(Ordering::Relaxed),
+            self.hedges.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// How long to wait on the primary connect before hedging
+///
+/// With `adaptive_delay` the primary's p95 connect time is used once it has
+/// samples; otherwise the static `delay_millis`.
+pub fn hedge_delay(primary: &Backend, hedging: &HedgingConfig) -> Duration {
+    let adaptive_ms = hedging
+        .adaptive_delay
+        .then(|| primary.connect_p95_ms())
+        .flatten();
+    match adaptive_ms {
+        Some(ms) => Duration::from_secs_f64(ms / 1000.0),
+        None => Duration::from_millis(hedging.delay_millis),
+    }
+}
+
+/// Second backend to race against `primary_id`
+///
+/// Picks the least loaded other backend of the `selector` group that
+/// accepts new connections, in the preferred priority tier (backups are
+/// not hedged to while a primary is healthy).
+pub fn pick_hedge_backend(
+    routing: &RouteTable,
+    primary_id: BackendId,
+    selector: &LabelSelector,
+) -> Option<Arc<Backend>> {
+    routing
+        .preferred_backends_matching(selector)
+        .into_iter()
+        .filter(|backend| backend.id() != primary_id)
+        .min_by_key(|backend| backend.active_connections())
+}
@@ -0,0 +1,36 @@
+//! Hop module
+//!
+//! Proxy loop detection for cleartext HTTP/1 connections: the first request
+//! head is forwarded with a [`HOP_HEADER`] field counting the load balancers
+//! it passed through, and a request past `max_hops` is answered
+//! [`LOOP_DETECTED_RESPONSE`] instead of being proxied.
+
+use super::http_head::replace_header_field;
+
+/// Request header field counting the load balancers a request passed through
+pub const HOP_HEADER: &str = "X-Lemonade-Hop";
+
+/// Answer to a request that passed through more than `max_hops` load
+/// balancers
+pub const LOOP_DETECTED_RESPONSE: &[u8] =
+    b"HTTP/1.1 508 Loop Detected\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+/// Rewrite the request head starting `prelude` to count one more hop,
+/// returning it with the hop count it now carries
+///
+/// Counts on from the highest hop count the client sent (none, or only
+/// malformed ones, count as 0). Returns `None` when `prelude` does not start
+/// with a complete head.
+pub fn with_hop_header(prelude: &[u8]) -> Option<(Vec<u8>, u32)> {
+    let mut hops = 0;
+    let rewritten = replace_header_field(prelude, HOP_HEADER, |sent| {
+        hops = sent
+            .iter()
+            .filter_map(|value| value.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0)
+            .saturating_add(1);
+        hops.to_string()
+    })?;
+    Some((rewritten, hops))
+}
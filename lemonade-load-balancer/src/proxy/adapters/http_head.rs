@@ -0,0 +1,218 @@
+//! HTTP head module
+//!
+//! Reads the start of a cleartext connection to find the request line and
+//! header fields of its first HTTP/1 request, for strategies that pick by
+//! request. The bytes read are handed back so they can be replayed to the
+//! backend. Also parses HTTP/1 response status lines.
+
+use crate::types::{RequestHeadLimits, RequestMeta};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Longest request method accepted
+const MAX_METHOD_LEN: usize = 16;
+
+/// Answer to a request no HTTP routing rule matches
+pub const NOT_FOUND_RESPONSE: &[u8] =
+    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+/// Result of looking for a request head in the start of a connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestHeadParse {
+    /// A complete request head
+    Parsed(RequestMeta),
+    /// Starts like HTTP, but the head is malformed
+    Malformed,
+    /// Not an HTTP/1 request
+    NotHttp,
+    /// More bytes are needed to decide
+    Incomplete,
+}
+
+/// Parse the first request head from the first bytes of a connection
+///
+/// The head ends at the first empty line; bare `\n` line endings are
+/// accepted. Header values that are not UTF-8 are decoded lossily, so
+/// malformed input yields [`RequestHeadParse::Malformed`] or
+/// [`RequestHeadParse::NotHttp`] rather than a panic.
+pub fn parse_request_head(buf: &[u8]) -> RequestHeadParse {
+    // The method, in uppercase letters, must start the connection
+    let method_end = buf.iter().position(|&b| b == b' ');
+    let method = &buf[..method_end.unwrap_or(buf.len())];
+    if method_end == Some(0)
+        || method.len() > MAX_METHOD_LEN
+        || !method.iter().all(u8::is_ascii_uppercase)
+    {
+        return RequestHeadParse::NotHttp;
+    }
+    let Some(head_end) = find_head_end(buf) else {
+        return RequestHeadParse::Incomplete;
+    };
+
+    let mut lines = buf[..head_end]
+        .split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+    let Some(Ok(request_line)) = lines.next().map(std::str::from_utf8) else {
+        return RequestHeadParse::Malformed;
+    };
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(target), Some(version), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return RequestHeadParse::Malformed;
+    };
+    if target.is_empty() || !version.starts_with("HTTP/1.") {
+        return RequestHeadParse::Malformed;
+    }
+
+    let mut headers = Vec::new();
+    for line in lines.filter(|line| !line.is_empty()) {
+        let Some(colon) = line.iter().position(|&b| b == b':') else {
+            return RequestHeadParse::Malformed;
+        };
+        let (name, value) = (&line[..colon], &line[colon + 1..]);
+        if name.is_empty() || !name.iter().copied().all(is_token_byte) {
+            return RequestHeadParse::Malformed;
+        }
+        headers.push((
+            String::from_utf8_lossy(name).to_ascii_lowercase(),
+            String::from_utf8_lossy(value.trim_ascii()).into_owned(),
+        ));
+    }
+
+    RequestHeadParse::Parsed(RequestMeta {
+        method: method.to_string(),
+        path: request_path(target),
+        headers,
+    })
+}
+
+/// Read the start of a connection until its first request head is parsed,
+/// the connection stops looking like HTTP, `limits.max_bytes` are read or
+/// `limits.timeout` elapses
+///
+/// Returns the request head, if found, and every byte read, which must be
+/// replayed to the backend.
+pub async fn sniff_request_head<R>(
+    reader: &mut R,
+    limits: RequestHeadLimits,
+) -> (Option<RequestMeta>, Vec<u8>)
+where
+    R: AsyncRead + Unpin,
+{
+    let deadline = tokio::time::Instant::now() + limits.timeout;
+    let mut buf = Vec::with_capacity(limits.max_bytes.min(4096));
+    let mut chunk = [0u8; 4096];
+    while buf.len() < limits.max_bytes {
+        let want = chunk.len().min(limits.max_bytes - buf.len());
+        let read = match tokio::time::timeout_at(
+            deadline,
+            reader.read(&mut chunk[..want]),
+        )
+        .await
+        {
+            Ok(Ok(n)) if n > 0 => n,
+            _ => break, // EOF, read error or timeout
+        };
+        buf.extend_from_slice(&chunk[..read]);
+        match parse_request_head(&buf) {
+            RequestHeadParse::Incomplete => continue,
+            RequestHeadParse::Parsed(meta) => return (Some(meta), buf),
+            RequestHeadParse::Malformed | RequestHeadParse::NotHttp => break,
+        }
+    }
+    (None, buf)
+}
+
+/// Status code of an HTTP/1.x status line (`HTTP/1.1 200 OK`), without its
+/// line ending
+pub fn parse_status_line(line: &[u8]) -> Option<u16> {
+    let line = std::str::from_utf8(line).ok()?.trim_end_matches('\r');
+    let mut parts = line.splitn(3, ' ');
+    if !parts.next()?.starts_with("HTTP/1.") {
+        return None;
+    }
+    let code = parts.next()?;
+    if code.len() != 3 || !code.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    code.parse().ok()
+}
+
+/// Rewrite the request head starting `prelude` to carry exactly one `name`
+/// field, right after the request line, leaving the bytes after the head
+/// untouched
+///
+/// `value` is handed the trimmed values of the `name` fields the client sent,
+/// in order, and returns the one to send. Returns `None` when `prelude` does
+/// not start with a complete head.
+pub(crate) fn replace_header_field(
+    prelude: &[u8],
+    name: &str,
+    value: impl FnOnce(&[&str]) -> String,
+) -> Option<Vec<u8>> {
+    let head_end = find_head_end(prelude)?;
+    let mut lines = prelude[..head_end].split_inclusive(|&b| b == b'\n');
+    let request_line = lines.next()?;
+
+    let mut sent = Vec::new();
+    let mut fields = Vec::with_capacity(head_end);
+    for line in lines {
+        match field_value(line, name) {
+            Some(value) => sent.push(value),
+            None => fields.extend_from_slice(line),
+        }
+    }
+
+    let field = format!("{}: {}\r\n", name, value(&sent));
+    let mut rewritten = Vec::with_capacity(prelude.len() + field.len());
+    rewritten.extend_from_slice(request_line);
+    rewritten.extend_from_slice(field.as_bytes());
+    rewritten.extend_from_slice(&fields);
+    rewritten.extend_from_slice(&prelude[head_end..]);
+    Some(rewritten)
+}
+
+/// Trimmed value of header field `line` if it is named `name` (any case)
+fn field_value<'a>(line: &'a [u8], name: &str) -> Option<&'a str> {
+    let colon = line.iter().position(|&b| b == b':')?;
+    let (field, value) = (&line[..colon], &line[colon + 1..]);
+    field
+        .eq_ignore_ascii_case(name.as_bytes())
+        .then(|| std::str::from_utf8(value.trim_ascii()).ok())
+        .flatten()
+}
+
+/// Offset just past the empty line ending the head, if it was read
+fn find_head_end(buf: &[u8]) -> Option<usize> {
+    buf.iter().enumerate().find_map(|(i, &b)| {
+        if b != b'\n' {
+            return None;
+        }
+        match &buf[i + 1..] {
+            [b'\n', ..] => Some(i + 2),
+            [b'\r', b'\n', ..] => Some(i + 3),
+            _ => None,
+        }
+    })
+}
+
+/// Path of a request target, without the query string or fragment
+///
+/// Absolute-form targets (`http://host/path`) lose their scheme and
+/// authority.
+fn request_path(target: &str) -> String {
+    let target = ["http://", "https://"]
+        .iter()
+        .find_map(|scheme| target.strip_prefix(scheme))
+        .map_or(target, |rest| rest.find('/').map_or("/", |i| &rest[i..]));
+    target
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Whether `b` may appear in a header field name (an RFC 9110 token)
+fn is_token_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
@@ -1,6 +1,33 @@
 //! Proxy adapters module
 //!
 
+mod copy;
+mod deadline;
+mod fd_budget;
+mod hedge;
+mod hop;
+mod http_head;
+mod slow_log;
+mod sni;
 mod tokio_proxy;
 
+pub use copy::{CopyOutcome, classify_close, copy_stream};
+pub use deadline::{
+    DEADLINE_EXCEEDED_STATUS, DEADLINE_HEADER, ResponseStatusTap, with_deadline_header,
+};
+#[cfg(feature = "test-util")]
+pub use fd_budget::parse_soft_fd_limit;
+pub use fd_budget::{FD_EXHAUSTED_PAUSE, FdBudget, is_fd_exhausted};
+pub use hedge::{HedgeBudget, hedge_delay, pick_hedge_backend};
+pub use hop::{HOP_HEADER, LOOP_DETECTED_RESPONSE, with_hop_header};
+pub use http_head::{NOT_FOUND_RESPONSE, parse_status_line, sniff_request_head};
+#[cfg(feature = "test-util")]
+pub use http_head::{RequestHeadParse, parse_request_head};
+pub use slow_log::{SlowLog, SlowLogEntry};
+pub use sni::{ClientHelloInfo, sniff_client_hello};
+#[cfg(feature = "test-util")]
+pub use sni::{
+    ClientHelloParse, ClientHelloSni, parse_client_hello, parse_client_hello_sni,
+    sniff_sni,
+};
 pub use tokio_proxy::TokioProxyService;
@@ -0,0 +1,222 @@
+//! Slow log module
+//!
+//! Warns about slow backend connects, long-lived connections and slow
+//! strategy picks once they cross the thresholds in [`ProxyConfig`].
+//! Warnings are rate-limited per backend (per strategy for picks) so a
+//! struggling backend cannot flood the logs; the number of warnings dropped
+//! is reported with the next one let through.
+
+use crate::prelude::*;
+use crate::proxy::models::ProxyConfig;
+use dashmap::DashMap;
+use std::hash::Hash;
+use std::time::Duration;
+
+/// Length of a rate limit window
+const WINDOW_MILLIS: u64 = 60_000;
+
+/// Connection details included in slow warnings
+#[derive(Debug, Clone, Copy)]
+pub struct SlowLogEntry<'a> {
+    /// Proxy-assigned connection id
+    pub connection_id: u64,
+    /// Backend serving the connection
+    pub backend: &'a Backend,
+    /// Client address, if known
+    pub client: Option<SocketAddr>,
+    /// Measured duration
+    pub elapsed: Duration,
+}
+
+/// Rate-limited slow connect and slow connection warnings
+#[derive(Debug, Default)]
+pub struct SlowLog {
+    /// Slow connect warnings per backend in the current window
+    connects: RateLimiter,
+    /// Slow connection warnings per backend in the current window
+    connections: RateLimiter,
+    /// Slow pick warnings per strategy in the current window
+    picks: RateLimiter<String>,
+    /// Route table generation the per-backend windows were last swept at
+    swept_generation: AtomicU64,
+}
+
+impl SlowLog {
+    /// Forget the windows of backends that left the route table
+    ///
+    /// Cheap when the generation has not changed since the last sweep.
+    /// Returns the number of windows evicted.
+    pub fn sweep(&self, ctx: &Context) -> usize {
+        let generation = ctx.generation();
+        if self.swept_generation.swap(generation, Ordering::AcqRel) == generation {
+            return 0;
+        }
+        let live = ctx.routing_table().backend_ids();
+        let evicted = self.connects.retain(&live) + self.connections.retain(&live);
+        ctx.record_stale_entries_evicted("slow_log", evicted);
+        evicted
+    }
+
+    /// Number of per-backend rate limit windows held
+    #[cfg(feature = "test-util")]
+    pub fn backend_windows(&self) -> usize {
+        self.connects.windows.len() + self.connections.windows.len()
+    }
+
+    /// Warn if a backend connect exceeded `slow_connect_warn_ms`
+    ///
+    /// Returns whether a warning was logged.
+    pub fn check_connect(
+        &self,
+        config: &ProxyConfig,
+        now_ms: u64,
+        entry: SlowLogEntry<'_>,
+    ) -> bool {
+        let Some(threshold_ms) = config.slow_connect_warn_ms else {
+            return false;
+        };
+        if entry.elapsed < Duration::from_millis(threshold_ms) {
+            return false;
+        }
+        let Some(suppressed) = self.connects.admit(
+            entry.backend.id(),
+            now_ms,
+            config.slow_log_max_per_minute,
+        ) else {
+            return false;
+        };
+        tracing::warn!(
+            connection.id = entry.connection_id,
+            backend.id = entry.backend.id(),
+            backend.name = entry.backend.name().unwrap_or("unknown"),
+            backend.addr = %entry.backend.address(),
+            client.addr = ?entry.client,
+            duration_ms = entry.elapsed.as_millis() as u64,
+            threshold_ms,
+            suppressed,
+            "Slow backend connect"
+        );
+        true
+    }
+
+    /// Warn if a connection stayed open longer than `slow_connection_warn_secs`
+    ///
+    /// Returns whether a warning was logged.
+    pub fn check_connection(
+        &self,
+        config: &ProxyConfig,
+        now_ms: u64,
+        entry: SlowLogEntry<'_>,
+    ) -> bool {
+        let Some(threshold_secs) = config.slow_connection_warn_secs else {
+            return false;
+        };
+        if entry.elapsed < Duration::from_secs(threshold_secs) {
+            return false;
+        }
+        let Some(suppressed) = self.connections.admit(
+            entry.backend.id(),
+            now_ms,
+            config.slow_log_max_per_minute,
+        ) else {
+            return false;
+        };
+        tracing::warn!(
+            connection.id = entry.connection_id,
+            backend.id = entry.backend.id(),
+            backend.name = entry.backend.name().unwrap_or("unknown"),
+            backend.addr = %entry.backend.address(),
+            client.addr = ?entry.client,
+            duration_ms = entry.elapsed.as_millis() as u64,
+            threshold_secs,
+            suppressed,
+            "Slow connection"
+        );
+        true
+    }
+
+    /// Warn if a strategy pick exceeded `strategy_pick_warn_micros`
+    ///
+    /// Returns whether a warning was logged.
+    pub fn check_pick(
+        &self,
+        config: &ProxyConfig,
+        now_ms: u64,
+        strategy: &str,
+        elapsed: Duration,
+    ) -> bool {
+        let Some(threshold_micros) = config.strategy_pick_warn_micros else {
+            return false;
+        };
+        if elapsed < Duration::from_micros(threshold_micros) {
+            return false;
+        }
+        let Some(suppressed) = self.picks.admit(
+            strategy.to_string(),
+            now_ms,
+            config.slow_log_max_per_minute,
+        ) else {
+            return false;
+        };
+        tracing::warn!(
+            strategy,
+            duration_micros = elapsed.as_micros() as u64,
+            threshold_micros,
+            suppressed,
+            "Slow strategy pick"
+        );
+        true
+    }
+}
+
+/// Fixed one-minute windows counting warnings per key (backend by default)
+#[derive(Debug)]
+struct RateLimiter<K: Eq + Hash = BackendId> {
+    windows: DashMap<K, Window>,
+}
+
+impl<K: Eq + Hash> Default for RateLimiter<K> {
+    fn default() -> Self {
+        Self {
+            windows: DashMap::new(),
+        }
+    }
+}
+
+/// Warnings logged and dropped in the current window
+#[derive(Debug, Default)]
+struct Window {
+    start_ms: u64,
+    logged: u32,
+    suppressed: u64,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    /// Admit a warning for `key`
+    ///
+    /// Returns the number of warnings dropped since the last one admitted,
+    /// or `None` once `max_per_minute` have been logged in this window.
+    fn admit(&self, key: K, now_ms: u64, max_per_minute: u32) -> Option<u64> {
+        let mut window = self.windows.entry(key).or_insert_with(|| Window {
+            start_ms: now_ms,
+            ..Window::default()
+        });
+        if now_ms.saturating_sub(window.start_ms) >= WINDOW_MILLIS {
+            window.start_ms = now_ms;
+            window.logged = 0;
+        }
+        if window.logged >= max_per_minute {
+            window.suppressed += 1;
+            return None;
+        }
+        window.logged += 1;
+        Some(std::mem::take(&mut window.suppressed))
+    }
+
+    /// Drop the windows of keys not in `live`, returning how many were dropped
+    fn retain(&self, live: &[K]) -> usize {
+        let before = self.windows.len();
+        self.windows.retain(|key, _| live.contains(key));
+        before - self.windows.len()
+    }
+}
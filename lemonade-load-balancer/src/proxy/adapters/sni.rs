@@ -0,0 +1,317 @@
+//! SNI module
+//!
+//! Reads the start of a connection to find the server name (SNI) and the
+//! offered application protocols (ALPN) in a TLS ClientHello without
+//! terminating TLS. The bytes read are handed back so they can be replayed
+//! to the backend.
+
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// TLS record content type for handshake messages
+const CONTENT_TYPE_HANDSHAKE: u8 = 0x16;
+/// Handshake message type for ClientHello
+const HANDSHAKE_CLIENT_HELLO: u8 = 0x01;
+/// Extension type for server_name
+const EXTENSION_SERVER_NAME: u16 = 0x0000;
+/// Extension type for application_layer_protocol_negotiation
+const EXTENSION_ALPN: u16 = 0x0010;
+/// Server name type for host names
+const NAME_TYPE_HOST_NAME: u8 = 0x00;
+/// Largest TLS record payload allowed (2^14 plus expansion headroom)
+const MAX_RECORD_LEN: usize = (1 << 14) + 2048;
+/// Largest ClientHello accepted
+const MAX_CLIENT_HELLO_LEN: usize = 1 << 16;
+/// Longest DNS host name
+const MAX_HOST_NAME_LEN: usize = 253;
+
+/// Fields of a ClientHello used for routing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientHelloInfo {
+    /// Host name from the SNI extension (lowercase, no trailing dot)
+    pub server_name: Option<String>,
+    /// Protocols from the ALPN extension, in the client's preference order
+    pub alpn: Vec<String>,
+}
+
+/// Result of looking for a ClientHello in the start of a connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientHelloParse {
+    /// A ClientHello, with whichever routing fields it carries
+    Parsed(ClientHelloInfo),
+    /// TLS, but the ClientHello is malformed
+    Malformed,
+    /// Not a TLS handshake
+    NotTls,
+    /// More bytes are needed to decide
+    Incomplete,
+}
+
+/// Result of looking for the SNI in the start of a connection
+#[cfg(feature = "test-util")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientHelloSni {
+    /// A ClientHello with this host name (lowercase, no trailing dot)
+    Found(String),
+    /// TLS, but the ClientHello has no usable host name or is malformed
+    Missing,
+    /// Not a TLS handshake
+    NotTls,
+    /// More bytes are needed to decide
+    Incomplete,
+}
+
+/// Parse the SNI host name from the first bytes of a connection
+///
+/// See [`parse_client_hello`].
+#[cfg(feature = "test-util")]
+pub fn parse_client_hello_sni(buf: &[u8]) -> ClientHelloSni {
+    match parse_client_hello(buf) {
+        ClientHelloParse::Parsed(ClientHelloInfo {
+            server_name: Some(name),
+            ..
+        }) => ClientHelloSni::Found(name),
+        ClientHelloParse::Parsed(_) | ClientHelloParse::Malformed => {
+            ClientHelloSni::Missing
+        }
+        ClientHelloParse::NotTls => ClientHelloSni::NotTls,
+        ClientHelloParse::Incomplete => ClientHelloSni::Incomplete,
+    }
+}
+
+/// Parse the ClientHello routing fields from the first bytes of a connection
+///
+/// Handles ClientHellos split over several handshake records. Every length
+/// is bounds-checked, so malformed input yields
+/// [`ClientHelloParse::Malformed`] or [`ClientHelloParse::NotTls`] rather
+/// than a panic.
+pub fn parse_client_hello(buf: &[u8]) -> ClientHelloParse {
+    let mut handshake = Vec::new();
+    let mut pos = 0;
+    loop {
+        let Some(header) = buf.get(pos..pos + 5) else {
+            return match buf.get(pos) {
+                Some(&content_type) if content_type != CONTENT_TYPE_HANDSHAKE => {
+                    non_handshake_record(pos)
+                }
+                _ => ClientHelloParse::Incomplete,
+            };
+        };
+        if header[0] != CONTENT_TYPE_HANDSHAKE {
+            return non_handshake_record(pos);
+        }
+        if header[1] != 0x03 {
+            return ClientHelloParse::NotTls;
+        }
+        let record_len = u16::from_be_bytes([header[3], header[4]]) as usize;
+        if record_len == 0 || record_len > MAX_RECORD_LEN {
+            return ClientHelloParse::NotTls;
+        }
+        let Some(fragment) = buf.get(pos + 5..pos + 5 + record_len) else {
+            return ClientHelloParse::Incomplete;
+        };
+        handshake.extend_from_slice(fragment);
+        pos += 5 + record_len;
+
+        if handshake.len() < 4 {
+            continue;
+        }
+        if handshake[0] != HANDSHAKE_CLIENT_HELLO {
+            return ClientHelloParse::NotTls;
+        }
+        let hello_len =
+            u32::from_be_bytes([0, handshake[1], handshake[2], handshake[3]]) as usize;
+        if hello_len > MAX_CLIENT_HELLO_LEN {
+            return ClientHelloParse::Malformed;
+        }
+        if let Some(hello) = handshake.get(4..4 + hello_len) {
+            return match client_hello_info(hello) {
+                Some(info) => ClientHelloParse::Parsed(info),
+                None => ClientHelloParse::Malformed,
+            };
+        }
+    }
+}
+
+/// A non-handshake record ends the search: the connection is not TLS, or a
+/// ClientHello was interrupted
+fn non_handshake_record(pos: usize) -> ClientHelloParse {
+    if pos == 0 {
+        ClientHelloParse::NotTls
+    } else {
+        ClientHelloParse::Malformed
+    }
+}
+
+/// Extract the routing fields from a ClientHello body
+///
+/// `None` when the extension framing is malformed; an invalid host name or
+/// protocol list only leaves that field empty.
+fn client_hello_info(hello: &[u8]) -> Option<ClientHelloInfo> {
+    let mut reader = Reader::new(hello);
+    reader.skip(2)?; // legacy_version
+    reader.skip(32)?; // random
+    let session_id_len = reader.u8()? as usize;
+    reader.skip(session_id_len)?;
+    let cipher_suites_len = reader.u16()? as usize;
+    reader.skip(cipher_suites_len)?;
+    let compression_len = reader.u8()? as usize;
+    reader.skip(compression_len)?;
+
+    let mut info = ClientHelloInfo::default();
+    let extensions_len = reader.u16()? as usize;
+    let mut extensions = Reader::new(reader.take(extensions_len)?);
+    while !extensions.is_empty() {
+        let extension_type = extensions.u16()?;
+        let extension_len = extensions.u16()? as usize;
+        let data = extensions.take(extension_len)?;
+        match extension_type {
+            EXTENSION_SERVER_NAME if info.server_name.is_none() => {
+                info.server_name = server_name(data);
+            }
+            EXTENSION_ALPN if info.alpn.is_empty() => {
+                info.alpn = alpn_protocols(data).unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+    Some(info)
+}
+
+/// Extract the host name from a server_name extension
+fn server_name(data: &[u8]) -> Option<String> {
+    let mut list = Reader::new(data);
+    let list_len = list.u16()? as usize;
+    let mut names = Reader::new(list.take(list_len)?);
+    while !names.is_empty() {
+        let name_type = names.u8()?;
+        let name_len = names.u16()? as usize;
+        let name = names.take(name_len)?;
+        if name_type == NAME_TYPE_HOST_NAME {
+            return host_name(name);
+        }
+    }
+    None
+}
+
+/// Extract the protocol names from an ALPN extension
+///
+/// Names that are empty or not UTF-8 are skipped.
+fn alpn_protocols(data: &[u8]) -> Option<Vec<String>> {
+    let mut list = Reader::new(data);
+    let list_len = list.u16()? as usize;
+    let mut names = Reader::new(list.take(list_len)?);
+    let mut protocols = Vec::new();
+    while !names.is_empty() {
+        let name_len = names.u8()? as usize;
+        let name = names.take(name_len)?;
+        if let Ok(name) = std::str::from_utf8(name)
+            && !name.is_empty()
+        {
+            protocols.push(name.to_string());
+        }
+    }
+    Some(protocols)
+}
+
+/// Validate and normalize a host name
+fn host_name(name: &[u8]) -> Option<String> {
+    let name = std::str::from_utf8(name).ok()?;
+    let name = name.strip_suffix('.').unwrap_or(name);
+    let valid = !name.is_empty()
+        && name.len() <= MAX_HOST_NAME_LEN
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_'));
+    valid.then(|| name.to_ascii_lowercase())
+}
+
+/// Bounds-checked big-endian reader
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.buf.len() {
+            return None;
+        }
+        let (head, tail) = self.buf.split_at(len);
+        self.buf = tail;
+        Some(head)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+}
+
+/// Read from `reader` until the SNI is known, then return it with the bytes read
+///
+/// See [`sniff_client_hello`].
+#[cfg(feature = "test-util")]
+pub async fn sniff_sni<R>(
+    reader: &mut R,
+    timeout: Duration,
+    max_bytes: usize,
+) -> (Option<String>, Vec<u8>)
+where
+    R: AsyncRead + Unpin,
+{
+    let (hello, buf) = sniff_client_hello(reader, timeout, max_bytes).await;
+    (hello.and_then(|hello| hello.server_name), buf)
+}
+
+/// Read from `reader` until a ClientHello is parsed, then return its routing
+/// fields with the bytes read
+///
+/// Gives up (returning `None`) when the connection is not TLS, sends a
+/// malformed ClientHello, sends more than `max_bytes` without completing
+/// one, closes, or is silent for longer than `timeout` in total.
+pub async fn sniff_client_hello<R>(
+    reader: &mut R,
+    timeout: Duration,
+    max_bytes: usize,
+) -> (Option<ClientHelloInfo>, Vec<u8>)
+where
+    R: AsyncRead + Unpin,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut buf = Vec::with_capacity(max_bytes.min(4096));
+    let mut chunk = [0u8; 4096];
+    while buf.len() < max_bytes {
+        let want = chunk.len().min(max_bytes - buf.len());
+        let read = match tokio::time::timeout_at(
+            deadline,
+            reader.read(&mut chunk[..want]),
+        )
+        .await
+        {
+            Ok(Ok(n)) if n > 0 => n,
+            _ => break, // EOF, read error or timeout
+        };
+        buf.extend_from_slice(&chunk[..read]);
+        match parse_client_hello(&buf) {
+            ClientHelloParse::Incomplete => continue,
+            ClientHelloParse::Parsed(info) => return (Some(info), buf),
+            ClientHelloParse::Malformed | ClientHelloParse::NotTls => break,
+        }
+    }
+    (None, buf)
+}
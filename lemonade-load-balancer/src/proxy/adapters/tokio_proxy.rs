@@ -4,15 +4,24 @@
 //! Runs on main thread for maximum performance (hot path)
 
 use crate::prelude::*;
+use crate::proxy::adapters::{
+    CopyOutcome, DEADLINE_EXCEEDED_STATUS, FD_EXHAUSTED_PAUSE, FdBudget, HedgeBudget,
+    LOOP_DETECTED_RESPONSE, NOT_FOUND_RESPONSE, ResponseStatusTap, SlowLog, SlowLogEntry,
+    classify_close, copy_stream, hedge_delay, is_fd_exhausted, pick_hedge_backend,
+    sniff_client_hello, sniff_request_head, with_deadline_header, with_hop_header,
+};
 use crate::proxy::error::ProxyError;
-use crate::proxy::models::{ConnectionEvent, ProxyConfig};
+use crate::proxy::models::{
+    ConnectionEvent, HedgingConfig, ProxyConfig, RetryBudgetConfig,
+};
 use crate::proxy::port::ProxyService;
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use std::io;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::time::Instant;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::task::JoinSet;
 use tracing::instrument;
@@ -22,6 +31,42 @@ use tracing::instrument;
 pub struct TokioProxyService {
     /// Proxy configuration (reference to global config's proxy slice)
     config: Arc<ArcSwap<ProxyConfig>>,
+    /// Share of connections that hedged
+    hedge_budget: Arc<HedgeBudget>,
+    /// Rate-limited slow connect and slow connection warnings
+    slow_log: Arc<SlowLog>,
+    /// Id given to the next proxied connection
+    next_connection_id: Arc<AtomicU64>,
+    /// Clients closed for sending nothing within `initial_read_timeout_millis`
+    silent_closed: Arc<AtomicU64>,
+    /// Connection ceiling derived from the open file limit
+    fd_budget: FdBudget,
+    /// Open connections from clients at a backend's address
+    backend_peers: Arc<AtomicUsize>,
+    /// Clients rejected past `max_backend_peer_connections` or `max_hops`
+    loop_rejected: Arc<AtomicU64>,
+    /// OTLP connection instruments, looked up once rather than per pick
+    connection_metrics: Arc<lemonade_observability::ConnectionMetrics>,
+}
+
+/// Bytes read from a client before its backend was picked, replayed to the
+/// backend ahead of the rest of the connection
+#[derive(Debug, Default)]
+struct ClientPrelude {
+    /// Bytes read so far
+    bytes: Vec<u8>,
+    /// When the first request runs out of time, if its head is in `bytes`
+    /// and its deadline is propagated
+    deadline: Option<Instant>,
+}
+
+impl From<Vec<u8>> for ClientPrelude {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            deadline: None,
+        }
+    }
 }
 
 impl TokioProxyService {
@@ -33,122 +78,927 @@ impl TokioProxyService {
     /// # Returns
     /// * `Ok(Self)` if service was created successfully
     pub fn new(config: Arc<ArcSwap<ProxyConfig>>) -> Result<Self, ProxyError> {
-        Ok(Self { config })
+        Ok(Self {
+            config,
+            hedge_budget: Arc::new(HedgeBudget::default()),
+            slow_log: Arc::new(SlowLog::default()),
+            next_connection_id: Arc::new(AtomicU64::new(1)),
+            silent_closed: Arc::new(AtomicU64::new(0)),
+            fd_budget: FdBudget::detect(),
+            backend_peers: Arc::new(AtomicUsize::new(0)),
+            loop_rejected: Arc::new(AtomicU64::new(0)),
+            connection_metrics: lemonade_observability::get_connection_metrics(
+                "lemonade-load-balancer",
+            ),
+        })
     }
 
-    /// Handle a single proxy connection
-    #[instrument(
-        skip(self, client_stream, backend, ctx),
-        fields(
-            service.name = "lemonade-load-balancer",
-            backend.id = %backend.id(),
-            backend.name = %backend.name().unwrap_or("unknown"),
-            backend.addr = %backend.address()
-        )
-    )]
-    async fn handle_connection(
+    /// Use `fd_budget` instead of the one read from the process limits
+    pub fn with_fd_budget(mut self, fd_budget: FdBudget) -> Self {
+        self.fd_budget = fd_budget;
+        self
+    }
+
+    /// Hedge totals as (connections eligible to hedge, connections hedged)
+    pub fn hedge_totals(&self) -> (u64, u64) {
+        self.hedge_budget.totals()
+    }
+
+    /// Clients closed without a backend connect because they sent nothing
+    /// within `initial_read_timeout_millis`
+    pub fn silent_connections_closed(&self) -> u64 {
+        self.silent_closed.load(Ordering::Relaxed)
+    }
+
+    /// Connections open from clients whose address is a backend's
+    pub fn backend_peer_connections(&self) -> usize {
+        self.backend_peers.load(Ordering::Relaxed)
+    }
+
+    /// Clients rejected as a likely proxy loop: because
+    /// `max_backend_peer_connections` were already open from backend
+    /// addresses, or because their first request had passed through more
+    /// than `max_hops` load balancers
+    pub fn loop_connections_rejected(&self) -> u64 {
+        self.loop_rejected.load(Ordering::Relaxed)
+    }
+
+    /// Start a new listener generation after a rebind
+    ///
+    /// Connections accepted on the previous listener keep running and stay
+    /// counted in drain accounting; a background task reports whether they
+    /// finish within the drain timeout.
+    fn start_listener_generation(ctx: &Arc<Context>) {
+        let generations = ctx.listener_generations();
+        let previous = generations.advance();
+        let remaining = generations.active(previous);
+        tracing::info!(
+            "Listener generation {} started, {} connections remain on generation {}",
+            generations.current(),
+            remaining,
+            previous
+        );
+        if remaining == 0 {
+            return;
+        }
+
+        let ctx = ctx.clone();
+        let drain_timeout =
+            Duration::from_millis(ctx.config().runtime.drain_timeout_millis);
+        tokio::spawn(async move {
+            match ctx.wait_for_generation_drain(previous, drain_timeout).await {
+                Ok(()) => {
+                    tracing::info!("Listener generation {} drained", previous);
+                }
+                Err(e) => tracing::warn!("{}", e),
+            }
+        });
+    }
+
+    /// Pick a backend from the group matching `selector` for `client`
+    ///
+    /// With `sticky_sessions`, a client goes back to the backend it was last
+    /// sent to while that backend is routed, healthy, not draining and below
+    /// `max_pending_connects`; otherwise the strategy picks and the client
+    /// sticks to its pick. `request` is the connection's first HTTP request,
+    /// for strategies that pick by request. Returns `None` (after logging
+    /// why) when the strategy finds no backend or the picked backend cannot
+    /// take new connections.
+    async fn select_backend(
+        &self,
+        ctx: &Arc<Context>,
+        strategy: &Arc<dyn StrategyService>,
+        selector: &LabelSelector,
+        client: IpAddr,
+        request: Option<&RequestMeta>,
+    ) -> Option<Selection> {
+        let config = self.config.load();
+        let now_ms = ctx.clock().now_millis();
+        // Unix socket clients have no address to stick by
+        let sticky = config.sticky_sessions && !client.is_unspecified();
+        if sticky
+            && let Some(backend) = Self::sticky_backend(ctx, selector, client, now_ms)
+        {
+            ctx.affinity()
+                .record(client, backend.id(), now_ms, config.sticky_ttl_millis);
+            return Some(Selection {
+                backend,
+                picked: false,
+            });
+        }
+
+        let backend = self.pick_backend(ctx, strategy, selector, request).await?;
+        if sticky {
+            ctx.affinity()
+                .record(client, backend.id(), now_ms, config.sticky_ttl_millis);
+        }
+        Some(Selection {
+            backend,
+            picked: true,
+        })
+    }
+
+    /// Reserve a connect slot on the backend of `selection`, chosen from the
+    /// group matching `selector`
+    ///
+    /// Picks skip backends at `max_pending_connects`, but connects start off
+    /// the accept loop: a backend that reached the cap since it was picked,
+    /// or whose circuit is open, is skipped and the strategy picks again, at
+    /// most once per routed backend. With the circuit breaker enabled, the
+    /// least loaded backend of the group that takes the connect is used
+    /// after that, for strategies that keep picking an open circuit. Only
+    /// the strategy pick that gets the slot counts the selection. Returns
+    /// `None` (after logging why) when no backend picked has a free slot.
+    async fn reserve_connect(
         &self,
-        client_stream: TcpStream,
-        backend: Arc<Backend>,
+        ctx: &Arc<Context>,
+        selection: Selection,
+        selector: &LabelSelector,
+        request: Option<&RequestMeta>,
+    ) -> Option<PendingConnect> {
+        let routing = ctx.routing_table();
+        if let Some(pending) = PendingConnect::admit(ctx, &routing, selection.backend) {
+            if selection.picked {
+                pending.record_selection();
+            }
+            return Some(pending);
+        }
+        let strategy = ctx.strategy();
+        for _ in 0..routing.len() {
+            let backend = self.pick_backend(ctx, &strategy, selector, request).await?;
+            if let Some(pending) = PendingConnect::admit(ctx, &routing, backend) {
+                pending.record_selection();
+                return Some(pending);
+            }
+        }
+        if ctx.config().proxy.circuit_breaker.enabled {
+            // Candidates skip open circuits already
+            let mut candidates = routing.preferred_backends_matching(selector);
+            candidates.sort_by_key(|backend| backend.active_connections());
+            for backend in candidates {
+                if let Some(pending) = PendingConnect::admit(ctx, &routing, backend) {
+                    pending.record_selection();
+                    return Some(pending);
+                }
+            }
+        }
+        if ctx.should_log("proxy.no_backend") {
+            tracing::warn!(
+                "No backend of group {} has a free connect slot (max_pending_connects {})",
+                selector,
+                self.config.load().max_pending_connects
+            );
+        }
+        None
+    }
+
+    /// Let the strategy pick a backend from the group matching `selector`
+    ///
+    /// With `request`, the strategy picks by the connection's first HTTP
+    /// request. The pick is timed into the context's pick timings and the
+    /// `lb.strategy.pick_duration` histogram, and warned about past
+    /// `strategy_pick_warn_micros`. Returns `None` (after logging why) when
+    /// the strategy finds no backend or the picked backend cannot take new
+    /// connections.
+    async fn pick_backend(
+        &self,
+        ctx: &Arc<Context>,
+        strategy: &Arc<dyn StrategyService>,
+        selector: &LabelSelector,
+        request: Option<&RequestMeta>,
+    ) -> Option<Arc<Backend>> {
+        let config = self.config.load();
+        let pick_start = Instant::now();
+        let picked = match request {
+            Some(request) => {
+                strategy
+                    .pick_backend_for_request(ctx.clone(), selector, request)
+                    .await
+            }
+            None if selector.is_empty() => strategy.pick_backend(ctx.clone()).await,
+            None => strategy.pick_backend_matching(ctx.clone(), selector).await,
+        };
+        let elapsed = pick_start.elapsed();
+        let strategy_name = strategy.strategy();
+        let micros = elapsed.as_micros() as u64;
+        ctx.pick_timings().record(strategy_name.as_ref(), micros);
+        self.connection_metrics
+            .record_strategy_pick(strategy_name.as_ref(), micros);
+        self.slow_log.check_pick(
+            &config,
+            ctx.clock().now_millis(),
+            strategy_name.as_ref(),
+            elapsed,
+        );
+        let backend = match picked {
+            Ok(backend) => backend,
+            Err(e) => {
+                if ctx.should_log("proxy.no_backend") {
+                    tracing::warn!("No backend available for group {}: {}", selector, e);
+                }
+                return None;
+            }
+        };
+
+        // Check if backend accepts new connections (not draining, healthy and
+        // enabled)
+        if !backend.can_accept_new_connections() {
+            tracing::debug!(
+                "Backend {} is draining, unhealthy or disabled, cannot accept new connection",
+                backend.id()
+            );
+            return None;
+        }
+        Some(backend)
+    }
+
+    /// Backend `client` sticks to, if it can still serve the group matching
+    /// `selector`, is in its preferred priority tier and zone (a client stuck
+    /// to a backup, or spilled over to another zone, goes back once the
+    /// preferred backends recover) and has room for another connect. A
+    /// backend drained by its admin state keeps its sticky clients
+    fn sticky_backend(
+        ctx: &Context,
+        selector: &LabelSelector,
+        client: IpAddr,
+        now_ms: u64,
+    ) -> Option<Arc<Backend>> {
+        let backend_id = ctx.affinity().get(client, now_ms)?;
+        let routing = ctx.routing_table();
+        let backend = routing.get(backend_id)?;
+        (backend.can_accept_sticky_connections()
+            && backend.matches(selector)
+            && Some(backend.priority()) == routing.active_priority(selector)
+            && routing.has_connect_room(&backend)
+            && routing
+                .preferred_zone(selector)
+                .is_none_or(|zone| backend.zone() == Some(zone)))
+        .then_some(backend)
+    }
+
+    /// Proxy a connection whose backend is picked off the accept loop
+    ///
+    /// With `initial_read_timeout_millis` the client must send its first
+    /// bytes in time or is closed before any backend is touched; with SNI or
+    /// ALPN routing the ClientHello then picks the backend group, and with a
+    /// strategy picking by request the first HTTP request head is read and
+    /// handed to the strategy. With `propagate_deadlines` that head is read
+    /// too, and forwarded with the time left of `request_timeout_millis`;
+    /// with `max_hops` it is forwarded counting one more hop, or answered
+    /// `508 Loop Detected` past the limit. With HTTP routing the head picks
+    /// the backend group by host and path.
+    async fn handle_deferred_connection(
+        &self,
+        mut client_stream: TcpStream,
+        peer: PeerInfo,
         ctx: Arc<Context>,
+        config: Arc<ProxyConfig>,
     ) -> Result<(), ProxyError> {
-        let backend_id = backend.id();
-        let backend_addr = backend.address().as_str();
+        let accepted_at = Instant::now();
+        if let Some(millis) = config.initial_read_timeout_millis
+            && let Some(reason) =
+                Self::await_first_bytes(&client_stream, Duration::from_millis(millis))
+                    .await
+        {
+            tracing::debug!(
+                "Closing client {} before any backend connect: {}",
+                peer.remote,
+                reason
+            );
+            self.silent_closed.fetch_add(1, Ordering::Relaxed);
+            lemonade_observability::get_connection_metrics("lemonade-load-balancer")
+                .record_rejected(reason);
+            return Ok(());
+        }
 
-        // Increment connection counter
-        backend.increment_connection();
+        if config.routes_by_client_hello() {
+            return self
+                .handle_client_hello_connection(client_stream, peer, ctx, config)
+                .await;
+        }
+        let strategy = ctx.strategy();
+        // Read per connection, so rule changes apply without a restart
+        let current = ctx.config();
+        let http_routing = &current.proxy.route_by_http;
+        // The bytes read looking for the request head are replayed to the
+        // backend; without a head the strategy picks as for any connection
+        let limits = strategy.request_head_limits().or_else(|| {
+            (config.rewrites_request_heads() || http_routing.enabled)
+                .then(RequestHeadLimits::default)
+        });
+        let (request, mut bytes) = match limits {
+            Some(limits) => sniff_request_head(&mut client_stream, limits).await,
+            None => (None, Vec::new()),
+        };
+        if let Some(max_hops) = config.max_hops
+            && request.is_some()
+        {
+            match self
+                .count_hop(&mut client_stream, &peer, bytes, max_hops)
+                .await
+            {
+                Some(counted) => bytes = counted,
+                None => return Ok(()),
+            }
+        }
+        let selector = if http_routing.enabled {
+            match self
+                .route_request(&mut client_stream, &peer, http_routing, request.as_ref())
+                .await
+            {
+                Some(selector) => selector,
+                None => return Ok(()),
+            }
+        } else {
+            LabelSelector::default()
+        };
+        let deadline = (config.propagate_deadlines && request.is_some())
+            .then(|| accepted_at + Duration::from_millis(config.request_timeout_millis));
+        let initial = ClientPrelude { bytes, deadline };
+        let backend = match self
+            .select_backend(
+                &ctx,
+                &strategy,
+                &selector,
+                peer.remote.ip(),
+                request.as_ref(),
+            )
+            .await
+        {
+            Some(backend) => {
+                self.reserve_connect(&ctx, backend, &selector, request.as_ref())
+                    .await
+            }
+            None => None,
+        };
+        match backend {
+            Some(backend) => {
+                self.handle_connection(
+                    client_stream,
+                    peer,
+                    backend,
+                    ctx,
+                    initial,
+                    &selector,
+                )
+                .await
+            }
+            None => Ok(()),
+        }
+    }
 
-        // Send connection opened event (non-blocking)
-        let _ = ctx
-            .channels()
-            .connection_tx()
-            .try_send(ConnectionEvent::Opened { backend_id });
+    /// Backend group of a connection by its first request, or `None` once a
+    /// request no rule matches was answered `404 Not Found`
+    async fn route_request(
+        &self,
+        client_stream: &mut TcpStream,
+        peer: &PeerInfo,
+        routing: &HttpRoutingConfig,
+        request: Option<&RequestMeta>,
+    ) -> Option<LabelSelector> {
+        let path = request.map_or("<none>", |request| request.path.as_str());
+        match routing.route_for(request) {
+            Some((route, selector)) => {
+                tracing::debug!(
+                    "Request for {} from {} matched route {}, group {}",
+                    path,
+                    peer.remote,
+                    route,
+                    selector
+                );
+                self.connection_metrics.record_http_route(route);
+                Some(selector.clone())
+            }
+            None => {
+                tracing::debug!(
+                    "Request for {} from {} matched no route, answering 404",
+                    path,
+                    peer.remote
+                );
+                self.connection_metrics.record_rejected("no_route");
+                let _ = client_stream.write_all(NOT_FOUND_RESPONSE).await;
+                let _ = client_stream.shutdown().await;
+                None
+            }
+        }
+    }
 
-        let connection_start = Instant::now();
+    /// Count this load balancer as one more hop of the request head read
+    /// into `bytes`, or answer `508 Loop Detected` past `max_hops`
+    ///
+    /// Returns the bytes to replay to the backend, or `None` once the client
+    /// was answered.
+    async fn count_hop(
+        &self,
+        client_stream: &mut TcpStream,
+        peer: &PeerInfo,
+        bytes: Vec<u8>,
+        max_hops: u32,
+    ) -> Option<Vec<u8>> {
+        let Some((counted, hops)) = with_hop_header(&bytes) else {
+            return Some(bytes);
+        };
+        if hops <= max_hops {
+            return Some(counted);
+        }
+        let rejected = self.loop_rejected.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::warn!(
+            "Possible proxy loop: request from {} would be hop {} of at most {}, answering 508 ({} rejected)",
+            peer.remote,
+            hops,
+            max_hops,
+            rejected
+        );
+        let _ = client_stream.write_all(LOOP_DETECTED_RESPONSE).await;
+        let _ = client_stream.shutdown().await;
+        None
+    }
+
+    /// Wait up to `timeout` for the client's first bytes, without consuming
+    /// them
+    ///
+    /// Returns why the client should be closed instead, if it stayed silent
+    /// or hung up.
+    async fn await_first_bytes(
+        stream: &TcpStream,
+        timeout: Duration,
+    ) -> Option<&'static str> {
+        let mut byte = [0u8; 1];
+        match tokio::time::timeout(timeout, stream.peek(&mut byte)).await {
+            Ok(Ok(0)) | Ok(Err(_)) => Some("client_closed"),
+            Ok(Ok(_)) => None,
+            Err(_) => Some("initial_read_timeout"),
+        }
+    }
+
+    /// Read the ClientHello, then proxy to a backend of the group its SNI
+    /// and ALPN select
+    ///
+    /// The bytes read while looking at the ClientHello are replayed to the
+    /// backend.
+    async fn handle_client_hello_connection(
+        &self,
+        mut client_stream: TcpStream,
+        peer: PeerInfo,
+        ctx: Arc<Context>,
+        config: Arc<ProxyConfig>,
+    ) -> Result<(), ProxyError> {
+        let (hello, initial) = sniff_client_hello(
+            &mut client_stream,
+            Duration::from_millis(config.route_by_sni.peek_timeout_millis),
+            config.route_by_sni.max_peek_bytes,
+        )
+        .await;
+
+        let strategy = ctx.strategy();
+        for selector in config.client_hello_selectors(hello.as_ref()) {
+            tracing::debug!(
+                "SNI {} ALPN {:?} routed to group {}",
+                hello
+                    .as_ref()
+                    .and_then(|hello| hello.server_name.as_deref())
+                    .unwrap_or("<none>"),
+                hello.as_ref().map(|hello| hello.alpn.as_slice()),
+                selector
+            );
+            if let Some(backend) = self
+                .select_backend(&ctx, &strategy, &selector, peer.remote.ip(), None)
+                .await
+                && let Some(backend) =
+                    self.reserve_connect(&ctx, backend, &selector, None).await
+            {
+                return self
+                    .handle_connection(
+                        client_stream,
+                        peer,
+                        backend,
+                        ctx,
+                        initial.into(),
+                        &selector,
+                    )
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Connect to a backend, reporting failures to health and metrics
+    ///
+    /// The backend's connection count is held while connecting and released
+    /// if the connect fails or the future is dropped (lost hedge race); the
+    /// reserved connect slot is released as soon as the connect resolves.
+    async fn connect_backend(
+        ctx: &Arc<Context>,
+        backend: PendingConnect,
+        peer: PeerInfo,
+    ) -> Result<(Arc<Backend>, BackendStream), ProxyError> {
+        let backend_id = backend.id();
+        let attempt = ConnectAttempt::start(ctx.clone(), backend, peer);
+        let connect_start = Instant::now();
 
-        // Connect to backend (ToSocketAddrs will resolve hostname lazily)
-        let backend_stream = match TcpStream::connect(backend_addr).await {
-            Ok(stream) => stream,
+        // Connect to backend (hostnames are resolved lazily), from the
+        // configured source address if any
+        let source = attempt
+            .backend
+            .source_address(ctx.config().proxy.backend_bind_address);
+        let breaker = &ctx.config().proxy.circuit_breaker;
+        match attempt.backend.address().connect_from(source).await {
+            Ok(stream) => {
+                if attempt.backend.circuit().record_success() {
+                    tracing::info!("Backend {} circuit closed", backend_id);
+                }
+                Ok((attempt.commit(), stream))
+            }
             Err(e) => {
-                backend.decrement_connection();
-                ctx.notify_connection_closed();
+                // A local source bind failure says nothing about the backend
+                if matches!(e, BackendConnectError::Connect(_))
+                    && attempt
+                        .backend
+                        .circuit()
+                        .record_failure(ctx.clock().now_millis(), breaker)
+                {
+                    tracing::warn!(
+                        "Backend {} circuit opened for {}ms after {} connect failures",
+                        backend_id,
+                        breaker.open_duration_millis,
+                        attempt.backend.circuit().failures()
+                    );
+                }
 
                 // ALERT HEALTH SERVICE - send failure event
-                let failure_event = match e.kind() {
-                    io::ErrorKind::ConnectionRefused => {
-                        BackendFailureEvent::ConnectionRefused { backend_id }
-                    }
-                    io::ErrorKind::TimedOut => {
-                        BackendFailureEvent::Timeout { backend_id }
+                let at_micros = ctx.clock().now_micros();
+                if ctx.health_enabled() {
+                    let failure_event = match &e {
+                        BackendConnectError::Bind { .. } => {
+                            BackendFailureEvent::SourceBindFailed {
+                                backend_id,
+                                at_micros,
+                            }
+                        }
+                        BackendConnectError::Connect(e)
+                            if e.kind() == io::ErrorKind::ConnectionRefused =>
+                        {
+                            BackendFailureEvent::ConnectionRefused {
+                                backend_id,
+                                at_micros,
+                            }
+                        }
+                        BackendConnectError::Connect(e)
+                            if e.kind() == io::ErrorKind::TimedOut =>
+                        {
+                            BackendFailureEvent::Timeout {
+                                backend_id,
+                                at_micros,
+                            }
+                        }
+                        _ => BackendFailureEvent::BackendClosed {
+                            backend_id,
+                            at_micros,
+                        },
+                    };
+                    let _ = ctx.channels().backend_failure_tx().try_send(failure_event);
+                }
+
+                // Send metrics event
+                if ctx.metrics_enabled() {
+                    let duration_micros = connect_start.elapsed().as_micros() as u64;
+                    let _ = ctx.channels().metrics_tx().try_send(
+                        MetricsEvent::RequestFailed {
+                            backend_id,
+                            latency_micros: duration_micros,
+                            error_class: MetricsErrorClass::ConnectionRefused,
+                            at_micros,
+                        },
+                    );
+                }
+
+                Err(match e {
+                    BackendConnectError::Bind { address, error } => {
+                        ProxyError::SourceBindFailed {
+                            backend_id,
+                            address,
+                            error,
+                        }
                     }
-                    _ => BackendFailureEvent::BackendClosed { backend_id },
-                };
+                    BackendConnectError::Connect(e) => ProxyError::Io(e),
+                })
+            }
+        }
+    }
 
-                let _ = ctx.channels().backend_failure_tx().try_send(failure_event);
+    /// Connect to `primary`, racing a second backend if it is slow
+    ///
+    /// After the hedge delay a connect to another healthy backend is started
+    /// (within the hedge rate and retry budgets) and the first attempt to
+    /// succeed wins;
+    /// the other attempt is cancelled and its connection count released.
+    async fn connect_hedged(
+        &self,
+        ctx: &Arc<Context>,
+        primary: PendingConnect,
+        peer: PeerInfo,
+        hedging: &HedgingConfig,
+        retry_budget: &RetryBudgetConfig,
+        selector: &LabelSelector,
+    ) -> Result<(Arc<Backend>, BackendStream), ProxyError> {
+        self.hedge_budget.record_connection();
+        let primary_id = primary.id();
+        let delay = hedge_delay(&primary, hedging);
 
-                // Send metrics event
-                let duration_micros = connection_start.elapsed().as_micros() as u64;
+        let primary_connect = Self::connect_backend(ctx, primary, peer);
+        tokio::pin!(primary_connect);
+        tokio::select! {
+            result = &mut primary_connect => return result,
+            _ = tokio::time::sleep(delay) => {}
+        }
+
+        let routing = ctx.routing_table();
+        let Some(hedge) = pick_hedge_backend(&routing, primary_id, selector)
+            .and_then(|hedge| PendingConnect::admit(ctx, &routing, hedge))
+        else {
+            return primary_connect.await;
+        };
+        if !self.hedge_budget.try_hedge(hedging.max_hedge_rate) {
+            return primary_connect.await;
+        }
+        if !ctx.retry_budget().try_spend(retry_budget) {
+            self.hedge_budget.cancel_hedge();
+            tracing::debug!(
+                "Retry budget spent, not hedging slow connect to backend {}",
+                primary_id
+            );
+            if ctx.metrics_enabled() {
                 let _ =
                     ctx.channels()
                         .metrics_tx()
-                        .try_send(MetricsEvent::RequestFailed {
-                            backend_id,
-                            latency_micros: duration_micros,
-                            error_class: MetricsErrorClass::ConnectionRefused,
+                        .try_send(MetricsEvent::RetrySuppressed {
+                            backend_id: primary_id,
+                            kind: RetryKind::Hedge,
+                            at_micros: ctx.clock().now_micros(),
                         });
+            }
+            return primary_connect.await;
+        }
+        let hedge_id = hedge.id();
+        tracing::debug!(
+            "Connect to backend {} slower than {:?}, hedging to backend {}",
+            primary_id,
+            delay,
+            hedge_id
+        );
+
+        let hedge_connect = Self::connect_backend(ctx, hedge, peer);
+        tokio::pin!(hedge_connect);
+        // First success wins; if one attempt fails, wait for the other
+        let result = tokio::select! {
+            result = &mut primary_connect => match result {
+                Ok(connected) => Ok(connected),
+                Err(_) => hedge_connect.await,
+            },
+            result = &mut hedge_connect => match result {
+                Ok(connected) => Ok(connected),
+                Err(_) => primary_connect.await,
+            },
+        };
+
+        if let Ok((winner, _)) = &result
+            && ctx.metrics_enabled()
+        {
+            let _ = ctx
+                .channels()
+                .metrics_tx()
+                .try_send(MetricsEvent::HedgeResolved {
+                    primary_backend_id: primary_id,
+                    hedge_backend_id: hedge_id,
+                    hedge_won: winner.id() == hedge_id,
+                    at_micros: ctx.clock().now_micros(),
+                });
+        }
+        result
+    }
 
-                return Err(ProxyError::Io(e));
+    /// Handle a single proxy connection
+    ///
+    /// `initial` holds client bytes already read (the SNI or request head
+    /// sniff), written to the backend before the copy; `selector` limits
+    /// hedging to the group.
+    #[instrument(
+        skip(self, client_stream, peer, backend, ctx, initial, selector),
+        fields(
+            service.name = "lemonade-load-balancer",
+            backend.id = %backend.id(),
+            backend.name = %backend.name().unwrap_or("unknown"),
+            backend.addr = %backend.address(),
+            client.addr = %peer.remote,
+            client.original_dst = ?peer.original_dst,
+            client.via_proxy_protocol = peer.via_proxy_protocol
+        )
+    )]
+    async fn handle_connection<S>(
+        &self,
+        client_stream: S,
+        peer: PeerInfo,
+        backend: PendingConnect,
+        ctx: Arc<Context>,
+        initial: ClientPrelude,
+        selector: &LabelSelector,
+    ) -> Result<(), ProxyError>
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let connection_start = Instant::now();
+        let connection_id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+        let client = Some(peer.remote);
+
+        let config = self.config.load_full();
+        let (backend, backend_stream) = if config.hedging.enabled {
+            self.connect_hedged(
+                &ctx,
+                backend,
+                peer,
+                &config.hedging,
+                &config.retry_budget,
+                selector,
+            )
+            .await?
+        } else {
+            Self::connect_backend(&ctx, backend, peer).await?
+        };
+        backend.apply_dscp(&backend_stream, config.backend_dscp);
+        ctx.retry_budget().record_success(&config.retry_budget);
+        let connect_elapsed = connection_start.elapsed();
+        let connect_micros = connect_elapsed.as_micros() as u64;
+        self.slow_log.sweep(&ctx);
+        self.slow_log.check_connect(
+            &self.config.load(),
+            ctx.clock().now_millis(),
+            SlowLogEntry {
+                connection_id,
+                backend: &backend,
+                client,
+                elapsed: connect_elapsed,
+            },
+        );
+
+        // The deadline counts down to here, past the pick and the connect
+        let mut initial_bytes = initial.bytes;
+        let deadline_propagated = match initial.deadline.and_then(|deadline| {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            with_deadline_header(&initial_bytes, remaining.as_millis() as u64)
+        }) {
+            Some(rewritten) => {
+                initial_bytes = rewritten;
+                backend.record_deadline_propagated();
+                true
             }
+            None => false,
         };
+        let initial = initial_bytes;
+
+        // Subscribed before copying so a close requested meanwhile is seen
+        let mut close_requested = backend.close_requested();
+        let evicted = backend.register_connection(connection_id);
 
         // Proxy data bidirectionally
         let (mut client_read, mut client_write) = tokio::io::split(client_stream);
         let (mut backend_read, mut backend_write) = tokio::io::split(backend_stream);
 
-        let client_to_backend = tokio::spawn(async move {
-            let mut bytes_sent = 0u64;
-            let mut buf = [0u8; 8192];
-            loop {
-                match client_read.read(&mut buf).await {
-                    Ok(0) => break, // EOF
-                    Ok(n) => {
-                        if backend_write.write_all(&buf[..n]).await.is_err() {
-                            break;
-                        }
-                        bytes_sent += n as u64;
-                    }
-                    Err(_) => break,
+        let coalesce = self.config.load().coalesce.clone();
+        let max_buffered_bytes = self.config.load().max_buffered_bytes;
+
+        let mut client_to_backend = tokio::spawn({
+            let coalesce = coalesce.clone();
+            async move {
+                if !initial.is_empty() && backend_write.write_all(&initial).await.is_err()
+                {
+                    let _ = backend_write.shutdown().await;
+                    return CopyOutcome::default();
                 }
+                let mut outcome = copy_stream(
+                    &mut client_read,
+                    &mut backend_write,
+                    &coalesce,
+                    max_buffered_bytes,
+                )
+                .await;
+                outcome.bytes += initial.len() as u64;
+                outcome
             }
-            bytes_sent
         });
 
-        let backend_to_client = tokio::spawn(async move {
-            let mut bytes_received = 0u64;
-            let mut buf = [0u8; 8192];
-            loop {
-                match backend_read.read(&mut buf).await {
-                    Ok(0) => break, // EOF
-                    Ok(n) => {
-                        if client_write.write_all(&buf[..n]).await.is_err() {
-                            break;
-                        }
-                        bytes_received += n as u64;
-                    }
-                    Err(_) => break,
-                }
+        // With a propagated deadline, the response status tells whether the
+        // backend gave up on it
+        let mut backend_to_client = tokio::spawn(async move {
+            if !deadline_propagated {
+                let outcome = copy_stream(
+                    &mut backend_read,
+                    &mut client_write,
+                    &coalesce,
+                    max_buffered_bytes,
+                )
+                .await;
+                return (outcome, None);
             }
-            bytes_received
+            let mut tapped = ResponseStatusTap::new(backend_read);
+            let outcome = copy_stream(
+                &mut tapped,
+                &mut client_write,
+                &coalesce,
+                max_buffered_bytes,
+            )
+            .await;
+            (outcome, tapped.status())
         });
 
-        // Wait for both directions to complete
-        let (bytes_sent, bytes_received) =
-            tokio::join!(client_to_backend, backend_to_client);
-        let bytes_sent = bytes_sent.unwrap_or(0);
-        let bytes_received = bytes_received.unwrap_or(0);
-        let duration_micros = connection_start.elapsed().as_micros() as u64;
+        // Wait for both directions to complete, unless the backend's drain
+        // policy or an unhealthy transition closes the connection first
+        // (aborting the copies drops both sockets; their byte counts are lost)
+        let drained = async {
+            let reason = close_requested
+                .wait_for(Option::is_some)
+                .await
+                .map(|reason| reason.unwrap_or_default());
+            match reason {
+                Ok(reason) => reason,
+                Err(_) => std::future::pending().await,
+            }
+        };
+        let forced = async {
+            tokio::select! {
+                reason = drained => reason,
+                _ = evicted.cancelled() => CloseReason::BackendUnhealthy,
+            }
+        };
+        let mut close_reason = CloseReason::Normal;
+        let (sent, received) = tokio::select! {
+            copied = async { tokio::join!(&mut client_to_backend, &mut backend_to_client) } => copied,
+            reason = forced => {
+                close_reason = reason;
+                client_to_backend.abort();
+                backend_to_client.abort();
+                tokio::join!(client_to_backend, backend_to_client)
+            }
+        };
+        let sent = sent.unwrap_or_default();
+        let bytes_sent = sent.bytes;
+        let (received, response_status) = received.unwrap_or_default();
+        if response_status == Some(DEADLINE_EXCEEDED_STATUS) {
+            backend.record_deadline_exceeded();
+        }
+        if close_reason == CloseReason::Normal {
+            close_reason = classify_close(&sent, &received);
+        }
+        backend
+            .record_backpressure(sent.backpressure_events + received.backpressure_events);
+        let bytes_received = received.bytes;
+        let ttfb_micros = received
+            .first_read_at
+            .map(|at| at.duration_since(connection_start).as_micros() as u64);
+        let duration = connection_start.elapsed();
+        let duration_micros = duration.as_micros() as u64;
+        self.slow_log.check_connection(
+            &self.config.load(),
+            ctx.clock().now_millis(),
+            SlowLogEntry {
+                connection_id,
+                backend: &backend,
+                client,
+                elapsed: duration,
+            },
+        );
+
+        if close_reason.is_forced() {
+            backend.record_forced_close();
+            tracing::info!(
+                connection_id,
+                "Connection to backend {} force-closed ({})",
+                backend.id(),
+                close_reason.as_str()
+            );
+        }
+        if close_reason == CloseReason::BackendAborted {
+            tracing::debug!(
+                connection_id,
+                "Backend {} aborted the connection mid-transfer",
+                backend.id()
+            );
+            // Passive health signal: the backend died under a live connection
+            if config.report_backend_aborts && ctx.health_enabled() {
+                let _ = ctx.channels().backend_failure_tx().try_send(
+                    BackendFailureEvent::BackendClosed {
+                        backend_id: backend.id(),
+                        at_micros: ctx.clock().now_micros(),
+                    },
+                );
+            }
+        } else {
+            // Weighed against the failures by outlier ejection
+            backend.record_connection_completed();
+        }
 
         // Decrement connection counter
+        let backend_id = backend.id();
+        backend.unregister_connection(connection_id);
         backend.decrement_connection();
         ctx.notify_connection_closed();
 
@@ -156,38 +1006,348 @@ impl TokioProxyService {
         let _ = ctx
             .channels()
             .connection_tx()
-            .try_send(ConnectionEvent::Closed { backend_id });
+            .try_send(ConnectionEvent::Closed { backend_id, peer });
 
         // Send metrics event
-        let _ = ctx
-            .channels()
-            .metrics_tx()
-            .try_send(MetricsEvent::ConnectionClosed {
-                backend_id,
-                duration_micros,
-                bytes_in: bytes_received,
-                bytes_out: bytes_sent,
+        if ctx.metrics_enabled() {
+            let _ =
+                ctx.channels()
+                    .metrics_tx()
+                    .try_send(MetricsEvent::ConnectionClosed {
+                        backend_id,
+                        duration_micros,
+                        connect_micros,
+                        ttfb_micros,
+                        bytes_in: bytes_received,
+                        bytes_out: bytes_sent,
+                        close_reason,
+                        peer,
+                        at_micros: ctx.clock().now_micros(),
+                    });
+        }
+
+        Ok(())
+    }
+
+    /// Whether the proxy is at its connection cap, logging the rejection of
+    /// `peer` if so
+    fn at_connection_cap(
+        &self,
+        config: &ProxyConfig,
+        routing: &RoutingSnapshot,
+        peer: &PeerInfo,
+    ) -> bool {
+        let Some(max_conns) = self
+            .fd_budget
+            .max_connections(config.max_connections, config.fd_headroom)
+        else {
+            return false;
+        };
+        let total_connections: usize = routing
+            .route_table
+            .all_backends()
+            .iter()
+            .map(|b| b.active_connections())
+            .sum();
+        if total_connections < max_conns as usize {
+            return false;
+        }
+        tracing::warn!(
+            "Max connections reached ({}), rejecting connection from {}",
+            max_conns,
+            peer.remote
+        );
+        true
+    }
+
+    /// Serve a connection accepted on the TCP listener
+    ///
+    /// The connection is tracked in `conn_tasks` until it closes, unless it
+    /// is rejected (connection cap, proxy loop, no backend) and closed here.
+    async fn serve_accepted(
+        &self,
+        ctx: &Arc<Context>,
+        routing: &mut RoutingSnapshot,
+        conn_tasks: &mut JoinSet<()>,
+        stream: TcpStream,
+        peer_addr: SocketAddr,
+    ) {
+        // Count the connection in this listener's generation until its task
+        // finishes (or it is rejected below)
+        let generation_guard = ctx.listener_generations().track();
+        routing.refresh(ctx);
+
+        // Check max connections
+        let config = self.config.load();
+        let peer = PeerInfo::accepted(&stream, peer_addr, config.transparent);
+        if self.at_connection_cap(&config, routing, &peer) {
+            drop(stream);
+            return;
+        }
+
+        // A client at a backend's address is likely this load balancer, or
+        // one chained to it, proxying back into itself
+        let backend_peer = match config.max_backend_peer_connections {
+            Some(max) if is_backend_peer(&routing.route_table, peer_addr) => {
+                let Some(guard) = BackendPeerGuard::acquire(&self.backend_peers, max)
+                else {
+                    let rejected = self.loop_rejected.fetch_add(1, Ordering::Relaxed) + 1;
+                    tracing::warn!(
+                        "Possible proxy loop: {} connections already open from backend addresses, rejecting connection from {} ({} rejected)",
+                        max,
+                        peer_addr,
+                        rejected
+                    );
+                    drop(stream);
+                    return;
+                };
+                Some(guard)
+            }
+            _ => None,
+        };
+
+        // With SNI or ALPN routing, a first-bytes timeout, a strategy
+        // picking by request, request head rewriting or HTTP routing, the
+        // backend is picked once the client has spoken, off the accept loop
+        if config.routes_by_client_hello()
+            || config.initial_read_timeout_millis.is_some()
+            || routing.strategy.request_head_limits().is_some()
+            || config.rewrites_request_heads()
+            || ctx.config().proxy.route_by_http.enabled
+        {
+            let svc_clone = self.clone();
+            let ctx_clone = ctx.clone();
+            let config = config.clone();
+            conn_tasks.spawn(async move {
+                let _ = svc_clone
+                    .handle_deferred_connection(stream, peer, ctx_clone, config)
+                    .await;
+                drop(backend_peer);
+                drop(generation_guard);
             });
+            return;
+        }
+
+        // Pick backend using strategy
+        let selector = LabelSelector::default();
+        let Some(backend) = self
+            .select_backend(ctx, &routing.strategy, &selector, peer.remote.ip(), None)
+            .await
+        else {
+            drop(stream);
+            return;
+        };
 
+        // Spawn connection handler (clone ctx before move)
+        let svc_clone = self.clone();
+        let ctx_clone = ctx.clone();
+        conn_tasks.spawn(async move {
+            if let Some(backend) = svc_clone
+                .reserve_connect(&ctx_clone, backend, &selector, None)
+                .await
+            {
+                let _ = svc_clone
+                    .handle_connection(
+                        stream,
+                        peer,
+                        backend,
+                        ctx_clone,
+                        ClientPrelude::default(),
+                        &selector,
+                    )
+                    .await;
+            }
+            drop(backend_peer);
+            drop(generation_guard);
+        });
+    }
+
+    /// Serve a connection accepted on the Unix socket listener
+    #[cfg(unix)]
+    async fn serve_accepted_unix(
+        &self,
+        ctx: &Arc<Context>,
+        routing: &mut RoutingSnapshot,
+        conn_tasks: &mut JoinSet<()>,
+        stream: tokio::net::UnixStream,
+    ) {
+        let generation_guard = ctx.listener_generations().track();
+        routing.refresh(ctx);
+        let peer = PeerInfo::unix();
+        if self.at_connection_cap(&self.config.load(), routing, &peer) {
+            return;
+        }
+
+        let selector = LabelSelector::default();
+        let Some(backend) = self
+            .select_backend(ctx, &routing.strategy, &selector, peer.remote.ip(), None)
+            .await
+        else {
+            return;
+        };
+
+        let svc_clone = self.clone();
+        let ctx_clone = ctx.clone();
+        conn_tasks.spawn(async move {
+            if let Some(backend) = svc_clone
+                .reserve_connect(&ctx_clone, backend, &selector, None)
+                .await
+            {
+                let _ = svc_clone
+                    .handle_connection(
+                        stream,
+                        peer,
+                        backend,
+                        ctx_clone,
+                        ClientPrelude::default(),
+                        &selector,
+                    )
+                    .await;
+            }
+            drop(generation_guard);
+        });
+    }
+
+    /// Wait for the connections still open at shutdown to finish
+    async fn drain_connection_tasks(ctx: &Context, mut conn_tasks: JoinSet<()>) {
+        ctx.readiness().set_accepting(false);
+        tracing::info!(
+            "Waiting for {} active connections to complete",
+            conn_tasks.len()
+        );
+        while (conn_tasks.join_next().await).is_some() {
+            // Drain all active connection tasks
+        }
+
+        tracing::info!("All proxy connections closed");
+    }
+
+    /// Accept connections on a Unix socket until shutdown
+    ///
+    /// Clients on a Unix socket have no IP address and send no TLS
+    /// ClientHello worth routing on (they are on the same host), so each
+    /// connection is handed to the strategy as soon as it is accepted.
+    #[cfg(unix)]
+    async fn accept_unix_connections(
+        &self,
+        ctx: Arc<Context>,
+        address: UnixAddress,
+    ) -> Result<(), ProxyError> {
+        let mut shutdown_rx = ctx.channels().shutdown_rx();
+        let listener = address.bind()?;
+        tracing::info!("Proxy listening on {}", address);
+        self.log_fd_budget();
+        ctx.readiness().set_accepting(true);
+
+        let mut conn_tasks = JoinSet::new();
+        let mut routing = RoutingSnapshot::new(&ctx);
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("Proxy received shutdown signal");
+                    break;
+                }
+
+                accept_result = listener.accept() => {
+                    match accept_result {
+                        Ok((stream, _)) => {
+                            self.serve_accepted_unix(&ctx, &mut routing, &mut conn_tasks, stream)
+                                .await;
+                        }
+                        Err(e) => {
+                            if ctx.should_log("proxy.accept_error") {
+                                tracing::error!("Accept error: {}", e);
+                            }
+                            tokio::time::sleep(Duration::from_millis(100)).await;
+                        }
+                    }
+                }
+
+                Some(_) = conn_tasks.join_next() => {}
+            }
+        }
+
+        let queued = match listener.into_std() {
+            Ok(listener) => take_queued(|| {
+                let (stream, _) = listener.accept()?;
+                stream.set_nonblocking(true)?;
+                tokio::net::UnixStream::from_std(stream)
+            }),
+            Err(e) => {
+                tracing::warn!("Failed to take queued connections: {}", e);
+                Vec::new()
+            }
+        };
+        address.remove_file();
+        for stream in queued {
+            self.serve_accepted_unix(&ctx, &mut routing, &mut conn_tasks, stream)
+                .await;
+        }
+        Self::drain_connection_tasks(&ctx, conn_tasks).await;
         Ok(())
     }
+
+    /// Accept connections on a Unix socket (unsupported on this platform)
+    #[cfg(not(unix))]
+    async fn accept_unix_connections(
+        &self,
+        _ctx: Arc<Context>,
+        address: UnixAddress,
+    ) -> Result<(), ProxyError> {
+        Err(ProxyError::Io(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("cannot listen on {} without Unix socket support", address),
+        )))
+    }
+
+    /// Log the connection cap the open file limit allows, warning when the
+    /// configured `max_connections` is above it
+    fn log_fd_budget(&self) {
+        let config = self.config.load();
+        if let Some(warning) = self
+            .fd_budget
+            .warning(config.max_connections, config.fd_headroom)
+        {
+            tracing::warn!("{}", warning);
+        } else if let Some(ceiling) = self.fd_budget.ceiling(config.fd_headroom) {
+            tracing::info!(
+                "Open file limit {} allows {} connections ({} descriptors kept back)",
+                self.fd_budget.soft_limit().unwrap_or_default(),
+                ceiling,
+                config.fd_headroom
+            );
+        }
+    }
 }
 
 #[async_trait]
 impl ProxyService for TokioProxyService {
-    #[tracing::instrument(skip(self, ctx), fields(service.name = "lemonade-load-balancer", service.type = "proxy"))]
+    #[tracing::instrument(skip(self, ctx), fields(service.name = "lemonade-load-balancer", service.type = "proxy", lb.group = %ctx.group()))]
     async fn accept_connections(&self, ctx: Arc<Context>) -> Result<(), ProxyError> {
         let mut shutdown_rx = ctx.channels().shutdown_rx();
         let mut config_rx = ctx.channels().config_rx();
 
+        // Never accept before the initial backend set is routable
+        ctx.readiness().wait_config_committed().await;
+
+        if let Some(address) = ctx.config().proxy.listen_unix.clone() {
+            return self.accept_unix_connections(ctx, address).await;
+        }
+
         // Get initial listen address
         let mut current_addr = ctx.config().proxy.listen_address;
         let mut listener = TcpListener::bind(current_addr).await?;
         tracing::info!("Proxy listening on {}", current_addr);
+        self.log_fd_budget();
+        ctx.readiness().set_accepting(true);
 
         // Track active connection tasks
         let mut conn_tasks = JoinSet::new();
 
+        // Strategy and route table reused across accepts until they are swapped
+        let mut routing = RoutingSnapshot::new(&ctx);
+
         loop {
             tokio::select! {
                 // Shutdown signal
@@ -215,6 +1375,7 @@ impl ProxyService for TokioProxyService {
                                 listener = new_listener;
                                 current_addr = new_addr;
                                 tracing::info!("Now accepting on {}", new_addr);
+                                Self::start_listener_generation(&ctx);
                             }
                             Err(e) => {
                                 tracing::error!("Failed to bind to {}: {}", new_addr, e);
@@ -223,6 +1384,7 @@ impl ProxyService for TokioProxyService {
                                     Ok(old_listener) => {
                                         listener = old_listener;
                                         tracing::warn!("Reverted to {}", current_addr);
+                                        Self::start_listener_generation(&ctx);
                                     }
                                     Err(e2) => {
                                         tracing::error!("Failed to revert to old address: {}", e2);
@@ -238,67 +1400,29 @@ impl ProxyService for TokioProxyService {
                 accept_result = listener.accept() => {
                     match accept_result {
                         Ok((stream, peer_addr)) => {
-                            // Check max connections
-                            let config = self.config.load();
-                            if let Some(max_conns) = config.max_connections {
-                                let routing = ctx.routing_table();
-                                let total_connections: usize = routing
-                                    .all_backends()
-                                    .iter()
-                                    .map(|b| b.active_connections())
-                                    .sum();
-                                if total_connections >= max_conns as usize {
-                                    tracing::warn!(
-                                        "Max connections reached ({}), rejecting connection from {}",
-                                        max_conns,
-                                        peer_addr
-                                    );
-                                    drop(stream);
-                                    continue;
-                                }
-                            }
-
-                            // Pick backend using strategy
-                            let strategy = ctx.strategy();
-                            let backend_meta = match strategy.pick_backend(ctx.clone()).await {
-                                Ok(b) => b,
-                                Err(e) => {
-                                    tracing::warn!("No backend available: {}", e);
-                                    drop(stream);
-                                    continue;
-                                }
-                            };
-
-                            // Get backend from route table
-                            let routing = ctx.routing_table();
-                            let backend = match routing.get(*backend_meta.id()) {
-                                Some(b) => b,
-                                None => {
-                                    tracing::warn!("Backend {} not found in route table", backend_meta.id());
-                                    drop(stream);
-                                    continue;
-                                }
-                            };
-
-                            // Check if backend accepts new connections (not draining and healthy)
-                            if !backend.can_accept_new_connections() {
-                                tracing::debug!(
-                                    "Backend {} is draining or unhealthy, cannot accept new connection",
-                                    backend.id()
+                            self.serve_accepted(&ctx, &mut routing, &mut conn_tasks, stream, peer_addr)
+                                .await;
+                        }
+                        Err(e) if is_fd_exhausted(&e) => {
+                            // Retrying would fail again until a descriptor is
+                            // freed, so stop accepting until a connection
+                            // closes (or the pause elapses)
+                            if ctx.should_log("proxy.fd_exhausted") {
+                                tracing::warn!(
+                                    "Out of file descriptors ({}), pausing accepts for up to {:?}",
+                                    e,
+                                    FD_EXHAUSTED_PAUSE
                                 );
-                                drop(stream);
-                                continue;
                             }
-
-                            // Spawn connection handler (clone ctx before move)
-                            let svc_clone = self.clone();
-                            let ctx_clone = ctx.clone();
-                            conn_tasks.spawn(async move {
-                                let _ = svc_clone.handle_connection(stream, backend, ctx_clone).await;
-                            });
+                            tokio::select! {
+                                Some(_) = conn_tasks.join_next() => {}
+                                _ = tokio::time::sleep(FD_EXHAUSTED_PAUSE) => {}
+                            }
                         }
                         Err(e) => {
-                            tracing::error!("Accept error: {}", e);
+                            if ctx.should_log("proxy.accept_error") {
+                                tracing::error!("Accept error: {}", e);
+                            }
                             // Brief pause to avoid tight loop on errors
                             tokio::time::sleep(Duration::from_millis(100)).await;
                         }
@@ -312,16 +1436,245 @@ impl ProxyService for TokioProxyService {
             }
         }
 
-        // Shutdown: wait for active connections to finish
-        tracing::info!(
-            "Waiting for {} active connections to complete",
-            conn_tasks.len()
-        );
-        while (conn_tasks.join_next().await).is_some() {
-            // Drain all active connection tasks
+        // Stop accepting, then serve the connections the kernel already
+        // completed: dropping the listener with them queued would reset
+        // clients that connected before the shutdown
+        let queued = match listener.into_std() {
+            Ok(listener) => take_queued(|| {
+                let (stream, peer_addr) = listener.accept()?;
+                stream.set_nonblocking(true)?;
+                Ok((TcpStream::from_std(stream)?, peer_addr))
+            }),
+            Err(e) => {
+                tracing::warn!("Failed to take queued connections: {}", e);
+                Vec::new()
+            }
+        };
+        if !queued.is_empty() {
+            tracing::info!("Serving {} connections queued at shutdown", queued.len());
+        }
+        for (stream, peer_addr) in queued {
+            self.serve_accepted(&ctx, &mut routing, &mut conn_tasks, stream, peer_addr)
+                .await;
         }
 
-        tracing::info!("All proxy connections closed");
+        Self::drain_connection_tasks(&ctx, conn_tasks).await;
         Ok(())
     }
 }
+
+/// Most connections taken off a listener's queue at shutdown
+///
+/// Tokio listens with a backlog of 1024, so no more can be queued when the
+/// accept loop stops; the cap keeps clients still connecting from holding
+/// off the drain.
+const LISTEN_BACKLOG: usize = 1024;
+
+/// Accept the connections queued on a listener, without waiting for more
+///
+/// `accept` is a non-blocking accept on the listener; taking stops once it
+/// fails (`WouldBlock` when the queue is empty) or after [`LISTEN_BACKLOG`]
+/// connections.
+fn take_queued<T>(mut accept: impl FnMut() -> io::Result<T>) -> Vec<T> {
+    let mut queued = Vec::new();
+    while queued.len() < LISTEN_BACKLOG {
+        match accept() {
+            Ok(connection) => queued.push(connection),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+    queued
+}
+
+/// Strategy and route table held by the accept loop between accepts
+///
+/// Reloaded only when the context's generation changes, so an accept does
+/// not pay for loading them from the context every time.
+struct RoutingSnapshot {
+    generation: u64,
+    strategy: Arc<dyn StrategyService>,
+    route_table: Arc<RouteTable>,
+}
+
+impl RoutingSnapshot {
+    /// Take the context's current strategy and route table
+    fn new(ctx: &Context) -> Self {
+        // Read the generation first: a swap racing with the loads below
+        // bumps it again and is picked up by the next refresh
+        let generation = ctx.generation();
+        Self {
+            generation,
+            strategy: ctx.strategy().as_ref().clone(),
+            route_table: ctx.routing_table(),
+        }
+    }
+
+    /// Reload the strategy and route table if they were swapped
+    fn refresh(&mut self, ctx: &Context) {
+        if ctx.generation() != self.generation {
+            *self = Self::new(ctx);
+        }
+    }
+}
+
+/// Whether `peer` has the address of a backend in `route_table`
+///
+/// Only IP literal backend addresses are compared, on the IP alone (the
+/// peer port is ephemeral).
+fn is_backend_peer(route_table: &RouteTable, peer: SocketAddr) -> bool {
+    let peer_ip = peer.ip().to_canonical();
+    route_table.all_backends().iter().any(|backend| {
+        backend
+            .address()
+            .as_str()
+            .parse::<SocketAddr>()
+            .is_ok_and(|address| address.ip().to_canonical() == peer_ip)
+    })
+}
+
+/// Connection from a backend address, counted until dropped
+struct BackendPeerGuard {
+    count: Arc<AtomicUsize>,
+}
+
+impl BackendPeerGuard {
+    /// Count one more connection, unless `max` are already open
+    fn acquire(count: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |open| {
+                (open < max).then_some(open + 1)
+            })
+            .ok()
+            .map(|_| Self {
+                count: count.clone(),
+            })
+    }
+}
+
+impl Drop for BackendPeerGuard {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Backend chosen for a connection, before a connect slot is reserved on it
+struct Selection {
+    backend: Arc<Backend>,
+    /// Whether the strategy picked it (sticky clients go back to theirs)
+    picked: bool,
+}
+
+/// Slot reserved for a connect to a backend, released when dropped
+///
+/// Held from the pick until the connect resolves, so a backend slow to
+/// accept never has more than the route table's `max_pending_connects`
+/// connects in flight.
+struct PendingConnect {
+    backend: Arc<Backend>,
+}
+
+impl PendingConnect {
+    /// Reserve a slot on `backend` for a connect, unless it is at the cap of
+    /// `routing` or the circuit breaker of `ctx` skips it
+    ///
+    /// Taking the slot first keeps a backend at the cap from spending its
+    /// circuit's half-open trial.
+    fn admit(ctx: &Context, routing: &RouteTable, backend: Arc<Backend>) -> Option<Self> {
+        let pending = Self::reserve(routing, backend)?;
+        pending
+            .circuit()
+            .try_acquire(
+                ctx.clock().now_millis(),
+                &ctx.config().proxy.circuit_breaker,
+            )
+            .then_some(pending)
+    }
+
+    /// Reserve a slot on `backend`, unless it is at the cap of `routing`
+    fn reserve(routing: &RouteTable, backend: Arc<Backend>) -> Option<Self> {
+        // Built only once reserved: dropping it releases the slot
+        let max = routing.max_pending_connects().unwrap_or(usize::MAX);
+        if backend.try_reserve_connect(max) {
+            Some(Self { backend })
+        } else {
+            None
+        }
+    }
+}
+
+impl std::ops::Deref for PendingConnect {
+    type Target = Backend;
+
+    fn deref(&self) -> &Backend {
+        &self.backend
+    }
+}
+
+impl Drop for PendingConnect {
+    fn drop(&mut self) {
+        self.backend.release_connect();
+    }
+}
+
+/// Backend connect in flight, counted as a connection to the backend
+///
+/// Dropping an attempt that was not committed (connect failed or lost a
+/// hedge race) releases the count and reports the connection closed.
+struct ConnectAttempt {
+    ctx: Arc<Context>,
+    backend: Arc<Backend>,
+    peer: PeerInfo,
+    committed: bool,
+    // Released once the attempt resolves, committed or not
+    _pending: PendingConnect,
+}
+
+impl ConnectAttempt {
+    /// Count a new connection to the backend of `pending`
+    fn start(ctx: Arc<Context>, pending: PendingConnect, peer: PeerInfo) -> Self {
+        let backend = pending.backend.clone();
+        backend.increment_connection();
+
+        // Send connection opened event (non-blocking)
+        let _ = ctx
+            .channels()
+            .connection_tx()
+            .try_send(ConnectionEvent::Opened {
+                backend_id: backend.id(),
+                peer,
+            });
+
+        Self {
+            ctx,
+            backend,
+            peer,
+            committed: false,
+            _pending: pending,
+        }
+    }
+
+    /// Keep the count for the established connection
+    fn commit(mut self) -> Arc<Backend> {
+        self.committed = true;
+        self.backend.clone()
+    }
+}
+
+impl Drop for ConnectAttempt {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        self.backend.decrement_connection();
+        self.ctx.notify_connection_closed();
+        let _ = self
+            .ctx
+            .channels()
+            .connection_tx()
+            .try_send(ConnectionEvent::Closed {
+                backend_id: self.backend.id(),
+                peer: self.peer,
+            });
+    }
+}
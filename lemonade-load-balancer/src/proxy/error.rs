@@ -7,6 +7,17 @@ pub enum ProxyError {
     /// Connection refused error
     #[error("tcp stream error: {0}")]
     Io(#[from] tokio::io::Error),
+    /// Backend socket could not be bound to the configured source address
+    #[error("backend {backend_id}: cannot bind to source address {address}: {error}")]
+    SourceBindFailed {
+        /// Backend the connection was for
+        backend_id: crate::types::BackendId,
+        /// Configured source address
+        address: std::net::IpAddr,
+        /// Why binding failed
+        #[source]
+        error: tokio::io::Error,
+    },
     /// Unexpected error
     #[error("unexpected error: {0}")]
     Unexpected(String),
@@ -2,14 +2,635 @@
 //!
 use crate::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 
 /// Proxy config struct
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProxyConfig {
     /// Listen address
     pub listen_address: SocketAddr,
     /// Max connections
     pub max_connections: Option<u64>,
+    /// Write coalescing for the copy loops (off by default)
+    #[serde(default)]
+    pub coalesce: CoalesceConfig,
+    /// Hedged backend connects (off by default)
+    #[serde(default)]
+    pub hedging: HedgingConfig,
+    /// Budget shared by retry and hedge attempts
+    #[serde(default)]
+    pub retry_budget: RetryBudgetConfig,
+    /// Per-backend circuit breaker on connect failures (off by default)
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Route TLS connections to backend groups by SNI (off by default)
+    #[serde(default)]
+    pub route_by_sni: SniRoutingConfig,
+    /// Route TLS connections to backend groups by ALPN (off by default)
+    #[serde(default)]
+    pub route_by_alpn: AlpnRoutingConfig,
+    /// Route cleartext HTTP/1 connections to backend groups by host and
+    /// path (off by default)
+    #[serde(default)]
+    pub route_by_http: HttpRoutingConfig,
+    /// Warn about backend connects slower than this, in milliseconds (off
+    /// when unset)
+    #[serde(default)]
+    pub slow_connect_warn_ms: Option<u64>,
+    /// Warn about connections open longer than this, in seconds (off when
+    /// unset)
+    #[serde(default)]
+    pub slow_connection_warn_secs: Option<u64>,
+    /// Warn about strategy picks slower than this, in microseconds (off when
+    /// unset)
+    #[serde(default)]
+    pub strategy_pick_warn_micros: Option<u64>,
+    /// Wait this long, in milliseconds, for the client's first bytes before
+    /// picking a backend; silent clients are closed without a backend
+    /// connect (off when unset)
+    #[serde(default)]
+    pub initial_read_timeout_millis: Option<u64>,
+    /// Most slow warnings of each kind logged per backend per minute
+    #[serde(default = "default_slow_log_max_per_minute")]
+    pub slow_log_max_per_minute: u32,
+    /// Most bytes a connection holds per direction between reading from one
+    /// side and writing to the other; reading pauses at the cap until the
+    /// slower side catches up
+    #[serde(default = "default_max_buffered_bytes")]
+    pub max_buffered_bytes: usize,
+    /// Local address backend connections are made from, for backends
+    /// without their own `bind_address` (the OS picks when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend_bind_address: Option<IpAddr>,
+    /// DSCP value (0-63) packets to backends are marked with, for backends
+    /// without their own `dscp` (unmarked when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend_dscp: Option<u8>,
+    /// File descriptors kept back from proxied connections for health
+    /// probes, the admin API and metrics export; the rest of the open file
+    /// limit, at two per connection, caps `max_connections`
+    #[serde(default = "default_fd_headroom")]
+    pub fd_headroom: u64,
+    /// Most connections open at once from clients whose address is a
+    /// backend's; further ones are rejected as a likely proxy loop (off when
+    /// unset)
+    #[serde(default)]
+    pub max_backend_peer_connections: Option<usize>,
+    /// Most load balancers the first request of a cleartext HTTP/1
+    /// connection may pass through, counted in an `X-Lemonade-Hop` request
+    /// header; past that the client is answered `508 Loop Detected` (off when
+    /// unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_hops: Option<u32>,
+    /// Read each client's original destination (`SO_ORIGINAL_DST`, Linux
+    /// only) for connections redirected to the listener by iptables
+    #[serde(default)]
+    pub transparent: bool,
+    /// Report backends that reset a connection mid-transfer to health
+    /// checking, as a passive failure
+    #[serde(default = "default_report_backend_aborts")]
+    pub report_backend_aborts: bool,
+    /// Send each client IP to the backend it was last sent to, while that
+    /// backend stays healthy and routed (off by default)
+    #[serde(default)]
+    pub sticky_sessions: bool,
+    /// How long a client stays stuck to its backend after its last
+    /// connection, in milliseconds
+    #[serde(default = "default_sticky_ttl_millis")]
+    pub sticky_ttl_millis: u64,
+    /// Unix socket to accept on instead of `listen_address`, written
+    /// `unix:/path/to.sock` or `unix:@name` (abstract, Linux only); only the
+    /// top-level backends are served on it. Takes effect on restart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen_unix: Option<UnixAddress>,
+    /// Connects in flight a backend takes before picks skip it: a backend
+    /// slow to accept gets at most this many, and the others take the
+    /// overflow (at least 1)
+    #[serde(default = "default_max_pending_connects")]
+    pub max_pending_connects: usize,
+    /// Zone this load balancer runs in: picks prefer healthy backends whose
+    /// `zone` matches (no preference when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_zone: Option<String>,
+    /// Healthy `local_zone` backends with room for another connect needed
+    /// to keep picks in the zone; below that, picks spill over to every zone
+    /// (at least 1)
+    #[serde(default = "default_zone_spillover_min_healthy")]
+    pub zone_spillover_min_healthy: usize,
+    /// Tell backends how long the first request of each cleartext HTTP/1
+    /// connection has left, in an `X-Lemonade-Deadline-Ms` request header
+    /// (off by default). First request only: later requests on a keep-alive
+    /// connection are forwarded unchanged, and only the first response is
+    /// checked for a `504`
+    #[serde(default)]
+    pub propagate_deadlines: bool,
+    /// Time budget of a connection's first request from accept, in
+    /// milliseconds, that propagated deadlines count down from
+    #[serde(default = "default_request_timeout_millis")]
+    pub request_timeout_millis: u64,
+}
+
+/// Default for [`ProxyConfig::slow_log_max_per_minute`]
+fn default_slow_log_max_per_minute() -> u32 {
+    10
+}
+
+/// Default for [`ProxyConfig::report_backend_aborts`]
+pub(crate) fn default_report_backend_aborts() -> bool {
+    true
+}
+
+/// Default for [`ProxyConfig::sticky_ttl_millis`] (5 minutes)
+pub(crate) fn default_sticky_ttl_millis() -> u64 {
+    300_000
+}
+
+/// Default for [`ProxyConfig::fd_headroom`]
+pub(crate) fn default_fd_headroom() -> u64 {
+    64
+}
+
+/// Default for [`ProxyConfig::max_pending_connects`]
+pub(crate) fn default_max_pending_connects() -> usize {
+    32
+}
+
+/// Default for [`ProxyConfig::zone_spillover_min_healthy`]
+pub(crate) fn default_zone_spillover_min_healthy() -> usize {
+    1
+}
+
+/// Default for [`ProxyConfig::request_timeout_millis`] (30 seconds)
+pub(crate) fn default_request_timeout_millis() -> u64 {
+    30_000
+}
+
+/// Default for [`ProxyConfig::max_buffered_bytes`] (256 KiB)
+pub(crate) fn default_max_buffered_bytes() -> usize {
+    256 * 1024
+}
+
+/// Write coalescing config
+///
+/// When enabled, small reads are accumulated and written to the peer in one
+/// go once `max_bytes` are pending or `coalesce_micros` have elapsed since the
+/// first pending byte, trading up to that delay for fewer write syscalls on
+/// chatty protocols.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoalesceConfig {
+    /// Enable write coalescing
+    pub enabled: bool,
+    /// Longest time pending data is held back, in microseconds
+    pub coalesce_micros: u64,
+    /// Pending bytes that trigger an immediate flush
+    pub max_bytes: usize,
+}
+
+impl Default for CoalesceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            coalesce_micros: 1000,
+            max_bytes: 16 * 1024,
+        }
+    }
+}
+
+/// Hedged connect config
+///
+/// When enabled, a backend connect that has not completed after the hedge
+/// delay is raced against a connect to a second healthy backend; the first
+/// to succeed serves the connection and the other attempt is cancelled.
+/// At most `max_hedge_rate` of the connections may hedge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HedgingConfig {
+    /// Enable hedged connects
+    pub enabled: bool,
+    /// Static hedge delay in milliseconds
+    pub delay_millis: u64,
+    /// Use the primary backend's p95 connect time as the hedge delay once it
+    /// has connect samples (`delay_millis` is used until then)
+    pub adaptive_delay: bool,
+    /// Fraction of connections allowed to hedge (0.0 to 1.0)
+    pub max_hedge_rate: f64,
+}
+
+impl HedgingConfig {
+    /// Validate the hedging settings
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(0.0..=1.0).contains(&self.max_hedge_rate) {
+            return Err(ConfigError::Proxy(format!(
+                "hedging.max_hedge_rate must be between 0.0 and 1.0, got {}",
+                self.max_hedge_rate
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for HedgingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay_millis: 50,
+            adaptive_delay: false,
+            max_hedge_rate: 0.05,
+        }
+    }
+}
+
+/// Retry budget config
+///
+/// Every successful backend connect earns `ratio` of an extra attempt, up to
+/// `max_tokens`; each retry or hedge spends one. With the budget spent,
+/// extra attempts are skipped and the original result is surfaced, so a
+/// struggling pool sees at most `ratio` more attempts than successes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryBudgetConfig {
+    /// Enforce the budget (extra attempts are unlimited when disabled)
+    pub enabled: bool,
+    /// Extra attempts earned per successful connect
+    pub ratio: f64,
+    /// Most attempts that can be saved up, also the initial budget
+    pub max_tokens: u32,
+}
+
+impl RetryBudgetConfig {
+    /// Validate the retry budget settings
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !self.ratio.is_finite() || self.ratio < 0.0 {
+            return Err(ConfigError::Proxy(format!(
+                "retry_budget.ratio must be a non-negative number, got {}",
+                self.ratio
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for RetryBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ratio: 0.2,
+            max_tokens: 10,
+        }
+    }
+}
+
+/// Circuit breaker config
+///
+/// When enabled, a backend whose connects fail `failure_threshold` times in
+/// a row has its circuit opened: picks landing on it are skipped for
+/// `open_duration_millis`. The first connect after that is a half-open
+/// trial; its success closes the circuit and its failure opens it again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Enable the circuit breaker
+    pub enabled: bool,
+    /// Connect failures in a row that open a backend's circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit skips its backend, in milliseconds
+    pub open_duration_millis: u64,
+}
+
+impl CircuitBreakerConfig {
+    /// Validate the circuit breaker settings
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.failure_threshold == 0 || self.open_duration_millis == 0 {
+            return Err(ConfigError::Proxy(
+                "circuit_breaker.failure_threshold and open_duration_millis must be at least 1"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            failure_threshold: 5,
+            open_duration_millis: 30_000,
+        }
+    }
+}
+
+/// SNI routing config
+///
+/// When enabled, the start of each connection is read to find the server
+/// name of a TLS ClientHello; the bytes read are replayed to the chosen
+/// backend before the normal copy. The name picks a backend group through
+/// the longest matching suffix in `routes`. Non-TLS connections, missing
+/// SNI, unmatched names and clients silent past `peek_timeout_millis` use
+/// the `fallback` group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SniRoutingConfig {
+    /// Enable SNI routing
+    pub enabled: bool,
+    /// Host name suffix to backend label selector (`example.com` matches the
+    /// name itself and its subdomains)
+    pub routes: BTreeMap<String, LabelSelector>,
+    /// Backend group for connections no route matches (empty selects all)
+    pub fallback: LabelSelector,
+    /// Longest time to wait for the ClientHello, in milliseconds
+    pub peek_timeout_millis: u64,
+    /// Most bytes buffered while looking for the ClientHello
+    pub max_peek_bytes: usize,
+}
+
+impl SniRoutingConfig {
+    /// Backend group for a connection with this server name
+    pub fn selector_for(&self, server_name: Option<&str>) -> &LabelSelector {
+        let Some(name) = server_name else {
+            return &self.fallback;
+        };
+        let name = name.trim_end_matches('.').to_ascii_lowercase();
+        self.routes
+            .iter()
+            .filter_map(|(suffix, selector)| {
+                let suffix = suffix.trim_start_matches("*.").trim_start_matches('.');
+                let suffix = suffix.trim_end_matches('.').to_ascii_lowercase();
+                let matched = name == suffix
+                    || name
+                        .strip_suffix(suffix.as_str())
+                        .is_some_and(|head| head.ends_with('.'));
+                matched.then_some((suffix.len(), selector))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map_or(&self.fallback, |(_, selector)| selector)
+    }
+
+    /// Validate the SNI routing settings
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.enabled && self.max_peek_bytes < 5 {
+            return Err(ConfigError::Proxy(format!(
+                "route_by_sni.max_peek_bytes must be at least 5, got {}",
+                self.max_peek_bytes
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SniRoutingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            routes: BTreeMap::new(),
+            fallback: LabelSelector::default(),
+            peek_timeout_millis: 200,
+            max_peek_bytes: 16 * 1024 + 5,
+        }
+    }
+}
+
+/// ALPN routing config
+///
+/// When enabled, the ClientHello is read as for SNI routing (with the
+/// `route_by_sni` peek settings) and the first protocol the client offers
+/// that has a route picks the backend group. Non-TLS connections, malformed
+/// ClientHellos, clients offering no routed protocol and clients silent past
+/// the peek timeout use the `fallback` group.
+///
+/// With SNI routing enabled too, SNI picks first and ALPN narrows within its
+/// group; an ALPN group that contradicts the SNI group's labels, or leaves no
+/// backend to pick, is ignored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlpnRoutingConfig {
+    /// Enable ALPN routing
+    pub enabled: bool,
+    /// Protocol name (e.g. `h2`, `http/1.1`) to backend label selector
+    pub routes: BTreeMap<String, LabelSelector>,
+    /// Backend group for connections no route matches (empty selects all)
+    pub fallback: LabelSelector,
+}
+
+impl AlpnRoutingConfig {
+    /// Backend group for a connection offering these protocols
+    pub fn selector_for(&self, protocols: &[String]) -> &LabelSelector {
+        protocols
+            .iter()
+            .find_map(|protocol| self.routes.get(protocol))
+            .unwrap_or(&self.fallback)
+    }
+
+    /// Validate the ALPN routing settings
+    pub fn validate(&self, sni: &SniRoutingConfig) -> Result<(), ConfigError> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if sni.max_peek_bytes < 5 {
+            return Err(ConfigError::Proxy(format!(
+                "route_by_sni.max_peek_bytes must be at least 5 for ALPN routing, got {}",
+                sni.max_peek_bytes
+            )));
+        }
+        if let Some(protocol) = self
+            .routes
+            .keys()
+            .find(|protocol| protocol.is_empty() || protocol.len() > 255)
+        {
+            return Err(ConfigError::Proxy(format!(
+                "route_by_alpn protocol names must be 1 to 255 bytes, got {:?}",
+                protocol
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Route reported for requests no rule matches that use the fallback group
+pub const FALLBACK_ROUTE: &str = "fallback";
+
+/// HTTP routing config
+///
+/// When enabled, the first request head of each connection is read and
+/// `rules` are tried in order: the first whose `host` and `path_prefix` both
+/// match picks the backend group, and the strategy picks a backend in it.
+/// Later requests on a keep-alive connection go to the same backend.
+/// Requests no rule matches, and connections sending no request head within
+/// the peek timeout, use the `fallback` group, or are answered
+/// `404 Not Found` with `reject_unmatched`.
+///
+/// Read from the context's config for each connection, so rule changes apply
+/// to the next connection without a restart.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpRoutingConfig {
+    /// Enable HTTP routing
+    pub enabled: bool,
+    /// Rules, tried in order
+    pub rules: Vec<HttpRouteRule>,
+    /// Backend group for requests no rule matches (empty selects all)
+    pub fallback: LabelSelector,
+    /// Answer requests no rule matches `404 Not Found` instead of using
+    /// `fallback`
+    pub reject_unmatched: bool,
+}
+
+/// HTTP routing rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpRouteRule {
+    /// Name the rule is reported under, as the `route` metric attribute
+    pub name: String,
+    /// Host the request must be for, port and case aside (any when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Start of the paths matched (`/api/` matches `/api/users`, not `/api`)
+    #[serde(default = "default_path_prefix")]
+    pub path_prefix: String,
+    /// Backend group the matched requests go to
+    pub group: LabelSelector,
+}
+
+/// Default for [`HttpRouteRule::path_prefix`]
+fn default_path_prefix() -> String {
+    "/".to_string()
+}
+
+impl HttpRouteRule {
+    /// Check whether `request` is for this rule's host and path
+    pub fn matches(&self, request: &RequestMeta) -> bool {
+        let host_matches = self.host.as_deref().is_none_or(|host| {
+            request.header("host").is_some_and(|sent| {
+                let sent = sent.trim();
+                // Port aside, leaving bracketed IPv6 literals whole
+                let sent = match sent.rsplit_once(':') {
+                    Some((name, port))
+                        if !name.ends_with(':')
+                            && port.bytes().all(|b| b.is_ascii_digit()) =>
+                    {
+                        name
+                    }
+                    _ => sent,
+                };
+                sent.trim_end_matches('.')
+                    .eq_ignore_ascii_case(host.trim_end_matches('.'))
+            })
+        });
+        host_matches && request.path.starts_with(&self.path_prefix)
+    }
+}
+
+impl HttpRoutingConfig {
+    /// Route and backend group for a connection whose first request is
+    /// `request` (`None` when no request head was read)
+    ///
+    /// The route is the matching rule's name, or [`FALLBACK_ROUTE`]. Returns
+    /// `None` when nothing matches and `reject_unmatched` is set.
+    pub fn route_for(
+        &self,
+        request: Option<&RequestMeta>,
+    ) -> Option<(&str, &LabelSelector)> {
+        let rule = request
+            .and_then(|request| self.rules.iter().find(|rule| rule.matches(request)));
+        match rule {
+            Some(rule) => Some((rule.name.as_str(), &rule.group)),
+            None if self.reject_unmatched => None,
+            None => Some((FALLBACK_ROUTE, &self.fallback)),
+        }
+    }
+
+    /// Validate the rules against the backends they route to
+    ///
+    /// Rule names must be distinct and not [`FALLBACK_ROUTE`], paths
+    /// prefixes start with `/`, hosts be non-empty, and each rule's group
+    /// select at least one of `backends`.
+    pub fn validate(&self, backends: &[BackendConfig]) -> Result<(), ConfigError> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let mut names = HashSet::new();
+        for rule in &self.rules {
+            if rule.name.is_empty() || rule.name == FALLBACK_ROUTE {
+                return Err(ConfigError::Proxy(format!(
+                    "route_by_http rule names must be non-empty and not {}, got {:?}",
+                    FALLBACK_ROUTE, rule.name
+                )));
+            }
+            if !names.insert(rule.name.as_str()) {
+                return Err(ConfigError::Proxy(format!(
+                    "route_by_http rule {} is defined twice",
+                    rule.name
+                )));
+            }
+            if !rule.path_prefix.starts_with('/')
+                || rule.path_prefix.contains(char::is_whitespace)
+            {
+                return Err(ConfigError::Proxy(format!(
+                    "route_by_http rule {} path_prefix must start with / and have no whitespace, got {:?}",
+                    rule.name, rule.path_prefix
+                )));
+            }
+            if rule.host.as_deref().is_some_and(|host| {
+                host.trim_end_matches('.').is_empty()
+                    || host.contains(|c: char| c.is_whitespace() || c == '/')
+            }) {
+                return Err(ConfigError::Proxy(format!(
+                    "route_by_http rule {} host must be a host name, got {:?}",
+                    rule.name, rule.host
+                )));
+            }
+            if !backends
+                .iter()
+                .any(|backend| rule.group.matches(&backend.labels))
+            {
+                return Err(ConfigError::Proxy(format!(
+                    "route_by_http rule {} routes to group {}, which selects no backend",
+                    rule.name, rule.group
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ProxyConfig {
+    /// Whether connections are routed by their ClientHello (SNI or ALPN)
+    pub fn routes_by_client_hello(&self) -> bool {
+        self.route_by_sni.enabled || self.route_by_alpn.enabled
+    }
+
+    /// Whether the first request head of cleartext connections is read and
+    /// rewritten (deadline propagation or hop counting)
+    pub fn rewrites_request_heads(&self) -> bool {
+        self.propagate_deadlines || self.max_hops.is_some()
+    }
+
+    /// Backend groups to try for a connection, most specific first
+    ///
+    /// `hello` is `None` for connections without a usable ClientHello. The
+    /// SNI group comes first; ALPN narrows it when both are enabled, with
+    /// the SNI group last, for when the narrowed group has no backend.
+    pub fn client_hello_selectors(
+        &self,
+        hello: Option<&ClientHelloInfo>,
+    ) -> Vec<LabelSelector> {
+        let alpn = self
+            .route_by_alpn
+            .selector_for(hello.map_or(&[], |hello| hello.alpn.as_slice()));
+        let sni = self
+            .route_by_sni
+            .selector_for(hello.and_then(|hello| hello.server_name.as_deref()));
+        match (self.route_by_sni.enabled, self.route_by_alpn.enabled) {
+            (false, true) => return vec![alpn.clone()],
+            (true, false) => return vec![sni.clone()],
+            (false, false) => return vec![LabelSelector::default()],
+            (true, true) => {}
+        }
+        match sni.and(alpn) {
+            Some(narrowed) if narrowed != *sni => vec![narrowed, sni.clone()],
+            _ => vec![sni.clone()],
+        }
+    }
 }
 
 /// Connection lifecycle events
@@ -21,13 +642,15 @@ pub struct ProxyConfig {
 /// # Examples
 ///
 /// ```no_run
-/// use lemonade_load_balancer::proxy::models::ConnectionEvent;
+/// use lemonade_load_balancer::prelude::{ConnectionEvent, PeerInfo};
+///
+/// let peer = PeerInfo::new("127.0.0.1:50000".parse().unwrap());
 ///
 /// // Report new connection opened
-/// let event = ConnectionEvent::Opened { backend_id: 0 };
+/// let event = ConnectionEvent::Opened { backend_id: 0, peer };
 ///
 /// // Report connection closed
-/// let event = ConnectionEvent::Closed { backend_id: 0 };
+/// let event = ConnectionEvent::Closed { backend_id: 0, peer };
 /// ```
 ///
 /// # Connection Lifecycle
@@ -50,6 +673,8 @@ pub enum ConnectionEvent {
     Opened {
         /// Backend identifier
         backend_id: BackendId,
+        /// Client of the connection
+        peer: PeerInfo,
     },
 
     /// A connection to a backend was closed
@@ -59,5 +684,7 @@ pub enum ConnectionEvent {
     Closed {
         /// Backend identifier
         backend_id: BackendId,
+        /// Client of the connection
+        peer: PeerInfo,
     },
 }
@@ -0,0 +1,201 @@
+//! Health state file module
+//!
+//! Persists backend health across restarts. The writer periodically saves a
+//! [`HealthStateSnapshot`] to the configured health state file, plus a final
+//! one during graceful shutdown; a new [`Context`] restores it onto the
+//! backends at the same addresses. Backends restored unhealthy stay out of
+//! rotation until the health service's startup sweep probes them.
+use super::writer::write_atomic;
+use crate::prelude::*;
+use std::path::Path;
+
+/// How often an idle writer checks whether a health state file was configured
+const IDLE_CHECK_INTERVAL_MILLIS: u64 = 1_000;
+
+/// Health state file writer
+#[derive(Debug, Clone, Default)]
+pub struct HealthStateWriter {
+    /// Backend groups captured alongside the writer's own context
+    groups: Option<Arc<Groups>>,
+}
+
+impl HealthStateWriter {
+    /// Create a writer capturing a single context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture every backend group in each snapshot
+    pub fn with_groups(mut self, groups: Arc<Groups>) -> Self {
+        self.groups = Some(groups);
+        self
+    }
+
+    /// Write snapshots until shutdown
+    ///
+    /// The writer idles while no `health_state_file` is configured, and
+    /// picks up path and interval changes from config reloads.
+    pub async fn run(&self, ctx: Arc<Context>) {
+        let mut shutdown_rx = ctx.channels().shutdown_rx();
+        // Whether the last write failed, so a persistent failure is logged once
+        let mut failing = false;
+
+        loop {
+            let interval = ctx
+                .config()
+                .health_state_file
+                .as_ref()
+                .map(|health_state| health_state.interval_millis)
+                .unwrap_or(IDLE_CHECK_INTERVAL_MILLIS);
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("Health state writer received shutdown signal");
+                    break;
+                }
+
+                _ = tokio::time::sleep(Duration::from_millis(interval)) => {
+                    let Some(health_state) = ctx.config().health_state_file.clone() else {
+                        continue;
+                    };
+                    match self.write(&ctx, &health_state.path).await {
+                        Ok(()) if failing => {
+                            tracing::info!(
+                                "Health state file {} written again",
+                                health_state.path.display()
+                            );
+                            failing = false;
+                        }
+                        Ok(()) => {}
+                        Err(e) if !failing => {
+                            tracing::warn!(
+                                "Failed to write health state file {}: {} (further failures logged at debug)",
+                                health_state.path.display(),
+                                e
+                            );
+                            failing = true;
+                        }
+                        Err(e) => {
+                            tracing::debug!(
+                                "Failed to write health state file {}: {}",
+                                health_state.path.display(),
+                                e
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Write the final snapshot during shutdown, if a health state file is
+    /// configured
+    pub async fn write_final(&self, ctx: &Arc<Context>) {
+        let Some(health_state) = ctx.config().health_state_file.clone() else {
+            return;
+        };
+        match self.write(ctx, &health_state.path).await {
+            Ok(()) => tracing::info!(
+                "Final health state written to {}",
+                health_state.path.display()
+            ),
+            Err(e) => tracing::warn!(
+                "Failed to write final health state to {}: {}",
+                health_state.path.display(),
+                e
+            ),
+        }
+    }
+
+    /// Capture a snapshot and atomically replace `path` with it
+    pub async fn write(&self, ctx: &Arc<Context>, path: &Path) -> std::io::Result<()> {
+        let snapshot = match &self.groups {
+            Some(groups) => {
+                HealthStateSnapshot::capture(groups.iter().map(|(_, ctx)| ctx))
+            }
+            None => HealthStateSnapshot::capture([ctx]),
+        };
+        write_atomic(path, &serde_json::to_vec_pretty(&snapshot)?).await
+    }
+}
+
+impl HealthStateFileConfig {
+    /// Apply the health states saved in the file to the backends of
+    /// `routing` at the same addresses
+    ///
+    /// Returns the number of backends restored. A missing file restores
+    /// nothing; a corrupt file, or one older than `max_state_age_millis` at
+    /// `now_ms`, is ignored with a warning.
+    pub fn restore(&self, routing: &RouteTable, now_ms: u64) -> usize {
+        let contents = match std::fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::debug!(
+                    "No health state file at {}, backends start healthy",
+                    self.path.display()
+                );
+                return 0;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Ignoring health state file {}: {}",
+                    self.path.display(),
+                    e
+                );
+                return 0;
+            }
+        };
+        let snapshot = match serde_json::from_slice::<HealthStateSnapshot>(&contents) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                tracing::warn!(
+                    "Ignoring corrupt health state file {}: {}",
+                    self.path.display(),
+                    e
+                );
+                return 0;
+            }
+        };
+        let age_ms = now_ms.saturating_sub(snapshot.written_at_ms);
+        if age_ms > self.max_state_age_millis {
+            tracing::warn!(
+                "Ignoring stale health state file {} ({}ms old, max_state_age_millis {})",
+                self.path.display(),
+                age_ms,
+                self.max_state_age_millis
+            );
+            return 0;
+        }
+
+        let mut restored = 0;
+        for backend in routing.all_backends() {
+            let Some(state) = snapshot.backends.get(backend.address().as_str()) else {
+                continue;
+            };
+            backend.restore_health(
+                state.alive,
+                state.consecutive_failures,
+                state.last_health_check_ms,
+            );
+            backend.circuit().restore(
+                state.circuit,
+                state.circuit_failures,
+                state.circuit_since_ms,
+            );
+            if !state.alive {
+                tracing::info!(
+                    "Backend {} ({}) restored unhealthy, out of rotation until probed",
+                    backend.id(),
+                    backend.address()
+                );
+            }
+            restored += 1;
+        }
+        tracing::info!(
+            "Restored the health of {} of {} backends from {}",
+            restored,
+            routing.len(),
+            self.path.display()
+        );
+        restored
+    }
+}
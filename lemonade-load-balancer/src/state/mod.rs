@@ -0,0 +1,6 @@
+//! State module
+//!
+
+pub mod health;
+pub mod models;
+pub mod writer;
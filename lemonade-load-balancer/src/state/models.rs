@@ -0,0 +1,299 @@
+//! State models module
+//!
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// State file config
+///
+/// Periodically writes a JSON snapshot of every backend group's route table
+/// and health to `path` (replaced atomically), plus a final snapshot during
+/// graceful shutdown, for tooling that reads the load balancer's view
+/// without an HTTP round trip.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateFileConfig {
+    /// Snapshot file path
+    pub path: PathBuf,
+    /// Interval between snapshots in milliseconds
+    #[serde(default = "default_interval_millis")]
+    pub interval_millis: u64,
+}
+
+/// Default for [`StateFileConfig::interval_millis`]
+fn default_interval_millis() -> u64 {
+    5_000
+}
+
+impl StateFileConfig {
+    /// Check that the file has a name and the interval is non-zero
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.path.file_name().is_none() {
+            return Err(ConfigError::StateFile(format!(
+                "state file path {} has no file name",
+                self.path.display()
+            )));
+        }
+        if self.interval_millis == 0 {
+            return Err(ConfigError::StateFile(
+                "state file interval must be greater than 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Health state file config
+///
+/// Persists every backend's health to `path`, keyed by backend address,
+/// periodically and during graceful shutdown. At startup the states found
+/// there are applied to the backends at the same addresses, so a restart
+/// does not send traffic to backends already known to be down.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthStateFileConfig {
+    /// Health state file path
+    pub path: PathBuf,
+    /// Interval between writes in milliseconds
+    #[serde(default = "default_interval_millis")]
+    pub interval_millis: u64,
+    /// Age in milliseconds past which a file is ignored at startup
+    #[serde(default = "default_max_state_age_millis")]
+    pub max_state_age_millis: u64,
+}
+
+/// Default for [`HealthStateFileConfig::max_state_age_millis`]
+fn default_max_state_age_millis() -> u64 {
+    300_000
+}
+
+impl HealthStateFileConfig {
+    /// Check that the file has a name and the interval and maximum age are
+    /// non-zero
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.path.file_name().is_none() {
+            return Err(ConfigError::StateFile(format!(
+                "health state file path {} has no file name",
+                self.path.display()
+            )));
+        }
+        if self.interval_millis == 0 {
+            return Err(ConfigError::StateFile(
+                "health state file interval must be greater than 0".to_string(),
+            ));
+        }
+        if self.max_state_age_millis == 0 {
+            return Err(ConfigError::StateFile(
+                "health state file max_state_age_millis must be greater than 0"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Snapshot written to the state file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// When the snapshot was taken, in milliseconds since the Unix epoch
+    pub written_at_ms: u64,
+    /// Whether this is the final snapshot written during shutdown
+    pub final_snapshot: bool,
+    /// State of every backend group, by name
+    pub groups: BTreeMap<String, GroupState>,
+}
+
+impl StateSnapshot {
+    /// Capture the state of `contexts`, timestamped with the first one's clock
+    pub fn capture<'a>(
+        contexts: impl IntoIterator<Item = &'a Arc<Context>>,
+        final_snapshot: bool,
+    ) -> Self {
+        let mut written_at_ms = None;
+        let groups = contexts
+            .into_iter()
+            .map(|ctx| {
+                written_at_ms.get_or_insert_with(|| ctx.clock().now_millis());
+                (ctx.group().to_string(), GroupState::capture(ctx))
+            })
+            .collect();
+        Self {
+            written_at_ms: written_at_ms.unwrap_or_default(),
+            final_snapshot,
+            groups,
+        }
+    }
+}
+
+/// State of one backend group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupState {
+    /// Route table and strategy generation
+    pub generation: u64,
+    /// Load balancing strategy
+    pub strategy: Strategy,
+    /// Proxy listen address
+    pub listen_address: SocketAddr,
+    /// Whether the proxy accepts new connections
+    pub accepting: bool,
+    /// Routed backends, by id
+    pub backends: Vec<BackendState>,
+    /// Outcomes of the proxy's backend failure reports
+    #[serde(default)]
+    pub backend_failures: BackendFailureStats,
+    /// Invariant violations found by the consistency audit
+    #[serde(default)]
+    pub invariant_violations: u64,
+}
+
+impl GroupState {
+    /// Capture the state of a context
+    pub fn capture(ctx: &Context) -> Self {
+        // Generation first: a swap racing with the reads below bumps it again
+        let generation = ctx.generation();
+        let config = ctx.config();
+        let now_ms = ctx.clock().now_millis();
+        let mut backends: Vec<BackendState> = ctx
+            .routing_table()
+            .all_backends()
+            .iter()
+            .map(|backend| BackendState::capture(backend, now_ms))
+            .collect();
+        backends.sort_by_key(|backend| backend.id);
+        Self {
+            generation,
+            strategy: config.strategy.clone(),
+            listen_address: config.proxy.listen_address,
+            accepting: ctx.readiness().is_accepting(),
+            backends,
+            backend_failures: ctx.channels().backend_failure_stats(),
+            invariant_violations: ctx.invariant_violations(),
+        }
+    }
+}
+
+/// State of one backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackendState {
+    /// Backend ID
+    pub id: BackendId,
+    /// Backend name
+    pub name: Option<String>,
+    /// Backend address
+    pub address: String,
+    /// Configured weight
+    pub weight: Option<u8>,
+    /// Weight after auto-weight and slow start scaling
+    pub effective_weight: f64,
+    /// Priority tier (lower is preferred)
+    #[serde(default)]
+    pub priority: u8,
+    /// Zone the backend runs in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+    /// Whether the last health check passed
+    pub alive: bool,
+    /// Whether the backend is draining
+    pub draining: bool,
+    /// Last health check time in milliseconds since the Unix epoch
+    pub last_health_check_ms: u64,
+    /// Open connections
+    pub active_connections: usize,
+    /// Connects in flight
+    #[serde(default)]
+    pub pending_connects: usize,
+    /// Picks that skipped the backend, per exclusion reason (selection trace)
+    #[serde(default)]
+    pub selection_exclusions: BTreeMap<String, u64>,
+}
+
+impl BackendState {
+    /// Capture the state of a backend at `now_ms`
+    pub fn capture(backend: &Backend, now_ms: u64) -> Self {
+        Self {
+            id: backend.id(),
+            name: backend.name().map(str::to_string),
+            address: backend.address().as_str().to_string(),
+            weight: backend.weight(),
+            effective_weight: backend.effective_weight_milli(now_ms) as f64 / 1000.0,
+            priority: backend.priority(),
+            zone: backend.zone().map(String::from),
+            alive: backend.is_alive(),
+            draining: backend.is_draining(),
+            last_health_check_ms: backend.last_health_check(),
+            active_connections: backend.active_connections(),
+            pending_connects: backend.pending_connects(),
+            selection_exclusions: backend
+                .selection_exclusions()
+                .into_iter()
+                .map(|(reason, count)| (reason.as_str().to_string(), count))
+                .collect(),
+        }
+    }
+}
+
+/// Snapshot written to the health state file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthStateSnapshot {
+    /// When the snapshot was taken, in milliseconds since the Unix epoch
+    pub written_at_ms: u64,
+    /// Health of every backend, by address
+    pub backends: BTreeMap<String, BackendHealthState>,
+}
+
+impl HealthStateSnapshot {
+    /// Capture the health of the backends of `contexts`, timestamped with the
+    /// first one's clock
+    ///
+    /// A backend address routed by several groups is recorded once.
+    pub fn capture<'a>(contexts: impl IntoIterator<Item = &'a Arc<Context>>) -> Self {
+        let mut written_at_ms = None;
+        let mut backends = BTreeMap::new();
+        for ctx in contexts {
+            written_at_ms.get_or_insert_with(|| ctx.clock().now_millis());
+            for backend in ctx.routing_table().all_backends() {
+                backends
+                    .entry(backend.address().as_str().to_string())
+                    .or_insert_with(|| BackendHealthState::capture(&backend));
+            }
+        }
+        Self {
+            written_at_ms: written_at_ms.unwrap_or_default(),
+            backends,
+        }
+    }
+}
+
+/// Persisted health of one backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackendHealthState {
+    /// Whether the last health check passed
+    pub alive: bool,
+    /// Unhealthy reports since the last healthy one
+    pub consecutive_failures: u32,
+    /// Last health check time in milliseconds since the Unix epoch
+    pub last_health_check_ms: u64,
+    /// State of the circuit breaker
+    #[serde(default)]
+    pub circuit: CircuitState,
+    /// Connect failures in a row counted by the circuit breaker
+    #[serde(default)]
+    pub circuit_failures: u32,
+    /// When the circuit last opened, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub circuit_since_ms: u64,
+}
+
+impl BackendHealthState {
+    /// Capture the health of a backend
+    pub fn capture(backend: &Backend) -> Self {
+        let circuit = backend.circuit();
+        Self {
+            alive: backend.is_alive(),
+            consecutive_failures: backend.consecutive_failures(),
+            last_health_check_ms: backend.last_health_check(),
+            circuit: circuit.state(),
+            circuit_failures: circuit.failures(),
+            circuit_since_ms: circuit.since_ms(),
+        }
+    }
+}
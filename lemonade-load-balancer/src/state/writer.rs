@@ -0,0 +1,141 @@
+//! State file writer module
+//!
+//! Periodically writes a [`StateSnapshot`] to the configured state file.
+//! The snapshot goes to a temporary file next to the target which is then
+//! renamed over it, so readers never see a partial file. Write failures are
+//! logged and never reach the proxy.
+use crate::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// How often an idle writer checks whether a state file was configured
+const IDLE_CHECK_INTERVAL_MILLIS: u64 = 1_000;
+
+/// State file writer
+#[derive(Debug, Clone, Default)]
+pub struct StateFileWriter {
+    /// Backend groups captured alongside the writer's own context
+    groups: Option<Arc<Groups>>,
+}
+
+impl StateFileWriter {
+    /// Create a writer capturing a single context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture every backend group in each snapshot
+    pub fn with_groups(mut self, groups: Arc<Groups>) -> Self {
+        self.groups = Some(groups);
+        self
+    }
+
+    /// Write snapshots until shutdown
+    ///
+    /// The writer idles while no `state_file` is configured, and picks up
+    /// path and interval changes from config reloads.
+    pub async fn run(&self, ctx: Arc<Context>) {
+        let mut shutdown_rx = ctx.channels().shutdown_rx();
+        // Whether the last write failed, so a persistent failure is logged once
+        let mut failing = false;
+
+        loop {
+            let interval = ctx
+                .config()
+                .state_file
+                .as_ref()
+                .map(|state_file| state_file.interval_millis)
+                .unwrap_or(IDLE_CHECK_INTERVAL_MILLIS);
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    tracing::info!("State file writer received shutdown signal");
+                    break;
+                }
+
+                _ = tokio::time::sleep(Duration::from_millis(interval)) => {
+                    let Some(state_file) = ctx.config().state_file.clone() else {
+                        continue;
+                    };
+                    match self.write(&ctx, &state_file.path, false).await {
+                        Ok(()) if failing => {
+                            tracing::info!(
+                                "State file {} written again",
+                                state_file.path.display()
+                            );
+                            failing = false;
+                        }
+                        Ok(()) => {}
+                        Err(e) if !failing => {
+                            tracing::warn!(
+                                "Failed to write state file {}: {} (further failures logged at debug)",
+                                state_file.path.display(),
+                                e
+                            );
+                            failing = true;
+                        }
+                        Err(e) => {
+                            tracing::debug!(
+                                "Failed to write state file {}: {}",
+                                state_file.path.display(),
+                                e
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Write the final snapshot during shutdown, if a state file is configured
+    pub async fn write_final(&self, ctx: &Arc<Context>) {
+        let Some(state_file) = ctx.config().state_file.clone() else {
+            return;
+        };
+        match self.write(ctx, &state_file.path, true).await {
+            Ok(()) => tracing::info!(
+                "Final state snapshot written to {}",
+                state_file.path.display()
+            ),
+            Err(e) => tracing::warn!(
+                "Failed to write final state snapshot to {}: {}",
+                state_file.path.display(),
+                e
+            ),
+        }
+    }
+
+    /// Capture a snapshot and atomically replace `path` with it
+    pub async fn write(
+        &self,
+        ctx: &Arc<Context>,
+        path: &Path,
+        final_snapshot: bool,
+    ) -> std::io::Result<()> {
+        let snapshot = match &self.groups {
+            Some(groups) => {
+                StateSnapshot::capture(groups.iter().map(|(_, ctx)| ctx), final_snapshot)
+            }
+            None => StateSnapshot::capture([ctx], final_snapshot),
+        };
+        write_atomic(path, &serde_json::to_vec_pretty(&snapshot)?).await
+    }
+}
+
+/// Write `contents` to a temporary file next to `path`, then rename it over
+/// `path` so readers never see a partial file
+pub(crate) async fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let temp_path = temp_path(path);
+    tokio::fs::write(&temp_path, contents).await?;
+    if let Err(e) = tokio::fs::rename(&temp_path, path).await {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Hidden temporary file next to `path` (same directory, so the rename is atomic)
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(".tmp");
+    path.with_file_name(name)
+}
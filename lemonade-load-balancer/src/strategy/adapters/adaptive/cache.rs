@@ -1,10 +1,16 @@
 //! Adaptive strategy cache module
 //!
 use crate::prelude::*;
+use std::sync::Weak;
 
 /// Cached score for a backend
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 struct CachedScore {
+    /// Backend scored, so a backend replaced under the same id never reads
+    /// its predecessor's score
+    backend: Weak<Backend>,
+    /// Backend weight at computation time
+    weight: Option<u8>,
     /// Computed score (lower is better)
     score: f64,
     /// Timestamp when score was computed (milliseconds)
@@ -13,9 +19,17 @@ struct CachedScore {
     metrics_version: u64,
     /// Connections version at computation time
     connections_version: u64,
+    /// Cache tick of the last read or write, for LRU eviction
+    last_used: AtomicU64,
 }
 
 /// Cache for adaptive strategy scores
+///
+/// Holds at most `max_entries` scores, evicting the least recently used one
+/// when full. A score is only returned for the very backend it was computed
+/// for, at the weight it had then, so scores survive migrations
+/// (`ConfigEvent::Migrated`) that keep a backend while a score computed for a
+/// removed backend is never returned for the backend reusing its id.
 pub struct AdaptiveCache {
     /// Cached scores per backend
     scores: DashMap<BackendId, CachedScore>,
@@ -25,6 +39,10 @@ pub struct AdaptiveCache {
     connections_version: AtomicU64,
     /// Cache TTL in milliseconds
     ttl_ms: u64,
+    /// Maximum number of cached scores
+    max_entries: usize,
+    /// Monotonic counter ordering reads and writes
+    tick: AtomicU64,
 }
 
 impl Default for AdaptiveCache {
@@ -37,19 +55,45 @@ impl Default for AdaptiveCache {
 impl AdaptiveCache {
     /// Create a new adaptive cache
     pub fn new(ttl_ms: u64) -> Self {
+        use super::constants::DEFAULT_CACHE_MAX_ENTRIES;
         Self {
             scores: DashMap::new(),
             metrics_version: AtomicU64::new(0),
             connections_version: AtomicU64::new(0),
             ttl_ms,
+            max_entries: DEFAULT_CACHE_MAX_ENTRIES,
+            tick: AtomicU64::new(0),
         }
     }
 
+    /// Bound the cache to `max_entries` scores (at least one)
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Cache TTL in milliseconds
+    pub fn ttl_ms(&self) -> u64 {
+        self.ttl_ms
+    }
+
+    /// Maximum number of cached scores
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
     /// Get cached score for a backend if valid
-    pub fn get(&self, backend_id: BackendId, now_ms: u64) -> Option<f64> {
-        let cached = self.scores.get(&backend_id)?;
+    pub fn get(&self, backend: &Arc<Backend>, now_ms: u64) -> Option<f64> {
+        let cached = self.scores.get(&backend.id())?;
         let cached_value = cached.value();
 
+        // Check that the score is for this backend at its current weight
+        if !std::ptr::eq(cached_value.backend.as_ptr(), Arc::as_ptr(backend))
+            || cached_value.weight != backend.weight()
+        {
+            return None;
+        }
+
         // Check if cache is still valid
         let age = now_ms.saturating_sub(cached_value.computed_at);
         if age > self.ttl_ms {
@@ -66,22 +110,60 @@ impl AdaptiveCache {
             return None; // Invalidated
         }
 
+        cached_value
+            .last_used
+            .store(self.next_tick(), Ordering::Relaxed);
         Some(cached_value.score)
     }
 
-    /// Store a score in cache
-    pub fn put(&self, backend_id: BackendId, score: f64, now_ms: u64) {
+    /// Store a score in cache, evicting the least recently used scores past
+    /// `max_entries`
+    pub fn put(&self, backend: &Arc<Backend>, score: f64, now_ms: u64) {
         let metrics_ver = self.metrics_version.load(Ordering::Relaxed);
         let conn_ver = self.connections_version.load(Ordering::Relaxed);
 
         let cached = CachedScore {
+            backend: Arc::downgrade(backend),
+            weight: backend.weight(),
             score,
             computed_at: now_ms,
             metrics_version: metrics_ver,
             connections_version: conn_ver,
+            last_used: AtomicU64::new(self.next_tick()),
         };
 
-        self.scores.insert(backend_id, cached);
+        self.scores.insert(backend.id(), cached);
+        while self.scores.len() > self.max_entries {
+            let Some(lru) = self
+                .scores
+                .iter()
+                .min_by_key(|entry| entry.value().last_used.load(Ordering::Relaxed))
+                .map(|entry| *entry.key())
+            else {
+                break;
+            };
+            self.scores.remove(&lru);
+        }
+    }
+
+    /// Drop the scores of backends no longer in `routing` (removed, or
+    /// replaced under the same id)
+    pub fn retain_routed(&self, routing: &RouteTable) {
+        self.scores.retain(|id, cached| {
+            routing.get(*id).is_some_and(|backend| {
+                std::ptr::eq(cached.backend.as_ptr(), Arc::as_ptr(&backend))
+            })
+        });
+    }
+
+    /// Backends with a cached score
+    pub fn backend_ids(&self) -> Vec<BackendId> {
+        self.scores.iter().map(|entry| *entry.key()).collect()
+    }
+
+    /// Next value of the access counter
+    fn next_tick(&self) -> u64 {
+        self.tick.fetch_add(1, Ordering::Relaxed)
     }
 }
 
@@ -89,6 +171,22 @@ impl AdaptiveCache {
 mod tests {
     use super::*;
 
+    /// Backend `id`, alone in its route table
+    fn test_backend(id: u8) -> Arc<Backend> {
+        let address = SocketAddr::from(([127, 0, 0, 1], 8080 + id as u16));
+        Arc::new(Backend::new(BackendConfig::from(BackendMeta::new(
+            id,
+            None::<String>,
+            address,
+            Some(1u8),
+        ))))
+    }
+
+    /// Backends 0 to 4
+    fn test_backends() -> Vec<Arc<Backend>> {
+        (0..5).map(test_backend).collect()
+    }
+
     #[test]
     fn adaptive_cache_new_should_succeed() {
         // Given: a TTL value
@@ -99,7 +197,7 @@ mod tests {
 
         // Then: cache is created successfully
         // Verify by using it
-        assert!(cache.get(1, 0).is_none()); // No cached value yet
+        assert!(cache.get(&test_backend(1), 0).is_none()); // No cached value yet
     }
 
     #[test]
@@ -110,20 +208,20 @@ mod tests {
 
         // Then: cache is created
         // Verify by using it
-        assert!(cache.get(1, 0).is_none()); // No cached value yet
+        assert!(cache.get(&test_backend(1), 0).is_none()); // No cached value yet
     }
 
     #[test]
     fn adaptive_cache_put_and_get_should_succeed() {
         // Given: an AdaptiveCache
         let cache = AdaptiveCache::new(1000);
-        let backend_id = 1u8;
+        let backend = test_backend(1);
         let score = 10.5;
         let now_ms = 1000u64;
 
         // When: putting a score and getting it
-        cache.put(backend_id, score, now_ms);
-        let retrieved = cache.get(backend_id, now_ms);
+        cache.put(&backend, score, now_ms);
+        let retrieved = cache.get(&backend, now_ms);
 
         // Then: score is retrieved correctly
         assert_eq!(retrieved, Some(10.5));
@@ -132,12 +230,14 @@ mod tests {
     #[test]
     fn adaptive_cache_get_expired_should_succeed() {
         // Given: an AdaptiveCache with cached score
+        let clock = MockClock::new(1000);
         let cache = AdaptiveCache::new(100); // TTL of 100ms
-        let backend_id = 1u8;
-        cache.put(backend_id, 10.5, 1000);
+        let backend = test_backend(1);
+        cache.put(&backend, 10.5, clock.now_millis());
 
         // When: getting score after TTL expires
-        let retrieved = cache.get(backend_id, 1200); // 200ms later, exceeds TTL
+        clock.advance(Duration::from_millis(200)); // exceeds TTL
+        let retrieved = cache.get(&backend, clock.now_millis());
 
         // Then: returns None (expired)
         assert_eq!(retrieved, None);
@@ -146,12 +246,14 @@ mod tests {
     #[test]
     fn adaptive_cache_get_within_ttl_should_succeed() {
         // Given: an AdaptiveCache with cached score
+        let clock = MockClock::new(1000);
         let cache = AdaptiveCache::new(1000); // TTL of 1000ms
-        let backend_id = 1u8;
-        cache.put(backend_id, 10.5, 1000);
+        let backend = test_backend(1);
+        cache.put(&backend, 10.5, clock.now_millis());
 
         // When: getting score within TTL
-        let retrieved = cache.get(backend_id, 1500); // 500ms later, within TTL
+        clock.advance(Duration::from_millis(500)); // within TTL
+        let retrieved = cache.get(&backend, clock.now_millis());
 
         // Then: score is returned
         assert_eq!(retrieved, Some(10.5));
@@ -163,7 +265,7 @@ mod tests {
         let cache = AdaptiveCache::new(1000);
 
         // When: getting score for non-existing backend
-        let retrieved = cache.get(99, 1000);
+        let retrieved = cache.get(&test_backend(99), 1000);
 
         // Then: returns None
         assert_eq!(retrieved, None);
@@ -173,43 +275,44 @@ mod tests {
     fn adaptive_cache_put_overwrite_should_succeed() {
         // Given: an AdaptiveCache with existing cached score
         let cache = AdaptiveCache::new(1000);
-        let backend_id = 1u8;
-        cache.put(backend_id, 10.5, 1000);
+        let backend = test_backend(1);
+        cache.put(&backend, 10.5, 1000);
 
         // When: putting a new score
-        cache.put(backend_id, 20.0, 2000);
+        cache.put(&backend, 20.0, 2000);
 
         // Then: new score is stored
-        let retrieved = cache.get(backend_id, 2000);
+        let retrieved = cache.get(&backend, 2000);
         assert_eq!(retrieved, Some(20.0));
     }
 
     #[test]
     fn adaptive_cache_multiple_backends_should_succeed() {
         // Given: an AdaptiveCache
+        let backends = test_backends();
         let cache = AdaptiveCache::new(1000);
         let now_ms = 1000u64;
 
         // When: putting scores for multiple backends
-        cache.put(1, 10.5, now_ms);
-        cache.put(2, 20.0, now_ms);
-        cache.put(3, 30.0, now_ms);
+        cache.put(&backends[1], 10.5, now_ms);
+        cache.put(&backends[2], 20.0, now_ms);
+        cache.put(&backends[3], 30.0, now_ms);
 
         // Then: all scores can be retrieved
-        assert_eq!(cache.get(1, now_ms), Some(10.5));
-        assert_eq!(cache.get(2, now_ms), Some(20.0));
-        assert_eq!(cache.get(3, now_ms), Some(30.0));
+        assert_eq!(cache.get(&backends[1], now_ms), Some(10.5));
+        assert_eq!(cache.get(&backends[2], now_ms), Some(20.0));
+        assert_eq!(cache.get(&backends[3], now_ms), Some(30.0));
     }
 
     #[test]
     fn adaptive_cache_get_at_exact_ttl_boundary_should_succeed() {
         // Given: an AdaptiveCache with cached score
         let cache = AdaptiveCache::new(100); // TTL of 100ms
-        let backend_id = 1u8;
-        cache.put(backend_id, 10.5, 1000);
+        let backend = test_backend(1);
+        cache.put(&backend, 10.5, 1000);
 
         // When: getting score exactly at TTL boundary
-        let retrieved = cache.get(backend_id, 1100); // Exactly 100ms later
+        let retrieved = cache.get(&backend, 1100); // Exactly 100ms later
 
         // Then: returns None (age > ttl_ms, 100 > 100 is false, so it's still valid)
         // Actually, age = 100, and 100 > 100 is false, so it returns the value
@@ -220,11 +323,11 @@ mod tests {
     fn adaptive_cache_get_just_after_ttl_should_succeed() {
         // Given: an AdaptiveCache with cached score
         let cache = AdaptiveCache::new(100); // TTL of 100ms
-        let backend_id = 1u8;
-        cache.put(backend_id, 10.5, 1000);
+        let backend = test_backend(1);
+        cache.put(&backend, 10.5, 1000);
 
         // When: getting score just after TTL expires
-        let retrieved = cache.get(backend_id, 1101); // 101ms later, exceeds TTL
+        let retrieved = cache.get(&backend, 1101); // 101ms later, exceeds TTL
 
         // Then: returns None (expired)
         assert_eq!(retrieved, None);
@@ -234,11 +337,11 @@ mod tests {
     fn adaptive_cache_get_just_before_ttl_should_succeed() {
         // Given: an AdaptiveCache with cached score
         let cache = AdaptiveCache::new(100); // TTL of 100ms
-        let backend_id = 1u8;
-        cache.put(backend_id, 10.5, 1000);
+        let backend = test_backend(1);
+        cache.put(&backend, 10.5, 1000);
 
         // When: getting score just before TTL expires
-        let retrieved = cache.get(backend_id, 1099); // 99ms later, just before TTL
+        let retrieved = cache.get(&backend, 1099); // 99ms later, just before TTL
 
         // Then: score is returned
         assert_eq!(retrieved, Some(10.5));
@@ -248,11 +351,11 @@ mod tests {
     fn adaptive_cache_get_with_zero_ttl_should_succeed() {
         // Given: an AdaptiveCache with zero TTL
         let cache = AdaptiveCache::new(0);
-        let backend_id = 1u8;
-        cache.put(backend_id, 10.5, 1000);
+        let backend = test_backend(1);
+        cache.put(&backend, 10.5, 1000);
 
         // When: getting score at same timestamp
-        let retrieved = cache.get(backend_id, 1000);
+        let retrieved = cache.get(&backend, 1000);
 
         // Then: returns score (age = 0, and 0 > 0 is false, so still valid)
         assert_eq!(retrieved, Some(10.5));
@@ -262,11 +365,11 @@ mod tests {
     fn adaptive_cache_get_with_zero_ttl_after_put_should_succeed() {
         // Given: an AdaptiveCache with zero TTL
         let cache = AdaptiveCache::new(0);
-        let backend_id = 1u8;
-        cache.put(backend_id, 10.5, 1000);
+        let backend = test_backend(1);
+        cache.put(&backend, 10.5, 1000);
 
         // When: getting score after put timestamp
-        let retrieved = cache.get(backend_id, 1001);
+        let retrieved = cache.get(&backend, 1001);
 
         // Then: returns None (age = 1, and 1 > 0 is true, so expired)
         assert_eq!(retrieved, None);
@@ -276,14 +379,98 @@ mod tests {
     fn adaptive_cache_get_with_future_timestamp_should_succeed() {
         // Given: an AdaptiveCache with cached score
         let cache = AdaptiveCache::new(1000);
-        let backend_id = 1u8;
-        cache.put(backend_id, 10.5, 1000);
+        let backend = test_backend(1);
+        cache.put(&backend, 10.5, 1000);
 
         // When: getting score with future timestamp (time went backwards)
-        let retrieved = cache.get(backend_id, 500); // Before computed_at
+        let retrieved = cache.get(&backend, 500); // Before computed_at
 
         // Then: age calculation handles it (saturating_sub returns 0)
         // Age is 0, which is <= TTL, so it should return the score
         assert_eq!(retrieved, Some(10.5));
     }
+
+    #[test]
+    fn adaptive_cache_evicts_least_recently_used_should_succeed() {
+        // Given: a cache bounded to two scores holding backends 1 and 2
+        let backends = test_backends();
+        let cache = AdaptiveCache::new(1000).with_max_entries(2);
+        cache.put(&backends[1], 10.0, 1000);
+        cache.put(&backends[2], 20.0, 1000);
+
+        // When: reading backend 1, then caching backend 3
+        assert_eq!(cache.get(&backends[1], 1000), Some(10.0));
+        cache.put(&backends[3], 30.0, 1000);
+
+        // Then: backend 2, the least recently used, is evicted
+        let mut ids = cache.backend_ids();
+        ids.sort();
+        assert_eq!(ids, [1, 3]);
+        assert_eq!(cache.get(&backends[2], 1000), None);
+
+        // When: caching backend 4
+        cache.put(&backends[4], 40.0, 1000);
+
+        // Then: backend 1, read before backend 3 was written, goes next
+        let mut ids = cache.backend_ids();
+        ids.sort();
+        assert_eq!(ids, [3, 4]);
+    }
+
+    #[test]
+    fn adaptive_cache_zero_max_entries_keeps_one_should_succeed() {
+        // Given: a cache bounded to zero scores
+        let backends = test_backends();
+        let cache = AdaptiveCache::new(1000).with_max_entries(0);
+
+        // When: caching two scores
+        cache.put(&backends[1], 10.0, 1000);
+        cache.put(&backends[2], 20.0, 1000);
+
+        // Then: the latest one is kept
+        assert_eq!(cache.max_entries(), 1);
+        assert_eq!(cache.backend_ids(), [2]);
+    }
+
+    #[test]
+    fn adaptive_cache_replaced_backend_misses_should_succeed() {
+        // Given: a score cached for backend 1
+        let cache = AdaptiveCache::new(1000);
+        let backend = test_backend(1);
+        cache.put(&backend, 10.0, 1000);
+
+        // When: another backend takes its id
+        let replacement = test_backend(1);
+
+        // Then: it never reads the score, which the original still does
+        assert_eq!(cache.get(&replacement, 1000), None);
+        assert_eq!(cache.get(&backend, 1000), Some(10.0));
+
+        // When: the original's weight changes
+        backend.set_weight(Some(5));
+
+        // Then: its score is stale
+        assert_eq!(cache.get(&backend, 1000), None);
+    }
+
+    #[test]
+    fn adaptive_cache_retain_routed_should_succeed() {
+        // Given: scores for backends 0, 1 and 2
+        let backends = test_backends();
+        let cache = AdaptiveCache::new(1000);
+        for backend in &backends[..3] {
+            cache.put(backend, backend.id() as f64, 1000);
+        }
+
+        // When: keeping those routed by a table holding backend 0, a
+        // replacement for backend 1 and no backend 2
+        let routing = RouteTable::new(Vec::new());
+        routing.insert(backends[0].clone()).expect("insert");
+        routing.insert(test_backend(1)).expect("insert");
+        cache.retain_routed(&routing);
+
+        // Then: only backend 0 keeps its score
+        assert_eq!(cache.backend_ids(), [0]);
+        assert_eq!(cache.get(&backends[0], 1000), Some(0.0));
+    }
 }
@@ -10,6 +10,9 @@ pub const DEFAULT_LATENCY_WEIGHT: f64 = 0.4;
 /// Default weight for error rate factor in adaptive scoring
 pub const DEFAULT_ERROR_WEIGHT: f64 = 0.2;
 
+/// Default weight for resource pressure factor in adaptive scoring (disabled)
+pub const DEFAULT_RESOURCE_WEIGHT: f64 = 0.0;
+
 /// Default maximum latency in milliseconds (used when no metrics available)
 pub const DEFAULT_MAX_LATENCY_MS: f64 = 1000.0;
 
@@ -22,9 +25,15 @@ pub const HIGH_ERROR_RATE_PENALTY: f64 = 0.5;
 /// Minimum weight factor to avoid division by zero
 pub const MIN_WEIGHT_FACTOR: f64 = 0.1;
 
+/// One pick in this many logs its score breakdown at debug level
+pub const EXPLAIN_LOG_SAMPLE_RATE: u64 = 1024;
+
 /// Default cache TTL in milliseconds
 pub const DEFAULT_CACHE_TTL_MS: u64 = 100;
 
+/// Default maximum number of cached scores (one per possible backend id)
+pub const DEFAULT_CACHE_MAX_ENTRIES: usize = crate::types::BackendId::MAX as usize + 1;
+
 /// Default backend weight when not specified
 pub const DEFAULT_BACKEND_WEIGHT: u8 = 1;
 
@@ -4,6 +4,7 @@
 //! - Connection load (normalized by weight)
 //! - Response latency (with variance penalty)
 //! - Error rates (with additional penalty for high error rates)
+//! - Resource pressure reported by workers (CPU and memory, off by default)
 //!
 //! All factors are normalized and combined using configurable weights.
 //! The strategy includes caching for performance optimization.
@@ -16,7 +17,9 @@ mod models;
 mod utils;
 
 use cache::AdaptiveCache;
-use models::AdaptiveWeights;
+use constants::EXPLAIN_LOG_SAMPLE_RATE;
+use models::AdaptiveParams;
+pub use models::AdaptiveWeights;
 use utils::*;
 
 /// Adaptive strategy implementation with multi-factor scoring
@@ -29,6 +32,8 @@ pub struct AdaptiveStrategy {
     cache: AdaptiveCache,
     /// Weights for scoring factors (connection, latency, error rate)
     weights: AdaptiveWeights,
+    /// Picks scored so far, sampling the debug breakdown log
+    picks: AtomicU64,
 }
 
 impl AdaptiveStrategy {
@@ -42,6 +47,7 @@ impl AdaptiveStrategy {
         Self {
             cache: AdaptiveCache::default(),
             weights: AdaptiveWeights::default(),
+            picks: AtomicU64::new(0),
         }
     }
 
@@ -54,8 +60,77 @@ impl AdaptiveStrategy {
         Self {
             cache: AdaptiveCache::default(),
             weights: custom_weights.unwrap_or_default(),
+            picks: AtomicU64::new(0),
         }
     }
+
+    /// Create a strategy from the config's `strategy_params` (`null` for defaults)
+    ///
+    /// Reads `conn_weight`, `latency_weight`, `error_weight`,
+    /// `resource_weight`, `cache_ttl_millis` and `cache_max_entries`; missing
+    /// ones keep their defaults. Negative weights, weights adding up to zero
+    /// and an empty cache are rejected.
+    pub fn from_params(params: serde_json::Value) -> Result<Self, StrategyError> {
+        if params.is_null() {
+            return Ok(Self::new());
+        }
+        let params = serde_json::from_value::<AdaptiveParams>(params).map_err(|e| {
+            StrategyError::UnexpectedError(format!("invalid adaptive params: {}", e))
+        })?;
+        params.validate()?;
+        Ok(Self {
+            cache: AdaptiveCache::new(params.cache_ttl_millis)
+                .with_max_entries(params.cache_max_entries),
+            weights: params.weights,
+            picks: AtomicU64::new(0),
+        })
+    }
+
+    /// Weight of the resource pressure factor (0.0 when disabled)
+    pub fn resource_weight(&self) -> f64 {
+        self.weights.resource_weight
+    }
+
+    /// Scoring weights
+    pub fn weights(&self) -> &AdaptiveWeights {
+        &self.weights
+    }
+
+    /// How long a computed score is reused, in milliseconds
+    pub fn cache_ttl_millis(&self) -> u64 {
+        self.cache.ttl_ms()
+    }
+
+    /// Maximum number of cached scores
+    pub fn cache_max_entries(&self) -> usize {
+        self.cache.max_entries()
+    }
+
+    /// Log the breakdown of one pick in [`EXPLAIN_LOG_SAMPLE_RATE`] at debug
+    /// level
+    fn log_sampled_breakdown(
+        &self,
+        backends: &[Arc<Backend>],
+        scoring_context: &models::ScoringContext,
+        current_timestamp_ms: u64,
+        picked: Option<BackendId>,
+    ) {
+        if !tracing::enabled!(tracing::Level::DEBUG)
+            || !self
+                .picks
+                .fetch_add(1, Ordering::Relaxed)
+                .is_multiple_of(EXPLAIN_LOG_SAMPLE_RATE)
+        {
+            return;
+        }
+        let breakdown = explain_backend_scores(
+            backends,
+            scoring_context,
+            &self.weights,
+            current_timestamp_ms,
+        );
+        tracing::debug!(?picked, ?breakdown, "Adaptive pick score breakdown");
+    }
 }
 
 impl Default for AdaptiveStrategy {
@@ -70,36 +145,42 @@ impl StrategyService for AdaptiveStrategy {
         Strategy::Adaptive
     }
 
+    /// Pick the best backend among all healthy backends
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        self.pick_backend_matching(ctx, &LabelSelector::default())
+            .await
+    }
+
     /// Pick the best backend using adaptive multi-factor scoring
     ///
     /// This method:
-    /// 1. Gets healthy backends from context
+    /// 1. Gets the healthy backends selected by `selector` from context
     /// 2. Prepares scoring context with normalized values
-    /// 3. Checks cache for existing scores
-    /// 4. Computes scores for backends (using cache when available)
-    /// 5. Returns backend with lowest score (best performance)
+    /// 3. Scores backends in a single pass (using cache when available)
+    /// 4. Returns backend with lowest score (best performance)
     ///
     /// # Arguments
     /// * `ctx` - Runtime context containing registries and state
+    /// * `selector` - Label selector restricting the candidates
     ///
     /// # Returns
-    /// Selected backend metadata, or error if no backends available
-    async fn pick_backend(
+    /// Selected backend, or error if no backends available
+    async fn pick_backend_matching(
         &self,
         ctx: Arc<Context>,
-    ) -> Result<BackendMeta, StrategyError> {
+        selector: &LabelSelector,
+    ) -> Result<Arc<Backend>, StrategyError> {
         let routing = ctx.routing_table();
-        let healthy_backends = routing.healthy_backends();
+        trace_exclusions(&ctx, &routing, selector, false);
+        let healthy_backends = routing.preferred_backends_matching(selector);
 
         // Early exit optimization: single backend
         if healthy_backends.len() == 1 {
             let backend = &healthy_backends[0];
-            return Ok(BackendMeta::new(
-                backend.id(),
-                backend.name(),
-                backend.address().clone(),
-                backend.weight(),
-            ));
+            return Ok(Arc::clone(backend));
         }
 
         if healthy_backends.is_empty() {
@@ -107,52 +188,64 @@ impl StrategyService for AdaptiveStrategy {
         }
 
         // Get current timestamp for cache TTL validation
-        let current_timestamp_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
+        let current_timestamp_ms = ctx.clock().now_millis();
 
         // Prepare scoring context with normalized maximum values
-        let scoring_context = prepare_scoring_context(&healthy_backends, routing.clone());
-
-        // Convert to BackendMeta for scoring (temporary compatibility)
-        let backend_metas: Vec<BackendMeta> = healthy_backends
-            .iter()
-            .map(|b| BackendMeta::new(b.id(), b.name(), b.address().clone(), b.weight()))
-            .collect();
+        let scoring_context = prepare_scoring_context(&healthy_backends);
 
-        // Check cache for all backends in parallel
-        let cached_scores: Vec<(BackendId, Option<f64>)> = backend_metas
-            .iter()
-            .map(|backend| {
-                (
-                    *backend.id(),
-                    self.cache.get(*backend.id(), current_timestamp_ms),
-                )
-            })
-            .collect();
-
-        // Compute scores for all backends (uses cache when available)
-        let scored_backends = compute_all_backend_scores(
-            &backend_metas,
+        // Score backends (uses cache when available) and keep the lowest
+        // score (best performance)
+        let picked = select_best_backend(
+            &healthy_backends,
             &scoring_context,
-            &cached_scores,
             &self.cache,
             &self.weights,
             current_timestamp_ms,
+        )
+        .cloned();
+        self.log_sampled_breakdown(
+            &healthy_backends,
+            &scoring_context,
+            current_timestamp_ms,
+            picked.as_ref().map(|backend| backend.id()),
         );
+        picked.ok_or(StrategyError::NoBackendAvailable)
+    }
 
-        // Find backend with lowest score (best performance)
-        let (_best_score, best_backend) = scored_backends
-            .iter()
-            .min_by(|(first_score, _), (second_score, _)| {
-                first_score
-                    .partial_cmp(second_score)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
-            .ok_or(StrategyError::NoBackendAvailable)?;
-
-        Ok(best_backend.clone())
+    fn tracked_backends(&self) -> Vec<BackendId> {
+        self.cache.backend_ids()
+    }
+
+    /// Drop the scores of removed or replaced backends and score the new
+    /// ones, so the first picks after the migration find them cached
+    fn on_migrate(&self, routing: &RouteTable, now_ms: u64) {
+        self.cache.retain_routed(routing);
+        let backends = routing.preferred_backends_matching(&LabelSelector::default());
+        if backends.len() > 1 {
+            select_best_backend(
+                &backends,
+                &prepare_scoring_context(&backends),
+                &self.cache,
+                &self.weights,
+                now_ms,
+            );
+        }
+    }
+
+    /// Score every healthy backend afresh, ignoring cached scores
+    ///
+    /// A pick may still use a score cached up to `cache_ttl_millis` ago, so
+    /// it can disagree with the lowest score explained here.
+    fn explain_pick(&self, ctx: &Context) -> Vec<BackendScoreBreakdown> {
+        let backends = ctx
+            .routing_table()
+            .preferred_backends_matching(&LabelSelector::default());
+        explain_backend_scores(
+            &backends,
+            &prepare_scoring_context(&backends),
+            &self.weights,
+            ctx.clock().now_millis(),
+        )
     }
 }
 
@@ -178,6 +271,7 @@ mod tests {
             .map(|meta| BackendConfig::from(meta.clone()))
             .collect();
         Config {
+            version: CONFIG_VERSION,
             source: ConfigSource::Environment,
             runtime: RuntimeConfig {
                 metrics_cap: 100,
@@ -186,6 +280,11 @@ mod tests {
                 background_timeout_millis: 1000,
                 accept_timeout_millis: 2000,
                 config_watch_interval_millis: 1000,
+                drain_progress_interval_millis: 1000,
+                drain_policy: DrainPolicy::Finish,
+                proxy_worker_threads: None,
+                audit_interval_millis: None,
+                log_rate_limit: LogRateLimitConfig::default(),
             },
             proxy: ProxyConfig {
                 listen_address: SocketAddr::new(
@@ -193,19 +292,79 @@ mod tests {
                     3000,
                 ),
                 max_connections: Some(1000),
+                coalesce: CoalesceConfig::default(),
+                hedging: HedgingConfig::default(),
+                retry_budget: RetryBudgetConfig::default(),
+                circuit_breaker: CircuitBreakerConfig::default(),
+                route_by_sni: SniRoutingConfig::default(),
+                route_by_alpn: AlpnRoutingConfig::default(),
+                route_by_http: HttpRoutingConfig::default(),
+                slow_connect_warn_ms: None,
+                slow_connection_warn_secs: None,
+                strategy_pick_warn_micros: None,
+                initial_read_timeout_millis: None,
+                slow_log_max_per_minute: 10,
+                max_buffered_bytes: 256 * 1024,
+                backend_bind_address: None,
+                backend_dscp: None,
+                fd_headroom: 64,
+                max_backend_peer_connections: None,
+                max_hops: None,
+                transparent: false,
+                report_backend_aborts: true,
+                sticky_sessions: false,
+                sticky_ttl_millis: 300_000,
+                listen_unix: None,
+                max_pending_connects: 32,
+                local_zone: None,
+                zone_spillover_min_healthy: 1,
+                propagate_deadlines: false,
+                request_timeout_millis: 30_000,
             },
             strategy: Strategy::Adaptive,
+            strategy_params: serde_json::Value::Null,
+            fallback: None,
             backends: backend_configs,
+            groups: Default::default(),
+            allow_duplicate_addresses: false,
+            auto_weight: false,
+            auto_weight_tuning: AutoWeightConfig::default(),
+            admin: AdminConfig::default(),
+            audit: AuditConfig::default(),
             health: HealthConfig {
                 interval: Duration::from_secs(5),
                 timeout: Duration::from_secs(1),
+                max_event_age_millis: 10_000,
+                evict_on_unhealthy: false,
+                slow_start_millis: 0,
+                outlier_error_percent: 0,
+                outlier_min_connections: 10,
+                outlier_window_millis: 10_000,
+                base_ejection_millis: 30_000,
+                max_ejection_percent: 10,
+                check: HealthCheck::Tcp,
+                healthy_threshold: 1,
+                unhealthy_threshold: 1,
+                jitter_percent: 0,
+                initial_state: InitialHealthState::default(),
+                reuse_connections: true,
             },
+            preflight: PreflightConfig::default(),
+            health_endpoint: HealthEndpointConfig::default(),
             metrics: MetricsConfig {
                 interval: Duration::from_secs(10),
                 timeout: Duration::from_secs(2),
+                max_event_age_millis: 30_000,
+                aggregation: LatencyAggregation::default(),
+                selection_trace: false,
             },
+            services: ServicesConfig::default(),
             otlp_protocol: None,
+            rng_seed: None,
+            state_file: None,
+            health_state_file: None,
             otlp_endpoint: None,
+            secrets: Default::default(),
         }
     }
 
@@ -246,6 +405,7 @@ mod tests {
             conn_weight: 0.5,
             latency_weight: 0.3,
             error_weight: 0.2,
+            resource_weight: 0.0,
         };
 
         // When: creating with custom weights
@@ -283,7 +443,40 @@ mod tests {
             .expect("Failed to pick backend");
 
         // Then: single backend is returned (early exit optimization)
-        assert_eq!(backend.id(), &0u8);
+        assert_eq!(backend.id(), 0u8);
+    }
+
+    #[tokio::test]
+    async fn adaptive_strategy_explain_pick_should_succeed() {
+        // Given: an AdaptiveStrategy over two backends, the second one loaded
+        let strategy = AdaptiveStrategy::default();
+        let config = create_test_config(vec![
+            create_test_backend(0, Some(1)),
+            create_test_backend(1, Some(1)),
+        ]);
+        let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+        ctx.routing_table()
+            .get(1)
+            .expect("backend 1")
+            .increment_connection();
+
+        // When: explaining a pick, then picking
+        let breakdown = strategy.explain_pick(&ctx);
+        let backend = strategy
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Failed to pick backend");
+
+        // Then: both backends are explained and the lowest score is picked
+        let mut ids: Vec<BackendId> = breakdown.iter().map(|b| b.backend_id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![0, 1]);
+        let lowest = breakdown
+            .iter()
+            .min_by(|a, b| a.score.total_cmp(&b.score))
+            .expect("a breakdown");
+        assert_eq!(lowest.backend_id, backend.id());
+        assert_eq!(backend.id(), 0);
     }
 
     #[tokio::test]
@@ -330,7 +523,7 @@ mod tests {
             .expect("Failed to pick backend");
 
         // Then: a backend is selected
-        assert!(*backend.id() == 0 || *backend.id() == 1);
+        assert!(backend.id() == 0 || backend.id() == 1);
     }
 
     #[tokio::test]
@@ -364,7 +557,7 @@ mod tests {
 
         // Then: backend with better metrics (lower latency) is selected
         // Backend 0 should be selected due to lower latency
-        assert_eq!(backend.id(), &0u8);
+        assert_eq!(backend.id(), 0u8);
     }
 
     #[tokio::test]
@@ -396,6 +589,107 @@ mod tests {
             .expect("Failed to pick backend");
 
         // Then: backend with fewer connections is selected
-        assert_eq!(backend.id(), &0u8);
+        assert_eq!(backend.id(), 0u8);
+    }
+
+    #[tokio::test]
+    async fn adaptive_strategy_cached_scores_expire_on_clock_should_succeed() {
+        // Given: an AdaptiveStrategy on a mock clock, backend 1 busier than 0
+        let strategy = AdaptiveStrategy::default();
+        let backends = vec![
+            create_test_backend(0, Some(1)),
+            create_test_backend(1, Some(1)),
+        ];
+        let clock = Arc::new(MockClock::new(1_000));
+        let ctx = Arc::new(
+            Context::with_clock(create_test_config(backends), clock.clone())
+                .expect("Failed to create context"),
+        );
+        let routing = ctx.routing_table();
+        let backend0 = routing.get(0).expect("backend 0");
+        let backend1 = routing.get(1).expect("backend 1");
+        backend1.increment_connection();
+        backend1.increment_connection();
+        let first = strategy.pick_backend(ctx.clone()).await.expect("pick");
+        assert_eq!(first.id(), 0u8);
+
+        // When: backend 0 becomes busier while the scores are still cached
+        for _ in 0..3 {
+            backend0.increment_connection();
+        }
+        let cached = strategy.pick_backend(ctx.clone()).await.expect("pick");
+
+        // Then: the cached scores are used until the clock passes the TTL
+        assert_eq!(cached.id(), 0u8);
+        clock.advance(Duration::from_millis(constants::DEFAULT_CACHE_TTL_MS + 1));
+        let refreshed = strategy.pick_backend(ctx).await.expect("pick");
+        assert_eq!(refreshed.id(), 1u8);
+    }
+
+    #[tokio::test]
+    async fn adaptive_strategy_configured_cache_ttl_should_succeed() {
+        // Given: a strategy caching scores for 2s, backend 1 busier than 0
+        let strategy = AdaptiveStrategy::from_params(
+            serde_json::json!({ "cache_ttl_millis": 2_000 }),
+        )
+        .expect("Failed to build strategy");
+        let backends = vec![
+            create_test_backend(0, Some(1)),
+            create_test_backend(1, Some(1)),
+        ];
+        let clock = Arc::new(MockClock::new(1_000));
+        let ctx = Arc::new(
+            Context::with_clock(create_test_config(backends), clock.clone())
+                .expect("Failed to create context"),
+        );
+        let routing = ctx.routing_table();
+        routing.get(1).expect("backend 1").increment_connection();
+        let first = strategy.pick_backend(ctx.clone()).await.expect("pick");
+        assert_eq!(first.id(), 0u8);
+
+        // When: backend 0 becomes busier
+        for _ in 0..3 {
+            routing.get(0).expect("backend 0").increment_connection();
+        }
+
+        // Then: the scores outlive the default TTL, but not the configured one
+        clock.advance(Duration::from_millis(constants::DEFAULT_CACHE_TTL_MS + 1));
+        let cached = strategy.pick_backend(ctx.clone()).await.expect("pick");
+        assert_eq!(cached.id(), 0u8);
+        clock.advance(Duration::from_millis(2_000));
+        let refreshed = strategy.pick_backend(ctx).await.expect("pick");
+        assert_eq!(refreshed.id(), 1u8);
+    }
+
+    #[tokio::test]
+    async fn adaptive_strategy_slow_start_lowers_preference_should_succeed() {
+        // Given: backend 0 less busy than 1, but recovering with a 10s slow start
+        let strategy = AdaptiveStrategy::default();
+        let backends = vec![
+            create_test_backend(0, Some(10)),
+            create_test_backend(1, Some(10)),
+        ];
+        let clock = Arc::new(MockClock::new(1_000));
+        let ctx = Arc::new(
+            Context::with_clock(create_test_config(backends), clock.clone())
+                .expect("Failed to create context"),
+        );
+        let routing = ctx.routing_table();
+        let backend0 = routing.get(0).expect("backend 0");
+        let backend1 = routing.get(1).expect("backend 1");
+        backend0.increment_connection();
+        backend1.increment_connection();
+        backend1.increment_connection();
+        backend0.start_slow_start(1_000, 10_000);
+
+        // When: picking right after recovery
+        let ramping = strategy.pick_backend(ctx.clone()).await.expect("pick");
+
+        // Then: the ramping backend scores as a light one and loses, until the
+        // window ends
+        assert_eq!(ramping.id(), 1u8);
+        clock.advance(Duration::from_secs(10));
+        let ramped = strategy.pick_backend(ctx).await.expect("pick");
+        assert_eq!(ramped.id(), 0u8);
     }
 }
@@ -1,13 +1,56 @@
 //! Adaptive strategy models module
 //!
 use crate::prelude::*;
+use serde::Deserialize;
+
+/// Adaptive strategy parameters (`strategy_params`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveParams {
+    /// Scoring weights
+    #[serde(flatten)]
+    pub weights: AdaptiveWeights,
+    /// How long a computed score is reused, in milliseconds
+    pub cache_ttl_millis: u64,
+    /// Maximum number of cached scores, least recently used evicted first
+    pub cache_max_entries: usize,
+}
+
+impl Default for AdaptiveParams {
+    fn default() -> Self {
+        Self {
+            weights: AdaptiveWeights::default(),
+            cache_ttl_millis: super::constants::DEFAULT_CACHE_TTL_MS,
+            cache_max_entries: super::constants::DEFAULT_CACHE_MAX_ENTRIES,
+        }
+    }
+}
+
+impl AdaptiveParams {
+    /// Check the weights and that the cache can hold at least one score
+    pub fn validate(&self) -> Result<(), StrategyError> {
+        self.weights.validate()?;
+        if self.cache_max_entries == 0 {
+            return Err(StrategyError::UnexpectedError(
+                "invalid adaptive params: cache_max_entries must be at least 1"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
 
 /// Configuration for adaptive strategy scoring weights
 ///
 /// These weights determine the relative importance of each factor
-/// in the adaptive scoring algorithm. All weights should sum to 1.0
-/// for optimal results, though this is not enforced.
-#[derive(Debug, Clone)]
+/// in the adaptive scoring algorithm. Only their ratios matter, so they
+/// need not sum to 1.0, but each must be a non-negative number and at
+/// least one must be positive (see [`AdaptiveWeights::validate`]).
+///
+/// Read from the config's `strategy_params`; missing weights keep their
+/// defaults.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
 pub struct AdaptiveWeights {
     /// Weight for connection load factor (0.0-1.0)
     /// Higher values prioritize backends with fewer connections
@@ -18,6 +61,9 @@ pub struct AdaptiveWeights {
     /// Weight for error rate factor (0.0-1.0)
     /// Higher values penalize backends with higher error rates
     pub error_weight: f64,
+    /// Weight for resource pressure factor (0.0-1.0, off by default)
+    /// Higher values prefer backends reporting lower CPU and memory usage
+    pub resource_weight: f64,
 }
 
 impl Default for AdaptiveWeights {
@@ -27,7 +73,37 @@ impl Default for AdaptiveWeights {
             conn_weight: DEFAULT_CONN_WEIGHT,
             latency_weight: DEFAULT_LATENCY_WEIGHT,
             error_weight: DEFAULT_ERROR_WEIGHT,
+            resource_weight: DEFAULT_RESOURCE_WEIGHT,
+        }
+    }
+}
+
+impl AdaptiveWeights {
+    /// Check that every weight is a finite, non-negative number and that they
+    /// do not add up to zero (every backend would score the same)
+    pub fn validate(&self) -> Result<(), StrategyError> {
+        let weights = [
+            ("conn_weight", self.conn_weight),
+            ("latency_weight", self.latency_weight),
+            ("error_weight", self.error_weight),
+            ("resource_weight", self.resource_weight),
+        ];
+        if let Some((name, weight)) = weights
+            .iter()
+            .find(|(_, weight)| !weight.is_finite() || *weight < 0.0)
+        {
+            return Err(StrategyError::UnexpectedError(format!(
+                "invalid adaptive params: {} must be a non-negative number, got {}",
+                name, weight
+            )));
+        }
+        if weights.iter().map(|(_, weight)| weight).sum::<f64>() <= 0.0 {
+            return Err(StrategyError::UnexpectedError(
+                "invalid adaptive params: at least one weight must be positive"
+                    .to_string(),
+            ));
         }
+        Ok(())
     }
 }
 
@@ -42,6 +118,8 @@ pub struct ScoringContext {
     pub max_latency_ms: f64,
     /// Maximum weight across all backends (for normalization)
     pub max_weight: f64,
-    /// Routing table for looking up backends
-    pub routing: Arc<RouteTable>,
+    /// Maximum worker-reported CPU usage across all backends (for normalization)
+    pub max_cpu_percent: f64,
+    /// Maximum worker-reported resident memory across all backends (for normalization)
+    pub max_rss_bytes: u64,
 }
@@ -107,26 +107,58 @@ pub fn compute_latency_score(
     latency_ratio * variance_penalty
 }
 
+/// Compute normalized resource pressure score
+///
+/// Normalizes the CPU usage and resident memory reported by the worker
+/// against the pool maximums and averages the readings available. Backends
+/// that reported nothing score zero (no pressure known).
+///
+/// # Arguments
+/// * `cpu_percent` - CPU usage reported by the worker (optional)
+/// * `rss_bytes` - Resident memory reported by the worker (optional)
+/// * `max_cpu_percent` - Maximum CPU usage across all backends
+/// * `max_rss_bytes` - Maximum resident memory across all backends
+///
+/// # Returns
+/// Normalized resource score (0.0-1.0), lower is better
+pub fn compute_resource_score(
+    cpu_percent: Option<f64>,
+    rss_bytes: Option<u64>,
+    max_cpu_percent: f64,
+    max_rss_bytes: u64,
+) -> f64 {
+    let cpu_ratio = cpu_percent
+        .filter(|_| max_cpu_percent > ZERO_F64)
+        .map(|cpu| cpu / max_cpu_percent);
+    let rss_ratio = rss_bytes
+        .filter(|_| max_rss_bytes > 0)
+        .map(|rss| rss as f64 / max_rss_bytes as f64);
+
+    match (cpu_ratio, rss_ratio) {
+        (Some(cpu), Some(rss)) => (cpu + rss) / 2.0,
+        (Some(ratio), None) | (None, Some(ratio)) => ratio,
+        (None, None) => ZERO_F64,
+    }
+}
+
 /// Prepare scoring context from backends
 ///
 /// Analyzes all backends to find maximum values for normalization
 /// and creates a context object containing all necessary data for scoring.
 ///
 /// # Arguments
-/// * `backends` - List of healthy backends (Arc<Backend>) to analyze
-/// * `routing` - Routing table for backend lookups
+/// * `backends` - List of healthy backends to analyze
 ///
 /// # Returns
 /// ScoringContext with normalized maximum values
-pub fn prepare_scoring_context(
-    backends: &[Arc<Backend>],
-    routing: Arc<RouteTable>,
-) -> ScoringContext {
+pub fn prepare_scoring_context(backends: &[Arc<Backend>]) -> ScoringContext {
     use super::constants::*;
 
     let mut max_connection_count = ZERO_F64 as usize;
     let mut max_latency_value = ZERO_F64;
     let mut max_weight_value = ZERO_F64;
+    let mut max_cpu_percent = ZERO_F64;
+    let mut max_rss_bytes = 0u64;
 
     for backend in backends {
         let connection_count = backend.active_connections();
@@ -137,15 +169,17 @@ pub fn prepare_scoring_context(
         max_weight_value = max_weight_value.max(backend_weight_value);
 
         // Get max latency from backend metrics (use p95 if available, else avg)
-        let backend_metrics = backend.metrics_snapshot();
-        let p95_latency = backend_metrics.p95_latency_ms;
-        let average_latency = backend_metrics.avg_latency_ms;
-        let latency_value = if p95_latency > average_latency {
-            p95_latency
-        } else {
-            average_latency
-        };
+        let response_latency = backend.response_latency();
+        let latency_value = response_latency.p95_ms.max(response_latency.mean_ms);
         max_latency_value = max_latency_value.max(latency_value);
+
+        // Resource usage reported by the worker (see ExternalMetricsService)
+        if let Some(cpu_percent) = backend.cpu_percent() {
+            max_cpu_percent = max_cpu_percent.max(cpu_percent);
+        }
+        if let Some(rss_bytes) = backend.rss_bytes() {
+            max_rss_bytes = max_rss_bytes.max(rss_bytes);
+        }
     }
 
     // Use default max latency if no metrics available
@@ -162,14 +196,16 @@ pub fn prepare_scoring_context(
         max_connections: max_connection_count,
         max_latency_ms: max_latency_value,
         max_weight: max_weight_value,
-        routing,
+        max_cpu_percent,
+        max_rss_bytes,
     }
 }
 
 /// Compute combined score for a single backend
 ///
 /// Combines normalized connection, latency, and error rate scores
-/// using the configured weights, then applies weight factor adjustment.
+/// using the configured weights, then applies weight factor adjustment
+/// (see [`compute_backend_score_breakdown`]).
 ///
 /// # Arguments
 /// * `backend_connection_count` - Current connection count for the backend
@@ -187,16 +223,49 @@ pub fn compute_backend_score(
     scoring_context: &ScoringContext,
     weights: &AdaptiveWeights,
 ) -> f64 {
+    // The id only labels the breakdown; it takes no part in scoring
+    compute_backend_score_breakdown(
+        BackendId::MIN,
+        backend_connection_count,
+        backend_weight_value,
+        backend_metrics,
+        scoring_context,
+        weights,
+    )
+    .score
+}
+
+/// Compute the score of a single backend along with its factors
+///
+/// # Arguments
+/// * `backend_id` - Id of the backend, copied into the breakdown
+/// * `backend_connection_count` - Current connection count for the backend
+/// * `backend_weight_value` - Weight of this backend
+/// * `backend_metrics` - Performance metrics for the backend (optional)
+/// * `scoring_context` - Context with normalization values and registries
+/// * `weights` - Weight configuration for scoring factors
+///
+/// # Returns
+/// Factor scores, weight factor and combined score (lower is better)
+pub fn compute_backend_score_breakdown(
+    backend_id: BackendId,
+    backend_connection_count: usize,
+    backend_weight_value: f64,
+    backend_metrics: Option<BackendMetrics>,
+    scoring_context: &ScoringContext,
+    weights: &AdaptiveWeights,
+) -> BackendScoreBreakdown {
     use super::constants::*;
 
     // Extract metrics or use defaults
     let average_latency = backend_metrics
         .as_ref()
-        .map(|metrics| metrics.avg_latency_ms)
+        .map(|metrics| metrics.response_latency_ms())
         .unwrap_or(DEFAULT_MAX_LATENCY_MS);
     let p95_latency = backend_metrics.as_ref().and_then(|metrics| {
-        if metrics.p95_latency_ms > ZERO_F64 {
-            Some(metrics.p95_latency_ms)
+        let p95_latency_ms = metrics.response_p95_latency_ms();
+        if p95_latency_ms > ZERO_F64 {
+            Some(p95_latency_ms)
         } else {
             None
         }
@@ -219,90 +288,156 @@ pub fn compute_backend_score(
         scoring_context.max_latency_ms,
     );
     let error_penalty_value = compute_error_penalty(error_rate_value);
+    let resource_score = backend_metrics.as_ref().map_or(ZERO_F64, |metrics| {
+        compute_resource_score(
+            metrics.cpu_percent,
+            metrics.rss_bytes,
+            scoring_context.max_cpu_percent,
+            scoring_context.max_rss_bytes,
+        )
+    });
 
     // Combine scores using configured weights (lower is better)
     let combined_score = (connection_score * weights.conn_weight)
         + (latency_score * weights.latency_weight)
-        + ((UNIT_WEIGHT_FACTOR - error_penalty_value) * weights.error_weight);
+        + ((UNIT_WEIGHT_FACTOR - error_penalty_value) * weights.error_weight)
+        + (resource_score * weights.resource_weight);
 
     // Apply weight factor (higher weight = preference)
     let weight_factor = if scoring_context.max_weight > ZERO_F64 {
         backend_weight_value / scoring_context.max_weight
     } else {
         UNIT_WEIGHT_FACTOR
-    };
+    }
+    .max(MIN_WEIGHT_FACTOR);
+
+    BackendScoreBreakdown {
+        backend_id,
+        connection_score,
+        latency_score,
+        error_penalty: error_penalty_value,
+        resource_score,
+        weight_factor,
+        // Divide by weight factor to give preference to higher weights
+        score: combined_score / weight_factor,
+    }
+}
 
-    // Divide by weight factor to give preference to higher weights
-    combined_score / weight_factor.max(MIN_WEIGHT_FACTOR)
+/// Effective weight a backend is scored with at `current_timestamp_ms`
+///
+/// A backend in slow start competes with its ramped weight.
+fn effective_weight(backend: &Backend, current_timestamp_ms: u64) -> f64 {
+    backend.weight().unwrap_or(DEFAULT_BACKEND_WEIGHT) as f64
+        * backend.slow_start_factor(current_timestamp_ms)
 }
 
-/// Compute scores for all backends
+/// Explain the score of every backend, bypassing the cache
 ///
-/// Computes scores for all backends, using cached scores when available.
-/// This method handles both cache lookup and score computation in a single pass.
+/// # Arguments
+/// * `backends` - List of healthy backends to score
+/// * `scoring_context` - Context with normalization values
+/// * `weights` - Weight configuration for scoring factors
+/// * `current_timestamp_ms` - Current timestamp in milliseconds for slow start
+///
+/// # Returns
+/// Score breakdown of each backend, in `backends` order
+pub fn explain_backend_scores(
+    backends: &[Arc<Backend>],
+    scoring_context: &ScoringContext,
+    weights: &AdaptiveWeights,
+    current_timestamp_ms: u64,
+) -> Vec<BackendScoreBreakdown> {
+    backends
+        .iter()
+        .map(|backend| {
+            compute_backend_score_breakdown(
+                backend.id(),
+                backend.active_connections(),
+                effective_weight(backend, current_timestamp_ms),
+                Some(backend.metrics_snapshot()),
+                scoring_context,
+                weights,
+            )
+        })
+        .collect()
+}
+
+/// Score a single backend, using its cached score when still valid
+///
+/// A freshly computed score is stored in the cache for later picks.
+///
+/// # Arguments
+/// * `backend` - Healthy backend to score
+/// * `scoring_context` - Context with normalization values
+/// * `cache` - Cache instance for reading and storing scores
+/// * `weights` - Weight configuration for scoring factors
+/// * `current_timestamp_ms` - Current timestamp in milliseconds for cache TTL
+///
+/// # Returns
+/// Backend score (lower is better)
+pub fn score_backend(
+    backend: &Arc<Backend>,
+    scoring_context: &ScoringContext,
+    cache: &super::cache::AdaptiveCache,
+    weights: &AdaptiveWeights,
+    current_timestamp_ms: u64,
+) -> f64 {
+    if let Some(cached_score_value) = cache.get(backend, current_timestamp_ms) {
+        return cached_score_value;
+    }
+
+    let new_score = compute_backend_score(
+        backend.active_connections(),
+        effective_weight(backend, current_timestamp_ms),
+        Some(backend.metrics_snapshot()),
+        scoring_context,
+        weights,
+    );
+
+    // Cache the score for future use
+    cache.put(backend, new_score, current_timestamp_ms);
+    new_score
+}
+
+/// Select the backend with the lowest score
+///
+/// Scores every backend in a single pass (see [`score_backend`]) without
+/// allocating; on equal scores the first backend wins.
 ///
 /// # Arguments
 /// * `backends` - List of healthy backends to score
-/// * `scoring_context` - Context with normalization values and registries
-/// * `cached_scores` - Pre-computed cached scores (backend_id -> score)
-/// * `cache` - Cache instance for storing computed scores
+/// * `scoring_context` - Context with normalization values
+/// * `cache` - Cache instance for reading and storing scores
 /// * `weights` - Weight configuration for scoring factors
 /// * `current_timestamp_ms` - Current timestamp in milliseconds for cache TTL
 ///
 /// # Returns
-/// Vector of (score, backend) tuples, sorted by score (lower is better)
-pub fn compute_all_backend_scores(
-    backends: &[BackendMeta],
+/// Best backend, or `None` if `backends` is empty
+pub fn select_best_backend<'a>(
+    backends: &'a [Arc<Backend>],
     scoring_context: &ScoringContext,
-    cached_scores: &[(BackendId, Option<f64>)],
     cache: &super::cache::AdaptiveCache,
     weights: &AdaptiveWeights,
     current_timestamp_ms: u64,
-) -> Vec<(f64, BackendMeta)> {
+) -> Option<&'a Arc<Backend>> {
     backends
         .iter()
-        .enumerate()
-        .map(|(backend_index, backend)| {
-            let backend_id = *backend.id();
-
-            // Get actual Backend from routing table to access connection count and metrics
-            let backend_connection_count = scoring_context
-                .routing
-                .get(backend_id)
-                .map(|b| b.active_connections())
-                .unwrap_or(0);
-
-            let backend_weight_value =
-                backend.weight().unwrap_or(DEFAULT_BACKEND_WEIGHT) as f64;
-
-            // Get metrics from backend
-            let backend_metrics = scoring_context
-                .routing
-                .get(backend_id)
-                .map(|b| b.metrics_snapshot());
-
-            // Use cached score if available and valid
-            let computed_score =
-                if let Some(cached_score_value) = cached_scores[backend_index].1 {
-                    cached_score_value
-                } else {
-                    // Compute new score
-                    let new_score = compute_backend_score(
-                        backend_connection_count,
-                        backend_weight_value,
-                        backend_metrics,
-                        scoring_context,
-                        weights,
-                    );
-
-                    // Cache the score for future use
-                    cache.put(backend_id, new_score, current_timestamp_ms);
-                    new_score
-                };
-
-            (computed_score, backend.clone())
+        .map(|backend| {
+            let score = score_backend(
+                backend,
+                scoring_context,
+                cache,
+                weights,
+                current_timestamp_ms,
+            );
+            (score, backend)
         })
-        .collect()
+        .min_by(|(first_score, _), (second_score, _)| {
+            first_score
+                .partial_cmp(second_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|(_, backend)| backend)
 }
 
 #[cfg(test)]
@@ -319,6 +454,11 @@ mod tests {
             name: Some(format!("backend-{}", id)),
             address: address.into(),
             weight,
+            labels: Labels::new(),
+            bind_address: None,
+            dscp: None,
+            priority: None,
+            zone: None,
         }
     }
 
@@ -582,7 +722,7 @@ mod tests {
         backends[1].record_request(20, false);
 
         // When: preparing scoring context
-        let context = prepare_scoring_context(&backends, routing.clone());
+        let context = prepare_scoring_context(&backends);
 
         // Then: context has correct max values
         assert_eq!(context.max_connections, 2); // Max connection count
@@ -601,7 +741,7 @@ mod tests {
         let backends = routing.all_backends();
 
         // When: preparing scoring context
-        let context = prepare_scoring_context(&backends, routing.clone());
+        let context = prepare_scoring_context(&backends);
 
         // Then: context uses default max latency when no metrics (DEFAULT_MAX_LATENCY_MS)
         // Since max_latency_value == 0.0, it gets set to DEFAULT_MAX_LATENCY_MS
@@ -618,7 +758,7 @@ mod tests {
         let backends = routing.all_backends();
 
         // When: preparing scoring context
-        let context = prepare_scoring_context(&backends, routing.clone());
+        let context = prepare_scoring_context(&backends);
 
         // Then: max_connections is MIN_CONNECTION_COUNT (when 0, it's set to minimum)
         assert_eq!(context.max_connections, MIN_CONNECTION_COUNT);
@@ -634,16 +774,19 @@ mod tests {
             p95_latency_ms: 15.0,
             error_rate: 0.05,
             last_updated_ms: 1000,
+            weight_multiplier: 1.0,
+            cpu_percent: None,
+            rss_bytes: None,
+            timings: ConnectionTimings::default(),
+            bytes_in_rate: 0,
+            bytes_out_rate: 0,
         });
-        let routing = Arc::new(RouteTable::new(vec![create_test_backend_config(
-            0,
-            Some(2),
-        )]));
         let scoring_context = ScoringContext {
             max_connections: 10,
             max_latency_ms: 100.0,
             max_weight: 4.0,
-            routing: routing.clone(),
+            max_cpu_percent: 0.0,
+            max_rss_bytes: 0,
         };
         let weights = AdaptiveWeights::default();
 
@@ -666,15 +809,12 @@ mod tests {
         let backend_connection_count = 5;
         let backend_weight_value = 2.0;
         let backend_metrics = None;
-        let routing = Arc::new(RouteTable::new(vec![create_test_backend_config(
-            0,
-            Some(2),
-        )]));
         let scoring_context = ScoringContext {
             max_connections: 10,
             max_latency_ms: 100.0,
             max_weight: 4.0,
-            routing: routing.clone(),
+            max_cpu_percent: 0.0,
+            max_rss_bytes: 0,
         };
         let weights = AdaptiveWeights::default();
 
@@ -697,15 +837,12 @@ mod tests {
         let backend_connection_count = 5;
         let backend_weight_value = 2.0;
         let backend_metrics = None;
-        let routing = Arc::new(RouteTable::new(vec![create_test_backend_config(
-            0,
-            Some(2),
-        )]));
         let scoring_context = ScoringContext {
             max_connections: 10,
             max_latency_ms: 100.0,
             max_weight: 0.0,
-            routing: routing.clone(),
+            max_cpu_percent: 0.0,
+            max_rss_bytes: 0,
         };
         let weights = AdaptiveWeights::default();
 
@@ -723,108 +860,186 @@ mod tests {
     }
 
     #[test]
-    fn compute_all_backend_scores_should_succeed() {
-        // Given: backends, scoring context, cache, and weights
-        let backend_configs = vec![
-            create_test_backend_config(0, Some(2)),
-            create_test_backend_config(1, Some(4)),
-        ];
-        let routing = Arc::new(RouteTable::new(backend_configs));
-        let backends_meta: Vec<BackendMeta> = routing
-            .all_backends()
-            .iter()
-            .map(|b| BackendMeta::new(b.id(), b.name(), b.address().clone(), b.weight()))
-            .collect();
+    fn compute_backend_score_breakdown_sums_to_score_should_succeed() {
+        // Given: backend data with every factor set and non-default weights
+        let backend_metrics = BackendMetrics {
+            avg_latency_ms: 40.0,
+            p95_latency_ms: 90.0,
+            error_rate: 0.15,
+            last_updated_ms: 1000,
+            weight_multiplier: 1.0,
+            cpu_percent: Some(35.0),
+            rss_bytes: Some(64 * 1024 * 1024),
+            timings: ConnectionTimings::default(),
+            bytes_in_rate: 0,
+            bytes_out_rate: 0,
+        };
         let scoring_context = ScoringContext {
             max_connections: 10,
-            max_latency_ms: 100.0,
-            max_weight: 4.0,
-            routing: routing.clone(),
+            max_latency_ms: 120.0,
+            max_weight: 8.0,
+            max_cpu_percent: 70.0,
+            max_rss_bytes: 128 * 1024 * 1024,
+        };
+        let weights = AdaptiveWeights {
+            conn_weight: 0.3,
+            latency_weight: 0.3,
+            error_weight: 0.2,
+            resource_weight: 0.2,
         };
-        let cached_scores = vec![(0, None), (1, None)]; // No cached scores
-        let cache = AdaptiveCache::new(1000);
-        let weights = AdaptiveWeights::default();
-        let current_timestamp_ms = 1000u64;
 
-        // When: computing all backend scores
-        let scores = compute_all_backend_scores(
-            &backends_meta,
+        // When: computing the score and its breakdown
+        let score = compute_backend_score(
+            4,
+            2.0,
+            Some(backend_metrics.clone()),
+            &scoring_context,
+            &weights,
+        );
+        let breakdown = compute_backend_score_breakdown(
+            7,
+            4,
+            2.0,
+            Some(backend_metrics),
             &scoring_context,
-            &cached_scores,
-            &cache,
             &weights,
-            current_timestamp_ms,
         );
 
-        // Then: scores are computed for all backends
-        assert_eq!(scores.len(), 2);
-        assert!(scores[0].0 >= 0.0);
-        assert!(scores[1].0 >= 0.0);
+        // Then: the weighted factors, divided by the weight factor, add up
+        // to the score
+        let combined = breakdown.connection_score * weights.conn_weight
+            + breakdown.latency_score * weights.latency_weight
+            + (UNIT_WEIGHT_FACTOR - breakdown.error_penalty) * weights.error_weight
+            + breakdown.resource_score * weights.resource_weight;
+        assert_eq!(breakdown.backend_id, 7);
+        assert_eq!(breakdown.weight_factor, 0.25);
+        assert!(breakdown.resource_score > 0.0);
+        assert_eq!(breakdown.score, score);
+        assert!((combined / breakdown.weight_factor - score).abs() < 1e-12);
     }
 
     #[test]
-    fn compute_all_backend_scores_with_cached_scores_should_succeed() {
-        // Given: backends with cached scores
-        let backend_configs = vec![create_test_backend_config(0, Some(2))];
-        let routing = Arc::new(RouteTable::new(backend_configs));
-        let backends_meta: Vec<BackendMeta> = routing
-            .all_backends()
-            .iter()
-            .map(|b| BackendMeta::new(b.id(), b.name(), b.address().clone(), b.weight()))
-            .collect();
-        let scoring_context = ScoringContext {
-            max_connections: 10,
-            max_latency_ms: 100.0,
-            max_weight: 4.0,
-            routing: routing.clone(),
-        };
-        let cached_scores = vec![(0, Some(5.5))]; // Cached score
+    fn explain_backend_scores_matches_select_best_backend_should_succeed() {
+        // Given: two backends, the second one loaded
+        let routing = RouteTable::new(vec![
+            create_test_backend_config(0, Some(2)),
+            create_test_backend_config(1, Some(2)),
+        ]);
+        let backends = routing.all_backends();
+        backends[1].increment_connection();
+        let scoring_context = prepare_scoring_context(&backends);
         let cache = AdaptiveCache::new(1000);
         let weights = AdaptiveWeights::default();
-        let current_timestamp_ms = 1000u64;
 
-        // When: computing all backend scores
-        let scores = compute_all_backend_scores(
-            &backends_meta,
-            &scoring_context,
-            &cached_scores,
-            &cache,
-            &weights,
-            current_timestamp_ms,
-        );
+        // When: explaining and scoring the backends
+        let breakdown =
+            explain_backend_scores(&backends, &scoring_context, &weights, 1000);
+        let best =
+            select_best_backend(&backends, &scoring_context, &cache, &weights, 1000);
+
+        // Then: every backend is explained with the score the pick used
+        assert_eq!(breakdown.len(), 2);
+        for (explained, backend) in breakdown.iter().zip(&backends) {
+            assert_eq!(explained.backend_id, backend.id());
+            assert_eq!(Some(explained.score), cache.get(backend, 1000));
+        }
+        assert!(breakdown[0].score < breakdown[1].score);
+        assert_eq!(best.map(|backend| backend.id()), Some(0));
+    }
+
+    #[test]
+    fn score_backend_caches_score_should_succeed() {
+        // Given: a backend, scoring context, empty cache and weights
+        let routing = RouteTable::new(vec![create_test_backend_config(0, Some(2))]);
+        let backends = routing.all_backends();
+        let scoring_context = prepare_scoring_context(&backends);
+        let cache = AdaptiveCache::new(1000);
+        let weights = AdaptiveWeights::default();
+
+        // When: scoring the backend
+        let score = score_backend(&backends[0], &scoring_context, &cache, &weights, 1000);
+
+        // Then: the score is computed and cached
+        assert!(score >= 0.0);
+        assert_eq!(cache.get(&backends[0], 1000), Some(score));
+    }
+
+    #[test]
+    fn score_backend_with_cached_score_should_succeed() {
+        // Given: a backend with a cached score
+        let routing = RouteTable::new(vec![create_test_backend_config(0, Some(2))]);
+        let backends = routing.all_backends();
+        let scoring_context = prepare_scoring_context(&backends);
+        let cache = AdaptiveCache::new(1000);
+        cache.put(&backends[0], 5.5, 1000);
+        let weights = AdaptiveWeights::default();
+
+        // When: scoring the backend
+        let score = score_backend(&backends[0], &scoring_context, &cache, &weights, 1000);
 
         // Then: cached score is used
-        assert_eq!(scores.len(), 1);
-        assert_eq!(scores[0].0, 5.5);
+        assert_eq!(score, 5.5);
     }
 
     #[test]
-    fn compute_all_backend_scores_empty_backends_should_succeed() {
+    fn select_best_backend_should_succeed() {
+        // Given: two backends, the second one loaded
+        let routing = RouteTable::new(vec![
+            create_test_backend_config(0, Some(2)),
+            create_test_backend_config(1, Some(2)),
+        ]);
+        let backends = routing.all_backends();
+        backends[1].increment_connection();
+        let scoring_context = prepare_scoring_context(&backends);
+        let cache = AdaptiveCache::new(1000);
+        let weights = AdaptiveWeights::default();
+
+        // When: selecting the best backend
+        let best =
+            select_best_backend(&backends, &scoring_context, &cache, &weights, 1000);
+
+        // Then: the idle backend wins and both scores are cached
+        assert_eq!(best.map(|backend| backend.id()), Some(0));
+        let mut cached = cache.backend_ids();
+        cached.sort_unstable();
+        assert_eq!(cached, vec![0, 1]);
+    }
+
+    #[test]
+    fn select_best_backend_with_cached_scores_should_succeed() {
+        // Given: two backends, the loaded one with a better cached score
+        let routing = RouteTable::new(vec![
+            create_test_backend_config(0, Some(2)),
+            create_test_backend_config(1, Some(2)),
+        ]);
+        let backends = routing.all_backends();
+        backends[1].increment_connection();
+        let scoring_context = prepare_scoring_context(&backends);
+        let cache = AdaptiveCache::new(1000);
+        cache.put(&backends[1], -1.0, 1000);
+        let weights = AdaptiveWeights::default();
+
+        // When: selecting the best backend
+        let best =
+            select_best_backend(&backends, &scoring_context, &cache, &weights, 1000);
+
+        // Then: the cached score is used instead of rescoring the backend
+        assert_eq!(best.map(|backend| backend.id()), Some(1));
+    }
+
+    #[test]
+    fn select_best_backend_empty_backends_should_succeed() {
         // Given: empty backends list
-        let backends = Vec::<BackendMeta>::new();
-        let routing = Arc::new(RouteTable::new(Vec::new()));
-        let scoring_context = ScoringContext {
-            max_connections: 1,
-            max_latency_ms: 100.0,
-            max_weight: 1.0,
-            routing: routing.clone(),
-        };
-        let cached_scores = Vec::new();
+        let backends = Vec::<Arc<Backend>>::new();
+        let scoring_context = prepare_scoring_context(&backends);
         let cache = AdaptiveCache::new(1000);
         let weights = AdaptiveWeights::default();
-        let current_timestamp_ms = 1000u64;
 
-        // When: computing all backend scores
-        let scores = compute_all_backend_scores(
-            &backends,
-            &scoring_context,
-            &cached_scores,
-            &cache,
-            &weights,
-            current_timestamp_ms,
-        );
+        // When: selecting the best backend
+        let best =
+            select_best_backend(&backends, &scoring_context, &cache, &weights, 1000);
 
-        // Then: empty vector is returned
-        assert!(scores.is_empty());
+        // Then: no backend is selected
+        assert!(best.is_none());
     }
 }
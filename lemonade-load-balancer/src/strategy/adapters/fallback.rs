@@ -0,0 +1,129 @@
+use crate::prelude::*;
+
+/// Fallback strategy
+///
+/// Picks with the primary strategy and, when it fails for any reason other
+/// than [`StrategyError::NoBackendAvailable`], with the fallback one. No
+/// backend being available is terminal: the fallback would find none
+/// either. Every fallback is counted (see [`Context::strategy_fallbacks`]).
+pub struct FallbackStrategy {
+    /// Strategy tried first
+    primary: Arc<dyn StrategyService>,
+    /// Strategy picking when the primary one fails
+    fallback: Arc<dyn StrategyService>,
+}
+
+impl FallbackStrategy {
+    /// Create a strategy falling back from `primary` to `fallback`
+    pub fn new(
+        primary: Arc<dyn StrategyService>,
+        fallback: Arc<dyn StrategyService>,
+    ) -> Self {
+        Self { primary, fallback }
+    }
+
+    /// Strategy tried first
+    pub fn primary(&self) -> &Arc<dyn StrategyService> {
+        &self.primary
+    }
+
+    /// Strategy picking when the primary one fails
+    pub fn fallback(&self) -> &Arc<dyn StrategyService> {
+        &self.fallback
+    }
+
+    /// Whether the primary strategy's `result` should be retried with the
+    /// fallback one, counting it if so
+    fn falls_back(
+        &self,
+        ctx: &Context,
+        result: &Result<Arc<Backend>, StrategyError>,
+    ) -> bool {
+        match result {
+            Ok(_) | Err(StrategyError::NoBackendAvailable) => false,
+            Err(e) => {
+                ctx.record_strategy_fallback(
+                    &self.primary.strategy(),
+                    &self.fallback.strategy(),
+                    e,
+                );
+                true
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl StrategyService for FallbackStrategy {
+    fn strategy(&self) -> Strategy {
+        self.primary.strategy()
+    }
+
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        let result = self.primary.pick_backend(ctx.clone()).await;
+        if self.falls_back(&ctx, &result) {
+            return self.fallback.pick_backend(ctx).await;
+        }
+        result
+    }
+
+    async fn pick_backend_matching(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        let result = self
+            .primary
+            .pick_backend_matching(ctx.clone(), selector)
+            .await;
+        if self.falls_back(&ctx, &result) {
+            return self.fallback.pick_backend_matching(ctx, selector).await;
+        }
+        result
+    }
+
+    /// The primary strategy decides whether requests are read
+    fn request_head_limits(&self) -> Option<RequestHeadLimits> {
+        self.primary.request_head_limits()
+    }
+
+    async fn pick_backend_for_request(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+        request: &RequestMeta,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        let result = self
+            .primary
+            .pick_backend_for_request(ctx.clone(), selector, request)
+            .await;
+        if self.falls_back(&ctx, &result) {
+            return self
+                .fallback
+                .pick_backend_for_request(ctx, selector, request)
+                .await;
+        }
+        result
+    }
+
+    fn tracked_backends(&self) -> Vec<BackendId> {
+        let mut ids = self.primary.tracked_backends();
+        ids.extend(self.fallback.tracked_backends());
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    fn on_migrate(&self, routing: &RouteTable, now_ms: u64) {
+        self.primary.on_migrate(routing, now_ms);
+        self.fallback.on_migrate(routing, now_ms);
+    }
+
+    /// Explain the primary strategy, which makes every pick it can
+    fn explain_pick(&self, ctx: &Context) -> Vec<BackendScoreBreakdown> {
+        self.primary.explain_pick(ctx)
+    }
+}
@@ -13,22 +13,32 @@ impl StrategyService for FastestResponseTimeStrategy {
     async fn pick_backend(
         &self,
         ctx: Arc<Context>,
-    ) -> Result<BackendMeta, StrategyError> {
+    ) -> Result<Arc<Backend>, StrategyError> {
+        self.pick_backend_matching(ctx, &LabelSelector::default())
+            .await
+    }
+
+    async fn pick_backend_matching(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+    ) -> Result<Arc<Backend>, StrategyError> {
         let routing = ctx.routing_table();
-        let healthy = routing.healthy_backends();
+        trace_exclusions(&ctx, &routing, selector, false);
+        let healthy = routing.preferred_backends_matching(selector);
 
         if healthy.is_empty() {
             return Err(StrategyError::NoBackendAvailable);
         }
 
-        // Find backend with lowest average latency
+        // Find backend with lowest response latency (time-to-first-byte when known)
         // Use first backend as fallback if no metrics available
         let backend = healthy
             .iter()
             .filter_map(|b| {
-                let metrics = b.metrics_snapshot();
-                if metrics.avg_latency_ms > 0.0 {
-                    Some((metrics.avg_latency_ms, b))
+                let latency_ms = b.response_latency().mean_ms;
+                if latency_ms > 0.0 {
+                    Some((latency_ms, b))
                 } else {
                     None
                 }
@@ -40,12 +50,7 @@ impl StrategyService for FastestResponseTimeStrategy {
             .or_else(|| healthy.first())
             .ok_or(StrategyError::NoBackendAvailable)?;
 
-        Ok(BackendMeta::new(
-            backend.id(),
-            backend.name(),
-            backend.address().clone(),
-            backend.weight(),
-        ))
+        Ok(Arc::clone(backend))
     }
 }
 
@@ -71,6 +76,7 @@ mod tests {
             .map(|meta| BackendConfig::from(meta.clone()))
             .collect();
         Config {
+            version: CONFIG_VERSION,
             source: ConfigSource::Environment,
             runtime: RuntimeConfig {
                 metrics_cap: 100,
@@ -79,6 +85,11 @@ mod tests {
                 background_timeout_millis: 1000,
                 accept_timeout_millis: 2000,
                 config_watch_interval_millis: 1000,
+                drain_progress_interval_millis: 1000,
+                drain_policy: DrainPolicy::Finish,
+                proxy_worker_threads: None,
+                audit_interval_millis: None,
+                log_rate_limit: LogRateLimitConfig::default(),
             },
             proxy: ProxyConfig {
                 listen_address: SocketAddr::new(
@@ -86,19 +97,79 @@ mod tests {
                     3000,
                 ),
                 max_connections: Some(1000),
+                coalesce: CoalesceConfig::default(),
+                hedging: HedgingConfig::default(),
+                retry_budget: RetryBudgetConfig::default(),
+                circuit_breaker: CircuitBreakerConfig::default(),
+                route_by_sni: SniRoutingConfig::default(),
+                route_by_alpn: AlpnRoutingConfig::default(),
+                route_by_http: HttpRoutingConfig::default(),
+                slow_connect_warn_ms: None,
+                slow_connection_warn_secs: None,
+                strategy_pick_warn_micros: None,
+                initial_read_timeout_millis: None,
+                slow_log_max_per_minute: 10,
+                max_buffered_bytes: 256 * 1024,
+                backend_bind_address: None,
+                backend_dscp: None,
+                fd_headroom: 64,
+                max_backend_peer_connections: None,
+                max_hops: None,
+                transparent: false,
+                report_backend_aborts: true,
+                sticky_sessions: false,
+                sticky_ttl_millis: 300_000,
+                listen_unix: None,
+                max_pending_connects: 32,
+                local_zone: None,
+                zone_spillover_min_healthy: 1,
+                propagate_deadlines: false,
+                request_timeout_millis: 30_000,
             },
             strategy: Strategy::FastestResponseTime,
+            strategy_params: serde_json::Value::Null,
+            fallback: None,
             backends: backend_configs,
+            groups: Default::default(),
+            allow_duplicate_addresses: false,
+            auto_weight: false,
+            auto_weight_tuning: AutoWeightConfig::default(),
+            admin: AdminConfig::default(),
+            audit: AuditConfig::default(),
             health: HealthConfig {
                 interval: Duration::from_secs(5),
                 timeout: Duration::from_secs(1),
+                max_event_age_millis: 10_000,
+                evict_on_unhealthy: false,
+                slow_start_millis: 0,
+                outlier_error_percent: 0,
+                outlier_min_connections: 10,
+                outlier_window_millis: 10_000,
+                base_ejection_millis: 30_000,
+                max_ejection_percent: 10,
+                check: HealthCheck::Tcp,
+                healthy_threshold: 1,
+                unhealthy_threshold: 1,
+                jitter_percent: 0,
+                initial_state: InitialHealthState::default(),
+                reuse_connections: true,
             },
+            preflight: PreflightConfig::default(),
+            health_endpoint: HealthEndpointConfig::default(),
             metrics: MetricsConfig {
                 interval: Duration::from_secs(10),
                 timeout: Duration::from_secs(2),
+                max_event_age_millis: 30_000,
+                aggregation: LatencyAggregation::default(),
+                selection_trace: false,
             },
+            services: ServicesConfig::default(),
             otlp_protocol: None,
+            rng_seed: None,
+            state_file: None,
+            health_state_file: None,
             otlp_endpoint: None,
+            secrets: Default::default(),
         }
     }
 
@@ -150,7 +221,7 @@ mod tests {
             .expect("Failed to pick backend");
 
         // Then: backend with lowest latency (backend 1) is selected
-        assert_eq!(backend.id(), &1u8);
+        assert_eq!(backend.id(), 1u8);
     }
 
     #[tokio::test]
@@ -170,7 +241,7 @@ mod tests {
             .expect("Failed to pick backend");
 
         // Then: a backend is selected as fallback (could be any backend with no metrics)
-        assert!(*backend.id() == 0 || *backend.id() == 1);
+        assert!(backend.id() == 0 || backend.id() == 1);
     }
 
     #[tokio::test]
@@ -206,7 +277,7 @@ mod tests {
             .expect("Failed to pick backend");
 
         // Then: backend with lowest latency among those with metrics (backend 1) is selected
-        assert_eq!(backend.id(), &1u8);
+        assert_eq!(backend.id(), 1u8);
     }
 
     #[tokio::test]
@@ -262,7 +333,7 @@ mod tests {
             .expect("Failed to pick backend");
 
         // Then: a backend is selected (tie-breaking behavior - first in iteration)
-        assert!(*backend.id() == 0 || *backend.id() == 1);
+        assert!(backend.id() == 0 || backend.id() == 1);
     }
 
     #[test]
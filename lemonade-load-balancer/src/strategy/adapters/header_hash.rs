@@ -0,0 +1,188 @@
+use crate::prelude::*;
+use serde::Deserialize;
+
+/// `hash_on` value selecting the request path
+const HASH_ON_PATH: &str = "path";
+
+/// Header hash strategy parameters (`strategy_params`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct HeaderHashParams {
+    /// Header field name, or `path`
+    hash_on: String,
+    /// Time allowed for a request head to arrive
+    peek_timeout_millis: u64,
+    /// Cap on the bytes read looking for a request head
+    max_head_bytes: usize,
+}
+
+impl Default for HeaderHashParams {
+    fn default() -> Self {
+        Self {
+            hash_on: String::new(),
+            peek_timeout_millis: DEFAULT_PEEK_TIMEOUT_MILLIS,
+            max_head_bytes: DEFAULT_MAX_HEAD_BYTES,
+        }
+    }
+}
+
+/// What the header hash strategy hashes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashOn {
+    /// Value of a header field, by lowercase name
+    Header(String),
+    /// Request path, without the query string
+    Path,
+}
+
+impl std::str::FromStr for HashOn {
+    type Err = StrategyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case(HASH_ON_PATH) {
+            return Ok(HashOn::Path);
+        }
+        let is_token =
+            |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+        if s.is_empty() || !s.chars().all(is_token) {
+            return Err(StrategyError::UnexpectedError(format!(
+                "invalid header_hash params: hash_on must be a header name or \"path\", got {:?}",
+                s
+            )));
+        }
+        Ok(HashOn::Header(s.to_ascii_lowercase()))
+    }
+}
+
+/// Header hash strategy implementation
+///
+/// The proxy reads the first HTTP request of each cleartext connection and
+/// the strategy hashes the configured header field, or the path, onto the
+/// healthy backends with rendezvous hashing: a key maps to the same backend
+/// while the candidate set is unchanged, and only keys of a backend that
+/// leaves move. Connections without the key (header absent or empty, or not
+/// HTTP) are picked round robin.
+pub struct HeaderHashStrategy {
+    /// What is hashed
+    hash_on: HashOn,
+    /// How much of a connection the proxy reads for the request head
+    limits: RequestHeadLimits,
+    /// Picks for connections without a key
+    round_robin: RoundRobinStrategy,
+}
+
+impl HeaderHashStrategy {
+    /// Create a strategy hashing `hash_on`, with default read limits
+    pub fn new(hash_on: HashOn) -> Self {
+        Self {
+            hash_on,
+            limits: RequestHeadLimits::default(),
+            round_robin: RoundRobinStrategy::default(),
+        }
+    }
+
+    /// Create a strategy from the config's `strategy_params`
+    ///
+    /// Reads `hash_on` (required), `peek_timeout_millis` and
+    /// `max_head_bytes`; zero limits are rejected.
+    pub fn from_params(params: serde_json::Value) -> Result<Self, StrategyError> {
+        let params = if params.is_null() {
+            HeaderHashParams::default()
+        } else {
+            serde_json::from_value::<HeaderHashParams>(params).map_err(|e| {
+                StrategyError::UnexpectedError(format!(
+                    "invalid header_hash params: {}",
+                    e
+                ))
+            })?
+        };
+        if params.peek_timeout_millis == 0 || params.max_head_bytes == 0 {
+            return Err(StrategyError::UnexpectedError(
+                "invalid header_hash params: peek_timeout_millis and max_head_bytes must be positive"
+                    .to_string(),
+            ));
+        }
+        let mut strategy = Self::new(params.hash_on.parse()?);
+        strategy.limits = RequestHeadLimits {
+            timeout: Duration::from_millis(params.peek_timeout_millis),
+            max_bytes: params.max_head_bytes,
+        };
+        Ok(strategy)
+    }
+
+    /// What is hashed
+    pub fn hash_on(&self) -> &HashOn {
+        &self.hash_on
+    }
+
+    /// Key of `request`, if it carries a non-empty one
+    fn key<'a>(&self, request: &'a RequestMeta) -> Option<&'a str> {
+        match &self.hash_on {
+            HashOn::Header(name) => request.header(name),
+            HashOn::Path => Some(request.path.as_str()),
+        }
+        .filter(|key| !key.is_empty())
+    }
+}
+
+/// FNV-1a hash of `key`, stable across builds and processes
+fn key_hash(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Rendezvous weight of `backend_id` for a key hash (SplitMix64 finalizer)
+fn rendezvous_weight(key_hash: u64, backend_id: BackendId) -> u64 {
+    let mut z =
+        key_hash ^ (u64::from(backend_id) + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[async_trait]
+impl StrategyService for HeaderHashStrategy {
+    fn strategy(&self) -> Strategy {
+        Strategy::HeaderHash
+    }
+
+    /// Without a request there is no key: pick round robin
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        self.round_robin.pick_backend(ctx).await
+    }
+
+    async fn pick_backend_matching(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        self.round_robin.pick_backend_matching(ctx, selector).await
+    }
+
+    fn request_head_limits(&self) -> Option<RequestHeadLimits> {
+        Some(self.limits)
+    }
+
+    async fn pick_backend_for_request(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+        request: &RequestMeta,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        let Some(key) = self.key(request) else {
+            return self.round_robin.pick_backend_matching(ctx, selector).await;
+        };
+        let routing = ctx.routing_table();
+        trace_exclusions(&ctx, &routing, selector, false);
+        let key_hash = key_hash(key);
+        routing
+            .preferred_backends_matching(selector)
+            .into_iter()
+            .max_by_key(|backend| rendezvous_weight(key_hash, backend.id()))
+            .ok_or(StrategyError::NoBackendAvailable)
+    }
+}
@@ -0,0 +1,46 @@
+use crate::prelude::*;
+
+/// Least bandwidth strategy
+///
+/// Picks the backend that moved the fewest bytes per second, in and out
+/// combined, over the last few metrics flush intervals (see
+/// [`ByteRateWindow`]).
+/// Ties, such as every backend before the first rates are in, go to the
+/// backend with the fewest active connections.
+#[derive(Default)]
+pub struct LeastBandwidthStrategy {}
+
+#[async_trait]
+impl StrategyService for LeastBandwidthStrategy {
+    fn strategy(&self) -> Strategy {
+        Strategy::LeastBandwidth
+    }
+
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        self.pick_backend_matching(ctx, &LabelSelector::default())
+            .await
+    }
+
+    async fn pick_backend_matching(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        let routing = ctx.routing_table();
+        trace_exclusions(&ctx, &routing, selector, false);
+        routing
+            .preferred_backends_matching(selector)
+            .into_iter()
+            .min_by_key(|backend| {
+                let (bytes_in_rate, bytes_out_rate) = backend.byte_rates();
+                (
+                    bytes_in_rate.saturating_add(bytes_out_rate),
+                    backend.active_connections(),
+                )
+            })
+            .ok_or(StrategyError::NoBackendAvailable)
+    }
+}
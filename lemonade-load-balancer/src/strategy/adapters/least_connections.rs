@@ -1,8 +1,106 @@
 use crate::prelude::*;
+use arc_swap::ArcSwapOption;
+use serde::Deserialize;
+use std::sync::Mutex;
+
+/// Default backend count from which picks use the bucket index
+const DEFAULT_INDEX_MIN_BACKENDS: usize = 64;
+
+/// Least connections strategy parameters (`strategy_params`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct LeastConnectionsParams {
+    /// Backend count from which picks use the bucket index instead of a scan
+    index_min_backends: usize,
+}
+
+impl Default for LeastConnectionsParams {
+    fn default() -> Self {
+        Self {
+            index_min_backends: DEFAULT_INDEX_MIN_BACKENDS,
+        }
+    }
+}
+
+/// Bucket index built for one route table generation
+struct GenerationIndex {
+    generation: u64,
+    index: Arc<ConnectionIndex>,
+}
 
 /// Least connections strategy implementation
-#[derive(Default)]
-pub struct LeastConnectionsStrategy {}
+///
+/// Small pools are scanned on every pick. From `index_min_backends` backends
+/// on, picks take the lowest bucket of a [`ConnectionIndex`] that is rebuilt
+/// whenever the context swaps its route table.
+pub struct LeastConnectionsStrategy {
+    /// Backend count from which picks use the bucket index
+    index_min_backends: usize,
+    /// Index for the current route table, built on first indexed pick
+    index: ArcSwapOption<GenerationIndex>,
+    /// Serializes index rebuilds so every backend reports to the stored index
+    rebuild: Mutex<()>,
+}
+
+impl Default for LeastConnectionsStrategy {
+    fn default() -> Self {
+        Self::new(DEFAULT_INDEX_MIN_BACKENDS)
+    }
+}
+
+impl LeastConnectionsStrategy {
+    /// Create a strategy using the bucket index from `index_min_backends` backends
+    pub fn new(index_min_backends: usize) -> Self {
+        Self {
+            index_min_backends,
+            index: ArcSwapOption::empty(),
+            rebuild: Mutex::new(()),
+        }
+    }
+
+    /// Create a strategy from the config's `strategy_params` (`null` for defaults)
+    pub fn from_params(params: serde_json::Value) -> Result<Self, StrategyError> {
+        let params = if params.is_null() {
+            LeastConnectionsParams::default()
+        } else {
+            serde_json::from_value::<LeastConnectionsParams>(params).map_err(|e| {
+                StrategyError::UnexpectedError(format!(
+                    "invalid least_connections params: {}",
+                    e
+                ))
+            })?
+        };
+        Ok(Self::new(params.index_min_backends))
+    }
+
+    /// Backend count from which picks use the bucket index
+    pub fn index_min_backends(&self) -> usize {
+        self.index_min_backends
+    }
+
+    /// Index for the context's current route table, rebuilding it if stale
+    fn index_for(&self, ctx: &Context, routing: &RouteTable) -> Arc<ConnectionIndex> {
+        let generation = ctx.generation();
+        if let Some(current) = self.index.load().as_ref()
+            && current.generation == generation
+        {
+            return current.index.clone();
+        }
+
+        let _rebuild = self.rebuild.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(current) = self.index.load().as_ref()
+            && current.generation == generation
+        {
+            return current.index.clone();
+        }
+        let index = ConnectionIndex::build(&routing.all_backends());
+        self.index.store(Some(Arc::new(GenerationIndex {
+            generation,
+            index: index.clone(),
+        })));
+        index
+    }
+}
 
 #[async_trait]
 impl StrategyService for LeastConnectionsStrategy {
@@ -13,9 +111,40 @@ impl StrategyService for LeastConnectionsStrategy {
     async fn pick_backend(
         &self,
         ctx: Arc<Context>,
-    ) -> Result<BackendMeta, StrategyError> {
+    ) -> Result<Arc<Backend>, StrategyError> {
+        self.pick_backend_matching(ctx, &LabelSelector::default())
+            .await
+    }
+
+    async fn pick_backend_matching(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+    ) -> Result<Arc<Backend>, StrategyError> {
         let routing = ctx.routing_table();
-        let healthy = routing.healthy_backends();
+        trace_exclusions(&ctx, &routing, selector, false);
+
+        if routing.len() >= self.index_min_backends {
+            // Lowest bucket first, skipping unhealthy and unselected backends,
+            // backends outside the preferred priority tier or zone and
+            // backends at the cap on connects in flight
+            let priority = routing.active_priority(selector);
+            let zone = routing.preferred_zone(selector);
+            let mut picked = None;
+            self.index_for(&ctx, &routing).find(|id| {
+                picked = routing.get(id).filter(|backend| {
+                    backend.can_accept_new_connections()
+                        && backend.matches(selector)
+                        && Some(backend.priority()) == priority
+                        && routing.has_connect_room(backend)
+                        && zone.is_none_or(|zone| backend.zone() == Some(zone))
+                });
+                picked.is_some()
+            });
+            return picked.ok_or(StrategyError::NoBackendAvailable);
+        }
+
+        let healthy = routing.preferred_backends_matching(selector);
 
         if healthy.is_empty() {
             return Err(StrategyError::NoBackendAvailable);
@@ -27,11 +156,6 @@ impl StrategyService for LeastConnectionsStrategy {
             .min_by_key(|b| b.active_connections())
             .ok_or(StrategyError::NoBackendAvailable)?;
 
-        Ok(BackendMeta::new(
-            backend.id(),
-            backend.name(),
-            backend.address().clone(),
-            backend.weight(),
-        ))
+        Ok(Arc::clone(backend))
     }
 }
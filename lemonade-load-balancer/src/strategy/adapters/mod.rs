@@ -2,13 +2,21 @@
 //!
 
 mod adaptive;
+mod fallback;
 mod fastest_response_time;
+mod header_hash;
+mod least_bandwidth;
 mod least_connections;
+mod random;
 mod round_robin;
 mod weighted_round_robin;
 
 pub use adaptive::*;
+pub use fallback::*;
 pub use fastest_response_time::*;
+pub use header_hash::*;
+pub use least_bandwidth::*;
 pub use least_connections::*;
+pub use random::*;
 pub use round_robin::*;
 pub use weighted_round_robin::*;
@@ -0,0 +1,160 @@
+use crate::prelude::*;
+use arc_swap::ArcSwapOption;
+use std::sync::{Mutex, OnceLock};
+
+/// Name of the random stream the strategy draws from
+const RNG_COMPONENT: &str = "strategy.random";
+
+/// Cumulative weights of the routed backends for one route table generation
+struct GenerationWeights {
+    generation: u64,
+    /// Backends in id order
+    backends: Vec<Arc<Backend>>,
+    /// Running sum of the backends' weights, `cumulative[i]` ending backend `i`
+    cumulative: Vec<u64>,
+}
+
+impl GenerationWeights {
+    fn build(generation: u64, routing: &RouteTable) -> Self {
+        let backends = routing.all_backends();
+        let cumulative = backends
+            .iter()
+            .scan(0u64, |total, backend| {
+                *total += u64::from(backend.weight().unwrap_or(1));
+                Some(*total)
+            })
+            .collect();
+        Self {
+            generation,
+            backends,
+            cumulative,
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.cumulative.last().copied().unwrap_or(0)
+    }
+
+    /// Weight of backend `i`
+    fn weight(&self, i: usize) -> u64 {
+        self.cumulative[i] - i.checked_sub(1).map_or(0, |j| self.cumulative[j])
+    }
+
+    /// Backend whose weight range holds `target` (`target < total`)
+    fn at(&self, target: u64) -> &Arc<Backend> {
+        &self.backends[self.cumulative.partition_point(|&sum| sum <= target)]
+    }
+}
+
+/// Weighted random strategy implementation
+///
+/// Picks a healthy backend with probability proportional to its configured
+/// weight (1 when unset, never for 0). Cumulative weights are cached and
+/// rebuilt when the context generation changes, which every migration
+/// (`ConfigEvent::Migrated`) and weight change bumps. Draws come from the
+/// context's seeded [`RngProvider`], so `rng_seed` reproduces pick sequences.
+#[derive(Default)]
+pub struct RandomStrategy {
+    /// Weights for the current route table, built on first pick
+    weights: ArcSwapOption<GenerationWeights>,
+    /// Random stream, taken from the context on first pick
+    rng: OnceLock<Mutex<RngStream>>,
+}
+
+impl RandomStrategy {
+    /// Create a weighted random strategy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Weights for the context's current route table, rebuilding them if stale
+    fn weights_for(&self, ctx: &Context, routing: &RouteTable) -> Arc<GenerationWeights> {
+        let generation = ctx.generation();
+        if let Some(current) = self.weights.load_full()
+            && current.generation == generation
+        {
+            return current;
+        }
+        // Concurrent rebuilds of a generation build the same weights
+        let weights = Arc::new(GenerationWeights::build(generation, routing));
+        self.weights.store(Some(weights.clone()));
+        weights
+    }
+
+    /// Uniform value in `[0, bound)` from the strategy's stream
+    fn below(&self, ctx: &Context, bound: u64) -> u64 {
+        self.rng
+            .get_or_init(|| Mutex::new(ctx.rng().stream(RNG_COMPONENT)))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .below(bound)
+    }
+}
+
+#[async_trait]
+impl StrategyService for RandomStrategy {
+    fn strategy(&self) -> Strategy {
+        Strategy::Random
+    }
+
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        self.pick_backend_matching(ctx, &LabelSelector::default())
+            .await
+    }
+
+    async fn pick_backend_matching(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        let routing = ctx.routing_table();
+        trace_exclusions(&ctx, &routing, selector, true);
+        let weights = self.weights_for(&ctx, &routing);
+        let priority = routing.active_priority(selector);
+        let zone = routing.preferred_zone(selector);
+        let eligible = |backend: &Arc<Backend>| {
+            backend.can_accept_new_connections()
+                && backend.matches(selector)
+                && Some(backend.priority()) == priority
+                && routing.has_connect_room(backend)
+                && zone.is_none_or(|zone| backend.zone() == Some(zone))
+        };
+
+        // Draw over every routed backend and keep the pick if it is eligible
+        let total = weights.total();
+        if total == 0 {
+            return Err(StrategyError::NoBackendAvailable);
+        }
+        let picked = weights.at(self.below(&ctx, total));
+        if eligible(picked) {
+            return Ok(Arc::clone(picked));
+        }
+
+        // Otherwise draw again over the eligible backends only; together both
+        // draws stay proportional to the weights of the eligible backends
+        let eligible_weights = || {
+            weights
+                .backends
+                .iter()
+                .enumerate()
+                .filter(|(_, backend)| eligible(backend))
+                .map(|(i, backend)| (backend, weights.weight(i)))
+        };
+        let eligible_total: u64 = eligible_weights().map(|(_, weight)| weight).sum();
+        if eligible_total == 0 {
+            return Err(StrategyError::NoBackendAvailable);
+        }
+        let mut target = self.below(&ctx, eligible_total);
+        for (backend, weight) in eligible_weights() {
+            if target < weight {
+                return Ok(Arc::clone(backend));
+            }
+            target -= weight;
+        }
+        // Health changed between the two passes
+        Err(StrategyError::NoBackendAvailable)
+    }
+}
@@ -15,9 +15,19 @@ impl StrategyService for RoundRobinStrategy {
     async fn pick_backend(
         &self,
         ctx: Arc<Context>,
-    ) -> Result<BackendMeta, StrategyError> {
+    ) -> Result<Arc<Backend>, StrategyError> {
+        self.pick_backend_matching(ctx, &LabelSelector::default())
+            .await
+    }
+
+    async fn pick_backend_matching(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+    ) -> Result<Arc<Backend>, StrategyError> {
         let routing = ctx.routing_table();
-        let healthy = routing.healthy_backends();
+        trace_exclusions(&ctx, &routing, selector, false);
+        let healthy = routing.preferred_backends_matching(selector);
 
         if healthy.is_empty() {
             return Err(StrategyError::NoBackendAvailable);
@@ -26,11 +36,6 @@ impl StrategyService for RoundRobinStrategy {
         // Round robin over healthy backends
         let idx = self.counter.fetch_add(1, Ordering::Relaxed) % healthy.len();
         let backend = &healthy[idx];
-        Ok(BackendMeta::new(
-            backend.id(),
-            backend.name(),
-            backend.address().clone(),
-            backend.weight(),
-        ))
+        Ok(Arc::clone(backend))
     }
 }
@@ -1,9 +1,25 @@
 use crate::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Current weights for one route table generation
+#[derive(Default)]
+struct SmoothWeights {
+    generation: u64,
+    current: HashMap<BackendId, i64>,
+}
 
 /// Weighted round robin strategy implementation
+///
+/// Smooth weighted round robin, as in nginx: every pick adds each candidate's
+/// effective weight to its current weight, takes the highest one and lowers it
+/// by the total. Weights 3/2 give `A B A B A` rather than `A A A B B`. Current
+/// weights are kept per [`BackendId`] and start over when the context moves to
+/// a new generation (route table swap or weight change). A backend in slow
+/// start takes part with its ramped weight.
 #[derive(Default)]
 pub struct WeightedRoundRobinStrategy {
-    current_index: AtomicUsize,
+    weights: Mutex<SmoothWeights>,
 }
 
 #[async_trait]
@@ -15,50 +31,56 @@ impl StrategyService for WeightedRoundRobinStrategy {
     async fn pick_backend(
         &self,
         ctx: Arc<Context>,
-    ) -> Result<BackendMeta, StrategyError> {
+    ) -> Result<Arc<Backend>, StrategyError> {
+        self.pick_backend_matching(ctx, &LabelSelector::default())
+            .await
+    }
+
+    async fn pick_backend_matching(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+    ) -> Result<Arc<Backend>, StrategyError> {
         let routing = ctx.routing_table();
-        let healthy = routing.healthy_backends();
+        trace_exclusions(&ctx, &routing, selector, true);
+        let healthy = routing.preferred_backends_matching(selector);
 
         if healthy.is_empty() {
             return Err(StrategyError::NoBackendAvailable);
         }
 
-        // Build weights vector for current healthy backends
-        let weights: Vec<usize> = healthy
-            .iter()
-            .map(|b| b.weight().unwrap_or(1) as usize)
-            .collect();
-
-        let total_weight: usize = weights.iter().sum();
-
-        if total_weight == 0 {
-            return Err(StrategyError::NoBackendAvailable);
+        let generation = ctx.generation();
+        let now_ms = ctx.clock().now_millis();
+        let mut weights = self.weights.lock().unwrap_or_else(|e| e.into_inner());
+        if weights.generation != generation {
+            weights.generation = generation;
+            weights.current.clear();
         }
 
-        // Weighted round robin selection
-        let current = self.current_index.fetch_add(1, Ordering::Relaxed);
-        let mut weight_sum = 0;
-        let target = current % total_weight;
-
+        // Smooth weighted round robin over current healthy backends (auto-weight
+        // and slow start aware)
+        let mut total: i64 = 0;
+        let mut best: Option<(usize, i64)> = None;
         for (i, backend) in healthy.iter().enumerate() {
-            weight_sum += weights[i];
-            if target < weight_sum {
-                return Ok(BackendMeta::new(
-                    backend.id(),
-                    backend.name(),
-                    backend.address().clone(),
-                    backend.weight(),
-                ));
+            let weight = i64::from(backend.effective_weight_milli(now_ms));
+            if weight == 0 {
+                continue;
+            }
+            let current = weights.current.entry(backend.id()).or_default();
+            *current += weight;
+            total += weight;
+            if best.is_none_or(|(_, highest)| *current > highest) {
+                best = Some((i, *current));
             }
         }
 
-        // Fallback to first backend (should never reach here)
-        let backend = &healthy[0];
-        Ok(BackendMeta::new(
-            backend.id(),
-            backend.name(),
-            backend.address().clone(),
-            backend.weight(),
-        ))
+        let Some((index, _)) = best else {
+            return Err(StrategyError::NoBackendAvailable);
+        };
+        let backend = &healthy[index];
+        if let Some(current) = weights.current.get_mut(&backend.id()) {
+            *current -= total;
+        }
+        Ok(Arc::clone(backend))
     }
 }
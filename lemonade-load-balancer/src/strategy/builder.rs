@@ -8,6 +8,11 @@ pub struct StrategyBuilder {
     /// Strategy
     strategy: Option<Strategy>,
     backends: Vec<BackendMeta>,
+    /// Strategy parameters (least connections, adaptive, header hash and custom
+    /// strategy factories)
+    params: serde_json::Value,
+    /// Strategy picking when the primary one fails
+    fallback: Option<Strategy>,
 }
 
 impl StrategyBuilder {
@@ -28,21 +33,68 @@ impl StrategyBuilder {
         self
     }
 
+    /// Set the strategy parameters
+    pub fn with_params(mut self, params: serde_json::Value) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Set the strategy picking when the primary one fails
+    pub fn with_fallback(mut self, fallback: Option<Strategy>) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
     /// Build the strategy
+    ///
+    /// Custom strategies are built by the factory registered for their name.
+    /// Least connections reads `index_min_backends` from the parameters,
+    /// adaptive reads its scoring weights and header hash what it hashes.
+    /// With a fallback, the strategy is wrapped in a [`FallbackStrategy`];
+    /// the fallback is built with default parameters.
     pub fn build(self) -> Result<Arc<dyn StrategyService>, StrategyError> {
-        match self.strategy {
+        let Some(fallback) = self.fallback else {
+            return Self::build_one(self.strategy, self.params);
+        };
+        let primary = Self::build_one(self.strategy, self.params)?;
+        let fallback = Self::build_one(Some(fallback), serde_json::Value::Null)?;
+        Ok(Arc::new(FallbackStrategy::new(primary, fallback)))
+    }
+
+    /// Build a single strategy from its parameters
+    fn build_one(
+        strategy: Option<Strategy>,
+        params: serde_json::Value,
+    ) -> Result<Arc<dyn StrategyService>, StrategyError> {
+        match strategy {
             Some(strategy) => match strategy {
-                Strategy::Adaptive => Ok(Arc::new(AdaptiveStrategy::default())),
+                Strategy::Adaptive => {
+                    Ok(Arc::new(AdaptiveStrategy::from_params(params)?))
+                }
                 Strategy::FastestResponseTime => {
                     Ok(Arc::new(FastestResponseTimeStrategy::default()))
                 }
+                Strategy::HeaderHash => {
+                    Ok(Arc::new(HeaderHashStrategy::from_params(params)?))
+                }
+                Strategy::LeastBandwidth => {
+                    Ok(Arc::new(LeastBandwidthStrategy::default()))
+                }
                 Strategy::LeastConnections => {
-                    Ok(Arc::new(LeastConnectionsStrategy::default()))
+                    Ok(Arc::new(LeastConnectionsStrategy::from_params(params)?))
                 }
+                Strategy::Random => Ok(Arc::new(RandomStrategy::new())),
                 Strategy::RoundRobin => Ok(Arc::new(RoundRobinStrategy::default())),
                 Strategy::WeightedRoundRobin => {
                     Ok(Arc::new(WeightedRoundRobinStrategy::default()))
                 }
+                Strategy::Custom(name) => match crate::strategy::lookup(&name) {
+                    Some(factory) => factory.build(params),
+                    None => Err(StrategyError::NotFound(format!(
+                        "no factory registered for custom strategy {}",
+                        name
+                    ))),
+                },
             },
             None => Err(StrategyError::NotFound("Strategy not found".to_string())),
         }
@@ -4,8 +4,14 @@
 pub const STRATEGY_ADAPTIVE: &str = "adaptive";
 /// Fastest response time strategy
 pub const STRATEGY_FASTEST_RESPONSE_TIME: &str = "fastest_response_time";
+/// Header hash strategy
+pub const STRATEGY_HEADER_HASH: &str = "header_hash";
+/// Least bandwidth strategy
+pub const STRATEGY_LEAST_BANDWIDTH: &str = "least_bandwidth";
 /// Least connections strategy
 pub const STRATEGY_LEAST_CONNECTIONS: &str = "least_connections";
+/// Weighted random strategy
+pub const STRATEGY_RANDOM: &str = "random";
 /// Round robin strategy
 pub const STRATEGY_ROUND_ROBIN: &str = "round_robin";
 /// Weighted round robin strategy
@@ -1,5 +1,8 @@
 //! Strategy module
 //!
+//! External strategies plug in through [`register`]: configure
+//! `strategy = "<name>"` and the builder asks the registered
+//! [`StrategyFactory`](registry::StrategyFactory) to build it.
 
 pub mod adapters;
 pub mod builder;
@@ -7,3 +10,7 @@ pub mod constants;
 pub mod error;
 pub mod models;
 pub mod port;
+pub mod registry;
+pub mod trace;
+
+pub use registry::{is_registered, lookup, register, unregister};
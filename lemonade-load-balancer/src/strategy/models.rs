@@ -5,19 +5,30 @@ use crate::prelude::*;
 use serde::{Deserialize, Serialize};
 
 /// Strategy enum
-#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+///
+/// Serialized as its name; names that are not built in parse as
+/// [`Strategy::Custom`] and are resolved through the strategy registry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum Strategy {
     /// Adaptive strategy
     Adaptive,
     /// Fastest response time strategy
     FastestResponseTime,
+    /// Header (or path) hash strategy
+    HeaderHash,
+    /// Least bandwidth strategy
+    LeastBandwidth,
     /// Least connections strategy
     LeastConnections,
+    /// Weighted random strategy
+    Random,
     /// Round robin strategy
     RoundRobin,
     /// Weighted round robin strategy
     WeightedRoundRobin,
+    /// External strategy registered with [`crate::strategy::register`]
+    Custom(String),
 }
 
 impl std::str::FromStr for Strategy {
@@ -27,22 +38,91 @@ impl std::str::FromStr for Strategy {
         match s {
             STRATEGY_ADAPTIVE => Ok(Strategy::Adaptive),
             STRATEGY_FASTEST_RESPONSE_TIME => Ok(Strategy::FastestResponseTime),
+            STRATEGY_HEADER_HASH => Ok(Strategy::HeaderHash),
+            STRATEGY_LEAST_BANDWIDTH => Ok(Strategy::LeastBandwidth),
             STRATEGY_LEAST_CONNECTIONS => Ok(Strategy::LeastConnections),
+            STRATEGY_RANDOM => Ok(Strategy::Random),
             STRATEGY_ROUND_ROBIN => Ok(Strategy::RoundRobin),
             STRATEGY_WEIGHTED_ROUND_ROBIN => Ok(Strategy::WeightedRoundRobin),
+            _ if is_custom_name(s) => Ok(Strategy::Custom(s.to_string())),
             _ => Err(StrategyError::NotFound(s.to_string())),
         }
     }
 }
 
+impl Strategy {
+    /// Whether the strategy ranks backends by aggregated metrics
+    ///
+    /// These strategies cannot rank anything when metrics aggregation is
+    /// disabled (see [`crate::prelude::ServicesConfig`]).
+    pub fn requires_metrics(&self) -> bool {
+        matches!(
+            self,
+            Strategy::Adaptive | Strategy::FastestResponseTime | Strategy::LeastBandwidth
+        )
+    }
+}
+
 impl AsRef<str> for Strategy {
     fn as_ref(&self) -> &str {
         match self {
             Strategy::Adaptive => STRATEGY_ADAPTIVE,
             Strategy::FastestResponseTime => STRATEGY_FASTEST_RESPONSE_TIME,
+            Strategy::HeaderHash => STRATEGY_HEADER_HASH,
+            Strategy::LeastBandwidth => STRATEGY_LEAST_BANDWIDTH,
             Strategy::LeastConnections => STRATEGY_LEAST_CONNECTIONS,
+            Strategy::Random => STRATEGY_RANDOM,
             Strategy::RoundRobin => STRATEGY_ROUND_ROBIN,
             Strategy::WeightedRoundRobin => STRATEGY_WEIGHTED_ROUND_ROBIN,
+            Strategy::Custom(name) => name,
         }
     }
 }
+
+impl TryFrom<String> for Strategy {
+    type Error = StrategyError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl From<Strategy> for String {
+    fn from(strategy: Strategy) -> Self {
+        strategy.as_ref().to_string()
+    }
+}
+
+/// Custom strategy names are non-empty and made of ASCII letters, digits,
+/// `_`, `-` and `.`
+fn is_custom_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Score breakdown of one candidate backend (see
+/// [`StrategyService::explain_pick`])
+///
+/// Factor scores are normalized against the other candidates. The final
+/// `score` is `(connection_score * conn_weight + latency_score *
+/// latency_weight + (1 - error_penalty) * error_weight + resource_score *
+/// resource_weight) / weight_factor`; lower is better.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BackendScoreBreakdown {
+    /// Backend id
+    pub backend_id: BackendId,
+    /// Connection load score, normalized by weight
+    pub connection_score: f64,
+    /// Latency score, including the variance penalty
+    pub latency_score: f64,
+    /// Error penalty, `1` for a backend without errors
+    pub error_penalty: f64,
+    /// Worker-reported resource pressure score
+    pub resource_score: f64,
+    /// Weight relative to the heaviest candidate, slow start included
+    pub weight_factor: f64,
+    /// Final score
+    pub score: f64,
+}
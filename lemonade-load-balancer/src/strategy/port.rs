@@ -7,7 +7,76 @@ use crate::prelude::*;
 pub trait StrategyService: Send + Sync + 'static {
     /// Get the strategy
     fn strategy(&self) -> Strategy;
-    /// Pick a backend, returns the selected backend metadata
-    async fn pick_backend(&self, ctx: Arc<Context>)
-    -> Result<BackendMeta, StrategyError>;
+    /// Pick a backend, returns the selected backend from the route table
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError>;
+
+    /// Pick a backend among the healthy backends selected by `selector`
+    ///
+    /// Built-in strategies restrict their candidates to the selected group.
+    /// The default implementation accepts the unrestricted pick only when it
+    /// matches the selector.
+    async fn pick_backend_matching(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        let picked = self.pick_backend(ctx).await?;
+        if picked.matches(selector) {
+            Ok(picked)
+        } else {
+            Err(StrategyError::NoBackendAvailable)
+        }
+    }
+
+    /// How much of a connection the proxy reads for its first HTTP request
+    /// head before picking, for strategies that pick by request
+    ///
+    /// With limits, the proxy picks for cleartext connections through
+    /// [`pick_backend_for_request`](Self::pick_backend_for_request) once
+    /// the head is read. Other strategies keep the default, `None`, and are
+    /// picked for as soon as a connection is accepted.
+    fn request_head_limits(&self) -> Option<RequestHeadLimits> {
+        None
+    }
+
+    /// Pick a backend among the healthy backends selected by `selector` for
+    /// a connection whose first HTTP request is `request`
+    ///
+    /// The default implementation ignores the request.
+    async fn pick_backend_for_request(
+        &self,
+        ctx: Arc<Context>,
+        selector: &LabelSelector,
+        _request: &RequestMeta,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        self.pick_backend_matching(ctx, selector).await
+    }
+
+    /// Backends the strategy keeps per-backend state for
+    ///
+    /// The consistency audit reports ids missing from the route table.
+    /// Stateless strategies keep the default, which reports none.
+    fn tracked_backends(&self) -> Vec<BackendId> {
+        Vec::new()
+    }
+
+    /// Prepare for a migration about to swap in `routing`
+    ///
+    /// Called by [`Context::migrate`] with the new route table before it is
+    /// swapped in. The strategy is kept across migrations that leave the
+    /// strategy, its parameters and its fallback unchanged. Strategies caching per-backend state drop the entries of removed or
+    /// replaced backends and may warm up new ones. The default does nothing.
+    fn on_migrate(&self, _routing: &RouteTable, _now_ms: u64) {}
+
+    /// Explain how a pick would rank the healthy backends
+    ///
+    /// Returns the score breakdown of every candidate of an unrestricted
+    /// pick. Strategies that do not score backends keep the default, which
+    /// explains nothing.
+    fn explain_pick(&self, _ctx: &Context) -> Vec<BackendScoreBreakdown> {
+        Vec::new()
+    }
 }
@@ -0,0 +1,73 @@
+//! Strategy registry module
+//!
+//! Process-wide registry of external strategy factories, consulted by the
+//! strategy builder for [`Strategy::Custom`] names
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+/// Builds an external strategy from its configured parameters
+pub trait StrategyFactory: Send + Sync + 'static {
+    /// Build the strategy
+    ///
+    /// `params` is the config's `strategy_params` value (`null` when unset).
+    fn build(
+        &self,
+        params: serde_json::Value,
+    ) -> Result<Arc<dyn StrategyService>, StrategyError>;
+}
+
+/// Registered factories by strategy name
+type Registry = HashMap<String, Arc<dyn StrategyFactory>>;
+
+static REGISTRY: LazyLock<RwLock<Registry>> = LazyLock::new(Default::default);
+
+/// Register a factory for a custom strategy name
+///
+/// Registering a name again replaces its factory. Built-in strategy names
+/// cannot be registered.
+pub fn register(
+    name: impl Into<String>,
+    factory: Arc<dyn StrategyFactory>,
+) -> Result<(), StrategyError> {
+    let name = name.into();
+    match name.parse::<Strategy>()? {
+        Strategy::Custom(_) => {}
+        _ => {
+            return Err(StrategyError::UnexpectedError(format!(
+                "cannot register built-in strategy name: {}",
+                name
+            )));
+        }
+    }
+    REGISTRY
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(name, factory);
+    Ok(())
+}
+
+/// Remove a custom strategy factory, returning it if it was registered
+pub fn unregister(name: &str) -> Option<Arc<dyn StrategyFactory>> {
+    REGISTRY
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(name)
+}
+
+/// Look up the factory registered for a custom strategy name
+pub fn lookup(name: &str) -> Option<Arc<dyn StrategyFactory>> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+        .cloned()
+}
+
+/// Check whether a factory is registered for a custom strategy name
+pub fn is_registered(name: &str) -> bool {
+    REGISTRY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains_key(name)
+}
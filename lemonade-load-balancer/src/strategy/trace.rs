@@ -0,0 +1,127 @@
+//! Selection trace module
+//!
+//! With `metrics.selection_trace` on, every pick counts why each backend it
+//! skipped was not a candidate. Strategies call [`trace_exclusions`] once per
+//! pick; the counts are kept on the backends, shown in `GET /status` and the
+//! state file, and exported by the metrics service as
+//! `lemonade_backend_selection_exclusions_total`.
+use crate::prelude::*;
+
+/// Why a backend was not a candidate for a pick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ExclusionReason {
+    /// Not selected by the pick's label selector (SNI or ALPN route)
+    NotInGroup,
+    /// Disabled or drained by an operator (see [`AdminState`])
+    Disabled,
+    /// Draining after a migration or an admin drain
+    Draining,
+    /// Failed its last health check
+    Unhealthy,
+    /// Weight 0 under a weight-aware strategy
+    ZeroWeight,
+    /// Backup in a priority tier behind one that has a healthy backend
+    Standby,
+    /// At the cap on connects in flight (`proxy.max_pending_connects`)
+    PendingConnects,
+    /// Outside `proxy.local_zone` while the local zone has capacity
+    RemoteZone,
+}
+
+impl ExclusionReason {
+    /// Every reason, in the order backends count them
+    pub const ALL: [ExclusionReason; 8] = [
+        ExclusionReason::NotInGroup,
+        ExclusionReason::Disabled,
+        ExclusionReason::Draining,
+        ExclusionReason::Unhealthy,
+        ExclusionReason::ZeroWeight,
+        ExclusionReason::Standby,
+        ExclusionReason::PendingConnects,
+        ExclusionReason::RemoteZone,
+    ];
+
+    /// Reason name used in metrics and snapshots
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExclusionReason::NotInGroup => "not_in_group",
+            ExclusionReason::Disabled => "disabled",
+            ExclusionReason::Draining => "draining",
+            ExclusionReason::Unhealthy => "unhealthy",
+            ExclusionReason::ZeroWeight => "zero_weight",
+            ExclusionReason::Standby => "standby",
+            ExclusionReason::PendingConnects => "pending_connects",
+            ExclusionReason::RemoteZone => "remote_zone",
+        }
+    }
+
+    /// Why `backend` is not a candidate for a pick under `selector`, if it is
+    /// excluded at all
+    ///
+    /// A backend excluded for several reasons is counted under the first one
+    /// of [`ExclusionReason::ALL`]. Zero weight only excludes backends from
+    /// `weighted` strategies. `priority` is the tier the pick is made from
+    /// (see [`RouteTable::active_priority`]), `max_pending_connects` the
+    /// cap on connects in flight, if any, and `zone` the zone the pick is
+    /// kept to, if any (see [`RouteTable::preferred_zone`]).
+    pub fn of(
+        backend: &Backend,
+        selector: &LabelSelector,
+        weighted: bool,
+        priority: Option<u8>,
+        max_pending_connects: Option<usize>,
+        zone: Option<&str>,
+    ) -> Option<Self> {
+        if !backend.matches(selector) {
+            Some(ExclusionReason::NotInGroup)
+        } else if !backend.admin_state().accepts_new_connections() {
+            Some(ExclusionReason::Disabled)
+        } else if !backend.is_active() {
+            Some(ExclusionReason::Draining)
+        } else if !backend.is_alive() {
+            Some(ExclusionReason::Unhealthy)
+        } else if weighted && backend.weight() == Some(0) {
+            Some(ExclusionReason::ZeroWeight)
+        } else if Some(backend.priority()) != priority {
+            Some(ExclusionReason::Standby)
+        } else if max_pending_connects
+            .is_some_and(|max| backend.pending_connects() >= max)
+        {
+            Some(ExclusionReason::PendingConnects)
+        } else if zone.is_some_and(|zone| backend.zone() != Some(zone)) {
+            Some(ExclusionReason::RemoteZone)
+        } else {
+            None
+        }
+    }
+}
+
+/// Count the backends of `routing` a pick under `selector` skipped, by
+/// reason, when the selection trace is on
+///
+/// `weighted` strategies (those that never pick a zero-weight backend) also
+/// count zero-weight backends.
+pub fn trace_exclusions(
+    ctx: &Context,
+    routing: &RouteTable,
+    selector: &LabelSelector,
+    weighted: bool,
+) {
+    if !ctx.config().metrics.selection_trace {
+        return;
+    }
+    let priority = routing.active_priority(selector);
+    let zone = routing.preferred_zone(selector);
+    for backend in routing.all_backends() {
+        if let Some(reason) = ExclusionReason::of(
+            &backend,
+            selector,
+            weighted,
+            priority,
+            routing.max_pending_connects(),
+            zone,
+        ) {
+            backend.record_exclusion(reason);
+        }
+    }
+}
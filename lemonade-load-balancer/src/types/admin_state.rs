@@ -0,0 +1,86 @@
+//! Admin state module
+//!
+//! Operator override of whether a backend takes traffic, set apart from what
+//! its health checks say
+use serde::{Deserialize, Serialize};
+
+/// Operator override of a backend's place in rotation
+///
+/// Serialized as `enabled`, `disabled` or `draining`. Health checks keep
+/// running whatever the state, so a re-enabled backend comes back with its
+/// current health.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminState {
+    /// Takes traffic while healthy
+    #[default]
+    Enabled,
+    /// Takes no new connections at all
+    Disabled,
+    /// Takes no new connections, except from clients stuck to it by a sticky
+    /// session
+    Draining,
+}
+
+impl AdminState {
+    /// Check whether the backend may be picked for new connections
+    pub fn accepts_new_connections(self) -> bool {
+        self == Self::Enabled
+    }
+
+    /// Check whether clients stuck to the backend may keep connecting to it
+    pub fn accepts_sticky_clients(self) -> bool {
+        self != Self::Disabled
+    }
+
+    /// State name, as used by the admin API and snapshots
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Enabled => "enabled",
+            Self::Disabled => "disabled",
+            Self::Draining => "draining",
+        }
+    }
+
+    /// Encoding stored in a backend's atomic
+    pub(crate) fn to_u8(self) -> u8 {
+        match self {
+            Self::Enabled => 0,
+            Self::Disabled => 1,
+            Self::Draining => 2,
+        }
+    }
+
+    /// Decode a backend's atomic, see [`AdminState::to_u8`]
+    pub(crate) fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Disabled,
+            2 => Self::Draining,
+            _ => Self::Enabled,
+        }
+    }
+}
+
+impl std::str::FromStr for AdminState {
+    type Err = AdminStateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "enabled" => Ok(Self::Enabled),
+            "disabled" => Ok(Self::Disabled),
+            "draining" => Ok(Self::Draining),
+            _ => Err(AdminStateError(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for AdminState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Unknown admin state
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid admin state '{0}' (expected enabled, disabled or draining)")]
+pub struct AdminStateError(pub String);
@@ -0,0 +1,110 @@
+//! Affinity table module
+//!
+//! Client IP to backend map behind sticky sessions. Entries expire
+//! `sticky_ttl_millis` after the client's last connection, and go away with
+//! their backend when a migration removes it.
+use crate::prelude::*;
+
+/// Backend a client sticks to, until `expires_at_ms`
+#[derive(Debug, Clone, Copy)]
+struct Affinity {
+    backend_id: BackendId,
+    expires_at_ms: u64,
+}
+
+/// TTL-bounded map from client IP to the backend serving it
+#[derive(Debug, Default)]
+pub struct AffinityTable {
+    entries: DashMap<IpAddr, Affinity>,
+    // Earliest time the next sweep of expired entries runs
+    next_sweep_ms: AtomicU64,
+}
+
+impl AffinityTable {
+    /// Backend `client` sticks to at `now_ms`, if its entry has not expired
+    pub fn get(&self, client: IpAddr, now_ms: u64) -> Option<BackendId> {
+        let affinity = *self.entries.get(&client)?;
+        if affinity.expires_at_ms > now_ms {
+            return Some(affinity.backend_id);
+        }
+        self.entries
+            .remove_if(&client, |_, affinity| affinity.expires_at_ms <= now_ms);
+        None
+    }
+
+    /// Stick `client` to `backend_id` for `ttl_ms` from `now_ms`
+    ///
+    /// At most once per TTL, also sweeps entries of clients that stopped
+    /// connecting, so the table stays bounded by the clients seen in one TTL.
+    pub fn record(
+        &self,
+        client: IpAddr,
+        backend_id: BackendId,
+        now_ms: u64,
+        ttl_ms: u64,
+    ) {
+        self.entries.insert(
+            client,
+            Affinity {
+                backend_id,
+                expires_at_ms: now_ms.saturating_add(ttl_ms),
+            },
+        );
+        let next_sweep_ms = self.next_sweep_ms.load(Ordering::Relaxed);
+        if now_ms >= next_sweep_ms
+            && self
+                .next_sweep_ms
+                .compare_exchange(
+                    next_sweep_ms,
+                    now_ms.saturating_add(ttl_ms),
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+        {
+            self.evict_expired(now_ms);
+        }
+    }
+
+    /// Drop entries expired at `now_ms`, returning how many went
+    pub fn evict_expired(&self, now_ms: u64) -> usize {
+        let before = self.entries.len();
+        self.entries
+            .retain(|_, affinity| affinity.expires_at_ms > now_ms);
+        before.saturating_sub(self.entries.len())
+    }
+
+    /// Drop entries pointing at any of `ids`, returning how many went
+    pub fn remove_backends(&self, ids: &[BackendId]) -> usize {
+        if ids.is_empty() {
+            return 0;
+        }
+        let before = self.entries.len();
+        self.entries
+            .retain(|_, affinity| !ids.contains(&affinity.backend_id));
+        before.saturating_sub(self.entries.len())
+    }
+
+    /// Ids of the backends clients stick to, expired entries included until
+    /// swept
+    pub fn tracked_backends(&self) -> Vec<BackendId> {
+        let mut ids: Vec<BackendId> = self
+            .entries
+            .iter()
+            .map(|entry| entry.value().backend_id)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Number of entries, expired ones included until swept
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the table has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
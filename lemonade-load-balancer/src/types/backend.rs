@@ -3,47 +3,178 @@
 //! Unified backend representation with metadata and runtime state
 
 use crate::prelude::*;
+use arc_swap::ArcSwapOption;
+use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Weak;
+use std::sync::atomic::{
+    AtomicBool, AtomicU8, AtomicU16, AtomicU32, AtomicU64, AtomicUsize, Ordering,
+};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+/// Stored weight of a backend without a configured weight
+const NO_WEIGHT: u16 = u16::MAX;
+
+/// Stored resource reading of a backend that has not reported one
+const NO_RESOURCE_SAMPLE: u64 = u64::MAX;
+
+/// Share of its weight a backend gets at the start of a slow start
+pub const SLOW_START_INITIAL_FACTOR: f64 = 0.1;
 
 /// Unified backend representation with metadata and runtime state
 #[derive(Debug)]
 pub struct Backend {
-    // Immutable metadata
+    // Metadata (address can be swapped in place by a config migration)
     id: BackendId,
-    name: Option<String>,
-    address: BackendAddress,
-    weight: Option<u8>,
+    // Interned so handing the name out never copies the string
+    name: Option<Arc<str>>,
+    address: ArcSwap<BackendAddress>,
+    // Configured weight, updated in place by weight-only migrations
+    // (`NO_WEIGHT` when unset)
+    weight: AtomicU16,
+    labels: Labels,
+    // Local address connections are made from (`None` defers to the proxy
+    // default)
+    bind_address: Option<IpAddr>,
+    // DSCP value connections are marked with (`None` defers to the proxy
+    // default)
+    dscp: Option<u8>,
+    // Priority tier (lower is preferred)
+    priority: u8,
+    // Zone the backend runs in
+    zone: Option<Arc<str>>,
 
     // Mutable state (atomic for lock-free access)
     alive: AtomicBool, // Default: true (healthy until proven otherwise)
+    consecutive_failures: AtomicU32,
     last_health_check_ms: AtomicU64,
+    // Down only because `initial_state` started it so, not probed since
+    started_unhealthy: AtomicBool,
     active_connections: AtomicUsize, // Used by health service to avoid checking busy backends
+    pending_connects: AtomicUsize,
     total_requests: AtomicU64,
     total_errors: AtomicU64,
     total_latency_ms: AtomicU64,
     last_metrics_update_ms: AtomicU64,
 
+    // Connection timing aggregates (histograms or reservoirs)
+    connect_timings: LatencyRecorder,
+    ttfb_timings: LatencyRecorder,
+    duration_timings: LatencyRecorder,
+    // Bytes per second over the last metrics flush intervals
+    byte_rates: ByteRateWindow,
+
+    // Hedged connects raced against this backend as the second choice
+    hedges_started: AtomicU64,
+    hedges_won: AtomicU64,
+
+    // First requests sent with a deadline header, and those answered 504
+    deadlines_propagated: AtomicU64,
+    deadlines_exceeded: AtomicU64,
+
+    // Auto-weight state (multiplier in thousandths, 1000 = configured weight)
+    weight_multiplier_milli: AtomicU32,
+
+    // Slow start after recovery (start time and window, 0 when not ramping)
+    slow_start_from_ms: AtomicU64,
+    slow_start_millis: AtomicU64,
+
+    // Selection trace: picks that skipped the backend per exclusion reason,
+    // and how many of them the metrics service has exported
+    selection_exclusions: [AtomicU64; ExclusionReason::ALL.len()],
+    exclusions_exported: [AtomicU64; ExclusionReason::ALL.len()],
+    // Picks that chose the backend, and how many of them were exported
+    selections: AtomicU64,
+    selections_exported: AtomicU64,
+
+    // Resource usage reported by the worker (CPU in thousandths of a percent)
+    cpu_percent_milli: AtomicU64,
+    rss_bytes: AtomicU64,
+
     // Migration state
     status: AtomicU8, // Active = 0, Draining = 1
+    // Skips the backend after a run of connect failures
+    circuit: CircuitBreaker,
+    // Operator override, see AdminState::to_u8
+    admin_state: AtomicU8,
+    // Set when open connections must be force-closed (drain policy)
+    close_signal: watch::Sender<Option<CloseReason>>,
+    forced_closes: AtomicU64,
+    // Connections closed without a failure reported to health checking,
+    // the volume outlier ejection weighs failures against
+    connections_completed: AtomicU64,
+    // Times a copy loop paused reading to wait for the slower side
+    backpressure_events: AtomicU64,
+    // Set once a failure to mark a connection with the DSCP value was logged
+    dscp_warned: AtomicBool,
+    // Open connections by connection id, cancelled to evict them when the
+    // backend turns unhealthy
+    connections: DashMap<u64, CancellationToken>,
+
+    // Least-connections bucket index told about connection count changes
+    // (weak so a replaced strategy's index is not kept alive)
+    connection_index: ArcSwapOption<Weak<ConnectionIndex>>,
 }
 
 impl Backend {
     /// Create new backend (STARTS HEALTHY by default)
     pub fn new(config: BackendConfig) -> Self {
+        Self::with_latency_aggregation(config, LatencyAggregation::default())
+    }
+
+    /// Create new backend aggregating connection timings in `aggregation`
+    pub fn with_latency_aggregation(
+        config: BackendConfig,
+        aggregation: LatencyAggregation,
+    ) -> Self {
         Self {
             id: config.id,
-            name: config.name,
-            address: config.address,
-            weight: config.weight,
+            name: config.name.map(Arc::from),
+            address: ArcSwap::from_pointee(config.address),
+            weight: AtomicU16::new(config.weight.map_or(NO_WEIGHT, u16::from)),
+            labels: config.labels,
+            bind_address: config.bind_address,
+            dscp: config.dscp,
+            priority: config.priority.unwrap_or_default(),
+            zone: config.zone.map(Arc::from),
             alive: AtomicBool::new(true), // ← HEALTHY BY DEFAULT
+            consecutive_failures: AtomicU32::new(0),
+            started_unhealthy: AtomicBool::new(false),
             last_health_check_ms: AtomicU64::new(0),
             active_connections: AtomicUsize::new(0),
+            pending_connects: AtomicUsize::new(0),
             total_requests: AtomicU64::new(0),
             total_errors: AtomicU64::new(0),
             total_latency_ms: AtomicU64::new(0),
             last_metrics_update_ms: AtomicU64::new(0),
+            connect_timings: LatencyRecorder::new(aggregation),
+            ttfb_timings: LatencyRecorder::new(aggregation),
+            duration_timings: LatencyRecorder::new(aggregation),
+            byte_rates: ByteRateWindow::new(),
+            hedges_started: AtomicU64::new(0),
+            hedges_won: AtomicU64::new(0),
+            deadlines_propagated: AtomicU64::new(0),
+            deadlines_exceeded: AtomicU64::new(0),
+            weight_multiplier_milli: AtomicU32::new(1000),
+            slow_start_from_ms: AtomicU64::new(0),
+            slow_start_millis: AtomicU64::new(0),
+            selection_exclusions: Default::default(),
+            exclusions_exported: Default::default(),
+            selections: AtomicU64::new(0),
+            selections_exported: AtomicU64::new(0),
+            cpu_percent_milli: AtomicU64::new(NO_RESOURCE_SAMPLE),
+            rss_bytes: AtomicU64::new(NO_RESOURCE_SAMPLE),
             status: AtomicU8::new(0), // Active
+            circuit: CircuitBreaker::default(),
+            admin_state: AtomicU8::new(AdminState::Enabled.to_u8()),
+            close_signal: watch::Sender::new(None),
+            forced_closes: AtomicU64::new(0),
+            connections_completed: AtomicU64::new(0),
+            backpressure_events: AtomicU64::new(0),
+            dscp_warned: AtomicBool::new(false),
+            connections: DashMap::new(),
+            connection_index: ArcSwapOption::empty(),
         }
     }
 
@@ -60,26 +191,266 @@ impl Backend {
     }
 
     /// Get the backend address
-    pub fn address(&self) -> &BackendAddress {
-        &self.address
+    ///
+    /// Shared with the backend, so reading it on the connect path does not
+    /// copy the address.
+    pub fn address(&self) -> Arc<BackendAddress> {
+        self.address.load_full()
+    }
+
+    /// Point the backend at a new address
+    ///
+    /// New connections use the new address; connections already open to the
+    /// old address are left untouched. Health and metrics state is preserved.
+    pub fn set_address(&self, address: BackendAddress) {
+        self.address.store(Arc::new(address));
+    }
+
+    /// Get the local address configured for connections to this backend
+    pub fn bind_address(&self) -> Option<IpAddr> {
+        self.bind_address
+    }
+
+    /// Local address to connect from, falling back to `default` (the
+    /// proxy-wide `backend_bind_address`)
+    pub fn source_address(&self, default: Option<IpAddr>) -> Option<IpAddr> {
+        self.bind_address.or(default)
+    }
+
+    /// Get the DSCP value configured for connections to this backend
+    pub fn dscp(&self) -> Option<u8> {
+        self.dscp
+    }
+
+    /// Mark packets sent on `stream` with this backend's DSCP value, falling
+    /// back to `default` (the proxy-wide `backend_dscp`)
+    ///
+    /// A failure is logged the first time it happens for this backend; the
+    /// connection then goes ahead unmarked. Unix socket connections are left
+    /// unmarked.
+    pub fn apply_dscp(&self, stream: &BackendStream, default: Option<u8>) {
+        let (Some(dscp), Some(stream)) = (self.dscp.or(default), stream.as_tcp()) else {
+            return;
+        };
+        if let Err(e) = set_dscp(stream, dscp)
+            && !self.dscp_warned.swap(true, Ordering::Relaxed)
+        {
+            tracing::warn!(
+                "Cannot mark connections to backend {} with DSCP {}: {}",
+                self.id,
+                dscp,
+                e
+            );
+        }
+    }
+
+    /// Get the backend priority tier (lower is preferred, 0 when unset)
+    ///
+    /// Strategies only pick from the most preferred tier that has a healthy
+    /// backend, so higher tiers act as backups.
+    pub fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    /// Get the zone the backend runs in, if configured
+    ///
+    /// With `proxy.local_zone` set, picks prefer backends of that zone (see
+    /// [`RouteTable::preferred_zone`]).
+    pub fn zone(&self) -> Option<&str> {
+        self.zone.as_deref()
     }
 
     /// Get the backend weight
     pub fn weight(&self) -> Option<u8> {
+        u8::try_from(self.weight.load(Ordering::Relaxed)).ok()
+    }
+
+    /// Change the configured weight in place
+    ///
+    /// Open connections and health and metrics state are untouched; strategies
+    /// read the new weight on their next pick.
+    pub fn set_weight(&self, weight: Option<u8>) {
         self.weight
+            .store(weight.map_or(NO_WEIGHT, u16::from), Ordering::Relaxed);
+    }
+
+    /// Get the backend labels
+    pub fn labels(&self) -> &Labels {
+        &self.labels
+    }
+
+    /// Check whether the backend is selected by a label selector
+    pub fn matches(&self, selector: &LabelSelector) -> bool {
+        selector.matches(&self.labels)
+    }
+
+    /// Get the effective weight at `now_ms` in thousandths (configured weight
+    /// scaled by the auto-weight multiplier and the slow start factor)
+    ///
+    /// Kept in fixed point so the scaling still tells backends apart at the
+    /// default weight of 1. A non-zero weight never drops below 1 so a slow
+    /// backend still receives some traffic.
+    pub fn effective_weight_milli(&self, now_ms: u64) -> u32 {
+        match self.weight().unwrap_or(1) {
+            0 => 0,
+            weight => {
+                let scaled = weight as f64
+                    * 1000.0
+                    * self.weight_multiplier()
+                    * self.slow_start_factor(now_ms);
+                (scaled.round() as u32).max(1)
+            }
+        }
+    }
+
+    /// Count a pick that skipped the backend for `reason`
+    pub fn record_exclusion(&self, reason: ExclusionReason) {
+        self.selection_exclusions[reason as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the picks that skipped the backend, per reason (reasons never
+    /// counted are left out)
+    pub fn selection_exclusions(&self) -> Vec<(ExclusionReason, u64)> {
+        ExclusionReason::ALL
+            .into_iter()
+            .map(|reason| {
+                let count =
+                    self.selection_exclusions[reason as usize].load(Ordering::Relaxed);
+                (reason, count)
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+
+    /// Take the picks that skipped the backend since the last call, per reason
+    ///
+    /// Used by the metrics service to export the counters as deltas.
+    pub fn take_unexported_exclusions(&self) -> Vec<(ExclusionReason, u64)> {
+        ExclusionReason::ALL
+            .into_iter()
+            .filter_map(|reason| {
+                let total =
+                    self.selection_exclusions[reason as usize].load(Ordering::Relaxed);
+                let exported = self.exclusions_exported[reason as usize]
+                    .swap(total, Ordering::Relaxed);
+                let delta = total.saturating_sub(exported);
+                (delta > 0).then_some((reason, delta))
+            })
+            .collect()
+    }
+
+    /// Count a pick that chose the backend
+    pub fn record_selection(&self) {
+        self.selections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the picks that chose the backend
+    pub fn selections(&self) -> u64 {
+        self.selections.load(Ordering::Relaxed)
+    }
+
+    /// Take the picks that chose the backend since the last call
+    ///
+    /// Used by the metrics service to export the counter as deltas.
+    pub fn take_unexported_selections(&self) -> u64 {
+        let total = self.selections.load(Ordering::Relaxed);
+        let exported = self.selections_exported.swap(total, Ordering::Relaxed);
+        total.saturating_sub(exported)
+    }
+
+    /// Get the auto-weight multiplier (1.0 = configured weight)
+    pub fn weight_multiplier(&self) -> f64 {
+        self.weight_multiplier_milli.load(Ordering::Relaxed) as f64 / 1000.0
+    }
+
+    /// Set the auto-weight multiplier (1.0 = configured weight)
+    pub fn set_weight_multiplier(&self, multiplier: f64) {
+        let milli = (multiplier.max(0.0) * 1000.0).round() as u32;
+        self.weight_multiplier_milli.store(milli, Ordering::Relaxed);
+    }
+
+    /// Ramp the backend up over `window_ms` from `now_ms`
+    ///
+    /// Called when the backend turns healthy again; a zero window ends any
+    /// ramp in progress.
+    pub fn start_slow_start(&self, now_ms: u64, window_ms: u64) {
+        self.slow_start_from_ms.store(now_ms, Ordering::Relaxed);
+        self.slow_start_millis.store(window_ms, Ordering::Relaxed);
+    }
+
+    /// Get the share of its weight the backend gets at `now_ms`
+    ///
+    /// Grows linearly from [`SLOW_START_INITIAL_FACTOR`] to 1.0 over the slow
+    /// start window, and is 1.0 outside of one.
+    pub fn slow_start_factor(&self, now_ms: u64) -> f64 {
+        let window_ms = self.slow_start_millis.load(Ordering::Relaxed);
+        if window_ms == 0 {
+            return 1.0;
+        }
+        let elapsed_ms =
+            now_ms.saturating_sub(self.slow_start_from_ms.load(Ordering::Relaxed));
+        if elapsed_ms >= window_ms {
+            return 1.0;
+        }
+        let progress = elapsed_ms as f64 / window_ms as f64;
+        SLOW_START_INITIAL_FACTOR + (1.0 - SLOW_START_INITIAL_FACTOR) * progress
     }
 
     // Health methods
 
+    /// Start the backend healthy or not, as `state` says, until its first
+    /// probe
+    pub fn with_initial_health(self, state: InitialHealthState) -> Self {
+        self.alive.store(state.is_healthy(), Ordering::Relaxed);
+        self.started_unhealthy
+            .store(!state.is_healthy(), Ordering::Relaxed);
+        self
+    }
+
+    /// Check whether the backend is down only because it started unhealthy,
+    /// with no health report or restored state since
+    pub fn started_unhealthy(&self) -> bool {
+        self.started_unhealthy.load(Ordering::Relaxed)
+    }
+
     /// Check if backend is alive
     pub fn is_alive(&self) -> bool {
         self.alive.load(Ordering::Relaxed)
     }
 
     /// Set health status
+    ///
+    /// Every unhealthy report extends the run of consecutive failures; a
+    /// healthy one ends it.
     pub fn set_health(&self, alive: bool, now_ms: u64) {
         self.alive.store(alive, Ordering::Relaxed);
+        self.started_unhealthy.store(false, Ordering::Relaxed);
         self.last_health_check_ms.store(now_ms, Ordering::Relaxed);
+        if alive {
+            self.consecutive_failures.store(0, Ordering::Relaxed);
+        } else {
+            self.consecutive_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Restore the health state persisted by a previous run
+    pub fn restore_health(
+        &self,
+        alive: bool,
+        consecutive_failures: u32,
+        last_health_check_ms: u64,
+    ) {
+        self.alive.store(alive, Ordering::Relaxed);
+        self.started_unhealthy.store(false, Ordering::Relaxed);
+        self.consecutive_failures
+            .store(consecutive_failures, Ordering::Relaxed);
+        self.last_health_check_ms
+            .store(last_health_check_ms, Ordering::Relaxed);
+    }
+
+    /// Get the number of unhealthy reports since the last healthy one
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures.load(Ordering::Relaxed)
     }
 
     /// Get last health check timestamp
@@ -92,11 +463,31 @@ impl Backend {
     /// Increment active connection count
     pub fn increment_connection(&self) {
         self.active_connections.fetch_add(1, Ordering::Relaxed);
+        self.update_connection_index();
     }
 
     /// Decrement active connection count
     pub fn decrement_connection(&self) {
         self.active_connections.fetch_sub(1, Ordering::Relaxed);
+        self.update_connection_index();
+    }
+
+    /// Report connection count changes to `index` (replacing any previous one)
+    pub fn attach_connection_index(&self, index: &Arc<ConnectionIndex>) {
+        self.connection_index
+            .store(Some(Arc::new(Arc::downgrade(index))));
+    }
+
+    /// Tell the attached connection index, if still alive, about a change
+    fn update_connection_index(&self) {
+        if let Some(index) = self
+            .connection_index
+            .load()
+            .as_ref()
+            .and_then(|index| index.upgrade())
+        {
+            index.update(self);
+        }
     }
 
     /// Get active connection count
@@ -104,6 +495,29 @@ impl Backend {
         self.active_connections.load(Ordering::Relaxed)
     }
 
+    /// Reserve a slot for a new connect, unless `max` connects are already in
+    /// flight
+    ///
+    /// Returns whether the slot was reserved; release it with
+    /// [`Backend::release_connect`] once the connect resolves.
+    pub fn try_reserve_connect(&self, max: usize) -> bool {
+        self.pending_connects
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |pending| {
+                (pending < max).then_some(pending + 1)
+            })
+            .is_ok()
+    }
+
+    /// Release a slot reserved with [`Backend::try_reserve_connect`]
+    pub fn release_connect(&self) {
+        self.pending_connects.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    /// Get the number of connects in flight
+    pub fn pending_connects(&self) -> usize {
+        self.pending_connects.load(Ordering::Acquire)
+    }
+
     /// Check if backend has capacity for health check
     /// Health service should skip backends with high connection load
     pub fn has_capacity_for_health_check(&self, max_connections: usize) -> bool {
@@ -122,6 +536,132 @@ impl Backend {
             .fetch_add(latency_ms, Ordering::Relaxed);
     }
 
+    /// Record the timings of a closed connection
+    ///
+    /// `ttfb_micros` is `None` when the backend never sent a byte.
+    pub fn record_connection_timings(
+        &self,
+        connect_micros: u64,
+        ttfb_micros: Option<u64>,
+        total_micros: u64,
+    ) {
+        self.connect_timings.record(connect_micros);
+        if let Some(ttfb_micros) = ttfb_micros {
+            self.ttfb_timings.record(ttfb_micros);
+        }
+        self.duration_timings.record(total_micros);
+    }
+
+    /// Record the bytes a closed connection received from (`bytes_in`) and
+    /// sent to (`bytes_out`) the backend
+    pub fn record_bytes(&self, bytes_in: u64, bytes_out: u64) {
+        self.byte_rates.record(bytes_in, bytes_out);
+    }
+
+    /// Close the current byte rate interval at `now_ms`
+    ///
+    /// Called on each metrics flush.
+    pub fn flush_byte_rates(&self, now_ms: u64) {
+        self.byte_rates.flush(now_ms);
+    }
+
+    /// Get byte rates as (bytes in per second, bytes out per second) over
+    /// the last few metrics flush intervals
+    pub fn byte_rates(&self) -> (u64, u64) {
+        self.byte_rates.rates()
+    }
+
+    /// Aggregation mode of the connection timings
+    pub fn latency_aggregation(&self) -> LatencyAggregation {
+        self.connect_timings.aggregation()
+    }
+
+    /// Recompute the percentiles of sampled connection timings
+    ///
+    /// Called on each metrics flush; a no-op for histogram aggregation.
+    pub fn flush_latency_percentiles(&self) {
+        self.connect_timings.flush();
+        self.ttfb_timings.flush();
+        self.duration_timings.flush();
+    }
+
+    /// 95th percentile connect time in milliseconds (None without samples)
+    pub fn connect_p95_ms(&self) -> Option<f64> {
+        let connect = self.connect_timings.snapshot();
+        (!connect.is_empty()).then(|| connect.percentile_ms(0.95))
+    }
+
+    /// Response latency summary (time-to-first-byte when known)
+    ///
+    /// Falls back like [`BackendMetrics::response_latency_ms`] to the connect
+    /// time, then to the request latency averages, without taking a
+    /// [`Self::metrics_snapshot`]; strategies call this on every pick.
+    pub fn response_latency(&self) -> LatencySummary {
+        let ttfb = self.ttfb_timings.summary();
+        if !ttfb.is_empty() {
+            return ttfb;
+        }
+        let connect = self.connect_timings.summary();
+        if !connect.is_empty() {
+            return connect;
+        }
+        let (count, total_latency_ms) = self.request_totals();
+        let mean_ms = if count > 0 {
+            total_latency_ms as f64 / count as f64
+        } else {
+            0.0
+        };
+        LatencySummary {
+            count,
+            mean_ms,
+            // Same estimate as the request p95 of the metrics snapshot
+            p95_ms: mean_ms * 1.5,
+        }
+    }
+
+    /// Record a hedged connect that raced this backend as the hedge target
+    pub fn record_hedge(&self, won: bool) {
+        self.hedges_started.fetch_add(1, Ordering::Relaxed);
+        if won {
+            self.hedges_won.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Get hedge totals as (hedges started, hedges won)
+    pub fn hedge_totals(&self) -> (u64, u64) {
+        (
+            self.hedges_started.load(Ordering::Relaxed),
+            self.hedges_won.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Record a first request forwarded to this backend with a deadline header
+    pub fn record_deadline_propagated(&self) {
+        self.deadlines_propagated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a request with a propagated deadline this backend answered with
+    /// `504 Gateway Timeout`
+    pub fn record_deadline_exceeded(&self) {
+        self.deadlines_exceeded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get deadline totals as (deadlines propagated, deadlines exceeded)
+    pub fn deadline_totals(&self) -> (u64, u64) {
+        (
+            self.deadlines_propagated.load(Ordering::Relaxed),
+            self.deadlines_exceeded.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Get cumulative request totals as (total requests, total latency in ms)
+    pub fn request_totals(&self) -> (u64, u64) {
+        (
+            self.total_requests.load(Ordering::Relaxed),
+            self.total_latency_ms.load(Ordering::Relaxed),
+        )
+    }
+
     /// Get metrics snapshot
     pub fn metrics_snapshot(&self) -> BackendMetrics {
         let total_requests = self.total_requests.load(Ordering::Relaxed);
@@ -145,12 +685,23 @@ impl Backend {
 
         // Calculate p95 latency (simplified - use average * 1.5 for now)
         let p95_latency_ms = avg_latency_ms * 1.5;
+        let (bytes_in_rate, bytes_out_rate) = self.byte_rates();
 
         BackendMetrics {
             avg_latency_ms,
             p95_latency_ms,
             error_rate,
             last_updated_ms,
+            weight_multiplier: self.weight_multiplier(),
+            cpu_percent: self.cpu_percent(),
+            rss_bytes: self.rss_bytes(),
+            timings: ConnectionTimings {
+                connect: self.connect_timings.snapshot(),
+                ttfb: self.ttfb_timings.snapshot(),
+                total: self.duration_timings.snapshot(),
+            },
+            bytes_in_rate,
+            bytes_out_rate,
         }
     }
 
@@ -159,6 +710,36 @@ impl Backend {
         self.last_metrics_update_ms.store(now_ms, Ordering::Relaxed);
     }
 
+    /// CPU usage last reported by the worker, in percent of one core
+    pub fn cpu_percent(&self) -> Option<f64> {
+        match self.cpu_percent_milli.load(Ordering::Relaxed) {
+            NO_RESOURCE_SAMPLE => None,
+            milli => Some(milli as f64 / 1000.0),
+        }
+    }
+
+    /// Resident memory last reported by the worker, in bytes
+    pub fn rss_bytes(&self) -> Option<u64> {
+        match self.rss_bytes.load(Ordering::Relaxed) {
+            NO_RESOURCE_SAMPLE => None,
+            bytes => Some(bytes),
+        }
+    }
+
+    /// Record the resource usage reported by the worker (None clears a reading)
+    pub fn set_resource_usage(&self, cpu_percent: Option<f64>, rss_bytes: Option<u64>) {
+        let cpu_milli = cpu_percent.map_or(NO_RESOURCE_SAMPLE, |percent| {
+            ((percent.max(0.0) * 1000.0).round() as u64).min(NO_RESOURCE_SAMPLE - 1)
+        });
+        self.cpu_percent_milli.store(cpu_milli, Ordering::Relaxed);
+        self.rss_bytes.store(
+            rss_bytes.map_or(NO_RESOURCE_SAMPLE, |bytes| {
+                bytes.min(NO_RESOURCE_SAMPLE - 1)
+            }),
+            Ordering::Relaxed,
+        );
+    }
+
     // Migration methods
 
     /// Mark backend as draining
@@ -177,9 +758,120 @@ impl Backend {
     }
 
     /// Check if backend can accept new connections
-    /// Returns true if backend is alive and not draining
+    /// Returns true if backend is alive, not draining and enabled by its
+    /// admin state
     pub fn can_accept_new_connections(&self) -> bool {
-        self.is_alive() && self.is_active()
+        self.is_alive()
+            && self.is_active()
+            && self.admin_state().accepts_new_connections()
+    }
+
+    /// Check if a client stuck to the backend can open another connection
+    ///
+    /// Like [`Backend::can_accept_new_connections`], except that a backend
+    /// drained by its admin state still takes its sticky clients.
+    pub fn can_accept_sticky_connections(&self) -> bool {
+        self.is_alive() && self.is_active() && self.admin_state().accepts_sticky_clients()
+    }
+
+    /// Get the circuit breaker fed by the proxy's connects to the backend
+    pub fn circuit(&self) -> &CircuitBreaker {
+        &self.circuit
+    }
+
+    // Admin methods
+
+    /// Get the operator override of the backend's place in rotation
+    pub fn admin_state(&self) -> AdminState {
+        AdminState::from_u8(self.admin_state.load(Ordering::Relaxed))
+    }
+
+    /// Set the operator override, returning the previous one
+    pub fn set_admin_state(&self, state: AdminState) -> AdminState {
+        AdminState::from_u8(self.admin_state.swap(state.to_u8(), Ordering::Relaxed))
+    }
+
+    /// Force-close every connection open to the backend, now and later
+    ///
+    /// The first reason given sticks; connections proxied afterwards are
+    /// closed as soon as they are established.
+    pub fn close_connections(&self, reason: CloseReason) {
+        self.close_signal.send_if_modified(|current| {
+            if current.is_some() {
+                return false;
+            }
+            *current = Some(reason);
+            true
+        });
+    }
+
+    /// Watch for a forced close of the backend's connections
+    pub fn close_requested(&self) -> watch::Receiver<Option<CloseReason>> {
+        self.close_signal.subscribe()
+    }
+
+    /// Register an open connection, returning the token that evicts it
+    pub fn register_connection(&self, connection_id: u64) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.connections.insert(connection_id, token.clone());
+        token
+    }
+
+    /// Forget a closed connection
+    pub fn unregister_connection(&self, connection_id: u64) {
+        self.connections.remove(&connection_id);
+    }
+
+    /// Cancel every registered connection, returning how many were evicted
+    ///
+    /// Unlike [`Backend::close_connections`] this only affects connections
+    /// open now; connections proxied afterwards are left alone.
+    pub fn evict_connections(&self) -> usize {
+        let mut evicted = 0;
+        for entry in self.connections.iter() {
+            if !entry.value().is_cancelled() {
+                entry.value().cancel();
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+
+    /// Record a connection the load balancer closed itself
+    pub fn record_forced_close(&self) {
+        self.forced_closes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the number of connections force-closed by a drain policy
+    pub fn forced_closes(&self) -> u64 {
+        self.forced_closes.load(Ordering::Relaxed)
+    }
+
+    /// Record a connection that closed without a failure being reported to
+    /// health checking
+    pub fn record_connection_completed(&self) {
+        self.connections_completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the number of connections closed without a failure reported to
+    /// health checking
+    pub fn connections_completed(&self) -> u64 {
+        self.connections_completed.load(Ordering::Relaxed)
+    }
+
+    /// Record the backpressure events of a finished connection
+    pub fn record_backpressure(&self, events: u64) {
+        if events > 0 {
+            self.backpressure_events
+                .fetch_add(events, Ordering::Relaxed);
+        }
+    }
+
+    /// Get the number of times proxying to or from this backend paused
+    /// reading until the slower side drained (see
+    /// [`ProxyConfig::max_buffered_bytes`])
+    pub fn backpressure_events(&self) -> u64 {
+        self.backpressure_events.load(Ordering::Relaxed)
     }
 }
 
@@ -192,17 +884,21 @@ impl Backend {
 /// # Examples
 ///
 /// ```
-/// use lemonade_load_balancer::types::backend::BackendConfig;
-/// use std::net::SocketAddr;
+/// use lemonade_load_balancer::prelude::{BackendAddress, BackendConfig};
 ///
 /// let config = BackendConfig {
 ///     id: 0,
 ///     name: Some("backend-1".to_string()),
-///     address: "127.0.0.1:8080".parse().unwrap(),
+///     address: BackendAddress::parse("127.0.0.1:8080").unwrap(),
 ///     weight: Some(10),
+///     labels: Default::default(),
+///     bind_address: None,
+///     dscp: None,
+///     priority: None,
+///     zone: None,
 /// };
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BackendConfig {
     /// Unique backend identifier (0-255)
     pub id: BackendId,
@@ -212,6 +908,25 @@ pub struct BackendConfig {
     pub address: BackendAddress,
     /// Optional weight for weighted load balancing strategies
     pub weight: Option<u8>,
+    /// Labels used by label selectors (e.g. SNI routing)
+    #[serde(default, skip_serializing_if = "Labels::is_empty")]
+    pub labels: Labels,
+    /// Local address backend connections are made from (overrides
+    /// `proxy.backend_bind_address`; the OS picks when neither is set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<IpAddr>,
+    /// DSCP value (0-63) packets to the backend are marked with (overrides
+    /// `proxy.backend_dscp`; unmarked when neither is set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dscp: Option<u8>,
+    /// Priority tier, lower is preferred (0 when unset): backends of a tier
+    /// only get traffic while no backend of a preferred tier is healthy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// Zone (e.g. availability zone) the backend runs in: picks prefer
+    /// backends in `proxy.local_zone`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
 }
 
 impl From<BackendMeta> for BackendConfig {
@@ -221,6 +936,11 @@ impl From<BackendMeta> for BackendConfig {
             name: meta.name().cloned(),
             address: meta.address().clone(),
             weight: meta.weight(),
+            labels: Labels::new(),
+            bind_address: None,
+            dscp: None,
+            priority: None,
+            zone: meta.zone().map(String::from),
         }
     }
 }
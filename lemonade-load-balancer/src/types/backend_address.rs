@@ -1,14 +1,18 @@
 //! Backend address module
 //!
-pub use error::BackendAddressError;
+use super::{BackendStream, UnixAddress};
+pub use error::{BackendAddressError, BackendConnectError};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use tokio::net::{TcpSocket, TcpStream};
 
 /// Backend address struct
 ///
 /// Supports both IP addresses and hostnames. Hostnames are resolved lazily
 /// at connection time, not during config parsing, allowing Docker service names
-/// to be used even if DNS isn't ready when the config is loaded.
+/// to be used even if DNS isn't ready when the config is loaded. Backends on
+/// the same host can also be reached over a Unix socket (see [`UnixAddress`]).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BackendAddress(String);
 
@@ -38,6 +42,12 @@ impl BackendAddress {
     /// Validates the format (must contain a colon for port) but doesn't
     /// resolve hostnames. Resolution happens lazily when ToSocketAddrs is used.
     pub fn parse(addr: &str) -> Result<Self, BackendAddressError> {
+        // Unix socket addresses are kept as written
+        if UnixAddress::is_unix(addr) {
+            UnixAddress::parse(addr)?;
+            return Ok(BackendAddress(addr.to_string()));
+        }
+
         // Basic format validation: must contain ':' for port
         if !addr.contains(':') {
             return Err(BackendAddressError::InvalidFormat(addr.to_string()));
@@ -60,6 +70,82 @@ impl BackendAddress {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// The Unix socket address, if the backend is reached over one
+    pub fn unix(&self) -> Option<UnixAddress> {
+        UnixAddress::parse(&self.0).ok()
+    }
+
+    /// Open a connection to the address, from `source` when set
+    ///
+    /// Without a source the OS picks the local address. With one, the
+    /// resolved addresses of the source's family are tried in turn from a
+    /// socket bound to `source` (on an ephemeral port). Unix socket
+    /// addresses ignore the source.
+    pub async fn connect_from(
+        &self,
+        source: Option<IpAddr>,
+    ) -> Result<BackendStream, BackendConnectError> {
+        if let Some(unix) = self.unix() {
+            return unix.connect().await.map_err(BackendConnectError::Connect);
+        }
+        let Some(source) = source else {
+            return TcpStream::connect(self.as_str())
+                .await
+                .map(BackendStream::Tcp)
+                .map_err(BackendConnectError::Connect);
+        };
+        let mut last_error = None;
+        for address in tokio::net::lookup_host(self.as_str())
+            .await
+            .map_err(BackendConnectError::Connect)?
+            .filter(|address| address.is_ipv4() == source.is_ipv4())
+        {
+            let socket = if source.is_ipv4() {
+                TcpSocket::new_v4()
+            } else {
+                TcpSocket::new_v6()
+            }
+            .map_err(|error| BackendConnectError::Bind {
+                address: source,
+                error,
+            })?;
+            socket.bind(SocketAddr::new(source, 0)).map_err(|error| {
+                BackendConnectError::Bind {
+                    address: source,
+                    error,
+                }
+            })?;
+            match socket.connect(address).await {
+                Ok(stream) => return Ok(BackendStream::Tcp(stream)),
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(match last_error {
+            Some(error) => BackendConnectError::Connect(error),
+            None => BackendConnectError::Bind {
+                address: source,
+                error: io::Error::new(
+                    io::ErrorKind::AddrNotAvailable,
+                    format!("{} has no address in the family of the source", self),
+                ),
+            },
+        })
+    }
+}
+
+/// Mark packets sent on `stream` with a DSCP value (0-63)
+///
+/// The value fills the upper six bits of the IPv4 TOS byte or the IPv6
+/// traffic class, depending on the family of the socket.
+pub fn set_dscp(stream: &TcpStream, dscp: u8) -> io::Result<()> {
+    let socket = socket2::SockRef::from(stream);
+    let tos = u32::from(dscp) << 2;
+    if stream.local_addr()?.is_ipv4() {
+        socket.set_tos_v4(tos)
+    } else {
+        socket.set_tclass_v6(tos)
+    }
 }
 
 impl From<SocketAddr> for BackendAddress {
@@ -105,4 +191,21 @@ mod error {
         #[error("backend address resolution failed: {0}")]
         ResolutionFailed(#[from] std::io::Error),
     }
+
+    /// Backend connect error enum
+    #[derive(Debug, thiserror::Error)]
+    pub enum BackendConnectError {
+        /// The socket could not be bound to the source address
+        #[error("cannot bind to source address {address}: {error}")]
+        Bind {
+            /// Source address the socket was bound to
+            address: std::net::IpAddr,
+            /// Why binding failed
+            #[source]
+            error: std::io::Error,
+        },
+        /// The backend could not be reached
+        #[error("{0}")]
+        Connect(std::io::Error),
+    }
 }
@@ -4,7 +4,10 @@ use crate::prelude::*;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// Backend meta struct
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// Identity is keyed by id: two metas with the same id are equal (and hash
+/// the same) regardless of name, address, weight or zone.
+#[derive(Debug, Clone)]
 pub struct BackendMeta {
     /// Unique identifier for the backend
     id: BackendId,
@@ -14,6 +17,22 @@ pub struct BackendMeta {
     address: BackendAddress,
     /// Weight of the backend
     weight: Option<u8>,
+    /// Zone the backend runs in
+    zone: Option<String>,
+}
+
+impl PartialEq for BackendMeta {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for BackendMeta {}
+
+impl std::hash::Hash for BackendMeta {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -22,6 +41,8 @@ struct BackendMetaSerde {
     name: Option<String>,
     address: BackendAddress,
     weight: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    zone: Option<String>,
 }
 
 impl Serialize for BackendMeta {
@@ -34,6 +55,7 @@ impl Serialize for BackendMeta {
             name: self.name.clone(),
             address: self.address.clone(),
             weight: self.weight,
+            zone: self.zone.clone(),
         }
         .serialize(serializer)
     }
@@ -50,6 +72,7 @@ impl<'de> Deserialize<'de> for BackendMeta {
             name: serde.name,
             address: serde.address,
             weight: serde.weight,
+            zone: serde.zone,
         })
     }
 }
@@ -67,9 +90,16 @@ impl BackendMeta {
             name: name.map(|n| n.into()),
             address: address.into(),
             weight: weight.map(|w| w.into()),
+            zone: None,
         }
     }
 
+    /// Set the zone the backend runs in
+    pub fn with_zone(mut self, zone: impl Into<String>) -> Self {
+        self.zone = Some(zone.into());
+        self
+    }
+
     /// Get the backend id
     pub fn id(&self) -> &BackendId {
         &self.id
@@ -89,4 +119,9 @@ impl BackendMeta {
     pub fn weight(&self) -> Option<u8> {
         self.weight
     }
+
+    /// Get the zone the backend runs in
+    pub fn zone(&self) -> Option<&str> {
+        self.zone.as_deref()
+    }
 }
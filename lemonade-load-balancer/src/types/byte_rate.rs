@@ -0,0 +1,129 @@
+//! Byte rate module
+//!
+//! Bytes-per-second a backend moved over its last few metrics flush
+//! intervals. Connections add their bytes when they close; each flush of the
+//! metrics service closes the current interval and recomputes the rates
+//! over the sliding window of [`BYTE_RATE_INTERVALS`] intervals.
+use crate::prelude::*;
+
+/// Flush intervals the byte rates are computed over
+pub const BYTE_RATE_INTERVALS: usize = 6;
+
+/// Stored flush time of a window never flushed
+const NEVER_FLUSHED: u64 = u64::MAX;
+
+/// Bytes moved during one flush interval
+#[derive(Debug, Default)]
+struct ByteRateInterval {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    elapsed_ms: AtomicU64,
+}
+
+/// Sliding window of the bytes a backend moved
+///
+/// Recording is lock-free and may happen from any task; flushing is done by
+/// the metrics service alone.
+#[derive(Debug)]
+pub struct ByteRateWindow {
+    /// Bytes received from the backend since the last flush
+    pending_in: AtomicU64,
+    /// Bytes sent to the backend since the last flush
+    pending_out: AtomicU64,
+    /// Last closed intervals, oldest overwritten first
+    intervals: [ByteRateInterval; BYTE_RATE_INTERVALS],
+    /// Intervals closed so far
+    closed: AtomicUsize,
+    /// Time of the last flush, in milliseconds
+    last_flush_ms: AtomicU64,
+    /// Rates over the window, in bytes per second
+    in_rate: AtomicU64,
+    out_rate: AtomicU64,
+}
+
+impl Default for ByteRateWindow {
+    fn default() -> Self {
+        Self {
+            pending_in: AtomicU64::new(0),
+            pending_out: AtomicU64::new(0),
+            intervals: Default::default(),
+            closed: AtomicUsize::new(0),
+            last_flush_ms: AtomicU64::new(NEVER_FLUSHED),
+            in_rate: AtomicU64::new(0),
+            out_rate: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ByteRateWindow {
+    /// Create an empty window
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the bytes of a closed connection to the current interval
+    pub fn record(&self, bytes_in: u64, bytes_out: u64) {
+        self.pending_in.fetch_add(bytes_in, Ordering::Relaxed);
+        self.pending_out.fetch_add(bytes_out, Ordering::Relaxed);
+    }
+
+    /// Close the current interval at `now_ms` and recompute the rates
+    ///
+    /// The first flush only starts the window, so the bytes recorded before
+    /// it count toward the first interval. A flush in the same millisecond
+    /// as the previous one is a no-op.
+    pub fn flush(&self, now_ms: u64) {
+        let last_flush_ms = self.last_flush_ms.load(Ordering::Acquire);
+        if last_flush_ms == NEVER_FLUSHED {
+            self.last_flush_ms.store(now_ms, Ordering::Release);
+            return;
+        }
+        let elapsed_ms = now_ms.saturating_sub(last_flush_ms);
+        if elapsed_ms == 0 {
+            return;
+        }
+        self.last_flush_ms.store(now_ms, Ordering::Release);
+
+        let closed = self.closed.fetch_add(1, Ordering::AcqRel);
+        let interval = &self.intervals[closed % BYTE_RATE_INTERVALS];
+        interval.bytes_in.store(
+            self.pending_in.swap(0, Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+        interval.bytes_out.store(
+            self.pending_out.swap(0, Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+        interval.elapsed_ms.store(elapsed_ms, Ordering::Relaxed);
+
+        let (bytes_in, bytes_out, window_ms) = self.intervals.iter().fold(
+            (0u64, 0u64, 0u64),
+            |(bytes_in, bytes_out, window_ms), interval| {
+                (
+                    bytes_in.saturating_add(interval.bytes_in.load(Ordering::Relaxed)),
+                    bytes_out.saturating_add(interval.bytes_out.load(Ordering::Relaxed)),
+                    window_ms + interval.elapsed_ms.load(Ordering::Relaxed),
+                )
+            },
+        );
+        self.in_rate
+            .store(per_second(bytes_in, window_ms), Ordering::Relaxed);
+        self.out_rate
+            .store(per_second(bytes_out, window_ms), Ordering::Relaxed);
+    }
+
+    /// Rates over the window as (bytes in per second, bytes out per second)
+    ///
+    /// Both are 0 until the window has closed an interval.
+    pub fn rates(&self) -> (u64, u64) {
+        (
+            self.in_rate.load(Ordering::Relaxed),
+            self.out_rate.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// `bytes` moved over `window_ms` milliseconds, in bytes per second
+fn per_second(bytes: u64, window_ms: u64) -> u64 {
+    (bytes as u128 * 1000 / window_ms.max(1) as u128) as u64
+}
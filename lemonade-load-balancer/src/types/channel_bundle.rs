@@ -15,7 +15,8 @@ pub struct ChannelBundle {
     health_tx: mpsc::Sender<HealthEvent>,
     health_rx: Mutex<Option<mpsc::Receiver<HealthEvent>>>,
 
-    // Backend failure events from proxy to health service (mpsc - single processor)
+    // Backend failure events from proxy to health service (mpsc - the health
+    // service is the only consumer)
     backend_failure_tx: mpsc::Sender<BackendFailureEvent>,
     backend_failure_rx: Mutex<Option<mpsc::Receiver<BackendFailureEvent>>>,
 
@@ -29,6 +30,19 @@ pub struct ChannelBundle {
 
     // Shutdown signal (broadcast - all services listen)
     shutdown_tx: broadcast::Sender<()>,
+
+    // Drain progress during shutdown (broadcast - multiple listeners)
+    drain_tx: broadcast::Sender<DrainProgress>,
+
+    // Events discarded as too old by their consumer
+    metrics_events_expired: AtomicU64,
+    health_events_expired: AtomicU64,
+
+    // Outcomes of the backend failure events the health service consumed
+    backend_failures_received: AtomicU64,
+    backend_failures_acted_upon: AtomicU64,
+    backend_failures_already_unhealthy: AtomicU64,
+    backend_failures_unknown_backend: AtomicU64,
 }
 
 impl ChannelBundle {
@@ -45,6 +59,7 @@ impl ChannelBundle {
         let (metrics_tx, metrics_rx) = mpsc::channel(metrics_cap);
         let (connection_tx, connection_rx) = mpsc::channel(connection_cap);
         let (shutdown_tx, _) = broadcast::channel(1);
+        let (drain_tx, _) = broadcast::channel(16);
 
         Self {
             config_tx,
@@ -57,6 +72,13 @@ impl ChannelBundle {
             connection_tx,
             connection_rx: Mutex::new(Some(connection_rx)),
             shutdown_tx,
+            drain_tx,
+            metrics_events_expired: AtomicU64::new(0),
+            health_events_expired: AtomicU64::new(0),
+            backend_failures_received: AtomicU64::new(0),
+            backend_failures_acted_upon: AtomicU64::new(0),
+            backend_failures_already_unhealthy: AtomicU64::new(0),
+            backend_failures_unknown_backend: AtomicU64::new(0),
         }
     }
 
@@ -131,4 +153,81 @@ impl ChannelBundle {
     pub fn shutdown_rx(&self) -> broadcast::Receiver<()> {
         self.shutdown_tx.subscribe()
     }
+
+    // Drain progress channel accessors
+
+    /// Get drain progress sender (broadcast - can be cloned)
+    pub fn drain_tx(&self) -> broadcast::Sender<DrainProgress> {
+        self.drain_tx.clone()
+    }
+
+    /// Get drain progress receiver (broadcast - can have multiple subscribers)
+    pub fn drain_rx(&self) -> broadcast::Receiver<DrainProgress> {
+        self.drain_tx.subscribe()
+    }
+
+    // Expired event counters
+
+    /// Count a metrics event discarded for exceeding `metrics.max_event_age_millis`
+    pub fn record_metrics_event_expired(&self) {
+        self.metrics_events_expired.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Metrics events discarded as expired so far
+    pub fn metrics_events_expired(&self) -> u64 {
+        self.metrics_events_expired.load(Ordering::Relaxed)
+    }
+
+    /// Count a backend failure event discarded for exceeding
+    /// `health.max_event_age_millis`
+    pub fn record_health_event_expired(&self) {
+        self.health_events_expired.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Backend failure events discarded as expired so far
+    pub fn health_events_expired(&self) -> u64 {
+        self.health_events_expired.load(Ordering::Relaxed)
+    }
+
+    // Backend failure outcome counters
+
+    /// Count a backend failure event taken off the channel
+    pub fn record_backend_failure_received(&self) {
+        self.backend_failures_received
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a backend failure event that marked its backend unhealthy
+    pub fn record_backend_failure_acted_upon(&self) {
+        self.backend_failures_acted_upon
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a backend failure event ignored for `reason`
+    pub fn record_backend_failure_ignored(&self, reason: BackendFailureIgnored) {
+        let counter = match reason {
+            BackendFailureIgnored::AlreadyUnhealthy => {
+                &self.backend_failures_already_unhealthy
+            }
+            BackendFailureIgnored::UnknownBackend => {
+                &self.backend_failures_unknown_backend
+            }
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Outcomes of the backend failure events consumed so far
+    pub fn backend_failure_stats(&self) -> BackendFailureStats {
+        BackendFailureStats {
+            received: self.backend_failures_received.load(Ordering::Relaxed),
+            acted_upon: self.backend_failures_acted_upon.load(Ordering::Relaxed),
+            ignored_already_unhealthy: self
+                .backend_failures_already_unhealthy
+                .load(Ordering::Relaxed),
+            ignored_unknown_backend: self
+                .backend_failures_unknown_backend
+                .load(Ordering::Relaxed),
+            expired: self.health_events_expired(),
+        }
+    }
 }
@@ -0,0 +1,195 @@
+//! Circuit breaker module
+//!
+//! Per-backend circuit breaker fed by the proxy's connect results. After
+//! `failure_threshold` connect failures in a row the circuit opens and the
+//! backend is skipped for `open_duration_millis`; then a single trial
+//! connect either closes the circuit or opens it again.
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, AtomicU32, AtomicU64, Ordering};
+
+/// State of a backend's circuit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Connects go through
+    #[default]
+    Closed,
+    /// Connects are skipped until the open duration is over
+    Open,
+    /// One trial connect is in flight; the others are skipped until it
+    /// resolves
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// State name, as used in `GET /status`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+
+    /// Encoding stored in the breaker's atomic
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Closed => 0,
+            Self::Open => 1,
+            Self::HalfOpen => 2,
+        }
+    }
+
+    /// Decode the breaker's atomic, see [`CircuitState::to_u8`]
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Open,
+            2 => Self::HalfOpen,
+            _ => Self::Closed,
+        }
+    }
+}
+
+impl std::fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Circuit breaker of one backend
+///
+/// Lock-free, so strategies and the proxy can consult it on every pick. A
+/// trial that never reports back (its connect lost a hedge race) is given
+/// up after another open duration, and the next connect becomes the trial.
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    /// Current state, see [`CircuitState::to_u8`]
+    state: AtomicU8,
+    /// Connect failures in a row
+    failures: AtomicU32,
+    /// When the circuit last opened or its trial started, in milliseconds
+    since_ms: AtomicU64,
+}
+
+impl CircuitBreaker {
+    /// Get the current state
+    pub fn state(&self) -> CircuitState {
+        CircuitState::from_u8(self.state.load(Ordering::Acquire))
+    }
+
+    /// Get the connect failures in a row
+    pub fn failures(&self) -> u32 {
+        self.failures.load(Ordering::Relaxed)
+    }
+
+    /// Get when the circuit last opened or its trial started, in
+    /// milliseconds
+    pub fn since_ms(&self) -> u64 {
+        self.since_ms.load(Ordering::Acquire)
+    }
+
+    /// Restore the circuit persisted by a previous run
+    ///
+    /// A trial in flight then never reports back, so a half-open circuit is
+    /// restored open: its next trial starts once the open duration from
+    /// `since_ms` is over.
+    pub fn restore(&self, state: CircuitState, failures: u32, since_ms: u64) {
+        let state = match state {
+            CircuitState::HalfOpen => CircuitState::Open,
+            state => state,
+        };
+        self.failures.store(failures, Ordering::Relaxed);
+        self.since_ms.store(since_ms, Ordering::Release);
+        self.state.store(state.to_u8(), Ordering::Release);
+    }
+
+    /// Check whether a connect may be attempted at `now_ms`, without taking
+    /// the trial of an open circuit
+    pub fn allows(&self, now_ms: u64, config: &CircuitBreakerConfig) -> bool {
+        !config.enabled
+            || self.state() == CircuitState::Closed
+            || now_ms
+                >= self
+                    .since_ms
+                    .load(Ordering::Acquire)
+                    .saturating_add(config.open_duration_millis)
+    }
+
+    /// Take the right to connect at `now_ms`
+    ///
+    /// Always granted while the circuit is closed. Once an open circuit's
+    /// duration is over, only the first caller is granted it, as the
+    /// circuit's half-open trial.
+    pub fn try_acquire(&self, now_ms: u64, config: &CircuitBreakerConfig) -> bool {
+        if !config.enabled {
+            return true;
+        }
+        let state = self.state();
+        if state == CircuitState::Closed {
+            return true;
+        }
+        let since = self.since_ms.load(Ordering::Acquire);
+        if now_ms < since.saturating_add(config.open_duration_millis) {
+            return false;
+        }
+        if self
+            .since_ms
+            .compare_exchange(since, now_ms, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return false;
+        }
+        // Left alone if a success closed the circuit meanwhile
+        let _ = self.state.compare_exchange(
+            state.to_u8(),
+            CircuitState::HalfOpen.to_u8(),
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+        true
+    }
+
+    /// Record a successful connect, closing the circuit
+    ///
+    /// Returns `true` if the circuit was not closed.
+    pub fn record_success(&self) -> bool {
+        self.failures.store(0, Ordering::Relaxed);
+        self.state
+            .swap(CircuitState::Closed.to_u8(), Ordering::AcqRel)
+            != CircuitState::Closed.to_u8()
+    }
+
+    /// Record a failed connect at `now_ms`
+    ///
+    /// Opens a closed circuit at `failure_threshold` failures in a row, and
+    /// a half-open one on its trial's failure. Returns `true` if this opened
+    /// the circuit.
+    pub fn record_failure(&self, now_ms: u64, config: &CircuitBreakerConfig) -> bool {
+        if !config.enabled {
+            return false;
+        }
+        let failures = self
+            .failures
+            .fetch_add(1, Ordering::Relaxed)
+            .saturating_add(1);
+        let from = match self.state() {
+            CircuitState::Closed if failures >= config.failure_threshold => {
+                CircuitState::Closed
+            }
+            CircuitState::HalfOpen => CircuitState::HalfOpen,
+            // Below the threshold, or a connect started before the circuit
+            // opened
+            _ => return false,
+        };
+        self.since_ms.store(now_ms, Ordering::Release);
+        self.state
+            .compare_exchange(
+                from.to_u8(),
+                CircuitState::Open.to_u8(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+    }
+}
@@ -0,0 +1,127 @@
+//! Clock module
+//!
+//! Time source injected into the context so the drain loops, health checks,
+//! metrics flushes and adaptive score cache can run on virtual time in tests
+use crate::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of wall-clock time and sleeps
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// Current time in milliseconds since the Unix epoch
+    fn now_millis(&self) -> u64;
+
+    /// Current time in microseconds since the Unix epoch
+    fn now_micros(&self) -> u64 {
+        self.now_millis().saturating_mul(1000)
+    }
+
+    /// Whether an event stamped `at_micros` is older than `max_age_millis`
+    ///
+    /// A zero max age never expires anything.
+    fn is_expired(&self, at_micros: u64, max_age_millis: u64) -> bool {
+        max_age_millis > 0
+            && self.now_micros().saturating_sub(at_micros)
+                > max_age_millis.saturating_mul(1000)
+    }
+
+    /// Sleep for the given duration
+    async fn sleep(&self, duration: Duration);
+
+    /// Sleep until `deadline_ms`, in milliseconds since the Unix epoch
+    async fn sleep_until(&self, deadline_ms: u64) {
+        self.sleep(Duration::from_millis(
+            deadline_ms.saturating_sub(self.now_millis()),
+        ))
+        .await;
+    }
+}
+
+/// Production clock backed by the system time and the tokio timer
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
+
+    fn now_micros(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// Manually advanced clock for deterministic tests
+///
+/// Time only moves when [`MockClock::advance`] or [`MockClock::set`] is
+/// called; sleepers wake once their deadline has been reached.
+#[cfg(feature = "test-util")]
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now_ms: AtomicU64,
+    notify: Notify,
+}
+
+#[cfg(feature = "test-util")]
+impl MockClock {
+    /// Create a mock clock starting at the given time
+    pub fn new(start_ms: u64) -> Self {
+        Self {
+            now_ms: AtomicU64::new(start_ms),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Move the clock forward and wake due sleepers
+    pub fn advance(&self, duration: Duration) {
+        self.now_ms
+            .fetch_add(duration.as_millis() as u64, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    /// Set the clock to an absolute time and wake due sleepers
+    pub fn set(&self, now_ms: u64) {
+        self.now_ms.store(now_ms, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+}
+
+#[async_trait]
+#[cfg(feature = "test-util")]
+impl Clock for MockClock {
+    fn now_millis(&self) -> u64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        self.sleep_until(
+            self.now_millis()
+                .saturating_add(duration.as_millis() as u64),
+        )
+        .await;
+    }
+
+    async fn sleep_until(&self, deadline_ms: u64) {
+        loop {
+            // Register before checking so an advance in between is not missed
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.now_millis() >= deadline_ms {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
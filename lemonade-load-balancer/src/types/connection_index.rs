@@ -0,0 +1,96 @@
+//! Connection index module
+//!
+//! Buckets backends by active connection count so the least loaded backend
+//! is found from the lowest buckets instead of by scanning every backend.
+//! Backends attached to an index report each connection count change to it;
+//! the count is re-read under the index lock, so concurrent changes can only
+//! make a placement briefly stale, never inconsistent.
+use crate::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Mutex;
+
+/// Backend ids bucketed by active connection count
+#[derive(Debug, Default)]
+pub struct ConnectionIndex {
+    inner: Mutex<Buckets>,
+}
+
+/// Buckets and the bucket each backend is in
+#[derive(Debug, Default)]
+struct Buckets {
+    by_count: BTreeMap<usize, BTreeSet<BackendId>>,
+    counts: HashMap<BackendId, usize>,
+}
+
+impl ConnectionIndex {
+    /// Build an index over `backends` and attach it to each of them
+    pub fn build(backends: &[Arc<Backend>]) -> Arc<Self> {
+        let index = Arc::new(Self::default());
+        for backend in backends {
+            // Attach before placing: a change racing with the placement is
+            // then either seen by it or reported to the index afterwards
+            backend.attach_connection_index(&index);
+            index.update(backend);
+        }
+        index
+    }
+
+    /// Move `backend` to the bucket of its current connection count
+    pub fn update(&self, backend: &Backend) {
+        let mut buckets = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let count = backend.active_connections();
+        let id = backend.id();
+        if let Some(previous) = buckets.counts.insert(id, count) {
+            if previous == count {
+                return;
+            }
+            buckets.remove_from(previous, id);
+        }
+        buckets.by_count.entry(count).or_default().insert(id);
+    }
+
+    /// First backend, by ascending connection count then id, that `accept`s
+    ///
+    /// Returns the backend id and the connection count it is indexed under.
+    pub fn find(
+        &self,
+        mut accept: impl FnMut(BackendId) -> bool,
+    ) -> Option<(BackendId, usize)> {
+        let buckets = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        buckets.by_count.iter().find_map(|(count, ids)| {
+            ids.iter()
+                .copied()
+                .find(|id| accept(*id))
+                .map(|id| (id, *count))
+        })
+    }
+
+    /// Connection count a backend is indexed under
+    pub fn count(&self, id: BackendId) -> Option<usize> {
+        let buckets = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        buckets.counts.get(&id).copied()
+    }
+
+    /// Number of indexed backends
+    pub fn len(&self) -> usize {
+        let buckets = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        buckets.counts.len()
+    }
+
+    /// Check if no backend is indexed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Buckets {
+    /// Remove `id` from a bucket, dropping the bucket once empty
+    fn remove_from(&mut self, count: usize, id: BackendId) {
+        if let Some(ids) = self.by_count.get_mut(&count) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.by_count.remove(&count);
+            }
+        }
+    }
+}
@@ -3,25 +3,73 @@
 //! Shared context for load balancer services with encapsulation
 
 use crate::prelude::*;
+use arc_swap::ArcSwapOption;
 pub use error::ContextError;
+use std::collections::HashMap;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use tokio::sync::Notify;
 
 /// App context struct - all fields private for encapsulation
 pub struct Context {
     // All fields private
+    group: String,
     config: ArcSwap<Config>,
     route_table: ArcSwap<RouteTable>,
     strategy: ArcSwap<Arc<dyn StrategyService>>,
+    // Bumped after every route table or strategy swap
+    generation: AtomicU64,
     channels: Arc<ChannelBundle>,
     migration_lock: Mutex<()>,
     // Notify for connection drain waiting
     connection_notify: Arc<Notify>,
+    // Accepted connections per listener generation
+    listener_generations: Arc<ListenerGenerations>,
+    // Latest progress of a running shutdown drain
+    drain_state: ArcSwapOption<DrainProgress>,
+    // Liveness and readiness flags for orchestrator probes
+    readiness: Readiness,
+    // Audit sink for state mutations
+    audit: AuditLog,
+    // Budget for retry and hedge attempts across the group
+    retry_budget: RetryBudget,
+    // Rate limit for repetitive warnings, per callsite
+    log_limiter: LogRateLimiter,
+    // Time source for drains, health checks, metrics and score caching
+    clock: Arc<dyn Clock>,
+    // Seeded random streams for randomized components
+    rng: RngProvider,
+    // Time spent in strategy picks, per strategy
+    pick_timings: PickTimings,
+    // Client IP to backend map for sticky sessions
+    affinity: AffinityTable,
+    // Latest probe results of each backend
+    health_history: HealthHistory,
+    // Per-backend state entries evicted after their backend left
+    stale_entries_evicted: AtomicU64,
+    // Invariant violations found by the consistency audit
+    invariant_violations: AtomicU64,
+    // Picks the fallback strategy made after the primary one failed
+    strategy_fallbacks: AtomicU64,
+    // Health checking runs (`services.health`, read at startup)
+    health_enabled: AtomicBool,
+    // Metrics aggregation runs (`services.metrics`, read at startup)
+    metrics_enabled: AtomicBool,
 }
 
 impl Context {
     /// Create a new context from config
     pub fn new(config: Config) -> Result<Self, ContextError> {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+
+    /// Create a new context from config with a custom time source
+    ///
+    /// Tests pass a [`MockClock`] to drive time-dependent behaviour manually.
+    pub fn with_clock(
+        config: Config,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self, ContextError> {
         // Create channel bundle
         let channels = Arc::new(ChannelBundle::new(
             config.runtime.metrics_cap,
@@ -30,32 +78,95 @@ impl Context {
             100,                        // backend_failure_cap
         ));
 
-        // Create route table from backend configs
-        let route_table = ArcSwap::from_pointee(RouteTable::new(config.backends.clone()));
+        // Create route table from backend configs (rejects duplicate ids/addresses)
+        let route_table =
+            RouteTable::with_duplicate_addresses(config.allow_duplicate_addresses)
+                .with_max_pending_connects(config.proxy.max_pending_connects)
+                .with_local_zone(
+                    config.proxy.local_zone.clone(),
+                    config.proxy.zone_spillover_min_healthy,
+                )
+                .with_circuit_breaker(
+                    config.proxy.circuit_breaker.clone(),
+                    clock.clone(),
+                );
+        let initial_health = Self::initial_health(&config, config.services.health);
+        for backend_config in config.backends.clone() {
+            route_table.insert(Arc::new(
+                Backend::with_latency_aggregation(
+                    backend_config,
+                    config.metrics.aggregation,
+                )
+                .with_initial_health(initial_health),
+            ))?;
+        }
+        let route_table = ArcSwap::from_pointee(route_table);
 
-        // Build strategy (convert BackendConfig to BackendMeta for compatibility)
-        let backend_metas: Vec<BackendMeta> = config
-            .backends
-            .iter()
-            .map(|c| BackendMeta::new(c.id, c.name.clone(), c.address.clone(), c.weight))
-            .collect();
-        let strategy = StrategyBuilder::new()
-            .with_strategy(config.strategy.clone())
-            .with_backends(backend_metas)
-            .build()?;
+        // Restore the health a previous run persisted; without health checks
+        // nothing would bring a restored-unhealthy backend back
+        if config.services.health
+            && let Some(health_state_file) = &config.health_state_file
+        {
+            health_state_file.restore(&route_table.load(), clock.now_millis());
+        }
 
+        // Build strategy
+        let strategy = Self::build_strategy(&config, config.services.metrics)?;
+        let audit = AuditLog::new(&config.audit);
+        let retry_budget = RetryBudget::new(config.proxy.retry_budget.max_tokens);
+        let rng = RngProvider::new(config.rng_seed);
+        let connection_notify = Arc::new(Notify::new());
+        let listener_generations =
+            Arc::new(ListenerGenerations::new(connection_notify.clone()));
+
+        // The route table and strategy above are the initial configuration
+        // commit; the proxy waits for it before binding
+        let readiness = Readiness::default();
+        readiness.mark_config_committed();
+
+        let config_services = config.services.clone();
+        let health_history = HealthHistory::new(config.runtime.health_cap);
         Ok(Self {
+            group: DEFAULT_GROUP.to_string(),
             config: ArcSwap::from_pointee(config),
             route_table,
             strategy: ArcSwap::from_pointee(strategy),
+            generation: AtomicU64::new(0),
             channels,
             migration_lock: Mutex::new(()),
-            connection_notify: Arc::new(Notify::new()),
+            connection_notify,
+            listener_generations,
+            drain_state: ArcSwapOption::empty(),
+            readiness,
+            audit,
+            retry_budget,
+            log_limiter: LogRateLimiter::new(),
+            clock,
+            rng,
+            pick_timings: PickTimings::default(),
+            affinity: AffinityTable::default(),
+            health_history,
+            stale_entries_evicted: AtomicU64::new(0),
+            invariant_violations: AtomicU64::new(0),
+            strategy_fallbacks: AtomicU64::new(0),
+            health_enabled: AtomicBool::new(config_services.health),
+            metrics_enabled: AtomicBool::new(config_services.metrics),
         })
     }
 
+    /// Set the name of the context's backend group
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = group.into();
+        self
+    }
+
     // Getters (no direct field access)
 
+    /// Get the backend group name
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
     /// Get config
     pub fn config(&self) -> Arc<Config> {
         self.config.load_full()
@@ -66,16 +177,196 @@ impl Context {
         self.route_table.load_full()
     }
 
+    /// Get the time source
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Get the randomness provider
+    ///
+    /// Seeded from `rng_seed` when set; randomized components take their own
+    /// named stream from it.
+    pub fn rng(&self) -> &RngProvider {
+        &self.rng
+    }
+
+    /// Get the strategy pick duration histograms
+    pub fn pick_timings(&self) -> &PickTimings {
+        &self.pick_timings
+    }
+
+    /// Get the sticky session affinity table
+    pub fn affinity(&self) -> &AffinityTable {
+        &self.affinity
+    }
+
+    /// Get the per-listener-generation connection tracking
+    pub fn listener_generations(&self) -> Arc<ListenerGenerations> {
+        self.listener_generations.clone()
+    }
+
+    /// Get the liveness and readiness flags
+    pub fn readiness(&self) -> &Readiness {
+        &self.readiness
+    }
+
     /// Get strategy
     pub fn strategy(&self) -> Arc<Arc<dyn StrategyService>> {
         self.strategy.load_full()
     }
 
+    /// Get the route table generation
+    ///
+    /// Bumped after every route table or strategy swap, so callers holding
+    /// [`Context::routing_table`] and [`Context::strategy`] across calls only
+    /// need to reload them when the generation changes.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
     /// Get channels
     pub fn channels(&self) -> &ChannelBundle {
         &self.channels
     }
 
+    /// Get the audit log
+    pub fn audit(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    /// Get the budget for retry and hedge attempts
+    pub fn retry_budget(&self) -> &RetryBudget {
+        &self.retry_budget
+    }
+
+    /// Get the rate limiter for repetitive warnings
+    pub fn log_limiter(&self) -> &LogRateLimiter {
+        &self.log_limiter
+    }
+
+    /// Whether a repetitive warning from `callsite` should be logged now
+    ///
+    /// Applies `runtime.log_rate_limit`; see [`LogRateLimiter::admit`].
+    pub fn should_log(&self, callsite: &'static str) -> bool {
+        self.log_limiter.admit(
+            callsite,
+            self.clock.now_millis(),
+            &self.config.load().runtime.log_rate_limit,
+        )
+    }
+
+    /// Count per-backend state entries evicted from `structure` because
+    /// their backend left the route table
+    pub fn record_stale_entries_evicted(&self, structure: &'static str, evicted: usize) {
+        if evicted == 0 {
+            return;
+        }
+        self.stale_entries_evicted
+            .fetch_add(evicted as u64, Ordering::Relaxed);
+        lemonade_observability::get_connection_metrics("lemonade-load-balancer")
+            .record_entries_evicted(structure, evicted as u64);
+        tracing::debug!(
+            structure,
+            evicted,
+            "Evicted state of backends no longer routed"
+        );
+    }
+
+    /// Count an invariant violation found by the consistency audit
+    pub fn record_invariant_violation(&self, violation: &InvariantViolation) {
+        self.invariant_violations.fetch_add(1, Ordering::Relaxed);
+        lemonade_observability::get_connection_metrics("lemonade-load-balancer")
+            .record_invariant_violation(violation.invariant());
+        tracing::error!(
+            group = %self.group,
+            invariant = violation.invariant(),
+            "Consistency audit: {}",
+            violation
+        );
+    }
+
+    /// Count a pick handed to the `fallback` strategy because the `primary`
+    /// one failed with `error`
+    pub fn record_strategy_fallback(
+        &self,
+        primary: &Strategy,
+        fallback: &Strategy,
+        error: &StrategyError,
+    ) {
+        self.strategy_fallbacks.fetch_add(1, Ordering::Relaxed);
+        lemonade_observability::get_connection_metrics("lemonade-load-balancer")
+            .record_strategy_fallback(primary.as_ref(), fallback.as_ref());
+        if self.should_log("strategy_fallback") {
+            tracing::warn!(
+                group = %self.group,
+                strategy = %primary.as_ref(),
+                fallback = %fallback.as_ref(),
+                "Strategy failed to pick a backend, falling back: {}",
+                error
+            );
+        }
+    }
+
+    /// Whether health checking runs
+    ///
+    /// When it does not, the proxy skips reporting connection failures.
+    pub fn health_enabled(&self) -> bool {
+        self.health_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Whether metrics aggregation runs
+    ///
+    /// When it does not, the proxy skips sending metrics events.
+    pub fn metrics_enabled(&self) -> bool {
+        self.metrics_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Get the per-backend state entries evicted so far
+    pub fn stale_entries_evicted(&self) -> u64 {
+        self.stale_entries_evicted.load(Ordering::Relaxed)
+    }
+
+    /// Get the invariant violations found by the consistency audit so far
+    pub fn invariant_violations(&self) -> u64 {
+        self.invariant_violations.load(Ordering::Relaxed)
+    }
+
+    /// Get the picks that chose each routed backend so far
+    ///
+    /// Counts live on the backends: a migration keeps those of unchanged
+    /// backends, and backends it adds or replaces start from zero.
+    pub fn selection_counts(&self) -> HashMap<BackendId, u64> {
+        self.routing_table()
+            .all_backends()
+            .iter()
+            .map(|backend| (backend.id(), backend.selections()))
+            .collect()
+    }
+
+    /// Get the latest probe results of `backend_id`, oldest first
+    ///
+    /// At most `runtime.health_cap` results are kept per backend. A backend
+    /// removed, replaced or re-addressed by a migration starts over with an
+    /// empty history.
+    pub fn health_history(&self, backend_id: BackendId) -> Vec<HealthProbeRecord> {
+        self.health_history.get(backend_id)
+    }
+
+    /// Get the ids of the backends with a probe history
+    pub fn health_history_backends(&self) -> Vec<BackendId> {
+        self.health_history.tracked_backends()
+    }
+
+    /// Record the result of a probe of `backend_id` in its history
+    pub fn record_probe(&self, backend_id: BackendId, record: HealthProbeRecord) {
+        self.health_history.record(backend_id, record);
+    }
+
+    /// Get the picks made by the fallback strategy so far
+    pub fn strategy_fallbacks(&self) -> u64 {
+        self.strategy_fallbacks.load(Ordering::Relaxed)
+    }
+
     // Private setters (used internally by migrate)
 
     fn set_config(&self, config: Arc<Config>) {
@@ -84,109 +375,293 @@ impl Context {
 
     fn set_strategy(&self, strategy: Arc<dyn StrategyService>) {
         self.strategy.store(Arc::new(strategy));
+        self.generation.fetch_add(1, Ordering::AcqRel);
     }
 
     fn set_routing_table(&self, rt: Arc<RouteTable>) {
         self.route_table.store(rt);
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Build the strategy for a config (converts BackendConfig to BackendMeta)
+    ///
+    /// Without metrics aggregation, strategies ranking backends by metrics
+    /// fall back to least connections.
+    fn build_strategy(
+        config: &Config,
+        metrics_enabled: bool,
+    ) -> Result<Arc<dyn StrategyService>, ContextError> {
+        let usable = |strategy: &Strategy| {
+            if !metrics_enabled && strategy.requires_metrics() {
+                tracing::warn!(
+                    strategy = %strategy.as_ref(),
+                    "Metrics aggregation is disabled, falling back to least_connections"
+                );
+                Strategy::LeastConnections
+            } else {
+                strategy.clone()
+            }
+        };
+        let strategy = usable(&config.strategy);
+        // A fallback replaced by the primary strategy would only repeat it
+        let fallback = config
+            .fallback
+            .as_ref()
+            .map(usable)
+            .filter(|fallback| *fallback != strategy);
+        let backend_metas: Vec<BackendMeta> = config
+            .backends
+            .iter()
+            .map(|c| BackendMeta::new(c.id, c.name.clone(), c.address.clone(), c.weight))
+            .collect();
+        Ok(StrategyBuilder::new()
+            .with_strategy(strategy)
+            .with_params(config.strategy_params.clone())
+            .with_fallback(fallback)
+            .with_backends(backend_metas)
+            .build()?)
     }
 
     /// Migrate to new config (handles backend draining, config/strategy update, listen address change)
     pub async fn migrate(&self, new_config: Config) -> Result<(), ContextError> {
+        self.migrate_as(new_config, AuditActor::Api).await
+    }
+
+    /// Migrate to new config on behalf of `actor`, recording the outcome in the audit log
+    pub async fn migrate_as(
+        &self,
+        new_config: Config,
+        actor: AuditActor,
+    ) -> Result<(), ContextError> {
+        let old_strategy = self.config().strategy.clone();
+        let result = self.apply_migration(new_config).await;
+        self.record_migration(&actor, &old_strategy, &result);
+        result.map(|_| ())
+    }
+
+    /// Replace the backend list, keeping the rest of the config
+    ///
+    /// This is the supported way to change backends programmatically (e.g.
+    /// from service discovery or an admin registration endpoint). It goes
+    /// through the same diff and apply as [`Context::migrate`]: the list is
+    /// validated (duplicate ids and addresses are rejected), unchanged
+    /// backends keep their `Backend` instance and counters, removed and
+    /// replaced ones are drained. A `HealthEvent::BackendConfigUpdated` is
+    /// sent for every added or changed backend, followed by a single
+    /// [`ConfigEvent::Migrated`]. An identical list changes nothing.
+    pub async fn set_backends(
+        &self,
+        backends: Vec<BackendConfig>,
+    ) -> Result<ConfigDiff, ContextError> {
+        let old_config = self.config();
+        let mut new_config = old_config.as_ref().clone();
+        new_config.backends = backends;
+        // Duplicates in an otherwise unchanged list diff as empty, so the list
+        // is validated before the diff, and a rejection audited the same way
+        if let Err(e) = new_config.validate() {
+            let result = Err(e.into());
+            self.record_migration(&AuditActor::Api, &old_config.strategy, &result);
+            return result;
+        }
+        if ConfigDiff::between(&old_config, &new_config).is_empty() {
+            return Ok(ConfigDiff::default());
+        }
+
+        let result = self.apply_migration(new_config).await;
+        self.record_migration(&AuditActor::Api, &old_config.strategy, &result);
+        let diff = result?;
+
+        let health_tx = self.channels.health_tx();
+        for backend_id in diff
+            .added_backends
+            .iter()
+            .chain(&diff.changed_backends)
+            .chain(&diff.address_changed_backends)
+            .chain(&diff.weight_changed_backends)
+        {
+            let _ = health_tx.try_send(HealthEvent::BackendConfigUpdated {
+                backend_id: *backend_id,
+            });
+        }
+        // Full migrations broadcast it already
+        if diff.is_weight_only() {
+            let _ = self.channels.config_tx().send(ConfigEvent::Migrated);
+        }
+        Ok(diff)
+    }
+
+    /// Record the outcome of a migration on behalf of `actor` in the audit log
+    fn record_migration(
+        &self,
+        actor: &AuditActor,
+        old_strategy: &Strategy,
+        result: &Result<ConfigDiff, ContextError>,
+    ) {
+        match result {
+            Ok(diff) if diff.is_strategy_only() => {
+                let new_strategy = diff.strategy.as_ref().unwrap_or(old_strategy);
+                self.audit.record(
+                    AuditRecord::new(actor, AuditAction::StrategyChange)
+                        .with_change(old_strategy.as_ref(), new_strategy.as_ref()),
+                );
+            }
+            Ok(diff) => {
+                self.audit.record(
+                    AuditRecord::new(actor, AuditAction::ConfigReload)
+                        .with_detail(diff.summary()),
+                );
+            }
+            Err(e) => {
+                self.audit.record(
+                    AuditRecord::new(actor, AuditAction::ConfigReload).rejected(e),
+                );
+            }
+        }
+    }
+
+    /// Health backends added under `config` start with
+    ///
+    /// Always healthy without health checks, which alone could bring a
+    /// backend started unhealthy into rotation.
+    fn initial_health(config: &Config, health_enabled: bool) -> InitialHealthState {
+        if health_enabled {
+            config.health.initial_state
+        } else {
+            InitialHealthState::Healthy
+        }
+    }
+
+    /// Apply a new config and return what changed
+    async fn apply_migration(
+        &self,
+        new_config: Config,
+    ) -> Result<ConfigDiff, ContextError> {
+        // Reject conflicting backends before touching any state
+        new_config.validate()?;
+
         // Acquire migration lock for critical section
         let _lock = self.migration_lock.lock().unwrap();
 
         let old_config = self.config();
-        let old_routing = self.routing_table();
+        let diff = ConfigDiff::between(&old_config, &new_config);
 
-        // Check if listen address changed
-        if old_config.proxy.listen_address != new_config.proxy.listen_address {
+        // Fast path: only the strategy changed, swap it in place and keep the
+        // routing table (and in-flight connections) untouched
+        if diff.is_strategy_only() {
+            let new_strategy = Self::build_strategy(&new_config, self.metrics_enabled())?;
+            let strategy = new_config.strategy.clone();
+            self.set_config(Arc::new(new_config));
+            self.set_strategy(new_strategy);
             let _ = self
                 .channels
                 .config_tx()
-                .send(ConfigEvent::ListenAddressChanged(
-                    new_config.proxy.listen_address,
-                ));
+                .send(ConfigEvent::StrategyChanged(strategy));
+            return Ok(diff);
         }
 
-        // Compare old vs new backends
-        let old_backends: std::collections::HashMap<BackendId, Arc<Backend>> =
-            old_routing
-                .all_backends()
-                .into_iter()
-                .map(|b| (b.id(), b))
-                .collect();
+        // Fast path: only backend weights changed, update them in place and
+        // keep the routing table (and in-flight connections) untouched
+        if diff.is_weight_only() {
+            self.apply_weights(&new_config, &diff.weight_changed_backends);
+            self.set_config(Arc::new(new_config));
+            return Ok(diff);
+        }
 
-        let new_backend_configs: std::collections::HashMap<BackendId, BackendConfig> =
-            new_config
-                .backends
-                .iter()
-                .map(|c| (c.id, c.clone()))
-                .collect();
+        let old_routing = self.routing_table();
 
-        // Identify backends to drain (removed or changed)
-        let mut to_drain: Vec<Arc<Backend>> = Vec::new();
-        let mut to_add: Vec<BackendConfig> = Vec::new();
-
-        // Check for removed or changed backends
-        for (id, old_backend) in &old_backends {
-            if let Some(new_config) = new_backend_configs.get(id) {
-                // Backend exists - check if changed
-                let old_addr = old_backend.address();
-                let old_name = old_backend.name();
-                if new_config.address != *old_addr
-                    || new_config.name.as_deref() != old_name
-                    || new_config.weight != old_backend.weight()
-                {
-                    // Backend changed - mark old as draining
-                    to_drain.push(old_backend.clone());
-                    to_add.push(new_config.clone());
-                }
-            } else {
-                // Backend removed - mark as draining
-                to_drain.push(old_backend.clone());
-            }
+        // Check if listen address changed
+        if let Some(listen_address) = diff.listen_address {
+            let _ = self
+                .channels
+                .config_tx()
+                .send(ConfigEvent::ListenAddressChanged(listen_address));
         }
 
-        // Check for new backends
-        for (id, new_config) in &new_backend_configs {
-            if !old_backends.contains_key(id) {
-                to_add.push(new_config.clone());
-            }
-        }
+        // Backends to drain (removed, or changed beyond their address)
+        let to_drain: Vec<Arc<Backend>> = diff
+            .removed_backends
+            .iter()
+            .chain(&diff.changed_backends)
+            .filter_map(|id| old_routing.get(*id))
+            .collect();
 
-        // Mark backends as draining
-        for backend in &to_drain {
-            backend.mark_draining();
-        }
+        // Backends to create (added, or replacing a changed one)
+        let to_add: Vec<BackendConfig> = new_config
+            .backends
+            .iter()
+            .filter(|c| {
+                diff.added_backends.contains(&c.id)
+                    || diff.changed_backends.contains(&c.id)
+            })
+            .cloned()
+            .collect();
 
-        // Create new route table
-        let mut new_backends: Vec<Arc<Backend>> = old_routing
-            .all_backends()
-            .into_iter()
-            .filter(|b| !to_drain.iter().any(|d| d.id() == b.id()))
+        // Backends that keep their state and only move to a new address
+        let to_readdress: Vec<(Arc<Backend>, BackendAddress)> = new_config
+            .backends
+            .iter()
+            .filter(|c| diff.address_changed_backends.contains(&c.id))
+            .filter_map(|c| old_routing.get(c.id).map(|b| (b, c.address.clone())))
             .collect();
 
-        // Add new backends
-        for config in &to_add {
-            new_backends.push(Arc::new(Backend::new(config.clone())));
+        // Prepare strategy update (kept, with its per-backend state, unless
+        // the strategy changed)
+        let new_strategy = match diff.strategy {
+            Some(_) => Some(Self::build_strategy(&new_config, self.metrics_enabled())?),
+            None => None,
+        };
+
+        // Create new route table (kept backends + new ones), published once
+        // the drain is over
+        let new_route_table =
+            RouteTable::with_duplicate_addresses(new_config.allow_duplicate_addresses)
+                .with_max_pending_connects(new_config.proxy.max_pending_connects)
+                .with_local_zone(
+                    new_config.proxy.local_zone.clone(),
+                    new_config.proxy.zone_spillover_min_healthy,
+                )
+                .with_circuit_breaker(
+                    new_config.proxy.circuit_breaker.clone(),
+                    self.clock.clone(),
+                );
+        for backend in old_routing.all_backends().into_iter().filter(|b| {
+            !to_drain.iter().any(|d| d.id() == b.id())
+                && !diff.address_changed_backends.contains(&b.id())
+        }) {
+            new_route_table.insert(backend)?;
+        }
+        let initial_health = Self::initial_health(&new_config, self.health_enabled());
+        for config in to_add {
+            let backend =
+                Backend::with_latency_aggregation(config, new_config.metrics.aggregation)
+                    .with_initial_health(initial_health);
+            // A replaced backend keeps its operator override
+            if let Some(old) = old_routing.get(backend.id()) {
+                backend.set_admin_state(old.admin_state());
+            }
+            new_route_table.insert(Arc::new(backend))?;
+        }
+        // Last, so no other backend is checked against an address being left
+        for (backend, _) in &to_readdress {
+            new_route_table.insert_moving(backend.clone())?;
         }
 
-        // Create new route table
-        let new_route_table = RouteTable::default();
-        for backend in new_backends {
-            new_route_table.insert(backend);
+        // Swap addresses in place once nothing can fail, before waiting on
+        // the drain, so new connections go to the new address at once (open
+        // connections to the old one are left to finish)
+        for (backend, address) in &to_readdress {
+            backend.set_address(address.clone());
         }
 
-        // Prepare strategy update (convert BackendConfig to BackendMeta)
-        let backend_metas: Vec<BackendMeta> = new_config
-            .backends
-            .iter()
-            .map(|c| BackendMeta::new(c.id, c.name.clone(), c.address.clone(), c.weight))
-            .collect();
-        let new_strategy = StrategyBuilder::new()
-            .with_strategy(new_config.strategy.clone())
-            .with_backends(backend_metas)
-            .build()?;
+        // Kept backends whose weight changed alongside other edits are
+        // updated in place, as on the weight-only path
+        self.apply_weights(&new_config, &diff.weight_changed_backends);
+
+        // Mark backends as draining, applying the drain policy to their
+        // open connections
+        for backend in &to_drain {
+            self.drain_backend(backend, new_config.runtime.drain_policy);
+        }
 
         // Release lock before await (waiting for drain)
         drop(_lock);
@@ -194,9 +669,10 @@ impl Context {
         // Wait for draining backends to have 0 connections (with timeout)
         let drain_timeout =
             Duration::from_millis(new_config.runtime.drain_timeout_millis);
-        let start = std::time::Instant::now();
+        let drain_timeout_ms = drain_timeout.as_millis() as u64;
+        let start_ms = self.clock.now_millis();
 
-        while start.elapsed() < drain_timeout {
+        while self.clock.now_millis().saturating_sub(start_ms) < drain_timeout_ms {
             let all_drained = to_drain
                 .iter()
                 .all(|backend| backend.active_connections() == 0);
@@ -206,71 +682,319 @@ impl Context {
             }
 
             // Wait a bit before checking again
-            tokio::time::sleep(Duration::from_millis(100)).await;
+            self.clock.sleep(Duration::from_millis(100)).await;
         }
 
         // Re-acquire lock for final updates
         let _lock2 = self.migration_lock.lock().unwrap();
 
+        if diff.audit_changed {
+            self.audit.reconfigure(&new_config.audit);
+        }
+
+        let strategy = match &new_strategy {
+            Some(strategy) => strategy.clone(),
+            None => Arc::clone(&*self.strategy()),
+        };
+        strategy.on_migrate(&new_route_table, self.clock.now_millis());
+
         // Update config, strategy, route table atomically
         self.set_config(Arc::new(new_config.clone()));
-        self.set_strategy(new_strategy);
+        if let Some(new_strategy) = new_strategy {
+            self.set_strategy(new_strategy);
+        }
         self.set_routing_table(Arc::new(new_route_table));
 
+        // Clients stuck to a drained backend pick again on their next connection
+        let drained: Vec<BackendId> = to_drain.iter().map(|b| b.id()).collect();
+        self.record_stale_entries_evicted(
+            "affinity",
+            self.affinity.remove_backends(&drained),
+        );
+        // Probe results of a removed, replaced or re-addressed backend say
+        // nothing about what is routed under its id now
+        let probed_elsewhere: Vec<BackendId> = drained
+            .iter()
+            .chain(&diff.address_changed_backends)
+            .copied()
+            .collect();
+        self.record_stale_entries_evicted(
+            "health_history",
+            self.health_history.remove_backends(&probed_elsewhere),
+        );
+
+        // Broadcast address changes so the health service re-probes them
+        for (backend, address) in to_readdress {
+            let _ = self
+                .channels
+                .config_tx()
+                .send(ConfigEvent::BackendAddressChanged {
+                    backend_id: backend.id(),
+                    address,
+                });
+        }
+
         // Broadcast ConfigEvent::Migrated
         let _ = self.channels.config_tx().send(ConfigEvent::Migrated);
 
-        Ok(())
+        Ok(diff)
+    }
+
+    /// Change the weight of a routed backend in place
+    ///
+    /// The running config is updated to match, so a later reload of an
+    /// unchanged config file restores the file's weight. Returns the previous
+    /// weight, or `None` if the backend is not routed.
+    pub fn set_backend_weight(
+        &self,
+        id: BackendId,
+        weight: Option<u8>,
+    ) -> Option<Option<u8>> {
+        let _lock = self.migration_lock.lock().unwrap();
+        let backend = self.routing_table().get(id)?;
+        let previous = backend.weight();
+        let mut config = self.config().as_ref().clone();
+        if let Some(backend_config) = config.backends.iter_mut().find(|b| b.id == id) {
+            backend_config.weight = weight;
+        }
+        self.apply_weights(&config, &[id]);
+        self.set_config(Arc::new(config));
+        Some(previous)
+    }
+
+    /// Set the operator override of a routed backend's place in rotation
+    ///
+    /// Takes effect on the next pick, without a reload, and health checks
+    /// keep running meanwhile. A reload replacing the backend keeps the
+    /// override. Returns the previous state, or `None` if the backend is not
+    /// routed.
+    pub fn set_admin_state(
+        &self,
+        id: BackendId,
+        state: AdminState,
+    ) -> Option<AdminState> {
+        self.set_admin_state_as(id, state, AuditActor::Api)
+    }
+
+    /// Set the operator override of a routed backend on behalf of `actor`,
+    /// recording the change in the audit log (see [`Context::set_admin_state`])
+    pub fn set_admin_state_as(
+        &self,
+        id: BackendId,
+        state: AdminState,
+        actor: AuditActor,
+    ) -> Option<AdminState> {
+        let _lock = self.migration_lock.lock().unwrap();
+        let backend = self.routing_table().get(id)?;
+        let previous = backend.set_admin_state(state);
+        self.audit.record(
+            AuditRecord::new(&actor, AuditAction::BackendAdminState)
+                .with_target(format!("backend:{}", id))
+                .with_change(previous.as_str(), state.as_str()),
+        );
+        if previous != state {
+            tracing::info!(
+                "Backend {} of group {} admin state changed from {} to {}",
+                id,
+                self.group,
+                previous,
+                state
+            );
+        }
+        Some(previous)
+    }
+
+    /// Copy the weights of `ids` from `config` onto the routed backends
+    ///
+    /// Bumps the generation so strategies rebuild weight-derived state.
+    fn apply_weights(&self, config: &Config, ids: &[BackendId]) {
+        let routing = self.routing_table();
+        for backend_config in config.backends.iter().filter(|b| ids.contains(&b.id)) {
+            if let Some(backend) = routing.get(backend_config.id) {
+                backend.set_weight(backend_config.weight);
+            }
+        }
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Mark a backend draining and apply `policy` to its open connections
+    ///
+    /// With [`DrainPolicy::Deadline`] a background task force-closes the
+    /// connections still open once the deadline passes, even if the backend
+    /// has left the route table by then.
+    pub fn drain_backend(&self, backend: &Arc<Backend>, policy: DrainPolicy) {
+        backend.mark_draining();
+        match policy {
+            DrainPolicy::Finish => {}
+            DrainPolicy::Immediate => {
+                backend.close_connections(CloseReason::DrainImmediate);
+            }
+            DrainPolicy::Deadline(millis) => {
+                let backend = backend.clone();
+                let clock = self.clock.clone();
+                // Fixed now so the deadline does not depend on when the task runs
+                let deadline_ms = clock.now_millis().saturating_add(millis);
+                tokio::spawn(async move {
+                    loop {
+                        let now_ms = clock.now_millis();
+                        if now_ms >= deadline_ms {
+                            break;
+                        }
+                        clock
+                            .sleep(Duration::from_millis(deadline_ms - now_ms))
+                            .await;
+                    }
+                    let remaining = backend.active_connections();
+                    if remaining > 0 {
+                        tracing::warn!(
+                            "Drain deadline of backend {} expired, force-closing {} connections",
+                            backend.id(),
+                            remaining
+                        );
+                    }
+                    backend.close_connections(CloseReason::DrainDeadline);
+                });
+            }
+        }
     }
 
     /// Wait for all connections to drain (for shutdown)
+    ///
+    /// Connections still being set up on a replaced listener are included.
+    /// Every `drain_progress_interval_millis` the remaining connections are
+    /// logged, published on the drain channel and exposed through
+    /// [`Context::drain_state`]; a final report is published once drained.
+    /// On timeout the error lists the backends still holding connections.
     pub async fn wait_for_drain(&self, timeout: Duration) -> Result<(), ContextError> {
-        let start = std::time::Instant::now();
+        let interval_ms = self.config().runtime.drain_progress_interval_millis;
+        let next_report_ms = AtomicU64::new(self.clock.now_millis());
+        let result = self
+            .wait_until_drained(timeout, || {
+                let progress = self.drain_progress();
+                let now_ms = self.clock.now_millis();
+                if progress.is_drained()
+                    || now_ms >= next_report_ms.load(Ordering::Relaxed)
+                {
+                    next_report_ms
+                        .store(now_ms.saturating_add(interval_ms), Ordering::Relaxed);
+                    self.report_drain_progress(progress.clone());
+                }
+                progress.remaining_total
+            })
+            .await;
 
-        while start.elapsed() < timeout {
+        let stuck = self.drain_progress();
+        self.drain_state.store(None);
+        result.map_err(|_| {
             let routing = self.routing_table();
-            let total_connections: usize = routing
-                .all_backends()
+            let backends: Vec<String> = stuck
+                .per_backend
                 .iter()
-                .map(|b| b.active_connections())
-                .sum();
+                .map(|(id, remaining)| {
+                    let name =
+                        routing.get(*id).and_then(|b| b.name().map(str::to_string));
+                    match name {
+                        Some(name) => format!("backend {} ({}): {}", id, name, remaining),
+                        None => format!("backend {}: {}", id, remaining),
+                    }
+                })
+                .collect();
+            ContextError::DrainTimeout(format!(
+                "{} connections still active after timeout; stuck backends: [{}]",
+                stuck.remaining_total,
+                backends.join(", ")
+            ))
+        })
+    }
 
-            if total_connections == 0 {
-                return Ok(());
-            }
+    /// Connections still open, in total and per backend
+    pub fn drain_progress(&self) -> DrainProgress {
+        let per_backend = self
+            .routing_table()
+            .all_backends()
+            .iter()
+            .map(|b| (b.id(), b.active_connections()))
+            .filter(|(_, remaining)| *remaining > 0)
+            .collect();
+        DrainProgress {
+            remaining_total: self.active_connections(),
+            per_backend,
+        }
+    }
 
-            // Wait for notification or timeout
-            let remaining = timeout - start.elapsed();
-            if remaining.is_zero() {
-                break;
+    /// Progress of the running shutdown drain, if any
+    pub fn drain_state(&self) -> Option<Arc<DrainProgress>> {
+        self.drain_state.load_full()
+    }
+
+    /// Log, publish and record a drain progress snapshot
+    fn report_drain_progress(&self, progress: DrainProgress) {
+        if progress.is_drained() {
+            tracing::info!("Drain complete");
+        } else {
+            tracing::info!("Draining: {}", progress);
+        }
+        let _ = self.channels.drain_tx().send(progress.clone());
+        self.drain_state.store(Some(Arc::new(progress)));
+    }
+
+    /// Wait for the connections accepted on a listener generation to finish
+    pub async fn wait_for_generation_drain(
+        &self,
+        generation: ListenerGeneration,
+        timeout: Duration,
+    ) -> Result<(), ContextError> {
+        self.wait_until_drained(timeout, || self.listener_generations.active(generation))
+            .await
+            .map_err(|remaining| {
+                ContextError::DrainTimeout(format!(
+                    "{} connections from listener generation {} still active after timeout",
+                    remaining, generation
+                ))
+            })
+    }
+
+    /// Active connections, counting both backend connections and accepted
+    /// connections not yet attached to a backend
+    pub fn active_connections(&self) -> usize {
+        let backend_connections: usize = self
+            .routing_table()
+            .all_backends()
+            .iter()
+            .map(|b| b.active_connections())
+            .sum();
+        backend_connections.max(self.listener_generations.total())
+    }
+
+    /// Wait until `count` reaches zero, returning the remaining count on timeout
+    async fn wait_until_drained(
+        &self,
+        timeout: Duration,
+        count: impl Fn() -> usize,
+    ) -> Result<(), usize> {
+        let timeout_ms = timeout.as_millis() as u64;
+        let start_ms = self.clock.now_millis();
+
+        while self.clock.now_millis().saturating_sub(start_ms) < timeout_ms {
+            if count() == 0 {
+                return Ok(());
             }
 
             tokio::select! {
                 _ = self.connection_notify.notified() => {
                     // Connection closed, check again
                 }
-                _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                _ = self.clock.sleep(Duration::from_millis(100)) => {
                     // Timeout, check again
                 }
             }
         }
 
         // Check one more time
-        let routing = self.routing_table();
-        let total_connections: usize = routing
-            .all_backends()
-            .iter()
-            .map(|b| b.active_connections())
-            .sum();
-
-        if total_connections > 0 {
-            return Err(ContextError::DrainTimeout(format!(
-                "{} connections still active after timeout",
-                total_connections
-            )));
+        match count() {
+            0 => Ok(()),
+            remaining => Err(remaining),
         }
-
-        Ok(())
     }
 
     /// Notify that a connection was closed (for drain waiting)
@@ -293,5 +1017,11 @@ mod error {
         /// Drain timeout error
         #[error("drain timeout: {0}")]
         DrainTimeout(String),
+        /// Invalid config error
+        #[error("invalid config: {0}")]
+        Config(#[from] ConfigError),
+        /// Route table error
+        #[error("route table error: {0}")]
+        RouteTable(#[from] RouteTableError),
     }
 }
@@ -0,0 +1,112 @@
+//! Drain policy module
+//!
+//! What happens to connections already open to a backend once it starts
+//! draining, and why a proxied connection was closed
+use serde::{Deserialize, Serialize};
+
+/// How connections open to a draining backend are treated
+///
+/// Serialized as `finish`, `immediate` or `deadline:<millis>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DrainPolicy {
+    /// Let open connections run until they close on their own
+    #[default]
+    Finish,
+    /// Force-close connections still open this many milliseconds after
+    /// draining began
+    Deadline(u64),
+    /// Force-close open connections as soon as draining begins
+    Immediate,
+}
+
+impl std::str::FromStr for DrainPolicy {
+    type Err = DrainPolicyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "finish" => Ok(Self::Finish),
+            "immediate" => Ok(Self::Immediate),
+            _ => s
+                .strip_prefix("deadline:")
+                .and_then(|millis| millis.parse().ok())
+                .map(Self::Deadline)
+                .ok_or_else(|| DrainPolicyError(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for DrainPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Finish => write!(f, "finish"),
+            Self::Deadline(millis) => write!(f, "deadline:{}", millis),
+            Self::Immediate => write!(f, "immediate"),
+        }
+    }
+}
+
+impl TryFrom<String> for DrainPolicy {
+    type Error = DrainPolicyError;
+
+    fn try_from(policy: String) -> Result<Self, Self::Error> {
+        policy.parse()
+    }
+}
+
+impl From<DrainPolicy> for String {
+    fn from(policy: DrainPolicy) -> Self {
+        policy.to_string()
+    }
+}
+
+/// Unknown drain policy
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid drain policy '{0}' (expected finish, immediate or deadline:<millis>)")]
+pub struct DrainPolicyError(pub String);
+
+/// Why a proxied connection was closed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseReason {
+    /// Client or backend closed the connection
+    #[default]
+    Normal,
+    /// Still open when its draining backend's deadline expired
+    DrainDeadline,
+    /// Cut when its backend started draining with the immediate policy
+    DrainImmediate,
+    /// Cut when its backend turned unhealthy (`health.evict_on_unhealthy`)
+    BackendUnhealthy,
+    /// Client reset the connection or went away mid-transfer
+    ClientAborted,
+    /// Backend reset the connection or went away mid-transfer
+    BackendAborted,
+}
+
+impl CloseReason {
+    /// Check whether the load balancer closed the connection itself
+    pub fn is_forced(&self) -> bool {
+        matches!(
+            self,
+            Self::DrainDeadline | Self::DrainImmediate | Self::BackendUnhealthy
+        )
+    }
+
+    /// Check whether one side reset the connection
+    pub fn is_abort(&self) -> bool {
+        matches!(self, Self::ClientAborted | Self::BackendAborted)
+    }
+
+    /// Reason name, as used in metric attributes
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::DrainDeadline => "drain_deadline",
+            Self::DrainImmediate => "drain_immediate",
+            Self::BackendUnhealthy => "backend_unhealthy",
+            Self::ClientAborted => "client_aborted",
+            Self::BackendAborted => "backend_aborted",
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! Drain progress module
+//!
+//! Snapshot of the connections still open while the load balancer drains
+use crate::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Connections still open during a shutdown drain
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DrainProgress {
+    /// Connections still open, including accepted ones not yet attached to
+    /// a backend
+    pub remaining_total: usize,
+    /// Open connections per backend (backends with none are left out)
+    pub per_backend: BTreeMap<BackendId, usize>,
+}
+
+impl DrainProgress {
+    /// Check whether every connection has closed
+    pub fn is_drained(&self) -> bool {
+        self.remaining_total == 0
+    }
+}
+
+impl std::fmt::Display for DrainProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} connections remaining", self.remaining_total)?;
+        let backends: Vec<String> = self
+            .per_backend
+            .iter()
+            .map(|(id, remaining)| format!("{} on backend {}", remaining, id))
+            .collect();
+        if !backends.is_empty() {
+            write!(f, " ({})", backends.join(", "))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+//! Groups module
+//!
+//! One [`Context`] per named backend group, each with its own route table,
+//! strategy and channels
+use crate::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicBool;
+
+/// Contexts of the backend groups served by one load balancer
+pub struct Groups {
+    contexts: BTreeMap<String, Arc<Context>>,
+    /// Names in listener start order (the implicit default group first)
+    order: Vec<String>,
+    shutting_down: AtomicBool,
+}
+
+impl Groups {
+    /// Create a context for every group of the config
+    pub fn new(config: &Config) -> Result<Self, ContextError> {
+        config.validate()?;
+        let mut contexts = BTreeMap::new();
+        let mut order = Vec::new();
+        for (name, group_config) in config.group_configs() {
+            let ctx = Context::new(group_config)?.with_group(name.clone());
+            contexts.insert(name.clone(), Arc::new(ctx));
+            order.push(name);
+        }
+        Ok(Self {
+            contexts,
+            order,
+            shutting_down: AtomicBool::new(false),
+        })
+    }
+
+    /// Get a group's context
+    pub fn get(&self, name: &str) -> Option<Arc<Context>> {
+        self.contexts.get(name).cloned()
+    }
+
+    /// Context of the first group (the one hosting the admin API)
+    pub fn primary(&self) -> Arc<Context> {
+        self.contexts[&self.order[0]].clone()
+    }
+
+    /// Group contexts in listener start order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Arc<Context>)> {
+        self.order
+            .iter()
+            .map(|name| (name.as_str(), &self.contexts[name]))
+    }
+
+    /// Migrate every group to its part of a new config
+    ///
+    /// Each group diffs and drains independently. Adding or removing groups
+    /// needs a restart, so a config with a different set of groups is rejected
+    /// before any group changes.
+    pub async fn migrate_as(
+        &self,
+        new_config: Config,
+        actor: AuditActor,
+    ) -> Result<(), ContextError> {
+        let checked = new_config.validate().and_then(|()| {
+            let names = new_config.group_names();
+            if names == self.order {
+                return Ok(());
+            }
+            Err(ConfigError::Groups(format!(
+                "groups changed from {:?} to {:?}; restart to apply",
+                self.order, names
+            )))
+        });
+        if let Err(e) = checked {
+            let e = ContextError::from(e);
+            self.primary()
+                .audit()
+                .record(AuditRecord::new(&actor, AuditAction::ConfigReload).rejected(&e));
+            return Err(e);
+        }
+        for (name, group_config) in new_config.group_configs() {
+            self.contexts[&name]
+                .migrate_as(group_config, actor.clone())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Signal every group to shut down (only the first call has an effect)
+    pub fn shutdown(&self) {
+        if self.shutting_down.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        for ctx in self.contexts.values() {
+            let _ = ctx.channels().shutdown_tx().send(());
+        }
+    }
+
+    /// Shut every group down as soon as one of them receives a shutdown signal
+    pub fn forward_shutdown(self: &Arc<Self>) {
+        for ctx in self.contexts.values() {
+            let mut shutdown_rx = ctx.channels().shutdown_rx();
+            let groups = self.clone();
+            tokio::spawn(async move {
+                if shutdown_rx.recv().await.is_ok() {
+                    groups.shutdown();
+                }
+            });
+        }
+    }
+}
+
+impl std::fmt::Debug for Groups {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Groups")
+            .field("groups", &self.order)
+            .finish()
+    }
+}
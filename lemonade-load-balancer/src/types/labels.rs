@@ -0,0 +1,65 @@
+//! Labels module
+//!
+//! Backend labels and the selectors that pick backend groups by label
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Backend labels (key to value)
+pub type Labels = BTreeMap<String, String>;
+
+/// Selects the backends whose labels contain every key/value pair
+///
+/// An empty selector matches every backend.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LabelSelector(Labels);
+
+impl LabelSelector {
+    /// Create a selector from the labels a backend must carry
+    pub fn new(labels: Labels) -> Self {
+        Self(labels)
+    }
+
+    /// Create a selector requiring a single label
+    pub fn single(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self(Labels::from([(key.into(), value.into())]))
+    }
+
+    /// Check whether the selector matches every backend
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Selector requiring the labels of both selectors, or `None` when they
+    /// require different values for the same key
+    pub fn and(&self, other: &LabelSelector) -> Option<LabelSelector> {
+        let mut labels = self.0.clone();
+        for (key, value) in &other.0 {
+            match labels.get(key) {
+                Some(existing) if existing != value => return None,
+                _ => {
+                    labels.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        Some(Self(labels))
+    }
+
+    /// Check whether a backend with these labels is selected
+    pub fn matches(&self, labels: &Labels) -> bool {
+        self.0
+            .iter()
+            .all(|(key, value)| labels.get(key) == Some(value))
+    }
+}
+
+impl std::fmt::Display for LabelSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pairs: Vec<String> = self
+            .0
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        write!(f, "{{{}}}", pairs.join(","))
+    }
+}
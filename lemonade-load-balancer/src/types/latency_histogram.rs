@@ -0,0 +1,197 @@
+//! Latency histogram module
+//!
+//! Lock-free fixed-bucket latency histogram used for per-backend timings
+use crate::prelude::*;
+
+/// Bucket upper bounds in microseconds (a final overflow bucket catches the rest)
+const BUCKET_BOUNDS_MICROS: [u64; 16] = [
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000,
+    500_000, 1_000_000, 2_500_000, 5_000_000, 10_000_000,
+];
+
+/// Number of buckets (bounds + overflow)
+const BUCKET_COUNT: usize = BUCKET_BOUNDS_MICROS.len() + 1;
+
+/// Latency histogram with atomic counters
+#[derive(Debug, Default)]
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKET_COUNT],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl LatencyHistogram {
+    /// Record a latency sample
+    pub fn record(&self, micros: u64) {
+        let index = BUCKET_BOUNDS_MICROS
+            .iter()
+            .position(|bound| micros <= *bound)
+            .unwrap_or(BUCKET_COUNT - 1);
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+
+    /// Take a point-in-time copy of the histogram
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        HistogramSnapshot {
+            buckets: self
+                .buckets
+                .iter()
+                .map(|bucket| bucket.load(Ordering::Relaxed))
+                .collect(),
+            count: self.count.load(Ordering::Relaxed),
+            sum_micros: self.sum_micros.load(Ordering::Relaxed),
+            percentiles: None,
+        }
+    }
+
+    /// Summarize the histogram without copying its buckets
+    pub fn summary(&self) -> LatencySummary {
+        let count = self.count.load(Ordering::Relaxed);
+        let sum_micros = self.sum_micros.load(Ordering::Relaxed);
+        let buckets = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed));
+        let mean_ms = mean_ms(count, sum_micros);
+        LatencySummary {
+            count,
+            mean_ms,
+            p95_ms: bucket_percentile_ms(buckets, count, 0.95).unwrap_or(mean_ms),
+        }
+    }
+}
+
+/// Sample count, mean and 95th percentile of a latency aggregate
+///
+/// Read from the live counters, so strategies can rank backends on every
+/// pick without the allocations of a [`HistogramSnapshot`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LatencySummary {
+    /// Total number of samples
+    pub count: u64,
+    /// Mean latency in milliseconds (0.0 when empty)
+    pub mean_ms: f64,
+    /// 95th percentile latency in milliseconds (0.0 when empty)
+    pub p95_ms: f64,
+}
+
+impl LatencySummary {
+    /// Check if the aggregate has no samples
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
+
+/// Mean of `count` samples summing to `sum_micros`, in milliseconds
+fn mean_ms(count: u64, sum_micros: u64) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+    sum_micros as f64 / count as f64 / 1000.0
+}
+
+/// Upper bound in milliseconds of the bucket holding the `quantile` sample
+/// (0.0 when empty, None when the buckets hold fewer than `count` samples)
+fn bucket_percentile_ms(
+    buckets: impl IntoIterator<Item = u64>,
+    count: u64,
+    quantile: f64,
+) -> Option<f64> {
+    if count == 0 {
+        return Some(0.0);
+    }
+    let rank = ((count as f64 * quantile.clamp(0.0, 1.0)).ceil() as u64).max(1);
+    let mut seen = 0;
+    for (index, bucket_count) in buckets.into_iter().enumerate() {
+        seen += bucket_count;
+        if seen >= rank {
+            let bound = BUCKET_BOUNDS_MICROS
+                .get(index)
+                .copied()
+                .unwrap_or(BUCKET_BOUNDS_MICROS[BUCKET_BOUNDS_MICROS.len() - 1]);
+            return Some(bound as f64 / 1000.0);
+        }
+    }
+    None
+}
+
+/// Point-in-time copy of a latency histogram
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistogramSnapshot {
+    /// Sample count per bucket (see [`HistogramSnapshot::bucket_bounds_micros`])
+    pub buckets: Vec<u64>,
+    /// Total number of samples
+    pub count: u64,
+    /// Sum of all samples in microseconds
+    pub sum_micros: u64,
+    /// Percentiles computed from sampled latencies (reservoir aggregation
+    /// only; bucket histograms derive them from the buckets)
+    pub percentiles: Option<LatencyPercentiles>,
+}
+
+impl HistogramSnapshot {
+    /// Bucket upper bounds in microseconds (the last bucket is unbounded)
+    pub fn bucket_bounds_micros() -> &'static [u64] {
+        &BUCKET_BOUNDS_MICROS
+    }
+
+    /// Check if the histogram has no samples
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Mean latency in milliseconds (0.0 when empty)
+    pub fn mean_ms(&self) -> f64 {
+        mean_ms(self.count, self.sum_micros)
+    }
+
+    /// Latency percentile in milliseconds, as the upper bound of the bucket
+    /// holding the `quantile` sample (0.0 when empty)
+    ///
+    /// Reservoir snapshots answer from their computed percentiles instead
+    /// (see [`LatencyPercentiles::at`]).
+    pub fn percentile_ms(&self, quantile: f64) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        if let Some(percentiles) = &self.percentiles {
+            return percentiles.at(quantile) as f64 / 1000.0;
+        }
+        bucket_percentile_ms(self.buckets.iter().copied(), self.count, quantile)
+            .unwrap_or_else(|| self.mean_ms())
+    }
+
+    /// Samples recorded since `earlier`, a previous snapshot of the same
+    /// histogram
+    ///
+    /// Reservoir snapshots keep their percentiles, which already cover
+    /// recent samples only.
+    pub fn since(&self, earlier: &HistogramSnapshot) -> HistogramSnapshot {
+        HistogramSnapshot {
+            buckets: self
+                .buckets
+                .iter()
+                .enumerate()
+                .map(|(index, count)| {
+                    count.saturating_sub(earlier.buckets.get(index).copied().unwrap_or(0))
+                })
+                .collect(),
+            count: self.count.saturating_sub(earlier.count),
+            sum_micros: self.sum_micros.saturating_sub(earlier.sum_micros),
+            percentiles: self.percentiles,
+        }
+    }
+}
+
+/// Connection timing histograms for a backend
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConnectionTimings {
+    /// Backend connect duration
+    pub connect: HistogramSnapshot,
+    /// Time from accepting the connection to the first byte from the backend
+    pub ttfb: HistogramSnapshot,
+    /// Total connection duration
+    pub total: HistogramSnapshot,
+}
@@ -0,0 +1,246 @@
+//! Latency reservoir module
+//!
+//! Fixed-size uniform sample of per-backend latencies (Vitter's algorithm R)
+//! kept with atomics only. Percentiles are computed from the sample on each
+//! metrics flush and published in the same [`HistogramSnapshot`] the bucket
+//! histograms produce, so readers do not depend on the aggregation mode.
+use crate::prelude::*;
+
+/// Samples kept per reservoir
+pub const RESERVOIR_CAPACITY: usize = 1024;
+
+/// Percentile slot value before the first flush
+const NO_PERCENTILE: u64 = u64::MAX;
+
+/// SplitMix64 increment, used to step the replacement draw state
+const SPLITMIX_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Latency percentiles computed from a reservoir, in microseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyPercentiles {
+    /// Median
+    pub p50_micros: u64,
+    /// 95th percentile
+    pub p95_micros: u64,
+    /// 99th percentile
+    pub p99_micros: u64,
+}
+
+impl LatencyPercentiles {
+    /// Compute the percentiles of `samples` by nearest rank (None when empty)
+    pub fn from_samples(mut samples: Vec<u64>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        let rank = |quantile: f64| {
+            let rank = ((samples.len() as f64 * quantile).ceil() as usize).max(1);
+            samples[rank - 1]
+        };
+        Some(Self {
+            p50_micros: rank(0.50),
+            p95_micros: rank(0.95),
+            p99_micros: rank(0.99),
+        })
+    }
+
+    /// Value for `quantile`, taken from the closest computed percentile at or
+    /// above it (p99 beyond the 95th)
+    pub fn at(&self, quantile: f64) -> u64 {
+        if quantile <= 0.50 {
+            self.p50_micros
+        } else if quantile <= 0.95 {
+            self.p95_micros
+        } else {
+            self.p99_micros
+        }
+    }
+}
+
+/// Lock-free latency reservoir
+///
+/// Each sample claims a stream position with one `fetch_add`; the first
+/// [`RESERVOIR_CAPACITY`] samples fill the reservoir and later ones replace a
+/// random slot with probability `capacity / position`. Slots hold the sample
+/// plus one, so a slot claimed but not yet written reads as empty.
+#[derive(Debug)]
+pub struct LatencyReservoir {
+    samples: Box<[AtomicU64]>,
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+    draw_state: AtomicU64,
+    percentiles: [AtomicU64; 3],
+}
+
+impl LatencyReservoir {
+    /// Create a reservoir keeping `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: (0..capacity).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+            draw_state: AtomicU64::new(0),
+            percentiles: std::array::from_fn(|_| AtomicU64::new(NO_PERCENTILE)),
+        }
+    }
+
+    /// Number of samples kept
+    #[cfg(feature = "test-util")]
+    pub fn capacity(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Record a latency sample
+    pub fn record(&self, micros: u64) {
+        let position = self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(micros, Ordering::Relaxed);
+        let capacity = self.samples.len() as u64;
+        let slot = if position < capacity {
+            position
+        } else {
+            self.draw_below(position + 1)
+        };
+        if let Some(sample) = self.samples.get(slot as usize) {
+            sample.store(micros.saturating_add(1), Ordering::Relaxed);
+        }
+    }
+
+    /// Compute the percentiles of the samples kept and publish them to
+    /// later snapshots
+    ///
+    /// Returns `None`, publishing nothing, while the reservoir is empty.
+    pub fn flush(&self) -> Option<LatencyPercentiles> {
+        let samples = self
+            .samples
+            .iter()
+            .filter_map(|sample| sample.load(Ordering::Relaxed).checked_sub(1))
+            .collect();
+        let percentiles = LatencyPercentiles::from_samples(samples)?;
+        let [p50, p95, p99] = &self.percentiles;
+        p50.store(percentiles.p50_micros, Ordering::Relaxed);
+        p95.store(percentiles.p95_micros, Ordering::Relaxed);
+        p99.store(percentiles.p99_micros, Ordering::Relaxed);
+        Some(percentiles)
+    }
+
+    /// Take a point-in-time copy with the percentiles of the last flush
+    ///
+    /// The snapshot has no buckets; before the first flush percentile
+    /// queries fall back to the mean.
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let [p50, p95, p99] = &self.percentiles;
+        let p50_micros = p50.load(Ordering::Relaxed);
+        HistogramSnapshot {
+            buckets: Vec::new(),
+            count: self.count.load(Ordering::Relaxed),
+            sum_micros: self.sum_micros.load(Ordering::Relaxed),
+            percentiles: (p50_micros != NO_PERCENTILE).then(|| LatencyPercentiles {
+                p50_micros,
+                p95_micros: p95.load(Ordering::Relaxed),
+                p99_micros: p99.load(Ordering::Relaxed),
+            }),
+        }
+    }
+
+    /// Summarize the reservoir with the 95th percentile of the last flush
+    ///
+    /// Before the first flush the percentile falls back to the mean.
+    pub fn summary(&self) -> LatencySummary {
+        let count = self.count.load(Ordering::Relaxed);
+        let sum_micros = self.sum_micros.load(Ordering::Relaxed);
+        let mean_ms = if count == 0 {
+            0.0
+        } else {
+            sum_micros as f64 / count as f64 / 1000.0
+        };
+        let [_, p95, _] = &self.percentiles;
+        let p95_micros = p95.load(Ordering::Relaxed);
+        LatencySummary {
+            count,
+            mean_ms,
+            p95_ms: if count == 0 || p95_micros == NO_PERCENTILE {
+                mean_ms
+            } else {
+                p95_micros as f64 / 1000.0
+            },
+        }
+    }
+
+    /// Uniform value in `[0, bound)` from a SplitMix64 stream stepped
+    /// atomically, so concurrent recorders never share a draw
+    fn draw_below(&self, bound: u64) -> u64 {
+        let mut z = self
+            .draw_state
+            .fetch_add(SPLITMIX_GAMMA, Ordering::Relaxed)
+            .wrapping_add(SPLITMIX_GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        ((u128::from(z) * u128::from(bound)) >> 64) as u64
+    }
+}
+
+impl Default for LatencyReservoir {
+    fn default() -> Self {
+        Self::new(RESERVOIR_CAPACITY)
+    }
+}
+
+/// Per-backend latency aggregate, in the configured [`LatencyAggregation`]
+#[derive(Debug)]
+pub enum LatencyRecorder {
+    /// Fixed-bucket histogram
+    Histogram(LatencyHistogram),
+    /// Sampled reservoir
+    Reservoir(LatencyReservoir),
+}
+
+impl LatencyRecorder {
+    /// Create an empty recorder for `aggregation`
+    pub fn new(aggregation: LatencyAggregation) -> Self {
+        match aggregation {
+            LatencyAggregation::Histogram => Self::Histogram(LatencyHistogram::default()),
+            LatencyAggregation::Reservoir => Self::Reservoir(LatencyReservoir::default()),
+        }
+    }
+
+    /// Aggregation mode of the recorder
+    pub fn aggregation(&self) -> LatencyAggregation {
+        match self {
+            Self::Histogram(_) => LatencyAggregation::Histogram,
+            Self::Reservoir(_) => LatencyAggregation::Reservoir,
+        }
+    }
+
+    /// Record a latency sample
+    pub fn record(&self, micros: u64) {
+        match self {
+            Self::Histogram(histogram) => histogram.record(micros),
+            Self::Reservoir(reservoir) => reservoir.record(micros),
+        }
+    }
+
+    /// Publish percentiles computed from the samples (no-op for histograms,
+    /// whose snapshots are always current)
+    pub fn flush(&self) {
+        if let Self::Reservoir(reservoir) = self {
+            reservoir.flush();
+        }
+    }
+
+    /// Take a point-in-time copy of the aggregate
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        match self {
+            Self::Histogram(histogram) => histogram.snapshot(),
+            Self::Reservoir(reservoir) => reservoir.snapshot(),
+        }
+    }
+
+    /// Summarize the aggregate without allocating
+    pub fn summary(&self) -> LatencySummary {
+        match self {
+            Self::Histogram(histogram) => histogram.summary(),
+            Self::Reservoir(reservoir) => reservoir.summary(),
+        }
+    }
+}
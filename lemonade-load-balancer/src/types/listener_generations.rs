@@ -0,0 +1,120 @@
+//! Listener generations module
+//!
+//! Tracks accepted connections per listener generation so connections
+//! accepted on a listener that was just replaced (listen address change) are
+//! still counted until they finish
+
+use crate::prelude::*;
+
+/// Listener generation number (incremented on every rebind)
+pub type ListenerGeneration = u64;
+
+/// Active connection counts per listener generation
+#[derive(Debug, Default)]
+pub struct ListenerGenerations {
+    /// Generation of the listener currently accepting connections
+    current: AtomicU64,
+    /// Active connections per generation (drained old generations are removed)
+    active: DashMap<ListenerGeneration, usize>,
+    /// Woken whenever a tracked connection finishes
+    notify: Arc<Notify>,
+}
+
+impl ListenerGenerations {
+    /// Create generation tracking, notifying `notify` when connections finish
+    pub fn new(notify: Arc<Notify>) -> Self {
+        Self {
+            current: AtomicU64::new(0),
+            active: DashMap::new(),
+            notify,
+        }
+    }
+
+    /// Generation of the listener currently accepting connections
+    pub fn current(&self) -> ListenerGeneration {
+        self.current.load(Ordering::Acquire)
+    }
+
+    /// Start a new generation for a freshly bound listener
+    ///
+    /// Returns the previous generation.
+    pub fn advance(&self) -> ListenerGeneration {
+        let previous = self.current.fetch_add(1, Ordering::AcqRel);
+        self.active.remove_if(&previous, |_, count| *count == 0);
+        previous
+    }
+
+    /// Track a connection accepted on the current listener
+    ///
+    /// The connection is counted until the returned guard is dropped.
+    pub fn track(self: &Arc<Self>) -> GenerationGuard {
+        let generation = self.current();
+        *self.active.entry(generation).or_insert(0) += 1;
+        GenerationGuard {
+            generations: self.clone(),
+            generation,
+        }
+    }
+
+    /// Active connections accepted on a generation
+    pub fn active(&self, generation: ListenerGeneration) -> usize {
+        self.active
+            .get(&generation)
+            .map(|count| *count)
+            .unwrap_or(0)
+    }
+
+    /// Active connections across all generations
+    pub fn total(&self) -> usize {
+        self.active.iter().map(|entry| *entry.value()).sum()
+    }
+
+    /// Active connection counts per generation, oldest first
+    ///
+    /// The current generation is always included.
+    pub fn snapshot(&self) -> Vec<(ListenerGeneration, usize)> {
+        let current = self.current();
+        let mut counts: Vec<(ListenerGeneration, usize)> = self
+            .active
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect();
+        if !counts.iter().any(|(generation, _)| *generation == current) {
+            counts.push((current, 0));
+        }
+        counts.sort_unstable_by_key(|(generation, _)| *generation);
+        counts
+    }
+
+    /// Stop counting a finished connection
+    fn release(&self, generation: ListenerGeneration) {
+        if let Some(mut count) = self.active.get_mut(&generation) {
+            *count = count.saturating_sub(1);
+        }
+        // Forget drained old generations
+        if generation != self.current() {
+            self.active.remove_if(&generation, |_, count| *count == 0);
+        }
+        self.notify.notify_waiters();
+    }
+}
+
+/// Keeps a connection counted in its listener generation while alive
+#[derive(Debug)]
+pub struct GenerationGuard {
+    generations: Arc<ListenerGenerations>,
+    generation: ListenerGeneration,
+}
+
+impl GenerationGuard {
+    /// Generation the connection was accepted on
+    pub fn generation(&self) -> ListenerGeneration {
+        self.generation
+    }
+}
+
+impl Drop for GenerationGuard {
+    fn drop(&mut self) {
+        self.generations.release(self.generation);
+    }
+}
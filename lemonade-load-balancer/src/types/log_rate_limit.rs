@@ -0,0 +1,116 @@
+//! Log rate limit module
+//!
+//! Caps repetitive warnings (no backend available, accept errors, proxy
+//! failures) so a failure storm cannot flood the logs. Each callsite, named
+//! by a static id, may log `burst` messages per window; the rest are counted
+//! and reported in a single "suppressed similar messages" line once the
+//! next window opens.
+use crate::prelude::*;
+use std::sync::atomic::AtomicU32;
+
+/// Counters of one callsite in the current window
+#[derive(Debug, Default)]
+struct CallsiteWindow {
+    /// Start of the window, in milliseconds
+    start_ms: AtomicU64,
+    /// Messages admitted in the window
+    logged: AtomicU32,
+    /// Messages dropped in the window
+    suppressed: AtomicU64,
+}
+
+/// Rate limiter for repetitive log messages, keyed by callsite id
+///
+/// Windows are atomics looked up by a `&'static str` key, so suppressing a
+/// message takes no allocation; only a callsite's first message inserts
+/// its window.
+#[derive(Debug, Default)]
+pub struct LogRateLimiter {
+    /// Windows per callsite
+    callsites: DashMap<&'static str, CallsiteWindow>,
+    /// Messages suppressed over the limiter's lifetime
+    suppressed_total: AtomicU64,
+}
+
+impl LogRateLimiter {
+    /// Create a limiter with no callsite seen yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Admit a message from `callsite` at `now_ms`
+    ///
+    /// Returns whether the message should be logged. Opening a new window
+    /// logs how many messages the previous one suppressed. Always admits
+    /// when `config` disables limiting.
+    pub fn admit(
+        &self,
+        callsite: &'static str,
+        now_ms: u64,
+        config: &LogRateLimitConfig,
+    ) -> bool {
+        if !config.is_enabled() {
+            return true;
+        }
+        match self.callsites.get(callsite) {
+            Some(window) => self.admit_in(callsite, &window, now_ms, config),
+            None => {
+                let window =
+                    self.callsites
+                        .entry(callsite)
+                        .or_insert_with(|| CallsiteWindow {
+                            start_ms: AtomicU64::new(now_ms),
+                            ..CallsiteWindow::default()
+                        });
+                self.admit_in(callsite, &window, now_ms, config)
+            }
+        }
+    }
+
+    /// Admit a message against `window`, rolling it over when it expired
+    fn admit_in(
+        &self,
+        callsite: &'static str,
+        window: &CallsiteWindow,
+        now_ms: u64,
+        config: &LogRateLimitConfig,
+    ) -> bool {
+        let start_ms = window.start_ms.load(Ordering::Acquire);
+        if now_ms.saturating_sub(start_ms) >= config.window_millis
+            && window
+                .start_ms
+                .compare_exchange(start_ms, now_ms, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            window.logged.store(0, Ordering::Release);
+            let suppressed = window.suppressed.swap(0, Ordering::AcqRel);
+            if suppressed > 0 {
+                tracing::warn!(
+                    callsite,
+                    suppressed,
+                    "Suppressed {} similar messages",
+                    suppressed
+                );
+            }
+        }
+
+        if window.logged.fetch_add(1, Ordering::AcqRel) < config.burst {
+            return true;
+        }
+        window.suppressed.fetch_add(1, Ordering::Relaxed);
+        self.suppressed_total.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    /// Messages from `callsite` suppressed in its current window
+    pub fn suppressed(&self, callsite: &'static str) -> u64 {
+        self.callsites
+            .get(callsite)
+            .map_or(0, |window| window.suppressed.load(Ordering::Relaxed))
+    }
+
+    /// Messages suppressed over the limiter's lifetime, across callsites
+    pub fn suppressed_total(&self) -> u64 {
+        self.suppressed_total.load(Ordering::Relaxed)
+    }
+}
@@ -46,10 +46,30 @@ impl MetricsSnapshot {
     pub fn remove(&self, backend_id: BackendId) {
         self.per_backend.remove(&backend_id);
     }
+
+    /// Drop the metrics of every backend not in `live`
+    ///
+    /// Returns the number of entries evicted. Entries are keyed by
+    /// [`BackendId`], so there are never more than 256 of them.
+    pub fn retain_backends(&self, live: &[BackendId]) -> usize {
+        let before = self.per_backend.len();
+        self.per_backend.retain(|id, _| live.contains(id));
+        before - self.per_backend.len()
+    }
+
+    /// Number of backends with metrics
+    pub fn len(&self) -> usize {
+        self.per_backend.len()
+    }
+
+    /// Check if no backend has metrics
+    pub fn is_empty(&self) -> bool {
+        self.per_backend.is_empty()
+    }
 }
 
 /// Backend performance struct
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct BackendMetrics {
     /// Average latency
     pub avg_latency_ms: f64,
@@ -59,4 +79,61 @@ pub struct BackendMetrics {
     pub error_rate: f32,
     /// Last updated timestamp
     pub last_updated_ms: u64,
+    /// Auto-weight multiplier applied to the configured weight
+    pub weight_multiplier: f64,
+    /// CPU usage reported by the worker on `/stats`, in percent of one core
+    pub cpu_percent: Option<f64>,
+    /// Resident memory reported by the worker on `/stats`, in bytes
+    pub rss_bytes: Option<u64>,
+    /// Connect, time-to-first-byte and total duration histograms
+    pub timings: ConnectionTimings,
+    /// Bytes per second received from the backend over the recent flush
+    /// intervals
+    pub bytes_in_rate: u64,
+    /// Bytes per second sent to the backend over the recent flush intervals
+    pub bytes_out_rate: u64,
+}
+
+impl BackendMetrics {
+    /// Response latency used by latency-aware strategies
+    ///
+    /// Prefers time-to-first-byte, then connect time, and falls back to the
+    /// request-level average when no connection timings were recorded.
+    pub fn response_latency_ms(&self) -> f64 {
+        if !self.timings.ttfb.is_empty() {
+            self.timings.ttfb.mean_ms()
+        } else if !self.timings.connect.is_empty() {
+            self.timings.connect.mean_ms()
+        } else {
+            self.avg_latency_ms
+        }
+    }
+
+    /// 95th percentile of the response latency (see [`Self::response_latency_ms`])
+    pub fn response_p95_latency_ms(&self) -> f64 {
+        if !self.timings.ttfb.is_empty() {
+            self.timings.ttfb.percentile_ms(0.95)
+        } else if !self.timings.connect.is_empty() {
+            self.timings.connect.percentile_ms(0.95)
+        } else {
+            self.p95_latency_ms
+        }
+    }
+}
+
+impl Default for BackendMetrics {
+    fn default() -> Self {
+        Self {
+            avg_latency_ms: 0.0,
+            p95_latency_ms: 0.0,
+            error_rate: 0.0,
+            last_updated_ms: 0,
+            weight_multiplier: 1.0,
+            cpu_percent: None,
+            rss_bytes: None,
+            timings: ConnectionTimings::default(),
+            bytes_in_rate: 0,
+            bytes_out_rate: 0,
+        }
+    }
 }
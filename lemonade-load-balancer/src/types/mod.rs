@@ -1,21 +1,81 @@
 //! Common module for the Load Balancer
 //!
 
+mod admin_state;
+mod affinity_table;
 mod backend;
 mod backend_address;
 mod backend_meta;
+mod backend_stream;
+mod byte_rate;
 mod channel_bundle;
+mod circuit_breaker;
+mod clock;
+mod connection_index;
 mod context;
+mod drain_policy;
+mod drain_progress;
+mod groups;
+mod labels;
+mod latency_histogram;
+mod latency_reservoir;
+mod listener_generations;
+mod log_rate_limit;
 mod metrics_registry;
+mod peer_info;
+mod pick_timings;
+mod readiness;
+mod request_meta;
+mod retry_budget;
+mod rng;
 mod route_table;
+mod unix_address;
 
 /// Backend identifier
 pub type BackendId = u8;
 
+pub use admin_state::{AdminState, AdminStateError};
+pub use affinity_table::AffinityTable;
+#[cfg(feature = "test-util")]
+pub use backend::SLOW_START_INITIAL_FACTOR;
 pub use backend::{Backend, BackendConfig};
-pub use backend_address::{BackendAddress, BackendAddressError};
+pub use backend_address::{
+    BackendAddress, BackendAddressError, BackendConnectError, set_dscp,
+};
 pub use backend_meta::BackendMeta;
+pub use backend_stream::BackendStream;
+#[cfg(feature = "test-util")]
+pub use byte_rate::BYTE_RATE_INTERVALS;
+pub use byte_rate::ByteRateWindow;
 pub use channel_bundle::ChannelBundle;
+pub use circuit_breaker::{CircuitBreaker, CircuitState};
+#[cfg(feature = "test-util")]
+pub use clock::MockClock;
+pub use clock::{Clock, SystemClock};
+pub use connection_index::ConnectionIndex;
 pub use context::{Context, ContextError};
+pub use drain_policy::{CloseReason, DrainPolicy, DrainPolicyError};
+pub use drain_progress::DrainProgress;
+pub use groups::Groups;
+pub use labels::{LabelSelector, Labels};
+pub use latency_histogram::{
+    ConnectionTimings, HistogramSnapshot, LatencyHistogram, LatencySummary,
+};
+pub use latency_reservoir::{LatencyPercentiles, LatencyRecorder};
+#[cfg(feature = "test-util")]
+pub use latency_reservoir::{LatencyReservoir, RESERVOIR_CAPACITY};
+#[cfg(feature = "test-util")]
+pub use listener_generations::GenerationGuard;
+pub use listener_generations::{ListenerGeneration, ListenerGenerations};
+pub use log_rate_limit::LogRateLimiter;
 pub use metrics_registry::{BackendMetrics, MetricsSnapshot};
-pub use route_table::RouteTable;
+pub use peer_info::PeerInfo;
+pub use pick_timings::PickTimings;
+pub use readiness::Readiness;
+pub use request_meta::{
+    DEFAULT_MAX_HEAD_BYTES, DEFAULT_PEEK_TIMEOUT_MILLIS, RequestHeadLimits, RequestMeta,
+};
+pub use retry_budget::RetryBudget;
+pub use rng::{RngProvider, RngStream};
+pub use route_table::{RouteTable, RouteTableError};
+pub use unix_address::UnixAddress;
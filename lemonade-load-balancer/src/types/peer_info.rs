@@ -0,0 +1,95 @@
+//! Peer info module
+//!
+//! What the load balancer knows about the client of a connection, gathered
+//! once at accept time and carried through connection handling, events and
+//! spans.
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::io;
+use tokio::net::TcpStream;
+
+/// Client side of a proxied connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    /// Address of the client (the immediate peer of the listener)
+    pub remote: SocketAddr,
+    /// Destination the client connected to before being redirected to the
+    /// listener, when read in transparent mode
+    pub original_dst: Option<SocketAddr>,
+    /// Whether `remote` came from a PROXY protocol header rather than the
+    /// socket
+    pub via_proxy_protocol: bool,
+}
+
+impl PeerInfo {
+    /// Peer info of a client at `remote`
+    pub fn new(remote: SocketAddr) -> Self {
+        Self {
+            remote,
+            original_dst: None,
+            via_proxy_protocol: false,
+        }
+    }
+
+    /// Peer info of a client on a Unix socket
+    ///
+    /// Such a client has no IP address; it is recorded as the unspecified
+    /// address (`0.0.0.0:0`).
+    pub fn unix() -> Self {
+        Self::new(SocketAddr::from(([0, 0, 0, 0], 0)))
+    }
+
+    /// Record the destination the client originally connected to
+    pub fn with_original_dst(mut self, original_dst: SocketAddr) -> Self {
+        self.original_dst = Some(original_dst);
+        self
+    }
+
+    /// Peer info of a connection accepted from `remote`
+    ///
+    /// With `transparent`, also reads the original destination off the
+    /// socket; a connection that was not redirected (or a platform without
+    /// `SO_ORIGINAL_DST`) leaves it unset.
+    pub fn accepted(stream: &TcpStream, remote: SocketAddr, transparent: bool) -> Self {
+        let peer = Self::new(remote);
+        if !transparent {
+            return peer;
+        }
+        match original_dst(stream) {
+            Ok(original_dst) => peer.with_original_dst(original_dst),
+            Err(e) => {
+                tracing::debug!("No original destination for client {}: {}", remote, e);
+                peer
+            }
+        }
+    }
+}
+
+/// Destination `stream` was addressed to before an iptables `REDIRECT` or
+/// `TPROXY` rule sent it to this socket
+#[cfg(target_os = "linux")]
+fn original_dst(stream: &TcpStream) -> io::Result<SocketAddr> {
+    let socket = socket2::SockRef::from(stream);
+    let address = if stream.local_addr()?.is_ipv4() {
+        socket.original_dst_v4()?
+    } else {
+        socket.original_dst_v6()?
+    };
+    address.as_socket().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "original destination is not an IP address",
+        )
+    })
+}
+
+/// Destination `stream` was addressed to before being redirected
+///
+/// `SO_ORIGINAL_DST` is Linux only.
+#[cfg(not(target_os = "linux"))]
+fn original_dst(_stream: &TcpStream) -> io::Result<SocketAddr> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "SO_ORIGINAL_DST is only available on Linux",
+    ))
+}
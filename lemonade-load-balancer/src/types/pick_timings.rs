@@ -0,0 +1,41 @@
+//! Pick timings module
+//!
+//! Time spent in strategy selection, per strategy. Every accepted connection
+//! waits for a pick, so a slow strategy adds its pick time to each one.
+use crate::prelude::*;
+use std::collections::BTreeMap;
+
+/// Strategy pick duration histograms, keyed by strategy name
+#[derive(Debug, Default)]
+pub struct PickTimings {
+    per_strategy: DashMap<String, LatencyHistogram>,
+}
+
+impl PickTimings {
+    /// Record how long a pick by `strategy` took
+    pub fn record(&self, strategy: &str, micros: u64) {
+        if let Some(histogram) = self.per_strategy.get(strategy) {
+            histogram.record(micros);
+            return;
+        }
+        self.per_strategy
+            .entry(strategy.to_string())
+            .or_default()
+            .record(micros);
+    }
+
+    /// Pick durations of `strategy`, if it picked at all
+    pub fn get(&self, strategy: &str) -> Option<HistogramSnapshot> {
+        self.per_strategy
+            .get(strategy)
+            .map(|histogram| histogram.snapshot())
+    }
+
+    /// Pick durations of every strategy that picked
+    pub fn snapshot(&self) -> BTreeMap<String, HistogramSnapshot> {
+        self.per_strategy
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().snapshot()))
+            .collect()
+    }
+}
@@ -0,0 +1,70 @@
+//! Readiness module
+//!
+//! Lifecycle flags used to answer liveness and readiness probes
+
+use crate::prelude::*;
+use std::sync::atomic::AtomicBool;
+use tokio::sync::Notify;
+
+/// Whether the initial configuration is committed, the proxy is accepting
+/// and the first health check has run
+#[derive(Debug, Default)]
+pub struct Readiness {
+    /// The context holds the initial configuration and backend set
+    config_committed: AtomicBool,
+    /// Wakes tasks waiting for the initial configuration commit
+    config_notify: Notify,
+    /// The accept loop is running on a bound listener
+    accepting: AtomicBool,
+    /// The initial health check of every backend has finished
+    health_checked: AtomicBool,
+}
+
+impl Readiness {
+    /// Record that the initial configuration and backend set are in place
+    pub fn mark_config_committed(&self) {
+        self.config_committed.store(true, Ordering::Release);
+        self.config_notify.notify_waiters();
+    }
+
+    /// Check whether the initial configuration is committed
+    pub fn is_config_committed(&self) -> bool {
+        self.config_committed.load(Ordering::Acquire)
+    }
+
+    /// Wait until the initial configuration is committed
+    ///
+    /// The proxy awaits this before binding, so the first accepted
+    /// connection never sees an empty route table.
+    pub async fn wait_config_committed(&self) {
+        loop {
+            let notified = self.config_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.is_config_committed() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Record whether the accept loop is running
+    pub fn set_accepting(&self, accepting: bool) {
+        self.accepting.store(accepting, Ordering::Release);
+    }
+
+    /// Check whether the accept loop is running
+    pub fn is_accepting(&self) -> bool {
+        self.accepting.load(Ordering::Acquire)
+    }
+
+    /// Record that the initial health check has finished
+    pub fn mark_health_checked(&self) {
+        self.health_checked.store(true, Ordering::Release);
+    }
+
+    /// Check whether the initial health check has finished
+    pub fn is_health_checked(&self) -> bool {
+        self.health_checked.load(Ordering::Acquire)
+    }
+}
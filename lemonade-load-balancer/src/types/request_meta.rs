@@ -0,0 +1,51 @@
+//! Request metadata module
+//!
+//! What the proxy read of a connection's first HTTP/1 request, for
+//! strategies that pick by request (see
+//! [`StrategyService::pick_backend_for_request`]).
+use crate::prelude::*;
+
+/// Request line and header fields of a connection's first HTTP/1 request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestMeta {
+    /// Request method
+    pub method: String,
+    /// Request path, without the query string or, for absolute-form
+    /// targets, the scheme and authority
+    pub path: String,
+    /// Header fields in request order, names lowercased
+    pub headers: Vec<(String, String)>,
+}
+
+impl RequestMeta {
+    /// Value of the first header field called `name` (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Default time allowed for a request head to arrive
+pub const DEFAULT_PEEK_TIMEOUT_MILLIS: u64 = 200;
+/// Default cap on the bytes read looking for a request head
+pub const DEFAULT_MAX_HEAD_BYTES: usize = 8 * 1024;
+
+/// How much of a connection the proxy reads looking for the request head
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestHeadLimits {
+    /// How long to wait for the whole head
+    pub timeout: Duration,
+    /// Most bytes read; longer heads are not parsed
+    pub max_bytes: usize,
+}
+
+impl Default for RequestHeadLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_millis(DEFAULT_PEEK_TIMEOUT_MILLIS),
+            max_bytes: DEFAULT_MAX_HEAD_BYTES,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Retry budget module
+//!
+//! Token bucket shared by every extra backend attempt (retries and hedges)
+//! of a backend group. Successful connects refill it by the configured
+//! ratio, so extra attempts stay proportional to successful traffic and
+//! stop once a struggling pool no longer produces successes.
+use crate::prelude::*;
+
+/// Fixed-point scale of the token count (thousandths of an attempt)
+const MILLIS_PER_TOKEN: u64 = 1_000;
+
+/// Token bucket for extra backend attempts
+#[derive(Debug)]
+pub struct RetryBudget {
+    /// Available attempts, in thousandths
+    milli_tokens: AtomicU64,
+    /// Extra attempts let through
+    spent: AtomicU64,
+    /// Extra attempts skipped for lack of budget
+    suppressed: AtomicU64,
+}
+
+impl RetryBudget {
+    /// Create a budget holding `tokens` attempts
+    pub fn new(tokens: u32) -> Self {
+        Self {
+            milli_tokens: AtomicU64::new(tokens as u64 * MILLIS_PER_TOKEN),
+            spent: AtomicU64::new(0),
+            suppressed: AtomicU64::new(0),
+        }
+    }
+
+    /// Earn `config.ratio` of an attempt for a successful connect
+    pub fn record_success(&self, config: &RetryBudgetConfig) {
+        let earned = (config.ratio * MILLIS_PER_TOKEN as f64) as u64;
+        let cap = config.max_tokens as u64 * MILLIS_PER_TOKEN;
+        let _ = self.milli_tokens.fetch_update(
+            Ordering::AcqRel,
+            Ordering::Acquire,
+            |tokens| {
+                let refilled = tokens.saturating_add(earned).min(cap);
+                (refilled != tokens).then_some(refilled)
+            },
+        );
+    }
+
+    /// Spend one attempt, counting it as suppressed if none is left
+    ///
+    /// Always succeeds when the budget is disabled.
+    pub fn try_spend(&self, config: &RetryBudgetConfig) -> bool {
+        let spent = !config.enabled
+            || self
+                .milli_tokens
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |tokens| {
+                    tokens.checked_sub(MILLIS_PER_TOKEN)
+                })
+                .is_ok();
+        if spent {
+            self.spent.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+        }
+        spent
+    }
+
+    /// Attempts currently available
+    pub fn tokens(&self) -> f64 {
+        self.milli_tokens.load(Ordering::Acquire) as f64 / MILLIS_PER_TOKEN as f64
+    }
+
+    /// Totals as (extra attempts let through, extra attempts suppressed)
+    pub fn totals(&self) -> (u64, u64) {
+        (
+            self.spent.load(Ordering::Relaxed),
+            self.suppressed.load(Ordering::Relaxed),
+        )
+    }
+}
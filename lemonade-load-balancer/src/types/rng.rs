@@ -0,0 +1,119 @@
+//! Rng module
+//!
+//! Randomness provider injected into the context so randomized components
+//! draw from reproducible streams. Every component asks for its own named
+//! stream, derived from the provider seed and the component name, so one
+//! consumer drawing more or fewer numbers never perturbs another.
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of seeded random streams, one per component
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RngProvider {
+    seed: u64,
+}
+
+impl RngProvider {
+    /// Create a provider from a fixed seed, or from entropy without one
+    pub fn new(seed: Option<u64>) -> Self {
+        Self {
+            seed: seed.unwrap_or_else(Self::entropy),
+        }
+    }
+
+    /// Seed of the provider (log it to reproduce a run with `rng_seed`)
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Random stream for a component
+    ///
+    /// The same seed and component name always yield the same sequence.
+    pub fn stream(&self, component: &str) -> RngStream {
+        // FNV-1a over the name, so the mapping is stable across builds
+        let name_hash = component
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+                (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+            });
+        RngStream::from_seed(self.seed ^ name_hash)
+    }
+
+    /// Seed from the process' random hasher keys and the current time
+    fn entropy() -> u64 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+        );
+        hasher.finish()
+    }
+}
+
+impl Default for RngProvider {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+/// Xoshiro256** generator owned by a single call site
+///
+/// Shared call sites wrap their stream in a mutex; the state is small enough
+/// that the lock is held for a few instructions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RngStream {
+    state: [u64; 4],
+}
+
+impl RngStream {
+    /// Create a stream, expanding the seed with SplitMix64
+    pub fn from_seed(seed: u64) -> Self {
+        let mut x = seed;
+        let mut next = || {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        Self {
+            state: [next(), next(), next(), next()],
+        }
+    }
+
+    /// Next random 64-bit value
+    pub fn next_u64(&mut self) -> u64 {
+        let [s0, s1, s2, s3] = &mut self.state;
+        let result = s1.wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = *s1 << 17;
+        *s2 ^= *s0;
+        *s3 ^= *s1;
+        *s1 ^= *s2;
+        *s0 ^= *s3;
+        *s2 ^= t;
+        *s3 = s3.rotate_left(45);
+        result
+    }
+
+    /// Uniform value in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform value in `[0, bound)` (0 when `bound` is 0)
+    pub fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return 0;
+        }
+        // Multiply-shift keeps the bias below 2^-64 per draw
+        ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+    }
+
+    /// Whether an event of probability `p` happens (clamped to `[0, 1]`)
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p.clamp(0.0, 1.0)
+    }
+}
@@ -1,23 +1,173 @@
 //! Route table module
 //!
 use crate::prelude::*;
+use dashmap::mapref::entry::Entry;
+pub use error::RouteTableError;
 
 /// Route table struct
+///
+/// Backend lists and ids are returned in id order.
 #[derive(Debug, Default)]
 pub struct RouteTable {
     /// Backends (private for encapsulation)
     backends: DashMap<BackendId, Arc<Backend>>,
+    /// Whether several backends may share the same address
+    allow_duplicate_addresses: bool,
+    /// Connects in flight a backend takes before picks skip it (unlimited
+    /// if `None`)
+    max_pending_connects: Option<usize>,
+    /// Zone picks prefer (no preference if `None`)
+    local_zone: Option<String>,
+    /// Candidates in `local_zone` needed to keep picks in it
+    zone_spillover_min_healthy: usize,
+    /// Circuit breaker picks consult (skipped if `None`)
+    circuit_breaker: Option<PickCircuitBreaker>,
+}
+
+/// Circuit breaker config, with the clock its open durations run on
+#[derive(Clone)]
+struct PickCircuitBreaker {
+    config: CircuitBreakerConfig,
+    clock: Arc<dyn Clock>,
+}
+
+impl std::fmt::Debug for PickCircuitBreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PickCircuitBreaker")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
 }
 
 impl RouteTable {
     /// Create a new route table from backend configs
+    ///
+    /// No duplicate checks are performed: a later config with an already
+    /// used id replaces the earlier one. Use [`RouteTable::try_new`] to reject
+    /// conflicting configs.
     pub fn new(configs: Vec<BackendConfig>) -> Self {
         let map = DashMap::from_iter(
             configs
                 .into_iter()
                 .map(|config| (config.id, Arc::new(Backend::new(config)))),
         );
-        Self { backends: map }
+        Self {
+            backends: map,
+            allow_duplicate_addresses: false,
+            max_pending_connects: None,
+            local_zone: None,
+            zone_spillover_min_healthy: 1,
+            circuit_breaker: None,
+        }
+    }
+
+    /// Create an empty route table, choosing whether duplicate addresses are allowed
+    pub fn with_duplicate_addresses(allow_duplicate_addresses: bool) -> Self {
+        Self {
+            backends: DashMap::new(),
+            allow_duplicate_addresses,
+            max_pending_connects: None,
+            local_zone: None,
+            zone_spillover_min_healthy: 1,
+            circuit_breaker: None,
+        }
+    }
+
+    /// Skip backends with `max` connects in flight when picking
+    pub fn with_max_pending_connects(mut self, max: usize) -> Self {
+        self.max_pending_connects = Some(max);
+        self
+    }
+
+    /// Prefer backends in `zone` when picking, while at least
+    /// `spillover_min_healthy` of them are candidates (see
+    /// [`RouteTable::preferred_zone`])
+    pub fn with_local_zone(
+        mut self,
+        zone: Option<String>,
+        spillover_min_healthy: usize,
+    ) -> Self {
+        self.local_zone = zone;
+        self.zone_spillover_min_healthy = spillover_min_healthy;
+        self
+    }
+
+    /// Skip backends whose circuit is open when picking, with open
+    /// durations measured on `clock` (a disabled breaker skips none)
+    pub fn with_circuit_breaker(
+        mut self,
+        config: CircuitBreakerConfig,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        self.circuit_breaker = config
+            .enabled
+            .then_some(PickCircuitBreaker { config, clock });
+        self
+    }
+
+    /// Create a new route table from backend configs, rejecting conflicts
+    pub fn try_new(
+        configs: Vec<BackendConfig>,
+        allow_duplicate_addresses: bool,
+    ) -> Result<Self, RouteTableError> {
+        Self::try_with_latency_aggregation(
+            configs,
+            allow_duplicate_addresses,
+            LatencyAggregation::default(),
+        )
+    }
+
+    /// Create a new route table from backend configs, rejecting conflicts,
+    /// whose backends aggregate connection timings in `aggregation`
+    pub fn try_with_latency_aggregation(
+        configs: Vec<BackendConfig>,
+        allow_duplicate_addresses: bool,
+        aggregation: LatencyAggregation,
+    ) -> Result<Self, RouteTableError> {
+        let table = Self::with_duplicate_addresses(allow_duplicate_addresses);
+        for config in configs {
+            table.insert(Arc::new(Backend::with_latency_aggregation(
+                config,
+                aggregation,
+            )))?;
+        }
+        Ok(table)
+    }
+
+    /// Check if duplicate addresses are allowed
+    pub fn allows_duplicate_addresses(&self) -> bool {
+        self.allow_duplicate_addresses
+    }
+
+    /// Get the cap on connects in flight per backend, if any
+    pub fn max_pending_connects(&self) -> Option<usize> {
+        self.max_pending_connects
+    }
+
+    /// Get the zone picks prefer, if any
+    pub fn local_zone(&self) -> Option<&str> {
+        self.local_zone.as_deref()
+    }
+
+    /// Check if `backend` can take another connect without reaching the cap
+    /// on connects in flight, and its circuit lets a connect through
+    pub fn has_connect_room(&self, backend: &Backend) -> bool {
+        self.max_pending_connects
+            .is_none_or(|max| backend.pending_connects() < max)
+            && self.circuit_breaker.as_ref().is_none_or(|breaker| {
+                backend
+                    .circuit()
+                    .allows(breaker.clock.now_millis(), &breaker.config)
+            })
+    }
+
+    /// Order listed backends by id
+    ///
+    /// The map iterates in a per-instance random order; listing by id keeps
+    /// picks reproducible across runs (see `rng_seed`).
+    fn by_id(mut backends: Vec<Arc<Backend>>) -> Vec<Arc<Backend>> {
+        backends.sort_unstable_by_key(|backend| backend.id());
+        backends
     }
 
     /// Get backend by id
@@ -27,45 +177,160 @@ impl RouteTable {
 
     /// Get all backends
     pub fn all_backends(&self) -> Vec<Arc<Backend>> {
-        self.backends
-            .iter()
-            .map(|entry| entry.value().clone())
-            .collect()
+        Self::by_id(
+            self.backends
+                .iter()
+                .map(|entry| entry.value().clone())
+                .collect(),
+        )
     }
 
-    /// Get healthy backends (alive && not draining)
+    /// Get healthy backends (alive, not draining and enabled by their admin
+    /// state)
     pub fn healthy_backends(&self) -> Vec<Arc<Backend>> {
+        Self::by_id(
+            self.backends
+                .iter()
+                .filter(|entry| entry.value().can_accept_new_connections())
+                .map(|entry| entry.value().clone())
+                .collect(),
+        )
+    }
+
+    /// Get healthy backends, only those in `zone` while at least the
+    /// spillover threshold of them are healthy
+    ///
+    /// Below the threshold (`proxy.zone_spillover_min_healthy`), the local
+    /// zone lacks capacity and every healthy backend is returned.
+    pub fn healthy_backends_preferring_zone(&self, zone: &str) -> Vec<Arc<Backend>> {
+        let mut healthy = self.healthy_backends();
+        self.keep_zone(&mut healthy, zone);
+        healthy
+    }
+
+    /// Get healthy backends selected by a label selector
+    pub fn healthy_backends_matching(
+        &self,
+        selector: &LabelSelector,
+    ) -> Vec<Arc<Backend>> {
+        Self::by_id(
+            self.backends
+                .iter()
+                .filter(|entry| {
+                    let backend = entry.value();
+                    backend.can_accept_new_connections() && backend.matches(selector)
+                })
+                .map(|entry| entry.value().clone())
+                .collect(),
+        )
+    }
+
+    /// Get the most preferred priority tier with a healthy backend selected
+    /// by a label selector
+    ///
+    /// Strategies only pick from this tier: backends of higher tiers are
+    /// backups that take traffic once the tiers before them have no healthy
+    /// backend left, and hand it back as soon as one recovers.
+    pub fn active_priority(&self, selector: &LabelSelector) -> Option<u8> {
         self.backends
             .iter()
             .filter(|entry| {
                 let backend = entry.value();
-                backend.is_alive() && backend.is_active()
+                backend.can_accept_new_connections() && backend.matches(selector)
             })
-            .map(|entry| entry.value().clone())
-            .collect()
+            .map(|entry| entry.value().priority())
+            .min()
+    }
+
+    /// Get the zone picks under a label selector are kept to, if any
+    ///
+    /// With a local zone, picks stay in it while it has at least
+    /// `zone_spillover_min_healthy` healthy backends selected by `selector`,
+    /// in the active priority tier and with room for another connect. Below
+    /// that, the zone lacks capacity and picks spill over to every zone.
+    pub fn preferred_zone(&self, selector: &LabelSelector) -> Option<&str> {
+        let zone = self.local_zone.as_deref()?;
+        let priority = self.active_priority(selector)?;
+        let local = self
+            .backends
+            .iter()
+            .filter(|entry| {
+                let backend = entry.value();
+                backend.zone() == Some(zone)
+                    && backend.can_accept_new_connections()
+                    && backend.matches(selector)
+                    && backend.priority() == priority
+                    && self.has_connect_room(backend)
+            })
+            .count();
+        (local >= self.zone_spillover_min_healthy).then_some(zone)
+    }
+
+    /// Get healthy backends selected by a label selector, in the most
+    /// preferred priority tier that has any (see
+    /// [`RouteTable::active_priority`]), that have room for another connect,
+    /// in the local zone unless it lacks capacity (see
+    /// [`RouteTable::preferred_zone`])
+    ///
+    /// Backends at the cap on connects in flight are skipped without
+    /// failing over to a backup tier: once a whole tier is saturated, picks
+    /// find no backend.
+    pub fn preferred_backends_matching(
+        &self,
+        selector: &LabelSelector,
+    ) -> Vec<Arc<Backend>> {
+        let mut healthy = self.healthy_backends_matching(selector);
+        if let Some(priority) = healthy.iter().map(|backend| backend.priority()).min() {
+            healthy.retain(|backend| {
+                backend.priority() == priority && self.has_connect_room(backend)
+            });
+        }
+        if let Some(zone) = self.local_zone.as_deref() {
+            self.keep_zone(&mut healthy, zone);
+        }
+        healthy
+    }
+
+    /// Keep only the backends in `zone`, if at least the spillover threshold
+    /// of them are listed
+    fn keep_zone(&self, backends: &mut Vec<Arc<Backend>>, zone: &str) {
+        let local = backends
+            .iter()
+            .filter(|backend| backend.zone() == Some(zone))
+            .count();
+        if local >= self.zone_spillover_min_healthy {
+            backends.retain(|backend| backend.zone() == Some(zone));
+        }
     }
 
     /// Get active backends (not draining)
     pub fn active_backends(&self) -> Vec<Arc<Backend>> {
-        self.backends
-            .iter()
-            .filter(|entry| entry.value().is_active())
-            .map(|entry| entry.value().clone())
-            .collect()
+        Self::by_id(
+            self.backends
+                .iter()
+                .filter(|entry| entry.value().is_active())
+                .map(|entry| entry.value().clone())
+                .collect(),
+        )
     }
 
     /// Get draining backends
     pub fn draining_backends(&self) -> Vec<Arc<Backend>> {
-        self.backends
-            .iter()
-            .filter(|entry| entry.value().is_draining())
-            .map(|entry| entry.value().clone())
-            .collect()
+        Self::by_id(
+            self.backends
+                .iter()
+                .filter(|entry| entry.value().is_draining())
+                .map(|entry| entry.value().clone())
+                .collect(),
+        )
     }
 
     /// Get all backend IDs
     pub fn backend_ids(&self) -> Vec<BackendId> {
-        self.backends.iter().map(|entry| *entry.key()).collect()
+        let mut ids: Vec<BackendId> =
+            self.backends.iter().map(|entry| *entry.key()).collect();
+        ids.sort_unstable();
+        ids
     }
 
     /// Get number of backends
@@ -78,9 +343,60 @@ impl RouteTable {
         self.backends.is_empty()
     }
 
+    /// Find backend by socket address
+    pub fn find_by_address(&self, address: SocketAddr) -> Option<Arc<Backend>> {
+        self.backends
+            .iter()
+            .find(|entry| {
+                entry.value().address().as_str().parse::<SocketAddr>().ok()
+                    == Some(address)
+            })
+            .map(|entry| entry.value().clone())
+    }
+
     /// Insert a backend
-    pub fn insert(&self, backend: Arc<Backend>) {
-        self.backends.insert(backend.id(), backend);
+    ///
+    /// Fails with [`RouteTableError::DuplicateId`] if the id is already used,
+    /// and with [`RouteTableError::DuplicateAddress`] if another backend has
+    /// the same address (unless duplicate addresses are allowed).
+    pub fn insert(&self, backend: Arc<Backend>) -> Result<(), RouteTableError> {
+        let id = backend.id();
+        // Check addresses before taking the entry lock (iterating while holding
+        // a shard lock would deadlock)
+        if !self.allow_duplicate_addresses
+            && let Some(existing) = self.backends.iter().find(|entry| {
+                *entry.key() != id && entry.value().address() == backend.address()
+            })
+        {
+            return Err(RouteTableError::DuplicateAddress {
+                address: backend.address().to_string(),
+                existing: *existing.key(),
+            });
+        }
+
+        match self.backends.entry(id) {
+            Entry::Occupied(_) => Err(RouteTableError::DuplicateId(id)),
+            Entry::Vacant(entry) => {
+                entry.insert(backend);
+                Ok(())
+            }
+        }
+    }
+
+    /// Insert a backend about to move to a new address, without checking
+    /// the address it is leaving for duplicates
+    ///
+    /// For migrations, whose new addresses were validated together: insert
+    /// these after every other backend, so none is checked against an address
+    /// being left.
+    pub fn insert_moving(&self, backend: Arc<Backend>) -> Result<(), RouteTableError> {
+        match self.backends.entry(backend.id()) {
+            Entry::Occupied(_) => Err(RouteTableError::DuplicateId(backend.id())),
+            Entry::Vacant(entry) => {
+                entry.insert(backend);
+                Ok(())
+            }
+        }
     }
 
     /// Remove a backend by id
@@ -99,3 +415,27 @@ impl RouteTable {
         self.backends.iter().position(|entry| *entry.key() == id)
     }
 }
+
+mod error {
+    //! Route table error module
+    //!
+    use crate::prelude::*;
+
+    /// Route table error enum
+    #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+    pub enum RouteTableError {
+        /// A backend with the same id is already registered
+        #[error("duplicate backend id: {0}")]
+        DuplicateId(BackendId),
+        /// A backend with the same address is already registered
+        #[error(
+            "duplicate backend address {address} (already used by backend {existing})"
+        )]
+        DuplicateAddress {
+            /// Conflicting address
+            address: String,
+            /// Id of the backend already using the address
+            existing: BackendId,
+        },
+    }
+}
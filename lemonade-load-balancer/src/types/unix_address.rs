@@ -0,0 +1,155 @@
+//! Unix address module
+//!
+//! Unix domain socket addresses for listeners and backends on the same host:
+//! `unix:/path/to.sock`, or `unix:@name` for an abstract socket (Linux only).
+//! Listening on one is compiled out on other platforms, and connecting to
+//! one fails with `Unsupported`.
+use crate::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::io;
+use std::path::PathBuf;
+
+/// Unix socket address
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UnixAddress {
+    /// Socket file at a filesystem path
+    Path(PathBuf),
+    /// Abstract socket name (Linux only)
+    Abstract(String),
+}
+
+impl UnixAddress {
+    /// Prefix of Unix socket addresses
+    pub const PREFIX: &'static str = "unix:";
+
+    /// Parse `unix:/path` or `unix:@name`
+    pub fn parse(addr: &str) -> Result<Self, BackendAddressError> {
+        let invalid = || BackendAddressError::InvalidFormat(addr.to_string());
+        let rest = addr.strip_prefix(Self::PREFIX).ok_or_else(invalid)?;
+        match rest.strip_prefix('@') {
+            Some("") => Err(invalid()),
+            Some(name) => Ok(Self::Abstract(name.to_string())),
+            None if rest.is_empty() => Err(invalid()),
+            None => Ok(Self::Path(PathBuf::from(rest))),
+        }
+    }
+
+    /// Whether `addr` is written as a Unix socket address
+    pub fn is_unix(addr: &str) -> bool {
+        addr.starts_with(Self::PREFIX)
+    }
+
+    /// Listen on the socket
+    ///
+    /// A socket file left at the path by a previous run is removed first;
+    /// any other file there makes the bind fail.
+    #[cfg(unix)]
+    pub fn bind(&self) -> io::Result<tokio::net::UnixListener> {
+        let listener = match self {
+            Self::Path(path) => {
+                remove_stale_socket(path)?;
+                std::os::unix::net::UnixListener::bind(path)?
+            }
+            Self::Abstract(name) => {
+                std::os::unix::net::UnixListener::bind_addr(&abstract_addr(name)?)?
+            }
+        };
+        listener.set_nonblocking(true)?;
+        tokio::net::UnixListener::from_std(listener)
+    }
+
+    /// Open a connection to the socket
+    #[cfg(unix)]
+    pub async fn connect(&self) -> io::Result<BackendStream> {
+        let stream = match self {
+            Self::Path(path) => tokio::net::UnixStream::connect(path).await?,
+            Self::Abstract(name) => {
+                let stream =
+                    std::os::unix::net::UnixStream::connect_addr(&abstract_addr(name)?)?;
+                stream.set_nonblocking(true)?;
+                tokio::net::UnixStream::from_std(stream)?
+            }
+        };
+        Ok(BackendStream::Unix(stream))
+    }
+
+    /// Open a connection to the socket (unsupported on this platform)
+    #[cfg(not(unix))]
+    pub async fn connect(&self) -> io::Result<BackendStream> {
+        Err(self.unsupported())
+    }
+
+    /// Remove the socket file, if the socket has one
+    ///
+    /// Called once the listener is closed, so the path can be bound again.
+    pub fn remove_file(&self) {
+        if let Self::Path(path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    /// Error for a Unix socket on a platform without them
+    #[cfg(not(unix))]
+    fn unsupported(&self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} needs Unix socket support", self),
+        )
+    }
+}
+
+/// Remove a socket file left at `path`, leaving other files alone
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
+
+/// Socket address of the abstract socket `name`
+#[cfg(target_os = "linux")]
+fn abstract_addr(name: &str) -> io::Result<std::os::unix::net::SocketAddr> {
+    use std::os::linux::net::SocketAddrExt;
+    std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())
+}
+
+/// Socket address of the abstract socket `name`
+///
+/// Abstract sockets are Linux only.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn abstract_addr(name: &str) -> io::Result<std::os::unix::net::SocketAddr> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("abstract Unix socket @{} needs Linux", name),
+    ))
+}
+
+impl std::fmt::Display for UnixAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Path(path) => write!(f, "{}{}", Self::PREFIX, path.display()),
+            Self::Abstract(name) => write!(f, "{}@{}", Self::PREFIX, name),
+        }
+    }
+}
+
+impl Serialize for UnixAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnixAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        UnixAddress::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
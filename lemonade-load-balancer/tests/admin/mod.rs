@@ -0,0 +1,7 @@
+//! Admin module tests
+//!
+//! Tests for admin API authentication and the admin server
+
+mod test_auth;
+mod test_models;
+mod test_server;
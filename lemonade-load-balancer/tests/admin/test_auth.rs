@@ -0,0 +1,79 @@
+//! Tests for AdminAuth
+
+use lemonade_load_balancer::prelude::*;
+
+fn admin_config(token: Option<&str>, read_only: bool) -> AdminConfig {
+    AdminConfig {
+        token: token.map(str::to_string),
+        read_only,
+        ..AdminConfig::default()
+    }
+}
+
+#[test]
+fn admin_auth_without_token_allows_requests_should_succeed() {
+    // Given: an admin config without a token
+    let config = admin_config(None, false);
+    let auth = AdminAuth::new(&config);
+
+    // When: authorizing read and write requests without credentials
+    // Then: both are allowed
+    assert!(auth.authorize(None, false).is_ok());
+    assert!(auth.authorize(None, true).is_ok());
+}
+
+#[test]
+fn admin_auth_with_token_requires_bearer_should_succeed() {
+    // Given: an admin config with a token
+    let config = admin_config(Some("s3cret"), false);
+    let auth = AdminAuth::new(&config);
+
+    // When: authorizing with missing, wrong, close and correct credentials
+    // Then: only the exact bearer token is accepted
+    assert!(matches!(
+        auth.authorize(None, false),
+        Err(AdminError::Unauthorized)
+    ));
+    assert!(matches!(
+        auth.authorize(Some("Bearer nope"), false),
+        Err(AdminError::Unauthorized)
+    ));
+    assert!(matches!(
+        auth.authorize(Some("Bearer s3cre"), false),
+        Err(AdminError::Unauthorized)
+    ));
+    assert!(matches!(
+        auth.authorize(Some("Basic s3cret"), false),
+        Err(AdminError::Unauthorized)
+    ));
+    assert!(auth.authorize(Some("Bearer s3cret"), true).is_ok());
+}
+
+#[test]
+fn admin_auth_read_only_rejects_mutations_should_succeed() {
+    // Given: a read-only admin config with a token
+    let config = admin_config(Some("s3cret"), true);
+    let auth = AdminAuth::new(&config);
+
+    // When: authorizing reads and writes
+    // Then: reads pass, writes are rejected, bad credentials are still 401 first
+    assert!(auth.authorize(Some("Bearer s3cret"), false).is_ok());
+    assert!(matches!(
+        auth.authorize(Some("Bearer s3cret"), true),
+        Err(AdminError::ReadOnly)
+    ));
+    assert!(matches!(
+        auth.authorize(None, true),
+        Err(AdminError::Unauthorized)
+    ));
+}
+
+#[test]
+fn constant_time_eq_should_succeed() {
+    assert!(constant_time_eq(b"token", b"token"));
+    assert!(!constant_time_eq(b"tokem", b"token"));
+    assert!(!constant_time_eq(b"tok", b"token"));
+    assert!(!constant_time_eq(b"token-and-more", b"token"));
+    assert!(!constant_time_eq(b"", b"token"));
+    assert!(constant_time_eq(b"", b""));
+}
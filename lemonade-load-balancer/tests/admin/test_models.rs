@@ -0,0 +1,57 @@
+//! Tests for AdminConfig
+
+use lemonade_load_balancer::prelude::*;
+
+#[test]
+fn admin_config_default_binds_loopback_should_succeed() {
+    // Given: the default admin config
+    let config = AdminConfig::default();
+
+    // Then: the API is disabled, loopback-only and writable
+    assert!(!config.enabled);
+    assert!(config.listen_address.ip().is_loopback());
+    assert!(config.token.is_none());
+    assert!(!config.read_only);
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn admin_config_remote_without_token_should_fail() {
+    // Given: an enabled admin API on a non-loopback address without a token
+    let config = AdminConfig {
+        enabled: true,
+        listen_address: "0.0.0.0:9090".parse().unwrap(),
+        ..AdminConfig::default()
+    };
+
+    // When: validating
+    // Then: it is rejected
+    assert!(matches!(config.validate(), Err(ConfigError::Admin(_))));
+
+    // And: a token makes it valid
+    let config = AdminConfig {
+        token: Some("s3cret".to_string()),
+        ..config
+    };
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn admin_config_empty_token_should_fail() {
+    let config = AdminConfig {
+        token: Some(String::new()),
+        ..AdminConfig::default()
+    };
+    assert!(matches!(config.validate(), Err(ConfigError::Admin(_))));
+}
+
+#[test]
+fn admin_config_debug_redacts_token_should_succeed() {
+    let config = AdminConfig {
+        token: Some("s3cret".to_string()),
+        ..AdminConfig::default()
+    };
+    let debug = format!("{:?}", config);
+    assert!(!debug.contains("s3cret"));
+    assert!(debug.contains("<redacted>"));
+}
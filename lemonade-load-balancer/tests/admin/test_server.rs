@@ -0,0 +1,589 @@
+//! Tests for AdminServer
+
+use lemonade_load_balancer::prelude::*;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::audit::test_log::read_records;
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Start an admin server for a context with two backends
+async fn start_admin(admin: AdminConfig) -> (SocketAddr, Arc<Context>) {
+    start_admin_with_audit(admin, AuditConfig::default()).await
+}
+
+/// Start an admin server auditing with the given config
+async fn start_admin_with_audit(
+    admin: AdminConfig,
+    audit: AuditConfig,
+) -> (SocketAddr, Arc<Context>) {
+    let mut config = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    config.admin = admin;
+    config.audit = audit;
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind admin listener");
+    let addr = listener.local_addr().expect("admin address");
+    tokio::spawn({
+        let ctx = ctx.clone();
+        async move { AdminServer::new(None).serve(listener, ctx).await }
+    });
+    (addr, ctx)
+}
+
+/// Send a raw HTTP/1.1 request and return the status code and body
+async fn send(
+    addr: SocketAddr,
+    method: &str,
+    path: &str,
+    token: Option<&str>,
+) -> (u16, String) {
+    send_body(addr, method, path, token, "").await
+}
+
+/// Send a raw HTTP/1.1 request with a body and return the status code and body
+async fn send_body(
+    addr: SocketAddr,
+    method: &str,
+    path: &str,
+    token: Option<&str>,
+    body: &str,
+) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr)
+        .await
+        .expect("Failed to connect to admin API");
+    let auth = token
+        .map(|token| format!("Authorization: Bearer {}\r\n", token))
+        .unwrap_or_default();
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\n{auth}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .expect("Failed to write request");
+    let mut response = String::new();
+    tokio::time::timeout(Duration::from_secs(2), stream.read_to_string(&mut response))
+        .await
+        .expect("Admin API response timed out")
+        .expect("Failed to read response");
+
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .expect("Malformed status line");
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default();
+    (status, body)
+}
+
+#[tokio::test]
+async fn admin_server_without_token_serves_status_should_succeed() {
+    // Given: an admin API without a token
+    let (addr, _ctx) = start_admin(AdminConfig::default()).await;
+
+    // When: requesting the status
+    let (status, body) = send(addr, "GET", "/status", None).await;
+
+    // Then: the backends are listed
+    assert_eq!(status, 200);
+    let json: serde_json::Value = serde_json::from_str(&body).expect("JSON body");
+    assert_eq!(json["backends"].as_array().map(Vec::len), Some(2));
+    assert_eq!(json["listener_generation"], 0);
+    assert_eq!(json["listener_generations"][0]["active_connections"], 0);
+}
+
+#[tokio::test]
+async fn admin_server_explains_adaptive_picks_should_succeed() {
+    // Given: an admin API over a round robin context
+    let (addr, ctx) = start_admin(AdminConfig::default()).await;
+
+    // When: explaining a pick of a strategy that does not score backends
+    let (status, body) = send(addr, "GET", "/strategy/explain", None).await;
+
+    // Then: nothing is explained
+    assert_eq!(status, 200);
+    let json: serde_json::Value = serde_json::from_str(&body).expect("JSON body");
+    assert_eq!(json["strategy"], "round_robin");
+    assert_eq!(json["backends"], serde_json::json!([]));
+
+    // When: switching to the adaptive strategy and explaining again
+    let mut config = (*ctx.config()).clone();
+    config.strategy = Strategy::Adaptive;
+    ctx.migrate(config).await.expect("Failed to migrate");
+    let (status, body) = send(addr, "GET", "/strategy/explain", None).await;
+
+    // Then: every healthy backend has a score breakdown
+    assert_eq!(status, 200);
+    let json: serde_json::Value = serde_json::from_str(&body).expect("JSON body");
+    assert_eq!(json["strategy"], "adaptive");
+    let backends = json["backends"].as_array().expect("backends array");
+    assert_eq!(backends.len(), 2);
+    for breakdown in backends {
+        for field in [
+            "connection_score",
+            "latency_score",
+            "error_penalty",
+            "resource_score",
+            "weight_factor",
+            "score",
+        ] {
+            assert!(breakdown[field].is_number(), "{} missing", field);
+        }
+    }
+}
+
+#[tokio::test]
+async fn admin_server_status_reports_build_info_should_succeed() {
+    // Given: an admin API
+    let (addr, _ctx) = start_admin(AdminConfig::default()).await;
+
+    // When: requesting the status
+    let (_, body) = send(addr, "GET", "/status", None).await;
+
+    // Then: the build information matches the load balancer's
+    let json: serde_json::Value = serde_json::from_str(&body).expect("JSON body");
+    let build = lemonade_load_balancer::api::build_info();
+    assert!(!build.git_sha.is_empty());
+    assert!(!build.build_timestamp.is_empty());
+    assert!(!build.rustc_version.is_empty());
+    assert_eq!(json["build"]["version"], build.version);
+    assert_eq!(json["build"]["git_sha"], build.git_sha);
+    assert_eq!(json["build"]["build_timestamp"], build.build_timestamp);
+    assert_eq!(json["build"]["rustc_version"], build.rustc_version);
+    assert_eq!(json["build"]["features"], serde_json::json!(["test-util"]));
+}
+
+#[tokio::test]
+async fn admin_server_status_reports_drain_during_shutdown_should_succeed() {
+    // Given: a connection held on backend 1 when shutdown starts
+    let (addr, ctx) = start_admin(AdminConfig::default()).await;
+    let (_, idle_body) = send(addr, "GET", "/status", None).await;
+    let idle: serde_json::Value = serde_json::from_str(&idle_body).expect("JSON body");
+    assert!(idle["drain"].is_null());
+    ctx.routing_table().get(1).unwrap().increment_connection();
+    let _ = ctx.channels().shutdown_tx().send(());
+    let drain = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { ctx.wait_for_drain(Duration::from_millis(500)).await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // When: requesting the status while the drain waits
+    let (status, body) = send(addr, "GET", "/status", None).await;
+
+    // Then: the admin API is still up and reports the remaining connection
+    assert_eq!(status, 200);
+    let json: serde_json::Value = serde_json::from_str(&body).expect("JSON body");
+    assert_eq!(json["drain"]["remaining_total"], 1);
+    assert_eq!(json["drain"]["per_backend"]["1"], 1);
+
+    ctx.routing_table().get(1).unwrap().decrement_connection();
+    ctx.notify_connection_closed();
+    drain
+        .await
+        .expect("Drain task panicked")
+        .expect("Drain should complete");
+}
+
+#[tokio::test]
+async fn admin_server_with_token_rejects_bad_credentials_should_succeed() {
+    // Given: an admin API with a token
+    let (addr, ctx) = start_admin(AdminConfig {
+        token: Some("s3cret".to_string()),
+        ..AdminConfig::default()
+    })
+    .await;
+
+    // When: requesting without, with a wrong and with a near-miss token
+    // Then: every attempt gets the same 401
+    let (missing, missing_body) = send(addr, "GET", "/status", None).await;
+    let (wrong, wrong_body) = send(addr, "GET", "/status", Some("nope")).await;
+    let (close, close_body) =
+        send(addr, "POST", "/backends/0/drain", Some("s3cre")).await;
+    assert_eq!((missing, wrong, close), (401, 401, 401));
+    assert_eq!(missing_body, wrong_body);
+    assert_eq!(wrong_body, close_body);
+    assert!(!ctx.routing_table().get(0).unwrap().is_draining());
+
+    // And: the right token is accepted
+    let (status, _) = send(addr, "POST", "/backends/0/drain", Some("s3cret")).await;
+    assert_eq!(status, 200);
+    assert!(ctx.routing_table().get(0).unwrap().is_draining());
+}
+
+#[tokio::test]
+async fn admin_server_read_only_rejects_posts_should_succeed() {
+    // Given: a read-only admin API
+    let (addr, ctx) = start_admin(AdminConfig {
+        read_only: true,
+        ..AdminConfig::default()
+    })
+    .await;
+
+    // When: reading the status and attempting mutations
+    let (read, _) = send(addr, "GET", "/status", None).await;
+    let (drain, _) = send(addr, "POST", "/backends/1/drain", None).await;
+    let (shutdown, _) = send(addr, "POST", "/shutdown", None).await;
+
+    // Then: reads succeed and mutations are forbidden
+    assert_eq!(read, 200);
+    assert_eq!(drain, 403);
+    assert_eq!(shutdown, 403);
+    assert!(!ctx.routing_table().get(1).unwrap().is_draining());
+}
+
+#[tokio::test]
+async fn admin_server_unknown_routes_should_fail() {
+    // Given: an admin API without a config file
+    let (addr, _ctx) = start_admin(AdminConfig::default()).await;
+
+    // When: hitting an unknown route, an unknown backend and reload
+    let (unknown, _) = send(addr, "GET", "/nope", None).await;
+    let (backend, _) = send(addr, "POST", "/backends/42/drain", None).await;
+    let (reload, _) = send(addr, "POST", "/config/reload", None).await;
+
+    // Then: they are rejected without side effects
+    assert_eq!(unknown, 404);
+    assert_eq!(backend, 404);
+    assert_eq!(reload, 409);
+}
+
+#[tokio::test]
+async fn admin_server_shutdown_triggers_shutdown_should_succeed() {
+    // Given: an admin API and a shutdown listener
+    let (addr, ctx) = start_admin(AdminConfig::default()).await;
+    let mut shutdown_rx = ctx.channels().shutdown_rx();
+
+    // When: requesting a shutdown
+    let (status, _) = send(addr, "POST", "/shutdown", None).await;
+
+    // Then: the shutdown signal is broadcast
+    assert_eq!(status, 200);
+    tokio::time::timeout(Duration::from_secs(1), shutdown_rx.recv())
+        .await
+        .expect("Shutdown signal should be sent")
+        .expect("Shutdown channel should be open");
+}
+
+#[tokio::test]
+async fn admin_server_audits_mutations_should_succeed() {
+    // Given: a token-protected admin API auditing to a file
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let audit_file = temp_dir.path().join("audit.jsonl");
+    let (addr, _ctx) = start_admin_with_audit(
+        AdminConfig {
+            token: Some("s3cret".to_string()),
+            ..AdminConfig::default()
+        },
+        AuditConfig {
+            file: Some(audit_file.clone()),
+            ..AuditConfig::default()
+        },
+    )
+    .await;
+
+    // When: reading the status, draining with and without the token
+    let _ = send(addr, "GET", "/status", Some("s3cret")).await;
+    let (denied, _) = send(addr, "POST", "/backends/1/drain", Some("wrong")).await;
+    let (drained, _) = send(addr, "POST", "/backends/1/drain", Some("s3cret")).await;
+    assert_eq!((denied, drained), (401, 200));
+
+    // Then: reads are not audited, the rejected and applied drains are
+    let records = read_records(&audit_file);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].actor, "admin");
+    assert_eq!(records[0].action, AuditAction::BackendDrain);
+    assert_eq!(records[0].outcome, AuditOutcome::Rejected);
+    assert!(records[1].actor.starts_with("admin:"));
+    assert!(!records[1].actor.contains("s3cret"));
+    assert_eq!(records[1].target.as_deref(), Some("backend:1"));
+    assert_eq!(records[1].old.as_deref(), Some("active"));
+    assert_eq!(records[1].new.as_deref(), Some("draining"));
+    assert_eq!(records[1].outcome, AuditOutcome::Applied);
+}
+
+#[tokio::test]
+async fn admin_server_group_routes_should_succeed() {
+    // Given: an admin server over a default group and an "edge" group
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    config.groups.insert(
+        "edge".to_string(),
+        GroupConfig {
+            listen_address: "127.0.0.1:3100".parse().unwrap(),
+            strategy: Strategy::LeastConnections,
+            strategy_params: serde_json::Value::Null,
+            fallback: None,
+            backends: vec![
+                BackendConfig::from(create_test_backend(0, None, Some(10u8))),
+                BackendConfig::from(create_test_backend(1, None, Some(10u8))),
+            ],
+            health: None,
+        },
+    );
+    let groups = Arc::new(Groups::new(&config).expect("Failed to create groups"));
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind admin listener");
+    let addr = listener.local_addr().expect("admin address");
+    tokio::spawn({
+        let server = AdminServer::new(None).with_groups(groups.clone());
+        let ctx = groups.primary();
+        async move { server.serve(listener, ctx).await }
+    });
+
+    // When: listing the groups and draining a backend of the edge group
+    let (listed, body) = send(addr, "GET", "/groups", None).await;
+    let (drained, _) = send(addr, "POST", "/groups/edge/backends/1/drain", None).await;
+    let (edge_status, edge_body) = send(addr, "GET", "/groups/edge/status", None).await;
+    let (unknown, _) = send(addr, "GET", "/groups/missing/status", None).await;
+
+    // Then: each group reports its own state
+    assert_eq!(
+        (listed, drained, edge_status, unknown),
+        (200, 200, 200, 404)
+    );
+    let groups_json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        groups_json["default"]["backends"].as_array().unwrap().len(),
+        1
+    );
+    assert_eq!(groups_json["edge"]["strategy"], "least_connections");
+    let edge_json: serde_json::Value = serde_json::from_str(&edge_body).unwrap();
+    assert_eq!(edge_json["group"], "edge");
+    assert_eq!(edge_json["backends"][1]["draining"], true);
+
+    // And: the default group's backend with the same id is untouched
+    let default = groups.get(DEFAULT_GROUP).unwrap();
+    assert!(!default.routing_table().get(0).unwrap().is_draining());
+}
+
+#[tokio::test]
+async fn admin_server_drain_policy_override_should_succeed() {
+    // Given: an admin API whose config keeps connections on drain
+    let (addr, ctx) = start_admin(AdminConfig::default()).await;
+    let routing = ctx.routing_table();
+
+    // When: draining one backend with the configured policy and one with an
+    // immediate override, and trying an invalid policy
+    let (kept, kept_body) = send(addr, "POST", "/backends/0/drain", None).await;
+    let (cut, cut_body) =
+        send(addr, "POST", "/backends/1/drain?policy=immediate", None).await;
+    let (invalid, _) =
+        send(addr, "POST", "/backends/0/drain?policy=eventually", None).await;
+
+    // Then: only the overridden backend's connections are closed
+    assert_eq!((kept, cut, invalid), (200, 200, 422));
+    assert!(kept_body.contains(r#""policy":"finish""#), "{}", kept_body);
+    assert!(cut_body.contains(r#""policy":"immediate""#), "{}", cut_body);
+    assert_eq!(*routing.get(0).unwrap().close_requested().borrow(), None);
+    assert_eq!(
+        *routing.get(1).unwrap().close_requested().borrow(),
+        Some(CloseReason::DrainImmediate)
+    );
+}
+
+#[tokio::test]
+async fn admin_server_patch_weight_updates_backend_in_place_should_succeed() {
+    // Given: an admin API and a backend with an open connection
+    let (addr, ctx) = start_admin(AdminConfig::default()).await;
+    let backend = ctx.routing_table().get(1).expect("backend 1");
+    backend.increment_connection();
+    let generation = ctx.generation();
+
+    // When: changing its weight
+    let (status, body) = send(addr, "PATCH", "/backends/1/weight?weight=30", None).await;
+
+    // Then: the same backend carries the new weight and keeps its connection
+    assert_eq!(status, 200, "{}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).expect("JSON body");
+    assert_eq!(json["weight"], 30);
+    let routed = ctx.routing_table().get(1).expect("backend 1");
+    assert!(Arc::ptr_eq(&backend, &routed));
+    assert_eq!(routed.weight(), Some(30));
+    assert_eq!(routed.active_connections(), 1);
+    assert!(!routed.is_draining());
+    assert_eq!(ctx.generation(), generation + 1);
+    assert_eq!(ctx.config().backends[1].weight, Some(30));
+
+    // When: clearing it, sending an invalid weight and targeting an unknown backend
+    let (cleared, _) = send(addr, "PATCH", "/backends/1/weight?weight=none", None).await;
+    let (invalid, _) = send(addr, "PATCH", "/backends/1/weight?weight=300", None).await;
+    let (missing, _) = send(addr, "PATCH", "/backends/1/weight", None).await;
+    let (unknown, _) = send(addr, "PATCH", "/backends/42/weight?weight=1", None).await;
+
+    // Then: only the valid request is applied
+    assert_eq!((cleared, invalid, missing, unknown), (200, 422, 422, 404));
+    assert_eq!(
+        ctx.routing_table().get(1).expect("backend 1").weight(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn admin_server_patch_admin_state_is_audited_should_succeed() {
+    // Given: an admin API auditing to a file
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let audit_file = temp_dir.path().join("audit.jsonl");
+    let (addr, ctx) = start_admin_with_audit(
+        AdminConfig::default(),
+        AuditConfig {
+            file: Some(audit_file.clone()),
+            ..AuditConfig::default()
+        },
+    )
+    .await;
+
+    // When: disabling a backend through the API, re-enabling it through the
+    // library, and sending an invalid state
+    let (status, body) = send(
+        addr,
+        "PATCH",
+        "/backends/1/admin-state?state=disabled",
+        None,
+    )
+    .await;
+    assert_eq!(status, 200, "{}", body);
+    assert!(body.contains(r#""admin_state":"disabled""#), "{}", body);
+    assert_eq!(
+        ctx.routing_table().get(1).unwrap().admin_state(),
+        AdminState::Disabled
+    );
+    ctx.set_admin_state(1, AdminState::Enabled);
+    let (invalid, _) =
+        send(addr, "PATCH", "/backends/1/admin-state?state=off", None).await;
+    let (unknown, _) = send(
+        addr,
+        "PATCH",
+        "/backends/42/admin-state?state=enabled",
+        None,
+    )
+    .await;
+    assert_eq!((invalid, unknown), (422, 404));
+
+    // Then: every change is audited with its old and new state, and the
+    // rejected requests too
+    let records = read_records(&audit_file);
+    assert_eq!(records.len(), 4);
+    assert!(
+        records
+            .iter()
+            .all(|record| record.action == AuditAction::BackendAdminState)
+    );
+    assert_eq!(records[0].actor, "admin");
+    assert_eq!(records[0].target.as_deref(), Some("backend:1"));
+    assert_eq!(records[0].old.as_deref(), Some("enabled"));
+    assert_eq!(records[0].new.as_deref(), Some("disabled"));
+    assert_eq!(records[1].actor, "api");
+    assert_eq!(records[1].old.as_deref(), Some("disabled"));
+    assert_eq!(records[1].new.as_deref(), Some("enabled"));
+    assert_eq!(records[2].outcome, AuditOutcome::Rejected);
+    assert_eq!(records[3].outcome, AuditOutcome::Rejected);
+}
+
+#[tokio::test]
+async fn admin_server_config_dry_run_reports_diff_without_applying_should_succeed() {
+    // Given: a read-only admin API and a candidate changing the strategy and
+    // adding a backend
+    let (addr, ctx) = start_admin(AdminConfig {
+        read_only: true,
+        ..AdminConfig::default()
+    })
+    .await;
+    let generation = ctx.generation();
+    let mut candidate = serde_json::to_value(&*ctx.config()).expect("Config as JSON");
+    candidate["strategy"] = serde_json::json!("least_connections");
+    candidate["backends"]
+        .as_array_mut()
+        .expect("Backend list")
+        .push(serde_json::json!({ "id": 2, "address": "127.0.0.1:8082" }));
+
+    // When: dry-running it
+    let (status, body) = send_body(
+        addr,
+        "POST",
+        "/config/dry-run",
+        None,
+        &candidate.to_string(),
+    )
+    .await;
+
+    // Then: the diff is reported and nothing is applied
+    assert_eq!(status, 200, "{}", body);
+    let report: DryRunReport = serde_json::from_str(&body).expect("Dry run report");
+    assert!(report.valid, "{:?}", report.errors);
+    let diff = &report.groups[DEFAULT_GROUP];
+    assert_eq!(diff.strategy, Some(Strategy::LeastConnections));
+    assert_eq!(diff.added_backends, vec![2]);
+    assert_eq!(ctx.generation(), generation);
+    assert_eq!(ctx.config().strategy, Strategy::RoundRobin);
+    assert_eq!(ctx.routing_table().len(), 2);
+}
+
+#[tokio::test]
+async fn admin_server_config_dry_run_reports_invalid_candidates_should_succeed() {
+    // Given: an admin API, a candidate with duplicate backend ids and one
+    // that is not a config at all
+    let (addr, ctx) = start_admin(AdminConfig::default()).await;
+    let generation = ctx.generation();
+    let mut duplicate = serde_json::to_value(&*ctx.config()).expect("Config as JSON");
+    duplicate["backends"]
+        .as_array_mut()
+        .expect("Backend list")
+        .push(serde_json::json!({ "id": 0, "address": "127.0.0.1:8082" }));
+
+    // When: dry-running them
+    let (status, body) = send_body(
+        addr,
+        "POST",
+        "/config/dry-run",
+        None,
+        &duplicate.to_string(),
+    )
+    .await;
+    let (garbled_status, garbled_body) = send_body(
+        addr,
+        "POST",
+        "/config/dry-run?format=toml",
+        None,
+        "strategy = [",
+    )
+    .await;
+    let (format_status, _) =
+        send_body(addr, "POST", "/config/dry-run?format=ini", None, "").await;
+
+    // Then: both are reported invalid with their errors, and nothing changes
+    assert_eq!(status, 200, "{}", body);
+    let report: DryRunReport = serde_json::from_str(&body).expect("Dry run report");
+    assert!(!report.valid);
+    assert!(
+        report.errors[0].contains("duplicate backend id"),
+        "{:?}",
+        report.errors
+    );
+    assert_eq!(garbled_status, 200, "{}", garbled_body);
+    let garbled: DryRunReport =
+        serde_json::from_str(&garbled_body).expect("Dry run report");
+    assert!(!garbled.valid);
+    assert_eq!(garbled.errors.len(), 1);
+    assert!(garbled.groups.is_empty());
+    assert_eq!(format_status, 422);
+    assert_eq!(ctx.generation(), generation);
+    assert_eq!(ctx.routing_table().len(), 2);
+}
@@ -0,0 +1,4 @@
+//! Public API module tests
+//!
+
+mod test_surface;
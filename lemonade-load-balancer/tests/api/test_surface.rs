@@ -0,0 +1,166 @@
+//! Public API surface tests
+//!
+//! This module tests:
+//! - The names exported by `api` against the reviewed snapshot
+//! - Embedding and extending the load balancer through `api` alone
+use lemonade_load_balancer::api::{self, *};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Source of the `api` module
+const API_SOURCE: &str = include_str!("../../src/api.rs");
+
+/// Reviewed export list, one name per line
+const API_SNAPSHOT: &str = include_str!("api.snapshot");
+
+/// Names exported by the `pub use` items of `source`
+///
+/// The last segment of every path in a use tree is exported; `a::b::{c, d}`
+/// exports `c` and `d`.
+fn exported_names(source: &str) -> BTreeSet<String> {
+    let code: String = source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut names = BTreeSet::new();
+    for item in code.split("pub use ").skip(1) {
+        let tree = item.split(';').next().unwrap_or_default();
+        let mut rest = tree;
+        while let Some(start) = rest.find(|c: char| c.is_alphanumeric() || c == '_') {
+            rest = &rest[start..];
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let (name, after) = rest.split_at(end);
+            if !after.trim_start().starts_with("::") {
+                names.insert(name.to_string());
+            }
+            rest = after;
+        }
+    }
+    names
+}
+
+#[test]
+fn api_surface_matches_snapshot_should_succeed() {
+    // Given: the reviewed snapshot
+    let expected: BTreeSet<String> = API_SNAPSHOT
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+
+    // When: collecting the names `api` exports
+    let exported = exported_names(API_SOURCE);
+
+    // Then: nothing was added or removed without updating the snapshot
+    let added: Vec<_> = exported.difference(&expected).collect();
+    let removed: Vec<_> = expected.difference(&exported).collect();
+    assert!(
+        added.is_empty() && removed.is_empty(),
+        "api surface changed (added: {:?}, removed: {:?}); review the change \
+         and update tests/api/api.snapshot",
+        added,
+        removed
+    );
+}
+
+#[test]
+fn api_exported_names_parse_use_trees_should_succeed() {
+    // Given: nested use trees with comments
+    let source = "// pub use ignored::Commented;\n\
+                  pub use crate::{a::First, b::{Second, c::Third}};\n\
+                  pub use dep::Fourth;";
+
+    // When: collecting the exported names
+    let names = exported_names(source);
+
+    // Then: only the leaf of each path is exported
+    assert_eq!(
+        names.into_iter().collect::<Vec<_>>(),
+        ["First", "Fourth", "Second", "Third"]
+    );
+}
+
+/// Picks the backend with the highest id, using only `api` types
+struct LastBackendStrategy;
+
+#[async_trait]
+impl StrategyService for LastBackendStrategy {
+    fn strategy(&self) -> Strategy {
+        Strategy::Custom("api_last_backend".to_string())
+    }
+
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> std::result::Result<Arc<Backend>, StrategyError> {
+        ctx.routing_table()
+            .healthy_backends()
+            .into_iter()
+            .max_by_key(|backend| backend.id())
+            .ok_or(StrategyError::NoBackendAvailable)
+    }
+}
+
+/// Builds [`LastBackendStrategy`]
+struct LastBackendFactory;
+
+impl StrategyFactory for LastBackendFactory {
+    fn build(
+        &self,
+        _params: serde_json::Value,
+    ) -> std::result::Result<Arc<dyn StrategyService>, StrategyError> {
+        Ok(Arc::new(LastBackendStrategy))
+    }
+}
+
+#[tokio::test]
+async fn api_embed_custom_strategy_should_succeed() {
+    // Given: a strategy registered through the api
+    api::register("api_last_backend", Arc::new(LastBackendFactory))
+        .expect("Registration should succeed");
+    assert!(api::is_registered("api_last_backend"));
+
+    // When: loading a config naming it and creating a context
+    let config = ConfigBuilder::from_content(
+        r#"{
+  "runtime": {
+    "metrics_cap": 100,
+    "health_cap": 50,
+    "drain_timeout_millis": 1000,
+    "background_timeout_millis": 1000,
+    "accept_timeout_millis": 1000,
+    "config_watch_interval_millis": 1000
+  },
+  "proxy": { "listen_address": "127.0.0.1:0" },
+  "strategy": "api_last_backend",
+  "backends": [
+    { "id": 0, "address": "127.0.0.1:11001" },
+    { "id": 1, "address": "127.0.0.1:11002" }
+  ],
+  "health": { "interval": 1000, "timeout": 500 },
+  "metrics": { "interval": 1000, "timeout": 500 }
+}"#,
+        ConfigFormat::Json,
+    )
+    .expect("Config should load");
+    let ctx = Arc::new(Context::new(config).expect("Context should build"));
+
+    // Then: the context reads back the config and routes with the strategy
+    assert_eq!(
+        ctx.config().strategy,
+        Strategy::Custom("api_last_backend".to_string())
+    );
+    assert_eq!(ctx.routing_table().backend_ids().len(), 2);
+    let picked = ctx
+        .strategy()
+        .pick_backend(ctx.clone())
+        .await
+        .expect("A backend should be picked");
+    assert_eq!(picked.id(), 1);
+
+    api::unregister("api_last_backend");
+}
@@ -4,7 +4,7 @@ use lemonade_load_balancer::App;
 use lemonade_load_balancer::prelude::*;
 use std::sync::Arc;
 
-use crate::common::fixtures::create_test_config_fast;
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
 
 // Mock service implementations
 struct MockConfigService;
@@ -179,3 +179,544 @@ async fn app_run_spawns_proxy_service_should_succeed() {
     // Cleanup
     app_handle.abort();
 }
+
+/// Proxy service that records whether it was started
+#[derive(Default)]
+struct StartedProxyService {
+    started: std::sync::atomic::AtomicBool,
+}
+
+#[async_trait]
+impl ProxyService for StartedProxyService {
+    async fn accept_connections(&self, ctx: Arc<Context>) -> Result<(), ProxyError> {
+        self.started.store(true, Ordering::SeqCst);
+        let mut shutdown_rx = ctx.channels().shutdown_rx();
+        let _ = shutdown_rx.recv().await;
+        Ok(())
+    }
+}
+
+/// Context with one listening and one closed backend and the given pre-flight settings
+async fn preflight_context(
+    preflight: PreflightConfig,
+) -> (Arc<Context>, tokio::net::TcpListener) {
+    let live = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind live backend");
+    let dead_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve dead port");
+    let backends = vec![
+        BackendMeta::new(
+            0u8,
+            Some("live"),
+            BackendAddress::from(live.local_addr().expect("live address")),
+            Some(1u8),
+        ),
+        BackendMeta::new(
+            1u8,
+            Some("dead"),
+            BackendAddress::from(dead_addr),
+            Some(1u8),
+        ),
+    ];
+    let mut config = create_test_config_fast(backends, Strategy::RoundRobin);
+    config.preflight = preflight;
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    (ctx, live)
+}
+
+/// App over mock services and the given proxy
+async fn app_with_proxy(proxy_service: Arc<StartedProxyService>) -> App {
+    App::new(
+        Arc::new(MockConfigService),
+        Arc::new(MockHealthService),
+        Arc::new(MockMetricsService),
+        proxy_service,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn app_run_preflight_strict_too_few_reachable_should_fail() {
+    // Given: one live and one dead backend, with both required to be reachable
+    let (ctx, _live) = preflight_context(PreflightConfig {
+        verify_backends_on_start: true,
+        strict: true,
+        min_reachable: 2,
+    })
+    .await;
+    let proxy = Arc::new(StartedProxyService::default());
+    let app = app_with_proxy(proxy.clone()).await;
+
+    // When: running the app
+    let result = tokio::time::timeout(Duration::from_secs(2), app.run(ctx))
+        .await
+        .expect("Pre-flight failure should end the run");
+
+    // Then: it refuses to start and the proxy never accepts
+    assert!(matches!(
+        result,
+        Err(lemonade_load_balancer::error::Error::Health(
+            HealthError::Preflight {
+                reachable: 1,
+                total: 2,
+                required: 2
+            }
+        ))
+    ));
+    assert!(!proxy.started.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn app_run_preflight_non_strict_unreachable_should_succeed() {
+    // Given: one live and one dead backend with a non-strict check
+    let (ctx, _live) = preflight_context(PreflightConfig {
+        verify_backends_on_start: true,
+        strict: false,
+        min_reachable: 2,
+    })
+    .await;
+    let proxy = Arc::new(StartedProxyService::default());
+    let app = app_with_proxy(proxy.clone()).await;
+
+    // When: running the app
+    let app_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { app.run(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Then: the dead backend is only logged and the proxy starts
+    assert!(proxy.started.load(Ordering::SeqCst));
+    let _ = ctx.channels().shutdown_tx().send(());
+    let result = tokio::time::timeout(Duration::from_secs(2), app_handle)
+        .await
+        .expect("App should stop")
+        .expect("App task should not panic");
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn verify_backends_counts_reachable_should_succeed() {
+    let (ctx, _live) = preflight_context(PreflightConfig::default()).await;
+
+    let reachable = BackendHealthService::verify_backends(
+        &ctx.routing_table(),
+        Duration::from_millis(500),
+        &PreflightConfig {
+            strict: true,
+            ..PreflightConfig::default()
+        },
+        None,
+    )
+    .await
+    .expect("One reachable backend satisfies the default minimum");
+
+    assert_eq!(reachable, 1);
+}
+
+#[tokio::test]
+async fn app_run_writes_final_state_snapshot_on_shutdown_should_succeed() {
+    // Given: an app with a state file writer
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let path = dir.path().join("state.json");
+    let mut config = create_test_config_fast(vec![], Strategy::RoundRobin);
+    config.state_file = Some(StateFileConfig {
+        path: path.clone(),
+        interval_millis: 60_000,
+    });
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let app = App::new(
+        Arc::new(MockConfigService),
+        Arc::new(MockHealthService),
+        Arc::new(MockMetricsService),
+        Arc::new(MockProxyService),
+    )
+    .await
+    .with_state_file(StateFileWriter::new());
+    let app_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { app.run(ctx).await }
+    });
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    assert!(!path.exists());
+
+    // When: shutting down
+    let _ = ctx.channels().shutdown_tx().send(());
+    let result = tokio::time::timeout(tokio::time::Duration::from_secs(2), app_handle)
+        .await
+        .expect("App should stop")
+        .expect("App should not panic");
+
+    // Then: a final snapshot was written
+    assert!(result.is_ok());
+    let snapshot: StateSnapshot =
+        serde_json::from_slice(&std::fs::read(&path).expect("State file should exist"))
+            .expect("State file should parse");
+    assert!(snapshot.final_snapshot);
+    assert!(!snapshot.groups[DEFAULT_GROUP].accepting);
+}
+
+#[tokio::test]
+async fn app_run_writes_final_health_state_on_shutdown_should_succeed() {
+    // Given: an app with a health state writer and an unhealthy backend
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let path = dir.path().join("health.json");
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1))],
+        Strategy::RoundRobin,
+    );
+    config.health_state_file = Some(HealthStateFileConfig {
+        path: path.clone(),
+        interval_millis: 60_000,
+        max_state_age_millis: 60_000,
+    });
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let backend = ctx.routing_table().get(0).unwrap();
+    backend.set_health(false, ctx.clock().now_millis());
+    let app = App::new(
+        Arc::new(MockConfigService),
+        Arc::new(MockHealthService),
+        Arc::new(MockMetricsService),
+        Arc::new(MockProxyService),
+    )
+    .await
+    .with_health_state_file(HealthStateWriter::new());
+    let app_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { app.run(ctx).await }
+    });
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    assert!(!path.exists());
+
+    // When: shutting down
+    let _ = ctx.channels().shutdown_tx().send(());
+    let result = tokio::time::timeout(tokio::time::Duration::from_secs(2), app_handle)
+        .await
+        .expect("App should stop")
+        .expect("App should not panic");
+
+    // Then: the backend's health was written, keyed by its address
+    assert!(result.is_ok());
+    let snapshot: HealthStateSnapshot = serde_json::from_slice(
+        &std::fs::read(&path).expect("Health state file should exist"),
+    )
+    .expect("Health state file should parse");
+    let state = &snapshot.backends[backend.address().as_str()];
+    assert!(!state.alive);
+    assert_eq!(state.consecutive_failures, 1);
+}
+
+/// Echo backend that answers every connection with what it reads
+async fn echo_backend() -> std::net::SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind echo backend");
+    let addr = listener.local_addr().expect("echo address");
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let (mut reader, mut writer) = stream.split();
+                let _ = tokio::io::copy(&mut reader, &mut writer).await;
+            });
+        }
+    });
+    addr
+}
+
+/// App over the real proxy, health, metrics and static config services
+async fn full_stack_app(config: &Config) -> App {
+    App::new(
+        Arc::new(StaticConfigService::new()),
+        Arc::new(
+            BackendHealthService::new(Arc::new(ArcSwap::from_pointee(
+                config.health.clone(),
+            )))
+            .expect("Failed to create health service"),
+        ),
+        Arc::new(
+            AggregatingMetricsService::new(Arc::new(ArcSwap::from_pointee(
+                config.metrics.clone(),
+            )))
+            .expect("Failed to create metrics service"),
+        ),
+        Arc::new(
+            TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+                .expect("Failed to create proxy service"),
+        ),
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn app_run_serves_first_connection_should_succeed() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let backend = echo_backend().await;
+    // Repeated so an accept racing the initial backend commit would show up
+    for attempt in 0..20 {
+        // Given: a full stack on a free port with one echo backend
+        let listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("Failed to reserve proxy port");
+        let mut config = create_test_config_fast(
+            vec![BackendMeta::new(
+                0u8,
+                Some("echo"),
+                BackendAddress::from(backend),
+                Some(1u8),
+            )],
+            Strategy::RoundRobin,
+        );
+        config.proxy.listen_address = listen_address;
+        let ctx =
+            Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+        let app = full_stack_app(&config).await;
+        let app_handle = tokio::spawn({
+            let ctx = ctx.clone();
+            async move { app.run(ctx).await }
+        });
+
+        // When: connecting the instant the listen socket is observable
+        let mut stream = loop {
+            match tokio::net::TcpStream::connect(listen_address).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::task::yield_now().await,
+            }
+        };
+        stream.write_all(b"ping").await.expect("write");
+        let mut reply = [0u8; 4];
+        let read =
+            tokio::time::timeout(Duration::from_secs(2), stream.read_exact(&mut reply))
+                .await
+                .expect("First connection should be answered");
+
+        // Then: the very first connection reached the backend
+        assert!(
+            read.is_ok(),
+            "attempt {}: first connection dropped",
+            attempt
+        );
+        assert_eq!(&reply, b"ping");
+        drop(stream);
+        let _ = ctx.channels().shutdown_tx().send(());
+        let _ = tokio::time::timeout(Duration::from_secs(5), app_handle).await;
+    }
+}
+
+/// Proxy service that records the thread it was started on
+#[derive(Default)]
+struct ThreadRecordingProxyService {
+    thread_name: std::sync::Mutex<Option<String>>,
+}
+
+#[async_trait]
+impl ProxyService for ThreadRecordingProxyService {
+    async fn accept_connections(&self, ctx: Arc<Context>) -> Result<(), ProxyError> {
+        *self.thread_name.lock().expect("thread name lock") =
+            std::thread::current().name().map(String::from);
+        let mut shutdown_rx = ctx.channels().shutdown_rx();
+        let _ = shutdown_rx.recv().await;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn app_run_proxy_worker_threads_runs_proxy_on_dedicated_runtime_should_succeed() {
+    // Given: an app with a dedicated two-thread proxy runtime
+    let mut config = create_test_config_fast(vec![], Strategy::RoundRobin);
+    config.runtime.proxy_worker_threads = Some(2);
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let proxy = Arc::new(ThreadRecordingProxyService::default());
+    let app = App::new(
+        Arc::new(MockConfigService),
+        Arc::new(MockHealthService),
+        Arc::new(MockMetricsService),
+        proxy.clone(),
+    )
+    .await;
+
+    // When: running the app and shutting it down
+    let app_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { app.run(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let _ = ctx.channels().shutdown_tx().send(());
+    let result = tokio::time::timeout(Duration::from_secs(2), app_handle)
+        .await
+        .expect("App should stop across both runtimes")
+        .expect("App should not panic");
+
+    // Then: the proxy ran on a proxy runtime worker and shutdown completed
+    assert!(result.is_ok());
+    assert_eq!(
+        proxy
+            .thread_name
+            .lock()
+            .expect("thread name lock")
+            .as_deref(),
+        Some("lemonade-proxy")
+    );
+}
+
+#[tokio::test]
+async fn app_run_dedicated_proxy_runtime_serves_and_drains_should_succeed() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Given: a full stack whose proxy runs on its own runtime
+    let backend = echo_backend().await;
+    let listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("echo"),
+            BackendAddress::from(backend),
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = listen_address;
+    config.runtime.proxy_worker_threads = Some(2);
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let app = full_stack_app(&config).await;
+    let app_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { app.run(ctx).await }
+    });
+
+    // When: proxying a connection and then shutting down
+    let mut stream = loop {
+        match tokio::net::TcpStream::connect(listen_address).await {
+            Ok(stream) => break stream,
+            Err(_) => tokio::time::sleep(Duration::from_millis(5)).await,
+        }
+    };
+    stream.write_all(b"pong").await.expect("write");
+    let mut reply = [0u8; 4];
+    tokio::time::timeout(Duration::from_secs(2), stream.read_exact(&mut reply))
+        .await
+        .expect("Connection should be answered")
+        .expect("read");
+    drop(stream);
+    let _ = ctx.channels().shutdown_tx().send(());
+    let result = tokio::time::timeout(Duration::from_secs(5), app_handle)
+        .await
+        .expect("App should stop")
+        .expect("App should not panic");
+
+    // Then: traffic was served and shutdown coordinated across both runtimes
+    assert_eq!(&reply, b"pong");
+    assert!(result.is_ok());
+    assert_eq!(ctx.listener_generations().total(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn app_run_shutdown_without_connections_takes_fast_path_should_succeed() {
+    // Given: a full stack with a state file and no open connection
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("echo"),
+            BackendAddress::from(echo_backend().await),
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = listen_address;
+    config.runtime.drain_timeout_millis = 5000;
+    config.runtime.background_timeout_millis = 5000;
+    config.state_file = Some(StateFileConfig {
+        path: dir.path().join("state.json"),
+        interval_millis: 60_000,
+    });
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let app = full_stack_app(&config)
+        .await
+        .with_state_file(StateFileWriter::new());
+    let app_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { app.run(ctx).await }
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !ctx.readiness().is_accepting() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Proxy should start accepting");
+
+    // When: shutting down
+    let started = std::time::Instant::now();
+    let _ = ctx.channels().shutdown_tx().send(());
+    let result = tokio::time::timeout(Duration::from_secs(2), app_handle)
+        .await
+        .expect("App should stop")
+        .expect("App should not panic");
+
+    // Then: it stopped well within the drain and background timeouts,
+    // without starting a drain (targets 50ms; bound kept loose for CI)
+    assert!(result.is_ok());
+    let elapsed = started.elapsed();
+    assert!(
+        elapsed < Duration::from_millis(500),
+        "shutdown took {:?}",
+        elapsed
+    );
+    assert!(ctx.drain_state().is_none());
+}
+
+#[tokio::test]
+async fn app_run_shutdown_with_open_connection_drains_should_succeed() {
+    // Given: an app whose backend still holds a connection
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("busy"),
+            BackendAddress::parse("127.0.0.1:1").unwrap(),
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    config.runtime.drain_timeout_millis = 5000;
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let backend = ctx.routing_table().get(0).expect("backend 0");
+    backend.increment_connection();
+    let app = App::new(
+        Arc::new(MockConfigService),
+        Arc::new(MockHealthService),
+        Arc::new(MockMetricsService),
+        Arc::new(MockProxyService),
+    )
+    .await;
+    let app_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { app.run(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: shutting down
+    let _ = ctx.channels().shutdown_tx().send(());
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    // Then: shutdown waits on the drain
+    assert!(!app_handle.is_finished());
+    let progress = ctx.drain_state().expect("Drain should be reported");
+    assert_eq!(progress.remaining_total, 1);
+
+    // And: completes once the connection closes
+    backend.decrement_connection();
+    ctx.notify_connection_closed();
+    let result = tokio::time::timeout(Duration::from_secs(2), app_handle)
+        .await
+        .expect("App should stop")
+        .expect("App should not panic");
+    assert!(result.is_ok());
+}
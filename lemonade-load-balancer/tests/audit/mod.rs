@@ -0,0 +1,5 @@
+//! Audit module tests
+//!
+//! Tests for the audit log sink and audited actions
+
+pub mod test_log;
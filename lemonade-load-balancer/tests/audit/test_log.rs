@@ -0,0 +1,178 @@
+//! Tests for AuditLog
+
+use lemonade_load_balancer::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Read every record from a JSONL audit file
+pub fn read_records(path: &Path) -> Vec<AuditRecord> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Audit line should be JSON"))
+        .collect()
+}
+
+fn audit_config(
+    temp_dir: &TempDir,
+    max_file_bytes: u64,
+    max_files: usize,
+) -> AuditConfig {
+    AuditConfig {
+        file: Some(temp_dir.path().join("audit.jsonl")),
+        max_file_bytes,
+        max_files,
+    }
+}
+
+#[test]
+fn audit_log_writes_one_record_per_action_should_succeed() {
+    // Given: an audit log with a file sink
+    let temp_dir = TempDir::new().unwrap();
+    let config = audit_config(&temp_dir, 1024 * 1024, 3);
+    let audit = AuditLog::new(&config);
+
+    // When: recording an applied and a rejected action
+    audit.record(
+        AuditRecord::new(
+            &AuditActor::Admin(Some("abcd1234".into())),
+            AuditAction::BackendDrain,
+        )
+        .with_target("backend:1")
+        .with_change("active", "draining"),
+    );
+    audit.record(
+        AuditRecord::new(&AuditActor::FileWatch, AuditAction::ConfigReload)
+            .rejected("parse error"),
+    );
+
+    // Then: both records are written with their fields
+    let records = read_records(config.file.as_ref().unwrap());
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].actor, "admin:abcd1234");
+    assert_eq!(records[0].action, AuditAction::BackendDrain);
+    assert_eq!(records[0].target.as_deref(), Some("backend:1"));
+    assert_eq!(records[0].old.as_deref(), Some("active"));
+    assert_eq!(records[0].new.as_deref(), Some("draining"));
+    assert_eq!(records[0].outcome, AuditOutcome::Applied);
+    assert!(records[0].timestamp_ms > 0);
+    assert_eq!(records[1].actor, "file-watch");
+    assert_eq!(records[1].outcome, AuditOutcome::Rejected);
+    assert_eq!(records[1].reason.as_deref(), Some("parse error"));
+}
+
+#[test]
+fn audit_log_rotates_by_size_should_succeed() {
+    // Given: an audit log rotating every ~2 records and keeping 2 old files
+    let temp_dir = TempDir::new().unwrap();
+    let config = audit_config(&temp_dir, 200, 2);
+    let audit = AuditLog::new(&config);
+    let path = config.file.clone().unwrap();
+
+    // When: recording many actions
+    for _ in 0..10 {
+        audit.record(AuditRecord::new(&AuditActor::Signal, AuditAction::Shutdown));
+    }
+
+    // Then: the active file stays under the limit and old files are kept up to max_files
+    let rotated_1 = temp_dir.path().join("audit.jsonl.1");
+    let rotated_2 = temp_dir.path().join("audit.jsonl.2");
+    let rotated_3 = temp_dir.path().join("audit.jsonl.3");
+    assert!(std::fs::metadata(&path).unwrap().len() <= 200);
+    assert!(rotated_1.exists());
+    assert!(rotated_2.exists());
+    assert!(!rotated_3.exists());
+    assert!(!read_records(&path).is_empty());
+    assert!(!read_records(&rotated_1).is_empty());
+}
+
+#[tokio::test]
+async fn audit_log_records_migrations_should_succeed() {
+    // Given: a context auditing to a file
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    config.audit = audit_config(&temp_dir, 1024 * 1024, 3);
+    let path = config.audit.file.clone().unwrap();
+    let ctx = Context::new(config.clone()).expect("Failed to create context");
+
+    // When: changing only the strategy, then the backends, then applying an invalid config
+    let mut strategy_only = config.clone();
+    strategy_only.strategy = Strategy::LeastConnections;
+    ctx.migrate_as(strategy_only.clone(), AuditActor::FileWatch)
+        .await
+        .expect("Strategy change should apply");
+
+    let mut with_backend = strategy_only.clone();
+    with_backend
+        .backends
+        .push(BackendConfig::from(create_test_backend(
+            1,
+            None,
+            Some(10u8),
+        )));
+    ctx.migrate(with_backend.clone())
+        .await
+        .expect("Backend change should apply");
+
+    let mut duplicate = with_backend.clone();
+    duplicate
+        .backends
+        .push(BackendConfig::from(create_test_backend(
+            1,
+            None,
+            Some(10u8),
+        )));
+    assert!(ctx.migrate(duplicate).await.is_err());
+
+    // Then: one record per migration with actor, change and outcome
+    let records = read_records(&path);
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].actor, "file-watch");
+    assert_eq!(records[0].action, AuditAction::StrategyChange);
+    assert_eq!(records[0].old.as_deref(), Some("round_robin"));
+    assert_eq!(records[0].new.as_deref(), Some("least_connections"));
+    assert_eq!(records[1].actor, "api");
+    assert_eq!(records[1].action, AuditAction::ConfigReload);
+    assert_eq!(records[1].outcome, AuditOutcome::Applied);
+    assert!(
+        records[1]
+            .detail
+            .as_deref()
+            .unwrap()
+            .contains("added_backends=[1]")
+    );
+    assert_eq!(records[2].action, AuditAction::ConfigReload);
+    assert_eq!(records[2].outcome, AuditOutcome::Rejected);
+    assert!(records[2].reason.is_some());
+}
+
+#[tokio::test]
+async fn audit_log_records_rejected_set_backends_should_succeed() {
+    // Given: a context auditing to a file
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    config.audit = audit_config(&temp_dir, 1024 * 1024, 3);
+    let path = config.audit.file.clone().unwrap();
+    let ctx = Context::new(config.clone()).expect("Failed to create context");
+
+    // When: replacing the backends with a list holding a duplicate id
+    let duplicate = vec![config.backends[0].clone(), config.backends[0].clone()];
+    assert!(ctx.set_backends(duplicate).await.is_err());
+
+    // Then: the rejected change is recorded, and nothing was applied
+    let records = read_records(&path);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].actor, "api");
+    assert_eq!(records[0].action, AuditAction::ConfigReload);
+    assert_eq!(records[0].outcome, AuditOutcome::Rejected);
+    assert!(records[0].reason.is_some());
+    assert_eq!(ctx.config().backends.len(), 1);
+}
@@ -66,6 +66,11 @@ pub fn create_test_config(
         background_timeout_millis: 1000,
         accept_timeout_millis: 2000,
         config_watch_interval_millis: 1000,
+        drain_progress_interval_millis: 1000,
+        drain_policy: DrainPolicy::Finish,
+        proxy_worker_threads: None,
+        audit_interval_millis: None,
+        log_rate_limit: LogRateLimitConfig::default(),
     })]
     runtime: RuntimeConfig,
 ) -> Config {
@@ -76,6 +81,7 @@ pub fn create_test_config(
         .collect();
 
     Config {
+        version: CONFIG_VERSION,
         source: ConfigSource::Environment,
         runtime: runtime.clone(),
         proxy: ProxyConfig {
@@ -84,19 +90,79 @@ pub fn create_test_config(
                 3000,
             ),
             max_connections: Some(1000),
+            coalesce: CoalesceConfig::default(),
+            hedging: HedgingConfig::default(),
+            retry_budget: RetryBudgetConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            route_by_sni: SniRoutingConfig::default(),
+            route_by_alpn: AlpnRoutingConfig::default(),
+            route_by_http: HttpRoutingConfig::default(),
+            slow_connect_warn_ms: None,
+            slow_connection_warn_secs: None,
+            strategy_pick_warn_micros: None,
+            initial_read_timeout_millis: None,
+            slow_log_max_per_minute: 10,
+            max_buffered_bytes: 256 * 1024,
+            backend_bind_address: None,
+            backend_dscp: None,
+            fd_headroom: 64,
+            max_backend_peer_connections: None,
+            max_hops: None,
+            transparent: false,
+            report_backend_aborts: true,
+            sticky_sessions: false,
+            sticky_ttl_millis: 300_000,
+            listen_unix: None,
+            max_pending_connects: 32,
+            local_zone: None,
+            zone_spillover_min_healthy: 1,
+            propagate_deadlines: false,
+            request_timeout_millis: 30_000,
         },
         strategy,
+        strategy_params: serde_json::Value::Null,
+        fallback: None,
         backends: backend_configs,
+        groups: Default::default(),
+        allow_duplicate_addresses: false,
+        auto_weight: false,
+        auto_weight_tuning: AutoWeightConfig::default(),
+        admin: AdminConfig::default(),
+        audit: AuditConfig::default(),
         health: HealthConfig {
             interval: Duration::from_secs(5),
             timeout: Duration::from_secs(1),
+            max_event_age_millis: 10_000,
+            evict_on_unhealthy: false,
+            slow_start_millis: 0,
+            outlier_error_percent: 0,
+            outlier_min_connections: 10,
+            outlier_window_millis: 10_000,
+            base_ejection_millis: 30_000,
+            max_ejection_percent: 10,
+            check: HealthCheck::Tcp,
+            healthy_threshold: 1,
+            unhealthy_threshold: 1,
+            jitter_percent: 0,
+            initial_state: InitialHealthState::default(),
+            reuse_connections: true,
         },
+        preflight: PreflightConfig::default(),
+        health_endpoint: HealthEndpointConfig::default(),
         metrics: MetricsConfig {
             interval: Duration::from_secs(10),
             timeout: Duration::from_secs(2),
+            max_event_age_millis: 30_000,
+            aggregation: LatencyAggregation::default(),
+            selection_trace: false,
         },
+        services: ServicesConfig::default(),
         otlp_protocol: None,
+        rng_seed: None,
+        state_file: None,
+        health_state_file: None,
         otlp_endpoint: None,
+        secrets: Default::default(),
     }
 }
 
@@ -120,6 +186,11 @@ pub fn create_test_config_fast(
             background_timeout_millis: 50,
             accept_timeout_millis: 50,
             config_watch_interval_millis: 100,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     )
 }
@@ -143,6 +214,11 @@ pub fn create_test_context(
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 100,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     Arc::new(Context::new(config).expect("Failed to create context"))
@@ -47,14 +47,14 @@ pub fn set_backends_healthy_by_id(ctx: &Arc<Context>, backend_ids: &[u8]) {
 /// When: checking the result
 /// Then: asserts that the backend matches expected ID
 pub fn assert_backend_selected(
-    result: Result<BackendMeta, StrategyError>,
+    result: Result<Arc<Backend>, StrategyError>,
     expected_id: u8,
 ) {
     assert!(result.is_ok(), "Expected backend selection to succeed");
     let backend = result.expect("Failed to get backend");
     assert_eq!(
         backend.id(),
-        &expected_id,
+        expected_id,
         "Expected backend ID {} but got {}",
         expected_id,
         backend.id()
@@ -66,7 +66,7 @@ pub fn assert_backend_selected(
 /// Given: a result from pick_backend
 /// When: checking the result
 /// Then: asserts that NoBackendAvailable error was returned
-pub fn assert_no_backend_available(result: Result<BackendMeta, StrategyError>) {
+pub fn assert_no_backend_available(result: Result<Arc<Backend>, StrategyError>) {
     assert!(result.is_err(), "Expected backend selection to fail");
     assert!(
         matches!(
@@ -3,4 +3,7 @@
 //! Tests for config service adapters
 
 mod test_builder;
+mod test_compat;
+mod test_diff;
 mod test_notify;
+mod test_secrets;
@@ -1,10 +1,17 @@
 //! Tests for ConfigBuilder
 
-use lemonade_load_balancer::prelude::{ConfigBuilder, ConfigSource, Strategy};
+use lemonade_load_balancer::prelude::{
+    AdaptiveStrategy, AdaptiveWeights, BackendAddress, BackendMeta, CircuitBreakerConfig,
+    ConfigBuilder, ConfigError, ConfigFormat, ConfigSource, HealthCheck,
+    LogRateLimitConfig, MAX_JITTER_PERCENT, RouteTableError, ServiceOverrides,
+    ServicesConfig, Strategy,
+};
 use std::fs;
 use std::path::PathBuf;
 use tempfile::TempDir;
 
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
 #[test]
 fn config_builder_from_env_with_custom_values_should_succeed() {
     let result = ConfigBuilder::from_env();
@@ -114,7 +121,11 @@ fn config_builder_from_file_json_should_succeed() {
   },
   "proxy": {
     "listen_address": "0.0.0.0:7000",
-    "max_connections": 30000
+    "max_connections": 30000,
+    "local_zone": "eu-west-1a",
+    "zone_spillover_min_healthy": 2,
+    "propagate_deadlines": true,
+    "request_timeout_millis": 5000
   },
   "strategy": "fastest_response_time",
   "backends": [
@@ -125,7 +136,9 @@ fn config_builder_from_file_json_should_succeed() {
     },
     {
       "id": 1,
-      "address": "127.0.0.1:11002"
+      "address": "127.0.0.1:11002",
+      "priority": 1,
+      "zone": "eu-west-1b"
     }
   ],
   "health": {
@@ -157,6 +170,14 @@ fn config_builder_from_file_json_should_succeed() {
     assert_eq!(config.backends.len(), 2);
     assert_eq!(config.backends[0].name, Some("json-backend-1".to_string()));
     assert_eq!(config.backends[1].name, None);
+    assert_eq!(config.backends[0].priority, None);
+    assert_eq!(config.backends[1].priority, Some(1));
+    assert_eq!(config.proxy.local_zone.as_deref(), Some("eu-west-1a"));
+    assert_eq!(config.proxy.zone_spillover_min_healthy, 2);
+    assert!(config.proxy.propagate_deadlines);
+    assert_eq!(config.proxy.request_timeout_millis, 5000);
+    assert_eq!(config.backends[0].zone, None);
+    assert_eq!(config.backends[1].zone.as_deref(), Some("eu-west-1b"));
 }
 
 #[test]
@@ -201,3 +222,644 @@ fn config_builder_from_file_none_defaults_should_succeed() {
     // Should use environment-based defaults since no file was provided
     assert_eq!(config.source, ConfigSource::Environment);
 }
+
+fn write_backends_config(temp_dir: &TempDir, backends: &str, extra: &str) -> PathBuf {
+    let config_path = temp_dir.path().join("config.json");
+    let config_content = format!(
+        r#"{{
+  "runtime": {{
+    "metrics_cap": 100,
+    "health_cap": 50,
+    "drain_timeout_millis": 1000,
+    "background_timeout_millis": 1000,
+    "accept_timeout_millis": 1000,
+    "config_watch_interval_millis": 1000
+  }},
+  "proxy": {{ "listen_address": "127.0.0.1:7000" }},
+  "strategy": "round_robin",
+  "backends": {backends},
+  {extra}
+  "health": {{ "interval": 1000, "timeout": 500 }},
+  "metrics": {{ "interval": 1000, "timeout": 500 }}
+}}"#
+    );
+    fs::write(&config_path, config_content).unwrap();
+    config_path
+}
+
+#[test]
+fn config_builder_from_file_duplicate_id_should_fail() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_backends_config(
+        &temp_dir,
+        r#"[{"id": 0, "address": "127.0.0.1:11001"}, {"id": 0, "address": "127.0.0.1:11002"}]"#,
+        "",
+    );
+
+    let result = ConfigBuilder::from_file(Some(config_path));
+    assert!(matches!(
+        result,
+        Err(ConfigError::Backends(RouteTableError::DuplicateId(0)))
+    ));
+}
+
+#[test]
+fn config_builder_from_file_duplicate_address_should_fail() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_backends_config(
+        &temp_dir,
+        r#"[{"id": 0, "address": "127.0.0.1:11001"}, {"id": 1, "address": "127.0.0.1:11001"}]"#,
+        "",
+    );
+
+    let result = ConfigBuilder::from_file(Some(config_path));
+    assert!(matches!(
+        result,
+        Err(ConfigError::Backends(RouteTableError::DuplicateAddress {
+            existing: 0,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn config_builder_from_file_allowed_duplicate_address_should_succeed() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_backends_config(
+        &temp_dir,
+        r#"[{"id": 0, "address": "127.0.0.1:11001"}, {"id": 1, "address": "127.0.0.1:11001"}]"#,
+        r#""allow_duplicate_addresses": true,"#,
+    );
+
+    let config = ConfigBuilder::from_file(Some(config_path))
+        .expect("Duplicate addresses should be allowed");
+    assert!(config.allow_duplicate_addresses);
+    assert_eq!(config.backends.len(), 2);
+}
+
+#[test]
+fn config_builder_from_file_remote_admin_without_token_should_fail() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_backends_config(
+        &temp_dir,
+        "[]",
+        r#""admin": { "enabled": true, "listen_address": "0.0.0.0:9090" },"#,
+    );
+
+    let result = ConfigBuilder::from_file(Some(config_path));
+    assert!(matches!(result, Err(ConfigError::Admin(_))));
+}
+
+#[test]
+fn config_builder_from_file_admin_defaults_to_loopback_should_succeed() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_backends_config(
+        &temp_dir,
+        "[]",
+        r#""admin": { "enabled": true, "read_only": true },"#,
+    );
+
+    let config = ConfigBuilder::from_file(Some(config_path))
+        .expect("Loopback admin API should not need a token");
+    assert!(config.admin.enabled);
+    assert!(config.admin.read_only);
+    assert!(config.admin.listen_address.ip().is_loopback());
+}
+
+#[test]
+fn config_builder_from_file_services_should_succeed() {
+    // Given: a config disabling health checks only
+    let temp_dir = TempDir::new().unwrap();
+    let config_path =
+        write_backends_config(&temp_dir, "[]", r#""services": { "health": false },"#);
+
+    // When: loading it
+    let config = ConfigBuilder::from_file(Some(config_path)).expect("Valid config");
+
+    // Then: the other services keep running by default
+    assert_eq!(
+        config.services,
+        ServicesConfig {
+            health: false,
+            metrics: true,
+            config_watch: true,
+        }
+    );
+}
+
+#[test]
+fn service_overrides_disable_services_should_succeed() {
+    // Given: every service enabled in the config
+    let mut services = ServicesConfig::default();
+
+    // When: applying `--no-metrics`
+    ServiceOverrides {
+        no_health_checks: false,
+        no_metrics: true,
+    }
+    .apply(&mut services);
+
+    // Then: only metrics aggregation is disabled
+    assert!(services.health);
+    assert!(!services.metrics);
+    assert!(services.config_watch);
+
+    // And: overrides never re-enable a service the config disabled
+    let mut services = ServicesConfig {
+        health: false,
+        ..ServicesConfig::default()
+    };
+    ServiceOverrides::default().apply(&mut services);
+    assert!(!services.health);
+}
+
+#[test]
+fn config_validate_zero_proxy_worker_threads_should_fail() {
+    let mut config = create_test_config_fast(vec![], Strategy::RoundRobin);
+    config.runtime.proxy_worker_threads = Some(0);
+
+    assert!(matches!(config.validate(), Err(ConfigError::Runtime(_))));
+
+    config.runtime.proxy_worker_threads = Some(2);
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn config_validate_zero_audit_interval_should_fail() {
+    let mut config = create_test_config_fast(vec![], Strategy::RoundRobin);
+    config.runtime.audit_interval_millis = Some(0);
+
+    assert!(matches!(config.validate(), Err(ConfigError::Runtime(_))));
+
+    config.runtime.audit_interval_millis = Some(60_000);
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn config_builder_from_file_bind_address_should_succeed() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_backends_config(
+        &temp_dir,
+        r#"[{"id": 0, "address": "127.0.0.1:11001", "bind_address": "127.0.0.2"},
+            {"id": 1, "address": "127.0.0.1:11002"}]"#,
+        "",
+    );
+
+    let config = ConfigBuilder::from_file(Some(config_path))
+        .expect("A same-family bind address should be accepted");
+    assert_eq!(
+        config.backends[0].bind_address,
+        Some("127.0.0.2".parse().unwrap())
+    );
+    assert_eq!(config.backends[1].bind_address, None);
+}
+
+#[test]
+fn config_builder_from_file_bind_address_family_mismatch_should_fail() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_backends_config(
+        &temp_dir,
+        r#"[{"id": 0, "address": "127.0.0.1:11001", "bind_address": "::1"}]"#,
+        "",
+    );
+
+    let result = ConfigBuilder::from_file(Some(config_path));
+    assert!(matches!(result, Err(ConfigError::Proxy(_))));
+}
+
+#[test]
+fn config_validate_backend_bind_address_family_mismatch_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("backend-0"),
+            BackendAddress::parse("[::1]:11001").unwrap(),
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    // Given: an IPv4 proxy-wide source for an IPv6 backend
+    config.proxy.backend_bind_address = Some("127.0.0.1".parse().unwrap());
+
+    // Then: validation rejects it
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+
+    // And: the backend's own bind address takes precedence
+    config.backends[0].bind_address = Some("::1".parse().unwrap());
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn config_validate_dscp_out_of_range_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    // Given: the largest DSCP values
+    config.proxy.backend_dscp = Some(63);
+    config.backends[0].dscp = Some(63);
+    assert!(config.validate().is_ok());
+
+    // Then: a proxy-wide value past six bits is rejected
+    config.proxy.backend_dscp = Some(64);
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+
+    // And: so is a backend's own value
+    config.proxy.backend_dscp = None;
+    config.backends[0].dscp = Some(64);
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+}
+
+#[test]
+fn config_validate_zero_max_pending_connects_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    // Given: a single connect in flight per backend
+    config.proxy.max_pending_connects = 1;
+    assert!(config.validate().is_ok());
+
+    // Then: a cap no connect could start under is rejected
+    config.proxy.max_pending_connects = 0;
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+}
+
+#[test]
+fn config_validate_zones_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    // Given: a local zone and a zoned backend
+    config.proxy.local_zone = Some("eu-west-1a".to_string());
+    config.backends[0].zone = Some("eu-west-1a".to_string());
+    assert!(config.validate().is_ok());
+
+    // Then: a spillover threshold no zone could meet is rejected
+    config.proxy.zone_spillover_min_healthy = 0;
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+
+    // And: so are empty zone names
+    config.proxy.zone_spillover_min_healthy = 1;
+    config.proxy.local_zone = Some(String::new());
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+    config.proxy.local_zone = None;
+    config.backends[0].zone = Some(String::new());
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+}
+
+#[test]
+fn config_validate_outlier_ejection_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    // Given: outlier ejection at a quarter of connections failed
+    config.health.outlier_error_percent = 25;
+    config.health.max_ejection_percent = 100;
+    assert!(config.validate().is_ok());
+
+    // Then: more than every backend cannot be ejected
+    config.health.max_ejection_percent = 101;
+    assert!(matches!(config.validate(), Err(ConfigError::Health(_))));
+
+    // And: nor more than every connection fail
+    config.health.max_ejection_percent = 10;
+    config.health.outlier_error_percent = 101;
+    assert!(matches!(config.validate(), Err(ConfigError::Health(_))));
+
+    // And: a rate needs at least one connection
+    config.health.outlier_error_percent = 25;
+    config.health.outlier_min_connections = 0;
+    assert!(matches!(config.validate(), Err(ConfigError::Health(_))));
+
+    // And: an ejection must last
+    config.health.outlier_min_connections = 10;
+    config.health.base_ejection_millis = 0;
+    assert!(matches!(config.validate(), Err(ConfigError::Health(_))));
+
+    // And: a zero length or volume is fine while ejection is disabled
+    config.health.outlier_error_percent = 0;
+    config.health.outlier_min_connections = 0;
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn config_validate_health_thresholds_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    // Given: health read with the default thresholds
+    config.health = serde_json::from_value(serde_json::json!({
+        "interval_millis": 1000,
+        "timeout_millis": 100,
+    }))
+    .expect("Failed to parse");
+    assert_eq!(config.health.healthy_threshold, 2);
+    assert_eq!(config.health.unhealthy_threshold, 3);
+    assert!(config.validate().is_ok());
+
+    // Then: a threshold of zero is rejected
+    config.health.unhealthy_threshold = 0;
+    assert!(matches!(config.validate(), Err(ConfigError::Health(_))));
+    config.health.unhealthy_threshold = 1;
+    config.health.healthy_threshold = 0;
+    assert!(matches!(config.validate(), Err(ConfigError::Health(_))));
+}
+
+#[test]
+fn config_validate_health_jitter_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    // Given: health read with the default jitter
+    config.health = serde_json::from_value(serde_json::json!({
+        "interval_millis": 1000,
+        "timeout_millis": 100,
+    }))
+    .expect("Failed to parse");
+    assert_eq!(config.health.jitter_percent, 10);
+    assert!(config.validate().is_ok());
+
+    // Then: up to half the interval is accepted, more is rejected
+    config.health.jitter_percent = MAX_JITTER_PERCENT;
+    assert!(config.validate().is_ok());
+    config.health.jitter_percent = MAX_JITTER_PERCENT + 1;
+    assert!(matches!(config.validate(), Err(ConfigError::Health(_))));
+}
+
+#[test]
+fn config_validate_circuit_breaker_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    // Given: a circuit breaker read with its defaults
+    config.proxy.circuit_breaker =
+        serde_json::from_value(serde_json::json!({ "enabled": true }))
+            .expect("Failed to parse");
+    assert_eq!(config.proxy.circuit_breaker.failure_threshold, 5);
+    assert_eq!(config.proxy.circuit_breaker.open_duration_millis, 30_000);
+    assert!(config.validate().is_ok());
+
+    // Then: a zero threshold or open duration is rejected
+    for breaker in [
+        CircuitBreakerConfig {
+            failure_threshold: 0,
+            ..config.proxy.circuit_breaker.clone()
+        },
+        CircuitBreakerConfig {
+            open_duration_millis: 0,
+            ..config.proxy.circuit_breaker.clone()
+        },
+    ] {
+        config.proxy.circuit_breaker = breaker;
+        assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+    }
+}
+
+#[test]
+fn config_validate_http_check_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    // Given: an HTTP check read with its defaults
+    config.health.check = serde_json::from_value(serde_json::json!({ "type": "http" }))
+        .expect("Failed to parse");
+    assert_eq!(
+        config.health.check,
+        HealthCheck::Http {
+            path: "/health".to_string(),
+            expected_status: 200,
+            host_header: None,
+        }
+    );
+    assert!(config.validate().is_ok());
+
+    // Then: the path must be absolute
+    let check =
+        |path: &str, expected_status: u16, host_header: Option<&str>| HealthCheck::Http {
+            path: path.to_string(),
+            expected_status,
+            host_header: host_header.map(String::from),
+        };
+    config.health.check = check("health", 200, None);
+    assert!(matches!(config.validate(), Err(ConfigError::Health(_))));
+
+    // And: the status must be an HTTP status code
+    config.health.check = check("/health", 700, None);
+    assert!(matches!(config.validate(), Err(ConfigError::Health(_))));
+
+    // And: the host header cannot inject headers
+    config.health.check = check("/health", 204, Some("a\r\nX-Evil: 1"));
+    assert!(matches!(config.validate(), Err(ConfigError::Health(_))));
+    config.health.check = check("/health", 204, Some("lb.internal"));
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn config_validate_log_rate_limit_zero_burst_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    // Given: a rate limit that would drop every message
+    config.runtime.log_rate_limit = LogRateLimitConfig {
+        window_millis: 1_000,
+        burst: 0,
+    };
+    assert!(matches!(config.validate(), Err(ConfigError::Runtime(_))));
+
+    // Then: a zero burst is fine once limiting is disabled
+    config.runtime.log_rate_limit.window_millis = 0;
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn config_builder_from_file_backend_at_listen_address_should_fail() {
+    // Given: a backend pointing at the proxy's own listen address
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_backends_config(
+        &temp_dir,
+        r#"[{"id": 0, "address": "127.0.0.1:11001"},
+            {"id": 1, "address": "127.0.0.1:7000"}]"#,
+        "",
+    );
+
+    // When: loading the config
+    let result = ConfigBuilder::from_file(Some(config_path));
+
+    // Then: it is rejected as a proxy loop
+    match result {
+        Err(ConfigError::Proxy(message)) => {
+            assert!(message.contains("proxy loop"), "{}", message)
+        }
+        other => panic!("expected a proxy loop error, got {:?}", other),
+    }
+}
+
+#[test]
+fn config_validate_backend_at_own_listener_should_fail() {
+    let backend = |address: &str| {
+        BackendMeta::new(
+            0u8,
+            Some("backend-0"),
+            BackendAddress::parse(address).unwrap(),
+            Some(1u8),
+        )
+    };
+
+    // Given: a proxy listening on every interface
+    let mut config =
+        create_test_config_fast(vec![backend("127.0.0.1:3000")], Strategy::RoundRobin);
+    config.proxy.listen_address = "0.0.0.0:3000".parse().unwrap();
+
+    // Then: a loopback backend on the same port reaches it and is rejected
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+
+    // And: another port, or a hostname that cannot be checked, is accepted
+    config.backends = vec![backend("127.0.0.1:3001").into()];
+    assert!(config.validate().is_ok());
+    config.backends = vec![backend("localhost:3000").into()];
+    assert!(config.validate().is_ok());
+
+    // Given: the enabled admin API listening where a backend points
+    config.proxy.listen_address = "127.0.0.1:3000".parse().unwrap();
+    config.backends = vec![backend("127.0.0.1:9900").into()];
+    config.admin.listen_address = "127.0.0.1:9900".parse().unwrap();
+    assert!(config.validate().is_ok());
+    config.admin.enabled = true;
+
+    // Then: the backend is rejected
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+}
+
+/// Adaptive strategy params with every weight and the cache TTL set
+const ADAPTIVE_TOML: &str = r#"
+strategy = "adaptive"
+backends = [{ id = 0, address = "127.0.0.1:10001" }]
+runtime = { metrics_cap = 100, health_cap = 50, drain_timeout_millis = 1000, background_timeout_millis = 1000, accept_timeout_millis = 1000, config_watch_interval_millis = 1000 }
+proxy = { listen_address = "127.0.0.1:7000" }
+health = { interval = 1000, timeout = 500 }
+metrics = { interval = 1000, timeout = 500 }
+
+[strategy_params]
+conn_weight = 0.5
+latency_weight = 0.2
+error_weight = 0.3
+cache_ttl_millis = 250
+cache_max_entries = 16
+"#;
+
+const ADAPTIVE_JSON: &str = r#"{
+  "strategy": "adaptive",
+  "backends": [{ "id": 0, "address": "127.0.0.1:10001" }],
+  "runtime": {
+    "metrics_cap": 100,
+    "health_cap": 50,
+    "drain_timeout_millis": 1000,
+    "background_timeout_millis": 1000,
+    "accept_timeout_millis": 1000,
+    "config_watch_interval_millis": 1000
+  },
+  "proxy": { "listen_address": "127.0.0.1:7000" },
+  "health": { "interval": 1000, "timeout": 500 },
+  "metrics": { "interval": 1000, "timeout": 500 },
+  "strategy_params": {
+    "conn_weight": 0.5,
+    "latency_weight": 0.2,
+    "error_weight": 0.3,
+    "cache_ttl_millis": 250,
+    "cache_max_entries": 16
+  }
+}"#;
+
+const ADAPTIVE_YAML: &str = r#"
+strategy: adaptive
+backends:
+  - id: 0
+    address: "127.0.0.1:10001"
+runtime:
+  metrics_cap: 100
+  health_cap: 50
+  drain_timeout_millis: 1000
+  background_timeout_millis: 1000
+  accept_timeout_millis: 1000
+  config_watch_interval_millis: 1000
+proxy:
+  listen_address: "127.0.0.1:7000"
+health:
+  interval: 1000
+  timeout: 500
+metrics:
+  interval: 1000
+  timeout: 500
+strategy_params:
+  conn_weight: 0.5
+  latency_weight: 0.2
+  error_weight: 0.3
+  cache_ttl_millis: 250
+  cache_max_entries: 16
+"#;
+
+#[rstest::rstest]
+#[case(ADAPTIVE_TOML, ConfigFormat::Toml)]
+#[case(ADAPTIVE_JSON, ConfigFormat::Json)]
+#[case(ADAPTIVE_YAML, ConfigFormat::Yaml)]
+fn config_builder_adaptive_params_round_trip_should_succeed(
+    #[case] content: &str,
+    #[case] format: ConfigFormat,
+) {
+    // When: loading adaptive params from a config file
+    let config = ConfigBuilder::from_content(content, format).expect("Failed to load");
+
+    // Then: the strategy is built with them
+    let strategy = AdaptiveStrategy::from_params(config.strategy_params.clone())
+        .expect("Failed to build adaptive strategy");
+    assert_eq!(
+        strategy.weights(),
+        &AdaptiveWeights {
+            conn_weight: 0.5,
+            latency_weight: 0.2,
+            error_weight: 0.3,
+            ..AdaptiveWeights::default()
+        }
+    );
+    assert_eq!(strategy.cache_ttl_millis(), 250);
+    assert_eq!(strategy.cache_max_entries(), 16);
+
+    // And: they survive writing the config back out
+    let written = serde_json::to_string(&config).expect("Failed to serialize");
+    let reloaded = ConfigBuilder::from_content(&written, ConfigFormat::Json)
+        .expect("Failed to load");
+    assert_eq!(reloaded.strategy_params, config.strategy_params);
+}
+
+#[rstest::rstest]
+#[case(serde_json::json!({ "conn_weight": -0.5 }))]
+#[case(serde_json::json!({
+    "conn_weight": 0.0,
+    "latency_weight": 0.0,
+    "error_weight": 0.0,
+    "resource_weight": 0.0
+}))]
+#[case(serde_json::json!({ "cache_ttl_millis": -1 }))]
+#[case(serde_json::json!({ "cache_max_entries": 0 }))]
+fn config_validate_invalid_adaptive_params_should_fail(
+    #[case] params: serde_json::Value,
+) {
+    // Given: an adaptive config with invalid params
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::Adaptive,
+    );
+    assert!(config.validate().is_ok());
+    config.strategy_params = params;
+
+    // Then: validation rejects them
+    assert!(matches!(config.validate(), Err(ConfigError::Strategy(_))));
+}
@@ -0,0 +1,204 @@
+//! Tests for config schema versions and migration
+//!
+use lemonade_load_balancer::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tempfile::TempDir;
+
+use crate::common::fixtures::{create_test_backends, create_test_config_fast};
+
+/// Pinned version 1 config file
+const CONFIG_V1: &str = include_str!("fixtures/config_v1.toml");
+
+fn write_config(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, content).expect("Failed to write config");
+    path
+}
+
+/// Assert the settings of the pinned version 1 file
+fn assert_v1_settings(config: &Config) {
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(config.strategy, Strategy::LeastConnections);
+    assert_eq!(config.runtime.config_watch_interval_millis, 750);
+    assert_eq!(config.proxy.listen_address.to_string(), "127.0.0.1:9100");
+    assert_eq!(config.backends.len(), 2);
+    assert_eq!(config.backends[0].weight, Some(3));
+    assert_eq!(config.health.interval, Duration::from_millis(12000));
+    assert_eq!(config.health.timeout, Duration::from_millis(4000));
+    assert_eq!(config.metrics.interval, Duration::from_millis(8000));
+    assert_eq!(config.metrics.timeout, Duration::from_millis(2000));
+    assert_eq!(
+        config.auto_weight_tuning.interval,
+        Duration::from_millis(2500)
+    );
+    let api_health = config.groups["api"].health.as_ref().expect("api health");
+    assert_eq!(api_health.interval, Duration::from_millis(6000));
+    assert_eq!(api_health.timeout, Duration::from_millis(1500));
+}
+
+fn file_value(path: &Path) -> serde_json::Value {
+    let content = fs::read_to_string(path).expect("Failed to read config");
+    ConfigFormat::from_path(path)
+        .and_then(|format| format.parse(&content))
+        .expect("Failed to parse config")
+}
+
+#[test]
+fn config_v1_file_loads_should_succeed() {
+    // Given: the pinned version 1 file, including a field unknown today
+    let dir = TempDir::new().unwrap();
+    let path = write_config(&dir, "config.toml", CONFIG_V1);
+
+    // When: loading it
+    let config = ConfigBuilder::from_file(Some(path)).expect("v1 file should load");
+
+    // Then: its old field names are upgraded to the current schema
+    assert_v1_settings(&config);
+}
+
+#[test]
+fn config_migrate_to_latest_round_trips_should_succeed() {
+    // Given: the pinned version 1 file without the unknown field
+    let dir = TempDir::new().unwrap();
+    let content = CONFIG_V1.replace("unknown_tuning_knob = true\n", "");
+    let path = write_config(&dir, "config.toml", &content);
+    let before = ConfigBuilder::from_file(Some(&path)).expect("v1 file should load");
+
+    // When: migrating it to the latest version
+    let migration = migrate_file(&path).expect("Migration should succeed");
+
+    // Then: the file declares the current version and uses the new names
+    assert_eq!(
+        migration,
+        Migration {
+            from_version: 1,
+            to_version: CONFIG_VERSION
+        }
+    );
+    let migrated = file_value(&path);
+    assert_eq!(migrated["version"], CONFIG_VERSION);
+    assert_eq!(migrated["health"]["interval_millis"], 12000);
+    assert!(migrated["health"].get("interval").is_none());
+    assert_eq!(migrated["groups"]["api"]["health"]["timeout_millis"], 1500);
+
+    // And: it loads strictly into the same config
+    let after = ConfigBuilder::from_file(Some(&path)).expect("migrated file should load");
+    assert_eq!(after, before);
+    assert_v1_settings(&after);
+
+    // When: migrating again
+    let again = migrate_file(&path).expect("Migration should succeed");
+
+    // Then: the file is already current and left alone
+    assert!(!again.is_upgrade());
+    assert_eq!(file_value(&path), migrated);
+}
+
+#[test]
+fn config_migrate_to_latest_keeps_format_should_succeed() {
+    // Given: a version 1 file in JSON and YAML
+    let dir = TempDir::new().unwrap();
+    let value = {
+        let mut value = ConfigFormat::Toml
+            .parse(&CONFIG_V1.replace("unknown_tuning_knob = true\n", ""))
+            .unwrap();
+        value.as_object_mut().unwrap().remove("groups");
+        value
+    };
+    for (name, format) in [
+        ("config.json", ConfigFormat::Json),
+        ("config.yaml", ConfigFormat::Yaml),
+    ] {
+        let path = write_config(&dir, name, &format.render(&value).unwrap());
+
+        // When: migrating it
+        let migration = migrate_file(&path).expect("Migration should succeed");
+
+        // Then: it is rewritten in the same format at the current version
+        assert!(migration.is_upgrade());
+        assert_eq!(file_value(&path)["metrics"]["interval_millis"], 8000);
+        let config = ConfigBuilder::from_file(Some(&path)).expect("should load");
+        assert_eq!(config.version, CONFIG_VERSION);
+    }
+}
+
+#[test]
+fn config_current_version_rejects_unknown_fields_should_fail() {
+    // Given: a current-version file with a misspelled field
+    let dir = TempDir::new().unwrap();
+    let mut value = serde_json::to_value(create_test_config_fast(
+        create_test_backends(2),
+        Strategy::RoundRobin,
+    ))
+    .unwrap();
+    value["proxy"]["hedging"]["enabeld"] = serde_json::Value::Bool(true);
+    let path = write_config(&dir, "config.json", &value.to_string());
+
+    // When: loading it
+    let result = ConfigBuilder::from_file(Some(&path));
+
+    // Then: the typo is reported by path
+    match result {
+        Err(ConfigError::UnknownFields(fields)) => {
+            assert_eq!(fields, vec!["proxy.hedging.enabeld".to_string()]);
+        }
+        other => panic!("Expected unknown fields error, got {:?}", other),
+    }
+
+    // And: the same file without a version loads as version 1
+    value.as_object_mut().unwrap().remove("version");
+    value["health"] = serde_json::json!({ "interval": 1000, "timeout": 500 });
+    value["metrics"] = serde_json::json!({ "interval": 1000, "timeout": 500 });
+    let path = write_config(&dir, "config.json", &value.to_string());
+    assert!(ConfigBuilder::from_file(Some(&path)).is_ok());
+}
+
+#[test]
+fn config_newer_version_rejected_should_fail() {
+    // Given: a file written for a newer binary
+    let dir = TempDir::new().unwrap();
+    let mut value = serde_json::to_value(create_test_config_fast(
+        create_test_backends(1),
+        Strategy::RoundRobin,
+    ))
+    .unwrap();
+    value["version"] = serde_json::json!(CONFIG_VERSION + 1);
+    let path = write_config(&dir, "config.json", &value.to_string());
+
+    // When: loading or migrating it
+    let loaded = ConfigBuilder::from_file(Some(&path));
+    let migrated = migrate_file(&path);
+
+    // Then: both fail with the supported range
+    for result in [loaded.map(|_| ()), migrated.map(|_| ())] {
+        match result {
+            Err(ConfigError::UnsupportedVersion { found, supported }) => {
+                assert_eq!(found, CONFIG_VERSION as u64 + 1);
+                assert_eq!(supported, CONFIG_VERSION);
+            }
+            other => panic!("Expected unsupported version error, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn config_serialized_round_trips_strictly_should_succeed() {
+    // Given: a config with optional sections set
+    let mut config = create_test_config_fast(create_test_backends(3), Strategy::Adaptive);
+    config.state_file = Some(StateFileConfig {
+        path: PathBuf::from("/tmp/lemonade-state.json"),
+        interval_millis: 1000,
+    });
+    config.proxy.retry_budget.ratio = 0.5;
+
+    // When: serializing and loading it back as a current-version file
+    let value = serde_json::to_value(&config).unwrap();
+    let loaded = config_from_value(value).expect("Serialized config should load");
+
+    // Then: nothing but the (unserialized) source is lost or reported unknown
+    assert_eq!(loaded.source, ConfigSource::File);
+    config.source = ConfigSource::File;
+    assert_eq!(loaded, config);
+}
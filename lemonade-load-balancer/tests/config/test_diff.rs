@@ -0,0 +1,344 @@
+//! Config diff tests
+//!
+//! Tests for ConfigDiff computation between two configurations
+
+use super::super::common::fixtures::*;
+use lemonade_load_balancer::prelude::*;
+
+#[test]
+fn config_diff_identical_configs_should_be_empty() {
+    // Given: two identical configs
+    let backends = vec![create_test_backend(0, None, Some(10u8))];
+    let config = create_test_config_fast(backends, Strategy::RoundRobin);
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&config, &config.clone());
+
+    // Then: diff is empty
+    assert!(diff.is_empty());
+    assert!(!diff.is_strategy_only());
+}
+
+#[test]
+fn config_diff_strategy_change_should_be_strategy_only() {
+    // Given: two configs differing only by strategy
+    let backends = vec![create_test_backend(0, None, Some(10u8))];
+    let old = create_test_config_fast(backends.clone(), Strategy::RoundRobin);
+    let new = create_test_config_fast(backends, Strategy::LeastConnections);
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: only the strategy is reported
+    assert!(diff.is_strategy_only());
+    assert_eq!(diff.strategy, Some(Strategy::LeastConnections));
+    assert!(!diff.backends_changed());
+}
+
+#[test]
+fn config_diff_fallback_change_should_be_strategy_only() {
+    // Given: two configs differing only by fallback strategy
+    let backends = vec![create_test_backend(0, None, Some(10u8))];
+    let old = create_test_config_fast(backends, Strategy::Adaptive);
+    let mut new = old.clone();
+    new.fallback = Some(Strategy::RoundRobin);
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: the strategy is rebuilt
+    assert!(diff.is_strategy_only());
+    assert_eq!(diff.strategy, Some(Strategy::Adaptive));
+}
+
+#[test]
+fn config_diff_backend_changes_should_succeed() {
+    // Given: configs with added, removed and changed backends
+    let old = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    let new = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(20u8)),
+            create_test_backend(2, None, Some(10u8)),
+        ],
+        Strategy::LeastConnections,
+    );
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: backend changes are reported and it is not strategy-only
+    assert_eq!(diff.added_backends, vec![2]);
+    assert_eq!(diff.removed_backends, vec![1]);
+    assert_eq!(diff.weight_changed_backends, vec![0]);
+    assert!(diff.changed_backends.is_empty());
+    assert!(diff.backends_changed());
+    assert!(!diff.is_strategy_only());
+}
+
+#[test]
+fn config_diff_listen_address_change_should_succeed() {
+    // Given: configs differing by listen address and strategy
+    let old = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    let mut new = create_test_config_fast(Vec::new(), Strategy::LeastConnections);
+    new.proxy.listen_address = "127.0.0.1:4000".parse().expect("valid address");
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: listen address is reported and proxy is otherwise unchanged
+    assert_eq!(diff.listen_address, Some(new.proxy.listen_address));
+    assert!(!diff.proxy_changed);
+    assert!(!diff.is_strategy_only());
+}
+
+#[test]
+fn config_diff_runtime_change_should_not_be_strategy_only() {
+    // Given: configs differing by strategy and runtime settings
+    let old = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    let mut new = create_test_config_fast(Vec::new(), Strategy::LeastConnections);
+    new.runtime.drain_timeout_millis += 1;
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: runtime change is reported
+    assert!(diff.runtime_changed);
+    assert!(!diff.is_strategy_only());
+}
+
+#[test]
+fn config_diff_address_only_change_should_be_address_changed() {
+    // Given: configs where a backend keeps its id but moves address
+    let old = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let mut new = old.clone();
+    new.backends[0].address = create_test_backend(9, None, Some(10u8)).address().clone();
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: the backend is reported as address-changed only
+    assert_eq!(diff.address_changed_backends, vec![0]);
+    assert!(diff.changed_backends.is_empty());
+    assert!(diff.backends_changed());
+}
+
+#[test]
+fn config_diff_address_and_weight_change_should_be_changed() {
+    // Given: configs where a backend moves address and changes weight
+    let old = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let mut new = old.clone();
+    new.backends[0].address = create_test_backend(9, None, Some(10u8)).address().clone();
+    new.backends[0].weight = Some(20);
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: the backend is reported as changed
+    assert_eq!(diff.changed_backends, vec![0]);
+    assert!(diff.address_changed_backends.is_empty());
+}
+
+#[test]
+fn config_diff_address_and_bind_address_change_should_be_changed() {
+    // Given: configs where a backend moves address and source address
+    let old = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let mut new = old.clone();
+    new.backends[0].address = create_test_backend(9, None, Some(10u8)).address().clone();
+    new.backends[0].bind_address = Some("127.0.0.2".parse().unwrap());
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: the backend is replaced rather than moved in place
+    assert_eq!(diff.changed_backends, vec![0]);
+    assert!(diff.address_changed_backends.is_empty());
+}
+
+#[test]
+fn config_diff_dscp_change_should_be_changed() {
+    // Given: configs where a backend changes only its DSCP value
+    let old = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let mut new = old.clone();
+    new.backends[0].dscp = Some(46);
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: the backend is replaced
+    assert_eq!(diff.changed_backends, vec![0]);
+    assert!(diff.weight_changed_backends.is_empty());
+}
+
+#[test]
+fn config_diff_priority_change_should_be_changed() {
+    // Given: configs where a backend changes its weight and its priority
+    let old = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let mut new = old.clone();
+    new.backends[0].weight = Some(20);
+    new.backends[0].priority = Some(1);
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: the backend is replaced, not just reweighted
+    assert_eq!(diff.changed_backends, vec![0]);
+    assert!(diff.weight_changed_backends.is_empty());
+}
+
+#[test]
+fn config_diff_zone_change_should_be_changed() {
+    // Given: configs where a backend changes its address and its zone
+    let old = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let mut new = old.clone();
+    new.backends[0].address = create_test_backend(9, None, Some(10u8)).address().clone();
+    new.backends[0].zone = Some("eu-west-1b".to_string());
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: the backend is replaced, not just readdressed
+    assert_eq!(diff.changed_backends, vec![0]);
+    assert!(diff.address_changed_backends.is_empty());
+}
+
+#[test]
+fn config_diff_weight_only_change_should_be_weight_only() {
+    // Given: configs where a backend only changes weight
+    let old = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+        ],
+        Strategy::WeightedRoundRobin,
+    );
+    let mut new = old.clone();
+    new.backends[1].weight = Some(30);
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: the backend is reported as weight-changed and nothing else changed
+    assert_eq!(diff.weight_changed_backends, vec![1]);
+    assert!(diff.changed_backends.is_empty());
+    assert!(diff.backends_changed());
+    assert!(diff.is_weight_only());
+    assert!(!diff.is_strategy_only());
+    assert_eq!(diff.summary(), "weight_changed_backends=[1]");
+}
+
+#[test]
+fn config_diff_weight_and_strategy_change_should_not_be_weight_only() {
+    // Given: configs changing a weight and the strategy
+    let old = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let mut new = old.clone();
+    new.backends[0].weight = Some(30);
+    new.strategy = Strategy::WeightedRoundRobin;
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: neither fast path applies
+    assert_eq!(diff.weight_changed_backends, vec![0]);
+    assert!(!diff.is_weight_only());
+    assert!(!diff.is_strategy_only());
+}
+
+#[tokio::test]
+async fn config_diff_groups_only_change_should_not_be_empty() {
+    // Given: a config with an `api` group, and one changing only that
+    // group's strategy
+    let mut old = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    old.groups.insert(
+        "api".to_string(),
+        GroupConfig {
+            listen_address: "127.0.0.1:4000".parse().unwrap(),
+            strategy: Strategy::RoundRobin,
+            strategy_params: serde_json::Value::Null,
+            fallback: None,
+            backends: vec![BackendConfig::from(create_test_backend(1, None, None))],
+            health: None,
+        },
+    );
+    let mut new = old.clone();
+    new.groups.get_mut("api").unwrap().strategy = Strategy::LeastConnections;
+
+    // When: computing the diff
+    let diff = ConfigDiff::between(&old, &new);
+
+    // Then: the change is reported rather than dropped as no change
+    assert_eq!(diff.other_changed, vec!["groups".to_string()]);
+    assert!(!diff.is_empty());
+    assert!(!diff.is_strategy_only());
+    assert_eq!(diff.summary(), "groups");
+
+    // And: a reload swaps the edited config in
+    let ctx = Context::new(old).expect("Failed to create context");
+    ctx.migrate(new).await.expect("Failed to migrate");
+    assert_eq!(
+        ctx.config().groups["api"].strategy,
+        Strategy::LeastConnections
+    );
+}
+
+#[test]
+fn dry_run_report_changed_groups_should_be_invalid() {
+    // Given: a live default group and a candidate adding a named group
+    let backends = vec![create_test_backend(0, None, Some(10u8))];
+    let live = create_test_config_fast(backends.clone(), Strategy::RoundRobin);
+    let mut candidate = create_test_config_fast(backends, Strategy::LeastConnections);
+    candidate.groups.insert(
+        "api".to_string(),
+        GroupConfig {
+            listen_address: "127.0.0.1:4000".parse().unwrap(),
+            strategy: Strategy::RoundRobin,
+            strategy_params: serde_json::Value::Null,
+            fallback: None,
+            backends: vec![BackendConfig::from(create_test_backend(1, None, None))],
+            health: None,
+        },
+    );
+
+    // When: checking the candidate
+    let report =
+        DryRunReport::check(&[(DEFAULT_GROUP.to_string(), Arc::new(live))], &candidate);
+
+    // Then: it is rejected like a reload would be, with the live group's diff
+    assert!(!report.valid);
+    assert_eq!(report.errors.len(), 1);
+    assert!(report.errors[0].contains("restart to apply"));
+    assert_eq!(
+        report.groups[DEFAULT_GROUP].strategy,
+        Some(Strategy::LeastConnections)
+    );
+    assert!(!report.groups.contains_key("api"));
+}
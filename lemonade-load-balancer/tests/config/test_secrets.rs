@@ -0,0 +1,169 @@
+//! Tests for config secret references
+
+use lemonade_load_balancer::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+fn config_with_token(token: &str) -> Config {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    config.admin.token = Some(token.to_string());
+    config
+}
+
+#[test]
+fn config_resolve_secrets_env_reference_should_succeed() {
+    // Given: a token referencing an environment variable
+    let path = std::env::var("PATH").expect("PATH should be set");
+    let mut config = config_with_token("${env:PATH}");
+
+    // When: resolving secrets
+    config
+        .resolve_secrets()
+        .expect("Env reference should resolve");
+
+    // Then: the value is substituted and the field is marked as a secret
+    assert_eq!(config.admin.token.as_deref(), Some(path.as_str()));
+    assert!(config.secrets.contains("admin.token"));
+}
+
+#[test]
+fn config_resolve_secrets_file_reference_should_succeed() {
+    // Given: an OTLP endpoint embedded around a file reference
+    let temp_dir = TempDir::new().unwrap();
+    let secret_path = temp_dir.path().join("host");
+    fs::write(&secret_path, "collector.internal\n").unwrap();
+    let mut config = config_with_token("plain");
+    config.otlp_endpoint =
+        Some(format!("http://${{file:{}}}:4317", secret_path.display()));
+
+    // When: resolving secrets
+    config
+        .resolve_secrets()
+        .expect("File reference should resolve");
+
+    // Then: the trimmed file content is substituted; plain values are untouched
+    assert_eq!(
+        config.otlp_endpoint.as_deref(),
+        Some("http://collector.internal:4317")
+    );
+    assert!(config.secrets.contains("otlp_endpoint"));
+    assert_eq!(config.admin.token.as_deref(), Some("plain"));
+    assert!(!config.secrets.contains("admin.token"));
+}
+
+#[test]
+fn config_resolve_secrets_escaped_literal_should_succeed() {
+    // Given: a value with an escaped `${`
+    let mut config = config_with_token("pa$${env:PATH}ss");
+
+    // When: resolving secrets
+    config.resolve_secrets().expect("Escape should resolve");
+
+    // Then: the literal is kept and the field is not a secret
+    assert_eq!(config.admin.token.as_deref(), Some("pa${env:PATH}ss"));
+    assert!(config.secrets.is_empty());
+}
+
+#[test]
+fn config_resolve_secrets_missing_sources_should_fail() {
+    // Given: references to a missing env var, a missing file and an unknown scheme
+    for (token, reason) in [
+        (
+            "${env:LEMONADE_TEST_UNSET_SECRET_VAR}",
+            "LEMONADE_TEST_UNSET_SECRET_VAR",
+        ),
+        (
+            "${file:/nonexistent/lemonade/secret}",
+            "/nonexistent/lemonade/secret",
+        ),
+        ("${vault:secret}", "unsupported"),
+        ("${env:PATH", "unterminated"),
+    ] {
+        let mut config = config_with_token(token);
+
+        // When: resolving secrets
+        let result = config.resolve_secrets();
+
+        // Then: the error names the field
+        match result {
+            Err(ConfigError::SecretResolution {
+                field,
+                reason: message,
+            }) => {
+                assert_eq!(field, "admin.token");
+                assert!(message.contains(reason), "{message}");
+            }
+            other => panic!("Expected SecretResolution for {token}, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn config_debug_redacts_resolved_secrets_should_succeed() {
+    // Given: a config whose OTLP endpoint and backend name come from a file
+    let temp_dir = TempDir::new().unwrap();
+    let secret_path = temp_dir.path().join("secret");
+    fs::write(&secret_path, "top-secret-value").unwrap();
+    let reference = format!("${{file:{}}}", secret_path.display());
+    let mut config = config_with_token("plain-token");
+    config.otlp_endpoint = Some(reference.clone());
+    config.backends[0].name = Some(reference);
+    config
+        .resolve_secrets()
+        .expect("File reference should resolve");
+
+    // When: formatting and redacting the config
+    let debug = format!("{:?}", config);
+    let redacted = config.redacted();
+
+    // Then: the secret never appears and the real values are kept on the original
+    assert!(!debug.contains("top-secret-value"));
+    assert!(debug.contains(REDACTED));
+    assert_eq!(redacted.otlp_endpoint.as_deref(), Some(REDACTED));
+    assert_eq!(redacted.backends[0].name.as_deref(), Some(REDACTED));
+    assert_eq!(config.otlp_endpoint.as_deref(), Some("top-secret-value"));
+}
+
+#[test]
+fn config_builder_from_file_resolves_secrets_should_succeed() {
+    // Given: a config file referencing a secret file for the admin token
+    let temp_dir = TempDir::new().unwrap();
+    let secret_path = temp_dir.path().join("token");
+    fs::write(&secret_path, "s3cret\n").unwrap();
+    let config_path = temp_dir.path().join("config.json");
+    fs::write(
+        &config_path,
+        format!(
+            r#"{{
+  "runtime": {{
+    "metrics_cap": 100,
+    "health_cap": 50,
+    "drain_timeout_millis": 1000,
+    "background_timeout_millis": 1000,
+    "accept_timeout_millis": 1000,
+    "config_watch_interval_millis": 1000
+  }},
+  "proxy": {{ "listen_address": "127.0.0.1:7000" }},
+  "strategy": "round_robin",
+  "backends": [],
+  "admin": {{ "token": "${{file:{}}}" }},
+  "health": {{ "interval": 1000, "timeout": 500 }},
+  "metrics": {{ "interval": 1000, "timeout": 500 }}
+}}"#,
+            secret_path.display()
+        ),
+    )
+    .unwrap();
+
+    // When: loading the config
+    let config = ConfigBuilder::from_file(Some(config_path)).expect("Config should load");
+
+    // Then: the token is resolved before validation and marked as a secret
+    assert_eq!(config.admin.token.as_deref(), Some("s3cret"));
+    assert!(config.secrets.contains("admin.token"));
+}
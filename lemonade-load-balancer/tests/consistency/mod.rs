@@ -0,0 +1,5 @@
+//! Consistency module tests
+//!
+//! Tests for the consistency audit
+
+mod test_checker;
@@ -0,0 +1,278 @@
+//! Tests for ConsistencyChecker
+//!
+//! Each test corrupts one structure and checks the audit reports it.
+use lemonade_load_balancer::prelude::*;
+use lemonade_load_balancer::strategy;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Round robin over the healthy backends that also claims to keep state for
+/// backends it was told about, routed or not
+struct PhantomStateStrategy {
+    name: String,
+    tracked: Vec<BackendId>,
+}
+
+#[async_trait]
+impl StrategyService for PhantomStateStrategy {
+    fn strategy(&self) -> Strategy {
+        Strategy::Custom(self.name.clone())
+    }
+
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        ctx.routing_table()
+            .healthy_backends()
+            .into_iter()
+            .next()
+            .ok_or(StrategyError::NoBackendAvailable)
+    }
+
+    fn tracked_backends(&self) -> Vec<BackendId> {
+        self.tracked.clone()
+    }
+}
+
+/// Builds [`PhantomStateStrategy`] tracking a fixed set of backends
+struct PhantomStateFactory {
+    name: String,
+    tracked: Vec<BackendId>,
+}
+
+impl StrategyFactory for PhantomStateFactory {
+    fn build(
+        &self,
+        _params: serde_json::Value,
+    ) -> Result<Arc<dyn StrategyService>, StrategyError> {
+        Ok(Arc::new(PhantomStateStrategy {
+            name: self.name.clone(),
+            tracked: self.tracked.clone(),
+        }))
+    }
+}
+
+/// Context with backends 0 and 1 whose strategy tracks `tracked`
+fn create_context_tracking(name: &str, tracked: Vec<BackendId>) -> Arc<Context> {
+    strategy::register(
+        name,
+        Arc::new(PhantomStateFactory {
+            name: name.to_string(),
+            tracked,
+        }),
+    )
+    .expect("Registration should succeed");
+    let config = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+        ],
+        Strategy::Custom(name.to_string()),
+    );
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+/// Context with backends 0 and 1 using `strategy`
+fn create_context(strategy: Strategy) -> Arc<Context> {
+    let config = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+        ],
+        strategy,
+    );
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+#[test]
+fn consistency_checker_consistent_context_should_succeed() {
+    // Given: a context nothing was done to
+    let ctx = create_context(Strategy::RoundRobin);
+
+    // When: auditing it
+    let found = ConsistencyChecker::audit(&ctx);
+
+    // Then: no invariant is violated and nothing is counted
+    assert_eq!(found, 0);
+    assert_eq!(ctx.invariant_violations(), 0);
+}
+
+#[tokio::test]
+async fn consistency_checker_adaptive_cache_of_routed_backends_should_succeed() {
+    // Given: an adaptive strategy that scored and cached the routed backends
+    let ctx = create_context(Strategy::Adaptive);
+    ctx.strategy()
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Pick should succeed");
+    assert!(!ctx.strategy().tracked_backends().is_empty());
+
+    // When: checking the invariants
+    let violations = ConsistencyChecker::check(&ctx);
+
+    // Then: every cached score belongs to a routed backend
+    assert!(violations.is_empty(), "{:?}", violations);
+}
+
+#[test]
+fn consistency_checker_unknown_tracked_backend_should_fail() {
+    // Given: a strategy keeping state for backend 9, which is not routed
+    let ctx = create_context_tracking("phantom_state_check", vec![0, 9]);
+
+    // When: auditing the context
+    let violations = ConsistencyChecker::check(&ctx);
+    let found = ConsistencyChecker::audit(&ctx);
+
+    // Then: only the unknown backend is reported, and it is counted
+    assert_eq!(
+        violations,
+        vec![InvariantViolation::UnknownBackend {
+            structure: "strategy",
+            backend_id: 9,
+        }]
+    );
+    assert_eq!(violations[0].invariant(), "unknown_backend");
+    assert_eq!(found, 1);
+    assert_eq!(ctx.invariant_violations(), 1);
+}
+
+#[test]
+fn consistency_checker_unknown_sticky_backend_should_fail() {
+    // Given: a client stuck to backend 9, which is not routed
+    let ctx = create_context(Strategy::RoundRobin);
+    let client = std::net::IpAddr::from([10, 0, 0, 1]);
+    ctx.affinity().record(client, 9, 0, u64::MAX);
+    ctx.affinity()
+        .record(std::net::IpAddr::from([10, 0, 0, 2]), 1, 0, u64::MAX);
+
+    // When: checking the invariants
+    let violations = ConsistencyChecker::check(&ctx);
+
+    // Then: only the unknown backend is reported, against the affinity table
+    assert_eq!(
+        violations,
+        vec![InvariantViolation::UnknownBackend {
+            structure: "affinity",
+            backend_id: 9,
+        }]
+    );
+}
+
+#[test]
+fn consistency_checker_unknown_probed_backend_should_fail() {
+    // Given: probe results recorded for backend 9, which is not routed
+    let ctx = create_context(Strategy::RoundRobin);
+    let passed = Ok(Duration::from_micros(250));
+    ctx.record_probe(0, HealthProbeRecord::new(1, &passed));
+    ctx.record_probe(9, HealthProbeRecord::new(1, &passed));
+
+    // When: checking the invariants
+    let violations = ConsistencyChecker::check(&ctx);
+
+    // Then: only the unknown backend is reported, against the health history
+    assert_eq!(
+        violations,
+        vec![InvariantViolation::UnknownBackend {
+            structure: "health_history",
+            backend_id: 9,
+        }]
+    );
+}
+
+#[test]
+fn consistency_checker_leaked_connection_counts_should_fail() {
+    // Given: backend counters raised without any accepted connection
+    let ctx = create_context(Strategy::RoundRobin);
+    let backend = ctx.routing_table().get(0).expect("Backend not found");
+    let tolerance = ConsistencyChecker::connection_tolerance(0);
+    for _ in 0..tolerance {
+        backend.increment_connection();
+    }
+
+    // When: the surplus is within the tolerance
+    // Then: nothing is reported
+    assert!(ConsistencyChecker::check(&ctx).is_empty());
+
+    // When: one more connection is leaked
+    backend.increment_connection();
+
+    // Then: the mismatch is reported with both counts
+    assert_eq!(
+        ConsistencyChecker::check(&ctx),
+        vec![InvariantViolation::ConnectionCountMismatch {
+            backends: tolerance + 1,
+            accepted: 0,
+            tolerance,
+        }]
+    );
+
+    // When: the connections are accounted for by the listener
+    let _guards: Vec<_> = (0..=tolerance)
+        .map(|_| ctx.listener_generations().track())
+        .collect();
+
+    // Then: the counts match again
+    assert!(ConsistencyChecker::check(&ctx).is_empty());
+}
+
+#[test]
+fn consistency_checker_connection_tolerance_should_succeed() {
+    // Given/When/Then: a floor for small counts and a tenth of large ones
+    assert_eq!(ConsistencyChecker::connection_tolerance(0), 8);
+    assert_eq!(ConsistencyChecker::connection_tolerance(80), 8);
+    assert_eq!(ConsistencyChecker::connection_tolerance(1000), 100);
+}
+
+#[tokio::test]
+async fn consistency_checker_run_periodic_audit_should_succeed() {
+    // Given: a corrupted context auditing every 10ms
+    let name = "phantom_state_run";
+    strategy::register(
+        name,
+        Arc::new(PhantomStateFactory {
+            name: name.to_string(),
+            tracked: vec![9],
+        }),
+    )
+    .expect("Registration should succeed");
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::Custom(name.to_string()),
+    );
+    config.runtime.audit_interval_millis = Some(10);
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+
+    // When: running the audit task for a while
+    let handle = tokio::spawn(ConsistencyChecker::run(ctx.clone()));
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while ctx.invariant_violations() < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("The audit should report the violation on every round");
+
+    // Then: the task stops on shutdown
+    let _ = ctx.channels().shutdown_tx().send(());
+    tokio::time::timeout(Duration::from_secs(1), handle)
+        .await
+        .expect("The audit should stop on shutdown")
+        .expect("The audit task should not panic");
+}
+
+#[tokio::test]
+async fn consistency_checker_run_disabled_should_succeed() {
+    // Given: a corrupted context with the audit disabled
+    let ctx = create_context_tracking("phantom_state_disabled", vec![9]);
+
+    // When: running the audit task
+    tokio::time::timeout(Duration::from_secs(1), ConsistencyChecker::run(ctx.clone()))
+        .await
+        .expect("A disabled audit should return at once");
+
+    // Then: nothing was audited
+    assert_eq!(ctx.invariant_violations(), 0);
+}
@@ -3,3 +3,13 @@
 //! Tests for health service adapters
 
 mod test_backend;
+mod test_endpoint;
+mod test_failure_channel;
+mod test_history;
+mod test_http_check;
+mod test_initial_state;
+mod test_models;
+mod test_noop;
+mod test_outlier;
+mod test_schedule;
+mod test_thresholds;
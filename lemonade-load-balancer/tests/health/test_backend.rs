@@ -5,7 +5,7 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::common::fixtures::create_test_context;
+use crate::common::fixtures::{create_test_config_fast, create_test_context};
 
 #[tokio::test]
 async fn backend_health_service_new_should_succeed() {
@@ -13,6 +13,20 @@ async fn backend_health_service_new_should_succeed() {
     let config = HealthConfig {
         interval: Duration::from_millis(1),
         timeout: Duration::from_millis(1),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
     };
 
     // When: creating BackendHealthService
@@ -28,6 +42,20 @@ async fn backend_health_service_check_health_performs_health_checks_should_succe
     let config = HealthConfig {
         interval: Duration::from_millis(10),
         timeout: Duration::from_millis(100),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
     };
     let service = Arc::new(
         BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
@@ -72,6 +100,20 @@ async fn backend_health_service_healthy_backend_updates_state_should_succeed() {
     let config = HealthConfig {
         interval: Duration::from_millis(10),
         timeout: Duration::from_millis(100),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
     };
     let service = Arc::new(
         BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
@@ -128,6 +170,20 @@ async fn backend_health_service_respects_backend_load_should_succeed() {
     let config = HealthConfig {
         interval: Duration::from_millis(10),
         timeout: Duration::from_millis(100),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
     };
     let service = Arc::new(
         BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
@@ -176,6 +232,20 @@ async fn backend_health_service_handles_failure_events_should_succeed() {
     let config = HealthConfig {
         interval: Duration::from_millis(100),
         timeout: Duration::from_millis(100),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
     };
     let service = Arc::new(
         BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
@@ -204,7 +274,10 @@ async fn backend_health_service_handles_failure_events_should_succeed() {
     // Send a failure event
     let failure_tx = ctx.channels().backend_failure_tx();
     let _ = failure_tx
-        .send(BackendFailureEvent::ConnectionRefused { backend_id: 0 })
+        .send(BackendFailureEvent::ConnectionRefused {
+            backend_id: 0,
+            at_micros: ctx.clock().now_micros(),
+        })
         .await;
 
     // Wait a bit for event processing
@@ -222,3 +295,687 @@ async fn backend_health_service_handles_failure_events_should_succeed() {
     // Wait for service to stop
     let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
 }
+
+#[tokio::test]
+async fn backend_health_service_reprobes_on_address_change_should_succeed() {
+    // Given: a backend pointing at a closed port and a slow periodic interval
+    let config = HealthConfig {
+        interval: Duration::from_secs(60),
+        timeout: Duration::from_millis(100),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
+    };
+    let service = Arc::new(
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
+            .expect("Failed to create service"),
+    );
+    let closed_addr = {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind server");
+        listener.local_addr().expect("Failed to get server address")
+    };
+    let backend = BackendMeta::new(0u8, Some("test"), closed_addr, Some(10u8));
+    let ctx = create_test_context(vec![backend]);
+
+    let health_handle = tokio::spawn({
+        let service = service.clone();
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+
+    // Wait for the initial sweep to mark the backend unhealthy
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let routed = ctx.routing_table().get(0).expect("Backend not found");
+    assert!(!routed.is_alive());
+
+    // When: migrating the backend to a live listener
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind server");
+    let live_addr = listener.local_addr().expect("Failed to get server address");
+    let mut new_config = (*ctx.config()).clone();
+    new_config.backends[0].address = live_addr.into();
+    ctx.migrate(new_config).await.expect("Migration failed");
+
+    // Then: the backend is re-probed immediately, long before the next tick
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(routed.is_alive());
+    assert_eq!(routed.address().as_str(), live_addr.to_string());
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+}
+
+#[tokio::test]
+async fn backend_health_service_periodic_checks_follow_mock_clock_should_succeed() {
+    // Given: a health service with a long interval on a mock clock
+    let config = HealthConfig {
+        interval: Duration::from_secs(30),
+        timeout: Duration::from_millis(100),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
+    };
+    let service = Arc::new(
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
+            .expect("Failed to create service"),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind server");
+    let server_addr = listener.local_addr().expect("Failed to get server address");
+    let server_handle = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            drop(stream);
+        }
+    });
+    let clock = Arc::new(MockClock::new(1_000));
+    let ctx = Arc::new(
+        Context::with_clock(
+            create_test_config_fast(
+                vec![BackendMeta::new(0u8, Some("test"), server_addr, Some(10u8))],
+                Strategy::RoundRobin,
+            ),
+            clock.clone(),
+        )
+        .expect("Failed to create context"),
+    );
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+    let backend = ctx.routing_table().get(0).expect("backend 0");
+
+    // When: only the initial check has run
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // Then: the check is stamped with the virtual time and no periodic check
+    // runs until the clock advances by the interval
+    assert_eq!(backend.last_health_check(), 1_000);
+    clock.advance(Duration::from_secs(29));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(backend.last_health_check(), 1_000);
+    clock.advance(Duration::from_secs(1));
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while backend.last_health_check() != 31_000 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Periodic check should run once the interval elapses");
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn backend_health_service_recovery_starts_slow_start_should_succeed() {
+    // Given: a health service with a 10s slow start on a mock clock, past its
+    // initial check
+    let config = HealthConfig {
+        interval: Duration::from_secs(30),
+        timeout: Duration::from_millis(100),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 10_000,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
+    };
+    let service = Arc::new(
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
+            .expect("Failed to create service"),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind server");
+    let server_addr = listener.local_addr().expect("Failed to get server address");
+    let server_handle = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            drop(stream);
+        }
+    });
+    let clock = Arc::new(MockClock::new(1_000));
+    let ctx = Arc::new(
+        Context::with_clock(
+            create_test_config_fast(
+                vec![BackendMeta::new(0u8, Some("test"), server_addr, Some(10u8))],
+                Strategy::WeightedRoundRobin,
+            ),
+            clock.clone(),
+        )
+        .expect("Failed to create context"),
+    );
+    let backend = ctx.routing_table().get(0).expect("backend 0");
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while !ctx.readiness().is_health_checked() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Initial health check should finish");
+    assert_eq!(backend.effective_weight_milli(1_000), 10_000);
+
+    // When: the backend is marked down and the next periodic probe succeeds
+    backend.set_health(false, 1_000);
+    clock.advance(Duration::from_secs(30));
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while !backend.is_alive() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Backend should recover");
+
+    // Then: it comes back at a tenth of its weight and ramps up over the window
+    assert_eq!(backend.effective_weight_milli(31_000), 1_000);
+    assert_eq!(backend.effective_weight_milli(36_000), 5_500);
+    assert_eq!(backend.effective_weight_milli(41_000), 10_000);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn backend_health_service_coalesces_probes_of_shared_address_should_succeed() {
+    // Given: two backends on one address whose server counts connections
+    let config = HealthConfig {
+        interval: Duration::from_secs(30),
+        timeout: Duration::from_millis(100),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
+    };
+    let service = Arc::new(
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
+            .expect("Failed to create service"),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind server");
+    let server_addr = listener.local_addr().expect("Failed to get server address");
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let server_handle = tokio::spawn({
+        let accepted = accepted.clone();
+        async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                drop(stream);
+            }
+        }
+    });
+    let mut lb_config = create_test_config_fast(
+        vec![
+            BackendMeta::new(0u8, Some("blue"), server_addr, Some(10u8)),
+            BackendMeta::new(1u8, Some("green"), server_addr, Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    lb_config.allow_duplicate_addresses = true;
+    let clock = Arc::new(MockClock::new(1_000));
+    let ctx = Arc::new(
+        Context::with_clock(lb_config, clock.clone()).expect("Failed to create context"),
+    );
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+    let routing = ctx.routing_table();
+    let blue = routing.get(0).expect("backend 0");
+    let green = routing.get(1).expect("backend 1");
+
+    // When: the initial sweep and one periodic sweep run
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while !ctx.readiness().is_health_checked() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Initial health check should finish");
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    clock.advance(Duration::from_secs(30));
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while blue.last_health_check() != 31_000 || green.last_health_check() != 31_000 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Periodic check should update both backends");
+
+    // Then: each sweep sent one probe and both backends took its result
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    assert!(blue.is_alive());
+    assert!(green.is_alive());
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn backend_health_service_discards_expired_failure_events_should_succeed() {
+    // Given: a healthy backend and failure events expiring after 10s
+    let config = HealthConfig {
+        interval: Duration::from_secs(60),
+        timeout: Duration::from_millis(100),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
+    };
+    let service = Arc::new(
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
+            .expect("Failed to create service"),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind server");
+    let server_addr = listener.local_addr().expect("Failed to get server address");
+    let server_handle = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            drop(stream);
+        }
+    });
+    let clock = Arc::new(MockClock::new(100_000));
+    let ctx = Arc::new(
+        Context::with_clock(
+            create_test_config_fast(
+                vec![BackendMeta::new(0u8, Some("test"), server_addr, Some(10u8))],
+                Strategy::RoundRobin,
+            ),
+            clock.clone(),
+        )
+        .expect("Failed to create context"),
+    );
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+    let backend = ctx.routing_table().get(0).expect("backend 0");
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while !ctx.readiness().is_health_checked() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Initial health check should finish");
+    assert!(backend.is_alive());
+
+    // When: a backlog of failures reported a minute ago is processed
+    let failure_tx = ctx.channels().backend_failure_tx();
+    let stale_micros = clock.now_micros() - 60_000_000;
+    for _ in 0..3 {
+        let _ = failure_tx
+            .send(BackendFailureEvent::Timeout {
+                backend_id: 0,
+                at_micros: stale_micros,
+            })
+            .await;
+    }
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while ctx.channels().health_events_expired() < 3 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Stale failures should be expired");
+
+    // Then: they are counted as expired and the backend stays up
+    assert_eq!(ctx.channels().health_events_expired(), 3);
+    assert!(backend.is_alive());
+
+    // When: a fresh failure arrives
+    let _ = failure_tx
+        .send(BackendFailureEvent::Timeout {
+            backend_id: 0,
+            at_micros: clock.now_micros(),
+        })
+        .await;
+
+    // Then: it still marks the backend down
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while backend.is_alive() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Fresh failure should mark the backend unhealthy");
+    assert_eq!(ctx.channels().health_events_expired(), 3);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+    server_handle.abort();
+}
+
+/// Listener that accepts until [`StallableListener::stall`], after which
+/// connects to it hang until they time out
+struct StallableListener {
+    _listener: Arc<tokio::net::TcpListener>,
+    address: SocketAddr,
+    accept_handle: tokio::task::JoinHandle<()>,
+    fillers: Vec<tokio::net::TcpStream>,
+}
+
+impl StallableListener {
+    /// Listen with a backlog of one pending connection
+    async fn start() -> Self {
+        let socket = tokio::net::TcpSocket::new_v4().expect("socket");
+        socket
+            .bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+            .expect("bind");
+        let listener = Arc::new(socket.listen(0).expect("listen"));
+        let address = listener.local_addr().expect("address");
+        let accept_handle = tokio::spawn({
+            let listener = listener.clone();
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    drop(stream);
+                }
+            }
+        });
+        Self {
+            _listener: listener,
+            address,
+            accept_handle,
+            fillers: Vec::new(),
+        }
+    }
+
+    /// Stop accepting and fill the backlog so new connects hang
+    async fn stall(&mut self) {
+        self.accept_handle.abort();
+        let _ = (&mut self.accept_handle).await;
+        for _ in 0..4 {
+            match tokio::time::timeout(
+                Duration::from_millis(200),
+                tokio::net::TcpStream::connect(self.address),
+            )
+            .await
+            {
+                Ok(Ok(stream)) => self.fillers.push(stream),
+                _ => return,
+            }
+        }
+        panic!("backlog never filled");
+    }
+}
+
+/// Health service with a long interval on a mock clock and a slow timeout,
+/// over `backends`; returns the context, its clock and its health events
+async fn start_probing(
+    backends: Vec<BackendMeta>,
+) -> (
+    Arc<Context>,
+    Arc<MockClock>,
+    tokio::sync::mpsc::Receiver<HealthEvent>,
+) {
+    let config = HealthConfig {
+        interval: Duration::from_secs(30),
+        timeout: Duration::from_millis(800),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
+    };
+    let service = BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
+        .expect("Failed to create service");
+    let clock = Arc::new(MockClock::new(1_000));
+    let ctx = Arc::new(
+        Context::with_clock(
+            create_test_config_fast(backends, Strategy::RoundRobin),
+            clock.clone(),
+        )
+        .expect("Failed to create context"),
+    );
+    let health_rx = ctx.channels().health_rx().expect("health receiver");
+    tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !ctx.readiness().is_health_checked() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Initial health check should finish");
+    (ctx, clock, health_rx)
+}
+
+/// Backend ids reported unhealthy among the queued health events
+fn unhealthy_reports(
+    health_rx: &mut tokio::sync::mpsc::Receiver<HealthEvent>,
+) -> Vec<u8> {
+    let mut reports = Vec::new();
+    while let Ok(event) = health_rx.try_recv() {
+        if let HealthEvent::BackendUnhealthy { backend_id, .. } = event {
+            reports.push(backend_id);
+        }
+    }
+    reports
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn backend_health_service_discards_probe_of_old_address_should_succeed() {
+    // Given: a healthy backend whose listener stops answering
+    let mut old = StallableListener::start().await;
+    let (ctx, clock, mut health_rx) = start_probing(vec![BackendMeta::new(
+        0u8,
+        Some("moving"),
+        old.address,
+        Some(10u8),
+    )])
+    .await;
+    let backend = ctx.routing_table().get(0).expect("backend 0");
+    assert!(backend.is_alive());
+    old.stall().await;
+
+    // And: a periodic probe to the old address is in flight
+    clock.advance(Duration::from_secs(30));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // When: the backend migrates to a live address mid-probe
+    let live = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind server");
+    let live_addr = live.local_addr().expect("live address");
+    let mut new_config = (*ctx.config()).clone();
+    new_config.backends[0].address = live_addr.into();
+    ctx.migrate(new_config).await.expect("Migration failed");
+
+    // Then: once the old probe would have timed out, the backend never
+    // flipped unhealthy
+    tokio::time::sleep(Duration::from_millis(1_200)).await;
+    assert!(backend.is_alive());
+    assert_eq!(backend.address().as_str(), live_addr.to_string());
+    assert!(unhealthy_reports(&mut health_rx).is_empty());
+
+    let _ = ctx.channels().shutdown_tx().send(());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn backend_health_service_cancels_probe_of_removed_backend_should_succeed() {
+    // Given: two healthy backends, one of which stops answering
+    let mut leaving = StallableListener::start().await;
+    let staying = StallableListener::start().await;
+    let (ctx, clock, mut health_rx) = start_probing(vec![
+        BackendMeta::new(0u8, Some("leaving"), leaving.address, Some(10u8)),
+        BackendMeta::new(1u8, Some("staying"), staying.address, Some(10u8)),
+    ])
+    .await;
+    let removed = ctx.routing_table().get(0).expect("backend 0");
+    leaving.stall().await;
+
+    // And: a periodic probe to it is in flight
+    clock.advance(Duration::from_secs(30));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // When: the backend is removed mid-probe
+    let mut new_config = (*ctx.config()).clone();
+    new_config.backends.retain(|backend| backend.id != 0);
+    ctx.migrate(new_config).await.expect("Migration failed");
+
+    // Then: the probe is cancelled instead of reporting into the old id
+    tokio::time::sleep(Duration::from_millis(1_200)).await;
+    assert!(removed.is_alive());
+    assert!(unhealthy_reports(&mut health_rx).is_empty());
+    assert!(ctx.routing_table().get(1).expect("backend 1").is_alive());
+
+    let _ = ctx.channels().shutdown_tx().send(());
+}
+
+/// Wait for the next health transition among the health events
+async fn next_transition(
+    health_rx: &mut tokio::sync::mpsc::Receiver<HealthEvent>,
+) -> (BackendId, HealthStatus, TransitionCause) {
+    tokio::time::timeout(Duration::from_secs(3), async {
+        loop {
+            match health_rx.recv().await {
+                Some(HealthEvent::HealthTransition {
+                    backend_id,
+                    to,
+                    cause,
+                    ..
+                }) => return (backend_id, to, cause),
+                Some(_) => continue,
+                None => panic!("health channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("No health transition reported")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn backend_health_service_reports_timeout_transition_reason_should_succeed() {
+    // Given: a healthy backend whose listener stops answering
+    let mut listener = StallableListener::start().await;
+    let (ctx, clock, mut health_rx) = start_probing(vec![BackendMeta::new(
+        0u8,
+        Some("stalled"),
+        listener.address,
+        Some(10u8),
+    )])
+    .await;
+    listener.stall().await;
+
+    // When: the next periodic probe times out
+    clock.advance(Duration::from_secs(30));
+    let (backend_id, to, cause) = next_transition(&mut health_rx).await;
+
+    // Then: the transition carries the timeout reason
+    assert_eq!((backend_id, to), (0, HealthStatus::Unhealthy));
+    assert_eq!(cause.reason, Some(HealthFailureReason::Timeout));
+    assert_eq!(cause.consecutive_failures, 1);
+    assert!(
+        cause
+            .detail
+            .as_deref()
+            .is_some_and(|detail| detail.starts_with("no connection within")),
+        "{:?}",
+        cause
+    );
+
+    let _ = ctx.channels().shutdown_tx().send(());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn backend_health_service_reports_refused_transition_reason_should_succeed() {
+    // Given: a healthy backend that goes away
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let address = listener.local_addr().expect("backend address");
+    let (ctx, clock, mut health_rx) = start_probing(vec![BackendMeta::new(
+        0u8,
+        Some("gone"),
+        address,
+        Some(10u8),
+    )])
+    .await;
+    drop(listener);
+
+    // When: the next periodic probe is refused
+    clock.advance(Duration::from_secs(30));
+    let (backend_id, to, cause) = next_transition(&mut health_rx).await;
+
+    // Then: the transition carries the refused reason and the connect error
+    assert_eq!((backend_id, to), (0, HealthStatus::Unhealthy));
+    assert_eq!(cause.reason, Some(HealthFailureReason::ConnectionRefused));
+    assert_eq!(cause.consecutive_failures, 1);
+    assert!(cause.detail.is_some_and(|detail| !detail.is_empty()));
+    assert_eq!(cause.rtt_micros, None);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+}
@@ -0,0 +1,188 @@
+//! Tests for HealthEndpointServer
+//!
+use lemonade_load_balancer::prelude::*;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Start a health endpoint for `ctx` on an ephemeral port
+async fn start_endpoint(ctx: Arc<Context>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind health endpoint");
+    let addr = listener.local_addr().expect("health endpoint address");
+    tokio::spawn(async move { HealthEndpointServer::new().serve(listener, ctx).await });
+    addr
+}
+
+/// Send a GET and return the status code and body
+async fn get(addr: SocketAddr, path: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr)
+        .await
+        .expect("Failed to connect to health endpoint");
+    let request =
+        format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .expect("Failed to write request");
+    let mut response = String::new();
+    tokio::time::timeout(Duration::from_secs(2), stream.read_to_string(&mut response))
+        .await
+        .expect("Health endpoint response timed out")
+        .expect("Failed to read response");
+
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .expect("Malformed status line");
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default();
+    (status, body)
+}
+
+/// Poll `path` until it answers `status`
+async fn wait_for_status(addr: SocketAddr, path: &str, status: u16) -> String {
+    for _ in 0..100 {
+        let (code, body) = get(addr, path).await;
+        if code == status {
+            return body;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("{} never answered {}", path, status);
+}
+
+#[tokio::test]
+async fn health_endpoint_readiness_transitions_should_succeed() {
+    // Given: a live backend and a health endpoint before the proxy starts
+    let backend_listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let backend_handle = tokio::spawn(async move {
+        while let Ok((stream, _)) = backend_listener.accept().await {
+            drop(stream);
+        }
+    });
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(0u8, Some("only"), backend_addr, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = proxy_addr;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let addr = start_endpoint(ctx.clone()).await;
+
+    // Then: neither live nor ready
+    let (live, _) = get(addr, "/healthz").await;
+    let (ready, body) = get(addr, "/readyz").await;
+    assert_eq!((live, ready), (503, 503));
+    let json: serde_json::Value = serde_json::from_str(&body).expect("JSON body");
+    assert_eq!(json["status"], "unavailable");
+    assert!(json["reason"].as_str().unwrap().contains("not accepting"));
+
+    // When: the proxy and the health service start
+    let proxy = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create proxy");
+    let health =
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.health)))
+            .expect("Failed to create health service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { proxy.accept_connections(ctx).await }
+    });
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { health.check_health(ctx).await }
+    });
+
+    // Then: live, and ready once the initial health check found the backend
+    wait_for_status(addr, "/healthz", 200).await;
+    let body = wait_for_status(addr, "/readyz", 200).await;
+    assert!(ctx.readiness().is_health_checked());
+    let json: serde_json::Value = serde_json::from_str(&body).expect("JSON body");
+    assert_eq!(json["status"], "ok");
+
+    // When: every backend goes down
+    backend_handle.abort();
+    ctx.routing_table()
+        .get(0)
+        .expect("backend 0")
+        .set_health(false, 0);
+
+    // Then: still live but not ready, naming the healthy count
+    let (live, _) = get(addr, "/healthz").await;
+    let (ready, body) = get(addr, "/readyz").await;
+    assert_eq!((live, ready), (200, 503));
+    let json: serde_json::Value = serde_json::from_str(&body).expect("JSON body");
+    assert!(
+        json["reason"]
+            .as_str()
+            .unwrap()
+            .contains("0 healthy backends, 1 required"),
+        "{}",
+        body
+    );
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_secs(1), health_handle).await;
+    let _ = tokio::time::timeout(Duration::from_secs(1), proxy_handle).await;
+    assert!(!ctx.readiness().is_accepting());
+}
+
+#[tokio::test]
+async fn health_endpoint_initial_health_check_pending_should_not_be_ready() {
+    // Given: an accepting proxy whose initial health check has not finished
+    let config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    ctx.readiness().set_accepting(true);
+    let addr = start_endpoint(ctx.clone()).await;
+
+    // When: probing readiness
+    let (status, body) = get(addr, "/readyz").await;
+
+    // Then: not ready until the health check finishes
+    assert_eq!(status, 503);
+    assert!(body.contains("initial health check"), "{}", body);
+    ctx.readiness().mark_health_checked();
+    assert_eq!(get(addr, "/readyz").await.0, 200);
+}
+
+#[tokio::test]
+async fn health_endpoint_min_healthy_backends_should_succeed() {
+    // Given: two healthy backends of three, requiring three
+    let mut config = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+            create_test_backend(2, None, Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    config.health_endpoint.min_healthy_backends = 3;
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    ctx.readiness().set_accepting(true);
+    ctx.readiness().mark_health_checked();
+    ctx.routing_table()
+        .get(2)
+        .expect("backend 2")
+        .set_health(false, 0);
+    let addr = start_endpoint(ctx.clone()).await;
+
+    // When/Then: not ready, and unknown paths are not found
+    let (status, body) = get(addr, "/readyz").await;
+    assert_eq!(status, 503);
+    assert!(body.contains("2 healthy backends, 3 required"), "{}", body);
+    assert_eq!(get(addr, "/nope").await.0, 404);
+}
@@ -0,0 +1,260 @@
+//! Tests for the backend failure channel contract
+//!
+//! Failure events go through the context's real `ChannelBundle` into a
+//! running `BackendHealthService`, the channel's only consumer.
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Health service with periodic probes far enough apart not to interfere
+fn health_service() -> Arc<BackendHealthService> {
+    let config = HealthConfig {
+        interval: Duration::from_secs(60),
+        timeout: Duration::from_millis(200),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
+    };
+    Arc::new(
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
+            .expect("Failed to create service"),
+    )
+}
+
+/// Wait until the consumed events reach `expected`
+async fn wait_for_stats(ctx: &Context, expected: BackendFailureStats) {
+    let settled = tokio::time::timeout(Duration::from_secs(2), async {
+        while ctx.channels().backend_failure_stats() != expected {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await;
+    assert!(
+        settled.is_ok(),
+        "expected {:?}, got {:?}",
+        expected,
+        ctx.channels().backend_failure_stats()
+    );
+}
+
+#[tokio::test]
+async fn backend_failure_events_each_variant_counted_should_succeed() {
+    // Given: five reachable backends checked healthy by a running service
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind server");
+    let server_addr = listener.local_addr().expect("server address");
+    let server_handle = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            drop(stream);
+        }
+    });
+    let backends = (0..5u8)
+        .map(|id| {
+            BackendMeta::new(
+                id,
+                Some("backend"),
+                BackendAddress::from(server_addr),
+                Some(1u8),
+            )
+        })
+        .collect();
+    let mut config = create_test_config_fast(backends, Strategy::RoundRobin);
+    config.allow_duplicate_addresses = true;
+    let clock = Arc::new(MockClock::new(100_000));
+    let ctx = Arc::new(
+        Context::with_clock(config, clock.clone()).expect("Failed to create context"),
+    );
+    let service = health_service();
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !ctx.readiness().is_health_checked() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Initial health check should finish");
+    let routing = ctx.routing_table();
+    assert!(
+        routing
+            .all_backends()
+            .iter()
+            .all(|backend| backend.is_alive())
+    );
+    let failure_tx = ctx.channels().backend_failure_tx();
+    let now = clock.now_micros();
+
+    // When: each variant is reported for a different healthy backend
+    let events = [
+        BackendFailureEvent::ConnectionRefused {
+            backend_id: 0,
+            at_micros: now,
+        },
+        BackendFailureEvent::Timeout {
+            backend_id: 1,
+            at_micros: now,
+        },
+        BackendFailureEvent::BackendClosed {
+            backend_id: 2,
+            at_micros: now,
+        },
+        BackendFailureEvent::ConsecutiveErrors {
+            backend_id: 3,
+            count: 5,
+            at_micros: now,
+        },
+        BackendFailureEvent::SourceBindFailed {
+            backend_id: 4,
+            at_micros: now,
+        },
+    ];
+    for event in events {
+        failure_tx
+            .send(event)
+            .await
+            .expect("Channel should be open");
+    }
+
+    // Then: every event marks its backend unhealthy
+    wait_for_stats(
+        &ctx,
+        BackendFailureStats {
+            received: 5,
+            acted_upon: 5,
+            ..BackendFailureStats::default()
+        },
+    )
+    .await;
+    assert!(
+        routing
+            .all_backends()
+            .iter()
+            .all(|backend| !backend.is_alive())
+    );
+
+    // When: a failure is reported again for an unhealthy backend, for an
+    // unrouted backend, and long after it happened
+    failure_tx
+        .send(BackendFailureEvent::Timeout {
+            backend_id: 0,
+            at_micros: now,
+        })
+        .await
+        .expect("Channel should be open");
+    failure_tx
+        .send(BackendFailureEvent::ConnectionRefused {
+            backend_id: 9,
+            at_micros: now,
+        })
+        .await
+        .expect("Channel should be open");
+    failure_tx
+        .send(BackendFailureEvent::BackendClosed {
+            backend_id: 1,
+            at_micros: now - 60_000_000,
+        })
+        .await
+        .expect("Channel should be open");
+
+    // Then: each is ignored under its own counter
+    wait_for_stats(
+        &ctx,
+        BackendFailureStats {
+            received: 8,
+            acted_upon: 5,
+            ignored_already_unhealthy: 1,
+            ignored_unknown_backend: 1,
+            expired: 1,
+        },
+    )
+    .await;
+    assert!(routing.get(9).is_none());
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn backend_failure_event_for_unhealthy_backend_keeps_state_should_succeed() {
+    // Given: a backend that refuses connections, so starts unhealthy
+    let refused_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve refused port");
+    let ctx = Arc::new(
+        Context::new(create_test_config_fast(
+            vec![BackendMeta::new(
+                0u8,
+                Some("down"),
+                BackendAddress::from(refused_addr),
+                Some(1u8),
+            )],
+            Strategy::RoundRobin,
+        ))
+        .expect("Failed to create context"),
+    );
+    let mut health_rx = ctx.channels().health_rx().expect("health receiver");
+    let service = health_service();
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !ctx.readiness().is_health_checked() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Initial health check should finish");
+    let backend = ctx.routing_table().get(0).expect("backend 0");
+    assert!(!backend.is_alive());
+    while health_rx.try_recv().is_ok() {}
+
+    // When: the proxy reports a failure for it
+    ctx.channels()
+        .backend_failure_tx()
+        .send(BackendFailureEvent::ConnectionRefused {
+            backend_id: 0,
+            at_micros: ctx.clock().now_micros(),
+        })
+        .await
+        .expect("Channel should be open");
+
+    // Then: it is counted as ignored, with no health transition
+    wait_for_stats(
+        &ctx,
+        BackendFailureStats {
+            received: 1,
+            ignored_already_unhealthy: 1,
+            ..BackendFailureStats::default()
+        },
+    )
+    .await;
+    assert!(!backend.is_alive());
+    while let Ok(event) = health_rx.try_recv() {
+        assert!(
+            !matches!(event, HealthEvent::HealthTransition { .. }),
+            "unexpected transition: {:?}",
+            event
+        );
+    }
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+}
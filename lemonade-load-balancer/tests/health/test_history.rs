@@ -0,0 +1,161 @@
+//! Tests for the per-backend health probe history
+//!
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Record of a probe completed at `at_ms`, failed when `passed` is false
+fn probe(at_ms: u64, passed: bool) -> HealthProbeRecord {
+    let result = match passed {
+        true => Ok(Duration::from_micros(250)),
+        false => Err(ProbeFailure::new(
+            HealthFailureReason::ConnectionRefused,
+            "refused",
+        )),
+    };
+    HealthProbeRecord::new(at_ms, &result)
+}
+
+/// Completion times of `records`
+fn times(records: &[HealthProbeRecord]) -> Vec<u64> {
+    records.iter().map(|record| record.at_ms).collect()
+}
+
+#[test]
+fn health_history_evicts_oldest_should_succeed() {
+    // Given: a history keeping 3 records per backend
+    let history = HealthHistory::new(3);
+
+    // When: backend 0 is probed 5 times
+    for at_ms in 1..=5 {
+        history.record(0, probe(at_ms, at_ms % 2 == 0));
+    }
+
+    // Then: only its 3 latest probes are kept, oldest first
+    let records = history.get(0);
+    assert_eq!(times(&records), vec![3, 4, 5]);
+    assert_eq!(
+        records
+            .iter()
+            .map(|record| record.passed())
+            .collect::<Vec<_>>(),
+        vec![false, true, false]
+    );
+    assert_eq!(records[1].rtt_micros, Some(250));
+    assert_eq!(
+        records[2].failure,
+        Some(HealthFailureReason::ConnectionRefused)
+    );
+
+    // And: other backends have no history until probed
+    assert!(history.get(1).is_empty());
+    assert_eq!(history.remove_backends(&[0, 1]), 1);
+    assert!(history.get(0).is_empty());
+}
+
+#[tokio::test]
+async fn context_health_history_cleared_on_migration_should_succeed() {
+    // Given: a context keeping 4 probe results per backend, with backends
+    // 0 to 3 probed 6 times each
+    let mut config = create_test_config_fast(
+        (0..4)
+            .map(|id| create_test_backend(id, None, Some(1u8)))
+            .collect(),
+        Strategy::RoundRobin,
+    );
+    config.runtime.health_cap = 4;
+    let ctx = Context::new(config.clone()).expect("Failed to create context");
+    for at_ms in 1..=6 {
+        for backend_id in 0..4 {
+            ctx.record_probe(backend_id, probe(at_ms, true));
+        }
+    }
+    assert_eq!(times(&ctx.health_history(0)), vec![3, 4, 5, 6]);
+
+    // When: a reload replaces backend 1, removes backend 2 and moves
+    // backend 3 to another address
+    config.backends[1].name = Some("replacement".to_string());
+    config.backends[3].address = "127.0.0.1:9999"
+        .parse::<std::net::SocketAddr>()
+        .unwrap()
+        .into();
+    config.backends.retain(|backend| backend.id != 2);
+    ctx.migrate(config).await.expect("Failed to migrate");
+
+    // Then: only the unchanged backend keeps its history
+    assert_eq!(times(&ctx.health_history(0)), vec![3, 4, 5, 6]);
+    for backend_id in 1..4 {
+        assert!(ctx.health_history(backend_id).is_empty());
+    }
+    assert_eq!(ctx.stale_entries_evicted(), 3);
+}
+
+#[tokio::test]
+async fn health_service_records_probes_should_succeed() {
+    // Given: a backend checked every 10ms
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.backends = vec![BackendConfig::from(BackendMeta::new(
+        0u8,
+        None::<String>,
+        addr,
+        Some(1),
+    ))];
+    config.health.interval = Duration::from_millis(10);
+    config.health.timeout = Duration::from_millis(100);
+    let service =
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.health.clone())))
+            .expect("Failed to create service");
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+
+    // When: it answers a few probes, then goes away
+    for _ in 0..100 {
+        if ctx.health_history(0).len() >= 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    drop(listener);
+    for _ in 0..100 {
+        if ctx.health_history(0).iter().any(|record| !record.passed()) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Then: its history holds the passing probes, with their RTT, followed
+    // by the failing ones, with their reason
+    let history = ctx.health_history(0);
+    let first_failed = history
+        .iter()
+        .position(|record| !record.passed())
+        .expect("A probe should have failed");
+    assert!(first_failed >= 3);
+    assert!(
+        history[..first_failed]
+            .iter()
+            .all(|record| record.rtt_micros.is_some())
+    );
+    assert!(
+        history[first_failed..]
+            .iter()
+            .all(|record| record.failure == Some(HealthFailureReason::ConnectionRefused))
+    );
+    assert!(
+        history
+            .windows(2)
+            .all(|pair| pair[0].at_ms <= pair[1].at_ms)
+    );
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    health_handle.abort();
+}
@@ -0,0 +1,456 @@
+//! Tests for HTTP health checks
+//!
+//! This module tests:
+//! - Probing over HTTP and checking the status
+//! - Reusing keep-alive connections between probes
+//! - Closing pooled connections of backends marked down or removed
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::common::fixtures::create_test_context;
+
+/// Status a [`http_backend`] answers with to send a response that is not HTTP
+const NOT_HTTP: u16 = 0;
+
+/// Status a [`http_backend`] answers with to never answer
+const SILENT: u16 = 1;
+
+/// Backend answering every request with the status in `status`, keeping the
+/// request heads it received
+pub(crate) async fn http_backend(
+    status: Arc<AtomicU16>,
+) -> (
+    SocketAddr,
+    Arc<Mutex<Vec<String>>>,
+    tokio::task::JoinHandle<()>,
+) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let received = requests.clone();
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let status = status.clone();
+            let received = received.clone();
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut chunk = [0u8; 256];
+                while !head.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut chunk).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => head.extend_from_slice(&chunk[..read]),
+                    }
+                }
+                received
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&head).into_owned());
+                let response = match status.load(Ordering::SeqCst) {
+                    NOT_HTTP => "PONG\r\n".to_string(),
+                    SILENT => {
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                        return;
+                    }
+                    code => format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\n\r\n", code),
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    (addr, requests, handle)
+}
+
+/// Connections a [`keep_alive_backend`] accepted and still has open
+#[derive(Clone, Default)]
+pub(crate) struct ConnectionCounts {
+    accepted: Arc<AtomicUsize>,
+    open: Arc<AtomicUsize>,
+}
+
+/// Backend answering every request of a connection with the status in
+/// `status`, keeping the connection open for the next one
+pub(crate) async fn keep_alive_backend(
+    status: Arc<AtomicU16>,
+) -> (SocketAddr, ConnectionCounts, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let counts = ConnectionCounts::default();
+    let counted = counts.clone();
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            counted.accepted.fetch_add(1, Ordering::SeqCst);
+            counted.open.fetch_add(1, Ordering::SeqCst);
+            let status = status.clone();
+            let counted = counted.clone();
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut chunk = [0u8; 256];
+                loop {
+                    match stream.read(&mut chunk).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(read) => head.extend_from_slice(&chunk[..read]),
+                    }
+                    if !head.ends_with(b"\r\n\r\n") {
+                        continue;
+                    }
+                    head.clear();
+                    let response = format!(
+                        "HTTP/1.1 {} X\r\nContent-Length: 2\r\n\r\nok",
+                        status.load(Ordering::SeqCst)
+                    );
+                    if stream.write_all(response.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+                counted.open.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+    (addr, counts, handle)
+}
+
+/// HTTP check of `/health` expecting a 200
+pub(crate) fn http_check() -> HealthCheck {
+    HealthCheck::Http {
+        path: "/health".to_string(),
+        expected_status: 200,
+        host_header: None,
+    }
+}
+
+#[tokio::test]
+async fn http_probe_expected_status_should_succeed() {
+    // Given: a backend answering 200
+    let (addr, requests, handle) = http_backend(Arc::new(AtomicU16::new(200))).await;
+    let address = BackendAddress::from(addr);
+
+    // When: probing it over HTTP
+    let result = BackendHealthService::probe(
+        &address,
+        None,
+        Duration::from_millis(500),
+        &http_check(),
+    )
+    .await;
+
+    // Then: the probe passes after a GET of the path with the address as host
+    assert!(result.is_ok(), "{:?}", result);
+    let request = requests.lock().unwrap()[0].clone();
+    assert!(
+        request.starts_with("GET /health HTTP/1.1\r\n"),
+        "{}",
+        request
+    );
+    assert!(
+        request.contains(&format!("Host: {}\r\n", addr)),
+        "{}",
+        request
+    );
+
+    // And: a configured host header is sent instead
+    BackendHealthService::http_probe(
+        &address,
+        None,
+        Duration::from_millis(500),
+        "/ready?full=1",
+        200,
+        Some("lb.internal"),
+    )
+    .await
+    .expect("Probe should pass");
+    let request = requests.lock().unwrap()[1].clone();
+    assert!(request.starts_with("GET /ready?full=1 HTTP/1.1\r\n"));
+    assert!(request.contains("Host: lb.internal\r\n"));
+    handle.abort();
+}
+
+#[tokio::test]
+async fn http_probe_unexpected_response_should_fail() {
+    // Given: a backend whose answer changes
+    let status = Arc::new(AtomicU16::new(503));
+    let (addr, _, handle) = http_backend(status.clone()).await;
+    let address = BackendAddress::from(addr);
+    let check = http_check();
+    let probe = || {
+        BackendHealthService::probe(&address, None, Duration::from_millis(200), &check)
+    };
+
+    // When/Then: a different status is an invalid response
+    let failure = probe().await.expect_err("503 should fail");
+    assert_eq!(failure.reason, HealthFailureReason::InvalidResponse);
+    assert!(failure.detail.contains("503"), "{}", failure.detail);
+
+    // And: so is an answer that is not HTTP
+    status.store(NOT_HTTP, Ordering::SeqCst);
+    let failure = probe().await.expect_err("non-HTTP answer should fail");
+    assert_eq!(failure.reason, HealthFailureReason::InvalidResponse);
+
+    // And: no answer within the timeout is a timeout
+    status.store(SILENT, Ordering::SeqCst);
+    let failure = probe().await.expect_err("no answer should fail");
+    assert_eq!(failure.reason, HealthFailureReason::Timeout);
+
+    // And: a plain TCP check still passes
+    let result = BackendHealthService::probe(
+        &address,
+        None,
+        Duration::from_millis(200),
+        &HealthCheck::Tcp,
+    )
+    .await;
+    assert!(result.is_ok());
+    handle.abort();
+}
+
+/// Wait up to a second for backend 0 of `ctx` to be `alive`
+async fn wait_for_health(ctx: &Context, alive: bool) -> bool {
+    for _ in 0..100 {
+        if ctx
+            .routing_table()
+            .get(0)
+            .is_some_and(|b| b.is_alive() == alive)
+        {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn backend_health_service_http_check_flips_health_should_succeed() {
+    // Given: a health service checking /health every 10ms
+    let status = Arc::new(AtomicU16::new(200));
+    let (addr, _, handle) = http_backend(status.clone()).await;
+    let ctx = create_test_context(vec![BackendMeta::new(
+        0u8,
+        Some("worker"),
+        addr,
+        Some(10u8),
+    )]);
+    let config = HealthConfig {
+        interval: Duration::from_millis(10),
+        timeout: Duration::from_millis(100),
+        check: http_check(),
+        ..ctx.config().health.clone()
+    };
+    let service = BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config)))
+        .expect("Failed to create service");
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+
+    // Then: the backend answering 200 is healthy
+    assert!(wait_for_health(&ctx, true).await);
+
+    // When: the backend starts answering 503 while still accepting
+    status.store(503, Ordering::SeqCst);
+
+    // Then: it is marked unhealthy
+    assert!(wait_for_health(&ctx, false).await);
+
+    // When: it answers 200 again
+    status.store(200, Ordering::SeqCst);
+
+    // Then: it comes back
+    assert!(wait_for_health(&ctx, true).await);
+
+    // When: the backend stops
+    handle.abort();
+    let _ = handle.await;
+
+    // Then: it is marked unhealthy
+    assert!(wait_for_health(&ctx, false).await);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+}
+
+/// Health config probing `/health` over HTTP, reusing connections when
+/// `reuse_connections` is set
+fn pooled_http_config(ctx: &Context, reuse_connections: bool) -> HealthConfig {
+    HealthConfig {
+        interval: Duration::from_millis(10),
+        timeout: Duration::from_millis(200),
+        check: http_check(),
+        reuse_connections,
+        ..ctx.config().health.clone()
+    }
+}
+
+#[tokio::test]
+async fn http_probe_reuses_connection_should_succeed() {
+    // Given: a keep-alive backend and a service reusing connections
+    let (addr, counts, handle) = keep_alive_backend(Arc::new(AtomicU16::new(200))).await;
+    let address = Arc::new(BackendAddress::from(addr));
+    let config = pooled_http_config(&create_test_context(vec![]), true);
+    let service =
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.clone())))
+            .expect("Failed to create service");
+
+    // When: probing it three times
+    let mut timings = Vec::new();
+    for _ in 0..3 {
+        timings.push(
+            service
+                .check_endpoint(&address, None, &config)
+                .await
+                .expect("Probe should pass"),
+        );
+    }
+
+    // Then: one connection carries every probe, and only the first one
+    // measures a handshake
+    assert_eq!(counts.accepted.load(Ordering::SeqCst), 1);
+    assert_eq!(service.pooled_connections(), 1);
+    assert!(timings[0].handshake.is_some());
+    assert!(timings[1..].iter().all(|timing| timing.handshake.is_none()));
+    handle.abort();
+}
+
+#[tokio::test]
+async fn http_probe_without_reuse_opens_connections_should_succeed() {
+    // Given: a keep-alive backend and a service not reusing connections
+    let (addr, counts, handle) = keep_alive_backend(Arc::new(AtomicU16::new(200))).await;
+    let address = Arc::new(BackendAddress::from(addr));
+    let config = pooled_http_config(&create_test_context(vec![]), false);
+    let service =
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.clone())))
+            .expect("Failed to create service");
+
+    // When: probing it three times
+    for _ in 0..3 {
+        let timings = service
+            .check_endpoint(&address, None, &config)
+            .await
+            .expect("Probe should pass");
+        assert_eq!(timings.handshake, None);
+    }
+
+    // Then: each probe opened its own connection and none is kept
+    assert_eq!(counts.accepted.load(Ordering::SeqCst), 3);
+    assert_eq!(service.pooled_connections(), 0);
+    handle.abort();
+}
+
+#[tokio::test]
+async fn http_probe_reconnects_after_backend_closes_should_succeed() {
+    // Given: a backend closing each connection after its response, and a
+    // service reusing connections
+    let (addr, requests, handle) = http_backend(Arc::new(AtomicU16::new(200))).await;
+    let address = Arc::new(BackendAddress::from(addr));
+    let config = pooled_http_config(&create_test_context(vec![]), true);
+    let service =
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.clone())))
+            .expect("Failed to create service");
+
+    // When: probing it twice
+    let first = service.check_endpoint(&address, None, &config).await;
+    let second = service.check_endpoint(&address, None, &config).await;
+
+    // Then: both pass, the second over a new connection
+    assert!(first.is_ok(), "{:?}", first);
+    assert!(
+        second.is_ok_and(|timings| timings.handshake.is_some()),
+        "second probe should reconnect"
+    );
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    handle.abort();
+}
+
+/// Wait up to a second for `condition` to hold
+async fn wait_until(condition: impl Fn() -> bool) -> bool {
+    for _ in 0..100 {
+        if condition() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn backend_health_service_closes_pooled_connection_on_mark_down_should_succeed() {
+    // Given: a health service probing a keep-alive backend every 10ms
+    let status = Arc::new(AtomicU16::new(200));
+    let (addr, counts, handle) = keep_alive_backend(status.clone()).await;
+    let ctx = create_test_context(vec![BackendMeta::new(
+        0u8,
+        Some("worker"),
+        addr,
+        Some(10u8),
+    )]);
+    let service = Arc::new(
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(pooled_http_config(
+            &ctx, true,
+        ))))
+        .expect("Failed to create service"),
+    );
+    let health_handle = tokio::spawn({
+        let (ctx, service) = (ctx.clone(), service.clone());
+        async move { service.check_health(ctx).await }
+    });
+
+    // Then: its probes share one connection
+    assert!(wait_until(|| ctx.health_history(0).len() >= 4).await);
+    assert_eq!(counts.accepted.load(Ordering::SeqCst), 1);
+
+    // When: the backend starts answering 503 over the same connection
+    status.store(503, Ordering::SeqCst);
+
+    // Then: once marked down its connection is closed and the next probe
+    // opens a new one
+    assert!(wait_for_health(&ctx, false).await);
+    assert!(wait_until(|| counts.accepted.load(Ordering::SeqCst) == 2).await);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+    handle.abort();
+}
+
+#[tokio::test]
+async fn backend_health_service_closes_pooled_connection_on_removal_should_succeed() {
+    // Given: a health service probing two keep-alive backends every 10ms
+    let status = Arc::new(AtomicU16::new(200));
+    let (kept_addr, _, kept_handle) = keep_alive_backend(status.clone()).await;
+    let (removed_addr, removed_counts, removed_handle) =
+        keep_alive_backend(status.clone()).await;
+    let ctx = create_test_context(vec![
+        BackendMeta::new(0u8, Some("kept"), kept_addr, Some(10u8)),
+        BackendMeta::new(1u8, Some("removed"), removed_addr, Some(10u8)),
+    ]);
+    let service = Arc::new(
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(pooled_http_config(
+            &ctx, true,
+        ))))
+        .expect("Failed to create service"),
+    );
+    let health_handle = tokio::spawn({
+        let (ctx, service) = (ctx.clone(), service.clone());
+        async move { service.check_health(ctx).await }
+    });
+    assert!(wait_until(|| service.pooled_connections() == 2).await);
+
+    // When: a reload removes backend 1
+    let mut new_config = (*ctx.config()).clone();
+    new_config.backends.retain(|backend| backend.id != 1);
+    ctx.migrate(new_config).await.expect("Migration failed");
+
+    // Then: its pooled connection is closed, the other one kept
+    assert!(wait_until(|| removed_counts.open.load(Ordering::SeqCst) == 0).await);
+    assert_eq!(service.pooled_connections(), 1);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+    kept_handle.abort();
+    removed_handle.abort();
+}
@@ -0,0 +1,222 @@
+//! Tests for the initial health state of backends
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Round robin config over `backends`, whose backends start unhealthy
+fn start_unhealthy_config(backends: Vec<BackendMeta>) -> Config {
+    let mut config = create_test_config_fast(backends, Strategy::RoundRobin);
+    config.health.initial_state = InitialHealthState::Unhealthy;
+    config
+}
+
+/// Whether each routed backend of `ctx` is alive, by id
+fn alive(ctx: &Context) -> Vec<(BackendId, bool)> {
+    let mut alive: Vec<_> = ctx
+        .routing_table()
+        .all_backends()
+        .iter()
+        .map(|backend| (backend.id(), backend.is_alive()))
+        .collect();
+    alive.sort();
+    alive
+}
+
+/// Reply to one connection through the proxy, empty when it was rejected
+async fn reply(proxy: SocketAddr) -> String {
+    let mut client = tokio::net::TcpStream::connect(proxy)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut reply = String::new();
+    let _ =
+        tokio::time::timeout(Duration::from_secs(2), client.read_to_string(&mut reply))
+            .await
+            .expect("Proxy should answer or close");
+    reply
+}
+
+#[test]
+fn initial_health_state_parse_should_succeed() {
+    // Given/When: health read without an initial state
+    let config: HealthConfig = serde_json::from_value(serde_json::json!({
+        "interval_millis": 1000,
+        "timeout_millis": 100,
+    }))
+    .expect("Failed to parse");
+
+    // Then: backends start healthy
+    assert_eq!(config.initial_state, InitialHealthState::Healthy);
+
+    // And: both states parse from config and env var values
+    assert_eq!(
+        serde_json::from_value::<InitialHealthState>(serde_json::json!("unhealthy"))
+            .expect("Failed to parse"),
+        InitialHealthState::Unhealthy
+    );
+    assert_eq!("healthy".parse(), Ok(InitialHealthState::Healthy));
+    assert_eq!("unhealthy".parse(), Ok(InitialHealthState::Unhealthy));
+    assert!("down".parse::<InitialHealthState>().is_err());
+}
+
+#[tokio::test]
+async fn context_start_unhealthy_should_succeed() {
+    // Given/When: a context whose backends start unhealthy
+    let mut config = start_unhealthy_config(
+        (0..2)
+            .map(|id| create_test_backend(id, None, Some(1u8)))
+            .collect(),
+    );
+    let ctx = Context::new(config.clone()).expect("Failed to create context");
+
+    // Then: none is routed to before a probe
+    assert_eq!(alive(&ctx), vec![(0, false), (1, false)]);
+    assert!(ctx.routing_table().healthy_backends().is_empty());
+
+    // When: backend 0 passes a probe and a reload adds backend 2
+    ctx.routing_table()
+        .get(0)
+        .unwrap()
+        .set_health(true, ctx.clock().now_millis());
+    config
+        .backends
+        .push(create_test_backend(2, None, Some(1u8)).into());
+    ctx.migrate(config.clone())
+        .await
+        .expect("Failed to migrate");
+
+    // Then: the added backend starts unhealthy too, the others keep their
+    // health
+    assert_eq!(alive(&ctx), vec![(0, true), (1, false), (2, false)]);
+
+    // And: without health checks to bring them in, backends start healthy
+    config.services.health = false;
+    let ctx = Context::new(config).expect("Failed to create context");
+    assert_eq!(alive(&ctx), vec![(0, true), (1, true), (2, true)]);
+}
+
+#[test]
+fn started_unhealthy_until_reported_or_restored_should_succeed() {
+    // Given: backends started unhealthy by the initial state
+    let ctx = Context::new(start_unhealthy_config(
+        (0..2)
+            .map(|id| create_test_backend(id, None, Some(1u8)))
+            .collect(),
+    ))
+    .expect("Failed to create context");
+    let routing = ctx.routing_table();
+    let (probed, restored) = (routing.get(0).unwrap(), routing.get(1).unwrap());
+    assert!(probed.started_unhealthy() && restored.started_unhealthy());
+
+    // When: one fails a probe and the other is restored down from the
+    // health state file
+    let now_ms = ctx.clock().now_millis();
+    probed.set_health(false, now_ms);
+    restored.restore_health(false, 3, now_ms);
+
+    // Then: neither may skip the healthy threshold any more
+    assert!(!probed.started_unhealthy());
+    assert!(!restored.started_unhealthy());
+
+    // And: backends started healthy never could
+    let backend = Backend::new(create_test_backend(2, None, Some(1u8)).into());
+    assert!(!backend.started_unhealthy());
+}
+
+#[tokio::test]
+async fn start_unhealthy_rejects_until_first_probe_should_succeed() {
+    // Given: a proxy over a serving b0 that starts unhealthy, probed every
+    // 500ms and brought back after 3 passing probes
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let mut config = start_unhealthy_config(vec![BackendMeta::new(
+        0u8,
+        Some("b0"),
+        listener.local_addr().unwrap(),
+        Some(1u8),
+    )]);
+    let backend_handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let _ = tokio::io::AsyncWriteExt::write_all(&mut stream, b"b0").await;
+        }
+    });
+    config.proxy.listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    config.health.interval = Duration::from_millis(500);
+    config.health.timeout = Duration::from_millis(100);
+    config.health.healthy_threshold = 3;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let proxy = config.proxy.listen_address;
+    let service =
+        TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+            .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client connects before the initial probe
+    // Then: it is rejected
+    assert_eq!(reply(proxy).await, "");
+
+    // When: health checking starts
+    let health =
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.health.clone())))
+            .expect("Failed to create service");
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { health.check_health(ctx).await }
+    });
+    for _ in 0..100 {
+        if ctx.readiness().is_health_checked() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Then: the initial probe brings b0 in, and clients reach it
+    assert!(ctx.routing_table().get(0).unwrap().is_alive());
+    assert_eq!(reply(proxy).await, "b0");
+
+    // When: a reload adds b1, serving too
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    config.backends.push(
+        BackendMeta::new(1u8, Some("b1"), listener.local_addr().unwrap(), Some(1u8))
+            .into(),
+    );
+    let added_handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let _ = tokio::io::AsyncWriteExt::write_all(&mut stream, b"b1").await;
+        }
+    });
+    ctx.migrate(config).await.expect("Failed to migrate");
+    let added = ctx.routing_table().get(1).expect("b1 is routed");
+    for _ in 0..200 {
+        if added.is_alive() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Then: its first passing probe brings it in, short of the threshold
+    assert!(added.is_alive());
+    assert_eq!(ctx.health_history(1).len(), 1);
+    let mut served = vec![reply(proxy).await, reply(proxy).await];
+    served.sort();
+    assert_eq!(served, ["b0", "b1"]);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    proxy_handle.abort();
+    for handle in [health_handle, backend_handle, added_handle] {
+        handle.abort();
+    }
+}
@@ -0,0 +1,46 @@
+//! Tests for health models
+//!
+use lemonade_load_balancer::prelude::*;
+
+#[test]
+fn transition_cause_failure_truncates_detail_should_succeed() {
+    // Given: an error text longer than the limit, with a multi-byte character
+    // straddling it
+    let detail = format!(
+        "{}é{}",
+        "x".repeat(MAX_TRANSITION_DETAIL_LEN - 1),
+        "y".repeat(50)
+    );
+
+    // When: building a failure cause
+    let cause = TransitionCause::failure(HealthFailureReason::Timeout, 3, detail);
+
+    // Then: the detail is cut on a character boundary within the limit
+    let kept = cause.detail.expect("detail");
+    assert_eq!(kept.len(), MAX_TRANSITION_DETAIL_LEN - 1);
+    assert_eq!(cause.reason, Some(HealthFailureReason::Timeout));
+    assert_eq!(cause.consecutive_failures, 3);
+}
+
+#[test]
+fn health_failure_reason_codes_should_be_distinct() {
+    // Given: the failure reasons
+    let reasons = [
+        HealthFailureReason::Timeout,
+        HealthFailureReason::ConnectionRefused,
+        HealthFailureReason::InvalidResponse,
+        HealthFailureReason::DnsError,
+        HealthFailureReason::Transport,
+    ];
+
+    // When: rendering their codes
+    let codes: std::collections::HashSet<&str> =
+        reasons.iter().map(HealthFailureReason::as_str).collect();
+
+    // Then: every reason has its own code
+    assert_eq!(codes.len(), reasons.len());
+    assert_eq!(
+        HealthFailureReason::ConnectionRefused.to_string(),
+        "connection_refused"
+    );
+}
@@ -0,0 +1,30 @@
+//! Tests for NoopHealthService
+//!
+use lemonade_load_balancer::prelude::*;
+use std::time::Duration;
+
+use crate::common::fixtures::{create_test_backend, create_test_context};
+
+#[tokio::test]
+async fn noop_health_service_marks_checked_without_probing_should_succeed() {
+    // Given: a backend nobody listens on
+    let ctx = create_test_context(vec![create_test_backend(0, None, Some(10u8))]);
+    let mut health_rx = ctx
+        .channels()
+        .health_rx()
+        .expect("Health receiver should be available");
+
+    // When: running the no-op health service
+    tokio::time::timeout(
+        Duration::from_secs(1),
+        NoopHealthService::new().check_health(ctx.clone()),
+    )
+    .await
+    .expect("No-op health service should return right away");
+
+    // Then: readiness does not wait on a check, the backend stays healthy
+    // and no health events are produced
+    assert!(ctx.readiness().is_health_checked());
+    assert!(ctx.routing_table().get(0).expect("Backend").is_alive());
+    assert!(health_rx.try_recv().is_err());
+}
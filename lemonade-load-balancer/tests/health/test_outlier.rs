@@ -0,0 +1,369 @@
+//! Tests for outlier ejection
+//!
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Health config ejecting at half of at least 3 connections failed within
+/// 10s, for 30s at first
+fn outlier_config(max_ejection_percent: u8) -> HealthConfig {
+    HealthConfig {
+        outlier_error_percent: 50,
+        outlier_min_connections: 3,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent,
+        ..create_test_config_fast(Vec::new(), Strategy::RoundRobin).health
+    }
+}
+
+/// Route table over backends `0..count`
+fn route_table(count: u8) -> RouteTable {
+    RouteTable::new(
+        (0..count)
+            .map(|id| BackendConfig::from(create_test_backend(id, None, Some(1))))
+            .collect(),
+    )
+}
+
+/// Record `count` failures of `backend`, one per second from `at_ms`, and
+/// return the last verdict
+fn burst(
+    detector: &mut OutlierDetector,
+    backend: &Arc<Backend>,
+    at_ms: u64,
+    count: u64,
+    config: &HealthConfig,
+    routing: &RouteTable,
+) -> OutlierVerdict {
+    (0..count)
+        .map(|i| detector.record_failure(backend, at_ms + i * 1_000, config, routing))
+        .last()
+        .expect("at least one failure")
+}
+
+#[test]
+fn outlier_detector_burst_ejects_should_succeed() {
+    // Given: four backends
+    let config = outlier_config(50);
+    let routing = route_table(4);
+    let backend = routing.get(0).unwrap();
+    let mut detector = OutlierDetector::default();
+
+    // When: failures come slower than three per window
+    for i in 0..6 {
+        let verdict = detector.record_failure(&backend, i * 6_000, &config, &routing);
+        // Then: the backend is never ejected
+        assert_eq!(verdict, OutlierVerdict::Tolerated);
+    }
+
+    // When: three failures come within the window
+    let verdict = burst(&mut detector, &backend, 100_000, 3, &config, &routing);
+
+    // Then: the backend is ejected for the base interval
+    assert_eq!(verdict, OutlierVerdict::Ejected(Duration::from_secs(30)));
+    assert!(detector.is_ejected(0, 102_000));
+    assert!(detector.is_ejected(0, 131_999));
+    assert!(!detector.is_ejected(0, 132_000));
+    assert_eq!(detector.ejected_count(102_000), 1);
+    assert_eq!(detector.next_readmission(), Some(132_000));
+
+    // And: failures while ejected do not extend the ejection
+    let verdict = burst(&mut detector, &backend, 110_000, 3, &config, &routing);
+    assert_eq!(verdict, OutlierVerdict::Tolerated);
+    assert_eq!(detector.next_readmission(), Some(132_000));
+
+    // And: the backend is handed back once the ejection ends
+    assert!(detector.take_readmitted(131_999).is_empty());
+    let readmitted = detector.take_readmitted(132_000);
+    assert_eq!(readmitted.len(), 1);
+    assert!(Arc::ptr_eq(&readmitted[0], &backend));
+    assert_eq!(detector.next_readmission(), None);
+}
+
+#[test]
+fn outlier_detector_error_rate_should_succeed() {
+    // Given: four backends
+    let config = outlier_config(50);
+    let routing = route_table(4);
+    let busy = routing.get(0).unwrap();
+    let mut detector = OutlierDetector::default();
+
+    // When: a busy backend fails one connection in a hundred
+    for i in 0..20 {
+        for _ in 0..99 {
+            busy.record_connection_completed();
+        }
+        let verdict = detector.record_failure(&busy, i * 100, &config, &routing);
+
+        // Then: it is never ejected, however many failures the window holds
+        assert_eq!(verdict, OutlierVerdict::Tolerated);
+    }
+
+    // When: its completed connections stop while failures go on
+    let verdict = burst(&mut detector, &busy, 2_000, 11, &config, &routing);
+
+    // Then: once the old connections leave the window, the rate ejects it
+    assert!(matches!(verdict, OutlierVerdict::Ejected(_)));
+
+    // Given: an idle backend
+    let idle = routing.get(1).unwrap();
+
+    // When: it fails each of its few connections
+    let verdicts: Vec<_> = (0..3)
+        .map(|i| detector.record_failure(&idle, i * 1_000, &config, &routing))
+        .collect();
+
+    // Then: it is ejected once the window holds enough of them
+    assert_eq!(verdicts[..2], [OutlierVerdict::Tolerated; 2]);
+    assert!(matches!(verdicts[2], OutlierVerdict::Ejected(_)));
+}
+
+#[test]
+fn outlier_detector_repeated_ejections_back_off_should_succeed() {
+    // Given: a backend ejected once, for 30s
+    let config = outlier_config(50);
+    let routing = route_table(4);
+    let backend = routing.get(0).unwrap();
+    let mut detector = OutlierDetector::default();
+    burst(&mut detector, &backend, 0, 3, &config, &routing);
+    let mut now_ms = 32_000;
+
+    // When: it fails again right after every readmission
+    let mut intervals = Vec::new();
+    for _ in 0..7 {
+        detector.take_readmitted(now_ms);
+        let OutlierVerdict::Ejected(interval) =
+            burst(&mut detector, &backend, now_ms, 3, &config, &routing)
+        else {
+            panic!("backend should be ejected");
+        };
+        intervals.push(interval.as_secs());
+        now_ms = detector.next_readmission().unwrap();
+    }
+
+    // Then: each ejection doubles, up to 32 times the base interval
+    assert_eq!(intervals, [60, 120, 240, 480, 960, 960, 960]);
+
+    // When: it stays in rotation for as long as it was last out
+    detector.take_readmitted(now_ms);
+    let verdict = burst(
+        &mut detector,
+        &backend,
+        now_ms + 960_000,
+        3,
+        &config,
+        &routing,
+    );
+
+    // Then: the next ejection starts over from the base interval
+    assert_eq!(verdict, OutlierVerdict::Ejected(Duration::from_secs(30)));
+}
+
+#[test]
+fn outlier_detector_max_ejection_percent_should_succeed() {
+    // Given: four backends, at most half of them ejected
+    let config = outlier_config(50);
+    let routing = route_table(4);
+    let mut detector = OutlierDetector::default();
+
+    // When: three backends fail in bursts
+    let verdicts: Vec<_> = (0..3)
+        .map(|id| {
+            let backend = routing.get(id).unwrap();
+            burst(&mut detector, &backend, 0, 3, &config, &routing)
+        })
+        .collect();
+
+    // Then: the third stays in rotation
+    assert!(matches!(verdicts[0], OutlierVerdict::Ejected(_)));
+    assert!(matches!(verdicts[1], OutlierVerdict::Ejected(_)));
+    assert_eq!(
+        verdicts[2],
+        OutlierVerdict::Skipped(EjectionSkipped::MaxEjectionPercent)
+    );
+    assert_eq!(detector.ejected_count(2_000), 2);
+
+    // And: a zero percent still ejects one backend
+    let config = outlier_config(0);
+    let mut detector = OutlierDetector::default();
+    let first = burst(
+        &mut detector,
+        &routing.get(0).unwrap(),
+        0,
+        3,
+        &config,
+        &routing,
+    );
+    let second = burst(
+        &mut detector,
+        &routing.get(1).unwrap(),
+        0,
+        3,
+        &config,
+        &routing,
+    );
+    assert!(matches!(first, OutlierVerdict::Ejected(_)));
+    assert_eq!(
+        second,
+        OutlierVerdict::Skipped(EjectionSkipped::MaxEjectionPercent)
+    );
+}
+
+#[test]
+fn outlier_detector_last_healthy_backend_is_kept_should_succeed() {
+    // Given: two backends, the other one unhealthy
+    let config = outlier_config(100);
+    let routing = route_table(2);
+    routing.get(1).unwrap().set_health(false, 0);
+    let backend = routing.get(0).unwrap();
+    let mut detector = OutlierDetector::default();
+
+    // When: the last healthy backend fails in a burst
+    let verdict = burst(&mut detector, &backend, 0, 3, &config, &routing);
+
+    // Then: it is not ejected
+    assert_eq!(
+        verdict,
+        OutlierVerdict::Skipped(EjectionSkipped::LastHealthy)
+    );
+    assert!(!detector.is_ejected(0, 2_000));
+
+    // When: the other backend recovers and the next failure arrives
+    routing.get(1).unwrap().set_health(true, 0);
+    let verdict = detector.record_failure(&backend, 3_000, &config, &routing);
+
+    // Then: the burst still in the window ejects it
+    assert!(matches!(verdict, OutlierVerdict::Ejected(_)));
+}
+
+#[test]
+fn outlier_detector_disabled_or_replaced_should_succeed() {
+    // Given: outlier ejection disabled
+    let routing = route_table(2);
+    let backend = routing.get(0).unwrap();
+    let mut config = outlier_config(50);
+    config.outlier_error_percent = 0;
+    let mut detector = OutlierDetector::default();
+
+    // Then: no burst ejects a backend
+    let verdict = burst(&mut detector, &backend, 0, 10, &config, &routing);
+    assert_eq!(verdict, OutlierVerdict::Tolerated);
+
+    // Given: an ejected backend
+    let config = outlier_config(50);
+    burst(&mut detector, &backend, 0, 3, &config, &routing);
+    assert!(detector.is_ejected(0, 2_000));
+
+    // When: a migration replaces it
+    detector.retain_current(&route_table(2));
+
+    // Then: its ejection is forgotten
+    assert!(!detector.is_ejected(0, 2_000));
+    assert_eq!(detector.next_readmission(), None);
+}
+
+/// Wait until `backend` has health `alive`
+async fn wait_for_health(backend: &Backend, alive: bool) {
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while backend.is_alive() != alive {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Backend health should change");
+}
+
+#[tokio::test]
+async fn backend_health_service_ejects_and_readmits_outlier_should_succeed() {
+    // Given: two reachable backends probed every 10s on a mock clock
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind server");
+    let server_addr = listener.local_addr().expect("Failed to get server address");
+    let server_handle = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            drop(stream);
+        }
+    });
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.allow_duplicate_addresses = true;
+    config.backends = (0u8..2)
+        .map(|id| {
+            BackendConfig::from(BackendMeta::new(
+                id,
+                None::<String>,
+                server_addr,
+                Some(1),
+            ))
+        })
+        .collect();
+    config.health = HealthConfig {
+        interval: Duration::from_secs(10),
+        timeout: Duration::from_millis(100),
+        ..outlier_config(50)
+    };
+    let service =
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.health.clone())))
+            .expect("Failed to create service");
+    let clock = Arc::new(MockClock::new(100_000));
+    let ctx = Arc::new(
+        Context::with_clock(config, clock.clone()).expect("Failed to create context"),
+    );
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while !ctx.readiness().is_health_checked() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Initial health check should finish");
+    let backend = ctx.routing_table().get(0).expect("backend 0");
+    let failure_tx = ctx.channels().backend_failure_tx();
+    let fail = || BackendFailureEvent::BackendClosed {
+        backend_id: 0,
+        at_micros: clock.now_micros(),
+    };
+
+    // When: the proxy reports a burst of three failures
+    for _ in 0..3 {
+        let _ = failure_tx.send(fail()).await;
+    }
+    wait_for_health(&backend, false).await;
+
+    // Then: passing probes do not bring it back for 30s
+    for _ in 0..2 {
+        clock.advance(Duration::from_secs(10));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!backend.is_alive());
+    }
+    clock.advance(Duration::from_millis(9_999));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!backend.is_alive());
+
+    // And: the probe at the end of the ejection re-admits it
+    clock.advance(Duration::from_millis(1));
+    wait_for_health(&backend, true).await;
+
+    // When: it fails in a burst again right away
+    for _ in 0..3 {
+        let _ = failure_tx.send(fail()).await;
+    }
+    wait_for_health(&backend, false).await;
+
+    // Then: it is ejected for twice as long
+    clock.advance(Duration::from_millis(59_999));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!backend.is_alive());
+    clock.advance(Duration::from_millis(1));
+    wait_for_health(&backend, true).await;
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), health_handle).await;
+    server_handle.abort();
+}
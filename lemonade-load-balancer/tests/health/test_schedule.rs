@@ -0,0 +1,196 @@
+//! Tests for the staggered and jittered schedule of periodic health probes
+//!
+use lemonade_load_balancer::prelude::*;
+use std::collections::HashSet;
+use std::sync::atomic::AtomicU16;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::test_http_check::{http_backend, http_check};
+use crate::common::fixtures::create_test_config_fast;
+
+/// Status a [`http_backend`] answers with to never answer
+const SILENT: u16 = 1;
+
+/// Wait up to two seconds for `requests` to hold `count` requests
+async fn wait_for_requests(requests: &Mutex<Vec<String>>, count: usize) -> bool {
+    for _ in 0..200 {
+        if requests.lock().unwrap().len() >= count {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    false
+}
+
+#[test]
+fn stagger_offset_spreads_consecutive_ids_should_succeed() {
+    // Given: an interval of 800ms
+    let interval = Duration::from_millis(800);
+
+    // When: staggering backends 0 to 7
+    let offsets: Vec<u64> = (0..8u8)
+        .map(|id| stagger_offset(id, interval).as_millis() as u64)
+        .collect();
+
+    // Then: they are spread evenly over the interval
+    assert_eq!(offsets, vec![0, 400, 200, 600, 100, 500, 300, 700]);
+
+    // And: any id stays within the interval
+    assert!((0..=u8::MAX).all(|id| stagger_offset(id, interval) < interval));
+}
+
+#[test]
+fn jittered_interval_stays_in_bounds_should_succeed() {
+    let interval = Duration::from_millis(1000);
+
+    // Given/When: intervals of several backends over many rounds with 10%
+    // jitter
+    let intervals: Vec<Duration> = (0..4u8)
+        .flat_map(|id| {
+            (0..100).map(move |round| jittered_interval(id, round, interval, 10))
+        })
+        .collect();
+
+    // Then: each stays within 10% of the interval, and they vary
+    assert!(intervals.iter().all(|jittered| {
+        *jittered >= Duration::from_millis(900)
+            && *jittered <= Duration::from_millis(1100)
+    }));
+    assert!(intervals.iter().collect::<HashSet<_>>().len() > 50);
+
+    // And: the same backend and round always get the same interval
+    assert_eq!(
+        jittered_interval(3, 7, interval, 10),
+        jittered_interval(3, 7, interval, 10)
+    );
+
+    // And: no jitter keeps the interval exact
+    assert!((0..100).all(|round| jittered_interval(5, round, interval, 0) == interval));
+}
+
+#[test]
+fn probe_schedule_take_due_should_succeed() {
+    // Given: backends 0 and 1 scheduled at 1000ms with a 100ms interval
+    let interval = Duration::from_millis(100);
+    let mut schedule = ProbeSchedule::default();
+    assert_eq!(schedule.next_due(), None);
+    schedule.sync(&[0, 1], 1_000, interval);
+
+    // Then: backend 1 is due half an interval in, backend 0 a full interval
+    assert_eq!(schedule.next_due(), Some(1_050));
+    assert!(schedule.take_due(1_049, interval, 0).is_empty());
+    assert_eq!(schedule.take_due(1_050, interval, 0), vec![1]);
+    assert_eq!(schedule.next_due(), Some(1_100));
+    assert_eq!(schedule.take_due(1_100, interval, 0), vec![0]);
+
+    // And: each is due one interval after it was taken
+    assert_eq!(schedule.next_due(), Some(1_150));
+    assert_eq!(schedule.take_due(1_200, interval, 0), vec![0, 1]);
+    assert_eq!(schedule.next_due(), Some(1_300));
+
+    // When: backend 0 is dropped and backend 2 added
+    schedule.sync(&[1, 2], 1_200, interval);
+
+    // Then: backend 1 keeps its schedule and backend 2 starts staggered
+    assert_eq!(schedule.take_due(1_300, interval, 0), vec![1, 2]);
+    schedule.sync(&[], 1_300, interval);
+    assert_eq!(schedule.next_due(), None);
+}
+
+#[tokio::test]
+async fn backend_health_service_staggers_probes_should_succeed() {
+    // Given: a health service probing two backends every 10s on a mock clock
+    let (first_addr, first_requests, first_handle) =
+        http_backend(Arc::new(AtomicU16::new(200))).await;
+    let (second_addr, second_requests, second_handle) =
+        http_backend(Arc::new(AtomicU16::new(200))).await;
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.backends = [first_addr, second_addr]
+        .into_iter()
+        .enumerate()
+        .map(|(id, addr)| {
+            BackendConfig::from(BackendMeta::new(id as u8, None::<String>, addr, Some(1)))
+        })
+        .collect();
+    config.health = HealthConfig {
+        interval: Duration::from_secs(10),
+        timeout: Duration::from_millis(500),
+        check: http_check(),
+        ..config.health
+    };
+    let service =
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.health.clone())))
+            .expect("Failed to create service");
+    let clock = Arc::new(MockClock::new(100_000));
+    let ctx = Arc::new(
+        Context::with_clock(config, clock.clone()).expect("Failed to create context"),
+    );
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+    assert!(wait_for_requests(&first_requests, 1).await);
+    assert!(wait_for_requests(&second_requests, 1).await);
+
+    // When: half an interval passes
+    clock.advance(Duration::from_secs(5));
+
+    // Then: only the second backend is probed
+    assert!(wait_for_requests(&second_requests, 2).await);
+    assert_eq!(first_requests.lock().unwrap().len(), 1);
+
+    // When: the other half passes
+    clock.advance(Duration::from_secs(5));
+
+    // Then: the first backend is probed, the second not again yet
+    assert!(wait_for_requests(&first_requests, 2).await);
+    assert_eq!(second_requests.lock().unwrap().len(), 2);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    for handle in [first_handle, second_handle, health_handle] {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn backend_health_service_slow_probe_no_pile_up_should_succeed() {
+    // Given: a backend that never answers, probed every 10ms with a 1s
+    // timeout
+    let (addr, requests, handle) = http_backend(Arc::new(AtomicU16::new(SILENT))).await;
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.backends = vec![BackendConfig::from(BackendMeta::new(
+        0u8,
+        None::<String>,
+        addr,
+        Some(1),
+    ))];
+    config.health = HealthConfig {
+        interval: Duration::from_millis(10),
+        timeout: Duration::from_secs(1),
+        check: http_check(),
+        jitter_percent: MAX_JITTER_PERCENT,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
+        ..config.health
+    };
+    let service =
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.health.clone())))
+            .expect("Failed to create service");
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+
+    // When: the initial check times out and a periodic probe starts hanging
+    assert!(wait_for_requests(&requests, 2).await);
+
+    // Then: many intervals later, no probe was stacked on the hanging one
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(requests.lock().unwrap().len(), 2);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    handle.abort();
+    health_handle.abort();
+}
@@ -0,0 +1,201 @@
+//! Tests for the healthy and unhealthy thresholds of health transitions
+//!
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc::Receiver;
+
+use super::test_http_check::{http_backend, http_check};
+use crate::common::fixtures::create_test_config_fast;
+
+/// Health service probing backend 0 every 10s on a mock clock, with the
+/// status its HTTP check answers
+struct Probed {
+    ctx: Arc<Context>,
+    clock: Arc<MockClock>,
+    status: Arc<AtomicU16>,
+    events: Receiver<HealthEvent>,
+    handles: Vec<tokio::task::JoinHandle<()>>,
+}
+
+impl Probed {
+    /// Start the service once the initial check passed
+    async fn start(healthy_threshold: u32, unhealthy_threshold: u32) -> Self {
+        let status = Arc::new(AtomicU16::new(200));
+        let (addr, _, server_handle) = http_backend(status.clone()).await;
+        let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+        config.backends = vec![BackendConfig::from(BackendMeta::new(
+            0u8,
+            None::<String>,
+            addr,
+            Some(1),
+        ))];
+        config.health = HealthConfig {
+            interval: Duration::from_secs(10),
+            timeout: Duration::from_millis(500),
+            check: http_check(),
+            healthy_threshold,
+            unhealthy_threshold,
+            ..config.health
+        };
+        let service = BackendHealthService::new(Arc::new(ArcSwap::from_pointee(
+            config.health.clone(),
+        )))
+        .expect("Failed to create service");
+        let clock = Arc::new(MockClock::new(100_000));
+        let ctx = Arc::new(
+            Context::with_clock(config, clock.clone()).expect("Failed to create context"),
+        );
+        let events = ctx.channels().health_rx().expect("health receiver");
+        let health_handle = tokio::spawn({
+            let ctx = ctx.clone();
+            async move { service.check_health(ctx).await }
+        });
+        let mut probed = Self {
+            ctx,
+            clock,
+            status,
+            events,
+            handles: vec![server_handle, health_handle],
+        };
+        assert_eq!(probed.next_probe().await, Some(true));
+        probed
+    }
+
+    /// Wait for the next probe result, applied, and return whether it passed
+    ///
+    /// Returns `None` for a health transition.
+    async fn next_probe(&mut self) -> Option<bool> {
+        let event = tokio::time::timeout(Duration::from_secs(1), self.events.recv())
+            .await
+            .expect("Probe should finish")
+            .expect("health channel");
+        // Let the service apply the probe it reported
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        match event {
+            HealthEvent::BackendHealthy { .. } => Some(true),
+            HealthEvent::BackendUnhealthy { .. } => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Run one periodic probe answered with `status` and return the health
+    /// transitions it caused
+    async fn probe(&mut self, status: u16) -> Vec<HealthStatus> {
+        self.status.store(status, Ordering::SeqCst);
+        self.clock.advance(Duration::from_secs(10));
+        assert_eq!(self.next_probe().await, Some(status == 200));
+        self.transitions()
+    }
+
+    /// Health transitions reported since the last probe
+    fn transitions(&mut self) -> Vec<HealthStatus> {
+        let mut transitions = Vec::new();
+        while let Ok(event) = self.events.try_recv() {
+            if let HealthEvent::HealthTransition { to, .. } = event {
+                transitions.push(to);
+            }
+        }
+        transitions
+    }
+
+    /// Whether backend 0 is routed to
+    fn is_alive(&self) -> bool {
+        self.ctx
+            .routing_table()
+            .get(0)
+            .expect("backend 0")
+            .is_alive()
+    }
+
+    /// Stop the service and the backend
+    async fn stop(self) {
+        let _ = self.ctx.channels().shutdown_tx().send(());
+        for handle in self.handles {
+            handle.abort();
+        }
+    }
+}
+
+#[tokio::test]
+async fn health_thresholds_flapping_backend_stays_healthy_should_succeed() {
+    // Given: a healthy backend needing 3 failed probes in a row to go down
+    let mut probed = Probed::start(2, 3).await;
+
+    // When: its probes flap, never failing three times in a row
+    for status in [503, 200, 503, 503, 200, 503, 503, 200] {
+        // Then: no transition happens and it stays healthy
+        assert!(probed.probe(status).await.is_empty(), "status {}", status);
+        assert!(probed.is_alive());
+    }
+
+    // When: it fails three times in a row
+    assert!(probed.probe(503).await.is_empty());
+    assert!(probed.probe(503).await.is_empty());
+    assert!(probed.is_alive());
+
+    // Then: only the third failure takes it down
+    assert_eq!(probed.probe(503).await, vec![HealthStatus::Unhealthy]);
+    assert!(!probed.is_alive());
+    probed.stop().await;
+}
+
+#[tokio::test]
+async fn health_thresholds_recovery_needs_passing_streak_should_succeed() {
+    // Given: a backend taken down by its probes, needing 2 passing probes in
+    // a row to come back
+    let mut probed = Probed::start(2, 1).await;
+    assert_eq!(probed.probe(503).await, vec![HealthStatus::Unhealthy]);
+
+    // When: its probes flap
+    for status in [200, 503, 200, 503] {
+        // Then: it stays down
+        assert!(probed.probe(status).await.is_empty(), "status {}", status);
+        assert!(!probed.is_alive());
+    }
+
+    // When: two probes in a row pass
+    assert!(probed.probe(200).await.is_empty());
+    assert!(!probed.is_alive());
+
+    // Then: the second one brings it back
+    assert_eq!(probed.probe(200).await, vec![HealthStatus::Healthy]);
+    assert!(probed.is_alive());
+
+    // And: a proxy failure takes it down at once and ends the passing streak
+    let failure_tx = probed.ctx.channels().backend_failure_tx();
+    let _ = failure_tx
+        .send(BackendFailureEvent::BackendClosed {
+            backend_id: 0,
+            at_micros: probed.clock.now_micros(),
+        })
+        .await;
+    assert_eq!(probed.next_probe().await, Some(false));
+    assert_eq!(probed.transitions(), vec![HealthStatus::Unhealthy]);
+    assert!(!probed.is_alive());
+    assert!(probed.probe(200).await.is_empty());
+    assert!(!probed.is_alive());
+    assert_eq!(probed.probe(200).await, vec![HealthStatus::Healthy]);
+    probed.stop().await;
+}
+
+#[tokio::test]
+async fn health_thresholds_replaced_backend_starts_over_should_succeed() {
+    // Given: a backend two failed probes into a streak of three
+    let mut probed = Probed::start(2, 3).await;
+    assert!(probed.probe(503).await.is_empty());
+    assert!(probed.probe(503).await.is_empty());
+
+    // When: a migration replaces it under the same id
+    let mut config = (*probed.ctx.config()).clone();
+    config.backends[0].name = Some("replacement".to_string());
+    probed.ctx.migrate(config).await.expect("Failed to migrate");
+
+    // Then: the new backend's streak starts over
+    assert!(probed.probe(503).await.is_empty());
+    assert!(probed.probe(503).await.is_empty());
+    assert!(probed.is_alive());
+    assert_eq!(probed.probe(503).await, vec![HealthStatus::Unhealthy]);
+    probed.stop().await;
+}
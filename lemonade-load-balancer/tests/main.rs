@@ -1,11 +1,16 @@
 //! Root test module - imports all test modules
 //! This file ensures all test modules are included in test runs
 
+mod admin;
+mod api;
 mod app;
+mod audit;
 pub mod common;
 mod config;
+mod consistency;
 mod health;
 mod metrics;
 mod proxy;
+mod state;
 mod strategy;
 mod types;
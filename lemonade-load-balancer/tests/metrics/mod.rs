@@ -4,3 +4,4 @@
 
 mod test_aggregating;
 mod test_external;
+mod test_weight_controller;
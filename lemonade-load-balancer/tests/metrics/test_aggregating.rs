@@ -4,7 +4,7 @@ use lemonade_load_balancer::prelude::*;
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::common::fixtures::create_test_context;
+use crate::common::fixtures::{create_test_config_fast, create_test_context};
 
 #[tokio::test]
 async fn aggregating_metrics_service_new_should_succeed() {
@@ -12,6 +12,9 @@ async fn aggregating_metrics_service_new_should_succeed() {
     let config = MetricsConfig {
         interval: Duration::from_millis(1),
         timeout: Duration::from_millis(1),
+        max_event_age_millis: 30_000,
+        aggregation: LatencyAggregation::default(),
+        selection_trace: false,
     };
 
     // When: creating AggregatingMetricsService
@@ -27,6 +30,9 @@ async fn aggregating_metrics_service_collect_metrics_should_succeed() {
     let config = MetricsConfig {
         interval: Duration::from_millis(10),
         timeout: Duration::from_millis(1),
+        max_event_age_millis: 30_000,
+        aggregation: LatencyAggregation::default(),
+        selection_trace: false,
     };
     let service = Arc::new(
         AggregatingMetricsService::new(Arc::new(ArcSwap::from_pointee(config)))
@@ -58,6 +64,9 @@ async fn aggregating_metrics_service_processes_events_should_succeed() {
     let config = MetricsConfig {
         interval: Duration::from_millis(10),
         timeout: Duration::from_millis(1),
+        max_event_age_millis: 30_000,
+        aggregation: LatencyAggregation::default(),
+        selection_trace: false,
     };
     let service = Arc::new(
         AggregatingMetricsService::new(Arc::new(ArcSwap::from_pointee(config)))
@@ -95,8 +104,13 @@ async fn aggregating_metrics_service_processes_events_should_succeed() {
         .send(MetricsEvent::ConnectionClosed {
             backend_id: 0,
             duration_micros: 5000,
+            connect_micros: 500,
+            ttfb_micros: Some(1500),
             bytes_in: 100,
             bytes_out: 200,
+            close_reason: CloseReason::Normal,
+            peer: PeerInfo::new("127.0.0.1:50000".parse().unwrap()),
+            at_micros: ctx.clock().now_micros(),
         })
         .await;
 
@@ -109,6 +123,11 @@ async fn aggregating_metrics_service_processes_events_should_succeed() {
         let metrics = backend.metrics_snapshot();
         // Check that metrics were updated (either latency or error rate)
         assert!(metrics.avg_latency_ms > 0.0 || metrics.error_rate > 0.0);
+        // Connection timings are recorded separately from the total duration
+        assert_eq!(metrics.timings.connect.count, 1);
+        assert_eq!(metrics.timings.ttfb.count, 1);
+        assert_eq!(metrics.timings.total.count, 1);
+        assert_eq!(metrics.timings.ttfb.sum_micros, 1500);
     }
 
     // Send shutdown signal
@@ -117,3 +136,78 @@ async fn aggregating_metrics_service_processes_events_should_succeed() {
     // Wait for service to stop
     let _ = tokio::time::timeout(Duration::from_millis(100), metrics_handle).await;
 }
+
+#[tokio::test]
+async fn aggregating_metrics_service_expires_stale_backlog_should_succeed() {
+    // Given: a backlog of slow connections from a minute ago queued ahead of
+    // two fresh ones, with events expiring after 30s
+    let config = MetricsConfig {
+        interval: Duration::from_secs(10),
+        timeout: Duration::from_millis(1),
+        max_event_age_millis: 30_000,
+        aggregation: LatencyAggregation::default(),
+        selection_trace: false,
+    };
+    let service = AggregatingMetricsService::new(Arc::new(ArcSwap::from_pointee(config)))
+        .expect("Failed to create service");
+    let clock = Arc::new(MockClock::new(100_000_000));
+    let ctx = Arc::new(
+        Context::with_clock(
+            create_test_config_fast(
+                vec![BackendMeta::new(
+                    0u8,
+                    Some("test"),
+                    "127.0.0.1:8080".parse::<std::net::SocketAddr>().unwrap(),
+                    Some(10u8),
+                )],
+                Strategy::RoundRobin,
+            ),
+            clock.clone(),
+        )
+        .expect("Failed to create context"),
+    );
+    let closed = |duration_micros: u64, at_micros: u64| MetricsEvent::ConnectionClosed {
+        backend_id: 0,
+        duration_micros,
+        connect_micros: 100,
+        ttfb_micros: None,
+        bytes_in: 0,
+        bytes_out: 0,
+        close_reason: CloseReason::Normal,
+        peer: PeerInfo::new("127.0.0.1:50000".parse().unwrap()),
+        at_micros,
+    };
+    let metrics_tx = ctx.channels().metrics_tx();
+    let stale_micros = clock.now_micros() - 60_000_000;
+    for _ in 0..5 {
+        assert!(metrics_tx.try_send(closed(900_000, stale_micros)).is_ok());
+    }
+    let fresh_micros = clock.now_micros() - 1_000_000;
+    for _ in 0..2 {
+        assert!(metrics_tx.try_send(closed(5_000, fresh_micros)).is_ok());
+    }
+
+    // When: the service catches up on the backlog
+    let metrics_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.collect_metrics(ctx).await }
+    });
+    let backend = ctx.routing_table().get(0).expect("backend 0");
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while backend.metrics_snapshot().timings.total.count < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Fresh events should be processed");
+
+    // Then: stale events are counted as expired and only fresh ones are
+    // folded into the stats
+    assert_eq!(ctx.channels().metrics_events_expired(), 5);
+    let timings = backend.metrics_snapshot().timings;
+    assert_eq!(timings.total.count, 2);
+    assert_eq!(timings.total.sum_micros, 10_000);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), metrics_handle).await;
+}
@@ -1,8 +1,12 @@
 //! Tests for ExternalMetricsService
 //!
+use crate::common::fixtures::create_test_config_fast;
 use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
 
 #[tokio::test]
 async fn external_metrics_service_new_should_succeed() {
@@ -10,6 +14,9 @@ async fn external_metrics_service_new_should_succeed() {
     let config = MetricsConfig {
         interval: Duration::from_millis(1),
         timeout: Duration::from_millis(1),
+        max_event_age_millis: 30_000,
+        aggregation: LatencyAggregation::default(),
+        selection_trace: false,
     };
 
     // When: creating ExternalMetricsService
@@ -34,3 +41,175 @@ async fn external_metrics_service_fetch_otlp_should_succeed() {
     // Then: metrics are parsed and returned
     // TODO: Implement when OTLP support is added
 }
+
+/// Serve `response` to every connection, as a worker's `/stats` endpoint would
+async fn spawn_stats_worker(response: String) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind stats worker");
+    let address = listener.local_addr().expect("Failed to get worker address");
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let response = response.clone();
+            tokio::spawn(async move {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    address
+}
+
+/// HTTP response carrying a `/stats` body
+fn stats_response(body: &str) -> String {
+    format!(
+        "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+}
+
+/// Context over `addresses`, using adaptive with `params`
+fn adaptive_context(addresses: &[SocketAddr], params: serde_json::Value) -> Arc<Context> {
+    let backends = addresses
+        .iter()
+        .enumerate()
+        .map(|(id, address)| {
+            BackendMeta::new(
+                id as u8,
+                Some(format!("worker-{}", id)),
+                *address,
+                Some(10u8),
+            )
+        })
+        .collect();
+    let mut config = create_test_config_fast(backends, Strategy::Adaptive);
+    config.strategy_params = params;
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+/// External metrics service with a short timeout
+fn external_service() -> ExternalMetricsService {
+    let config = MetricsConfig {
+        interval: Duration::from_millis(50),
+        timeout: Duration::from_millis(500),
+        max_event_age_millis: 30_000,
+        aggregation: LatencyAggregation::default(),
+        selection_trace: false,
+    };
+    ExternalMetricsService::new(Arc::new(ArcSwap::from_pointee(config)))
+        .expect("Failed to create service")
+}
+
+#[test]
+fn worker_stats_parse_response_should_succeed() {
+    // Given: a worker's `/stats` response
+    let response = stats_response(
+        r#"{"service_name":"worker","cpu_percent":42.5,"rss_bytes":1048576}"#,
+    );
+
+    // When: parsing it
+    let stats = WorkerStats::parse_response(response.as_bytes()).unwrap();
+
+    // Then: the resource fields are read and the rest ignored
+    assert_eq!(stats.cpu_percent, Some(42.5));
+    assert_eq!(stats.rss_bytes, Some(1_048_576));
+
+    // And: null readings (e.g. first sample) parse as missing
+    let stats = WorkerStats::parse(br#"{"cpu_percent":null,"rss_bytes":null}"#).unwrap();
+    assert_eq!(stats, WorkerStats::default());
+}
+
+#[test]
+fn worker_stats_parse_response_should_fail() {
+    // Non-200 status
+    let not_found = "HTTP/1.1 404 Not Found\r\nContent-Length: 2\r\n\r\n{}";
+    assert!(WorkerStats::parse_response(not_found.as_bytes()).is_err());
+
+    // Truncated headers
+    assert!(WorkerStats::parse_response(b"HTTP/1.1 200 OK\r\n").is_err());
+
+    // Body that is not JSON
+    let garbage = stats_response("not json");
+    assert!(WorkerStats::parse_response(garbage.as_bytes()).is_err());
+}
+
+#[tokio::test]
+async fn external_metrics_service_poll_workers_should_succeed() {
+    // Given: a worker reporting its resource usage and an unreachable backend
+    let worker =
+        spawn_stats_worker(stats_response(r#"{"cpu_percent":75.0,"rss_bytes":2048}"#))
+            .await;
+    let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let unreachable = closed.local_addr().unwrap();
+    drop(closed);
+    let ctx = adaptive_context(&[worker, unreachable], serde_json::Value::Null);
+    let stale = ctx.routing_table().get(1).unwrap();
+    stale.set_resource_usage(Some(10.0), Some(1));
+
+    // When: polling the workers once
+    let reported = external_service().poll_workers(&ctx).await;
+
+    // Then: the reachable worker's usage lands in its backend metrics
+    assert_eq!(reported, 1);
+    let metrics = ctx.routing_table().get(0).unwrap().metrics_snapshot();
+    assert_eq!(metrics.cpu_percent, Some(75.0));
+    assert_eq!(metrics.rss_bytes, Some(2048));
+
+    // And: the unreachable backend's stale reading is cleared
+    let metrics = stale.metrics_snapshot();
+    assert_eq!(metrics.cpu_percent, None);
+    assert_eq!(metrics.rss_bytes, None);
+}
+
+#[tokio::test]
+async fn external_metrics_service_feeds_adaptive_resource_factor_should_succeed() {
+    // Given: a busy worker and an idle one, with the resource factor enabled
+    let busy =
+        spawn_stats_worker(stats_response(r#"{"cpu_percent":95.0,"rss_bytes":900}"#))
+            .await;
+    let idle =
+        spawn_stats_worker(stats_response(r#"{"cpu_percent":5.0,"rss_bytes":100}"#))
+            .await;
+    let ctx =
+        adaptive_context(&[busy, idle], serde_json::json!({ "resource_weight": 1.0 }));
+    let strategy = AdaptiveStrategy::from_params(ctx.config().strategy_params.clone())
+        .expect("Failed to build adaptive strategy");
+    assert_eq!(strategy.resource_weight(), 1.0);
+
+    // When: polling the workers and picking a backend
+    external_service().poll_workers(&ctx).await;
+    let picked = strategy
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Failed to pick backend");
+
+    // Then: the idle worker wins on resource pressure
+    assert_eq!(picked.id(), 1);
+}
+
+#[tokio::test]
+async fn external_metrics_service_collect_metrics_stops_on_shutdown_should_succeed() {
+    // Given: the service polling a worker in the background
+    let worker =
+        spawn_stats_worker(stats_response(r#"{"cpu_percent":1.0,"rss_bytes":1}"#)).await;
+    let ctx = adaptive_context(&[worker], serde_json::Value::Null);
+    let service = external_service();
+    let handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.collect_metrics(ctx).await }
+    });
+
+    // When: the first round has run and shutdown is signalled
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let _ = ctx.channels().shutdown_tx().send(());
+
+    // Then: the loop stops and the reading was recorded
+    tokio::time::timeout(Duration::from_secs(1), handle)
+        .await
+        .expect("Service did not stop")
+        .unwrap();
+    let metrics = ctx.routing_table().get(0).unwrap().metrics_snapshot();
+    assert_eq!(metrics.cpu_percent, Some(1.0));
+}
@@ -0,0 +1,275 @@
+//! Tests for WeightController
+//!
+//! Simulations feed synthetic latency series and check the auto-weight
+//! controller converges without oscillating.
+use lemonade_load_balancer::prelude::*;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Route table with `count` backends of weight 10
+fn create_route_table(count: u8) -> RouteTable {
+    RouteTable::new(
+        (0..count)
+            .map(|id| BackendConfig::from(create_test_backend(id, None, Some(10u8))))
+            .collect(),
+    )
+}
+
+/// Record one window of traffic: `latencies[i]` ms for backend `i`
+fn feed_window(routing: &RouteTable, latencies: &[u64]) {
+    for (id, latency) in latencies.iter().enumerate() {
+        let backend = routing.get(id as u8).expect("Backend not found");
+        for _ in 0..10 {
+            backend.record_request(*latency, false);
+        }
+    }
+}
+
+/// Run `rounds` adjustment rounds and return backend `id`'s multiplier after each
+fn simulate(
+    controller: &mut WeightController,
+    routing: &RouteTable,
+    latencies: &[u64],
+    rounds: usize,
+    id: u8,
+) -> Vec<f64> {
+    let backend = routing.get(id).expect("Backend not found");
+    (0..rounds)
+        .map(|_| {
+            feed_window(routing, latencies);
+            controller.adjust(routing);
+            backend.weight_multiplier()
+        })
+        .collect()
+}
+
+#[test]
+fn weight_controller_slow_backend_converges_should_succeed() {
+    // Given: three backends, one persistently 4x slower than the others
+    let routing = create_route_table(3);
+    let config = AutoWeightConfig::default();
+    let mut controller = WeightController::new(config.clone());
+    controller.adjust(&routing); // Prime the window totals
+
+    // When: running the controller over a steady latency series
+    let series = simulate(&mut controller, &routing, &[10, 10, 40], 30, 2);
+
+    // Then: the multiplier decreases monotonically, in bounded steps, to median/latency
+    let mut previous = 1.0;
+    for value in &series {
+        assert!(
+            *value <= previous + 1e-9,
+            "multiplier oscillated: {series:?}"
+        );
+        assert!(previous - value <= config.max_step + 1e-3);
+        previous = *value;
+    }
+    let last = *series.last().expect("Empty series");
+    assert!((last - 0.25).abs() < 0.01, "did not converge: {series:?}");
+
+    // And: the fast backends keep their configured weight
+    for id in [0, 1] {
+        let backend = routing.get(id).expect("Backend not found");
+        assert_eq!(backend.weight_multiplier(), 1.0);
+        assert_eq!(backend.effective_weight_milli(0), 10_000);
+    }
+    let slow = routing.get(2).expect("Backend not found");
+    assert_eq!(
+        slow.effective_weight_milli(0),
+        (last * 10_000.0).round() as u32
+    );
+    assert!((slow.metrics_snapshot().weight_multiplier - last).abs() < 1e-9);
+}
+
+#[test]
+fn weight_controller_tail_latency_should_succeed() {
+    // Given: three backends answering in 5ms, backend 2 with the same average
+    // but a slow tail
+    let routing = create_route_table(3);
+    let mut controller = WeightController::new(AutoWeightConfig::default());
+    controller.adjust(&routing);
+
+    // When: running the controller over windows of 20 requests each
+    for _ in 0..30 {
+        for id in 0..3u8 {
+            let backend = routing.get(id).expect("Backend not found");
+            for request in 0..20u64 {
+                let ttfb_micros = match (id, request) {
+                    (2, 0..17) => 2_000,
+                    (2, _) => 20_000,
+                    _ => 5_000,
+                };
+                backend.record_request(ttfb_micros / 1000, false);
+                backend.record_connection_timings(100, Some(ttfb_micros), ttfb_micros);
+            }
+        }
+        controller.adjust(&routing);
+    }
+
+    // Then: backend 2 is weighted down by its p95, the others are not
+    let slow = routing.get(2).expect("Backend not found");
+    assert!(
+        slow.weight_multiplier() < 0.5,
+        "{}",
+        slow.weight_multiplier()
+    );
+    for id in [0, 1] {
+        let backend = routing.get(id).expect("Backend not found");
+        assert_eq!(backend.weight_multiplier(), 1.0);
+    }
+}
+
+#[tokio::test]
+async fn weight_controller_default_weights_shift_traffic_should_succeed() {
+    // Given: weighted round robin over three backends without a configured
+    // weight, backend 2 persistently 4x slower than the others
+    let ctx = Arc::new(
+        Context::new(create_test_config_fast(
+            (0..3)
+                .map(|id| create_test_backend(id, None, None))
+                .collect(),
+            Strategy::WeightedRoundRobin,
+        ))
+        .expect("Failed to create context"),
+    );
+    let strategy = WeightedRoundRobinStrategy::default();
+    let routing = ctx.routing_table();
+    let mut controller = WeightController::new(AutoWeightConfig::default());
+    controller.adjust(&routing);
+
+    // When: picking 300 times before and after the controller converges
+    let mut shares = Vec::new();
+    for rounds in [0, 30] {
+        simulate(&mut controller, &routing, &[10, 10, 40], rounds, 2);
+        let mut picks = 0;
+        for _ in 0..300 {
+            let backend = strategy
+                .pick_backend(ctx.clone())
+                .await
+                .expect("Failed to pick backend");
+            picks += usize::from(backend.id() == 2);
+        }
+        shares.push(picks);
+    }
+
+    // Then: backend 2 goes from a third of the traffic to about
+    // 0.25 / 2.25 of it
+    assert_eq!(shares[0], 100);
+    assert!((30..=37).contains(&shares[1]), "{:?}", shares);
+}
+
+#[test]
+fn weight_controller_recovered_backend_resets_should_succeed() {
+    // Given: a slow backend whose multiplier has been lowered
+    let routing = create_route_table(3);
+    let mut controller = WeightController::new(AutoWeightConfig::default());
+    controller.adjust(&routing);
+    simulate(&mut controller, &routing, &[10, 10, 40], 30, 2);
+
+    // When: the backend recovers to the median latency
+    let series = simulate(&mut controller, &routing, &[10, 10, 10], 30, 2);
+
+    // Then: the multiplier climbs back monotonically to the configured weight
+    let mut previous = 0.0;
+    for value in &series {
+        assert!(
+            *value >= previous - 1e-9,
+            "multiplier oscillated: {series:?}"
+        );
+        previous = *value;
+    }
+    assert_eq!(*series.last().expect("Empty series"), 1.0);
+}
+
+#[test]
+fn weight_controller_noisy_latency_stays_stable_should_succeed() {
+    // Given: a slow backend with jittery latency around 40ms
+    let routing = create_route_table(3);
+    let mut controller = WeightController::new(AutoWeightConfig::default());
+    controller.adjust(&routing);
+    simulate(&mut controller, &routing, &[10, 10, 40], 30, 2);
+
+    // When: latency alternates between 36ms and 44ms
+    let backend = routing.get(2).expect("Backend not found");
+    let series: Vec<f64> = (0..40)
+        .map(|round| {
+            let latency = if round % 2 == 0 { 36 } else { 44 };
+            feed_window(&routing, &[10, 10, latency]);
+            controller.adjust(&routing);
+            backend.weight_multiplier()
+        })
+        .collect();
+
+    // Then: the multiplier stays within the band of the jitter targets
+    for value in &series {
+        assert!((0.22..=0.28).contains(value), "unstable: {series:?}");
+    }
+}
+
+#[test]
+fn weight_controller_respects_min_multiplier_should_succeed() {
+    // Given: a controller with a 0.5 floor
+    let routing = create_route_table(3);
+    let config = AutoWeightConfig {
+        min_multiplier: 0.5,
+        ..AutoWeightConfig::default()
+    };
+    let mut controller = WeightController::new(config);
+    controller.adjust(&routing);
+
+    // When: one backend is 10x slower than the median
+    let series = simulate(&mut controller, &routing, &[10, 10, 100], 30, 2);
+
+    // Then: the multiplier never goes below the floor
+    assert!(series.iter().all(|value| *value >= 0.5));
+    assert_eq!(*series.last().expect("Empty series"), 0.5);
+}
+
+#[test]
+fn weight_controller_reset_should_succeed() {
+    // Given: a backend with a lowered multiplier
+    let routing = create_route_table(2);
+    let backend = routing.get(1).expect("Backend not found");
+    backend.set_weight_multiplier(0.4);
+    let mut controller = WeightController::new(AutoWeightConfig::default());
+
+    // When: resetting the controller
+    controller.reset(&routing);
+
+    // Then: the configured weight is restored
+    assert_eq!(backend.weight_multiplier(), 1.0);
+    assert_eq!(backend.effective_weight_milli(0), 10_000);
+}
+
+#[test]
+fn weight_controller_single_sample_should_not_adjust() {
+    // Given: only one backend receiving traffic
+    let routing = create_route_table(2);
+    let mut controller = WeightController::new(AutoWeightConfig::default());
+    controller.adjust(&routing);
+
+    // When: adjusting with a single latency sample
+    let backend = routing.get(0).expect("Backend not found");
+    backend.record_request(100, false);
+    let changes = controller.adjust(&routing);
+
+    // Then: nothing changes (no median to compare against)
+    assert!(changes.is_empty());
+    assert_eq!(backend.weight_multiplier(), 1.0);
+}
+
+#[test]
+fn weight_controller_retain_backends_evicts_removed_should_succeed() {
+    // Given: a controller primed with the totals of three backends
+    let routing = create_route_table(3);
+    let mut controller = WeightController::new(AutoWeightConfig::default());
+    feed_window(&routing, &[10, 10, 10]);
+    controller.adjust(&routing);
+
+    // When: two of the backends leave the route table
+    let evicted = controller.retain_backends(&create_route_table(1));
+
+    // Then: their totals are dropped, and only once
+    assert_eq!(evicted, 2);
+    assert_eq!(controller.retain_backends(&create_route_table(1)), 0);
+}
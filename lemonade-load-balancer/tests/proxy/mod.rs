@@ -2,4 +2,25 @@
 //!
 //! Tests for proxy service adapters
 
+mod test_abort;
+mod test_admin_state;
+mod test_bind_address;
+mod test_circuit_breaker;
+mod test_copy;
+mod test_deadline;
+mod test_fd_budget;
+mod test_hedge;
+mod test_http_head;
+mod test_http_route;
+mod test_initial_read;
+mod test_loop;
+mod test_peer_info;
+mod test_pending_connects;
+mod test_selections;
+mod test_shutdown;
+mod test_slow_log;
+mod test_sni;
+mod test_sticky;
 mod test_tokio;
+#[cfg(unix)]
+mod test_unix;
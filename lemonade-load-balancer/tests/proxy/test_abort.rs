@@ -0,0 +1,272 @@
+//! Tests for client and backend abort classification
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::common::fixtures::{create_test_config_fast, create_test_context};
+
+/// Drop `stream` with SO_LINGER 0, so the peer sees a reset instead of a FIN
+fn reset(stream: TcpStream) {
+    socket2::SockRef::from(&stream)
+        .set_linger(Some(Duration::ZERO))
+        .expect("Failed to set SO_LINGER");
+    drop(stream);
+}
+
+/// Backend answering "pong" to a ping, then either holding the connection
+/// open or resetting it
+async fn spawn_backend(reset_after_reply: bool) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let address = listener.local_addr().expect("backend address");
+    tokio::spawn(async move {
+        if let Ok((mut stream, _)) = listener.accept().await {
+            let mut buf = [0u8; 4];
+            if stream.read_exact(&mut buf).await.is_err() {
+                return;
+            }
+            let _ = stream.write_all(b"pong").await;
+            if reset_after_reply {
+                reset(stream);
+            } else {
+                // Hold until the proxy closes its side
+                let _ = stream.read(&mut buf).await;
+            }
+        }
+    });
+    address
+}
+
+/// Proxy in front of `backend`, with the metrics and failure receivers
+async fn start_proxy(
+    backend: SocketAddr,
+) -> (
+    SocketAddr,
+    MpscReceiver<MetricsEvent>,
+    MpscReceiver<BackendFailureEvent>,
+    tokio::task::JoinHandle<Result<(), ProxyError>>,
+) {
+    let proxy_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(0u8, Some("backend"), backend, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = proxy_address;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let metrics_rx = ctx.channels().metrics_rx().unwrap();
+    let failure_rx = ctx.channels().backend_failure_rx().unwrap();
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let handle = tokio::spawn(async move { service.accept_connections(ctx).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    (proxy_address, metrics_rx, failure_rx, handle)
+}
+
+/// Close reason of the next closed connection
+async fn next_close_reason(metrics_rx: &mut MpscReceiver<MetricsEvent>) -> CloseReason {
+    tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match metrics_rx.recv().await {
+                Some(MetricsEvent::ConnectionClosed { close_reason, .. }) => {
+                    break close_reason;
+                }
+                Some(_) => continue,
+                None => panic!("Metrics channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("ConnectionClosed event should be sent")
+}
+
+/// Send a ping through the proxy and read the reply
+async fn ping(proxy_address: SocketAddr) -> TcpStream {
+    let mut client = TcpStream::connect(proxy_address)
+        .await
+        .expect("Failed to connect to proxy");
+    client.write_all(b"ping").await.unwrap();
+    let mut reply = [0u8; 4];
+    client.read_exact(&mut reply).await.unwrap();
+    assert_eq!(&reply, b"pong");
+    client
+}
+
+#[test]
+fn classify_close_should_succeed() {
+    let outcome = |read_error, write_error| CopyOutcome {
+        read_error,
+        write_error,
+        ..CopyOutcome::default()
+    };
+    let reset = Some(std::io::ErrorKind::ConnectionReset);
+    let pipe = Some(std::io::ErrorKind::BrokenPipe);
+    let clean = outcome(None, None);
+
+    // Errors on the client socket: reading the client or writing to it
+    assert_eq!(
+        classify_close(&outcome(reset, None), &clean),
+        CloseReason::ClientAborted
+    );
+    assert_eq!(
+        classify_close(&clean, &outcome(None, pipe)),
+        CloseReason::ClientAborted
+    );
+
+    // Errors on the backend socket: writing the backend or reading it
+    assert_eq!(
+        classify_close(&outcome(None, pipe), &clean),
+        CloseReason::BackendAborted
+    );
+    assert_eq!(
+        classify_close(&clean, &outcome(reset, None)),
+        CloseReason::BackendAborted
+    );
+
+    // A backend abort wins, and other errors are normal closes
+    assert_eq!(
+        classify_close(&outcome(reset, None), &outcome(reset, None)),
+        CloseReason::BackendAborted
+    );
+    let timed_out = Some(std::io::ErrorKind::TimedOut);
+    assert_eq!(
+        classify_close(&outcome(timed_out, None), &clean),
+        CloseReason::Normal
+    );
+    assert_eq!(classify_close(&clean, &clean), CloseReason::Normal);
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_classifies_client_abort_should_succeed() {
+    // Given: a proxy in front of a backend that holds its connections
+    let backend = spawn_backend(false).await;
+    let (proxy_address, mut metrics_rx, mut failure_rx, handle) =
+        start_proxy(backend).await;
+
+    // When: the client resets the connection mid-session
+    let client = ping(proxy_address).await;
+    reset(client);
+
+    // Then: the close is a client abort and the backend is not reported
+    assert_eq!(
+        next_close_reason(&mut metrics_rx).await,
+        CloseReason::ClientAborted
+    );
+    assert!(failure_rx.try_recv().is_err());
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_classifies_backend_abort_should_succeed() {
+    // Given: a proxy in front of a backend that resets after replying
+    let backend = spawn_backend(true).await;
+    let (proxy_address, mut metrics_rx, mut failure_rx, handle) =
+        start_proxy(backend).await;
+
+    // When: a client talks to it, reads until the proxy closes and hangs up
+    let mut client = ping(proxy_address).await;
+    let mut rest = Vec::new();
+    let _ = client.read_to_end(&mut rest).await;
+    drop(client);
+
+    // Then: the close is a backend abort
+    assert_eq!(
+        next_close_reason(&mut metrics_rx).await,
+        CloseReason::BackendAborted
+    );
+
+    // And: the backend is reported to health checking
+    let failure = tokio::time::timeout(Duration::from_secs(1), failure_rx.recv())
+        .await
+        .expect("Backend failure should be reported");
+    assert!(
+        matches!(
+            failure,
+            Some(BackendFailureEvent::BackendClosed { backend_id: 0, .. })
+        ),
+        "{:?}",
+        failure
+    );
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn aggregating_metrics_service_counts_only_backend_aborts_should_succeed() {
+    // Given: two backends and the aggregating metrics service
+    let ctx = create_test_context(vec![
+        BackendMeta::new(
+            0u8,
+            Some("client-aborts"),
+            "127.0.0.1:8080".parse::<SocketAddr>().unwrap(),
+            Some(10u8),
+        ),
+        BackendMeta::new(
+            1u8,
+            Some("backend-aborts"),
+            "127.0.0.1:8081".parse::<SocketAddr>().unwrap(),
+            Some(10u8),
+        ),
+    ]);
+    let config = MetricsConfig {
+        interval: Duration::from_millis(10),
+        timeout: Duration::from_millis(1),
+        max_event_age_millis: 30_000,
+        aggregation: LatencyAggregation::default(),
+        selection_trace: false,
+    };
+    let service = AggregatingMetricsService::new(Arc::new(ArcSwap::from_pointee(config)))
+        .expect("Failed to create service");
+    let metrics_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.collect_metrics(ctx).await }
+    });
+
+    // When: backend 0 sees client aborts and backend 1 backend aborts
+    let metrics_tx = ctx.channels().metrics_tx();
+    for (backend_id, close_reason) in [
+        (0, CloseReason::ClientAborted),
+        (0, CloseReason::ClientAborted),
+        (1, CloseReason::BackendAborted),
+        (1, CloseReason::BackendAborted),
+    ] {
+        metrics_tx
+            .send(MetricsEvent::ConnectionClosed {
+                backend_id,
+                duration_micros: 5000,
+                connect_micros: 500,
+                ttfb_micros: Some(1500),
+                bytes_in: 100,
+                bytes_out: 200,
+                close_reason,
+                peer: PeerInfo::new("127.0.0.1:50000".parse().unwrap()),
+                at_micros: ctx.clock().now_micros(),
+            })
+            .await
+            .unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // Then: only the backend aborts count as errors
+    let routing = ctx.routing_table();
+    assert_eq!(routing.get(0).unwrap().metrics_snapshot().error_rate, 0.0);
+    assert_eq!(routing.get(1).unwrap().metrics_snapshot().error_rate, 1.0);
+
+    // And: adaptive scoring prefers the backend whose clients went away
+    let strategy = AdaptiveStrategy::new();
+    let picked = strategy
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Failed to pick backend");
+    assert_eq!(picked.id(), 0);
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), metrics_handle).await;
+}
@@ -0,0 +1,77 @@
+//! Tests for admin state overrides of proxied traffic
+//!
+use lemonade_load_balancer::prelude::*;
+
+use super::test_sticky::{backend_id, backend_name, start_proxy};
+
+#[tokio::test]
+async fn admin_state_disable_and_enable_should_succeed() {
+    // Given: a round robin proxy over b0, b1 and b2
+    let (ctx, proxy, handles, proxy_handle) = start_proxy(false, [None; 3]).await;
+
+    // When: b1 is disabled
+    assert_eq!(
+        ctx.set_admin_state(1, AdminState::Disabled),
+        Some(AdminState::Enabled)
+    );
+
+    // Then: the very next connections skip it, without a reload
+    let generation = ctx.generation();
+    for _ in 0..6 {
+        assert_ne!(backend_name(proxy).await, "b1");
+    }
+    assert!(ctx.routing_table().get(1).unwrap().is_alive());
+
+    // When: b1 is enabled again
+    assert_eq!(
+        ctx.set_admin_state(1, AdminState::Enabled),
+        Some(AdminState::Disabled)
+    );
+
+    // Then: it takes connections again
+    let mut served = Vec::new();
+    for _ in 0..3 {
+        served.push(backend_name(proxy).await);
+    }
+    served.sort();
+    assert_eq!(served, ["b0", "b1", "b2"]);
+    assert_eq!(ctx.generation(), generation);
+
+    proxy_handle.abort();
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn admin_state_draining_keeps_sticky_clients_should_succeed() {
+    // Given: a client stuck to a backend
+    let (ctx, proxy, handles, proxy_handle) = start_proxy(true, [None; 3]).await;
+    let first = backend_name(proxy).await;
+    let stuck = backend_id(&first);
+
+    // When: its backend is drained by its admin state
+    ctx.set_admin_state(stuck, AdminState::Draining);
+
+    // Then: the client keeps reaching it, while strategies skip it
+    assert_eq!(backend_name(proxy).await, first);
+    assert!(
+        ctx.routing_table()
+            .healthy_backends()
+            .iter()
+            .all(|backend| backend.id() != stuck)
+    );
+
+    // When: it is disabled
+    ctx.set_admin_state(stuck, AdminState::Disabled);
+
+    // Then: the client moves to another backend
+    let moved = backend_name(proxy).await;
+    assert_ne!(moved, first);
+    assert_eq!(backend_name(proxy).await, moved);
+
+    proxy_handle.abort();
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,154 @@
+//! Tests for binding backend connections to a source address
+//!
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Reserve a free loopback port
+fn free_addr() -> SocketAddr {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve port")
+}
+
+/// Start a proxy for `config` and return its context
+async fn start_proxy(config: Config) -> (Arc<Context>, tokio::task::JoinHandle<()>) {
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move {
+            let _ = service.accept_connections(ctx).await;
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    (ctx, handle)
+}
+
+/// Proxy one connection through `proxy_addr` and return the peer address the
+/// backend saw
+async fn backend_peer_through(
+    backend_listener: tokio::net::TcpListener,
+    proxy_addr: SocketAddr,
+) -> SocketAddr {
+    let accept = tokio::spawn(async move {
+        let (_stream, peer) = backend_listener.accept().await.expect("accept");
+        peer
+    });
+    let mut client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let _ = tokio::io::AsyncWriteExt::write_all(&mut client, b"ping").await;
+    tokio::time::timeout(Duration::from_secs(2), accept)
+        .await
+        .expect("Backend should be reached")
+        .expect("Accept task should not panic")
+}
+
+#[tokio::test]
+async fn backend_bind_address_sets_source_of_backend_connections_should_succeed() {
+    // Given: a backend on 127.0.0.1 configured to be reached from 127.0.0.2
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let proxy_addr = free_addr();
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("backend-0"),
+            BackendAddress::from(backend_addr),
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = proxy_addr;
+    config.backends[0].bind_address = Some("127.0.0.2".parse().unwrap());
+    let (_ctx, proxy_handle) = start_proxy(config).await;
+
+    // When: a connection is proxied
+    let peer = backend_peer_through(backend_listener, proxy_addr).await;
+
+    // Then: the backend sees it coming from the configured source address
+    assert_eq!(peer.ip(), "127.0.0.2".parse::<IpAddr>().unwrap());
+
+    proxy_handle.abort();
+}
+
+#[tokio::test]
+async fn proxy_backend_bind_address_default_applies_should_succeed() {
+    // Given: a proxy-wide source address and a backend without its own
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let proxy_addr = free_addr();
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("backend-0"),
+            BackendAddress::from(backend_addr),
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = proxy_addr;
+    config.proxy.backend_bind_address = Some("127.0.0.3".parse().unwrap());
+    let (_ctx, proxy_handle) = start_proxy(config).await;
+
+    // When: a connection is proxied
+    let peer = backend_peer_through(backend_listener, proxy_addr).await;
+
+    // Then: the proxy-wide source address is used
+    assert_eq!(peer.ip(), "127.0.0.3".parse::<IpAddr>().unwrap());
+
+    proxy_handle.abort();
+}
+
+#[tokio::test]
+async fn unavailable_bind_address_reports_source_bind_failure_should_succeed() {
+    // Given: a backend whose source address is not assigned to this host
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let proxy_addr = free_addr();
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("backend-0"),
+            BackendAddress::from(backend_addr),
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = proxy_addr;
+    // TEST-NET-1, never assigned locally
+    config.backends[0].bind_address = Some("192.0.2.1".parse().unwrap());
+    let (ctx, proxy_handle) = start_proxy(config).await;
+    let mut failure_rx = ctx
+        .channels()
+        .backend_failure_rx()
+        .expect("Failure receiver should be available");
+
+    // When: a connection is attempted
+    let mut client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let _ = tokio::io::AsyncWriteExt::write_all(&mut client, b"ping").await;
+
+    // Then: health is told the source address could not be bound
+    let event = tokio::time::timeout(Duration::from_secs(2), failure_rx.recv())
+        .await
+        .expect("A failure event should be sent")
+        .expect("Failure channel should be open");
+    assert!(matches!(
+        event,
+        BackendFailureEvent::SourceBindFailed { backend_id: 0, .. }
+    ));
+
+    proxy_handle.abort();
+}
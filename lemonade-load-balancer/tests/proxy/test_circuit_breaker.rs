@@ -0,0 +1,128 @@
+//! Tests for the per-backend circuit breaker of the proxy
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// How long an open circuit skips its backend
+const OPEN_DURATION: Duration = Duration::from_secs(10);
+
+/// Serve every connection accepted on `listener` its backend's name
+fn serve_name(
+    listener: tokio::net::TcpListener,
+    name: &'static str,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let _ =
+                tokio::io::AsyncWriteExt::write_all(&mut stream, name.as_bytes()).await;
+        }
+    })
+}
+
+/// Reply to one connection through the proxy, empty when no backend served
+/// it
+async fn reply(proxy: SocketAddr) -> String {
+    let mut client = tokio::net::TcpStream::connect(proxy)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut reply = String::new();
+    let _ =
+        tokio::time::timeout(Duration::from_secs(2), client.read_to_string(&mut reply))
+            .await
+            .expect("Proxy should answer or close");
+    reply
+}
+
+/// Replies to `count` connections through the proxy
+async fn replies(proxy: SocketAddr, count: usize) -> Vec<String> {
+    let mut replies = Vec::new();
+    for _ in 0..count {
+        replies.push(reply(proxy).await);
+    }
+    replies
+}
+
+#[tokio::test]
+async fn circuit_breaker_open_half_open_closed_should_succeed() {
+    // Given: a round robin proxy over a refusing b0 and a serving b1, whose
+    // circuits open after 2 connect failures in a row
+    let refusing = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve backend port");
+    let serving = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let mut handles = vec![];
+    let mut config = create_test_config_fast(
+        vec![
+            BackendMeta::new(0u8, Some("b0"), refusing, Some(1u8)),
+            BackendMeta::new(1u8, Some("b1"), serving.local_addr().unwrap(), Some(1u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    handles.push(serve_name(serving, "b1"));
+    config.proxy.listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    config.proxy.circuit_breaker = CircuitBreakerConfig {
+        enabled: true,
+        failure_threshold: 2,
+        open_duration_millis: OPEN_DURATION.as_millis() as u64,
+    };
+    let clock = Arc::new(MockClock::new(100_000));
+    let ctx = Arc::new(
+        Context::with_clock(config.clone(), clock.clone())
+            .expect("Failed to create context"),
+    );
+    let service =
+        TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+            .expect("Failed to create service");
+    let proxy = config.proxy.listen_address;
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let b0 = ctx.routing_table().get(0).expect("b0 is routed");
+
+    // When: b0 refuses the connections picked for it twice
+    let served = replies(proxy, 4).await;
+
+    // Then: its circuit opens
+    assert_eq!(served.iter().filter(|reply| reply.is_empty()).count(), 2);
+    assert_eq!(b0.circuit().state(), CircuitState::Open);
+
+    // And: while open, every connection goes to b1
+    assert!(replies(proxy, 4).await.iter().all(|reply| reply == "b1"));
+
+    // When: the open duration passes with b0 still refusing
+    clock.advance(OPEN_DURATION);
+
+    // Then: one half-open trial reaches b0 and opens the circuit again
+    let served = replies(proxy, 4).await;
+    assert_eq!(served.iter().filter(|reply| reply.is_empty()).count(), 1);
+    assert_eq!(b0.circuit().state(), CircuitState::Open);
+
+    // When: b0 comes back and the open duration passes again
+    let listener = tokio::net::TcpListener::bind(refusing)
+        .await
+        .expect("Failed to bind b0");
+    handles.push(serve_name(listener, "b0"));
+    clock.advance(OPEN_DURATION);
+
+    // Then: the trial succeeds, closing the circuit, and b0 takes its share
+    let mut served = replies(proxy, 4).await;
+    assert_eq!(b0.circuit().state(), CircuitState::Closed);
+    served.sort();
+    assert_eq!(served, ["b0", "b0", "b1", "b1"]);
+
+    proxy_handle.abort();
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,260 @@
+//! Copy tests
+//!
+//! Tests for the proxy copy loop with and without write coalescing, and its
+//! buffered-bytes cap
+
+use lemonade_load_balancer::prelude::*;
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context as TaskContext, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+
+/// Reader returning one queued chunk per read (split to fit), then EOF
+struct ChunkedReader {
+    chunks: VecDeque<Vec<u8>>,
+}
+
+impl ChunkedReader {
+    fn new(chunk_size: usize, count: usize) -> Self {
+        Self {
+            chunks: (0..count)
+                .map(|i| vec![(i % 251) as u8; chunk_size])
+                .collect(),
+        }
+    }
+
+    fn expected(&self) -> Vec<u8> {
+        self.chunks.iter().flatten().copied().collect()
+    }
+}
+
+impl AsyncRead for ChunkedReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if let Some(mut chunk) = self.chunks.pop_front() {
+            // Hand back what does not fit in the caller's buffer
+            if chunk.len() > buf.remaining() {
+                let rest = chunk.split_off(buf.remaining());
+                self.chunks.push_front(rest);
+            }
+            buf.put_slice(&chunk);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// What a counting writer has seen
+#[derive(Default)]
+struct Written {
+    data: Vec<u8>,
+    writes: usize,
+    shutdown: bool,
+}
+
+/// Writer counting write calls (one per syscall on a real socket)
+#[derive(Clone, Default)]
+struct CountingWriter {
+    written: Arc<Mutex<Written>>,
+}
+
+impl AsyncWrite for CountingWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut written = self.written.lock().unwrap();
+        written.data.extend_from_slice(buf);
+        written.writes += 1;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        _cx: &mut TaskContext<'_>,
+    ) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        _cx: &mut TaskContext<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.written.lock().unwrap().shutdown = true;
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn coalescing(coalesce_micros: u64, max_bytes: usize) -> CoalesceConfig {
+    CoalesceConfig {
+        enabled: true,
+        coalesce_micros,
+        max_bytes,
+    }
+}
+
+#[tokio::test]
+async fn copy_stream_without_coalescing_writes_every_read_should_succeed() {
+    // Given: a chatty source of 100 reads of 40 bytes
+    let mut reader = ChunkedReader::new(40, 100);
+    let expected = reader.expected();
+    let mut writer = CountingWriter::default();
+
+    // When: copying with coalescing disabled
+    let outcome = copy_stream(
+        &mut reader,
+        &mut writer,
+        &CoalesceConfig::default(),
+        1 << 20,
+    )
+    .await;
+
+    // Then: every read becomes a write and the writer is shut down
+    let written = writer.written.lock().unwrap();
+    assert_eq!(written.writes, 100);
+    assert_eq!(written.data, expected);
+    assert!(written.shutdown);
+    assert_eq!(outcome.bytes, 4000);
+    assert!(outcome.first_read_at.is_some());
+}
+
+#[tokio::test]
+async fn copy_stream_with_coalescing_reduces_writes_should_succeed() {
+    // Given: the same chatty source
+    let mut reader = ChunkedReader::new(40, 100);
+    let expected = reader.expected();
+    let mut writer = CountingWriter::default();
+
+    // When: copying with a 1 KiB coalescing threshold
+    let outcome = copy_stream(
+        &mut reader,
+        &mut writer,
+        &coalescing(1_000_000, 1024),
+        1 << 20,
+    )
+    .await;
+
+    // Then: writes are batched by size, the tail is flushed on EOF before
+    // shutdown, and no byte is lost or reordered
+    let written = writer.written.lock().unwrap();
+    assert_eq!(written.writes, 4);
+    assert_eq!(written.data, expected);
+    assert!(written.shutdown);
+    assert_eq!(outcome.bytes, 4000);
+}
+
+#[tokio::test]
+async fn copy_stream_with_coalescing_flushes_after_timeout_should_succeed() {
+    // Given: an open source that sends a few bytes and then goes quiet
+    let (mut source, mut reader) = tokio::io::duplex(1024);
+    let writer = CountingWriter::default();
+    let copy = tokio::spawn({
+        let mut writer = writer.clone();
+        async move {
+            copy_stream(&mut reader, &mut writer, &coalescing(1000, 1024), 1 << 20).await
+        }
+    });
+
+    // When: fewer bytes than the threshold are sent
+    source.write_all(b"hello").await.expect("write");
+
+    // Then: they are flushed once the coalescing delay elapses
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while writer.written.lock().unwrap().data.is_empty() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    })
+    .await
+    .expect("Pending data should be flushed after the coalescing delay");
+    assert_eq!(writer.written.lock().unwrap().data, b"hello");
+    assert!(!copy.is_finished());
+
+    // And: closing the source completes the copy with exact accounting
+    drop(source);
+    let outcome = copy.await.expect("copy task");
+    assert_eq!(outcome.bytes, 5);
+    assert!(writer.written.lock().unwrap().shutdown);
+}
+
+/// Copy a fast 256 KiB source into a reader draining 2 KiB per millisecond
+async fn copy_to_slow_reader(
+    coalesce: CoalesceConfig,
+    max_buffered_bytes: usize,
+) -> (CopyOutcome, Vec<u8>, Vec<u8>) {
+    let mut reader = ChunkedReader::new(4096, 64);
+    let expected = reader.expected();
+    let (mut writer, mut slow_side) = tokio::io::duplex(2048);
+    let drain = tokio::spawn(async move {
+        let mut received = Vec::new();
+        let mut buf = [0u8; 2048];
+        loop {
+            match slow_side.read(&mut buf).await {
+                Ok(0) | Err(_) => return received,
+                Ok(n) => received.extend_from_slice(&buf[..n]),
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    });
+    let outcome =
+        copy_stream(&mut reader, &mut writer, &coalesce, max_buffered_bytes).await;
+    drop(writer);
+    let received = drain.await.expect("drain task");
+    (outcome, expected, received)
+}
+
+#[tokio::test]
+async fn copy_stream_with_coalescing_caps_buffered_bytes_should_succeed() {
+    // Given: a coalescing threshold far above a 4 KiB buffered-bytes cap
+    let coalesce = coalescing(1_000_000, 1 << 20);
+
+    // When: a fast source is copied to a slow reader
+    let (outcome, expected, received) = copy_to_slow_reader(coalesce, 4096).await;
+
+    // Then: the buffer high-water mark stays at the cap, reading paused for
+    // the slow side, and no byte is lost or reordered
+    assert!(outcome.max_buffered <= 4096, "{}", outcome.max_buffered);
+    assert!(outcome.max_buffered > 0);
+    assert!(outcome.backpressure_events > 0);
+    assert_eq!(outcome.bytes, expected.len() as u64);
+    assert_eq!(received, expected);
+}
+
+#[tokio::test]
+async fn copy_stream_without_coalescing_caps_buffered_bytes_should_succeed() {
+    // Given: a buffered-bytes cap below the read buffer size
+    // When: a fast source is copied to a slow reader without coalescing
+    let (outcome, expected, received) =
+        copy_to_slow_reader(CoalesceConfig::default(), 1024).await;
+
+    // Then: reads never hold more than the cap
+    assert!(outcome.max_buffered <= 1024, "{}", outcome.max_buffered);
+    assert!(outcome.backpressure_events > 0);
+    assert_eq!(received, expected);
+}
+
+#[tokio::test]
+async fn copy_stream_with_ready_writer_has_no_backpressure_should_succeed() {
+    // Given: a writer that always accepts data immediately
+    let mut reader = ChunkedReader::new(4096, 64);
+    let mut writer = CountingWriter::default();
+
+    // When: copying with the default cap
+    let outcome = copy_stream(
+        &mut reader,
+        &mut writer,
+        &coalescing(1_000_000, 1 << 20),
+        256 * 1024,
+    )
+    .await;
+
+    // Then: no backpressure is recorded and the buffer stays under the cap
+    assert_eq!(outcome.backpressure_events, 0);
+    assert!(outcome.max_buffered <= 256 * 1024);
+    assert_eq!(outcome.bytes, 4096 * 64);
+}
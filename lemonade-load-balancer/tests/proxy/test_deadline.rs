@@ -0,0 +1,272 @@
+//! Tests for propagating request deadlines to backends
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::common::fixtures::create_test_config_fast;
+
+#[test]
+fn with_deadline_header_should_succeed() {
+    // Given: a request head followed by body bytes
+    let prelude = b"POST /work HTTP/1.1\r\nHost: lb.test\r\n\r\nbody";
+
+    // When: adding a deadline
+    let rewritten = with_deadline_header(prelude, 1500).expect("head is complete");
+
+    // Then: the header follows the request line and the body is untouched
+    assert_eq!(
+        rewritten,
+        b"POST /work HTTP/1.1\r\nX-Lemonade-Deadline-Ms: 1500\r\nHost: lb.test\r\n\r\nbody"
+    );
+
+    // And: a shorter client deadline is kept, a longer one is cut down, and
+    // either way only one header is sent
+    let tighter = b"GET / HTTP/1.1\r\nx-lemonade-deadline-ms: 40\r\n\r\n";
+    assert_eq!(
+        with_deadline_header(tighter, 1500).expect("head is complete"),
+        b"GET / HTTP/1.1\r\nX-Lemonade-Deadline-Ms: 40\r\n\r\n"
+    );
+    let looser = b"GET / HTTP/1.1\r\nX-Lemonade-Deadline-Ms: 90000\r\n\r\n";
+    assert_eq!(
+        with_deadline_header(looser, 1500).expect("head is complete"),
+        b"GET / HTTP/1.1\r\nX-Lemonade-Deadline-Ms: 1500\r\n\r\n"
+    );
+
+    // And: bytes without a complete head are left alone
+    assert!(with_deadline_header(b"GET / HTTP/1.1\r\nHost: lb", 1500).is_none());
+}
+
+#[tokio::test]
+async fn response_status_tap_should_succeed() {
+    // Given: a response whose status line arrives in two reads
+    let response = b"HTTP/1.1 504 Gateway Timeout\r\nContent-Length: 0\r\n\r\n";
+    let reader = (&response[..6]).chain(&response[6..]);
+
+    // When: reading it through the tap
+    let mut tap = ResponseStatusTap::new(reader);
+    let mut read = Vec::new();
+    tap.read_to_end(&mut read).await.expect("Failed to read");
+
+    // Then: the bytes pass through and the status is seen
+    assert_eq!(read, response);
+    assert_eq!(tap.status(), Some(504));
+
+    // And: a response that is not HTTP has no status
+    let mut tap = ResponseStatusTap::new(&b"PONG\r\n"[..]);
+    let mut read = Vec::new();
+    tap.read_to_end(&mut read).await.expect("Failed to read");
+    assert_eq!(tap.status(), None);
+}
+
+/// Backend honouring the deadline header: `504` when it is below 100 ms,
+/// else `200` with the deadline it received as the body
+async fn deadline_backend() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let head = String::from_utf8_lossy(&head).into_owned();
+                let deadline = head
+                    .lines()
+                    .find_map(|line| line.strip_prefix("X-Lemonade-Deadline-Ms: "))
+                    .unwrap_or("none")
+                    .to_string();
+                let response = match deadline.parse::<u64>() {
+                    Ok(ms) if ms < 100 => {
+                        "HTTP/1.1 504 Gateway Timeout\r\nConnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                    _ => format!(
+                        "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{}",
+                        deadline
+                    ),
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    (addr, handle)
+}
+
+/// Send `request` through the proxy and return the whole response
+async fn request_through(proxy_addr: SocketAddr, request: &[u8]) -> String {
+    let mut client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        client.write_all(request).await.expect("Failed to write");
+        client
+            .read_to_end(&mut response)
+            .await
+            .expect("Failed to read response");
+    })
+    .await
+    .expect("Backend reply should arrive");
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_propagates_deadlines_should_succeed() {
+    // Given: a proxy propagating a 2 s request budget to one backend
+    let (addr, backend_handle) = deadline_backend().await;
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.backends = vec![BackendConfig::from(BackendMeta::new(
+        0u8,
+        Some("deadline"),
+        BackendAddress::from(addr),
+        Some(1u8),
+    ))];
+    config.proxy.listen_address = proxy_addr;
+    config.proxy.propagate_deadlines = true;
+    config.proxy.request_timeout_millis = 2_000;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client sends a request without a deadline
+    let response =
+        request_through(proxy_addr, b"GET /work HTTP/1.1\r\nHost: lb.test\r\n\r\n").await;
+
+    // Then: the backend saw what was left of the budget
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    let deadline: u64 = response
+        .rsplit("\r\n\r\n")
+        .next()
+        .and_then(|body| body.parse().ok())
+        .expect("backend should report a deadline");
+    assert!((1_000..=2_000).contains(&deadline), "{}", deadline);
+
+    // When: a client asks for a deadline the backend cannot meet
+    let response = request_through(
+        proxy_addr,
+        b"GET /work HTTP/1.1\r\nHost: lb.test\r\nX-Lemonade-Deadline-Ms: 20\r\n\r\n",
+    )
+    .await;
+
+    // Then: the backend gave up and the proxy counted it
+    assert!(response.starts_with("HTTP/1.1 504"), "{}", response);
+    let backend = ctx.routing_table().get(0).expect("backend is routed");
+    let mut totals = backend.deadline_totals();
+    for _ in 0..50 {
+        if totals == (2, 1) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        totals = backend.deadline_totals();
+    }
+    assert_eq!(totals, (2, 1));
+
+    proxy_handle.abort();
+    backend_handle.abort();
+}
+
+/// Backend answering two pipelined requests on one connection, each with the
+/// deadline header it carried (`none` without one) as the body
+async fn pipelined_backend() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut read = Vec::new();
+                let mut buf = [0u8; 1024];
+                while read.windows(4).filter(|w| *w == b"\r\n\r\n").count() < 2 {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => read.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let read = String::from_utf8_lossy(&read).into_owned();
+                let mut response = String::new();
+                for head in read.split("\r\n\r\n").take(2) {
+                    let deadline = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("X-Lemonade-Deadline-Ms: "))
+                        .unwrap_or("none");
+                    response.push_str(&format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                        deadline.len(),
+                        deadline
+                    ));
+                }
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    (addr, handle)
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_propagates_deadline_to_first_request_only_should_succeed() {
+    // Given: a proxy propagating deadlines to a keep-alive backend
+    let (addr, backend_handle) = pipelined_backend().await;
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.backends = vec![BackendConfig::from(BackendMeta::new(
+        0u8,
+        Some("pipelined"),
+        BackendAddress::from(addr),
+        Some(1u8),
+    ))];
+    config.proxy.listen_address = proxy_addr;
+    config.proxy.propagate_deadlines = true;
+    config.proxy.request_timeout_millis = 2_000;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client pipelines two requests on one connection
+    let response = request_through(
+        proxy_addr,
+        b"GET /a HTTP/1.1\r\nHost: lb.test\r\n\r\nGET /b HTTP/1.1\r\nHost: lb.test\r\n\r\n",
+    )
+    .await;
+
+    // Then: only the first carried a deadline; the second went unchanged
+    let bodies: Vec<&str> = response
+        .split("HTTP/1.1 200 OK\r\n")
+        .skip(1)
+        .filter_map(|part| part.split("\r\n\r\n").nth(1))
+        .collect();
+    assert_eq!(bodies.len(), 2, "{}", response);
+    let first: u64 = bodies[0].parse().expect("first request has a deadline");
+    assert!((1_000..=2_000).contains(&first), "{}", first);
+    assert_eq!(bodies[1], "none");
+    let backend = ctx.routing_table().get(0).expect("backend is routed");
+    assert_eq!(backend.deadline_totals().0, 1);
+
+    proxy_handle.abort();
+    backend_handle.abort();
+}
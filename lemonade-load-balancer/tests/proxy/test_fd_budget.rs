@@ -0,0 +1,145 @@
+//! Tests for the file descriptor budget
+//!
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::common::fixtures::create_test_config_fast;
+
+#[test]
+fn fd_budget_ceiling_keeps_headroom_and_two_fds_per_connection_should_succeed() {
+    let budget = FdBudget::new(Some(1024));
+
+    assert_eq!(budget.ceiling(64), Some(480));
+    assert_eq!(budget.ceiling(2000), Some(0));
+    assert_eq!(FdBudget::new(None).ceiling(64), None);
+}
+
+#[test]
+fn fd_budget_clamps_max_connections_should_succeed() {
+    // Given: a limit supporting 100 connections
+    let budget = FdBudget::new(Some(264));
+
+    // Then: an unset or higher max is lowered to the ceiling, a lower one kept
+    assert_eq!(budget.max_connections(None, 64), Some(100));
+    assert_eq!(budget.max_connections(Some(10_000), 64), Some(100));
+    assert_eq!(budget.max_connections(Some(50), 64), Some(50));
+
+    // And: an unknown limit leaves the configured max alone
+    let unknown = FdBudget::new(None);
+    assert_eq!(unknown.max_connections(None, 64), None);
+    assert_eq!(unknown.max_connections(Some(10_000), 64), Some(10_000));
+}
+
+#[test]
+fn fd_budget_warns_when_max_connections_exceeds_limit_should_succeed() {
+    let budget = FdBudget::new(Some(264));
+
+    let warning = budget
+        .warning(Some(10_000), 64)
+        .expect("A max above the ceiling should warn");
+    assert!(warning.contains("10000"));
+    assert!(warning.contains("capping at 100"));
+
+    assert_eq!(budget.warning(Some(100), 64), None);
+    assert_eq!(budget.warning(None, 64), None);
+    assert_eq!(FdBudget::new(None).warning(Some(10_000), 64), None);
+}
+
+#[test]
+fn parse_soft_fd_limit_reads_max_open_files_should_succeed() {
+    let limits = "Limit                     Soft Limit           Hard Limit           Units     \n\
+                  Max processes             63396                63396                processes \n\
+                  Max open files            1024                 524288               files     \n";
+    assert_eq!(parse_soft_fd_limit(limits), Some(1024));
+
+    let unlimited =
+        "Max open files            unlimited            unlimited            files\n";
+    assert_eq!(parse_soft_fd_limit(unlimited), None);
+    assert_eq!(parse_soft_fd_limit(""), None);
+}
+
+#[test]
+fn is_fd_exhausted_matches_emfile_and_enfile_should_succeed() {
+    assert!(is_fd_exhausted(&std::io::Error::from_raw_os_error(24)));
+    assert!(is_fd_exhausted(&std::io::Error::from_raw_os_error(23)));
+    assert!(!is_fd_exhausted(&std::io::Error::from_raw_os_error(111)));
+    assert!(!is_fd_exhausted(&std::io::Error::other("unrelated")));
+}
+
+#[tokio::test]
+async fn fd_budget_low_ceiling_rejects_connections_over_it_should_succeed() {
+    // Given: an echo backend
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let server_handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = backend_listener.accept().await {
+            tokio::spawn(async move {
+                let (mut read, mut write) = stream.split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+        }
+    });
+
+    // And: a proxy with no max_connections whose fd limit only fits two
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("backend-0"),
+            BackendAddress::from(backend_addr),
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = proxy_addr;
+    config.proxy.max_connections = None;
+    config.proxy.fd_headroom = 64;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service")
+        .with_fd_budget(FdBudget::new(Some(68)));
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move {
+            let _ = service.accept_connections(ctx).await;
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: two connections are held open
+    let mut held = Vec::new();
+    for _ in 0..2 {
+        let mut client = tokio::net::TcpStream::connect(proxy_addr)
+            .await
+            .expect("Failed to connect to proxy");
+        client.write_all(b"ping").await.expect("write");
+        let mut buf = [0u8; 4];
+        tokio::time::timeout(Duration::from_secs(2), client.read_exact(&mut buf))
+            .await
+            .expect("Echo should arrive")
+            .expect("Echo read should succeed");
+        held.push(client);
+    }
+
+    // Then: a third is closed without reaching the backend
+    let mut rejected = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let _ = rejected.write_all(b"ping").await;
+    let mut buf = [0u8; 4];
+    let read = tokio::time::timeout(Duration::from_secs(2), rejected.read(&mut buf))
+        .await
+        .expect("Rejected connection should be closed");
+    assert!(matches!(read, Ok(0) | Err(_)));
+    assert_eq!(ctx.routing_table().get(0).unwrap().active_connections(), 2);
+
+    drop(held);
+    proxy_handle.abort();
+    server_handle.abort();
+}
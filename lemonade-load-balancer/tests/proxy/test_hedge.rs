@@ -0,0 +1,331 @@
+//! Tests for hedged backend connects
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+#[test]
+fn hedge_budget_caps_hedge_rate_should_succeed() {
+    // Given: a budget allowing a quarter of the connections to hedge
+    let budget = HedgeBudget::default();
+
+    // When: eight connections all try to hedge
+    let hedged = (0..8)
+        .filter(|_| {
+            budget.record_connection();
+            budget.try_hedge(0.25)
+        })
+        .count();
+
+    // Then: only two of them may hedge
+    assert_eq!(hedged, 2);
+    assert_eq!(budget.totals(), (8, 2));
+}
+
+#[test]
+fn hedge_budget_zero_rate_never_hedges_should_succeed() {
+    let budget = HedgeBudget::default();
+    budget.record_connection();
+
+    assert!(!budget.try_hedge(0.0));
+}
+
+#[test]
+fn hedge_delay_static_and_adaptive_should_succeed() {
+    // Given: a backend with recorded connect times of about 10ms
+    let backend = Backend::new(BackendConfig::from(create_test_backend(0, None, None)));
+    for _ in 0..20 {
+        backend.record_connection_timings(9_000, None, 20_000);
+    }
+    let static_delay = HedgingConfig {
+        delay_millis: 40,
+        ..HedgingConfig::default()
+    };
+    let adaptive = HedgingConfig {
+        adaptive_delay: true,
+        ..static_delay.clone()
+    };
+
+    // When/Then: the static delay ignores the samples, the adaptive one uses them
+    assert_eq!(
+        hedge_delay(&backend, &static_delay),
+        Duration::from_millis(40)
+    );
+    let delay = hedge_delay(&backend, &adaptive);
+    assert!(delay > Duration::ZERO && delay <= Duration::from_millis(10));
+
+    // And: the adaptive delay falls back to the static one without samples
+    let fresh = Backend::new(BackendConfig::from(create_test_backend(1, None, None)));
+    assert_eq!(hedge_delay(&fresh, &adaptive), Duration::from_millis(40));
+}
+
+#[test]
+fn pick_hedge_backend_prefers_least_loaded_other_should_succeed() {
+    // Given: four backends, one draining and one busy
+    let routing = RouteTable::new(
+        (0..4)
+            .map(|id| BackendConfig::from(create_test_backend(id, None, None)))
+            .collect(),
+    );
+    routing.get(1).unwrap().mark_draining();
+    routing.get(2).unwrap().increment_connection();
+
+    // When: picking a hedge for backend 0
+    let hedge = pick_hedge_backend(&routing, 0, &LabelSelector::default())
+        .expect("A hedge should be available");
+
+    // Then: the idle, non-draining other backend is chosen
+    assert_eq!(hedge.id(), 3);
+
+    // And: no hedge is available when the primary is the only backend
+    let single = RouteTable::new(vec![BackendConfig::from(create_test_backend(
+        0, None, None,
+    ))]);
+    assert!(pick_hedge_backend(&single, 0, &LabelSelector::default()).is_none());
+}
+
+#[test]
+fn hedging_config_rejects_invalid_rate_should_fail() {
+    let config = HedgingConfig {
+        max_hedge_rate: 1.5,
+        ..HedgingConfig::default()
+    };
+
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+    assert!(HedgingConfig::default().validate().is_ok());
+}
+
+/// Listener whose accept queue is full, so new connects stall in SYN retries
+async fn stalled_listener() -> (tokio::net::TcpListener, Vec<tokio::net::TcpStream>) {
+    let socket = tokio::net::TcpSocket::new_v4().expect("Failed to create socket");
+    socket
+        .bind("127.0.0.1:0".parse().unwrap())
+        .expect("Failed to bind slow backend");
+    let listener = socket.listen(1).expect("Failed to listen");
+    let addr = listener.local_addr().expect("slow backend address");
+    // Fill the accept queue and never accept
+    let mut held = Vec::new();
+    while tokio::time::timeout(
+        Duration::from_millis(100),
+        tokio::net::TcpStream::connect(addr),
+    )
+    .await
+    .is_ok_and(|stream| stream.map(|s| held.push(s)).is_ok())
+    {
+        assert!(held.len() < 16, "accept queue never filled");
+    }
+    (listener, held)
+}
+
+async fn echo_listener() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let (mut read, mut write) = stream.split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+        }
+    });
+    (addr, handle)
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_hedge_wins_over_slow_backend_should_succeed() {
+    // Given: a backend whose connects stall and a fast echo backend
+    let (slow_listener, _held) = stalled_listener().await;
+    let slow_addr = slow_listener.local_addr().expect("slow backend address");
+    let (fast_addr, server_handle) = echo_listener().await;
+
+    // And: a round-robin proxy over both with hedging after 50ms
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let backends = vec![
+        BackendMeta::new(
+            0u8,
+            Some("slow"),
+            BackendAddress::from(slow_addr),
+            Some(1u8),
+        ),
+        BackendMeta::new(
+            1u8,
+            Some("fast"),
+            BackendAddress::from(fast_addr),
+            Some(1u8),
+        ),
+    ];
+    let mut config = create_test_config_fast(backends, Strategy::RoundRobin);
+    config.proxy.listen_address = proxy_addr;
+    config.proxy.hedging = HedgingConfig {
+        enabled: true,
+        delay_millis: 50,
+        adaptive_delay: false,
+        max_hedge_rate: 1.0,
+    };
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let mut metrics_rx = ctx
+        .channels()
+        .metrics_rx()
+        .expect("Metrics receiver should be available");
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let service = service.clone();
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: two clients connect, so round robin sends one to the slow backend
+    for _ in 0..2 {
+        let mut client = tokio::net::TcpStream::connect(proxy_addr)
+            .await
+            .expect("Failed to connect to proxy");
+        let mut echoed = [0u8; 5];
+        tokio::time::timeout(Duration::from_millis(800), async {
+            client.write_all(b"hedge").await.expect("Failed to write");
+            client
+                .read_exact(&mut echoed)
+                .await
+                .expect("Failed to read echo");
+        })
+        .await
+        .expect("Echo should arrive before the slow connect would retry");
+        assert_eq!(&echoed, b"hedge");
+    }
+
+    // Then: one connect hedged and the hedge to the fast backend won
+    let (primary, hedge, won) = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match metrics_rx.recv().await {
+                Some(MetricsEvent::HedgeResolved {
+                    primary_backend_id,
+                    hedge_backend_id,
+                    hedge_won,
+                    ..
+                }) => break (primary_backend_id, hedge_backend_id, hedge_won),
+                Some(_) => continue,
+                None => panic!("Metrics channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("HedgeResolved event should be sent");
+    assert_eq!((primary, hedge, won), (0, 1, true));
+    assert_eq!(service.hedge_totals(), (2, 1));
+
+    // And: the cancelled attempt released its count; the others drain to zero
+    let routing = ctx.routing_table();
+    assert_eq!(routing.get(0).unwrap().active_connections(), 0);
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while routing.get(1).unwrap().active_connections() != 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Fast backend connections should close with the clients");
+
+    proxy_handle.abort();
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_spent_retry_budget_suppresses_hedge_should_succeed() {
+    // Given: a backend whose connects stall and a fast echo backend
+    let (slow_listener, _held) = stalled_listener().await;
+    let slow_addr = slow_listener.local_addr().expect("slow backend address");
+    let (fast_addr, server_handle) = echo_listener().await;
+
+    // And: a hedging proxy over both with an empty retry budget that
+    // successes do not refill
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let backends = vec![
+        BackendMeta::new(
+            0u8,
+            Some("slow"),
+            BackendAddress::from(slow_addr),
+            Some(1u8),
+        ),
+        BackendMeta::new(
+            1u8,
+            Some("fast"),
+            BackendAddress::from(fast_addr),
+            Some(1u8),
+        ),
+    ];
+    let mut config = create_test_config_fast(backends, Strategy::RoundRobin);
+    config.proxy.listen_address = proxy_addr;
+    config.proxy.hedging = HedgingConfig {
+        enabled: true,
+        delay_millis: 50,
+        adaptive_delay: false,
+        max_hedge_rate: 1.0,
+    };
+    config.proxy.retry_budget = RetryBudgetConfig {
+        enabled: true,
+        ratio: 0.0,
+        max_tokens: 0,
+    };
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let mut metrics_rx = ctx
+        .channels()
+        .metrics_rx()
+        .expect("Metrics receiver should be available");
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let service = service.clone();
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: two clients connect, so round robin sends one to the slow backend
+    let mut clients = Vec::new();
+    for _ in 0..2 {
+        clients.push(
+            tokio::net::TcpStream::connect(proxy_addr)
+                .await
+                .expect("Failed to connect to proxy"),
+        );
+    }
+
+    // Then: the slow connect is not hedged and the suppression is reported
+    let backend_id = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match metrics_rx.recv().await {
+                Some(MetricsEvent::RetrySuppressed {
+                    backend_id, kind, ..
+                }) => {
+                    assert_eq!(kind, RetryKind::Hedge);
+                    break backend_id;
+                }
+                Some(MetricsEvent::HedgeResolved { .. }) => {
+                    panic!("Hedge should be suppressed")
+                }
+                Some(_) => continue,
+                None => panic!("Metrics channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("RetrySuppressed event should be sent");
+    assert_eq!(backend_id, 0);
+    assert_eq!(ctx.retry_budget().totals(), (0, 1));
+    // And: the skipped hedge does not count against the hedge rate
+    assert_eq!(service.hedge_totals(), (2, 0));
+
+    drop(clients);
+    proxy_handle.abort();
+    server_handle.abort();
+}
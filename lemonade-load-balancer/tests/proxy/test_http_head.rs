@@ -0,0 +1,219 @@
+//! Tests for reading HTTP request heads for header hash routing
+//!
+use lemonade_load_balancer::prelude::*;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::common::fixtures::create_test_config_fast;
+
+const REQUEST: &[u8] =
+    b"GET /tenants/42?page=2 HTTP/1.1\r\nHost: example.com\r\nX-Tenant-Id:  acme \r\n\r\n";
+
+#[test]
+fn parse_request_head_should_succeed() {
+    // When: parsing a complete request head
+    let RequestHeadParse::Parsed(meta) = parse_request_head(REQUEST) else {
+        panic!("request head should parse");
+    };
+
+    // Then: the path drops the query and headers are trimmed, looked up by
+    // any case
+    assert_eq!(meta.method, "GET");
+    assert_eq!(meta.path, "/tenants/42");
+    assert_eq!(meta.header("x-tenant-id"), Some("acme"));
+    assert_eq!(meta.header("HOST"), Some("example.com"));
+    assert_eq!(meta.header("X-Missing"), None);
+
+    // And: absolute-form targets and bare line endings are accepted
+    let RequestHeadParse::Parsed(meta) =
+        parse_request_head(b"POST http://example.com/a/b#x HTTP/1.0\nA: 1\n\n")
+    else {
+        panic!("absolute-form head should parse");
+    };
+    assert_eq!(meta.path, "/a/b");
+    assert_eq!(meta.header("a"), Some("1"));
+}
+
+#[test]
+fn parse_request_head_incomplete_should_wait() {
+    // When/Then: every prefix of a head is incomplete, not rejected
+    for end in 0..REQUEST.len() - 1 {
+        assert_eq!(
+            parse_request_head(&REQUEST[..end]),
+            RequestHeadParse::Incomplete,
+            "prefix of {} bytes",
+            end
+        );
+    }
+}
+
+#[test]
+fn parse_request_head_non_http_should_fail() {
+    assert_eq!(
+        parse_request_head(&[0x16, 0x03, 0x01, 0x00, 0x05]),
+        RequestHeadParse::NotHttp
+    );
+    assert_eq!(parse_request_head(b"ping\r\n"), RequestHeadParse::NotHttp);
+    assert_eq!(
+        parse_request_head(b"GET / SPDY/3\r\n\r\n"),
+        RequestHeadParse::Malformed
+    );
+    assert_eq!(
+        parse_request_head(b"GET / HTTP/1.1\r\nno colon\r\n\r\n"),
+        RequestHeadParse::Malformed
+    );
+}
+
+#[test]
+fn parse_request_head_malformed_should_not_panic() {
+    // When: corrupting each byte of a valid head in turn
+    for i in 0..REQUEST.len() {
+        for value in [0x00, b'\n', b':', 0xff] {
+            let mut corrupt = REQUEST.to_vec();
+            corrupt[i] = value;
+
+            // Then: parsing never panics
+            let _ = parse_request_head(&corrupt);
+        }
+    }
+}
+
+#[tokio::test]
+async fn sniff_request_head_returns_bytes_read_should_succeed() {
+    let limits = RequestHeadLimits {
+        timeout: Duration::from_millis(200),
+        max_bytes: 1024,
+    };
+
+    // Given: a request head followed by a body
+    let mut input = REQUEST.to_vec();
+    input.extend_from_slice(b"body");
+    let (meta, read) = sniff_request_head(&mut &input[..], limits).await;
+
+    // Then: the head is parsed and every byte read is handed back
+    assert_eq!(meta.expect("head").header("x-tenant-id"), Some("acme"));
+    assert!(input.starts_with(&read));
+    assert!(read.len() >= REQUEST.len());
+
+    // And: a head past the byte cap is not parsed, but its bytes are kept
+    let short = RequestHeadLimits {
+        max_bytes: 16,
+        ..limits
+    };
+    let mut reader = REQUEST;
+    let (meta, read) = sniff_request_head(&mut reader, short).await;
+    assert!(meta.is_none());
+    assert_eq!(read, &REQUEST[..16]);
+}
+
+/// Backend that writes its name, then echoes what it receives
+async fn named_echo_backend(
+    name: &'static str,
+) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let _ = stream.write_all(name.as_bytes()).await;
+                let (mut read, mut write) = stream.split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+        }
+    });
+    (addr, handle)
+}
+
+/// Send `payload` through the proxy and return the backend name and echo
+async fn send_through(proxy_addr: SocketAddr, payload: &[u8]) -> (String, Vec<u8>) {
+    let mut client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut name = [0u8; 5];
+    let mut echoed = vec![0u8; payload.len()];
+    tokio::time::timeout(Duration::from_secs(2), async {
+        client.write_all(payload).await.expect("Failed to write");
+        client
+            .read_exact(&mut name)
+            .await
+            .expect("Failed to read name");
+        client
+            .read_exact(&mut echoed)
+            .await
+            .expect("Failed to read echo");
+    })
+    .await
+    .expect("Backend reply should arrive");
+    (String::from_utf8_lossy(&name).into_owned(), echoed)
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_routes_by_header_hash_should_succeed() {
+    // Given: three backends picked by X-Tenant-Id
+    let mut backends = Vec::new();
+    let mut handles = Vec::new();
+    for (id, name) in ["alpha", "bravo", "delta"].into_iter().enumerate() {
+        let (addr, handle) = named_echo_backend(name).await;
+        backends.push(BackendConfig::from(BackendMeta::new(
+            id as u8,
+            Some(name),
+            BackendAddress::from(addr),
+            Some(1u8),
+        )));
+        handles.push(handle);
+    }
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(Vec::new(), Strategy::HeaderHash);
+    config.backends = backends;
+    config.strategy_params = serde_json::json!({ "hash_on": "X-Tenant-Id" });
+    config.proxy.listen_address = proxy_addr;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: each tenant connects three times
+    let mut routed: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for _ in 0..3 {
+        for tenant in ["acme", "globex", "initech", "umbrella"] {
+            let request = format!(
+                "GET / HTTP/1.1\r\nHost: lb.test\r\nX-Tenant-Id: {}\r\n\r\n",
+                tenant
+            );
+            let (backend, echoed) = send_through(proxy_addr, request.as_bytes()).await;
+            // Then: the request head read for the pick is replayed
+            assert_eq!(echoed, request.as_bytes());
+            routed.entry(tenant.to_string()).or_default().push(backend);
+        }
+    }
+
+    // Then: every connection of a tenant reached the same backend
+    for (tenant, backends) in &routed {
+        assert!(
+            backends.iter().all(|backend| *backend == backends[0]),
+            "{} routed to {:?}",
+            tenant,
+            backends
+        );
+    }
+
+    // And: traffic that is not HTTP is still proxied
+    let (_, echoed) = send_through(proxy_addr, b"PING\r\n\r\n").await;
+    assert_eq!(echoed, b"PING\r\n\r\n");
+
+    proxy_handle.abort();
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,277 @@
+//! Tests for routing connections by their first HTTP request
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Request head for `path` on `host`
+fn request(host: &str, path: &str) -> RequestMeta {
+    RequestMeta {
+        method: "GET".to_string(),
+        path: path.to_string(),
+        headers: vec![("host".to_string(), host.to_string())],
+    }
+}
+
+/// Rule named `name` sending `path_prefix` to the `group` label `group`
+fn rule(name: &str, host: Option<&str>, path_prefix: &str, group: &str) -> HttpRouteRule {
+    HttpRouteRule {
+        name: name.to_string(),
+        host: host.map(str::to_string),
+        path_prefix: path_prefix.to_string(),
+        group: LabelSelector::single("group", group),
+    }
+}
+
+/// Routing `/api/` and `/static/` to their groups, and `admin.test` to its own
+fn api_and_static_routing() -> HttpRoutingConfig {
+    HttpRoutingConfig {
+        enabled: true,
+        rules: vec![
+            rule("admin", Some("admin.test"), "/", "static"),
+            rule("api", None, "/api/", "api"),
+            rule("static", None, "/static/", "static"),
+        ],
+        fallback: LabelSelector::single("group", "api"),
+        reject_unmatched: false,
+    }
+}
+
+/// Backend config carrying a `group` label
+fn labelled_backend(id: u8, name: &str, addr: SocketAddr, group: &str) -> BackendConfig {
+    let mut backend = BackendConfig::from(BackendMeta::new(
+        id,
+        Some(name),
+        BackendAddress::from(addr),
+        Some(1u8),
+    ));
+    backend.labels = Labels::from([("group".to_string(), group.to_string())]);
+    backend
+}
+
+#[test]
+fn http_routing_config_route_for_should_succeed() {
+    // Given: rules for a host, `/api/` and `/static/`
+    let routing = api_and_static_routing();
+
+    // When/Then: the first matching rule picks the group
+    let route = |host: &str, path: &str| {
+        routing
+            .route_for(Some(&request(host, path)))
+            .map(|(route, _)| route.to_string())
+    };
+    assert_eq!(route("lb.test", "/api/users").as_deref(), Some("api"));
+    assert_eq!(
+        route("lb.test", "/static/app.js").as_deref(),
+        Some("static")
+    );
+    assert_eq!(
+        route("ADMIN.test:8080", "/api/users").as_deref(),
+        Some("admin")
+    );
+    assert_eq!(
+        routing.route_for(Some(&request("lb.test", "/static/"))),
+        Some(("static", &LabelSelector::single("group", "static")))
+    );
+
+    // And: a prefix is matched as written, and requests no rule matches or
+    // without a head use the fallback group
+    assert_eq!(route("lb.test", "/api").as_deref(), Some(FALLBACK_ROUTE));
+    assert_eq!(
+        routing.route_for(None),
+        Some((FALLBACK_ROUTE, &LabelSelector::single("group", "api")))
+    );
+
+    // When: unmatched requests are rejected
+    let routing = HttpRoutingConfig {
+        reject_unmatched: true,
+        ..api_and_static_routing()
+    };
+
+    // Then: they get no group
+    assert_eq!(routing.route_for(Some(&request("lb.test", "/other"))), None);
+    assert_eq!(routing.route_for(None), None);
+}
+
+#[test]
+fn http_routing_config_validate_should_fail() {
+    // Given: an "api" and a "static" backend routed to by HTTP rules
+    let addr: SocketAddr = "127.0.0.1:9001".parse().unwrap();
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.backends = vec![
+        labelled_backend(0, "api", addr, "api"),
+        labelled_backend(1, "static", "127.0.0.1:9002".parse().unwrap(), "static"),
+    ];
+    config.proxy.route_by_http = api_and_static_routing();
+    assert!(config.validate().is_ok());
+
+    // When/Then: a rule routing to a group with no backend is rejected
+    config
+        .proxy
+        .route_by_http
+        .rules
+        .push(rule("images", None, "/images/", "images"));
+    assert!(matches!(config.validate(), Err(ConfigError::Proxy(_))));
+
+    // And: so are a duplicate name, a relative path and an empty host
+    let invalid = [
+        rule("api", None, "/v2/", "api"),
+        rule("v2", None, "v2/", "api"),
+        rule("v2", Some(""), "/v2/", "api"),
+        rule(FALLBACK_ROUTE, None, "/v2/", "api"),
+    ];
+    for invalid in invalid {
+        config.proxy.route_by_http = api_and_static_routing();
+        config.proxy.route_by_http.rules.push(invalid.clone());
+        assert!(
+            matches!(config.validate(), Err(ConfigError::Proxy(_))),
+            "{:?}",
+            invalid
+        );
+    }
+
+    // And: rules are not checked while HTTP routing is disabled
+    config.proxy.route_by_http.enabled = false;
+    assert!(config.validate().is_ok());
+}
+
+/// Backend that writes its name, then echoes what it receives
+async fn named_echo_backend(
+    name: &'static str,
+) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let _ = stream.write_all(name.as_bytes()).await;
+                let (mut read, mut write) = stream.split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+        }
+    });
+    (addr, handle)
+}
+
+/// Send a request for `path` through the proxy and return the name of the
+/// backend it reached, checking the request was replayed unchanged
+async fn route_through(proxy_addr: SocketAddr, path: &str) -> String {
+    let request = format!("GET {} HTTP/1.1\r\nHost: lb.test\r\n\r\n", path);
+    let mut client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut name = [0u8; 5];
+    let mut echoed = vec![0u8; request.len()];
+    tokio::time::timeout(Duration::from_secs(2), async {
+        client
+            .write_all(request.as_bytes())
+            .await
+            .expect("Failed to write");
+        client
+            .read_exact(&mut name)
+            .await
+            .expect("Failed to read name");
+        client
+            .read_exact(&mut echoed)
+            .await
+            .expect("Failed to read echo");
+    })
+    .await
+    .expect("Backend reply should arrive");
+    assert_eq!(echoed, request.as_bytes());
+    String::from_utf8_lossy(&name).into_owned()
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_routes_by_http_path_should_succeed() {
+    // Given: two "api" and two "static" workers in different label groups
+    let workers = [
+        ("api-1", "api"),
+        ("api-2", "api"),
+        ("sta-1", "static"),
+        ("sta-2", "static"),
+    ];
+    let mut handles = Vec::new();
+    let mut backends = Vec::new();
+    for (id, (name, group)) in workers.into_iter().enumerate() {
+        let (addr, handle) = named_echo_backend(name).await;
+        backends.push(labelled_backend(id as u8, name, addr, group));
+        handles.push(handle);
+    }
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.backends = backends;
+    config.proxy.listen_address = proxy_addr;
+
+    // And: `/api/` and `/static/` routed to their groups, the rest rejected
+    config.proxy.route_by_http = HttpRoutingConfig {
+        reject_unmatched: true,
+        ..api_and_static_routing()
+    };
+    config
+        .validate()
+        .expect("HTTP routing config should validate");
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service =
+        TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+            .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When/Then: each request reaches a worker of its group, despite round
+    // robin, and is replayed unchanged
+    for _ in 0..2 {
+        for (path, group) in [("/api/users", "api"), ("/static/app.js", "sta")] {
+            let backend = route_through(proxy_addr, path).await;
+            assert!(backend.starts_with(group), "{} routed to {}", path, backend);
+        }
+    }
+
+    // And: a request no rule matches is answered 404
+    let mut client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        client
+            .write_all(b"GET /other HTTP/1.1\r\nHost: lb.test\r\n\r\n")
+            .await
+            .expect("Failed to write");
+        let _ = client.read_to_end(&mut response).await;
+    })
+    .await
+    .expect("The unmatched request should be answered");
+    assert!(response.starts_with(b"HTTP/1.1 404 Not Found"));
+
+    // When: a reload swaps the groups of the two rules
+    let mut reloaded = config.clone();
+    for rule in &mut reloaded.proxy.route_by_http.rules {
+        rule.group = match rule.name.as_str() {
+            "api" => LabelSelector::single("group", "static"),
+            _ => LabelSelector::single("group", "api"),
+        };
+    }
+    ctx.migrate(reloaded).await.expect("Failed to migrate");
+
+    // Then: the next connections follow the new rules
+    for (path, group) in [("/api/users", "sta"), ("/static/app.js", "api")] {
+        let backend = route_through(proxy_addr, path).await;
+        assert!(backend.starts_with(group), "{} routed to {}", path, backend);
+    }
+
+    proxy_handle.abort();
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,93 @@
+//! Tests for the first-bytes timeout
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Echo backend counting the connections it accepts
+async fn counting_echo_backend()
+-> (SocketAddr, Arc<AtomicUsize>, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let handle = tokio::spawn({
+        let accepted = accepted.clone();
+        async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let (mut read, mut write) = stream.split();
+                    let _ = tokio::io::copy(&mut read, &mut write).await;
+                });
+            }
+        }
+    });
+    (addr, accepted, handle)
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_closes_silent_clients_without_backend_connect_should_succeed()
+ {
+    // Given: a proxy that waits 200ms for the client's first bytes
+    let (backend_addr, accepted, backend_handle) = counting_echo_backend().await;
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(0u8, Some("echo"), backend_addr, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = proxy_addr;
+    config.proxy.initial_read_timeout_millis = Some(200);
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let service = service.clone();
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client connects and never sends a byte
+    let started = Instant::now();
+    let mut silent = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut buf = [0u8; 1];
+    let read = tokio::time::timeout(Duration::from_secs(2), silent.read(&mut buf))
+        .await
+        .expect("Silent client should be closed");
+
+    // Then: it is closed after the timeout and no backend was connected
+    assert!(matches!(read, Ok(0) | Err(_)));
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert_eq!(accepted.load(Ordering::SeqCst), 0);
+    assert_eq!(service.silent_connections_closed(), 1);
+
+    // When: a client speaks promptly
+    let mut prompt = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    prompt.write_all(b"ping").await.expect("Failed to write");
+    let mut echoed = [0u8; 4];
+    tokio::time::timeout(Duration::from_secs(2), prompt.read_exact(&mut echoed))
+        .await
+        .expect("Echo should arrive")
+        .expect("Failed to read echo");
+
+    // Then: it is proxied as usual, first bytes included
+    assert_eq!(&echoed, b"ping");
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    assert_eq!(service.silent_connections_closed(), 1);
+
+    proxy_handle.abort();
+    backend_handle.abort();
+}
@@ -0,0 +1,202 @@
+//! Tests for proxy loop detection
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Reserve a local port for a proxy listener
+fn reserve_port() -> SocketAddr {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port")
+}
+
+/// Start a proxy on `listen_address` forwarding to `backend`, with its loop
+/// guards set by `guard`
+async fn start_proxy(
+    listen_address: SocketAddr,
+    backend: SocketAddr,
+    guard: impl FnOnce(&mut ProxyConfig),
+) -> (
+    TokioProxyService,
+    tokio::task::JoinHandle<Result<(), ProxyError>>,
+) {
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(0u8, Some("next-hop"), backend, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = listen_address;
+    guard(&mut config.proxy);
+    config.validate().expect("Each hop alone is a valid config");
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let handle = tokio::spawn({
+        let service = service.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    (service, handle)
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_two_proxy_loop_is_cut_should_succeed() {
+    // Given: two proxies forwarding to each other, each allowing 4
+    // connections from backend addresses
+    let first_address = reserve_port();
+    let second_address = reserve_port();
+    let max_backend_peers = |proxy: &mut ProxyConfig| {
+        proxy.max_backend_peer_connections = Some(4);
+    };
+    let (first, first_handle) =
+        start_proxy(first_address, second_address, max_backend_peers).await;
+    let (second, second_handle) =
+        start_proxy(second_address, first_address, max_backend_peers).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client connects to the first proxy
+    let mut client = tokio::net::TcpStream::connect(first_address)
+        .await
+        .expect("Failed to connect to proxy");
+
+    // Then: the chain hops back and forth until a proxy rejects a hop, and
+    // the client is closed instead of the loop running away
+    let mut buf = [0u8; 1];
+    let read = tokio::time::timeout(Duration::from_secs(5), client.read(&mut buf))
+        .await
+        .expect("The looping client should be closed");
+    assert!(matches!(read, Ok(0) | Err(_)));
+    let rejected = first.loop_connections_rejected() + second.loop_connections_rejected();
+    assert_eq!(rejected, 1);
+
+    // And: every hop is released once the client goes away
+    drop(client);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while first.backend_peer_connections() + second.backend_peer_connections() > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Every hop should close");
+
+    first_handle.abort();
+    second_handle.abort();
+}
+
+#[test]
+fn with_hop_header_should_succeed() {
+    // Given: a request head without a hop count
+    let prelude = b"GET / HTTP/1.1\r\nHost: lb.test\r\n\r\n";
+
+    // When: counting a hop
+    let (rewritten, hops) = with_hop_header(prelude).expect("head is complete");
+
+    // Then: it is the first hop, sent right after the request line
+    assert_eq!(hops, 1);
+    assert_eq!(
+        rewritten,
+        b"GET / HTTP/1.1\r\nX-Lemonade-Hop: 1\r\nHost: lb.test\r\n\r\n"
+    );
+
+    // And: hops already counted are counted on from the highest, in one header
+    let counted = b"GET / HTTP/1.1\r\nx-lemonade-hop: 2\r\nX-Lemonade-Hop: 1\r\n\r\n";
+    assert_eq!(
+        with_hop_header(counted).expect("head is complete"),
+        (b"GET / HTTP/1.1\r\nX-Lemonade-Hop: 3\r\n\r\n".to_vec(), 3)
+    );
+
+    // And: bytes without a complete head are left alone
+    assert!(with_hop_header(b"GET / HTTP/1.1\r\nHost: lb").is_none());
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_http_loop_is_answered_508_should_succeed() {
+    // Given: two proxies forwarding to each other, each allowing 3 hops
+    let first_address = reserve_port();
+    let second_address = reserve_port();
+    let max_hops = |proxy: &mut ProxyConfig| proxy.max_hops = Some(3);
+    let (first, first_handle) =
+        start_proxy(first_address, second_address, max_hops).await;
+    let (second, second_handle) =
+        start_proxy(second_address, first_address, max_hops).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client sends a request to the first proxy
+    let mut client = tokio::net::TcpStream::connect(first_address)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        client
+            .write_all(b"GET / HTTP/1.1\r\nHost: lb.test\r\n\r\n")
+            .await
+            .expect("Failed to write");
+        let _ = client.read_to_end(&mut response).await;
+    })
+    .await
+    .expect("The looping request should be answered");
+
+    // Then: the fourth hop, back at the second proxy, is refused and the
+    // client sees why
+    let response = String::from_utf8_lossy(&response);
+    assert!(
+        response.starts_with("HTTP/1.1 508 Loop Detected"),
+        "{}",
+        response
+    );
+    assert_eq!(first.loop_connections_rejected(), 0);
+    assert_eq!(second.loop_connections_rejected(), 1);
+
+    first_handle.abort();
+    second_handle.abort();
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_counts_hop_to_backend_should_succeed() {
+    // Given: a proxy allowing 3 hops in front of a backend echoing its
+    // request head
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend = listener.local_addr().expect("backend address");
+    let backend_handle = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.expect("Failed to accept");
+        let mut head = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !head.ends_with(b"\r\n\r\n") {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => head.extend_from_slice(&buf[..n]),
+            }
+        }
+        let _ = stream.write_all(&head).await;
+    });
+    let proxy_address = reserve_port();
+    let (_service, proxy_handle) =
+        start_proxy(proxy_address, backend, |proxy| proxy.max_hops = Some(3)).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client already one hop in sends a request
+    let mut client = tokio::net::TcpStream::connect(proxy_address)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut forwarded = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        client
+            .write_all(b"GET / HTTP/1.1\r\nX-Lemonade-Hop: 1\r\n\r\n")
+            .await
+            .expect("Failed to write");
+        let _ = client.read_to_end(&mut forwarded).await;
+    })
+    .await
+    .expect("The backend should answer");
+
+    // Then: the backend saw this proxy counted as the second hop
+    assert_eq!(forwarded, b"GET / HTTP/1.1\r\nX-Lemonade-Hop: 2\r\n\r\n");
+
+    proxy_handle.abort();
+    backend_handle.abort();
+}
@@ -0,0 +1,177 @@
+//! Tests for client peer info plumbing
+//!
+use lemonade_load_balancer::prelude::*;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Fields of one span, formatted as strings
+type SpanFields = HashMap<String, String>;
+
+/// Fields of the spans named `handle_connection`, in creation order
+#[derive(Clone, Default)]
+struct ConnectionSpans(Arc<Mutex<Vec<SpanFields>>>);
+
+/// Collects the fields of one span as strings
+struct FieldCollector<'a>(&'a mut SpanFields);
+
+impl Visit for FieldCollector<'_> {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for ConnectionSpans {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: LayerContext<'_, S>) {
+        if attrs.metadata().name() != "handle_connection" {
+            return;
+        }
+        let mut fields = HashMap::new();
+        attrs.record(&mut FieldCollector(&mut fields));
+        self.0.lock().unwrap().push(fields);
+    }
+}
+
+/// Start an echo backend answering one connection
+async fn spawn_echo_backend() -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let address = listener.local_addr().expect("backend address");
+    tokio::spawn(async move {
+        if let Ok((mut stream, _)) = listener.accept().await {
+            let mut buf = [0u8; 4];
+            if stream.read_exact(&mut buf).await.is_ok() {
+                let _ = stream.write_all(&buf).await;
+            }
+        }
+    });
+    address
+}
+
+#[tokio::test]
+async fn peer_info_accepted_should_succeed() {
+    // Given: a connection accepted from a client
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind listener");
+    let listen_address = listener.local_addr().unwrap();
+    let client = tokio::net::TcpStream::connect(listen_address)
+        .await
+        .unwrap();
+    let (stream, remote) = listener.accept().await.unwrap();
+
+    // When: building its peer info outside and inside transparent mode
+    let plain = PeerInfo::accepted(&stream, remote, false);
+    let transparent = PeerInfo::accepted(&stream, remote, true);
+
+    // Then: the client address is kept and no destination is read by default
+    assert_eq!(plain, PeerInfo::new(client.local_addr().unwrap()));
+    assert!(!plain.via_proxy_protocol);
+
+    // And: a connection that was not redirected has no other destination
+    // than the listener itself
+    assert_eq!(transparent.remote, remote);
+    assert!(
+        transparent.original_dst.is_none()
+            || transparent.original_dst == Some(listen_address),
+        "{:?}",
+        transparent
+    );
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_propagates_peer_info_should_succeed() {
+    // Given: a proxy in front of an echo backend, with its spans recorded
+    let spans = ConnectionSpans::default();
+    let _guard = tracing::subscriber::set_default(
+        tracing_subscriber::registry().with(spans.clone()),
+    );
+    let backend_address = spawn_echo_backend().await;
+    let proxy_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("echo"),
+            backend_address,
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = proxy_address;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let mut connection_rx = ctx.channels().connection_rx().unwrap();
+    let mut metrics_rx = ctx.channels().metrics_rx().unwrap();
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client exchanges a message through the proxy and hangs up
+    let mut client = tokio::net::TcpStream::connect(proxy_address)
+        .await
+        .expect("Failed to connect to proxy");
+    let expected = PeerInfo::new(client.local_addr().unwrap());
+    client.write_all(b"ping").await.unwrap();
+    let mut reply = [0u8; 4];
+    client.read_exact(&mut reply).await.unwrap();
+    drop(client);
+
+    // Then: the connection events carry the client's peer info
+    let opened = tokio::time::timeout(Duration::from_secs(2), connection_rx.recv())
+        .await
+        .expect("Opened event should be sent");
+    assert!(
+        matches!(opened, Some(ConnectionEvent::Opened { peer, .. }) if peer == expected),
+        "{:?}",
+        opened
+    );
+    let closed = tokio::time::timeout(Duration::from_secs(2), connection_rx.recv())
+        .await
+        .expect("Closed event should be sent");
+    assert!(
+        matches!(closed, Some(ConnectionEvent::Closed { peer, .. }) if peer == expected),
+        "{:?}",
+        closed
+    );
+
+    // And: so does the connection closed metrics event
+    let metrics_peer = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match metrics_rx.recv().await {
+                Some(MetricsEvent::ConnectionClosed { peer, .. }) => break Some(peer),
+                Some(_) => continue,
+                None => break None,
+            }
+        }
+    })
+    .await
+    .expect("ConnectionClosed event should be sent");
+    assert_eq!(metrics_peer, Some(expected));
+
+    // And: the connection span records the client fields
+    let spans = spans.0.lock().unwrap().clone();
+    assert_eq!(spans.len(), 1, "{:?}", spans);
+    assert_eq!(spans[0]["client.addr"], expected.remote.to_string());
+    assert_eq!(spans[0]["client.original_dst"], "None");
+    assert_eq!(spans[0]["client.via_proxy_protocol"], "false");
+
+    proxy_handle.abort();
+}
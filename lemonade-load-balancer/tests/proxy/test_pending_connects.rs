@@ -0,0 +1,149 @@
+//! Tests for the per-backend cap on connects in flight
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Connects in flight the slow backend may have
+const CAP: usize = 4;
+
+/// Clients held open through the proxy
+const CLIENTS: usize = 40;
+
+/// Time between two accepts of the slow backend
+const SLOW_ACCEPT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Backend with a one-slot accept queue, accepting one connection every
+/// [`SLOW_ACCEPT_INTERVAL`]; connects past the queue hang until it accepts
+fn slow_backend() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let socket = tokio::net::TcpSocket::new_v4().expect("Failed to create socket");
+    socket
+        .bind("127.0.0.1:0".parse().unwrap())
+        .expect("Failed to bind slow backend");
+    let listener = socket.listen(1).expect("Failed to listen");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        let mut accepted = Vec::new();
+        loop {
+            tokio::time::sleep(SLOW_ACCEPT_INTERVAL).await;
+            match listener.accept().await {
+                Ok((stream, _)) => accepted.push(stream),
+                Err(_) => return,
+            }
+        }
+    });
+    (addr, handle)
+}
+
+/// Backend accepting every connection at once and holding it open
+async fn fast_backend() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind fast backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        let mut accepted = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            accepted.push(stream);
+        }
+    });
+    (addr, handle)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn pending_connects_plateau_at_cap_while_others_absorb_overflow_should_succeed() {
+    // Given: a round robin proxy over a backend slow to accept and a fast
+    // one, with at most CAP connects in flight per backend
+    let (slow_addr, slow_handle) = slow_backend();
+    let (fast_addr, fast_handle) = fast_backend().await;
+    let mut config = create_test_config_fast(
+        vec![
+            BackendMeta::new(0u8, Some("slow"), slow_addr, Some(1u8)),
+            BackendMeta::new(1u8, Some("fast"), fast_addr, Some(1u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    config.proxy.max_connections = None;
+    config.proxy.max_pending_connects = CAP;
+    let proxy = config.proxy.listen_address;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service =
+        TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+            .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    while !ctx.readiness().is_accepting() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    let routing = ctx.routing_table();
+    let slow = routing.get(0).unwrap();
+    let fast = routing.get(1).unwrap();
+
+    // Given: the slow backend's connects in flight sampled throughout
+    let stop = Arc::new(AtomicBool::new(false));
+    let max_pending = Arc::new(AtomicUsize::new(0));
+    let sampler = tokio::spawn({
+        let slow = slow.clone();
+        let stop = stop.clone();
+        let max_pending = max_pending.clone();
+        async move {
+            while !stop.load(Ordering::Acquire) {
+                max_pending.fetch_max(slow.pending_connects(), Ordering::AcqRel);
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        }
+    });
+
+    // When: clients connect one after the other and stay connected
+    let mut streams = Vec::new();
+    for routed in 1..=CLIENTS {
+        streams.push(
+            tokio::net::TcpStream::connect(proxy)
+                .await
+                .expect("Failed to connect to proxy"),
+        );
+
+        // Then: each client is routed to a backend, none rejected
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while slow.active_connections() + fast.active_connections() < routed {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("Client should be routed");
+    }
+    stop.store(true, Ordering::Release);
+    sampler.await.expect("Sampler panicked");
+
+    // Then: the slow backend's connects in flight reached the cap but never
+    // went past it
+    assert_eq!(max_pending.load(Ordering::Acquire), CAP);
+    assert!(slow.pending_connects() <= CAP);
+
+    // Then: the fast backend took the overflow, well past its round robin share
+    assert!(
+        fast.active_connections() > CLIENTS / 2,
+        "fast {} slow {}",
+        fast.active_connections(),
+        slow.active_connections()
+    );
+
+    // Then: the gauge is in the group state
+    let state = GroupState::capture(&ctx);
+    assert!((1..=CAP).contains(&state.backends[0].pending_connects));
+    assert_eq!(state.backends[1].pending_connects, 0);
+
+    drop(streams);
+    proxy_handle.abort();
+    slow_handle.abort();
+    fast_handle.abort();
+}
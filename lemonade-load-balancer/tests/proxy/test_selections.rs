@@ -0,0 +1,212 @@
+//! Tests for per-backend selection counts
+//!
+use lemonade_load_balancer::prelude::*;
+use lemonade_load_balancer::strategy;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Connections made through the proxy
+const CONNECTIONS: u64 = 300;
+
+/// Backend replying to every connection, then closing it
+async fn reply_backend() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let _ = tokio::io::AsyncWriteExt::write_all(&mut stream, b"ok").await;
+            });
+        }
+    });
+    (addr, handle)
+}
+
+/// Picks the healthy backend with the lowest id, even one at its cap on
+/// connects in flight (as a pick racing the cap does)
+struct LowestIdStrategy;
+
+#[async_trait]
+impl StrategyService for LowestIdStrategy {
+    fn strategy(&self) -> Strategy {
+        Strategy::Custom("lowest_id_selections".to_string())
+    }
+
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        ctx.routing_table()
+            .healthy_backends()
+            .into_iter()
+            .min_by_key(|backend| backend.id())
+            .ok_or(StrategyError::NoBackendAvailable)
+    }
+}
+
+/// Builds [`LowestIdStrategy`]
+struct LowestIdFactory;
+
+impl StrategyFactory for LowestIdFactory {
+    fn build(
+        &self,
+        _params: serde_json::Value,
+    ) -> Result<Arc<dyn StrategyService>, StrategyError> {
+        Ok(Arc::new(LowestIdStrategy))
+    }
+}
+
+/// Reply to one connection through `proxy`, empty when it was rejected
+async fn reply(proxy: SocketAddr) -> Vec<u8> {
+    let mut stream = tokio::net::TcpStream::connect(proxy)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut reply = Vec::new();
+    let _ = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut reply))
+        .await
+        .expect("Reply should end");
+    reply
+}
+
+/// Backend `id` named `name` at `address`
+fn backend(id: BackendId, name: &str, address: SocketAddr) -> BackendMeta {
+    BackendMeta::new(id, Some(name), address, Some(1u8))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn selection_counts_even_under_round_robin_should_succeed() {
+    // Given: a round robin proxy in front of three backends
+    let mut backends = Vec::new();
+    let mut handles = Vec::new();
+    for _ in 0..3 {
+        let (address, handle) = reply_backend().await;
+        backends.push(address);
+        handles.push(handle);
+    }
+    let mut config = create_test_config_fast(
+        (0..3)
+            .map(|id| backend(id, "reply", backends[id as usize]))
+            .collect(),
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let proxy = config.proxy.listen_address;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service =
+        TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+            .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    while !ctx.readiness().is_accepting() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    // When: a few hundred clients connect one after another
+    for _ in 0..CONNECTIONS {
+        let mut stream = tokio::net::TcpStream::connect(proxy)
+            .await
+            .expect("Failed to connect to proxy");
+        let mut reply = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut reply))
+            .await
+            .expect("Reply should end")
+            .expect("Failed to read reply");
+        assert_eq!(reply, b"ok");
+    }
+
+    // Then: each backend was picked as often as the others
+    let per_backend = CONNECTIONS / 3;
+    assert_eq!(
+        ctx.selection_counts(),
+        HashMap::from([(0, per_backend), (1, per_backend), (2, per_backend)])
+    );
+
+    // When: a migration removes backend 1 and replaces backend 2
+    config.backends = vec![
+        backend(0, "reply", backends[0]).into(),
+        backend(2, "replaced", backends[2]).into(),
+    ];
+    ctx.migrate(config).await.expect("Migration failed");
+
+    // Then: the unchanged backend keeps its count, the replacement starts over
+    assert_eq!(
+        ctx.selection_counts(),
+        HashMap::from([(0, per_backend), (2, 0)])
+    );
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_secs(2), proxy_handle).await;
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn selection_not_counted_at_connect_cap_should_succeed() {
+    // Given: a proxy whose strategy keeps picking backend 0, allowed one
+    // connect in flight per backend, with backend 0's slot taken
+    strategy::register("lowest_id_selections", Arc::new(LowestIdFactory))
+        .expect("Registration should succeed");
+    let mut addresses = Vec::new();
+    let mut handles = Vec::new();
+    for _ in 0..2 {
+        let (address, handle) = reply_backend().await;
+        addresses.push(address);
+        handles.push(handle);
+    }
+    let mut config = create_test_config_fast(
+        vec![
+            backend(0, "reply", addresses[0]),
+            backend(1, "reply", addresses[1]),
+        ],
+        Strategy::Custom("lowest_id_selections".to_string()),
+    );
+    config.proxy.listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    config.proxy.max_pending_connects = 1;
+    let proxy = config.proxy.listen_address;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service =
+        TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+            .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    while !ctx.readiness().is_accepting() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    let capped = ctx.routing_table().get(0).expect("backend 0");
+    assert!(capped.try_reserve_connect(1));
+
+    // When: a client connects
+    // Then: every pick lands on the capped backend, so it is rejected, and
+    // none of the picks counts
+    assert!(reply(proxy).await.is_empty());
+    assert_eq!(ctx.selection_counts(), HashMap::from([(0, 0), (1, 0)]));
+
+    // When: the slot is released and another client connects
+    capped.release_connect();
+    assert_eq!(reply(proxy).await, b"ok");
+
+    // Then: the pick that got the connect counts
+    assert_eq!(ctx.selection_counts(), HashMap::from([(0, 1), (1, 0)]));
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_secs(2), proxy_handle).await;
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,145 @@
+//! Tests for shutting down the proxy while clients connect
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Reply every backend connection gets before the backend closes it
+const REPLY: [u8; 16 * 1024] = [b'x'; 16 * 1024];
+
+/// Shutdowns raced against connecting clients
+const ROUNDS: usize = 200;
+
+/// Clients connecting in a tight loop during each round
+const CLIENTS: usize = 4;
+
+/// What a client saw on one connection through the proxy
+enum Outcome {
+    /// The whole reply, then a clean close
+    Served,
+    /// Closed or reset before any byte of the reply
+    Closed,
+    /// Part of the reply, then a close or reset
+    Truncated(usize),
+}
+
+/// Backend writing [`REPLY`] to every connection, then closing it
+async fn reply_backend() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let _ = tokio::io::AsyncWriteExt::write_all(&mut stream, &REPLY).await;
+            });
+        }
+    });
+    (addr, handle)
+}
+
+/// Read one connection to its end
+async fn read_reply(mut stream: tokio::net::TcpStream) -> Outcome {
+    let mut buf = [0u8; 4096];
+    let mut read = 0;
+    loop {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) if read == 0 => return Outcome::Closed,
+            Ok(0) if read == REPLY.len() => return Outcome::Served,
+            Ok(0) | Err(_) => return Outcome::Truncated(read),
+            Ok(n) => read += n,
+        }
+    }
+}
+
+/// Connect to `proxy` until it refuses or `stop` is set, failing on any
+/// truncated reply, and on any connection established before `shutdown`
+/// that was not served
+async fn connect_loop(
+    proxy: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+) {
+    while !stop.load(Ordering::Acquire) {
+        let Ok(stream) = tokio::net::TcpStream::connect(proxy).await else {
+            return;
+        };
+        let before_shutdown = !shutdown.load(Ordering::Acquire);
+        match tokio::time::timeout(Duration::from_secs(5), read_reply(stream))
+            .await
+            .expect("Reply should end")
+        {
+            Outcome::Served => {}
+            Outcome::Closed => assert!(
+                !before_shutdown,
+                "connection established before shutdown was closed unserved"
+            ),
+            Outcome::Truncated(read) => {
+                panic!("reply truncated after {} of {} bytes", read, REPLY.len())
+            }
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn shutdown_during_connect_storm_serves_every_accepted_client_should_succeed() {
+    // Given: a backend replying to every connection
+    let (backend, backend_handle) = reply_backend().await;
+
+    for round in 0..ROUNDS {
+        // Given: a proxy in front of it, with clients connecting in a tight loop
+        let mut config = create_test_config_fast(
+            vec![BackendMeta::new(0u8, Some("reply"), backend, Some(1u8))],
+            Strategy::RoundRobin,
+        );
+        config.proxy.listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("Failed to reserve proxy port");
+        config.proxy.max_connections = None;
+        let proxy = config.proxy.listen_address;
+        let ctx =
+            Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+        let service =
+            TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+                .expect("Failed to create service");
+        let proxy_handle = tokio::spawn({
+            let ctx = ctx.clone();
+            async move { service.accept_connections(ctx).await }
+        });
+        while !ctx.readiness().is_accepting() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let stop = Arc::new(AtomicBool::new(false));
+        let clients: Vec<_> = (0..CLIENTS)
+            .map(|_| tokio::spawn(connect_loop(proxy, shutdown.clone(), stop.clone())))
+            .collect();
+
+        // When: the proxy shuts down while they connect
+        tokio::time::sleep(Duration::from_micros(100 * (round % 20) as u64)).await;
+        shutdown.store(true, Ordering::Release);
+        let _ = ctx.channels().shutdown_tx().send(());
+
+        // Then: it drains and exits, and every client saw a whole reply or a
+        // close before any byte, never a cut-off one
+        tokio::time::timeout(Duration::from_secs(5), proxy_handle)
+            .await
+            .expect("Proxy should drain and exit")
+            .expect("Proxy task panicked")
+            .expect("Proxy failed");
+        stop.store(true, Ordering::Release);
+        for client in clients {
+            client
+                .await
+                .expect("Client saw a truncated or unserved reply");
+        }
+    }
+
+    backend_handle.abort();
+}
@@ -0,0 +1,426 @@
+//! Tests for SlowLog
+//!
+use lemonade_load_balancer::prelude::*;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Log output captured by a test subscriber
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    /// Install a subscriber writing into the capture on this thread
+    fn install(&self) -> tracing::subscriber::DefaultGuard {
+        tracing_subscriber::fmt()
+            .with_writer(self.clone())
+            .with_ansi(false)
+            .with_max_level(tracing::Level::WARN)
+            .finish()
+            .set_default()
+    }
+
+    /// Captured lines containing `needle`
+    fn lines_with(&self, needle: &str) -> Vec<String> {
+        let bytes = self.0.lock().unwrap().clone();
+        String::from_utf8_lossy(&bytes)
+            .lines()
+            .filter(|line| line.contains(needle))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Proxy config with the given slow thresholds and rate limit
+fn slow_config(
+    connect_ms: Option<u64>,
+    connection_secs: Option<u64>,
+    max_per_minute: u32,
+) -> ProxyConfig {
+    let mut config = create_test_config_fast(vec![], Strategy::RoundRobin).proxy;
+    config.slow_connect_warn_ms = connect_ms;
+    config.slow_connection_warn_secs = connection_secs;
+    config.slow_log_max_per_minute = max_per_minute;
+    config
+}
+
+fn backend(id: u8) -> Backend {
+    Backend::new(BackendConfig::from(create_test_backend(
+        id,
+        Some(format!("backend-{}", id)),
+        Some(10u8),
+    )))
+}
+
+fn entry(backend: &Backend, elapsed: Duration) -> SlowLogEntry<'_> {
+    SlowLogEntry {
+        connection_id: 42,
+        backend,
+        client: Some("127.0.0.1:50000".parse().unwrap()),
+        elapsed,
+    }
+}
+
+#[test]
+fn slow_log_connect_over_threshold_should_warn() {
+    // Given: a 100ms slow connect threshold
+    let logs = CapturedLogs::default();
+    let _guard = logs.install();
+    let config = slow_config(Some(100), None, 10);
+    let slow_log = SlowLog::default();
+    let backend = backend(0);
+
+    // When: one connect is under and one over the threshold
+    let fast =
+        slow_log.check_connect(&config, 0, entry(&backend, Duration::from_millis(20)));
+    let slow =
+        slow_log.check_connect(&config, 0, entry(&backend, Duration::from_millis(250)));
+
+    // Then: only the slow connect warns, with the connection details
+    assert!(!fast);
+    assert!(slow);
+    let lines = logs.lines_with("Slow backend connect");
+    assert_eq!(lines.len(), 1);
+    for field in [
+        "connection.id=42",
+        "backend.id=0",
+        "backend.name=\"backend-0\"",
+        "client.addr=Some(127.0.0.1:50000)",
+        "duration_ms=250",
+    ] {
+        assert!(
+            lines[0].contains(field),
+            "missing {} in {}",
+            field,
+            lines[0]
+        );
+    }
+}
+
+#[test]
+fn slow_log_disabled_thresholds_should_not_warn() {
+    let config = slow_config(None, None, 10);
+    let slow_log = SlowLog::default();
+    let backend = backend(0);
+
+    assert!(!slow_log.check_connect(
+        &config,
+        0,
+        entry(&backend, Duration::from_secs(60))
+    ));
+    assert!(!slow_log.check_connection(
+        &config,
+        0,
+        entry(&backend, Duration::from_secs(3600))
+    ));
+}
+
+#[test]
+fn slow_log_connection_over_threshold_should_warn() {
+    let logs = CapturedLogs::default();
+    let _guard = logs.install();
+    let config = slow_config(None, Some(30), 10);
+    let slow_log = SlowLog::default();
+    let backend = backend(3);
+
+    assert!(!slow_log.check_connection(
+        &config,
+        0,
+        entry(&backend, Duration::from_secs(5))
+    ));
+    assert!(slow_log.check_connection(
+        &config,
+        0,
+        entry(&backend, Duration::from_secs(45))
+    ));
+
+    let lines = logs.lines_with("Slow connection");
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].contains("backend.id=3"));
+    assert!(lines[0].contains("duration_ms=45000"));
+}
+
+#[test]
+fn slow_log_rate_limit_per_backend_per_minute_should_hold() {
+    // Given: at most two warnings per backend per minute on a mock clock
+    let logs = CapturedLogs::default();
+    let _guard = logs.install();
+    let clock = MockClock::new(0);
+    let config = slow_config(Some(10), None, 2);
+    let slow_log = SlowLog::default();
+    let (first, second) = (backend(0), backend(1));
+    let slow = Duration::from_millis(500);
+
+    // When: backend 0 has a storm of slow connects within one minute
+    let logged = (0..5)
+        .filter(|_| {
+            slow_log.check_connect(&config, clock.now_millis(), entry(&first, slow))
+        })
+        .count();
+
+    // Then: only two warn, and other backends have their own budget
+    assert_eq!(logged, 2);
+    assert!(slow_log.check_connect(&config, clock.now_millis(), entry(&second, slow)));
+    assert_eq!(logs.lines_with("backend.id=0").len(), 2);
+
+    // When: the next minute starts
+    clock.advance(Duration::from_secs(59));
+    assert!(!slow_log.check_connect(&config, clock.now_millis(), entry(&first, slow)));
+    clock.advance(Duration::from_secs(1));
+
+    // Then: backend 0 warns again, reporting the warnings dropped meanwhile
+    assert!(slow_log.check_connect(&config, clock.now_millis(), entry(&first, slow)));
+    let lines = logs.lines_with("backend.id=0");
+    assert_eq!(lines.len(), 3);
+    assert!(lines[2].contains("suppressed=4"), "{}", lines[2]);
+}
+
+#[tokio::test]
+async fn proxy_slow_connect_should_warn() {
+    // Given: a proxy treating every backend connect as slow
+    let logs = CapturedLogs::default();
+    let _guard = logs.install();
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let backend_handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = backend_listener.accept().await {
+            let _ = stream.write_all(b"pong").await;
+        }
+    });
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("slow"),
+            BackendAddress::from(backend_addr),
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = proxy_addr;
+    config.proxy.slow_connect_warn_ms = Some(0);
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client is proxied to the backend
+    let mut client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut reply = [0u8; 4];
+    tokio::time::timeout(Duration::from_secs(2), client.read_exact(&mut reply))
+        .await
+        .expect("Backend reply should arrive")
+        .expect("Failed to read reply");
+
+    // Then: the slow connect is logged with the backend and client
+    let client_addr = client.local_addr().expect("client address").to_string();
+    let lines = logs.lines_with("Slow backend connect");
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].contains("backend.name=\"slow\""), "{}", lines[0]);
+    assert!(lines[0].contains(&client_addr), "{}", lines[0]);
+
+    proxy_handle.abort();
+    backend_handle.abort();
+}
+
+#[test]
+fn slow_log_pick_over_threshold_should_warn() {
+    // Given: a 500µs pick threshold, at most one warning per minute
+    let logs = CapturedLogs::default();
+    let _guard = logs.install();
+    let mut config = slow_config(None, None, 1);
+    config.strategy_pick_warn_micros = Some(500);
+    let slow_log = SlowLog::default();
+
+    // When: picks under and over the threshold, then a second slow pick
+    let fast = slow_log.check_pick(&config, 0, "adaptive", Duration::from_micros(100));
+    let slow = slow_log.check_pick(&config, 0, "adaptive", Duration::from_millis(2));
+    let limited = slow_log.check_pick(&config, 0, "adaptive", Duration::from_millis(3));
+
+    // Then: only the first slow pick warns, other strategies have their own budget
+    assert!(!fast);
+    assert!(slow);
+    assert!(!limited);
+    assert!(slow_log.check_pick(&config, 0, "round_robin", Duration::from_millis(1)));
+    let lines = logs.lines_with("Slow strategy pick");
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("strategy=\"adaptive\""), "{}", lines[0]);
+    assert!(lines[0].contains("duration_micros=2000"), "{}", lines[0]);
+    assert!(lines[0].contains("threshold_micros=500"), "{}", lines[0]);
+}
+
+/// Picks the first healthy backend after sleeping
+struct SlowStrategy;
+
+#[async_trait]
+impl StrategyService for SlowStrategy {
+    fn strategy(&self) -> Strategy {
+        Strategy::Custom("slow_pick_test".to_string())
+    }
+
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        ctx.routing_table()
+            .healthy_backends()
+            .into_iter()
+            .next()
+            .ok_or(StrategyError::NoBackendAvailable)
+    }
+}
+
+/// Builds [`SlowStrategy`]
+struct SlowStrategyFactory;
+
+impl StrategyFactory for SlowStrategyFactory {
+    fn build(
+        &self,
+        _params: serde_json::Value,
+    ) -> Result<Arc<dyn StrategyService>, StrategyError> {
+        Ok(Arc::new(SlowStrategy))
+    }
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_slow_strategy_pick_should_be_timed_and_warn() {
+    // Given: a proxy whose strategy takes 5ms per pick, warning past 1ms
+    let logs = CapturedLogs::default();
+    let _guard = logs.install();
+    lemonade_load_balancer::strategy::register(
+        "slow_pick_test",
+        Arc::new(SlowStrategyFactory),
+    )
+    .expect("Registration should succeed");
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::Custom("slow_pick_test".to_string()),
+    );
+    config.backends[0].address = BackendAddress::from(backend_addr);
+    config.proxy.listen_address = proxy_addr;
+    config.proxy.strategy_pick_warn_micros = Some(1_000);
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client connects through the proxy
+    let _client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let _accepted =
+        tokio::time::timeout(Duration::from_secs(2), backend_listener.accept())
+            .await
+            .expect("Backend should be reached")
+            .expect("Backend accept");
+
+    // Then: the pick was timed under the strategy's name and warned about
+    let picks = ctx
+        .pick_timings()
+        .get("slow_pick_test")
+        .expect("Pick should be timed");
+    assert_eq!(picks.count, 1);
+    assert!(picks.sum_micros >= 5_000, "{:?}", picks);
+    let lines = logs.lines_with("Slow strategy pick");
+    assert_eq!(lines.len(), 1);
+    assert!(
+        lines[0].contains("strategy=\"slow_pick_test\""),
+        "{}",
+        lines[0]
+    );
+
+    proxy_handle.abort();
+}
+
+#[tokio::test]
+async fn slow_log_backend_churn_keeps_windows_bounded_should_succeed() {
+    // Given: a context routing to four backends and a slow log warning on every connect
+    const LIVE: usize = 4;
+    const ROUNDS: usize = 250;
+    let config = slow_config(Some(1), Some(1), 10);
+    let ids = |round: usize| {
+        (0..LIVE)
+            .map(|k| ((round * LIVE + k) % 256) as u8)
+            .collect::<Vec<_>>()
+    };
+    let metas = |round: usize| {
+        ids(round)
+            .into_iter()
+            .map(|id| create_test_backend(id, None, Some(10u8)))
+            .collect::<Vec<_>>()
+    };
+    let ctx = Arc::new(
+        Context::new(create_test_config_fast(metas(0), Strategy::RoundRobin))
+            .expect("Failed to create context"),
+    );
+    let slow_log = SlowLog::default();
+
+    // When: 1,000 backend ids cycle through migrations, each leaving slow log state
+    for round in 1..=ROUNDS {
+        ctx.migrate(create_test_config_fast(metas(round), Strategy::RoundRobin))
+            .await
+            .expect("Migration failed");
+        for backend in ctx.routing_table().all_backends() {
+            slow_log.check_connect(&config, 0, entry(&backend, Duration::from_secs(1)));
+            slow_log.check_connection(
+                &config,
+                0,
+                entry(&backend, Duration::from_secs(5)),
+            );
+        }
+        slow_log.sweep(&ctx);
+
+        // Then: windows only ever cover the live backends
+        assert!(slow_log.backend_windows() <= 2 * LIVE);
+    }
+    let live = ctx.routing_table().backend_ids();
+    assert_eq!(live, ids(ROUNDS));
+    assert_eq!(slow_log.backend_windows(), 2 * LIVE);
+    assert!(ctx.stale_entries_evicted() >= ((ROUNDS - 1) * 2 * LIVE) as u64);
+}
@@ -0,0 +1,503 @@
+//! Tests for SNI and ALPN routing
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Build a TLS record carrying a ClientHello, with an SNI extension when
+/// `server_name` is set
+fn client_hello(server_name: Option<&str>) -> Vec<u8> {
+    client_hello_with_alpn(server_name, &[])
+}
+
+/// Build a TLS record carrying a ClientHello, with an ALPN extension when
+/// `alpn` is not empty
+fn client_hello_with_alpn(server_name: Option<&str>, alpn: &[&str]) -> Vec<u8> {
+    let mut extensions = Vec::new();
+    // supported_versions (TLS 1.3), ahead of the SNI
+    extensions.extend_from_slice(&[0x00, 0x2b, 0x00, 0x03, 0x02, 0x03, 0x04]);
+    if let Some(name) = server_name {
+        let name = name.as_bytes();
+        let list_len = name.len() + 3;
+        extensions.extend_from_slice(&[0x00, 0x00]);
+        extensions.extend_from_slice(&((list_len + 2) as u16).to_be_bytes());
+        extensions.extend_from_slice(&(list_len as u16).to_be_bytes());
+        extensions.push(0x00);
+        extensions.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        extensions.extend_from_slice(name);
+    }
+    if !alpn.is_empty() {
+        let list: Vec<u8> = alpn
+            .iter()
+            .flat_map(|protocol| {
+                std::iter::once(protocol.len() as u8).chain(protocol.bytes())
+            })
+            .collect();
+        extensions.extend_from_slice(&[0x00, 0x10]);
+        extensions.extend_from_slice(&((list.len() + 2) as u16).to_be_bytes());
+        extensions.extend_from_slice(&(list.len() as u16).to_be_bytes());
+        extensions.extend_from_slice(&list);
+    }
+
+    let mut hello = vec![0x03, 0x03];
+    hello.extend_from_slice(&[0x42; 32]); // random
+    hello.push(32); // session id
+    hello.extend_from_slice(&[0x07; 32]);
+    hello.extend_from_slice(&[0x00, 0x02, 0x13, 0x01]); // cipher suites
+    hello.extend_from_slice(&[0x01, 0x00]); // compression methods
+    hello.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+    hello.extend_from_slice(&extensions);
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(hello.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&hello);
+
+    let mut record = vec![0x16, 0x03, 0x01];
+    record.extend_from_slice(&(handshake.len() as u16).to_be_bytes());
+    record.extend_from_slice(&handshake);
+    record
+}
+
+/// Split the handshake of a single-record ClientHello over two records
+fn split_into_two_records(record: &[u8], at: usize) -> Vec<u8> {
+    let handshake = &record[5..];
+    let mut out = Vec::new();
+    for part in [&handshake[..at], &handshake[at..]] {
+        out.extend_from_slice(&[0x16, 0x03, 0x01]);
+        out.extend_from_slice(&(part.len() as u16).to_be_bytes());
+        out.extend_from_slice(part);
+    }
+    out
+}
+
+#[test]
+fn parse_client_hello_sni_two_hostnames_should_succeed() {
+    assert_eq!(
+        parse_client_hello_sni(&client_hello(Some("api.example.com"))),
+        ClientHelloSni::Found("api.example.com".to_string())
+    );
+    assert_eq!(
+        parse_client_hello_sni(&client_hello(Some("WWW.Example.ORG."))),
+        ClientHelloSni::Found("www.example.org".to_string())
+    );
+}
+
+#[test]
+fn parse_client_hello_sni_fragmented_records_should_succeed() {
+    // Given: a ClientHello whose handshake spans two records
+    let record = split_into_two_records(&client_hello(Some("api.example.com")), 3);
+
+    // When/Then: every prefix is incomplete and the whole yields the name
+    for end in 0..record.len() {
+        assert_eq!(
+            parse_client_hello_sni(&record[..end]),
+            ClientHelloSni::Incomplete
+        );
+    }
+    assert_eq!(
+        parse_client_hello_sni(&record),
+        ClientHelloSni::Found("api.example.com".to_string())
+    );
+}
+
+#[test]
+fn parse_client_hello_sni_without_extension_should_be_missing() {
+    assert_eq!(
+        parse_client_hello_sni(&client_hello(None)),
+        ClientHelloSni::Missing
+    );
+}
+
+#[test]
+fn parse_client_hello_sni_non_tls_should_fail() {
+    assert_eq!(
+        parse_client_hello_sni(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n"),
+        ClientHelloSni::NotTls
+    );
+    // Handshake record that is not a ClientHello
+    assert_eq!(
+        parse_client_hello_sni(&[0x16, 0x03, 0x03, 0x00, 0x04, 0x02, 0x00, 0x00, 0x00]),
+        ClientHelloSni::NotTls
+    );
+}
+
+#[test]
+fn parse_client_hello_sni_malformed_should_not_panic() {
+    // Given: a valid ClientHello
+    let valid = client_hello(Some("api.example.com"));
+
+    // When: corrupting each byte in turn
+    for i in 0..valid.len() {
+        for value in [0x00, 0x7f, 0xff] {
+            let mut corrupt = valid.clone();
+            corrupt[i] = value;
+
+            // Then: parsing never panics
+            let _ = parse_client_hello_sni(&corrupt);
+        }
+    }
+
+    // And: an SNI length running past the extension is rejected
+    let mut bad_name_len = valid.clone();
+    let len_at = bad_name_len.len() - "api.example.com".len() - 2;
+    bad_name_len[len_at..len_at + 2].copy_from_slice(&0x0fffu16.to_be_bytes());
+    assert_eq!(
+        parse_client_hello_sni(&bad_name_len),
+        ClientHelloSni::Missing
+    );
+}
+
+#[test]
+fn sni_routing_config_selector_for_should_succeed() {
+    // Given: a generic and a more specific route plus a fallback
+    let config = SniRoutingConfig {
+        enabled: true,
+        routes: [
+            (
+                "example.com".to_string(),
+                LabelSelector::single("group", "web"),
+            ),
+            (
+                "*.api.example.com".to_string(),
+                LabelSelector::single("group", "api"),
+            ),
+        ]
+        .into(),
+        fallback: LabelSelector::single("group", "default"),
+        ..SniRoutingConfig::default()
+    };
+
+    // When/Then: the longest matching suffix wins, on label boundaries only
+    let group = |name| config.selector_for(name).to_string();
+    assert_eq!(group(Some("v1.api.example.com")), "{group=api}");
+    assert_eq!(group(Some("api.example.com")), "{group=api}");
+    assert_eq!(group(Some("www.Example.com")), "{group=web}");
+    assert_eq!(group(Some("example.com")), "{group=web}");
+    assert_eq!(group(Some("badexample.com")), "{group=default}");
+    assert_eq!(group(None), "{group=default}");
+}
+
+#[test]
+fn parse_client_hello_alpn_lists_should_succeed() {
+    // Given: ClientHellos offering different ALPN lists
+    let parsed = |server_name, alpn: &[&str]| match parse_client_hello(
+        &client_hello_with_alpn(server_name, alpn),
+    ) {
+        ClientHelloParse::Parsed(info) => info,
+        other => panic!("ClientHello should parse, got {:?}", other),
+    };
+
+    // When/Then: the protocols are kept in the client's order, next to the SNI
+    let info = parsed(Some("api.example.com"), &["h2", "http/1.1"]);
+    assert_eq!(info.server_name.as_deref(), Some("api.example.com"));
+    assert_eq!(info.alpn, ["h2", "http/1.1"]);
+    assert_eq!(parsed(None, &["http/1.1"]).alpn, ["http/1.1"]);
+    assert!(parsed(Some("api.example.com"), &[]).alpn.is_empty());
+}
+
+#[test]
+fn parse_client_hello_malformed_alpn_should_keep_sni() {
+    // Given: an ALPN protocol length running past its list
+    let mut hello = client_hello_with_alpn(Some("api.example.com"), &["h2"]);
+    let len_at = hello.len() - 3;
+    hello[len_at] = 0x40;
+
+    // When: parsing
+    let parsed = parse_client_hello(&hello);
+
+    // Then: the ALPN list is dropped but the SNI still routes
+    assert_eq!(
+        parsed,
+        ClientHelloParse::Parsed(ClientHelloInfo {
+            server_name: Some("api.example.com".to_string()),
+            alpn: Vec::new(),
+        })
+    );
+    assert_eq!(
+        parse_client_hello(b"GET / HTTP/1.1\r\n\r\n"),
+        ClientHelloParse::NotTls
+    );
+}
+
+/// Proxy config routing `h2` and `http/1.1` to protocol groups
+fn alpn_proxy_config() -> ProxyConfig {
+    let mut proxy = create_test_config_fast(Vec::new(), Strategy::RoundRobin).proxy;
+    proxy.route_by_alpn = AlpnRoutingConfig {
+        enabled: true,
+        routes: [
+            ("h2".to_string(), LabelSelector::single("proto", "h2")),
+            ("http/1.1".to_string(), LabelSelector::single("proto", "h1")),
+        ]
+        .into(),
+        fallback: LabelSelector::single("proto", "h1"),
+    };
+    proxy
+}
+
+/// Groups picked for a ClientHello, as strings
+fn selectors(proxy: &ProxyConfig, hello: Option<ClientHelloInfo>) -> Vec<String> {
+    proxy
+        .client_hello_selectors(hello.as_ref())
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// ClientHello routing fields
+fn hello(server_name: Option<&str>, alpn: &[&str]) -> Option<ClientHelloInfo> {
+    Some(ClientHelloInfo {
+        server_name: server_name.map(String::from),
+        alpn: alpn.iter().map(|protocol| protocol.to_string()).collect(),
+    })
+}
+
+#[test]
+fn client_hello_selectors_alpn_only_should_succeed() {
+    // Given: ALPN routing without SNI routing
+    let proxy = alpn_proxy_config();
+
+    // When/Then: the first offered protocol with a route picks the group
+    assert_eq!(
+        selectors(&proxy, hello(None, &["h2", "http/1.1"])),
+        ["{proto=h2}"]
+    );
+    assert_eq!(
+        selectors(&proxy, hello(None, &["spdy/3", "http/1.1"])),
+        ["{proto=h1}"]
+    );
+    assert_eq!(selectors(&proxy, hello(None, &["h2"])), ["{proto=h2}"]);
+
+    // And: no routed protocol, no ALPN or no parsable ClientHello falls back
+    assert_eq!(selectors(&proxy, hello(None, &["spdy/3"])), ["{proto=h1}"]);
+    assert_eq!(selectors(&proxy, hello(None, &[])), ["{proto=h1}"]);
+    assert_eq!(selectors(&proxy, None), ["{proto=h1}"]);
+}
+
+#[test]
+fn client_hello_selectors_sni_then_alpn_should_succeed() {
+    // Given: SNI routing to sites and ALPN routing to protocols
+    let mut proxy = alpn_proxy_config();
+    proxy.route_by_sni = SniRoutingConfig {
+        enabled: true,
+        routes: [
+            ("api.test".to_string(), LabelSelector::single("site", "api")),
+            (
+                "legacy.test".to_string(),
+                LabelSelector::single("proto", "h1"),
+            ),
+        ]
+        .into(),
+        fallback: LabelSelector::single("site", "web"),
+        ..SniRoutingConfig::default()
+    };
+
+    // When/Then: ALPN narrows the SNI group, which stays as the last resort
+    assert_eq!(
+        selectors(&proxy, hello(Some("api.test"), &["h2"])),
+        ["{proto=h2,site=api}", "{site=api}"]
+    );
+    assert_eq!(
+        selectors(&proxy, hello(Some("unknown.test"), &["http/1.1"])),
+        ["{proto=h1,site=web}", "{site=web}"]
+    );
+
+    // And: an ALPN group contradicting the SNI group is ignored
+    assert_eq!(
+        selectors(&proxy, hello(Some("legacy.test"), &["h2"])),
+        ["{proto=h1}"]
+    );
+
+    // And: without a ClientHello both fall back
+    assert_eq!(
+        selectors(&proxy, None),
+        ["{proto=h1,site=web}", "{site=web}"]
+    );
+}
+
+#[test]
+fn alpn_routing_config_invalid_protocol_should_fail() {
+    // Given: an empty protocol name
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.proxy = alpn_proxy_config();
+    config
+        .proxy
+        .route_by_alpn
+        .routes
+        .insert(String::new(), LabelSelector::default());
+
+    // When/Then: validation rejects it
+    assert!(config.validate().is_err());
+}
+
+/// Backend that writes its name, then echoes what it receives
+async fn named_echo_backend(
+    name: &'static str,
+) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let _ = stream.write_all(name.as_bytes()).await;
+                let (mut read, mut write) = stream.split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+        }
+    });
+    (addr, handle)
+}
+
+/// Backend config carrying a `group` label
+fn labelled_backend(id: u8, name: &str, addr: SocketAddr, group: &str) -> BackendConfig {
+    let mut backend = BackendConfig::from(BackendMeta::new(
+        id,
+        Some(name),
+        BackendAddress::from(addr),
+        Some(1u8),
+    ));
+    backend.labels = Labels::from([("group".to_string(), group.to_string())]);
+    backend
+}
+
+/// Send `payload` through the proxy and return the backend name and echo
+async fn send_through(proxy_addr: SocketAddr, payload: &[u8]) -> (String, Vec<u8>) {
+    let mut client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut name = [0u8; 5];
+    let mut echoed = vec![0u8; payload.len()];
+    tokio::time::timeout(Duration::from_secs(2), async {
+        client.write_all(payload).await.expect("Failed to write");
+        client
+            .read_exact(&mut name)
+            .await
+            .expect("Failed to read name");
+        client
+            .read_exact(&mut echoed)
+            .await
+            .expect("Failed to read echo");
+    })
+    .await
+    .expect("Backend reply should arrive");
+    (String::from_utf8_lossy(&name).into_owned(), echoed)
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_routes_by_sni_should_succeed() {
+    // Given: an "alpha" and a "bravo" backend in different label groups
+    let (alpha_addr, alpha_handle) = named_echo_backend("alpha").await;
+    let (bravo_addr, bravo_handle) = named_echo_backend("bravo").await;
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.backends = vec![
+        labelled_backend(0, "alpha", alpha_addr, "a"),
+        labelled_backend(1, "bravo", bravo_addr, "b"),
+    ];
+    config.proxy.listen_address = proxy_addr;
+
+    // And: SNI routes for two host names, falling back to group "a"
+    config.proxy.route_by_sni = SniRoutingConfig {
+        enabled: true,
+        routes: [
+            (
+                "alpha.test".to_string(),
+                LabelSelector::single("group", "a"),
+            ),
+            (
+                "bravo.test".to_string(),
+                LabelSelector::single("group", "b"),
+            ),
+        ]
+        .into(),
+        fallback: LabelSelector::single("group", "a"),
+        peek_timeout_millis: 200,
+        ..SniRoutingConfig::default()
+    };
+    config.validate().expect("SNI config should validate");
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When/Then: each ClientHello reaches its group, despite round robin, and
+    // the buffered handshake bytes are replayed to the backend
+    for _ in 0..2 {
+        for (host, expected) in [("bravo.test", "bravo"), ("www.alpha.test", "alpha")] {
+            let hello = client_hello(Some(host));
+            let (backend, echoed) = send_through(proxy_addr, &hello).await;
+            assert_eq!(backend, expected, "SNI {} routed to {}", host, backend);
+            assert_eq!(echoed, hello);
+        }
+    }
+
+    // And: non-TLS traffic goes to the fallback group
+    let (backend, echoed) = send_through(proxy_addr, b"PING\r\n").await;
+    assert_eq!(backend, "alpha");
+    assert_eq!(echoed, b"PING\r\n");
+
+    proxy_handle.abort();
+    alpha_handle.abort();
+    bravo_handle.abort();
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_routes_by_alpn_should_succeed() {
+    // Given: an h2 and an http/1.1 backend routed by ALPN only
+    let (h2_addr, h2_handle) = named_echo_backend("h2bkd").await;
+    let (h1_addr, h1_handle) = named_echo_backend("h1bkd").await;
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    let mut h2 = labelled_backend(0, "h2", h2_addr, "x");
+    h2.labels = Labels::from([("proto".to_string(), "h2".to_string())]);
+    let mut h1 = labelled_backend(1, "h1", h1_addr, "x");
+    h1.labels = Labels::from([("proto".to_string(), "h1".to_string())]);
+    config.backends = vec![h2, h1];
+    config.proxy = ProxyConfig {
+        listen_address: proxy_addr,
+        ..alpn_proxy_config()
+    };
+    config.validate().expect("ALPN config should validate");
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When/Then: each ClientHello reaches its protocol group, untouched
+    for _ in 0..2 {
+        for (alpn, expected) in [
+            (&["h2", "http/1.1"][..], "h2bkd"),
+            (&["http/1.1"][..], "h1bkd"),
+        ] {
+            let hello = client_hello_with_alpn(Some("site.test"), alpn);
+            let (backend, echoed) = send_through(proxy_addr, &hello).await;
+            assert_eq!(backend, expected, "ALPN {:?} routed to {}", alpn, backend);
+            assert_eq!(echoed, hello);
+        }
+    }
+
+    // And: non-TLS traffic goes to the fallback group
+    let (backend, _) = send_through(proxy_addr, b"PING\r\n").await;
+    assert_eq!(backend, "h1bkd");
+
+    proxy_handle.abort();
+    h2_handle.abort();
+    h1_handle.abort();
+}
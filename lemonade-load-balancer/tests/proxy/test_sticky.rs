@@ -0,0 +1,188 @@
+//! Tests for sticky sessions
+//!
+use lemonade_load_balancer::prelude::*;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Backend replying with its name to every connection
+async fn named_backend(name: &'static str) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let _ =
+                tokio::io::AsyncWriteExt::write_all(&mut stream, name.as_bytes()).await;
+        }
+    });
+    (addr, handle)
+}
+
+/// Read the name of the backend serving one connection
+pub(crate) async fn backend_name(proxy: SocketAddr) -> String {
+    let mut client = tokio::net::TcpStream::connect(proxy)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut reply = String::new();
+    tokio::time::timeout(Duration::from_secs(2), client.read_to_string(&mut reply))
+        .await
+        .expect("Backend reply should arrive")
+        .expect("Failed to read reply");
+    reply
+}
+
+/// Round robin proxy over three named backends, in the given priority tiers
+pub(crate) async fn start_proxy(
+    sticky_sessions: bool,
+    priorities: [Option<u8>; 3],
+) -> (
+    Arc<Context>,
+    SocketAddr,
+    Vec<tokio::task::JoinHandle<()>>,
+    tokio::task::JoinHandle<Result<(), ProxyError>>,
+) {
+    let mut backends = Vec::new();
+    let mut handles = Vec::new();
+    for (id, name) in ["b0", "b1", "b2"].into_iter().enumerate() {
+        let (addr, handle) = named_backend(name).await;
+        backends.push(BackendMeta::new(id as u8, Some(name), addr, Some(1u8)));
+        handles.push(handle);
+    }
+    let mut config = create_test_config_fast(backends, Strategy::RoundRobin);
+    for (backend, priority) in config.backends.iter_mut().zip(priorities) {
+        backend.priority = priority;
+    }
+    config.proxy.listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    config.proxy.sticky_sessions = sticky_sessions;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service =
+        TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+            .expect("Failed to create service");
+    let proxy = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    (ctx, config.proxy.listen_address, handles, proxy)
+}
+
+/// Id of the backend named `name`
+pub(crate) fn backend_id(name: &str) -> BackendId {
+    name.trim_start_matches('b').parse().expect("backend name")
+}
+
+#[tokio::test]
+async fn sticky_sessions_keep_client_on_backend_should_succeed() {
+    // Given: a sticky round robin proxy
+    let (ctx, proxy, handles, proxy_handle) = start_proxy(true, [None; 3]).await;
+
+    // When: the same client connects several times
+    let mut served = Vec::new();
+    for _ in 0..6 {
+        served.push(backend_name(proxy).await);
+    }
+
+    // Then: every connection reaches its first backend
+    assert!(served.iter().all(|name| *name == served[0]), "{:?}", served);
+    let now_ms = ctx.clock().now_millis();
+    assert_eq!(
+        ctx.affinity().get("127.0.0.1".parse().unwrap(), now_ms),
+        Some(backend_id(&served[0]))
+    );
+
+    proxy_handle.abort();
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn sticky_sessions_reassign_after_backend_failure_should_succeed() {
+    // Given: a client stuck to a backend
+    let (ctx, proxy, handles, proxy_handle) = start_proxy(true, [None; 3]).await;
+    let first = backend_name(proxy).await;
+    let failed = ctx
+        .routing_table()
+        .get(backend_id(&first))
+        .expect("first backend is routed");
+
+    // When: that backend fails its health check
+    failed.set_health(false, ctx.clock().now_millis());
+    let mut served = Vec::new();
+    for _ in 0..4 {
+        served.push(backend_name(proxy).await);
+    }
+
+    // Then: the client moves to another backend and sticks to it
+    assert_ne!(served[0], first);
+    assert!(served.iter().all(|name| *name == served[0]), "{:?}", served);
+
+    // When: the failed backend recovers
+    failed.set_health(true, ctx.clock().now_millis());
+
+    // Then: the client stays on its new backend
+    assert_eq!(backend_name(proxy).await, served[0]);
+
+    proxy_handle.abort();
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn sticky_sessions_disabled_spread_client_should_succeed() {
+    // Given: a round robin proxy without sticky sessions
+    let (ctx, proxy, handles, proxy_handle) = start_proxy(false, [None; 3]).await;
+
+    // When: the same client connects three times
+    let mut served = Vec::new();
+    for _ in 0..3 {
+        served.push(backend_name(proxy).await);
+    }
+
+    // Then: the strategy spreads its connections and nothing is recorded
+    served.sort();
+    assert_eq!(served, ["b0", "b1", "b2"]);
+    assert!(ctx.affinity().is_empty());
+
+    proxy_handle.abort();
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn sticky_sessions_return_to_recovered_primary_should_succeed() {
+    // Given: a sticky client of primary b0, with backups b1 and b2
+    let (ctx, proxy, handles, proxy_handle) =
+        start_proxy(true, [None, Some(1), Some(1)]).await;
+    assert_eq!(backend_name(proxy).await, "b0");
+    let primary = ctx.routing_table().get(0).expect("primary is routed");
+
+    // When: the primary fails
+    primary.set_health(false, ctx.clock().now_millis());
+
+    // Then: the client fails over to a backup and sticks to it
+    let backup = backend_name(proxy).await;
+    assert_ne!(backup, "b0");
+    assert_eq!(backend_name(proxy).await, backup);
+
+    // When: the primary recovers
+    primary.set_health(true, ctx.clock().now_millis());
+
+    // Then: the client goes back to it
+    assert_eq!(backend_name(proxy).await, "b0");
+    assert_eq!(backend_name(proxy).await, "b0");
+
+    proxy_handle.abort();
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -5,7 +5,9 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::common::fixtures::{create_test_backend, create_test_context};
+use crate::common::fixtures::{
+    create_test_backend, create_test_config_fast, create_test_context,
+};
 
 #[tokio::test]
 async fn tokio_proxy_service_new_should_succeed() {
@@ -13,6 +15,34 @@ async fn tokio_proxy_service_new_should_succeed() {
     let config = ProxyConfig {
         listen_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0), // Use 0 for auto-assign
         max_connections: Some(1000),
+        coalesce: CoalesceConfig::default(),
+        hedging: HedgingConfig::default(),
+        retry_budget: RetryBudgetConfig::default(),
+        circuit_breaker: CircuitBreakerConfig::default(),
+        route_by_sni: SniRoutingConfig::default(),
+        route_by_alpn: AlpnRoutingConfig::default(),
+        route_by_http: HttpRoutingConfig::default(),
+        slow_connect_warn_ms: None,
+        slow_connection_warn_secs: None,
+        strategy_pick_warn_micros: None,
+        initial_read_timeout_millis: None,
+        slow_log_max_per_minute: 10,
+        max_buffered_bytes: 256 * 1024,
+        backend_bind_address: None,
+        backend_dscp: None,
+        fd_headroom: 64,
+        max_backend_peer_connections: None,
+        max_hops: None,
+        transparent: false,
+        report_backend_aborts: true,
+        sticky_sessions: false,
+        sticky_ttl_millis: 300_000,
+        listen_unix: None,
+        max_pending_connects: 32,
+        local_zone: None,
+        zone_spillover_min_healthy: 1,
+        propagate_deadlines: false,
+        request_timeout_millis: 30_000,
     };
 
     // When: creating TokioProxyService
@@ -28,6 +58,34 @@ async fn tokio_proxy_service_accept_connections_binds_listener_should_succeed()
     let config = ProxyConfig {
         listen_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0),
         max_connections: Some(1000),
+        coalesce: CoalesceConfig::default(),
+        hedging: HedgingConfig::default(),
+        retry_budget: RetryBudgetConfig::default(),
+        circuit_breaker: CircuitBreakerConfig::default(),
+        route_by_sni: SniRoutingConfig::default(),
+        route_by_alpn: AlpnRoutingConfig::default(),
+        route_by_http: HttpRoutingConfig::default(),
+        slow_connect_warn_ms: None,
+        slow_connection_warn_secs: None,
+        strategy_pick_warn_micros: None,
+        initial_read_timeout_millis: None,
+        slow_log_max_per_minute: 10,
+        max_buffered_bytes: 256 * 1024,
+        backend_bind_address: None,
+        backend_dscp: None,
+        fd_headroom: 64,
+        max_backend_peer_connections: None,
+        max_hops: None,
+        transparent: false,
+        report_backend_aborts: true,
+        sticky_sessions: false,
+        sticky_ttl_millis: 300_000,
+        listen_unix: None,
+        max_pending_connects: 32,
+        local_zone: None,
+        zone_spillover_min_healthy: 1,
+        propagate_deadlines: false,
+        request_timeout_millis: 30_000,
     };
     let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config)))
         .expect("Failed to create service");
@@ -70,6 +128,34 @@ async fn tokio_proxy_service_proxies_connection_should_succeed() {
     let proxy_config = ProxyConfig {
         listen_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0),
         max_connections: Some(1000),
+        coalesce: CoalesceConfig::default(),
+        hedging: HedgingConfig::default(),
+        retry_budget: RetryBudgetConfig::default(),
+        circuit_breaker: CircuitBreakerConfig::default(),
+        route_by_sni: SniRoutingConfig::default(),
+        route_by_alpn: AlpnRoutingConfig::default(),
+        route_by_http: HttpRoutingConfig::default(),
+        slow_connect_warn_ms: None,
+        slow_connection_warn_secs: None,
+        strategy_pick_warn_micros: None,
+        initial_read_timeout_millis: None,
+        slow_log_max_per_minute: 10,
+        max_buffered_bytes: 256 * 1024,
+        backend_bind_address: None,
+        backend_dscp: None,
+        fd_headroom: 64,
+        max_backend_peer_connections: None,
+        max_hops: None,
+        transparent: false,
+        report_backend_aborts: true,
+        sticky_sessions: false,
+        sticky_ttl_millis: 300_000,
+        listen_unix: None,
+        max_pending_connects: 32,
+        local_zone: None,
+        zone_spillover_min_healthy: 1,
+        propagate_deadlines: false,
+        request_timeout_millis: 30_000,
     };
     let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(proxy_config)))
         .expect("Failed to create service");
@@ -102,6 +188,34 @@ async fn tokio_proxy_service_max_connections_limit_enforced_should_succeed() {
     let config = ProxyConfig {
         listen_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0),
         max_connections: Some(1),
+        coalesce: CoalesceConfig::default(),
+        hedging: HedgingConfig::default(),
+        retry_budget: RetryBudgetConfig::default(),
+        circuit_breaker: CircuitBreakerConfig::default(),
+        route_by_sni: SniRoutingConfig::default(),
+        route_by_alpn: AlpnRoutingConfig::default(),
+        route_by_http: HttpRoutingConfig::default(),
+        slow_connect_warn_ms: None,
+        slow_connection_warn_secs: None,
+        strategy_pick_warn_micros: None,
+        initial_read_timeout_millis: None,
+        slow_log_max_per_minute: 10,
+        max_buffered_bytes: 256 * 1024,
+        backend_bind_address: None,
+        backend_dscp: None,
+        fd_headroom: 64,
+        max_backend_peer_connections: None,
+        max_hops: None,
+        transparent: false,
+        report_backend_aborts: true,
+        sticky_sessions: false,
+        sticky_ttl_millis: 300_000,
+        listen_unix: None,
+        max_pending_connects: 32,
+        local_zone: None,
+        zone_spillover_min_healthy: 1,
+        propagate_deadlines: false,
+        request_timeout_millis: 30_000,
     };
     let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config)))
         .expect("Failed to create service");
@@ -120,6 +234,34 @@ async fn tokio_proxy_service_sends_metrics_events_should_succeed() {
     let config = ProxyConfig {
         listen_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0),
         max_connections: Some(1000),
+        coalesce: CoalesceConfig::default(),
+        hedging: HedgingConfig::default(),
+        retry_budget: RetryBudgetConfig::default(),
+        circuit_breaker: CircuitBreakerConfig::default(),
+        route_by_sni: SniRoutingConfig::default(),
+        route_by_alpn: AlpnRoutingConfig::default(),
+        route_by_http: HttpRoutingConfig::default(),
+        slow_connect_warn_ms: None,
+        slow_connection_warn_secs: None,
+        strategy_pick_warn_micros: None,
+        initial_read_timeout_millis: None,
+        slow_log_max_per_minute: 10,
+        max_buffered_bytes: 256 * 1024,
+        backend_bind_address: None,
+        backend_dscp: None,
+        fd_headroom: 64,
+        max_backend_peer_connections: None,
+        max_hops: None,
+        transparent: false,
+        report_backend_aborts: true,
+        sticky_sessions: false,
+        sticky_ttl_millis: 300_000,
+        listen_unix: None,
+        max_pending_connects: 32,
+        local_zone: None,
+        zone_spillover_min_healthy: 1,
+        propagate_deadlines: false,
+        request_timeout_millis: 30_000,
     };
     let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config)))
         .expect("Failed to create service");
@@ -138,6 +280,34 @@ async fn tokio_proxy_service_accept_connections_with_no_backends_should_reject()
     let config = ProxyConfig {
         listen_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0),
         max_connections: Some(1000),
+        coalesce: CoalesceConfig::default(),
+        hedging: HedgingConfig::default(),
+        retry_budget: RetryBudgetConfig::default(),
+        circuit_breaker: CircuitBreakerConfig::default(),
+        route_by_sni: SniRoutingConfig::default(),
+        route_by_alpn: AlpnRoutingConfig::default(),
+        route_by_http: HttpRoutingConfig::default(),
+        slow_connect_warn_ms: None,
+        slow_connection_warn_secs: None,
+        strategy_pick_warn_micros: None,
+        initial_read_timeout_millis: None,
+        slow_log_max_per_minute: 10,
+        max_buffered_bytes: 256 * 1024,
+        backend_bind_address: None,
+        backend_dscp: None,
+        fd_headroom: 64,
+        max_backend_peer_connections: None,
+        max_hops: None,
+        transparent: false,
+        report_backend_aborts: true,
+        sticky_sessions: false,
+        sticky_ttl_millis: 300_000,
+        listen_unix: None,
+        max_pending_connects: 32,
+        local_zone: None,
+        zone_spillover_min_healthy: 1,
+        propagate_deadlines: false,
+        request_timeout_millis: 30_000,
     };
     let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config)))
         .expect("Failed to create service");
@@ -171,6 +341,34 @@ async fn tokio_proxy_service_accept_connections_with_max_connections_should_reje
     let config = ProxyConfig {
         listen_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0),
         max_connections: Some(0),
+        coalesce: CoalesceConfig::default(),
+        hedging: HedgingConfig::default(),
+        retry_budget: RetryBudgetConfig::default(),
+        circuit_breaker: CircuitBreakerConfig::default(),
+        route_by_sni: SniRoutingConfig::default(),
+        route_by_alpn: AlpnRoutingConfig::default(),
+        route_by_http: HttpRoutingConfig::default(),
+        slow_connect_warn_ms: None,
+        slow_connection_warn_secs: None,
+        strategy_pick_warn_micros: None,
+        initial_read_timeout_millis: None,
+        slow_log_max_per_minute: 10,
+        max_buffered_bytes: 256 * 1024,
+        backend_bind_address: None,
+        backend_dscp: None,
+        fd_headroom: 64,
+        max_backend_peer_connections: None,
+        max_hops: None,
+        transparent: false,
+        report_backend_aborts: true,
+        sticky_sessions: false,
+        sticky_ttl_millis: 300_000,
+        listen_unix: None,
+        max_pending_connects: 32,
+        local_zone: None,
+        zone_spillover_min_healthy: 1,
+        propagate_deadlines: false,
+        request_timeout_millis: 30_000,
     };
     let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config)))
         .expect("Failed to create service");
@@ -199,3 +397,620 @@ async fn tokio_proxy_service_accept_connections_with_max_connections_should_reje
         let _ = accept_handle.await;
     }
 }
+
+#[tokio::test]
+async fn tokio_proxy_service_records_connect_and_first_byte_timings_should_succeed() {
+    // Given: a backend that answers 100ms after receiving a request
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let server_handle = tokio::spawn(async move {
+        if let Ok((mut stream, _)) = backend_listener.accept().await {
+            let mut buf = [0u8; 1024];
+            let _ = tokio::io::AsyncReadExt::read(&mut stream, &mut buf).await;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let _ = tokio::io::AsyncWriteExt::write_all(&mut stream, b"pong").await;
+        }
+    });
+
+    // And: a proxy listening on a free port in front of it
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let backend = BackendMeta::new(
+        0u8,
+        Some("backend-0"),
+        BackendAddress::from(backend_addr),
+        Some(10u8),
+    );
+    let mut config = create_test_config_fast(vec![backend], Strategy::RoundRobin);
+    config.proxy.listen_address = proxy_addr;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let mut metrics_rx = ctx
+        .channels()
+        .metrics_rx()
+        .expect("Metrics receiver should be available");
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client sends a request, reads the reply and lingers before closing
+    let mut client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    tokio::io::AsyncWriteExt::write_all(&mut client, b"ping")
+        .await
+        .expect("Failed to write request");
+    let mut buf = [0u8; 4];
+    tokio::io::AsyncReadExt::read_exact(&mut client, &mut buf)
+        .await
+        .expect("Failed to read reply");
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(client);
+
+    // Then: connect, first-byte and total timings are reported separately
+    let closed = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match metrics_rx.recv().await {
+                Some(MetricsEvent::ConnectionClosed {
+                    duration_micros,
+                    connect_micros,
+                    ttfb_micros,
+                    ..
+                }) => break Some((connect_micros, ttfb_micros, duration_micros)),
+                Some(_) => continue,
+                None => break None,
+            }
+        }
+    })
+    .await
+    .expect("ConnectionClosed event should be sent")
+    .expect("Metrics channel should stay open");
+    let (connect_micros, ttfb_micros, duration_micros) = closed;
+    let ttfb_micros = ttfb_micros.expect("Backend sent a reply");
+    assert_eq!(&buf, b"pong");
+    assert!(connect_micros < ttfb_micros);
+    assert!(ttfb_micros >= 100_000);
+    assert!(ttfb_micros < duration_micros);
+
+    proxy_handle.abort();
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_coalescing_echo_preserves_data_should_succeed() {
+    // Given: an echo backend
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let server_handle = tokio::spawn(async move {
+        if let Ok((mut stream, _)) = backend_listener.accept().await {
+            let (mut read, mut write) = stream.split();
+            let _ = tokio::io::copy(&mut read, &mut write).await;
+        }
+    });
+
+    // And: a proxy with write coalescing enabled in front of it
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let backend = BackendMeta::new(
+        0u8,
+        Some("backend-0"),
+        BackendAddress::from(backend_addr),
+        Some(10u8),
+    );
+    let mut config = create_test_config_fast(vec![backend], Strategy::RoundRobin);
+    config.proxy.listen_address = proxy_addr;
+    config.proxy.coalesce = CoalesceConfig {
+        enabled: true,
+        coalesce_micros: 1000,
+        max_bytes: 4096,
+    };
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let mut metrics_rx = ctx
+        .channels()
+        .metrics_rx()
+        .expect("Metrics receiver should be available");
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: a client streams data in uneven chunks, then half-closes
+    let payload: Vec<u8> = (0..100_000u32).map(|i| (i % 253) as u8).collect();
+    let client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let (mut client_read, mut client_write) = client.into_split();
+    let writer = tokio::spawn({
+        let payload = payload.clone();
+        async move {
+            for (i, chunk) in payload.chunks(37).enumerate() {
+                tokio::io::AsyncWriteExt::write_all(&mut client_write, chunk)
+                    .await
+                    .expect("Failed to write chunk");
+                if i % 100 == 0 {
+                    tokio::task::yield_now().await;
+                }
+            }
+            tokio::io::AsyncWriteExt::shutdown(&mut client_write)
+                .await
+                .expect("Failed to half-close");
+        }
+    });
+    let mut echoed = Vec::new();
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        tokio::io::AsyncReadExt::read_to_end(&mut client_read, &mut echoed),
+    )
+    .await
+    .expect("Echo should complete after the half-close")
+    .expect("Failed to read echo");
+    writer.await.expect("writer task");
+
+    // Then: the echo is complete and in order, and byte counts are exact
+    assert_eq!(echoed.len(), payload.len());
+    assert!(echoed == payload, "echoed data should match the payload");
+    let (bytes_in, bytes_out) = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match metrics_rx.recv().await {
+                Some(MetricsEvent::ConnectionClosed {
+                    bytes_in,
+                    bytes_out,
+                    ..
+                }) => break (bytes_in, bytes_out),
+                Some(_) => continue,
+                None => panic!("Metrics channel closed"),
+            }
+        }
+    })
+    .await
+    .expect("ConnectionClosed event should be sent");
+    assert_eq!(bytes_in, payload.len() as u64);
+    assert_eq!(bytes_out, payload.len() as u64);
+
+    proxy_handle.abort();
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_listen_address_change_tracks_old_connections_should_succeed()
+{
+    // Given: an echo backend and a proxy on a first address
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let server_handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = backend_listener.accept().await {
+            tokio::spawn(async move {
+                let (mut read, mut write) = stream.split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+        }
+    });
+    let reserve = || {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("Failed to reserve proxy port")
+    };
+    let (old_addr, new_addr) = (reserve(), reserve());
+    let backend = BackendMeta::new(
+        0u8,
+        Some("backend-0"),
+        BackendAddress::from(backend_addr),
+        Some(10u8),
+    );
+    let mut config = create_test_config_fast(vec![backend], Strategy::RoundRobin);
+    config.proxy.listen_address = old_addr;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service =
+        TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+            .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // And: an open connection accepted on the first listener
+    let mut client = tokio::net::TcpStream::connect(old_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    let mut buf = [0u8; 3];
+    tokio::io::AsyncWriteExt::write_all(&mut client, b"one")
+        .await
+        .expect("write");
+    tokio::io::AsyncReadExt::read_exact(&mut client, &mut buf)
+        .await
+        .expect("read");
+
+    // When: the listen address changes
+    let mut new_config = config.clone();
+    new_config.proxy.listen_address = new_addr;
+    ctx.migrate(new_config).await.expect("Migration failed");
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while tokio::net::TcpStream::connect(new_addr).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Proxy should accept on the new address");
+
+    // Then: the old connection is still counted under the old generation
+    let generations = ctx.listener_generations();
+    assert_eq!(generations.current(), 1);
+    assert_eq!(generations.active(0), 1);
+
+    // And: it keeps working until the client closes it
+    tokio::io::AsyncWriteExt::write_all(&mut client, b"two")
+        .await
+        .expect("write");
+    tokio::io::AsyncReadExt::read_exact(&mut client, &mut buf)
+        .await
+        .expect("read");
+    assert_eq!(&buf, b"two");
+    drop(client);
+    ctx.wait_for_generation_drain(0, Duration::from_secs(2))
+        .await
+        .expect("Old generation should drain");
+    assert_eq!(generations.active(0), 0);
+
+    proxy_handle.abort();
+    server_handle.abort();
+}
+
+/// Open a client connection through a proxy in front of an echo backend
+///
+/// Returns the context, the client (already echoed once) and the metrics
+/// receiver; the handles stop the proxy and backend.
+async fn open_echo_connection(
+    drain_policy: DrainPolicy,
+) -> (
+    Arc<Context>,
+    tokio::net::TcpStream,
+    tokio::sync::mpsc::Receiver<MetricsEvent>,
+    [tokio::task::JoinHandle<()>; 2],
+) {
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let server_handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = backend_listener.accept().await {
+            tokio::spawn(async move {
+                let (mut read, mut write) = stream.split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+        }
+    });
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let backend = BackendMeta::new(
+        0u8,
+        Some("backend-0"),
+        BackendAddress::from(backend_addr),
+        Some(10u8),
+    );
+    let mut config = create_test_config_fast(vec![backend], Strategy::RoundRobin);
+    config.proxy.listen_address = proxy_addr;
+    config.runtime.drain_timeout_millis = 2_000;
+    config.runtime.drain_policy = drain_policy;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let metrics_rx = ctx
+        .channels()
+        .metrics_rx()
+        .expect("Metrics receiver should be available");
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move {
+            let _ = service.accept_connections(ctx).await;
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let mut client = tokio::net::TcpStream::connect(proxy_addr)
+        .await
+        .expect("Failed to connect to proxy");
+    assert_echoes(&mut client).await;
+    (ctx, client, metrics_rx, [proxy_handle, server_handle])
+}
+
+/// Assert the connection still echoes data
+async fn assert_echoes(client: &mut tokio::net::TcpStream) {
+    let mut buf = [0u8; 4];
+    tokio::io::AsyncWriteExt::write_all(client, b"ping")
+        .await
+        .expect("write");
+    tokio::io::AsyncReadExt::read_exact(client, &mut buf)
+        .await
+        .expect("read");
+    assert_eq!(&buf, b"ping");
+}
+
+/// Wait for the proxy to close the client connection
+async fn assert_closed_by_proxy(client: &mut tokio::net::TcpStream) {
+    let mut buf = [0u8; 4];
+    let read = tokio::time::timeout(
+        Duration::from_secs(2),
+        tokio::io::AsyncReadExt::read(client, &mut buf),
+    )
+    .await
+    .expect("Proxy should close the connection");
+    assert!(matches!(read, Ok(0) | Err(_)), "{:?}", read);
+}
+
+/// Close reason of the next closed connection
+async fn next_close_reason(
+    metrics_rx: &mut tokio::sync::mpsc::Receiver<MetricsEvent>,
+) -> CloseReason {
+    tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match metrics_rx.recv().await {
+                Some(MetricsEvent::ConnectionClosed { close_reason, .. }) => {
+                    break close_reason;
+                }
+                Some(_) => continue,
+                None => panic!("Metrics channel should stay open"),
+            }
+        }
+    })
+    .await
+    .expect("ConnectionClosed event should be sent")
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_drain_policy_finish_keeps_connections_should_succeed() {
+    // Given: an open connection to a backend drained with the finish policy
+    let (ctx, mut client, mut metrics_rx, handles) =
+        open_echo_connection(DrainPolicy::Finish).await;
+    let backend = ctx.routing_table().get(0).expect("backend");
+
+    // When: the backend starts draining
+    ctx.drain_backend(&backend, DrainPolicy::Finish);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Then: the connection keeps working until the client closes it
+    assert_echoes(&mut client).await;
+    drop(client);
+    assert_eq!(
+        next_close_reason(&mut metrics_rx).await,
+        CloseReason::Normal
+    );
+    assert_eq!(backend.forced_closes(), 0);
+
+    handles.iter().for_each(|handle| handle.abort());
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_drain_policy_deadline_closes_lingering_should_succeed() {
+    // Given: an open connection to a backend
+    let (ctx, mut client, mut metrics_rx, handles) =
+        open_echo_connection(DrainPolicy::Finish).await;
+    let backend = ctx.routing_table().get(0).expect("backend");
+
+    // When: the backend starts draining with a 200ms deadline
+    ctx.drain_backend(&backend, DrainPolicy::Deadline(200));
+
+    // Then: the connection works until the deadline, then is force-closed
+    assert_echoes(&mut client).await;
+    assert_closed_by_proxy(&mut client).await;
+    assert_eq!(
+        next_close_reason(&mut metrics_rx).await,
+        CloseReason::DrainDeadline
+    );
+    assert_eq!(backend.forced_closes(), 1);
+    assert_eq!(backend.active_connections(), 0);
+
+    handles.iter().for_each(|handle| handle.abort());
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_drain_policy_immediate_on_migration_should_succeed() {
+    // Given: an open connection and the immediate drain policy
+    let (ctx, mut client, mut metrics_rx, handles) =
+        open_echo_connection(DrainPolicy::Immediate).await;
+    let backend = ctx.routing_table().get(0).expect("backend");
+
+    // When: a migration removes the backend
+    let mut config = (*ctx.config()).clone();
+    config.backends.clear();
+    let started = std::time::Instant::now();
+    ctx.migrate(config).await.expect("Migration failed");
+
+    // Then: the connection is cut without waiting for the drain timeout
+    assert!(started.elapsed() < Duration::from_millis(1_000));
+    assert_closed_by_proxy(&mut client).await;
+    assert_eq!(
+        next_close_reason(&mut metrics_rx).await,
+        CloseReason::DrainImmediate
+    );
+    assert_eq!(backend.forced_closes(), 1);
+    assert!(ctx.routing_table().is_empty());
+
+    handles.iter().for_each(|handle| handle.abort());
+}
+
+/// Run a health service over `ctx` and mark backend 0 unhealthy through a
+/// proxy failure event
+async fn flip_backend_unhealthy(
+    ctx: &Arc<Context>,
+    evict_on_unhealthy: bool,
+) -> tokio::task::JoinHandle<()> {
+    let mut health = ctx.config().health.clone();
+    health.evict_on_unhealthy = evict_on_unhealthy;
+    let service = BackendHealthService::new(Arc::new(ArcSwap::from_pointee(health)))
+        .expect("Failed to create health service");
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+    let backend = ctx.routing_table().get(0).expect("backend");
+    while !ctx.readiness().is_health_checked() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(backend.is_alive());
+
+    ctx.channels()
+        .backend_failure_tx()
+        .send(BackendFailureEvent::ConnectionRefused {
+            backend_id: 0,
+            at_micros: ctx.clock().now_micros(),
+        })
+        .await
+        .expect("Failure channel should stay open");
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while backend.is_alive() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Backend should turn unhealthy");
+    health_handle
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_evict_on_unhealthy_closes_connections_should_succeed() {
+    // Given: an open connection and eviction enabled
+    let (ctx, mut client, mut metrics_rx, handles) =
+        open_echo_connection(DrainPolicy::Finish).await;
+    let backend = ctx.routing_table().get(0).expect("backend");
+
+    // When: the backend turns unhealthy
+    let health_handle = flip_backend_unhealthy(&ctx, true).await;
+
+    // Then: the connection is cut with the unhealthy close reason
+    assert_closed_by_proxy(&mut client).await;
+    assert_eq!(
+        next_close_reason(&mut metrics_rx).await,
+        CloseReason::BackendUnhealthy
+    );
+    assert_eq!(backend.forced_closes(), 1);
+    assert_eq!(backend.active_connections(), 0);
+
+    health_handle.abort();
+    handles.iter().for_each(|handle| handle.abort());
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_unhealthy_keeps_connections_by_default_should_succeed() {
+    // Given: an open connection and eviction disabled (the default)
+    let (ctx, mut client, mut metrics_rx, handles) =
+        open_echo_connection(DrainPolicy::Finish).await;
+    let backend = ctx.routing_table().get(0).expect("backend");
+    assert!(!ctx.config().health.evict_on_unhealthy);
+
+    // When: the backend turns unhealthy
+    let health_handle = flip_backend_unhealthy(&ctx, false).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Then: the connection keeps flowing until the client closes it
+    assert_echoes(&mut client).await;
+    drop(client);
+    assert_eq!(
+        next_close_reason(&mut metrics_rx).await,
+        CloseReason::Normal
+    );
+    assert_eq!(backend.forced_closes(), 0);
+
+    health_handle.abort();
+    handles.iter().for_each(|handle| handle.abort());
+}
+
+#[tokio::test]
+async fn tokio_proxy_service_disabled_services_send_no_events_should_succeed() {
+    // Given: an echo backend and a backend refusing connections
+    let backend_listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let backend_addr = backend_listener.local_addr().expect("backend address");
+    let server_handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = backend_listener.accept().await {
+            tokio::spawn(async move {
+                let (mut read, mut write) = stream.split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+        }
+    });
+    let refused_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve refused port");
+
+    // And: a proxy in front of them with health checks and metrics disabled
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let backends = vec![
+        BackendMeta::new(
+            0u8,
+            Some("backend-0"),
+            BackendAddress::from(backend_addr),
+            Some(10u8),
+        ),
+        BackendMeta::new(
+            1u8,
+            Some("backend-1"),
+            BackendAddress::from(refused_addr),
+            Some(10u8),
+        ),
+    ];
+    let mut config = create_test_config_fast(backends, Strategy::RoundRobin);
+    config.proxy.listen_address = proxy_addr;
+    config.services.health = false;
+    config.services.metrics = false;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    assert!(!ctx.health_enabled());
+    assert!(!ctx.metrics_enabled());
+    let mut metrics_rx = ctx
+        .channels()
+        .metrics_rx()
+        .expect("Metrics receiver should be available");
+    let mut failure_rx = ctx
+        .channels()
+        .backend_failure_rx()
+        .expect("Failure receiver should be available");
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move {
+            let _ = service.accept_connections(ctx).await;
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: one connection is proxied and one fails to reach its backend
+    for _ in 0..2 {
+        let mut client = tokio::net::TcpStream::connect(proxy_addr)
+            .await
+            .expect("Failed to connect to proxy");
+        let _ = tokio::io::AsyncWriteExt::write_all(&mut client, b"ping").await;
+        let mut buf = [0u8; 4];
+        let _ = tokio::time::timeout(
+            Duration::from_secs(2),
+            tokio::io::AsyncReadExt::read(&mut client, &mut buf),
+        )
+        .await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Then: neither metrics nor failure events are sent
+    assert!(metrics_rx.try_recv().is_err());
+    assert!(failure_rx.try_recv().is_err());
+
+    proxy_handle.abort();
+    server_handle.abort();
+}
@@ -0,0 +1,151 @@
+//! Tests for proxying over Unix domain sockets
+//!
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+use crate::common::fixtures::create_test_config_fast;
+
+/// Unix socket backend replying with `name` to every connection
+fn unix_backend(
+    address: &UnixAddress,
+    name: &'static str,
+) -> tokio::task::JoinHandle<()> {
+    let listener = address.bind().expect("Failed to bind backend");
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let _ =
+                tokio::io::AsyncWriteExt::write_all(&mut stream, name.as_bytes()).await;
+        }
+    })
+}
+
+/// Start a proxy for `config`, waiting until it accepts
+async fn start_proxy(
+    config: Config,
+) -> (
+    Arc<Context>,
+    tokio::task::JoinHandle<Result<(), ProxyError>>,
+) {
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !ctx.readiness().is_accepting() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Proxy should start accepting");
+    (ctx, handle)
+}
+
+/// Read everything the proxy relays on `stream`
+async fn read_reply(mut stream: impl AsyncReadExt + Unpin) -> String {
+    let mut reply = String::new();
+    tokio::time::timeout(Duration::from_secs(2), stream.read_to_string(&mut reply))
+        .await
+        .expect("Backend reply should arrive")
+        .expect("Failed to read reply");
+    reply
+}
+
+#[tokio::test]
+async fn proxy_tcp_listener_to_unix_backend_should_succeed() {
+    // Given: a proxy on TCP in front of a backend on a Unix socket
+    let dir = tempfile::tempdir().unwrap();
+    let backend_address = UnixAddress::Path(dir.path().join("backend.sock"));
+    let backend = unix_backend(&backend_address, "unix-backend");
+    let mut config = create_test_config_fast(
+        vec![BackendMeta::new(
+            0u8,
+            Some("unix"),
+            BackendAddress::parse(&backend_address.to_string()).unwrap(),
+            Some(1u8),
+        )],
+        Strategy::RoundRobin,
+    );
+    config.proxy.listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let (ctx, proxy) = start_proxy(config.clone()).await;
+
+    // When: a TCP client connects
+    let client = tokio::net::TcpStream::connect(config.proxy.listen_address)
+        .await
+        .expect("Failed to connect to proxy");
+
+    // Then: it reaches the Unix socket backend
+    assert_eq!(read_reply(client).await, "unix-backend");
+    assert!(
+        BackendHealthService::connect_probe(
+            &ctx.routing_table().get(0).unwrap().address(),
+            None,
+            Duration::from_secs(1)
+        )
+        .await
+        .is_ok()
+    );
+
+    proxy.abort();
+    backend.abort();
+}
+
+#[tokio::test]
+async fn proxy_unix_listener_to_unix_backend_should_succeed() {
+    // Given: a proxy listening on a Unix socket in front of two Unix socket
+    // backends
+    let dir = tempfile::tempdir().unwrap();
+    let listen_address = UnixAddress::Path(dir.path().join("lb.sock"));
+    let mut backends = Vec::new();
+    let mut handles = Vec::new();
+    for (id, name) in ["b0", "b1"].into_iter().enumerate() {
+        let address = UnixAddress::Path(dir.path().join(format!("{}.sock", name)));
+        handles.push(unix_backend(&address, name));
+        backends.push(BackendMeta::new(
+            id as u8,
+            Some(name),
+            BackendAddress::parse(&address.to_string()).unwrap(),
+            Some(1u8),
+        ));
+    }
+    let mut config = create_test_config_fast(backends, Strategy::RoundRobin);
+    config.proxy.listen_unix = Some(listen_address.clone());
+    config.proxy.sticky_sessions = true;
+    let (ctx, proxy) = start_proxy(config).await;
+
+    // When: Unix socket clients connect
+    let UnixAddress::Path(path) = &listen_address else {
+        unreachable!()
+    };
+    let mut served = Vec::new();
+    for _ in 0..4 {
+        let client = tokio::net::UnixStream::connect(path)
+            .await
+            .expect("Failed to connect to proxy");
+        served.push(read_reply(client).await);
+    }
+
+    // Then: the strategy spreads them (no address to stick by)
+    served.sort();
+    assert_eq!(served, ["b0", "b0", "b1", "b1"]);
+    assert!(ctx.affinity().is_empty());
+
+    // When: the proxy shuts down
+    let _ = ctx.channels().shutdown_tx().send(());
+    proxy
+        .await
+        .expect("Proxy task panicked")
+        .expect("Proxy should stop cleanly");
+
+    // Then: its socket file is removed
+    assert!(!path.exists());
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,6 @@
+//! State module tests
+//!
+//! Tests for the state file snapshot and writer, and the health state file
+
+mod test_health;
+mod test_writer;
@@ -0,0 +1,305 @@
+//! Tests for the health state file
+
+use lemonade_load_balancer::prelude::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+use crate::common::fixtures::{
+    create_test_backend_with_details, create_test_config_fast,
+};
+
+/// Config over `backends` persisting health to `path` every 20ms
+fn health_state_config(path: &Path, backends: Vec<BackendMeta>) -> Config {
+    let mut config = create_test_config_fast(backends, Strategy::RoundRobin);
+    config.health_state_file = Some(HealthStateFileConfig {
+        path: path.to_path_buf(),
+        interval_millis: 20,
+        max_state_age_millis: 60_000,
+    });
+    config
+}
+
+/// Backends 0 at `down` and 1 at `up`
+fn two_backends(down: u16, up: u16) -> Vec<BackendMeta> {
+    vec![
+        create_test_backend_with_details(0, "backend-0", down),
+        create_test_backend_with_details(1, "backend-1", up),
+    ]
+}
+
+/// Listener on a free local port, and the port
+async fn listening_port() -> (tokio::net::TcpListener, u16) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let port = listener.local_addr().expect("backend address").port();
+    (listener, port)
+}
+
+/// Ids of the backends picked over `count` picks
+async fn picked(ctx: &Arc<Context>, count: usize) -> Vec<BackendId> {
+    let mut ids = Vec::new();
+    for _ in 0..count {
+        let backend = ctx
+            .strategy()
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Failed to pick backend");
+        ids.push(backend.id());
+    }
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Write `snapshot` to `path`
+fn write_snapshot(path: &Path, snapshot: &HealthStateSnapshot) {
+    std::fs::write(path, serde_json::to_vec(snapshot).unwrap()).expect("write snapshot");
+}
+
+#[tokio::test]
+async fn health_state_restored_after_restart_until_probed_should_succeed() {
+    // Given: backends that accept probes once the load balancer restarts,
+    // and a running load balancer that saw the first fail three times
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("health.json");
+    let (down_listener, down) = listening_port().await;
+    let (up_listener, up) = listening_port().await;
+    let config = health_state_config(&path, two_backends(down, up));
+    let before =
+        Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let failing = before.routing_table().get(0).unwrap();
+    for _ in 0..3 {
+        failing.set_health(false, before.clock().now_millis());
+    }
+
+    // When: the writer persists it and the load balancer shuts down
+    let writer = tokio::spawn({
+        let ctx = before.clone();
+        async move { HealthStateWriter::new().run(ctx).await }
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !path.exists() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Health state file should be written periodically");
+    let _ = before.channels().shutdown_tx().send(());
+    writer.await.expect("Writer panicked");
+    HealthStateWriter::new().write_final(&before).await;
+
+    // When: restarting with the ids reshuffled
+    let after = Arc::new(
+        Context::new(health_state_config(
+            &path,
+            vec![
+                create_test_backend_with_details(5, "backend-1", up),
+                create_test_backend_with_details(7, "backend-0", down),
+            ],
+        ))
+        .expect("Failed to create context"),
+    );
+
+    // Then: the backend at the failed address starts out of rotation, with
+    // its failure count
+    let routing = after.routing_table();
+    let restored = routing.get(7).unwrap();
+    assert!(!restored.is_alive());
+    assert_eq!(restored.consecutive_failures(), 3);
+    assert!(routing.get(5).unwrap().is_alive());
+    assert_eq!(picked(&after, 10).await, [5]);
+
+    // When: the health service probes it at startup
+    let health = tokio::spawn({
+        let ctx = after.clone();
+        let config = Arc::new(ArcSwap::from_pointee(ctx.config().health.clone()));
+        async move {
+            BackendHealthService::new(config)
+                .expect("Failed to create health service")
+                .check_health(ctx)
+                .await
+        }
+    });
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !restored.is_alive() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("Backend should pass its probe");
+
+    // Then: it re-enters rotation
+    assert_eq!(restored.consecutive_failures(), 0);
+    assert_eq!(picked(&after, 10).await, [5, 7]);
+
+    let _ = after.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_secs(1), health).await;
+    drop((down_listener, up_listener));
+}
+
+#[tokio::test]
+async fn health_state_restores_open_circuit_should_succeed() {
+    // Given: a fresh file saving backend 0's circuit as opened just now, and
+    // a circuit breaker keeping circuits open for a minute
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("health.json");
+    let mut config = health_state_config(&path, two_backends(9000, 9001));
+    config.proxy.circuit_breaker = CircuitBreakerConfig {
+        enabled: true,
+        failure_threshold: 3,
+        open_duration_millis: 60_000,
+    };
+    let now_ms = SystemClock.now_millis();
+    write_snapshot(
+        &path,
+        &HealthStateSnapshot {
+            written_at_ms: now_ms,
+            backends: BTreeMap::from([(
+                config.backends[0].address.as_str().to_string(),
+                BackendHealthState {
+                    alive: true,
+                    consecutive_failures: 0,
+                    last_health_check_ms: now_ms,
+                    circuit: CircuitState::HalfOpen,
+                    circuit_failures: 3,
+                    circuit_since_ms: now_ms,
+                },
+            )]),
+        },
+    );
+
+    // When: starting from it
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+
+    // Then: the circuit is restored open (its trial died with the previous
+    // run) and picks skip the backend until the open duration is over
+    let restored = ctx.routing_table().get(0).unwrap();
+    assert_eq!(restored.circuit().state(), CircuitState::Open);
+    assert_eq!(restored.circuit().failures(), 3);
+    assert_eq!(picked(&ctx, 4).await, vec![1]);
+
+    // And: the captured state round-trips
+    let captured = BackendHealthState::capture(&restored);
+    assert_eq!(captured.circuit, CircuitState::Open);
+    assert_eq!(captured.circuit_since_ms, now_ms);
+}
+
+#[test]
+fn health_state_stale_file_ignored_should_succeed() {
+    // Given: a health state file older than max_state_age_millis
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("health.json");
+    let config = health_state_config(&path, two_backends(9000, 9001));
+    let address = config.backends[0].address.as_str().to_string();
+    let now_ms = SystemClock.now_millis();
+    write_snapshot(
+        &path,
+        &HealthStateSnapshot {
+            written_at_ms: now_ms - 120_000,
+            backends: BTreeMap::from([(
+                address,
+                BackendHealthState {
+                    alive: false,
+                    consecutive_failures: 4,
+                    last_health_check_ms: now_ms - 120_000,
+                    circuit: CircuitState::Closed,
+                    circuit_failures: 0,
+                    circuit_since_ms: 0,
+                },
+            )]),
+        },
+    );
+
+    // When: starting from it
+    let ctx = Context::new(config).expect("Failed to create context");
+
+    // Then: the backend starts healthy
+    assert!(ctx.routing_table().get(0).unwrap().is_alive());
+}
+
+#[test]
+fn health_state_corrupt_file_ignored_should_succeed() {
+    // Given: a corrupt health state file
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("health.json");
+    std::fs::write(&path, b"{ not json").expect("write file");
+    let config = health_state_config(&path, two_backends(9000, 9001));
+
+    // When: starting from it
+    let health_state_file = config.health_state_file.clone().unwrap();
+    let ctx = Context::new(config).expect("Failed to create context");
+
+    // Then: nothing is restored and every backend starts healthy
+    let routing = ctx.routing_table();
+    assert_eq!(
+        health_state_file.restore(&routing, SystemClock.now_millis()),
+        0
+    );
+    assert!(
+        routing
+            .all_backends()
+            .iter()
+            .all(|backend| backend.is_alive())
+    );
+}
+
+#[test]
+fn health_state_not_restored_without_health_checks_should_succeed() {
+    // Given: a fresh file marking backend 0 unhealthy, and health checks off
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("health.json");
+    let mut config = health_state_config(&path, two_backends(9000, 9001));
+    config.services.health = false;
+    let now_ms = SystemClock.now_millis();
+    write_snapshot(
+        &path,
+        &HealthStateSnapshot {
+            written_at_ms: now_ms,
+            backends: BTreeMap::from([(
+                config.backends[0].address.as_str().to_string(),
+                BackendHealthState {
+                    alive: false,
+                    consecutive_failures: 1,
+                    last_health_check_ms: now_ms,
+                    circuit: CircuitState::Closed,
+                    circuit_failures: 0,
+                    circuit_since_ms: 0,
+                },
+            )]),
+        },
+    );
+
+    // When: starting from it
+    let ctx = Context::new(config).expect("Failed to create context");
+
+    // Then: nothing would probe the backend back, so it starts healthy
+    assert!(ctx.routing_table().get(0).unwrap().is_alive());
+}
+
+#[test]
+fn health_state_file_config_validate_should_fail() {
+    let valid = HealthStateFileConfig {
+        path: "health.json".into(),
+        interval_millis: 1_000,
+        max_state_age_millis: 60_000,
+    };
+    let zero_interval = HealthStateFileConfig {
+        interval_millis: 0,
+        ..valid.clone()
+    };
+    let zero_age = HealthStateFileConfig {
+        max_state_age_millis: 0,
+        ..valid.clone()
+    };
+    let no_name = HealthStateFileConfig {
+        path: "/".into(),
+        ..valid.clone()
+    };
+    assert!(valid.validate().is_ok());
+    for config in [zero_interval, zero_age, no_name] {
+        assert!(matches!(config.validate(), Err(ConfigError::StateFile(_))));
+    }
+}
@@ -0,0 +1,177 @@
+//! Tests for StateFileWriter
+
+use lemonade_load_balancer::prelude::*;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Context over two backends writing its state file to `path` every 20ms
+fn state_file_context(path: &Path) -> Arc<Context> {
+    let mut config = create_test_config_fast(
+        vec![
+            create_test_backend(0, Some("backend-0".to_string()), Some(10u8)),
+            create_test_backend(1, Some("backend-1".to_string()), Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    config.state_file = Some(StateFileConfig {
+        path: path.to_path_buf(),
+        interval_millis: 20,
+    });
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+/// Read and parse the state file
+fn read_snapshot(path: &Path) -> Option<StateSnapshot> {
+    let contents = std::fs::read(path).ok()?;
+    Some(serde_json::from_slice(&contents).expect("State file should always parse"))
+}
+
+/// Poll the state file until `condition` holds for its snapshot
+async fn wait_for_snapshot(
+    path: &Path,
+    condition: impl Fn(&StateSnapshot) -> bool,
+) -> StateSnapshot {
+    tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            if let Some(snapshot) = read_snapshot(path)
+                && condition(&snapshot)
+            {
+                return snapshot;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("State file should reach the expected state")
+}
+
+#[tokio::test]
+async fn state_file_writer_tracks_traffic_and_migration_should_succeed() {
+    // Given: a context writing its state file
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("state.json");
+    let ctx = state_file_context(&path);
+    let writer = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { StateFileWriter::new().run(ctx).await }
+    });
+
+    // Then: the first snapshot lists both backends at generation 0
+    let snapshot = wait_for_snapshot(&path, |_| true).await;
+    let group = &snapshot.groups[DEFAULT_GROUP];
+    assert_eq!(group.generation, 0);
+    assert_eq!(group.strategy, Strategy::RoundRobin);
+    assert_eq!(group.backends.len(), 2);
+    assert!(!snapshot.final_snapshot);
+
+    // When: connections open and backend 1 fails its health check
+    let routing = ctx.routing_table();
+    for _ in 0..3 {
+        routing.get(0).unwrap().increment_connection();
+    }
+    routing.get(1).unwrap().set_health(false, 1_000);
+
+    // Then: the file follows
+    wait_for_snapshot(&path, |snapshot| {
+        let backends = &snapshot.groups[DEFAULT_GROUP].backends;
+        backends[0].active_connections == 3
+            && !backends[1].alive
+            && backends[1].last_health_check_ms == 1_000
+    })
+    .await;
+
+    // When: a migration adds a backend and switches strategy
+    let mut config = (*ctx.config()).clone();
+    config.strategy = Strategy::LeastConnections;
+    config
+        .backends
+        .push(create_test_backend(2, None, Some(10u8)).into());
+    ctx.migrate(config).await.expect("Migration failed");
+
+    // Then: the file reports the new generation, strategy and backend
+    let generation = ctx.generation();
+    assert!(generation > 0);
+    let snapshot = wait_for_snapshot(&path, |snapshot| {
+        snapshot.groups[DEFAULT_GROUP].generation == generation
+    })
+    .await;
+    let group = &snapshot.groups[DEFAULT_GROUP];
+    assert_eq!(group.strategy, Strategy::LeastConnections);
+    let ids: Vec<BackendId> = group.backends.iter().map(|b| b.id).collect();
+    assert_eq!(ids, vec![0, 1, 2]);
+
+    // And: the writer stops on shutdown without leaving a temp file behind
+    let _ = ctx.channels().shutdown_tx().send(());
+    tokio::time::timeout(Duration::from_secs(1), writer)
+        .await
+        .expect("Writer should stop on shutdown")
+        .expect("Writer should not panic");
+    let files: Vec<_> = std::fs::read_dir(dir.path())
+        .expect("read dir")
+        .map(|entry| entry.expect("entry").file_name())
+        .collect();
+    assert_eq!(files, vec![std::ffi::OsString::from("state.json")]);
+}
+
+#[tokio::test]
+async fn state_file_writer_write_failure_is_reported_should_fail() {
+    // Given: a state file in a directory that does not exist
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("missing").join("state.json");
+    let ctx = state_file_context(&path);
+
+    // When: writing a snapshot directly and running the writer
+    let result = StateFileWriter::new().write(&ctx, &path, false).await;
+    let writer = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { StateFileWriter::new().run(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    // Then: the direct write fails, the writer keeps running and nothing
+    // is written
+    assert!(result.is_err());
+    assert!(!writer.is_finished());
+    assert!(!path.exists());
+    assert!(
+        ctx.routing_table()
+            .get(0)
+            .unwrap()
+            .can_accept_new_connections()
+    );
+    writer.abort();
+}
+
+#[tokio::test]
+async fn state_file_writer_write_final_marks_snapshot_should_succeed() {
+    // Given: a context with a state file
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("state.json");
+    let ctx = state_file_context(&path);
+
+    // When: writing the final snapshot
+    StateFileWriter::new().write_final(&ctx).await;
+
+    // Then: it is marked final
+    let snapshot = read_snapshot(&path).expect("State file should exist");
+    assert!(snapshot.final_snapshot);
+    assert_eq!(snapshot.groups[DEFAULT_GROUP].backends.len(), 2);
+}
+
+#[test]
+fn state_file_config_validate_should_fail() {
+    // Given/When/Then: a zero interval and a path without a file name are rejected
+    let zero = StateFileConfig {
+        path: "state.json".into(),
+        interval_millis: 0,
+    };
+    let no_name = StateFileConfig {
+        path: "/".into(),
+        interval_millis: 1_000,
+    };
+    assert!(matches!(zero.validate(), Err(ConfigError::StateFile(_))));
+    assert!(matches!(no_name.validate(), Err(ConfigError::StateFile(_))));
+}
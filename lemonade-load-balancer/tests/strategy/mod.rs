@@ -2,8 +2,17 @@
 //!
 //! Tests for load balancing strategies
 
+mod test_adaptive;
 mod test_builder;
+mod test_fallback;
+mod test_header_hash;
+mod test_least_bandwidth;
 mod test_least_connections;
 mod test_models;
+mod test_priority;
+mod test_random;
+mod test_registry;
 mod test_round_robin;
+mod test_trace;
 mod test_weighted_round_robin;
+mod test_zone;
@@ -0,0 +1,85 @@
+//! Tests for the adaptive strategy score cache across migrations
+//!
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Adaptive context with backends 0 to 2, each scored once
+async fn scored_context() -> Arc<Context> {
+    let config = create_test_config_fast(
+        (0..3)
+            .map(|id| create_test_backend(id, None, Some(10u8)))
+            .collect(),
+        Strategy::Adaptive,
+    );
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    ctx.strategy()
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Pick should succeed");
+    ctx
+}
+
+/// Backends the strategy of `ctx` caches a score for, sorted
+fn tracked(ctx: &Context) -> Vec<BackendId> {
+    let mut ids = ctx.strategy().tracked_backends();
+    ids.sort_unstable();
+    ids
+}
+
+#[tokio::test]
+async fn adaptive_cache_kept_across_migration_should_succeed() {
+    // Given: an adaptive context with a cached score for every backend
+    let ctx = scored_context().await;
+    let strategy = ctx.strategy();
+    assert_eq!(tracked(&ctx), vec![0, 1, 2]);
+
+    // When: removing backend 2, replacing backend 1 and adding backend 3
+    let mut config = (*ctx.config()).clone();
+    config.backends.retain(|backend| backend.id != 2);
+    config.backends[1].name = Some("replacement".to_string());
+    config
+        .backends
+        .push(BackendConfig::from(create_test_backend(
+            3,
+            None,
+            Some(10u8),
+        )));
+    ctx.migrate(config).await.expect("Failed to migrate");
+
+    // Then: the strategy is kept, the removed backend's score is dropped and
+    // the new backends are scored ahead of their first pick
+    assert!(Arc::ptr_eq(&strategy, &ctx.strategy()));
+    assert_eq!(tracked(&ctx), vec![0, 1, 3]);
+    assert!(ConsistencyChecker::check(&ctx).is_empty());
+
+    // And: picks only return routed backends
+    let routing = ctx.routing_table();
+    for _ in 0..8 {
+        let picked = ctx
+            .strategy()
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Pick should succeed");
+        let routed = routing.get(picked.id()).expect("picked backend is routed");
+        assert!(Arc::ptr_eq(&picked, &routed));
+    }
+}
+
+#[tokio::test]
+async fn adaptive_cache_params_change_rebuilds_strategy_should_succeed() {
+    // Given: an adaptive context with a cached score for every backend
+    let ctx = scored_context().await;
+    let strategy = ctx.strategy();
+
+    // When: changing the scoring weights and removing backend 0
+    let mut config = (*ctx.config()).clone();
+    config.strategy_params = serde_json::json!({ "conn_weight": 1.0 });
+    config.backends.retain(|backend| backend.id != 0);
+    ctx.migrate(config).await.expect("Failed to migrate");
+
+    // Then: a new strategy is built, with scores for the routed backends only
+    assert!(!Arc::ptr_eq(&strategy, &ctx.strategy()));
+    assert_eq!(tracked(&ctx), vec![1, 2]);
+}
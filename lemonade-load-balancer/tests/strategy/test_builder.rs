@@ -10,6 +10,7 @@
 
 use super::super::common::fixtures::*;
 use lemonade_load_balancer::prelude::*;
+use rstest::*;
 
 #[test]
 fn strategy_builder_with_strategy_should_succeed() {
@@ -55,6 +56,20 @@ fn strategy_builder_build_adaptive_should_succeed() {
     assert!(matches!(strategy_service.strategy(), Strategy::Adaptive));
 }
 
+#[test]
+fn strategy_builder_build_adaptive_invalid_params_should_fail() {
+    // Given: adaptive params with a non-numeric weight
+    let builder = StrategyBuilder::new()
+        .with_strategy(Strategy::Adaptive)
+        .with_params(serde_json::json!({ "resource_weight": "high" }));
+
+    // When: building the strategy
+    let result = builder.build();
+
+    // Then: the params are rejected
+    assert!(result.is_err());
+}
+
 #[test]
 fn strategy_builder_build_fastest_response_time_should_succeed() {
     // Given: a StrategyBuilder with FastestResponseTime strategy
@@ -72,6 +87,55 @@ fn strategy_builder_build_fastest_response_time_should_succeed() {
     ));
 }
 
+#[test]
+fn strategy_builder_build_header_hash_should_succeed() {
+    // Given: a StrategyBuilder with HeaderHash strategy hashing a header
+    let builder = StrategyBuilder::new()
+        .with_strategy(Strategy::HeaderHash)
+        .with_params(serde_json::json!({ "hash_on": "X-Tenant-Id" }));
+
+    // When: building the strategy
+    let result = builder.build();
+
+    // Then: build succeeds and the proxy is asked for request heads
+    let strategy_service = result.expect("Failed to build strategy");
+    assert_eq!(strategy_service.strategy(), Strategy::HeaderHash);
+    assert!(strategy_service.request_head_limits().is_some());
+}
+
+#[rstest]
+#[case(serde_json::Value::Null)]
+#[case(serde_json::json!({ "hash_on": "" }))]
+#[case(serde_json::json!({ "hash_on": "X Tenant" }))]
+#[case(serde_json::json!({ "hash_on": "path", "max_head_bytes": 0 }))]
+fn strategy_builder_build_header_hash_invalid_params_should_fail(
+    #[case] params: serde_json::Value,
+) {
+    // Given: header hash params without a valid key or limit
+    let builder = StrategyBuilder::new()
+        .with_strategy(Strategy::HeaderHash)
+        .with_params(params);
+
+    // When: building the strategy
+    let result = builder.build();
+
+    // Then: the params are rejected
+    assert!(result.is_err());
+}
+
+#[test]
+fn strategy_builder_build_least_bandwidth_should_succeed() {
+    // Given: a StrategyBuilder with LeastBandwidth strategy
+    let builder = StrategyBuilder::new().with_strategy(Strategy::LeastBandwidth);
+
+    // When: building the strategy
+    let result = builder.build();
+
+    // Then: build succeeds with LeastBandwidth strategy
+    let strategy_service = result.expect("Failed to build strategy");
+    assert_eq!(strategy_service.strategy(), Strategy::LeastBandwidth);
+}
+
 #[test]
 fn strategy_builder_build_least_connections_should_succeed() {
     // Given: a StrategyBuilder with LeastConnections strategy
@@ -103,6 +167,19 @@ fn strategy_builder_build_round_robin_should_succeed() {
     assert!(matches!(strategy_service.strategy(), Strategy::RoundRobin));
 }
 
+#[test]
+fn strategy_builder_build_random_should_succeed() {
+    // Given: a StrategyBuilder with Random strategy
+    let builder = StrategyBuilder::new().with_strategy(Strategy::Random);
+
+    // When: building the strategy
+    let result = builder.build();
+
+    // Then: build succeeds with Random strategy
+    let strategy_service = result.expect("Failed to build strategy");
+    assert!(matches!(strategy_service.strategy(), Strategy::Random));
+}
+
 #[test]
 fn strategy_builder_build_weighted_round_robin_should_succeed() {
     // Given: a StrategyBuilder with WeightedRoundRobin strategy
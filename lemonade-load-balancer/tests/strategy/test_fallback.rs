@@ -0,0 +1,183 @@
+//! Tests for the fallback strategy
+//!
+use lemonade_load_balancer::prelude::*;
+use lemonade_load_balancer::strategy;
+use std::collections::BTreeSet;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Fails every pick with an internal error
+struct FailingStrategy;
+
+#[async_trait]
+impl StrategyService for FailingStrategy {
+    fn strategy(&self) -> Strategy {
+        Strategy::Custom("failing".to_string())
+    }
+
+    async fn pick_backend(
+        &self,
+        _ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        Err(StrategyError::UnexpectedError(
+            "scores unavailable".to_string(),
+        ))
+    }
+}
+
+/// Builds [`FailingStrategy`]
+struct FailingFactory;
+
+impl StrategyFactory for FailingFactory {
+    fn build(
+        &self,
+        _params: serde_json::Value,
+    ) -> Result<Arc<dyn StrategyService>, StrategyError> {
+        Ok(Arc::new(FailingStrategy))
+    }
+}
+
+/// Context over backends 0 and 1 picking with `strategy`
+fn two_backend_context(strategy: Strategy, fallback: Option<Strategy>) -> Arc<Context> {
+    let mut config = create_test_config_fast(
+        (0..2)
+            .map(|id| create_test_backend(id, None, Some(1)))
+            .collect(),
+        strategy,
+    );
+    config.fallback = fallback;
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+/// Ids of the backends `strategy` picked over `count` picks
+async fn picked(
+    strategy: &Arc<dyn StrategyService>,
+    ctx: &Arc<Context>,
+    count: usize,
+) -> BTreeSet<BackendId> {
+    let mut ids = BTreeSet::new();
+    for _ in 0..count {
+        let backend = strategy
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Failed to pick backend");
+        ids.insert(backend.id());
+    }
+    ids
+}
+
+#[tokio::test]
+async fn fallback_strategy_primary_error_falls_through_should_succeed() {
+    // Given: a failing primary strategy falling back to round robin
+    let ctx = two_backend_context(Strategy::RoundRobin, None);
+    let strategy: Arc<dyn StrategyService> = Arc::new(FallbackStrategy::new(
+        Arc::new(FailingStrategy),
+        Arc::new(RoundRobinStrategy::default()),
+    ));
+
+    // When: picking
+    let ids = picked(&strategy, &ctx, 4).await;
+
+    // Then: round robin serves every pick, and each fallback is counted
+    assert_eq!(ids, BTreeSet::from([0, 1]));
+    assert_eq!(ctx.strategy_fallbacks(), 4);
+    assert_eq!(strategy.strategy(), Strategy::Custom("failing".to_string()));
+}
+
+#[tokio::test]
+async fn fallback_strategy_primary_success_keeps_primary_should_succeed() {
+    // Given: a working primary strategy with a fallback
+    let ctx = two_backend_context(Strategy::RoundRobin, None);
+    ctx.routing_table().get(1).unwrap().increment_connection();
+    let strategy: Arc<dyn StrategyService> = Arc::new(FallbackStrategy::new(
+        Arc::new(LeastConnectionsStrategy::default()),
+        Arc::new(RoundRobinStrategy::default()),
+    ));
+
+    // When: picking
+    let ids = picked(&strategy, &ctx, 4).await;
+
+    // Then: the primary strategy serves every pick
+    assert_eq!(ids, BTreeSet::from([0]));
+    assert_eq!(ctx.strategy_fallbacks(), 0);
+}
+
+#[test]
+fn fallback_strategy_explains_primary_should_succeed() {
+    // Given: an adaptive primary strategy and the reverse pairing
+    let ctx = two_backend_context(Strategy::Adaptive, Some(Strategy::RoundRobin));
+    let reversed: Arc<dyn StrategyService> = Arc::new(FallbackStrategy::new(
+        Arc::new(RoundRobinStrategy::default()),
+        Arc::new(AdaptiveStrategy::default()),
+    ));
+
+    // When: explaining picks
+    let explained = ctx.strategy().explain_pick(&ctx);
+
+    // Then: only the primary strategy is explained
+    assert_eq!(explained.len(), 2);
+    assert!(reversed.explain_pick(&ctx).is_empty());
+}
+
+#[tokio::test]
+async fn fallback_strategy_no_backend_available_should_fail() {
+    // Given: every backend unhealthy
+    let ctx = two_backend_context(Strategy::LeastConnections, Some(Strategy::RoundRobin));
+    for backend in ctx.routing_table().all_backends() {
+        backend.set_health(false, ctx.clock().now_millis());
+    }
+
+    // When: picking
+    let result = ctx.strategy().pick_backend(ctx.clone()).await;
+
+    // Then: the error is terminal and the fallback is not tried
+    assert!(matches!(result, Err(StrategyError::NoBackendAvailable)));
+    assert_eq!(ctx.strategy_fallbacks(), 0);
+}
+
+#[tokio::test]
+async fn fallback_strategy_built_from_config_should_succeed() {
+    // Given: a registered failing strategy, configured with a fallback
+    strategy::register("failing_with_fallback", Arc::new(FailingFactory))
+        .expect("Registration should succeed");
+    let custom = Strategy::Custom("failing_with_fallback".to_string());
+    let ctx = two_backend_context(custom.clone(), Some(Strategy::RoundRobin));
+
+    // When: picking through the context's strategy
+    let picked = ctx
+        .strategy()
+        .pick_backend_matching(ctx.clone(), &LabelSelector::default())
+        .await;
+
+    // Then: the fallback picks, under the primary strategy's name
+    assert!(picked.is_ok());
+    assert_eq!(
+        ctx.strategy().strategy(),
+        Strategy::Custom("failing".to_string())
+    );
+    assert_eq!(ctx.strategy_fallbacks(), 1);
+
+    // And: without a fallback the error reaches the caller
+    let ctx = two_backend_context(custom, None);
+    let result = ctx.strategy().pick_backend(ctx.clone()).await;
+    assert!(matches!(result, Err(StrategyError::UnexpectedError(_))));
+    assert_eq!(ctx.strategy_fallbacks(), 0);
+}
+
+#[test]
+fn fallback_strategy_config_validate_should_fail() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1))],
+        Strategy::Adaptive,
+    );
+    config.fallback = Some(Strategy::RoundRobin);
+    assert!(config.validate().is_ok());
+
+    // Then: a fallback repeating the primary strategy is rejected
+    config.fallback = Some(Strategy::Adaptive);
+    assert!(matches!(config.validate(), Err(ConfigError::Strategy(_))));
+
+    // And: so is an unregistered custom fallback
+    config.fallback = Some(Strategy::Custom("unregistered_fallback".to_string()));
+    assert!(matches!(config.validate(), Err(ConfigError::Strategy(_))));
+}
@@ -0,0 +1,164 @@
+//! Tests for the header hash strategy
+//!
+use lemonade_load_balancer::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Header hash context over backends `0..count`, with `strategy_params`
+fn header_hash_context(count: u8, params: serde_json::Value) -> Arc<Context> {
+    let mut config = create_test_config_fast(
+        (0..count)
+            .map(|id| create_test_backend(id, None, Some(1)))
+            .collect(),
+        Strategy::HeaderHash,
+    );
+    config.strategy_params = params;
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+/// Request for `path` carrying `headers`
+fn request(path: &str, headers: &[(&str, &str)]) -> RequestMeta {
+    RequestMeta {
+        method: "GET".to_string(),
+        path: path.to_string(),
+        headers: headers
+            .iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.to_string()))
+            .collect(),
+    }
+}
+
+/// Backend picked for `request`
+async fn pick(ctx: &Arc<Context>, request: &RequestMeta) -> BackendId {
+    ctx.strategy()
+        .pick_backend_for_request(ctx.clone(), &LabelSelector::default(), request)
+        .await
+        .expect("Failed to pick backend")
+        .id()
+}
+
+/// Backend picked for each of 64 tenants
+async fn tenant_map(ctx: &Arc<Context>) -> BTreeMap<String, BackendId> {
+    let mut map = BTreeMap::new();
+    for tenant in (0..64).map(|i| format!("tenant-{}", i)) {
+        let backend_id = pick(ctx, &request("/", &[("X-Tenant-Id", &tenant)])).await;
+        map.insert(tenant, backend_id);
+    }
+    map
+}
+
+#[tokio::test]
+async fn header_hash_same_header_same_backend_should_succeed() {
+    // Given: a header hash strategy over four backends hashing X-Tenant-Id
+    let ctx = header_hash_context(4, serde_json::json!({ "hash_on": "X-Tenant-Id" }));
+
+    // When: picking repeatedly for the same tenants
+    let first = tenant_map(&ctx).await;
+
+    // Then: every tenant keeps its backend, whatever the path or header case
+    assert_eq!(tenant_map(&ctx).await, first);
+    for (tenant, backend_id) in &first {
+        let other_path = request("/other", &[("x-tenant-id", tenant)]);
+        assert_eq!(pick(&ctx, &other_path).await, *backend_id);
+    }
+
+    // And: tenants spread over every backend
+    let used: BTreeSet<_> = first.values().copied().collect();
+    assert_eq!(used, BTreeSet::from([0, 1, 2, 3]));
+}
+
+#[tokio::test]
+async fn header_hash_missing_header_round_robin_should_succeed() {
+    // Given: a header hash strategy over three backends
+    let ctx = header_hash_context(3, serde_json::json!({ "hash_on": "X-Tenant-Id" }));
+
+    // When: picking for requests without the header, or with it empty
+    let mut picked = Vec::new();
+    for headers in [&[][..], &[("X-Tenant-Id", "")][..], &[("Host", "a")][..]] {
+        picked.push(pick(&ctx, &request("/", headers)).await);
+    }
+
+    // Then: the picks rotate over the backends
+    let unique: BTreeSet<_> = picked.iter().copied().collect();
+    assert_eq!(unique.len(), 3, "picks {:?} should round robin", picked);
+}
+
+#[tokio::test]
+async fn header_hash_on_path_should_succeed() {
+    // Given: a header hash strategy hashing the request path
+    let ctx = header_hash_context(4, serde_json::json!({ "hash_on": "path" }));
+
+    // When/Then: a path keeps its backend whatever the headers
+    for path in ["/a", "/b/c", "/tenants/42"] {
+        let backend_id = pick(&ctx, &request(path, &[])).await;
+        for _ in 0..4 {
+            let with_header = request(path, &[("X-Tenant-Id", "x")]);
+            assert_eq!(pick(&ctx, &with_header).await, backend_id);
+        }
+    }
+}
+
+#[tokio::test]
+async fn header_hash_backend_set_change_moves_only_its_keys_should_succeed() {
+    // Given: tenants mapped over four backends
+    let ctx = header_hash_context(4, serde_json::json!({ "hash_on": "X-Tenant-Id" }));
+    let before = tenant_map(&ctx).await;
+
+    // When: backend 2 goes unhealthy
+    ctx.routing_table()
+        .get(2)
+        .expect("backend 2")
+        .set_health(false, ctx.clock().now_millis());
+    let unhealthy = tenant_map(&ctx).await;
+
+    // Then: only backend 2's tenants move, and none go to it
+    for (tenant, backend_id) in &before {
+        if *backend_id == 2 {
+            assert_ne!(unhealthy[tenant], 2);
+        } else {
+            assert_eq!(unhealthy[tenant], *backend_id, "{} moved", tenant);
+        }
+    }
+
+    // And: once it recovers, every tenant goes back
+    ctx.routing_table()
+        .get(2)
+        .expect("backend 2")
+        .set_health(true, ctx.clock().now_millis());
+    assert_eq!(tenant_map(&ctx).await, before);
+
+    // When: a migration removes backend 3
+    let mut config = (*ctx.config()).clone();
+    config.backends.retain(|backend| backend.id != 3);
+    ctx.migrate(config).await.expect("Failed to migrate");
+
+    // Then: only backend 3's tenants move
+    let migrated = tenant_map(&ctx).await;
+    for (tenant, backend_id) in &before {
+        if *backend_id != 3 {
+            assert_eq!(migrated[tenant], *backend_id, "{} moved", tenant);
+        }
+    }
+    assert!(!migrated.values().any(|backend_id| *backend_id == 3));
+}
+
+#[tokio::test]
+async fn header_hash_without_request_round_robin_should_succeed() {
+    // Given: a header hash strategy over two backends
+    let ctx = header_hash_context(2, serde_json::json!({ "hash_on": "X-Tenant-Id" }));
+
+    // When: picking without a request (e.g. a non-HTTP connection)
+    let mut picked = BTreeSet::new();
+    for _ in 0..2 {
+        let backend = ctx
+            .strategy()
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Failed to pick backend");
+        picked.insert(backend.id());
+    }
+
+    // Then: the picks rotate over both backends
+    assert_eq!(picked, BTreeSet::from([0, 1]));
+}
@@ -0,0 +1,175 @@
+//! Tests for the least bandwidth strategy
+//!
+use lemonade_load_balancer::prelude::*;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Length of every flush interval in these tests
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// Least bandwidth context over backends 0 and 1 on `clock`, with the
+/// aggregating metrics service running
+fn bandwidth_context(
+    clock: &Arc<MockClock>,
+) -> (Arc<Context>, tokio::task::JoinHandle<()>) {
+    let config = create_test_config_fast(
+        (0..2)
+            .map(|id| create_test_backend(id, None, Some(1)))
+            .collect(),
+        Strategy::LeastBandwidth,
+    );
+    let ctx = Arc::new(
+        Context::with_clock(config.clone(), clock.clone())
+            .expect("Failed to create context"),
+    );
+    let service =
+        AggregatingMetricsService::new(Arc::new(ArcSwap::from_pointee(config.metrics)))
+            .expect("Failed to create service");
+    let handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.collect_metrics(ctx).await }
+    });
+    (ctx, handle)
+}
+
+/// Connection to `backend_id` closed now, having moved `bytes_in` and
+/// `bytes_out`
+fn closed(
+    ctx: &Context,
+    backend_id: BackendId,
+    bytes_in: u64,
+    bytes_out: u64,
+) -> MetricsEvent {
+    MetricsEvent::ConnectionClosed {
+        backend_id,
+        duration_micros: 1_000,
+        connect_micros: 100,
+        ttfb_micros: Some(200),
+        bytes_in,
+        bytes_out,
+        close_reason: CloseReason::Normal,
+        peer: PeerInfo::new("127.0.0.1:50000".parse().unwrap()),
+        at_micros: ctx.clock().now_micros(),
+    }
+}
+
+/// Send `events`, then flush and wait until the service has flushed
+async fn send_and_flush(ctx: &Context, events: Vec<MetricsEvent>) {
+    let metrics_tx = ctx.channels().metrics_tx();
+    for event in events {
+        metrics_tx.send(event).await.expect("Failed to send event");
+    }
+    metrics_tx
+        .send(MetricsEvent::FlushSnapshot)
+        .await
+        .expect("Failed to send flush");
+    let now_ms = ctx.clock().now_millis();
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while ctx
+            .routing_table()
+            .all_backends()
+            .iter()
+            .any(|backend| backend.metrics_snapshot().last_updated_ms != now_ms)
+        {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    })
+    .await
+    .expect("Flush should be processed");
+}
+
+/// Ids of the backends picked over `count` picks
+async fn picked(ctx: &Arc<Context>, count: usize) -> BTreeSet<BackendId> {
+    let mut ids = BTreeSet::new();
+    for _ in 0..count {
+        let backend = ctx
+            .strategy()
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Failed to pick backend");
+        ids.insert(backend.id());
+    }
+    ids
+}
+
+#[tokio::test]
+async fn least_bandwidth_favors_quieter_backend_should_succeed() {
+    // Given: a running metrics service with the byte rate window started
+    let clock = Arc::new(MockClock::new(100_000_000));
+    let (ctx, handle) = bandwidth_context(&clock);
+    send_and_flush(&ctx, Vec::new()).await;
+
+    // When: backend 0 serves large downloads over one interval while
+    // backend 1 serves small ones
+    clock.advance(INTERVAL);
+    send_and_flush(
+        &ctx,
+        vec![
+            closed(&ctx, 0, 4_000_000, 1_000),
+            closed(&ctx, 0, 6_000_000, 1_000),
+            closed(&ctx, 1, 20_000, 500),
+        ],
+    )
+    .await;
+
+    // Then: the rates are bytes per second over the interval
+    let routing = ctx.routing_table();
+    let busy = routing.get(0).unwrap().metrics_snapshot();
+    assert_eq!(
+        (busy.bytes_in_rate, busy.bytes_out_rate),
+        (10_000_000, 2_000)
+    );
+    let quiet = routing.get(1).unwrap().metrics_snapshot();
+    assert_eq!((quiet.bytes_in_rate, quiet.bytes_out_rate), (20_000, 500));
+
+    // Then: every pick goes to the quieter backend
+    assert_eq!(picked(&ctx, 20).await, BTreeSet::from([1]));
+
+    // When: backend 1 moves more over the next interval than backend 0 did
+    // over both
+    clock.advance(INTERVAL);
+    send_and_flush(&ctx, vec![closed(&ctx, 1, 30_000_000, 0)]).await;
+
+    // Then: picks follow the window to backend 0
+    assert_eq!(routing.get(0).unwrap().byte_rates(), (5_000_000, 1_000));
+    assert_eq!(picked(&ctx, 20).await, BTreeSet::from([0]));
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), handle).await;
+}
+
+#[tokio::test]
+async fn least_bandwidth_without_rates_picks_fewest_connections_should_succeed() {
+    // Given: no byte rates yet, and a connection open on backend 0
+    let (ctx, handle) = bandwidth_context(&Arc::new(MockClock::new(100_000_000)));
+    ctx.routing_table().get(0).unwrap().increment_connection();
+
+    // When: picking
+    let ids = picked(&ctx, 5).await;
+
+    // Then: the tie goes to the backend with fewer connections
+    assert_eq!(ids, BTreeSet::from([1]));
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), handle).await;
+}
+
+#[tokio::test]
+async fn least_bandwidth_all_unhealthy_should_fail() {
+    // Given: every backend unhealthy
+    let (ctx, handle) = bandwidth_context(&Arc::new(MockClock::new(100_000_000)));
+    for backend in ctx.routing_table().all_backends() {
+        backend.set_health(false, ctx.clock().now_millis());
+    }
+
+    // When: picking
+    let result = ctx.strategy().pick_backend(ctx.clone()).await;
+
+    // Then: no backend is available
+    assert!(matches!(result, Err(StrategyError::NoBackendAvailable)));
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    let _ = tokio::time::timeout(Duration::from_millis(100), handle).await;
+}
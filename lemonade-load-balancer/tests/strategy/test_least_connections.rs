@@ -2,7 +2,9 @@
 //!
 use lemonade_load_balancer::prelude::*;
 
-use crate::common::fixtures::{create_test_backend, create_test_context};
+use crate::common::fixtures::{
+    create_test_backend, create_test_backends, create_test_context,
+};
 
 #[test]
 fn least_connections_strategy_strategy_should_succeed() {
@@ -45,7 +47,7 @@ async fn least_connections_strategy_pick_backend_with_least_connections_should_s
         .expect("Failed to pick backend");
 
     // Backend 1 has least connections (2)
-    assert_eq!(backend.id(), &1u8);
+    assert_eq!(backend.id(), 1u8);
 }
 
 #[tokio::test]
@@ -72,7 +74,7 @@ async fn least_connections_strategy_pick_backend_with_equal_connections_should_s
         .expect("Failed to pick backend");
 
     // Either backend can be selected (tie-breaking)
-    assert!(*backend.id() == 0 || *backend.id() == 1);
+    assert!(backend.id() == 0 || backend.id() == 1);
 }
 
 #[tokio::test]
@@ -110,7 +112,7 @@ async fn least_connections_strategy_pick_backend_with_zero_connections_should_su
         .expect("Failed to pick backend");
 
     // Either backend can be selected (both have zero connections)
-    assert!(*backend.id() == 0 || *backend.id() == 1);
+    assert!(backend.id() == 0 || backend.id() == 1);
 }
 
 #[test]
@@ -118,3 +120,186 @@ fn least_connections_strategy_default_should_succeed() {
     let strategy = LeastConnectionsStrategy::default();
     assert!(matches!(strategy.strategy(), Strategy::LeastConnections));
 }
+
+/// Smallest active connection count among healthy backends
+fn true_minimum(ctx: &Context) -> usize {
+    ctx.routing_table()
+        .healthy_backends()
+        .iter()
+        .map(|backend| backend.active_connections())
+        .min()
+        .expect("at least one healthy backend")
+}
+
+#[test]
+fn least_connections_strategy_from_params_should_succeed() {
+    // Given/When: default, explicit and invalid parameters
+    let default = LeastConnectionsStrategy::from_params(serde_json::Value::Null)
+        .expect("null params should use defaults");
+    let explicit = LeastConnectionsStrategy::from_params(
+        serde_json::json!({ "index_min_backends": 8 }),
+    )
+    .expect("valid params");
+    let invalid = LeastConnectionsStrategy::from_params(
+        serde_json::json!({ "index_min_backends": "many" }),
+    );
+
+    // Then: the crossover is read from the parameters
+    assert_eq!(default.index_min_backends(), 64);
+    assert_eq!(explicit.index_min_backends(), 8);
+    assert!(invalid.is_err());
+}
+
+#[tokio::test]
+async fn least_connections_strategy_indexed_pick_is_true_minimum_should_succeed() {
+    // Given: 200 backends with scattered connection counts, always indexed
+    let strategy = LeastConnectionsStrategy::new(0);
+    let ctx = create_test_context(create_test_backends(200));
+    let routing = ctx.routing_table();
+    for backend in routing.all_backends() {
+        let id = backend.id() as usize;
+        for _ in 0..(id * 37 % 23) + 3 {
+            backend.increment_connection();
+        }
+    }
+
+    for round in 0..300 {
+        // When: picking and then connecting to the picked backend
+        let picked = strategy
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Failed to pick backend");
+
+        // Then: the pick has no more connections than the true minimum
+        assert_eq!(
+            picked.active_connections(),
+            true_minimum(&ctx),
+            "round {}",
+            round
+        );
+        picked.increment_connection();
+        if round % 3 == 0 {
+            routing
+                .get((round % 200) as u8)
+                .expect("backend")
+                .decrement_connection();
+        }
+    }
+}
+
+#[tokio::test]
+async fn least_connections_strategy_indexed_pick_skips_unhealthy_should_succeed() {
+    // Given: an indexed strategy where the least loaded backend is down
+    let strategy = LeastConnectionsStrategy::new(0);
+    let ctx = create_test_context(create_test_backends(3));
+    let routing = ctx.routing_table();
+    routing.get(1).unwrap().increment_connection();
+    routing.get(2).unwrap().increment_connection();
+    routing.get(2).unwrap().increment_connection();
+    routing.get(0).unwrap().set_health(false, 0);
+
+    // When: picking
+    let picked = strategy
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Failed to pick backend");
+
+    // Then: the least loaded healthy backend is picked
+    assert_eq!(picked.id(), 1);
+
+    // When: every backend is down
+    for backend in routing.all_backends() {
+        backend.set_health(false, 0);
+    }
+
+    // Then: no backend is available
+    assert!(matches!(
+        strategy.pick_backend(ctx).await,
+        Err(StrategyError::NoBackendAvailable)
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn least_connections_strategy_index_survives_concurrent_updates_should_succeed() {
+    // Given: an indexed strategy over 128 backends
+    let strategy = std::sync::Arc::new(LeastConnectionsStrategy::new(0));
+    let ctx = create_test_context(create_test_backends(128));
+    strategy
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Failed to build index");
+
+    // When: tasks open and close connections while others pick
+    let mut tasks = Vec::new();
+    for task in 0..8u64 {
+        let ctx = ctx.clone();
+        let strategy = strategy.clone();
+        tasks.push(tokio::spawn(async move {
+            for step in 0..500u64 {
+                let picked = strategy
+                    .pick_backend(ctx.clone())
+                    .await
+                    .expect("Failed to pick backend");
+                picked.increment_connection();
+                if (step + task) % 2 == 0 {
+                    picked.decrement_connection();
+                }
+                if step % 64 == 0 {
+                    tokio::task::yield_now().await;
+                }
+            }
+        }));
+    }
+    for task in tasks {
+        task.await.expect("task panicked");
+    }
+
+    // Then: once quiet, picks are exact again and within one connection of
+    // every other backend (the picks kept the pool balanced)
+    let picked = strategy
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Failed to pick backend");
+    let minimum = true_minimum(&ctx);
+    assert_eq!(picked.active_connections(), minimum);
+    let maximum = ctx
+        .routing_table()
+        .all_backends()
+        .iter()
+        .map(|backend| backend.active_connections())
+        .max()
+        .unwrap();
+    assert!(maximum - minimum <= 1, "min {} max {}", minimum, maximum);
+}
+
+#[tokio::test]
+async fn least_connections_strategy_index_rebuilds_on_route_table_swap_should_succeed() {
+    // Given: an indexed strategy that has served picks
+    let strategy = LeastConnectionsStrategy::new(0);
+    let ctx = create_test_context(create_test_backends(2));
+    for backend in ctx.routing_table().all_backends() {
+        backend.increment_connection();
+    }
+    strategy
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Failed to pick backend");
+
+    // When: a config migration adds an idle backend
+    let mut config = (*ctx.config()).clone();
+    config
+        .backends
+        .push(BackendConfig::from(create_test_backend(
+            2,
+            None,
+            Some(10u8),
+        )));
+    ctx.migrate(config).await.expect("Failed to migrate");
+
+    // Then: the new backend is indexed and picked
+    let picked = strategy
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Failed to pick backend");
+    assert_eq!(picked.id(), 2);
+}
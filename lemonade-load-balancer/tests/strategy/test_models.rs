@@ -6,7 +6,10 @@ use rstest::*;
 #[rstest]
 #[case("adaptive", Strategy::Adaptive)]
 #[case("fastest_response_time", Strategy::FastestResponseTime)]
+#[case("header_hash", Strategy::HeaderHash)]
+#[case("least_bandwidth", Strategy::LeastBandwidth)]
 #[case("least_connections", Strategy::LeastConnections)]
+#[case("random", Strategy::Random)]
 #[case("round_robin", Strategy::RoundRobin)]
 #[case("weighted_round_robin", Strategy::WeightedRoundRobin)]
 fn strategy_from_str_should_succeed(#[case] input: &str, #[case] expected: Strategy) {
@@ -16,18 +19,41 @@ fn strategy_from_str_should_succeed(#[case] input: &str, #[case] expected: Strat
 }
 
 #[rstest]
-#[case("invalid_strategy")]
+#[case("my_strategy")]
+#[case("ADAPTIVE")] // case sensitive, so not the built-in
+#[case("vendor.sticky-v2")]
+fn strategy_from_str_unknown_is_custom_should_succeed(#[case] input: &str) {
+    let result = input.parse::<Strategy>();
+    assert_eq!(result.unwrap(), Strategy::Custom(input.to_string()));
+}
+
+#[rstest]
 #[case("")]
-#[case("ADAPTIVE")] // case sensitive
+#[case("has space")]
+#[case("slash/name")]
 fn strategy_from_str_invalid_should_fail(#[case] input: &str) {
     let result = input.parse::<Strategy>();
     assert!(result.is_err());
 }
 
+#[rstest]
+#[case(Strategy::RoundRobin, "\"round_robin\"")]
+#[case(Strategy::Custom("my_strategy".to_string()), "\"my_strategy\"")]
+fn strategy_serializes_as_name_should_succeed(
+    #[case] strategy: Strategy,
+    #[case] json: &str,
+) {
+    assert_eq!(serde_json::to_string(&strategy).unwrap(), json);
+    assert_eq!(serde_json::from_str::<Strategy>(json).unwrap(), strategy);
+}
+
 #[rstest]
 #[case(Strategy::Adaptive, "adaptive")]
 #[case(Strategy::FastestResponseTime, "fastest_response_time")]
+#[case(Strategy::HeaderHash, "header_hash")]
+#[case(Strategy::LeastBandwidth, "least_bandwidth")]
 #[case(Strategy::LeastConnections, "least_connections")]
+#[case(Strategy::Random, "random")]
 #[case(Strategy::RoundRobin, "round_robin")]
 #[case(Strategy::WeightedRoundRobin, "weighted_round_robin")]
 fn strategy_as_ref_should_succeed(#[case] strategy: Strategy, #[case] expected: &str) {
@@ -37,7 +63,10 @@ fn strategy_as_ref_should_succeed(#[case] strategy: Strategy, #[case] expected:
 #[rstest]
 #[case(Strategy::Adaptive)]
 #[case(Strategy::FastestResponseTime)]
+#[case(Strategy::HeaderHash)]
+#[case(Strategy::LeastBandwidth)]
 #[case(Strategy::LeastConnections)]
+#[case(Strategy::Random)]
 #[case(Strategy::RoundRobin)]
 #[case(Strategy::WeightedRoundRobin)]
 fn strategy_clone_should_succeed(#[case] strategy: Strategy) {
@@ -57,9 +86,13 @@ fn strategy_round_trip_parse_all_variants() {
     let strategies = vec![
         Strategy::Adaptive,
         Strategy::FastestResponseTime,
+        Strategy::HeaderHash,
+        Strategy::LeastBandwidth,
         Strategy::LeastConnections,
+        Strategy::Random,
         Strategy::RoundRobin,
         Strategy::WeightedRoundRobin,
+        Strategy::Custom("my_strategy".to_string()),
     ];
 
     for strategy in strategies {
@@ -0,0 +1,123 @@
+//! Tests for priority tiers
+//!
+use lemonade_load_balancer::prelude::*;
+use rstest::rstest;
+use std::collections::BTreeSet;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Context with primaries 0 and 1 (priority unset) and backups 2 and 3
+/// (priority 1)
+fn tiered_context(strategy: Strategy, params: serde_json::Value) -> Arc<Context> {
+    let mut config = create_test_config_fast(
+        (0..4)
+            .map(|id| create_test_backend(id, None, Some(1)))
+            .collect(),
+        strategy,
+    );
+    config.strategy_params = params;
+    for backend in &mut config.backends {
+        backend.priority = (backend.id >= 2).then_some(1);
+    }
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+/// Ids of the backends picked over `count` picks
+async fn picked(ctx: &Arc<Context>, count: usize) -> BTreeSet<BackendId> {
+    let mut ids = BTreeSet::new();
+    for _ in 0..count {
+        let backend = ctx
+            .strategy()
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Failed to pick backend");
+        ids.insert(backend.id());
+    }
+    ids
+}
+
+/// Set the health of backends `ids`
+fn set_health(ctx: &Context, ids: &[BackendId], alive: bool) {
+    for id in ids {
+        ctx.routing_table()
+            .get(*id)
+            .unwrap()
+            .set_health(alive, ctx.clock().now_millis());
+    }
+}
+
+#[rstest]
+#[case(Strategy::RoundRobin, serde_json::Value::Null)]
+#[case(Strategy::LeastConnections, serde_json::Value::Null)]
+#[case(Strategy::LeastConnections, serde_json::json!({ "index_min_backends": 0 }))]
+#[case(Strategy::WeightedRoundRobin, serde_json::Value::Null)]
+#[case(Strategy::Random, serde_json::Value::Null)]
+#[case(Strategy::FastestResponseTime, serde_json::Value::Null)]
+#[case(Strategy::LeastBandwidth, serde_json::Value::Null)]
+#[case(Strategy::Adaptive, serde_json::Value::Null)]
+#[tokio::test]
+async fn priority_tiers_fail_over_and_back_should_succeed(
+    #[case] strategy: Strategy,
+    #[case] params: serde_json::Value,
+) {
+    // Given: healthy primaries and backups
+    let ctx = tiered_context(strategy, params);
+
+    // Then: only the primaries are picked
+    assert!(picked(&ctx, 20).await.is_subset(&BTreeSet::from([0, 1])));
+
+    // When: one primary fails
+    set_health(&ctx, &[0], false);
+
+    // Then: the other primary takes all the traffic
+    assert_eq!(picked(&ctx, 20).await, BTreeSet::from([1]));
+
+    // When: the last primary fails
+    set_health(&ctx, &[1], false);
+
+    // Then: the very next picks go to the backups
+    let backups = picked(&ctx, 20).await;
+    assert!(!backups.is_empty());
+    assert!(backups.is_subset(&BTreeSet::from([2, 3])), "{:?}", backups);
+
+    // When: a primary recovers
+    set_health(&ctx, &[0], true);
+
+    // Then: traffic goes back to it
+    assert_eq!(picked(&ctx, 20).await, BTreeSet::from([0]));
+}
+
+#[tokio::test]
+async fn priority_tiers_all_unhealthy_should_fail() {
+    // Given: every backend of every tier unhealthy
+    let ctx = tiered_context(Strategy::RoundRobin, serde_json::Value::Null);
+    set_health(&ctx, &[0, 1, 2, 3], false);
+
+    // When: picking
+    let result = ctx.strategy().pick_backend(ctx.clone()).await;
+
+    // Then: no backend is available
+    assert!(matches!(result, Err(StrategyError::NoBackendAvailable)));
+}
+
+#[tokio::test]
+async fn priority_tiers_trace_standby_backups_should_succeed() {
+    // Given: a traced context with healthy primaries
+    let ctx = tiered_context(Strategy::RoundRobin, serde_json::Value::Null);
+    let mut config = ctx.config().as_ref().clone();
+    config.metrics.selection_trace = true;
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+
+    // When: picking twice
+    picked(&ctx, 2).await;
+
+    // Then: the backups are counted as standby, the primaries not at all
+    let routing = ctx.routing_table();
+    for id in [2, 3] {
+        assert_eq!(
+            routing.get(id).unwrap().selection_exclusions(),
+            [(ExclusionReason::Standby, 2)]
+        );
+    }
+    assert!(routing.get(0).unwrap().selection_exclusions().is_empty());
+}
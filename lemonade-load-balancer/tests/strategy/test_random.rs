@@ -0,0 +1,138 @@
+//! Tests for Random strategy
+//!
+use lemonade_load_balancer::prelude::*;
+
+use crate::common::fixtures::{
+    create_test_backend, create_test_config_fast, create_test_context,
+};
+
+/// Seeded context routing to `backends` with the random strategy
+fn seeded_context(backends: Vec<BackendMeta>) -> Arc<Context> {
+    let mut config = create_test_config_fast(backends, Strategy::Random);
+    config.rng_seed = Some(42);
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+/// Picks per backend id over `count` picks
+async fn pick_counts(
+    strategy: &RandomStrategy,
+    ctx: &Arc<Context>,
+    count: usize,
+) -> [usize; 4] {
+    let mut counts = [0; 4];
+    for _ in 0..count {
+        let backend = strategy
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Failed to pick backend");
+        counts[backend.id() as usize] += 1;
+    }
+    counts
+}
+
+#[test]
+fn random_strategy_strategy_should_succeed() {
+    let strategy = RandomStrategy::new();
+    assert!(matches!(strategy.strategy(), Strategy::Random));
+}
+
+#[tokio::test]
+async fn random_strategy_pick_backend_proportional_to_weights_should_succeed() {
+    // Given: backends weighted 3, 1 and unset (1)
+    let strategy = RandomStrategy::new();
+    let ctx = seeded_context(vec![
+        create_test_backend(0, None, Some(3)),
+        create_test_backend(1, None, Some(1)),
+        create_test_backend(2, None, None),
+    ]);
+
+    // When: picking many times
+    let counts = pick_counts(&strategy, &ctx, 10_000).await;
+
+    // Then: picks follow the 3:1:1 weights within a few percent
+    assert!((5_700..6_300).contains(&counts[0]), "{:?}", counts);
+    assert!((1_700..2_300).contains(&counts[1]), "{:?}", counts);
+    assert!((1_700..2_300).contains(&counts[2]), "{:?}", counts);
+}
+
+#[tokio::test]
+async fn random_strategy_pick_backend_skips_unhealthy_should_succeed() {
+    // Given: backends weighted 6, 1 and 3, the heaviest one unhealthy
+    let strategy = RandomStrategy::new();
+    let ctx = seeded_context(vec![
+        create_test_backend(0, None, Some(6)),
+        create_test_backend(1, None, Some(1)),
+        create_test_backend(2, None, Some(3)),
+    ]);
+    ctx.routing_table().get(0).unwrap().set_health(false, 1000);
+
+    // When: picking many times
+    let counts = pick_counts(&strategy, &ctx, 10_000).await;
+
+    // Then: the unhealthy backend is never picked and the others keep 1:3
+    assert_eq!(counts[0], 0);
+    assert!((2_200..2_800).contains(&counts[1]), "{:?}", counts);
+    assert!((7_200..7_800).contains(&counts[2]), "{:?}", counts);
+}
+
+#[tokio::test]
+async fn random_strategy_pick_backend_after_weight_change_should_succeed() {
+    // Given: two equally weighted backends, picked once to cache the weights
+    let strategy = RandomStrategy::new();
+    let ctx = seeded_context(vec![
+        create_test_backend(0, None, Some(1)),
+        create_test_backend(1, None, Some(1)),
+    ]);
+    let _ = pick_counts(&strategy, &ctx, 1).await;
+
+    // When: backend 0's weight drops to 0
+    ctx.set_backend_weight(0, Some(0));
+
+    // Then: the cached weights are rebuilt and backend 0 is no longer picked
+    let counts = pick_counts(&strategy, &ctx, 1_000).await;
+    assert_eq!(counts, [0, 1_000, 0, 0]);
+}
+
+#[tokio::test]
+async fn random_strategy_pick_backend_with_zero_weights_should_fail() {
+    let strategy = RandomStrategy::new();
+    let ctx = create_test_context(vec![
+        create_test_backend(0, None, Some(0)),
+        create_test_backend(1, None, Some(0)),
+    ]);
+
+    let result = strategy.pick_backend(ctx).await;
+    assert!(matches!(result, Err(StrategyError::NoBackendAvailable)));
+}
+
+#[tokio::test]
+async fn random_strategy_pick_backend_with_empty_healthy_should_fail() {
+    let strategy = RandomStrategy::new();
+    let ctx = create_test_context(vec![create_test_backend(0, None, Some(1))]);
+    ctx.routing_table().get(0).unwrap().set_health(false, 1000);
+
+    let result = strategy.pick_backend(ctx).await;
+    assert!(matches!(result, Err(StrategyError::NoBackendAvailable)));
+}
+
+#[tokio::test]
+async fn random_strategy_from_config_should_succeed() {
+    // Given: a config using the random strategy by name
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1))],
+        "random".parse().expect("Failed to parse strategy"),
+    );
+    config.rng_seed = Some(7);
+
+    // When: building the context
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+
+    // Then: it routes with the random strategy
+    assert!(matches!(ctx.strategy().strategy(), Strategy::Random));
+    let backend = ctx
+        .strategy()
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Failed to pick backend");
+    assert_eq!(backend.id(), 0);
+}
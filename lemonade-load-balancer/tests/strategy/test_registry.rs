@@ -0,0 +1,238 @@
+//! Strategy registry tests
+//!
+use lemonade_load_balancer::prelude::*;
+use lemonade_load_balancer::strategy;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Always picks the healthy backend with the lowest id
+struct AlwaysFirstStrategy {
+    name: String,
+}
+
+#[async_trait]
+impl StrategyService for AlwaysFirstStrategy {
+    fn strategy(&self) -> Strategy {
+        Strategy::Custom(self.name.clone())
+    }
+
+    async fn pick_backend(
+        &self,
+        ctx: Arc<Context>,
+    ) -> Result<Arc<Backend>, StrategyError> {
+        ctx.routing_table()
+            .healthy_backends()
+            .into_iter()
+            .min_by_key(|backend| backend.id())
+            .ok_or(StrategyError::NoBackendAvailable)
+    }
+}
+
+/// Builds [`AlwaysFirstStrategy`]; rejects non-object params
+struct AlwaysFirstFactory {
+    name: String,
+}
+
+impl StrategyFactory for AlwaysFirstFactory {
+    fn build(
+        &self,
+        params: serde_json::Value,
+    ) -> Result<Arc<dyn StrategyService>, StrategyError> {
+        if !(params.is_null() || params.is_object()) {
+            return Err(StrategyError::UnexpectedError(format!(
+                "invalid params: {}",
+                params
+            )));
+        }
+        Ok(Arc::new(AlwaysFirstStrategy {
+            name: self.name.clone(),
+        }))
+    }
+}
+
+/// Register the always-first strategy under a test-specific name
+fn register_always_first(name: &str) -> Strategy {
+    strategy::register(
+        name,
+        Arc::new(AlwaysFirstFactory {
+            name: name.to_string(),
+        }),
+    )
+    .expect("Registration should succeed");
+    Strategy::Custom(name.to_string())
+}
+
+#[test]
+fn strategy_registry_register_and_build_should_succeed() {
+    // Given: a registered custom strategy
+    let custom = register_always_first("always_first_build");
+
+    // When: building it through the strategy builder
+    let built = StrategyBuilder::new()
+        .with_strategy(custom.clone())
+        .with_params(serde_json::json!({ "ignored": true }))
+        .build()
+        .expect("Custom strategy should build");
+
+    // Then: the factory's strategy is returned
+    assert_eq!(built.strategy(), custom);
+    assert!(strategy::is_registered("always_first_build"));
+}
+
+#[test]
+fn strategy_registry_factory_params_error_should_fail() {
+    let custom = register_always_first("always_first_params");
+
+    let result = StrategyBuilder::new()
+        .with_strategy(custom)
+        .with_params(serde_json::json!(42))
+        .build();
+
+    assert!(matches!(result, Err(StrategyError::UnexpectedError(_))));
+}
+
+#[test]
+fn strategy_registry_unregistered_build_should_fail() {
+    let result = StrategyBuilder::new()
+        .with_strategy(Strategy::Custom("never_registered".to_string()))
+        .build();
+
+    assert!(matches!(result, Err(StrategyError::NotFound(_))));
+}
+
+#[test]
+fn strategy_registry_builtin_name_should_fail() {
+    let result = strategy::register(
+        "round_robin",
+        Arc::new(AlwaysFirstFactory {
+            name: "round_robin".to_string(),
+        }),
+    );
+
+    assert!(result.is_err());
+    assert!(!strategy::is_registered("round_robin"));
+}
+
+#[test]
+fn strategy_registry_unregister_should_succeed() {
+    register_always_first("always_first_unregister");
+
+    assert!(strategy::unregister("always_first_unregister").is_some());
+    assert!(strategy::lookup("always_first_unregister").is_none());
+}
+
+#[test]
+fn config_validate_unregistered_custom_strategy_should_fail() {
+    // Given: a config naming a strategy nobody registered
+    let config = create_test_config_fast(
+        vec![create_test_backend(0, None, None)],
+        Strategy::Custom("missing_factory".to_string()),
+    );
+
+    // When/Then: validation fails fast
+    assert!(matches!(config.validate(), Err(ConfigError::Strategy(_))));
+}
+
+#[test]
+fn config_from_file_custom_strategy_should_succeed() {
+    // Given: a registered strategy named in a config file
+    register_always_first("always_first_file");
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, None)],
+        Strategy::Custom("always_first_file".to_string()),
+    );
+    config.strategy_params = serde_json::json!({ "mode": "first" });
+    std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+    // When: loading the file
+    let loaded = ConfigBuilder::from_file(Some(config_path)).expect("Config should load");
+
+    // Then: the custom strategy and its params survive the round trip
+    assert_eq!(
+        loaded.strategy,
+        Strategy::Custom("always_first_file".to_string())
+    );
+    assert_eq!(
+        loaded.strategy_params,
+        serde_json::json!({ "mode": "first" })
+    );
+}
+
+/// Backend that writes its name and closes
+async fn named_backend(name: &'static str) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let _ =
+                tokio::io::AsyncWriteExt::write_all(&mut stream, name.as_bytes()).await;
+        }
+    });
+    (addr, handle)
+}
+
+#[tokio::test]
+async fn custom_strategy_routes_proxy_traffic_should_succeed() {
+    // Given: two backends and a proxy using the registered always-first strategy
+    let custom = register_always_first("always_first_traffic");
+    let (first_addr, first_handle) = named_backend("first").await;
+    let (second_addr, second_handle) = named_backend("second").await;
+    let proxy_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    let backends = vec![
+        BackendMeta::new(
+            1u8,
+            Some("second"),
+            BackendAddress::from(second_addr),
+            Some(1u8),
+        ),
+        BackendMeta::new(
+            0u8,
+            Some("first"),
+            BackendAddress::from(first_addr),
+            Some(1u8),
+        ),
+    ];
+    let mut config = create_test_config_fast(backends, custom.clone());
+    config.proxy.listen_address = proxy_addr;
+    config
+        .validate()
+        .expect("Registered strategy should validate");
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    assert_eq!(ctx.strategy().strategy(), custom);
+    let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy)))
+        .expect("Failed to create service");
+    let proxy_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: several clients connect through the proxy
+    for _ in 0..4 {
+        let mut client = tokio::net::TcpStream::connect(proxy_addr)
+            .await
+            .expect("Failed to connect to proxy");
+        let mut reply = String::new();
+        tokio::time::timeout(Duration::from_secs(2), client.read_to_string(&mut reply))
+            .await
+            .expect("Backend reply should arrive")
+            .expect("Failed to read reply");
+
+        // Then: every connection is served by the lowest-id backend
+        assert_eq!(reply, "first");
+    }
+
+    proxy_handle.abort();
+    first_handle.abort();
+    second_handle.abort();
+}
@@ -0,0 +1,164 @@
+//! Tests for the selection trace
+//!
+use lemonade_load_balancer::prelude::*;
+use rstest::rstest;
+use std::collections::BTreeMap;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Context with a backend in each excluded state, all but backend 4 labelled
+/// `group=web`:
+/// 0 healthy, 1 unhealthy, 2 draining, 3 zero weight, 4 in another group
+fn traced_context(strategy: Strategy, selection_trace: bool) -> Arc<Context> {
+    let mut config = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(1)),
+            create_test_backend(1, None, Some(1)),
+            create_test_backend(2, None, Some(1)),
+            create_test_backend(3, None, Some(0)),
+            create_test_backend(4, None, Some(1)),
+        ],
+        strategy,
+    );
+    for backend in &mut config.backends {
+        let group = if backend.id == 4 { "other" } else { "web" };
+        backend.labels = Labels::from([("group".to_string(), group.to_string())]);
+    }
+    config.metrics.selection_trace = selection_trace;
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let routing = ctx.routing_table();
+    routing.get(1).unwrap().set_health(false, 1000);
+    routing.get(2).unwrap().mark_draining();
+    ctx
+}
+
+/// Pick `count` times from the `group=web` backends
+async fn pick_web(ctx: &Arc<Context>, count: usize) {
+    let selector = LabelSelector::single("group", "web");
+    for _ in 0..count {
+        ctx.strategy()
+            .pick_backend_matching(ctx.clone(), &selector)
+            .await
+            .expect("Failed to pick backend");
+    }
+}
+
+/// Exclusion counts of a backend by reason name
+fn exclusions(ctx: &Context, id: BackendId) -> BTreeMap<&'static str, u64> {
+    ctx.routing_table()
+        .get(id)
+        .unwrap()
+        .selection_exclusions()
+        .into_iter()
+        .map(|(reason, count)| (reason.as_str(), count))
+        .collect()
+}
+
+#[rstest]
+#[case(Strategy::RoundRobin, false)]
+#[case(Strategy::LeastConnections, false)]
+#[case(Strategy::WeightedRoundRobin, true)]
+#[case(Strategy::Random, true)]
+#[case(Strategy::FastestResponseTime, false)]
+#[case(Strategy::LeastBandwidth, false)]
+#[case(Strategy::Adaptive, false)]
+#[tokio::test]
+async fn trace_exclusions_counts_each_reason_should_succeed(
+    #[case] strategy: Strategy,
+    #[case] weighted: bool,
+) {
+    // Given: a traced context with a backend in each excluded state
+    let ctx = traced_context(strategy, true);
+
+    // When: picking three times
+    pick_web(&ctx, 3).await;
+
+    // Then: every skipped backend is counted once per pick under its reason
+    assert!(exclusions(&ctx, 0).is_empty());
+    assert_eq!(exclusions(&ctx, 1), BTreeMap::from([("unhealthy", 3)]));
+    assert_eq!(exclusions(&ctx, 2), BTreeMap::from([("draining", 3)]));
+    if weighted {
+        assert_eq!(exclusions(&ctx, 3), BTreeMap::from([("zero_weight", 3)]));
+    } else {
+        assert!(exclusions(&ctx, 3).is_empty());
+    }
+    assert_eq!(exclusions(&ctx, 4), BTreeMap::from([("not_in_group", 3)]));
+}
+
+#[tokio::test]
+async fn trace_exclusions_disabled_counts_nothing_should_succeed() {
+    // Given: a context with the selection trace off (the default)
+    let ctx = traced_context(Strategy::WeightedRoundRobin, false);
+
+    // When: picking
+    pick_web(&ctx, 3).await;
+
+    // Then: no backend has exclusion counts
+    for id in 0..5 {
+        assert!(exclusions(&ctx, id).is_empty(), "backend {}", id);
+    }
+}
+
+#[tokio::test]
+async fn trace_exclusions_in_group_state_should_succeed() {
+    // Given: a traced context after a few picks
+    let ctx = traced_context(Strategy::RoundRobin, true);
+    pick_web(&ctx, 2).await;
+
+    // When: capturing the group state
+    let state = GroupState::capture(&ctx);
+
+    // Then: the counts appear on the backends of the snapshot
+    let counts: Vec<BTreeMap<String, u64>> = state
+        .backends
+        .iter()
+        .map(|backend| backend.selection_exclusions.clone())
+        .collect();
+    assert!(counts[0].is_empty());
+    assert_eq!(counts[1], BTreeMap::from([("unhealthy".to_string(), 2)]));
+    assert_eq!(counts[4], BTreeMap::from([("not_in_group".to_string(), 2)]));
+}
+
+#[test]
+fn exclusion_reason_of_prefers_first_reason_should_succeed() {
+    // Given: a draining, unhealthy, zero-weight backend outside the selector
+    let backend =
+        Backend::new(BackendConfig::from(create_test_backend(0, None, Some(0))));
+    backend.set_health(false, 1000);
+    backend.mark_draining();
+    let other = LabelSelector::single("group", "other");
+
+    // Then: it is counted under the first reason that applies
+    assert_eq!(
+        ExclusionReason::of(&backend, &other, true, Some(0), None, None),
+        Some(ExclusionReason::NotInGroup)
+    );
+    let any = LabelSelector::default();
+    assert_eq!(
+        ExclusionReason::of(&backend, &any, true, Some(0), None, None),
+        Some(ExclusionReason::Draining)
+    );
+}
+
+#[test]
+fn exclusion_reason_of_pending_connect_cap_should_succeed() {
+    // Given: a healthy backend with one connect in flight
+    let backend =
+        Backend::new(BackendConfig::from(create_test_backend(0, None, Some(1))));
+    assert!(backend.try_reserve_connect(1));
+    let any = LabelSelector::default();
+
+    // Then: it is excluded only under a cap it has reached
+    assert_eq!(
+        ExclusionReason::of(&backend, &any, true, Some(0), Some(1), None),
+        Some(ExclusionReason::PendingConnects)
+    );
+    assert_eq!(
+        ExclusionReason::of(&backend, &any, true, Some(0), Some(2), None),
+        None
+    );
+    assert_eq!(
+        ExclusionReason::of(&backend, &any, true, Some(0), None, None),
+        None
+    );
+}
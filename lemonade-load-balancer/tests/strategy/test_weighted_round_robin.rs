@@ -1,8 +1,28 @@
 //! Tests for WeightedRoundRobin strategy
 //!
 use lemonade_load_balancer::prelude::*;
+use rstest::rstest;
 
-use crate::common::fixtures::{create_test_backend, create_test_context};
+use crate::common::fixtures::{
+    create_test_backend, create_test_config_fast, create_test_context,
+};
+
+/// Backend ids of the next `count` picks
+async fn pick_sequence(
+    strategy: &WeightedRoundRobinStrategy,
+    ctx: &Arc<Context>,
+    count: usize,
+) -> Vec<BackendId> {
+    let mut sequence = Vec::with_capacity(count);
+    for _ in 0..count {
+        let backend = strategy
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Failed to pick backend");
+        sequence.push(backend.id());
+    }
+    sequence
+}
 
 #[test]
 fn weighted_round_robin_strategy_strategy_should_succeed() {
@@ -28,7 +48,7 @@ async fn weighted_round_robin_strategy_pick_backend_with_weights_should_succeed(
             .pick_backend(ctx.clone())
             .await
             .expect("Failed to pick backend");
-        selected.push(*backend.id());
+        selected.push(backend.id());
     }
 
     // With weights 3:1:2, backend 0 should be selected more
@@ -55,7 +75,7 @@ async fn weighted_round_robin_strategy_pick_backend_with_equal_weights_should_su
             .pick_backend(ctx.clone())
             .await
             .expect("Failed to pick backend");
-        selected.push(*backend.id());
+        selected.push(backend.id());
     }
 
     // Should be approximately equal
@@ -121,8 +141,8 @@ async fn weighted_round_robin_strategy_pick_backend_with_none_weights_should_suc
         .expect("Failed to pick backend");
 
     // None weight defaults to 1
-    assert!(*backend1.id() == 0 || *backend1.id() == 1);
-    assert!(*backend2.id() == 0 || *backend2.id() == 1);
+    assert!(backend1.id() == 0 || backend1.id() == 1);
+    assert!(backend2.id() == 0 || backend2.id() == 1);
 }
 
 #[test]
@@ -130,3 +150,173 @@ fn weighted_round_robin_strategy_default_should_succeed() {
     let strategy = WeightedRoundRobinStrategy::default();
     assert!(matches!(strategy.strategy(), Strategy::WeightedRoundRobin));
 }
+
+#[rstest]
+#[case(&[3, 2], &[0, 1, 0, 1, 0])]
+#[case(&[2, 1], &[0, 1, 0])]
+#[case(&[1, 1, 1], &[0, 1, 2])]
+#[case(&[5, 1, 1], &[0, 0, 1, 0, 2, 0, 0])]
+#[case(&[4, 2, 1], &[0, 1, 0, 2, 0, 1, 0])]
+#[case(&[2, 0, 1], &[0, 2, 0])]
+#[tokio::test]
+async fn weighted_round_robin_strategy_pick_backend_interleaves_should_succeed(
+    #[case] weights: &[u8],
+    #[case] cycle: &[BackendId],
+) {
+    // Given: backends with the given weights
+    let strategy = WeightedRoundRobinStrategy::default();
+    let backends = weights
+        .iter()
+        .enumerate()
+        .map(|(id, weight)| create_test_backend(id as u8, None, Some(*weight)))
+        .collect();
+    let ctx = create_test_context(backends);
+
+    // When: picking two full cycles
+    let sequence = pick_sequence(&strategy, &ctx, cycle.len() * 2).await;
+
+    // Then: each cycle interleaves the backends smoothly, in the same order
+    assert_eq!(sequence[..cycle.len()], *cycle);
+    assert_eq!(sequence[cycle.len()..], *cycle);
+}
+
+#[tokio::test]
+async fn weighted_round_robin_strategy_pick_backend_skips_unhealthy_should_succeed() {
+    // Given: backends weighted 3, 2 and 1, the heaviest one unhealthy
+    let strategy = WeightedRoundRobinStrategy::default();
+    let ctx = create_test_context(vec![
+        create_test_backend(0, None, Some(3)),
+        create_test_backend(1, None, Some(2)),
+        create_test_backend(2, None, Some(1)),
+    ]);
+    ctx.routing_table().get(0).unwrap().set_health(false, 1000);
+
+    // When: picking one cycle of the remaining weights
+    let sequence = pick_sequence(&strategy, &ctx, 3).await;
+
+    // Then: only healthy backends are picked, still interleaved
+    assert_eq!(sequence, [1, 2, 1]);
+}
+
+#[tokio::test]
+async fn weighted_round_robin_strategy_pick_backend_after_migrate_should_succeed() {
+    // Given: backends weighted 3 and 2, partway through a cycle
+    let strategy = WeightedRoundRobinStrategy::default();
+    let config = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(3)),
+            create_test_backend(1, None, Some(2)),
+        ],
+        Strategy::WeightedRoundRobin,
+    );
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    assert_eq!(pick_sequence(&strategy, &ctx, 2).await, [0, 1]);
+
+    // When: migrating to a backend set with a third backend weighted 1
+    let mut new_config = config;
+    new_config
+        .backends
+        .push(BackendConfig::from(create_test_backend(2, None, Some(1))));
+    ctx.migrate(new_config).await.expect("Failed to migrate");
+
+    // Then: the cycle starts over for the new weights 3/2/1
+    let sequence = pick_sequence(&strategy, &ctx, 6).await;
+    assert_eq!(sequence, [0, 1, 0, 2, 1, 0]);
+}
+
+#[tokio::test]
+async fn weighted_round_robin_strategy_pick_backend_after_weight_change_should_succeed() {
+    // Given: two equally weighted backends, partway through a cycle
+    let strategy = WeightedRoundRobinStrategy::default();
+    let ctx = create_test_context(vec![
+        create_test_backend(0, None, Some(1)),
+        create_test_backend(1, None, Some(1)),
+    ]);
+    assert_eq!(pick_sequence(&strategy, &ctx, 1).await, [0]);
+
+    // When: backend 1's weight goes up to 2
+    ctx.set_backend_weight(1, Some(2));
+
+    // Then: current weights start over instead of carrying the old cycle
+    assert_eq!(pick_sequence(&strategy, &ctx, 3).await, [1, 0, 1]);
+}
+
+#[tokio::test]
+async fn weighted_round_robin_strategy_pick_backend_during_slow_start_should_succeed() {
+    // Given: two backends weighted 10, backend 1 recovering with a 10s slow start
+    let strategy = WeightedRoundRobinStrategy::default();
+    let clock = Arc::new(MockClock::new(1_000));
+    let ctx = Arc::new(
+        Context::with_clock(
+            create_test_config_fast(
+                vec![
+                    create_test_backend(0, None, Some(10)),
+                    create_test_backend(1, None, Some(10)),
+                ],
+                Strategy::WeightedRoundRobin,
+            ),
+            clock.clone(),
+        )
+        .expect("Failed to create context"),
+    );
+    ctx.routing_table()
+        .get(1)
+        .unwrap()
+        .start_slow_start(1_000, 10_000);
+
+    // When: picking 100 times right after recovery, halfway and after the window
+    let mut shares = Vec::new();
+    for _ in 0..3 {
+        let sequence = pick_sequence(&strategy, &ctx, 100).await;
+        shares.push(sequence.iter().filter(|&&id| id == 1).count());
+        clock.advance(Duration::from_secs(5));
+    }
+
+    // Then: backend 1's share grows from a tenth of its weight to a full half
+    assert!(
+        shares[0] < shares[1] && shares[1] < shares[2],
+        "{:?}",
+        shares
+    );
+    assert!((8..=10).contains(&shares[0]), "{:?}", shares);
+    assert_eq!(shares[2], 50);
+}
+
+#[tokio::test]
+async fn weighted_round_robin_strategy_slow_start_default_weights_should_succeed() {
+    // Given: two backends without a configured weight, backend 1 recovering
+    // with a 10s slow start
+    let strategy = WeightedRoundRobinStrategy::default();
+    let clock = Arc::new(MockClock::new(1_000));
+    let ctx = Arc::new(
+        Context::with_clock(
+            create_test_config_fast(
+                vec![
+                    create_test_backend(0, None, None),
+                    create_test_backend(1, None, None),
+                ],
+                Strategy::WeightedRoundRobin,
+            ),
+            clock.clone(),
+        )
+        .expect("Failed to create context"),
+    );
+    ctx.routing_table()
+        .get(1)
+        .unwrap()
+        .start_slow_start(1_000, 10_000);
+
+    // When: picking 100 times right after recovery, halfway and after the window
+    let mut shares = Vec::new();
+    for _ in 0..3 {
+        let sequence = pick_sequence(&strategy, &ctx, 100).await;
+        shares.push(sequence.iter().filter(|&&id| id == 1).count());
+        clock.advance(Duration::from_secs(5));
+    }
+
+    // Then: backend 1 ramps from 0.1 / 1.1 of the traffic to a full half
+    // instead of getting its share back at once
+    assert!((8..=10).contains(&shares[0]), "{:?}", shares);
+    assert!((34..=37).contains(&shares[1]), "{:?}", shares);
+    assert_eq!(shares[2], 50);
+}
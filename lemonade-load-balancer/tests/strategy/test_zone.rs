@@ -0,0 +1,163 @@
+//! Tests for zone-aware routing
+//!
+use lemonade_load_balancer::prelude::*;
+use rstest::rstest;
+use std::collections::BTreeSet;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Context in zone "a" with local backends 0 and 1 and remote backends 2
+/// and 3 (zone "b"), keeping picks local while `min_healthy` are healthy
+fn zoned_context(
+    strategy: Strategy,
+    params: serde_json::Value,
+    min_healthy: usize,
+) -> Arc<Context> {
+    let mut config = create_test_config_fast(
+        (0..4)
+            .map(|id| create_test_backend(id, None, Some(1)))
+            .collect(),
+        strategy,
+    );
+    config.strategy_params = params;
+    config.proxy.local_zone = Some("a".to_string());
+    config.proxy.zone_spillover_min_healthy = min_healthy;
+    for backend in &mut config.backends {
+        backend.zone = Some(if backend.id < 2 { "a" } else { "b" }.to_string());
+    }
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+/// Ids of the backends picked over `count` picks
+async fn picked(ctx: &Arc<Context>, count: usize) -> BTreeSet<BackendId> {
+    let mut ids = BTreeSet::new();
+    for _ in 0..count {
+        let backend = ctx
+            .strategy()
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Failed to pick backend");
+        ids.insert(backend.id());
+    }
+    ids
+}
+
+/// Set the health of backends `ids`
+fn set_health(ctx: &Context, ids: &[BackendId], alive: bool) {
+    for id in ids {
+        ctx.routing_table()
+            .get(*id)
+            .unwrap()
+            .set_health(alive, ctx.clock().now_millis());
+    }
+}
+
+#[rstest]
+#[case(Strategy::RoundRobin, serde_json::Value::Null)]
+#[case(Strategy::LeastConnections, serde_json::Value::Null)]
+#[case(Strategy::LeastConnections, serde_json::json!({ "index_min_backends": 0 }))]
+#[case(Strategy::WeightedRoundRobin, serde_json::Value::Null)]
+#[case(Strategy::Random, serde_json::Value::Null)]
+#[case(Strategy::FastestResponseTime, serde_json::Value::Null)]
+#[case(Strategy::LeastBandwidth, serde_json::Value::Null)]
+#[case(Strategy::Adaptive, serde_json::Value::Null)]
+#[case(Strategy::HeaderHash, serde_json::json!({ "hash_on": "path" }))]
+#[tokio::test]
+async fn zone_local_first_spill_over_and_back_should_succeed(
+    #[case] strategy: Strategy,
+    #[case] params: serde_json::Value,
+) {
+    // Given: healthy local and remote backends
+    let ctx = zoned_context(strategy, params, 1);
+
+    // Then: only the local backends are picked
+    assert!(picked(&ctx, 20).await.is_subset(&BTreeSet::from([0, 1])));
+
+    // When: one local backend fails
+    set_health(&ctx, &[0], false);
+
+    // Then: the other local backend takes all the traffic
+    assert_eq!(picked(&ctx, 20).await, BTreeSet::from([1]));
+
+    // When: the last local backend fails
+    set_health(&ctx, &[1], false);
+
+    // Then: picks spill over to the remote zone
+    let remote = picked(&ctx, 20).await;
+    assert!(!remote.is_empty());
+    assert!(remote.is_subset(&BTreeSet::from([2, 3])), "{:?}", remote);
+
+    // When: a local backend recovers
+    set_health(&ctx, &[0], true);
+
+    // Then: traffic goes back to it
+    assert_eq!(picked(&ctx, 20).await, BTreeSet::from([0]));
+}
+
+#[tokio::test]
+async fn zone_spillover_threshold_should_succeed() {
+    // Given: picks kept local only while both local backends are healthy
+    let ctx = zoned_context(Strategy::RoundRobin, serde_json::Value::Null, 2);
+    assert_eq!(picked(&ctx, 8).await, BTreeSet::from([0, 1]));
+
+    // When: one local backend fails
+    set_health(&ctx, &[0], false);
+
+    // Then: picks spread over the remaining local and the remote backends
+    assert_eq!(picked(&ctx, 12).await, BTreeSet::from([1, 2, 3]));
+}
+
+#[tokio::test]
+async fn zone_change_local_zone_on_migrate_should_succeed() {
+    // Given: a context in zone "a"
+    let ctx = zoned_context(Strategy::RoundRobin, serde_json::Value::Null, 1);
+
+    // When: migrating to zone "b"
+    let mut config = (*ctx.config()).clone();
+    config.proxy.local_zone = Some("b".to_string());
+    ctx.migrate(config).await.expect("Failed to migrate");
+
+    // Then: picks go to zone "b"
+    assert_eq!(ctx.routing_table().local_zone(), Some("b"));
+    assert_eq!(picked(&ctx, 8).await, BTreeSet::from([2, 3]));
+
+    // When: migrating backend 0 into zone "b" and dropping the preference
+    let mut config = (*ctx.config()).clone();
+    config.backends[0].zone = Some("b".to_string());
+    ctx.migrate(config.clone())
+        .await
+        .expect("Failed to migrate");
+
+    // Then: backend 0 is picked with the other zone "b" backends
+    assert_eq!(picked(&ctx, 12).await, BTreeSet::from([0, 2, 3]));
+
+    // When: no zone is preferred
+    config.proxy.local_zone = None;
+    ctx.migrate(config).await.expect("Failed to migrate");
+
+    // Then: every backend is picked
+    assert_eq!(picked(&ctx, 16).await, BTreeSet::from([0, 1, 2, 3]));
+}
+
+#[tokio::test]
+async fn zone_trace_remote_backends_should_succeed() {
+    // Given: a traced context with healthy local backends
+    let ctx = zoned_context(Strategy::RoundRobin, serde_json::Value::Null, 1);
+    let mut config = ctx.config().as_ref().clone();
+    config.metrics.selection_trace = true;
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+
+    // When: picking twice
+    picked(&ctx, 2).await;
+
+    // Then: the remote backends are counted as remote, the local ones not
+    // at all
+    let routing = ctx.routing_table();
+    for id in [2, 3] {
+        assert_eq!(
+            routing.get(id).unwrap().selection_exclusions(),
+            [(ExclusionReason::RemoteZone, 2)]
+        );
+    }
+    assert!(routing.get(0).unwrap().selection_exclusions().is_empty());
+}
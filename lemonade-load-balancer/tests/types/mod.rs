@@ -2,10 +2,26 @@
 //!
 //! Tests for all type definitions in the crate
 
+mod test_admin_state;
+mod test_affinity_table;
 mod test_backend;
 mod test_backend_address;
 mod test_backend_meta;
+mod test_byte_rate;
 mod test_channel_bundle;
+mod test_circuit_breaker;
+mod test_clock;
 mod test_context;
+mod test_drain_policy;
+mod test_groups;
+mod test_latency_histogram;
+mod test_latency_reservoir;
+mod test_listener_generations;
+mod test_log_rate_limit;
 mod test_metrics_registry;
+mod test_pick_timings;
+mod test_readiness;
+mod test_retry_budget;
+mod test_rng;
 mod test_route_table;
+mod test_unix_address;
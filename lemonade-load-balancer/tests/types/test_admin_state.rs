@@ -0,0 +1,211 @@
+//! Admin state tests
+//!
+//! Tests for AdminState parsing and backend admin overrides
+
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Round robin context over backends 0 to 2
+fn admin_context() -> Arc<Context> {
+    let config = create_test_config_fast(
+        (0..3)
+            .map(|id| create_test_backend(id, None, Some(1u8)))
+            .collect(),
+        Strategy::RoundRobin,
+    );
+    Arc::new(Context::new(config).expect("Failed to create context"))
+}
+
+/// Ids of the healthy backends of `ctx`
+fn healthy_ids(ctx: &Context) -> Vec<BackendId> {
+    ctx.routing_table()
+        .healthy_backends()
+        .iter()
+        .map(|backend| backend.id())
+        .collect()
+}
+
+#[test]
+fn admin_state_parse_and_display_should_succeed() {
+    // Given/When: every state name
+    let parsed: Vec<AdminState> = ["enabled", "disabled", "draining"]
+        .iter()
+        .map(|state| state.parse().expect("valid state"))
+        .collect();
+
+    // Then: they parse, display and serialize back to the same name
+    assert_eq!(
+        parsed,
+        vec![
+            AdminState::Enabled,
+            AdminState::Disabled,
+            AdminState::Draining
+        ]
+    );
+    for state in parsed {
+        assert_eq!(state.to_string(), state.as_str());
+        assert_eq!(serde_json::json!(state), serde_json::json!(state.as_str()));
+    }
+    assert_eq!(AdminState::default(), AdminState::Enabled);
+
+    // And: unknown names are rejected
+    for state in ["", "Enabled", "maintenance"] {
+        assert!(state.parse::<AdminState>().is_err(), "{}", state);
+    }
+}
+
+#[test]
+fn backend_admin_state_blocks_new_connections_should_succeed() {
+    // Given: a healthy backend
+    let backend = Backend::new(create_test_backend(0, None, Some(10u8)).into());
+    assert_eq!(backend.admin_state(), AdminState::Enabled);
+    assert!(backend.can_accept_new_connections());
+
+    // When: it is drained by its admin state
+    assert_eq!(
+        backend.set_admin_state(AdminState::Draining),
+        AdminState::Enabled
+    );
+
+    // Then: it takes no new connections but keeps sticky clients
+    assert!(!backend.can_accept_new_connections());
+    assert!(backend.can_accept_sticky_connections());
+
+    // When: it is disabled
+    backend.set_admin_state(AdminState::Disabled);
+
+    // Then: it takes neither, and stays active and alive
+    assert!(!backend.can_accept_new_connections());
+    assert!(!backend.can_accept_sticky_connections());
+    assert!(backend.is_active());
+    assert!(backend.is_alive());
+}
+
+#[tokio::test]
+async fn context_set_admin_state_stops_picks_should_succeed() {
+    // Given: a context over three healthy backends
+    let ctx = admin_context();
+
+    // When: backend 1 is disabled
+    assert_eq!(
+        ctx.set_admin_state(1, AdminState::Disabled),
+        Some(AdminState::Enabled)
+    );
+
+    // Then: it is left out of the healthy backends and every pick
+    assert_eq!(healthy_ids(&ctx), vec![0, 2]);
+    for _ in 0..6 {
+        let picked = ctx
+            .strategy()
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Pick should succeed");
+        assert_ne!(picked.id(), 1);
+    }
+
+    // And: the pick trace counts it as disabled
+    let disabled = ctx.routing_table().get(1).unwrap();
+    assert_eq!(
+        ExclusionReason::of(
+            &disabled,
+            &LabelSelector::default(),
+            false,
+            Some(0),
+            None,
+            None
+        ),
+        Some(ExclusionReason::Disabled)
+    );
+    assert!(ConsistencyChecker::check(&ctx).is_empty());
+
+    // When: it is enabled again
+    ctx.set_admin_state(1, AdminState::Enabled);
+
+    // Then: it is back
+    assert_eq!(healthy_ids(&ctx), vec![0, 1, 2]);
+
+    // And: unknown backends have no admin state
+    assert_eq!(ctx.set_admin_state(9, AdminState::Disabled), None);
+}
+
+#[tokio::test]
+async fn context_admin_state_survives_replacement_should_succeed() {
+    // Given: backends 1 and 2 disabled
+    let ctx = admin_context();
+    ctx.set_admin_state(1, AdminState::Disabled);
+    ctx.set_admin_state(2, AdminState::Draining);
+
+    // When: a reload replaces backend 1 and removes backend 2
+    let mut config = (*ctx.config()).clone();
+    config.backends[1].name = Some("replacement".to_string());
+    config.backends.retain(|backend| backend.id != 2);
+    ctx.migrate(config.clone())
+        .await
+        .expect("Failed to migrate");
+
+    // Then: the replacement keeps the override
+    let replaced = ctx.routing_table().get(1).expect("backend 1");
+    assert_eq!(replaced.name(), Some("replacement"));
+    assert_eq!(replaced.admin_state(), AdminState::Disabled);
+    assert_eq!(healthy_ids(&ctx), vec![0]);
+
+    // And: a backend added back under a removed id starts enabled
+    config
+        .backends
+        .push(create_test_backend(2, None, Some(1u8)).into());
+    ctx.migrate(config).await.expect("Failed to migrate");
+    assert_eq!(
+        ctx.routing_table().get(2).unwrap().admin_state(),
+        AdminState::Enabled
+    );
+}
+
+#[tokio::test]
+async fn disabled_backend_keeps_health_checks_should_succeed() {
+    // Given: a disabled backend checked every 10ms
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.backends = vec![BackendConfig::from(BackendMeta::new(
+        0u8,
+        None::<String>,
+        addr,
+        Some(1),
+    ))];
+    config.health.interval = Duration::from_millis(10);
+    config.health.timeout = Duration::from_millis(100);
+    let service =
+        BackendHealthService::new(Arc::new(ArcSwap::from_pointee(config.health.clone())))
+            .expect("Failed to create service");
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    ctx.set_admin_state(0, AdminState::Disabled);
+    let health_handle = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.check_health(ctx).await }
+    });
+
+    // When: the backend goes away while disabled
+    drop(listener);
+    let backend = ctx.routing_table().get(0).expect("backend 0");
+    for _ in 0..100 {
+        if !backend.is_alive() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Then: its probes still mark it unhealthy
+    assert!(!backend.is_alive());
+
+    // And: re-enabling it does not bring it back into rotation
+    ctx.set_admin_state(0, AdminState::Enabled);
+    assert!(healthy_ids(&ctx).is_empty());
+
+    let _ = ctx.channels().shutdown_tx().send(());
+    health_handle.abort();
+}
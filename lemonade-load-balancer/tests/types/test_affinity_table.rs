@@ -0,0 +1,99 @@
+//! Affinity table tests
+//!
+use super::super::common::fixtures::*;
+use lemonade_load_balancer::prelude::*;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+
+const TTL_MS: u64 = 1_000;
+
+/// Client address `10.0.0.<last>`
+fn client(last: u8) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(10, 0, 0, last))
+}
+
+#[test]
+fn affinity_table_record_and_get_should_succeed() {
+    // Given: a client stuck to backend 1
+    let table = AffinityTable::default();
+    table.record(client(1), 1, 0, TTL_MS);
+
+    // When/Then: it is found until its TTL runs out, then dropped
+    assert_eq!(table.get(client(1), TTL_MS - 1), Some(1));
+    assert_eq!(table.get(client(2), 0), None);
+    assert_eq!(table.get(client(1), TTL_MS), None);
+    assert!(table.is_empty());
+}
+
+#[test]
+fn affinity_table_record_refreshes_ttl_should_succeed() {
+    // Given: a client that connects again halfway through its TTL
+    let table = AffinityTable::default();
+    table.record(client(1), 1, 0, TTL_MS);
+    table.record(client(1), 2, TTL_MS / 2, TTL_MS);
+
+    // Then: it sticks to its latest backend, counted from its latest connection
+    assert_eq!(table.get(client(1), TTL_MS + 1), Some(2));
+    assert_eq!(table.get(client(1), TTL_MS / 2 + TTL_MS), None);
+}
+
+#[test]
+fn affinity_table_record_sweeps_expired_entries_should_succeed() {
+    // Given: clients that stopped connecting
+    let table = AffinityTable::default();
+    for last in 0..10 {
+        table.record(client(last), 0, 0, TTL_MS);
+    }
+
+    // When: a new client connects after their TTL
+    table.record(client(100), 1, TTL_MS, TTL_MS);
+
+    // Then: only the new client is left
+    assert_eq!(table.len(), 1);
+    assert_eq!(table.get(client(100), TTL_MS), Some(1));
+}
+
+#[test]
+fn affinity_table_remove_backends_should_succeed() {
+    // Given: clients stuck to three backends
+    let table = AffinityTable::default();
+    for last in 0..6 {
+        table.record(client(last), last % 3, 0, TTL_MS);
+    }
+
+    // When: backends 0 and 2 are removed
+    let removed = table.remove_backends(&[0, 2]);
+
+    // Then: only the clients of backend 1 keep their entry
+    assert_eq!(removed, 4);
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.get(client(1), 0), Some(1));
+    assert_eq!(table.get(client(4), 0), Some(1));
+    assert_eq!(table.get(client(0), 0), None);
+}
+
+#[tokio::test]
+async fn context_migrate_evicts_affinity_of_removed_backends_should_succeed() {
+    // Given: clients stuck to both backends of a context
+    let config = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, None),
+            create_test_backend(1, None, None),
+        ],
+        Strategy::RoundRobin,
+    );
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let now_ms = ctx.clock().now_millis();
+    ctx.affinity().record(client(0), 0, now_ms, TTL_MS * 60);
+    ctx.affinity().record(client(1), 1, now_ms, TTL_MS * 60);
+
+    // When: backend 1 is removed
+    let mut new_config = config;
+    new_config.backends.retain(|b| b.id == 0);
+    ctx.migrate(new_config).await.expect("Failed to migrate");
+
+    // Then: its clients lose their entry and it is counted as evicted
+    assert_eq!(ctx.affinity().get(client(0), now_ms), Some(0));
+    assert_eq!(ctx.affinity().get(client(1), now_ms), None);
+    assert_eq!(ctx.stale_entries_evicted(), 1);
+}
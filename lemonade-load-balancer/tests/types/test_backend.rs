@@ -11,6 +11,11 @@ fn create_test_backend_config() -> BackendConfig {
         name: Some("test-backend".to_string()),
         address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080).into(),
         weight: Some(10),
+        labels: Labels::new(),
+        bind_address: None,
+        dscp: None,
+        priority: None,
+        zone: None,
     }
 }
 
@@ -61,6 +66,91 @@ fn test_backend_health_set_and_get() {
     assert_eq!(backend.last_health_check(), now_ms2);
 }
 
+#[test]
+fn test_backend_slow_start_ramps_weight() {
+    let backend = Backend::new(create_test_backend_config());
+    assert_eq!(backend.slow_start_factor(0), 1.0);
+    assert_eq!(backend.effective_weight_milli(0), 10_000);
+
+    // Recovered at t=1000 with a 10s window
+    backend.start_slow_start(1_000, 10_000);
+
+    assert_eq!(backend.slow_start_factor(1_000), SLOW_START_INITIAL_FACTOR);
+    assert_eq!(backend.effective_weight_milli(1_000), 1_000);
+    assert!((backend.slow_start_factor(6_000) - 0.55).abs() < 1e-9);
+    assert_eq!(backend.effective_weight_milli(6_000), 5_500);
+    assert_eq!(backend.slow_start_factor(11_000), 1.0);
+    assert_eq!(backend.effective_weight_milli(11_000), 10_000);
+
+    // A zero window ends the ramp
+    backend.start_slow_start(11_000, 0);
+    assert_eq!(backend.effective_weight_milli(11_000), 10_000);
+}
+
+#[test]
+fn test_backend_selection_exclusions_export_deltas() {
+    let backend = Backend::new(create_test_backend_config());
+    assert!(backend.take_unexported_exclusions().is_empty());
+
+    backend.record_exclusion(ExclusionReason::Unhealthy);
+    backend.record_exclusion(ExclusionReason::Unhealthy);
+    backend.record_exclusion(ExclusionReason::Draining);
+    assert_eq!(
+        backend.take_unexported_exclusions(),
+        vec![
+            (ExclusionReason::Draining, 1),
+            (ExclusionReason::Unhealthy, 2)
+        ]
+    );
+    assert!(backend.take_unexported_exclusions().is_empty());
+
+    // Totals keep counting across exports
+    backend.record_exclusion(ExclusionReason::Unhealthy);
+    assert_eq!(
+        backend.take_unexported_exclusions(),
+        vec![(ExclusionReason::Unhealthy, 1)]
+    );
+    assert_eq!(
+        backend.selection_exclusions(),
+        vec![
+            (ExclusionReason::Draining, 1),
+            (ExclusionReason::Unhealthy, 3)
+        ]
+    );
+}
+
+#[test]
+fn test_backend_selections_export_deltas() {
+    let backend = Backend::new(create_test_backend_config());
+    assert_eq!(backend.take_unexported_selections(), 0);
+
+    backend.record_selection();
+    backend.record_selection();
+    assert_eq!(backend.take_unexported_selections(), 2);
+    assert_eq!(backend.take_unexported_selections(), 0);
+
+    // Totals keep counting across exports
+    backend.record_selection();
+    assert_eq!(backend.take_unexported_selections(), 1);
+    assert_eq!(backend.selections(), 3);
+}
+
+#[test]
+fn test_backend_consecutive_failures() {
+    let config = create_test_backend_config();
+    let backend = Backend::new(config);
+    assert_eq!(backend.consecutive_failures(), 0);
+
+    // Every unhealthy report extends the run
+    backend.set_health(false, 1_000);
+    backend.set_health(false, 2_000);
+    assert_eq!(backend.consecutive_failures(), 2);
+
+    // A healthy report ends it
+    backend.set_health(true, 3_000);
+    assert_eq!(backend.consecutive_failures(), 0);
+}
+
 #[test]
 fn test_backend_connection_tracking() {
     let config = create_test_backend_config();
@@ -105,6 +195,27 @@ fn test_backend_has_capacity_for_health_check() {
     assert_eq!(backend.active_connections(), 10);
 }
 
+#[test]
+fn test_backend_pending_connects_capped() {
+    let config = create_test_backend_config();
+    let backend = Backend::new(config);
+
+    // Slots are reserved up to the cap
+    assert!(backend.try_reserve_connect(2));
+    assert!(backend.try_reserve_connect(2));
+    assert_eq!(backend.pending_connects(), 2);
+
+    // At the cap, reserving fails without counting
+    assert!(!backend.try_reserve_connect(2));
+    assert_eq!(backend.pending_connects(), 2);
+
+    // A released slot can be reserved again
+    backend.release_connect();
+    assert_eq!(backend.pending_connects(), 1);
+    assert!(backend.try_reserve_connect(2));
+    assert_eq!(backend.pending_connects(), 2);
+}
+
 #[test]
 fn test_backend_metrics_recording() {
     let config = create_test_backend_config();
@@ -258,6 +369,11 @@ fn test_backend_concurrent_connections() {
         name: Some("concurrent-test".to_string()),
         address: BackendAddress::parse("127.0.0.1:8080").unwrap(),
         weight: Some(10),
+        labels: Labels::new(),
+        bind_address: None,
+        dscp: None,
+        priority: None,
+        zone: None,
     };
     let backend = Arc::new(Backend::new(backend_config));
 
@@ -289,6 +405,11 @@ fn test_backend_metrics_timestamp_update() {
         name: Some("timestamp-test".to_string()),
         address: BackendAddress::parse("127.0.0.1:8080").unwrap(),
         weight: Some(10),
+        labels: Labels::new(),
+        bind_address: None,
+        dscp: None,
+        priority: None,
+        zone: None,
     };
     let backend = Backend::new(backend_config);
 
@@ -296,3 +417,81 @@ fn test_backend_metrics_timestamp_update() {
     let metrics = backend.metrics_snapshot();
     assert_eq!(metrics.last_updated_ms, 5000);
 }
+
+/// Backend at `address` marked with `dscp`, and a stream established to it
+async fn dscp_backend_stream(listen: &str, dscp: Option<u8>) -> (Backend, BackendStream) {
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .expect("Failed to bind backend");
+    let address = listener
+        .local_addr()
+        .expect("Failed to get backend address");
+    let backend = Backend::new(BackendConfig {
+        dscp,
+        ..BackendConfig::from(BackendMeta::new(0u8, Some("dscp"), address, None::<u8>))
+    });
+    let stream = tokio::net::TcpStream::connect(address)
+        .await
+        .expect("Failed to connect to backend");
+    (backend, BackendStream::from(stream))
+}
+
+#[tokio::test]
+async fn test_backend_apply_dscp_ipv4() {
+    // Given: an IPv4 backend stream and a proxy-wide DSCP value
+    let (backend, stream) = dscp_backend_stream("127.0.0.1:0", None).await;
+
+    // When: marking the stream
+    backend.apply_dscp(&stream, Some(46));
+
+    // Then: the TOS byte carries the DSCP value
+    let tos = socket2::SockRef::from(stream.as_tcp().unwrap())
+        .tos_v4()
+        .unwrap();
+    assert_eq!(tos >> 2, 46);
+}
+
+#[tokio::test]
+async fn test_backend_apply_dscp_ipv6() {
+    // Given: an IPv6 backend stream (skipped without IPv6 loopback)
+    let Ok(listener) = tokio::net::TcpListener::bind("[::1]:0").await else {
+        return;
+    };
+    drop(listener);
+    let (backend, stream) = dscp_backend_stream("[::1]:0", None).await;
+
+    // When: marking the stream
+    backend.apply_dscp(&stream, Some(10));
+
+    // Then: the traffic class carries the DSCP value
+    let tclass = socket2::SockRef::from(stream.as_tcp().unwrap())
+        .tclass_v6()
+        .unwrap();
+    assert_eq!(tclass >> 2, 10);
+}
+
+#[tokio::test]
+async fn test_backend_apply_dscp_override_and_unset() {
+    // Given: a backend with its own DSCP value
+    let (backend, stream) = dscp_backend_stream("127.0.0.1:0", Some(8)).await;
+    assert_eq!(backend.dscp(), Some(8));
+
+    // When: marking with a different proxy-wide default
+    backend.apply_dscp(&stream, Some(46));
+
+    // Then: the backend's own value wins
+    let tos = socket2::SockRef::from(stream.as_tcp().unwrap())
+        .tos_v4()
+        .unwrap();
+    assert_eq!(tos >> 2, 8);
+
+    // And: a stream with no value anywhere is left unmarked
+    let (unmarked, stream) = dscp_backend_stream("127.0.0.1:0", None).await;
+    unmarked.apply_dscp(&stream, None);
+    assert_eq!(
+        socket2::SockRef::from(stream.as_tcp().unwrap())
+            .tos_v4()
+            .unwrap(),
+        0
+    );
+}
@@ -232,3 +232,87 @@ fn test_backend_address_ne_different_ip() {
     ));
     assert_ne!(addr1, addr2);
 }
+
+#[tokio::test]
+async fn test_connect_from_binds_source_address() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = BackendAddress::from(listener.local_addr().unwrap());
+    let source = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2));
+
+    let stream = addr.connect_from(Some(source)).await.unwrap();
+    let (_, peer) = listener.accept().await.unwrap();
+
+    assert_eq!(stream.as_tcp().unwrap().local_addr().unwrap().ip(), source);
+    assert_eq!(peer.ip(), source);
+}
+
+#[tokio::test]
+async fn test_connect_from_family_mismatch_is_bind_error() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = BackendAddress::from(listener.local_addr().unwrap());
+
+    let result = addr
+        .connect_from(Some(IpAddr::V6(Ipv6Addr::LOCALHOST)))
+        .await;
+
+    assert!(matches!(result, Err(BackendConnectError::Bind { .. })));
+}
+
+#[rstest]
+#[case("unix:/run/worker.sock")]
+#[case("unix:@worker")]
+fn test_parse_unix(#[case] addr_str: &str) {
+    let addr = BackendAddress::parse(addr_str).expect("Failed to parse address");
+    assert_eq!(addr.as_str(), addr_str);
+    assert_eq!(
+        addr.unix(),
+        Some(UnixAddress::parse(addr_str).expect("Failed to parse Unix address"))
+    );
+}
+
+#[rstest]
+#[case("unix:")]
+#[case("unix:@")]
+fn test_parse_unix_invalid(#[case] addr_str: &str) {
+    assert!(BackendAddress::parse(addr_str).is_err());
+}
+
+#[test]
+fn test_tcp_address_is_not_unix() {
+    let addr = BackendAddress::parse("127.0.0.1:8080").unwrap();
+    assert_eq!(addr.unix(), None);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_connect_from_unix_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("backend.sock");
+    let listener = tokio::net::UnixListener::bind(&path).unwrap();
+    let addr = BackendAddress::parse(&format!("unix:{}", path.display())).unwrap();
+
+    // The source address only applies to TCP
+    let stream = addr
+        .connect_from(Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2))))
+        .await
+        .unwrap();
+    listener.accept().await.unwrap();
+
+    assert!(matches!(stream, BackendStream::Unix(_)));
+    assert!(stream.as_tcp().is_none());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_connect_from_missing_unix_socket_is_connect_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let addr = BackendAddress::parse(&format!(
+        "unix:{}",
+        dir.path().join("none.sock").display()
+    ))
+    .unwrap();
+
+    let result = addr.connect_from(None).await;
+
+    assert!(matches!(result, Err(BackendConnectError::Connect(_))));
+}
@@ -134,6 +134,23 @@ fn test_clone(
     assert_eq!(meta.weight(), cloned.weight());
 }
 
+#[test]
+fn test_equality_is_keyed_by_id() {
+    use std::collections::HashSet;
+
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8080);
+    let other_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9090);
+    let meta = BackendMeta::new(1u8, Some("backend-1"), addr, Some(10u8));
+    let same_id = BackendMeta::new(1u8, Some("renamed"), other_addr, Some(20u8));
+    let other_id = BackendMeta::new(2u8, Some("backend-1"), addr, Some(10u8));
+
+    assert_eq!(meta, same_id);
+    assert_ne!(meta, other_id);
+
+    let set: HashSet<BackendMeta> = [meta, same_id, other_id].into_iter().collect();
+    assert_eq!(set.len(), 2);
+}
+
 // Property-based tests
 proptest! {
     #[test]
@@ -200,7 +217,8 @@ fn test_backend_meta_equality_different_address() {
         SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
         Some(10u8),
     );
-    assert_ne!(meta1, meta2);
+    // Identity is keyed by id only
+    assert_eq!(meta1, meta2);
 }
 
 #[test]
@@ -217,7 +235,8 @@ fn test_backend_meta_equality_different_weight() {
         SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080),
         Some(20u8),
     );
-    assert_ne!(meta1, meta2);
+    // Identity is keyed by id only
+    assert_eq!(meta1, meta2);
 }
 
 #[test]
@@ -234,7 +253,8 @@ fn test_backend_meta_equality_different_name() {
         SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080),
         Some(10u8),
     );
-    assert_ne!(meta1, meta2);
+    // Identity is keyed by id only
+    assert_eq!(meta1, meta2);
 }
 
 #[test]
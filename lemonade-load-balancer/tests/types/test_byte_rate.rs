@@ -0,0 +1,81 @@
+//! Tests for the byte rate window
+//!
+use lemonade_load_balancer::prelude::*;
+
+#[test]
+fn byte_rate_window_rates_over_closed_intervals_should_succeed() {
+    // Given: a window started at 0 ms
+    let window = ByteRateWindow::new();
+    window.flush(0);
+
+    // When: 3000 bytes in and 600 out are recorded over two seconds, in two
+    // intervals
+    window.record(1_000, 200);
+    window.flush(500);
+    window.record(2_000, 400);
+    window.flush(2_000);
+
+    // Then: the rates are bytes per second over both
+    assert_eq!(window.rates(), (1_500, 300));
+}
+
+#[test]
+fn byte_rate_window_first_flush_starts_window_should_succeed() {
+    // Given: bytes recorded before the window started
+    let window = ByteRateWindow::new();
+    window.record(4_000, 0);
+
+    // When: the first flush starts it
+    window.flush(1_000);
+
+    // Then: no rate yet
+    assert_eq!(window.rates(), (0, 0));
+
+    // When: the first interval closes, two seconds later
+    window.flush(3_000);
+
+    // Then: the early bytes count toward it
+    assert_eq!(window.rates(), (2_000, 0));
+}
+
+#[test]
+fn byte_rate_window_same_millisecond_flush_is_noop_should_succeed() {
+    // Given: a window with a closed interval
+    let window = ByteRateWindow::new();
+    window.flush(0);
+    window.record(1_000, 1_000);
+    window.flush(1_000);
+
+    // When: more bytes come and a second flush lands in the same millisecond
+    window.record(9_000, 0);
+    window.flush(1_000);
+
+    // Then: the rates are unchanged, and the bytes wait for the next interval
+    assert_eq!(window.rates(), (1_000, 1_000));
+    window.flush(2_000);
+    assert_eq!(window.rates(), (5_000, 500));
+}
+
+#[test]
+fn byte_rate_window_slides_past_old_intervals_should_succeed() {
+    // Given: a burst in the first one-second interval
+    let window = ByteRateWindow::new();
+    window.flush(0);
+    window.record(60_000, 6_000);
+    window.flush(1_000);
+    assert_eq!(window.rates(), (60_000, 6_000));
+
+    // When: the window fills with quiet intervals
+    for second in 2..=BYTE_RATE_INTERVALS as u64 {
+        window.flush(second * 1_000);
+    }
+
+    // Then: the burst is averaged over the whole window
+    assert_eq!(window.rates(), (10_000, 1_000));
+
+    // When: one more quiet interval pushes the burst out
+    window.flush((BYTE_RATE_INTERVALS as u64 + 1) * 1_000);
+
+    // Then: the rates drop to zero
+    assert_eq!(window.rates(), (0, 0));
+}
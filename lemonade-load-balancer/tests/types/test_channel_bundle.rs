@@ -107,13 +107,16 @@ fn test_backend_failure_sender() {
         .backend_failure_rx()
         .expect("Backend failure receiver should be available");
     let sender = bundle.backend_failure_tx();
-    let event = BackendFailureEvent::ConnectionRefused { backend_id: 1 };
+    let event = BackendFailureEvent::ConnectionRefused {
+        backend_id: 1,
+        at_micros: 1000,
+    };
     let send_result = sender.try_send(event.clone());
     assert!(send_result.is_ok());
     let received = failure_rx.try_recv();
     assert!(received.is_ok());
     match received.expect("Failed to receive event") {
-        BackendFailureEvent::ConnectionRefused { backend_id } => {
+        BackendFailureEvent::ConnectionRefused { backend_id, .. } => {
             assert_eq!(backend_id, 1);
         }
         _ => panic!("Unexpected event type"),
@@ -132,14 +135,22 @@ fn test_connection_sender() {
         .connection_rx()
         .expect("Connection receiver should be available");
     let sender = bundle.connection_tx();
-    let event = ConnectionEvent::Opened { backend_id: 1 };
+    let peer = PeerInfo::new("127.0.0.1:50000".parse().unwrap());
+    let event = ConnectionEvent::Opened {
+        backend_id: 1,
+        peer,
+    };
     let send_result = sender.try_send(event.clone());
     assert!(send_result.is_ok());
     let received = connection_rx.try_recv();
     assert!(received.is_ok());
     match received.expect("Failed to receive event") {
-        ConnectionEvent::Opened { backend_id } => {
+        ConnectionEvent::Opened {
+            backend_id,
+            peer: received_peer,
+        } => {
             assert_eq!(backend_id, 1);
+            assert_eq!(received_peer, peer);
         }
         _ => panic!("Unexpected event type"),
     }
@@ -200,6 +211,7 @@ fn test_metrics_sender_clone() {
         backend_id: 1,
         latency_micros: 100,
         status_code: 200,
+        at_micros: 1000,
     };
     assert!(sender1.try_send(event1).is_ok());
     assert!(sender2.try_send(event2).is_ok());
@@ -263,18 +275,25 @@ fn test_metrics_sender_all_event_types() {
         MetricsEvent::ConnectionClosed {
             backend_id: 1,
             duration_micros: 5000,
+            connect_micros: 500,
+            ttfb_micros: Some(1500),
             bytes_in: 100,
             bytes_out: 200,
+            close_reason: CloseReason::Normal,
+            peer: PeerInfo::new("127.0.0.1:50000".parse().unwrap()),
+            at_micros: 1000,
         },
         MetricsEvent::RequestCompleted {
             backend_id: 1,
             latency_micros: 100,
             status_code: 200,
+            at_micros: 1000,
         },
         MetricsEvent::RequestFailed {
             backend_id: 1,
             latency_micros: 50,
             error_class: MetricsErrorClass::Timeout,
+            at_micros: 1000,
         },
         MetricsEvent::FlushSnapshot,
     ];
@@ -312,6 +331,7 @@ fn test_health_sender_all_event_types() {
             backend_id: 1,
             from: HealthStatus::Unhealthy,
             to: HealthStatus::Healthy,
+            cause: TransitionCause::recovery(100),
         },
         HealthEvent::BackendConfigUpdated { backend_id: 1 },
     ];
@@ -0,0 +1,101 @@
+//! Circuit breaker tests
+//!
+//! Tests for the per-backend circuit breaker lifecycle
+
+use lemonade_load_balancer::prelude::*;
+
+/// Enabled breaker opening after 3 failures, for 1s
+fn breaker_config() -> CircuitBreakerConfig {
+    CircuitBreakerConfig {
+        enabled: true,
+        failure_threshold: 3,
+        open_duration_millis: 1_000,
+    }
+}
+
+#[test]
+fn circuit_breaker_opens_after_failure_threshold_should_succeed() {
+    // Given: a closed circuit
+    let config = breaker_config();
+    let breaker = CircuitBreaker::default();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    // When: connects fail twice, then succeed
+    assert!(!breaker.record_failure(100, &config));
+    assert!(!breaker.record_failure(200, &config));
+    assert!(!breaker.record_success());
+
+    // Then: the run starts over and the circuit stays closed
+    assert_eq!(breaker.failures(), 0);
+    assert!(!breaker.record_failure(300, &config));
+    assert!(!breaker.record_failure(400, &config));
+    assert!(breaker.try_acquire(400, &config));
+
+    // When: a third connect in a row fails
+    assert!(breaker.record_failure(500, &config));
+
+    // Then: the circuit is open for the open duration
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert!(!breaker.allows(1_499, &config));
+    assert!(!breaker.try_acquire(1_499, &config));
+    assert!(breaker.allows(1_500, &config));
+}
+
+#[test]
+fn circuit_breaker_half_open_trial_should_succeed() {
+    // Given: a circuit opened at 0
+    let config = breaker_config();
+    let breaker = CircuitBreaker::default();
+    for _ in 0..3 {
+        breaker.record_failure(0, &config);
+    }
+
+    // When: connects are attempted once the open duration is over
+    assert!(breaker.try_acquire(1_000, &config));
+
+    // Then: only the first is let through, as the half-open trial
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    assert!(!breaker.allows(1_000, &config));
+    assert!(!breaker.try_acquire(1_000, &config));
+
+    // When: the trial fails
+    assert!(breaker.record_failure(1_200, &config));
+
+    // Then: the circuit opens again for a full duration
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert!(!breaker.try_acquire(2_199, &config));
+
+    // When: the next trial succeeds
+    assert!(breaker.try_acquire(2_200, &config));
+    assert!(breaker.record_success());
+
+    // Then: the circuit is closed
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert!(breaker.try_acquire(2_200, &config));
+}
+
+#[test]
+fn circuit_breaker_lost_trial_and_disabled_should_succeed() {
+    // Given: a half-open circuit whose trial never reports back
+    let config = breaker_config();
+    let breaker = CircuitBreaker::default();
+    for _ in 0..3 {
+        breaker.record_failure(0, &config);
+    }
+    assert!(breaker.try_acquire(1_000, &config));
+
+    // Then: another trial is let through a full duration later
+    assert!(!breaker.try_acquire(1_999, &config));
+    assert!(breaker.try_acquire(2_000, &config));
+
+    // And: a disabled breaker lets everything through and records nothing
+    let disabled = CircuitBreakerConfig::default();
+    assert!(!disabled.enabled);
+    assert!(breaker.try_acquire(2_000, &disabled));
+    assert!(breaker.allows(2_000, &disabled));
+    let fresh = CircuitBreaker::default();
+    for _ in 0..10 {
+        assert!(!fresh.record_failure(0, &disabled));
+    }
+    assert_eq!(fresh.state(), CircuitState::Closed);
+}
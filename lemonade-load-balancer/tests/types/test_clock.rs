@@ -0,0 +1,88 @@
+//! Clock tests
+//!
+//! Tests for the SystemClock and MockClock time sources
+
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn system_clock_now_millis_should_succeed() {
+    // Given: a system clock
+    let clock = SystemClock;
+
+    // When: reading the time twice
+    let first = clock.now_millis();
+    let second = clock.now_millis();
+
+    // Then: the time is past the epoch and does not go backwards
+    assert!(first > 0);
+    assert!(second >= first);
+}
+
+#[test]
+fn mock_clock_advance_and_set_should_succeed() {
+    // Given: a mock clock
+    let clock = MockClock::new(1_000);
+
+    // When: advancing and setting the time
+    clock.advance(Duration::from_millis(250));
+    let advanced = clock.now_millis();
+    clock.set(5_000);
+
+    // Then: time only moves when told to
+    assert_eq!(advanced, 1_250);
+    assert_eq!(clock.now_millis(), 5_000);
+}
+
+#[tokio::test]
+async fn mock_clock_sleep_wakes_on_advance_should_succeed() {
+    // Given: a task sleeping one second on a mock clock
+    let clock = Arc::new(MockClock::new(0));
+    let sleeper = tokio::spawn({
+        let clock = clock.clone();
+        async move { clock.sleep(Duration::from_secs(1)).await }
+    });
+
+    // When: advancing short of and then past the deadline
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    clock.advance(Duration::from_millis(999));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let early = sleeper.is_finished();
+    clock.advance(Duration::from_millis(1));
+
+    // Then: the sleeper only wakes once the deadline is reached
+    assert!(!early);
+    tokio::time::timeout(Duration::from_secs(1), sleeper)
+        .await
+        .expect("Sleeper should wake after the advance")
+        .expect("Sleeper task panicked");
+}
+
+#[tokio::test]
+async fn mock_clock_zero_sleep_should_succeed() {
+    // Given: a mock clock that is never advanced
+    let clock = MockClock::new(0);
+
+    // When: sleeping for zero time
+    let result =
+        tokio::time::timeout(Duration::from_millis(100), clock.sleep(Duration::ZERO))
+            .await;
+
+    // Then: the sleep completes immediately
+    assert!(result.is_ok());
+}
+
+#[test]
+fn clock_is_expired_should_compare_event_age() {
+    // Given: a mock clock at t=100s
+    let clock = MockClock::new(100_000);
+
+    // Then: events are expired only once strictly older than the max age,
+    // and a zero max age never expires
+    assert_eq!(clock.now_micros(), 100_000_000);
+    assert!(!clock.is_expired(90_000_000, 10_000));
+    assert!(clock.is_expired(89_999_999, 10_000));
+    assert!(!clock.is_expired(0, 0));
+    assert!(!clock.is_expired(200_000_000, 10_000));
+}
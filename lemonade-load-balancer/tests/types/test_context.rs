@@ -3,7 +3,7 @@
 //! Tests for the Context type covering:
 //! - Construction (new)
 //! - Getters (strategy, routing_table, channels)
-//! - Migration (migrate)
+//! - Migration (migrate, set_backends)
 //! - Drain waiting (wait_for_drain)
 //! - Channel operations
 
@@ -29,6 +29,11 @@ fn context_new_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
 
@@ -54,6 +59,11 @@ fn context_new_with_empty_backends_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
 
@@ -80,6 +90,11 @@ fn context_strategy_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Context::new(config).expect("Failed to create context");
@@ -108,6 +123,11 @@ fn context_routing_table_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Context::new(config).expect("Failed to create context");
@@ -133,6 +153,11 @@ fn context_channels_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Context::new(config).expect("Failed to create context");
@@ -199,6 +224,11 @@ async fn context_migrate_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Arc::new(Context::new(config1).expect("Failed to create context"));
@@ -218,6 +248,11 @@ async fn context_migrate_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
 
@@ -242,6 +277,11 @@ async fn context_wait_for_drain_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
@@ -267,6 +307,11 @@ async fn context_wait_for_drain_with_connections_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
@@ -309,6 +354,11 @@ fn context_notify_connection_closed_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Context::new(config).expect("Failed to create context");
@@ -337,6 +387,11 @@ fn context_backends_start_healthy_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Context::new(config).expect("Failed to create context");
@@ -373,6 +428,11 @@ async fn context_migrate_with_removed_backends_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Arc::new(Context::new(config1).expect("Failed to create context"));
@@ -389,6 +449,11 @@ async fn context_migrate_with_removed_backends_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
 
@@ -415,6 +480,11 @@ async fn context_migrate_with_changed_listen_address_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Arc::new(Context::new(config1).expect("Failed to create context"));
@@ -430,6 +500,11 @@ async fn context_migrate_with_changed_listen_address_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     config2.proxy.listen_address =
@@ -459,6 +534,11 @@ async fn context_wait_for_drain_timeout_should_fail() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
@@ -493,6 +573,11 @@ async fn context_migrate_with_strategy_change_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Arc::new(Context::new(config1).expect("Failed to create context"));
@@ -508,6 +593,11 @@ async fn context_migrate_with_strategy_change_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
 
@@ -535,6 +625,11 @@ fn context_config_getter_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Context::new(config.clone()).expect("Failed to create context");
@@ -560,6 +655,11 @@ async fn context_migrate_with_same_backends_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Arc::new(Context::new(config1).expect("Failed to create context"));
@@ -575,6 +675,11 @@ async fn context_migrate_with_same_backends_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
 
@@ -599,6 +704,11 @@ async fn context_migrate_with_added_backends_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
     let ctx = Arc::new(Context::new(config1).expect("Failed to create context"));
@@ -619,6 +729,11 @@ async fn context_migrate_with_added_backends_should_succeed() {
             background_timeout_millis: 1000,
             accept_timeout_millis: 2000,
             config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 1000,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
         },
     );
 
@@ -628,3 +743,856 @@ async fn context_migrate_with_added_backends_should_succeed() {
     assert!(result.is_ok());
     assert_eq!(ctx.routing_table().len(), 3);
 }
+
+#[tokio::test]
+async fn context_migrate_strategy_only_should_keep_routing_table() {
+    // Given: a Context with RoundRobin strategy and in-flight connections
+    let backends = vec![
+        create_test_backend(0, None, Some(10u8)),
+        create_test_backend(1, None, Some(10u8)),
+    ];
+    let config1 = create_test_config_fast(backends.clone(), Strategy::RoundRobin);
+    let ctx = Arc::new(Context::new(config1).expect("Failed to create context"));
+    let mut config_rx = ctx.channels().config_tx().subscribe();
+    let routing_before = ctx.routing_table();
+    for backend in routing_before.all_backends() {
+        backend.increment_connection();
+    }
+
+    // And: traffic picking backends concurrently
+    let picker_ctx = ctx.clone();
+    let picker = tokio::spawn(async move {
+        for _ in 0..50 {
+            let strategy = picker_ctx.strategy();
+            strategy
+                .pick_backend(picker_ctx.clone())
+                .await
+                .expect("Pick should succeed during migration");
+            tokio::task::yield_now().await;
+        }
+    });
+
+    // When: migrating to a config where only the strategy changed
+    let config2 = create_test_config_fast(backends, Strategy::LeastConnections);
+    let start = std::time::Instant::now();
+    let result = ctx.migrate(config2).await;
+    picker.await.expect("Picker task should not panic");
+
+    // Then: strategy is swapped without draining or rebuilding the table
+    assert!(result.is_ok());
+    assert!(start.elapsed() < Duration::from_millis(100));
+    assert_eq!(ctx.strategy().strategy(), Strategy::LeastConnections);
+    assert_eq!(ctx.config().strategy, Strategy::LeastConnections);
+    assert!(Arc::ptr_eq(&routing_before, &ctx.routing_table()));
+    for backend in ctx.routing_table().all_backends() {
+        assert_eq!(backend.active_connections(), 1);
+        assert!(!backend.is_draining());
+    }
+
+    // And: only a StrategyChanged event is broadcast
+    match config_rx.try_recv() {
+        Ok(ConfigEvent::StrategyChanged(strategy)) => {
+            assert_eq!(strategy, Strategy::LeastConnections)
+        }
+        other => panic!("Expected StrategyChanged event, got {:?}", other),
+    }
+    assert!(config_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn context_migrate_strategy_and_backends_should_rebuild_routing_table() {
+    // Given: a Context with RoundRobin strategy
+    let backends = vec![create_test_backend(0, None, Some(10u8))];
+    let config1 = create_test_config_fast(backends, Strategy::RoundRobin);
+    let ctx = Arc::new(Context::new(config1).expect("Failed to create context"));
+    let mut config_rx = ctx.channels().config_tx().subscribe();
+
+    // When: migrating with a strategy change and an added backend
+    let backends = vec![
+        create_test_backend(0, None, Some(10u8)),
+        create_test_backend(1, None, Some(10u8)),
+    ];
+    let config2 = create_test_config_fast(backends, Strategy::LeastConnections);
+    let result = ctx.migrate(config2).await;
+
+    // Then: the full migration path runs
+    assert!(result.is_ok());
+    assert_eq!(ctx.routing_table().len(), 2);
+    assert_eq!(ctx.strategy().strategy(), Strategy::LeastConnections);
+    assert!(matches!(config_rx.try_recv(), Ok(ConfigEvent::Migrated)));
+}
+
+#[test]
+fn context_new_with_duplicate_backend_ids_should_fail() {
+    // Given: a Config with two backends sharing an id
+    let mut config = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    config.backends[1].id = 0;
+
+    // When: creating a new Context
+    let result = Context::new(config);
+
+    // Then: creation fails with a duplicate id error
+    assert!(matches!(
+        result,
+        Err(ContextError::RouteTable(RouteTableError::DuplicateId(0)))
+    ));
+}
+
+#[tokio::test]
+async fn context_migrate_with_duplicate_addresses_should_fail() {
+    // Given: a Context with one backend
+    let backends = vec![create_test_backend(0, None, Some(10u8))];
+    let ctx = Arc::new(
+        Context::new(create_test_config_fast(backends, Strategy::RoundRobin))
+            .expect("Failed to create context"),
+    );
+
+    // When: migrating to a config with two backends sharing an address
+    let mut config2 = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    config2.backends[1].address = config2.backends[0].address.clone();
+    let result = ctx.migrate(config2.clone()).await;
+
+    // Then: migration is rejected and the old state is kept
+    assert!(matches!(result, Err(ContextError::Config(_))));
+    assert_eq!(ctx.routing_table().len(), 1);
+
+    // And: the same config is accepted when duplicates are allowed
+    config2.allow_duplicate_addresses = true;
+    assert!(ctx.migrate(config2).await.is_ok());
+    assert_eq!(ctx.routing_table().len(), 2);
+}
+
+#[tokio::test]
+async fn context_migrate_with_address_change_should_update_in_place() {
+    // Given: a Context with a backend on listener A and traffic recorded
+    let listener_a = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind listener A");
+    let listener_b = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind listener B");
+    let addr_a = listener_a.local_addr().expect("Failed to get address A");
+    let addr_b = listener_b.local_addr().expect("Failed to get address B");
+
+    let backend = BackendMeta::new(0u8, Some("backend-0"), addr_a, Some(10u8));
+    let config1 = create_test_config_fast(vec![backend], Strategy::RoundRobin);
+    let ctx = Arc::new(Context::new(config1.clone()).expect("Failed to create context"));
+    let mut config_rx = ctx.channels().config_tx().subscribe();
+
+    let routed = ctx.routing_table().get(0).expect("Backend not found");
+    routed.increment_connection();
+    routed.record_request(10, false);
+    routed.record_request(30, true);
+    let metrics_before = routed.metrics_snapshot();
+
+    // When: migrating the backend to listener B
+    let mut config2 = config1.clone();
+    config2.backends[0].address = addr_b.into();
+    ctx.migrate(config2).await.expect("Migration failed");
+
+    // Then: the same backend is kept, with its counters and open connection
+    let after = ctx.routing_table().get(0).expect("Backend not found");
+    assert!(Arc::ptr_eq(&routed, &after));
+    assert!(after.is_active());
+    assert_eq!(after.active_connections(), 1);
+    let metrics_after = after.metrics_snapshot();
+    assert_eq!(metrics_after.avg_latency_ms, metrics_before.avg_latency_ms);
+    assert_eq!(metrics_after.error_rate, metrics_before.error_rate);
+
+    // And: new connections are routed to listener B
+    let picked = ctx
+        .strategy()
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Pick should succeed");
+    assert_eq!(picked.address().as_str(), addr_b.to_string());
+    let _client = tokio::net::TcpStream::connect(picked.address().as_str())
+        .await
+        .expect("Failed to connect to new address");
+    let accepted =
+        tokio::time::timeout(Duration::from_millis(500), listener_b.accept()).await;
+    assert!(
+        accepted.is_ok(),
+        "Listener B should accept the new connection"
+    );
+
+    // And: an address change event precedes the migration event
+    match config_rx.try_recv() {
+        Ok(ConfigEvent::BackendAddressChanged {
+            backend_id,
+            address,
+        }) => {
+            assert_eq!(backend_id, 0);
+            assert_eq!(address.as_str(), addr_b.to_string());
+        }
+        other => panic!("Expected BackendAddressChanged event, got {:?}", other),
+    }
+    assert!(matches!(config_rx.try_recv(), Ok(ConfigEvent::Migrated)));
+}
+
+#[tokio::test]
+async fn context_migrate_swapped_addresses_should_succeed() {
+    // Given: b0 and b1 at their own addresses
+    let config1 = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    let ctx = Context::new(config1.clone()).expect("Failed to create context");
+    let (b0, b1) = (
+        ctx.routing_table().get(0).unwrap(),
+        ctx.routing_table().get(1).unwrap(),
+    );
+    let (addr0, addr1) = (b0.address(), b1.address());
+
+    // When: one reload swaps their addresses and adds b2 at none of them
+    let mut config2 = config1;
+    config2.backends[0].address = (*addr1).clone();
+    config2.backends[1].address = (*addr0).clone();
+    config2
+        .backends
+        .push(create_test_backend(2, None, Some(10u8)).into());
+    ctx.migrate(config2).await.expect("Migration failed");
+
+    // Then: both are kept and moved, neither checked against the address
+    // the other was leaving
+    let routing = ctx.routing_table();
+    assert!(Arc::ptr_eq(&b0, &routing.get(0).unwrap()));
+    assert!(Arc::ptr_eq(&b1, &routing.get(1).unwrap()));
+    assert_eq!(b0.address(), addr1);
+    assert_eq!(b1.address(), addr0);
+    assert_eq!(routing.len(), 3);
+}
+
+#[tokio::test]
+async fn context_migrate_drain_waits_on_mock_clock_should_succeed() {
+    // Given: a Context on a mock clock with a one minute drain timeout and
+    // a backend holding a connection open
+    let runtime = RuntimeConfig {
+        metrics_cap: 100,
+        health_cap: 50,
+        drain_timeout_millis: 60_000,
+        background_timeout_millis: 1000,
+        accept_timeout_millis: 2000,
+        config_watch_interval_millis: 1000,
+        drain_progress_interval_millis: 1000,
+        drain_policy: DrainPolicy::Finish,
+        proxy_worker_threads: None,
+        audit_interval_millis: None,
+        log_rate_limit: LogRateLimitConfig::default(),
+    };
+    let config1 = create_test_config(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+        runtime.clone(),
+    );
+    let clock = Arc::new(MockClock::new(0));
+    let ctx = Arc::new(
+        Context::with_clock(config1, clock.clone()).expect("Failed to create context"),
+    );
+    ctx.routing_table()
+        .get(1)
+        .expect("backend 1")
+        .increment_connection();
+
+    // When: migrating to a config without that backend
+    let config2 = create_test_config(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+        runtime,
+    );
+    let migration = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { ctx.migrate(config2).await }
+    });
+
+    // Then: the migration waits until virtual time passes the drain timeout
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!migration.is_finished());
+    assert!(ctx.routing_table().get(1).expect("backend 1").is_draining());
+    clock.advance(Duration::from_secs(60));
+    let result = tokio::time::timeout(Duration::from_secs(1), migration)
+        .await
+        .expect("Migration should finish once the clock advances")
+        .expect("Migration task panicked");
+    assert!(result.is_ok());
+    assert!(ctx.routing_table().get(1).is_none());
+}
+
+#[tokio::test]
+async fn context_migrate_address_change_during_drain_should_succeed() {
+    // Given: a Context on a mock clock with a one minute drain timeout and
+    // backend 1 holding a connection open
+    let mut config1 = create_test_config_fast(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, None, Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+    );
+    config1.runtime.drain_timeout_millis = 60_000;
+    let clock = Arc::new(MockClock::new(0));
+    let ctx = Arc::new(
+        Context::with_clock(config1.clone(), clock.clone())
+            .expect("Failed to create context"),
+    );
+    ctx.routing_table()
+        .get(1)
+        .expect("backend 1")
+        .increment_connection();
+
+    // When: a migration removes backend 1 and moves backend 0
+    let mut config2 = config1.clone();
+    config2.backends.retain(|backend| backend.id == 0);
+    config2.backends[0].address = "127.0.0.1:9999".parse::<SocketAddr>().unwrap().into();
+    let migration = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { ctx.migrate(config2).await }
+    });
+
+    // Then: backend 0 dials its new address while backend 1 still drains
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!migration.is_finished());
+    assert_eq!(
+        ctx.routing_table()
+            .get(0)
+            .expect("backend 0")
+            .address()
+            .as_str(),
+        "127.0.0.1:9999"
+    );
+    clock.advance(Duration::from_secs(60));
+    let result = tokio::time::timeout(Duration::from_secs(1), migration)
+        .await
+        .expect("Migration should finish once the clock advances")
+        .expect("Migration task panicked");
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn context_wait_for_drain_timeout_on_mock_clock_should_fail() {
+    // Given: a Context on a mock clock with a connection that won't close
+    let config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let clock = Arc::new(MockClock::new(0));
+    let ctx = Arc::new(
+        Context::with_clock(config, clock.clone()).expect("Failed to create context"),
+    );
+    ctx.routing_table()
+        .get(0)
+        .expect("backend 0")
+        .increment_connection();
+
+    // When: waiting ten minutes of virtual time for the drain
+    let drain = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { ctx.wait_for_drain(Duration::from_secs(600)).await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!drain.is_finished());
+    clock.advance(Duration::from_secs(600));
+
+    // Then: the drain times out without waiting in real time
+    let result = tokio::time::timeout(Duration::from_secs(1), drain)
+        .await
+        .expect("Drain should time out once the clock advances")
+        .expect("Drain task panicked");
+    assert!(matches!(result, Err(ContextError::DrainTimeout(_))));
+}
+
+#[tokio::test]
+async fn context_wait_for_drain_reports_progress_should_succeed() {
+    // Given: connections held on two backends and a short progress interval
+    let config = create_test_config(
+        vec![
+            create_test_backend(0, None, Some(10u8)),
+            create_test_backend(1, Some("stuck".to_string()), Some(10u8)),
+        ],
+        Strategy::RoundRobin,
+        RuntimeConfig {
+            metrics_cap: 100,
+            health_cap: 50,
+            drain_timeout_millis: 5000,
+            background_timeout_millis: 1000,
+            accept_timeout_millis: 2000,
+            config_watch_interval_millis: 1000,
+            drain_progress_interval_millis: 20,
+            drain_policy: DrainPolicy::Finish,
+            proxy_worker_threads: None,
+            audit_interval_millis: None,
+            log_rate_limit: LogRateLimitConfig::default(),
+        },
+    );
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let mut drain_rx = ctx.channels().drain_rx();
+    for id in [0, 1] {
+        ctx.routing_table()
+            .get(id)
+            .expect("backend")
+            .increment_connection();
+    }
+
+    // When: backend 0 closes its connection mid-wait and backend 1 never does
+    let closer = tokio::spawn({
+        let ctx = ctx.clone();
+        async move {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            ctx.routing_table()
+                .get(0)
+                .expect("backend 0")
+                .decrement_connection();
+            ctx.notify_connection_closed();
+        }
+    });
+    let result = ctx.wait_for_drain(Duration::from_millis(400)).await;
+    closer.await.expect("Closer task panicked");
+
+    // Then: the events count down from both backends to the stuck one
+    let mut events = Vec::new();
+    while let Ok(progress) = drain_rx.try_recv() {
+        events.push(progress);
+    }
+    let first = events.first().expect("At least one progress event");
+    assert_eq!(first.remaining_total, 2);
+    assert_eq!(first.per_backend.len(), 2);
+    let last = events.last().expect("At least one progress event");
+    assert_eq!(last.remaining_total, 1);
+    assert_eq!(last.per_backend.get(&1), Some(&1));
+    assert!(!last.per_backend.contains_key(&0));
+    assert!(
+        events
+            .windows(2)
+            .all(|pair| pair[1].remaining_total <= pair[0].remaining_total)
+    );
+
+    // Then: the timeout names the stuck backend and the drain state is cleared
+    match result {
+        Err(ContextError::DrainTimeout(message)) => {
+            assert!(message.contains("backend 1 (stuck): 1"), "{}", message);
+            assert!(!message.contains("backend 0"), "{}", message);
+        }
+        other => panic!("Expected a drain timeout, got {:?}", other),
+    }
+    assert!(ctx.drain_state().is_none());
+}
+
+#[tokio::test]
+async fn context_wait_for_drain_reports_completion_should_succeed() {
+    // Given: no open connections
+    let config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let mut drain_rx = ctx.channels().drain_rx();
+
+    // When: waiting for the drain
+    ctx.wait_for_drain(Duration::from_millis(100))
+        .await
+        .expect("Drain should complete");
+
+    // Then: a final drained event is published
+    let progress = drain_rx.try_recv().expect("Completion event");
+    assert!(progress.is_drained());
+    assert!(progress.per_backend.is_empty());
+}
+
+#[tokio::test]
+async fn context_wait_for_drain_counts_accepted_connections_should_succeed() {
+    // Given: a connection accepted but not yet attached to a backend
+    let config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let accepted = ctx.listener_generations().track();
+
+    // When: waiting for drain while it is open, then after it finishes
+    let open = ctx.wait_for_drain(Duration::from_millis(50)).await;
+    drop(accepted);
+    let drained = ctx.wait_for_drain(Duration::from_millis(50)).await;
+
+    // Then: the accepted connection holds the drain until it finishes
+    assert!(matches!(open, Err(ContextError::DrainTimeout(_))));
+    assert!(drained.is_ok());
+}
+
+#[tokio::test]
+async fn context_wait_for_generation_drain_should_succeed() {
+    // Given: a connection on a listener generation that was replaced
+    let config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let generations = ctx.listener_generations();
+    let old = generations.track();
+    let previous = generations.advance();
+    let new = generations.track();
+
+    // When: waiting for the old generation while it is open and after it closes
+    let open = ctx
+        .wait_for_generation_drain(previous, Duration::from_millis(50))
+        .await;
+    let waiter = tokio::spawn({
+        let ctx = ctx.clone();
+        async move {
+            ctx.wait_for_generation_drain(previous, Duration::from_secs(5))
+                .await
+        }
+    });
+    drop(old);
+
+    // Then: only the old generation is waited for
+    assert!(matches!(open, Err(ContextError::DrainTimeout(_))));
+    let drained = tokio::time::timeout(Duration::from_secs(1), waiter)
+        .await
+        .expect("Generation drain should finish")
+        .expect("Waiter panicked");
+    assert!(drained.is_ok());
+    assert_eq!(generations.active(new.generation()), 1);
+}
+
+#[tokio::test]
+async fn context_migrate_weight_only_should_update_in_place() {
+    // Given: a weighted round-robin Context with in-flight connections
+    let backends = vec![
+        create_test_backend(0, None, Some(1u8)),
+        create_test_backend(1, None, Some(1u8)),
+    ];
+    let config1 = create_test_config_fast(backends, Strategy::WeightedRoundRobin);
+    let ctx = Arc::new(Context::new(config1.clone()).expect("Failed to create context"));
+    let mut config_rx = ctx.channels().config_tx().subscribe();
+    let routing_before = ctx.routing_table();
+    let backends_before = routing_before.all_backends();
+    for backend in &backends_before {
+        backend.increment_connection();
+    }
+    let generation = ctx.generation();
+
+    // And: traffic picking backends concurrently
+    let picker_ctx = ctx.clone();
+    let picker = tokio::spawn(async move {
+        for _ in 0..50 {
+            picker_ctx
+                .strategy()
+                .pick_backend(picker_ctx.clone())
+                .await
+                .expect("Pick should succeed during migration");
+            tokio::task::yield_now().await;
+        }
+    });
+
+    // When: migrating to a config where only backend 1's weight changed
+    let mut config2 = config1;
+    config2.backends[1].weight = Some(3);
+    let start = std::time::Instant::now();
+    let result = ctx.migrate(config2).await;
+    picker.await.expect("Picker task should not panic");
+
+    // Then: the backend is updated in place within one generation, without
+    // draining or rebuilding anything
+    assert!(result.is_ok());
+    assert!(start.elapsed() < Duration::from_millis(100));
+    assert_eq!(ctx.generation(), generation + 1);
+    assert!(Arc::ptr_eq(&routing_before, &ctx.routing_table()));
+    for backend in &backends_before {
+        let routed = ctx
+            .routing_table()
+            .get(backend.id())
+            .expect("backend routed");
+        assert!(Arc::ptr_eq(backend, &routed));
+        assert_eq!(routed.active_connections(), 1);
+        assert!(!routed.is_draining());
+    }
+    assert_eq!(ctx.routing_table().get(1).and_then(|b| b.weight()), Some(3));
+    assert_eq!(ctx.config().backends[1].weight, Some(3));
+    assert!(config_rx.try_recv().is_err());
+
+    // And: picks follow the new weights
+    let strategy = ctx.strategy();
+    let mut picks = [0usize; 2];
+    for _ in 0..40 {
+        let backend = strategy
+            .pick_backend(ctx.clone())
+            .await
+            .expect("Pick should succeed");
+        picks[backend.id() as usize] += 1;
+    }
+    assert_eq!(picks, [10, 30]);
+}
+
+#[tokio::test]
+async fn context_migrate_weight_with_added_backend_should_update_in_place() {
+    // Given: a Context over b0 with weight 10
+    let config1 = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::WeightedRoundRobin,
+    );
+    let ctx = Context::new(config1.clone()).expect("Failed to create context");
+    let b0 = ctx.routing_table().get(0).expect("b0 routed");
+
+    // When: one reload changes b0's weight to 50 and adds b1
+    let mut config2 = config1;
+    config2.backends[0].weight = Some(50);
+    config2
+        .backends
+        .push(create_test_backend(1, None, Some(10u8)).into());
+    ctx.migrate(config2).await.expect("Failed to migrate");
+
+    // Then: b0 is kept, with the new weight the config says
+    let routed = ctx.routing_table().get(0).expect("b0 routed");
+    assert!(Arc::ptr_eq(&b0, &routed));
+    assert_eq!(routed.weight(), Some(50));
+    assert_eq!(ctx.config().backends[0].weight, Some(50));
+    assert!(ctx.routing_table().get(1).is_some());
+}
+
+#[tokio::test]
+async fn context_metrics_disabled_falls_back_to_least_connections_should_succeed() {
+    // Given: an adaptive strategy with metrics aggregation disabled
+    let backends = vec![
+        create_test_backend(0, None, Some(10u8)),
+        create_test_backend(1, None, Some(10u8)),
+    ];
+    let mut config = create_test_config_fast(backends.clone(), Strategy::Adaptive);
+    config.services.metrics = false;
+
+    // When: creating the context
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+
+    // Then: least connections serves the picks
+    assert!(!ctx.metrics_enabled());
+    assert!(ctx.health_enabled());
+    assert_eq!(ctx.strategy().strategy(), Strategy::LeastConnections);
+    ctx.strategy()
+        .pick_backend(ctx.clone())
+        .await
+        .expect("Pick should succeed");
+
+    // And: a reload to fastest response time falls back as well, even when
+    // the new file enables metrics (services are read at startup)
+    let reloaded = create_test_config_fast(backends, Strategy::FastestResponseTime);
+    ctx.migrate(reloaded).await.expect("Migration failed");
+    assert!(!ctx.metrics_enabled());
+    assert_eq!(ctx.strategy().strategy(), Strategy::LeastConnections);
+}
+
+#[test]
+fn context_metrics_enabled_keeps_adaptive_strategy_should_succeed() {
+    let config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::Adaptive,
+    );
+
+    let ctx = Context::new(config).expect("Failed to create context");
+
+    assert!(ctx.metrics_enabled());
+    assert_eq!(ctx.strategy().strategy(), Strategy::Adaptive);
+}
+
+#[tokio::test]
+async fn context_set_backends_preserves_unchanged_backends_should_succeed() {
+    // Given: a Context with two backends, one with traffic recorded
+    let config = create_test_config_fast(create_test_backends(2), Strategy::RoundRobin);
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let kept = ctx.routing_table().get(0).expect("backend 0");
+    kept.record_request(10, false);
+    kept.record_request(30, true);
+    let metrics_before = kept.metrics_snapshot();
+
+    // When: replacing backend 1 with backend 2 and keeping backend 0
+    let backends = vec![
+        config.backends[0].clone(),
+        BackendConfig::from(create_test_backend(2, None, Some(10u8))),
+    ];
+    let diff = ctx
+        .set_backends(backends)
+        .await
+        .expect("set_backends should succeed");
+
+    // Then: backend 0 is the same instance, with its counters
+    assert_eq!(diff.added_backends, vec![2]);
+    assert_eq!(diff.removed_backends, vec![1]);
+    let routing = ctx.routing_table();
+    let after = routing.get(0).expect("backend 0");
+    assert!(Arc::ptr_eq(&kept, &after));
+    let metrics_after = after.metrics_snapshot();
+    assert_eq!(metrics_after.avg_latency_ms, metrics_before.avg_latency_ms);
+    assert_eq!(metrics_after.error_rate, metrics_before.error_rate);
+    assert!(routing.get(1).is_none());
+    assert!(routing.get(2).is_some());
+
+    // And: the config follows, with everything but the backends untouched
+    let new_config = ctx.config();
+    assert_eq!(new_config.backends.len(), 2);
+    assert_eq!(new_config.proxy, config.proxy);
+    assert_eq!(new_config.health, config.health);
+}
+
+#[tokio::test]
+async fn context_set_backends_emits_events_should_succeed() {
+    // Given: a Context with three backends
+    let config = create_test_config_fast(create_test_backends(3), Strategy::RoundRobin);
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let mut health_rx = ctx.channels().health_rx().expect("health receiver");
+    let mut config_rx = ctx.channels().config_tx().subscribe();
+
+    // When: renaming backend 1, reweighting backend 2 and adding backend 3
+    let mut backends = config.backends.clone();
+    backends[1].name = Some("renamed".to_string());
+    backends[2].weight = Some(99);
+    backends.push(BackendConfig::from(create_test_backend(
+        3,
+        None,
+        Some(10u8),
+    )));
+    ctx.set_backends(backends.clone())
+        .await
+        .expect("set_backends should succeed");
+
+    // Then: one config update per added or changed backend
+    let mut updated = Vec::new();
+    while let Ok(event) = health_rx.try_recv() {
+        match event {
+            HealthEvent::BackendConfigUpdated { backend_id } => updated.push(backend_id),
+            other => panic!("Unexpected health event {:?}", other),
+        }
+    }
+    updated.sort_unstable();
+    assert_eq!(updated, vec![1, 2, 3]);
+
+    // And: a single migration broadcast
+    assert!(matches!(config_rx.try_recv(), Ok(ConfigEvent::Migrated)));
+    assert!(config_rx.try_recv().is_err());
+
+    // And: setting the same list again emits nothing
+    ctx.set_backends(backends)
+        .await
+        .expect("set_backends should succeed");
+    assert!(health_rx.try_recv().is_err());
+    assert!(config_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn context_set_backends_weight_only_broadcasts_once_should_succeed() {
+    // Given: a Context with one backend
+    let config = create_test_config_fast(create_test_backends(1), Strategy::RoundRobin);
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let routed = ctx.routing_table().get(0).expect("backend 0");
+    let mut config_rx = ctx.channels().config_tx().subscribe();
+
+    // When: changing only its weight
+    let mut backends = config.backends.clone();
+    backends[0].weight = Some(3);
+    ctx.set_backends(backends)
+        .await
+        .expect("set_backends should succeed");
+
+    // Then: the weight is updated in place with one broadcast
+    assert!(Arc::ptr_eq(&routed, &ctx.routing_table().get(0).unwrap()));
+    assert_eq!(routed.weight(), Some(3));
+    assert!(matches!(config_rx.try_recv(), Ok(ConfigEvent::Migrated)));
+    assert!(config_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn context_set_backends_with_duplicates_should_fail() {
+    // Given: a Context with one backend
+    let config = create_test_config_fast(create_test_backends(1), Strategy::RoundRobin);
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+
+    // When: setting a list with a duplicate id
+    let backends = vec![config.backends[0].clone(), config.backends[0].clone()];
+    let result = ctx.set_backends(backends).await;
+
+    // Then: it is rejected and nothing changes
+    assert!(result.is_err());
+    assert_eq!(ctx.routing_table().len(), 1);
+    assert_eq!(ctx.config().backends.len(), 1);
+}
+
+#[tokio::test]
+async fn context_migrate_rebuilds_adaptive_weights_should_succeed() {
+    // Given: backend 0 busier, backend 1 failing half its requests
+    let backends = vec![
+        create_test_backend(0, None, Some(10u8)),
+        create_test_backend(1, None, Some(10u8)),
+    ];
+    let weights = |conn_weight: f64, error_weight: f64| {
+        serde_json::json!({
+            "conn_weight": conn_weight,
+            "latency_weight": 0.0,
+            "error_weight": error_weight,
+        })
+    };
+    let mut config = create_test_config_fast(backends, Strategy::Adaptive);
+    config.strategy_params = weights(1.0, 0.0);
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let routing = ctx.routing_table();
+    let busy = routing.get(0).unwrap();
+    busy.increment_connection();
+    busy.record_request(10, false);
+    let failing = routing.get(1).unwrap();
+    failing.record_request(10, false);
+    failing.record_request(10, true);
+
+    // Then: weighing connections alone favors the idle backend
+    let picked = ctx.strategy().pick_backend(ctx.clone()).await.unwrap();
+    assert_eq!(picked.id(), 1);
+
+    // When: reloading with errors weighed alone
+    config.strategy_params = weights(0.0, 1.0);
+    ctx.migrate(config).await.expect("Migration failed");
+
+    // Then: the rebuilt strategy favors the healthy backend
+    let picked = ctx.strategy().pick_backend(ctx.clone()).await.unwrap();
+    assert_eq!(picked.id(), 0);
+}
+
+#[tokio::test]
+async fn context_migrate_invalidates_adaptive_cache_should_succeed() {
+    // Given: an adaptive strategy caching scores for a minute, and backend 0
+    // weighted above backend 1 with one connection each
+    let weighted = |first: u8, second: u8| {
+        vec![
+            create_test_backend(0, None, Some(first)),
+            create_test_backend(1, None, Some(second)),
+        ]
+    };
+    let mut config = create_test_config_fast(weighted(10, 1), Strategy::Adaptive);
+    config.strategy_params = serde_json::json!({ "cache_ttl_millis": 60_000 });
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    for backend in ctx.routing_table().all_backends() {
+        backend.increment_connection();
+    }
+    let strategy = ctx.strategy();
+    assert_eq!(strategy.pick_backend(ctx.clone()).await.unwrap().id(), 0);
+
+    // When: a migration swaps the weights, keeping the strategy
+    config.backends = weighted(1, 10).into_iter().map(Into::into).collect();
+    ctx.migrate(config).await.expect("Migration failed");
+    assert!(Arc::ptr_eq(&strategy, &ctx.strategy()));
+
+    // Then: the scores cached before the migration are not reused
+    assert_eq!(strategy.pick_backend(ctx.clone()).await.unwrap().id(), 1);
+}
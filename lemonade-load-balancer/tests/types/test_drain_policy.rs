@@ -0,0 +1,119 @@
+//! Drain policy tests
+//!
+//! Tests for DrainPolicy parsing and the backend force-close signal
+
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+#[test]
+fn drain_policy_parse_and_display_should_succeed() {
+    // Given/When: every policy form
+    let parsed: Vec<DrainPolicy> = ["finish", "immediate", "deadline:1500"]
+        .iter()
+        .map(|policy| policy.parse().expect("valid policy"))
+        .collect();
+
+    // Then: they parse and display back to the same string
+    assert_eq!(
+        parsed,
+        vec![
+            DrainPolicy::Finish,
+            DrainPolicy::Immediate,
+            DrainPolicy::Deadline(1500)
+        ]
+    );
+    assert_eq!(DrainPolicy::Deadline(1500).to_string(), "deadline:1500");
+    assert_eq!(DrainPolicy::default(), DrainPolicy::Finish);
+}
+
+#[test]
+fn drain_policy_parse_invalid_should_fail() {
+    // Given/When/Then: unknown names and malformed deadlines are rejected
+    for policy in ["", "abort", "deadline", "deadline:", "deadline:soon"] {
+        assert!(policy.parse::<DrainPolicy>().is_err(), "{}", policy);
+    }
+}
+
+#[test]
+fn drain_policy_serde_round_trip_should_succeed() {
+    // Given: a runtime config with a deadline policy
+    let json = serde_json::json!(DrainPolicy::Deadline(250));
+
+    // When/Then: it serializes as a string and deserializes back
+    assert_eq!(json, serde_json::json!("deadline:250"));
+    assert_eq!(
+        serde_json::from_value::<DrainPolicy>(json).expect("valid policy"),
+        DrainPolicy::Deadline(250)
+    );
+    assert!(serde_json::from_value::<DrainPolicy>(serde_json::json!("later")).is_err());
+}
+
+#[test]
+fn backend_close_connections_keeps_first_reason_should_succeed() {
+    // Given: a backend and a watcher
+    let backend = Backend::new(create_test_backend(0, None, Some(10u8)).into());
+    let watcher = backend.close_requested();
+    assert_eq!(*watcher.borrow(), None);
+
+    // When: closing twice with different reasons
+    backend.close_connections(CloseReason::DrainDeadline);
+    backend.close_connections(CloseReason::DrainImmediate);
+
+    // Then: the first reason sticks, also for later watchers
+    assert_eq!(*watcher.borrow(), Some(CloseReason::DrainDeadline));
+    assert_eq!(
+        *backend.close_requested().borrow(),
+        Some(CloseReason::DrainDeadline)
+    );
+}
+
+#[test]
+fn backend_evict_connections_cancels_open_connections_only_should_succeed() {
+    // Given: a backend with two registered connections, one already closed
+    let backend = Backend::new(create_test_backend(0, None, Some(10u8)).into());
+    let open = backend.register_connection(1);
+    let closed = backend.register_connection(2);
+    backend.unregister_connection(2);
+
+    // When: evicting the backend's connections
+    let evicted = backend.evict_connections();
+
+    // Then: only the open connection is cancelled, and later ones are not
+    assert_eq!(evicted, 1);
+    assert!(open.is_cancelled());
+    assert!(!closed.is_cancelled());
+    assert!(!backend.register_connection(3).is_cancelled());
+    assert_eq!(backend.evict_connections(), 1);
+}
+
+#[tokio::test]
+async fn context_drain_backend_deadline_follows_clock_should_succeed() {
+    // Given: a context on a mock clock
+    let clock = Arc::new(MockClock::new(1_000_000));
+    let config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    let ctx = Context::with_clock(config, clock.clone()).expect("context");
+    let backend = ctx.routing_table().get(0).expect("backend");
+
+    // When: draining with a 500ms deadline
+    ctx.drain_backend(&backend, DrainPolicy::Deadline(500));
+    tokio::task::yield_now().await;
+
+    // Then: connections are closed only once the clock reaches the deadline
+    assert!(backend.is_draining());
+    clock.advance(Duration::from_millis(499));
+    tokio::task::yield_now().await;
+    assert_eq!(*backend.close_requested().borrow(), None);
+    clock.advance(Duration::from_millis(1));
+    let mut watcher = backend.close_requested();
+    tokio::time::timeout(Duration::from_secs(1), watcher.wait_for(Option::is_some))
+        .await
+        .expect("deadline should close connections")
+        .expect("backend alive");
+    assert_eq!(*watcher.borrow(), Some(CloseReason::DrainDeadline));
+}
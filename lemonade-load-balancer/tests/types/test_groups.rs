@@ -0,0 +1,349 @@
+//! Tests for backend groups
+//!
+use lemonade_load_balancer::prelude::*;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Group listening on `port` with the given strategy and backends
+fn group(port: u16, strategy: Strategy, backends: Vec<BackendMeta>) -> GroupConfig {
+    GroupConfig {
+        listen_address: SocketAddr::from(([127, 0, 0, 1], port)),
+        strategy,
+        strategy_params: serde_json::Value::Null,
+        fallback: None,
+        backends: backends.into_iter().map(BackendConfig::from).collect(),
+        health: None,
+    }
+}
+
+/// Config with only named groups `api` and `web`
+fn grouped_config() -> Config {
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    let mut web = group(
+        4100,
+        Strategy::LeastConnections,
+        vec![create_test_backend(0, None, None)],
+    );
+    web.health = Some(HealthConfig {
+        interval: Duration::from_secs(1),
+        timeout: Duration::from_millis(200),
+        max_event_age_millis: 10_000,
+        evict_on_unhealthy: false,
+        slow_start_millis: 0,
+        outlier_error_percent: 0,
+        outlier_min_connections: 10,
+        outlier_window_millis: 10_000,
+        base_ejection_millis: 30_000,
+        max_ejection_percent: 10,
+        check: HealthCheck::Tcp,
+        healthy_threshold: 1,
+        unhealthy_threshold: 1,
+        jitter_percent: 0,
+        initial_state: InitialHealthState::default(),
+        reuse_connections: true,
+    });
+    config.groups = BTreeMap::from([
+        (
+            "api".to_string(),
+            group(
+                4000,
+                Strategy::RoundRobin,
+                vec![
+                    create_test_backend(0, None, None),
+                    create_test_backend(1, None, None),
+                ],
+            ),
+        ),
+        ("web".to_string(), web),
+    ]);
+    config
+}
+
+#[test]
+fn config_flat_backends_form_default_group_should_succeed() {
+    // Given: a flat config without groups
+    let config = create_test_config_fast(
+        vec![create_test_backend(0, None, None)],
+        Strategy::RoundRobin,
+    );
+
+    // When/Then: the backends form the single default group
+    assert_eq!(config.group_names(), vec![DEFAULT_GROUP.to_string()]);
+    assert_eq!(config.group_config(DEFAULT_GROUP), Some(config.clone()));
+    assert!(config.group_config("api").is_none());
+}
+
+#[test]
+fn config_group_config_should_succeed() {
+    // Given: a config with two named groups and no top-level backends
+    let config = grouped_config();
+    config.validate().expect("Grouped config should validate");
+
+    // When: splitting out the groups
+    let names = config.group_names();
+    let web = config.group_config("web").expect("web group");
+
+    // Then: there is no default group and each group gets its own settings
+    assert_eq!(names, vec!["api".to_string(), "web".to_string()]);
+    assert_eq!(web.proxy.listen_address.port(), 4100);
+    assert_eq!(web.strategy, Strategy::LeastConnections);
+    assert_eq!(web.backends.len(), 1);
+    assert_eq!(web.health.interval, Duration::from_secs(1));
+    assert!(web.groups.is_empty());
+
+    // And: unset group health settings fall back to the top-level ones
+    let api = config.group_config("api").expect("api group");
+    assert_eq!(api.health, config.health);
+}
+
+#[test]
+fn config_groups_round_trip_should_succeed() {
+    // Given: a grouped config written to a file
+    let config = grouped_config();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+    // When: loading it back
+    let loaded = ConfigBuilder::from_file(Some(config_path)).expect("Config should load");
+
+    // Then: the groups survive
+    assert_eq!(loaded.groups, config.groups);
+}
+
+#[test]
+fn config_groups_alongside_flat_backends_should_succeed() {
+    // Given: top-level backends next to a named group
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, None)],
+        Strategy::RoundRobin,
+    );
+    config
+        .groups
+        .insert("api".to_string(), grouped_config().groups["api"].clone());
+
+    // When/Then: the top-level backends still form the default group
+    config.validate().expect("Config should validate");
+    assert_eq!(
+        config.group_names(),
+        vec![DEFAULT_GROUP.to_string(), "api".to_string()]
+    );
+}
+
+#[test]
+fn config_groups_invalid_should_fail() {
+    // Given: a named default group clashing with top-level backends
+    let mut clash = create_test_config_fast(
+        vec![create_test_backend(0, None, None)],
+        Strategy::RoundRobin,
+    );
+    clash.groups.insert(
+        DEFAULT_GROUP.to_string(),
+        group(4000, Strategy::RoundRobin, Vec::new()),
+    );
+
+    // And: two groups on one listen address
+    let mut shared_address = grouped_config();
+    shared_address.groups.get_mut("web").unwrap().listen_address =
+        SocketAddr::from(([127, 0, 0, 1], 4000));
+
+    // And: a group with duplicate backend ids
+    let mut duplicate_ids = grouped_config();
+    duplicate_ids
+        .groups
+        .get_mut("web")
+        .unwrap()
+        .backends
+        .push(BackendConfig::from(create_test_backend(0, None, None)));
+
+    // When/Then: validation rejects each
+    assert!(matches!(clash.validate(), Err(ConfigError::Groups(_))));
+    assert!(matches!(
+        shared_address.validate(),
+        Err(ConfigError::Groups(_))
+    ));
+    assert!(matches!(
+        duplicate_ids.validate(),
+        Err(ConfigError::Backends(_))
+    ));
+}
+
+#[tokio::test]
+async fn groups_migrate_per_group_should_succeed() {
+    // Given: contexts for the api and web groups
+    let config = grouped_config();
+    let groups = Groups::new(&config).expect("Failed to create groups");
+    let api = groups.get("api").expect("api group");
+    let web = groups.get("web").expect("web group");
+    assert_eq!(api.group(), "api");
+    assert_eq!(web.strategy().strategy(), Strategy::LeastConnections);
+
+    // When: a backend is added to the web group only
+    let mut new_config = config.clone();
+    new_config
+        .groups
+        .get_mut("web")
+        .unwrap()
+        .backends
+        .push(BackendConfig::from(create_test_backend(1, None, None)));
+    groups
+        .migrate_as(new_config, AuditActor::Api)
+        .await
+        .expect("Migration should succeed");
+
+    // Then: only the web group's route table changed
+    assert_eq!(web.routing_table().len(), 2);
+    assert_eq!(api.routing_table().len(), 2);
+    assert!(web.config().groups.is_empty());
+}
+
+#[tokio::test]
+async fn groups_migrate_changed_group_set_should_fail() {
+    // Given: contexts for the api and web groups
+    let config = grouped_config();
+    let groups = Groups::new(&config).expect("Failed to create groups");
+
+    // When: a config drops the web group
+    let mut new_config = config.clone();
+    new_config.groups.remove("web");
+    let result = groups.migrate_as(new_config, AuditActor::Api).await;
+
+    // Then: it is rejected and the web group keeps running unchanged
+    assert!(matches!(
+        result,
+        Err(ContextError::Config(ConfigError::Groups(_)))
+    ));
+    assert!(groups.get("web").is_some());
+}
+
+#[tokio::test]
+async fn groups_shutdown_reaches_every_group_should_succeed() {
+    let groups = Arc::new(Groups::new(&grouped_config()).expect("groups"));
+    groups.forward_shutdown();
+    let mut web_shutdown = groups.get("web").unwrap().channels().shutdown_rx();
+
+    // When: only the api group is told to shut down
+    let _ = groups.get("api").unwrap().channels().shutdown_tx().send(());
+
+    // Then: the web group hears it too
+    tokio::time::timeout(Duration::from_secs(1), web_shutdown.recv())
+        .await
+        .expect("Shutdown should be forwarded")
+        .expect("Shutdown channel open");
+}
+
+/// Backend that writes its name and closes
+async fn named_backend(name: &'static str) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind backend");
+    let addr = listener.local_addr().expect("backend address");
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let _ =
+                tokio::io::AsyncWriteExt::write_all(&mut stream, name.as_bytes()).await;
+        }
+    });
+    (addr, handle)
+}
+
+/// Reserve a free local port
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve port")
+        .port()
+}
+
+/// Read the name of the backend serving one connection
+async fn backend_name(port: u16) -> String {
+    let mut client = tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .expect("Failed to connect to proxy");
+    let mut reply = String::new();
+    tokio::time::timeout(Duration::from_secs(2), client.read_to_string(&mut reply))
+        .await
+        .expect("Backend reply should arrive")
+        .expect("Failed to read reply");
+    reply
+}
+
+#[tokio::test]
+async fn groups_two_listeners_two_strategies_should_succeed() {
+    // Given: round robin over a1/a2 and weighted round robin over b1 (weight 3)
+    // and b2 (weight 1), each group on its own listener
+    let (a1, a1_handle) = named_backend("a1").await;
+    let (a2, a2_handle) = named_backend("a2").await;
+    let (b1, b1_handle) = named_backend("b1").await;
+    let (b2, b2_handle) = named_backend("b2").await;
+    let (port_a, port_b) = (free_port(), free_port());
+    let backend = |id, name, addr| {
+        BackendMeta::new(id, Some(name), BackendAddress::from(addr), Some(1u8))
+    };
+    let mut config = create_test_config_fast(Vec::new(), Strategy::RoundRobin);
+    config.groups = BTreeMap::from([
+        (
+            "a".to_string(),
+            group(
+                port_a,
+                Strategy::RoundRobin,
+                vec![backend(0u8, "a1", a1), backend(1u8, "a2", a2)],
+            ),
+        ),
+        (
+            "b".to_string(),
+            group(
+                port_b,
+                Strategy::WeightedRoundRobin,
+                vec![
+                    BackendMeta::new(
+                        0u8,
+                        Some("b1"),
+                        BackendAddress::from(b1),
+                        Some(3u8),
+                    ),
+                    backend(1u8, "b2", b2),
+                ],
+            ),
+        ),
+    ]);
+    let groups = Groups::new(&config).expect("Failed to create groups");
+    let mut proxies = Vec::new();
+    for (_, ctx) in groups.iter() {
+        let service = TokioProxyService::new(Arc::new(ArcSwap::from_pointee(
+            ctx.config().proxy.clone(),
+        )))
+        .expect("Failed to create service");
+        let ctx = ctx.clone();
+        proxies.push(tokio::spawn(async move {
+            service.accept_connections(ctx).await
+        }));
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // When: eight connections go to each listener, interleaved
+    let mut served_a = Vec::new();
+    let mut served_b = Vec::new();
+    for _ in 0..8 {
+        served_a.push(backend_name(port_a).await);
+        served_b.push(backend_name(port_b).await);
+    }
+
+    // Then: each listener only reaches its own group, using its own strategy
+    let count =
+        |served: &[String], name: &str| served.iter().filter(|s| *s == name).count();
+    assert_eq!((count(&served_a, "a1"), count(&served_a, "a2")), (4, 4));
+    assert_eq!((count(&served_b, "b1"), count(&served_b, "b2")), (6, 2));
+
+    for handle in proxies {
+        handle.abort();
+    }
+    for handle in [a1_handle, a2_handle, b1_handle, b2_handle] {
+        handle.abort();
+    }
+}
@@ -0,0 +1,157 @@
+//! Tests for LatencyHistogram
+//!
+//! This module tests:
+//! - Recording samples into buckets
+//! - Snapshot statistics (mean, percentiles)
+//! - Summaries read without a snapshot
+//! - Backend connection timing recording
+
+use lemonade_load_balancer::prelude::*;
+
+/// Test empty histogram snapshot
+///
+/// Given: a new LatencyHistogram
+/// When: taking a snapshot
+/// Then: snapshot is empty and statistics are zero
+#[test]
+fn test_latency_histogram_empty_snapshot() {
+    let histogram = LatencyHistogram::default();
+    let snapshot = histogram.snapshot();
+    assert!(snapshot.is_empty());
+    assert_eq!(
+        snapshot.buckets.len(),
+        HistogramSnapshot::bucket_bounds_micros().len() + 1
+    );
+    assert_eq!(snapshot.mean_ms(), 0.0);
+    assert_eq!(snapshot.percentile_ms(0.95), 0.0);
+}
+
+/// Test recording samples
+///
+/// Given: a LatencyHistogram
+/// When: recording samples of different magnitudes
+/// Then: count, sum and buckets reflect the samples
+#[test]
+fn test_latency_histogram_record() {
+    let histogram = LatencyHistogram::default();
+    histogram.record(80);
+    histogram.record(2_000);
+    histogram.record(60_000_000);
+
+    let snapshot = histogram.snapshot();
+    assert_eq!(snapshot.count, 3);
+    assert_eq!(snapshot.sum_micros, 60_002_080);
+    assert_eq!(snapshot.buckets[0], 1);
+    assert_eq!(snapshot.buckets[4], 1);
+    assert_eq!(snapshot.buckets[snapshot.buckets.len() - 1], 1);
+}
+
+/// Test mean and percentile
+///
+/// Given: a histogram with 19 fast samples and 1 slow sample
+/// When: computing the mean and percentiles
+/// Then: p50 stays in the fast bucket and p100 reaches the slow one
+#[test]
+fn test_latency_histogram_mean_and_percentile() {
+    let histogram = LatencyHistogram::default();
+    for _ in 0..19 {
+        histogram.record(1_000);
+    }
+    histogram.record(100_000);
+
+    let snapshot = histogram.snapshot();
+    assert!((snapshot.mean_ms() - 5.95).abs() < f64::EPSILON);
+    assert_eq!(snapshot.percentile_ms(0.5), 1.0);
+    assert_eq!(snapshot.percentile_ms(0.95), 1.0);
+    assert_eq!(snapshot.percentile_ms(1.0), 100.0);
+}
+
+/// Test the histogram summary
+///
+/// Given: a histogram with 19 fast samples and 1 slow sample
+/// When: summarizing it
+/// Then: the summary agrees with the snapshot statistics
+#[test]
+fn test_latency_histogram_summary_matches_snapshot() {
+    let histogram = LatencyHistogram::default();
+    assert_eq!(histogram.summary(), LatencySummary::default());
+    for _ in 0..19 {
+        histogram.record(1_000);
+    }
+    histogram.record(100_000);
+
+    let snapshot = histogram.snapshot();
+    assert_eq!(
+        histogram.summary(),
+        LatencySummary {
+            count: snapshot.count,
+            mean_ms: snapshot.mean_ms(),
+            p95_ms: snapshot.percentile_ms(0.95),
+        }
+    );
+}
+
+/// Test backend response latency
+///
+/// Given: a Backend
+/// When: recording requests, then connect times, then a first byte
+/// Then: the response latency follows the same fallbacks as the metrics
+///       snapshot
+#[test]
+fn test_backend_response_latency_matches_snapshot() {
+    let backend = Backend::new(BackendConfig {
+        id: 0,
+        name: Some("response-latency-test".to_string()),
+        address: BackendAddress::parse("127.0.0.1:8080").unwrap(),
+        weight: Some(10),
+        labels: Labels::new(),
+        bind_address: None,
+        dscp: None,
+        priority: None,
+        zone: None,
+    });
+    assert!(backend.response_latency().is_empty());
+
+    let assert_matches = |backend: &Backend| {
+        let metrics = backend.metrics_snapshot();
+        let response_latency = backend.response_latency();
+        assert_eq!(response_latency.mean_ms, metrics.response_latency_ms());
+        assert_eq!(response_latency.p95_ms, metrics.response_p95_latency_ms());
+    };
+    backend.record_request(100, false);
+    assert_matches(&backend);
+    backend.record_connection_timings(4_000, None, 10_000);
+    assert_matches(&backend);
+    backend.record_connection_timings(500, Some(20_000), 80_000);
+    assert_matches(&backend);
+    assert_eq!(backend.response_latency().count, 1);
+}
+
+/// Test backend connection timings
+///
+/// Given: a Backend
+/// When: recording connection timings with and without a first byte
+/// Then: ttfb only counts connections that received data
+#[test]
+fn test_backend_record_connection_timings() {
+    let backend = Backend::new(BackendConfig {
+        id: 0,
+        name: Some("timings-test".to_string()),
+        address: BackendAddress::parse("127.0.0.1:8080").unwrap(),
+        weight: Some(10),
+        labels: Labels::new(),
+        bind_address: None,
+        dscp: None,
+        priority: None,
+        zone: None,
+    });
+
+    backend.record_connection_timings(500, Some(20_000), 80_000);
+    backend.record_connection_timings(700, None, 1_000);
+
+    let timings = backend.metrics_snapshot().timings;
+    assert_eq!(timings.connect.count, 2);
+    assert_eq!(timings.ttfb.count, 1);
+    assert_eq!(timings.total.count, 2);
+    assert_eq!(timings.ttfb.sum_micros, 20_000);
+}
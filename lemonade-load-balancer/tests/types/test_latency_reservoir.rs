@@ -0,0 +1,249 @@
+//! Tests for LatencyReservoir
+//!
+//! This module tests:
+//! - Filling and flushing the reservoir
+//! - Reservoir percentiles against exact percentiles of a known distribution
+//! - Backends and contexts aggregating in reservoir mode
+
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Samples drawn from the synthetic distribution
+const STREAM_LEN: usize = 100_000;
+
+/// Exponentially distributed latencies (mean 5ms) with a 2% tail of slow
+/// responses around 200ms, from a fixed seed
+fn synthetic_latencies() -> Vec<u64> {
+    let mut rng = RngStream::from_seed(741);
+    (0..STREAM_LEN)
+        .map(|_| {
+            let base = -(1.0 - rng.next_f64()).ln() * 5_000.0;
+            let micros = if rng.chance(0.02) {
+                200_000.0 + base * 10.0
+            } else {
+                base
+            };
+            micros as u64
+        })
+        .collect()
+}
+
+/// Fraction of `sorted` samples at or below `value`
+fn rank_of(sorted: &[u64], value: u64) -> f64 {
+    sorted.partition_point(|sample| *sample <= value) as f64 / sorted.len() as f64
+}
+
+/// Test an empty reservoir
+///
+/// Given: a new LatencyReservoir
+/// When: flushing and taking a snapshot
+/// Then: nothing is published and statistics are zero
+#[test]
+fn test_latency_reservoir_empty() {
+    let reservoir = LatencyReservoir::default();
+    assert_eq!(reservoir.capacity(), RESERVOIR_CAPACITY);
+    assert_eq!(reservoir.flush(), None);
+
+    let snapshot = reservoir.snapshot();
+    assert!(snapshot.is_empty());
+    assert_eq!(snapshot.percentiles, None);
+    assert_eq!(snapshot.percentile_ms(0.99), 0.0);
+}
+
+/// Test a reservoir holding every sample
+///
+/// Given: a reservoir with room for every sample recorded
+/// When: flushing it
+/// Then: the percentiles are exact nearest-rank percentiles
+#[test]
+fn test_latency_reservoir_under_capacity_is_exact() {
+    let reservoir = LatencyReservoir::new(200);
+    for micros in (1..=100).rev() {
+        reservoir.record(micros * 1_000);
+    }
+
+    let percentiles = reservoir.flush().expect("Reservoir has samples");
+    assert_eq!(
+        percentiles,
+        LatencyPercentiles {
+            p50_micros: 50_000,
+            p95_micros: 95_000,
+            p99_micros: 99_000,
+        }
+    );
+    let snapshot = reservoir.snapshot();
+    assert_eq!(snapshot.count, 100);
+    assert_eq!(snapshot.sum_micros, 5_050_000);
+    assert_eq!(snapshot.percentile_ms(0.5), 50.0);
+    assert_eq!(snapshot.percentile_ms(0.9), 95.0);
+    assert_eq!(snapshot.percentile_ms(0.99), 99.0);
+}
+
+/// Test snapshots between flushes
+///
+/// Given: a reservoir with samples but no flush yet
+/// When: querying percentiles, then flushing and recording more
+/// Then: percentiles fall back to the mean and only move on flush
+#[test]
+fn test_latency_reservoir_percentiles_published_on_flush() {
+    let reservoir = LatencyReservoir::new(16);
+    reservoir.record(1_000);
+    reservoir.record(3_000);
+    assert_eq!(reservoir.snapshot().percentiles, None);
+    assert_eq!(reservoir.snapshot().percentile_ms(0.95), 2.0);
+
+    reservoir.flush();
+    reservoir.record(500_000);
+    let snapshot = reservoir.snapshot();
+    assert_eq!(snapshot.count, 3);
+    assert_eq!(snapshot.percentile_ms(0.99), 3.0);
+
+    reservoir.flush();
+    assert_eq!(reservoir.snapshot().percentile_ms(0.99), 500.0);
+}
+
+/// Test the reservoir summary
+///
+/// Given: a reservoir with samples but no flush yet
+/// When: summarizing it before and after a flush
+/// Then: the p95 falls back to the mean, then matches the snapshot
+#[test]
+fn test_latency_reservoir_summary_matches_snapshot() {
+    let reservoir = LatencyReservoir::new(16);
+    assert_eq!(reservoir.summary(), LatencySummary::default());
+    reservoir.record(1_000);
+    reservoir.record(3_000);
+    assert_eq!(
+        reservoir.summary(),
+        LatencySummary {
+            count: 2,
+            mean_ms: 2.0,
+            p95_ms: 2.0,
+        }
+    );
+
+    reservoir.flush();
+    let snapshot = reservoir.snapshot();
+    assert_eq!(reservoir.summary().p95_ms, snapshot.percentile_ms(0.95));
+    assert_eq!(reservoir.summary().p95_ms, 3.0);
+}
+
+/// Test reservoir accuracy
+///
+/// Given: 100k latencies from a skewed distribution with a slow tail
+/// When: sampling them into a 1024-slot reservoir
+/// Then: each reservoir percentile sits within a small rank error of the
+///       exact percentile
+#[test]
+fn test_latency_reservoir_percentiles_within_tolerance() {
+    let latencies = synthetic_latencies();
+    let reservoir = LatencyReservoir::default();
+    for micros in &latencies {
+        reservoir.record(*micros);
+    }
+    let percentiles = reservoir.flush().expect("Reservoir has samples");
+    let mut sorted = latencies.clone();
+    sorted.sort_unstable();
+    let exact = LatencyPercentiles::from_samples(latencies).expect("Stream has samples");
+
+    for (quantile, sampled, exact, rank_tolerance) in [
+        (0.50, percentiles.p50_micros, exact.p50_micros, 0.05),
+        (0.95, percentiles.p95_micros, exact.p95_micros, 0.02),
+        (0.99, percentiles.p99_micros, exact.p99_micros, 0.01),
+    ] {
+        let rank = rank_of(&sorted, sampled);
+        assert!(
+            (rank - quantile).abs() <= rank_tolerance,
+            "p{}: sampled {}us (rank {:.4}) vs exact {}us",
+            quantile * 100.0,
+            sampled,
+            rank,
+            exact
+        );
+    }
+    let snapshot = reservoir.snapshot();
+    assert_eq!(snapshot.count, STREAM_LEN as u64);
+    assert_eq!(snapshot.sum_micros, sorted.iter().sum::<u64>());
+}
+
+/// Test concurrent recording
+///
+/// Given: a reservoir shared by several threads
+/// When: each records its own distinct latencies
+/// Then: every sample is counted and the reservoir holds only recorded values
+#[test]
+fn test_latency_reservoir_concurrent_record() {
+    let reservoir = Arc::new(LatencyReservoir::new(64));
+    let threads: Vec<_> = (1..=4u64)
+        .map(|thread| {
+            let reservoir = reservoir.clone();
+            std::thread::spawn(move || {
+                for _ in 0..10_000 {
+                    reservoir.record(thread * 1_000);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().expect("Recorder thread panicked");
+    }
+
+    let percentiles = reservoir.flush().expect("Reservoir has samples");
+    assert_eq!(reservoir.snapshot().count, 40_000);
+    for value in [
+        percentiles.p50_micros,
+        percentiles.p95_micros,
+        percentiles.p99_micros,
+    ] {
+        assert!([1_000, 2_000, 3_000, 4_000].contains(&value), "{}", value);
+    }
+}
+
+/// Test latency aggregation parsing
+///
+/// Given: aggregation mode names
+/// When: parsing them
+/// Then: known modes parse and others are rejected
+#[test]
+fn test_latency_aggregation_from_str() {
+    assert_eq!(
+        "histogram".parse::<LatencyAggregation>(),
+        Ok(LatencyAggregation::Histogram)
+    );
+    assert_eq!(
+        "reservoir".parse::<LatencyAggregation>(),
+        Ok(LatencyAggregation::Reservoir)
+    );
+    assert!("hdr".parse::<LatencyAggregation>().is_err());
+}
+
+/// Test backend timings in reservoir mode
+///
+/// Given: a context aggregating latencies in reservoirs
+/// When: recording connection timings and flushing
+/// Then: the backend snapshot reports sampled percentiles and no buckets
+#[test]
+fn test_backend_reservoir_connection_timings() {
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(10u8))],
+        Strategy::RoundRobin,
+    );
+    config.metrics.aggregation = LatencyAggregation::Reservoir;
+    let ctx = Context::new(config).expect("Failed to create context");
+    let backend = ctx.routing_table().get(0).expect("Backend not found");
+    assert_eq!(backend.latency_aggregation(), LatencyAggregation::Reservoir);
+
+    for ttfb_ms in 1..=100 {
+        backend.record_connection_timings(500, Some(ttfb_ms * 1_000), 200_000);
+    }
+    backend.flush_latency_percentiles();
+
+    let metrics = backend.metrics_snapshot();
+    assert!(metrics.timings.ttfb.buckets.is_empty());
+    assert_eq!(metrics.timings.ttfb.count, 100);
+    assert_eq!(metrics.response_p95_latency_ms(), 95.0);
+    assert_eq!(backend.connect_p95_ms(), Some(0.5));
+    assert_eq!(metrics.timings.total.percentile_ms(0.5), 200.0);
+}
@@ -0,0 +1,66 @@
+//! Listener generations tests
+//!
+//! Tests for per-listener-generation connection tracking
+
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+
+fn generations() -> Arc<ListenerGenerations> {
+    Arc::new(ListenerGenerations::new(Arc::new(Notify::new())))
+}
+
+#[test]
+fn listener_generations_track_and_release_should_succeed() {
+    // Given: generation tracking on the first listener
+    let generations = generations();
+
+    // When: tracking two connections and dropping one
+    let first = generations.track();
+    let second = generations.track();
+    drop(first);
+
+    // Then: only the live connection is counted
+    assert_eq!(second.generation(), 0);
+    assert_eq!(generations.active(0), 1);
+    assert_eq!(generations.total(), 1);
+    drop(second);
+    assert_eq!(generations.total(), 0);
+    assert_eq!(generations.snapshot(), vec![(0, 0)]);
+}
+
+#[test]
+fn listener_generations_advance_keeps_old_connections_should_succeed() {
+    // Given: a connection open on the first listener
+    let generations = generations();
+    let old = generations.track();
+
+    // When: the listener is replaced and a new connection arrives
+    let previous = generations.advance();
+    let new = generations.track();
+
+    // Then: both generations are counted separately
+    assert_eq!(previous, 0);
+    assert_eq!(generations.current(), 1);
+    assert_eq!(new.generation(), 1);
+    assert_eq!(generations.snapshot(), vec![(0, 1), (1, 1)]);
+
+    // And: the old generation disappears once drained
+    drop(old);
+    assert_eq!(generations.snapshot(), vec![(1, 1)]);
+    drop(new);
+    assert_eq!(generations.snapshot(), vec![(1, 0)]);
+}
+
+#[test]
+fn listener_generations_advance_without_connections_should_succeed() {
+    // Given: an idle listener
+    let generations = generations();
+
+    // When: rebinding twice
+    generations.advance();
+    generations.advance();
+
+    // Then: only the current, empty generation is reported
+    assert_eq!(generations.current(), 2);
+    assert_eq!(generations.snapshot(), vec![(2, 0)]);
+}
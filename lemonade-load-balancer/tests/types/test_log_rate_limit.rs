@@ -0,0 +1,165 @@
+//! Tests for LogRateLimiter
+//!
+use lemonade_load_balancer::prelude::*;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+
+/// Log output captured by a test subscriber
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    /// Install a subscriber writing into the capture on this thread
+    fn install(&self) -> tracing::subscriber::DefaultGuard {
+        tracing_subscriber::fmt()
+            .with_writer(self.clone())
+            .with_ansi(false)
+            .with_max_level(tracing::Level::WARN)
+            .finish()
+            .set_default()
+    }
+
+    /// Captured lines containing `needle`
+    fn lines_with(&self, needle: &str) -> Vec<String> {
+        let bytes = self.0.lock().unwrap().clone();
+        String::from_utf8_lossy(&bytes)
+            .lines()
+            .filter(|line| line.contains(needle))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+const LIMIT: LogRateLimitConfig = LogRateLimitConfig {
+    window_millis: 1_000,
+    burst: 10,
+};
+
+/// Log a "No backend available" warning through `limiter`, as the proxy does
+fn warn_no_backend(limiter: &LogRateLimiter, now_ms: u64, config: &LogRateLimitConfig) {
+    if limiter.admit("test.no_backend", now_ms, config) {
+        tracing::warn!("No backend available");
+    }
+}
+
+#[test]
+fn log_rate_limiter_burst_is_summarized_should_succeed() {
+    let logs = CapturedLogs::default();
+    let _guard = logs.install();
+    let limiter = LogRateLimiter::new();
+
+    // Given: a burst of 25 identical warnings within one window
+    for offset in 0..25 {
+        warn_no_backend(&limiter, 100 + offset, &LIMIT);
+    }
+
+    // Then: only the first 10 are logged and the rest are counted
+    assert_eq!(logs.lines_with("No backend available").len(), 10);
+    assert_eq!(limiter.suppressed("test.no_backend"), 15);
+    assert!(logs.lines_with("Suppressed").is_empty());
+
+    // When: the next window opens
+    warn_no_backend(&limiter, 1_100, &LIMIT);
+
+    // Then: one summary reports the suppressed count and the message logs again
+    let summaries = logs.lines_with("Suppressed 15 similar messages");
+    assert_eq!(summaries.len(), 1);
+    assert!(summaries[0].contains("test.no_backend"));
+    assert_eq!(logs.lines_with("No backend available").len(), 11);
+    assert_eq!(limiter.suppressed("test.no_backend"), 0);
+    assert_eq!(limiter.suppressed_total(), 15);
+}
+
+#[test]
+fn log_rate_limiter_quiet_window_has_no_summary_should_succeed() {
+    let logs = CapturedLogs::default();
+    let _guard = logs.install();
+    let limiter = LogRateLimiter::new();
+
+    // Given: a window that stayed within its burst
+    for offset in 0..5 {
+        warn_no_backend(&limiter, offset, &LIMIT);
+    }
+
+    // When: the next window opens
+    warn_no_backend(&limiter, 5_000, &LIMIT);
+
+    // Then: every message was logged and nothing is summarized
+    assert_eq!(logs.lines_with("No backend available").len(), 6);
+    assert!(logs.lines_with("Suppressed").is_empty());
+    assert_eq!(limiter.suppressed_total(), 0);
+}
+
+#[test]
+fn log_rate_limiter_callsites_are_independent_should_succeed() {
+    // Given: one callsite that spent its burst
+    let limiter = LogRateLimiter::new();
+    let config = LogRateLimitConfig {
+        window_millis: 1_000,
+        burst: 1,
+    };
+    assert!(limiter.admit("test.first", 0, &config));
+    assert!(!limiter.admit("test.first", 1, &config));
+
+    // Then: another callsite still has its own budget
+    assert!(limiter.admit("test.second", 2, &config));
+    assert_eq!(limiter.suppressed("test.first"), 1);
+    assert_eq!(limiter.suppressed("test.second"), 0);
+}
+
+#[test]
+fn log_rate_limiter_disabled_should_succeed() {
+    // Given: rate limiting switched off
+    let limiter = LogRateLimiter::new();
+    let config = LogRateLimitConfig {
+        window_millis: 0,
+        burst: 1,
+    };
+
+    // Then: every message is admitted
+    assert!((0..100).all(|now_ms| limiter.admit("test.off", now_ms, &config)));
+    assert_eq!(limiter.suppressed_total(), 0);
+}
+
+#[test]
+fn context_should_log_applies_runtime_config_should_succeed() {
+    // Given: a context allowing two messages per window
+    let mut config = create_test_config_fast(
+        vec![create_test_backend(0, None, Some(1u8))],
+        Strategy::RoundRobin,
+    );
+    config.runtime.log_rate_limit = LogRateLimitConfig {
+        window_millis: 60_000,
+        burst: 2,
+    };
+    let ctx = Context::new(config).expect("Failed to create context");
+
+    // Then: the third message within the window is suppressed
+    assert!(ctx.should_log("test.ctx"));
+    assert!(ctx.should_log("test.ctx"));
+    assert!(!ctx.should_log("test.ctx"));
+    assert_eq!(ctx.log_limiter().suppressed("test.ctx"), 1);
+}
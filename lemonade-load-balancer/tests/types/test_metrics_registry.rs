@@ -32,6 +32,12 @@ fn test_update() {
         p95_latency_ms: 20.0,
         error_rate: 0.1,
         last_updated_ms: 1000,
+        weight_multiplier: 1.0,
+        cpu_percent: None,
+        rss_bytes: None,
+        timings: ConnectionTimings::default(),
+        bytes_in_rate: 0,
+        bytes_out_rate: 0,
     };
     snapshot.update(1, metrics.clone());
     assert!(snapshot.has_metrics(1));
@@ -55,6 +61,12 @@ fn test_update_overwrite() {
         p95_latency_ms: 20.0,
         error_rate: 0.1,
         last_updated_ms: 1000,
+        weight_multiplier: 1.0,
+        cpu_percent: None,
+        rss_bytes: None,
+        timings: ConnectionTimings::default(),
+        bytes_in_rate: 0,
+        bytes_out_rate: 0,
     };
     snapshot.update(1, metrics1);
     let metrics2 = BackendMetrics {
@@ -62,6 +74,12 @@ fn test_update_overwrite() {
         p95_latency_ms: 25.0,
         error_rate: 0.2,
         last_updated_ms: 2000,
+        weight_multiplier: 1.0,
+        cpu_percent: None,
+        rss_bytes: None,
+        timings: ConnectionTimings::default(),
+        bytes_in_rate: 0,
+        bytes_out_rate: 0,
     };
     snapshot.update(1, metrics2);
     let retrieved = snapshot.get(1).expect("Metrics not found");
@@ -82,6 +100,12 @@ fn test_get_existing() {
         p95_latency_ms: 20.0,
         error_rate: 0.1,
         last_updated_ms: 1000,
+        weight_multiplier: 1.0,
+        cpu_percent: None,
+        rss_bytes: None,
+        timings: ConnectionTimings::default(),
+        bytes_in_rate: 0,
+        bytes_out_rate: 0,
     };
     snapshot.update(1, metrics.clone());
     let retrieved = snapshot.get(1);
@@ -169,6 +193,12 @@ fn test_avg_latency_existing() {
         p95_latency_ms: 50.0,
         error_rate: 0.05,
         last_updated_ms: 1000,
+        weight_multiplier: 1.0,
+        cpu_percent: None,
+        rss_bytes: None,
+        timings: ConnectionTimings::default(),
+        bytes_in_rate: 0,
+        bytes_out_rate: 0,
     };
     snapshot.update(1, metrics);
     assert_eq!(snapshot.avg_latency(1), Some(25.5));
@@ -198,6 +228,12 @@ fn test_error_rate_existing() {
         p95_latency_ms: 20.0,
         error_rate: 0.15,
         last_updated_ms: 1000,
+        weight_multiplier: 1.0,
+        cpu_percent: None,
+        rss_bytes: None,
+        timings: ConnectionTimings::default(),
+        bytes_in_rate: 0,
+        bytes_out_rate: 0,
     };
     snapshot.update(1, metrics);
     assert_eq!(snapshot.error_rate(1), Some(0.15));
@@ -255,12 +291,24 @@ fn test_multiple_backends() {
         p95_latency_ms: 20.0,
         error_rate: 0.1,
         last_updated_ms: 1000,
+        weight_multiplier: 1.0,
+        cpu_percent: None,
+        rss_bytes: None,
+        timings: ConnectionTimings::default(),
+        bytes_in_rate: 0,
+        bytes_out_rate: 0,
     };
     let metrics2 = BackendMetrics {
         avg_latency_ms: 20.0,
         p95_latency_ms: 40.0,
         error_rate: 0.2,
         last_updated_ms: 2000,
+        weight_multiplier: 1.0,
+        cpu_percent: None,
+        rss_bytes: None,
+        timings: ConnectionTimings::default(),
+        bytes_in_rate: 0,
+        bytes_out_rate: 0,
     };
     snapshot.update(1, metrics1);
     snapshot.update(2, metrics2);
@@ -296,6 +344,12 @@ fn test_backend_metrics_clone() {
         p95_latency_ms: 20.0,
         error_rate: 0.1,
         last_updated_ms: 1000,
+        weight_multiplier: 1.0,
+        cpu_percent: None,
+        rss_bytes: None,
+        timings: ConnectionTimings::default(),
+        bytes_in_rate: 0,
+        bytes_out_rate: 0,
     };
     let cloned = metrics.clone();
     assert_eq!(cloned.avg_latency_ms, metrics.avg_latency_ms);
@@ -316,6 +370,12 @@ fn test_backend_metrics_debug() {
         p95_latency_ms: 20.0,
         error_rate: 0.1,
         last_updated_ms: 1000,
+        weight_multiplier: 1.0,
+        cpu_percent: None,
+        rss_bytes: None,
+        timings: ConnectionTimings::default(),
+        bytes_in_rate: 0,
+        bytes_out_rate: 0,
     };
     let debug_str = format!("{:?}", metrics);
     assert!(!debug_str.is_empty());
@@ -332,3 +392,66 @@ fn test_metrics_snapshot_debug() {
     let debug_str = format!("{:?}", snapshot);
     assert!(!debug_str.is_empty());
 }
+
+/// Test response latency prefers time-to-first-byte
+///
+/// Given: BackendMetrics with and without connection timings
+/// When: reading the response latency
+/// Then: ttfb is preferred, then connect time, then the request average
+#[test]
+fn test_backend_metrics_response_latency() {
+    let backend = Backend::new(BackendConfig {
+        id: 0,
+        name: None,
+        address: BackendAddress::parse("127.0.0.1:8080").unwrap(),
+        weight: Some(10),
+        labels: Labels::new(),
+        bind_address: None,
+        dscp: None,
+        priority: None,
+        zone: None,
+    });
+    backend.record_request(200, false);
+    assert_eq!(backend.metrics_snapshot().response_latency_ms(), 200.0);
+
+    backend.record_connection_timings(2_000, None, 200_000);
+    assert_eq!(backend.metrics_snapshot().response_latency_ms(), 2.0);
+
+    backend.record_connection_timings(2_000, Some(10_000), 200_000);
+    let metrics = backend.metrics_snapshot();
+    assert_eq!(metrics.response_latency_ms(), 10.0);
+    assert_eq!(metrics.response_p95_latency_ms(), 10.0);
+}
+
+/// Test retain backends
+///
+/// Given: a MetricsSnapshot with metrics for three backends
+/// When: retaining only two of them
+/// Then: the third is evicted and reported
+#[test]
+fn test_retain_backends() {
+    let snapshot = MetricsSnapshot::default();
+    for id in 0..3 {
+        snapshot.update(
+            id,
+            BackendMetrics {
+                avg_latency_ms: 10.0,
+                p95_latency_ms: 20.0,
+                error_rate: 0.0,
+                last_updated_ms: 1000,
+                weight_multiplier: 1.0,
+                cpu_percent: None,
+                rss_bytes: None,
+                timings: ConnectionTimings::default(),
+                bytes_in_rate: 0,
+                bytes_out_rate: 0,
+            },
+        );
+    }
+    assert_eq!(snapshot.len(), 3);
+
+    assert_eq!(snapshot.retain_backends(&[0, 2]), 1);
+    assert_eq!(snapshot.len(), 2);
+    assert!(!snapshot.has_metrics(1));
+    assert_eq!(snapshot.retain_backends(&[0, 2]), 0);
+}
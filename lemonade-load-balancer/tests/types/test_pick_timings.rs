@@ -0,0 +1,26 @@
+//! Tests for PickTimings
+//!
+use lemonade_load_balancer::prelude::*;
+
+#[test]
+fn pick_timings_record_per_strategy_should_succeed() {
+    // Given: empty pick timings
+    let timings = PickTimings::default();
+    assert!(timings.get("round_robin").is_none());
+
+    // When: two strategies record picks
+    timings.record("round_robin", 3);
+    timings.record("round_robin", 7);
+    timings.record("adaptive", 1_500);
+
+    // Then: each strategy has its own histogram
+    let round_robin = timings.get("round_robin").expect("round robin samples");
+    assert_eq!(round_robin.count, 2);
+    assert_eq!(round_robin.sum_micros, 10);
+    let snapshot = timings.snapshot();
+    assert_eq!(
+        snapshot.keys().collect::<Vec<_>>(),
+        ["adaptive", "round_robin"]
+    );
+    assert_eq!(snapshot["adaptive"].percentile_ms(0.99), 2.5);
+}
@@ -0,0 +1,46 @@
+//! Tests for Readiness
+//!
+use lemonade_load_balancer::prelude::*;
+use std::sync::Arc;
+
+use crate::common::fixtures::create_test_context;
+
+#[tokio::test]
+async fn readiness_wait_config_committed_blocks_until_marked_should_succeed() {
+    // Given: readiness without a configuration commit and a waiting task
+    let readiness = Arc::new(Readiness::default());
+    assert!(!readiness.is_config_committed());
+    let waiter = tokio::spawn({
+        let readiness = readiness.clone();
+        async move { readiness.wait_config_committed().await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!waiter.is_finished());
+
+    // When: the initial configuration is committed
+    readiness.mark_config_committed();
+
+    // Then: the waiter is released
+    tokio::time::timeout(Duration::from_secs(1), waiter)
+        .await
+        .expect("Waiter should be released")
+        .expect("Waiter should not panic");
+    assert!(readiness.is_config_committed());
+}
+
+#[tokio::test]
+async fn context_new_commits_initial_config_should_succeed() {
+    // Given: a freshly built context
+    let ctx = create_test_context(vec![]);
+
+    // When: waiting for the initial configuration commit
+    let waited = tokio::time::timeout(
+        Duration::from_millis(100),
+        ctx.readiness().wait_config_committed(),
+    )
+    .await;
+
+    // Then: the backend set was committed at construction
+    assert!(waited.is_ok());
+    assert!(ctx.readiness().is_config_committed());
+}
@@ -0,0 +1,92 @@
+//! Tests for the retry budget
+//!
+use lemonade_load_balancer::prelude::*;
+
+use crate::common::fixtures::{create_test_backends, create_test_config_fast};
+
+fn budget_config(ratio: f64, max_tokens: u32) -> RetryBudgetConfig {
+    RetryBudgetConfig {
+        enabled: true,
+        ratio,
+        max_tokens,
+    }
+}
+
+#[test]
+fn retry_budget_exhausts_and_recovers_should_succeed() {
+    // Given: a budget of two attempts earning one per five successes
+    let config = budget_config(0.2, 2);
+    let budget = RetryBudget::new(config.max_tokens);
+
+    // When: three extra attempts are made without any success
+    let allowed = (0..3).filter(|_| budget.try_spend(&config)).count();
+
+    // Then: the third is suppressed
+    assert_eq!(allowed, 2);
+    assert_eq!(budget.totals(), (2, 1));
+    assert_eq!(budget.tokens(), 0.0);
+
+    // When: four successes refill less than one attempt
+    (0..4).for_each(|_| budget.record_success(&config));
+
+    // Then: extra attempts are still suppressed
+    assert!(!budget.try_spend(&config));
+    assert_eq!(budget.totals(), (2, 2));
+
+    // When: the fifth success completes an attempt
+    budget.record_success(&config);
+
+    // Then: one extra attempt is allowed again
+    assert!(budget.try_spend(&config));
+    assert!(!budget.try_spend(&config));
+    assert_eq!(budget.totals(), (3, 3));
+}
+
+#[test]
+fn retry_budget_refill_capped_at_max_tokens_should_succeed() {
+    // Given: an empty budget capped at three attempts
+    let config = budget_config(1.0, 3);
+    let budget = RetryBudget::new(0);
+
+    // When: many successes are recorded
+    (0..10).for_each(|_| budget.record_success(&config));
+
+    // Then: only three attempts were saved up
+    assert_eq!(budget.tokens(), 3.0);
+    assert_eq!((0..5).filter(|_| budget.try_spend(&config)).count(), 3);
+}
+
+#[test]
+fn retry_budget_disabled_never_suppresses_should_succeed() {
+    // Given: an empty budget that is not enforced
+    let config = RetryBudgetConfig {
+        enabled: false,
+        ..RetryBudgetConfig::default()
+    };
+    let budget = RetryBudget::new(0);
+
+    // Then: every extra attempt is allowed
+    assert!((0..5).all(|_| budget.try_spend(&config)));
+    assert_eq!(budget.totals(), (5, 0));
+}
+
+#[test]
+fn retry_budget_config_rejects_negative_ratio_should_fail() {
+    assert!(budget_config(-0.1, 10).validate().is_err());
+    assert!(budget_config(f64::NAN, 10).validate().is_err());
+    assert!(RetryBudgetConfig::default().validate().is_ok());
+}
+
+#[test]
+fn context_retry_budget_starts_full_should_succeed() {
+    // Given: a config allowing four saved attempts
+    let mut config =
+        create_test_config_fast(create_test_backends(2), Strategy::RoundRobin);
+    config.proxy.retry_budget = budget_config(0.2, 4);
+
+    // When: creating the context
+    let ctx = Context::new(config).expect("Failed to create context");
+
+    // Then: its budget holds four attempts
+    assert_eq!(ctx.retry_budget().tokens(), 4.0);
+}
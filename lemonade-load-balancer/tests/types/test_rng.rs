@@ -0,0 +1,105 @@
+//! Rng tests
+//!
+//! Tests for the seeded RngProvider and its per-component streams
+
+use crate::common::fixtures::{create_test_backend, create_test_config_fast};
+use lemonade_load_balancer::prelude::*;
+use rstest::rstest;
+
+/// First values drawn from a component stream
+fn draws(provider: &RngProvider, component: &str, count: usize) -> Vec<u64> {
+    let mut stream = provider.stream(component);
+    (0..count).map(|_| stream.next_u64()).collect()
+}
+
+#[test]
+fn rng_provider_same_seed_same_streams_should_succeed() {
+    // Given: two providers with the same seed
+    let first = RngProvider::new(Some(42));
+    let second = RngProvider::new(Some(42));
+
+    // When: drawing from the same component stream
+    // Then: both produce the same sequence, and report the seed
+    assert_eq!(first.seed(), 42);
+    assert_eq!(
+        draws(&first, "strategy", 32),
+        draws(&second, "strategy", 32)
+    );
+    assert_ne!(
+        draws(&first, "strategy", 32),
+        draws(&RngProvider::new(Some(43)), "strategy", 32)
+    );
+}
+
+#[test]
+fn rng_provider_component_streams_are_independent_should_succeed() {
+    // Given: a seeded provider
+    let provider = RngProvider::new(Some(7));
+    let expected = draws(&provider, "shedding", 16);
+
+    // When: another component draws heavily before shedding does
+    let _ = draws(&provider, "strategy", 1_000);
+
+    // Then: the shedding stream is unchanged, and differs from the other one
+    assert_eq!(draws(&provider, "shedding", 16), expected);
+    assert_ne!(draws(&provider, "strategy", 16), expected);
+}
+
+#[rstest]
+#[case(0)]
+#[case(1)]
+#[case(3)]
+#[case(1_000)]
+fn rng_stream_below_and_chance_stay_in_range_should_succeed(#[case] bound: u64) {
+    // Given: a seeded stream
+    let mut stream = RngStream::from_seed(bound);
+
+    // When: drawing bounded values and probabilities
+    for _ in 0..1_000 {
+        // Then: values stay within their bounds
+        assert!(stream.below(bound) < bound.max(1));
+        let value = stream.next_f64();
+        assert!((0.0..1.0).contains(&value));
+        assert!(!stream.chance(0.0));
+        assert!(stream.chance(1.0));
+    }
+}
+
+#[tokio::test]
+async fn context_rng_seed_reproduces_pick_sequences_should_succeed() {
+    // Given: two runs of every built-in strategy with the same rng_seed
+    for strategy in [
+        Strategy::Adaptive,
+        Strategy::FastestResponseTime,
+        Strategy::LeastConnections,
+        Strategy::Random,
+        Strategy::RoundRobin,
+        Strategy::WeightedRoundRobin,
+    ] {
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let backends = (0..4)
+                .map(|id| create_test_backend(id, None, Some(id + 1)))
+                .collect();
+            let mut config = create_test_config_fast(backends, strategy.clone());
+            config.rng_seed = Some(1234);
+            let ctx = Arc::new(Context::new(config).expect("context"));
+
+            // When: picking a sequence of backends
+            let mut picks = Vec::new();
+            for _ in 0..32 {
+                let backend = ctx
+                    .strategy()
+                    .pick_backend(ctx.clone())
+                    .await
+                    .expect("Failed to pick backend");
+                picks.push(backend.id());
+            }
+            assert_eq!(ctx.rng().seed(), 1234);
+            runs.push(picks);
+        }
+
+        // Then: both runs picked the same backends in the same order
+        assert_eq!(runs[0], runs[1], "{:?}", strategy);
+    }
+}
@@ -5,6 +5,10 @@
 //! - Accessors (len, is_empty, get, backend_ids)
 //! - Search operations (find_index, contains)
 //! - Filtering (healthy_backends, active_backends, draining_backends)
+//! - Priority tiers (active_priority, preferred_backends_matching)
+//! - Pending connect cap (has_connect_room, preferred_backends_matching)
+//! - Circuit breaker (has_connect_room, preferred_backends_matching)
+//! - Local zone preference (preferred_zone, healthy_backends_preferring_zone)
 //! - Insertion and removal
 
 use super::super::common::fixtures::*;
@@ -318,9 +322,16 @@ fn route_table_insert_should_succeed() {
         name: Some("new-backend".to_string()),
         address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 9090).into(),
         weight: Some(20),
+        labels: Labels::new(),
+        bind_address: None,
+        dscp: None,
+        priority: None,
+        zone: None,
     };
     let backend = Arc::new(Backend::new(config));
-    table.insert(backend.clone());
+    table
+        .insert(backend.clone())
+        .expect("Insert should succeed");
 
     // Then: backend is inserted
     assert_eq!(table.len(), 1);
@@ -442,3 +453,311 @@ fn route_table_active_backends_filters_draining_should_succeed() {
     // Then: draining backend is not included
     assert_eq!(active.len(), 0);
 }
+
+#[test]
+fn route_table_preferred_backends_by_group_should_succeed() {
+    // Given: web backends in tiers 0 and 1, and an api backend in tier 2
+    let backends = (0..4)
+        .map(|id| {
+            let mut config = backend_meta_to_config(create_test_backend_with_details(
+                id,
+                &format!("backend-{}", id),
+                8080 + id as u16,
+            ));
+            let group = if id == 3 { "api" } else { "web" };
+            config.labels = Labels::from([("group".to_string(), group.to_string())]);
+            config.priority = [None, Some(1), Some(1), Some(2)][id as usize];
+            config
+        })
+        .collect();
+    let table = RouteTable::new(backends);
+    let web = LabelSelector::single("group", "web");
+    let api = LabelSelector::single("group", "api");
+    let ids = |backends: Vec<Arc<Backend>>| -> Vec<BackendId> {
+        backends.iter().map(|backend| backend.id()).collect()
+    };
+
+    // Then: each group is served by its own most preferred tier
+    assert_eq!(table.active_priority(&web), Some(0));
+    assert_eq!(ids(table.preferred_backends_matching(&web)), [0]);
+    assert_eq!(table.active_priority(&api), Some(2));
+    assert_eq!(ids(table.preferred_backends_matching(&api)), [3]);
+
+    // When: the web primary fails
+    table.get(0).unwrap().set_health(false, 1000);
+
+    // Then: the web backups take over, and the healthy list is unchanged
+    assert_eq!(table.active_priority(&web), Some(1));
+    assert_eq!(ids(table.preferred_backends_matching(&web)), [1, 2]);
+    assert_eq!(table.healthy_backends().len(), 3);
+
+    // When: every web backend fails
+    table.get(1).unwrap().set_health(false, 1000);
+    table.get(2).unwrap().set_health(false, 1000);
+
+    // Then: the web group has no tier left
+    assert_eq!(table.active_priority(&web), None);
+    assert!(table.preferred_backends_matching(&web).is_empty());
+}
+
+#[test]
+fn route_table_preferred_backends_skip_pending_connect_cap_should_succeed() {
+    // Given: two backends, at most two connects in flight each
+    let backends = (0..2)
+        .map(|id| {
+            backend_meta_to_config(create_test_backend_with_details(
+                id,
+                &format!("backend-{}", id),
+                8080 + id as u16,
+            ))
+        })
+        .collect();
+    let table = RouteTable::new(backends).with_max_pending_connects(2);
+    let any = LabelSelector::default();
+    assert_eq!(table.max_pending_connects(), Some(2));
+
+    // When: backend 0 reaches the cap
+    let saturated = table.get(0).unwrap();
+    assert!(saturated.try_reserve_connect(2));
+    assert!(saturated.try_reserve_connect(2));
+
+    // Then: picks skip it, though it stays healthy and in the tier
+    assert!(!table.has_connect_room(&saturated));
+    let preferred: Vec<BackendId> = table
+        .preferred_backends_matching(&any)
+        .iter()
+        .map(|backend| backend.id())
+        .collect();
+    assert_eq!(preferred, [1]);
+    assert_eq!(table.healthy_backends().len(), 2);
+    assert_eq!(table.active_priority(&any), Some(0));
+
+    // When: one of its connects resolves
+    saturated.release_connect();
+
+    // Then: it is picked from again
+    assert_eq!(table.preferred_backends_matching(&any).len(), 2);
+
+    // And: a table without a cap never skips a backend
+    assert!(RouteTable::new(Vec::new()).max_pending_connects().is_none());
+}
+
+#[test]
+fn route_table_preferred_backends_skip_open_circuit_should_succeed() {
+    // Given: two backends behind a circuit breaker opening after two connect
+    // failures for 1s
+    let backends = (0..2)
+        .map(|id| {
+            backend_meta_to_config(create_test_backend_with_details(
+                id,
+                &format!("backend-{}", id),
+                8080 + id as u16,
+            ))
+        })
+        .collect();
+    let breaker = CircuitBreakerConfig {
+        enabled: true,
+        failure_threshold: 2,
+        open_duration_millis: 1_000,
+    };
+    let clock = Arc::new(MockClock::new(10_000));
+    let table =
+        RouteTable::new(backends).with_circuit_breaker(breaker.clone(), clock.clone());
+    let any = LabelSelector::default();
+    let preferred = |table: &RouteTable| -> Vec<BackendId> {
+        table
+            .preferred_backends_matching(&any)
+            .iter()
+            .map(|backend| backend.id())
+            .collect()
+    };
+
+    // When: backend 0's connects fail twice in a row
+    let failing = table.get(0).unwrap();
+    failing.circuit().record_failure(10_000, &breaker);
+    assert_eq!(preferred(&table), [0, 1]);
+    failing.circuit().record_failure(10_000, &breaker);
+
+    // Then: picks skip it while its circuit is open, though it stays healthy
+    assert!(!table.has_connect_room(&failing));
+    assert_eq!(preferred(&table), [1]);
+    assert_eq!(table.healthy_backends().len(), 2);
+
+    // When: the open duration is over
+    clock.advance(std::time::Duration::from_millis(1_000));
+
+    // Then: it is picked from again, for its half-open trial
+    assert_eq!(preferred(&table), [0, 1]);
+
+    // And: a disabled breaker never skips a backend
+    let table = RouteTable::new(vec![backend_meta_to_config(
+        create_test_backend_with_details(0, "backend-0", 8080),
+    )])
+    .with_circuit_breaker(CircuitBreakerConfig::default(), clock);
+    let failing = table.get(0).unwrap();
+    for _ in 0..10 {
+        failing.circuit().record_failure(10_000, &breaker);
+    }
+    assert!(table.has_connect_room(&failing));
+}
+
+#[test]
+fn route_table_preferred_zone_spillover_should_succeed() {
+    // Given: backends 0 and 1 in zone "a", 2 in zone "b" and 3 without one,
+    // keeping picks in zone "a" while two of its backends are healthy
+    let backends = (0..4)
+        .map(|id| {
+            let mut config = backend_meta_to_config(create_test_backend_with_details(
+                id,
+                &format!("backend-{}", id),
+                8080 + id as u16,
+            ));
+            config.zone =
+                [Some("a"), Some("a"), Some("b"), None][id as usize].map(String::from);
+            config
+        })
+        .collect();
+    let table = RouteTable::new(backends).with_local_zone(Some("a".to_string()), 2);
+    let any = LabelSelector::default();
+    let ids = |backends: Vec<Arc<Backend>>| -> Vec<BackendId> {
+        backends.iter().map(|backend| backend.id()).collect()
+    };
+
+    // Then: only the local backends are preferred
+    assert_eq!(table.local_zone(), Some("a"));
+    assert_eq!(table.preferred_zone(&any), Some("a"));
+    assert_eq!(ids(table.preferred_backends_matching(&any)), [0, 1]);
+    assert_eq!(ids(table.healthy_backends_preferring_zone("a")), [0, 1]);
+    assert_eq!(
+        ids(table.healthy_backends_preferring_zone("b")),
+        [0, 1, 2, 3]
+    );
+
+    // When: a local backend fails
+    table.get(0).unwrap().set_health(false, 1000);
+
+    // Then: the zone is below the threshold and every zone is preferred
+    assert_eq!(table.preferred_zone(&any), None);
+    assert_eq!(ids(table.preferred_backends_matching(&any)), [1, 2, 3]);
+    assert_eq!(ids(table.healthy_backends_preferring_zone("a")), [1, 2, 3]);
+
+    // When: it recovers
+    table.get(0).unwrap().set_health(true, 1000);
+
+    // Then: picks go back to the local zone
+    assert_eq!(ids(table.preferred_backends_matching(&any)), [0, 1]);
+
+    // And: a table without a local zone prefers none
+    let table = RouteTable::new(Vec::new());
+    assert!(table.local_zone().is_none());
+    assert!(table.preferred_zone(&any).is_none());
+}
+
+#[test]
+fn route_table_insert_duplicate_id_should_fail() {
+    // Given: a RouteTable with a backend
+    let table = RouteTable::try_new(
+        vec![backend_meta_to_config(create_test_backend_with_details(
+            1,
+            "backend-1",
+            8080,
+        ))],
+        false,
+    )
+    .expect("Failed to create route table");
+
+    // When: inserting another backend with the same id
+    let backend = Arc::new(Backend::new(backend_meta_to_config(
+        create_test_backend_with_details(1, "backend-2", 8081),
+    )));
+    let result = table.insert(backend);
+
+    // Then: insert fails and the original backend is kept
+    assert_eq!(result, Err(RouteTableError::DuplicateId(1)));
+    assert_eq!(table.len(), 1);
+    assert_eq!(
+        table.get(1).expect("Backend not found").name(),
+        Some("backend-1")
+    );
+}
+
+#[test]
+fn route_table_insert_duplicate_address_should_fail() {
+    // Given: a RouteTable rejecting duplicate addresses
+    let table = RouteTable::with_duplicate_addresses(false);
+    table
+        .insert(Arc::new(Backend::new(backend_meta_to_config(
+            create_test_backend_with_details(1, "backend-1", 8080),
+        ))))
+        .expect("Insert should succeed");
+
+    // When: inserting a backend with another id but the same address
+    let result = table.insert(Arc::new(Backend::new(backend_meta_to_config(
+        create_test_backend_with_details(2, "backend-2", 8080),
+    ))));
+
+    // Then: insert fails with the conflicting backend id
+    assert!(matches!(
+        result,
+        Err(RouteTableError::DuplicateAddress { existing: 1, .. })
+    ));
+    assert_eq!(table.len(), 1);
+}
+
+#[test]
+fn route_table_insert_allowed_duplicate_address_should_succeed() {
+    // Given: a RouteTable allowing duplicate addresses
+    let table = RouteTable::with_duplicate_addresses(true);
+    assert!(table.allows_duplicate_addresses());
+
+    // When: inserting two backends sharing an address
+    for id in [1, 2] {
+        table
+            .insert(Arc::new(Backend::new(backend_meta_to_config(
+                create_test_backend_with_details(id, "shared", 8080),
+            ))))
+            .expect("Insert should succeed");
+    }
+
+    // Then: both backends are registered
+    assert_eq!(table.len(), 2);
+}
+
+#[test]
+fn route_table_try_new_with_duplicates_should_fail() {
+    // Given: backend configs sharing an address
+    let backends = vec![
+        backend_meta_to_config(create_test_backend_with_details(1, "backend-1", 8080)),
+        backend_meta_to_config(create_test_backend_with_details(2, "backend-2", 8080)),
+    ];
+
+    // When: creating a checked RouteTable
+    let result = RouteTable::try_new(backends.clone(), false);
+
+    // Then: creation fails unless duplicates are allowed
+    assert!(matches!(
+        result,
+        Err(RouteTableError::DuplicateAddress { .. })
+    ));
+    assert!(RouteTable::try_new(backends, true).is_ok());
+}
+
+#[test]
+fn route_table_find_by_address_should_succeed() {
+    // Given: a RouteTable with backends
+    let backends = vec![
+        backend_meta_to_config(create_test_backend_with_details(1, "backend-1", 8080)),
+        backend_meta_to_config(create_test_backend_with_details(2, "backend-2", 8081)),
+    ];
+    let table = RouteTable::new(backends);
+
+    // When: looking up backends by address
+    let found =
+        table.find_by_address(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8081));
+    let missing =
+        table.find_by_address(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9999));
+
+    // Then: only the matching backend is returned
+    assert_eq!(found.expect("Backend not found").id(), 2);
+    assert!(missing.is_none());
+}
@@ -0,0 +1,90 @@
+//! Tests for Unix socket addresses
+//!
+use lemonade_load_balancer::prelude::*;
+use rstest::*;
+use std::path::PathBuf;
+
+#[rstest]
+#[case("unix:/run/lb.sock", UnixAddress::Path(PathBuf::from("/run/lb.sock")))]
+#[case(
+    "unix:relative.sock",
+    UnixAddress::Path(PathBuf::from("relative.sock"))
+)]
+#[case("unix:@lemonade", UnixAddress::Abstract("lemonade".to_string()))]
+fn unix_address_parse_should_succeed(#[case] addr: &str, #[case] expected: UnixAddress) {
+    let parsed = UnixAddress::parse(addr).expect("Failed to parse Unix address");
+    assert_eq!(parsed, expected);
+    assert_eq!(parsed.to_string(), addr);
+    assert!(UnixAddress::is_unix(addr));
+}
+
+#[rstest]
+#[case("unix:")]
+#[case("unix:@")]
+#[case("/run/lb.sock")]
+#[case("127.0.0.1:3000")]
+fn unix_address_parse_should_fail(#[case] addr: &str) {
+    assert!(UnixAddress::parse(addr).is_err());
+}
+
+#[test]
+fn unix_address_serde_roundtrip_should_succeed() {
+    let address = UnixAddress::parse("unix:@lemonade").unwrap();
+    let json = serde_json::to_string(&address).unwrap();
+    assert_eq!(json, "\"unix:@lemonade\"");
+    assert_eq!(serde_json::from_str::<UnixAddress>(&json).unwrap(), address);
+    assert!(serde_json::from_str::<UnixAddress>("\"unix:\"").is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_address_bind_replaces_stale_socket_should_succeed() {
+    // Given: a socket file left behind by a listener that is gone
+    let dir = tempfile::tempdir().unwrap();
+    let address = UnixAddress::Path(dir.path().join("lb.sock"));
+    drop(address.bind().expect("Failed to bind"));
+    assert!(dir.path().join("lb.sock").exists());
+
+    // When: binding the same path again
+    let listener = address.bind().expect("Stale socket should be replaced");
+
+    // Then: clients reach the new listener
+    let (accepted, connected) = tokio::join!(listener.accept(), address.connect());
+    assert!(accepted.is_ok());
+    assert!(connected.is_ok());
+
+    // When: the listener is closed
+    drop(listener);
+    address.remove_file();
+
+    // Then: its socket file is gone
+    assert!(!dir.path().join("lb.sock").exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_address_bind_keeps_other_files_should_fail() {
+    // Given: a regular file at the socket path
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("lb.sock");
+    std::fs::write(&path, b"not a socket").unwrap();
+
+    // When/Then: binding fails and the file is left alone
+    let address = UnixAddress::Path(path.clone());
+    assert!(address.bind().is_err());
+    assert_eq!(std::fs::read(&path).unwrap(), b"not a socket");
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn unix_address_abstract_socket_should_succeed() {
+    let address =
+        UnixAddress::parse(&format!("unix:@lemonade-test-{}", std::process::id()))
+            .unwrap();
+    let listener = address.bind().expect("Failed to bind abstract socket");
+
+    let (accepted, connected) = tokio::join!(listener.accept(), address.connect());
+
+    assert!(accepted.is_ok());
+    assert!(connected.is_ok());
+}
@@ -0,0 +1,89 @@
+//! Build script capturing build information
+//!
+//! Exposes the git commit, the build time and the compiler version to the
+//! crate as `LEMONADE_BUILD_*` environment variables, read by
+//! `build_info`. Each value falls back to "unknown" when it cannot be
+//! determined (e.g. building from a source archive without git).
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Value used when a field cannot be determined
+const UNKNOWN: &str = "unknown";
+
+fn main() {
+    println!("cargo:rustc-env=LEMONADE_BUILD_GIT_SHA={}", git_sha());
+    println!(
+        "cargo:rustc-env=LEMONADE_BUILD_TIMESTAMP={}",
+        build_timestamp()
+    );
+    println!("cargo:rustc-env=LEMONADE_BUILD_RUSTC={}", rustc_version());
+
+    // Rebuild when the checked out commit moves; watching a missing path
+    // would rerun the script on every build.
+    for path in ["../.git/HEAD", "../.git/refs/heads"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
+
+/// Output of `program args`, trimmed, if it ran successfully
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Commit the workspace is built from
+fn git_sha() -> String {
+    command_output("git", &["rev-parse", "HEAD"]).unwrap_or_else(|| UNKNOWN.to_string())
+}
+
+/// Compiler building the crate
+fn rustc_version() -> String {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    command_output(&rustc, &["--version"]).unwrap_or_else(|| UNKNOWN.to_string())
+}
+
+/// Current time as an RFC 3339 UTC timestamp
+fn build_timestamp() -> String {
+    let Ok(elapsed) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+        return UNKNOWN.to_string();
+    };
+    let secs = elapsed.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let time_of_day = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3_600,
+        time_of_day % 3_600 / 60,
+        time_of_day % 60
+    )
+}
+
+/// Gregorian date of a day count since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
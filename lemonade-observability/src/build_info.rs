@@ -0,0 +1,85 @@
+//! Build Information
+//!
+//! Git commit, build time and compiler version captured at compile time by
+//! the build script, plus the version and cargo features of the crate
+//! reporting them. Surfaced in startup banners, the admin `/status`, the
+//! worker `/info` endpoint, `lemonade --version` and the OTLP resource.
+
+use serde::Serialize;
+use std::fmt;
+
+/// Commit the binary was built from, or "unknown" outside a git checkout
+pub const GIT_SHA: &str = env!("LEMONADE_BUILD_GIT_SHA");
+
+/// Build time, as an RFC 3339 UTC timestamp
+pub const BUILD_TIMESTAMP: &str = env!("LEMONADE_BUILD_TIMESTAMP");
+
+/// Version of the compiler that built the binary
+pub const RUSTC_VERSION: &str = env!("LEMONADE_BUILD_RUSTC");
+
+/// Length of the abbreviated commit shown in one-line summaries
+const SHORT_SHA_LEN: usize = 12;
+
+/// Build information of a crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    /// Crate version
+    pub version: &'static str,
+    /// Commit the binary was built from
+    pub git_sha: &'static str,
+    /// Build time, as an RFC 3339 UTC timestamp
+    pub build_timestamp: &'static str,
+    /// Compiler version
+    pub rustc_version: &'static str,
+    /// Enabled cargo features, sorted
+    pub features: Vec<&'static str>,
+}
+
+impl BuildInfo {
+    /// Build information of a crate at `version`, with no feature enabled
+    pub fn new(version: &'static str) -> Self {
+        Self {
+            version,
+            git_sha: GIT_SHA,
+            build_timestamp: BUILD_TIMESTAMP,
+            rustc_version: RUSTC_VERSION,
+            features: Vec::new(),
+        }
+    }
+
+    /// Record `features` as enabled
+    pub fn with_features(
+        mut self,
+        features: impl IntoIterator<Item = &'static str>,
+    ) -> Self {
+        self.features.extend(features);
+        self.features.sort_unstable();
+        self.features.dedup();
+        self
+    }
+
+    /// Commit abbreviated for display
+    pub fn short_sha(&self) -> &'static str {
+        self.git_sha.get(..SHORT_SHA_LEN).unwrap_or(self.git_sha)
+    }
+
+    /// One-line summary: version and abbreviated commit
+    pub fn summary(&self) -> String {
+        format!("{} ({})", self.version, self.short_sha())
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let features = if self.features.is_empty() {
+            "none".to_string()
+        } else {
+            self.features.join(", ")
+        };
+        writeln!(f, "version: {}", self.version)?;
+        writeln!(f, "git sha: {}", self.git_sha)?;
+        writeln!(f, "build timestamp: {}", self.build_timestamp)?;
+        writeln!(f, "rustc: {}", self.rustc_version)?;
+        write!(f, "features: {}", features)
+    }
+}
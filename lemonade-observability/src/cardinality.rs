@@ -0,0 +1,165 @@
+//! Metric Attribute Cardinality Guard
+//!
+//! Every distinct attribute value creates a new time series in the metrics
+//! backend. The guard caps the distinct values kept per attribute key: once
+//! a key has seen its cap, new values are folded into [`OTHER_VALUE`] and the
+//! fold is counted in `lemonade_metrics_cardinality_limited_total{key}`.
+
+use dashmap::DashMap;
+use opentelemetry::KeyValue;
+use opentelemetry::global;
+use opentelemetry::metrics::Counter;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Value standing in for every value beyond a key's cap
+pub const OTHER_VALUE: &str = "__other__";
+
+/// Environment variable overriding the default caps, as
+/// `key=cap,key=cap` (e.g. `http.route=50,http.method=20`)
+pub const CARDINALITY_LIMITS_ENV_KEY: &str = "LEMONADE_METRICS_CARDINALITY_LIMITS";
+
+/// Default distinct `http.route` values per service
+pub const DEFAULT_HTTP_ROUTE_LIMIT: usize = 50;
+
+/// Default distinct `http.method` values per service
+pub const DEFAULT_HTTP_METHOD_LIMIT: usize = 20;
+
+/// Distinct values allowed per attribute key (keys without a cap are not
+/// limited)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardinalityLimits {
+    limits: BTreeMap<String, usize>,
+}
+
+impl CardinalityLimits {
+    /// No caps at all
+    pub fn unlimited() -> Self {
+        Self {
+            limits: BTreeMap::new(),
+        }
+    }
+
+    /// Cap the distinct values of `key`
+    pub fn with_limit(mut self, key: impl Into<String>, limit: usize) -> Self {
+        self.limits.insert(key.into(), limit);
+        self
+    }
+
+    /// Cap of `key`, if any
+    pub fn limit(&self, key: &str) -> Option<usize> {
+        self.limits.get(key).copied()
+    }
+
+    /// Defaults, overridden per key by [`CARDINALITY_LIMITS_ENV_KEY`]
+    ///
+    /// Malformed entries are ignored with a warning.
+    pub fn from_env() -> Self {
+        let mut limits = Self::default();
+        if let Ok(value) = std::env::var(CARDINALITY_LIMITS_ENV_KEY) {
+            limits.apply(&value);
+        }
+        limits
+    }
+
+    /// Apply `key=cap,key=cap` overrides
+    fn apply(&mut self, overrides: &str) {
+        for entry in overrides
+            .split(',')
+            .filter(|entry| !entry.trim().is_empty())
+        {
+            let parsed: Option<(&str, usize)> = entry
+                .split_once('=')
+                .and_then(|(key, cap)| Some((key.trim(), cap.trim().parse().ok()?)));
+            match parsed {
+                Some((key, cap)) if !key.is_empty() => {
+                    self.limits.insert(key.to_string(), cap);
+                }
+                _ => tracing::warn!(
+                    "Ignoring invalid {} entry: {}",
+                    CARDINALITY_LIMITS_ENV_KEY,
+                    entry
+                ),
+            }
+        }
+    }
+}
+
+impl Default for CardinalityLimits {
+    fn default() -> Self {
+        Self::unlimited()
+            .with_limit("http.route", DEFAULT_HTTP_ROUTE_LIMIT)
+            .with_limit("http.method", DEFAULT_HTTP_METHOD_LIMIT)
+    }
+}
+
+/// Per-key distinct value tracking for one set of instruments
+pub struct CardinalityGuard {
+    limits: CardinalityLimits,
+    /// Values admitted so far, per capped key
+    seen: DashMap<String, HashSet<String>>,
+    /// Folds per key, for inspection
+    folded: DashMap<String, AtomicU64>,
+    /// Folds per key, exported
+    limited_total: Counter<u64>,
+}
+
+impl CardinalityGuard {
+    /// Create a guard exporting its fold counter on the `service_name` meter
+    pub fn new(service_name: &'static str, limits: CardinalityLimits) -> Self {
+        let limited_total = global::meter(service_name)
+            .u64_counter("lemonade_metrics_cardinality_limited_total")
+            .with_description(
+                "Attribute values folded into __other__ by the cardinality guard",
+            )
+            .build();
+        Self {
+            limits,
+            seen: DashMap::new(),
+            folded: DashMap::new(),
+            limited_total,
+        }
+    }
+
+    /// Value to record for `key`: `value` itself while the key is under its
+    /// cap (or was already admitted), [`OTHER_VALUE`] beyond it
+    pub fn fold(&self, key: &str, value: &str) -> String {
+        let Some(limit) = self.limits.limit(key) else {
+            return value.to_string();
+        };
+        if self
+            .seen
+            .get(key)
+            .is_some_and(|values| values.contains(value))
+        {
+            return value.to_string();
+        }
+        let mut values = self.seen.entry(key.to_string()).or_default();
+        if values.contains(value) || values.len() < limit {
+            values.insert(value.to_string());
+            return value.to_string();
+        }
+        drop(values);
+
+        self.folded
+            .entry(key.to_string())
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+        self.limited_total
+            .add(1, &[KeyValue::new("key", key.to_string())]);
+        OTHER_VALUE.to_string()
+    }
+
+    /// Number of values folded for `key` so far
+    pub fn folded(&self, key: &str) -> u64 {
+        self.folded
+            .get(key)
+            .map(|count| count.load(Ordering::Relaxed))
+            .unwrap_or(0)
+    }
+
+    /// Distinct values admitted for `key` so far
+    pub fn distinct(&self, key: &str) -> usize {
+        self.seen.get(key).map(|values| values.len()).unwrap_or(0)
+    }
+}
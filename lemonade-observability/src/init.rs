@@ -5,17 +5,22 @@
 use std::sync::OnceLock;
 
 use opentelemetry::global;
-use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
 use opentelemetry_sdk::trace::{BatchSpanProcessor, Sampler, SdkTracerProvider};
 use opentelemetry_stdout::SpanExporter as StdoutSpanExporter;
 use tracing::Level;
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
+use crate::reload::{
+    build_metric_exporter, build_span_processor, reloadable_metric_exporter,
+    reloadable_span_processor,
+};
 use crate::resource::create_resource;
 
 static INIT_TRACING: OnceLock<()> = OnceLock::new();
 static INIT_METRICS: OnceLock<()> = OnceLock::new();
+static TRACER_PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+static METER_PROVIDER: OnceLock<SdkMeterProvider> = OnceLock::new();
 
 /// Initialize OpenTelemetry tracing with OTLP export
 ///
@@ -38,7 +43,8 @@ static INIT_METRICS: OnceLock<()> = OnceLock::new();
 ///
 /// # Note
 /// This function can be called multiple times, but only the first call will initialize the global
-/// tracer provider and subscriber. Subsequent calls are ignored. Each service should call this
+/// tracer provider and subscriber. Subsequent calls are ignored; use
+/// [`crate::reconfigure_exporters`] to change the OTLP endpoint at runtime. Each service should call this
 /// with its own service name and version to ensure proper resource identification.
 pub fn init_tracing(
     service_name: &str,
@@ -54,77 +60,30 @@ pub fn init_tracing(
         let resource =
             create_resource(service_name, service_version, service_instance_id);
 
-        // Create tracer provider with appropriate exporter based on OTLP config availability
-        let tracer_provider = if let (Some(endpoint), Some(protocol)) = (otlp_endpoint, otlp_protocol) {
-            // Note: We can't use tracing::info! here because tracing isn't initialized yet
-            // Use eprintln! which will be captured by Docker logs
-            eprintln!("[OTLP] Initializing OTLP exporter: endpoint={}, protocol={}", endpoint, protocol);
-            // Use OTLP exporter
-            match protocol {
-                "grpc" => {
-                    let exporter = opentelemetry_otlp::SpanExporterBuilder::default()
-                        .with_tonic()
-                        .with_endpoint(endpoint)
-                        .build()
-                        .expect("Failed to build OTLP gRPC span exporter");
-
-                    eprintln!("[OTLP] OTLP gRPC exporter built successfully");
-
-                    // Create batch span processor with explicit runtime configuration
-                    // The runtime is automatically detected from the rt-tokio feature
-                    let batch_processor = BatchSpanProcessor::builder(exporter)
-                        .build();
-
-                    eprintln!("[OTLP] Batch span processor created with AlwaysOn sampler");
-
-                    SdkTracerProvider::builder()
-                        .with_span_processor(batch_processor)
-                        .with_sampler(Sampler::AlwaysOn)
-                        .with_resource(resource)
-                        .build()
-                }
-                "http" => {
-                    let exporter = opentelemetry_otlp::SpanExporterBuilder::default()
-                        .with_http()
-                        .with_endpoint(endpoint)
-                        .build()
-                        .expect("Failed to build OTLP HTTP span exporter");
-
-                    eprintln!("[OTLP] OTLP HTTP exporter built successfully");
-
-                    // Create batch span processor with explicit runtime configuration
-                    // The runtime is automatically detected from the rt-tokio feature
-                    let batch_processor = BatchSpanProcessor::builder(exporter)
-                        .build();
-
-                    eprintln!("[OTLP] Batch span processor created with AlwaysOn sampler");
-
-                    SdkTracerProvider::builder()
-                        .with_span_processor(batch_processor)
-                        .with_sampler(Sampler::AlwaysOn)
-                        .with_resource(resource)
-                        .build()
-                }
-                _ => {
-                    eprintln!("[OTLP] Warning: Unsupported OTLP protocol: {}. Falling back to console exporter.", protocol);
-                    let exporter = StdoutSpanExporter::default();
-                    SdkTracerProvider::builder()
-                        .with_batch_exporter(exporter)
-                        .with_resource(resource)
-                        .build()
-                }
-            }
-        } else {
-            eprintln!("[OTLP] No OTLP config provided (endpoint={:?}, protocol={:?}), using console exporter fallback", otlp_endpoint, otlp_protocol);
-            // Fallback to console exporter when OTLP config is not available
-            let exporter = StdoutSpanExporter::default();
-            SdkTracerProvider::builder()
-                .with_batch_exporter(exporter)
-                .with_resource(resource)
-                .build()
-        };
-
-        // Set as global tracer provider
+        // Create tracer provider around the reloadable span processor so the
+        // exporter can be replaced later (see `reconfigure_exporters`)
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_span_processor(reloadable_span_processor().clone())
+            .with_sampler(Sampler::AlwaysOn)
+            .with_resource(resource)
+            .build();
+
+        // Use OTLP exporter when configured, console exporter as fallback
+        // Note: We can't use tracing::info! here because tracing isn't initialized yet
+        // Use eprintln! which will be captured by Docker logs
+        eprintln!(
+            "[OTLP] Initializing span exporter: endpoint={:?}, protocol={:?}",
+            otlp_endpoint, otlp_protocol
+        );
+        let span_processor = build_span_processor(otlp_endpoint, otlp_protocol)
+            .unwrap_or_else(|e| {
+                eprintln!("[OTLP] Warning: {}. Falling back to console exporter.", e);
+                BatchSpanProcessor::builder(StdoutSpanExporter::default()).build()
+            });
+        reloadable_span_processor().replace(span_processor);
+
+        // Set as global tracer provider, keeping a handle for flushing
+        let _ = TRACER_PROVIDER.set(tracer_provider.clone());
         global::set_tracer_provider(tracer_provider);
 
         // Create environment filter (defaults to "info" if RUST_LOG not set)
@@ -145,12 +104,16 @@ pub fn init_tracing(
         let otel_layer = tracing_opentelemetry::layer()
             .with_tracer(global::tracer(service_name_owned));
 
-        // Initialize tracing subscriber with all layers
-        tracing_subscriber::registry()
+        // Initialize tracing subscriber with all layers; another logger may
+        // already be installed (e.g. by a framework started earlier)
+        if let Err(e) = tracing_subscriber::registry()
             .with(filter_layer)
             .with(fmt_layer)
             .with(otel_layer)
-            .init();
+            .try_init()
+        {
+            eprintln!("[OTLP] Warning: tracing subscriber not installed: {}", e);
+        }
     });
 
     let _tracer = global::tracer(service_name.to_string());
@@ -183,66 +146,56 @@ pub fn init_metrics(
     otlp_protocol: Option<&str>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     INIT_METRICS.get_or_init(|| {
-        let resource = create_resource(service_name, service_version, service_instance_id);
-
-        let meter_provider = if let (Some(endpoint), Some(protocol)) = (otlp_endpoint, otlp_protocol) {
-            eprintln!("[OTLP Metrics] Initializing OTLP metrics exporter: endpoint={}, protocol={}", endpoint, protocol);
-            match protocol {
-                "grpc" => {
-                    let exporter = opentelemetry_otlp::MetricExporterBuilder::new()
-                        .with_tonic()
-                        .with_endpoint(endpoint)
-                        .build()
-                        .expect("Failed to build OTLP gRPC metrics exporter");
-
-                    eprintln!("[OTLP Metrics] OTLP gRPC metrics exporter built successfully");
-
-                    let reader = PeriodicReader::builder(exporter)
-                        .with_interval(std::time::Duration::from_secs(10))
-                        .build();
-
-                    SdkMeterProvider::builder()
-                        .with_resource(resource)
-                        .with_reader(reader)
-                        .build()
-                }
-                "http" => {
-                    let exporter = opentelemetry_otlp::MetricExporterBuilder::new()
-                        .with_http()
-                        .with_endpoint(endpoint)
-                        .build()
-                        .expect("Failed to build OTLP HTTP metrics exporter");
-
-                    eprintln!("[OTLP Metrics] OTLP HTTP metrics exporter built successfully");
-
-                    let reader = PeriodicReader::builder(exporter)
-                        .with_interval(std::time::Duration::from_secs(10))
-                        .build();
-
-                    SdkMeterProvider::builder()
-                        .with_resource(resource)
-                        .with_reader(reader)
-                        .build()
-                }
-                _ => {
-                    eprintln!("[OTLP Metrics] Warning: Unsupported OTLP protocol: {}. Metrics will not be exported.", protocol);
-                    // Create a no-op meter provider if protocol is unsupported
-                    SdkMeterProvider::builder()
-                        .with_resource(resource)
-                        .build()
-                }
-            }
-        } else {
-            eprintln!("[OTLP Metrics] No OTLP config provided (endpoint={:?}, protocol={:?}), metrics will not be exported", otlp_endpoint, otlp_protocol);
-            // Create a no-op meter provider if OTLP config is not available
-            SdkMeterProvider::builder()
-                .with_resource(resource)
-                .build()
-        };
+        let resource =
+            create_resource(service_name, service_version, service_instance_id);
 
+        // The periodic reader always exports through the reloadable exporter,
+        // which drops metrics until an OTLP exporter is set
+        let reader = PeriodicReader::builder(reloadable_metric_exporter().clone())
+            .with_interval(std::time::Duration::from_secs(10))
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_resource(resource)
+            .with_reader(reader)
+            .build();
+
+        eprintln!(
+            "[OTLP Metrics] Initializing metrics exporter: endpoint={:?}, protocol={:?}",
+            otlp_endpoint, otlp_protocol
+        );
+        let metric_exporter = build_metric_exporter(otlp_endpoint, otlp_protocol)
+            .unwrap_or_else(|e| {
+                eprintln!(
+                    "[OTLP Metrics] Warning: {}. Metrics will not be exported.",
+                    e
+                );
+                None
+            });
+        if metric_exporter.is_none() {
+            eprintln!(
+                "[OTLP Metrics] No OTLP exporter configured, metrics will not be exported"
+            );
+        }
+        reloadable_metric_exporter().replace(metric_exporter);
+
+        let _ = METER_PROVIDER.set(meter_provider.clone());
         global::set_meter_provider(meter_provider);
         eprintln!("[OTLP Metrics] Metrics provider initialized successfully");
     });
 
     Ok(())
 }
+
+/// Export the spans and metrics buffered so far
+///
+/// Does nothing for a provider that was not initialized. Flushing blocks;
+/// call this from a blocking task in async code, or on the way out.
+pub fn flush_exporters() -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(tracer_provider) = TRACER_PROVIDER.get() {
+        tracer_provider.force_flush()?;
+    }
+    if let Some(meter_provider) = METER_PROVIDER.get() {
+        meter_provider.force_flush()?;
+    }
+    Ok(())
+}
@@ -3,12 +3,20 @@
 //! Provides centralized observability initialization using OpenTelemetry SDK
 //! with tracing integration for distributed tracing across load balancer and workers.
 
+pub mod build_info;
+pub mod cardinality;
 pub mod init;
 pub mod metrics;
+pub mod reload;
 pub mod resource;
 
-pub use init::{init_metrics, init_tracing};
-pub use metrics::{HttpMetrics, get_http_metrics};
+pub use build_info::BuildInfo;
+pub use cardinality::{CardinalityGuard, CardinalityLimits};
+pub use init::{flush_exporters, init_metrics, init_tracing};
+pub use metrics::{
+    ConnectionMetrics, HttpMetrics, get_connection_metrics, get_http_metrics,
+};
+pub use reload::{reconfigure_exporters, set_span_exporter};
 pub use resource::create_resource;
 
 #[cfg(test)]
@@ -29,6 +37,27 @@ mod tests {
         assert!(!resource.is_empty());
     }
 
+    #[test]
+    fn test_create_resource_has_build_sha() {
+        let resource = create_resource("test-service", "1.0.0", "test-instance-1");
+        let sha = resource
+            .get(&opentelemetry::Key::new("service.build.sha"))
+            .expect("service.build.sha attribute");
+        assert_eq!(sha.as_str(), build_info::GIT_SHA);
+    }
+
+    #[test]
+    fn test_build_info_fields_are_set() {
+        let info = BuildInfo::new("1.0.0").with_features(["zeta", "alpha", "zeta"]);
+        assert_eq!(info.version, "1.0.0");
+        assert!(!info.git_sha.is_empty());
+        assert!(!info.build_timestamp.is_empty());
+        assert!(info.rustc_version.starts_with("rustc "));
+        assert_eq!(info.features, vec!["alpha", "zeta"]);
+        assert!(info.git_sha.starts_with(info.short_sha()));
+        assert!(info.to_string().contains("features: alpha, zeta"));
+    }
+
     #[test]
     fn test_init_tracing_succeeds() {
         // This test verifies init_tracing can be called without panicking
@@ -40,4 +69,122 @@ mod tests {
         let result2 = init_tracing("test-init-2", "1.0.0", "test-instance-2", None, None);
         assert!(result2.is_ok());
     }
+
+    /// Span exporter keeping the names of the spans it receives
+    #[derive(Debug, Clone, Default)]
+    struct RecordingExporter {
+        names: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl RecordingExporter {
+        fn names(&self) -> Vec<String> {
+            self.names
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|name| name.starts_with("reload-test"))
+                .cloned()
+                .collect()
+        }
+    }
+
+    impl opentelemetry_sdk::trace::SpanExporter for RecordingExporter {
+        async fn export(
+            &self,
+            batch: Vec<opentelemetry_sdk::trace::SpanData>,
+        ) -> opentelemetry_sdk::error::OTelSdkResult {
+            let names = batch.into_iter().map(|span| span.name.into_owned());
+            self.names.lock().unwrap().extend(names);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_set_span_exporter_routes_later_spans_to_new_exporter() {
+        use opentelemetry::trace::{Span, Tracer};
+
+        init_tracing("test-reload", "1.0.0", "test-instance-1", None, None).unwrap();
+        let tracer = opentelemetry::global::tracer("test-reload");
+
+        // Given: spans exported in memory
+        let first = RecordingExporter::default();
+        set_span_exporter(first.clone());
+        tracer.start("reload-test-first").end();
+
+        // When: the exporter is swapped at runtime
+        let second = RecordingExporter::default();
+        set_span_exporter(second.clone());
+        tracer.start("reload-test-second").end();
+        set_span_exporter(RecordingExporter::default());
+
+        // Then: each span lands in the exporter active when it ended, and the
+        // old exporter was flushed on swap
+        assert_eq!(first.names(), vec!["reload-test-first"]);
+        assert_eq!(second.names(), vec!["reload-test-second"]);
+    }
+
+    #[test]
+    fn test_cardinality_guard_folds_values_beyond_cap() {
+        // Given: a guard capping routes at 3 distinct values
+        let guard = CardinalityGuard::new(
+            "test-cardinality",
+            CardinalityLimits::unlimited().with_limit("http.route", 3),
+        );
+
+        // When: more distinct routes than the cap are recorded
+        let folded: Vec<String> = (0..10)
+            .map(|i| guard.fold("http.route", &format!("/route-{}", i)))
+            .collect();
+
+        // Then: the first three are kept, the rest folded and counted
+        assert_eq!(&folded[..3], ["/route-0", "/route-1", "/route-2"]);
+        assert!(
+            folded[3..]
+                .iter()
+                .all(|value| value == cardinality::OTHER_VALUE)
+        );
+        assert_eq!(guard.distinct("http.route"), 3);
+        assert_eq!(guard.folded("http.route"), 7);
+
+        // And: admitted values keep passing through, uncapped keys are untouched
+        assert_eq!(guard.fold("http.route", "/route-1"), "/route-1");
+        assert_eq!(guard.fold("client.ip", "10.0.0.1"), "10.0.0.1");
+        assert_eq!(guard.folded("http.route"), 7);
+        assert_eq!(guard.folded("client.ip"), 0);
+    }
+
+    #[test]
+    fn test_http_metrics_records_folded_routes() {
+        // Given: HTTP metrics capping routes at 2 distinct values
+        let metrics = HttpMetrics::with_limits(
+            "test-cardinality-http",
+            CardinalityLimits::unlimited().with_limit("http.route", 2),
+        );
+
+        // When: requests hit five distinct routes
+        for i in 0..5 {
+            metrics.record_request("GET", &format!("/item/{}", i), 200, 10);
+        }
+
+        // Then: the extra routes were folded
+        assert_eq!(metrics.cardinality.distinct("http.route"), 2);
+        assert_eq!(metrics.cardinality.folded("http.route"), 3);
+        assert_eq!(metrics.cardinality.folded("http.method"), 0);
+    }
+
+    #[test]
+    fn test_cardinality_limits_defaults() {
+        let limits = CardinalityLimits::default();
+        assert_eq!(
+            limits.limit("http.route"),
+            Some(cardinality::DEFAULT_HTTP_ROUTE_LIMIT)
+        );
+        assert_eq!(limits.limit("backend.id"), None);
+    }
+
+    #[test]
+    fn test_reconfigure_exporters_rejects_unknown_protocol() {
+        let result = reconfigure_exporters(Some("http://localhost:4317"), Some("udp"));
+        assert!(result.is_err());
+    }
 }
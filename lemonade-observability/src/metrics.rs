@@ -2,9 +2,10 @@
 //!
 //! Provides helpers for collecting HTTP request metrics using OpenTelemetry
 
+use crate::cardinality::{CardinalityGuard, CardinalityLimits};
 use opentelemetry::KeyValue;
 use opentelemetry::global;
-use opentelemetry::metrics::{Counter, Histogram};
+use opentelemetry::metrics::{Counter, Gauge, Histogram};
 use std::sync::Arc;
 
 /// HTTP metrics for a service
@@ -13,6 +14,8 @@ pub struct HttpMetrics {
     pub requests_total: Counter<u64>,
     /// Histogram for HTTP request duration in seconds
     pub request_duration_seconds: Histogram<f64>,
+    /// Caps on distinct route and method values
+    pub cardinality: CardinalityGuard,
 }
 
 impl HttpMetrics {
@@ -22,8 +25,14 @@ impl HttpMetrics {
     /// * `service_name` - The name of the service (e.g., "lemonade-worker-actix")
     ///
     /// # Returns
-    /// * `Self` with initialized metrics instruments
+    /// * `Self` with initialized metrics instruments, attribute values capped
+    ///   by [`CardinalityLimits::from_env`]
     pub fn new(service_name: &'static str) -> Self {
+        Self::with_limits(service_name, CardinalityLimits::from_env())
+    }
+
+    /// Create HTTP metrics for a service with explicit attribute caps
+    pub fn with_limits(service_name: &'static str, limits: CardinalityLimits) -> Self {
         let meter = global::meter(service_name);
 
         let requests_total = meter
@@ -39,6 +48,7 @@ impl HttpMetrics {
         Self {
             requests_total,
             request_duration_seconds,
+            cardinality: CardinalityGuard::new(service_name, limits),
         }
     }
 
@@ -49,6 +59,9 @@ impl HttpMetrics {
     /// * `route` - HTTP route (e.g., "/health", "/work")
     /// * `status_code` - HTTP status code (e.g., 200, 404, 500)
     /// * `duration_micros` - Request duration in microseconds (converted to seconds for histogram)
+    ///
+    /// Methods and routes beyond their cardinality cap are recorded as
+    /// `__other__`.
     pub fn record_request(
         &self,
         method: &str,
@@ -57,8 +70,8 @@ impl HttpMetrics {
         duration_micros: u64,
     ) {
         let attributes = vec![
-            KeyValue::new("http.method", method.to_string()),
-            KeyValue::new("http.route", route.to_string()),
+            KeyValue::new("http.method", self.cardinality.fold("http.method", method)),
+            KeyValue::new("http.route", self.cardinality.fold("http.route", route)),
             KeyValue::new("http.status_code", status_code as i64),
         ];
 
@@ -89,3 +102,336 @@ pub fn get_http_metrics(service_name: &str) -> Arc<HttpMetrics> {
         })
         .clone()
 }
+
+/// Backend connection timing metrics for a service
+pub struct ConnectionMetrics {
+    /// Histogram for backend connect duration in seconds
+    pub connect_duration_seconds: Histogram<f64>,
+    /// Histogram for time to the first backend byte in seconds
+    pub time_to_first_byte_seconds: Histogram<f64>,
+    /// Histogram for total connection duration in seconds
+    pub connection_duration_seconds: Histogram<f64>,
+    /// Counter for connections the load balancer closed itself
+    pub connections_force_closed_total: Counter<u64>,
+    /// Counter for connections reset by the client or the backend
+    pub connections_aborted_total: Counter<u64>,
+    /// Counter for retry and hedge attempts skipped by the retry budget
+    pub retries_suppressed_total: Counter<u64>,
+    /// Counter for client connections closed before reaching a backend
+    pub connections_rejected_total: Counter<u64>,
+    /// Counter for connections routed by their first HTTP request
+    pub http_routed_total: Counter<u64>,
+    /// Histogram for time spent in strategy selection in seconds
+    pub strategy_pick_duration: Histogram<f64>,
+    /// Counter for per-backend state entries evicted after their backend left
+    pub backend_entries_evicted_total: Counter<u64>,
+    /// Counter for invariant violations found by the consistency audit
+    pub invariant_violations_total: Counter<u64>,
+    /// Counter for picks handed to the fallback strategy
+    pub strategy_fallbacks_total: Counter<u64>,
+    /// Counter for picks that skipped a backend, per exclusion reason
+    pub selection_exclusions_total: Counter<u64>,
+    /// Counter for picks that chose a backend
+    pub backend_selections_total: Counter<u64>,
+    /// Gauge for backend connects in flight
+    pub pending_connects: Gauge<u64>,
+}
+
+impl ConnectionMetrics {
+    /// Create connection timing metrics for a service
+    ///
+    /// # Arguments
+    /// * `service_name` - The name of the service (e.g., "lemonade-load-balancer")
+    ///
+    /// # Returns
+    /// * `Self` with initialized metrics instruments
+    pub fn new(service_name: &'static str) -> Self {
+        let meter = global::meter(service_name);
+
+        let connect_duration_seconds = meter
+            .f64_histogram("lemonade_backend_connect_duration_seconds")
+            .with_description("Backend connect duration in seconds")
+            .build();
+
+        let time_to_first_byte_seconds = meter
+            .f64_histogram("lemonade_backend_time_to_first_byte_seconds")
+            .with_description("Time to the first backend byte in seconds")
+            .build();
+
+        let connection_duration_seconds = meter
+            .f64_histogram("lemonade_connection_duration_seconds")
+            .with_description("Total proxied connection duration in seconds")
+            .build();
+
+        let connections_force_closed_total = meter
+            .u64_counter("lemonade_connections_force_closed_total")
+            .with_description("Connections force-closed by a backend drain policy")
+            .build();
+
+        let connections_aborted_total = meter
+            .u64_counter("lemonade_connections_aborted_total")
+            .with_description(
+                "Connections reset mid-transfer by the client or the backend",
+            )
+            .build();
+
+        let retries_suppressed_total = meter
+            .u64_counter("lemonade_retries_suppressed_total")
+            .with_description("Retry and hedge attempts skipped by the retry budget")
+            .build();
+
+        let connections_rejected_total = meter
+            .u64_counter("lemonade_connections_rejected_total")
+            .with_description("Client connections closed before reaching a backend")
+            .build();
+
+        let http_routed_total = meter
+            .u64_counter("lemonade_http_routed_total")
+            .with_description("Connections routed by their first HTTP request, per route")
+            .build();
+
+        let strategy_pick_duration = meter
+            .f64_histogram("lb.strategy.pick_duration")
+            .with_unit("s")
+            .with_description("Time spent picking a backend, per strategy, in seconds")
+            .build();
+
+        let backend_entries_evicted_total = meter
+            .u64_counter("lemonade_backend_entries_evicted_total")
+            .with_description(
+                "Per-backend state entries evicted after their backend left",
+            )
+            .build();
+
+        let invariant_violations_total = meter
+            .u64_counter("lemonade_invariant_violations_total")
+            .with_description("Invariant violations found by the consistency audit")
+            .build();
+
+        let strategy_fallbacks_total = meter
+            .u64_counter("lemonade_strategy_fallbacks_total")
+            .with_description("Picks handed to the fallback strategy")
+            .build();
+
+        let selection_exclusions_total = meter
+            .u64_counter("lemonade_backend_selection_exclusions_total")
+            .with_description("Picks that skipped a backend, per exclusion reason")
+            .build();
+
+        let backend_selections_total = meter
+            .u64_counter("lemonade_backend_selections_total")
+            .with_description("Picks that chose a backend")
+            .build();
+
+        let pending_connects = meter
+            .u64_gauge("lemonade_backend_pending_connects")
+            .with_description("Backend connects in flight")
+            .build();
+
+        Self {
+            connect_duration_seconds,
+            time_to_first_byte_seconds,
+            connection_duration_seconds,
+            connections_force_closed_total,
+            connections_aborted_total,
+            retries_suppressed_total,
+            connections_rejected_total,
+            http_routed_total,
+            strategy_pick_duration,
+            backend_entries_evicted_total,
+            invariant_violations_total,
+            strategy_fallbacks_total,
+            selection_exclusions_total,
+            backend_selections_total,
+            pending_connects,
+        }
+    }
+
+    /// Record the timings of a closed connection
+    ///
+    /// # Arguments
+    /// * `backend_id` - Backend the connection was proxied to
+    /// * `connect_micros` - Backend connect duration in microseconds
+    /// * `ttfb_micros` - Time to the first backend byte in microseconds, if any
+    /// * `duration_micros` - Total connection duration in microseconds
+    pub fn record(
+        &self,
+        backend_id: u8,
+        connect_micros: u64,
+        ttfb_micros: Option<u64>,
+        duration_micros: u64,
+    ) {
+        let attributes = [KeyValue::new("backend.id", backend_id as i64)];
+
+        self.connect_duration_seconds
+            .record(connect_micros as f64 / 1_000_000.0, &attributes);
+        if let Some(ttfb_micros) = ttfb_micros {
+            self.time_to_first_byte_seconds
+                .record(ttfb_micros as f64 / 1_000_000.0, &attributes);
+        }
+        self.connection_duration_seconds
+            .record(duration_micros as f64 / 1_000_000.0, &attributes);
+    }
+
+    /// Record a connection force-closed by the load balancer
+    ///
+    /// # Arguments
+    /// * `backend_id` - Backend the connection was proxied to
+    /// * `reason` - Why it was closed (e.g., "drain_deadline")
+    pub fn record_forced_close(&self, backend_id: u8, reason: &'static str) {
+        let attributes = [
+            KeyValue::new("backend.id", backend_id as i64),
+            KeyValue::new("close.reason", reason),
+        ];
+        self.connections_force_closed_total.add(1, &attributes);
+    }
+
+    /// Record a connection reset mid-transfer
+    ///
+    /// # Arguments
+    /// * `backend_id` - Backend the connection was proxied to
+    /// * `reason` - Which side reset it (e.g., "backend_aborted")
+    pub fn record_aborted(&self, backend_id: u8, reason: &'static str) {
+        let attributes = [
+            KeyValue::new("backend.id", backend_id as i64),
+            KeyValue::new("close.reason", reason),
+        ];
+        self.connections_aborted_total.add(1, &attributes);
+    }
+
+    /// Record an extra attempt skipped because the retry budget was spent
+    ///
+    /// # Arguments
+    /// * `backend_id` - Backend of the original attempt
+    /// * `kind` - Kind of attempt skipped (e.g., "hedge")
+    pub fn record_retry_suppressed(&self, backend_id: u8, kind: &'static str) {
+        let attributes = [
+            KeyValue::new("backend.id", backend_id as i64),
+            KeyValue::new("retry.kind", kind),
+        ];
+        self.retries_suppressed_total.add(1, &attributes);
+    }
+
+    /// Record a client connection closed before reaching a backend
+    ///
+    /// # Arguments
+    /// * `reason` - Why it was closed (e.g., "initial_read_timeout")
+    pub fn record_rejected(&self, reason: &'static str) {
+        self.connections_rejected_total
+            .add(1, &[KeyValue::new("reject.reason", reason)]);
+    }
+
+    /// Record a connection routed by its first HTTP request
+    ///
+    /// # Arguments
+    /// * `route` - Name of the rule that matched (e.g., "api"), or "fallback"
+    pub fn record_http_route(&self, route: &str) {
+        self.http_routed_total
+            .add(1, &[KeyValue::new("route", route.to_string())]);
+    }
+
+    /// Record how long a strategy took to pick a backend
+    ///
+    /// # Arguments
+    /// * `strategy` - Strategy name (e.g., "round_robin")
+    /// * `micros` - Pick duration in microseconds
+    pub fn record_strategy_pick(&self, strategy: &str, micros: u64) {
+        self.strategy_pick_duration.record(
+            micros as f64 / 1_000_000.0,
+            &[KeyValue::new("lb.strategy", strategy.to_string())],
+        );
+    }
+
+    /// Record per-backend state entries evicted because their backend left
+    /// the route table
+    ///
+    /// # Arguments
+    /// * `structure` - Structure the entries were evicted from (e.g., "slow_log")
+    /// * `count` - Number of entries evicted
+    pub fn record_entries_evicted(&self, structure: &'static str, count: u64) {
+        self.backend_entries_evicted_total
+            .add(count, &[KeyValue::new("structure", structure)]);
+    }
+
+    /// Record an invariant violation found by the consistency audit
+    ///
+    /// # Arguments
+    /// * `invariant` - Invariant that was violated (e.g., "unknown_backend")
+    pub fn record_invariant_violation(&self, invariant: &'static str) {
+        self.invariant_violations_total
+            .add(1, &[KeyValue::new("invariant", invariant)]);
+    }
+
+    /// Record a pick handed to the fallback strategy
+    ///
+    /// # Arguments
+    /// * `strategy` - Strategy that failed to pick (e.g., "adaptive")
+    /// * `fallback` - Strategy the pick was handed to (e.g., "round_robin")
+    pub fn record_strategy_fallback(&self, strategy: &str, fallback: &str) {
+        self.strategy_fallbacks_total.add(
+            1,
+            &[
+                KeyValue::new("lb.strategy", strategy.to_string()),
+                KeyValue::new("lb.fallback", fallback.to_string()),
+            ],
+        );
+    }
+
+    /// Record picks that skipped a backend
+    ///
+    /// # Arguments
+    /// * `backend_id` - Backend that was skipped
+    /// * `reason` - Why it was skipped (e.g., "unhealthy")
+    /// * `count` - Number of picks that skipped it
+    pub fn record_selection_exclusions(
+        &self,
+        backend_id: u8,
+        reason: &'static str,
+        count: u64,
+    ) {
+        let attributes = [
+            KeyValue::new("backend.id", backend_id as i64),
+            KeyValue::new("exclusion.reason", reason),
+        ];
+        self.selection_exclusions_total.add(count, &attributes);
+    }
+
+    /// Record picks that chose a backend
+    ///
+    /// # Arguments
+    /// * `backend_id` - Backend that was chosen
+    /// * `count` - Number of picks that chose it
+    pub fn record_backend_selections(&self, backend_id: u8, count: u64) {
+        self.backend_selections_total
+            .add(count, &[KeyValue::new("backend.id", backend_id as i64)]);
+    }
+
+    /// Record the connects in flight to a backend
+    ///
+    /// # Arguments
+    /// * `backend_id` - Backend being connected to
+    /// * `pending` - Connects started but not yet established or failed
+    pub fn record_pending_connects(&self, backend_id: u8, pending: u64) {
+        self.pending_connects
+            .record(pending, &[KeyValue::new("backend.id", backend_id as i64)]);
+    }
+}
+
+/// Get or create connection timing metrics for a service (thread-safe)
+pub fn get_connection_metrics(service_name: &str) -> Arc<ConnectionMetrics> {
+    use dashmap::DashMap;
+    use std::sync::OnceLock;
+
+    static METRICS_MAP: OnceLock<DashMap<String, Arc<ConnectionMetrics>>> =
+        OnceLock::new();
+
+    let map = METRICS_MAP.get_or_init(DashMap::new);
+
+    map.entry(service_name.to_string())
+        .or_insert_with(|| {
+            let static_name: &'static str =
+                Box::leak(service_name.to_string().into_boxed_str());
+            Arc::new(ConnectionMetrics::new(static_name))
+        })
+        .clone()
+}
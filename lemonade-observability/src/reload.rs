@@ -0,0 +1,241 @@
+//! Exporter Reconfiguration
+//!
+//! The tracer and meter providers are built once, around a span processor and
+//! a metric exporter whose inner exporter can be replaced at runtime. Tracers
+//! and instruments created from the providers keep working across a swap, so
+//! a changed OTLP endpoint takes effect without restarting the process.
+
+use std::error::Error;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+use std::time::Duration;
+
+use opentelemetry::Context;
+use opentelemetry_otlp::{MetricExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::metrics::Temporality;
+use opentelemetry_sdk::metrics::data::ResourceMetrics;
+use opentelemetry_sdk::metrics::exporter::PushMetricExporter;
+use opentelemetry_sdk::trace::{
+    BatchSpanProcessor, Span, SpanData, SpanExporter, SpanProcessor,
+};
+use opentelemetry_stdout::SpanExporter as StdoutSpanExporter;
+
+static SPAN_PROCESSOR: OnceLock<ReloadableSpanProcessor> = OnceLock::new();
+static METRIC_EXPORTER: OnceLock<ReloadableMetricExporter> = OnceLock::new();
+
+/// Replace the span and metric exporters of the running process
+///
+/// Builds OTLP exporters for `otlp_endpoint` and `otlp_protocol` ("grpc" or
+/// "http"); without both, spans go to the console exporter and metrics are
+/// not exported. The previous exporters are flushed and shut down, so data
+/// buffered for the old endpoint is not lost. Nothing is replaced if the new
+/// exporters cannot be built.
+///
+/// Flushing blocks; call this from a blocking task in async code.
+pub fn reconfigure_exporters(
+    otlp_endpoint: Option<&str>,
+    otlp_protocol: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let span_processor = build_span_processor(otlp_endpoint, otlp_protocol)?;
+    let metric_exporter = build_metric_exporter(otlp_endpoint, otlp_protocol)?;
+    reloadable_span_processor().replace(span_processor);
+    reloadable_metric_exporter().replace(metric_exporter);
+    Ok(())
+}
+
+/// Replace the span exporter of the running process
+///
+/// The previous exporter is flushed and shut down. Spans ending after the
+/// call are batched to `exporter` (e.g. an in-memory exporter in tests).
+pub fn set_span_exporter<E: SpanExporter + 'static>(exporter: E) {
+    reloadable_span_processor().replace(BatchSpanProcessor::builder(exporter).build());
+}
+
+/// Span processor shared with the tracer provider
+pub(crate) fn reloadable_span_processor() -> &'static ReloadableSpanProcessor {
+    SPAN_PROCESSOR.get_or_init(ReloadableSpanProcessor::default)
+}
+
+/// Metric exporter shared with the meter provider
+pub(crate) fn reloadable_metric_exporter() -> &'static ReloadableMetricExporter {
+    METRIC_EXPORTER.get_or_init(ReloadableMetricExporter::default)
+}
+
+/// Batch processor exporting spans over OTLP, or to the console without both
+/// an endpoint and a protocol
+pub(crate) fn build_span_processor(
+    otlp_endpoint: Option<&str>,
+    otlp_protocol: Option<&str>,
+) -> Result<BatchSpanProcessor, Box<dyn Error>> {
+    let (Some(endpoint), Some(protocol)) = (otlp_endpoint, otlp_protocol) else {
+        return Ok(BatchSpanProcessor::builder(StdoutSpanExporter::default()).build());
+    };
+    let exporter = match protocol {
+        "grpc" => opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()?,
+        "http" => opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .build()?,
+        _ => return Err(format!("unsupported OTLP protocol: {}", protocol).into()),
+    };
+    Ok(BatchSpanProcessor::builder(exporter).build())
+}
+
+/// OTLP metric exporter, or `None` without both an endpoint and a protocol
+pub(crate) fn build_metric_exporter(
+    otlp_endpoint: Option<&str>,
+    otlp_protocol: Option<&str>,
+) -> Result<Option<MetricExporter>, Box<dyn Error>> {
+    let (Some(endpoint), Some(protocol)) = (otlp_endpoint, otlp_protocol) else {
+        return Ok(None);
+    };
+    let exporter = match protocol {
+        "grpc" => MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()?,
+        "http" => MetricExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .build()?,
+        _ => return Err(format!("unsupported OTLP protocol: {}", protocol).into()),
+    };
+    Ok(Some(exporter))
+}
+
+/// Span processor forwarding to a replaceable inner processor
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReloadableSpanProcessor {
+    inner: Arc<SpanProcessorSlot>,
+}
+
+/// Shared state of a [`ReloadableSpanProcessor`]
+#[derive(Debug, Default)]
+struct SpanProcessorSlot {
+    /// Processor spans are forwarded to
+    processor: RwLock<Option<Box<dyn SpanProcessor>>>,
+    /// Resource of the tracer provider, handed to every new processor
+    resource: RwLock<Option<Resource>>,
+}
+
+impl ReloadableSpanProcessor {
+    /// Forward spans to `processor`, then flush and shut down the previous one
+    pub(crate) fn replace<P: SpanProcessor + 'static>(&self, mut processor: P) {
+        if let Some(resource) = read(&self.inner.resource).as_ref() {
+            processor.set_resource(resource);
+        }
+        let previous = write(&self.inner.processor).replace(Box::new(processor));
+        if let Some(previous) = previous {
+            if let Err(e) = previous.force_flush() {
+                tracing::warn!("Failed to flush previous span exporter: {}", e);
+            }
+            if let Err(e) = previous.shutdown() {
+                tracing::warn!("Failed to shut down previous span exporter: {}", e);
+            }
+        }
+    }
+}
+
+impl SpanProcessor for ReloadableSpanProcessor {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        if let Some(processor) = read(&self.inner.processor).as_ref() {
+            processor.on_start(span, cx);
+        }
+    }
+
+    fn on_end(&self, span: SpanData) {
+        // Holding the read lock keeps a swap from shutting the processor down
+        // while the span is handed over
+        if let Some(processor) = read(&self.inner.processor).as_ref() {
+            processor.on_end(span);
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        match read(&self.inner.processor).as_ref() {
+            Some(processor) => processor.force_flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        match write(&self.inner.processor).take() {
+            Some(processor) => processor.shutdown_with_timeout(timeout),
+            None => Ok(()),
+        }
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        *write(&self.inner.resource) = Some(resource.clone());
+        if let Some(processor) = write(&self.inner.processor).as_mut() {
+            processor.set_resource(resource);
+        }
+    }
+}
+
+/// Metric exporter forwarding to a replaceable OTLP exporter
+///
+/// Exports are dropped while no exporter is set. Temporality is cumulative,
+/// so the first export to a new endpoint carries the full totals.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReloadableMetricExporter {
+    exporter: Arc<RwLock<Option<Arc<MetricExporter>>>>,
+}
+
+impl ReloadableMetricExporter {
+    /// Export to `exporter`, then flush and shut down the previous one
+    pub(crate) fn replace(&self, exporter: Option<MetricExporter>) {
+        let previous =
+            std::mem::replace(&mut *write(&self.exporter), exporter.map(Arc::new));
+        if let Some(previous) = previous {
+            if let Err(e) = previous.force_flush() {
+                tracing::warn!("Failed to flush previous metric exporter: {}", e);
+            }
+            if let Err(e) = previous.shutdown() {
+                tracing::warn!("Failed to shut down previous metric exporter: {}", e);
+            }
+        }
+    }
+}
+
+impl PushMetricExporter for ReloadableMetricExporter {
+    async fn export(&self, metrics: &ResourceMetrics) -> OTelSdkResult {
+        let exporter = read(&self.exporter).clone();
+        match exporter {
+            Some(exporter) => exporter.export(metrics).await,
+            None => Ok(()),
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        match read(&self.exporter).as_ref() {
+            Some(exporter) => exporter.force_flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        match write(&self.exporter).take() {
+            Some(exporter) => exporter.shutdown_with_timeout(timeout),
+            None => Ok(()),
+        }
+    }
+
+    fn temporality(&self) -> Temporality {
+        Temporality::Cumulative
+    }
+}
+
+/// Read a lock, ignoring poisoning (the guarded values stay consistent)
+fn read<T>(lock: &RwLock<T>) -> std::sync::RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Write a lock, ignoring poisoning (the guarded values stay consistent)
+fn write<T>(lock: &RwLock<T>) -> std::sync::RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
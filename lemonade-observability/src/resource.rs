@@ -2,6 +2,7 @@
 //!
 //! Creates OpenTelemetry Resource with service identification attributes
 
+use crate::build_info::GIT_SHA;
 use opentelemetry::KeyValue;
 use opentelemetry_sdk::Resource;
 
@@ -13,7 +14,8 @@ use opentelemetry_sdk::Resource;
 /// * `service_instance_id` - Unique identifier for the service instance (e.g., "lemonade-worker-1")
 ///
 /// # Returns
-/// OpenTelemetry Resource with standard attributes
+/// OpenTelemetry Resource with standard attributes, plus `service.build.sha`
+/// (the commit the binary was built from)
 pub fn create_resource(
     service_name: impl Into<String>,
     service_version: impl Into<String>,
@@ -25,6 +27,7 @@ pub fn create_resource(
         .with_attributes(vec![
             KeyValue::new("service.version", service_version.into()),
             KeyValue::new("service.instance.id", service_instance_id.into()),
+            KeyValue::new("service.build.sha", GIT_SHA),
         ])
         .build()
 }
@@ -0,0 +1,202 @@
+//! Access log module
+//!
+//! One line per served request, with the same fields whatever framework the
+//! worker runs on. Each worker only measures the request and hands an
+//! [`AccessLogEntry`] to the shared [`AccessLog`], which formats and writes it.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Header carrying the request id logged with each request
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Accepted access log formats
+pub const ACCESS_LOG_FORMATS: &[&str] = &["off", "json", "common"];
+
+/// Access log format
+///
+/// `json` writes one JSON object per request; `common` writes
+/// `<request id> [<timestamp ms>] "<method> <path>" <status> <bytes> <duration us>`,
+/// with `-` for a missing request id.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLogFormat {
+    /// No access log
+    #[default]
+    Off,
+    /// One JSON object per line
+    Json,
+    /// Common-log-style text line
+    Common,
+}
+
+impl std::str::FromStr for AccessLogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "json" => Ok(Self::Json),
+            "common" => Ok(Self::Common),
+            _ => Err(format!(
+                "Unsupported access log format: {}. Accepted values: {}",
+                s,
+                ACCESS_LOG_FORMATS.join(", ")
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for AccessLogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::Json => write!(f, "json"),
+            Self::Common => write!(f, "common"),
+        }
+    }
+}
+
+/// One served request
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccessLogEntry {
+    /// Request method
+    pub method: String,
+    /// Request path (without the query string)
+    pub path: String,
+    /// Response status code
+    pub status: u16,
+    /// Time spent serving the request, in microseconds
+    pub duration_micros: u64,
+    /// Response body size in bytes
+    pub bytes: u64,
+    /// Value of the `x-request-id` request header
+    pub request_id: Option<String>,
+}
+
+impl AccessLogEntry {
+    /// Create an entry for a request served in `duration`
+    pub fn new(
+        method: impl Into<String>,
+        path: impl Into<String>,
+        status: u16,
+        duration: Duration,
+        bytes: u64,
+        request_id: Option<String>,
+    ) -> Self {
+        Self {
+            method: method.into(),
+            path: path.into(),
+            status,
+            duration_micros: duration.as_micros() as u64,
+            bytes,
+            request_id,
+        }
+    }
+
+    /// Render the entry as a log line, or `None` when logging is off
+    pub fn render(&self, format: AccessLogFormat, timestamp_ms: u64) -> Option<String> {
+        match format {
+            AccessLogFormat::Off => None,
+            AccessLogFormat::Json => {
+                #[derive(Serialize)]
+                struct Line<'a> {
+                    timestamp_ms: u64,
+                    #[serde(flatten)]
+                    entry: &'a AccessLogEntry,
+                }
+                serde_json::to_string(&Line {
+                    timestamp_ms,
+                    entry: self,
+                })
+                .ok()
+            }
+            AccessLogFormat::Common => Some(format!(
+                "{} [{}] \"{} {}\" {} {} {}",
+                self.request_id.as_deref().unwrap_or("-"),
+                timestamp_ms,
+                self.method,
+                self.path,
+                self.status,
+                self.bytes,
+                self.duration_micros
+            )),
+        }
+    }
+}
+
+/// Where access log lines go
+#[derive(Debug, Clone)]
+enum AccessLogSink {
+    /// Standard output
+    Stdout,
+    /// Kept in memory (tests)
+    Memory(Arc<Mutex<Vec<String>>>),
+}
+
+/// Access log shared by a worker's request handlers
+#[derive(Debug, Clone)]
+pub struct AccessLog {
+    /// Line format
+    format: AccessLogFormat,
+    /// Line destination
+    sink: AccessLogSink,
+}
+
+impl AccessLog {
+    /// Access log writing to standard output
+    pub fn new(format: AccessLogFormat) -> Self {
+        Self {
+            format,
+            sink: AccessLogSink::Stdout,
+        }
+    }
+
+    /// Access log keeping its lines in memory, see [`AccessLog::lines`]
+    pub fn in_memory(format: AccessLogFormat) -> Self {
+        Self {
+            format,
+            sink: AccessLogSink::Memory(Arc::default()),
+        }
+    }
+
+    /// Line format
+    pub fn format(&self) -> AccessLogFormat {
+        self.format
+    }
+
+    /// Check whether requests are logged
+    ///
+    /// Adapters can skip measuring requests when this is false.
+    pub fn is_enabled(&self) -> bool {
+        self.format != AccessLogFormat::Off
+    }
+
+    /// Log a served request
+    pub fn record(&self, entry: &AccessLogEntry) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        let Some(line) = entry.render(self.format, timestamp_ms) else {
+            return;
+        };
+        match &self.sink {
+            AccessLogSink::Stdout => println!("{}", line),
+            AccessLogSink::Memory(lines) => {
+                lines.lock().unwrap_or_else(|e| e.into_inner()).push(line)
+            }
+        }
+    }
+
+    /// Lines kept by an in-memory access log (empty for standard output)
+    pub fn lines(&self) -> Vec<String> {
+        match &self.sink {
+            AccessLogSink::Stdout => Vec::new(),
+            AccessLogSink::Memory(lines) => {
+                lines.lock().unwrap_or_else(|e| e.into_inner()).clone()
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Bootstrap module
+//!
+//! Startup shared by every worker framework: tracing and metrics
+//! initialization, application state and a startup banner with the same
+//! fields whichever framework serves the routes.
+use crate::AppState;
+use crate::config::{Config, WorkerAddress};
+use std::error::Error;
+use std::fmt;
+
+/// Everything a worker needs to start serving
+pub struct BootstrapHandle {
+    /// Application state to serve
+    pub state: AppState,
+    /// Address to listen on
+    pub listen_address: WorkerAddress,
+    /// Startup banner (already logged)
+    pub banner: StartupBanner,
+    /// Flushes buffered spans and metrics when dropped; hold it until the
+    /// server stops
+    pub observability: ObservabilityGuard,
+}
+
+/// Initialize tracing and metrics for `service_name` and build the state
+/// of a worker
+///
+/// `service_name` identifies the worker framework (e.g.
+/// `lemonade-worker-axum`); the config's service name identifies the
+/// instance. Logs the startup banner.
+pub fn bootstrap(
+    config: Config,
+    service_name: &'static str,
+) -> Result<BootstrapHandle, Box<dyn Error>> {
+    let version = env!("CARGO_PKG_VERSION");
+    lemonade_observability::init_tracing(
+        service_name,
+        version,
+        config.service_name(),
+        config.otlp_endpoint(),
+        config.otlp_protocol(),
+    )?;
+    lemonade_observability::init_metrics(
+        service_name,
+        version,
+        config.service_name(),
+        config.otlp_endpoint(),
+        config.otlp_protocol(),
+    )?;
+
+    let banner = StartupBanner::new(&config, service_name, version);
+    banner.log();
+    Ok(BootstrapHandle {
+        listen_address: config.listen_address().clone(),
+        state: AppState::new(config),
+        banner,
+        observability: ObservabilityGuard { _private: () },
+    })
+}
+
+/// Fields logged once when a worker starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartupBanner {
+    /// Worker framework service name
+    pub service_name: &'static str,
+    /// Worker version
+    pub service_version: &'static str,
+    /// Commit the worker was built from
+    pub git_sha: &'static str,
+    /// Instance name from the config
+    pub instance_id: String,
+    /// Address to listen on
+    pub listen_address: WorkerAddress,
+    /// Work delay in milliseconds
+    pub work_delay_ms: u64,
+    /// OTLP exporter endpoint, if any
+    pub otlp_endpoint: Option<String>,
+    /// OTLP exporter protocol, if any
+    pub otlp_protocol: Option<String>,
+    /// Access log format
+    pub access_log: String,
+}
+
+impl StartupBanner {
+    /// Banner of a worker started with `config`
+    pub fn new(
+        config: &Config,
+        service_name: &'static str,
+        service_version: &'static str,
+    ) -> Self {
+        Self {
+            service_name,
+            service_version,
+            git_sha: lemonade_observability::build_info::GIT_SHA,
+            instance_id: config.service_name().to_string(),
+            listen_address: config.listen_address().clone(),
+            work_delay_ms: config.work_delay().as_millis() as u64,
+            otlp_endpoint: config.otlp_endpoint().map(String::from),
+            otlp_protocol: config.otlp_protocol().map(String::from),
+            access_log: config.access_log().to_string(),
+        }
+    }
+
+    /// Log the banner as one structured event
+    pub fn log(&self) {
+        tracing::info!(
+            service.name = self.service_name,
+            service.version = self.service_version,
+            service.build.sha = self.git_sha,
+            service.instance.id = %self.instance_id,
+            listen.address = %self.listen_address,
+            work_delay_ms = self.work_delay_ms,
+            otlp.endpoint = ?self.otlp_endpoint,
+            otlp.protocol = ?self.otlp_protocol,
+            access_log = %self.access_log,
+            "Worker starting"
+        );
+    }
+}
+
+impl fmt::Display for StartupBanner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} [{}] ({}) on {}, work delay {}ms, otlp {:?}/{:?}, access log {}",
+            self.service_name,
+            self.service_version,
+            self.git_sha,
+            self.instance_id,
+            self.listen_address,
+            self.work_delay_ms,
+            self.otlp_endpoint,
+            self.otlp_protocol,
+            self.access_log
+        )
+    }
+}
+
+/// Flushes buffered spans and metrics when dropped
+#[must_use = "dropping the guard flushes telemetry immediately"]
+pub struct ObservabilityGuard {
+    _private: (),
+}
+
+impl Drop for ObservabilityGuard {
+    fn drop(&mut self) {
+        if let Err(e) = lemonade_observability::flush_exporters() {
+            eprintln!("Failed to flush telemetry: {}", e);
+        }
+    }
+}
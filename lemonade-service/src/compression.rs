@@ -0,0 +1,266 @@
+//! Response compression module
+//!
+//! Responses are compressed the same way whatever framework the worker runs
+//! on. Each worker hands the response body and the request's
+//! `Accept-Encoding` header to the shared [`ResponseCompression`], which
+//! picks the encoding, compresses the body and counts the outcome.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Request header listing the encodings the client accepts
+pub const ACCEPT_ENCODING_HEADER: &str = "accept-encoding";
+
+/// Response header naming the encoding of a compressed body
+pub const CONTENT_ENCODING_HEADER: &str = "content-encoding";
+
+/// Response header listing the request headers the response depends on
+pub const VARY_HEADER: &str = "vary";
+
+/// Accepted compression modes
+pub const COMPRESSION_MODES: &[&str] = &["off", "gzip", "auto"];
+
+/// Default smallest body compressed, in bytes
+pub const DEFAULT_COMPRESSION_MIN_BYTES: usize = 1024;
+
+/// Brotli quality, traded for speed as for dynamic web content
+const BROTLI_QUALITY: u32 = 5;
+
+/// Brotli window size (log2)
+const BROTLI_WINDOW_BITS: u32 = 22;
+
+/// Brotli encoder buffer size
+const BROTLI_BUFFER_SIZE: usize = 4096;
+
+/// Response compression mode
+///
+/// `gzip` compresses for clients accepting gzip; `auto` prefers brotli and
+/// falls back to gzip, following the client's preference when it gives one.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionMode {
+    /// Responses are sent uncompressed
+    #[default]
+    Off,
+    /// gzip only
+    Gzip,
+    /// brotli or gzip
+    Auto,
+}
+
+impl std::str::FromStr for CompressionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "gzip" => Ok(Self::Gzip),
+            "auto" => Ok(Self::Auto),
+            _ => Err(format!(
+                "Unsupported compression mode: {}. Accepted values: {}",
+                s,
+                COMPRESSION_MODES.join(", ")
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for CompressionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::Gzip => write!(f, "gzip"),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// Encoding of a compressed response body
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    /// gzip
+    Gzip,
+    /// brotli
+    Brotli,
+}
+
+impl ContentEncoding {
+    /// `Content-Encoding` header value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Brotli => "br",
+        }
+    }
+
+    /// Compress a body
+    pub fn encode(&self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(
+                    Vec::new(),
+                    flate2::Compression::default(),
+                );
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+            Self::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(
+                    Vec::new(),
+                    BROTLI_BUFFER_SIZE,
+                    BROTLI_QUALITY,
+                    BROTLI_WINDOW_BITS,
+                );
+                encoder.write_all(body)?;
+                encoder.flush()?;
+                Ok(encoder.into_inner())
+            }
+        }
+    }
+}
+
+/// Responses sent compressed and uncompressed while compression is enabled
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompressionStats {
+    /// Responses sent compressed
+    pub compressed: u64,
+    /// Responses sent as is (encoding not accepted or body too small)
+    pub uncompressed: u64,
+}
+
+/// Response compression shared by a worker's request handlers
+#[derive(Debug, Clone)]
+pub struct ResponseCompression {
+    /// Compression mode
+    mode: CompressionMode,
+    /// Smallest body compressed, in bytes
+    min_bytes: usize,
+    /// Responses sent compressed
+    compressed: Arc<AtomicU64>,
+    /// Responses sent as is
+    uncompressed: Arc<AtomicU64>,
+}
+
+impl ResponseCompression {
+    /// Compress bodies of at least `min_bytes` bytes in `mode`
+    pub fn new(mode: CompressionMode, min_bytes: usize) -> Self {
+        Self {
+            mode,
+            min_bytes,
+            compressed: Arc::default(),
+            uncompressed: Arc::default(),
+        }
+    }
+
+    /// Compression mode
+    pub fn mode(&self) -> CompressionMode {
+        self.mode
+    }
+
+    /// Smallest body compressed, in bytes
+    pub fn min_bytes(&self) -> usize {
+        self.min_bytes
+    }
+
+    /// Check whether responses may be compressed
+    ///
+    /// Adapters can skip buffering response bodies when this is false.
+    pub fn is_enabled(&self) -> bool {
+        self.mode != CompressionMode::Off
+    }
+
+    /// Encoding to send a `body_len`-byte body in, given the request's
+    /// `Accept-Encoding` header
+    ///
+    /// Empty bodies and bodies under the threshold are never compressed.
+    pub fn negotiate(
+        &self,
+        accept_encoding: Option<&str>,
+        body_len: usize,
+    ) -> Option<ContentEncoding> {
+        if !self.is_enabled() || body_len == 0 || body_len < self.min_bytes {
+            return None;
+        }
+        let accept_encoding = accept_encoding?;
+        let gzip = accepted_quality(accept_encoding, ContentEncoding::Gzip);
+        match self.mode {
+            CompressionMode::Off => None,
+            CompressionMode::Gzip => gzip.map(|_| ContentEncoding::Gzip),
+            CompressionMode::Auto => {
+                match (
+                    accepted_quality(accept_encoding, ContentEncoding::Brotli),
+                    gzip,
+                ) {
+                    (Some(br), Some(gzip)) if gzip > br => Some(ContentEncoding::Gzip),
+                    (Some(_), _) => Some(ContentEncoding::Brotli),
+                    (None, Some(_)) => Some(ContentEncoding::Gzip),
+                    (None, None) => None,
+                }
+            }
+        }
+    }
+
+    /// Compress a response body for the request's `Accept-Encoding` header
+    ///
+    /// Returns the encoding and the compressed body, or `None` when the body
+    /// is to be sent as is. Either outcome is counted.
+    pub fn compress(
+        &self,
+        accept_encoding: Option<&str>,
+        body: &[u8],
+    ) -> Option<(ContentEncoding, Vec<u8>)> {
+        let compressed = self.negotiate(accept_encoding, body.len()).and_then(
+            |encoding| match encoding.encode(body) {
+                Ok(compressed) => Some((encoding, compressed)),
+                Err(e) => {
+                    tracing::warn!(
+                        encoding = encoding.as_str(),
+                        "Failed to compress response: {}",
+                        e
+                    );
+                    None
+                }
+            },
+        );
+        let counter = if compressed.is_some() {
+            &self.compressed
+        } else {
+            &self.uncompressed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        compressed
+    }
+
+    /// Responses sent compressed and uncompressed so far
+    pub fn stats(&self) -> CompressionStats {
+        CompressionStats {
+            compressed: self.compressed.load(Ordering::Relaxed),
+            uncompressed: self.uncompressed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Quality the client gives an encoding in an `Accept-Encoding` header
+///
+/// `None` when it is not accepted (absent, or `q=0`). A `*` entry covers
+/// encodings not listed by name.
+fn accepted_quality(accept_encoding: &str, encoding: ContentEncoding) -> Option<f32> {
+    let mut wildcard = None;
+    for entry in accept_encoding.split(',') {
+        let mut parts = entry.split(';');
+        let name = parts.next().unwrap_or_default().trim();
+        let quality = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if name.eq_ignore_ascii_case(encoding.as_str()) {
+            return (quality > 0.0).then_some(quality);
+        }
+        if name == "*" {
+            wildcard = Some(quality);
+        }
+    }
+    wildcard.filter(|quality| *quality > 0.0)
+}
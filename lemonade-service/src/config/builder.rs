@@ -1,7 +1,9 @@
 //! Config Builder module
 //!
+//! Loads worker configs from files (JSON, TOML, YAML) and the environment,
+//! layered with the precedence CLI flag > environment > config file
 
-use super::{Config, ConfigError, WorkerAddress};
+use super::{Config, ConfigError, ConfigLayer, WorkerAddress};
 use std::{path::PathBuf, time::Duration};
 
 use constants::*;
@@ -13,37 +15,19 @@ pub struct ConfigBuilder;
 
 impl ConfigBuilder {
     /// Load configuration from environment variables
+    ///
+    /// Reads `LEMONADE_WORKER_LISTEN_ADDRESS`, `LEMONADE_WORKER_SERVICE_NAME`,
+    /// `LEMONADE_WORKER_WORK_DELAY_MS`, `LEMONADE_WORKER_ACCESS_LOG`,
+    /// `LEMONADE_WORKER_COMPRESSION`, `LEMONADE_WORKER_COMPRESSION_MIN_BYTES`,
+    /// `LEMONADE_WORKER_PROMETHEUS_METRICS`, `LEMONADE_OTLP_ENDPOINT` and `LEMONADE_OTLP_PROTOCOL`; unset variables fall back to the defaults.
     pub fn from_env() -> Result<Config, ConfigError> {
         dotenv().ok();
-
-        let listen_address = WorkerAddress::parse(
-            std::env::var(WORKER_LISTEN_ADDRESS_ENV_KEY)
-                .unwrap_or_else(|_| WORKER_LISTEN_ADDRESS_DEFAULT.to_string())
-                .as_str(),
-        )?;
-
-        let service_name = std::env::var(WORKER_SERVICE_NAME_ENV_KEY)
-            .unwrap_or_else(|_| WORKER_SERVICE_NAME_DEFAULT.to_string());
-
-        let work_delay_ms = std::env::var(WORKER_WORK_DELAY_ENV_KEY)
-            .unwrap_or_else(|_| WORKER_WORK_DELAY_DEFAULT.to_string())
-            .parse::<u64>()
-            .map_err(|e| ConfigError::Parse(format!("Invalid WORK_DELAY_MS: {}", e)))?;
-
-        let otlp_endpoint = std::env::var(WORKER_OTLP_ENDPOINT_ENV_KEY).ok();
-
-        let otlp_protocol = std::env::var(WORKER_OTLP_PROTOCOL_ENV_KEY).ok();
-
-        Ok(Config {
-            listen_address,
-            service_name,
-            work_delay: Duration::from_millis(work_delay_ms),
-            otlp_endpoint,
-            otlp_protocol,
-        })
+        ConfigLayer::from_env()?.apply(Self::defaults()?)
     }
 
-    /// Load configuration from a file (supports JSON and TOML)
+    /// Load configuration from a file (supports JSON, TOML and YAML)
+    ///
+    /// Errors reading, parsing or validating the file name the file.
     pub fn from_file(path: Option<impl Into<PathBuf>>) -> Result<Config, ConfigError> {
         if let Some(path) = path {
             let path = path.into();
@@ -51,7 +35,6 @@ impl ConfigBuilder {
                 return Err(ConfigError::FileNotFound(path));
             }
 
-            let content = std::fs::read_to_string(&path)?;
             let extension =
                 path.extension()
                     .and_then(|ext| ext.to_str())
@@ -59,30 +42,78 @@ impl ConfigBuilder {
                         ConfigError::UnsupportedFormat(path.to_string_lossy().to_string())
                     })?;
 
-            match extension.to_lowercase().as_str() {
-                "json" => {
-                    let config: Config = serde_json::from_str(&content)?;
-                    Ok(config)
-                }
-                "toml" => {
-                    let config: Config = toml::from_str(&content)?;
-                    Ok(config)
-                }
-                "yaml" | "yml" => {
-                    let config: Config = serde_yaml::from_str(&content)?;
-                    Ok(config)
-                }
-                _ => Err(ConfigError::UnsupportedFormat(
-                    path.to_string_lossy().to_string(),
-                )),
-            }
+            let parse = |content: &str| -> Result<Config, ConfigError> {
+                let config: Config = match extension.to_lowercase().as_str() {
+                    "json" => serde_json::from_str(content)?,
+                    "toml" => toml::from_str(content)?,
+                    "yaml" | "yml" => serde_yaml::from_str(content)?,
+                    _ => {
+                        return Err(ConfigError::UnsupportedFormat(
+                            path.to_string_lossy().to_string(),
+                        ));
+                    }
+                };
+                config.otlp().validate()?;
+                Ok(config)
+            };
+
+            std::fs::read_to_string(&path)
+                .map_err(ConfigError::from)
+                .and_then(|content| parse(&content))
+                .map_err(|e| match e {
+                    ConfigError::UnsupportedFormat(_) => e,
+                    e => ConfigError::InvalidFile {
+                        path: path.clone(),
+                        source: Box::new(e),
+                    },
+                })
         } else {
             Self::from_env()
         }
     }
+
+    /// Load configuration from an optional file, the environment and flags
+    ///
+    /// The file (or the defaults when there is none) is overridden by the
+    /// environment, which is overridden by `flags`.
+    ///
+    /// # Arguments
+    /// * `path` - Optional config file
+    /// * `flags` - Values given on the command line
+    pub fn load(
+        path: Option<impl Into<PathBuf>>,
+        flags: ConfigLayer,
+    ) -> Result<Config, ConfigError> {
+        dotenv().ok();
+        let base = match path {
+            Some(path) => Self::from_file(Some(path))?,
+            None => Self::defaults()?,
+        };
+        Self::layer(base, flags, ConfigLayer::from_env()?)
+    }
+
+    /// Apply explicit flag and environment layers over a config
+    ///
+    /// Each field is taken from the highest-precedence layer that sets it.
+    pub fn layer(
+        config: Config,
+        flags: ConfigLayer,
+        env: ConfigLayer,
+    ) -> Result<Config, ConfigError> {
+        flags.or(env).apply(config)
+    }
+
+    /// Default configuration
+    fn defaults() -> Result<Config, ConfigError> {
+        Ok(Config::new(
+            WorkerAddress::parse(WORKER_LISTEN_ADDRESS_DEFAULT)?,
+            WORKER_SERVICE_NAME_DEFAULT,
+            Duration::from_millis(WORKER_WORK_DELAY_DEFAULT),
+        ))
+    }
 }
 
-mod constants {
+pub(super) mod constants {
     //! Constants module
     //!
     pub const WORKER_LISTEN_ADDRESS_ENV_KEY: &str = "LEMONADE_WORKER_LISTEN_ADDRESS";
@@ -91,9 +122,15 @@ mod constants {
 
     pub const WORKER_WORK_DELAY_ENV_KEY: &str = "LEMONADE_WORKER_WORK_DELAY_MS";
 
-    pub const WORKER_OTLP_ENDPOINT_ENV_KEY: &str = "LEMONADE_OTLP_ENDPOINT";
+    pub const WORKER_ACCESS_LOG_ENV_KEY: &str = "LEMONADE_WORKER_ACCESS_LOG";
+
+    pub const WORKER_COMPRESSION_ENV_KEY: &str = "LEMONADE_WORKER_COMPRESSION";
+
+    pub const WORKER_COMPRESSION_MIN_BYTES_ENV_KEY: &str =
+        "LEMONADE_WORKER_COMPRESSION_MIN_BYTES";
 
-    pub const WORKER_OTLP_PROTOCOL_ENV_KEY: &str = "LEMONADE_OTLP_PROTOCOL";
+    pub const WORKER_PROMETHEUS_METRICS_ENV_KEY: &str =
+        "LEMONADE_WORKER_PROMETHEUS_METRICS";
 
     pub const WORKER_LISTEN_ADDRESS_DEFAULT: &str = "127.0.0.1:50200";
 
@@ -25,6 +25,17 @@ pub enum ConfigError {
     /// Worker address error
     #[error("Worker address error: {0}")]
     WorkerAddress(#[from] WorkerAddressError),
+    /// Unknown OTLP protocol
+    #[error("Unsupported OTLP protocol: {0}. Accepted values: grpc, http")]
+    OtlpProtocol(String),
+    /// Config file could not be read, parsed or validated
+    #[error("invalid config file {}: {source}", path.display())]
+    InvalidFile {
+        /// Path of the config file
+        path: PathBuf,
+        /// Underlying error
+        source: Box<ConfigError>,
+    },
     /// Unsupported file format
     #[error("Unsupported file format: {0}. Supported formats: .json, .toml, .yaml, .yml")]
     UnsupportedFormat(String),
@@ -0,0 +1,160 @@
+//! Config layer module
+//!
+//! Partial worker configs from the command line or the environment, applied
+//! over a base config with the precedence flag > environment > config file
+
+use super::builder::constants::*;
+use super::{Config, ConfigError, OtlpConfig, WorkerAddress};
+use crate::access_log::AccessLogFormat;
+use crate::compression::CompressionMode;
+use std::time::Duration;
+
+/// Partial worker config from a single source
+///
+/// Unset fields leave the underlying value untouched.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigLayer {
+    /// Listen address
+    pub listen_address: Option<WorkerAddress>,
+    /// Service name
+    pub service_name: Option<String>,
+    /// Work delay
+    pub work_delay: Option<Duration>,
+    /// OTLP exporter settings
+    pub otlp: OtlpConfig,
+    /// Access log format
+    pub access_log: Option<AccessLogFormat>,
+    /// Response compression mode
+    pub compression: Option<CompressionMode>,
+    /// Smallest response body compressed, in bytes
+    pub compression_min_bytes: Option<usize>,
+    /// Prometheus `/metrics` endpoint
+    pub prometheus_metrics: Option<bool>,
+}
+
+impl ConfigLayer {
+    /// Read the `LEMONADE_WORKER_*` and `LEMONADE_OTLP_*` variables that are set
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let listen_address = std::env::var(WORKER_LISTEN_ADDRESS_ENV_KEY)
+            .ok()
+            .map(|v| WorkerAddress::parse(&v))
+            .transpose()?;
+
+        let service_name = std::env::var(WORKER_SERVICE_NAME_ENV_KEY).ok();
+
+        let work_delay = std::env::var(WORKER_WORK_DELAY_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<u64>().map(Duration::from_millis).map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        WORKER_WORK_DELAY_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let access_log = std::env::var(WORKER_ACCESS_LOG_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<AccessLogFormat>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        WORKER_ACCESS_LOG_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let compression = std::env::var(WORKER_COMPRESSION_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<CompressionMode>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        WORKER_COMPRESSION_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let compression_min_bytes = std::env::var(WORKER_COMPRESSION_MIN_BYTES_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<usize>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        WORKER_COMPRESSION_MIN_BYTES_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let prometheus_metrics = std::env::var(WORKER_PROMETHEUS_METRICS_ENV_KEY)
+            .ok()
+            .map(|v| {
+                v.parse::<bool>().map_err(|e| {
+                    ConfigError::Parse(format!(
+                        "Invalid {}: {}",
+                        WORKER_PROMETHEUS_METRICS_ENV_KEY, e
+                    ))
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            listen_address,
+            service_name,
+            work_delay,
+            otlp: OtlpConfig::from_env(),
+            access_log,
+            compression,
+            compression_min_bytes,
+            prometheus_metrics,
+        })
+    }
+
+    /// Fill unset fields from a lower-precedence layer
+    pub fn or(self, fallback: ConfigLayer) -> Self {
+        Self {
+            listen_address: self.listen_address.or(fallback.listen_address),
+            service_name: self.service_name.or(fallback.service_name),
+            work_delay: self.work_delay.or(fallback.work_delay),
+            otlp: self.otlp.or(fallback.otlp),
+            access_log: self.access_log.or(fallback.access_log),
+            compression: self.compression.or(fallback.compression),
+            compression_min_bytes: self
+                .compression_min_bytes
+                .or(fallback.compression_min_bytes),
+            prometheus_metrics: self.prometheus_metrics.or(fallback.prometheus_metrics),
+        }
+    }
+
+    /// Apply the set fields over a config
+    pub fn apply(self, mut config: Config) -> Result<Config, ConfigError> {
+        if let Some(listen_address) = self.listen_address {
+            config.listen_address = listen_address;
+        }
+        if let Some(service_name) = self.service_name {
+            config.service_name = service_name;
+        }
+        if let Some(work_delay) = self.work_delay {
+            config.work_delay = work_delay;
+        }
+        if let Some(access_log) = self.access_log {
+            config.access_log = access_log;
+        }
+        if let Some(compression) = self.compression {
+            config.compression = compression;
+        }
+        if let Some(compression_min_bytes) = self.compression_min_bytes {
+            config.compression_min_bytes = compression_min_bytes;
+        }
+        if let Some(prometheus_metrics) = self.prometheus_metrics {
+            config.prometheus_metrics = prometheus_metrics;
+        }
+        let otlp = self.otlp.or(config.otlp());
+        otlp.validate()?;
+        Ok(config.with_otlp(otlp))
+    }
+}
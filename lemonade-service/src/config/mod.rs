@@ -1,16 +1,25 @@
 //! Config module
 //!
+use crate::access_log::AccessLogFormat;
+use crate::compression::{CompressionMode, DEFAULT_COMPRESSION_MIN_BYTES};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 mod builder;
 mod error;
+mod layer;
+mod otlp;
 mod serde_helpers;
 mod worker_address;
 
 pub use builder::ConfigBuilder;
 pub use error::ConfigError;
-pub use worker_address::{WorkerAddress, WorkerAddressError};
+pub use layer::ConfigLayer;
+pub use otlp::{
+    OTLP_ENDPOINT_ENV_KEY, OTLP_PROTOCOL_ENV_KEY, OTLP_PROTOCOLS, OtlpConfig,
+    parse_otlp_protocol,
+};
+pub use worker_address::{UNIX_ADDRESS_PREFIX, WorkerAddress, WorkerAddressError};
 
 /// Config struct
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -19,8 +28,8 @@ pub struct Config {
     listen_address: WorkerAddress,
     /// Service name
     service_name: String,
-    /// Work delay
-    #[serde(with = "serde_helpers")]
+    /// Work delay (milliseconds)
+    #[serde(with = "serde_helpers", alias = "work_delay_ms")]
     work_delay: Duration,
     /// OTLP exporter endpoint (optional)
     #[serde(default)]
@@ -28,6 +37,23 @@ pub struct Config {
     /// OTLP exporter protocol (optional)
     #[serde(default)]
     otlp_protocol: Option<String>,
+    /// Access log format
+    #[serde(default)]
+    access_log: AccessLogFormat,
+    /// Response compression mode
+    #[serde(default)]
+    compression: CompressionMode,
+    /// Smallest response body compressed, in bytes
+    #[serde(default = "default_compression_min_bytes")]
+    compression_min_bytes: usize,
+    /// Serve request metrics on `/metrics` in Prometheus text format
+    #[serde(default)]
+    prometheus_metrics: bool,
+}
+
+/// Default smallest response body compressed
+fn default_compression_min_bytes() -> usize {
+    DEFAULT_COMPRESSION_MIN_BYTES
 }
 
 impl Config {
@@ -43,6 +69,10 @@ impl Config {
             work_delay: work_delay.into(),
             otlp_endpoint: None,
             otlp_protocol: None,
+            access_log: AccessLogFormat::Off,
+            compression: CompressionMode::Off,
+            compression_min_bytes: DEFAULT_COMPRESSION_MIN_BYTES,
+            prometheus_metrics: false,
         }
     }
     /// Get the listen address
@@ -69,4 +99,55 @@ impl Config {
     pub fn otlp_protocol(&self) -> Option<&str> {
         self.otlp_protocol.as_deref()
     }
+
+    /// Get the OTLP exporter settings
+    pub fn otlp(&self) -> OtlpConfig {
+        OtlpConfig::new(self.otlp_endpoint.clone(), self.otlp_protocol.clone())
+    }
+
+    /// Get the access log format
+    pub fn access_log(&self) -> AccessLogFormat {
+        self.access_log
+    }
+
+    /// Get the response compression mode
+    pub fn compression(&self) -> CompressionMode {
+        self.compression
+    }
+
+    /// Get the smallest response body compressed, in bytes
+    pub fn compression_min_bytes(&self) -> usize {
+        self.compression_min_bytes
+    }
+
+    /// Whether `/metrics` serves request metrics in Prometheus text format
+    pub fn prometheus_metrics(&self) -> bool {
+        self.prometheus_metrics
+    }
+
+    /// Replace the response compression mode and threshold
+    pub fn with_compression(mut self, mode: CompressionMode, min_bytes: usize) -> Self {
+        self.compression = mode;
+        self.compression_min_bytes = min_bytes;
+        self
+    }
+
+    /// Replace the access log format
+    pub fn with_access_log(mut self, access_log: AccessLogFormat) -> Self {
+        self.access_log = access_log;
+        self
+    }
+
+    /// Enable or disable the Prometheus `/metrics` endpoint
+    pub fn with_prometheus_metrics(mut self, enabled: bool) -> Self {
+        self.prometheus_metrics = enabled;
+        self
+    }
+
+    /// Replace the OTLP exporter settings
+    pub fn with_otlp(mut self, otlp: OtlpConfig) -> Self {
+        self.otlp_endpoint = otlp.endpoint;
+        self.otlp_protocol = otlp.protocol;
+        self
+    }
 }
@@ -0,0 +1,64 @@
+//! OTLP config module
+//!
+//! OTLP exporter settings shared by every config source, layered with the
+//! precedence CLI flag > environment > config file
+
+use super::ConfigError;
+
+/// Accepted OTLP exporter protocols
+pub const OTLP_PROTOCOLS: &[&str] = &["grpc", "http"];
+
+/// Environment variable holding the OTLP endpoint (shared with the LB)
+pub const OTLP_ENDPOINT_ENV_KEY: &str = "LEMONADE_OTLP_ENDPOINT";
+
+/// Environment variable holding the OTLP protocol (shared with the LB)
+pub const OTLP_PROTOCOL_ENV_KEY: &str = "LEMONADE_OTLP_PROTOCOL";
+
+/// OTLP exporter settings from a single source
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OtlpConfig {
+    /// OTLP exporter endpoint
+    pub endpoint: Option<String>,
+    /// OTLP exporter protocol (one of [`OTLP_PROTOCOLS`])
+    pub protocol: Option<String>,
+}
+
+impl OtlpConfig {
+    /// Create OTLP settings
+    pub fn new(endpoint: Option<String>, protocol: Option<String>) -> Self {
+        Self { endpoint, protocol }
+    }
+
+    /// Read `LEMONADE_OTLP_ENDPOINT` / `LEMONADE_OTLP_PROTOCOL`
+    pub fn from_env() -> Self {
+        Self {
+            endpoint: std::env::var(OTLP_ENDPOINT_ENV_KEY).ok(),
+            protocol: std::env::var(OTLP_PROTOCOL_ENV_KEY).ok(),
+        }
+    }
+
+    /// Fill unset fields from a lower-precedence source
+    pub fn or(self, fallback: OtlpConfig) -> Self {
+        Self {
+            endpoint: self.endpoint.or(fallback.endpoint),
+            protocol: self.protocol.or(fallback.protocol),
+        }
+    }
+
+    /// Reject unknown protocols
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(protocol) = &self.protocol {
+            parse_otlp_protocol(protocol)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse an OTLP protocol, listing the accepted values on error
+pub fn parse_otlp_protocol(protocol: &str) -> Result<String, ConfigError> {
+    if OTLP_PROTOCOLS.contains(&protocol) {
+        Ok(protocol.to_string())
+    } else {
+        Err(ConfigError::OtlpProtocol(protocol.to_string()))
+    }
+}
@@ -1,31 +1,145 @@
 //! Worker Address module
 //!
-use serde::{Deserialize, Serialize};
+//! A worker listens on a TCP socket address or, for sidecars on the same
+//! host as the load balancer, on a Unix domain socket: `unix:/path/to.sock`,
+//! or `unix:@name` for an abstract socket (Linux only).
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::io;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 pub use error::WorkerAddressError;
 
-/// Worker address struct
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-pub struct WorkerAddress(SocketAddr);
+/// Prefix of Unix socket worker addresses
+pub const UNIX_ADDRESS_PREFIX: &str = "unix:";
+
+/// Worker address enum
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WorkerAddress {
+    /// TCP socket address
+    Tcp(SocketAddr),
+    /// Unix socket file at a filesystem path
+    Unix(PathBuf),
+    /// Abstract Unix socket name (Linux only)
+    Abstract(String),
+}
 
 impl WorkerAddress {
     /// Create a new worker address
     pub fn parse(address: &str) -> Result<Self, WorkerAddressError> {
-        let addr = address.parse::<SocketAddr>()?;
-        Ok(Self(addr))
+        let Some(rest) = address.strip_prefix(UNIX_ADDRESS_PREFIX) else {
+            return Ok(Self::Tcp(address.parse::<SocketAddr>()?));
+        };
+        match rest.strip_prefix('@') {
+            Some("") => Err(WorkerAddressError::empty(address)),
+            Some(name) => Ok(Self::Abstract(name.to_string())),
+            None if rest.is_empty() => Err(WorkerAddressError::empty(address)),
+            None => Ok(Self::Unix(PathBuf::from(rest))),
+        }
+    }
+
+    /// The TCP socket address, if the worker listens on TCP
+    pub fn tcp(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp(address) => Some(*address),
+            Self::Unix(_) | Self::Abstract(_) => None,
+        }
+    }
+
+    /// Error for a server (or platform) that cannot listen on this address
+    pub fn unsupported(&self, server: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} cannot listen on {}", server, self),
+        )
+    }
+
+    /// Listen on the Unix socket, non-blocking
+    ///
+    /// A socket file left at the path by a previous run is removed first;
+    /// any other file there makes the bind fail. TCP addresses are rejected.
+    #[cfg(unix)]
+    pub fn bind_unix(&self) -> io::Result<std::os::unix::net::UnixListener> {
+        use std::os::unix::net::UnixListener;
+        let listener = match self {
+            Self::Tcp(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not a Unix socket address", self),
+                ));
+            }
+            Self::Unix(path) => {
+                remove_stale_socket(path)?;
+                UnixListener::bind(path)?
+            }
+            Self::Abstract(name) => UnixListener::bind_addr(&abstract_addr(name)?)?,
+        };
+        listener.set_nonblocking(true)?;
+        Ok(listener)
     }
 }
 
+/// Remove a socket file left at `path`, leaving other files alone
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
+
+/// Socket address of the abstract socket `name`
+#[cfg(target_os = "linux")]
+fn abstract_addr(name: &str) -> io::Result<std::os::unix::net::SocketAddr> {
+    use std::os::linux::net::SocketAddrExt;
+    std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())
+}
+
+/// Socket address of the abstract socket `name`
+///
+/// Abstract sockets are Linux only.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn abstract_addr(name: &str) -> io::Result<std::os::unix::net::SocketAddr> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("abstract Unix socket @{} needs Linux", name),
+    ))
+}
+
 impl From<SocketAddr> for WorkerAddress {
     fn from(address: SocketAddr) -> Self {
-        Self(address)
+        Self::Tcp(address)
+    }
+}
+
+impl fmt::Display for WorkerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(address) => write!(f, "{}", address),
+            Self::Unix(path) => write!(f, "{}{}", UNIX_ADDRESS_PREFIX, path.display()),
+            Self::Abstract(name) => write!(f, "{}@{}", UNIX_ADDRESS_PREFIX, name),
+        }
     }
 }
 
-impl AsRef<SocketAddr> for WorkerAddress {
-    fn as_ref(&self) -> &SocketAddr {
-        &self.0
+impl Serialize for WorkerAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for WorkerAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let address = String::deserialize(deserializer)?;
+        WorkerAddress::parse(&address).map_err(serde::de::Error::custom)
     }
 }
 
@@ -37,6 +151,13 @@ mod error {
     #[error("invalid worker address: {0}")]
     pub struct WorkerAddressError(String);
 
+    impl WorkerAddressError {
+        /// Error for a Unix socket address without a path or name
+        pub(super) fn empty(address: &str) -> Self {
+            Self(format!("{} names no socket", address))
+        }
+    }
+
     impl From<AddrParseError> for WorkerAddressError {
         fn from(error: AddrParseError) -> Self {
             Self(error.to_string())
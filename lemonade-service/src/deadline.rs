@@ -0,0 +1,16 @@
+//! Deadline module
+//!
+//! Callers may send how long they will still wait for a response in the
+//! `X-Lemonade-Deadline-Ms` header. Workers skip work that cannot finish in
+//! time and answer `504 Gateway Timeout` instead of doing it for nobody.
+use std::time::Duration;
+
+/// Header carrying the milliseconds the caller will still wait
+pub const DEADLINE_HEADER: &str = "x-lemonade-deadline-ms";
+
+/// Parse a deadline header value
+///
+/// A missing or malformed value means no deadline.
+pub fn parse_deadline(value: Option<&str>) -> Option<Duration> {
+    value?.trim().parse::<u64>().ok().map(Duration::from_millis)
+}
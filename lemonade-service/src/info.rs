@@ -0,0 +1,29 @@
+//! Info module
+//!
+//! Build information of a worker, served on the `/info` endpoint.
+use lemonade_observability::BuildInfo;
+use serde::Serialize;
+
+/// Build information of the worker service
+pub fn build_info() -> BuildInfo {
+    BuildInfo::new(env!("CARGO_PKG_VERSION"))
+}
+
+/// Info response returned by the `/info` endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InfoResponse {
+    /// Service name
+    pub service_name: String,
+    /// Build information
+    pub build: BuildInfo,
+}
+
+impl InfoResponse {
+    /// Create an info response for `service_name`
+    pub fn new(service_name: impl Into<String>) -> Self {
+        Self {
+            service_name: service_name.into(),
+            build: build_info(),
+        }
+    }
+}
@@ -1,10 +1,26 @@
 //! Lemonade Service Library
 //!
 
+pub mod access_log;
+pub mod bootstrap;
+pub mod compression;
 pub mod config;
+pub mod deadline;
 pub mod error_response;
+pub mod info;
+pub mod prometheus;
+pub mod request_stats;
+pub mod resources;
 pub mod worker;
 
+pub use crate::bootstrap::{
+    BootstrapHandle, ObservabilityGuard, StartupBanner, bootstrap,
+};
+
+use crate::access_log::AccessLog;
+use crate::compression::ResponseCompression;
+use crate::request_stats::RequestStats;
+use crate::resources::{DEFAULT_SAMPLE_INTERVAL, ResourceSampler};
 use crate::worker::WorkerServiceImpl;
 use std::sync::Arc;
 
@@ -15,6 +31,14 @@ pub struct AppState {
     pub worker_service: Arc<WorkerServiceImpl>,
     /// Config
     pub config: Arc<crate::config::Config>,
+    /// Access log (format from the config, written to standard output)
+    pub access_log: AccessLog,
+    /// Response compression (mode and threshold from the config)
+    pub compression: ResponseCompression,
+    /// Process resource sampler behind the `/stats` endpoint
+    pub resources: Arc<ResourceSampler>,
+    /// Request stats behind the Prometheus `/metrics` endpoint
+    pub request_stats: Arc<RequestStats>,
 }
 
 impl AppState {
@@ -26,7 +50,32 @@ impl AppState {
         );
         Self {
             worker_service: Arc::new(worker_service),
+            access_log: AccessLog::new(config.access_log()),
+            compression: ResponseCompression::new(
+                config.compression(),
+                config.compression_min_bytes(),
+            ),
+            resources: Arc::new(ResourceSampler::system(DEFAULT_SAMPLE_INTERVAL)),
+            request_stats: Arc::new(RequestStats::new(config.prometheus_metrics())),
             config: Arc::new(config),
         }
     }
+
+    /// Replace the access log (e.g. with an in-memory one in tests)
+    pub fn with_access_log(mut self, access_log: AccessLog) -> Self {
+        self.access_log = access_log;
+        self
+    }
+
+    /// Replace the response compression
+    pub fn with_compression(mut self, compression: ResponseCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Replace the resource sampler (e.g. with a stubbed probe in tests)
+    pub fn with_resources(mut self, resources: ResourceSampler) -> Self {
+        self.resources = Arc::new(resources);
+        self
+    }
 }
@@ -0,0 +1,130 @@
+//! Prometheus module
+//!
+//! Renders the worker's [`RequestStats`](crate::request_stats::RequestStats)
+//! in the Prometheus text exposition format (version 0.0.4) for `/metrics`.
+//! Metric names match the ones exported over OTLP, and every series carries
+//! the `service` and `framework` labels.
+use crate::AppState;
+use crate::request_stats::RequestStatsSnapshot;
+use std::fmt::Write;
+
+/// Content type of the exposition
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Requests counter
+pub const REQUESTS_TOTAL: &str = "lemonade_http_requests_total";
+
+/// In-flight requests gauge
+pub const REQUESTS_IN_FLIGHT: &str = "lemonade_http_requests_in_flight";
+
+/// Request latency histogram
+pub const REQUEST_DURATION_SECONDS: &str = "lemonade_http_request_duration_seconds";
+
+/// `/metrics` body for a worker, or `None` when the endpoint is disabled
+pub fn scrape(state: &AppState, framework: &str) -> Option<String> {
+    if !state.config.prometheus_metrics() {
+        return None;
+    }
+    Some(render(
+        &state.request_stats.snapshot(),
+        state.config.service_name(),
+        framework,
+    ))
+}
+
+/// Render `snapshot` for the worker `service_name` running on `framework`
+pub fn render(
+    snapshot: &RequestStatsSnapshot,
+    service_name: &str,
+    framework: &str,
+) -> String {
+    let common = format!(
+        "service=\"{}\",framework=\"{}\"",
+        escape_label_value(service_name),
+        escape_label_value(framework)
+    );
+    let mut out = String::new();
+
+    header(
+        &mut out,
+        REQUESTS_TOTAL,
+        "counter",
+        "Total number of HTTP requests",
+    );
+    for (key, count) in &snapshot.requests {
+        let _ = writeln!(
+            out,
+            "{}{{{},method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+            REQUESTS_TOTAL,
+            common,
+            escape_label_value(&key.method),
+            escape_label_value(&key.route),
+            key.status,
+            count
+        );
+    }
+
+    header(
+        &mut out,
+        REQUESTS_IN_FLIGHT,
+        "gauge",
+        "HTTP requests currently being served",
+    );
+    let _ = writeln!(
+        out,
+        "{}{{{}}} {}",
+        REQUESTS_IN_FLIGHT, common, snapshot.in_flight
+    );
+
+    header(
+        &mut out,
+        REQUEST_DURATION_SECONDS,
+        "histogram",
+        "HTTP request duration in seconds",
+    );
+    for (bound, count) in &snapshot.latency_buckets {
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{},le=\"{}\"}} {}",
+            REQUEST_DURATION_SECONDS, common, bound, count
+        );
+    }
+    let _ = writeln!(
+        out,
+        "{}_bucket{{{},le=\"+Inf\"}} {}",
+        REQUEST_DURATION_SECONDS, common, snapshot.latency_count
+    );
+    let _ = writeln!(
+        out,
+        "{}_sum{{{}}} {}",
+        REQUEST_DURATION_SECONDS, common, snapshot.latency_sum_seconds
+    );
+    let _ = writeln!(
+        out,
+        "{}_count{{{}}} {}",
+        REQUEST_DURATION_SECONDS, common, snapshot.latency_count
+    );
+    out
+}
+
+/// Escape a label value: backslash, double quote and line feed are escaped
+/// as the format requires, other control characters become `_`
+pub fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c if c.is_control() => escaped.push('_'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `# HELP` and `# TYPE` lines of a metric
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
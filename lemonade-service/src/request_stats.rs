@@ -0,0 +1,152 @@
+//! Request stats module
+//!
+//! Request counters, an in-flight gauge and a latency histogram kept by the
+//! worker itself, so `/metrics` can serve them without an OTLP collector.
+//! Every framework reports requests the same way: [`RequestStats::request_started`]
+//! when one comes in, [`RequestStats::request_finished`] with its outcome.
+use lemonade_observability::cardinality::{DEFAULT_HTTP_ROUTE_LIMIT, OTHER_VALUE};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram buckets, in seconds
+pub const LATENCY_BUCKETS_SECONDS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Distinct routes counted before new ones are folded into `__other__`
+pub const MAX_ROUTES: usize = DEFAULT_HTTP_ROUTE_LIMIT;
+
+/// Labels of one request counter
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RequestKey {
+    /// Request method
+    pub method: String,
+    /// Request path, or `__other__` beyond [`MAX_ROUTES`]
+    pub route: String,
+    /// Response status code
+    pub status: u16,
+}
+
+/// Point-in-time copy of the request stats
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestStatsSnapshot {
+    /// Finished requests per method, route and status
+    pub requests: BTreeMap<RequestKey, u64>,
+    /// Requests started and not finished yet
+    pub in_flight: u64,
+    /// Cumulative request count per bucket of [`LATENCY_BUCKETS_SECONDS`]
+    pub latency_buckets: Vec<(f64, u64)>,
+    /// Total time spent serving finished requests, in seconds
+    pub latency_sum_seconds: f64,
+    /// Finished requests
+    pub latency_count: u64,
+}
+
+/// Counters updated under the lock
+#[derive(Debug, Default)]
+struct Recorded {
+    requests: BTreeMap<RequestKey, u64>,
+    /// Routes admitted as label values
+    routes: BTreeSet<String>,
+    /// Requests per bucket (not cumulative), the last one past every bound
+    buckets: [u64; LATENCY_BUCKETS_SECONDS.len() + 1],
+    sum_micros: u64,
+    count: u64,
+}
+
+/// Request stats of a worker
+///
+/// A disabled registry records nothing, so workers not serving `/metrics`
+/// pay only for the check.
+#[derive(Debug, Default)]
+pub struct RequestStats {
+    enabled: bool,
+    in_flight: AtomicU64,
+    recorded: Mutex<Recorded>,
+}
+
+impl RequestStats {
+    /// Create a registry, recording only when `enabled`
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    /// Whether requests are recorded
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// A request came in
+    pub fn request_started(&self) {
+        if self.enabled {
+            self.in_flight.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// A request started with [`Self::request_started`] was served
+    pub fn request_finished(
+        &self,
+        method: &str,
+        route: &str,
+        status: u16,
+        duration: Duration,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let _ = self
+            .in_flight
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+
+        let seconds = duration.as_secs_f64();
+        let bucket = LATENCY_BUCKETS_SECONDS
+            .iter()
+            .position(|&bound| seconds <= bound)
+            .unwrap_or(LATENCY_BUCKETS_SECONDS.len());
+
+        let mut recorded = self.recorded.lock().unwrap_or_else(|e| e.into_inner());
+        let route =
+            if recorded.routes.contains(route) || recorded.routes.len() < MAX_ROUTES {
+                recorded.routes.insert(route.to_string());
+                route
+            } else {
+                OTHER_VALUE
+            };
+        *recorded
+            .requests
+            .entry(RequestKey {
+                method: method.to_string(),
+                route: route.to_string(),
+                status,
+            })
+            .or_default() += 1;
+        recorded.buckets[bucket] += 1;
+        recorded.sum_micros += duration.as_micros() as u64;
+        recorded.count += 1;
+    }
+
+    /// Copy of the current stats
+    pub fn snapshot(&self) -> RequestStatsSnapshot {
+        let recorded = self.recorded.lock().unwrap_or_else(|e| e.into_inner());
+        let latency_buckets = LATENCY_BUCKETS_SECONDS
+            .iter()
+            .zip(&recorded.buckets)
+            .scan(0, |cumulative, (&bound, &count)| {
+                *cumulative += count;
+                Some((bound, *cumulative))
+            })
+            .collect();
+        RequestStatsSnapshot {
+            requests: recorded.requests.clone(),
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            latency_buckets,
+            latency_sum_seconds: recorded.sum_micros as f64 / 1_000_000.0,
+            latency_count: recorded.count,
+        }
+    }
+}
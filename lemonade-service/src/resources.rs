@@ -0,0 +1,198 @@
+//! Resources module
+//!
+//! Workers report their own CPU usage and resident memory on `/stats` so a
+//! load balancer can weigh actual backend pressure. Readings come from a
+//! [`ResourceProbe`]; the [`ResourceSampler`] turns CPU time deltas into a
+//! percentage and refreshes at most once per interval.
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default interval between two resource samples
+pub const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Clock ticks per second used by `/proc/self/stat` (USER_HZ)
+///
+/// Fixed at 100 by the Linux ABI on every architecture we run on.
+const CLOCK_TICKS_PER_SEC: u64 = 100;
+
+/// Source of raw process resource readings
+pub trait ResourceProbe: Send + Sync {
+    /// CPU time (user + system) consumed by the process so far
+    fn cpu_time(&self) -> Option<Duration>;
+
+    /// Resident set size in bytes
+    fn rss_bytes(&self) -> Option<u64>;
+}
+
+/// Probe reading the current process from `/proc/self` (Linux only)
+///
+/// On other platforms every reading is unavailable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcfsProbe;
+
+impl ResourceProbe for ProcfsProbe {
+    fn cpu_time(&self) -> Option<Duration> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+        let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+        let ticks = parse_stat_cpu_ticks(&stat)?;
+        Some(Duration::from_millis(ticks * 1000 / CLOCK_TICKS_PER_SEC))
+    }
+
+    fn rss_bytes(&self) -> Option<u64> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        parse_status_rss_bytes(&status)
+    }
+}
+
+/// User plus system clock ticks from the contents of `/proc/<pid>/stat`
+///
+/// The command name (field 2) may contain spaces and parentheses, so fields
+/// are counted from the last closing parenthesis.
+pub fn parse_stat_cpu_ticks(stat: &str) -> Option<u64> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    let mut fields = rest.split_whitespace();
+    // Fields after the command name start at 3 (state); utime is 14, stime 15
+    let utime = fields.nth(11)?.parse::<u64>().ok()?;
+    let stime = fields.next()?.parse::<u64>().ok()?;
+    Some(utime + stime)
+}
+
+/// Resident set size in bytes from the contents of `/proc/<pid>/status`
+pub fn parse_status_rss_bytes(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib = line
+        .trim_start_matches("VmRSS:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// One resource sample
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceSample {
+    /// CPU usage over the last interval, in percent of one core
+    /// (None until two readings were taken)
+    pub cpu_percent: Option<f64>,
+    /// Resident set size in bytes
+    pub rss_bytes: Option<u64>,
+}
+
+/// Last reading kept between samples
+#[derive(Debug, Default)]
+struct SamplerState {
+    /// When the last reading was taken
+    taken_at: Option<Instant>,
+    /// CPU time at the last reading
+    cpu_time: Option<Duration>,
+    /// Sample computed at the last reading
+    sample: ResourceSample,
+}
+
+/// Resource sampler refreshing at most once per interval
+///
+/// Sampling is lazy: a call older than the interval takes a new reading,
+/// any other call returns the cached sample.
+pub struct ResourceSampler {
+    /// Raw readings source
+    probe: Arc<dyn ResourceProbe>,
+    /// Minimum time between two readings
+    interval: Duration,
+    /// Last reading
+    state: Mutex<SamplerState>,
+}
+
+impl std::fmt::Debug for ResourceSampler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceSampler")
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResourceSampler {
+    /// Create a sampler over `probe`, taking a first reading now
+    pub fn new(probe: Arc<dyn ResourceProbe>, interval: Duration) -> Self {
+        let sampler = Self {
+            probe,
+            interval,
+            state: Mutex::new(SamplerState::default()),
+        };
+        sampler.sample_at(Instant::now());
+        sampler
+    }
+
+    /// Create a sampler over the current process
+    pub fn system(interval: Duration) -> Self {
+        Self::new(Arc::new(ProcfsProbe), interval)
+    }
+
+    /// Minimum time between two readings
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Current sample, refreshed if the last one is older than the interval
+    pub fn sample(&self) -> ResourceSample {
+        self.sample_at(Instant::now())
+    }
+
+    /// Sample as of `now` (see [`Self::sample`])
+    pub fn sample_at(&self, now: Instant) -> ResourceSample {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = state
+            .taken_at
+            .map(|taken_at| now.saturating_duration_since(taken_at));
+        if elapsed.is_some_and(|elapsed| elapsed < self.interval) {
+            return state.sample;
+        }
+
+        let cpu_time = self.probe.cpu_time();
+        let cpu_percent = match (state.cpu_time, cpu_time, elapsed) {
+            (Some(previous), Some(current), Some(elapsed)) if !elapsed.is_zero() => {
+                let used = current.saturating_sub(previous);
+                Some(used.as_secs_f64() / elapsed.as_secs_f64() * 100.0)
+            }
+            // Too close to the last reading to tell; keep the previous value
+            (Some(_), Some(_), Some(_)) => state.sample.cpu_percent,
+            _ => None,
+        };
+        state.sample = ResourceSample {
+            cpu_percent,
+            rss_bytes: self.probe.rss_bytes(),
+        };
+        state.taken_at = Some(now);
+        state.cpu_time = cpu_time;
+        state.sample
+    }
+}
+
+/// Stats response returned by the `/stats` endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsResponse {
+    /// Service name
+    pub service_name: String,
+    /// CPU usage over the last sample interval, in percent of one core
+    pub cpu_percent: Option<f64>,
+    /// Resident set size in bytes
+    pub rss_bytes: Option<u64>,
+}
+
+impl StatsResponse {
+    /// Create a stats response from a resource sample
+    pub fn new(service_name: impl Into<String>, sample: ResourceSample) -> Self {
+        Self {
+            service_name: service_name.into(),
+            cpu_percent: sample.cpu_percent,
+            rss_bytes: sample.rss_bytes,
+        }
+    }
+}
@@ -81,4 +81,22 @@ impl WorkService for WorkerServiceImpl {
             Err(WorkError::new(WORKER_SERVICE_ERROR_MESSAGE.to_owned()))
         }
     }
+
+    /// Perform a work, or skip it when it would outlast the deadline
+    async fn work_within(
+        &self,
+        deadline: Option<Duration>,
+    ) -> Result<WorkResponse, WorkError> {
+        match deadline {
+            Some(deadline) if self.validate() && self.work_delay() > deadline => {
+                tracing::debug!(
+                    work_delay_ms = self.work_delay().as_millis() as u64,
+                    deadline_ms = deadline.as_millis() as u64,
+                    "Skipping work that would outlast its deadline"
+                );
+                Err(WorkError::deadline_exceeded(self.work_delay(), deadline))
+            }
+            _ => self.work().await,
+        }
+    }
 }
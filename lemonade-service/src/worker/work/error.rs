@@ -1,15 +1,43 @@
 //! Work error module
 //!
+use std::time::Duration;
 
 /// Work error struct
 #[derive(Debug, thiserror::Error)]
-#[error("health error: {0}")]
-pub struct WorkError(String);
+#[error("health error: {message}")]
+pub struct WorkError {
+    /// Error message
+    message: String,
+    /// The work was skipped because it could not finish before the deadline
+    deadline_exceeded: bool,
+}
 
 impl WorkError {
     /// Create a new work error
     #[must_use]
     pub fn new(message: impl Into<String>) -> Self {
-        Self(message.into())
+        Self {
+            message: message.into(),
+            deadline_exceeded: false,
+        }
+    }
+
+    /// Create an error for work skipped because it would outlast the deadline
+    #[must_use]
+    pub fn deadline_exceeded(work: Duration, deadline: Duration) -> Self {
+        Self {
+            message: format!(
+                "work of {}ms exceeds deadline of {}ms",
+                work.as_millis(),
+                deadline.as_millis()
+            ),
+            deadline_exceeded: true,
+        }
+    }
+
+    /// Check whether the work was skipped for its deadline
+    #[must_use]
+    pub fn is_deadline_exceeded(&self) -> bool {
+        self.deadline_exceeded
     }
 }
@@ -2,10 +2,22 @@
 //!
 use super::{error::WorkError, models::WorkResponse};
 use async_trait::async_trait;
+use std::time::Duration;
 
 /// Worker service trait
 #[async_trait]
 pub trait WorkService: Send + Sync + 'static {
     /// Perform a work
     async fn work(&self) -> Result<WorkResponse, WorkError>;
+
+    /// Perform a work the caller will wait at most `deadline` for
+    ///
+    /// Without a deadline this is [`WorkService::work`].
+    async fn work_within(
+        &self,
+        deadline: Option<Duration>,
+    ) -> Result<WorkResponse, WorkError> {
+        let _ = deadline;
+        self.work().await
+    }
 }
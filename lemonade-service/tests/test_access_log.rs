@@ -0,0 +1,84 @@
+//! Tests for the access log module
+//!
+use lemonade_service::access_log::{AccessLog, AccessLogEntry, AccessLogFormat};
+use rstest::rstest;
+use std::time::Duration;
+
+fn entry(request_id: Option<&str>) -> AccessLogEntry {
+    AccessLogEntry::new(
+        "GET",
+        "/work",
+        200,
+        Duration::from_micros(1_500),
+        57,
+        request_id.map(String::from),
+    )
+}
+
+#[rstest]
+#[case("off", AccessLogFormat::Off)]
+#[case("json", AccessLogFormat::Json)]
+#[case("common", AccessLogFormat::Common)]
+fn access_log_format_parse_and_display(
+    #[case] name: &str,
+    #[case] format: AccessLogFormat,
+) {
+    assert_eq!(name.parse::<AccessLogFormat>().unwrap(), format);
+    assert_eq!(format.to_string(), name);
+}
+
+#[test]
+fn access_log_format_unknown_rejected() {
+    let err = "apache".parse::<AccessLogFormat>().unwrap_err();
+    assert!(err.contains("off, json, common"), "{}", err);
+}
+
+#[test]
+fn access_log_entry_render_json() {
+    let line = entry(Some("abc"))
+        .render(AccessLogFormat::Json, 1_000)
+        .unwrap();
+
+    let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "timestamp_ms": 1_000,
+            "method": "GET",
+            "path": "/work",
+            "status": 200,
+            "duration_micros": 1_500,
+            "bytes": 57,
+            "request_id": "abc",
+        })
+    );
+}
+
+#[test]
+fn access_log_entry_render_common() {
+    assert_eq!(
+        entry(None)
+            .render(AccessLogFormat::Common, 1_000)
+            .as_deref(),
+        Some("- [1000] \"GET /work\" 200 57 1500")
+    );
+    assert_eq!(entry(None).render(AccessLogFormat::Off, 1_000), None);
+}
+
+#[test]
+fn access_log_in_memory_keeps_lines_when_enabled() {
+    // Given: an enabled and a disabled in-memory access log
+    let enabled = AccessLog::in_memory(AccessLogFormat::Common);
+    let disabled = AccessLog::in_memory(AccessLogFormat::Off);
+
+    // When: recording a request on both (and through a clone)
+    enabled.clone().record(&entry(Some("abc")));
+    disabled.record(&entry(Some("abc")));
+
+    // Then: only the enabled log keeps a line
+    assert!(enabled.is_enabled());
+    assert!(!disabled.is_enabled());
+    assert_eq!(enabled.lines().len(), 1);
+    assert!(enabled.lines()[0].starts_with("abc ["));
+    assert!(disabled.lines().is_empty());
+}
@@ -0,0 +1,134 @@
+//! Tests for the response compression module
+//!
+use lemonade_service::compression::{
+    CompressionMode, CompressionStats, ContentEncoding, ResponseCompression,
+};
+use rstest::rstest;
+use std::io::Read;
+
+/// A body well over the default threshold that compresses well
+fn body() -> Vec<u8> {
+    "{\"status\":\"ok\",\"payload\":\"lemonade\"}"
+        .repeat(100)
+        .into_bytes()
+}
+
+/// Decoder of a compressed body
+type Decode = fn(&[u8]) -> Vec<u8>;
+
+fn gunzip(compressed: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(compressed)
+        .read_to_end(&mut decoded)
+        .expect("valid gzip");
+    decoded
+}
+
+fn unbrotli(compressed: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::new();
+    brotli::Decompressor::new(compressed, 4096)
+        .read_to_end(&mut decoded)
+        .expect("valid brotli");
+    decoded
+}
+
+#[rstest]
+#[case("off", CompressionMode::Off)]
+#[case("gzip", CompressionMode::Gzip)]
+#[case("auto", CompressionMode::Auto)]
+fn compression_mode_parse_and_display(#[case] name: &str, #[case] mode: CompressionMode) {
+    assert_eq!(name.parse::<CompressionMode>().unwrap(), mode);
+    assert_eq!(mode.to_string(), name);
+}
+
+#[test]
+fn compression_mode_unknown_rejected() {
+    let err = "zstd".parse::<CompressionMode>().unwrap_err();
+    assert!(err.contains("off, gzip, auto"), "{}", err);
+}
+
+#[rstest]
+#[case(CompressionMode::Off, Some("gzip, br"), None)]
+#[case(CompressionMode::Gzip, None, None)]
+#[case(CompressionMode::Gzip, Some("gzip, br"), Some(ContentEncoding::Gzip))]
+#[case(CompressionMode::Gzip, Some("br"), None)]
+#[case(CompressionMode::Gzip, Some("gzip;q=0, br"), None)]
+#[case(CompressionMode::Auto, Some("gzip, br"), Some(ContentEncoding::Brotli))]
+#[case(CompressionMode::Auto, Some("GZIP"), Some(ContentEncoding::Gzip))]
+#[case(
+    CompressionMode::Auto,
+    Some("br;q=0.5, gzip;q=0.8"),
+    Some(ContentEncoding::Gzip)
+)]
+#[case(CompressionMode::Auto, Some("identity"), None)]
+#[case(CompressionMode::Auto, Some("*"), Some(ContentEncoding::Brotli))]
+#[case(CompressionMode::Auto, Some("br;q=0, *"), Some(ContentEncoding::Gzip))]
+fn response_compression_negotiate(
+    #[case] mode: CompressionMode,
+    #[case] accept_encoding: Option<&str>,
+    #[case] expected: Option<ContentEncoding>,
+) {
+    let compression = ResponseCompression::new(mode, 16);
+    assert_eq!(compression.negotiate(accept_encoding, 64), expected);
+}
+
+#[test]
+fn response_compression_threshold() {
+    // Given: a 100-byte threshold
+    let compression = ResponseCompression::new(CompressionMode::Gzip, 100);
+
+    // Then: smaller and empty bodies are sent as is
+    assert_eq!(compression.negotiate(Some("gzip"), 99), None);
+    assert_eq!(
+        compression.negotiate(Some("gzip"), 100),
+        Some(ContentEncoding::Gzip)
+    );
+    let no_threshold = ResponseCompression::new(CompressionMode::Gzip, 0);
+    assert_eq!(no_threshold.negotiate(Some("gzip"), 0), None);
+}
+
+#[rstest]
+#[case("gzip", ContentEncoding::Gzip, gunzip as Decode)]
+#[case("br", ContentEncoding::Brotli, unbrotli as Decode)]
+fn response_compression_compress_round_trip(
+    #[case] accept_encoding: &str,
+    #[case] expected: ContentEncoding,
+    #[case] decode: Decode,
+) {
+    // Given: auto compression and a compressible body
+    let compression = ResponseCompression::new(CompressionMode::Auto, 1024);
+    let body = body();
+
+    // When: compressing it for a client accepting one encoding
+    let (encoding, compressed) = compression
+        .compress(Some(accept_encoding), &body)
+        .expect("body should be compressed");
+
+    // Then: the body shrinks and decodes back to the original
+    assert_eq!(encoding, expected);
+    assert_eq!(encoding.as_str(), accept_encoding);
+    assert!(compressed.len() < body.len());
+    assert_eq!(decode(&compressed), body);
+}
+
+#[test]
+fn response_compression_stats_count_outcomes() {
+    // Given: gzip compression shared by two handlers
+    let compression = ResponseCompression::new(CompressionMode::Gzip, 1024);
+    let handler = compression.clone();
+    let body = body();
+
+    // When: serving one client accepting gzip, one not, and one small body
+    assert!(handler.compress(Some("gzip"), &body).is_some());
+    assert!(handler.compress(None, &body).is_none());
+    assert!(compression.compress(Some("gzip"), b"{}").is_none());
+
+    // Then: every response is counted on the shared counters
+    assert_eq!(
+        compression.stats(),
+        CompressionStats {
+            compressed: 1,
+            uncompressed: 2,
+        }
+    );
+}
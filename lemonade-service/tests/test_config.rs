@@ -1,8 +1,14 @@
 //! Tests for the config module
 //!
-use lemonade_service::config::{Config, ConfigBuilder, WorkerAddress};
+use lemonade_service::access_log::AccessLogFormat;
+use lemonade_service::compression::{CompressionMode, DEFAULT_COMPRESSION_MIN_BYTES};
+use lemonade_service::config::{
+    Config, ConfigBuilder, ConfigError, ConfigLayer, OtlpConfig, WorkerAddress,
+    parse_otlp_protocol,
+};
 use proptest::prelude::*;
-use std::time::Duration;
+use rstest::rstest;
+use std::{path::PathBuf, time::Duration};
 
 mod common;
 use common::*;
@@ -61,6 +67,451 @@ fn config_builder_from_env() {
     assert!(!config.work_delay().is_zero());
 }
 
+fn otlp(endpoint: Option<&str>, protocol: Option<&str>) -> OtlpConfig {
+    OtlpConfig::new(endpoint.map(String::from), protocol.map(String::from))
+}
+
+fn otlp_layer(endpoint: Option<&str>, protocol: Option<&str>) -> ConfigLayer {
+    ConfigLayer {
+        otlp: otlp(endpoint, protocol),
+        ..ConfigLayer::default()
+    }
+}
+
+fn file_config(endpoint: Option<&str>, protocol: Option<&str>) -> Config {
+    Config::new(
+        WorkerAddress::parse("127.0.0.1:8080").unwrap(),
+        "test-service",
+        Duration::from_millis(1),
+    )
+    .with_otlp(otlp(endpoint, protocol))
+}
+
+#[rstest]
+#[case::none(None, None, None, None)]
+#[case::file_only(None, None, Some("file"), Some("file"))]
+#[case::env_only(None, Some("env"), None, Some("env"))]
+#[case::flag_only(Some("flag"), None, None, Some("flag"))]
+#[case::env_over_file(None, Some("env"), Some("file"), Some("env"))]
+#[case::flag_over_file(Some("flag"), None, Some("file"), Some("flag"))]
+#[case::flag_over_env(Some("flag"), Some("env"), None, Some("flag"))]
+#[case::flag_over_all(Some("flag"), Some("env"), Some("file"), Some("flag"))]
+fn config_builder_otlp_endpoint_precedence(
+    #[case] flag: Option<&str>,
+    #[case] env: Option<&str>,
+    #[case] file: Option<&str>,
+    #[case] expected: Option<&str>,
+) {
+    let config = ConfigBuilder::layer(
+        file_config(file, None),
+        otlp_layer(flag, None),
+        otlp_layer(env, None),
+    )
+    .expect("OTLP layering should succeed");
+
+    assert_eq!(config.otlp_endpoint(), expected);
+}
+
+#[rstest]
+#[case::none(None, None, None, None)]
+#[case::file_only(None, None, Some("grpc"), Some("grpc"))]
+#[case::env_only(None, Some("http"), None, Some("http"))]
+#[case::flag_only(Some("grpc"), None, None, Some("grpc"))]
+#[case::env_over_file(None, Some("http"), Some("grpc"), Some("http"))]
+#[case::flag_over_file(Some("http"), None, Some("grpc"), Some("http"))]
+#[case::flag_over_env(Some("grpc"), Some("http"), None, Some("grpc"))]
+#[case::flag_over_all(Some("http"), Some("grpc"), Some("grpc"), Some("http"))]
+fn config_builder_otlp_protocol_precedence(
+    #[case] flag: Option<&str>,
+    #[case] env: Option<&str>,
+    #[case] file: Option<&str>,
+    #[case] expected: Option<&str>,
+) {
+    let config = ConfigBuilder::layer(
+        file_config(None, file),
+        otlp_layer(None, flag),
+        otlp_layer(None, env),
+    )
+    .expect("OTLP layering should succeed");
+
+    assert_eq!(config.otlp_protocol(), expected);
+}
+
+#[test]
+fn config_builder_otlp_fields_layer_independently() {
+    // Endpoint from the flag, protocol from the file
+    let config = ConfigBuilder::layer(
+        file_config(Some("http://file:4317"), Some("grpc")),
+        otlp_layer(Some("http://flag:4317"), None),
+        ConfigLayer::default(),
+    )
+    .expect("OTLP layering should succeed");
+
+    assert_eq!(config.otlp_endpoint(), Some("http://flag:4317"));
+    assert_eq!(config.otlp_protocol(), Some("grpc"));
+}
+
+#[rstest]
+#[case::flag(Some("udp"), None, None)]
+#[case::env(None, Some("GRPC"), None)]
+#[case::file(None, None, Some("http/protobuf"))]
+fn config_builder_otlp_unknown_protocol_rejected(
+    #[case] flag: Option<&str>,
+    #[case] env: Option<&str>,
+    #[case] file: Option<&str>,
+) {
+    let result = ConfigBuilder::layer(
+        file_config(None, file),
+        otlp_layer(None, flag),
+        otlp_layer(None, env),
+    );
+
+    let error = result.expect_err("Unknown protocol should be rejected");
+    assert!(matches!(error, ConfigError::OtlpProtocol(_)));
+    assert!(error.to_string().contains("grpc, http"));
+}
+
+#[rstest]
+#[case("grpc")]
+#[case("http")]
+fn parse_otlp_protocol_accepts_known_values(#[case] protocol: &str) {
+    assert_eq!(parse_otlp_protocol(protocol).unwrap(), protocol);
+}
+
+#[test]
+fn config_builder_from_file_rejects_unknown_otlp_protocol() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("worker.toml");
+    std::fs::write(
+        &path,
+        "listen_address = \"127.0.0.1:8080\"\nservice_name = \"worker\"\nwork_delay = 20\notlp_protocol = \"udp\"\n",
+    )
+    .unwrap();
+
+    let result = ConfigBuilder::from_file(Some(path.clone()));
+
+    match result {
+        Err(ConfigError::InvalidFile {
+            path: error_path,
+            source,
+        }) => {
+            assert_eq!(error_path, path);
+            assert!(matches!(*source, ConfigError::OtlpProtocol(_)));
+        }
+        other => panic!("Expected InvalidFile, got {:?}", other),
+    }
+}
+
+fn write_fixture(dir: &tempfile::TempDir, name: &str, content: &str) -> PathBuf {
+    let path = dir.path().join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[rstest]
+#[case::toml(
+    "worker.toml",
+    "listen_address = \"127.0.0.1:4001\"\nservice_name = \"worker-1\"\nwork_delay_ms = 35\n"
+)]
+#[case::json(
+    "worker.json",
+    r#"{"listen_address": "127.0.0.1:4001", "service_name": "worker-1", "work_delay_ms": 35}"#
+)]
+#[case::yaml(
+    "worker.yaml",
+    "listen_address: \"127.0.0.1:4001\"\nservice_name: worker-1\nwork_delay_ms: 35\n"
+)]
+#[case::yml_work_delay(
+    "worker.yml",
+    "listen_address: \"127.0.0.1:4001\"\nservice_name: worker-1\nwork_delay: 35\n"
+)]
+fn config_builder_from_file_all_formats(#[case] name: &str, #[case] content: &str) {
+    // Given: a fixture file in the format given by its extension
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_fixture(&dir, name, content);
+
+    // When: loading the file
+    let config = ConfigBuilder::from_file(Some(path)).expect("Fixture should load");
+
+    // Then: every field comes from the file
+    assert_eq!(config.listen_address().to_string(), "127.0.0.1:4001");
+    assert_eq!(config.service_name(), "worker-1");
+    assert_eq!(config.work_delay(), Duration::from_millis(35));
+}
+
+#[rstest]
+#[case::toml("worker.toml", "listen_address = ")]
+#[case::json("worker.json", "{\"listen_address\": ")]
+#[case::yaml("worker.yaml", "listen_address: [")]
+#[case::bad_address(
+    "worker.toml",
+    "listen_address = \"not-an-address\"\nservice_name = \"w\"\nwork_delay = 1\n"
+)]
+fn config_builder_from_file_invalid_reports_path(
+    #[case] name: &str,
+    #[case] content: &str,
+) {
+    // Given: a malformed fixture file
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_fixture(&dir, name, content);
+
+    // When: loading the file
+    let error = ConfigBuilder::from_file(Some(path.clone()))
+        .expect_err("Malformed file should be rejected");
+
+    // Then: the error names the file
+    assert!(
+        matches!(&error, ConfigError::InvalidFile { path: p, .. } if *p == path),
+        "unexpected error: {:?}",
+        error
+    );
+    assert!(error.to_string().contains(&path.display().to_string()));
+}
+
+#[test]
+fn config_builder_from_file_missing_and_unsupported() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.toml");
+    let unsupported = write_fixture(&dir, "worker.ini", "service_name = w");
+
+    assert!(matches!(
+        ConfigBuilder::from_file(Some(missing)),
+        Err(ConfigError::FileNotFound(_))
+    ));
+    assert!(matches!(
+        ConfigBuilder::from_file(Some(unsupported)),
+        Err(ConfigError::UnsupportedFormat(_))
+    ));
+}
+
+#[test]
+fn config_builder_layer_env_overrides_file() {
+    // Given: a file config and an environment layer setting the name and delay
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_fixture(
+        &dir,
+        "worker.toml",
+        "listen_address = \"127.0.0.1:4001\"\nservice_name = \"file\"\nwork_delay_ms = 35\n",
+    );
+    let file = ConfigBuilder::from_file(Some(path)).unwrap();
+    let env = ConfigLayer {
+        service_name: Some("env".to_string()),
+        work_delay: Some(Duration::from_millis(50)),
+        ..ConfigLayer::default()
+    };
+    let flags = ConfigLayer {
+        work_delay: Some(Duration::from_millis(5)),
+        ..ConfigLayer::default()
+    };
+
+    // When: layering flags > env > file
+    let config = ConfigBuilder::layer(file, flags, env).unwrap();
+
+    // Then: each field comes from the highest layer that sets it
+    assert_eq!(config.listen_address().to_string(), "127.0.0.1:4001");
+    assert_eq!(config.service_name(), "env");
+    assert_eq!(config.work_delay(), Duration::from_millis(5));
+}
+
+#[test]
+fn config_builder_access_log_from_file_and_flags() {
+    // Given: a file enabling the JSON access log
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_fixture(
+        &dir,
+        "worker.toml",
+        "listen_address = \"127.0.0.1:4001\"\nservice_name = \"file\"\nwork_delay_ms = 35\naccess_log = \"json\"\n",
+    );
+    let file = ConfigBuilder::from_file(Some(path)).unwrap();
+    assert_eq!(file.access_log(), AccessLogFormat::Json);
+
+    // When: a flag selects the common format
+    let flags = ConfigLayer {
+        access_log: Some(AccessLogFormat::Common),
+        ..ConfigLayer::default()
+    };
+    let config = ConfigBuilder::layer(file, flags, ConfigLayer::default()).unwrap();
+
+    // Then: the flag wins, and configs without the field keep it off
+    assert_eq!(config.access_log(), AccessLogFormat::Common);
+    assert_eq!(
+        Config::new(
+            WorkerAddress::parse("127.0.0.1:8080").unwrap(),
+            "test-service",
+            Duration::from_millis(1),
+        )
+        .access_log(),
+        AccessLogFormat::Off
+    );
+}
+
+#[test]
+fn config_builder_compression_from_file_and_layers() {
+    // Given: a file enabling gzip with a 512-byte threshold
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_fixture(
+        &dir,
+        "worker.toml",
+        "listen_address = \"127.0.0.1:4001\"\nservice_name = \"file\"\nwork_delay_ms = 35\ncompression = \"gzip\"\ncompression_min_bytes = 512\n",
+    );
+    let file = ConfigBuilder::from_file(Some(path)).unwrap();
+    assert_eq!(file.compression(), CompressionMode::Gzip);
+    assert_eq!(file.compression_min_bytes(), 512);
+
+    // When: a flag selects auto and the environment lowers the threshold
+    let flags = ConfigLayer {
+        compression: Some(CompressionMode::Auto),
+        ..ConfigLayer::default()
+    };
+    let env = ConfigLayer {
+        compression: Some(CompressionMode::Off),
+        compression_min_bytes: Some(0),
+        ..ConfigLayer::default()
+    };
+    let config = ConfigBuilder::layer(file, flags, env).unwrap();
+
+    // Then: each field comes from the highest layer, and configs without
+    // the fields leave compression off
+    assert_eq!(config.compression(), CompressionMode::Auto);
+    assert_eq!(config.compression_min_bytes(), 0);
+    let defaults = Config::new(
+        WorkerAddress::parse("127.0.0.1:8080").unwrap(),
+        "test-service",
+        Duration::from_millis(1),
+    );
+    assert_eq!(defaults.compression(), CompressionMode::Off);
+    assert_eq!(
+        defaults.compression_min_bytes(),
+        DEFAULT_COMPRESSION_MIN_BYTES
+    );
+}
+
+#[test]
+fn config_builder_prometheus_metrics_from_file_and_layers() {
+    // Given: a file enabling the Prometheus endpoint
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_fixture(
+        &dir,
+        "worker.toml",
+        "listen_address = \"127.0.0.1:4001\"\nservice_name = \"file\"\nwork_delay_ms = 35\nprometheus_metrics = true\n",
+    );
+    let file = ConfigBuilder::from_file(Some(path)).unwrap();
+    assert!(file.prometheus_metrics());
+
+    // When: the environment disables it
+    let env = ConfigLayer {
+        prometheus_metrics: Some(false),
+        ..ConfigLayer::default()
+    };
+    let config = ConfigBuilder::layer(file.clone(), ConfigLayer::default(), env).unwrap();
+
+    // Then: the environment wins, and a flag wins over both
+    assert!(!config.prometheus_metrics());
+    let flags = ConfigLayer {
+        prometheus_metrics: Some(true),
+        ..ConfigLayer::default()
+    };
+    let env = ConfigLayer {
+        prometheus_metrics: Some(false),
+        ..ConfigLayer::default()
+    };
+    assert!(
+        ConfigBuilder::layer(file, flags, env)
+            .unwrap()
+            .prometheus_metrics()
+    );
+}
+
+#[test]
+fn config_layer_or_prefers_self() {
+    let flags = ConfigLayer {
+        listen_address: Some(WorkerAddress::parse("127.0.0.1:1").unwrap()),
+        ..ConfigLayer::default()
+    };
+    let env = ConfigLayer {
+        listen_address: Some(WorkerAddress::parse("127.0.0.1:2").unwrap()),
+        service_name: Some("env".to_string()),
+        ..ConfigLayer::default()
+    };
+
+    let merged = flags.or(env);
+
+    assert_eq!(
+        merged.listen_address,
+        Some(WorkerAddress::parse("127.0.0.1:1").unwrap())
+    );
+    assert_eq!(merged.service_name.as_deref(), Some("env"));
+    assert_eq!(merged.work_delay, None);
+}
+
+#[rstest]
+#[case("127.0.0.1:8080")]
+#[case("[::1]:8080")]
+#[case("unix:/run/lemonade/worker.sock")]
+#[case("unix:@lemonade-worker")]
+fn worker_address_parse_round_trips(#[case] address: &str) {
+    let parsed = WorkerAddress::parse(address).unwrap();
+
+    assert_eq!(parsed.to_string(), address);
+    let json = serde_json::to_string(&parsed).unwrap();
+    assert_eq!(
+        serde_json::from_str::<WorkerAddress>(&json).unwrap(),
+        parsed
+    );
+}
+
+#[rstest]
+#[case("unix:")]
+#[case("unix:@")]
+#[case("not-an-address")]
+fn worker_address_parse_rejects_invalid(#[case] address: &str) {
+    assert!(WorkerAddress::parse(address).is_err());
+}
+
+#[test]
+fn worker_address_unix_from_file() {
+    // Given: a config file with a Unix socket listen address
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_fixture(
+        &dir,
+        "worker.toml",
+        "listen_address = \"unix:/run/worker.sock\"\nservice_name = \"uds\"\nwork_delay_ms = 1\n",
+    );
+
+    // When: loading the file
+    let config = ConfigBuilder::from_file(Some(path)).unwrap();
+
+    // Then: the worker listens on the socket, not on TCP
+    assert_eq!(
+        config.listen_address(),
+        &WorkerAddress::Unix(PathBuf::from("/run/worker.sock"))
+    );
+    assert_eq!(config.listen_address().tcp(), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn worker_address_bind_unix_replaces_stale_socket() {
+    // Given: a socket file left behind by a worker that is gone
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("worker.sock");
+    let address = WorkerAddress::Unix(path.clone());
+    drop(address.bind_unix().unwrap());
+
+    // When: binding the same path again
+    let listener = address.bind_unix().unwrap();
+    std::os::unix::net::UnixStream::connect(&path).unwrap();
+
+    // Then: clients reach the new listener, and TCP addresses are refused
+    assert!(listener.accept().is_ok());
+    assert!(
+        WorkerAddress::parse("127.0.0.1:0")
+            .unwrap()
+            .bind_unix()
+            .is_err()
+    );
+}
+
 // Property-based tests
 proptest! {
     #[test]
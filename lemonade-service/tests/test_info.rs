@@ -0,0 +1,32 @@
+//! Tests for the Info module
+//!
+use lemonade_observability::build_info::{BUILD_TIMESTAMP, GIT_SHA, RUSTC_VERSION};
+use lemonade_service::info::{InfoResponse, build_info};
+
+#[test]
+fn build_info_fields_are_set_should_succeed() {
+    let info = build_info();
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert!(!info.git_sha.is_empty());
+    assert!(!info.build_timestamp.is_empty());
+    assert!(info.rustc_version.starts_with("rustc "));
+    assert!(info.features.is_empty());
+}
+
+#[test]
+fn info_response_serializes_build_info_should_succeed() {
+    let json = serde_json::to_value(InfoResponse::new("worker")).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "service_name": "worker",
+            "build": {
+                "version": env!("CARGO_PKG_VERSION"),
+                "git_sha": GIT_SHA,
+                "build_timestamp": BUILD_TIMESTAMP,
+                "rustc_version": RUSTC_VERSION,
+                "features": [],
+            },
+        })
+    );
+}
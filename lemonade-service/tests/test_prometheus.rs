@@ -0,0 +1,132 @@
+//! Tests for the request stats registry and the Prometheus renderer
+//!
+use lemonade_observability::cardinality::OTHER_VALUE;
+use lemonade_service::prometheus::{escape_label_value, render};
+use lemonade_service::request_stats::{
+    LATENCY_BUCKETS_SECONDS, MAX_ROUTES, RequestKey, RequestStats,
+};
+use rstest::rstest;
+use std::time::Duration;
+
+/// Request key for `GET route` answered with `status`
+fn get(route: &str, status: u16) -> RequestKey {
+    RequestKey {
+        method: "GET".to_string(),
+        route: route.to_string(),
+        status,
+    }
+}
+
+#[test]
+fn request_stats_records_requests_should_succeed() {
+    // Given: an enabled registry with three requests started
+    let stats = RequestStats::new(true);
+    stats.request_started();
+    stats.request_started();
+    stats.request_started();
+
+    // When: two of them finish
+    stats.request_finished("GET", "/work", 200, Duration::from_millis(20));
+    stats.request_finished("GET", "/health", 500, Duration::from_secs(20));
+
+    // Then: each is counted once, with its latency bucket
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.in_flight, 1);
+    assert_eq!(snapshot.requests.len(), 2);
+    assert_eq!(snapshot.requests[&get("/work", 200)], 1);
+    assert_eq!(snapshot.requests[&get("/health", 500)], 1);
+    assert_eq!(snapshot.latency_count, 2);
+    assert!((snapshot.latency_sum_seconds - 20.02).abs() < 1e-9);
+    assert_eq!(
+        snapshot.latency_buckets.len(),
+        LATENCY_BUCKETS_SECONDS.len()
+    );
+    // 20ms falls in the 0.025 bucket, 20s past every bound
+    for (bound, count) in snapshot.latency_buckets {
+        assert_eq!(count, u64::from(bound >= 0.025), "le={}", bound);
+    }
+}
+
+#[test]
+fn request_stats_disabled_records_nothing_should_succeed() {
+    let stats = RequestStats::new(false);
+    stats.request_started();
+    stats.request_finished("GET", "/work", 200, Duration::from_millis(1));
+
+    let snapshot = stats.snapshot();
+    assert!(!stats.is_enabled());
+    assert_eq!(snapshot.in_flight, 0);
+    assert!(snapshot.requests.is_empty());
+    assert_eq!(snapshot.latency_count, 0);
+}
+
+#[test]
+fn request_stats_folds_routes_beyond_limit_should_succeed() {
+    // Given: a registry that has seen as many routes as it keeps
+    let stats = RequestStats::new(true);
+    for i in 0..MAX_ROUTES {
+        stats.request_finished("GET", &format!("/r{}", i), 404, Duration::ZERO);
+    }
+
+    // When: new routes come in, and a known one again
+    stats.request_finished("GET", "/new-1", 404, Duration::ZERO);
+    stats.request_finished("GET", "/new-2", 404, Duration::ZERO);
+    stats.request_finished("GET", "/r0", 404, Duration::ZERO);
+
+    // Then: new routes share the other route, known ones keep theirs
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.requests[&get(OTHER_VALUE, 404)], 2);
+    assert_eq!(snapshot.requests[&get("/r0", 404)], 2);
+    assert_eq!(snapshot.requests.len(), MAX_ROUTES + 1);
+}
+
+#[rstest]
+#[case("worker", "worker")]
+#[case("a\"b", "a\\\"b")]
+#[case("a\\b", "a\\\\b")]
+#[case("a\nb", "a\\nb")]
+#[case("a\rb\tc", "a_b_c")]
+#[case("lemonade-ü", "lemonade-ü")]
+fn escape_label_value_should_succeed(#[case] value: &str, #[case] expected: &str) {
+    assert_eq!(escape_label_value(value), expected);
+}
+
+#[test]
+fn render_exposition_should_succeed() {
+    // Given: one finished request on a worker whose name needs escaping
+    let stats = RequestStats::new(true);
+    stats.request_started();
+    stats.request_finished("GET", "/work", 200, Duration::from_millis(3));
+
+    // When: rendering it
+    let body = render(&stats.snapshot(), "my \"worker\"", "axum");
+
+    // Then: every family has its help and type, and labels are escaped
+    let labels = r#"service="my \"worker\"",framework="axum""#;
+    let expected = [
+        "# HELP lemonade_http_requests_total Total number of HTTP requests".to_string(),
+        "# TYPE lemonade_http_requests_total counter".to_string(),
+        format!(
+            "lemonade_http_requests_total{{{},method=\"GET\",route=\"/work\",status=\"200\"}} 1",
+            labels
+        ),
+        "# HELP lemonade_http_requests_in_flight HTTP requests currently being served"
+            .to_string(),
+        "# TYPE lemonade_http_requests_in_flight gauge".to_string(),
+        format!("lemonade_http_requests_in_flight{{{}}} 0", labels),
+        "# HELP lemonade_http_request_duration_seconds HTTP request duration in seconds"
+            .to_string(),
+        "# TYPE lemonade_http_request_duration_seconds histogram".to_string(),
+        format!(
+            "lemonade_http_request_duration_seconds_bucket{{{},le=\"0.005\"}} 1",
+            labels
+        ),
+    ];
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines[..expected.len()], expected);
+    assert!(body.ends_with(&format!(
+        "lemonade_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 1\n\
+         lemonade_http_request_duration_seconds_sum{{{labels}}} 0.003\n\
+         lemonade_http_request_duration_seconds_count{{{labels}}} 1\n"
+    )));
+}
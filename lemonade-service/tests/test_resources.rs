@@ -0,0 +1,127 @@
+//! Tests for the Resources module
+//!
+use lemonade_service::resources::{
+    ProcfsProbe, ResourceProbe, ResourceSample, ResourceSampler, StatsResponse,
+    parse_stat_cpu_ticks, parse_status_rss_bytes,
+};
+use rstest::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Probe returning readings set by the test
+#[derive(Default)]
+struct StubProbe {
+    cpu_millis: AtomicU64,
+    rss_bytes: AtomicU64,
+}
+
+impl ResourceProbe for StubProbe {
+    fn cpu_time(&self) -> Option<Duration> {
+        Some(Duration::from_millis(
+            self.cpu_millis.load(Ordering::Relaxed),
+        ))
+    }
+
+    fn rss_bytes(&self) -> Option<u64> {
+        Some(self.rss_bytes.load(Ordering::Relaxed))
+    }
+}
+
+#[rstest]
+#[case(
+    "1234 (worker) S 1 1234 1234 0 -1 4194560 100 0 0 0 250 50 0 0 20 0",
+    300
+)]
+#[case("42 (my (odd) name) R 1 42 42 0 -1 0 0 0 0 0 7 3 0 0 20 0", 10)]
+fn parse_stat_cpu_ticks_should_succeed(#[case] stat: &str, #[case] expected: u64) {
+    assert_eq!(parse_stat_cpu_ticks(stat), Some(expected));
+}
+
+#[rstest]
+#[case("")]
+#[case("1234 worker S 1")]
+#[case("1234 (worker) S 1 2 3")]
+fn parse_stat_cpu_ticks_malformed_should_fail(#[case] stat: &str) {
+    assert_eq!(parse_stat_cpu_ticks(stat), None);
+}
+
+#[test]
+fn parse_status_rss_bytes_should_succeed() {
+    let status = "Name:\tworker\nVmPeak:\t  20000 kB\nVmRSS:\t    5120 kB\nThreads:\t4\n";
+    assert_eq!(parse_status_rss_bytes(status), Some(5120 * 1024));
+    assert_eq!(parse_status_rss_bytes("Name:\tworker\n"), None);
+}
+
+#[test]
+fn procfs_probe_reads_current_process_should_succeed() {
+    let probe = ProcfsProbe;
+    if cfg!(target_os = "linux") {
+        assert!(probe.rss_bytes().is_some_and(|rss| rss > 0));
+        assert!(probe.cpu_time().is_some());
+    } else {
+        assert_eq!(probe.rss_bytes(), None);
+        assert_eq!(probe.cpu_time(), None);
+    }
+}
+
+#[test]
+fn resource_sampler_computes_cpu_percent_should_succeed() {
+    // Given: a sampler over a stubbed probe
+    let probe = Arc::new(StubProbe::default());
+    probe.rss_bytes.store(64 * 1024 * 1024, Ordering::Relaxed);
+    let sampler = ResourceSampler::new(probe.clone(), Duration::from_secs(5));
+    let start = Instant::now();
+
+    // When: the process used 2.5s of CPU over the next 10s
+    probe.cpu_millis.store(2_500, Ordering::Relaxed);
+    let sample = sampler.sample_at(start + Duration::from_secs(10));
+
+    // Then: it reports about 25% of one core and the resident size
+    let cpu_percent = sample.cpu_percent.expect("cpu percent after two readings");
+    assert!(
+        (cpu_percent - 25.0).abs() < 1.0,
+        "cpu_percent = {}",
+        cpu_percent
+    );
+    assert_eq!(sample.rss_bytes, Some(64 * 1024 * 1024));
+}
+
+#[test]
+fn resource_sampler_caches_within_interval_should_succeed() {
+    // Given: a sample taken 10s in
+    let probe = Arc::new(StubProbe::default());
+    let sampler = ResourceSampler::new(probe.clone(), Duration::from_secs(5));
+    let start = Instant::now();
+    probe.rss_bytes.store(1_000, Ordering::Relaxed);
+    let first = sampler.sample_at(start + Duration::from_secs(10));
+
+    // When: the readings change but the interval has not elapsed
+    probe.rss_bytes.store(2_000, Ordering::Relaxed);
+    let cached = sampler.sample_at(start + Duration::from_secs(12));
+
+    // Then: the cached sample is returned until the interval elapses
+    assert_eq!(cached, first);
+    let refreshed = sampler.sample_at(start + Duration::from_secs(16));
+    assert_eq!(refreshed.rss_bytes, Some(2_000));
+}
+
+#[test]
+fn stats_response_serializes_sample_should_succeed() {
+    let response = StatsResponse::new(
+        "worker",
+        ResourceSample {
+            cpu_percent: Some(12.5),
+            rss_bytes: Some(4096),
+        },
+    );
+    let json = serde_json::to_value(&response).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "service_name": "worker",
+            "cpu_percent": 12.5,
+            "rss_bytes": 4096,
+        })
+    );
+}
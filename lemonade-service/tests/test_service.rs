@@ -109,6 +109,31 @@ async fn work_service_work_should_succeed(
     }
 }
 
+#[tokio::test]
+#[rstest]
+#[case(Duration::from_secs(10), Some(Duration::from_millis(5)), false)]
+#[case(Duration::from_millis(1), Some(Duration::from_millis(500)), true)]
+#[case(Duration::from_millis(1), None, true)]
+async fn work_service_work_within_deadline_should_succeed(
+    #[case] work_delay: Duration,
+    #[case] deadline: Option<Duration>,
+    #[case] should_work: bool,
+) {
+    let service = WorkerServiceImpl::new("test-service", work_delay);
+    let started = std::time::Instant::now();
+    let result = service.work_within(deadline).await;
+
+    if should_work {
+        assert!(result.expect("Failed to work").status());
+    } else {
+        // Short-circuits instead of sleeping through the work delay
+        let error = result.expect_err("Expected deadline error");
+        assert!(error.is_deadline_exceeded());
+        assert!(format!("{}", error).contains("exceeds deadline of 5ms"));
+        assert!(started.elapsed() < work_delay);
+    }
+}
+
 // Property-based tests using proptest
 
 proptest! {
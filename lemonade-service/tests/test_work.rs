@@ -1,5 +1,6 @@
 //! Tests for the Work module
 //!
+use lemonade_service::deadline::parse_deadline;
 use lemonade_service::worker::{WorkError, WorkResponse};
 use proptest::prelude::*;
 use rstest::*;
@@ -37,6 +38,34 @@ fn work_error_display_should_succeed(#[case] message: &str) {
     assert!(display_str.contains(message));
 }
 
+#[test]
+fn work_error_new_is_not_deadline_exceeded_should_succeed() {
+    assert!(!WorkError::new("boom").is_deadline_exceeded());
+    assert!(
+        WorkError::deadline_exceeded(
+            std::time::Duration::from_millis(20),
+            std::time::Duration::from_millis(10)
+        )
+        .is_deadline_exceeded()
+    );
+}
+
+#[rstest]
+#[case(Some("250"), Some(250))]
+#[case(Some(" 0 "), Some(0))]
+#[case(Some("soon"), None)]
+#[case(Some("-5"), None)]
+#[case(None, None)]
+fn parse_deadline_should_succeed(
+    #[case] value: Option<&str>,
+    #[case] millis: Option<u64>,
+) {
+    assert_eq!(
+        parse_deadline(value),
+        millis.map(std::time::Duration::from_millis)
+    );
+}
+
 #[rstest]
 #[case(true, "test-service", 100u64)]
 #[case(false, "my-service", 200u64)]
@@ -0,0 +1,59 @@
+//! Access log middleware
+//!
+use actix_web::{
+    Error,
+    body::{BodySize, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web,
+};
+use lemonade_service::{
+    AppState,
+    access_log::{AccessLogEntry, REQUEST_ID_HEADER},
+};
+use std::time::Instant;
+
+/// Write each request to the worker's access log and request stats
+pub async fn log_request(
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let Some(state) = request
+        .app_data::<web::Data<AppState>>()
+        .filter(|state| state.access_log.is_enabled() || state.request_stats.is_enabled())
+        .cloned()
+    else {
+        return next.call(request).await;
+    };
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let path = request.path().to_string();
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    state.request_stats.request_started();
+    let response = next.call(request).await;
+    let status = match &response {
+        Ok(response) => response.status().as_u16(),
+        Err(e) => e.as_response_error().status_code().as_u16(),
+    };
+    let elapsed = start.elapsed();
+    state
+        .request_stats
+        .request_finished(&method, &path, status, elapsed);
+    let response = response?;
+
+    if state.access_log.is_enabled() {
+        let bytes = match response.response().body().size() {
+            BodySize::Sized(bytes) => bytes,
+            BodySize::None | BodySize::Stream => 0,
+        };
+        state.access_log.record(&AccessLogEntry::new(
+            method, path, status, elapsed, bytes, request_id,
+        ));
+    }
+    Ok(response)
+}
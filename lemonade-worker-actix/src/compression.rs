@@ -0,0 +1,65 @@
+//! Response compression middleware
+//!
+use actix_web::{
+    Error,
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{CONTENT_LENGTH, HeaderName, HeaderValue},
+    middleware::Next,
+    web,
+};
+use lemonade_service::{
+    AppState,
+    compression::{ACCEPT_ENCODING_HEADER, CONTENT_ENCODING_HEADER, VARY_HEADER},
+};
+
+/// Compress responses the client accepts an encoding for
+pub async fn compress_response(
+    request: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let Some(state) = request
+        .app_data::<web::Data<AppState>>()
+        .filter(|state| state.compression.is_enabled())
+        .cloned()
+    else {
+        return Ok(next.call(request).await?.map_into_boxed_body());
+    };
+    let accept_encoding = request
+        .headers()
+        .get(ACCEPT_ENCODING_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    let response = next.call(request).await?;
+    if response.headers().contains_key(CONTENT_ENCODING_HEADER) {
+        return Ok(response.map_into_boxed_body());
+    }
+
+    let (request, response) = response.into_parts();
+    let (mut response, body) = response.into_parts();
+    let body = actix_web::body::to_bytes(body).await.map_err(|e| {
+        let e: Box<dyn std::error::Error> = e.into();
+        actix_web::error::ErrorInternalServerError(e.to_string())
+    })?;
+    let headers = response.headers_mut();
+    headers.append(
+        HeaderName::from_static(VARY_HEADER),
+        HeaderValue::from_static(ACCEPT_ENCODING_HEADER),
+    );
+    let body = match state
+        .compression
+        .compress(accept_encoding.as_deref(), &body)
+    {
+        Some((encoding, compressed)) => {
+            headers.insert(
+                HeaderName::from_static(CONTENT_ENCODING_HEADER),
+                HeaderValue::from_static(encoding.as_str()),
+            );
+            headers.remove(CONTENT_LENGTH);
+            BoxBody::new(compressed)
+        }
+        None => BoxBody::new(body),
+    };
+    Ok(ServiceResponse::new(request, response.set_body(body)))
+}
@@ -1,9 +1,13 @@
 //! Handler module
 //!
-use actix_web::{HttpResponse, Responder, web};
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
 use lemonade_service::AppState;
 use lemonade_service::{
+    deadline::{DEADLINE_HEADER, parse_deadline},
     error_response::ErrorResponse,
+    info::InfoResponse,
+    prometheus::{self, PROMETHEUS_CONTENT_TYPE},
+    resources::StatsResponse,
     worker::{HealthService, WorkService},
 };
 use std::time::Instant;
@@ -32,18 +36,32 @@ pub async fn health_handler(state: web::Data<AppState>) -> impl Responder {
 }
 
 /// Work handler
-#[instrument(skip(state), fields(framework.name = "actix-web", http.route = "/work"))]
-pub async fn work_handler(state: web::Data<AppState>) -> impl Responder {
+#[instrument(skip(state, req), fields(framework.name = "actix-web", http.route = "/work"))]
+pub async fn work_handler(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> impl Responder {
     let start = Instant::now();
     let metrics = lemonade_observability::get_http_metrics("lemonade-worker-actix");
+    let deadline = parse_deadline(
+        req.headers()
+            .get(DEADLINE_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
 
-    match state.worker_service.work().await {
+    match state.worker_service.work_within(deadline).await {
         Ok(response) => {
             let status = 200;
             let duration_micros = start.elapsed().as_micros() as u64;
             metrics.record_request("GET", "/work", status, duration_micros);
             HttpResponse::Ok().json(response)
         }
+        Err(e) if e.is_deadline_exceeded() => {
+            let status = 504;
+            let duration_micros = start.elapsed().as_micros() as u64;
+            metrics.record_request("GET", "/work", status, duration_micros);
+            HttpResponse::GatewayTimeout().json(ErrorResponse::new(format!("{}", e)))
+        }
         Err(e) => {
             let status = 500;
             let duration_micros = start.elapsed().as_micros() as u64;
@@ -52,3 +70,56 @@ pub async fn work_handler(state: web::Data<AppState>) -> impl Responder {
         }
     }
 }
+
+/// Stats handler (process CPU and resident memory)
+#[instrument(skip(state), fields(framework.name = "actix-web", http.route = "/stats"))]
+pub async fn stats_handler(state: web::Data<AppState>) -> impl Responder {
+    let start = Instant::now();
+    let metrics = lemonade_observability::get_http_metrics("lemonade-worker-actix");
+
+    let response = StatsResponse::new(
+        state.worker_service.service_name(),
+        state.resources.sample(),
+    );
+
+    let duration_micros = start.elapsed().as_micros() as u64;
+    metrics.record_request("GET", "/stats", 200, duration_micros);
+    HttpResponse::Ok().json(response)
+}
+
+/// Info handler (build information)
+#[instrument(skip(state), fields(framework.name = "actix-web", http.route = "/info"))]
+pub async fn info_handler(state: web::Data<AppState>) -> impl Responder {
+    let start = Instant::now();
+    let metrics = lemonade_observability::get_http_metrics("lemonade-worker-actix");
+
+    let response = InfoResponse::new(state.worker_service.service_name());
+
+    let duration_micros = start.elapsed().as_micros() as u64;
+    metrics.record_request("GET", "/info", 200, duration_micros);
+    HttpResponse::Ok().json(response)
+}
+
+/// Prometheus metrics handler (404 unless `prometheus_metrics` is enabled)
+#[instrument(skip(state), fields(framework.name = "actix-web", http.route = "/metrics"))]
+pub async fn metrics_handler(state: web::Data<AppState>) -> impl Responder {
+    let start = Instant::now();
+    let metrics = lemonade_observability::get_http_metrics("lemonade-worker-actix");
+
+    let (status, response) = match prometheus::scrape(&state, "actix-web") {
+        Some(body) => (
+            200,
+            HttpResponse::Ok()
+                .content_type(PROMETHEUS_CONTENT_TYPE)
+                .body(body),
+        ),
+        None => (
+            404,
+            HttpResponse::NotFound().json(ErrorResponse::new("Not Found")),
+        ),
+    };
+
+    let duration_micros = start.elapsed().as_micros() as u64;
+    metrics.record_request("GET", "/metrics", status, duration_micros);
+    response
+}
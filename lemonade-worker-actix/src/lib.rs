@@ -1,51 +1,67 @@
 //! Lemonade worker Actix
 //!
+mod access_log;
+mod compression;
 mod handler;
 
-use actix_web::{App, HttpServer, web};
+use actix_web::{App, HttpServer, middleware::from_fn, web};
 use actix_web_opentelemetry::RequestTracing;
-use handler::{health_handler, work_handler};
-use lemonade_service::{AppState, config::Config};
+use handler::{
+    health_handler, info_handler, metrics_handler, stats_handler, work_handler,
+};
+use lemonade_service::{
+    AppState, BootstrapHandle,
+    config::{Config, WorkerAddress},
+};
+
+/// Service name of the Actix worker
+pub const SERVICE_NAME: &str = "lemonade-worker-actix";
+
+/// Initialize tracing and metrics and build the worker state
+pub fn bootstrap(config: Config) -> Result<BootstrapHandle, Box<dyn std::error::Error>> {
+    lemonade_service::bootstrap(config, SERVICE_NAME)
+}
 
 /// Run the Actix worker server
 pub async fn run(config: Config) -> Result<(), Box<dyn std::error::Error>> {
-    // Log OTLP config for debugging
-    eprintln!(
-        "[Worker-Actix] OTLP config: endpoint={:?}, protocol={:?}",
-        config.otlp_endpoint(),
-        config.otlp_protocol()
-    );
-    // Initialize tracing with service name from config and worker package version
-    lemonade_observability::init_tracing(
-        "lemonade-worker-actix",
-        env!("CARGO_PKG_VERSION"),
-        config.service_name(),
-        config.otlp_endpoint(),
-        config.otlp_protocol(),
-    )?;
-
-    // Initialize metrics
-    lemonade_observability::init_metrics(
-        "lemonade-worker-actix",
-        env!("CARGO_PKG_VERSION"),
-        config.service_name(),
-        config.otlp_endpoint(),
-        config.otlp_protocol(),
-    )?;
+    let BootstrapHandle {
+        state,
+        observability: _observability,
+        ..
+    } = bootstrap(config)?;
+    serve(state).await
+}
 
-    let state = AppState::new(config);
-    let listen_addr = *state.config.listen_address().as_ref();
+/// Serve the worker routes on the configured listen address
+///
+/// Unlike [`run`], tracing and metrics are not initialized.
+pub async fn serve(state: AppState) -> Result<(), Box<dyn std::error::Error>> {
+    let address = state.config.listen_address().clone();
 
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(state.clone()))
+            .wrap(from_fn(compression::compress_response))
+            .wrap(from_fn(access_log::log_request))
             .wrap(RequestTracing::new())
             .route("/health", web::get().to(health_handler))
             .route("/work", web::get().to(work_handler))
-    })
-    .bind(listen_addr)?
-    .run()
-    .await?;
+            .route("/stats", web::get().to(stats_handler))
+            .route("/info", web::get().to(info_handler))
+            .route("/metrics", web::get().to(metrics_handler))
+    });
+    let server = match &address {
+        WorkerAddress::Tcp(addr) => server.bind(addr)?,
+        #[cfg(unix)]
+        WorkerAddress::Unix(_) | WorkerAddress::Abstract(_) => {
+            server.listen_uds(address.bind_unix()?)?
+        }
+        #[cfg(not(unix))]
+        WorkerAddress::Unix(_) | WorkerAddress::Abstract(_) => {
+            return Err(address.unsupported("Actix worker").into());
+        }
+    };
+    server.run().await?;
 
     Ok(())
 }
@@ -0,0 +1,52 @@
+//! Access log middleware
+//!
+use axum::{
+    body::HttpBody,
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use lemonade_service::{
+    AppState,
+    access_log::{AccessLogEntry, REQUEST_ID_HEADER},
+};
+use std::time::Instant;
+
+/// Write each request to the worker's access log and request stats
+pub async fn log_request(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.access_log.is_enabled() && !state.request_stats.is_enabled() {
+        return next.run(request).await;
+    }
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    state.request_stats.request_started();
+    let response = next.run(request).await;
+    let status = response.status().as_u16();
+    let elapsed = start.elapsed();
+    state
+        .request_stats
+        .request_finished(&method, &path, status, elapsed);
+
+    if state.access_log.is_enabled() {
+        state.access_log.record(&AccessLogEntry::new(
+            method,
+            path,
+            status,
+            elapsed,
+            response.body().size_hint().exact().unwrap_or(0),
+            request_id,
+        ));
+    }
+    response
+}
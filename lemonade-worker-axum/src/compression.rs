@@ -0,0 +1,61 @@
+//! Response compression middleware
+//!
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header::CONTENT_LENGTH},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use lemonade_service::{
+    AppState,
+    compression::{ACCEPT_ENCODING_HEADER, CONTENT_ENCODING_HEADER, VARY_HEADER},
+};
+
+/// Compress responses the client accepts an encoding for
+pub async fn compress_response(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.compression.is_enabled() {
+        return next.run(request).await;
+    }
+    let accept_encoding = request
+        .headers()
+        .get(ACCEPT_ENCODING_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    let response = next.run(request).await;
+    if response.headers().contains_key(CONTENT_ENCODING_HEADER) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::warn!("Failed to buffer response for compression: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    parts.headers.append(
+        VARY_HEADER,
+        HeaderValue::from_static(ACCEPT_ENCODING_HEADER),
+    );
+    match state
+        .compression
+        .compress(accept_encoding.as_deref(), &body)
+    {
+        Some((encoding, compressed)) => {
+            parts.headers.insert(
+                CONTENT_ENCODING_HEADER,
+                HeaderValue::from_static(encoding.as_str()),
+            );
+            parts.headers.remove(CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(compressed))
+        }
+        None => Response::from_parts(parts, Body::from(body)),
+    }
+}
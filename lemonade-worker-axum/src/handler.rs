@@ -1,9 +1,17 @@
 //! Handler module
 //!
-use axum::{extract::State, http::StatusCode, response::Json};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json, Response},
+};
 use lemonade_service::AppState;
 use lemonade_service::{
+    deadline::{DEADLINE_HEADER, parse_deadline},
     error_response::ErrorResponse,
+    info::InfoResponse,
+    prometheus::{self, PROMETHEUS_CONTENT_TYPE},
+    resources::StatsResponse,
     worker::{HealthResponse, HealthService, WorkResponse, WorkService},
 };
 use std::time::Instant;
@@ -35,22 +43,94 @@ pub async fn health_handler(State(state): State<AppState>) -> HealthHandlerResul
 }
 
 /// Work handler
-#[instrument(skip(state), fields(framework.name = "axum", http.route = "/work"))]
-pub async fn work_handler(State(state): State<AppState>) -> WorkHandlerResult {
+#[instrument(skip(state, headers), fields(framework.name = "axum", http.route = "/work"))]
+pub async fn work_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WorkHandlerResult {
     let start = Instant::now();
     let metrics = lemonade_observability::get_http_metrics("lemonade-worker-axum");
+    let deadline = parse_deadline(
+        headers
+            .get(DEADLINE_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
 
-    let result = match state.worker_service.work().await {
+    let result = match state.worker_service.work_within(deadline).await {
         Ok(response) => Ok(Json(response)),
+        Err(e) if e.is_deadline_exceeded() => Err((
+            StatusCode::GATEWAY_TIMEOUT,
+            Json(ErrorResponse::new(format!("{}", e))),
+        )),
         Err(e) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse::new(format!("{}", e))),
         )),
     };
 
-    let status = result.as_ref().map(|_| 200).unwrap_or(500);
+    let status = match &result {
+        Ok(_) => 200,
+        Err((code, _)) => code.as_u16(),
+    };
     let duration_micros = start.elapsed().as_micros() as u64;
     metrics.record_request("GET", "/work", status, duration_micros);
 
     result
 }
+
+/// Stats handler (process CPU and resident memory)
+#[instrument(skip(state), fields(framework.name = "axum", http.route = "/stats"))]
+pub async fn stats_handler(State(state): State<AppState>) -> Json<StatsResponse> {
+    let start = Instant::now();
+    let metrics = lemonade_observability::get_http_metrics("lemonade-worker-axum");
+
+    let response = StatsResponse::new(
+        state.worker_service.service_name(),
+        state.resources.sample(),
+    );
+
+    let duration_micros = start.elapsed().as_micros() as u64;
+    metrics.record_request("GET", "/stats", 200, duration_micros);
+
+    Json(response)
+}
+
+/// Info handler (build information)
+#[instrument(skip(state), fields(framework.name = "axum", http.route = "/info"))]
+pub async fn info_handler(State(state): State<AppState>) -> Json<InfoResponse> {
+    let start = Instant::now();
+    let metrics = lemonade_observability::get_http_metrics("lemonade-worker-axum");
+
+    let response = InfoResponse::new(state.worker_service.service_name());
+
+    let duration_micros = start.elapsed().as_micros() as u64;
+    metrics.record_request("GET", "/info", 200, duration_micros);
+
+    Json(response)
+}
+
+/// Prometheus metrics handler (404 unless `prometheus_metrics` is enabled)
+#[instrument(skip(state), fields(framework.name = "axum", http.route = "/metrics"))]
+pub async fn metrics_handler(State(state): State<AppState>) -> Response {
+    let start = Instant::now();
+    let metrics = lemonade_observability::get_http_metrics("lemonade-worker-axum");
+
+    let response = match prometheus::scrape(&state, "axum") {
+        Some(body) => {
+            ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], body).into_response()
+        }
+        None => {
+            (StatusCode::NOT_FOUND, Json(ErrorResponse::new("Not Found"))).into_response()
+        }
+    };
+
+    let duration_micros = start.elapsed().as_micros() as u64;
+    metrics.record_request(
+        "GET",
+        "/metrics",
+        response.status().as_u16(),
+        duration_micros,
+    );
+
+    response
+}
@@ -1,41 +1,59 @@
 //! Lemonade worker Axum
 //!
+mod access_log;
+mod compression;
 mod handler;
 mod router;
 
-use lemonade_service::{AppState, config::Config};
-use router::create_router;
+use lemonade_service::{
+    AppState, BootstrapHandle,
+    config::{Config, WorkerAddress},
+};
 use tokio::net::TcpListener;
 
+pub use router::create_router;
+
+/// Service name of the Axum worker
+pub const SERVICE_NAME: &str = "lemonade-worker-axum";
+
+/// Initialize tracing and metrics and build the worker state
+pub fn bootstrap(config: Config) -> Result<BootstrapHandle, Box<dyn std::error::Error>> {
+    lemonade_service::bootstrap(config, SERVICE_NAME)
+}
+
 /// Run the Axum worker server
 pub async fn run(config: Config) -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing with service name from config and worker package version
-    lemonade_observability::init_tracing(
-        "lemonade-worker-axum",
-        env!("CARGO_PKG_VERSION"),
-        config.service_name(),
-        config.otlp_endpoint(),
-        config.otlp_protocol(),
-    )?;
-
-    // Initialize metrics
-    lemonade_observability::init_metrics(
-        "lemonade-worker-axum",
-        env!("CARGO_PKG_VERSION"),
-        config.service_name(),
-        config.otlp_endpoint(),
-        config.otlp_protocol(),
-    )?;
+    let BootstrapHandle {
+        state,
+        observability: _observability,
+        ..
+    } = bootstrap(config)?;
+    serve(state).await
+}
 
-    let state = AppState::new(config);
+/// Serve the worker routes on the configured listen address
+///
+/// Unlike [`run`], tracing and metrics are not initialized.
+pub async fn serve(state: AppState) -> Result<(), Box<dyn std::error::Error>> {
     let app = create_router(state.clone());
+    let address = state.config.listen_address();
 
-    let listener = TcpListener::bind(state.config.listen_address().as_ref()).await?;
-    println!(
-        "Axum worker listening on {}",
-        state.config.listen_address().as_ref()
-    );
-
-    axum::serve(listener, app).await?;
+    match address {
+        WorkerAddress::Tcp(addr) => {
+            let listener = TcpListener::bind(addr).await?;
+            println!("Axum worker listening on {}", address);
+            axum::serve(listener, app).await?;
+        }
+        #[cfg(unix)]
+        WorkerAddress::Unix(_) | WorkerAddress::Abstract(_) => {
+            let listener = tokio::net::UnixListener::from_std(address.bind_unix()?)?;
+            println!("Axum worker listening on {}", address);
+            axum::serve(listener, app).await?;
+        }
+        #[cfg(not(unix))]
+        WorkerAddress::Unix(_) | WorkerAddress::Abstract(_) => {
+            return Err(address.unsupported("Axum worker").into());
+        }
+    }
     Ok(())
 }
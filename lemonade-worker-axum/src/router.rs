@@ -1,7 +1,7 @@
 //! Router module
 //!
-use crate::handler;
-use axum::{Router, routing::get};
+use crate::{access_log, compression, handler};
+use axum::{Router, middleware, routing::get};
 use lemonade_service::AppState;
 use tower_http::classify::ServerErrorsFailureClass;
 use tower_http::trace::TraceLayer;
@@ -12,6 +12,13 @@ pub fn create_router(state: AppState) -> Router {
     Router::new()
         .route("/health", get(handler::health_handler))
         .route("/work", get(handler::work_handler))
+        .route("/stats", get(handler::stats_handler))
+        .route("/info", get(handler::info_handler))
+        .route("/metrics", get(handler::metrics_handler))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            compression::compress_response,
+        ))
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(|request: &axum::http::Request<_>| {
@@ -40,5 +47,9 @@ pub fn create_router(state: AppState) -> Router {
                     tracing::event!(Level::ERROR, "request failed");
                 }),
         )
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            access_log::log_request,
+        ))
         .with_state(state)
 }
@@ -0,0 +1,48 @@
+//! Response compression module
+//!
+//! Hyper has no compression middleware: responses are buffered and
+//! compressed through the shared [`ResponseCompression`], as the other
+//! workers do.
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    Response,
+    body::Bytes,
+    header::{CONTENT_LENGTH, HeaderValue},
+};
+use lemonade_service::compression::{
+    ACCEPT_ENCODING_HEADER, CONTENT_ENCODING_HEADER, ResponseCompression, VARY_HEADER,
+};
+
+/// Compress a response if the client accepts an encoding for it
+pub async fn compress_response(
+    response: Response<Full<Bytes>>,
+    accept_encoding: Option<&str>,
+    compression: &ResponseCompression,
+) -> Response<Full<Bytes>> {
+    if !compression.is_enabled()
+        || response.headers().contains_key(CONTENT_ENCODING_HEADER)
+    {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match body.collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(never) => match never {},
+    };
+    parts.headers.append(
+        VARY_HEADER,
+        HeaderValue::from_static(ACCEPT_ENCODING_HEADER),
+    );
+    match compression.compress(accept_encoding, &body) {
+        Some((encoding, compressed)) => {
+            parts.headers.insert(
+                CONTENT_ENCODING_HEADER,
+                HeaderValue::from_static(encoding.as_str()),
+            );
+            parts.headers.remove(CONTENT_LENGTH);
+            Response::from_parts(parts, Full::new(Bytes::from(compressed)))
+        }
+        None => Response::from_parts(parts, Full::new(body)),
+    }
+}
@@ -1,10 +1,20 @@
 //! Handler module
 //!
+use crate::compression::compress_response;
 use http_body_util::Full;
-use hyper::{Request, Response, StatusCode, body::Bytes};
+use hyper::{
+    Request, Response, StatusCode,
+    body::{Body, Bytes},
+};
 use lemonade_service::AppState;
 use lemonade_service::{
+    access_log::{AccessLogEntry, REQUEST_ID_HEADER},
+    compression::ACCEPT_ENCODING_HEADER,
+    deadline::{DEADLINE_HEADER, parse_deadline},
     error_response::ErrorResponse,
+    info::InfoResponse,
+    prometheus::{self, PROMETHEUS_CONTENT_TYPE},
+    resources::StatsResponse,
     worker::{HealthService, WorkService},
 };
 use opentelemetry::global;
@@ -29,6 +39,16 @@ pub async fn handle_request(
     let method = req.method().clone();
     let method_str = method.to_string();
     let path = req.uri().path().to_string();
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+    let accept_encoding = req
+        .headers()
+        .get(ACCEPT_ENCODING_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
 
     let span = tracing::span!(
         tracing::Level::INFO,
@@ -41,14 +61,46 @@ pub async fn handle_request(
     );
 
     // Execute handler within the span context
-    let result = handle_request_inner(req, state, path.clone())
+    let access_log = state.access_log.clone();
+    let compression = state.compression.clone();
+    let request_stats = state.request_stats.clone();
+    request_stats.request_started();
+    let result = match handle_request_inner(req, state, path.clone())
         .instrument(span)
-        .await;
+        .await
+    {
+        Ok(response) => {
+            Ok(
+                compress_response(response, accept_encoding.as_deref(), &compression)
+                    .await,
+            )
+        }
+        Err(never) => match never {},
+    };
 
     // Record metrics
     let status_code = result.as_ref().map(|r| r.status().as_u16()).unwrap_or(500);
-    let duration_micros = start.elapsed().as_micros() as u64;
+    let elapsed = start.elapsed();
+    let duration_micros = elapsed.as_micros() as u64;
     metrics.record_request(&method_str, &path, status_code, duration_micros);
+    request_stats.request_finished(&method_str, &path, status_code, elapsed);
+
+    // Write the access log
+    if access_log.is_enabled() {
+        let bytes = result
+            .as_ref()
+            .ok()
+            .and_then(|r| r.body().size_hint().exact())
+            .unwrap_or(0);
+        access_log.record(&AccessLogEntry::new(
+            method_str,
+            path,
+            status_code,
+            elapsed,
+            bytes,
+            request_id,
+        ));
+    }
 
     result
 }
@@ -59,6 +111,11 @@ async fn handle_request_inner(
     state: AppState,
     path: String,
 ) -> Result<Response<Full<Bytes>>, Infallible> {
+    let deadline = parse_deadline(
+        req.headers()
+            .get(DEADLINE_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
     let response = match path.as_str() {
         "/health" => match state.worker_service.health_check().await {
             Ok(response) => {
@@ -83,7 +140,7 @@ async fn handle_request_inner(
                 resp
             }
         },
-        "/work" => match state.worker_service.work().await {
+        "/work" => match state.worker_service.work_within(deadline).await {
             Ok(response) => {
                 let json = serde_json::to_string(&response).unwrap_or_default();
                 let resp = Response::builder()
@@ -95,14 +152,66 @@ async fn handle_request_inner(
                 resp
             }
             Err(e) => {
+                let status = if e.is_deadline_exceeded() {
+                    StatusCode::GATEWAY_TIMEOUT
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                };
                 let error = ErrorResponse::new(format!("{}", e));
                 let json = serde_json::to_string(&error).unwrap_or_default();
                 let resp = Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .status(status)
                     .header("Content-Type", "application/json")
                     .body(Full::new(Bytes::from(json)))
                     .unwrap();
-                tracing::Span::current().record("http.status_code", 500);
+                tracing::Span::current().record("http.status_code", status.as_u16());
+                resp
+            }
+        },
+        "/stats" => {
+            let stats = StatsResponse::new(
+                state.worker_service.service_name(),
+                state.resources.sample(),
+            );
+            let json = serde_json::to_string(&stats).unwrap_or_default();
+            let resp = Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(Bytes::from(json)))
+                .unwrap();
+            tracing::Span::current().record("http.status_code", 200);
+            resp
+        }
+        "/info" => {
+            let info = InfoResponse::new(state.worker_service.service_name());
+            let json = serde_json::to_string(&info).unwrap_or_default();
+            let resp = Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(Full::new(Bytes::from(json)))
+                .unwrap();
+            tracing::Span::current().record("http.status_code", 200);
+            resp
+        }
+        "/metrics" => match prometheus::scrape(&state, "hyper") {
+            Some(body) => {
+                let resp = Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", PROMETHEUS_CONTENT_TYPE)
+                    .body(Full::new(Bytes::from(body)))
+                    .unwrap();
+                tracing::Span::current().record("http.status_code", 200);
+                resp
+            }
+            None => {
+                let error = ErrorResponse::new("Not Found");
+                let json = serde_json::to_string(&error).unwrap_or_default();
+                let resp = Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .header("Content-Type", "application/json")
+                    .body(Full::new(Bytes::from(json)))
+                    .unwrap();
+                tracing::Span::current().record("http.status_code", 404);
                 resp
             }
         },
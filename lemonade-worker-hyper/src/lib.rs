@@ -1,55 +1,81 @@
 //! Lemonade worker Hyper
 //!
+mod compression;
 mod handler;
 
 use handler::handle_request;
 use hyper::{server::conn::http1::Builder, service::service_fn};
 use hyper_util::rt::TokioIo;
-use lemonade_service::{AppState, config::Config};
+use lemonade_service::{
+    AppState, BootstrapHandle,
+    config::{Config, WorkerAddress},
+};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpListener;
 
+/// Service name of the Hyper worker
+pub const SERVICE_NAME: &str = "lemonade-worker-hyper";
+
+/// Initialize tracing and metrics and build the worker state
+pub fn bootstrap(config: Config) -> Result<BootstrapHandle, Box<dyn std::error::Error>> {
+    lemonade_service::bootstrap(config, SERVICE_NAME)
+}
+
 /// Run the Hyper worker server
 pub async fn run(config: Config) -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing with service name from config and worker package version
-    lemonade_observability::init_tracing(
-        "lemonade-worker-hyper",
-        env!("CARGO_PKG_VERSION"),
-        config.service_name(),
-        config.otlp_endpoint(),
-        config.otlp_protocol(),
-    )?;
-
-    // Initialize metrics
-    lemonade_observability::init_metrics(
-        "lemonade-worker-hyper",
-        env!("CARGO_PKG_VERSION"),
-        config.service_name(),
-        config.otlp_endpoint(),
-        config.otlp_protocol(),
-    )?;
-
-    let state = AppState::new(config);
-
-    let listener = TcpListener::bind(state.config.listen_address().as_ref()).await?;
-    println!(
-        "Hyper worker listening on {}",
-        state.config.listen_address().as_ref()
-    );
-
-    loop {
-        let (stream, _) = listener.accept().await?;
-        let state = state.clone();
-
-        tokio::task::spawn(async move {
-            let io = TokioIo::new(stream);
-            let service = service_fn(move |req| {
-                let state = state.clone();
-                handle_request(req, state)
-            });
-
-            if let Err(err) = Builder::new().serve_connection(io, service).await {
-                eprintln!("Error serving connection: {:?}", err);
+    let BootstrapHandle {
+        state,
+        observability: _observability,
+        ..
+    } = bootstrap(config)?;
+    serve(state).await
+}
+
+/// Serve the worker routes on the configured listen address
+///
+/// Unlike [`run`], tracing and metrics are not initialized.
+pub async fn serve(state: AppState) -> Result<(), Box<dyn std::error::Error>> {
+    let address = state.config.listen_address().clone();
+
+    match &address {
+        WorkerAddress::Tcp(addr) => {
+            let listener = TcpListener::bind(addr).await?;
+            println!("Hyper worker listening on {}", address);
+            loop {
+                let (stream, _) = listener.accept().await?;
+                serve_connection(stream, state.clone());
             }
-        });
+        }
+        #[cfg(unix)]
+        WorkerAddress::Unix(_) | WorkerAddress::Abstract(_) => {
+            let listener = tokio::net::UnixListener::from_std(address.bind_unix()?)?;
+            println!("Hyper worker listening on {}", address);
+            loop {
+                let (stream, _) = listener.accept().await?;
+                serve_connection(stream, state.clone());
+            }
+        }
+        #[cfg(not(unix))]
+        WorkerAddress::Unix(_) | WorkerAddress::Abstract(_) => {
+            Err(address.unsupported("Hyper worker").into())
+        }
     }
 }
+
+/// Serve HTTP/1 requests on an accepted connection, on its own task
+fn serve_connection<S>(stream: S, state: AppState)
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    tokio::task::spawn(async move {
+        let io = TokioIo::new(stream);
+        let service = service_fn(move |req| {
+            let state = state.clone();
+            handle_request(req, state)
+        });
+
+        if let Err(err) = Builder::new().serve_connection(io, service).await {
+            eprintln!("Error serving connection: {:?}", err);
+        }
+    });
+}
@@ -0,0 +1,61 @@
+//! Response compression fairing for Rocket
+//!
+use lemonade_service::{
+    AppState,
+    compression::{ACCEPT_ENCODING_HEADER, CONTENT_ENCODING_HEADER, VARY_HEADER},
+};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Request, Response};
+use std::io::Cursor;
+
+/// Compression fairing that compresses responses the client accepts an
+/// encoding for
+///
+/// Attached before the tracing fairing, so the access log sees the size
+/// of the body actually sent.
+pub struct CompressionFairing;
+
+#[rocket::async_trait]
+impl Fairing for CompressionFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Compression Fairing",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(
+        &self,
+        request: &'r Request<'_>,
+        response: &mut Response<'r>,
+    ) {
+        let Some(state) = request
+            .rocket()
+            .state::<AppState>()
+            .filter(|state| state.compression.is_enabled())
+        else {
+            return;
+        };
+        if response.headers().contains(CONTENT_ENCODING_HEADER) {
+            return;
+        }
+
+        let body = match response.body_mut().to_bytes().await {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!("Failed to buffer response for compression: {}", e);
+                return;
+            }
+        };
+        response.adjoin_raw_header(VARY_HEADER, ACCEPT_ENCODING_HEADER);
+        let accept_encoding = request.headers().get_one(ACCEPT_ENCODING_HEADER);
+        let body = match state.compression.compress(accept_encoding, &body) {
+            Some((encoding, compressed)) => {
+                response.set_raw_header(CONTENT_ENCODING_HEADER, encoding.as_str());
+                compressed
+            }
+            None => body,
+        };
+        response.set_sized_body(body.len(), Cursor::new(body));
+    }
+}
@@ -1,9 +1,17 @@
 //! Tracing fairing for Rocket
 //!
+use lemonade_service::{
+    AppState,
+    access_log::{AccessLogEntry, REQUEST_ID_HEADER},
+};
 use opentelemetry::global;
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::{Request, Response};
 use std::collections::HashMap;
+use std::time::Instant;
+
+/// When the request reached the fairing (request-local cache)
+struct RequestStart(Instant);
 
 /// Helper to extract headers from Rocket request
 struct RocketHeaderExtractor<'r> {
@@ -34,7 +42,8 @@ impl<'r> opentelemetry::propagation::Extractor for RocketHeaderExtractor<'r> {
     }
 }
 
-/// Tracing fairing that creates spans for HTTP requests
+/// Tracing fairing that creates spans for HTTP requests, records the
+/// request stats and writes the access log
 pub struct TracingFairing;
 
 #[rocket::async_trait]
@@ -47,6 +56,11 @@ impl Fairing for TracingFairing {
     }
 
     async fn on_request(&self, request: &mut Request<'_>, _: &mut rocket::Data<'_>) {
+        request.local_cache(|| RequestStart(Instant::now()));
+        if let Some(state) = request.rocket().state::<AppState>() {
+            state.request_stats.request_started();
+        }
+
         // Extract trace context from headers for distributed tracing
         let extractor = RocketHeaderExtractor::new(request);
         let _parent_cx = global::get_text_map_propagator(|prop| prop.extract(&extractor));
@@ -69,11 +83,39 @@ impl Fairing for TracingFairing {
 
     async fn on_response<'r>(
         &self,
-        _request: &'r Request<'_>,
+        request: &'r Request<'_>,
         response: &mut Response<'r>,
     ) {
         // Record status code in the span if it exists
         let status_code = response.status().code;
         tracing::Span::current().record("http.status_code", status_code);
+
+        // Record the request stats and write the access log
+        let Some(state) = request.rocket().state::<AppState>() else {
+            return;
+        };
+        let start = request.local_cache(|| RequestStart(Instant::now()));
+        let elapsed = start.0.elapsed();
+        state.request_stats.request_finished(
+            request.method().as_str(),
+            request.uri().path().as_str(),
+            status_code,
+            elapsed,
+        );
+        if !state.access_log.is_enabled() {
+            return;
+        }
+        let bytes = response.body_mut().size().await.unwrap_or(0);
+        state.access_log.record(&AccessLogEntry::new(
+            request.method().as_str(),
+            request.uri().path().as_str(),
+            status_code,
+            elapsed,
+            bytes as u64,
+            request
+                .headers()
+                .get_one(REQUEST_ID_HEADER)
+                .map(String::from),
+        ));
     }
 }
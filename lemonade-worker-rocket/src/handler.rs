@@ -2,17 +2,38 @@
 //!
 use lemonade_service::{
     AppState,
+    deadline::{DEADLINE_HEADER, parse_deadline},
     error_response::ErrorResponse,
+    info::InfoResponse,
+    prometheus::{self, PROMETHEUS_CONTENT_TYPE},
+    resources::StatsResponse,
     worker::{HealthResponse, HealthService, WorkResponse, WorkService},
 };
+use rocket::http::{ContentType, Status};
+use rocket::request::{FromRequest, Outcome, Request};
 use rocket::serde::json::Json;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tracing::instrument;
 
 type HealthHandlerResult =
     Result<Json<HealthResponse>, (rocket::http::Status, Json<ErrorResponse>)>;
 type WorkHandlerResult =
     Result<Json<WorkResponse>, (rocket::http::Status, Json<ErrorResponse>)>;
+type MetricsHandlerResult = Result<(ContentType, String), (Status, Json<ErrorResponse>)>;
+
+/// Deadline sent by the caller in the deadline header, if any
+pub struct Deadline(Option<Duration>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Deadline {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(Deadline(parse_deadline(
+            request.headers().get_one(DEADLINE_HEADER),
+        )))
+    }
+}
 
 /// Health check handler
 #[rocket::get("/health")]
@@ -38,22 +59,91 @@ pub async fn health_handler(state: &rocket::State<AppState>) -> HealthHandlerRes
 
 /// Work handler
 #[rocket::get("/work")]
-#[instrument(skip(state), fields(framework.name = "rocket", http.route = "/work"))]
-pub async fn work_handler(state: &rocket::State<AppState>) -> WorkHandlerResult {
+#[instrument(skip(state, deadline), fields(framework.name = "rocket", http.route = "/work"))]
+pub async fn work_handler(
+    state: &rocket::State<AppState>,
+    deadline: Deadline,
+) -> WorkHandlerResult {
     let start = Instant::now();
     let metrics = lemonade_observability::get_http_metrics("lemonade-worker-rocket");
 
-    let result = match state.worker_service.work().await {
+    let result = match state.worker_service.work_within(deadline.0).await {
         Ok(response) => Ok(Json(response)),
+        Err(e) if e.is_deadline_exceeded() => Err((
+            rocket::http::Status::GatewayTimeout,
+            Json(ErrorResponse::new(format!("{}", e))),
+        )),
         Err(e) => Err((
             rocket::http::Status::InternalServerError,
             Json(ErrorResponse::new(format!("{}", e))),
         )),
     };
 
-    let status = result.as_ref().map(|_| 200).unwrap_or(500);
+    let status = match &result {
+        Ok(_) => 200,
+        Err((code, _)) => code.code,
+    };
     let duration_micros = start.elapsed().as_micros() as u64;
     metrics.record_request("GET", "/work", status, duration_micros);
 
     result
 }
+
+/// Stats handler (process CPU and resident memory)
+#[rocket::get("/stats")]
+#[instrument(skip(state), fields(framework.name = "rocket", http.route = "/stats"))]
+pub async fn stats_handler(state: &rocket::State<AppState>) -> Json<StatsResponse> {
+    let start = Instant::now();
+    let metrics = lemonade_observability::get_http_metrics("lemonade-worker-rocket");
+
+    let response = StatsResponse::new(
+        state.worker_service.service_name(),
+        state.resources.sample(),
+    );
+
+    let duration_micros = start.elapsed().as_micros() as u64;
+    metrics.record_request("GET", "/stats", 200, duration_micros);
+
+    Json(response)
+}
+
+/// Info handler (build information)
+#[rocket::get("/info")]
+#[instrument(skip(state), fields(framework.name = "rocket", http.route = "/info"))]
+pub async fn info_handler(state: &rocket::State<AppState>) -> Json<InfoResponse> {
+    let start = Instant::now();
+    let metrics = lemonade_observability::get_http_metrics("lemonade-worker-rocket");
+
+    let response = InfoResponse::new(state.worker_service.service_name());
+
+    let duration_micros = start.elapsed().as_micros() as u64;
+    metrics.record_request("GET", "/info", 200, duration_micros);
+
+    Json(response)
+}
+
+/// Prometheus metrics handler (404 unless `prometheus_metrics` is enabled)
+#[rocket::get("/metrics")]
+#[instrument(skip(state), fields(framework.name = "rocket", http.route = "/metrics"))]
+pub async fn metrics_handler(state: &rocket::State<AppState>) -> MetricsHandlerResult {
+    let start = Instant::now();
+    let metrics = lemonade_observability::get_http_metrics("lemonade-worker-rocket");
+
+    let result = match prometheus::scrape(state, "rocket") {
+        Some(body) => Ok((
+            ContentType::parse_flexible(PROMETHEUS_CONTENT_TYPE)
+                .unwrap_or(ContentType::Plain),
+            body,
+        )),
+        None => Err((Status::NotFound, Json(ErrorResponse::new("Not Found")))),
+    };
+
+    let status = match &result {
+        Ok(_) => 200,
+        Err((status, _)) => status.code,
+    };
+    let duration_micros = start.elapsed().as_micros() as u64;
+    metrics.record_request("GET", "/metrics", status, duration_micros);
+
+    result
+}
@@ -1,34 +1,43 @@
 //! Lemonade worker Rocket
 //!
+mod compression;
 mod fairing;
 mod handler;
 
+use compression::CompressionFairing;
 use fairing::TracingFairing;
-use handler::{health_handler, work_handler};
-use lemonade_service::{AppState, config::Config};
+use handler::{
+    health_handler, info_handler, metrics_handler, stats_handler, work_handler,
+};
+use lemonade_service::{AppState, BootstrapHandle, config::Config};
+
+/// Service name of the Rocket worker
+pub const SERVICE_NAME: &str = "lemonade-worker-rocket";
+
+/// Initialize tracing and metrics and build the worker state
+pub fn bootstrap(config: Config) -> Result<BootstrapHandle, Box<dyn std::error::Error>> {
+    lemonade_service::bootstrap(config, SERVICE_NAME)
+}
 
 /// Run the Rocket worker server
 pub async fn run(config: Config) -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing with service name from config and worker package version
-    lemonade_observability::init_tracing(
-        "lemonade-worker-rocket",
-        env!("CARGO_PKG_VERSION"),
-        config.service_name(),
-        config.otlp_endpoint(),
-        config.otlp_protocol(),
-    )?;
-
-    // Initialize metrics
-    lemonade_observability::init_metrics(
-        "lemonade-worker-rocket",
-        env!("CARGO_PKG_VERSION"),
-        config.service_name(),
-        config.otlp_endpoint(),
-        config.otlp_protocol(),
-    )?;
+    let BootstrapHandle {
+        state,
+        observability: _observability,
+        ..
+    } = bootstrap(config)?;
+    serve(state).await
+}
 
-    let state = AppState::new(config);
-    let addr = *state.config.listen_address().as_ref();
+/// Serve the worker routes on the configured listen address
+///
+/// Unlike [`run`], tracing and metrics are not initialized. Rocket 0.5 only
+/// listens on TCP, so a Unix socket address is an error.
+pub async fn serve(state: AppState) -> Result<(), Box<dyn std::error::Error>> {
+    let address = state.config.listen_address();
+    let Some(addr) = address.tcp() else {
+        return Err(address.unsupported("Rocket worker").into());
+    };
     let rocket_config = rocket::Config {
         address: addr.ip(),
         port: addr.port(),
@@ -36,9 +45,19 @@ pub async fn run(config: Config) -> Result<(), Box<dyn std::error::Error>> {
     };
 
     let _rocket = rocket::custom(&rocket_config)
+        .attach(CompressionFairing)
         .attach(TracingFairing)
         .manage(state)
-        .mount("/", rocket::routes![health_handler, work_handler])
+        .mount(
+            "/",
+            rocket::routes![
+                health_handler,
+                work_handler,
+                stats_handler,
+                info_handler,
+                metrics_handler
+            ],
+        )
         .launch()
         .await?;
 
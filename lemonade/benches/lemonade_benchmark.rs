@@ -1,5 +1,8 @@
 //! Benchmark module for Lemonade Tokio
 //!
+//! With `LEMONADE_BENCH_REPORT` set, the results are also written to that
+//! file as a bench report for `bench-compare`.
+use bench_utils::{BenchMetrics, ReportWriter};
 use criterion::{Criterion, criterion_group, criterion_main};
 use std::{
     sync::{Arc, Mutex},
@@ -43,6 +46,8 @@ fn bench_server(c: &mut Criterion) {
 
     let url = format!("http://{}/work", bench_address);
 
+    let report = ReportWriter::from_env();
+    let started = std::time::Instant::now();
     c.bench_function(&format!("Lemonade Benchmark - {}", bench_address), |b| {
         let stats = stats.clone();
         let client = client.clone();
@@ -96,8 +101,14 @@ fn bench_server(c: &mut Criterion) {
         });
     });
 
+    let elapsed = started.elapsed();
+
     let final_stats = stats.lock().unwrap();
     final_stats.print_summary(&format!("Lemonade Benchmark - {}", bench_address));
+    report.record(
+        format!("lemonade/{}", bench_address),
+        final_stats.bench_metrics(elapsed),
+    );
 }
 
 /// Tracks success and failure counts for benchmark requests with response time statistics
@@ -206,6 +217,13 @@ impl RequestStats {
         (avg, median, min, max, p95, p99)
     }
 
+    /// Bench report metrics over every request made in `elapsed`
+    pub fn bench_metrics(&self, elapsed: Duration) -> BenchMetrics {
+        let mut times = self.success_times.lock().unwrap().clone();
+        times.extend_from_slice(&self.failure_times.lock().unwrap());
+        BenchMetrics::from_latencies(&times, self.failed, elapsed)
+    }
+
     /// Print the summary of the request stats
     pub fn print_summary(&self, bench_name: &str) {
         let total = self.total();
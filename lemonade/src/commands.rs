@@ -1,4 +1,8 @@
 use clap::Subcommand;
+use lemonade_service::{
+    access_log::AccessLogFormat, compression::CompressionMode,
+    config::parse_otlp_protocol,
+};
 use std::path::PathBuf;
 
 /// Commands for the Lemonade CLI
@@ -13,7 +17,7 @@ pub enum LemonadeCommands {
         #[arg(short = 'f', long = "framework", value_name = "FRAMEWORK")]
         framework: String,
 
-        /// Path to configuration file (JSON or TOML)
+        /// Path to configuration file (JSON, TOML or YAML)
         #[arg(short = 'c', long = "config", value_name = "CONFIG_FILE")]
         config: Option<PathBuf>,
 
@@ -28,6 +32,30 @@ pub enum LemonadeCommands {
         /// Work delay in milliseconds
         #[arg(short = 'd', long = "delay", value_name = "DELAY_MILLISECONDS")]
         delay: Option<u64>,
+
+        /// OTLP exporter endpoint (overrides LEMONADE_OTLP_ENDPOINT and the config file)
+        #[arg(long = "otlp-endpoint", value_name = "OTLP_ENDPOINT")]
+        otlp_endpoint: Option<String>,
+
+        /// OTLP exporter protocol: grpc or http (overrides LEMONADE_OTLP_PROTOCOL and the config file)
+        #[arg(long = "otlp-protocol", value_name = "OTLP_PROTOCOL", value_parser = parse_otlp_protocol)]
+        otlp_protocol: Option<String>,
+
+        /// Access log format: off, json or common (overrides LEMONADE_WORKER_ACCESS_LOG and the config file)
+        #[arg(long = "access-log", value_name = "ACCESS_LOG_FORMAT")]
+        access_log: Option<AccessLogFormat>,
+
+        /// Response compression: off, gzip or auto (overrides LEMONADE_WORKER_COMPRESSION and the config file)
+        #[arg(long = "compression", value_name = "COMPRESSION_MODE")]
+        compression: Option<CompressionMode>,
+
+        /// Smallest response body compressed, in bytes (overrides LEMONADE_WORKER_COMPRESSION_MIN_BYTES and the config file)
+        #[arg(long = "compression-min-bytes", value_name = "BYTES")]
+        compression_min_bytes: Option<usize>,
+
+        /// Serve request metrics on /metrics in Prometheus text format (overrides LEMONADE_WORKER_PROMETHEUS_METRICS and the config file)
+        #[arg(long = "prometheus-metrics")]
+        prometheus_metrics: bool,
     },
     /// Run a load balancer
     #[command(alias = "lb")]
@@ -35,5 +63,52 @@ pub enum LemonadeCommands {
         /// Path to configuration file (JSON or TOML)
         #[arg(short = 'c', long = "config", value_name = "CONFIG_FILE")]
         config: Option<PathBuf>,
+
+        /// Disable health checking (overrides services.health in the config)
+        #[arg(long = "no-health-checks")]
+        no_health_checks: bool,
+
+        /// Disable metrics aggregation (overrides services.metrics in the config)
+        #[arg(long = "no-metrics")]
+        no_metrics: bool,
+    },
+    /// Check a load balancer configuration file
+    Validate {
+        /// Path to configuration file (JSON, TOML or YAML)
+        #[arg(short = 'c', long = "config", value_name = "CONFIG_FILE")]
+        config: PathBuf,
+
+        /// Rewrite a file written for an older schema version in the current one
+        #[arg(long = "migrate-to-latest", conflicts_with = "against_running")]
+        migrate_to_latest: bool,
+
+        /// Check the file against a running load balancer through its admin API
+        /// (host:port) and report what it would change, without applying it
+        #[arg(long = "against-running", value_name = "ADMIN_ADDR")]
+        against_running: Option<String>,
+    },
+    /// Check that the environment can run a load balancer
+    Doctor {
+        /// Path to configuration file (JSON, TOML or YAML); LEMONADE_LB_* variables without one
+        #[arg(short = 'c', long = "config", value_name = "CONFIG_FILE")]
+        config: Option<PathBuf>,
+
+        /// Also connect to every backend
+        #[arg(long = "check-backends")]
+        check_backends: bool,
+
+        /// Print the report as JSON
+        #[arg(long = "json")]
+        json: bool,
+    },
+    /// Send requests through a load balancer and check the responses
+    Verify {
+        /// Load balancer configuration file (JSON, TOML or YAML)
+        #[arg(long = "lb-config", value_name = "CONFIG_FILE")]
+        lb_config: PathBuf,
+
+        /// Request spec file (YAML or JSON)
+        #[arg(long = "requests", value_name = "SPEC_FILE")]
+        requests: PathBuf,
     },
 }
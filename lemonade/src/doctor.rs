@@ -0,0 +1,433 @@
+//! Doctor module
+//!
+//! Environment checks run by `lemonade doctor` before starting a load
+//! balancer. Each check is a small function returning a [`CheckResult`];
+//! the report fails when any required check fails.
+use lemonade_load_balancer::api::{Config, ConfigBuilder};
+use serde::Serialize;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Timeout for each network probe
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// File descriptors kept aside for listeners, the admin API, config files and
+/// logs on top of two per proxied connection
+const FD_HEADROOM: u64 = 64;
+
+/// Earliest plausible wall-clock time (2024-01-01T00:00:00Z)
+const MIN_PLAUSIBLE_EPOCH_SECS: u64 = 1_704_067_200;
+
+/// Largest accepted gap between the wall clock and the monotonic clock over
+/// the clock check
+const MAX_CLOCK_DRIFT: Duration = Duration::from_millis(500);
+
+/// Outcome of a single check
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// The check succeeded
+    Pass,
+    /// The check found a problem
+    Fail,
+    /// The check did not run (disabled, or a prerequisite failed)
+    Skip,
+}
+
+/// Result of a single doctor check
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    /// Check name
+    pub name: &'static str,
+    /// Outcome
+    pub status: CheckStatus,
+    /// Whether a failure fails the whole report
+    pub required: bool,
+    /// What was checked, or why it failed
+    pub detail: String,
+}
+
+impl CheckResult {
+    /// Passed check
+    pub fn pass(name: &'static str, required: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            required,
+            detail: detail.into(),
+        }
+    }
+
+    /// Failed check
+    pub fn fail(name: &'static str, required: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            required,
+            detail: detail.into(),
+        }
+    }
+
+    /// Skipped check
+    pub fn skip(name: &'static str, required: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Skip,
+            required,
+            detail: detail.into(),
+        }
+    }
+
+    /// Whether this check fails the report
+    pub fn is_blocking(&self) -> bool {
+        self.required && self.status == CheckStatus::Fail
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match (self.status, self.required) {
+            (CheckStatus::Pass, _) => "PASS",
+            (CheckStatus::Fail, true) => "FAIL",
+            (CheckStatus::Fail, false) => "WARN",
+            (CheckStatus::Skip, _) => "SKIP",
+        };
+        write!(f, "[{}] {}: {}", label, self.name, self.detail)
+    }
+}
+
+/// Results of every doctor check, in the order they ran
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DoctorReport {
+    /// Whether no required check failed
+    pub ok: bool,
+    /// Individual check results
+    pub checks: Vec<CheckResult>,
+}
+
+impl DoctorReport {
+    /// Build a report from check results
+    pub fn new(checks: Vec<CheckResult>) -> Self {
+        Self {
+            ok: !checks.iter().any(CheckResult::is_blocking),
+            checks,
+        }
+    }
+
+    /// Number of failed required checks
+    pub fn blocking_failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.is_blocking())
+            .count()
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "{}", check)?;
+        }
+        let passed = self
+            .checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Pass)
+            .count();
+        write!(
+            f,
+            "{} of {} checks passed, {} required check(s) failed",
+            passed,
+            self.checks.len(),
+            self.blocking_failures()
+        )
+    }
+}
+
+/// Run every check against a load balancer config
+///
+/// The config is loaded like `lemonade load-balancer` does (from
+/// `config_file`, or from `LEMONADE_LB_*` variables without one). Backends
+/// are only connected to with `check_backends`.
+pub async fn run_doctor(
+    config_file: Option<&Path>,
+    check_backends: bool,
+) -> DoctorReport {
+    let (config_check, config) = check_config(config_file);
+    let mut checks = vec![config_check];
+    match config {
+        Some(config) => {
+            checks.push(check_listen_address(&config).await);
+            checks.push(check_backends_resolvable(&config).await);
+            checks.push(if check_backends {
+                check_backends_reachable(&config).await
+            } else {
+                CheckResult::skip(
+                    "backends_reachable",
+                    true,
+                    "pass --check-backends to connect to every backend",
+                )
+            });
+            checks.push(check_otlp_endpoint(&config).await);
+            checks.push(check_fd_limit(&config));
+        }
+        None => {
+            for (name, required) in [
+                ("listen_address", true),
+                ("backends_resolvable", true),
+                ("backends_reachable", true),
+                ("otlp_endpoint", false),
+                ("fd_limit", true),
+            ] {
+                checks.push(CheckResult::skip(name, required, "config did not load"));
+            }
+        }
+    }
+    checks.push(check_clock().await);
+    DoctorReport::new(checks)
+}
+
+/// The config parses and validates
+fn check_config(config_file: Option<&Path>) -> (CheckResult, Option<Config>) {
+    let source = config_file
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "environment".to_string());
+    match ConfigBuilder::from_file(config_file.map(PathBuf::from)) {
+        Ok(config) => (
+            CheckResult::pass(
+                "config",
+                true,
+                format!(
+                    "{} is valid (version {}, {} backends)",
+                    source,
+                    config.version,
+                    config.backends.len()
+                ),
+            ),
+            Some(config),
+        ),
+        Err(e) => (
+            CheckResult::fail("config", true, format!("{}: {}", source, e)),
+            None,
+        ),
+    }
+}
+
+/// The proxy listen address can be bound
+async fn check_listen_address(config: &Config) -> CheckResult {
+    let address = config.proxy.listen_address;
+    match tokio::net::TcpListener::bind(address).await {
+        Ok(_) => {
+            CheckResult::pass("listen_address", true, format!("{} is free", address))
+        }
+        Err(e) => CheckResult::fail(
+            "listen_address",
+            true,
+            format!("cannot bind {}: {}", address, e),
+        ),
+    }
+}
+
+/// Every backend address resolves
+async fn check_backends_resolvable(config: &Config) -> CheckResult {
+    let mut failures = Vec::new();
+    for backend in &config.backends {
+        let address = backend.address.as_str();
+        if let Err(e) = resolve(address).await {
+            failures.push(format!("{} ({})", address, e));
+        }
+    }
+    if failures.is_empty() {
+        CheckResult::pass(
+            "backends_resolvable",
+            true,
+            format!("{} backend addresses resolve", config.backends.len()),
+        )
+    } else {
+        CheckResult::fail(
+            "backends_resolvable",
+            true,
+            format!("unresolvable: {}", failures.join(", ")),
+        )
+    }
+}
+
+/// Every backend accepts a TCP connection
+async fn check_backends_reachable(config: &Config) -> CheckResult {
+    let mut failures = Vec::new();
+    for backend in &config.backends {
+        let address = backend.address.as_str();
+        if let Err(e) = connect(address).await {
+            failures.push(format!("{} ({})", address, e));
+        }
+    }
+    if failures.is_empty() {
+        CheckResult::pass(
+            "backends_reachable",
+            true,
+            format!("{} backends accept connections", config.backends.len()),
+        )
+    } else {
+        CheckResult::fail(
+            "backends_reachable",
+            true,
+            format!("unreachable: {}", failures.join(", ")),
+        )
+    }
+}
+
+/// The OTLP collector accepts connections on the configured protocol's port
+///
+/// Not required: the load balancer runs without a collector, it only loses
+/// telemetry.
+async fn check_otlp_endpoint(config: &Config) -> CheckResult {
+    let (Some(endpoint), Some(protocol)) = (
+        config.otlp_endpoint.as_deref(),
+        config.otlp_protocol.as_deref(),
+    ) else {
+        return CheckResult::skip("otlp_endpoint", false, "no OTLP exporter configured");
+    };
+    let Some(authority) = otlp_authority(endpoint, protocol) else {
+        return CheckResult::fail(
+            "otlp_endpoint",
+            false,
+            format!("cannot parse {} endpoint {}", protocol, endpoint),
+        );
+    };
+    match connect(&authority).await {
+        Ok(()) => CheckResult::pass(
+            "otlp_endpoint",
+            false,
+            format!("{} collector at {} is reachable", protocol, authority),
+        ),
+        Err(e) => CheckResult::fail(
+            "otlp_endpoint",
+            false,
+            format!("{} collector at {}: {}", protocol, authority, e),
+        ),
+    }
+}
+
+/// `host:port` of an OTLP endpoint URL, defaulting the port by protocol
+fn otlp_authority(endpoint: &str, protocol: &str) -> Option<String> {
+    let without_scheme = endpoint
+        .split_once("://")
+        .map_or(endpoint, |(_, rest)| rest);
+    let authority = without_scheme.split('/').next()?;
+    if authority.is_empty() {
+        return None;
+    }
+    let has_port = authority
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+    if has_port {
+        return Some(authority.to_string());
+    }
+    let default_port = match protocol {
+        "grpc" => 4317,
+        _ => 4318,
+    };
+    Some(format!("{}:{}", authority, default_port))
+}
+
+/// The open-file limit covers two descriptors per allowed connection
+fn check_fd_limit(config: &Config) -> CheckResult {
+    let Some(max_connections) = config.proxy.max_connections else {
+        return CheckResult::skip("fd_limit", true, "no proxy.max_connections set");
+    };
+    let Some(limit) = open_files_limit() else {
+        return CheckResult::skip("fd_limit", true, "open-file limit not available");
+    };
+    let needed = max_connections
+        .saturating_mul(2)
+        .saturating_add(FD_HEADROOM);
+    match limit {
+        None => CheckResult::pass("fd_limit", true, "open-file limit is unlimited"),
+        Some(limit) if limit >= needed => CheckResult::pass(
+            "fd_limit",
+            true,
+            format!("ulimit -n {} covers {} needed", limit, needed),
+        ),
+        Some(limit) => CheckResult::fail(
+            "fd_limit",
+            true,
+            format!(
+                "ulimit -n {} is below the {} needed for max_connections {}",
+                limit, needed, max_connections
+            ),
+        ),
+    }
+}
+
+/// Soft open-file limit of the process (`Some(None)` when unlimited)
+///
+/// Read from `/proc/self/limits`; `None` where it does not exist.
+fn open_files_limit() -> Option<Option<u64>> {
+    let limits = std::fs::read_to_string("/proc/self/limits").ok()?;
+    let line = limits
+        .lines()
+        .find(|line| line.starts_with("Max open files"))?;
+    let soft = line
+        .trim_start_matches("Max open files")
+        .split_whitespace()
+        .next()?;
+    if soft == "unlimited" {
+        return Some(None);
+    }
+    soft.parse().ok().map(Some)
+}
+
+/// The wall clock is plausible and moves with the monotonic clock
+async fn check_clock() -> CheckResult {
+    let Ok(since_epoch) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+        return CheckResult::fail("clock", false, "system time is before 1970");
+    };
+    if since_epoch.as_secs() < MIN_PLAUSIBLE_EPOCH_SECS {
+        return CheckResult::fail(
+            "clock",
+            false,
+            format!(
+                "system time {}s since epoch is in the past",
+                since_epoch.as_secs()
+            ),
+        );
+    }
+
+    let wall_start = SystemTime::now();
+    let monotonic_start = Instant::now();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let monotonic = monotonic_start.elapsed();
+    let wall = wall_start.elapsed().unwrap_or_default();
+    let drift = wall.abs_diff(monotonic);
+    if drift > MAX_CLOCK_DRIFT {
+        return CheckResult::fail(
+            "clock",
+            false,
+            format!("wall clock moved {:?} off the monotonic clock", drift),
+        );
+    }
+    CheckResult::pass("clock", false, "system time is plausible and steady")
+}
+
+/// Resolve a `host:port` address
+async fn resolve(address: &str) -> Result<Vec<SocketAddr>, String> {
+    let resolved = tokio::time::timeout(CHECK_TIMEOUT, tokio::net::lookup_host(address))
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map_err(|e| e.to_string())?
+        .collect::<Vec<_>>();
+    if resolved.is_empty() {
+        return Err("no addresses".to_string());
+    }
+    Ok(resolved)
+}
+
+/// Open (and drop) a TCP connection to a `host:port` address
+async fn connect(address: &str) -> Result<(), String> {
+    tokio::time::timeout(CHECK_TIMEOUT, tokio::net::TcpStream::connect(address))
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map(drop)
+        .map_err(|e| e.to_string())
+}
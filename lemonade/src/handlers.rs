@@ -1,49 +1,35 @@
 //! Command handlers
 //!
-use lemonade_service::config::{Config, ConfigBuilder, WorkerAddress};
+use crate::doctor::run_doctor;
+use crate::verify::{VerifySpec, run_verify};
+use lemonade_load_balancer::api::{
+    CONFIG_VERSION, ConfigBuilder as LoadBalancerConfigBuilder, ConfigFormat,
+    DryRunReport, ServiceOverrides, migrate_file, upgrade,
+};
+use lemonade_service::config::{ConfigBuilder, ConfigLayer, OtlpConfig};
 use std::{path::PathBuf, time::Duration};
 
+/// Admin API token used by `validate --against-running`
+const ADMIN_TOKEN_ENV_KEY: &str = "LEMONADE_LB_ADMIN_TOKEN";
+
+/// Interval between checks of a worker config file for OTLP changes
+const WORKER_CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
 /// Run a worker server
-#[tracing::instrument(skip_all, fields(service.name = %framework, service.instance.id = ?name))]
+///
+/// Settings are layered as `flags` > `LEMONADE_WORKER_*` / `LEMONADE_OTLP_*` >
+/// `config_file` > defaults. With a config file, edits to its OTLP settings
+/// are applied while the worker runs.
+#[tracing::instrument(skip_all, fields(service.name = %framework, service.instance.id = ?flags.service_name))]
 pub async fn run_worker(
     framework: String,
     config_file: Option<PathBuf>,
-    address: Option<String>,
-    name: Option<String>,
-    delay: Option<u64>,
+    flags: ConfigLayer,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let config = if let Some(path) = config_file {
-        // Load from file
-        ConfigBuilder::from_file(Some(path))?
-    } else if address.is_some() || name.is_some() || delay.is_some() {
-        // Build from individual arguments (with fallback to env for missing values)
-        let listen_address = if let Some(addr) = address {
-            WorkerAddress::parse(&addr)?
-        } else {
-            // Try from env or use default
-            ConfigBuilder::from_env()?.listen_address().clone()
-        };
-
-        let service_name = name.unwrap_or_else(|| {
-            std::env::var("LEMONADE_WORKER_SERVICE_NAME")
-                .unwrap_or_else(|_| "lemonade-worker".to_string())
-        });
-
-        let work_delay = if let Some(d) = delay {
-            Duration::from_millis(d)
-        } else {
-            std::env::var("LEMONADE_WORKER_WORK_DELAY_MS")
-                .unwrap_or_else(|_| "20".to_string())
-                .parse::<u64>()
-                .map(Duration::from_millis)
-                .unwrap_or(Duration::from_millis(20))
-        };
-
-        Config::new(listen_address, service_name, work_delay)
-    } else {
-        // No arguments provided, load from env
-        ConfigBuilder::from_env()?
-    };
+    let config = ConfigBuilder::load(config_file.clone(), flags.clone())?;
+    if let Some(config_file) = config_file {
+        tokio::spawn(watch_worker_otlp(config_file, flags, config.otlp()));
+    }
 
     let framework_lower = framework.to_lowercase();
     match framework_lower.as_str() {
@@ -71,10 +57,188 @@ pub async fn run_worker(
     Ok(())
 }
 
+/// Reconfigure the OTLP exporters when the worker config file changes them
+///
+/// The file is layered under the same flags and environment as at startup,
+/// so settings overridden there keep their value.
+async fn watch_worker_otlp(
+    config_file: PathBuf,
+    flags: ConfigLayer,
+    mut current: OtlpConfig,
+) {
+    let modified_at = |path: &PathBuf| {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+    let mut modified = modified_at(&config_file);
+    let mut interval = tokio::time::interval(WORKER_CONFIG_WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = modified_at(&config_file);
+        if now == modified {
+            continue;
+        }
+        modified = now;
+
+        let otlp = match ConfigBuilder::load(Some(config_file.clone()), flags.clone()) {
+            Ok(config) => config.otlp(),
+            Err(e) => {
+                tracing::warn!("Ignoring invalid worker config: {}", e);
+                continue;
+            }
+        };
+        if otlp == current {
+            continue;
+        }
+        current = otlp.clone();
+
+        tracing::info!(
+            otlp.endpoint = ?otlp.endpoint,
+            otlp.protocol = ?otlp.protocol,
+            "Reconfiguring OTLP exporters"
+        );
+        let result = tokio::task::spawn_blocking(move || {
+            lemonade_observability::reconfigure_exporters(
+                otlp.endpoint.as_deref(),
+                otlp.protocol.as_deref(),
+            )
+            .map_err(|e| e.to_string())
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::error!("Failed to reconfigure OTLP exporters: {}", e),
+            Err(e) => tracing::error!("OTLP reconfiguration task failed: {}", e),
+        }
+    }
+}
+
 /// Run a load balancer
+///
+/// `overrides` disables background services on top of the config file.
 #[tracing::instrument(skip_all, fields(service.name = "load-balancer", service.instance.id = "tokio"))]
 pub async fn run_load_balancer(
     config_file: Option<PathBuf>,
+    overrides: ServiceOverrides,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    lemonade_load_balancer::run(config_file).await
+    lemonade_load_balancer::run_with_overrides(config_file, overrides).await
+}
+
+/// Check a load balancer config file, optionally migrating it first
+///
+/// Without `migrate_to_latest`, a file written for an older schema version
+/// is validated as loaded (upgraded in memory) and left untouched.
+pub fn validate_config(
+    config_file: PathBuf,
+    migrate_to_latest: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = config_file.display();
+    if migrate_to_latest {
+        let migration = migrate_file(&config_file)?;
+        if migration.is_upgrade() {
+            println!(
+                "{}: migrated from version {} to {}",
+                path, migration.from_version, migration.to_version
+            );
+        } else {
+            println!("{}: already at version {}", path, migration.to_version);
+        }
+    }
+
+    let format = ConfigFormat::from_path(&config_file)?;
+    let mut declared = format.parse(&std::fs::read_to_string(&config_file)?)?;
+    let version = upgrade(&mut declared)?;
+    LoadBalancerConfigBuilder::from_file(Some(&config_file))?;
+    println!("{}: valid (version {})", path, version);
+    if version < CONFIG_VERSION {
+        println!(
+            "{}: written for version {}, run with --migrate-to-latest to upgrade to {}",
+            path, version, CONFIG_VERSION
+        );
+    }
+    Ok(())
+}
+
+/// Check a load balancer config file against a running load balancer
+///
+/// Posts the file to the `POST /config/dry-run` route of the admin API at
+/// `admin_address`, which parses and validates it as a reload would and
+/// reports what it would change; nothing is applied. The admin token is read
+/// from `LEMONADE_LB_ADMIN_TOKEN`. Fails when the file would be rejected.
+pub async fn validate_against_running(
+    config_file: PathBuf,
+    admin_address: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = config_file.display();
+    let format = ConfigFormat::from_path(&config_file)?;
+    let content = std::fs::read_to_string(&config_file)?;
+    let url = format!(
+        "http://{}/config/dry-run?format={}",
+        admin_address,
+        format.as_str()
+    );
+    let mut request = reqwest::Client::new().post(&url).body(content);
+    if let Ok(token) = std::env::var(ADMIN_TOKEN_ENV_KEY) {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("{} answered {}: {}", admin_address, status, body).into());
+    }
+    let report: DryRunReport = response.json().await?;
+
+    for (group, diff) in &report.groups {
+        println!("{}: group {}: {}", path, group, diff.summary());
+    }
+    if !report.valid {
+        for error in &report.errors {
+            println!("{}: {}", path, error);
+        }
+        return Err(format!("{}: rejected by {}", path, admin_address).into());
+    }
+    println!("{}: valid against {}", path, admin_address);
+    Ok(())
+}
+
+/// Run the doctor checks and print their report
+///
+/// Fails when a required check fails, so the exit status can gate a deploy.
+pub async fn doctor(
+    config_file: Option<PathBuf>,
+    check_backends: bool,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let report = run_doctor(config_file.as_deref(), check_backends).await;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report);
+    }
+    if !report.ok {
+        return Err(format!(
+            "{} required doctor check(s) failed",
+            report.blocking_failures()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Run a request spec through a load balancer and print the report
+///
+/// Fails when any check fails, so the exit status can gate CI.
+pub async fn verify(
+    lb_config: PathBuf,
+    requests: PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let spec = VerifySpec::from_file(&requests)?;
+    let report = run_verify(&lb_config, &spec).await?;
+    println!("{}", report);
+    if !report.is_ok() {
+        return Err(format!("{} verify check(s) failed", report.failures()).into());
+    }
+    Ok(())
 }
@@ -1,18 +1,60 @@
 //! Lemonade library
 //!
 mod commands;
+mod doctor;
 mod handlers;
+mod verify;
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 pub use commands::LemonadeCommands;
-pub use handlers::{run_load_balancer, run_worker};
+pub use doctor::{CheckResult, CheckStatus, DoctorReport, run_doctor};
+pub use handlers::{
+    doctor, run_load_balancer, run_worker, validate_against_running, validate_config,
+    verify,
+};
+use lemonade_load_balancer::api::ServiceOverrides;
+use lemonade_observability::BuildInfo;
+use lemonade_service::config::{ConfigLayer, OtlpConfig, WorkerAddress};
+use std::time::Duration;
+pub use verify::{
+    DistributionSpec, Expectation, RequestReport, RequestSpec, SpawnSpec, VerifyReport,
+    VerifySpec, run_verify,
+};
 
 #[derive(Parser)]
 #[command(name = "lemonade")]
 #[command(about = "Lemonade load balancer and worker CLI", long_about = None)]
+#[command(disable_version_flag = true, arg_required_else_help = true)]
 struct LemonadeCli {
+    /// Print version (with -vv, also the git SHA, build time, rustc and features)
+    #[arg(short = 'V', long = "version")]
+    version: bool,
+
+    /// Increase output detail
+    #[arg(short = 'v', action = clap::ArgAction::Count)]
+    verbose: u8,
+
     #[command(subcommand)]
-    command: LemonadeCommands,
+    command: Option<LemonadeCommands>,
+}
+
+/// Build information of the CLI, with the load balancer's cargo features
+pub fn build_info() -> BuildInfo {
+    BuildInfo::new(env!("CARGO_PKG_VERSION"))
+        .with_features(lemonade_load_balancer::api::build_info().features)
+}
+
+/// Text printed by `lemonade --version`
+///
+/// One line with the version and abbreviated commit; from `verbose` 2
+/// (`-vv`) on, every build information field on its own line.
+pub fn version_text(verbose: u8) -> String {
+    let info = build_info();
+    if verbose >= 2 {
+        format!("lemonade\n{}", info)
+    } else {
+        format!("lemonade {}", info.summary())
+    }
 }
 
 /// Run the Lemonade CLI
@@ -20,16 +62,81 @@ struct LemonadeCli {
 /// This function parses the CLI arguments and runs the appropriate command.
 pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let cli = LemonadeCli::parse();
+    if cli.version {
+        println!("{}", version_text(cli.verbose));
+        return Ok(());
+    }
+    let Some(command) = cli.command else {
+        LemonadeCli::command()
+            .error(
+                clap::error::ErrorKind::MissingSubcommand,
+                "a subcommand is required",
+            )
+            .exit();
+    };
 
-    match cli.command {
+    match command {
         LemonadeCommands::Worker {
             framework,
             config,
             address,
             name,
             delay,
-        } => run_worker(framework, config, address, name, delay).await?,
-        LemonadeCommands::LoadBalancer { config } => run_load_balancer(config).await?,
+            otlp_endpoint,
+            otlp_protocol,
+            access_log,
+            compression,
+            compression_min_bytes,
+            prometheus_metrics,
+        } => {
+            let flags = ConfigLayer {
+                listen_address: address
+                    .as_deref()
+                    .map(WorkerAddress::parse)
+                    .transpose()?,
+                service_name: name,
+                work_delay: delay.map(Duration::from_millis),
+                otlp: OtlpConfig::new(otlp_endpoint, otlp_protocol),
+                access_log,
+                compression,
+                compression_min_bytes,
+                prometheus_metrics: prometheus_metrics.then_some(true),
+            };
+            run_worker(framework, config, flags).await?
+        }
+        LemonadeCommands::LoadBalancer {
+            config,
+            no_health_checks,
+            no_metrics,
+        } => {
+            run_load_balancer(
+                config,
+                ServiceOverrides {
+                    no_health_checks,
+                    no_metrics,
+                },
+            )
+            .await?
+        }
+        LemonadeCommands::Validate {
+            config,
+            migrate_to_latest,
+            against_running,
+        } => match against_running {
+            Some(admin_address) => {
+                validate_against_running(config, &admin_address).await?
+            }
+            None => validate_config(config, migrate_to_latest)?,
+        },
+        LemonadeCommands::Doctor {
+            config,
+            check_backends,
+            json,
+        } => doctor(config, check_backends, json).await?,
+        LemonadeCommands::Verify {
+            lb_config,
+            requests,
+        } => verify(lb_config, requests).await?,
     }
 
     Ok(())
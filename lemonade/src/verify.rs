@@ -0,0 +1,614 @@
+//! Verify module
+//!
+//! `lemonade verify` starts a load balancer from a static config, sends the
+//! requests described in a spec file through it and checks the responses:
+//! status, JSON fields and how requests spread over the backends. Backends
+//! are expected to be running already, unless the spec's `spawn` section
+//! starts in-process workers to route to instead.
+use lemonade_load_balancer::api::{
+    AggregatingMetricsService, App, ArcSwap, BackendConfig, BackendHealthService,
+    BackendMeta, Config, ConfigBuilder, Context, StaticConfigService, TokioProxyService,
+};
+use lemonade_service::AppState;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// How long workers and the load balancer get to start, and to stop
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Timeout of each request sent through the load balancer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Requests to send through the load balancer and what to expect back
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct VerifySpec {
+    /// Workers to start in-process and route to instead of the configured
+    /// backends
+    #[serde(default)]
+    pub spawn: Option<SpawnSpec>,
+    /// Requests, sent in order
+    pub requests: Vec<RequestSpec>,
+}
+
+impl VerifySpec {
+    /// Load a spec from a YAML (or JSON) file
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_yaml::from_str(&content)
+            .map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+}
+
+/// In-process workers started for the run
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnSpec {
+    /// Worker framework (actix, axum, hyper or rocket)
+    #[serde(default = "default_framework")]
+    pub framework: String,
+    /// Number of workers, named `verify-worker-<n>`
+    pub workers: usize,
+    /// Work delay of every worker in milliseconds (workers need at least 1)
+    #[serde(default = "default_work_delay_ms")]
+    pub work_delay_ms: u64,
+}
+
+/// Default for [`SpawnSpec::framework`]
+fn default_framework() -> String {
+    "axum".to_string()
+}
+
+/// Default for [`SpawnSpec::work_delay_ms`]
+fn default_work_delay_ms() -> u64 {
+    1
+}
+
+/// A request sent `count` times
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RequestSpec {
+    /// Name used in the report (defaults to `<method> <path>`)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// HTTP method
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// Request path
+    pub path: String,
+    /// Request body
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Number of times the request is sent
+    #[serde(default = "default_count")]
+    pub count: usize,
+    /// Checks applied to every response
+    #[serde(default)]
+    pub expect: Expectation,
+}
+
+impl RequestSpec {
+    /// Name used in the report
+    pub fn display_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("{} {}", self.method, self.path))
+    }
+}
+
+/// Default for [`RequestSpec::method`]
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// Default for [`RequestSpec::count`]
+fn default_count() -> usize {
+    1
+}
+
+/// What every response to a request must satisfy
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Expectation {
+    /// Response status
+    #[serde(default)]
+    pub status: Option<u16>,
+    /// Expected JSON body fields, by dotted path (e.g. `service`)
+    #[serde(default)]
+    pub json: BTreeMap<String, serde_json::Value>,
+    /// How the responses must spread over the backends
+    #[serde(default)]
+    pub distribution: Option<DistributionSpec>,
+}
+
+/// Spread of responses over the backends that served them
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DistributionSpec {
+    /// JSON body field naming the backend that answered
+    #[serde(default = "default_distribution_field")]
+    pub field: String,
+    /// Smallest share of the responses each backend must serve (0.0 to 1.0)
+    pub min_share: f64,
+    /// Backends expected to answer (defaults to the routed backend count)
+    #[serde(default)]
+    pub backends: Option<usize>,
+}
+
+/// Default for [`DistributionSpec::field`]
+fn default_distribution_field() -> String {
+    "service".to_string()
+}
+
+/// Outcome of one request spec
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestReport {
+    /// Request name
+    pub name: String,
+    /// Requests sent
+    pub sent: usize,
+    /// Responses per value of the distribution field (when checked)
+    pub served: BTreeMap<String, usize>,
+    /// Failed checks, with the request number they apply to
+    pub failures: Vec<String>,
+}
+
+/// Outcome of a verify run
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyReport {
+    /// One entry per request spec, in order
+    pub requests: Vec<RequestReport>,
+}
+
+impl VerifyReport {
+    /// Whether every check passed
+    pub fn is_ok(&self) -> bool {
+        self.requests
+            .iter()
+            .all(|request| request.failures.is_empty())
+    }
+
+    /// Number of failed checks
+    pub fn failures(&self) -> usize {
+        self.requests
+            .iter()
+            .map(|request| request.failures.len())
+            .sum()
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for request in &self.requests {
+            let label = if request.failures.is_empty() {
+                "PASS"
+            } else {
+                "FAIL"
+            };
+            write!(f, "[{}] {} ({} sent)", label, request.name, request.sent)?;
+            if !request.served.is_empty() {
+                let served: Vec<String> = request
+                    .served
+                    .iter()
+                    .map(|(backend, count)| format!("{}={}", backend, count))
+                    .collect();
+                write!(f, " served: {}", served.join(", "))?;
+            }
+            writeln!(f)?;
+            for failure in &request.failures {
+                writeln!(f, "  - {}", failure)?;
+            }
+        }
+        write!(f, "{} failed check(s)", self.failures())
+    }
+}
+
+/// Start a load balancer from `lb_config` and run `spec` through it
+///
+/// The config is loaded once and not watched. Only the top-level backends
+/// are served; with a `spawn` section they are replaced by the spawned
+/// workers. A listen port of 0 is replaced by a free port.
+pub async fn run_verify(
+    lb_config: &Path,
+    spec: &VerifySpec,
+) -> Result<VerifyReport, Box<dyn Error>> {
+    let mut config = ConfigBuilder::from_file(Some(lb_config))?;
+    let mut workers = Vec::new();
+    if let Some(spawn) = &spec.spawn {
+        workers = spawn_workers(spawn).await?;
+        config.backends = workers
+            .iter()
+            .map(|worker| worker.backend.clone())
+            .collect();
+    }
+    if config.proxy.listen_address.port() == 0 {
+        // The proxy does not report its bound port, so pick one up front
+        config.proxy.listen_address =
+            std::net::TcpListener::bind(config.proxy.listen_address)?.local_addr()?;
+    }
+    let backends = config.backends.len();
+    let listen_address = config.proxy.listen_address;
+    let (ctx, lb) = match start_load_balancer(config).await {
+        Ok(started) => started,
+        Err(e) => {
+            stop_workers(workers).await;
+            return Err(e);
+        }
+    };
+
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .pool_max_idle_per_host(0)
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    let mut requests = Vec::with_capacity(spec.requests.len());
+    for request in &spec.requests {
+        requests.push(run_request(&client, listen_address, request, backends).await);
+    }
+
+    shutdown(&ctx, lb, workers).await;
+    Ok(VerifyReport { requests })
+}
+
+/// Stop the load balancer, then the spawned workers
+///
+/// The load balancer stops accepting and drains its connections (bounded by
+/// `runtime.drain_timeout_millis`) before any worker is stopped, so requests
+/// still in flight reach a live worker.
+async fn shutdown(
+    ctx: &Context,
+    lb: tokio::task::JoinHandle<lemonade_load_balancer::error::Result<()>>,
+    workers: Vec<SpawnedWorker>,
+) {
+    let started = Instant::now();
+    let _ = ctx.channels().shutdown_tx().send(());
+    match tokio::time::timeout(STARTUP_TIMEOUT, lb).await {
+        Ok(Ok(Err(e))) => tracing::warn!("Load balancer stopped with an error: {}", e),
+        Ok(Err(e)) => tracing::warn!("Load balancer task failed: {}", e),
+        Err(_) => tracing::warn!("Load balancer did not stop in time"),
+        Ok(Ok(Ok(()))) => {}
+    }
+    let lb_drain = started.elapsed();
+
+    let started = Instant::now();
+    let stopped = workers.len();
+    stop_workers(workers).await;
+    tracing::info!(
+        lb_drain_ms = lb_drain.as_millis() as u64,
+        workers_stop_ms = started.elapsed().as_millis() as u64,
+        workers = stopped,
+        "Cluster shut down"
+    );
+}
+
+/// Send one request spec and check every response
+async fn run_request(
+    client: &reqwest::Client,
+    listen_address: SocketAddr,
+    request: &RequestSpec,
+    backends: usize,
+) -> RequestReport {
+    let mut report = RequestReport {
+        name: request.display_name(),
+        sent: 0,
+        served: BTreeMap::new(),
+        failures: Vec::new(),
+    };
+    let method = match reqwest::Method::from_bytes(request.method.as_bytes()) {
+        Ok(method) => method,
+        Err(e) => {
+            report
+                .failures
+                .push(format!("invalid method {}: {}", request.method, e));
+            return report;
+        }
+    };
+    let url = format!("http://{}{}", listen_address, request.path);
+    let expect = &request.expect;
+
+    for number in 1..=request.count {
+        let mut builder = client.request(method.clone(), &url);
+        if let Some(body) = &request.body {
+            builder = builder.body(body.clone());
+        }
+        report.sent += 1;
+        let response = match builder.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                report
+                    .failures
+                    .push(format!("#{}: request failed: {}", number, e));
+                continue;
+            }
+        };
+        let status = response.status().as_u16();
+        if let Some(expected) = expect.status
+            && status != expected
+        {
+            report.failures.push(format!(
+                "#{}: status {}, expected {}",
+                number, status, expected
+            ));
+        }
+        if expect.json.is_empty() && expect.distribution.is_none() {
+            continue;
+        }
+        let body: serde_json::Value = match response.json().await {
+            Ok(body) => body,
+            Err(e) => {
+                report
+                    .failures
+                    .push(format!("#{}: body is not JSON: {}", number, e));
+                continue;
+            }
+        };
+        for (path, expected) in &expect.json {
+            match json_field(&body, path) {
+                Some(actual) if actual == expected => {}
+                Some(actual) => report.failures.push(format!(
+                    "#{}: {} is {}, expected {}",
+                    number, path, actual, expected
+                )),
+                None => report
+                    .failures
+                    .push(format!("#{}: {} is missing", number, path)),
+            }
+        }
+        if let Some(distribution) = &expect.distribution {
+            let backend = match json_field(&body, &distribution.field) {
+                Some(serde_json::Value::String(value)) => value.clone(),
+                Some(value) => value.to_string(),
+                None => {
+                    report
+                        .failures
+                        .push(format!("#{}: {} is missing", number, distribution.field));
+                    continue;
+                }
+            };
+            *report.served.entry(backend).or_default() += 1;
+        }
+    }
+
+    if let Some(distribution) = &expect.distribution {
+        check_distribution(&mut report, distribution, backends);
+    }
+    report
+}
+
+/// Check that every expected backend served at least its minimum share
+fn check_distribution(
+    report: &mut RequestReport,
+    distribution: &DistributionSpec,
+    backends: usize,
+) {
+    let expected_backends = distribution.backends.unwrap_or(backends);
+    if report.served.len() < expected_backends {
+        report.failures.push(format!(
+            "distribution: {} of {} backends answered",
+            report.served.len(),
+            expected_backends
+        ));
+    }
+    if report.sent == 0 {
+        return;
+    }
+    for (backend, count) in &report.served {
+        let share = *count as f64 / report.sent as f64;
+        if share < distribution.min_share {
+            report.failures.push(format!(
+                "distribution: {} served {:.1}% of requests, expected at least {:.1}%",
+                backend,
+                share * 100.0,
+                distribution.min_share * 100.0
+            ));
+        }
+    }
+}
+
+/// Field of a JSON value by dotted path (array elements by index)
+fn json_field<'a>(
+    value: &'a serde_json::Value,
+    path: &str,
+) -> Option<&'a serde_json::Value> {
+    path.split('.')
+        .try_fold(value, |value, segment| match value {
+            serde_json::Value::Object(fields) => fields.get(segment),
+            serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+/// An in-process worker started from the spawn section
+struct SpawnedWorker {
+    /// Backend config routing to the worker
+    backend: BackendConfig,
+    /// Stops the worker's server when sent (or dropped)
+    stop: oneshot::Sender<()>,
+    /// Thread running the worker's runtime
+    thread: std::thread::JoinHandle<()>,
+}
+
+/// Stop every worker and wait for its thread to exit
+async fn stop_workers(workers: Vec<SpawnedWorker>) {
+    let threads: Vec<_> = workers
+        .into_iter()
+        .map(|worker| {
+            let _ = worker.stop.send(());
+            worker.thread
+        })
+        .collect();
+    let joined = tokio::task::spawn_blocking(move || {
+        for thread in threads {
+            let _ = thread.join();
+        }
+    });
+    if tokio::time::timeout(STARTUP_TIMEOUT, joined).await.is_err() {
+        tracing::warn!("Spawned workers did not stop in time");
+    }
+}
+
+/// Start the spawn section's workers
+///
+/// Workers already started are stopped if a later one fails to start.
+async fn spawn_workers(spawn: &SpawnSpec) -> Result<Vec<SpawnedWorker>, Box<dyn Error>> {
+    let mut workers = Vec::with_capacity(spawn.workers);
+    for index in 0..spawn.workers {
+        let name = format!("verify-worker-{}", index);
+        let address = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+        let config = lemonade_service::config::Config::new(
+            address,
+            name.clone(),
+            Duration::from_millis(spawn.work_delay_ms.max(1)),
+        );
+        let backend = BackendConfig::from(BackendMeta::new(
+            index as u8,
+            Some(name),
+            address,
+            Some(1u8),
+        ));
+        let started = match spawn_worker(&spawn.framework, AppState::new(config), backend)
+        {
+            Ok(worker) => worker,
+            Err(e) => {
+                stop_workers(workers).await;
+                return Err(e);
+            }
+        };
+        workers.push(started);
+        if let Err(e) = wait_for_listener(address).await {
+            stop_workers(workers).await;
+            return Err(e);
+        }
+    }
+    Ok(workers)
+}
+
+/// Serve a worker on its own thread and runtime (actix servers are not `Send`)
+fn spawn_worker(
+    framework: &str,
+    state: AppState,
+    backend: BackendConfig,
+) -> Result<SpawnedWorker, Box<dyn Error>> {
+    let framework = match framework.to_lowercase().as_str() {
+        "actix" | "actix-web" => "actix",
+        "axum" => "axum",
+        "hyper" => "hyper",
+        "rocket" => "rocket",
+        _ => {
+            return Err(format!(
+                "Unknown framework: {}. Supported: actix, axum, hyper, rocket",
+                framework
+            )
+            .into());
+        }
+    };
+    let (stop, stopped) = oneshot::channel();
+    let thread = std::thread::Builder::new()
+        .name(format!("verify-{}", framework))
+        .spawn(move || {
+            let runtime = match tokio::runtime::Runtime::new() {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    return tracing::error!("Failed to start worker runtime: {}", e);
+                }
+            };
+            let result = runtime.block_on(async move {
+                let serve = async move {
+                    match framework {
+                        "actix" => lemonade_worker_actix::serve(state).await,
+                        "axum" => lemonade_worker_axum::serve(state).await,
+                        "hyper" => lemonade_worker_hyper::serve(state).await,
+                        _ => lemonade_worker_rocket::serve(state).await,
+                    }
+                    .map_err(|e| e.to_string())
+                };
+                tokio::select! {
+                    result = serve => result,
+                    _ = stopped => Ok(()),
+                }
+            });
+            // Dropping the runtime cancels the worker's remaining tasks
+            drop(runtime);
+            if let Err(e) = result {
+                tracing::error!("Spawned {} worker failed: {}", framework, e);
+            }
+        })?;
+    Ok(SpawnedWorker {
+        backend,
+        stop,
+        thread,
+    })
+}
+
+/// Wait until something accepts connections on `address`
+async fn wait_for_listener(address: SocketAddr) -> Result<(), Box<dyn Error>> {
+    tokio::time::timeout(STARTUP_TIMEOUT, async {
+        while tokio::net::TcpStream::connect(address).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .map_err(|_| format!("nothing listening on {} in time", address).into())
+}
+
+/// Run a load balancer with a static config until its context is shut down
+///
+/// Returns once the proxy accepts connections and the initial health check
+/// has finished.
+async fn start_load_balancer(
+    config: Config,
+) -> Result<
+    (
+        Arc<Context>,
+        tokio::task::JoinHandle<lemonade_load_balancer::error::Result<()>>,
+    ),
+    Box<dyn Error>,
+> {
+    let ctx = Arc::new(Context::new(config.clone())?);
+    let app = App::new(
+        Arc::new(StaticConfigService::new()),
+        Arc::new(BackendHealthService::new(Arc::new(ArcSwap::from_pointee(
+            config.health.clone(),
+        )))?),
+        Arc::new(AggregatingMetricsService::new(Arc::new(
+            ArcSwap::from_pointee(config.metrics.clone()),
+        ))?),
+        Arc::new(TokioProxyService::new(Arc::new(ArcSwap::from_pointee(
+            config.proxy.clone(),
+        )))?),
+    )
+    .await;
+    let lb = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { app.run(ctx).await }
+    });
+
+    let ready = tokio::time::timeout(STARTUP_TIMEOUT, async {
+        while !(ctx.readiness().is_accepting() && ctx.readiness().is_health_checked()) {
+            if lb.is_finished() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+    if lb.is_finished() {
+        return match lb.await {
+            Ok(Err(e)) => Err(format!("load balancer failed to start: {}", e).into()),
+            _ => Err("load balancer stopped during startup".into()),
+        };
+    }
+    if ready.is_err() {
+        let _ = ctx.channels().shutdown_tx().send(());
+        return Err("load balancer was not ready in time".into());
+    }
+    Ok((ctx, lb))
+}
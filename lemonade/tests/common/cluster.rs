@@ -0,0 +1,409 @@
+//! End-to-end cluster harness
+//!
+//! [`E2eCluster`] runs a load balancer and a set of axum workers inside the
+//! test process, on ephemeral ports. The load balancer is built from a JSON
+//! config file so tests can hot-reload it, and is inspected through its
+//! [`Context`]; each worker serves an extra `/e2e/requests` route counting the
+//! `/work` requests it answered. New scenarios should add steps on top of
+//! these helpers rather than new scaffolding.
+
+use axum::{Json, Router, extract::Request, middleware::Next, routing::get};
+use lemonade_load_balancer::api::*;
+use lemonade_service::AppState;
+use lemonade_service::worker::WorkResponse;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// How long [`E2eCluster::wait_until`] waits before failing the test
+const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Artificial work delay of every worker
+const WORK_DELAY: Duration = Duration::from_millis(20);
+
+/// Request counters reported by a worker's `/e2e/requests` route
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WorkerStats {
+    /// Worker service name
+    pub service: String,
+    /// `/work` requests served since the worker was first started
+    pub requests: u64,
+}
+
+/// An axum worker that can be killed and restarted on the same address
+pub struct E2eWorker {
+    name: String,
+    address: SocketAddr,
+    requests: Arc<AtomicU64>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl E2eWorker {
+    /// Start a worker on an ephemeral port
+    pub async fn start(name: impl Into<String>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind worker");
+        let mut worker = Self {
+            name: name.into(),
+            address: listener.local_addr().expect("worker address"),
+            requests: Arc::new(AtomicU64::new(0)),
+            handle: None,
+        };
+        worker.serve(listener);
+        worker
+    }
+
+    /// Worker service name, as returned by `/work`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Address the worker listens on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Stop accepting connections, as if the worker process died
+    pub fn kill(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+
+    /// Start serving again on the original address
+    pub async fn restart(&mut self) {
+        self.kill();
+        let listener = TcpListener::bind(self.address)
+            .await
+            .expect("Failed to rebind worker");
+        self.serve(listener);
+    }
+
+    /// Fetch the worker's `/e2e/requests` counts
+    pub async fn stats(&self, client: &reqwest::Client) -> WorkerStats {
+        client
+            .get(format!("http://{}/e2e/requests", self.address))
+            .send()
+            .await
+            .expect("Failed to fetch worker stats")
+            .json()
+            .await
+            .expect("Invalid worker stats")
+    }
+
+    /// Serve the axum worker router plus `/e2e/requests` on `listener`
+    fn serve(&mut self, listener: TcpListener) {
+        let config = lemonade_service::config::Config::new(
+            self.address,
+            self.name.clone(),
+            WORK_DELAY,
+        );
+        let requests = self.requests.clone();
+        let stats_requests = self.requests.clone();
+        let service = self.name.clone();
+        let app = Router::new()
+            .route(
+                "/e2e/requests",
+                get(move || {
+                    let stats = serde_json::json!({
+                        "service": service,
+                        "requests": stats_requests.load(Ordering::SeqCst),
+                    });
+                    async move { Json(stats) }
+                }),
+            )
+            .merge(
+                lemonade_worker_axum::create_router(AppState::new(config)).layer(
+                    axum::middleware::from_fn(move |request: Request, next: Next| {
+                        let requests = requests.clone();
+                        async move {
+                            if request.uri().path() == "/work" {
+                                requests.fetch_add(1, Ordering::SeqCst);
+                            }
+                            next.run(request).await
+                        }
+                    }),
+                ),
+            );
+        self.handle = Some(tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        }));
+    }
+}
+
+impl Drop for E2eWorker {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+/// A load balancer in front of axum workers, all in the test process
+pub struct E2eCluster {
+    /// Keeps the config file alive
+    _dir: TempDir,
+    config_path: PathBuf,
+    config: Config,
+    ctx: Arc<Context>,
+    workers: Vec<E2eWorker>,
+    lb: Option<JoinHandle<lemonade_load_balancer::error::Result<()>>>,
+    client: reqwest::Client,
+}
+
+impl E2eCluster {
+    /// Start `workers` workers behind a round-robin load balancer
+    ///
+    /// Returns once the proxy accepts connections and the initial health
+    /// check has finished.
+    pub async fn start(workers: usize) -> Self {
+        let mut started = Vec::with_capacity(workers);
+        for index in 0..workers {
+            started.push(E2eWorker::start(format!("worker-{}", index)).await);
+        }
+
+        let mut config = ConfigBuilder::from_env().expect("Failed to build config");
+        config.proxy.listen_address = reserve_port();
+        config.proxy.max_connections = None;
+        config.strategy = Strategy::RoundRobin;
+        config.backends = started
+            .iter()
+            .enumerate()
+            .map(|(id, worker)| backend_config(id, worker))
+            .collect();
+        config.health.interval = Duration::from_millis(100);
+        config.health.timeout = Duration::from_millis(200);
+        config.runtime.config_watch_interval_millis = 50;
+        config.runtime.background_timeout_millis = 1_000;
+        config.runtime.drain_timeout_millis = 2_000;
+        config.admin.enabled = false;
+        config.otlp_endpoint = None;
+        config.otlp_protocol = None;
+
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let config_path = dir.path().join("lemonade.json");
+        write_config(&config_path, &config);
+        let config =
+            ConfigBuilder::from_file(Some(&config_path)).expect("Failed to load config");
+        let ctx =
+            Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+
+        let app = App::new(
+            Arc::new(
+                NotifyConfigService::new(Some(config_path.clone()))
+                    .expect("Failed to create config service"),
+            ),
+            Arc::new(
+                BackendHealthService::new(Arc::new(ArcSwap::from_pointee(
+                    config.health.clone(),
+                )))
+                .expect("Failed to create health service"),
+            ),
+            Arc::new(
+                AggregatingMetricsService::new(Arc::new(ArcSwap::from_pointee(
+                    config.metrics.clone(),
+                )))
+                .expect("Failed to create metrics service"),
+            ),
+            Arc::new(
+                TokioProxyService::new(Arc::new(ArcSwap::from_pointee(
+                    config.proxy.clone(),
+                )))
+                .expect("Failed to create proxy service"),
+            ),
+        )
+        .await;
+        let lb = tokio::spawn({
+            let ctx = ctx.clone();
+            async move { app.run(ctx).await }
+        });
+
+        let cluster = Self {
+            _dir: dir,
+            config_path,
+            config,
+            ctx,
+            workers: started,
+            lb: Some(lb),
+            client: reqwest::Client::builder()
+                .no_proxy()
+                .pool_max_idle_per_host(0)
+                .timeout(Duration::from_secs(2))
+                .build()
+                .expect("Failed to build HTTP client"),
+        };
+        cluster
+            .wait_until("load balancer ready", |ctx| {
+                ctx.readiness().is_accepting() && ctx.readiness().is_health_checked()
+            })
+            .await;
+        cluster
+    }
+
+    /// Load balancer context
+    pub fn ctx(&self) -> &Arc<Context> {
+        &self.ctx
+    }
+
+    /// HTTP client that opens a new connection per request
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Worker started as backend `id`
+    pub fn worker(&mut self, id: usize) -> &mut E2eWorker {
+        &mut self.workers[id]
+    }
+
+    /// Route table entry for backend `id`
+    pub fn backend(&self, id: usize) -> Arc<Backend> {
+        self.ctx
+            .routing_table()
+            .get(id as BackendId)
+            .unwrap_or_else(|| panic!("backend {} is not routed", id))
+    }
+
+    /// Send `GET /work` through the load balancer
+    pub async fn work(&self) -> Result<WorkResponse, reqwest::Error> {
+        self.client
+            .get(format!("http://{}/work", self.config.proxy.listen_address))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// Send `count` requests and count the responses per worker name
+    pub async fn distribute(&self, count: usize) -> BTreeMap<String, usize> {
+        let mut served = BTreeMap::new();
+        for _ in 0..count {
+            let response = self.work().await.expect("Request through the LB failed");
+            *served.entry(response.service().to_string()).or_default() += 1;
+        }
+        served
+    }
+
+    /// `/e2e/requests` counts of every worker, by name
+    pub async fn worker_requests(&self) -> BTreeMap<String, u64> {
+        let mut requests = BTreeMap::new();
+        for worker in &self.workers {
+            let stats = worker.stats(&self.client).await;
+            requests.insert(stats.service, stats.requests);
+        }
+        requests
+    }
+
+    /// Start a new worker and hot-reload the config file to route to it
+    ///
+    /// Returns the new backend id once the route table contains it.
+    pub async fn add_worker(&mut self) -> usize {
+        let id = self.workers.len();
+        let worker = E2eWorker::start(format!("worker-{}", id)).await;
+        self.config.backends.push(backend_config(id, &worker));
+        self.workers.push(worker);
+        write_config(&self.config_path, &self.config);
+        self.wait_until("reloaded config routes the new worker", |ctx| {
+            ctx.routing_table().get(id as BackendId).is_some()
+        })
+        .await;
+        id
+    }
+
+    /// Poll the context until `condition` holds, failing after a timeout
+    pub async fn wait_until(
+        &self,
+        description: &str,
+        condition: impl Fn(&Context) -> bool,
+    ) {
+        tokio::time::timeout(WAIT_TIMEOUT, async {
+            while !condition(&self.ctx) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("timed out waiting for: {}", description));
+    }
+
+    /// Signal shutdown and wait for the load balancer to drain and stop
+    pub async fn shutdown(&mut self) -> lemonade_load_balancer::error::Result<()> {
+        let _ = self.ctx.channels().shutdown_tx().send(());
+        let lb = self.lb.take().expect("load balancer already shut down");
+        tokio::time::timeout(WAIT_TIMEOUT, lb)
+            .await
+            .expect("Load balancer did not stop in time")
+            .expect("Load balancer task panicked")
+    }
+}
+
+/// How long each stage of [`E2eCluster::shutdown_cluster`] took
+#[derive(Debug, Clone, Copy)]
+pub struct ClusterShutdown {
+    /// Stopping the load balancer, including draining its connections
+    pub lb_drain: Duration,
+    /// Stopping the workers afterwards
+    pub workers_stop: Duration,
+}
+
+impl E2eCluster {
+    /// Stop the load balancer first, then the workers
+    ///
+    /// Workers keep serving until the load balancer has drained, so requests
+    /// in flight when shutdown starts still complete.
+    pub async fn shutdown_cluster(&mut self) -> ClusterShutdown {
+        let started = std::time::Instant::now();
+        self.shutdown()
+            .await
+            .expect("Load balancer failed to shut down");
+        let lb_drain = started.elapsed();
+
+        let started = std::time::Instant::now();
+        for worker in &mut self.workers {
+            worker.kill();
+        }
+        ClusterShutdown {
+            lb_drain,
+            workers_stop: started.elapsed(),
+        }
+    }
+}
+
+impl Drop for E2eCluster {
+    fn drop(&mut self) {
+        if let Some(lb) = self.lb.take() {
+            let _ = self.ctx.channels().shutdown_tx().send(());
+            lb.abort();
+        }
+    }
+}
+
+/// Backend config routing to a worker
+fn backend_config(id: usize, worker: &E2eWorker) -> BackendConfig {
+    BackendConfig::from(BackendMeta::new(
+        id as BackendId,
+        Some(worker.name()),
+        worker.address(),
+        Some(1u8),
+    ))
+}
+
+/// Write the config as JSON (bumping the mtime the watcher polls)
+fn write_config(path: &Path, config: &Config) {
+    std::fs::write(
+        path,
+        serde_json::to_string_pretty(config).expect("Failed to serialize config"),
+    )
+    .expect("Failed to write config");
+}
+
+/// A free local port for the proxy listener
+fn reserve_port() -> SocketAddr {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port")
+}
@@ -0,0 +1,5 @@
+//! Common test utilities
+//!
+//! Shared helpers for the workspace end-to-end tests.
+
+pub mod cluster;
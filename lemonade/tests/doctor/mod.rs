@@ -0,0 +1,5 @@
+//! Doctor tests
+//!
+//! Run `lemonade doctor` checks against known-good and known-broken configs.
+
+mod test_doctor;
@@ -0,0 +1,111 @@
+//! Doctor check tests
+use lemonade::{CheckStatus, DoctorReport, run_doctor};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Write a YAML load balancer config with the given proxy and backend addresses
+fn write_config(dir: &TempDir, listen_address: &str, backends: &[String]) -> PathBuf {
+    let backends: String = backends
+        .iter()
+        .enumerate()
+        .map(|(id, address)| format!("  - id: {}\n    address: \"{}\"\n", id, address))
+        .collect();
+    let path = dir.path().join("lemonade.yaml");
+    std::fs::write(
+        &path,
+        format!(
+            "version: 2\nstrategy: round_robin\n\
+             runtime:\n  metrics_cap: 100\n  health_cap: 50\n  drain_timeout_millis: 1000\n  \
+             background_timeout_millis: 1000\n  accept_timeout_millis: 1000\n  config_watch_interval_millis: 1000\n\
+             proxy:\n  listen_address: \"{}\"\n  max_connections: 16\n\
+             backends:\n{}\
+             health:\n  interval_millis: 1000\n  timeout_millis: 1000\n\
+             metrics:\n  interval_millis: 1000\n  timeout_millis: 1000\n",
+            listen_address, backends
+        ),
+    )
+    .expect("Failed to write config");
+    path
+}
+
+/// Status of a named check
+fn status(report: &DoctorReport, name: &str) -> CheckStatus {
+    report
+        .checks
+        .iter()
+        .find(|check| check.name == name)
+        .unwrap_or_else(|| panic!("missing check {}", name))
+        .status
+}
+
+#[tokio::test]
+async fn doctor_known_good_config_should_succeed() {
+    // Given: a config with a free listen address and a listening backend
+    let dir = TempDir::new().expect("temp dir");
+    let backend = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("backend listener");
+    let backend_address = backend.local_addr().expect("backend address").to_string();
+    let path = write_config(&dir, "127.0.0.1:0", &[backend_address]);
+
+    // When: running the doctor with backend connections
+    let report = run_doctor(Some(&path), true).await;
+
+    // Then: every required check passes and the report is scriptable JSON
+    assert!(report.ok, "{}", report);
+    assert_eq!(report.blocking_failures(), 0);
+    assert_eq!(status(&report, "config"), CheckStatus::Pass);
+    assert_eq!(status(&report, "listen_address"), CheckStatus::Pass);
+    assert_eq!(status(&report, "backends_resolvable"), CheckStatus::Pass);
+    assert_eq!(status(&report, "backends_reachable"), CheckStatus::Pass);
+    assert_eq!(status(&report, "otlp_endpoint"), CheckStatus::Skip);
+    let json = serde_json::to_value(&report).expect("report JSON");
+    assert_eq!(json["ok"], true);
+    assert_eq!(json["checks"][0]["name"], "config");
+    assert_eq!(json["checks"][0]["status"], "pass");
+}
+
+#[tokio::test]
+async fn doctor_known_broken_config_should_fail() {
+    // Given: a config whose listen address is taken and whose backends do
+    // not resolve or accept connections
+    let dir = TempDir::new().expect("temp dir");
+    let taken = std::net::TcpListener::bind("127.0.0.1:0").expect("listener");
+    let closed = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("closed port");
+    let path = write_config(
+        &dir,
+        &taken.local_addr().expect("taken address").to_string(),
+        &["doctor-backend.invalid:80".to_string(), closed.to_string()],
+    );
+
+    // When: running the doctor with backend connections
+    let report = run_doctor(Some(&path), true).await;
+
+    // Then: the config loads but the environment checks fail the report
+    assert!(!report.ok, "{}", report);
+    assert_eq!(status(&report, "config"), CheckStatus::Pass);
+    assert_eq!(status(&report, "listen_address"), CheckStatus::Fail);
+    assert_eq!(status(&report, "backends_resolvable"), CheckStatus::Fail);
+    assert_eq!(status(&report, "backends_reachable"), CheckStatus::Fail);
+    assert!(report.to_string().contains("[FAIL] listen_address"));
+}
+
+#[tokio::test]
+async fn doctor_invalid_config_skips_dependent_checks_should_fail() {
+    // Given: a config file that does not parse
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("lemonade.yaml");
+    std::fs::write(&path, "proxy: [not, a, map]\n").expect("write config");
+
+    // When: running the doctor
+    let report = run_doctor(Some(Path::new(&path)), false).await;
+
+    // Then: the config check fails and the checks that need it are skipped
+    assert!(!report.ok);
+    assert_eq!(status(&report, "config"), CheckStatus::Fail);
+    assert_eq!(status(&report, "listen_address"), CheckStatus::Skip);
+    assert_eq!(status(&report, "fd_limit"), CheckStatus::Skip);
+    assert_eq!(status(&report, "clock"), CheckStatus::Pass);
+}
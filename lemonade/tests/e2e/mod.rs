@@ -0,0 +1,12 @@
+//! End-to-end tests
+//!
+//! Run the load balancer and axum workers in-process, on ephemeral ports.
+
+mod test_access_log;
+mod test_bootstrap;
+mod test_cluster;
+mod test_compression;
+mod test_deadline;
+mod test_prometheus;
+#[cfg(unix)]
+mod test_unix_socket;
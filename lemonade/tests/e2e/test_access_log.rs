@@ -0,0 +1,100 @@
+//! Access log parity tests
+//!
+//! Every framework's worker serves the same requests and must log the same
+//! access log fields.
+use lemonade_service::AppState;
+use lemonade_service::access_log::{AccessLog, AccessLogFormat, REQUEST_ID_HEADER};
+use lemonade_service::config::Config;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Workers under test, by name
+const FRAMEWORKS: [&str; 4] = ["actix", "axum", "hyper", "rocket"];
+
+/// Start a worker on its own thread and runtime (actix servers are not `Send`)
+///
+/// Returns its in-memory access log once it accepts connections.
+async fn start_worker(framework: &'static str) -> (SocketAddr, AccessLog) {
+    let address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve worker port");
+    let config = Config::new(address, "parity", Duration::from_millis(1));
+    let access_log = AccessLog::in_memory(AccessLogFormat::Json);
+    let state = AppState::new(config).with_access_log(access_log.clone());
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().expect("worker runtime");
+        let result = runtime.block_on(async move {
+            match framework {
+                "actix" => lemonade_worker_actix::serve(state).await,
+                "axum" => lemonade_worker_axum::serve(state).await,
+                "hyper" => lemonade_worker_hyper::serve(state).await,
+                _ => lemonade_worker_rocket::serve(state).await,
+            }
+            .map_err(|e| e.to_string())
+        });
+        if let Err(e) = result {
+            eprintln!("{} worker failed: {}", framework, e);
+        }
+    });
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while tokio::net::TcpStream::connect(address).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("{} worker should start", framework));
+    (address, access_log)
+}
+
+/// Access log lines without the fields that vary between runs
+fn comparable(access_log: &AccessLog) -> Vec<serde_json::Value> {
+    access_log
+        .lines()
+        .iter()
+        .map(|line| {
+            let mut value: serde_json::Value =
+                serde_json::from_str(line).expect("JSON access log line");
+            let fields = value.as_object_mut().expect("JSON object");
+            assert!(fields.remove("timestamp_ms").is_some(), "{}", line);
+            assert!(fields.remove("duration_micros").is_some(), "{}", line);
+            value
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn e2e_access_log_fields_match_across_frameworks_should_succeed() {
+    // Given: one worker per framework, each with a JSON access log
+    let client = reqwest::Client::new();
+    let mut logs = Vec::new();
+    for framework in FRAMEWORKS {
+        let (address, access_log) = start_worker(framework).await;
+
+        // When: sending the same requests to each of them
+        for (path, request_id) in [("/work", "req-1"), ("/health", "req-2")] {
+            let response = client
+                .get(format!("http://{}{}", address, path))
+                .header(REQUEST_ID_HEADER, request_id)
+                .send()
+                .await
+                .expect("request");
+            assert!(response.status().is_success(), "{} {}", framework, path);
+            response.bytes().await.expect("body");
+        }
+        logs.push((framework, access_log));
+    }
+
+    // Then: every framework logged the same fields, timestamps and timings aside
+    let (_, reference) = &logs[0];
+    let expected = comparable(reference);
+    assert_eq!(expected.len(), 2);
+    assert_eq!(expected[0]["method"], "GET");
+    assert_eq!(expected[0]["path"], "/work");
+    assert_eq!(expected[0]["status"], 200);
+    assert_eq!(expected[0]["request_id"], "req-1");
+    assert!(expected[0]["bytes"].as_u64().unwrap_or_default() > 0);
+    for (framework, access_log) in &logs {
+        assert_eq!(comparable(access_log), expected, "{}", framework);
+    }
+}
@@ -0,0 +1,130 @@
+//! Worker bootstrap conformance tests
+//!
+//! Every framework's worker starts through the shared bootstrap and must log
+//! the same startup banner fields.
+use lemonade_service::BootstrapHandle;
+use lemonade_service::config::Config;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Log output captured by a test subscriber
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    /// Install a JSON subscriber writing into the capture on this thread
+    fn install(&self) -> tracing::subscriber::DefaultGuard {
+        tracing_subscriber::fmt()
+            .json()
+            .with_writer(self.clone())
+            .with_max_level(tracing::Level::INFO)
+            .finish()
+            .set_default()
+    }
+
+    /// Fields of the captured startup banners, in order
+    fn banners(&self) -> Vec<serde_json::Map<String, serde_json::Value>> {
+        let bytes = self.0.lock().unwrap().clone();
+        String::from_utf8_lossy(&bytes)
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter_map(|event| event["fields"].as_object().cloned())
+            .filter(|fields| fields["message"] == "Worker starting")
+            .collect()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[test]
+fn worker_bootstrap_should_log_identical_banner_fields_across_frameworks() {
+    // Given: the same config for every framework
+    let config = Config::new(
+        "127.0.0.1:50599".parse::<std::net::SocketAddr>().unwrap(),
+        "bootstrap",
+        Duration::from_millis(5),
+    );
+    // Keep the rest of the test binary quiet: bootstrap only installs its
+    // global subscriber when none is set
+    let _ = tracing::subscriber::set_global_default(
+        tracing::subscriber::NoSubscriber::default(),
+    );
+    let logs = CapturedLogs::default();
+    let _guard = logs.install();
+
+    // When: bootstrapping each worker
+    let handles: Vec<(&str, BootstrapHandle)> = vec![
+        (
+            lemonade_worker_actix::SERVICE_NAME,
+            lemonade_worker_actix::bootstrap(config.clone()).expect("actix bootstrap"),
+        ),
+        (
+            lemonade_worker_axum::SERVICE_NAME,
+            lemonade_worker_axum::bootstrap(config.clone()).expect("axum bootstrap"),
+        ),
+        (
+            lemonade_worker_hyper::SERVICE_NAME,
+            lemonade_worker_hyper::bootstrap(config.clone()).expect("hyper bootstrap"),
+        ),
+        (
+            lemonade_worker_rocket::SERVICE_NAME,
+            lemonade_worker_rocket::bootstrap(config).expect("rocket bootstrap"),
+        ),
+    ];
+
+    // Then: each handle carries the config and its framework's service name
+    for (service_name, handle) in &handles {
+        assert_eq!(handle.banner.service_name, *service_name);
+        assert_eq!(handle.listen_address.to_string(), "127.0.0.1:50599");
+        assert_eq!(handle.state.config.service_name(), "bootstrap");
+    }
+
+    // And: the logged banners differ only in the service name
+    let mut banners = logs.banners();
+    assert_eq!(banners.len(), 4, "{:?}", banners);
+    for (banner, (service_name, _)) in banners.iter_mut().zip(&handles) {
+        assert_eq!(banner.remove("service.name"), Some((*service_name).into()));
+    }
+    assert!(
+        banners.iter().all(|banner| *banner == banners[0]),
+        "{:?}",
+        banners
+    );
+    let expected = [
+        "access_log",
+        "listen.address",
+        "message",
+        "otlp.endpoint",
+        "otlp.protocol",
+        "service.build.sha",
+        "service.instance.id",
+        "service.version",
+        "work_delay_ms",
+    ];
+    assert_eq!(banners[0].keys().collect::<Vec<_>>(), expected);
+    assert_eq!(banners[0]["work_delay_ms"], 5);
+    assert_eq!(
+        banners[0]["service.build.sha"],
+        lemonade_service::info::build_info().git_sha
+    );
+}
@@ -0,0 +1,178 @@
+//! End-to-end cluster tests
+//!
+use lemonade_load_balancer::api::*;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::common::cluster::E2eCluster;
+
+/// Requests served per worker between two `/stats` snapshots
+fn served_since(
+    before: &BTreeMap<String, u64>,
+    after: &BTreeMap<String, u64>,
+) -> BTreeMap<String, u64> {
+    after
+        .iter()
+        .map(|(name, requests)| {
+            let previous = before.get(name).copied().unwrap_or_default();
+            (name.clone(), requests - previous)
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn e2e_cluster_lifecycle_should_succeed() {
+    // Given: two axum workers behind a round-robin load balancer
+    let mut cluster = E2eCluster::start(2).await;
+    let client = cluster.client().clone();
+
+    // When: sending requests through the load balancer
+    let before = cluster.worker_requests().await;
+    let served = cluster.distribute(10).await;
+
+    // Then: they alternate between the workers, as both workers' stats agree
+    assert_eq!(served.get("worker-0"), Some(&5), "{:?}", served);
+    assert_eq!(served.get("worker-1"), Some(&5), "{:?}", served);
+    let after = cluster.worker_requests().await;
+    assert_eq!(
+        served_since(&before, &after),
+        BTreeMap::from([("worker-0".to_string(), 5), ("worker-1".to_string(), 5)])
+    );
+
+    // When: worker 1 dies
+    cluster.worker(1).kill();
+
+    // Then: health checks eject it and all traffic goes to worker 0
+    cluster
+        .wait_until("worker 1 ejected", |ctx| {
+            ctx.routing_table().get(1).is_some_and(|b| !b.is_alive())
+        })
+        .await;
+    assert_eq!(cluster.ctx().routing_table().healthy_backends().len(), 1);
+    let before = cluster.worker(0).stats(&client).await;
+    let served = cluster.distribute(6).await;
+    assert_eq!(served.get("worker-0"), Some(&6), "{:?}", served);
+    let after = cluster.worker(0).stats(&client).await;
+    assert_eq!(after.requests - before.requests, 6);
+
+    // When: worker 1 comes back on the same address
+    cluster.worker(1).restart().await;
+
+    // Then: it is marked healthy again and receives traffic
+    cluster
+        .wait_until("worker 1 recovered", |ctx| {
+            ctx.routing_table().get(1).is_some_and(|b| b.is_alive())
+        })
+        .await;
+    let served = cluster.distribute(10).await;
+    assert!(
+        served.get("worker-1").is_some_and(|n| *n > 0),
+        "{:?}",
+        served
+    );
+    assert!(
+        served.get("worker-0").is_some_and(|n| *n > 0),
+        "{:?}",
+        served
+    );
+
+    // When: the config file is edited to add a third worker
+    let id = cluster.add_worker().await;
+
+    // Then: the reloaded route table spreads traffic over all three
+    cluster
+        .wait_until("worker 2 healthy", |ctx| {
+            ctx.routing_table()
+                .get(id as BackendId)
+                .is_some_and(|b| b.is_alive())
+        })
+        .await;
+    assert_eq!(cluster.ctx().routing_table().len(), 3);
+    let before = cluster.worker_requests().await;
+    let served = cluster.distribute(9).await;
+    let after = cluster.worker_requests().await;
+    for name in ["worker-0", "worker-1", "worker-2"] {
+        assert_eq!(served.get(name), Some(&3), "{:?}", served);
+    }
+    assert!(served_since(&before, &after).values().all(|n| *n == 3));
+
+    // When: shutting down while a request is in flight on a proxied
+    // connection that was open before the shutdown signal
+    let mut stream = TcpStream::connect(cluster.ctx().config().proxy.listen_address)
+        .await
+        .expect("Failed to connect to proxy");
+    cluster
+        .wait_until("connection proxied", |ctx| {
+            ctx.routing_table()
+                .all_backends()
+                .iter()
+                .any(|b| b.active_connections() > 0)
+        })
+        .await;
+    let (result, response) = tokio::join!(cluster.shutdown(), async move {
+        stream
+            .write_all(
+                b"GET /work HTTP/1.1\r\nHost: lemonade\r\nConnection: close\r\n\r\n",
+            )
+            .await?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        std::io::Result::Ok(String::from_utf8_lossy(&response).into_owned())
+    });
+
+    // Then: the in-flight request completes, the drain finishes and the
+    // proxy stops accepting
+    assert!(result.is_ok(), "{:?}", result);
+    let response = response.expect("in-flight request failed");
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(cluster.ctx().drain_progress().is_drained());
+    assert!(!cluster.ctx().readiness().is_accepting());
+    assert!(cluster.work().await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn e2e_cluster_shutdown_drains_lb_before_workers_should_succeed() {
+    // Given: a worker behind the load balancer and a proxied connection
+    // whose request is only half sent
+    let mut cluster = E2eCluster::start(1).await;
+    let worker_address = cluster.worker(0).address();
+    let mut stream = TcpStream::connect(cluster.ctx().config().proxy.listen_address)
+        .await
+        .expect("Failed to connect to proxy");
+    stream
+        .write_all(b"GET /work HTTP/1.1\r\nHost: lemonade\r\n")
+        .await
+        .expect("Failed to write request head");
+    cluster
+        .wait_until("connection proxied", |ctx| {
+            ctx.routing_table()
+                .all_backends()
+                .iter()
+                .any(|b| b.active_connections() > 0)
+        })
+        .await;
+
+    // When: the cluster shuts down while the client is still sending
+    let (stages, response) = tokio::join!(cluster.shutdown_cluster(), async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        stream.write_all(b"Connection: close\r\n\r\n").await?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        std::io::Result::Ok(String::from_utf8_lossy(&response).into_owned())
+    });
+
+    // Then: the worker was still serving when the request completed, and
+    // was only stopped after the load balancer drained
+    let response = response.expect("in-flight request failed");
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(
+        stages.lb_drain >= Duration::from_millis(200),
+        "{:?}",
+        stages
+    );
+    assert!(cluster.ctx().drain_progress().is_drained());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(TcpStream::connect(worker_address).await.is_err());
+}
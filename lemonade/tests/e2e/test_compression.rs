@@ -0,0 +1,130 @@
+//! Response compression parity tests
+//!
+//! Every framework's worker compresses the same responses the same way.
+use lemonade_service::AppState;
+use lemonade_service::compression::{
+    CompressionMode, CompressionStats, ResponseCompression,
+};
+use lemonade_service::config::Config;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Workers under test, by name
+const FRAMEWORKS: [&str; 4] = ["actix", "axum", "hyper", "rocket"];
+
+/// Start a worker compressing every non-empty response on its own thread and
+/// runtime (actix servers are not `Send`)
+///
+/// Returns its compression counters once it accepts connections.
+async fn start_worker(framework: &'static str) -> (SocketAddr, ResponseCompression) {
+    let address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve worker port");
+    let config = Config::new(address, "compression", Duration::from_millis(1))
+        .with_compression(CompressionMode::Auto, 1);
+    let state = AppState::new(config);
+    let compression = state.compression.clone();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().expect("worker runtime");
+        let result = runtime.block_on(async move {
+            match framework {
+                "actix" => lemonade_worker_actix::serve(state).await,
+                "axum" => lemonade_worker_axum::serve(state).await,
+                "hyper" => lemonade_worker_hyper::serve(state).await,
+                _ => lemonade_worker_rocket::serve(state).await,
+            }
+            .map_err(|e| e.to_string())
+        });
+        if let Err(e) = result {
+            eprintln!("{} worker failed: {}", framework, e);
+        }
+    });
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while tokio::net::TcpStream::connect(address).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("{} worker should start", framework));
+    (address, compression)
+}
+
+/// `Content-Encoding` and body of a `/work` response
+async fn fetch_work(
+    client: &reqwest::Client,
+    address: SocketAddr,
+    accept_encoding: Option<&str>,
+) -> (Option<String>, Vec<u8>) {
+    let mut request = client.get(format!("http://{}/work", address));
+    if let Some(accept_encoding) = accept_encoding {
+        request = request.header("accept-encoding", accept_encoding);
+    }
+    let response = request.send().await.expect("request");
+    assert!(response.status().is_success());
+    assert_eq!(
+        response
+            .headers()
+            .get("vary")
+            .and_then(|value| value.to_str().ok()),
+        Some("accept-encoding")
+    );
+    let encoding = response
+        .headers()
+        .get("content-encoding")
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+    (encoding, response.bytes().await.expect("body").to_vec())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn e2e_compression_matches_across_frameworks_should_succeed() {
+    // Given: one worker per framework, each compressing in auto mode
+    let client = reqwest::Client::new();
+    let mut responses = Vec::new();
+    for framework in FRAMEWORKS {
+        let (address, compression) = start_worker(framework).await;
+
+        // When: requesting /work without, with gzip and with brotli accepted
+        let (plain_encoding, plain) = fetch_work(&client, address, None).await;
+        let (gzip_encoding, gzip) = fetch_work(&client, address, Some("gzip")).await;
+        let (br_encoding, br) = fetch_work(&client, address, Some("gzip, br")).await;
+
+        // Then: only accepted encodings are used and both decode to the
+        // uncompressed body
+        assert_eq!(plain_encoding, None, "{}", framework);
+        assert_eq!(gzip_encoding.as_deref(), Some("gzip"), "{}", framework);
+        assert_eq!(br_encoding.as_deref(), Some("br"), "{}", framework);
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(gzip.as_slice())
+            .read_to_end(&mut decoded)
+            .expect("valid gzip");
+        assert_eq!(decoded, plain, "{}", framework);
+        let mut decoded = Vec::new();
+        brotli::Decompressor::new(br.as_slice(), 4096)
+            .read_to_end(&mut decoded)
+            .expect("valid brotli");
+        assert_eq!(decoded, plain, "{}", framework);
+
+        // And: the worker counted each outcome
+        assert_eq!(
+            compression.stats(),
+            CompressionStats {
+                compressed: 2,
+                uncompressed: 1,
+            },
+            "{}",
+            framework
+        );
+        responses.push((framework, plain, gzip, br));
+    }
+
+    // Then: every framework sent the same bytes, compressed or not
+    let (_, plain, gzip, br) = &responses[0];
+    for (framework, other_plain, other_gzip, other_br) in &responses {
+        assert_eq!(other_plain, plain, "{}", framework);
+        assert_eq!(other_gzip, gzip, "{}", framework);
+        assert_eq!(other_br, br, "{}", framework);
+    }
+}
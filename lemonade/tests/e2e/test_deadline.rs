@@ -0,0 +1,88 @@
+//! Deadline propagation tests
+//!
+//! Every framework's worker skips work that would outlast the deadline sent
+//! in the deadline header and answers 504 instead.
+use lemonade_service::AppState;
+use lemonade_service::config::Config;
+use lemonade_service::deadline::DEADLINE_HEADER;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// Workers under test, by name
+const FRAMEWORKS: [&str; 4] = ["actix", "axum", "hyper", "rocket"];
+
+/// Work delay of the workers under test
+const WORK_DELAY: Duration = Duration::from_millis(300);
+
+/// Start a worker with [`WORK_DELAY`] on its own thread and runtime
+async fn start_worker(framework: &'static str) -> SocketAddr {
+    let address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve worker port");
+    let state = AppState::new(Config::new(address, "deadline", WORK_DELAY));
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().expect("worker runtime");
+        let result = runtime.block_on(async move {
+            match framework {
+                "actix" => lemonade_worker_actix::serve(state).await,
+                "axum" => lemonade_worker_axum::serve(state).await,
+                "hyper" => lemonade_worker_hyper::serve(state).await,
+                _ => lemonade_worker_rocket::serve(state).await,
+            }
+            .map_err(|e| e.to_string())
+        });
+        if let Err(e) = result {
+            eprintln!("{} worker failed: {}", framework, e);
+        }
+    });
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while tokio::net::TcpStream::connect(address).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("{} worker should start", framework));
+    address
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn e2e_deadline_short_circuits_across_frameworks_should_succeed() {
+    let client = reqwest::Client::new();
+    for framework in FRAMEWORKS {
+        // Given: a worker whose work takes longer than the caller will wait
+        let address = start_worker(framework).await;
+        let url = format!("http://{}/work", address);
+
+        // When: sending a deadline shorter than the work delay
+        let started = Instant::now();
+        let response = client
+            .get(&url)
+            .header(DEADLINE_HEADER, "50")
+            .send()
+            .await
+            .expect("request");
+
+        // Then: the worker answers 504 without doing the work
+        assert_eq!(response.status().as_u16(), 504, "{}", framework);
+        assert!(started.elapsed() < WORK_DELAY, "{}", framework);
+        let body: serde_json::Value = response.json().await.expect("JSON body");
+        assert!(
+            body["message"]
+                .as_str()
+                .is_some_and(|m| m.contains("exceeds deadline of 50ms")),
+            "{}: {}",
+            framework,
+            body
+        );
+
+        // And: a deadline the work fits in is served as usual
+        let response = client
+            .get(&url)
+            .header(DEADLINE_HEADER, "5000")
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(response.status().as_u16(), 200, "{}", framework);
+    }
+}
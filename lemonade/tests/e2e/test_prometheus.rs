@@ -0,0 +1,398 @@
+//! Prometheus metrics endpoint tests
+//!
+//! Every framework's worker serves the same `/metrics` exposition, checked
+//! with a strict parser of the text format.
+use lemonade_service::AppState;
+use lemonade_service::config::Config;
+use lemonade_service::prometheus::PROMETHEUS_CONTENT_TYPE;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Workers under test: name and the framework label they report
+const FRAMEWORKS: [(&str, &str); 4] = [
+    ("actix", "actix-web"),
+    ("axum", "axum"),
+    ("hyper", "hyper"),
+    ("rocket", "rocket"),
+];
+
+/// Start a worker on its own thread and runtime (actix servers are not `Send`)
+async fn start_worker(framework: &'static str, prometheus_metrics: bool) -> SocketAddr {
+    let address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve worker port");
+    let config = Config::new(address, "prom \"worker\"", Duration::from_millis(1))
+        .with_prometheus_metrics(prometheus_metrics);
+    let state = AppState::new(config);
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().expect("worker runtime");
+        let result = runtime.block_on(async move {
+            match framework {
+                "actix" => lemonade_worker_actix::serve(state).await,
+                "axum" => lemonade_worker_axum::serve(state).await,
+                "hyper" => lemonade_worker_hyper::serve(state).await,
+                _ => lemonade_worker_rocket::serve(state).await,
+            }
+            .map_err(|e| e.to_string())
+        });
+        if let Err(e) = result {
+            eprintln!("{} worker failed: {}", framework, e);
+        }
+    });
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while tokio::net::TcpStream::connect(address).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("{} worker should start", framework));
+    address
+}
+
+/// Labels of one sample, by name
+type Labels = BTreeMap<String, String>;
+
+/// One sample: metric name, labels and value
+#[derive(Debug, Clone, PartialEq)]
+struct Sample {
+    name: String,
+    labels: Labels,
+    value: f64,
+}
+
+/// A parsed exposition: metric family types, and samples in order
+#[derive(Debug, Default)]
+struct Exposition {
+    types: HashMap<String, String>,
+    samples: Vec<Sample>,
+}
+
+impl Exposition {
+    /// Value of the sample `name` whose labels include `labels`
+    fn value(&self, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        self.samples
+            .iter()
+            .find(|sample| {
+                sample.name == name
+                    && labels.iter().all(|(k, v)| {
+                        sample.labels.get(*k).map(String::as_str) == Some(*v)
+                    })
+            })
+            .map(|sample| sample.value)
+    }
+}
+
+/// Whether `name` is a valid metric or label name
+fn is_valid_name(name: &str, allow_colon: bool) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || (allow_colon && c == ':'))
+        && chars
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || (allow_colon && c == ':'))
+}
+
+/// Parse `{name="value",...}` at the start of `input`, returning the labels
+/// and the rest of the line
+fn parse_labels(input: &str) -> Result<(Labels, &str), String> {
+    let mut labels = Labels::new();
+    let mut rest = input
+        .strip_prefix('{')
+        .ok_or_else(|| format!("expected '{{' in {:?}", input))?;
+    loop {
+        if let Some(after) = rest.strip_prefix('}') {
+            return Ok((labels, after));
+        }
+        let (name, after) = rest
+            .split_once("=\"")
+            .ok_or_else(|| format!("expected label in {:?}", rest))?;
+        if !is_valid_name(name, false) {
+            return Err(format!("invalid label name {:?}", name));
+        }
+        let mut value = String::new();
+        let mut chars = after.char_indices();
+        let end = loop {
+            match chars.next() {
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, '\\')) => value.push('\\'),
+                    Some((_, '"')) => value.push('"'),
+                    Some((_, 'n')) => value.push('\n'),
+                    other => return Err(format!("invalid escape {:?}", other)),
+                },
+                Some((i, '"')) => break i,
+                Some((_, '\n')) => return Err("raw line feed in label value".into()),
+                Some((_, c)) => value.push(c),
+                None => return Err(format!("unterminated label value in {:?}", after)),
+            }
+        };
+        if labels.insert(name.to_string(), value).is_some() {
+            return Err(format!("duplicate label {:?}", name));
+        }
+        rest = &after[end + 1..];
+        rest = rest.strip_prefix(',').unwrap_or(rest);
+    }
+}
+
+/// Parse an exposition, rejecting anything the text format does not allow
+///
+/// Every sample must belong to a family declared with `# TYPE` before it,
+/// series must be unique, and histograms must have cumulative buckets ending
+/// with `+Inf` equal to their count.
+fn parse_exposition(body: &str) -> Result<Exposition, String> {
+    let mut exposition = Exposition::default();
+    for line in body.lines() {
+        if let Some(comment) = line.strip_prefix("# ") {
+            let mut parts = comment.splitn(3, ' ');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("HELP"), Some(name), Some(_)) if is_valid_name(name, true) => {}
+                (Some("TYPE"), Some(name), Some(kind)) if is_valid_name(name, true) => {
+                    if !["counter", "gauge", "histogram", "summary", "untyped"]
+                        .contains(&kind)
+                    {
+                        return Err(format!("unknown type {:?}", kind));
+                    }
+                    if exposition.samples.iter().any(|s| s.name.starts_with(name)) {
+                        return Err(format!("TYPE of {} after its samples", name));
+                    }
+                    if exposition
+                        .types
+                        .insert(name.to_string(), kind.to_string())
+                        .is_some()
+                    {
+                        return Err(format!("duplicate TYPE for {}", name));
+                    }
+                }
+                _ => return Err(format!("malformed comment {:?}", line)),
+            }
+            continue;
+        }
+        if line.is_empty() {
+            return Err("empty line".into());
+        }
+
+        let name_end = line.find(['{', ' ']).unwrap_or(line.len());
+        let name = &line[..name_end];
+        if !is_valid_name(name, true) {
+            return Err(format!("invalid metric name {:?}", name));
+        }
+        let (labels, rest) = if line[name_end..].starts_with('{') {
+            parse_labels(&line[name_end..])?
+        } else {
+            (Labels::new(), &line[name_end..])
+        };
+        let value = rest
+            .strip_prefix(' ')
+            .ok_or_else(|| format!("expected value in {:?}", line))?;
+        let value = match value {
+            "+Inf" => f64::INFINITY,
+            "-Inf" => f64::NEG_INFINITY,
+            value => value
+                .parse::<f64>()
+                .map_err(|e| format!("invalid value in {:?}: {}", line, e))?,
+        };
+
+        let family = match exposition.types.get(name) {
+            Some(kind) if kind != "histogram" => name,
+            _ => ["_bucket", "_sum", "_count"]
+                .iter()
+                .find_map(|suffix| name.strip_suffix(suffix))
+                .filter(|family| {
+                    exposition.types.get(*family).map(String::as_str) == Some("histogram")
+                })
+                .ok_or_else(|| format!("sample {} has no TYPE", name))?,
+        };
+        if exposition.types[family] == "counter" && value < 0.0 {
+            return Err(format!("negative counter {:?}", line));
+        }
+        let sample = Sample {
+            name: name.to_string(),
+            labels,
+            value,
+        };
+        if exposition
+            .samples
+            .iter()
+            .any(|s| s.name == sample.name && s.labels == sample.labels)
+        {
+            return Err(format!("duplicate series {:?}", line));
+        }
+        exposition.samples.push(sample);
+    }
+
+    for (family, _) in exposition
+        .types
+        .iter()
+        .filter(|(_, kind)| *kind == "histogram")
+    {
+        let buckets: Vec<&Sample> = exposition
+            .samples
+            .iter()
+            .filter(|s| s.name == format!("{}_bucket", family))
+            .collect();
+        let bounds: Vec<f64> = buckets
+            .iter()
+            .map(|s| {
+                let le = s.labels.get("le").ok_or("bucket without le")?;
+                match le.as_str() {
+                    "+Inf" => Ok(f64::INFINITY),
+                    le => le.parse::<f64>().map_err(|_| "invalid le"),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        if !bounds.windows(2).all(|w| w[0] < w[1])
+            || bounds.last() != Some(&f64::INFINITY)
+        {
+            return Err(format!("{} buckets are not increasing up to +Inf", family));
+        }
+        if !buckets.windows(2).all(|w| w[0].value <= w[1].value) {
+            return Err(format!("{} buckets are not cumulative", family));
+        }
+        let count = exposition
+            .value(&format!("{}_count", family), &[])
+            .ok_or_else(|| format!("{} has no count", family))?;
+        if buckets.last().map(|s| s.value) != Some(count) {
+            return Err(format!("{} +Inf bucket differs from its count", family));
+        }
+    }
+    Ok(exposition)
+}
+
+#[test]
+fn parse_exposition_rejects_malformed_input_should_fail() {
+    for body in [
+        "lemonade_x 1\n",
+        "# TYPE lemonade_x counter\nlemonade_x{a=\"b} 1\n",
+        "# TYPE lemonade_x counter\nlemonade_x{a=\"b\"} one\n",
+        "# TYPE lemonade_x counter\nlemonade_x 1\nlemonade_x 2\n",
+        "# TYPE lemonade_x gauge\n# TYPE lemonade_x gauge\n",
+        "# TYPE h histogram\nh_bucket{le=\"1\"} 2\nh_bucket{le=\"+Inf\"} 1\nh_count 1\n",
+    ] {
+        assert!(parse_exposition(body).is_err(), "{:?}", body);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn e2e_prometheus_metrics_across_frameworks_should_succeed() {
+    let client = reqwest::Client::new();
+    for (framework, label) in FRAMEWORKS {
+        // Given: a worker serving Prometheus metrics
+        let address = start_worker(framework, true).await;
+
+        // When: it serves three /work and two /health requests, then is scraped
+        for path in ["/work", "/work", "/work", "/health", "/health"] {
+            let response = client
+                .get(format!("http://{}{}", address, path))
+                .send()
+                .await
+                .expect("request");
+            assert!(response.status().is_success(), "{} {}", framework, path);
+        }
+        let response = client
+            .get(format!("http://{}/metrics", address))
+            .send()
+            .await
+            .expect("scrape");
+
+        // Then: the exposition is well formed
+        assert_eq!(response.status(), 200, "{}", framework);
+        assert_eq!(
+            response
+                .headers()
+                .get("content-type")
+                .and_then(|value| value.to_str().ok()),
+            Some(PROMETHEUS_CONTENT_TYPE),
+            "{}",
+            framework
+        );
+        let body = response.text().await.expect("body");
+        let exposition = parse_exposition(&body)
+            .unwrap_or_else(|e| panic!("{}: {}\n{}", framework, e, body));
+
+        // And: it declares each family with its type
+        assert_eq!(exposition.types.len(), 3, "{}", framework);
+        assert_eq!(exposition.types["lemonade_http_requests_total"], "counter");
+        assert_eq!(
+            exposition.types["lemonade_http_requests_in_flight"],
+            "gauge"
+        );
+        assert_eq!(
+            exposition.types["lemonade_http_request_duration_seconds"],
+            "histogram"
+        );
+
+        // And: every series carries the escaped worker and framework labels
+        for sample in &exposition.samples {
+            assert_eq!(sample.labels["service"], "prom \"worker\"", "{}", framework);
+            assert_eq!(sample.labels["framework"], label, "{}", framework);
+        }
+
+        // And: the values count the requests served before the scrape, with
+        // the scrape itself in flight
+        let requests = |route| {
+            exposition.value(
+                "lemonade_http_requests_total",
+                &[("method", "GET"), ("route", route), ("status", "200")],
+            )
+        };
+        assert_eq!(requests("/work"), Some(3.0), "{}", framework);
+        assert_eq!(requests("/health"), Some(2.0), "{}", framework);
+        assert_eq!(requests("/metrics"), None, "{}", framework);
+        assert_eq!(
+            exposition.value("lemonade_http_requests_in_flight", &[]),
+            Some(1.0),
+            "{}",
+            framework
+        );
+        assert_eq!(
+            exposition.value("lemonade_http_request_duration_seconds_count", &[]),
+            Some(5.0),
+            "{}",
+            framework
+        );
+        let sum = exposition
+            .value("lemonade_http_request_duration_seconds_sum", &[])
+            .expect("sum");
+        assert!(sum > 0.0, "{}: {}", framework, sum);
+
+        // And: the next scrape counts the previous one
+        let body = client
+            .get(format!("http://{}/metrics", address))
+            .send()
+            .await
+            .expect("scrape")
+            .text()
+            .await
+            .expect("body");
+        let exposition = parse_exposition(&body).expect("valid exposition");
+        assert_eq!(
+            exposition.value(
+                "lemonade_http_requests_total",
+                &[("route", "/metrics"), ("status", "200")],
+            ),
+            Some(1.0),
+            "{}",
+            framework
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn e2e_prometheus_metrics_disabled_should_fail() {
+    // Given: workers with the default config
+    let client = reqwest::Client::new();
+    for (framework, _) in FRAMEWORKS {
+        let address = start_worker(framework, false).await;
+
+        // When: scraping /metrics
+        let response = client
+            .get(format!("http://{}/metrics", address))
+            .send()
+            .await
+            .expect("scrape");
+
+        // Then: the endpoint does not exist
+        assert_eq!(response.status(), 404, "{}", framework);
+    }
+}
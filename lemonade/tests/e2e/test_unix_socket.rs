@@ -0,0 +1,193 @@
+//! Unix domain socket tests
+//!
+//! Workers listen on Unix sockets, and the load balancer both listens on one
+//! and proxies to them.
+use lemonade_load_balancer::api::*;
+use lemonade_service::AppState;
+use lemonade_service::config::{Config, WorkerAddress};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+/// Workers that can listen on a Unix socket, by name
+const FRAMEWORKS: [&str; 3] = ["actix", "axum", "hyper"];
+
+/// Start a worker on `address`, on its own thread and runtime
+fn spawn_worker(
+    framework: &'static str,
+    address: WorkerAddress,
+) -> std::sync::mpsc::Receiver<String> {
+    let state = AppState::new(Config::new(address, framework, Duration::from_millis(1)));
+    let (failed, failure) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().expect("worker runtime");
+        let result = runtime.block_on(async move {
+            match framework {
+                "actix" => lemonade_worker_actix::serve(state).await,
+                "axum" => lemonade_worker_axum::serve(state).await,
+                "hyper" => lemonade_worker_hyper::serve(state).await,
+                _ => lemonade_worker_rocket::serve(state).await,
+            }
+            .map_err(|e| e.to_string())
+        });
+        if let Err(e) = result {
+            let _ = failed.send(e);
+        }
+    });
+    failure
+}
+
+/// Wait until a Unix socket at `path` accepts connections
+async fn wait_for_socket(path: &Path) {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while UnixStream::connect(path).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("{} should accept connections", path.display()));
+}
+
+/// Send `GET <path>` over `stream` and return the response
+async fn get<S>(mut stream: S, path: &str) -> String
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    );
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .expect("Failed to send request");
+    let mut response = String::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_string(&mut response))
+        .await
+        .expect("Response should arrive")
+        .expect("Failed to read response");
+    response
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn e2e_unix_socket_proxy_across_frameworks_should_succeed() {
+    // Given: every Unix-capable worker on a socket file, behind a load
+    // balancer listening on a socket too
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let mut backends = Vec::new();
+    for (id, framework) in FRAMEWORKS.into_iter().enumerate() {
+        let path = dir.path().join(format!("{}.sock", framework));
+        spawn_worker(framework, WorkerAddress::Unix(path.clone()));
+        wait_for_socket(&path).await;
+        let address = BackendAddress::parse(&format!("unix:{}", path.display()))
+            .expect("unix backend address");
+        backends.push(BackendConfig::from(BackendMeta::new(
+            id as BackendId,
+            Some(framework),
+            address,
+            Some(1u8),
+        )));
+    }
+    let lb_path = dir.path().join("lb.sock");
+    let mut config = ConfigBuilder::from_env().expect("Failed to build config");
+    config.proxy.listen_address = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve proxy port");
+    config.proxy.listen_unix = Some(UnixAddress::Path(lb_path.clone()));
+    config.proxy.max_connections = None;
+    config.strategy = Strategy::RoundRobin;
+    config.backends = backends;
+    let ctx = Arc::new(Context::new(config.clone()).expect("Failed to create context"));
+    let service =
+        TokioProxyService::new(Arc::new(ArcSwap::from_pointee(config.proxy.clone())))
+            .expect("Failed to create proxy service");
+    let lb = tokio::spawn({
+        let ctx = ctx.clone();
+        async move { service.accept_connections(ctx).await }
+    });
+    wait_for_socket(&lb_path).await;
+
+    // When: health checking the workers
+    for backend in ctx.routing_table().all_backends() {
+        // Then: each socket is reachable
+        BackendHealthService::connect_probe(
+            &backend.address(),
+            None,
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap_or_else(|e| panic!("{} should be reachable: {:?}", backend.address(), e));
+    }
+
+    // When: sending one request per worker through the load balancer socket
+    let mut served = Vec::new();
+    for _ in FRAMEWORKS {
+        let stream = UnixStream::connect(&lb_path)
+            .await
+            .expect("Failed to connect");
+        let response = get(stream, "/work").await;
+
+        // Then: a worker answers it
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        served.extend(
+            FRAMEWORKS
+                .into_iter()
+                .filter(|framework| response.contains(&format!("\"{}\"", framework))),
+        );
+    }
+
+    // Then: round robin reached every worker once
+    served.sort();
+    assert_eq!(served, ["actix", "axum", "hyper"]);
+
+    lb.abort();
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn e2e_unix_socket_abstract_worker_should_succeed() {
+    // Given: an axum worker on an abstract socket
+    let name = format!("lemonade-e2e-{}", std::process::id());
+    spawn_worker("axum", WorkerAddress::Abstract(name.clone()));
+
+    // When: connecting to it through its backend address
+    let address =
+        BackendAddress::parse(&format!("unix:@{}", name)).expect("abstract address");
+    let stream = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            match address.connect_from(None).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+            }
+        }
+    })
+    .await
+    .expect("abstract worker should start");
+
+    // Then: it serves requests
+    let response = get(stream, "/health").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+}
+
+#[tokio::test]
+async fn e2e_unix_socket_rocket_worker_should_fail() {
+    // Given: a rocket worker configured with a Unix socket
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("rocket.sock");
+
+    // When: starting it
+    let failure = spawn_worker("rocket", WorkerAddress::Unix(path.clone()));
+
+    // Then: it refuses to start instead of listening elsewhere
+    let error = failure
+        .recv_timeout(Duration::from_secs(10))
+        .expect("rocket worker should fail");
+    assert!(
+        error.contains("Rocket worker cannot listen on unix:"),
+        "{}",
+        error
+    );
+    assert!(!path.exists());
+}
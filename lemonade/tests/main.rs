@@ -0,0 +1,9 @@
+//! Root test module - imports all test modules
+//! This file ensures all test modules are included in test runs
+
+pub mod common;
+mod doctor;
+mod e2e;
+mod validate;
+mod verify;
+mod version;
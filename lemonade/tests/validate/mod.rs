@@ -0,0 +1,5 @@
+//! Validate tests
+//!
+//! Check config files against a load balancer admin API served in-process.
+
+mod test_validate;
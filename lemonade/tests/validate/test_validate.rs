@@ -0,0 +1,90 @@
+//! `lemonade validate --against-running` tests
+use lemonade::validate_against_running;
+use lemonade_load_balancer::api::{AdminServer, ConfigBuilder, Context};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+
+/// Write a YAML load balancer config with backends of the given ids
+fn write_config(dir: &TempDir, name: &str, ids: &[u8]) -> PathBuf {
+    let backends: String = ids
+        .iter()
+        .map(|id| {
+            format!(
+                "  - id: {}\n    address: \"127.0.0.1:{}\"\n",
+                id,
+                9100 + *id as u16
+            )
+        })
+        .collect();
+    let path = dir.path().join(name);
+    std::fs::write(
+        &path,
+        format!(
+            "version: 2\nstrategy: round_robin\n\
+             runtime:\n  metrics_cap: 100\n  health_cap: 50\n  drain_timeout_millis: 1000\n  \
+             background_timeout_millis: 1000\n  accept_timeout_millis: 1000\n  config_watch_interval_millis: 1000\n\
+             proxy:\n  listen_address: \"127.0.0.1:9099\"\n\
+             backends:\n{}\
+             health:\n  interval_millis: 1000\n  timeout_millis: 1000\n\
+             metrics:\n  interval_millis: 1000\n  timeout_millis: 1000\n",
+            backends
+        ),
+    )
+    .expect("Failed to write config");
+    path
+}
+
+/// Serve the admin API of a load balancer running `config`
+async fn start_admin(config: &PathBuf) -> (String, Arc<Context>) {
+    let config = ConfigBuilder::from_file(Some(config)).expect("Valid live config");
+    let ctx = Arc::new(Context::new(config).expect("Failed to create context"));
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind admin listener");
+    let addr = listener.local_addr().expect("admin address");
+    tokio::spawn({
+        let ctx = ctx.clone();
+        async move { AdminServer::new(None).serve(listener, ctx).await }
+    });
+    (addr.to_string(), ctx)
+}
+
+#[tokio::test]
+async fn validate_against_running_reports_without_applying_should_succeed() {
+    // Given: a running load balancer with two backends
+    let dir = TempDir::new().expect("temp dir");
+    let live = write_config(&dir, "live.yaml", &[0, 1]);
+    let (admin, ctx) = start_admin(&live).await;
+    let generation = ctx.generation();
+
+    // When: checking a candidate adding a backend and one reusing an id
+    let added = write_config(&dir, "added.yaml", &[0, 1, 2]);
+    let duplicate = write_config(&dir, "duplicate.yaml", &[0, 0]);
+    let added_result = validate_against_running(added, &admin).await;
+    let duplicate_result = validate_against_running(duplicate, &admin).await;
+
+    // Then: only the valid candidate passes, and the live config is untouched
+    assert!(added_result.is_ok(), "{:?}", added_result.err());
+    let error = duplicate_result
+        .expect_err("Duplicate ids should be rejected")
+        .to_string();
+    assert!(error.contains("rejected"), "{}", error);
+    assert_eq!(ctx.generation(), generation);
+    assert_eq!(ctx.routing_table().len(), 2);
+}
+
+#[tokio::test]
+async fn validate_against_running_unreachable_admin_should_fail() {
+    let dir = TempDir::new().expect("temp dir");
+    let config = write_config(&dir, "config.yaml", &[0]);
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to reserve port")
+        .port();
+
+    let result = validate_against_running(config, &format!("127.0.0.1:{}", port)).await;
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,5 @@
+//! Verify tests
+//!
+//! Run `lemonade verify` specs through an in-process load balancer.
+
+mod test_verify;
@@ -0,0 +1,107 @@
+//! Verify run tests
+use lemonade::{VerifySpec, run_verify};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Write a YAML round-robin load balancer config without backends
+fn write_config(dir: &TempDir) -> PathBuf {
+    let path = dir.path().join("lemonade.yaml");
+    std::fs::write(
+        &path,
+        "version: 2\nstrategy: round_robin\n\
+         runtime:\n  metrics_cap: 100\n  health_cap: 50\n  drain_timeout_millis: 1000\n  \
+         background_timeout_millis: 1000\n  accept_timeout_millis: 1000\n  config_watch_interval_millis: 1000\n\
+         proxy:\n  listen_address: \"127.0.0.1:0\"\n  max_connections: 16\n\
+         backends: []\n\
+         health:\n  interval_millis: 1000\n  timeout_millis: 1000\n\
+         metrics:\n  interval_millis: 1000\n  timeout_millis: 1000\n",
+    )
+    .expect("Failed to write config");
+    path
+}
+
+/// Write a request spec spawning two axum workers
+fn write_spec(dir: &TempDir, requests: &str) -> PathBuf {
+    let path = dir.path().join("spec.yaml");
+    std::fs::write(
+        &path,
+        format!(
+            "spawn:\n  framework: axum\n  workers: 2\nrequests:\n{}",
+            requests
+        ),
+    )
+    .expect("Failed to write spec");
+    path
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_conforming_backends_should_pass() {
+    // Given: a spec expecting every response to succeed and spread evenly
+    let dir = TempDir::new().expect("temp dir");
+    let config = write_config(&dir);
+    let spec = VerifySpec::from_file(&write_spec(
+        &dir,
+        "  - name: work\n    path: /work\n    count: 10\n    expect:\n      \
+         status: 200\n      json:\n        status: true\n      \
+         distribution:\n        min_share: 0.3\n  \
+         - path: /health\n    expect:\n      status: 200\n",
+    ))
+    .expect("spec");
+
+    // When: running it through the load balancer
+    let report = run_verify(&config, &spec).await.expect("verify run");
+
+    // Then: every check passes and both workers answered
+    assert!(report.is_ok(), "{}", report);
+    assert_eq!(report.requests.len(), 2);
+    assert_eq!(report.requests[0].sent, 10);
+    assert_eq!(report.requests[0].served.len(), 2);
+    assert_eq!(report.requests[1].name, "GET /health");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_nonconforming_responses_should_fail() {
+    // Given: a spec with a wrong status, a wrong field and a skewed share
+    let dir = TempDir::new().expect("temp dir");
+    let config = write_config(&dir);
+    let spec = VerifySpec::from_file(&write_spec(
+        &dir,
+        "  - name: work\n    path: /work\n    count: 4\n    expect:\n      \
+         status: 201\n      json:\n        service: nobody\n      \
+         distribution:\n        min_share: 0.9\n",
+    ))
+    .expect("spec");
+
+    // When: running it through the load balancer
+    let report = run_verify(&config, &spec).await.expect("verify run");
+
+    // Then: each broken expectation is reported
+    assert!(!report.is_ok());
+    let failures = &report.requests[0].failures;
+    assert!(
+        failures
+            .iter()
+            .any(|f| f.contains("status 200, expected 201"))
+    );
+    assert!(failures.iter().any(|f| f.contains("service is")));
+    assert!(failures.iter().any(|f| f.starts_with("distribution:")));
+    assert!(report.to_string().contains("[FAIL] work"));
+}
+
+#[test]
+fn verify_spec_with_unknown_fields_should_be_rejected() {
+    // Given: a spec with a misspelled expectation
+    let dir = TempDir::new().expect("temp dir");
+    let path = dir.path().join("spec.yaml");
+    std::fs::write(
+        &path,
+        "requests:\n  - path: /work\n    expect:\n      stauts: 200\n",
+    )
+    .expect("Failed to write spec");
+
+    // When: loading it
+    let result = VerifySpec::from_file(&path);
+
+    // Then: the typo is an error rather than a silently skipped check
+    assert!(result.is_err());
+}
@@ -0,0 +1,6 @@
+//! Version tests
+//!
+//! Check `lemonade --version` and the build information it shares with the
+//! load balancer and the workers.
+
+mod test_version;
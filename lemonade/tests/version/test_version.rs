@@ -0,0 +1,61 @@
+//! Tests for the version output
+//!
+use std::process::Command;
+
+/// Run the CLI binary with `args` and return its standard output
+fn lemonade(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_lemonade"))
+        .args(args)
+        .output()
+        .expect("Failed to run lemonade");
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).expect("UTF-8 output")
+}
+
+#[test]
+fn build_info_is_consistent_across_surfaces_should_succeed() {
+    // Given: the build information of the CLI, load balancer and workers
+    let cli = lemonade::build_info();
+    let load_balancer = lemonade_load_balancer::api::build_info();
+    let worker = lemonade_service::info::build_info();
+
+    // Then: every field is set
+    assert!(!cli.version.is_empty());
+    assert!(!cli.git_sha.is_empty());
+    assert!(!cli.build_timestamp.is_empty());
+    assert!(cli.rustc_version.starts_with("rustc "));
+
+    // And: the surfaces report the same build
+    for other in [&load_balancer, &worker] {
+        assert_eq!(other.git_sha, cli.git_sha);
+        assert_eq!(other.build_timestamp, cli.build_timestamp);
+        assert_eq!(other.rustc_version, cli.rustc_version);
+    }
+    assert_eq!(cli.features, load_balancer.features);
+}
+
+#[test]
+fn version_flag_prints_summary_should_succeed() {
+    // When: asking for the version
+    let output = lemonade(&["--version"]);
+
+    // Then: one line with the version and abbreviated commit
+    assert_eq!(output.trim_end(), lemonade::version_text(0));
+    assert_eq!(
+        output.trim_end(),
+        format!("lemonade {}", lemonade::build_info().summary())
+    );
+}
+
+#[test]
+fn version_flag_with_vv_prints_build_details_should_succeed() {
+    // When: asking for the version with -vv
+    let output = lemonade(&["-vv", "--version"]);
+
+    // Then: every build field is printed
+    let info = lemonade::build_info();
+    assert!(output.contains(&format!("git sha: {}", info.git_sha)));
+    assert!(output.contains(&format!("build timestamp: {}", info.build_timestamp)));
+    assert!(output.contains(&format!("rustc: {}", info.rustc_version)));
+    assert!(output.contains("features: "));
+}